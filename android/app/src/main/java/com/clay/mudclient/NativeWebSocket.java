@@ -322,12 +322,12 @@ public class NativeWebSocket {
      * Wire protocol (all sizes fixed, all reads exact):
      *   C->S HELLO (6 bytes):      C7 4C 41 59 01 00
      *   S->C CHALLENGE (34 bytes): C7 4B + 32 random bytes
-     *   C->S RESPONSE (32 bytes):  raw SHA256(auth_key_utf8 || challenge) —
-     *                              NOT the hex-string convention used by the
-     *                              WS-level challenge-response in app.js's
-     *                              tryAuthWithKey/hash_with_challenge; this is
-     *                              a separate transport-gating proof using the
-     *                              raw digest bytes.
+     *   C->S RESPONSE (32 bytes):  raw SHA256(hex(SHA256(auth_key_utf8)) ||
+     *                              challenge) — the server keeps only a device
+     *                              token's SHA-256. Unlike the WS-level
+     *                              challenge-response in app.js's
+     *                              tryAuthWithKey/hash_with_challenge, the
+     *                              result is sent as raw digest bytes.
      *   S->C ACK (2 bytes):        C7 06
      */
     private static class KnockSocket extends Socket {
@@ -381,11 +381,16 @@ public class NativeWebSocket {
             }
             byte[] challenge = Arrays.copyOfRange(challengeMsg, 2, 34);
 
-            // C->S RESPONSE: raw SHA256(auth_key_utf8 || challenge)
+            // C->S RESPONSE: raw SHA256(hex(SHA256(auth_key_utf8)) || challenge)
             byte[] digest;
             try {
                 MessageDigest md = MessageDigest.getInstance("SHA-256");
-                md.update(authKey.getBytes(StandardCharsets.UTF_8));
+                byte[] keyHash = md.digest(authKey.getBytes(StandardCharsets.UTF_8));
+                StringBuilder hex = new StringBuilder();
+                for (byte b : keyHash) {
+                    hex.append(String.format("%02x", b));
+                }
+                md.update(hex.toString().getBytes(StandardCharsets.US_ASCII));
                 md.update(challenge);
                 digest = md.digest();
             } catch (NoSuchAlgorithmException e) {
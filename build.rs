@@ -1,6 +1,6 @@
 use std::collections::BTreeSet;
 use std::fs;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::Path;
 
 fn main() {
@@ -53,6 +53,8 @@ fn main() {
     println!("cargo:rerun-if-changed=Cargo.toml");
     println!("cargo:rerun-if-changed=clay2.png");
 
+    write_web_assets();
+
     // On Windows, embed the application icon into the executable
     #[cfg(target_os = "windows")]
     embed_windows_icon();
@@ -62,6 +64,28 @@ fn main() {
     copy_webview2_dll_if_needed();
 }
 
+/// Gzip the static web assets http.rs serves (app.js, style.css) into OUT_DIR and
+/// export each one's ETag, so the web server never compresses them at runtime.
+/// The ETag is the first 16 hex digits of the SHA-256 of the uncompressed file,
+/// hashed as-is (no CRLF folding) since that's exactly what include_str! embeds.
+fn write_web_assets() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    for (file, env_name) in [("src/web/app.js", "WEB_APP_JS_ETAG"), ("src/web/style.css", "WEB_STYLE_CSS_ETAG")] {
+        let contents = fs::read(file).unwrap_or_else(|e| panic!("reading {}: {}", file, e));
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&contents).expect("gzip into memory");
+        let gzipped = encoder.finish().expect("gzip into memory");
+        let name = Path::new(file).file_name().unwrap().to_str().unwrap();
+        fs::write(Path::new(&out_dir).join(format!("{}.gz", name)), gzipped)
+            .unwrap_or_else(|e| panic!("writing {}.gz: {}", name, e));
+
+        let mut hasher = Sha256::new();
+        hasher.update(&contents);
+        let etag: String = hasher.finalize().iter().take(8).map(|b| format!("{:02x}", b)).collect();
+        println!("cargo:rustc-env={}={}", env_name, etag);
+    }
+}
+
 /// Embed Windows PE metadata (version info, description) and optionally an icon.
 /// PE metadata helps AV heuristics trust the binary and reduces false positives.
 #[cfg(target_os = "windows")]
@@ -405,7 +429,7 @@ fn collect_source_files(dir: &Path, files: &mut BTreeSet<String>) {
     }
 }
 
-// Simple SHA256 implementation (no external dependencies in build script)
+// Simple SHA256 implementation (keeps the build hash free of external dependencies)
 struct Sha256 {
     state: [u32; 8],
     buffer: Vec<u8>,
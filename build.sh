@@ -85,6 +85,15 @@ echo "  [+] rustls-backend (TLS)"
 FEATURES="$FEATURES,ssh-transport"
 echo "  [+] ssh-transport (--ssh tunneling)"
 
+# --- Desktop notifications (/notify, Notify Activity) ---
+# Pure Rust D-Bus client on Linux; Termux has no notification daemon to talk to.
+if $IS_ANDROID; then
+    echo "  [-] desktop-notify (not available on Android)"
+else
+    FEATURES="$FEATURES,desktop-notify"
+    echo "  [+] desktop-notify (desktop notifications)"
+fi
+
 # --- musl static linking (Linux only) ---
 if [ "$OS" = "linux" ]; then
     MUSL_TARGET="x86_64-unknown-linux-musl"
@@ -0,0 +1,422 @@
+//! Let's Encrypt certificates for the web interface (`/web acme <domain>`).
+//!
+//! A small RFC 8555 client: an ES256 account key (`acme-account.key`), one order per
+//! issuance, and the HTTP-01 challenge answered by a listener Clay binds on port 80
+//! only while the order is being validated. The issued chain and its key land in
+//! `acme-cert.pem`/`acme-key.pem` in the config directory, which
+//! `resolve_web_cert_files` in main.rs prefers over the self-signed certificate.
+//! Certificates are renewed once they are `RENEW_AFTER` old (Let's Encrypt issues
+//! them for 90 days); `App::start_acme_if_due` starts both the first issuance and
+//! renewals, and the result comes back as `AppEvent::AcmeResult`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
+use base64::Engine as _;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Let's Encrypt's production directory
+pub const DIRECTORY_URL: &str = "https://acme-v02.api.letsencrypt.org/directory";
+/// Port the HTTP-01 validation requests arrive on (fixed by RFC 8555)
+pub const CHALLENGE_PORT: u16 = 80;
+/// Age at which a certificate is renewed: 60 of its 90 days
+pub const RENEW_AFTER: Duration = Duration::from_secs(60 * 24 * 60 * 60);
+/// How long to wait after a failed attempt before trying again on its own
+pub const RETRY_AFTER: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Pause between polls of a pending authorization or order
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Polls before giving up on an authorization or order (about a minute)
+const POLL_ATTEMPTS: usize = 30;
+const CHALLENGE_PREFIX: &str = "/.well-known/acme-challenge/";
+
+/// The issued certificate chain
+pub fn cert_path() -> PathBuf {
+    crate::clay_config_path("acme-cert.pem")
+}
+
+/// The issued certificate's private key
+pub fn key_path() -> PathBuf {
+    crate::clay_config_path("acme-key.pem")
+}
+
+fn account_key_path() -> PathBuf {
+    crate::clay_config_path("acme-account.key")
+}
+
+/// Remove the issued certificate and key (the account key is kept for the next domain)
+pub fn remove_certificate() {
+    let _ = std::fs::remove_file(cert_path());
+    let _ = std::fs::remove_file(key_path());
+}
+
+/// True if there is no certificate at `path` or it is old enough to renew
+pub fn renewal_due(path: &Path) -> bool {
+    let age = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok());
+    age.map_or(true, |age| age >= RENEW_AFTER)
+}
+
+/// Check a domain for `/web acme`, lowercased. HTTP-01 can't prove wildcards, and
+/// Let's Encrypt wants a public DNS name rather than an IP address.
+pub fn validate_domain(domain: &str) -> Result<String, String> {
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    if domain.parse::<std::net::IpAddr>().is_ok() {
+        return Err("Let's Encrypt needs a domain name, not an IP address.".to_string());
+    }
+    let labels_ok = domain.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    if !labels_ok || !domain.contains('.') || domain.len() > 253 {
+        return Err(format!("'{}' isn't a valid domain name.", domain));
+    }
+    Ok(domain)
+}
+
+fn b64(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+/// The account key's public JWK, members in the lexicographic order RFC 7638
+/// requires for the thumbprint
+fn jwk_json(public_key: &[u8]) -> String {
+    // Uncompressed P-256 point: 0x04 || x || y
+    format!(
+        r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#,
+        b64(&public_key[1..33]),
+        b64(&public_key[33..65]),
+    )
+}
+
+/// The key authorization served for an HTTP-01 token
+fn key_authorization(token: &str, jwk: &str) -> String {
+    format!("{}.{}", token, b64(&Sha256::digest(jwk.as_bytes())))
+}
+
+/// The token in an HTTP-01 validation request line, if that's what it is
+fn challenge_token(request: &str) -> Option<&str> {
+    let mut parts = request.lines().next()?.split_whitespace();
+    if parts.next()? != "GET" {
+        return None;
+    }
+    let token = parts.next()?.strip_prefix(CHALLENGE_PREFIX)?;
+    let valid = !token.is_empty()
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(token)
+}
+
+/// Tokens being validated, mapped to their key authorizations
+type Challenges = Arc<Mutex<HashMap<String, String>>>;
+
+/// Bind the HTTP-01 listener and answer validation requests until aborted
+async fn start_responder(challenges: Challenges) -> Result<tokio::task::JoinHandle<()>, String> {
+    let listener = match tokio::net::TcpListener::bind(("::", CHALLENGE_PORT)).await {
+        Ok(listener) => listener,
+        Err(_) => tokio::net::TcpListener::bind(("0.0.0.0", CHALLENGE_PORT)).await.map_err(|e| {
+            format!("can't listen on port {} for the HTTP-01 challenge: {}", CHALLENGE_PORT, e)
+        })?,
+    };
+    Ok(tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else { continue };
+            let challenges = challenges.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 2048];
+                let n = match tokio::time::timeout(Duration::from_secs(10), stream.read(&mut buf)).await {
+                    Ok(Ok(n)) if n > 0 => n,
+                    _ => return,
+                };
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let answer = challenge_token(&request)
+                    .and_then(|token| challenges.lock().ok()?.get(token).cloned());
+                let response = match answer {
+                    Some(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(), body,
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    }))
+}
+
+/// The account key, created (owner-only) on first use
+fn load_or_create_account_key(rng: &SystemRandom) -> Result<EcdsaKeyPair, String> {
+    let path = account_key_path();
+    let stored = std::fs::read_to_string(&path).ok()
+        .and_then(|text| BASE64.decode(text.trim()).ok());
+    let pkcs8 = match stored {
+        Some(pkcs8) => pkcs8,
+        None => {
+            let doc = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, rng)
+                .map_err(|_| "couldn't generate an account key".to_string())?;
+            crate::util::write_secret_file(&path, BASE64.encode(doc.as_ref()).as_bytes())
+                .map_err(|e| format!("couldn't save {}: {}", path.display(), e))?;
+            doc.as_ref().to_vec()
+        }
+    };
+    EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &pkcs8, rng)
+        .map_err(|_| format!("{} isn't a valid account key; delete it to create a new one", path.display()))
+}
+
+struct AcmeResponse {
+    location: Option<String>,
+    body: String,
+}
+
+impl AcmeResponse {
+    fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap_or_default()
+    }
+}
+
+/// Why an authorization or order ended up invalid
+fn invalid_reason(resource: &Value) -> String {
+    let detail = resource["error"]["detail"].as_str().or_else(|| {
+        resource["challenges"].as_array()?.iter()
+            .find_map(|challenge| challenge["error"]["detail"].as_str())
+    });
+    detail.unwrap_or("validation failed").to_string()
+}
+
+struct AcmeClient {
+    http: reqwest::Client,
+    rng: SystemRandom,
+    key: EcdsaKeyPair,
+    jwk: String,
+    new_nonce: String,
+    nonce: Option<String>,
+    /// Account URL once registered; requests are signed with it instead of the JWK
+    kid: Option<String>,
+}
+
+impl AcmeClient {
+    async fn new(directory_url: &str) -> Result<(Self, Value), String> {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("clay/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let directory: Value = http.get(directory_url).send().await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("{}: {}", directory_url, e))?
+            .json().await
+            .map_err(|e| format!("{}: {}", directory_url, e))?;
+        let new_nonce = directory["newNonce"].as_str()
+            .ok_or("the ACME directory has no newNonce")?
+            .to_string();
+        let rng = SystemRandom::new();
+        let key = load_or_create_account_key(&rng)?;
+        let jwk = jwk_json(key.public_key().as_ref());
+        let client = Self { http, rng, key, jwk, new_nonce, nonce: None, kid: None };
+        Ok((client, directory))
+    }
+
+    async fn fresh_nonce(&self) -> Result<String, String> {
+        let response = self.http.head(&self.new_nonce).send().await.map_err(|e| e.to_string())?;
+        response.headers().get("replay-nonce")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| "the ACME server sent no nonce".to_string())
+    }
+
+    /// A signed POST; `None` is a POST-as-GET. A stale nonce is retried once.
+    async fn post(&mut self, url: &str, payload: Option<&Value>) -> Result<AcmeResponse, String> {
+        let mut retried = false;
+        loop {
+            let nonce = match self.nonce.take() {
+                Some(nonce) => nonce,
+                None => self.fresh_nonce().await?,
+            };
+            let mut protected = json!({ "alg": "ES256", "nonce": nonce, "url": url });
+            match &self.kid {
+                Some(kid) => protected["kid"] = json!(kid),
+                None => protected["jwk"] = serde_json::from_str(&self.jwk).unwrap_or_default(),
+            }
+            let protected = b64(protected.to_string().as_bytes());
+            let payload = payload.map(|p| b64(p.to_string().as_bytes())).unwrap_or_default();
+            let signature = self.key.sign(&self.rng, format!("{}.{}", protected, payload).as_bytes())
+                .map_err(|_| "couldn't sign the ACME request".to_string())?;
+            let body = json!({ "protected": protected, "payload": payload, "signature": b64(signature.as_ref()) });
+
+            let response = self.http.post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/jose+json")
+                .body(body.to_string())
+                .send().await
+                .map_err(|e| format!("{}: {}", url, e))?;
+            let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+            self.nonce = header("replay-nonce");
+            let location = header("location");
+            let status = response.status();
+            let text = response.text().await.map_err(|e| format!("{}: {}", url, e))?;
+            if status.is_success() {
+                return Ok(AcmeResponse { location, body: text });
+            }
+            let problem: Value = serde_json::from_str(&text).unwrap_or_default();
+            if !retried && problem["type"] == "urn:ietf:params:acme:error:badNonce" {
+                retried = true;
+                continue;
+            }
+            return Err(problem["detail"].as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{}: HTTP {}", url, status)));
+        }
+    }
+
+    /// POST-as-GET `url` until its status is `valid`
+    async fn poll_valid(&mut self, url: &str) -> Result<Value, String> {
+        for _ in 0..POLL_ATTEMPTS {
+            let resource = self.post(url, None).await?.json();
+            match resource["status"].as_str().unwrap_or("") {
+                "valid" => return Ok(resource),
+                "invalid" => return Err(invalid_reason(&resource)),
+                _ => tokio::time::sleep(POLL_INTERVAL).await,
+            }
+        }
+        Err("timed out waiting for Let's Encrypt".to_string())
+    }
+
+    async fn register(&mut self, new_account: &str, email: &str) -> Result<(), String> {
+        let mut payload = json!({ "termsOfServiceAgreed": true });
+        if !email.is_empty() {
+            payload["contact"] = json!([format!("mailto:{}", email)]);
+        }
+        let response = self.post(new_account, Some(&payload)).await?;
+        self.kid = Some(response.location.ok_or("the ACME server returned no account URL")?);
+        Ok(())
+    }
+
+    /// Answer an authorization's HTTP-01 challenge and wait for it to be validated
+    async fn authorize(&mut self, authz_url: &str, challenges: &Challenges) -> Result<(), String> {
+        let authz = self.post(authz_url, None).await?.json();
+        if authz["status"] == "valid" {
+            return Ok(());
+        }
+        let challenge = authz["challenges"].as_array()
+            .and_then(|list| list.iter().find(|c| c["type"] == "http-01"))
+            .ok_or("Let's Encrypt offered no HTTP-01 challenge")?;
+        let (Some(token), Some(url)) = (challenge["token"].as_str(), challenge["url"].as_str()) else {
+            return Err("malformed HTTP-01 challenge".to_string());
+        };
+        if let Ok(mut map) = challenges.lock() {
+            map.insert(token.to_string(), key_authorization(token, &self.jwk));
+        }
+        self.post(url, Some(&json!({}))).await?;
+        self.poll_valid(authz_url).await.map(|_| ())
+    }
+}
+
+/// Obtain a certificate for `domain` and save it (with its key) to `cert_path()` /
+/// `key_path()`. `email` (optional) is the account contact for expiry notices.
+pub async fn obtain_certificate(domain: String, email: String) -> Result<(), String> {
+    let (mut client, directory) = AcmeClient::new(DIRECTORY_URL).await?;
+    let endpoint = |name: &str| directory[name].as_str().map(str::to_string)
+        .ok_or_else(|| format!("the ACME directory has no {}", name));
+    client.register(&endpoint("newAccount")?, &email).await?;
+
+    let identifiers = json!({ "identifiers": [{ "type": "dns", "value": domain }] });
+    let response = client.post(&endpoint("newOrder")?, Some(&identifiers)).await?;
+    let order_url = response.location.clone().ok_or("the ACME server returned no order URL")?;
+    let order = response.json();
+
+    let challenges: Challenges = Arc::default();
+    let responder = start_responder(challenges.clone()).await?;
+    let authorized = async {
+        for authz in order["authorizations"].as_array().into_iter().flatten() {
+            if let Some(authz_url) = authz.as_str() {
+                client.authorize(authz_url, &challenges).await?;
+            }
+        }
+        Ok::<(), String>(())
+    }.await;
+    responder.abort();
+    authorized?;
+
+    let cert_key = rcgen::KeyPair::generate().map_err(|e| e.to_string())?;
+    let csr = rcgen::CertificateParams::new(vec![domain.clone()])
+        .and_then(|params| params.serialize_request(&cert_key))
+        .map_err(|e| e.to_string())?;
+    let finalize = order["finalize"].as_str().ok_or("the order has no finalize URL")?;
+    client.post(finalize, Some(&json!({ "csr": b64(csr.der()) }))).await?;
+    let order = client.poll_valid(&order_url).await?;
+    let cert_url = order["certificate"].as_str().ok_or("the order has no certificate URL")?;
+    let chain = client.post(cert_url, None).await?.body;
+
+    crate::util::write_secret_file(&key_path(), cert_key.serialize_pem().as_bytes())
+        .and_then(|()| std::fs::write(cert_path(), chain))
+        .map_err(|e| format!("couldn't save the certificate: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_domain_accepts_names_and_rejects_the_rest() {
+        assert_eq!(validate_domain(" Mud.Example.COM. "), Ok("mud.example.com".to_string()));
+        assert!(validate_domain("example").is_err());
+        assert!(validate_domain("*.example.com").is_err());
+        assert!(validate_domain("-bad.example.com").is_err());
+        assert!(validate_domain("a..example.com").is_err());
+        assert!(validate_domain("203.0.113.7").is_err());
+        assert!(validate_domain("::1").is_err());
+    }
+
+    #[test]
+    fn jwk_is_canonical_and_key_authorization_uses_its_thumbprint() {
+        let mut point = vec![0x04];
+        point.extend([1u8; 32]);
+        point.extend([2u8; 32]);
+        let jwk = jwk_json(&point);
+        assert_eq!(jwk, format!(r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#, b64(&[1; 32]), b64(&[2; 32])));
+        let ka = key_authorization("tok_en-1", &jwk);
+        let (token, thumbprint) = ka.split_once('.').unwrap();
+        assert_eq!(token, "tok_en-1");
+        assert_eq!(thumbprint, b64(&Sha256::digest(jwk.as_bytes())));
+        assert_eq!(thumbprint.len(), 43, "unpadded base64url SHA-256");
+    }
+
+    #[test]
+    fn challenge_token_only_matches_validation_requests() {
+        assert_eq!(challenge_token("GET /.well-known/acme-challenge/abc_DEF-1 HTTP/1.1\r\nHost: x\r\n\r\n"), Some("abc_DEF-1"));
+        assert_eq!(challenge_token("POST /.well-known/acme-challenge/abc HTTP/1.1\r\n"), None);
+        assert_eq!(challenge_token("GET /.well-known/acme-challenge/ HTTP/1.1\r\n"), None);
+        assert_eq!(challenge_token("GET /.well-known/acme-challenge/../etc HTTP/1.1\r\n"), None);
+        assert_eq!(challenge_token("GET /clay/ HTTP/1.1\r\n"), None);
+    }
+
+    #[test]
+    fn renewal_is_due_without_a_certificate_but_not_for_a_fresh_one() {
+        let path = std::env::temp_dir().join(format!("clay-acme-test-{}.pem", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(renewal_due(&path));
+        std::fs::write(&path, "cert").unwrap();
+        assert!(!renewal_due(&path));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn invalid_reason_prefers_the_challenge_error() {
+        let authz = json!({ "status": "invalid", "challenges": [
+            { "type": "dns-01" },
+            { "type": "http-01", "error": { "detail": "Connection refused" } },
+        ]});
+        assert_eq!(invalid_reason(&authz), "Connection refused");
+        assert_eq!(invalid_reason(&json!({ "status": "invalid" })), "validation failed");
+    }
+}
@@ -350,9 +350,13 @@ pub fn substitute_pattern_captures(command: &str, captures: &[&str]) -> String {
 
 /// Result of checking action triggers on a line
 pub struct ActionTriggerResult {
+    pub action_name: String,        // Name of the action that fired
     pub should_gag: bool,           // If true, suppress the line from output
     pub commands: Vec<String>,      // Commands to execute
     pub highlight_color: Option<String>, // If Some, highlight the line with this color
+    pub capture: Option<String>,    // If Some, copy the line into this capture buffer
+    pub pipes: Vec<String>,         // Programs to feed the line to (/pipe <program>)
+    pub captures: Vec<String>,      // $0 (the whole match) and the capture groups $1..
 }
 
 /// Convert a wildcard pattern (* and ?) to a regex pattern
@@ -585,67 +589,98 @@ pub fn check_action_triggers(
 ) -> Option<ActionTriggerResult> {
     // Strip ANSI codes for pattern matching
     let plain_line = strip_ansi_codes(line);
+    actions.iter().find_map(|action| action_trigger(action, &plain_line, world_name))
+}
 
-    for action in actions {
-        // Skip disabled actions
-        if !action.enabled {
-            continue;
-        }
+/// Every action a line matches for a world, in list order (`/trigger test`). Only the
+/// first fires, as check_action_triggers returns it; the rest are shadowed by it.
+pub fn matching_actions(line: &str, world_name: &str, actions: &[Action]) -> Vec<ActionTriggerResult> {
+    let plain_line = strip_ansi_codes(line);
+    actions.iter().filter_map(|action| action_trigger(action, &plain_line, world_name)).collect()
+}
 
-        // Skip actions with no patterns (manual /name only)
-        if action.patterns.is_empty() {
-            continue;
-        }
+/// What `action` does with a line (ANSI already stripped), if it's eligible and matches
+fn action_trigger(action: &Action, plain_line: &str, world_name: &str) -> Option<ActionTriggerResult> {
+    // Skip disabled actions
+    if !action.enabled {
+        return None;
+    }
 
-        // Check if world matches (empty or comma-list = eligible worlds, case-insensitive)
-        if !action_matches_world(&action.world, world_name) {
-            continue;
-        }
+    // Skip actions with no patterns (manual /name only)
+    if action.patterns.is_empty() {
+        return None;
+    }
 
-        // Test each pattern in order; first match fires the action
-        for mp in &action.patterns {
-            if let Some(ref regex) = mp.compiled_regex {
-                if let Some(caps) = regex.captures(&plain_line) {
-                    // Extract capture groups: $0 is full match, $1-$9 are groups
-                    let captures: Vec<&str> = caps.iter()
-                        .map(|m| m.map(|m| m.as_str()).unwrap_or(""))
-                        .collect();
-
-                    let commands = split_action_commands(&action.command);
-                    let should_gag = commands.iter().any(|cmd|
-                        cmd.eq_ignore_ascii_case("/gag") || cmd.to_lowercase().starts_with("/gag ")
-                    );
-
-                    // Check for /highlight command and extract color
-                    let highlight_color = commands.iter().find_map(|cmd| {
+    // Check if world matches (empty or comma-list = eligible worlds, case-insensitive)
+    if !action_matches_world(&action.world, world_name) {
+        return None;
+    }
+
+    // Test each pattern in order; first match fires the action
+    for mp in &action.patterns {
+        if let Some(ref regex) = mp.compiled_regex {
+            if let Some(caps) = regex.captures(plain_line) {
+                // Extract capture groups: $0 is full match, $1-$9 are groups
+                let captures: Vec<&str> = caps.iter()
+                    .map(|m| m.map(|m| m.as_str()).unwrap_or(""))
+                    .collect();
+
+                let commands = split_action_commands(&action.command);
+                let should_gag = commands_gag(&commands);
+
+                // Check for /highlight command and extract color
+                let highlight_color = commands.iter().find_map(|cmd| {
+                    let lower = cmd.to_lowercase();
+                    if lower == "/highlight" {
+                        Some(String::new()) // No color specified, use default
+                    } else if lower.starts_with("/highlight ") {
+                        Some(cmd[11..].trim().to_string()) // Extract color after "/highlight "
+                    } else {
+                        None
+                    }
+                });
+
+                // Check for /capture <name> (copy the line into a capture buffer)
+                let capture = commands.iter()
+                    .find_map(|cmd| crate::capture::action_target(cmd).map(str::to_string));
+
+                // /pipe <program> gets the line on stdin; the program is taken as written,
+                // never substituted, so text from the MUD can't reach the shell
+                let pipes = commands.iter()
+                    .filter_map(|cmd| crate::shell::pipe_target(cmd).map(str::to_string))
+                    .collect();
+
+                // Commands run by the shell (/shell, /sh, /quote !) get each capture as one quoted word
+                let quoted: Vec<String> = captures.iter().map(|c| crate::shell::quote(c)).collect();
+                let quoted: Vec<&str> = quoted.iter().map(String::as_str).collect();
+
+                // Filter out /gag, /highlight, /capture and /pipe, roll $[...] dice, then
+                // substitute captures (after, so text from the MUD can't add a roll)
+                let filtered_commands: Vec<String> = commands.into_iter()
+                    .filter(|cmd| {
                         let lower = cmd.to_lowercase();
-                        if lower == "/highlight" {
-                            Some(String::new()) // No color specified, use default
-                        } else if lower.starts_with("/highlight ") {
-                            Some(cmd[11..].trim().to_string()) // Extract color after "/highlight "
-                        } else {
-                            None
-                        }
-                    });
-
-                    // Filter out /gag and /highlight, then substitute captures in commands
-                    let filtered_commands: Vec<String> = commands.into_iter()
-                        .filter(|cmd| {
-                            let lower = cmd.to_lowercase();
-                            !lower.eq_ignore_ascii_case("/gag")
-                                && !lower.starts_with("/gag ")
-                                && lower != "/highlight"
-                                && !lower.starts_with("/highlight ")
-                        })
-                        .map(|cmd| substitute_pattern_captures(&cmd, &captures))
-                        .collect();
-
-                    return Some(ActionTriggerResult {
-                        should_gag,
-                        commands: filtered_commands,
-                        highlight_color,
-                    });
-                }
+                        !lower.eq_ignore_ascii_case("/gag")
+                            && !lower.starts_with("/gag ")
+                            && lower != "/highlight"
+                            && !lower.starts_with("/highlight ")
+                            && crate::capture::action_target(cmd).is_none()
+                            && crate::shell::pipe_target(cmd).is_none()
+                    })
+                    .map(|cmd| {
+                        let captures = if crate::shell::runs_shell(&cmd) { &quoted } else { &captures };
+                        substitute_pattern_captures(&crate::dice::expand(&cmd), captures)
+                    })
+                    .collect();
+
+                return Some(ActionTriggerResult {
+                    action_name: action.name.clone(),
+                    should_gag,
+                    commands: filtered_commands,
+                    highlight_color,
+                    capture,
+                    pipes,
+                    captures: captures.iter().map(|c| c.to_string()).collect(),
+                });
             }
         }
     }
@@ -653,6 +688,88 @@ pub fn check_action_triggers(
     None
 }
 
+/// Whether an action's commands include /gag (the matched line is hidden)
+fn commands_gag(commands: &[String]) -> bool {
+    commands.iter().any(|cmd|
+        cmd.eq_ignore_ascii_case("/gag") || cmd.to_lowercase().starts_with("/gag ")
+    )
+}
+
+/// What a sample line does against an action's patterns (the action editor's tester)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternTest {
+    /// Index of the first pattern that matched
+    pub pattern: usize,
+    /// $0 (the whole match) and the capture groups $1..
+    pub captures: Vec<String>,
+    /// The command has /gag, so the line would be hidden
+    pub gag: bool,
+}
+
+/// Compile patterns the way Action::compile_regex does, but report the first one that
+/// doesn't build ("Pattern 2: unclosed group", numbered as the editor shows them)
+/// instead of skipping it. Empty patterns compile to None.
+pub fn check_patterns(patterns: &[String], match_type: MatchType) -> Result<Vec<Option<Regex>>, String> {
+    patterns.iter().enumerate().map(|(i, pattern)| {
+        if pattern.trim().is_empty() {
+            return Ok(None);
+        }
+        let regex_pattern = match match_type {
+            MatchType::Wildcard => wildcard_to_regex(pattern),
+            MatchType::Regexp => pattern.clone(),
+        };
+        RegexBuilder::new(&regex_pattern)
+            .case_insensitive(true)
+            .build()
+            .map(Some)
+            .map_err(|e| {
+                // The regex crate's message ends with the reason after the pattern and caret
+                let msg = e.to_string();
+                let reason = msg.lines().last().unwrap_or("").trim_start_matches("error: ").trim().to_string();
+                format!("Pattern {}: {}", i + 1, reason)
+            })
+    }).collect()
+}
+
+/// Run a sample line through patterns as check_action_triggers would. Err if a pattern
+/// doesn't compile, Ok(None) if none matches.
+pub fn test_patterns(
+    patterns: &[String],
+    match_type: MatchType,
+    command: &str,
+    sample: &str,
+) -> Result<Option<PatternTest>, String> {
+    let compiled = check_patterns(patterns, match_type)?;
+    let plain = strip_ansi_codes(sample);
+    Ok(compiled.iter().enumerate().find_map(|(i, regex)| {
+        let caps = regex.as_ref()?.captures(&plain)?;
+        Some(PatternTest {
+            pattern: i,
+            captures: caps.iter().map(|m| m.map(|m| m.as_str().to_string()).unwrap_or_default()).collect(),
+            gag: commands_gag(&split_action_commands(command)),
+        })
+    }))
+}
+
+/// One-line summary of a pattern test for the editor: the error, "No match", or the
+/// matching pattern with its captures
+pub fn describe_pattern_test(result: &Result<Option<PatternTest>, String>) -> String {
+    match result {
+        Err(e) => e.clone(),
+        Ok(None) => "No match".to_string(),
+        Ok(Some(t)) => {
+            let mut out = format!("Pattern {} matches", t.pattern + 1);
+            for (i, cap) in t.captures.iter().enumerate().take(10) {
+                out.push_str(&format!(" ${}=\"{}\"", i, cap));
+            }
+            if t.gag {
+                out.push_str(" (gagged)");
+            }
+            out
+        }
+    }
+}
+
 /// Pre-compile action patterns into regexes for a specific world.
 /// Flattens across all patterns of all eligible actions.
 /// Call once before iterating over lines, not per-line.
@@ -694,10 +811,168 @@ pub fn line_matches_compiled_patterns(
     false
 }
 
+/// Insert a copy of `actions[index]` right after it, named `<name>_copy` (`_copy2`, ...
+/// if that's taken). Returns the copy's index, None if there's no such action.
+pub fn duplicate_action(actions: &mut Vec<Action>, index: usize) -> Option<usize> {
+    let mut copy = actions.get(index)?.clone();
+    let base = format!("{}_copy", copy.name);
+    copy.name = (1..)
+        .map(|n| if n == 1 { base.clone() } else { format!("{}{}", base, n) })
+        .find(|name| !actions.iter().any(|a| a.name.eq_ignore_ascii_case(name)))
+        .unwrap_or(base);
+    actions.insert(index + 1, copy);
+    Some(index + 1)
+}
+
+/// Swap `actions[index]` with the one above it (`up`) or below it; actions are checked
+/// in list order. Returns the action's new index, None if it can't move that way.
+pub fn move_action(actions: &mut [Action], index: usize, up: bool) -> Option<usize> {
+    let target = if up { index.checked_sub(1)? } else { index + 1 };
+    if index >= actions.len() || target >= actions.len() {
+        return None;
+    }
+    actions.swap(index, target);
+    Some(target)
+}
+
+/// A shareable set of actions (a trigger pack), as `/actions export` writes it
+#[derive(Serialize, Deserialize)]
+struct ActionPack {
+    actions: Vec<Action>,
+}
+
+/// Write actions for sharing: TOML (`[[actions]]` tables) or JSON (`{"actions": [...]}`).
+/// Owners are left out, since they only mean something on the server that made them.
+pub fn export_action_pack(actions: &[Action], as_toml: bool) -> Result<String, String> {
+    let pack = ActionPack {
+        actions: actions.iter().cloned().map(|a| Action { owner: None, ..a }).collect(),
+    };
+    if as_toml {
+        toml::to_string(&pack).map_err(|e| e.to_string())
+    } else {
+        serde_json::to_string_pretty(&pack).map_err(|e| e.to_string())
+    }
+}
+
+/// Read a shared set of actions: JSON (`{"actions": [...]}` or a bare array) or TOML.
+/// Legacy single-pattern actions are migrated; owners are dropped.
+pub fn parse_action_pack(text: &str) -> Result<Vec<Action>, String> {
+    let trimmed = text.trim_start();
+    let mut actions = if trimmed.starts_with('[') && serde_json::from_str::<serde_json::Value>(trimmed).is_ok() {
+        serde_json::from_str::<Vec<Action>>(trimmed).map_err(|e| e.to_string())?
+    } else if trimmed.starts_with('{') {
+        serde_json::from_str::<ActionPack>(trimmed).map_err(|e| e.to_string())?.actions
+    } else {
+        toml::from_str::<ActionPack>(text).map_err(|e| e.message().to_string())?.actions
+    };
+    for action in &mut actions {
+        action.normalize();
+        action.owner = None;
+    }
+    if actions.iter().any(|a| a.name.is_empty()) {
+        return Err("every action needs a name".to_string());
+    }
+    Ok(actions)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // --- duplicate_action / move_action ---
+
+    #[test]
+    fn test_duplicate_action_inserts_uniquely_named_copy() {
+        let mut actions = vec![named_action("heal"), named_action("wave")];
+        actions[0].command = "cast heal".to_string();
+        assert_eq!(duplicate_action(&mut actions, 0), Some(1));
+        assert_eq!(actions[1].name, "heal_copy");
+        assert_eq!(actions[1].command, "cast heal");
+        assert_eq!(duplicate_action(&mut actions, 0), Some(1));
+        assert_eq!(actions[1].name, "heal_copy2");
+        let names: Vec<&str> = actions.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["heal", "heal_copy2", "heal_copy", "wave"]);
+        assert_eq!(duplicate_action(&mut actions, 9), None);
+    }
+
+    #[test]
+    fn test_move_action_swaps_neighbors() {
+        let mut actions = vec![named_action("a"), named_action("b"), named_action("c")];
+        assert_eq!(move_action(&mut actions, 2, true), Some(1));
+        assert_eq!(move_action(&mut actions, 0, false), Some(1));
+        let names: Vec<&str> = actions.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "a", "b"]);
+        assert_eq!(move_action(&mut actions, 0, true), None);
+        assert_eq!(move_action(&mut actions, 2, false), None);
+        assert_eq!(move_action(&mut actions, 5, true), None);
+    }
+
+    // --- action packs ---
+
+    #[test]
+    fn test_action_pack_round_trip() {
+        let mut action = Action::new();
+        action.name = "heal".to_string();
+        action.world = "aardwolf".to_string();
+        action.match_type = MatchType::Wildcard;
+        action.patterns.push(MatchPattern { pattern: "* bleeds".to_string(), compiled_regex: None });
+        action.command = "cast heal $1".to_string();
+        action.owner = Some("alice".to_string());
+        let actions = vec![action];
+
+        for as_toml in [true, false] {
+            let text = export_action_pack(&actions, as_toml).unwrap();
+            assert!(!text.contains("alice"));
+            let back = parse_action_pack(&text).unwrap();
+            assert_eq!(back.len(), 1);
+            assert_eq!(back[0].name, "heal");
+            assert_eq!(back[0].world, "aardwolf");
+            assert_eq!(back[0].match_type, MatchType::Wildcard);
+            assert_eq!(back[0].display_pattern(), "* bleeds");
+            assert_eq!(back[0].command, "cast heal $1");
+            assert_eq!(back[0].owner, None);
+        }
+    }
+
+    #[test]
+    fn test_action_pack_accepts_bare_array_and_legacy_pattern() {
+        let back = parse_action_pack(r#"[{"name": "hi", "world": "", "pattern": "^hello", "command": "wave"}]"#).unwrap();
+        assert_eq!(back[0].display_pattern(), "^hello");
+        assert!(back[0].enabled);
+        assert!(parse_action_pack(r#"[{"world": "", "command": "x"}]"#).is_err());
+        assert!(parse_action_pack("[[actions]]\nname = \"\"\nworld = \"\"\ncommand = \"x\"\n").is_err());
+        assert!(parse_action_pack("not a pack").is_err());
+    }
+
+    // --- test_patterns ---
+
+    #[test]
+    fn test_pattern_tester_reports_captures_and_gag() {
+        let patterns = vec![String::new(), "^(\\w+) tells you '(.*)'".to_string()];
+        let result = test_patterns(&patterns, MatchType::Regexp, "/gag; reply $2", "Bob tells you 'hi'");
+        assert_eq!(result, Ok(Some(PatternTest {
+            pattern: 1,
+            captures: vec!["Bob tells you 'hi'".into(), "Bob".into(), "hi".into()],
+            gag: true,
+        })));
+        assert_eq!(describe_pattern_test(&result),
+            "Pattern 2 matches $0=\"Bob tells you 'hi'\" $1=\"Bob\" $2=\"hi\" (gagged)");
+
+        let result = test_patterns(&["* arrives".to_string()], MatchType::Wildcard, "wave", "A rat arrives");
+        assert_eq!(result.unwrap().unwrap().captures[1], "A rat");
+        assert_eq!(test_patterns(&["^x$".to_string()], MatchType::Regexp, "", "y"), Ok(None));
+    }
+
+    #[test]
+    fn test_pattern_tester_rejects_bad_regex() {
+        let patterns = vec!["fine".to_string(), "(unclosed".to_string()];
+        let err = check_patterns(&patterns, MatchType::Regexp).unwrap_err();
+        assert!(err.starts_with("Pattern 2: "), "{}", err);
+        assert!(!err.contains('\n'));
+        // The same text is a literal in wildcard mode
+        assert!(check_patterns(&patterns, MatchType::Wildcard).is_ok());
+    }
+
     // --- split_action_commands ---
 
     #[test]
@@ -1022,6 +1297,24 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_matching_actions_lists_shadowed_matches() {
+        let mut actions = vec![
+            make_action("tell", r"^(\w+) tells you (.*)", "reply $2", MatchType::Regexp),
+            make_action("other", "zzz", "nod", MatchType::Regexp),
+            make_action("any", "*tells*", "/gag", MatchType::Wildcard),
+        ];
+        actions.push(Action { enabled: false, ..make_action("off", "tells", "x", MatchType::Regexp) });
+        let found = matching_actions("Bob tells you hi", "", &actions);
+        let names: Vec<&str> = found.iter().map(|r| r.action_name.as_str()).collect();
+        assert_eq!(names, vec!["tell", "any"]);
+        assert_eq!(found[0].captures, vec!["Bob tells you hi", "Bob", "hi"]);
+        assert_eq!(found[0].commands, vec!["reply hi"]);
+        assert!(found[1].should_gag);
+        // The first of them is what fires
+        assert_eq!(check_action_triggers("Bob tells you hi", "", &actions).unwrap().action_name, "tell");
+    }
+
     #[test]
     fn test_trigger_case_insensitive() {
         let actions = vec![make_action("test", "hello", "nod", MatchType::Regexp)];
@@ -1047,6 +1340,33 @@ mod tests {
         assert!(result.commands.is_empty());
     }
 
+    #[test]
+    fn test_trigger_capture_command() {
+        let actions = vec![make_action("tells", "tells you", "/capture tells; /gag", MatchType::Regexp)];
+        let result = check_action_triggers("Bob tells you: hi", "", &actions).unwrap();
+        assert_eq!(result.capture, Some("tells".to_string()));
+        assert!(result.should_gag);
+        // /capture <name> should be filtered from commands
+        assert!(result.commands.is_empty());
+    }
+
+    #[test]
+    fn test_trigger_pipe_command() {
+        let actions = vec![make_action("ooc", r"(\w+) says OOC", "/pipe ./log-ooc $1; nod $1", MatchType::Regexp)];
+        let result = check_action_triggers("Bob says OOC, hi", "", &actions).unwrap();
+        // The program is taken as written; the line goes to its stdin
+        assert_eq!(result.pipes, vec!["./log-ooc $1"]);
+        assert_eq!(result.commands, vec!["nod Bob"]);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_trigger_shell_command_quotes_captures() {
+        let actions = vec![make_action("tell", r"(\w+) tells you: (.*)", "/shell notify $1 $2; say hi $2", MatchType::Regexp)];
+        let result = check_action_triggers("Bob tells you: x'; rm -rf ~ #", "", &actions).unwrap();
+        assert_eq!(result.commands, vec![r"/shell notify 'Bob' 'x'\''; rm -rf ~ #'", "say hi x'; rm -rf ~ #"]);
+    }
+
     #[test]
     fn test_trigger_capture_substitution() {
         let actions = vec![make_action(
@@ -247,6 +247,28 @@ pub fn play_file(
     }
 }
 
+/// Play a notification sound file (/notify -w sound, /notify -s) on a thread of its
+/// own, with its own backend so the caller needn't hold one. `~/` is the home directory.
+pub fn play_notify_sound(file: &str) {
+    // Files only: the players would happily fetch a URL too
+    if file.is_empty() || file.contains("://") {
+        return;
+    }
+    let path = match file.strip_prefix("~/") {
+        Some(home_rel) => std::path::PathBuf::from(crate::get_home_dir()).join(home_rel),
+        None => std::path::PathBuf::from(file),
+    };
+    std::thread::spawn(move || {
+        let backend = init_audio();
+        if let Some(mut handle) = play_file(&backend, &path, 100, 1) {
+            // A native sink stops when dropped, so hold it until the sound ends
+            while handle.try_wait() == Some(false) {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+        }
+    });
+}
+
 /// B5 (security remediation): validate a MUD-supplied GMCP `Client.Media.*` URL before
 /// it's ever handed to curl. `download_to_cache` is the single choke point both media
 /// call sites (`main.rs` "Play" and "Load") route through, so validating here covers
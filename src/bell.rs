@@ -0,0 +1,77 @@
+//! BEL characters (Ctrl-G) in server output.
+//!
+//! Output text never shows BEL (encoding.rs strips it), so bells are counted from
+//! the raw bytes, leaving out the BELs that end OSC sequences. Each world counts
+//! its bells until it is next viewed (`bell_count`, shown in the /worlds popup)
+//! and reacts as its `/bell` mode says: ignore (the default), flash the status
+//! bar, ring the terminal bell, or send a notification as `/notify` does. The
+//! console, remote console and web clients all flash and ring.
+
+/// What a world does when its output rings the bell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BellMode {
+    #[default]
+    Ignore,
+    Flash,
+    Ring,
+    Notify,
+}
+
+impl BellMode {
+    pub const ALL: [BellMode; 4] = [BellMode::Ignore, BellMode::Flash, BellMode::Ring, BellMode::Notify];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BellMode::Ignore => "ignore",
+            BellMode::Flash => "flash",
+            BellMode::Ring => "ring",
+            BellMode::Notify => "notify",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// How long the status bar stays inverted after a bell
+pub const FLASH_DURATION: std::time::Duration = std::time::Duration::from_millis(600);
+
+/// Bells in a chunk of raw server output. BELs ending an OSC sequence (`ESC ]`)
+/// aren't bells; an OSC cut off at the end of the chunk is assumed to end there.
+pub fn count_bells(bytes: &[u8]) -> usize {
+    let mut count = 0;
+    let mut in_osc = false;
+    let mut prev = 0u8;
+    for &b in bytes {
+        if in_osc {
+            // OSC ends with BEL or ST (ESC \)
+            if b == 0x07 || (prev == 0x1b && b == b'\\') {
+                in_osc = false;
+            }
+        } else if prev == 0x1b && b == b']' {
+            in_osc = true;
+        } else if b == 0x07 {
+            count += 1;
+        }
+        prev = b;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_bells_skips_osc_terminators() {
+        assert_eq!(count_bells(b"Bob pages you.\x07\r\n"), 1);
+        assert_eq!(count_bells(b"\x07\x07x\x07"), 3);
+        assert_eq!(count_bells(b"\x1b]8;;http://x.org\x07link\x1b]8;;\x07 \x07"), 1);
+        assert_eq!(count_bells(b"\x1b]0;title\x1b\\\x07"), 1);
+        assert_eq!(count_bells(b"no bells"), 0);
+        assert_eq!(BellMode::from_name("RING"), Some(BellMode::Ring));
+        assert_eq!(BellMode::from_name("loud"), None);
+        assert_eq!(BellMode::default().name(), "ignore");
+    }
+}
@@ -0,0 +1,110 @@
+//! Chat capture buffers and the console capture pane.
+//!
+//! An action whose commands include `/capture <name>` copies each line it matches
+//! into the capture buffer `<name>` (add `/gag` as well to take the line out of the
+//! world). Capture buffers are connectionless worlds, the same ones tag routes copy
+//! into, so the web and GUI clients list them with the other worlds and they keep
+//! their own scrollback and unseen count.
+//!
+//! The console can show one capture below the output as a split pane
+//! (`/capture pane <name>`, `/capture height <rows>`), or switch the whole output to
+//! it and back (`/capture view <name>`, then `/capture view`).
+
+/// Default rows of capture text in the split pane (plus its title row)
+pub const DEFAULT_PANE_HEIGHT: u16 = 8;
+
+/// `/capture` subcommands; any other first word is a buffer name
+pub const SUBCOMMANDS: &[&str] = &["pane", "view", "height", "clear"];
+
+/// Subcommands that only change the local console's layout
+pub const CONSOLE_SUBCOMMANDS: &[&str] = &["pane", "view", "height"];
+
+/// Which capture is on screen, and how (one per App; session state)
+#[derive(Debug)]
+pub struct PaneManager {
+    /// Capture buffers that received lines this session, in first-use order
+    pub captures: Vec<String>,
+    /// Capture shown in the split pane below the output
+    pub pane: Option<String>,
+    /// Rows of capture text in the pane
+    pub height: u16,
+    /// World to go back to when leaving the capture view
+    pub view_return: Option<usize>,
+}
+
+impl Default for PaneManager {
+    fn default() -> Self {
+        Self {
+            captures: Vec::new(),
+            pane: None,
+            height: DEFAULT_PANE_HEIGHT,
+            view_return: None,
+        }
+    }
+}
+
+impl PaneManager {
+    pub fn note_capture(&mut self, name: &str) {
+        if !self.captures.iter().any(|c| c.eq_ignore_ascii_case(name)) {
+            self.captures.push(name.to_string());
+        }
+    }
+
+    /// Rows the pane takes (title included) out of `available` output rows, 0 when
+    /// nothing is shown. The pane never takes more than half the output area.
+    pub fn pane_rows(&self, shown: bool, available: u16) -> u16 {
+        if !shown {
+            return 0;
+        }
+        let rows = (self.height + 1).min(available / 2);
+        if rows < 2 { 0 } else { rows }
+    }
+}
+
+/// Buffer named by an action command `/capture <name>` (None for other commands,
+/// including the `/capture` subcommands)
+pub fn action_target(cmd: &str) -> Option<&str> {
+    let (head, rest) = cmd.trim().split_once(char::is_whitespace)?;
+    if !head.eq_ignore_ascii_case("/capture") {
+        return None;
+    }
+    let name = rest.trim();
+    let is_subcommand = SUBCOMMANDS.iter().any(|s| name.split_whitespace().next().is_some_and(|w| w.eq_ignore_ascii_case(s)));
+    (!name.is_empty() && !name.contains(char::is_whitespace) && !is_subcommand).then_some(name)
+}
+
+/// Whether `/capture <args>` is handled by the console itself (pane and view layout)
+pub fn is_console_subcommand(args: &str) -> bool {
+    args.split_whitespace().next()
+        .is_some_and(|w| CONSOLE_SUBCOMMANDS.iter().any(|s| w.eq_ignore_ascii_case(s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_target() {
+        assert_eq!(action_target("/capture tells"), Some("tells"));
+        assert_eq!(action_target("/CAPTURE  channels "), Some("channels"));
+        assert_eq!(action_target("/capture pane tells"), None);
+        assert_eq!(action_target("/capture"), None);
+        assert_eq!(action_target("/capture two words"), None);
+        assert_eq!(action_target("/captured tells"), None);
+        assert!(is_console_subcommand("pane tells"));
+        assert!(!is_console_subcommand("clear tells"));
+    }
+
+    #[test]
+    fn test_pane_rows() {
+        let mut panes = PaneManager::default();
+        assert_eq!(panes.pane_rows(false, 40), 0);
+        assert_eq!(panes.pane_rows(true, 40), DEFAULT_PANE_HEIGHT + 1);
+        panes.height = 30;
+        assert_eq!(panes.pane_rows(true, 40), 20);
+        assert_eq!(panes.pane_rows(true, 3), 0);
+        panes.note_capture("Tells");
+        panes.note_capture("tells");
+        assert_eq!(panes.captures, vec!["Tells"]);
+    }
+}
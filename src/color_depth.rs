@@ -0,0 +1,293 @@
+//! Downgrading server colors for terminals with fewer of them.
+//!
+//! Servers send 256-color (`38;5;n`) and 24-bit (`38;2;r;g;b`) SGR codes whether or
+//! not the terminal can show them, and a 16-color console shows them wrong (or not
+//! at all). The console detects what its terminal supports at startup (`COLORTERM`,
+//! then the `colors` capability of the terminfo entry for `TERM`) and rewrites the
+//! codes in each output line to the nearest color it has. The web and GUI clients
+//! always get the original codes. The same goes for curly underlines, which mark
+//! misspelled input where the terminal has them.
+
+use std::path::PathBuf;
+
+/// How many colors the terminal shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDepth {
+    /// The 16 ANSI colors
+    Ansi16,
+    /// The xterm 256-color palette
+    Ansi256,
+    /// 24-bit color: nothing to downgrade
+    #[default]
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// The depth of the terminal this process runs in
+    pub fn detect() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let depth = Self::from_env(&var("COLORTERM"), &term);
+        if depth != Self::Ansi16 || term.is_empty() {
+            return depth;
+        }
+        match terminfo_colors(&term) {
+            Some(n) if n >= 1 << 24 => Self::TrueColor,
+            Some(n) if n >= 256 => Self::Ansi256,
+            _ => Self::Ansi16,
+        }
+    }
+
+    /// The depth `COLORTERM` and `TERM` alone tell. Windows consoles (no `TERM`) have
+    /// 24-bit color since Windows 10.
+    pub fn from_env(colorterm: &str, term: &str) -> Self {
+        let colorterm = colorterm.to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else if term.is_empty() && cfg!(windows) {
+            Self::TrueColor
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+/// Whether the terminal this process runs in draws curly underlines (`4:3`)
+pub fn undercurl_supported() -> bool {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    undercurl_from_env(&var("TERM"), &var("TERM_PROGRAM"), &var("VTE_VERSION"))
+}
+
+/// Whether `TERM`, `TERM_PROGRAM` and `VTE_VERSION` name a terminal known to have
+/// curly underlines. Others may read the colon subparameter as a separate code
+/// (italic, blink), so they keep the straight underline.
+pub fn undercurl_from_env(term: &str, term_program: &str, vte_version: &str) -> bool {
+    ["kitty", "wezterm", "foot", "alacritty", "ghostty", "contour"].iter().any(|t| term.contains(t))
+        || matches!(term_program, "WezTerm" | "iTerm.app" | "ghostty" | "vscode")
+        // VTE (GNOME Terminal, Tilix, ...) since 0.52
+        || vte_version.parse::<u32>().is_ok_and(|v| v >= 5200)
+}
+
+/// The `colors` number of the compiled terminfo entry for `term`, if one is found
+fn terminfo_colors(term: &str) -> Option<u32> {
+    let first = term.chars().next()?;
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Ok(dir) = std::env::var("TERMINFO") {
+        dirs.push(dir.into());
+    }
+    dirs.push(PathBuf::from(crate::get_home_dir()).join(".terminfo"));
+    if let Ok(list) = std::env::var("TERMINFO_DIRS") {
+        dirs.extend(list.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+    }
+    dirs.extend(["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo", "/usr/lib/terminfo"].map(PathBuf::from));
+    dirs.iter()
+        // Linux files entries under their first letter, macOS under its hex code
+        .flat_map(|dir| [dir.join(first.to_string()).join(term), dir.join(format!("{:x}", first as u32)).join(term)])
+        .find_map(|path| std::fs::read(path).ok())
+        .and_then(|data| parse_terminfo_colors(&data))
+}
+
+/// The `colors` number (numeric capability 13) of a compiled terminfo entry, in the
+/// legacy (16-bit numbers) or extended (32-bit numbers) format
+fn parse_terminfo_colors(data: &[u8]) -> Option<u32> {
+    const COLORS: usize = 13;
+    let short = |at: usize| -> Option<usize> { Some(u16::from_le_bytes([*data.get(at)?, *data.get(at + 1)?]) as usize) };
+    let number_size = match short(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let (names_size, bools_count, numbers_count) = (short(2)?, short(4)?, short(6)?);
+    if COLORS >= numbers_count {
+        return None;
+    }
+    let mut at = 12 + names_size + bools_count;
+    // The numbers start on an even byte
+    at += at % 2;
+    let at = at + COLORS * number_size;
+    let bytes = data.get(at..at + number_size)?;
+    let n = if number_size == 2 {
+        i16::from_le_bytes([bytes[0], bytes[1]]) as i32
+    } else {
+        i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    };
+    u32::try_from(n).ok()
+}
+
+/// The usual xterm RGB values of the 16 ANSI colors
+const ANSI16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+/// The six levels of each channel in the 256-color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// RGB of a 256-color palette entry
+pub fn rgb_of_256(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => ANSI16_RGB[n as usize],
+        16..=231 => {
+            let n = n - 16;
+            (CUBE_LEVELS[(n / 36) as usize], CUBE_LEVELS[(n / 6 % 6) as usize], CUBE_LEVELS[(n % 6) as usize])
+        }
+        _ => {
+            let gray = 8 + (n - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// The 256-color entry nearest `rgb`, from the color cube or the gray ramp
+pub fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |c: u8| CUBE_LEVELS.iter().enumerate().min_by_key(|(_, &l)| (l as i32 - c as i32).abs()).map_or(0, |(i, _)| i as u8);
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    let avg = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = 232 + (avg.saturating_sub(3) / 10).min(23) as u8;
+    if distance(rgb_of_256(gray), rgb) < distance(rgb_of_256(cube), rgb) { gray } else { cube }
+}
+
+/// The ANSI color (0-15) nearest `rgb`
+pub fn nearest_16(rgb: (u8, u8, u8)) -> u8 {
+    (0..16u8).min_by_key(|&i| distance(ANSI16_RGB[i as usize], rgb)).unwrap_or(7)
+}
+
+/// Rewrite the 256-color and 24-bit SGR codes in `s` to colors `depth` has
+pub fn downgrade(s: &str, depth: ColorDepth) -> String {
+    if depth == ColorDepth::TrueColor || !s.contains("\x1b[") {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find("\x1b[") {
+        out.push_str(&rest[..pos]);
+        let seq = &rest[pos + 2..];
+        let params_len = seq.find(|c: char| !(c.is_ascii_digit() || c == ';')).unwrap_or(seq.len());
+        if !seq[params_len..].starts_with('m') {
+            out.push_str("\x1b[");
+            rest = seq;
+            continue;
+        }
+        let params: Vec<&str> = seq[..params_len].split(';').collect();
+        let mut codes: Vec<String> = Vec::with_capacity(params.len());
+        let mut i = 0;
+        while i < params.len() {
+            let arg = |k: usize| params.get(i + k).and_then(|p| p.parse::<u8>().ok());
+            let color = match (params[i], params.get(i + 1).copied()) {
+                ("38" | "48", Some("5")) => arg(2).map(|n| (3, Err(n))),
+                ("38" | "48", Some("2")) => match (arg(2), arg(3), arg(4)) {
+                    (Some(r), Some(g), Some(b)) => Some((5, Ok((r, g, b)))),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let Some((len, color)) = color else {
+                codes.push(params[i].to_string());
+                i += 1;
+                continue;
+            };
+            let fg = params[i] == "38";
+            match (depth, color) {
+                (ColorDepth::Ansi256, Err(n)) => codes.push(format!("{};5;{}", params[i], n)),
+                (ColorDepth::Ansi256, Ok(rgb)) => codes.push(format!("{};5;{}", params[i], nearest_256(rgb))),
+                (_, color) => {
+                    let idx = match color {
+                        Err(n) if n < 16 => n,
+                        Err(n) => nearest_16(rgb_of_256(n)),
+                        Ok(rgb) => nearest_16(rgb),
+                    };
+                    let base = match (fg, idx < 8) {
+                        (true, true) => 30,
+                        (true, false) => 90 - 8,
+                        (false, true) => 40,
+                        (false, false) => 100 - 8,
+                    };
+                    codes.push((base + idx as u32).to_string());
+                }
+            }
+            i += len;
+        }
+        out.push_str("\x1b[");
+        out.push_str(&codes.join(";"));
+        out.push('m');
+        rest = &seq[params_len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_env() {
+        assert_eq!(ColorDepth::from_env("truecolor", "xterm-256color"), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env("24BIT", "xterm"), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env("", "xterm-direct"), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env("", "screen-256color"), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env("", "linux"), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn test_undercurl_from_env() {
+        assert!(undercurl_from_env("xterm-kitty", "", ""));
+        assert!(undercurl_from_env("xterm-256color", "WezTerm", ""));
+        assert!(undercurl_from_env("xterm-256color", "", "7600"));
+        assert!(!undercurl_from_env("xterm-256color", "", "5000"), "VTE before 0.52");
+        assert!(!undercurl_from_env("xterm-256color", "Apple_Terminal", ""));
+        assert!(!undercurl_from_env("linux", "", ""));
+    }
+
+    #[test]
+    fn test_parse_terminfo_colors() {
+        // Header, names "x\0", two booleans, then numbers (colors is the 14th)
+        let entry = |magic: u16, size: usize, colors: i32| {
+            let mut data = Vec::new();
+            for n in [magic, 2, 2, 15, 0, 0] {
+                data.extend(n.to_le_bytes());
+            }
+            data.extend(b"x\0\x01\x01");
+            for i in 0..15 {
+                let n = if i == 13 { colors } else { -1 };
+                data.extend(&n.to_le_bytes()[..size]);
+            }
+            data
+        };
+        assert_eq!(parse_terminfo_colors(&entry(0o432, 2, 8)), Some(8));
+        assert_eq!(parse_terminfo_colors(&entry(0o432, 2, 256)), Some(256));
+        assert_eq!(parse_terminfo_colors(&entry(0o1036, 4, 1 << 24)), Some(1 << 24));
+        assert_eq!(parse_terminfo_colors(&entry(0o432, 2, -1)), None, "absent");
+        assert_eq!(parse_terminfo_colors(b"not terminfo"), None);
+        assert_eq!(parse_terminfo_colors(&entry(0o432, 2, 8)[..30]), None, "truncated");
+    }
+
+    #[test]
+    fn test_nearest_colors() {
+        assert_eq!(nearest_256((255, 0, 0)), 196);
+        assert_eq!(nearest_256((0, 0, 0)), 16);
+        assert_eq!(nearest_256((128, 128, 128)), 244);
+        assert_eq!(rgb_of_256(196), (255, 0, 0));
+        assert_eq!(nearest_16((250, 10, 10)), 9);
+        assert_eq!(nearest_16((180, 0, 0)), 1);
+        assert_eq!(nearest_16(rgb_of_256(231)), 15);
+    }
+
+    #[test]
+    fn test_downgrade() {
+        let s = "\x1b[1;38;2;255;0;0mred\x1b[48;5;21m \x1b[0m";
+        assert_eq!(downgrade(s, ColorDepth::TrueColor), s);
+        assert_eq!(downgrade(s, ColorDepth::Ansi256), "\x1b[1;38;5;196mred\x1b[48;5;21m \x1b[0m");
+        assert_eq!(downgrade(s, ColorDepth::Ansi16), "\x1b[1;91mred\x1b[44m \x1b[0m");
+        assert_eq!(downgrade("\x1b[38;5;3;48;5;9mx", ColorDepth::Ansi16), "\x1b[33;101mx");
+        // Other SGR and CSI codes, and incomplete color codes, pass through
+        assert_eq!(downgrade("\x1b[2K\x1b[31;4mx\x1b[38;2;1m", ColorDepth::Ansi16), "\x1b[2K\x1b[31;4mx\x1b[38;2;1m");
+    }
+}
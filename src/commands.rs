@@ -11,14 +11,13 @@ use async_recursion::async_recursion;
 use bytes::BytesMut;
 use futures::StreamExt;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 
 #[allow(unused_imports)]
 use crate::{
     App, AppEvent, Command, World, WorldType, SocketFd,
-    WsMessage, WriteCommand, StreamReader, StreamWriter,
-    Encoding, AutoConnectType,
+    WsMessage, WriteCommand, StreamReader, StreamWriter, MudTransport,
+    Encoding, AutoConnectType, TimestampMode,
     parse_command, get_version_string,
     split_action_commands, substitute_action_args, execute_recall,
     find_invocable_action,
@@ -27,7 +26,7 @@ use crate::{
     process_telnet, find_safe_split_point,
     local_time_from_epoch,
     VERSION, BUILD_DATE, BUILD_HASH,
-    tf, persistence, telnet, util,
+    tf, persistence, telnet, util, keybindings,
     popup,
 };
 
@@ -1220,9 +1219,11 @@ pub(crate) fn spawn_remote_ping_check(
 
 #[async_recursion(?Send)]
 pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sender<AppEvent>) -> bool {
-    let parsed = parse_command(cmd);
+    let parsed = app.resolve_open_uri(parse_command(cmd));
 
     match parsed {
+        // Already turned into a WorldSwitch by resolve_open_uri
+        Command::OpenUri { .. } => {}
         Command::Help => {
             app.open_help_popup_new();
         }
@@ -1247,6 +1248,10 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             app.add_output("Font settings are available in the web and GUI interfaces.");
         }
         Command::Quit => {
+            if app.quit_needs_confirm() {
+                app.open_quit_confirm();
+                return false;
+            }
             // Kill all TLS proxy processes before quitting
             for world in &app.worlds {
                 #[cfg(unix)]
@@ -1271,6 +1276,29 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             // Open web settings popup
             app.open_web_popup_new();
         }
+        Command::WebBans => {
+            for line in web_bans_lines(&app.ban_list) {
+                app.add_output(&line);
+            }
+        }
+        Command::WebViewer { ref args } => {
+            let msg = web_viewer_command(app, args);
+            app.add_output(&msg);
+        }
+        Command::WebAcme { ref args } => {
+            let msg = web_acme_command(app, args);
+            app.add_output(&msg);
+        }
+        Command::WebPush { ref args } => {
+            let msg = web_push_command(app, args);
+            app.add_output(&msg);
+        }
+        Command::WebTokens { ref args } => {
+            match DeviceTokenRequest::parse(args) {
+                Ok(request) => app.apply_device_token_request(request),
+                Err(usage) => app.add_output(&usage),
+            }
+        }
         Command::WorldSelector => {
             // /worlds (no args) - show world selector popup
             app.open_world_selector_new();
@@ -1345,11 +1373,10 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
         Command::WorldsList => {
             // Output connected worlds list as text
             let current_idx = app.current_world_index;
-            const KEEPALIVE_SECS: u64 = 5 * 60;
             let worlds_info: Vec<util::WorldListInfo> = app.worlds.iter().enumerate().map(|(idx, world)| {
                 let now = std::time::Instant::now();
                 let next_nop = if world.connected {
-                    world.last_send_time.map(|t| KEEPALIVE_SECS.saturating_sub(t.elapsed().as_secs()))
+                    world.last_send_time.map(|t| world.settings.keep_alive_interval().as_secs().saturating_sub(t.elapsed().as_secs()))
                 } else {
                     None
                 };
@@ -1403,6 +1430,41 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             }
             return false;
         }
+        Command::Export { args } => {
+            for line in export_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::TriggerTest { args } => {
+            for line in trigger_test_command(app, app.current_world_index, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::ActionsExport { args } => {
+            for line in actions_export_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::ActionsImport { args } => {
+            for line in actions_import_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Lock { args } => {
+            for line in lock_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Unlock { args } => {
+            for line in unlock_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::ImportFile { args } => {
+            for line in import_file_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
         Command::Import { addr } => {
             if !app.is_master {
                 app.add_output("Only the master client can use /import.");
@@ -1462,19 +1524,47 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             } else {
                 (raw_host, None)
             };
+            let tls_verify = app.current_world().settings.tls_verify;
+            let tls_client_cert = (
+                app.current_world().settings.tls_client_cert.clone(),
+                app.current_world().settings.tls_client_key.clone(),
+            );
+            let tls_identity = if use_ssl {
+                match crate::platform::ClientIdentity::load(&tls_client_cert.0, &tls_client_cert.1) {
+                    Ok(identity) => identity,
+                    Err(e) => {
+                        app.add_output(&e);
+                        return false;
+                    }
+                }
+            } else {
+                None
+            };
+
+            // A world behind an SSH bastion is reached through the tunnel (see
+            // ssh::connect_world), which carries its TLS too
+            let ssh_world = if app.current_world().settings.ssh_host.is_empty() {
+                None
+            } else {
+                Some(app.current_world().settings.clone())
+            };
 
             // Check if using TLS proxy for connection preservation
             // TLS proxy not available on Android
             #[cfg(not(target_os = "android"))]
-            let use_tls_proxy = use_ssl && app.settings.tls_proxy_enabled;
+            let use_tls_proxy = use_ssl && app.settings.tls_proxy_enabled && ssh_world.is_none();
             #[cfg(target_os = "android")]
             let use_tls_proxy = false;
 
             let ssl_msg = if use_ssl {
                 if use_tls_proxy { " with SSL (via proxy)" } else { " with SSL" }
             } else { "" };
+            let via_msg = match &ssh_world {
+                Some(settings) => format!(" via SSH {}", settings.ssh_host),
+                None => String::new(),
+            };
             app.add_output("");
-            app.add_output(&format!("Connecting to {}:{}{}...", host, port, ssl_msg));
+            app.add_output(&format!("Connecting to {}:{}{}{}...", host, port, ssl_msg, via_msg));
             app.add_output("");
 
             // Handle TLS proxy case separately (proxy does its own TCP connect)
@@ -1482,7 +1572,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             #[cfg(all(unix, not(target_os = "android")))]
             if use_tls_proxy {
                 let world_name = app.current_world().name.clone();
-                match spawn_tls_proxy(&world_name, &host, &port) {
+                match spawn_tls_proxy(&world_name, &host, &port, tls_verify, (&tls_client_cert.0, &tls_client_cert.1)) {
                     Ok((proxy_pid, socket_path)) => {
                         // Connect to the proxy via Unix socket
                         match tokio::net::UnixStream::connect(&socket_path).await {
@@ -1515,7 +1605,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
 
                                 // Open log file if enabled
                                 if app.current_world().settings.log_enabled {
-                                    if app.current_world_mut().open_log_file() {
+                                    if app.current_world_mut().start_log_session() {
                                         let log_path = app.current_world().get_log_path();
                                         app.add_output(&format!("Logging to: {}", log_path.display()));
                                     } else {
@@ -1526,6 +1616,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                 // Setup writer channel (before reader task so telnet_tx is available)
                                 let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                                 app.current_world_mut().command_tx = Some(cmd_tx.clone());
+                                let send_encoding = app.current_world().writer_encoding();
 
                                 // Fire TF CONNECT hook
                                 let hook_result = tf::bridge::fire_event(&mut app.tf_engine, tf::TfHookEvent::Connect);
@@ -1539,24 +1630,28 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                 // Send auto-login if configured (for Connect type)
                                 // Requires BOTH username AND password to be set
                                 let skip_login = app.current_world().skip_auto_login;
-                                let auto_connect_type = app.current_world().settings.auto_connect_type;
-                                // Only clear skip flag for Connect type; Prompt/MooPrompt check it later in handle_prompt
-                                if auto_connect_type == AutoConnectType::Connect {
+                                // Only clear skip flag when the login is sent now; logins that wait for prompts check it later
+                                if !app.current_world().settings.login_waits_for_server() {
                                     app.current_world_mut().skip_auto_login = false;
                                 }
-                                let user = app.current_world().settings.user.clone();
-                                let password = app.current_world().settings.password.clone();
+                                let (user, password) = app.login_credentials(app.current_world_index);
+                                let login = if !skip_login && !user.is_empty() && !password.is_empty() {
+                                    app.current_world().settings.login_lines(&user, &password)
+                                } else {
+                                    Vec::new()
+                                };
                                 // FANSI worlds: always set up client detection window
                                 if app.current_world().settings.encoding == Encoding::Fansi {
                                     app.current_world_mut().fansi_detect_until = Some(std::time::Instant::now() + Duration::from_secs(2));
-                                    if !skip_login && !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                                        let connect_cmd = format!("connect {} {}", user, password);
-                                        app.current_world_mut().fansi_login_pending = Some(connect_cmd);
+                                    if !login.is_empty() {
+                                        app.current_world_mut().fansi_login_pending = Some(login);
+                                    }
+                                } else {
+                                    for line in login {
+                                        let _ = cmd_tx.send(WriteCommand::Text(line)).await;
                                     }
-                                } else if !skip_login && !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                                    let connect_cmd = format!("connect {} {}", user, password);
-                                    let _ = cmd_tx.send(WriteCommand::Text(connect_cmd)).await;
                                 }
+                                app.current_world().start_connect_commands(skip_login, !user.is_empty() && !password.is_empty());
 
                                 // Start reader task with telnet processing
                                 app.current_world_mut().connection_id += 1;
@@ -1579,6 +1674,9 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                                     if result.telnet_detected {
                                                         let _ = event_tx_read.send(AppEvent::TelnetDetected(read_world_name.clone())).await;
                                                     }
+                                                    if let Some(on) = result.server_echo {
+                                                        let _ = event_tx_read.send(AppEvent::ServerEcho(read_world_name.clone(), on)).await;
+                                                    }
                                                     if result.gmcp_negotiated {
                                                         let _ = event_tx_read.send(AppEvent::GmcpNegotiated(read_world_name.clone())).await;
                                                     }
@@ -1619,6 +1717,9 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                                     if result.telnet_detected {
                                                         let _ = event_tx_read.send(AppEvent::TelnetDetected(read_world_name.clone())).await;
                                                     }
+                                                    if let Some(on) = result.server_echo {
+                                                        let _ = event_tx_read.send(AppEvent::ServerEcho(read_world_name.clone(), on)).await;
+                                                    }
                                                     if result.naws_requested {
                                                         let _ = event_tx_read.send(AppEvent::NawsRequested(read_world_name.clone())).await;
                                                     }
@@ -1660,11 +1761,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                 tokio::spawn(async move {
                                     while let Some(cmd) = cmd_rx.recv().await {
                                         let bytes = match &cmd {
-                                            WriteCommand::Text(text) => {
-                                                let mut b = text.as_bytes().to_vec();
-                                                b.extend_from_slice(b"\r\n");
-                                                b
-                                            }
+                                            WriteCommand::Text(text) => send_encoding.get().line_bytes(text),
                                             WriteCommand::Raw(raw) => raw.clone(),
                                             WriteCommand::Shutdown => break,
                                         };
@@ -1697,7 +1794,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             #[cfg(windows)]
             if use_tls_proxy {
                 let world_name = app.current_world().name.clone();
-                match spawn_tls_proxy(&world_name, &host, &port) {
+                match spawn_tls_proxy(&world_name, &host, &port, tls_verify, (&tls_client_cert.0, &tls_client_cert.1)) {
                     Ok((proxy_pid, pipe_path)) => {
                         use tokio::net::windows::named_pipe::ClientOptions;
                         match ClientOptions::new().open(&pipe_path) {
@@ -1723,7 +1820,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                 let world_name = app.current_world().name.clone();
 
                                 if app.current_world().settings.log_enabled {
-                                    if app.current_world_mut().open_log_file() {
+                                    if app.current_world_mut().start_log_session() {
                                         let log_path = app.current_world().get_log_path();
                                         app.add_output(&format!("Logging to: {}", log_path.display()));
                                     } else {
@@ -1733,6 +1830,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
 
                                 let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                                 app.current_world_mut().command_tx = Some(cmd_tx.clone());
+                                let send_encoding = app.current_world().writer_encoding();
 
                                 let hook_result = tf::bridge::fire_event(&mut app.tf_engine, tf::TfHookEvent::Connect);
                                 for cmd in hook_result.send_commands {
@@ -1743,22 +1841,26 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                 }
 
                                 let skip_login = app.current_world().skip_auto_login;
-                                let auto_connect_type = app.current_world().settings.auto_connect_type;
-                                if auto_connect_type == AutoConnectType::Connect {
+                                if !app.current_world().settings.login_waits_for_server() {
                                     app.current_world_mut().skip_auto_login = false;
                                 }
-                                let user = app.current_world().settings.user.clone();
-                                let password = app.current_world().settings.password.clone();
+                                let (user, password) = app.login_credentials(app.current_world_index);
+                                let login = if !skip_login && !user.is_empty() && !password.is_empty() {
+                                    app.current_world().settings.login_lines(&user, &password)
+                                } else {
+                                    Vec::new()
+                                };
                                 if app.current_world().settings.encoding == Encoding::Fansi {
                                     app.current_world_mut().fansi_detect_until = Some(std::time::Instant::now() + Duration::from_secs(2));
-                                    if !skip_login && !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                                        let connect_cmd = format!("connect {} {}", user, password);
-                                        app.current_world_mut().fansi_login_pending = Some(connect_cmd);
+                                    if !login.is_empty() {
+                                        app.current_world_mut().fansi_login_pending = Some(login);
+                                    }
+                                } else {
+                                    for line in login {
+                                        let _ = cmd_tx.send(WriteCommand::Text(line)).await;
                                     }
-                                } else if !skip_login && !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                                    let connect_cmd = format!("connect {} {}", user, password);
-                                    let _ = cmd_tx.send(WriteCommand::Text(connect_cmd)).await;
                                 }
+                                app.current_world().start_connect_commands(skip_login, !user.is_empty() && !password.is_empty());
 
                                 app.current_world_mut().connection_id += 1;
                                 let reader_conn_id = app.current_world().connection_id;
@@ -1779,6 +1881,9 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                                     if result.telnet_detected {
                                                         let _ = event_tx_read.send(AppEvent::TelnetDetected(read_world_name.clone())).await;
                                                     }
+                                                    if let Some(on) = result.server_echo {
+                                                        let _ = event_tx_read.send(AppEvent::ServerEcho(read_world_name.clone(), on)).await;
+                                                    }
                                                     if result.gmcp_negotiated {
                                                         let _ = event_tx_read.send(AppEvent::GmcpNegotiated(read_world_name.clone())).await;
                                                     }
@@ -1819,6 +1924,9 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                                     if result.telnet_detected {
                                                         let _ = event_tx_read.send(AppEvent::TelnetDetected(read_world_name.clone())).await;
                                                     }
+                                                    if let Some(on) = result.server_echo {
+                                                        let _ = event_tx_read.send(AppEvent::ServerEcho(read_world_name.clone(), on)).await;
+                                                    }
                                                     if result.naws_requested {
                                                         let _ = event_tx_read.send(AppEvent::NawsRequested(read_world_name.clone())).await;
                                                     }
@@ -1859,11 +1967,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                 tokio::spawn(async move {
                                     while let Some(cmd) = cmd_rx.recv().await {
                                         let bytes = match &cmd {
-                                            WriteCommand::Text(text) => {
-                                                let mut b = text.as_bytes().to_vec();
-                                                b.extend_from_slice(b"\r\n");
-                                                b
-                                            }
+                                            WriteCommand::Text(text) => send_encoding.get().line_bytes(text),
                                             WriteCommand::Raw(raw) => raw.clone(),
                                             WriteCommand::Shutdown => break,
                                         };
@@ -1898,58 +2002,44 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             let connect_port = port.clone();
             let connect_use_ssl = use_ssl;
             let event_tx_connect = event_tx.clone();
+            let send_encoding = app.current_world().writer_encoding();
 
             tokio::spawn(async move {
-                // Try connecting to a host, resolving DNS and preferring IPv4
-                async fn try_connect_host(host: &str, port: &str) -> Result<TcpStream, std::io::Error> {
-                    use tokio::net::lookup_host;
-                    let addrs: Vec<std::net::SocketAddr> = lookup_host(
-                        format!("{}:{}", host, port)
-                    ).await?.collect();
-                    if addrs.is_empty() {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::AddrNotAvailable,
-                            format!("Could not resolve {}", host),
-                        ));
-                    }
-                    let mut sorted = addrs.clone();
-                    sorted.sort_by_key(|a| if a.is_ipv4() { 0 } else { 1 });
-                    let mut last_err = None;
-                    let mut ipv4_err = None;
-                    for addr in &sorted {
-                        match TcpStream::connect(addr).await {
-                            Ok(stream) => return Ok(stream),
-                            Err(e) => {
-                                if addr.is_ipv4() && ipv4_err.is_none() {
-                                    ipv4_err = Some(e);
-                                } else {
-                                    last_err = Some(e);
-                                }
+                // Try primary host, fall back to secondary if configured. Each host's
+                // IPv4 and IPv6 addresses are raced (see happy_eyeballs.rs).
+                let connect_result = if let Some(settings) = &ssh_world {
+                    crate::ssh::connect_world(settings, tls_identity.as_ref()).await
+                        .map(|(r, w)| MudTransport::Tunneled(r, w))
+                } else {
+                    let direct = match crate::happy_eyeballs::connect(&connect_host, &connect_port).await {
+                        Ok(connected) => Ok((connect_host.clone(), connected)),
+                        Err(primary_err) => {
+                            if let Some(ref fallback) = connect_fallback {
+                                let _ = event_tx_connect.send(AppEvent::ServerData(
+                                    world_name.clone(),
+                                    format!("Primary host {} failed: {}, trying {}...\r\n", connect_host, primary_err, fallback).into_bytes(),
+                                )).await;
+                                crate::happy_eyeballs::connect(fallback, &connect_port).await.map(|connected| (fallback.clone(), connected))
+                            } else {
+                                Err(primary_err)
                             }
                         }
-                    }
-                    Err(ipv4_err.or(last_err).unwrap_or_else(|| {
-                        std::io::Error::other("Connection failed")
-                    }))
-                }
-
-                // Try primary host, fall back to secondary if configured
-                let connect_result = match try_connect_host(&connect_host, &connect_port).await {
-                    Ok(stream) => Ok(stream),
-                    Err(primary_err) => {
-                        if let Some(ref fallback) = connect_fallback {
+                    };
+                    if let Ok((reached_host, (_, reached_addr))) = &direct {
+                        // Say which address answered when the host is a name
+                        if reached_host.parse::<std::net::IpAddr>().is_err() {
                             let _ = event_tx_connect.send(AppEvent::ServerData(
                                 world_name.clone(),
-                                format!("Primary host {} failed: {}, trying {}...\r\n", connect_host, primary_err, fallback).into_bytes(),
+                                format!("Connected to {} at {}.\r\n", reached_host, reached_addr).into_bytes(),
                             )).await;
-                            try_connect_host(fallback, &connect_port).await
-                        } else {
-                            Err(primary_err)
                         }
                     }
+                    direct.map(|(_, (tcp_stream, _))| MudTransport::Direct(tcp_stream)).map_err(|e| e.to_string())
                 };
-                match connect_result {
-                    Ok(tcp_stream) => {
+                let connected = match connect_result {
+                    // The tunnel already negotiated TLS if the world uses it
+                    Ok(MudTransport::Tunneled(r, w)) => Ok((None, Ok((r, w, true)))),
+                    Ok(MudTransport::Direct(tcp_stream)) => {
                         // Store the socket fd/handle for hot reload (before splitting)
                         #[cfg(unix)]
                         let socket_fd: Option<SocketFd> = {
@@ -1971,10 +2061,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                         let connection_result: Result<(StreamReader, StreamWriter, bool), String> = if connect_use_ssl {
                             #[cfg(feature = "native-tls-backend")]
                             {
-                                let connector = match native_tls::TlsConnector::builder()
-                                    .danger_accept_invalid_certs(true)
-                                    .build()
-                                {
+                                let connector = match crate::platform::mud_native_tls_connector(tls_verify, tls_identity.as_ref()) {
                                     Ok(c) => c,
                                     Err(e) => {
                                         let _ = event_tx_connect.send(AppEvent::ConnectionFailed(
@@ -1989,7 +2076,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                 match connector.connect(&connect_host, tcp_stream).await {
                                     Ok(tls_stream) => {
                                         let peer_cert = tls_stream.get_ref().peer_certificate().ok().flatten();
-                                        match crate::platform::check_native_tls_peer_pin(&format!("{}:{}", connect_host, connect_port), peer_cert) {
+                                        match crate::platform::check_native_tls_peer_pin(&format!("{}:{}", connect_host, connect_port), tls_verify, peer_cert) {
                                             Ok(()) => {
                                                 let (r, w) = tokio::io::split(tls_stream);
                                                 Ok((StreamReader::Tls(r), StreamWriter::Tls(w), true))
@@ -2005,17 +2092,19 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
 
                             #[cfg(feature = "rustls-backend")]
                             {
-                                use rustls::RootCertStore;
                                 use tokio_rustls::TlsConnector;
                                 use rustls::pki_types::ServerName;
 
-                                let mut root_store = RootCertStore::empty();
-                                root_store.roots = webpki_roots::TLS_SERVER_ROOTS.to_vec();
-
-                                let config = rustls::ClientConfig::builder()
-                                    .dangerous()
-                                    .with_custom_certificate_verifier(Arc::new(crate::platform::danger_rustls::TofuVerifier::new(format!("{}:{}", connect_host, connect_port))))
-                                    .with_no_client_auth();
+                                let config = match crate::platform::danger_rustls::mud_client_config(&format!("{}:{}", connect_host, connect_port), tls_verify, tls_identity.as_ref()) {
+                                    Ok(c) => c,
+                                    Err(e) => {
+                                        let _ = event_tx_connect.send(AppEvent::ConnectionFailed(
+                                            world_name.clone(),
+                                            e
+                                        )).await;
+                                        return;
+                                    }
+                                };
 
                                 let connector = TlsConnector::from(Arc::new(config));
                                 let server_name = match ServerName::try_from(connect_host.clone()) {
@@ -2048,7 +2137,12 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                             let (r, w) = tcp_stream.into_split();
                             Ok((StreamReader::Plain(r), StreamWriter::Plain(w), false))
                         };
-
+                        Ok((socket_fd, connection_result))
+                    }
+                    Err(e) => Err(e),
+                };
+                match connected {
+                    Ok((socket_fd, connection_result)) => {
                         match connection_result {
                             Ok((mut read_half, mut write_half, is_tls)) => {
                                 // Create command channel
@@ -2077,6 +2171,9 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                                     if result.telnet_detected {
                                                         let _ = event_tx_read.send(AppEvent::TelnetDetected(read_world_name.clone())).await;
                                                     }
+                                                    if let Some(on) = result.server_echo {
+                                                        let _ = event_tx_read.send(AppEvent::ServerEcho(read_world_name.clone(), on)).await;
+                                                    }
                                                     if result.gmcp_negotiated {
                                                         let _ = event_tx_read.send(AppEvent::GmcpNegotiated(read_world_name.clone())).await;
                                                     }
@@ -2139,6 +2236,9 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                                     if result.telnet_detected {
                                                         let _ = event_tx_read.send(AppEvent::TelnetDetected(read_world_name.clone())).await;
                                                     }
+                                                    if let Some(on) = result.server_echo {
+                                                        let _ = event_tx_read.send(AppEvent::ServerEcho(read_world_name.clone(), on)).await;
+                                                    }
                                                     if result.naws_requested {
                                                         let _ = event_tx_read.send(AppEvent::NawsRequested(read_world_name.clone())).await;
                                                     }
@@ -2186,7 +2286,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                                     while let Some(cmd) = cmd_rx.recv().await {
                                         match cmd {
                                             WriteCommand::Text(text) => {
-                                                let bytes = format!("{}\r\n", text).into_bytes();
+                                                let bytes = send_encoding.get().line_bytes(&text);
                                                 if write_half.write_all(&bytes).await.is_err() {
                                                     break;
                                                 }
@@ -2247,6 +2347,53 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                 app.add_output("Not connected.");
             }
         }
+        Command::DisconnectAll => {
+            let count = app.disconnect_all_worlds();
+            app.add_output(&format!("Disconnected {} world{}.", count, if count == 1 { "" } else { "s" }));
+        }
+        Command::ConnectAll => {
+            if !app.is_master {
+                app.add_output("Only the master client can initiate connections.");
+                return false;
+            }
+            // By name: a connect can discard the unused initial world and shift indices
+            let names: Vec<String> = app.auto_connect_worlds().into_iter().map(|idx| app.worlds[idx].name.clone()).collect();
+            if names.is_empty() {
+                app.add_output("No worlds to connect.");
+                return false;
+            }
+            let prev_name = app.current_world().name.clone();
+            let mut bulk = crate::BulkConnect::default();
+            for name in names {
+                let Some(world_index) = app.find_world_index(&name) else { continue };
+                app.current_world_index = world_index;
+                let connection_id = app.worlds[world_index].connection_id;
+                if Box::pin(handle_command("/__connect", app, event_tx.clone())).await {
+                    return true;
+                }
+                let Some(world_index) = app.find_world_index(&name) else { continue };
+                let world = &app.worlds[world_index];
+                if world.connected {
+                    // Made without a ConnectionSuccess event (TLS proxy, Slack, Discord)
+                    bulk.connected += 1;
+                    app.ws_broadcast(WsMessage::WorldConnected { world_index, name });
+                } else if world.connection_id != connection_id {
+                    bulk.pending.push(name);
+                } else {
+                    bulk.failed.push(name);
+                }
+            }
+            if let Some(world_index) = app.find_world_index(&prev_name) {
+                app.current_world_index = world_index;
+            }
+            if bulk.pending.is_empty() {
+                app.add_output(&bulk.summary());
+            } else {
+                let total = bulk.pending.len() + bulk.connected + bulk.failed.len();
+                app.add_output(&format!("Connecting {} worlds...", total));
+                app.bulk_connect = Some(bulk);
+            }
+        }
         Command::Flush => {
             let line_count = app.current_world().output_lines.len();
             app.current_world_mut().output_lines.clear();
@@ -2385,6 +2532,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                             std::io::stdout(),
                             crossterm::terminal::EnterAlternateScreen,
                             crossterm::event::EnableBracketedPaste,
+                            crossterm::event::EnableFocusChange,
                             crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
                             crossterm::cursor::MoveTo(0, 0)
                         );
@@ -2394,6 +2542,47 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                 }
             }
         }
+        Command::Detach => {
+            #[cfg(not(all(unix, not(target_os = "android"))))]
+            {
+                app.add_output("/detach is not available on this platform.");
+            }
+
+            #[cfg(all(unix, not(target_os = "android")))]
+            {
+                // --attach comes back in through the web server, which needs a login
+                if !app.settings.http_enabled
+                    || (app.settings.websocket_password.is_empty() && app.settings.websocket_auth_key.is_none())
+                {
+                    app.add_output("Enable the web interface with a password (/web) first, so clay --attach can reconnect.");
+                    return false;
+                }
+
+                let tls_worlds: Vec<_> = app
+                    .worlds
+                    .iter()
+                    .filter(|w| w.connected && w.is_tls && w.proxy_pid.is_none())
+                    .map(|w| w.name.clone())
+                    .collect();
+                if !tls_worlds.is_empty() {
+                    app.add_output(&format!(
+                        "Warning: TLS connections will be closed: {}",
+                        tls_worlds.join(", ")
+                    ));
+                }
+
+                app.ws_broadcast(WsMessage::ServerReloading);
+                match crate::platform::spawn_detached_session(app) {
+                    // The background process owns the connections now; unlike /quit,
+                    // leave the TLS proxies running
+                    Ok(()) => {
+                        crate::SESSION_DETACHED.store(true, std::sync::atomic::Ordering::Relaxed);
+                        return true;
+                    }
+                    Err(e) => app.add_output(&format!("Detach failed: {}", e)),
+                }
+            }
+        }
         Command::Update { force } => {
             #[cfg(target_os = "android")]
             {
@@ -2491,13 +2680,23 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             app.add_output("Playing test music (Super Mario Bros)...");
         }
         Command::Notify { message } => {
-            // Send notification to mobile clients
-            let title = app.current_world().name.clone();
-            app.ws_broadcast(WsMessage::Notification {
-                title,
-                message: message.clone(),
-            });
-            app.add_output(&format!("Notification sent: {}", message));
+            // Send notification to mobile clients and the desktop
+            for line in notify_command(app, app.current_world_index, &message) {
+                app.add_output(&line);
+            }
+        }
+        Command::Calc { expr, roll, send } => {
+            match crate::dice::result_line(&expr, roll) {
+                Ok(line) if send => {
+                    if let Some(tx) = &app.current_world().command_tx {
+                        let _ = tx.try_send(WriteCommand::Text(line));
+                        app.current_world_mut().last_send_time = Some(std::time::Instant::now());
+                    } else {
+                        app.add_output("Not connected.");
+                    }
+                }
+                Ok(line) | Err(line) => app.add_output(&line),
+            }
         }
         Command::Say { text } => {
             // Speak text via TTS
@@ -2584,7 +2783,7 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
             app.add_output("  Example: /url https://github.com/c-hudson/clay");
         }
         Command::Dump => {
-            // Dump comprehensive debug state to ~/.clay/dump.log
+            // Dump comprehensive debug state to ~/.config/clay/dump.log
             use std::io::Write;
 
             let dump_path = crate::clay_config_path("dump.log");
@@ -2792,6 +2991,184 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
         Command::Window { .. } => {
             app.add_output("Use /window from a web or GUI client to open a new window.");
         }
+        Command::Macro { args } => {
+            if args.is_empty() {
+                app.open_macros_popup(0);
+            } else {
+                let world_idx = app.current_world_index;
+                for line in macro_command(app, world_idx, &args) {
+                    app.add_output(&line);
+                }
+            }
+        }
+        Command::TagRoute { args } => {
+            if args.is_empty() {
+                app.open_tag_routes_popup(0);
+            } else {
+                for line in tag_route_command(app, &args) {
+                    app.add_output(&line);
+                }
+            }
+        }
+        Command::Gag { args } => {
+            if args.is_empty() {
+                app.open_gags_popup(0);
+            } else {
+                let world_idx = app.current_world_index;
+                for line in gag_command(app, world_idx, &args) {
+                    app.add_output(&line);
+                }
+            }
+        }
+        Command::Ungag { args } => {
+            for line in ungag_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Profile { args } => {
+            for line in profile_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Config { args } => {
+            for line in config_command(&args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Numpad { args } => {
+            let world_idx = app.current_world_index;
+            for line in numpad_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Secret { args } => {
+            let world_idx = app.current_world_index;
+            for line in secret_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::LoopGuard { args } => {
+            for line in loopguard_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Clipboard { args } => {
+            for line in clipboard_command(app, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Throttle { args } => {
+            let world_idx = app.current_world_index;
+            for line in throttle_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::LocalEcho { args } => {
+            let world_idx = app.current_world_index;
+            for line in localecho_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Emoji { args } => {
+            let world_idx = app.current_world_index;
+            for line in emoji_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Autocorrect { args } => {
+            let world_idx = app.current_world_index;
+            for line in autocorrect_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::AddWord { word } => {
+            for line in addword_command(app, &word) {
+                app.add_output(&line);
+            }
+        }
+        Command::SpellIgnore { word } => {
+            for line in spellignore_command(app, &word) {
+                app.add_output(&line);
+            }
+        }
+        Command::Unignore { word } => {
+            for line in unignore_command(app, &word) {
+                app.add_output(&line);
+            }
+        }
+        Command::Palette { args } => {
+            let world_idx = app.current_world_index;
+            for line in palette_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Tint { args } => {
+            let world_idx = app.current_world_index;
+            for line in tint_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Images { args } => {
+            let world_idx = app.current_world_index;
+            for line in images_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::SpellLang { args } => {
+            let world_idx = app.current_world_index;
+            for line in spelllang_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Urls => {
+            app.open_urls_popup();
+        }
+        Command::Timestamps { args } => {
+            let world_idx = app.current_world_index;
+            for line in timestamps_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Bell { args } => {
+            let world_idx = app.current_world_index;
+            for line in bell_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Log { args } => {
+            let world_idx = app.current_world_index;
+            for line in log_command(app, world_idx, &args) {
+                app.add_output(&line);
+            }
+        }
+        Command::Replay { args } => {
+            let world_idx = app.current_world_index;
+            for line in replay_command(app, world_idx, &args, Some(&event_tx)) {
+                app.add_output(&line);
+            }
+        }
+        Command::SendFile { args } => {
+            let world_idx = app.current_world_index;
+            for line in sendfile_command(app, world_idx, &args, Some(&event_tx)) {
+                app.add_output(&line);
+            }
+        }
+        Command::Shell { command, pipe } => {
+            let world_idx = app.current_world_index;
+            for line in shell_command(app, world_idx, &command, pipe, Some(&event_tx)) {
+                app.add_output(&line);
+            }
+        }
+        Command::Split { args } => {
+            for line in split_command(app, &args, App::switch_world) {
+                app.add_output(&line);
+            }
+        }
+        Command::Capture { args } => {
+            for line in capture_console_command(app, &args, App::switch_world) {
+                app.add_output(&line);
+            }
+        }
         Command::Tag => {
             // Toggle MUD tag display (same as F2) - silent, no output
             app.show_tags = !app.show_tags;
@@ -2813,11 +3190,12 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
                 let commands = split_action_commands(&action.command);
                 let mut sent_to_server = false;
                 for cmd_str in commands {
-                    // Substitute $1-$9 and $* with arguments
-                    let cmd_str = substitute_action_args(&cmd_str, &args);
+                    // Expand secrets first so arguments can't reference one, then $1-$9 and $*,
+                    // then $[...] rolls (which may use the arguments)
+                    let cmd_str = crate::dice::expand(&substitute_action_args(&app.expand_secrets(app.current_world_index, &cmd_str), &args));
 
-                    // Skip /gag commands when invoked manually
-                    if cmd_str.eq_ignore_ascii_case("/gag") || cmd_str.to_lowercase().starts_with("/gag ") {
+                    // Skip /gag and /capture <name> commands when invoked manually
+                    if cmd_str.eq_ignore_ascii_case("/gag") || cmd_str.to_lowercase().starts_with("/gag ") || crate::capture::action_target(&cmd_str).is_some() {
                         continue;
                     }
 
@@ -2990,7 +3368,9 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
         Command::NotACommand { text } => {
             // Not a command - send to MUD as regular input
             if let Some(tx) = &app.current_world().command_tx {
-                let _ = tx.try_send(WriteCommand::Text(text));
+                let _ = tx.try_send(WriteCommand::Text(text.clone()));
+                let world_idx = app.current_world_index;
+                app.echo_sent(world_idx, &text);
                 app.current_world_mut().last_send_time = Some(std::time::Instant::now());
             }
         }
@@ -3001,6 +3381,2016 @@ pub(crate) async fn handle_command(cmd: &str, app: &mut App, event_tx: mpsc::Sen
     false
 }
 
+// ============================================================================
+// Command helpers
+// ============================================================================
+//
+// Each `*_command` below runs one command and returns the lines to show. The console,
+// WebSocket (main.rs) and daemon handlers all call them, and they save settings on
+// any change.
+
+/// Run a `/tagroute` subcommand against the tag route list and return the lines to show.
+///
+///   /tagroute list                          - list routes
+///   /tagroute add [-w<world>] <tag> <action> - add a route (action: gag, gag-idle:MINS,
+///                                             copy:WORLD, move:WORLD, hilite:COLOR)
+///   /tagroute del <n>                       - delete route n
+///   /tagroute on|off <n>                    - enable/disable route n
+pub(crate) fn tag_route_command(app: &mut App, args: &str) -> Vec<String> {
+    use crate::tag_routes::{TagRoute, TagRouteAction};
+
+    const USAGE: &str = "Usage: /tagroute [list | add [-w<world>] <tag> <action> | del <n> | on <n> | off <n>]";
+    let parts: Vec<&str> = args.split_whitespace().collect();
+    let sub = parts.first().map(|s| s.to_lowercase()).unwrap_or_else(|| "list".to_string());
+
+    // Route numbers are 1-based, as shown by /tagroute list
+    let route_index = |app: &App| -> Option<usize> {
+        let n: usize = parts.get(1)?.parse().ok()?;
+        (n >= 1 && n <= app.settings.tag_routes.len()).then(|| n - 1)
+    };
+
+    match sub.as_str() {
+        "list" | "-l" => {
+            if app.settings.tag_routes.is_empty() {
+                return vec!["No tag routes defined. Use /tagroute add <tag> <action>.".to_string()];
+            }
+            let mut out = vec![format!("{:<4} {:<16} {:<16} {:<20} {}", "#", "Tag", "World", "Action", "Enabled")];
+            for (i, r) in app.settings.tag_routes.iter().enumerate() {
+                let world = if r.world.is_empty() { "(all)" } else { &r.world };
+                out.push(format!("{:<4} {:<16} {:<16} {:<20} {}",
+                    i + 1, r.tag, world, r.action.spec(), if r.enabled { "yes" } else { "no" }));
+            }
+            out
+        }
+        "add" => {
+            let mut rest: &[&str] = &parts[1..];
+            let mut world = String::new();
+            if let Some(w) = rest.first().and_then(|a| a.strip_prefix("-w")) {
+                world = w.to_string();
+                rest = &rest[1..];
+            }
+            if rest.len() != 2 {
+                return vec![USAGE.to_string()];
+            }
+            let Some(action) = TagRouteAction::parse(rest[1]) else {
+                return vec![format!("Unknown tag route action '{}'. Use gag, gag-idle:MINS, copy:WORLD, move:WORLD or hilite:COLOR.", rest[1])];
+            };
+            let mut route = TagRoute::new(rest[0], action);
+            if route.tag.is_empty() {
+                return vec![USAGE.to_string()];
+            }
+            route.world = world;
+            let msg = format!("Added tag route {}: [{}] -> {}", app.settings.tag_routes.len() + 1, route.tag, route.action.spec());
+            app.settings.tag_routes.push(route);
+            tag_routes_changed(app);
+            vec![msg]
+        }
+        "del" | "delete" | "rm" => match route_index(app) {
+            Some(idx) => {
+                let route = app.settings.tag_routes.remove(idx);
+                tag_routes_changed(app);
+                vec![format!("Deleted tag route [{}] -> {}", route.tag, route.action.spec())]
+            }
+            None => vec![USAGE.to_string()],
+        },
+        "on" | "off" => match route_index(app) {
+            Some(idx) => {
+                let enabled = sub == "on";
+                app.settings.tag_routes[idx].enabled = enabled;
+                tag_routes_changed(app);
+                vec![format!("Tag route {} {}.", idx + 1, if enabled { "enabled" } else { "disabled" })]
+            }
+            None => vec![USAGE.to_string()],
+        },
+        _ => vec![USAGE.to_string()],
+    }
+}
+
+/// Save the tag routes and send them to web/GUI clients, whose tag routes popup
+/// may be open
+pub(crate) fn tag_routes_changed(app: &mut App) {
+    let _ = persistence::save_settings(app);
+    app.ws_broadcast(WsMessage::TagRoutesUpdated { routes: app.settings.tag_routes.clone() });
+}
+
+/// Save the gag list (temporary gags are left out) and send it to web/GUI clients,
+/// whose gags popup may be open
+pub(crate) fn gags_changed(app: &mut App) {
+    let _ = persistence::save_settings(app);
+    app.ws_broadcast(WsMessage::GagsUpdated { gags: app.settings.gags.clone() });
+}
+
+/// Run `/gag` against the gag list and return the lines to show.
+///
+///   /gag list                          - list gags
+///   /gag [-w[<world>]] [-t] <pattern>  - add a gag (-w alone: the current world,
+///                                        -t: temporary, forgotten when Clay exits)
+///   /gag on|off <n>                    - enable/disable gag n
+pub(crate) fn gag_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    use crate::gags::Gag;
+
+    const USAGE: &str = "Usage: /gag [list | [-w<world>] [-t] <pattern> | on <n> | off <n>]";
+    let parts: Vec<&str> = args.split_whitespace().collect();
+
+    match parts.as_slice() {
+        [] | ["list"] | ["-l"] => {
+            if app.settings.gags.is_empty() {
+                return vec!["No gags defined. Use /gag <pattern>.".to_string()];
+            }
+            let mut out = vec![format!("{:<4} {:<16} {:<8} {}", "#", "World", "Enabled", "Pattern")];
+            for (i, g) in app.settings.gags.iter().enumerate() {
+                let world = if g.world.is_empty() { "(all)" } else { &g.world };
+                let temporary = if g.temporary { "  (temporary)" } else { "" };
+                out.push(format!("{:<4} {:<16} {:<8} {}{}",
+                    i + 1, world, if g.enabled { "yes" } else { "no" }, g.pattern, temporary));
+            }
+            out
+        }
+        [sub @ ("on" | "off"), n] if n.parse::<usize>().is_ok() => {
+            // Gag numbers are 1-based, as shown by /gag list
+            let n: usize = n.parse().unwrap_or(0);
+            if n == 0 || n > app.settings.gags.len() {
+                return vec![format!("No gag {}. See /gag list.", n)];
+            }
+            let enabled = *sub == "on";
+            app.settings.gags[n - 1].enabled = enabled;
+            gags_changed(app);
+            vec![format!("Gag {} {}.", n, if enabled { "enabled" } else { "disabled" })]
+        }
+        _ => {
+            let mut rest = args.trim();
+            let mut world = String::new();
+            let mut temporary = false;
+            loop {
+                if let Some(after) = rest.strip_prefix("-t").filter(|a| a.is_empty() || a.starts_with(char::is_whitespace)) {
+                    temporary = true;
+                    rest = after.trim_start();
+                } else if rest.starts_with("-w") {
+                    match world_option(app, world_idx, rest) {
+                        Ok((idx, tail)) => {
+                            world = app.worlds[idx].name.clone();
+                            rest = tail;
+                        }
+                        Err(e) => return vec![e],
+                    }
+                } else {
+                    break;
+                }
+            }
+            if rest.is_empty() {
+                return vec![USAGE.to_string()];
+            }
+            let mut gag = Gag::new(rest);
+            gag.world = world;
+            gag.temporary = temporary;
+            let msg = format!("Added gag {}: {}{}{}", app.settings.gags.len() + 1, gag.pattern,
+                if gag.world.is_empty() { String::new() } else { format!(" in {}", gag.world) },
+                if temporary { " (temporary)" } else { "" });
+            app.settings.gags.push(gag);
+            gags_changed(app);
+            vec![msg]
+        }
+    }
+}
+
+/// Run `/ungag <n|pattern>`: delete gag n (as numbered by /gag list) or every gag
+/// with that pattern. A pattern not in the list is tried against the gags TF scripts
+/// make, which are TF macros rather than list entries.
+pub(crate) fn ungag_command(app: &mut App, args: &str) -> Vec<String> {
+    let arg = args.trim();
+    if arg.is_empty() {
+        return vec!["Usage: /ungag <n|pattern>".to_string()];
+    }
+    let removed: Vec<crate::gags::Gag> = match arg.parse::<usize>() {
+        Ok(n) if n >= 1 && n <= app.settings.gags.len() => vec![app.settings.gags.remove(n - 1)],
+        Ok(n) => return vec![format!("No gag {}. See /gag list.", n)],
+        Err(_) => {
+            let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut app.settings.gags)
+                .into_iter()
+                .partition(|g| g.pattern == arg);
+            app.settings.gags = kept;
+            removed
+        }
+    };
+    if removed.is_empty() {
+        return match tf::builtins::cmd_ungag(&mut app.tf_engine, arg) {
+            tf::TfCommandResult::Success(Some(msg)) => vec![msg],
+            _ => vec![format!("No gag matches '{}'. See /gag list.", arg)],
+        };
+    }
+    gags_changed(app);
+    removed.iter().map(|g| format!("Removed gag: {}", g.pattern)).collect()
+}
+
+/// Run a `/profile` subcommand against the profile list and return the lines to show.
+///
+///   /profile [list]                          - show the active profile and list all
+///   /profile add <name> <days> <times> [opts] - add or replace a profile (opts: notify=off,
+///                                              tts=off, gag=tag1,tag2)
+///   /profile del <name>                      - delete a profile
+pub(crate) fn profile_command(app: &mut App, args: &str) -> Vec<String> {
+    use crate::profiles::Profile;
+
+    const USAGE: &str = "Usage: /profile [list | add <name> <days> <times> [notify=off] [tts=off] [gag=tags] | del <name>]";
+    let parts: Vec<&str> = args.split_whitespace().collect();
+    let sub = parts.first().map(|s| s.to_lowercase()).unwrap_or_else(|| "list".to_string());
+
+    match sub.as_str() {
+        "list" | "-l" => {
+            if app.settings.profiles.is_empty() {
+                return vec!["No profiles defined. Use /profile add <name> <days> <times> [options].".to_string()];
+            }
+            let mut out = vec![match &app.active_profile {
+                Some(name) => format!("Active profile: {}", name),
+                None => "Active profile: (none)".to_string(),
+            }];
+            for p in &app.settings.profiles {
+                let marker = if app.active_profile.as_deref() == Some(p.name.as_str()) { "*" } else { " " };
+                out.push(format!("{} {:<12} {}", marker, p.name, p.describe()));
+            }
+            out
+        }
+        "add" => {
+            let Some(name) = parts.get(1) else {
+                return vec![USAGE.to_string()];
+            };
+            let profile = match Profile::from_args(name, &parts[2..]) {
+                Ok(p) => p,
+                Err(e) => return vec![format!("Profile error: {}", e), USAGE.to_string()],
+            };
+            let msg = format!("Profile '{}': {}", profile.name, profile.describe());
+            match app.settings.profiles.iter_mut().find(|p| p.name.eq_ignore_ascii_case(name)) {
+                Some(existing) => *existing = profile,
+                None => app.settings.profiles.push(profile),
+            }
+            let _ = persistence::save_settings(app);
+            app.refresh_active_profile();
+            vec![msg]
+        }
+        "del" | "delete" | "rm" => {
+            let Some(name) = parts.get(1) else {
+                return vec![USAGE.to_string()];
+            };
+            let Some(idx) = app.settings.profiles.iter().position(|p| p.name.eq_ignore_ascii_case(name)) else {
+                return vec![format!("No profile named '{}'.", name)];
+            };
+            let profile = app.settings.profiles.remove(idx);
+            let _ = persistence::save_settings(app);
+            app.refresh_active_profile();
+            vec![format!("Deleted profile '{}'.", profile.name)]
+        }
+        _ => vec![USAGE.to_string()],
+    }
+}
+
+/// Run `/config profile` and return the lines to show: the config profile in use (chosen
+/// at startup with `--profile=<name>`) and the others that exist on disk.
+pub(crate) fn config_command(args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /config profile";
+    let sub = args.split_whitespace().next().map(|s| s.to_lowercase()).unwrap_or_default();
+
+    match sub.as_str() {
+        "profile" | "profiles" => {
+            let current = crate::get_config_profile();
+            let mut out = vec![format!("Config profile: {}", current.unwrap_or("default"))];
+            let marker = |name: Option<&str>| if name == current { "*" } else { " " };
+            out.push(format!("{} default", marker(None)));
+            for name in crate::list_config_profiles() {
+                out.push(format!("{} {}", marker(Some(&name)), name));
+            }
+            out.push("Start Clay with --profile=<name> to use (or create) another profile.".to_string());
+            out
+        }
+        _ => vec![USAGE.to_string()],
+    }
+}
+
+/// Run `/loopguard [N|off]` and return the lines to show.
+pub(crate) fn loopguard_command(app: &mut App, args: &str) -> Vec<String> {
+    let limit = match args.trim().to_lowercase().as_str() {
+        "" => {
+            return vec![match app.settings.trigger_loop_limit {
+                0 => "Trigger loop watchdog is off.".to_string(),
+                n => format!("Trigger loop watchdog: actions firing more than {} times/sec are disabled.", n),
+            }];
+        }
+        "off" | "0" => 0,
+        "on" => crate::trigger_guard::DEFAULT_LIMIT,
+        value => match value.parse::<u32>() {
+            Ok(n) => n,
+            Err(_) => return vec!["Usage: /loopguard [<fires per second>|on|off]".to_string()],
+        },
+    };
+    app.settings.trigger_loop_limit = limit;
+    let _ = persistence::save_settings(app);
+    vec![match limit {
+        0 => "Trigger loop watchdog disabled.".to_string(),
+        n => format!("Trigger loop watchdog set to {} fires/sec.", n),
+    }]
+}
+
+/// Run `/throttle` and return the lines to show.
+///
+///   /throttle [-w<world>]          - show this world's priority and the batch interval
+///   /throttle [-w<world>] on|off   - mark a world low priority (batched in the background)
+///   /throttle interval <ms>        - set the batch interval for all low-priority worlds
+pub(crate) fn throttle_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /throttle [-w<world>] [on|off] | /throttle interval <ms>";
+    let mut rest = args.trim();
+    let mut world_idx = world_idx;
+
+    if let Some(ms) = rest.strip_prefix("interval") {
+        let ms = match ms.trim().parse::<u32>() {
+            Ok(ms) => ms,
+            Err(_) => return vec![USAGE.to_string()],
+        };
+        app.settings.background_batch_ms = ms;
+        let _ = persistence::save_settings(app);
+        return vec![match ms {
+            0 => "Background batching disabled.".to_string(),
+            ms => format!("Low-priority background worlds are processed every {} ms.", ms),
+        }];
+    }
+    if let Some(after) = rest.strip_prefix("-w") {
+        let (name, tail) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+        match app.find_world(name) {
+            Some(idx) => world_idx = idx,
+            None => return vec![format!("World '{}' not found.", name)],
+        }
+        rest = tail.trim();
+    }
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+
+    let enabled = match rest.to_lowercase().as_str() {
+        "" => {
+            let world = &app.worlds[world_idx];
+            let mut lines = vec![format!("{} is {} priority.", world_name,
+                if world.settings.low_priority { "low" } else { "normal" })];
+            if world.background_batch.is_holding() {
+                lines.push(format!("  {} bytes held for the next batch.", world.background_batch.held_bytes()));
+            }
+            let low: Vec<&str> = app.worlds.iter()
+                .filter(|w| w.settings.low_priority)
+                .map(|w| w.name.as_str())
+                .collect();
+            lines.push(match app.settings.background_batch_ms {
+                0 => "Background batching is disabled.".to_string(),
+                ms => format!("Low-priority worlds ({}) are batched every {} ms while in the background.",
+                    if low.is_empty() { "none".to_string() } else { low.join(", ") }, ms),
+            });
+            return lines;
+        }
+        "on" => true,
+        "off" => false,
+        _ => return vec![USAGE.to_string()],
+    };
+    app.worlds[world_idx].settings.low_priority = enabled;
+    let _ = persistence::save_settings(app);
+    vec![format!("{} is now {} priority.", world_name, if enabled { "low" } else { "normal" })]
+}
+
+/// Run `/notify [-s<sound>] <message>`, `/notify -w[<world>] [mute|unmute]`,
+/// `/notify -w[<world>] sound [<file>|off]` or bare `/notify` (status) for world
+/// `world_idx` and return the lines to show. Action commands run it too.
+pub(crate) fn notify_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /notify [-s<sound>] <message> | /notify -w[<world>] [mute|unmute|sound [<file>|off]]";
+    let rest = args.trim();
+
+    if let Some(after) = rest.strip_prefix("-w") {
+        let (name, tail) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+        let world_idx = if name.is_empty() {
+            world_idx
+        } else {
+            match app.find_world(name) {
+                Some(idx) => idx,
+                None => return vec![format!("World '{}' not found.", name)],
+            }
+        };
+        if world_idx >= app.worlds.len() {
+            return vec![USAGE.to_string()];
+        }
+        let world_name = app.worlds[world_idx].name.clone();
+        let tail = tail.trim();
+        let (verb, value) = tail.split_once(char::is_whitespace).unwrap_or((tail, ""));
+        let reply = match verb.to_lowercase().as_str() {
+            "" => {
+                let sound = &app.worlds[world_idx].settings.notify_sound;
+                return vec![format!("Notifications from {} are {}{}.", world_name,
+                    if app.worlds[world_idx].settings.notify_muted { "muted" } else { "on" },
+                    if sound.is_empty() { String::new() } else { format!(", with sound {}", sound) })];
+            }
+            "mute" | "unmute" if value.is_empty() => {
+                let muted = verb.eq_ignore_ascii_case("mute");
+                app.worlds[world_idx].settings.notify_muted = muted;
+                format!("Notifications from {} {}.", world_name, if muted { "muted" } else { "unmuted" })
+            }
+            "sound" => {
+                let value = value.trim();
+                if value.is_empty() {
+                    let sound = &app.worlds[world_idx].settings.notify_sound;
+                    return vec![if sound.is_empty() {
+                        format!("{} has no notification sound.", world_name)
+                    } else {
+                        format!("{} notification sound: {}", world_name, sound)
+                    }];
+                }
+                let sound = if value.eq_ignore_ascii_case("off") { String::new() } else { value.to_string() };
+                let reply = if sound.is_empty() {
+                    format!("{} has no notification sound now.", world_name)
+                } else {
+                    format!("{} notification sound set to {} (played where the window is unfocused).", world_name, sound)
+                };
+                app.worlds[world_idx].settings.notify_sound = sound;
+                reply
+            }
+            _ => return vec![USAGE.to_string()],
+        };
+        let _ = persistence::save_settings(app);
+        app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+            world_index: world_idx,
+            settings: app.world_settings_msg(world_idx),
+            name: world_name,
+        });
+        return vec![reply];
+    }
+
+    if rest.is_empty() {
+        let mut lines = vec![format!("Activity notifications (Notify Activity in /setup) are {}.",
+            if app.settings.notify_activity { "on" } else { "off" })];
+        if !crate::desktop_notify::AVAILABLE {
+            lines.push("  This build has no desktop notifications; /notify reaches web/mobile clients only.".to_string());
+        }
+        let muted: Vec<&str> = app.worlds.iter()
+            .filter(|w| w.settings.notify_muted)
+            .map(|w| w.name.as_str())
+            .collect();
+        if !muted.is_empty() {
+            lines.push(format!("  Muted worlds: {}", muted.join(", ")));
+        }
+        for world in app.worlds.iter().filter(|w| !w.settings.notify_sound.is_empty()) {
+            lines.push(format!("  {} sound: {}", world.name, world.settings.notify_sound));
+        }
+        lines.push(USAGE.to_string());
+        return lines;
+    }
+
+    // -s<sound> plays this sound instead of the world's (a trigger's own sound)
+    if let Some(after) = rest.strip_prefix("-s") {
+        let (sound, message) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+        let message = message.trim();
+        if sound.is_empty() || message.is_empty() {
+            return vec![USAGE.to_string()];
+        }
+        return vec![app.send_notification(world_idx, message, Some(sound))];
+    }
+    vec![app.send_notification(world_idx, rest, None)]
+}
+
+/// Run `/bell` and return the lines to show.
+///   /bell                                         - list the worlds' modes and counts
+///   /bell [-w[<world>]] [ignore|flash|ring|notify] - show or set a world's (default: this one)
+pub(crate) fn bell_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /bell [-w[<world>]] [ignore|flash|ring|notify]";
+    let rest = args.trim();
+
+    if rest.is_empty() {
+        let mut lines = vec!["Bell handling:".to_string()];
+        for world in &app.worlds {
+            let count = if world.bell_count > 0 { format!(" ({} unseen)", world.bell_count) } else { String::new() };
+            lines.push(format!("  {:<20} {}{}", world.name, world.settings.bell.name(), count));
+        }
+        lines.push(USAGE.to_string());
+        return lines;
+    }
+
+    let (world_idx, mode_arg) = match world_option(app, world_idx, rest) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    if mode_arg.is_empty() {
+        return vec![format!("Bell for {} is {}.", world_name, app.worlds[world_idx].settings.bell.name())];
+    }
+    let Some(mode) = crate::bell::BellMode::from_name(mode_arg) else {
+        return vec![USAGE.to_string()];
+    };
+    app.worlds[world_idx].settings.bell = mode;
+    let _ = persistence::save_settings(app);
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: world_name.clone(),
+    });
+    vec![format!("Bell for {} set to {}.", world_name, mode.name())]
+}
+
+/// Run `/trigger test [-w<world>] "<line>"` and return the lines to show: which actions
+/// the sample line matches for the world, their captures, and the commands the first
+/// one would run. Nothing is sent or executed.
+pub(crate) fn trigger_test_command(app: &App, world_idx: usize, args: &str) -> Vec<String> {
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    let sample = rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')).unwrap_or(rest);
+    if sample.is_empty() {
+        return vec!["Usage: /trigger test [-w<world>] \"<sample line>\"".to_string()];
+    }
+    let world_name = app.worlds.get(world_idx).map(|w| w.name.as_str()).unwrap_or("");
+    let matches = crate::actions::matching_actions(sample, world_name, &app.settings.actions);
+    if matches.is_empty() {
+        return vec![format!("No action matches that line in {}.", world_name)];
+    }
+    let mut out = Vec::new();
+    for (i, result) in matches.iter().enumerate() {
+        let captures: Vec<String> = result.captures.iter().enumerate().take(10)
+            .map(|(n, cap)| format!("${}=\"{}\"", n, cap))
+            .collect();
+        if i > 0 {
+            out.push(format!("Also matches '{}' (doesn't fire; the first match wins): {}", result.action_name, captures.join(" ")));
+            continue;
+        }
+        out.push(format!("'{}' fires: {}", result.action_name, captures.join(" ")));
+        if result.should_gag {
+            out.push("  gags the line".to_string());
+        }
+        if let Some(ref color) = result.highlight_color {
+            out.push(if color.is_empty() { "  highlights the line".to_string() } else { format!("  highlights the line {}", color) });
+        }
+        if let Some(ref name) = result.capture {
+            out.push(format!("  captures the line to {}", name));
+        }
+        for program in &result.pipes {
+            out.push(format!("  pipes the line to {}", program));
+        }
+        for command in &result.commands {
+            out.push(format!("  would run: {}", command));
+        }
+    }
+    out
+}
+
+/// Split a leading `-w<world>` off `args`: the world it names (`world_idx` when
+/// there is none, or just `-w`) and the rest
+fn world_option<'a>(app: &App, world_idx: usize, args: &'a str) -> Result<(usize, &'a str), String> {
+    let Some(after) = args.strip_prefix("-w") else {
+        return Ok((world_idx, args));
+    };
+    let (name, tail) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+    if name.is_empty() {
+        return Ok((world_idx, tail.trim()));
+    }
+    match app.find_world(name) {
+        Some(idx) => Ok((idx, tail.trim())),
+        None => Err(format!("World '{}' not found.", name)),
+    }
+}
+
+/// A file named to /export or /import: relative to the home directory unless absolute
+pub(crate) fn settings_file_path(file: &str) -> PathBuf {
+    let home = PathBuf::from(crate::get_home_dir());
+    home.join(file.strip_prefix("~/").unwrap_or(file))
+}
+
+/// Whether `/import <arg>` names a file (a path, or a `.toml`/`.tfrc`/`.tf`/`.xml` name) rather than another
+/// Clay instance to pull from. The web client's /import intercept uses the same test.
+pub(crate) fn is_settings_file_arg(arg: &str) -> bool {
+    arg.contains('/') || arg.contains('\\') || [".toml", ".tfrc", ".tf", ".xml"].iter().any(|ext| arg.ends_with(ext))
+}
+
+/// `<file> [passphrase]`; the passphrase is the rest of the line and may have spaces
+fn file_and_passphrase(args: &str) -> (&str, Option<&str>) {
+    match args.trim().split_once(char::is_whitespace) {
+        Some((file, passphrase)) => (file, Some(passphrase.trim()).filter(|p| !p.is_empty())),
+        None => (args.trim(), None),
+    }
+}
+
+/// Run `/export <file> [passphrase]` and return the lines to show.
+pub(crate) fn export_command(app: &App, args: &str) -> Vec<String> {
+    let (file, passphrase) = file_and_passphrase(args);
+    if file.is_empty() {
+        return vec!["Usage: /export <file> [passphrase]".to_string()];
+    }
+    let path = settings_file_path(file);
+    match persistence::export_settings_file(app, &path, passphrase) {
+        Ok(counts) => vec![format!(
+            "Exported {} worlds, {} actions and {} keybindings to {} ({}).",
+            counts.worlds, counts.actions, counts.keybindings, path.display(),
+            if passphrase.is_some() { "passwords encrypted with the passphrase" } else { "without passwords" },
+        )],
+        Err(e) => vec![format!("Could not write {}: {}", path.display(), e)],
+    }
+}
+
+/// Run `/actions export <file> [world]` and return the lines to show: the actions, or
+/// only those naming `world`, as a trigger pack (see actions::export_action_pack), TOML
+/// if the file ends in `.toml` and JSON otherwise.
+pub(crate) fn actions_export_command(app: &App, args: &str) -> Vec<String> {
+    let (file, world) = match args.trim().split_once(char::is_whitespace) {
+        Some((file, world)) => (file, world.trim()),
+        None => (args.trim(), ""),
+    };
+    if file.is_empty() {
+        return vec!["Usage: /actions export <file> [world]".to_string()];
+    }
+    let actions: Vec<crate::actions::Action> = app.settings.actions.iter()
+        .filter(|a| world.is_empty() || a.world.split(',').any(|w| w.trim().eq_ignore_ascii_case(world)))
+        .cloned()
+        .collect();
+    if actions.is_empty() {
+        return vec![format!("No actions for world '{}'.", world)];
+    }
+    let path = settings_file_path(file);
+    let as_toml = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+    let text = match crate::actions::export_action_pack(&actions, as_toml) {
+        Ok(text) => text,
+        Err(e) => return vec![format!("Could not write {}: {}", path.display(), e)],
+    };
+    match std::fs::write(&path, text) {
+        Ok(()) => vec![format!("Exported {} actions to {}.", actions.len(), path.display())],
+        Err(e) => vec![format!("Could not write {}: {}", path.display(), e)],
+    }
+}
+
+/// Run `/actions import <file>` and return the lines to show: merge a trigger pack's
+/// actions, replacing same-named ones.
+pub(crate) fn actions_import_command(app: &mut App, args: &str) -> Vec<String> {
+    let file = args.trim();
+    if file.is_empty() {
+        return vec!["Usage: /actions import <file>".to_string()];
+    }
+    let path = settings_file_path(file);
+    match std::fs::read_to_string(&path) {
+        Ok(text) => import_action_pack(app, &text, &path.display().to_string()).unwrap_or_else(|e| vec![e]),
+        Err(e) => vec![format!("Could not read {}: {}", path.display(), e)],
+    }
+}
+
+/// Merge a trigger pack's text (from a file, or uploaded by a web client) and tell every
+/// client about the new action list. Errs, changing nothing, if the text isn't a pack.
+pub(crate) fn import_action_pack(app: &mut App, text: &str, source: &str) -> Result<Vec<String>, String> {
+    let actions = crate::actions::parse_action_pack(text).map_err(|e| format!("{}: {}", source, e))?;
+    let count = actions.len();
+    merge_imported_actions(app, actions);
+    crate::compile_all_action_regexes(&mut app.settings.actions);
+    app.ws_broadcast(WsMessage::ActionsUpdated { actions: app.settings.actions.clone() });
+    let mut out = vec![format!("Imported {} actions from {}.", count, source)];
+    if let Err(e) = persistence::save_settings(app) {
+        out.push(format!("Saving settings failed: {}", e));
+    }
+    Ok(out)
+}
+
+/// Run `/lock [set <passphrase> | off]` and return the lines to show. See "Master
+/// passphrase" in persistence.rs.
+///   /lock                   - lock world credentials (saving them first)
+///   /lock set <passphrase>  - set or change the master passphrase
+///   /lock off               - remove the master passphrase
+pub(crate) fn lock_command(app: &mut App, args: &str) -> Vec<String> {
+    let (sub, rest) = match args.split_once(char::is_whitespace) {
+        Some((sub, rest)) => (sub, rest.trim()),
+        None => (args, ""),
+    };
+    let locked = persistence::credentials_locked();
+    let save = |app: &App, done: String| match persistence::save_settings(app) {
+        Ok(()) => vec![done],
+        Err(e) => vec![done, format!("Saving settings failed: {}", e)],
+    };
+    match sub {
+        "" if !persistence::master_passphrase_set() => vec!["No master passphrase is set (/lock set <passphrase>).".to_string()],
+        "" if locked => vec!["Credentials are already locked.".to_string()],
+        "" => {
+            if let Err(e) = persistence::save_settings(app) {
+                return vec![format!("Saving settings failed, not locking: {}", e)];
+            }
+            let count = persistence::lock_credentials(app);
+            vec![format!("Locked {} credentials; /unlock <passphrase> to use them again.", count)]
+        }
+        "set" | "off" if locked => vec!["Credentials are locked; /unlock them first.".to_string()],
+        "set" if rest.is_empty() => vec!["Usage: /lock set <passphrase>".to_string()],
+        "set" => match persistence::set_master_passphrase(Some(rest)) {
+            Ok(()) => save(app, "Master passphrase set; world credentials are encrypted with it.".to_string()),
+            Err(e) => vec![format!("Could not write master.dat: {}", e)],
+        },
+        "off" => match persistence::set_master_passphrase(None) {
+            Ok(()) => save(app, "Master passphrase removed.".to_string()),
+            Err(e) => vec![format!("Could not remove master.dat: {}", e)],
+        },
+        _ => vec!["Usage: /lock [set <passphrase> | off]".to_string()],
+    }
+}
+
+/// Run `/unlock <passphrase>` and return the lines to show.
+pub(crate) fn unlock_command(app: &mut App, args: &str) -> Vec<String> {
+    if args.is_empty() {
+        return vec!["Usage: /unlock <passphrase>".to_string()];
+    }
+    if !persistence::credentials_locked() {
+        return vec!["Credentials aren't locked.".to_string()];
+    }
+    match persistence::unlock_credentials(app, args) {
+        Ok(count) => vec![format!("Unlocked {} credentials.", count)],
+        Err(e) => vec![format!("Unlock failed: {}.", e)],
+    }
+}
+
+/// Run `/import <file> [passphrase]` and return the lines to show: the file is a
+/// `/export`, a TinyFugue config (see tf_import.rs) or a Mudlet/MUSHclient XML file
+/// (see xml_import.rs).
+pub(crate) fn import_file_command(app: &mut App, args: &str) -> Vec<String> {
+    let (file, passphrase) = file_and_passphrase(args);
+    let path = settings_file_path(file);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => return vec![format!("Could not read {}: {}", path.display(), e)],
+    };
+    let mut out = if crate::xml_import::is_client_xml(&text) {
+        match crate::xml_import::parse(&text) {
+            Ok(import) => {
+                let count = import.actions.len();
+                merge_imported_actions(app, import.actions);
+                let mut out = vec![format!("Imported {} actions from {}.", count, path.display())];
+                out.extend(import_notes(&import.notes, "items"));
+                out
+            }
+            Err(e) => return vec![format!("{}: {}", path.display(), e)],
+        }
+    } else if crate::tf_import::is_tf_config(&text) {
+        import_tf_config(app, &text, &path)
+    } else {
+        match persistence::import_settings_file(app, &text, passphrase) {
+            Ok(counts) => vec![format!(
+                "Imported {} worlds, {} actions and {} keybindings from {}.",
+                counts.worlds, counts.actions, counts.keybindings, path.display(),
+            )],
+            Err(e) => return vec![format!("{}: {}", path.display(), e)],
+        }
+    };
+    crate::compile_all_action_regexes(&mut app.settings.actions);
+    app.ws_broadcast(WsMessage::ActionsUpdated { actions: app.settings.actions.clone() });
+    if let Err(e) = persistence::save_settings(app) {
+        out.push(format!("Saving settings failed: {}", e));
+    }
+    if let Err(e) = app.keybindings.save(&crate::clay_config_path("keybindings.dat")) {
+        out.push(format!("Saving keybindings failed: {}", e));
+    }
+    out
+}
+
+/// Merge a TinyFugue config's worlds and actions; same-named ones are replaced
+fn import_tf_config(app: &mut App, text: &str, path: &std::path::Path) -> Vec<String> {
+    let import = crate::tf_import::parse(text);
+    for world in &import.worlds {
+        let idx = app.find_or_create_world(&world.name);
+        let settings = &mut app.worlds[idx].settings;
+        if let Some(ref host) = world.host {
+            settings.hostname = host.clone();
+        }
+        if let Some(ref port) = world.port {
+            settings.port = port.clone();
+        }
+        if let Some(ref user) = world.user {
+            settings.user = user.clone();
+        }
+        if let Some(ref password) = world.password {
+            settings.password = password.clone();
+        }
+        settings.use_ssl = world.use_ssl;
+    }
+    let action_count = import.actions.len();
+    merge_imported_actions(app, import.actions);
+
+    let mut out = vec![format!("Imported {} worlds and {} actions from {}.", import.worlds.len(), action_count, path.display())];
+    out.extend(import_notes(&import.notes, "lines"));
+    if !import.notes.is_empty() {
+        out.push("(/load runs a TF file as TF.)".to_string());
+    }
+    out
+}
+
+/// Add imported actions; same-named ones are replaced
+fn merge_imported_actions(app: &mut App, actions: Vec<crate::actions::Action>) {
+    for action in actions {
+        match app.settings.actions.iter_mut().find(|a| a.name == action.name) {
+            Some(existing) => *existing = action,
+            None => app.settings.actions.push(action),
+        }
+    }
+}
+
+/// An importer's notes on what it couldn't translate, the first few of them
+fn import_notes(notes: &[String], what: &str) -> Vec<String> {
+    const MAX_NOTES: usize = 20;
+    if notes.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![format!("{} {} weren't translated, or only in part:", notes.len(), what)];
+    out.extend(notes.iter().take(MAX_NOTES).map(|note| format!("  {}", note)));
+    if notes.len() > MAX_NOTES {
+        out.push(format!("  ... and {} more", notes.len() - MAX_NOTES));
+    }
+    out
+}
+
+/// A file named to /log or /replay: relative to the logs directory unless absolute or `~/`
+fn log_file_path(file: &str) -> PathBuf {
+    match file.strip_prefix("~/") {
+        Some(home_rel) => PathBuf::from(crate::get_home_dir()).join(home_rel),
+        None => World::get_logs_dir().join(file),
+    }
+}
+
+/// Run `/log` and return the lines to show. Start and stop hold until the world
+/// disconnects (see logging.rs).
+///   /log [-w[<world>]] [status]     - where the world is logging to, if anywhere
+///   /log [-w[<world>]] start [file] - log to the dated file, or to `file` (relative
+///                                     to the logs directory, `~/` for home)
+///   /log [-w[<world>]] stop         - stop logging
+///   /log [-w[<world>]] flush        - write out buffered lines now
+/// TF's `/log <file>` and `/log off` work too.
+pub(crate) fn log_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /log [-w[<world>]] [status|start [<file>]|stop|flush]";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let (sub, file) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let sub = sub.to_lowercase();
+    let (sub, file) = match sub.as_str() {
+        "" | "status" | "start" | "stop" | "off" | "flush" => (sub.as_str(), file.trim()),
+        _ => ("start", rest),
+    };
+    let world = &mut app.worlds[world_idx];
+    match sub {
+        "" | "status" => {
+            if world.log_handle.is_some() {
+                vec![format!(
+                    "Logging {} to {} ({}, timestamps {}).",
+                    world.name,
+                    world.get_log_path().display(),
+                    world.settings.log_format.name(),
+                    world.settings.log_timestamps.name()
+                )]
+            } else if world.logging_on() {
+                vec![format!("Logging {} from the next line, to {}.", world.name, world.get_log_path().display())]
+            } else {
+                vec![format!("Not logging {}.", world.name)]
+            }
+        }
+        "start" => {
+            let path = if file.is_empty() { None } else { Some(log_file_path(file)) };
+            world.close_log_file();
+            world.log_override = crate::logging::LogOverride::Started(path);
+            if world.open_log_file() {
+                vec![format!("Logging {} to {}.", world.name, world.get_log_path().display())]
+            } else {
+                let failed = world.get_log_path();
+                world.log_override = crate::logging::LogOverride::Setting;
+                vec![format!("Could not open log file {}.", failed.display())]
+            }
+        }
+        "stop" | "off" => {
+            let was_logging = world.log_handle.is_some();
+            world.close_log_file();
+            world.log_override = crate::logging::LogOverride::Stopped;
+            if was_logging {
+                vec![format!("Stopped logging {}.", world.name)]
+            } else {
+                vec![format!("Not logging {}.", world.name)]
+            }
+        }
+        // flush
+        _ => match world.log_handle.as_mut() {
+            Some(log) => {
+                log.flush();
+                vec![format!("Flushed the log of {}.", world.name)]
+            }
+            None => vec![format!("Not logging {}.", world.name)],
+        },
+    }
+}
+
+/// Run `/replay` and return the lines to show. Callers pass the channel the replay task
+/// sends its lines on.
+///   /replay [-w[<world>]] <file> [speed] - show a log in the world (see replay.rs)
+///   /replay [-w[<world>]] stop           - stop the world's replay
+pub(crate) fn replay_command(app: &mut App, world_idx: usize, args: &str, event_tx: Option<&mpsc::Sender<AppEvent>>) -> Vec<String> {
+    const USAGE: &str = "Usage: /replay [-w[<world>]] <file> [speed] | stop";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() || rest.is_empty() {
+        return vec![USAGE.to_string()];
+    }
+    if let Some(task) = app.worlds[world_idx].replay_task.take() {
+        task.abort();
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    if rest.eq_ignore_ascii_case("stop") {
+        return vec![format!("Stopped replaying into {}.", world_name)];
+    }
+
+    let (file, speed) = match rest.rsplit_once(char::is_whitespace) {
+        Some((file, speed)) => match speed.trim_end_matches(['x', 'X']).parse::<f64>() {
+            Ok(s) if s > 0.0 && s.is_finite() => (file.trim(), Some(s)),
+            Ok(_) => return vec!["Speed must be a positive number.".to_string()],
+            Err(_) => (rest, None),
+        },
+        None => (rest, None),
+    };
+    let path = log_file_path(file);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => return vec![format!("Could not read {}: {}", path.display(), e)],
+    };
+    if text.trim_start().starts_with("<!DOCTYPE html") {
+        return vec!["HTML logs can't be replayed; use a raw or plain log.".to_string()];
+    }
+    let Some(event_tx) = event_tx else {
+        return vec!["Replay isn't available here.".to_string()];
+    };
+    let chunks = crate::replay::plan(&text, speed);
+    let lines = text.lines().count();
+    app.worlds[world_idx].replay_task = Some(crate::replay::spawn(world_name.clone(), chunks, event_tx.clone()));
+    match speed {
+        Some(speed) => vec![format!("Replaying {} ({} lines) into {} at {}x.", path.display(), lines, world_name, speed)],
+        None => vec![format!("Replaying {} ({} lines) into {}.", path.display(), lines, world_name)],
+    }
+}
+
+/// Run `/localecho [-w[<world>]] [on|off]` for `world_idx` and return the lines to show.
+pub(crate) fn localecho_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /localecho [-w[<world>]] [on|off]";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    let enabled = match rest.to_lowercase().as_str() {
+        "" => {
+            let state = if app.worlds[world_idx].settings.local_echo { "on" } else { "off" };
+            return vec![format!("Local echo for {} is {}.", world_name, state)];
+        }
+        "on" => true,
+        "off" => false,
+        _ => return vec![USAGE.to_string()],
+    };
+    app.worlds[world_idx].settings.local_echo = enabled;
+    let _ = persistence::save_settings(app);
+    vec![format!("Local echo {} for {}.", if enabled { "enabled" } else { "disabled" }, world_name)]
+}
+
+/// Run `/emoji [-w[<world>]] [on|off]` for `world_idx` and return the lines to show.
+/// On change it also tells clients, which only Tab-complete `:name:` while it is on.
+pub(crate) fn emoji_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /emoji [-w[<world>]] [on|off]";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    let enabled = match rest.to_lowercase().as_str() {
+        "" => {
+            let state = if app.worlds[world_idx].settings.emoji_shortcodes { "on" } else { "off" };
+            return vec![format!("Emoji shortcodes for {} are {}.", world_name, state)];
+        }
+        "on" => true,
+        "off" => false,
+        _ => return vec![USAGE.to_string()],
+    };
+    app.worlds[world_idx].settings.emoji_shortcodes = enabled;
+    let _ = persistence::save_settings(app);
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: world_name.clone(),
+    });
+    vec![format!("Emoji shortcodes {} for {}.", if enabled { "enabled" } else { "disabled" }, world_name)]
+}
+
+/// Run `/autocorrect [-w[<world>]] [on|off]` for `world_idx` and return the lines to
+/// show. On change it also tells clients, since consoles correct their own input
+/// (`App::check_autocorrect`).
+pub(crate) fn autocorrect_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /autocorrect [-w[<world>]] [on|off]";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    let enabled = match rest.to_lowercase().as_str() {
+        "" => {
+            let state = if app.worlds[world_idx].settings.autocorrect { "on" } else { "off" };
+            return vec![format!("Autocorrect for {} is {}.", world_name, state)];
+        }
+        "on" => true,
+        "off" => false,
+        _ => return vec![USAGE.to_string()],
+    };
+    app.worlds[world_idx].settings.autocorrect = enabled;
+    let _ = persistence::save_settings(app);
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: world_name.clone(),
+    });
+    if enabled {
+        vec![format!("Autocorrect enabled for {} (Esc-r puts back the word you typed).", world_name)]
+    } else {
+        vec![format!("Autocorrect disabled for {}.", world_name)]
+    }
+}
+
+/// Run `/addword <word>` and return the lines to show. The spell_add_word key runs it
+/// too; a remote console adds to its own dictionary, since it spell-checks its input
+/// itself.
+pub(crate) fn addword_command(app: &mut App, word: &str) -> Vec<String> {
+    let word = word.trim();
+    if word.is_empty() {
+        return vec!["Usage: /addword <word>".to_string()];
+    }
+    let line = match app.spell_checker.add_word(word) {
+        Ok(true) => format!("Added '{}' to your dictionary.", word),
+        Ok(false) => format!("'{}' is already in your dictionary.", word),
+        Err(e) => e,
+    };
+    for checker in app.spell_languages.values_mut().flatten() {
+        checker.remember_word(word);
+    }
+    app.cached_misspelled.clear();
+    vec![line]
+}
+
+/// Run `/spellignore [<word>]` and return the lines to show: without a word, the
+/// ignored ones. Shared like `addword_command`; the list is kept apart from the
+/// dictionary so suggestions never offer its words.
+pub(crate) fn spellignore_command(app: &mut App, word: &str) -> Vec<String> {
+    let word = word.trim();
+    if word.is_empty() {
+        let (saved, session) = app.spell_ignore.words();
+        if saved.is_empty() && session.is_empty() {
+            return vec!["No words are ignored. Usage: /spellignore <word>".to_string()];
+        }
+        let mut lines = vec![format!("Ignored words: {}", if saved.is_empty() { "none".to_string() } else { saved.join(", ") })];
+        if !session.is_empty() {
+            lines.push(format!("Ignored until Clay exits: {}", session.join(", ")));
+        }
+        return lines;
+    }
+    let line = match app.spell_ignore.ignore(word, true) {
+        Ok(true) => format!("Ignoring '{}' in spell check (/unignore {} undoes it).", word, word),
+        Ok(false) => format!("'{}' is already ignored.", word),
+        Err(e) => e,
+    };
+    app.cached_misspelled.clear();
+    vec![line]
+}
+
+/// Run `/unignore <word>` and return the lines to show. Shared like `addword_command`.
+pub(crate) fn unignore_command(app: &mut App, word: &str) -> Vec<String> {
+    let word = word.trim();
+    if word.is_empty() {
+        return vec!["Usage: /unignore <word>".to_string()];
+    }
+    let line = match app.spell_ignore.unignore(word) {
+        Ok(true) => format!("'{}' is spell checked again.", word),
+        Ok(false) => format!("'{}' isn't ignored.", word),
+        Err(e) => e,
+    };
+    app.cached_misspelled.clear();
+    vec![line]
+}
+
+/// Run `/web viewer [<password>|off]`: report, set or clear the password for view-only
+/// web sign-in. Changing it signs out the viewers already connected.
+pub(crate) fn web_viewer_command(app: &mut App, args: &str) -> String {
+    let args = args.trim();
+    if app.multiuser_mode {
+        return "Viewer sign-in isn't available in multiuser mode.".to_string();
+    }
+    if args.is_empty() {
+        return if app.settings.websocket_viewer_password.is_empty() {
+            "Viewer sign-in is off. Use /web viewer <password> to turn it on.".to_string()
+        } else {
+            "Viewer sign-in is on. Use /web viewer <password> to change it or /web viewer off.".to_string()
+        };
+    }
+    let password = if args.eq_ignore_ascii_case("off") { "" } else { args };
+    if !password.is_empty() && password == app.settings.websocket_password {
+        return "The viewer password must differ from the web password.".to_string();
+    }
+    app.settings.websocket_viewer_password = password.to_string();
+    let signed_out = app.ws_server.as_ref().map_or(Some(0), |server| {
+        server.update_viewer_password(password);
+        server.sign_out_viewers()
+    });
+    let mut msg = if password.is_empty() {
+        "Viewer sign-in turned off.".to_string()
+    } else {
+        "Viewer password set: signing in with it shows output but refuses commands and changes.".to_string()
+    };
+    match signed_out {
+        Some(0) => {}
+        Some(n) => msg.push_str(&format!(" Signed out {} viewer(s).", n)),
+        None => msg.push_str(" Signing out connected viewers."),
+    }
+    if let Err(e) = persistence::save_settings(app) {
+        msg.push_str(&format!(" Saving settings failed: {}", e));
+    }
+    msg
+}
+
+/// Run `/web acme [<domain> [email]|renew|off]`: report, set, renew or turn off the
+/// Let's Encrypt certificate for the web interface (see acme.rs). Setting a domain
+/// replaces any custom cert/key files.
+pub(crate) fn web_acme_command(app: &mut App, args: &str) -> String {
+    let mut words = args.split_whitespace();
+    let msg = match words.next() {
+        None if app.settings.acme_domain.is_empty() => {
+            return "Let's Encrypt is off. Use /web acme <domain> [email] to get a certificate.".to_string();
+        }
+        None => {
+            let issued = std::fs::metadata(crate::acme::cert_path())
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.elapsed().ok())
+                .map(|age| format!("issued {} days ago", age.as_secs() / 86_400));
+            let state = if app.acme_in_progress {
+                "being requested".to_string()
+            } else {
+                issued.unwrap_or_else(|| "not issued yet".to_string())
+            };
+            return format!("Let's Encrypt certificate for {}: {}. Use /web acme renew or /web acme off.",
+                app.settings.acme_domain, state);
+        }
+        Some(word) if word.eq_ignore_ascii_case("off") => {
+            if app.settings.acme_domain.is_empty() {
+                return "Let's Encrypt is already off.".to_string();
+            }
+            let _ = app.set_acme_domain("", "");
+            app.web_restart_needed = app.settings.http_enabled;
+            "Let's Encrypt turned off; the web server goes back to its self-signed certificate.".to_string()
+        }
+        Some(word) if word.eq_ignore_ascii_case("renew") => {
+            if app.settings.acme_domain.is_empty() {
+                return "Let's Encrypt is off. Use /web acme <domain> [email] first.".to_string();
+            }
+            if app.acme_in_progress {
+                return "A Let's Encrypt request is already running.".to_string();
+            }
+            app.start_acme_if_due(true);
+            return format!("Requesting a Let's Encrypt certificate for {}...", app.settings.acme_domain);
+        }
+        Some(domain) => {
+            if let Err(e) = app.set_acme_domain(domain, words.next().unwrap_or("")) {
+                return e;
+            }
+            format!("Let's Encrypt is set up for {}: port {} must reach this machine while a certificate is validated.",
+                app.settings.acme_domain, crate::acme::CHALLENGE_PORT)
+        }
+    };
+    if let Err(e) = persistence::save_settings(app) {
+        return format!("{} Saving settings failed: {}", msg, e);
+    }
+    msg
+}
+
+/// What `/web push on|off` says anywhere but the web interface
+pub(crate) const WEB_PUSH_BROWSER_ONLY: &str =
+    "/web push on|off is for the browser that should (or shouldn't) get them: use it in the web interface.";
+
+/// Run `/web push [clear]`: how many browsers get Web Push activity notifications, or
+/// stop them all. A browser turns its own on or off with `/web push on|off`, which the
+/// WebSocket handlers bounce back to the web client (see web_push.rs).
+pub(crate) fn web_push_command(app: &mut App, args: &str) -> String {
+    match args.trim().to_ascii_lowercase().as_str() {
+        "" => match app.settings.push_subscriptions.len() {
+            0 => "No browsers get push notifications. Use /web push on in the web interface to add one.".to_string(),
+            n => format!("{} browser{} get{} push notifications. Use /web push clear to stop them all.",
+                n, if n == 1 { "" } else { "s" }, if n == 1 { "s" } else { "" }),
+        },
+        "clear" => match app.remove_push_subscriptions(&[]) {
+            0 => "No browsers get push notifications.".to_string(),
+            n => format!("Push notifications turned off for {} browser{}.", n, if n == 1 { "" } else { "s" }),
+        },
+        "on" | "off" => WEB_PUSH_BROWSER_ONLY.to_string(),
+        _ => "Usage: /web push [clear]".to_string(),
+    }
+}
+
+/// Whether `/web push` args are for the web client itself (its own subscription)
+pub(crate) fn web_push_is_local(args: &str) -> bool {
+    let args = args.trim();
+    args.eq_ignore_ascii_case("on") || args.eq_ignore_ascii_case("off")
+}
+
+/// Run `/web bans`: the bans with how long each has left, the IPs collecting strikes,
+/// and the limits.
+pub(crate) fn web_bans_lines(ban_list: &crate::BanList) -> Vec<String> {
+    use crate::http::{AUTH_BAN_DURATION, AUTH_FAILURE_THRESHOLD, CONNECT_RATE_WINDOW, MAX_WS_CONNECTS_PER_WINDOW};
+
+    let mut lines = vec![format!(
+        "{} failed sign-ins ban an IP for {}m; over {} new WebSocket connections in {}s are dropped.",
+        AUTH_FAILURE_THRESHOLD, AUTH_BAN_DURATION.as_secs() / 60, MAX_WS_CONNECTS_PER_WINDOW, CONNECT_RATE_WINDOW.as_secs(),
+    )];
+    let bans = ban_list.get_ban_info();
+    if bans.is_empty() {
+        lines.push("No hosts are banned.".to_string());
+    } else {
+        lines.push(format!("{:<20} {:<14} {}", "Banned host", "Ends", "Reason"));
+        for (ip, ends, reason) in bans {
+            lines.push(format!("{:<20} {:<14} {}", ip, ends, if reason.is_empty() { "(unknown)" } else { &reason }));
+        }
+    }
+    let strikes = ban_list.get_strikes();
+    if !strikes.is_empty() {
+        lines.push(format!("{:<20} {}", "Host with strikes", "Strikes"));
+        for (ip, count) in strikes {
+            lines.push(format!("{:<20} {}", ip, count));
+        }
+    }
+    lines.push("Use /unban <host> to lift a ban.".to_string());
+    lines
+}
+
+/// What `/web tokens [add <name>|revoke <name>]` asks for. The console carries it
+/// out itself; a remote console or web client sends it as a WebSocket request,
+/// since a new token's key must only reach whoever asked for it.
+#[derive(Debug, PartialEq)]
+pub(crate) enum DeviceTokenRequest {
+    List,
+    Create(String),
+    Revoke(String),
+}
+
+impl DeviceTokenRequest {
+    pub(crate) fn parse(args: &str) -> Result<Self, String> {
+        let args = args.trim();
+        let (sub, name) = args.split_once(char::is_whitespace).map_or((args, ""), |(s, n)| (s, n.trim()));
+        match sub.to_lowercase().as_str() {
+            "" | "list" if name.is_empty() => Ok(DeviceTokenRequest::List),
+            "add" if !name.is_empty() => Ok(DeviceTokenRequest::Create(name.to_string())),
+            "revoke" if !name.is_empty() => Ok(DeviceTokenRequest::Revoke(name.to_string())),
+            _ => Err("Usage: /web tokens [add <name>|revoke <name>]".to_string()),
+        }
+    }
+
+    pub(crate) fn into_ws(self) -> WsMessage {
+        match self {
+            DeviceTokenRequest::List => WsMessage::RequestDeviceTokens,
+            DeviceTokenRequest::Create(name) => WsMessage::CreateDeviceToken { name },
+            DeviceTokenRequest::Revoke(name) => WsMessage::RevokeDeviceToken { name },
+        }
+    }
+}
+
+/// Run `/spelllang [-w[<world>]] [<language>|off]` for `world_idx` and return the lines
+/// to show. On change it also tells clients, which load the Hunspell dictionary
+/// themselves when they spell-check.
+pub(crate) fn spelllang_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /spelllang [-w[<world>]] [<language>|off]";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    let languages = crate::hunspell::available_languages(&app.settings.dictionary_path);
+    let languages = if languages.is_empty() {
+        "none installed (put <language>.dic and .aff in ~/.config/clay/dict/)".to_string()
+    } else {
+        languages.join(", ")
+    };
+    let language = match rest {
+        "" => {
+            let current = &app.worlds[world_idx].settings.spell_language;
+            let current = if current.is_empty() { "the global dictionary" } else { current.as_str() };
+            return vec![
+                format!("Spell checking for {} uses {}.", world_name, current),
+                format!("Languages: {}", languages),
+            ];
+        }
+        off if off.eq_ignore_ascii_case("off") => String::new(),
+        name => match crate::spell::SpellChecker::for_language(name, &app.settings.dictionary_path) {
+            Some(checker) => {
+                app.spell_languages.insert(name.to_string(), Some(checker));
+                name.to_string()
+            }
+            None => return vec![format!("No Hunspell dictionary for {}. Languages: {}", name, languages)],
+        },
+    };
+    app.worlds[world_idx].settings.spell_language = language.clone();
+    let _ = persistence::save_settings(app);
+    app.cached_misspelled.clear();
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: world_name.clone(),
+    });
+    if language.is_empty() {
+        vec![format!("Spell checking for {} uses the global dictionary.", world_name)]
+    } else {
+        vec![format!("Spell checking for {} uses {}.", world_name, language)]
+    }
+}
+
+/// Run `/palette [-w[<world>]] [<name>|off]` for `world_idx` and return the lines to
+/// show. On change it also tells clients, which remap the world's ANSI colors themselves
+/// (theme.rs).
+pub(crate) fn palette_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /palette [-w[<world>]] [<name>|off]";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    let names = app.theme_file.palette_names().join(", ");
+    let palette = match rest.to_lowercase().as_str() {
+        "" => {
+            let current = &app.worlds[world_idx].settings.palette;
+            let current = if current.is_empty() { "off" } else { current.as_str() };
+            return vec![
+                format!("Palette for {} is {}.", world_name, current),
+                format!("Palettes: {} (add more as [palette:name] in theme.dat)", names),
+            ];
+        }
+        "off" => String::new(),
+        name if app.theme_file.palette(name).is_some() => name.to_string(),
+        name => return vec![format!("No palette named {}. Palettes: {}", name, names)],
+    };
+    app.worlds[world_idx].settings.palette = palette.clone();
+    let _ = persistence::save_settings(app);
+    app.needs_output_redraw = true;
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: world_name.clone(),
+    });
+    if palette.is_empty() {
+        vec![format!("Palette off for {}.", world_name)]
+    } else {
+        vec![format!("Palette {} for {}.", palette, world_name)]
+    }
+}
+
+/// Run `/tint [-w[<world>]] [#rrggbb|off]` for `world_idx` and return the lines to show.
+/// On change it also tells clients, which paint the color behind the world's output where
+/// no ANSI background is set.
+pub(crate) fn tint_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /tint [-w[<world>]] [#rrggbb|off]";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    let tint = match rest.to_lowercase().as_str() {
+        "" => {
+            let current = &app.worlds[world_idx].settings.tint;
+            let current = if current.is_empty() { "off" } else { current.as_str() };
+            return vec![format!("Tint for {} is {}.", world_name, current)];
+        }
+        "off" => String::new(),
+        color => match crate::theme::ThemeColor::from_hex(color) {
+            Some(c) => c.to_css(),
+            None => return vec![USAGE.to_string()],
+        },
+    };
+    app.worlds[world_idx].settings.tint = tint.clone();
+    let _ = persistence::save_settings(app);
+    app.needs_output_redraw = true;
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: world_name.clone(),
+    });
+    if tint.is_empty() {
+        vec![format!("Tint off for {}.", world_name)]
+    } else {
+        vec![format!("Tint {} for {}.", tint, world_name)]
+    }
+}
+
+/// Run `/images [-w[<world>]] [on|off]` for `world_idx` and return the lines to show.
+/// On change it also tells clients. Web/GUI clients then show image URLs in the world's
+/// output as size-capped pictures; the console has nowhere to draw them and keeps showing
+/// the links.
+pub(crate) fn images_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /images [-w[<world>]] [on|off]";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    let enabled = match rest.to_lowercase().as_str() {
+        "" => {
+            return vec![format!("Inline images for {} are {}.", world_name,
+                if app.worlds[world_idx].settings.inline_images { "on" } else { "off" })];
+        }
+        "on" => true,
+        "off" => false,
+        _ => return vec![USAGE.to_string()],
+    };
+    app.worlds[world_idx].settings.inline_images = enabled;
+    let _ = persistence::save_settings(app);
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: world_name.clone(),
+    });
+    if enabled {
+        vec![format!("Inline images on for {}: web and GUI clients fetch and show image links.", world_name)]
+    } else {
+        vec![format!("Inline images off for {}.", world_name)]
+    }
+}
+
+/// Run `/sendfile [-w[<world>]] <file> | abort` and return the lines to show. The lines
+/// go out from `App::send_file_line`.
+pub(crate) fn sendfile_command(app: &mut App, world_idx: usize, args: &str, event_tx: Option<&mpsc::Sender<AppEvent>>) -> Vec<String> {
+    const USAGE: &str = "Usage: /sendfile [-w[<world>]] <file> | abort";
+    let (world_idx, rest) = match world_option(app, world_idx, args.trim()) {
+        Ok(found) => found,
+        Err(e) => return vec![e],
+    };
+    if world_idx >= app.worlds.len() || rest.is_empty() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    if rest.eq_ignore_ascii_case("abort") {
+        let Some(job) = app.worlds[world_idx].send_file.take() else {
+            return vec![format!("Nothing is being sent to {}.", world_name)];
+        };
+        job.abort();
+        app.broadcast_send_progress(world_idx);
+        return vec![format!("Stopped sending {} to {} ({} of {} lines sent).", job.file, world_name, job.sent, job.total)];
+    }
+    if let Some(job) = &app.worlds[world_idx].send_file {
+        return vec![format!("Already sending {} to {}; /sendfile abort stops it.", job.file, world_name)];
+    }
+    if !app.worlds[world_idx].connected {
+        return vec![format!("{} is not connected.", world_name)];
+    }
+
+    let path = settings_file_path(rest);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => return vec![format!("Could not read {}: {}", path.display(), e)],
+    };
+    let lines: Vec<String> = text.lines().map(str::to_string).collect();
+    if lines.is_empty() {
+        return vec![format!("{} is empty.", path.display())];
+    }
+    let Some(event_tx) = event_tx else {
+        return vec!["Sending files isn't available here.".to_string()];
+    };
+    let total = lines.len();
+    app.worlds[world_idx].send_file = Some(crate::sendfile::SendFile {
+        task: Some(crate::sendfile::spawn(world_name.clone(), lines, event_tx.clone())),
+        file: path.display().to_string(),
+        sent: 0,
+        total,
+    });
+    app.broadcast_send_progress(world_idx);
+    vec![format!("Sending {} ({} lines) to {}.", path.display(), total, world_name)]
+}
+
+/// Start `/shell <command>` or `/pipe <command>` for `world_idx` (see shell.rs) and
+/// return the lines to show; the program's own output arrives later.
+pub(crate) fn shell_command(app: &mut App, world_idx: usize, command: &str, pipe: bool, event_tx: Option<&mpsc::Sender<AppEvent>>) -> Vec<String> {
+    if command.is_empty() {
+        return vec![format!("Usage: /{} <command>", if pipe { "pipe" } else { "shell" })];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+    if pipe && !app.worlds[world_idx].connected {
+        return vec![format!("{} is not connected.", world_name)];
+    }
+    let Some(event_tx) = event_tx else {
+        return vec!["Running programs isn't available here.".to_string()];
+    };
+    let output = if pipe { crate::shell::Output::Send } else { crate::shell::Output::Show };
+    crate::shell::spawn(world_name, command.to_string(), None, output, event_tx.clone());
+    Vec::new()
+}
+
+/// Run `/capture [list|clear <name>]` and return the lines to show. The console also
+/// takes the layout subcommands (see `capture_console_command`).
+pub(crate) fn capture_command(app: &mut App, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /capture [list] | /capture clear <name> | /capture pane <name>|off | /capture height <rows> | /capture view [<name>]";
+    let words: Vec<&str> = args.split_whitespace().collect();
+    match words.as_slice() {
+        [] | ["list"] => {
+            if app.panes.captures.is_empty() {
+                return vec!["No capture buffers yet. Add /capture <name> to an action's commands to start one.".to_string()];
+            }
+            let mut lines = vec!["Capture buffers:".to_string()];
+            for name in &app.panes.captures {
+                if let Some(world) = app.find_world(name).map(|idx| &app.worlds[idx]) {
+                    let shown = if app.panes.pane.as_deref().is_some_and(|p| p.eq_ignore_ascii_case(name)) { ", in pane" } else { "" };
+                    lines.push(format!("  {} ({} lines, {} unseen{})", world.name, world.output_lines.len(), world.unseen_lines, shown));
+                }
+            }
+            lines
+        }
+        ["clear", name] => {
+            let Some(idx) = app.find_world(name) else {
+                return vec![format!("Capture '{}' not found.", name)];
+            };
+            let world = &mut app.worlds[idx];
+            let line_count = world.output_lines.len();
+            world.output_lines.clear();
+            world.first_marked_new_index = None;
+            world.pending_lines.clear();
+            world.scroll_offset = 0;
+            world.unseen_lines = 0;
+            let name = world.name.clone();
+            app.needs_output_redraw = true;
+            app.ws_broadcast(WsMessage::WorldFlushed { world_index: idx });
+            vec![format!("Cleared {} lines from {}.", line_count, name)]
+        }
+        [name] if !crate::capture::SUBCOMMANDS.contains(name) => {
+            vec![format!("/capture {} is used in an action's commands: matching lines are copied into the {} buffer.", name, name)]
+        }
+        _ => vec![USAGE.to_string()],
+    }
+}
+
+/// Console-only `/capture pane|height|view`: lay a capture out below the output, or
+/// switch the output to it and back. `switch` changes the viewed world (the master
+/// console switches worlds, the remote console views the mirrored world).
+pub(crate) fn capture_console_command(app: &mut App, args: &str, switch: fn(&mut App, usize)) -> Vec<String> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    match words.as_slice() {
+        ["pane"] => vec![match &app.panes.pane {
+            Some(name) => format!("Capture pane shows {} ({} rows).", name, app.panes.height),
+            None => "Capture pane is off.".to_string(),
+        }],
+        ["pane", "off"] => {
+            app.panes.pane = None;
+            app.needs_output_redraw = true;
+            vec!["Capture pane off.".to_string()]
+        }
+        ["pane", name] => {
+            app.panes.pane = Some(name.to_string());
+            app.needs_output_redraw = true;
+            let mut lines = vec![format!("Capture pane shows {} below the output.", name)];
+            if app.find_world(name).is_none() {
+                lines.push(format!("  (it appears once {} receives its first line)", name));
+            }
+            lines
+        }
+        ["height", rows] => match rows.parse::<u16>() {
+            Ok(rows) if (2..=30).contains(&rows) => {
+                app.panes.height = rows;
+                app.needs_output_redraw = true;
+                vec![format!("Capture pane height set to {} rows.", rows)]
+            }
+            _ => vec!["Usage: /capture height <2-30>".to_string()],
+        },
+        ["view"] => match app.panes.view_return.take() {
+            Some(idx) if idx < app.worlds.len() => {
+                switch(app, idx);
+                Vec::new()
+            }
+            _ => vec!["Not viewing a capture. Use /capture view <name>.".to_string()],
+        },
+        ["view", name] => {
+            let Some(idx) = app.find_world(name) else {
+                return vec![format!("Capture '{}' not found.", name)];
+            };
+            if idx != app.current_world_index {
+                if app.panes.view_return.is_none() {
+                    app.panes.view_return = Some(app.current_world_index);
+                }
+                switch(app, idx);
+            }
+            vec![format!("Viewing {}. /capture view returns.", app.worlds[idx].name)]
+        }
+        _ => capture_command(app, args),
+    }
+}
+
+/// Run `/timestamps` and return the lines to show. On change it also tells clients.
+///
+///   /timestamps [off|hm|hms]                          - show or set the global prefix
+///   /timestamps -w[<world>] [off|hm|hms|global]       - show or set a world's (default: this one)
+pub(crate) fn timestamps_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /timestamps [off|hm|hms] | /timestamps -w[<world>] [off|hm|hms|global]";
+    fn describe(mode: TimestampMode) -> &'static str {
+        match mode {
+            TimestampMode::Off => "off",
+            TimestampMode::Minutes => "HH:MM",
+            TimestampMode::Seconds => "HH:MM:SS",
+        }
+    }
+    let rest = args.trim();
+
+    if let Some(after) = rest.strip_prefix("-w") {
+        let (name, tail) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+        let world_idx = if name.is_empty() {
+            world_idx
+        } else {
+            match app.find_world(name) {
+                Some(idx) => idx,
+                None => return vec![format!("World '{}' not found.", name)],
+            }
+        };
+        if world_idx >= app.worlds.len() {
+            return vec![USAGE.to_string()];
+        }
+        let world_name = app.worlds[world_idx].name.clone();
+        let mode = match tail.trim().to_lowercase().as_str() {
+            "" => {
+                return vec![match app.worlds[world_idx].settings.timestamps {
+                    Some(mode) => format!("Timestamps for {} are {}.", world_name, describe(mode)),
+                    None => format!("Timestamps for {} follow the global setting ({}).", world_name, describe(app.settings.timestamps)),
+                }];
+            }
+            "global" => None,
+            other => match TimestampMode::from_name(other) {
+                Some(mode) => Some(mode),
+                None => return vec![USAGE.to_string()],
+            },
+        };
+        app.worlds[world_idx].settings.timestamps = mode;
+        let _ = persistence::save_settings(app);
+        app.needs_output_redraw = true;
+        app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+            world_index: world_idx,
+            settings: app.world_settings_msg(world_idx),
+            name: world_name.clone(),
+        });
+        return vec![match mode {
+            Some(mode) => format!("Timestamps for {} set to {}.", world_name, describe(mode)),
+            None => format!("Timestamps for {} now follow the global setting.", world_name),
+        }];
+    }
+
+    if rest.is_empty() {
+        let mut lines = vec![format!("Timestamps are {}.", describe(app.settings.timestamps))];
+        let overrides: Vec<String> = app.worlds.iter()
+            .filter_map(|w| w.settings.timestamps.map(|m| format!("{} {}", w.name, describe(m))))
+            .collect();
+        if !overrides.is_empty() {
+            lines.push(format!("  World settings: {}", overrides.join(", ")));
+        }
+        if app.timestamps_toggled {
+            lines.push("  (inverted for this session by the toggle key)".to_string());
+        }
+        return lines;
+    }
+    let Some(mode) = TimestampMode::from_name(rest) else {
+        return vec![USAGE.to_string()];
+    };
+    app.settings.timestamps = mode;
+    let _ = persistence::save_settings(app);
+    app.needs_output_redraw = true;
+    app.ws_broadcast(WsMessage::GlobalSettingsUpdated {
+        settings: app.build_global_settings_msg(),
+        input_height: app.input_height,
+    });
+    vec![format!("Timestamps set to {}.", describe(mode))]
+}
+
+/// Console `/split`: show several worlds at once (see split.rs). `switch` changes the
+/// viewed world, as for `capture_console_command`.
+pub(crate) fn split_command(app: &mut App, args: &str, switch: fn(&mut App, usize)) -> Vec<String> {
+    const USAGE: &str = "Usage: /split [-h|-v] <world> | /split focus | /split close [<world>] | /split off";
+    let mut words: Vec<&str> = args.split_whitespace().collect();
+    let orientation = match words.first().copied() {
+        Some("-v") => Some(true),
+        Some("-h") => Some(false),
+        _ => None,
+    };
+    if orientation.is_some() {
+        words.remove(0);
+    }
+    app.needs_output_redraw = true;
+
+    match (words.as_slice(), orientation) {
+        ([], None) => {
+            if !app.split.is_active() {
+                return vec!["Output is not split. /split <world> shows another world alongside this one.".to_string()];
+            }
+            let names: Vec<String> = app.split.panes.iter().enumerate()
+                .map(|(i, name)| if i == app.split.focus { format!("[{}]", name) } else { name.clone() })
+                .collect();
+            vec![format!("Split {}: {}",
+                if app.split.vertical { "side by side" } else { "stacked" }, names.join(" | "))]
+        }
+        ([], Some(vertical)) => {
+            app.split.vertical = vertical;
+            vec![format!("Split panes are now {}.", if vertical { "side by side" } else { "stacked" })]
+        }
+        (["off"], None) => {
+            app.split.panes.clear();
+            app.split.focus = 0;
+            vec!["Split off.".to_string()]
+        }
+        (["focus"], None) => {
+            match app.split.next_focus().and_then(|name| app.find_world(name)) {
+                Some(idx) => {
+                    switch(app, idx);
+                    Vec::new()
+                }
+                None => vec!["Output is not split.".to_string()],
+            }
+        }
+        (["close"], None) | (["close", _], None) => {
+            let name = words.get(1).map_or_else(|| app.current_world().name.clone(), |n| n.to_string());
+            if !app.split.panes.iter().any(|p| p.eq_ignore_ascii_case(&name)) {
+                return vec![format!("{} has no split pane.", name)];
+            }
+            if let Some(idx) = app.split.close(&name).and_then(|next| app.find_world(&next)) {
+                switch(app, idx);
+            }
+            vec![format!("Closed the split pane for {}.", name)]
+        }
+        ([name], orientation) => {
+            let Some(idx) = app.find_world(name) else {
+                return vec![format!("World '{}' not found.", name)];
+            };
+            if idx == app.current_world_index {
+                return vec!["That world is already shown.".to_string()];
+            }
+            let current = app.current_world().name.clone();
+            let world_name = app.worlds[idx].name.clone();
+            if !app.split.add(&current, &world_name) {
+                return vec![if app.split.panes.len() >= crate::split::MAX_PANES {
+                    format!("At most {} split panes.", crate::split::MAX_PANES)
+                } else {
+                    format!("{} is already shown.", world_name)
+                }];
+            }
+            if let Some(vertical) = orientation {
+                app.split.vertical = vertical;
+            }
+            vec![format!("Showing {} alongside {}. /split focus moves between panes (Alt+O).", world_name, current)]
+        }
+        _ => vec![USAGE.to_string()],
+    }
+}
+
+/// Run `/clipboard [osc52|<command>]` and return the lines to show.
+pub(crate) fn clipboard_command(app: &mut App, args: &str) -> Vec<String> {
+    let args = args.trim();
+    if args.is_empty() {
+        return vec![if app.settings.clipboard_command.is_empty() {
+            "Copy mode uses the terminal clipboard (OSC 52).".to_string()
+        } else {
+            format!("Copy mode pipes text to: {}", app.settings.clipboard_command)
+        }];
+    }
+    app.settings.clipboard_command = if args.eq_ignore_ascii_case("osc52") { String::new() } else { args.to_string() };
+    let _ = persistence::save_settings(app);
+    vec![if app.settings.clipboard_command.is_empty() {
+        "Copy mode will use the terminal clipboard (OSC 52).".to_string()
+    } else {
+        format!("Copy mode will pipe text to: {}", app.settings.clipboard_command)
+    }]
+}
+
+/// Run `/numpad [-w<world>] [on|off]` for `world_idx` and return the lines to show.
+/// On change it also tells clients. With no argument, reports the current state and the
+/// key layout.
+pub(crate) fn numpad_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /numpad [-w<world>] [on|off]";
+    let mut rest = args.trim();
+    let mut world_idx = world_idx;
+
+    if let Some(after) = rest.strip_prefix("-w") {
+        let (name, tail) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+        match app.find_world(name) {
+            Some(idx) => world_idx = idx,
+            None => return vec![format!("World '{}' not found.", name)],
+        }
+        rest = tail.trim();
+    }
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+
+    let enabled = match rest.to_lowercase().as_str() {
+        "" => {
+            let state = if app.worlds[world_idx].settings.numpad_walk { "on" } else { "off" };
+            return vec![
+                format!("Numpad speedwalk for {} is {}.", world_name, state),
+                "  7 nw   8 n    9 ne".to_string(),
+                "  4 w    5 look 6 e".to_string(),
+                "  1 sw   2 s    3 se".to_string(),
+            ];
+        }
+        "on" => true,
+        "off" => false,
+        _ => return vec![USAGE.to_string()],
+    };
+    app.worlds[world_idx].settings.numpad_walk = enabled;
+    let _ = persistence::save_settings(app);
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: world_name.clone(),
+    });
+    vec![format!("Numpad speedwalk {} for {}.", if enabled { "enabled" } else { "disabled" }, world_name)]
+}
+
+/// Run a `/secret` subcommand and return the lines to show. Values are never shown.
+///
+///   /secret [list]                           - list secret names for this world and global
+///   /secret set [-w<world>|-g] <name> <value> - store a secret (default: this world)
+///   /secret del [-w<world>|-g] <name>         - delete a secret
+pub(crate) fn secret_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /secret [list | set [-w<world>|-g] <name> <value> | del [-w<world>|-g] <name>]";
+    let args = args.trim();
+    let (sub, mut rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let sub = if sub.is_empty() { "list".to_string() } else { sub.to_lowercase() };
+    rest = rest.trim_start();
+
+    // Scope: -g for global, -w<world> for another world, default the current world
+    let mut world_idx = Some(world_idx);
+    if let Some(after) = rest.strip_prefix("-g") {
+        if after.is_empty() || after.starts_with(char::is_whitespace) {
+            world_idx = None;
+            rest = after.trim_start();
+        }
+    } else if let Some(after) = rest.strip_prefix("-w") {
+        let (name, tail) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+        match app.find_world(name) {
+            Some(idx) => world_idx = Some(idx),
+            None => return vec![format!("World '{}' not found.", name)],
+        }
+        rest = tail.trim_start();
+    }
+    if world_idx.is_some_and(|idx| idx >= app.worlds.len()) {
+        return vec![USAGE.to_string()];
+    }
+    let scope = match world_idx {
+        Some(idx) => format!("world {}", app.worlds[idx].name),
+        None => "all worlds".to_string(),
+    };
+
+    match sub.as_str() {
+        "list" | "-l" => {
+            let mut out = Vec::new();
+            if let Some(idx) = world_idx {
+                let names: Vec<&str> = app.worlds[idx].settings.secrets.keys().map(|k| k.as_str()).collect();
+                out.push(format!("Secrets for {}: {}", app.worlds[idx].name,
+                    if names.is_empty() { "(none)".to_string() } else { names.join(", ") }));
+            }
+            let names: Vec<&str> = app.settings.secrets.keys().map(|k| k.as_str()).collect();
+            out.push(format!("Global secrets: {}",
+                if names.is_empty() { "(none)".to_string() } else { names.join(", ") }));
+            out.push("Reference as ${secret:NAME} or ${env:VAR} in user, password and action commands.".to_string());
+            out
+        }
+        "set" | "add" => {
+            let (name, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let value = value.trim();
+            if name.is_empty() || value.is_empty() {
+                return vec![USAGE.to_string()];
+            }
+            if !crate::secrets::is_valid_name(name) {
+                return vec![format!("Invalid secret name '{}' (use letters, digits, _ - .).", name)];
+            }
+            let secrets = match world_idx {
+                Some(idx) => &mut app.worlds[idx].settings.secrets,
+                None => &mut app.settings.secrets,
+            };
+            secrets.insert(name.to_string(), value.to_string());
+            let _ = persistence::save_settings(app);
+            vec![format!("Secret '{}' set for {}. Reference it as ${{secret:{}}}.", name, scope, name)]
+        }
+        "del" | "delete" | "rm" => {
+            let name = rest.trim();
+            if name.is_empty() {
+                return vec![USAGE.to_string()];
+            }
+            let secrets = match world_idx {
+                Some(idx) => &mut app.worlds[idx].settings.secrets,
+                None => &mut app.settings.secrets,
+            };
+            if secrets.remove(name).is_none() {
+                return vec![format!("No secret named '{}' for {}.", name, scope)];
+            }
+            let _ = persistence::save_settings(app);
+            vec![format!("Deleted secret '{}' for {}.", name, scope)]
+        }
+        _ => vec![USAGE.to_string()],
+    }
+}
+
+/// Run a `/macro` subcommand for `world_idx` (or the `-w<world>` it names) and return the
+/// lines to show.
+///
+///   /macro [list]               - list this world's function key macros
+///   /macro <key>                - show the macro bound to <key> (F1-F12, Shift-F1..)
+///   /macro <key> <text>         - bind <key> to a command or text (quotes optional)
+///   /macro -d <key>             - remove the macro on <key>
+pub(crate) fn macro_command(app: &mut App, world_idx: usize, args: &str) -> Vec<String> {
+    const USAGE: &str = "Usage: /macro [-w<world>] [list | <key> [text] | -d <key>]  (key: F1-F12 or Shift-F1..F12)";
+    let mut rest = args.trim();
+    let mut world_idx = world_idx;
+
+    if let Some(after) = rest.strip_prefix("-w") {
+        let (name, tail) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+        match app.find_world(name) {
+            Some(idx) => world_idx = idx,
+            None => return vec![format!("World '{}' not found.", name)],
+        }
+        rest = tail.trim();
+    }
+    if world_idx >= app.worlds.len() {
+        return vec![USAGE.to_string()];
+    }
+    let world_name = app.worlds[world_idx].name.clone();
+
+    if rest.is_empty() || rest.eq_ignore_ascii_case("list") {
+        let macros = &app.worlds[world_idx].settings.macros;
+        if macros.is_empty() {
+            return vec![format!("No macros defined for {}. Use /macro <key> <text>.", world_name)];
+        }
+        let mut out = vec![format!("Macros for {}:", world_name)];
+        out.extend(macros.iter().map(|(key, text)| format!("  {:<10} {}", key, text)));
+        return out;
+    }
+
+    let (first, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let tail = tail.trim();
+
+    if first == "-d" {
+        let Some(key) = keybindings::normalize_fkey_name(tail) else {
+            return vec![USAGE.to_string()];
+        };
+        return match app.worlds[world_idx].settings.macros.remove(&key) {
+            Some(_) => {
+                macros_changed(app, world_idx);
+                vec![format!("Removed macro {} for {}.", key, world_name)]
+            }
+            None => vec![format!("No macro on {} for {}.", key, world_name)],
+        };
+    }
+
+    let Some(key) = keybindings::normalize_fkey_name(first) else {
+        return vec![USAGE.to_string()];
+    };
+    if tail.is_empty() {
+        return match app.worlds[world_idx].settings.macros.get(&key) {
+            Some(text) => vec![format!("{}: {}", key, text)],
+            None => vec![format!("No macro on {} for {}.", key, world_name)],
+        };
+    }
+
+    // Allow the text to be quoted: /macro f5 "cast heal"
+    let text = tail.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(tail);
+    if text.is_empty() {
+        return vec![USAGE.to_string()];
+    }
+    app.worlds[world_idx].settings.macros.insert(key.clone(), text.to_string());
+    macros_changed(app, world_idx);
+    vec![format!("{} ({}) -> {}", key, world_name, text)]
+}
+
+/// Save a change to `world_idx`'s macros and send it to clients, which fire them
+pub(crate) fn macros_changed(app: &mut App, world_idx: usize) {
+    let _ = persistence::save_settings(app);
+    app.ws_broadcast(WsMessage::WorldSettingsUpdated {
+        world_index: world_idx,
+        settings: app.world_settings_msg(world_idx),
+        name: app.worlds[world_idx].name.clone(),
+    });
+}
+
 /// Process any pending world operations queued by TF functions like addworld()
 pub(crate) fn process_pending_world_ops(app: &mut App) {
     // Drain pending operations
@@ -0,0 +1,234 @@
+//! tmux-style copy mode for the console output area.
+//!
+//! Copy mode (F7 by default, action `copy_mode`) freezes the output view and puts a
+//! cursor on the last visible line. The cursor moves over the scrollback with vi or
+//! arrow keys, Space/v starts a selection, and Enter/y copies the selection as plain
+//! text (ANSI stripped, exactly as displayed) and leaves copy mode. Esc/q leaves
+//! without copying.
+//!
+//! The text goes to the system clipboard through OSC 52, which works in most
+//! terminals and over SSH, or through an external command set with `/clipboard`
+//! (e.g. `wl-copy` or `xclip -selection clipboard`) for terminals without it.
+
+use crate::{CachedNow, OutputLine};
+
+/// Copy mode state for the console (one per App; the cursor is on the current world)
+#[derive(Debug, Default)]
+pub struct CopyMode {
+    pub active: bool,
+    /// output_lines index of the cursor
+    pub line: usize,
+    /// Char column of the cursor within the line's displayed plain text
+    pub col: usize,
+    /// Other end of the selection, once started
+    pub anchor: Option<(usize, usize)>,
+}
+
+impl CopyMode {
+    pub fn enter(&mut self, line: usize) {
+        self.active = true;
+        self.line = line;
+        self.col = 0;
+        self.anchor = None;
+    }
+
+    pub fn exit(&mut self) {
+        self.active = false;
+        self.anchor = None;
+    }
+
+    /// Start a selection at the cursor, or drop the current one
+    pub fn toggle_selection(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some((self.line, self.col)),
+        };
+    }
+
+    /// Ordered (start, end) of the selection, both inclusive
+    pub fn selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        let anchor = self.anchor?;
+        let cursor = (self.line, self.col);
+        Some(if anchor <= cursor { (anchor, cursor) } else { (cursor, anchor) })
+    }
+
+    /// Columns `start..end` of line `idx` to show in reverse video: the selected part
+    /// of the line, or the cursor cell. `len` is the line's displayed length in chars.
+    pub fn highlight_range(&self, idx: usize, len: usize) -> Option<(usize, usize)> {
+        if !self.active {
+            return None;
+        }
+        match self.selection() {
+            Some(((sl, sc), (el, ec))) if idx >= sl && idx <= el => {
+                let start = if idx == sl { sc } else { 0 };
+                let end = if idx == el { ec + 1 } else { len };
+                Some((start.min(len), end.min(len).max(start.min(len))))
+            }
+            _ if idx == self.line => Some((self.col, self.col + 1)),
+            _ => None,
+        }
+    }
+}
+
+/// The output lines of a world as copy mode sees them: only lines that are displayed,
+/// as the plain text shown on screen
+pub struct CopyView<'a> {
+    pub lines: &'a std::collections::VecDeque<OutputLine>,
+    pub show_tags: bool,
+    pub temp_convert_enabled: bool,
+    pub zwj_enabled: bool,
+    pub timestamps: crate::TimestampMode,
+}
+
+impl CopyView<'_> {
+    /// Displayed plain text of line `idx`, or None if it is hidden (gagged)
+    pub fn text(&self, idx: usize) -> Option<String> {
+        let line = self.lines.get(idx)?;
+        crate::rendering::process_output_line(line, self.show_tags, self.temp_convert_enabled, self.zwj_enabled, self.timestamps, &CachedNow)
+            .map(|s| crate::util::strip_ansi_codes(&s))
+    }
+
+    pub fn prev_line(&self, idx: usize) -> Option<usize> {
+        (0..idx.min(self.lines.len())).rev().find(|&i| self.text(i).is_some())
+    }
+
+    pub fn next_line(&self, idx: usize) -> Option<usize> {
+        (idx + 1..self.lines.len()).find(|&i| self.text(i).is_some())
+    }
+
+    /// Nearest displayed line at or before `idx` (or after, if none before)
+    pub fn nearest_line(&self, idx: usize) -> Option<usize> {
+        let idx = idx.min(self.lines.len().saturating_sub(1));
+        if self.text(idx).is_some() {
+            return Some(idx);
+        }
+        self.prev_line(idx).or_else(|| self.next_line(idx))
+    }
+
+    pub fn line_len(&self, idx: usize) -> usize {
+        self.text(idx).map_or(0, |t| t.chars().count())
+    }
+
+    /// Selected text, lines joined with newlines (`end` inclusive)
+    pub fn selected_text(&self, start: (usize, usize), end: (usize, usize)) -> String {
+        let mut out: Vec<String> = Vec::new();
+        for idx in start.0..=end.0.min(self.lines.len().saturating_sub(1)) {
+            let Some(text) = self.text(idx) else { continue };
+            let from = if idx == start.0 { start.1 } else { 0 };
+            let to = if idx == end.0 { end.1 + 1 } else { usize::MAX };
+            out.push(text.chars().skip(from).take(to.saturating_sub(from)).collect());
+        }
+        out.join("\n")
+    }
+}
+
+/// Column of the start of the next word after `col` in `text` (vi `w`)
+pub fn next_word(text: &str, col: usize) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = col;
+    while i < chars.len() && !chars[i].is_whitespace() {
+        i += 1;
+    }
+    while i < chars.len() && chars[i].is_whitespace() {
+        i += 1;
+    }
+    i.min(chars.len().saturating_sub(1))
+}
+
+/// Column of the start of the word before `col` in `text` (vi `b`)
+pub fn prev_word(text: &str, col: usize) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = col.min(chars.len());
+    while i > 0 && chars[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    while i > 0 && !chars[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    i
+}
+
+/// Put `text` on the system clipboard: through `command` (run by the shell, text on its
+/// stdin) if set, otherwise with an OSC 52 escape to the terminal
+pub fn copy_to_clipboard(text: &str, command: &str) -> Result<(), String> {
+    use std::io::Write;
+    if command.trim().is_empty() {
+        let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, text.as_bytes());
+        let mut stdout = std::io::stdout();
+        stdout.write_all(format!("\x1b]52;c;{}\x07", encoded).as_bytes()).map_err(|e| e.to_string())?;
+        return stdout.flush().map_err(|e| e.to_string());
+    }
+
+    #[cfg(windows)]
+    let mut shell = std::process::Command::new("cmd");
+    #[cfg(windows)]
+    shell.args(["/C", command]);
+    #[cfg(not(windows))]
+    let mut shell = std::process::Command::new("sh");
+    #[cfg(not(windows))]
+    shell.args(["-c", command]);
+
+    let mut child = shell
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| format!("{}: {}", command, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    // Clipboard helpers like xclip stay around to serve the selection; reap in the background
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str, gagged: bool) -> OutputLine {
+        let mut l = OutputLine::new(text.to_string(), 0);
+        l.gagged = gagged;
+        l
+    }
+
+    #[test]
+    fn test_selection_text_skips_hidden_lines() {
+        let lines = std::collections::VecDeque::from(vec![
+            line("\x1b[31mfirst line\x1b[0m", false),
+            line("gagged", true),
+            line("second line", false),
+            line("third", false),
+        ]);
+        let view = CopyView { lines: &lines, show_tags: false, temp_convert_enabled: false, zwj_enabled: true, timestamps: crate::TimestampMode::Off };
+        assert_eq!(view.selected_text((0, 6), (2, 5)), "line\nsecond");
+        assert_eq!(view.selected_text((3, 0), (3, 99)), "third");
+        assert_eq!(view.next_line(0), Some(2));
+        assert_eq!(view.prev_line(2), Some(0));
+        assert_eq!(view.nearest_line(1), Some(0));
+    }
+
+    #[test]
+    fn test_highlight_range_and_words() {
+        let mut mode = CopyMode::default();
+        assert_eq!(mode.highlight_range(0, 5), None);
+        mode.enter(3);
+        mode.col = 2;
+        assert_eq!(mode.highlight_range(3, 10), Some((2, 3)));
+        mode.toggle_selection();
+        mode.line = 1;
+        mode.col = 4;
+        assert_eq!(mode.selection(), Some(((1, 4), (3, 2))));
+        assert_eq!(mode.highlight_range(1, 10), Some((4, 10)));
+        assert_eq!(mode.highlight_range(2, 7), Some((0, 7)));
+        assert_eq!(mode.highlight_range(3, 10), Some((0, 3)));
+        assert_eq!(mode.highlight_range(4, 10), None);
+
+        assert_eq!(next_word("foo bar  baz", 0), 4);
+        assert_eq!(next_word("foo bar  baz", 4), 9);
+        assert_eq!(prev_word("foo bar  baz", 9), 4);
+        assert_eq!(prev_word("foo bar", 2), 0);
+    }
+}
@@ -5,7 +5,6 @@ use std::time::{Duration, UNIX_EPOCH};
 use bytes::BytesMut;
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpStream,
     sync::mpsc,
 };
 
@@ -44,7 +43,18 @@ pub async fn run_local_server(port_override: Option<u16>) -> io::Result<()> {
     let (gui_tx, _gui_rx) = mpsc::unbounded_channel::<WsMessage>();
     let (_gui_to_app_tx, gui_to_app_rx) = mpsc::unbounded_channel::<WsMessage>();
 
-    crate::run_app_headless(gui_tx, gui_to_app_rx, Some(password), None, port_override).await
+    crate::run_app_headless(gui_tx, gui_to_app_rx, Some(password), None, port_override, None).await
+}
+
+/// Run headlessly as the background half of a `/detach`-ed console session: the
+/// connections restored from the reload state keep running, and `clay --attach`
+/// brings up a console on them again through the web server.
+pub async fn run_detached_session() -> io::Result<()> {
+    // Nothing reads or writes the GUI bridge, as for run_local_server
+    let (gui_tx, _gui_rx) = mpsc::unbounded_channel::<WsMessage>();
+    let (_gui_to_app_tx, gui_to_app_rx) = mpsc::unbounded_channel::<WsMessage>();
+
+    crate::run_app_headless(gui_tx, gui_to_app_rx, None, None, None, None).await
 }
 
 /// Run in daemon mode (-D) - background server for remote connections only
@@ -69,6 +79,7 @@ pub async fn run_daemon_server() -> io::Result<()> {
     }
 
     let (event_tx, mut event_rx) = mpsc::channel::<AppEvent>(100);
+    app.event_tx = Some(event_tx.clone());
 
     // Create WebSocket server state (for client management, no standalone listener)
     let ws_state = if !app.settings.websocket_password.is_empty() {
@@ -80,6 +91,7 @@ pub async fn run_daemon_server() -> io::Result<()> {
             false, // Not multiuser mode
             app.ban_list.clone(),
         );
+        server.update_viewer_password(&app.settings.websocket_viewer_password);
         let state = Arc::new(server.connection_state(event_tx.clone()));
         app.ws_server = Some(server);
         Some(state)
@@ -90,8 +102,9 @@ pub async fn run_daemon_server() -> io::Result<()> {
         SecurityGate {
             allow_list: server.allow_list.clone(),
             whitelisted_host: server.whitelisted_host.clone(),
-            auth_key: app.ws_auth_key_shared.clone(),
+            auth_keys: app.ws_auth_keys_shared.clone(),
             web_path: app.settings.web_path.clone(),
+            trusted_proxies: app.web_trusted_proxies(),
             ban_list: app.ban_list.clone(),
         }
     } else {
@@ -100,8 +113,9 @@ pub async fn run_daemon_server() -> io::Result<()> {
                 crate::websocket::parse_allow_list_csv(&app.settings.websocket_allow_list)
             )),
             whitelisted_host: Arc::new(std::sync::RwLock::new(app.settings.websocket_whitelisted_host.clone())),
-            auth_key: app.ws_auth_key_shared.clone(),
+            auth_keys: app.ws_auth_keys_shared.clone(),
             web_path: app.settings.web_path.clone(),
+            trusted_proxies: app.web_trusted_proxies(),
             ban_list: app.ban_list.clone(),
         }
     };
@@ -116,7 +130,7 @@ pub async fn run_daemon_server() -> io::Result<()> {
             // Start HTTPS+WSS
             #[cfg(any(feature = "native-tls-backend", feature = "rustls-backend"))]
             {
-                let mut https_server = HttpsServer::new(app.settings.http_port);
+                let mut https_server = HttpsServer::new(app.settings.http_port, app.web_listen());
                 match start_https_server(
                     &mut https_server,
                     &app.settings.websocket_cert_file,
@@ -138,7 +152,7 @@ pub async fn run_daemon_server() -> io::Result<()> {
             }
         } else {
             // Start HTTP+WS
-            let mut http_server = HttpServer::new(app.settings.http_port);
+            let mut http_server = HttpServer::new(app.settings.http_port, app.web_listen());
             match start_http_server(&mut http_server, ws_state.clone(), app.ban_list.clone(), app.gui_theme_colors().to_css_vars(), None, gate.clone()).await {
                 Ok(()) => {
                     let protocol = if ws_state.is_some() { "HTTP+WS" } else { "HTTP" };
@@ -155,7 +169,7 @@ pub async fn run_daemon_server() -> io::Result<()> {
     // Check if any servers are running
     if app.http_server.is_none() && app.https_server.is_none() {
         eprintln!("Error: No servers started. Enable HTTP in settings.");
-        eprintln!("Use /web command to configure, or edit ~/.clay/settings.dat");
+        eprintln!("Use /web command to configure, or edit ~/.config/clay/settings.toml");
         return Ok(());
     }
 
@@ -171,12 +185,26 @@ pub async fn run_daemon_server() -> io::Result<()> {
     let process_tick_sleep = tokio::time::sleep(FAR_FUTURE);
     tokio::pin!(process_tick_sleep);
 
+    // Background world batch release — only active while low-priority worlds hold output
+    let background_batch_sleep = tokio::time::sleep(FAR_FUTURE);
+    tokio::pin!(background_batch_sleep);
+
     // Main event loop - handles MUD connections and WebSocket messages
     loop {
         #[cfg(all(unix, not(target_os = "android")))]
         reap_zombie_children();
 
         tokio::select! {
+            // Release held output of low-priority background worlds (see throttle.rs)
+            _ = &mut background_batch_sleep => {
+                let mut retry = false;
+                for name in app.due_background_batches(std::time::Instant::now()) {
+                    retry |= event_tx.try_send(AppEvent::ServerData(name, Vec::new())).is_err();
+                }
+                let next = if retry { Duration::from_millis(100) } else { FAR_FUTURE };
+                background_batch_sleep.as_mut().reset(tokio::time::Instant::now() + next);
+            }
+
             // TF repeat process tick — only fires when processes exist
             _ = &mut process_tick_sleep => {
                 let now = std::time::Instant::now();
@@ -266,6 +294,26 @@ pub async fn run_daemon_server() -> io::Result<()> {
             }
             Some(event) = event_rx.recv() => {
                 match event {
+                    AppEvent::ReplayData(ref world_name, text) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.replay_output(world_idx, &text, 24, 200, true);
+                        }
+                    }
+                    AppEvent::SendFileLine(ref world_name, line) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.send_file_line(world_idx, line);
+                        }
+                    }
+                    AppEvent::ShellOutput(ref world_name, output, line) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.shell_output(world_idx, output, line);
+                        }
+                    }
+                    AppEvent::ServerEcho(ref world_name, on) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.set_server_echo(world_idx, on);
+                        }
+                    }
                     AppEvent::ServerData(ref world_name, bytes) => {
                         if let Some(world_idx) = app.find_world_index(world_name) {
                             // Use shared server data processing (same as console mode)
@@ -276,6 +324,9 @@ pub async fn run_daemon_server() -> io::Result<()> {
                                 200, // Fallback width — actual width computed from connected clients
                                 true, // is_daemon_mode
                             );
+                            if let Some(at) = app.next_background_release() {
+                                background_batch_sleep.as_mut().reset(tokio::time::Instant::from_std(at));
+                            }
 
                             // Execute any triggered commands
                             let saved_current_world = app.current_world_index;
@@ -325,6 +376,7 @@ pub async fn run_daemon_server() -> io::Result<()> {
                             if conn_id != app.worlds[world_idx].connection_id {
                                 continue;
                             }
+                            app.set_server_echo(world_idx, false);
                             app.worlds[world_idx].connected = false;
                             app.worlds[world_idx].command_tx = None;
 
@@ -334,10 +386,11 @@ pub async fn run_daemon_server() -> io::Result<()> {
                     }
                     AppEvent::WsClientMessage(client_id, msg) => {
                         // Check if this is an AuthRequest (client just authenticated)
-                        if matches!(*msg, WsMessage::AuthRequest { .. }) {
+                        if let WsMessage::AuthRequest { resume_seqs, .. } = &*msg {
                             // Send initial state after successful authentication
-                            let initial_state = app.build_initial_state();
+                            let initial_state = app.build_resumed_initial_state(resume_seqs);
                             app.ws_send_initial_state_and_mark(client_id, initial_state);
+                            app.ws_seed_client_unseen(client_id);
                         } else {
                             handle_daemon_ws_message(&mut app, client_id, *msg, &event_tx).await;
                         }
@@ -345,8 +398,8 @@ pub async fn run_daemon_server() -> io::Result<()> {
                     AppEvent::WsClientConnected(_client_id) => {
                         // Client connected but not yet authenticated - nothing to do
                     }
-                    AppEvent::WsClientDisconnected(_client_id) => {
-                        // Client disconnected, nothing special to do
+                    AppEvent::WsClientDisconnected(client_id) => {
+                        app.ws_client_unseen.remove(&client_id);
                     }
                     AppEvent::SystemMessage(msg) => {
                         // Print system messages (including connection rejections) to console
@@ -360,31 +413,13 @@ pub async fn run_daemon_server() -> io::Result<()> {
                     AppEvent::ApiLookupResult(client_id, world_index, result, cursor_start) => {
                         match result {
                             Ok(text) => app.ws_send_to_client(client_id, WsMessage::SetInputBuffer { text, cursor_start }),
-                            Err(e) => app.ws_send_to_client(client_id, WsMessage::ServerData {
-                                world_index,
-                                data: e,
-                                is_viewed: false,
-                                ts: current_timestamp_secs(),
-                                from_server: false,
-                                seq: 0,
-                                marked_new: false,
-                                flush: false, gagged: false,
-                            }),
+                            Err(e) => app.send_command_output(client_id, world_index, e),
                         }
                     }
                     AppEvent::RemoteListResult(requesting_client_id, world_index, lines) => {
                         app.remote_ping_responses = None;
                         for line in &lines {
-                            app.ws_send_to_client(requesting_client_id, WsMessage::ServerData {
-                                world_index,
-                                data: line.clone(),
-                                is_viewed: false,
-                                ts: current_timestamp_secs(),
-                                from_server: false,
-                                seq: 0,
-                                marked_new: false,
-                                flush: false, gagged: false,
-                            });
+                            app.send_command_output(requesting_client_id, world_index, line.clone());
                         }
                     }
                     AppEvent::Sigusr1Received => {
@@ -412,6 +447,15 @@ pub async fn run_daemon_server() -> io::Result<()> {
                     AppEvent::ImportResult(client_id, addr, result) => {
                         app.handle_import_result(client_id, addr, result);
                     }
+                    AppEvent::AcmeResult(domain, result) => {
+                        // The daemon's web server reads only websocket_cert_file, so the
+                        // certificate is only reported here (the GUI's headless loop,
+                        // which shares handle_daemon_ws_message, restarts onto it)
+                        app.handle_acme_result(&domain, &result);
+                    }
+                    AppEvent::PushGone(endpoints) => {
+                        app.remove_push_subscriptions(&endpoints);
+                    }
                     _ => {}
                 }
             }
@@ -446,7 +490,7 @@ pub async fn handle_daemon_ws_message(
             let command = rewrite_slashless_action(&command, &app.settings.actions, &world_name)
                 .unwrap_or(command);
             // Use shared command parsing (same as console mode)
-            let parsed = parse_command(&command);
+            let parsed = app.resolve_open_uri(parse_command(&command));
 
             // Reset more-mode counter when user sends a command
             if world_index < app.worlds.len() {
@@ -459,6 +503,8 @@ pub async fn handle_daemon_ws_message(
             }
 
             match parsed {
+                // Already turned into a WorldSwitch by resolve_open_uri
+                Command::OpenUri { .. } => {}
                 Command::ActionCommand { name, args } => {
                     // Execute action if it exists (respects the action's world field).
                     if let Some(action) = find_invocable_action(&app.settings.actions, &name, &world_name) {
@@ -477,9 +523,9 @@ pub async fn handle_daemon_ws_message(
                             let commands = split_action_commands(&action.command);
                             let mut sent_to_server = false;
                             for cmd in commands {
-                                let cmd = substitute_action_args(&cmd, &args);
+                                let cmd = crate::dice::expand(&substitute_action_args(&app.expand_secrets(world_index, &cmd), &args));
 
-                                if cmd.eq_ignore_ascii_case("/gag") || cmd.to_lowercase().starts_with("/gag ") {
+                                if cmd.eq_ignore_ascii_case("/gag") || cmd.to_lowercase().starts_with("/gag ") || crate::capture::action_target(&cmd).is_some() {
                                     continue;
                                 }
                                 // Unified command system - route through TF parser
@@ -651,8 +697,10 @@ pub async fn handle_daemon_ws_message(
                 Command::NotACommand { text } => {
                     // Regular text - send to MUD
                     if world_index < app.worlds.len() {
+                        let text = app.worlds[world_index].wrap_input(&text);
                         if let Some(tx) = &app.worlds[world_index].command_tx {
-                            if tx.try_send(WriteCommand::Text(text)).is_ok() {
+                            if tx.try_send(WriteCommand::Text(text.clone())).is_ok() {
+                                app.echo_sent(world_index, &text);
                                 app.worlds[world_index].last_send_time = Some(std::time::Instant::now());
                                 app.worlds[world_index].prompt.clear();
                             }
@@ -670,56 +718,188 @@ pub async fn handle_daemon_ws_message(
                     // Broadcast to all clients
                     app.ws_broadcast(WsMessage::ShowTagsChanged { show_tags: app.show_tags });
                 }
+                Command::Macro { args } => {
+                    let output = crate::commands::macro_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::TagRoute { args } if args.is_empty() => {
+                    // Bare /tagroute opens the client's own tag routes popup
+                    app.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.clone() });
+                }
+                Command::TagRoute { args } => {
+                    let output = crate::commands::tag_route_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Gag { args } if args.is_empty() => {
+                    // Bare /gag opens the client's own gags popup
+                    app.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.clone() });
+                }
+                Command::Gag { args } => {
+                    let output = crate::commands::gag_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Ungag { args } => {
+                    let output = crate::commands::ungag_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Profile { args } => {
+                    let output = crate::commands::profile_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Config { args } => {
+                    let output = crate::commands::config_command(&args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Numpad { args } => {
+                    let output = crate::commands::numpad_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Secret { args } => {
+                    let output = crate::commands::secret_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::LoopGuard { args } => {
+                    let output = crate::commands::loopguard_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Clipboard { args } => {
+                    let output = crate::commands::clipboard_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Timestamps { args } => {
+                    let output = crate::commands::timestamps_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Bell { args } => {
+                    let output = crate::commands::bell_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Replay { args } => {
+                    let output = crate::commands::replay_command(app, world_index, &args, Some(event_tx)).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Shell { command, pipe } => {
+                    let output = crate::commands::shell_command(app, world_index, &command, pipe, Some(event_tx));
+                    if !output.is_empty() {
+                        app.send_command_output(client_id, world_index, output.join("\n"));
+                    }
+                }
+                Command::SendFile { args } => {
+                    let output = crate::commands::sendfile_command(app, world_index, &args, Some(event_tx)).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Lock { args } => {
+                    let output = crate::commands::lock_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Unlock { args } => {
+                    let output = crate::commands::unlock_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Export { args } => {
+                    let output = crate::commands::export_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::TriggerTest { args } => {
+                    let output = crate::commands::trigger_test_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::ActionsExport { args } => {
+                    let output = crate::commands::actions_export_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::ActionsImport { args } => {
+                    let output = crate::commands::actions_import_command(app, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::ImportFile { args } => {
+                    let output = crate::commands::import_file_command(app, &args).join("\n");
+                    let initial_state = app.build_initial_state();
+                    app.ws_send_initial_state_and_mark(client_id, initial_state);
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Log { args } => {
+                    let output = crate::commands::log_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Split { .. } => {
+                    app.send_command_output(client_id, world_index, "/split is for the console; in the browser, open another window for the other world.");
+                }
+                Command::Capture { args } => {
+                    let output = if crate::capture::is_console_subcommand(&args) {
+                        "/capture pane, view and height lay out the console; in the browser, capture buffers are worlds of their own.".to_string()
+                    } else {
+                        crate::commands::capture_command(app, &args).join("\n")
+                    };
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Throttle { args } => {
+                    let output = crate::commands::throttle_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::LocalEcho { args } => {
+                    let output = crate::commands::localecho_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Emoji { args } => {
+                    let output = crate::commands::emoji_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Autocorrect { args } => {
+                    let output = crate::commands::autocorrect_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::AddWord { word } => {
+                    let output = crate::commands::addword_command(app, &word).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::SpellIgnore { word } => {
+                    let output = crate::commands::spellignore_command(app, &word).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Unignore { word } => {
+                    let output = crate::commands::unignore_command(app, &word).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Palette { args } => {
+                    let output = crate::commands::palette_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Tint { args } => {
+                    let output = crate::commands::tint_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Images { args } => {
+                    let output = crate::commands::images_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::SpellLang { args } => {
+                    let output = crate::commands::spelllang_command(app, world_index, &args).join("\n");
+                    app.send_command_output(client_id, world_index, output);
+                }
+                Command::Urls => {
+                    let urls = app.recent_urls(world_index, 20);
+                    let output = if urls.is_empty() {
+                        "No URLs in this world's output.".to_string()
+                    } else {
+                        format!("Recent URLs (newest first):\n{}", urls.join("\n"))
+                    };
+                    app.send_command_output(client_id, world_index, output);
+                }
                 Command::Dict { .. } | Command::Urban { .. } | Command::Translate { .. } | Command::TinyUrl { .. } => {
                     spawn_api_lookup(event_tx.clone(), client_id, world_index, parsed);
                 }
                 Command::DictUsage => {
-                    app.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: "Usage: /dict <word>".to_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                    });
+                    app.send_command_output(client_id, world_index, "Usage: /dict <word>");
                 }
                 Command::UrbanUsage => {
-                    app.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: "Usage: /urban <word>".to_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                    });
+                    app.send_command_output(client_id, world_index, "Usage: /urban <word>");
                 }
                 Command::TranslateUsage => {
-                    app.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: "Usage: /translate <lang> <text>".to_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                    });
+                    app.send_command_output(client_id, world_index, "Usage: /translate <lang> <text>");
                 }
                 Command::TinyUrlUsage => {
-                    app.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: "Usage: /url <url>".to_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                    });
+                    app.send_command_output(client_id, world_index, "Usage: /url <url>");
                 }
                 Command::HelpTopic { ref topic } => {
                     use crate::popup::definitions::help::get_topic_help;
@@ -830,7 +1010,7 @@ pub async fn handle_daemon_ws_message(
                         app.worlds[world_index].command_tx = None;
                         app.worlds[world_index].connected = false;
                         app.worlds[world_index].socket_fd = None;
-                        app.worlds[world_index].close_log_file();
+                        app.worlds[world_index].end_log_session();
                         app.worlds[world_index].prompt.clear();
                         app.ws_broadcast(WsMessage::ServerData {
                             world_index,
@@ -856,6 +1036,26 @@ pub async fn handle_daemon_ws_message(
                         });
                     }
                 }
+                Command::DisconnectAll => {
+                    let count = app.disconnect_all_worlds();
+                    app.send_command_output(client_id, world_index, format!("Disconnected {} world{}.", count, if count == 1 { "" } else { "s" }));
+                }
+                Command::ConnectAll => {
+                    // Daemon connects finish before /__connect returns, so the tally is ready at once
+                    let names: Vec<String> = app.auto_connect_worlds().into_iter().map(|idx| app.worlds[idx].name.clone()).collect();
+                    let mut bulk = crate::BulkConnect::default();
+                    for name in names {
+                        let Some(idx) = app.find_world_index(&name) else { continue };
+                        let connect = WsMessage::SendCommand { world_index: idx, command: "/__connect".to_string() };
+                        Box::pin(handle_daemon_ws_message(app, client_id, connect, event_tx)).await;
+                        if app.find_world_index(&name).is_some_and(|idx| app.worlds[idx].connected) {
+                            bulk.connected += 1;
+                        } else {
+                            bulk.failed.push(name);
+                        }
+                    }
+                    app.send_command_output(client_id, world_index, bulk.summary());
+                }
                 Command::Flush => {
                     if world_index < app.worlds.len() {
                         let line_count = app.worlds[world_index].output_lines.len();
@@ -925,6 +1125,24 @@ pub async fn handle_daemon_ws_message(
                         seq: 0, marked_new: false, flush: false, gagged: false,
                     });
                 }
+                Command::WebBans => {
+                    app.send_command_output(client_id, world_index, crate::commands::web_bans_lines(&app.ban_list).join("\n"));
+                }
+                Command::WebViewer { ref args } => {
+                    let data = crate::commands::web_viewer_command(app, args);
+                    app.send_command_output(client_id, world_index, data);
+                }
+                Command::WebAcme { ref args } => {
+                    let data = crate::commands::web_acme_command(app, args);
+                    app.send_command_output(client_id, world_index, data);
+                }
+                Command::WebPush { ref args } if crate::commands::web_push_is_local(args) => {
+                    app.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.clone() });
+                }
+                Command::WebPush { ref args } => {
+                    let data = crate::commands::web_push_command(app, args);
+                    app.send_command_output(client_id, world_index, data);
+                }
                 Command::BanList => {
                     let bans = app.ban_list.get_ban_info();
                     if bans.is_empty() {
@@ -942,12 +1160,12 @@ pub async fn handle_daemon_ws_message(
                         let mut output = String::new();
                         output.push_str("\nBanned Hosts:\n");
                         output.push_str(&"\u{2500}".repeat(70));
-                        output.push_str(&format!("\n{:<20} {:<12} {}\n", "Host", "Type", "Last URL/Reason"));
+                        output.push_str(&format!("\n{:<20} {:<14} {}\n", "Host", "Ends", "Last URL/Reason"));
                         output.push_str(&"\u{2500}".repeat(70));
                         output.push('\n');
                         for (ip, ban_type, reason) in &bans {
                             let reason_display = if reason.is_empty() { "(unknown)" } else { reason };
-                            output.push_str(&format!("{:<20} {:<12} {}\n", ip, ban_type, reason_display));
+                            output.push_str(&format!("{:<20} {:<14} {}\n", ip, ban_type, reason_display));
                         }
                         output.push_str(&"\u{2500}".repeat(70));
                         output.push_str("\nUse /unban <host> to remove a ban.");
@@ -1000,30 +1218,13 @@ pub async fn handle_daemon_ws_message(
                         world_index,
                         notes: test_notes,
                     });
-                    app.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: "Playing test music (Super Mario Bros)...".to_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                    });
+                    app.send_command_output(client_id, world_index, "Playing test music (Super Mario Bros)...");
                 }
                 Command::Notify { message } => {
-                    let title = if world_index < app.worlds.len() {
-                        app.worlds[world_index].name.clone()
-                    } else {
-                        "Clay".to_string()
-                    };
-                    app.ws_broadcast(WsMessage::Notification {
-                        title,
-                        message: message.clone(),
-                    });
+                    let data = crate::commands::notify_command(app, world_index, &message).join("\n");
                     app.ws_broadcast(WsMessage::ServerData {
                         world_index,
-                        data: format!("Notification sent: {}", message),
+                        data,
                         is_viewed: false,
                         ts: current_timestamp_secs(),
                         from_server: false,
@@ -1032,6 +1233,23 @@ pub async fn handle_daemon_ws_message(
                     flush: false, gagged: false,
                     });
                 }
+                Command::Calc { expr, roll, send } => {
+                    let reply = match crate::dice::result_line(&expr, roll) {
+                        Ok(line) if send => {
+                            let tx = app.worlds.get(world_index).and_then(|w| w.command_tx.as_ref());
+                            if tx.is_some_and(|tx| tx.try_send(WriteCommand::Text(line)).is_ok()) {
+                                app.worlds[world_index].last_send_time = Some(std::time::Instant::now());
+                                None
+                            } else {
+                                Some("Not connected.".to_string())
+                            }
+                        }
+                        Ok(line) | Err(line) => Some(line),
+                    };
+                    if let Some(data) = reply {
+                        app.send_command_output(client_id, world_index, data);
+                    }
+                }
                 Command::Say { text } => {
                     // Speak text via TTS (console subprocess + broadcast to web clients)
                     tts::speak(&app.tts_backend, &text, app.settings.tts_mode);
@@ -1119,41 +1337,26 @@ pub async fn handle_daemon_ws_message(
                     crate::debug_log(is_debug_enabled(), "DAEMON: Received /reload command, sending Sigusr1Received event");
                     let _ = event_tx.send(AppEvent::Sigusr1Received).await;
                 }
+                Command::Detach => {
+                    app.send_command_output(client_id, world_index, "This session already runs in the background; quit the client to leave it.");
+                }
                 Command::RemoteAttach { .. } => {
                     // A headless daemon (-D) has no local console/GUI to relaunch into —
                     // /connect only makes sense for an interactive master or client.
-                    app.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: "/connect is not available in daemon mode.".to_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                        marked_new: false,
-                        flush: false, gagged: false,
-                    });
+                    app.send_command_output(client_id, world_index, "/connect is not available in daemon mode.");
                 }
                 Command::Import { .. } => {
                     // Same reasoning as the WS-bounced-command rejection in main.rs: the
                     // command line can't carry a password/auth-key, so a dedicated
                     // ImportSettings message is required (later plan step).
-                    app.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: "Use the /import dialog (not the command line) so your password/auth-key aren't sent unprotected.".to_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                        marked_new: false,
-                        flush: false, gagged: false,
-                    });
+                    app.send_command_output(client_id, world_index, "Use the /import dialog (not the command line) so your password/auth-key aren't sent unprotected.");
                 }
                 // Commands that execute locally on the client
                 Command::Quit | Command::Update { .. } => {
                     app.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.clone() });
                 }
                 // UI popup commands - send back to client for local handling
-                Command::Help | Command::Menu | Command::Font | Command::Setup | Command::Web | Command::Actions { .. } |
+                Command::Help | Command::Menu | Command::Font | Command::Setup | Command::Web | Command::WebTokens { .. } | Command::Actions { .. } |
                 Command::WorldsList | Command::WorldSelector | Command::WorldEdit { .. } => {
                     app.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.clone() });
                 }
@@ -1162,16 +1365,7 @@ pub async fn handle_daemon_ws_message(
                     app.ws_send_to_client(client_id, WsMessage::OpenWindow { world });
                 }
                 Command::Version => {
-                    app.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: get_version_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                    });
+                    app.send_command_output(client_id, world_index, get_version_string());
                 }
                 // AddWorld - add or update world definition
                 Command::AddWorld { name, host, port, user, password, use_ssl } => {
@@ -1225,7 +1419,7 @@ pub async fn handle_daemon_ws_message(
                 Command::Connect { .. } => {
                     if world_index < app.worlds.len() && !app.worlds[world_index].connected {
                         if app.worlds[world_index].settings.has_connection_settings() {
-                            let settings = app.worlds[world_index].settings.clone();
+                            let settings = app.login_settings(world_index);
                             let world_name = app.worlds[world_index].name.clone();
 
                             let ssl_msg = if settings.use_ssl { " with SSL" } else { "" };
@@ -1250,11 +1444,15 @@ pub async fn handle_daemon_ws_message(
                                 app.worlds[world_index].connection_id,
                                 skip_login,
                                 app.settings.tls_proxy_enabled,
+                                app.worlds[world_index].writer_encoding(),
                             ).await {
                                 app.worlds[world_index].connected = true;
                                 app.worlds[world_index].command_tx = Some(cmd_tx);
                                 app.worlds[world_index].was_connected = true;
-                                app.worlds[world_index].skip_auto_login = false;
+                                // Diku/Script logins check the flag once the server prompts (advance_login)
+                                if !settings.login_waits_for_server() {
+                                    app.worlds[world_index].skip_auto_login = false;
+                                }
                                 app.worlds[world_index].socket_fd = socket_fd;
                                 app.worlds[world_index].is_tls = is_tls;
                                 app.worlds[world_index].proxy_pid = proxy_pid;
@@ -1293,12 +1491,13 @@ pub async fn handle_daemon_ws_message(
                 Command::WorldConnectBackground { ref name } => {
                     if let Some(idx) = app.worlds.iter().position(|w| w.name.eq_ignore_ascii_case(name)) {
                         if !app.worlds[idx].connected && app.worlds[idx].settings.has_connection_settings() {
-                            let settings = app.worlds[idx].settings.clone();
+                            let settings = app.login_settings(idx);
                             let world_name = app.worlds[idx].name.clone();
                             app.worlds[idx].connection_id += 1;
                             if let Some((cmd_tx, socket_fd, is_tls, proxy_pid, proxy_socket_path)) = connect_daemon_world(
                                 idx, world_name.clone(), &settings, event_tx.clone(),
                                 app.worlds[idx].connection_id, false, app.settings.tls_proxy_enabled,
+                                app.worlds[idx].writer_encoding(),
                             ).await {
                                 app.worlds[idx].connected = true;
                                 app.worlds[idx].command_tx = Some(cmd_tx);
@@ -1317,8 +1516,8 @@ pub async fn handle_daemon_ws_message(
                 }
                 Command::WorldSwitch { ref name } | Command::WorldConnectNoLogin { ref name } => {
                     if let Some(idx) = app.worlds.iter().position(|w| w.name.eq_ignore_ascii_case(name)) {
-                        app.switch_world(idx);
-                        app.ws_broadcast(WsMessage::WorldSwitched { new_index: idx });
+                        // Switch only the requesting client's world, not the others
+                        app.ws_focus_client_world(client_id, idx);
                         // Also send ExecuteLocalCommand so web clients can switch their local view
                         app.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.clone() });
                         // Connect if not connected and has settings
@@ -1328,7 +1527,7 @@ pub async fn handle_daemon_ws_message(
                             if matches!(parsed, Command::WorldConnectNoLogin { .. }) {
                                 app.worlds[idx].skip_auto_login = true;
                             }
-                            let settings = app.worlds[idx].settings.clone();
+                            let settings = app.login_settings(idx);
                             let world_name = app.worlds[idx].name.clone();
 
                             let ssl_msg = if settings.use_ssl { " with SSL" } else { "" };
@@ -1353,11 +1552,15 @@ pub async fn handle_daemon_ws_message(
                                 app.worlds[idx].connection_id,
                                 skip_login,
                                 app.settings.tls_proxy_enabled,
+                                app.worlds[idx].writer_encoding(),
                             ).await {
                                 app.worlds[idx].connected = true;
                                 app.worlds[idx].command_tx = Some(cmd_tx);
                                 app.worlds[idx].was_connected = true;
-                                app.worlds[idx].skip_auto_login = false;
+                                // Diku/Script logins check the flag once the server prompts (advance_login)
+                                if !settings.login_waits_for_server() {
+                                    app.worlds[idx].skip_auto_login = false;
+                                }
                                 app.worlds[idx].socket_fd = socket_fd;
                                 app.worlds[idx].is_tls = is_tls;
                                 app.worlds[idx].proxy_pid = proxy_pid;
@@ -1397,7 +1600,7 @@ pub async fn handle_daemon_ws_message(
         }
         WsMessage::ConnectWorld { world_index } => {
             if world_index < app.worlds.len() && !app.worlds[world_index].connected {
-                let settings = app.worlds[world_index].settings.clone();
+                let settings = app.login_settings(world_index);
                 let world_name = app.worlds[world_index].name.clone();
 
                 // Check if world has connection settings
@@ -1438,12 +1641,16 @@ pub async fn handle_daemon_ws_message(
                     app.worlds[world_index].connection_id,
                     skip_login,
                     app.settings.tls_proxy_enabled,
+                    app.worlds[world_index].writer_encoding(),
                 ).await {
                     // Connection succeeded
                     app.worlds[world_index].connected = true;
                     app.worlds[world_index].command_tx = Some(cmd_tx);
                     app.worlds[world_index].was_connected = true;
-                    app.worlds[world_index].skip_auto_login = false;
+                    // Diku/Script logins check the flag once the server prompts (advance_login)
+                    if !settings.login_waits_for_server() {
+                        app.worlds[world_index].skip_auto_login = false;
+                    }
                     app.worlds[world_index].socket_fd = socket_fd;
                     app.worlds[world_index].is_tls = is_tls;
                     app.worlds[world_index].proxy_pid = proxy_pid;
@@ -1477,12 +1684,12 @@ pub async fn handle_daemon_ws_message(
             }
         }
         WsMessage::SwitchWorld { world_index } => {
+            // Switch only the requesting client's world, not the others
             if world_index < app.worlds.len() {
-                app.current_world_index = world_index;
-                app.ws_broadcast(WsMessage::WorldSwitched { new_index: world_index });
+                app.ws_focus_client_world(client_id, world_index);
             }
         }
-        WsMessage::UpdateGlobalSettings { more_mode_enabled, spell_check_enabled, temp_convert_enabled, world_switch_mode, show_tags, debug_enabled, ansi_music_enabled, console_theme, gui_theme, gui_transparency, color_offset_percent, wrapspace, remote_initial_lines, input_height, font_name, font_size, web_font_size_phone, web_font_size_tablet, web_font_size_desktop, web_font_weight, web_font_line_height, web_font_letter_spacing, web_font_word_spacing, ws_allow_list, web_secure, http_enabled, http_port, web_path, ws_enabled: _, ws_port: _, ws_cert_file, ws_key_file, ws_password: _, tls_proxy_enabled, dictionary_path, mouse_enabled, zwj_enabled, new_line_indicator, tts_mode, tts_speak_mode, scrollback_enabled } => {
+        WsMessage::UpdateGlobalSettings { more_mode_enabled, spell_check_enabled, temp_convert_enabled, world_switch_mode, show_tags, debug_enabled, ansi_music_enabled, console_theme, gui_theme, gui_transparency, color_offset_percent, wrapspace, remote_initial_lines, input_height, font_name, font_size, web_font_size_phone, web_font_size_tablet, web_font_size_desktop, web_font_weight, web_font_line_height, web_font_letter_spacing, web_font_word_spacing, ws_allow_list, web_secure, http_enabled, http_port, web_path, ws_enabled: _, ws_port: _, ws_cert_file, ws_key_file, ws_password: _, tls_proxy_enabled, dictionary_path, mouse_enabled, zwj_enabled, new_line_indicator, tts_mode, tts_speak_mode, scrollback_enabled, status_format, tab_bar, notify_activity, scrollback_lines, confirm_quit, acme_domain, acme_email, web_bind_addr, web_unix_socket, web_trusted_proxies } => {
             app.settings.more_mode_enabled = more_mode_enabled;
             app.settings.spell_check_enabled = spell_check_enabled;
             app.settings.temp_convert_enabled = temp_convert_enabled;
@@ -1538,7 +1745,7 @@ pub async fn handle_daemon_ws_message(
             app.settings.tts_speak_mode = tts::TtsSpeakMode::from_name(&tts_speak_mode);
             if app.settings.dictionary_path != dictionary_path {
                 app.settings.dictionary_path = dictionary_path;
-                app.spell_checker = SpellChecker::new(&app.settings.dictionary_path);
+                app.reload_spell_checker();
             }
 
             let scrollback_changed = app.settings.scrollback_enabled != scrollback_enabled;
@@ -1546,6 +1753,45 @@ pub async fn handle_daemon_ws_message(
             if scrollback_changed {
                 app.init_scrollback();
             }
+            if let Some(status_format) = status_format {
+                app.settings.status_format = status_format;
+            }
+            if let Some(tab_bar) = tab_bar {
+                app.settings.tab_bar = tab_bar;
+            }
+            if let Some(notify_activity) = notify_activity {
+                app.settings.notify_activity = notify_activity;
+            }
+            if let Some(lines) = scrollback_lines {
+                app.settings.scrollback_lines = lines.clamp(crate::MIN_SCROLLBACK_LINES, crate::MAX_SCROLLBACK_LINES);
+            }
+            if let Some(confirm_quit) = confirm_quit {
+                app.settings.confirm_quit = confirm_quit;
+            }
+            if let Some(domain) = acme_domain {
+                match app.set_acme_domain(&domain, acme_email.as_deref().unwrap_or("")) {
+                    // Turned off: back to the self-signed certificate
+                    Ok(true) if domain.is_empty() => app.web_restart_needed = app.settings.http_enabled,
+                    Ok(_) => {}
+                    Err(e) => app.add_output(&e),
+                }
+            }
+            if web_bind_addr.is_some() || web_unix_socket.is_some() {
+                let bind_addr = web_bind_addr.unwrap_or_else(|| app.settings.web_bind_addr.clone());
+                let unix_socket = web_unix_socket.unwrap_or_else(|| app.settings.web_unix_socket.clone());
+                match app.set_web_listen(&bind_addr, &unix_socket) {
+                    Ok(true) => app.web_restart_needed = true,
+                    Ok(false) => {}
+                    Err(e) => app.add_output(&e),
+                }
+            }
+            if let Some(proxies) = web_trusted_proxies {
+                match app.set_trusted_proxies(&proxies) {
+                    Ok(true) => app.web_restart_needed = true,
+                    Ok(false) => {}
+                    Err(e) => app.add_output(&e),
+                }
+            }
 
             // Save settings. Tag the (debug-mode-only) audit log with which kind of
             // client pushed this, so a future settings-loss report can be traced back
@@ -1586,13 +1832,32 @@ pub async fn handle_daemon_ws_message(
                 }
             }
         }
+        WsMessage::RequestDeviceTokens => {
+            let reply = app.device_tokens_reply(crate::commands::DeviceTokenRequest::List);
+            app.ws_send_to_client(client_id, reply);
+        }
+        WsMessage::CreateDeviceToken { name } => {
+            let reply = app.device_tokens_reply(crate::commands::DeviceTokenRequest::Create(name));
+            app.ws_send_to_client(client_id, reply);
+        }
+        WsMessage::RevokeDeviceToken { name } => {
+            let reply = app.device_tokens_reply(crate::commands::DeviceTokenRequest::Revoke(name));
+            app.ws_send_to_client(client_id, reply);
+        }
+        WsMessage::PushSubscribe { endpoint, p256dh, auth } => {
+            let reply = app.add_push_subscription(&endpoint, &p256dh, &auth);
+            app.ws_send_client_line(client_id, &reply);
+        }
+        WsMessage::PushUnsubscribe { endpoint } => {
+            app.remove_push_subscriptions(&[endpoint]);
+            app.ws_send_client_line(client_id, "Push notifications off for this browser.");
+        }
         WsMessage::RequestConnectionsList => {
             let current_idx = app.current_world_index;
-            const KEEPALIVE_SECS: u64 = 5 * 60;
             let worlds_info: Vec<util::WorldListInfo> = app.worlds.iter().enumerate().map(|(idx, world)| {
                 let now = std::time::Instant::now();
                 let next_nop = if world.connected {
-                    world.last_send_time.map(|t| KEEPALIVE_SECS.saturating_sub(t.elapsed().as_secs()))
+                    world.last_send_time.map(|t| world.settings.keep_alive_interval().as_secs().saturating_sub(t.elapsed().as_secs()))
                 } else {
                     None
                 };
@@ -1628,8 +1893,9 @@ pub async fn handle_daemon_ws_message(
         }
         WsMessage::MarkWorldSeen { world_index } => {
             if world_index < app.worlds.len() {
+                // Seen overall (no console here), but only this client's indicator clears
                 app.worlds[world_index].mark_seen();
-                app.ws_broadcast(WsMessage::UnseenCleared { world_index });
+                app.ws_mark_client_seen(client_id, world_index);
                 app.broadcast_activity();
                 // Trigger console redraw to update activity indicator
                 app.needs_output_redraw = true;
@@ -1769,9 +2035,9 @@ pub async fn handle_daemon_ws_message(
                 .unwrap_or(app.current_world_index);
 
             let new_index = if direction == "up" {
-                app.calculate_prev_world_from(current)
+                app.calculate_prev_world_from(client_id, current)
             } else {
-                app.calculate_next_world_from(current)
+                app.calculate_next_world_from(client_id, current)
             };
 
             if let Some(idx) = new_index {
@@ -1822,7 +2088,7 @@ pub async fn handle_daemon_ws_message(
 
                     if lines_to_send > 0 {
                         let start = total_lines.saturating_sub(lines_to_send);
-                        let lines: Vec<TimestampedLine> = world.output_lines[start..].iter()
+                        let lines: Vec<TimestampedLine> = world.output_lines.range(start..)
                             .map(|line| {
                                 let ts = line.timestamp
                                     .duration_since(UNIX_EPOCH)
@@ -1848,11 +2114,11 @@ pub async fn handle_daemon_ws_message(
                         });
                     }
 
-                    // Also mark world as seen if it had unseen output
-                    if app.worlds[idx].unseen_lines > 0 {
+                    // Also mark world as seen (and by this client) if it had unseen output
+                    if app.worlds[idx].unseen_lines > 0 || app.ws_client_unseen.get(&client_id).is_some_and(|u| u.contains_key(&idx)) {
                         app.worlds[idx].unseen_lines = 0;
                         app.worlds[idx].first_unseen_at = None;
-                        app.ws_broadcast(WsMessage::UnseenCleared { world_index: idx });
+                        app.ws_mark_client_seen(client_id, idx);
                         app.broadcast_activity();
                     }
                 }
@@ -1860,91 +2126,7 @@ pub async fn handle_daemon_ws_message(
         }
         WsMessage::RequestScrollback { world_index, count, before_seq, after_seq } => {
             // Console client requests scrollback from master
-            if world_index < app.worlds.len() {
-                let world = &app.worlds[world_index];
-
-                // Find lines to send based on before_seq/after_seq
-                let lines: Vec<TimestampedLine> = if let Some(seq) = before_seq {
-                    // Send lines with seq < before_seq (older than what client has)
-                    let eligible: Vec<_> = world.output_lines.iter()
-                        .filter(|l| l.seq < seq)
-                        .collect();
-                    let start = eligible.len().saturating_sub(count);
-                    eligible[start..].iter()
-                        .map(|line| {
-                            let ts = line.timestamp
-                                .duration_since(UNIX_EPOCH)
-                                .map(|d| d.as_secs())
-                                .unwrap_or(0);
-                            TimestampedLine {
-                                text: line.text.clone(),
-                                ts,
-                                gagged: line.gagged,
-                                from_server: line.from_server,
-                                seq: line.seq,
-                                highlight_color: line.highlight_color.clone(),
-                                marked_new: line.marked_new,
-                                from_archive: line.from_archive,
-                            }
-                        })
-                        .collect()
-                } else if let Some(seq) = after_seq {
-                    // Reconnect gap-fill: the client kept its buffer across the
-                    // reconnect and only wants lines newer than the highest seq it
-                    // already has. Oldest-first (unlike before_seq's newest-first
-                    // slice) so the client can append+dedup in order.
-                    let eligible: Vec<_> = world.output_lines.iter()
-                        .filter(|l| l.seq > seq)
-                        .collect();
-                    let take = count.min(eligible.len());
-                    eligible[..take].iter()
-                        .map(|line| {
-                            let ts = line.timestamp
-                                .duration_since(UNIX_EPOCH)
-                                .map(|d| d.as_secs())
-                                .unwrap_or(0);
-                            TimestampedLine {
-                                text: line.text.clone(),
-                                ts,
-                                gagged: line.gagged,
-                                from_server: line.from_server,
-                                seq: line.seq,
-                                highlight_color: line.highlight_color.clone(),
-                                marked_new: line.marked_new,
-                                from_archive: line.from_archive,
-                            }
-                        })
-                        .collect()
-                } else {
-                    // No before_seq/after_seq - send last N lines (backwards compatible)
-                    let total_lines = world.output_lines.len();
-                    let start = total_lines.saturating_sub(count);
-                    world.output_lines[start..].iter()
-                        .map(|line| {
-                            let ts = line.timestamp
-                                .duration_since(UNIX_EPOCH)
-                                .map(|d| d.as_secs())
-                                .unwrap_or(0);
-                            TimestampedLine {
-                                text: line.text.clone(),
-                                ts,
-                                gagged: line.gagged,
-                                from_server: line.from_server,
-                                seq: line.seq,
-                                highlight_color: line.highlight_color.clone(),
-                                marked_new: line.marked_new,
-                                from_archive: line.from_archive,
-                            }
-                        })
-                        .collect()
-                };
-
-                // Works for both directions: before_seq slices the newest `count`
-                // eligible lines (so `lines.len() < count` means we ran out of older
-                // history); after_seq takes `count.min(eligible.len())` (so
-                // `lines.len() < count` means we returned every newer line available,
-                // i.e. the gap is fully closed).
-                let backfill_complete = lines.len() < count;
+            if let Some((lines, backfill_complete)) = app.scrollback_range(world_index, count, before_seq, after_seq) {
                 app.ws_send_to_client(client_id, WsMessage::ScrollbackLines {
                     world_index,
                     lines,
@@ -1976,13 +2158,40 @@ pub async fn handle_daemon_ws_message(
                     password: String::new(),
                     has_password: !world.settings.password.is_empty(),
                     use_ssl: world.settings.use_ssl,
+                    tls_verify: world.settings.tls_verify.name().to_string(),
+                    tls_client_cert: world.settings.tls_client_cert.clone(),
+                    tls_client_key: world.settings.tls_client_key.clone(),
+                    ssh_host: world.settings.ssh_host.clone(),
+                    ssh_user: world.settings.ssh_user.clone(),
+                    ssh_key: world.settings.ssh_key.clone(),
                     log_enabled: world.settings.log_enabled,
+                    log_format: world.settings.log_format.name().to_string(),
+                    log_timestamps: world.settings.log_timestamps.name().to_string(),
+                    log_session_markers: world.settings.log_session_markers,
                     encoding: world.settings.encoding.name().to_string(),
                     auto_connect_type: world.settings.auto_connect_type.name().to_string(),
+                    login_script: world.settings.login_script.clone(),
+                    connect_commands: world.settings.connect_commands.clone(),
+                    input_prefix: world.settings.input_prefix.clone(),
+                    input_suffix: world.settings.input_suffix.clone(),
                     keep_alive_type: world.settings.keep_alive_type.name().to_string(),
                     keep_alive_cmd: world.settings.keep_alive_cmd.clone(),
+                    keep_alive_interval_secs: world.settings.keep_alive_interval_secs,
+                    silence_alert_mins: world.settings.silence_alert_mins,
                     gmcp_packages: world.settings.gmcp_packages.clone(),
                     auto_reconnect_secs: world.settings.auto_reconnect_display(),
+                    numpad_walk: world.settings.numpad_walk,
+                    timestamps: world.settings.timestamps.map_or("", |m| m.name()).to_string(),
+                    notify_muted: world.settings.notify_muted,
+                    notify_sound: world.settings.notify_sound.clone(),
+                    inline_images: world.settings.inline_images,
+                    bell: world.settings.bell.name().to_string(),
+                    emoji_shortcodes: world.settings.emoji_shortcodes,
+                    autocorrect: world.settings.autocorrect,
+                    palette: world.settings.palette.clone(),
+                    tint: world.settings.tint.clone(),
+                    spell_language: world.settings.spell_language.clone(),
+                    macros: world.settings.macros.clone(),
                 },
                 last_send_secs: None,
                 last_recv_secs: None,
@@ -1994,6 +2203,8 @@ pub async fn handle_daemon_ws_message(
                 gmcp_user_enabled: world.gmcp_user_enabled,
                 total_output_lines: 0,
                 pending_count: 0,
+                bell_count: 0,
+                resumed: false,
             };
             app.ws_broadcast(WsMessage::WorldAdded { world: Box::new(world_state) });
             let _ = persistence::save_settings(app);
@@ -2018,23 +2229,60 @@ pub async fn handle_daemon_ws_message(
                 let _ = persistence::save_settings(app);
             }
         }
-        WsMessage::UpdateWorldSettings { world_index, name, hostname, port, user, password, use_ssl, log_enabled, encoding, auto_login, keep_alive_type, keep_alive_cmd, gmcp_packages, auto_reconnect_secs } => {
+        WsMessage::TestActionPatterns { match_type, patterns, command, sample } => {
+            let reply = WsMessage::action_patterns_tested(&match_type, &patterns, &command, &sample);
+            app.ws_send_to_client(client_id, reply);
+        }
+        WsMessage::MoveWorld { from, to } if app.reorder_world(from, to) => {
+            app.ws_broadcast(WsMessage::WorldMoved { from, to });
+            let _ = persistence::save_settings(app);
+        }
+        WsMessage::UpdateWorldSettings { world_index, name, hostname, port, user, password, use_ssl, log_enabled, encoding, auto_login, login_script, connect_commands, input_prefix, input_suffix, keep_alive_type, keep_alive_cmd, keep_alive_interval_secs, silence_alert_mins, gmcp_packages, auto_reconnect_secs, log_format, log_timestamps, log_session_markers, tls_verify, tls_client_cert, tls_client_key, ssh_host, ssh_user, ssh_key } => {
             if world_index < app.worlds.len() {
+                persistence::rename_locked_credentials(&app.worlds[world_index].name, &name);
                 app.worlds[world_index].name = name.clone();
                 app.worlds[world_index].settings.hostname = hostname.clone();
                 app.worlds[world_index].settings.port = port.clone();
                 app.worlds[world_index].settings.user = user.clone();
                 app.worlds[world_index].settings.password = password.clone();
                 app.worlds[world_index].settings.use_ssl = use_ssl;
+                if let Some(verify) = crate::platform::TlsVerify::from_name(&tls_verify) {
+                    app.worlds[world_index].settings.tls_verify = verify;
+                }
+                app.worlds[world_index].settings.tls_client_cert = tls_client_cert.clone();
+                app.worlds[world_index].settings.tls_client_key = tls_client_key.clone();
+                app.worlds[world_index].settings.ssh_host = ssh_host.clone();
+                app.worlds[world_index].settings.ssh_user = ssh_user.clone();
+                app.worlds[world_index].settings.ssh_key = ssh_key.clone();
                 app.worlds[world_index].settings.log_enabled = log_enabled;
-                app.worlds[world_index].settings.encoding = match encoding.as_str() {
-                    "latin1" => Encoding::Latin1,
-                    "fansi" => Encoding::Fansi,
-                    _ => Encoding::Utf8,
-                };
+                if let Some(format) = crate::logging::LogFormat::from_name(&log_format) {
+                    app.worlds[world_index].settings.log_format = format;
+                }
+                if let Some(stamps) = crate::logging::LogTimestamps::from_name(&log_timestamps) {
+                    app.worlds[world_index].settings.log_timestamps = stamps;
+                }
+                if let Some(markers) = log_session_markers {
+                    app.worlds[world_index].settings.log_session_markers = markers;
+                }
+                app.worlds[world_index].settings.encoding = Encoding::from_name(&encoding);
+                app.worlds[world_index].sync_send_encoding();
                 app.worlds[world_index].settings.auto_connect_type = AutoConnectType::from_name(&auto_login);
+                if let Some(script) = login_script {
+                    app.worlds[world_index].settings.login_script = script;
+                }
+                if let Some(commands) = connect_commands {
+                    app.worlds[world_index].settings.connect_commands = commands;
+                }
+                if let Some(prefix) = input_prefix {
+                    app.worlds[world_index].settings.input_prefix = prefix;
+                }
+                if let Some(suffix) = input_suffix {
+                    app.worlds[world_index].settings.input_suffix = suffix;
+                }
                 app.worlds[world_index].settings.keep_alive_type = KeepAliveType::from_name(&keep_alive_type);
                 app.worlds[world_index].settings.keep_alive_cmd = keep_alive_cmd.clone();
+                app.worlds[world_index].settings.keep_alive_interval_secs = keep_alive_interval_secs.max(crate::MIN_KEEPALIVE_SECS);
+                app.worlds[world_index].settings.silence_alert_mins = silence_alert_mins;
                 app.worlds[world_index].settings.gmcp_packages = gmcp_packages.clone();
                 let (ar_secs, ar_on_web) = crate::WorldSettings::parse_auto_reconnect(&auto_reconnect_secs);
                 app.worlds[world_index].settings.auto_reconnect_secs = ar_secs;
@@ -2045,23 +2293,37 @@ pub async fn handle_daemon_ws_message(
                     has_password: !password.is_empty(),
                     password: String::new(),
                     use_ssl, log_enabled, encoding,
+                    tls_verify: app.worlds[world_index].settings.tls_verify.name().to_string(),
+                    tls_client_cert, tls_client_key,
+                    ssh_host, ssh_user, ssh_key,
+                    log_format: app.worlds[world_index].settings.log_format.name().to_string(),
+                    log_timestamps: app.worlds[world_index].settings.log_timestamps.name().to_string(),
+                    log_session_markers: app.worlds[world_index].settings.log_session_markers,
                     auto_connect_type: auto_login,
-                    keep_alive_type, keep_alive_cmd, gmcp_packages,
+                    login_script: app.worlds[world_index].settings.login_script.clone(),
+                    connect_commands: app.worlds[world_index].settings.connect_commands.clone(),
+                    input_prefix: app.worlds[world_index].settings.input_prefix.clone(),
+                    input_suffix: app.worlds[world_index].settings.input_suffix.clone(),
+                    keep_alive_type, keep_alive_cmd, keep_alive_interval_secs, silence_alert_mins, gmcp_packages,
                     auto_reconnect_secs,
+                    numpad_walk: app.worlds[world_index].settings.numpad_walk,
+                    timestamps: app.worlds[world_index].settings.timestamps.map_or("", |m| m.name()).to_string(),
+                    notify_muted: app.worlds[world_index].settings.notify_muted,
+                    notify_sound: app.worlds[world_index].settings.notify_sound.clone(),
+                    inline_images: app.worlds[world_index].settings.inline_images,
+                    bell: app.worlds[world_index].settings.bell.name().to_string(),
+                    emoji_shortcodes: app.worlds[world_index].settings.emoji_shortcodes,
+                    autocorrect: app.worlds[world_index].settings.autocorrect,
+                    palette: app.worlds[world_index].settings.palette.clone(),
+                    tint: app.worlds[world_index].settings.tint.clone(),
+                    spell_language: app.worlds[world_index].settings.spell_language.clone(),
+                    macros: app.worlds[world_index].settings.macros.clone(),
                 };
                 app.ws_broadcast(WsMessage::WorldSettingsUpdated { world_index, settings: settings_msg, name });
             }
         }
         WsMessage::CalculateNextWorld { current_index } => {
-            let world_info: Vec<crate::util::WorldSwitchInfo> = app.worlds.iter()
-                .map(|w| crate::util::WorldSwitchInfo {
-                    name: w.name.clone(),
-                    connected: w.connected,
-                    unseen_lines: w.unseen_lines,
-                    pending_lines: w.pending_lines.len(),
-                    first_unseen_at: w.first_unseen_at,
-                })
-                .collect();
+            let world_info = app.client_world_switch_info(client_id);
             let next_idx = crate::util::calculate_next_world(
                 &world_info, current_index, app.settings.world_switch_mode,
             );
@@ -2070,15 +2332,7 @@ pub async fn handle_daemon_ws_message(
             }
         }
         WsMessage::CalculatePrevWorld { current_index } => {
-            let world_info: Vec<crate::util::WorldSwitchInfo> = app.worlds.iter()
-                .map(|w| crate::util::WorldSwitchInfo {
-                    name: w.name.clone(),
-                    connected: w.connected,
-                    unseen_lines: w.unseen_lines,
-                    pending_lines: w.pending_lines.len(),
-                    first_unseen_at: w.first_unseen_at,
-                })
-                .collect();
+            let world_info = app.client_world_switch_info(client_id);
             let prev_idx = crate::util::calculate_prev_world(
                 &world_info, current_index, app.settings.world_switch_mode,
             );
@@ -2101,12 +2355,8 @@ pub async fn handle_daemon_ws_message(
             }
             // Fall back to any world with unseen lines
             if oldest_idx.is_none() {
-                for (idx, world) in app.worlds.iter().enumerate() {
-                    if idx != current_index && world.unseen_lines > 0 {
-                        oldest_idx = Some(idx);
-                        break;
-                    }
-                }
+                oldest_idx = (0..app.worlds.len())
+                    .find(|&idx| idx != current_index && app.client_unseen(client_id, idx) > 0);
             }
             if let Some(ref ws) = app.ws_server {
                 ws.send_to_client(client_id, WsMessage::CalculatedWorld { index: oldest_idx });
@@ -2278,8 +2528,9 @@ keep_alive_type=Generic
     let gate = SecurityGate {
         allow_list: server.allow_list.clone(),
         whitelisted_host: server.whitelisted_host.clone(),
-        auth_key: app.ws_auth_key_shared.clone(),
+        auth_keys: app.ws_auth_keys_shared.clone(),
         web_path: app.settings.web_path.clone(),
+        trusted_proxies: app.web_trusted_proxies(),
         ban_list: app.ban_list.clone(),
     };
 
@@ -2288,7 +2539,7 @@ keep_alive_type=Generic
 
     // Start unified HTTP+WS server
     {
-        let mut http_server = HttpServer::new(app.settings.http_port);
+        let mut http_server = HttpServer::new(app.settings.http_port, app.web_listen());
         match start_http_server(&mut http_server, Some(ws_state.clone()), app.ban_list.clone(), app.gui_theme_colors().to_css_vars(), None, gate.clone()).await {
             Ok(()) => {
                 println!("HTTP+WS: http://0.0.0.0:{}", app.settings.http_port);
@@ -2420,7 +2671,7 @@ keep_alive_type=Generic
                         let already_connected = app.user_connections.get(&key).map(|c| c.connected).unwrap_or(false);
 
                         if world_index < app.worlds.len() && !already_connected {
-                            let settings = app.worlds[world_index].settings.clone();
+                            let settings = app.login_settings(world_index);
                             let world_name = app.worlds[world_index].name.clone();
 
                             // Check if world has connection settings
@@ -2597,6 +2848,21 @@ keep_alive_type=Generic
 
 /// Connect to a world for a specific user in multiuser mode
 /// Returns the command sender if successful
+/// Send the auto-login's opening lines and the On Connect commands after a short
+/// pause for the server's greeting
+fn spawn_login(cmd_tx: &mpsc::Sender<WriteCommand>, login: Vec<String>, commands: Vec<String>) {
+    if login.is_empty() && commands.is_empty() {
+        return;
+    }
+    let tx = cmd_tx.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        for cmd in login.into_iter().chain(commands) {
+            let _ = tx.send(WriteCommand::Text(cmd)).await;
+        }
+    });
+}
+
 pub async fn connect_multiuser_world(
     world_index: usize,
     username: String,
@@ -2610,22 +2876,31 @@ pub async fn connect_multiuser_world(
     if host.is_empty() || port.is_empty() {
         return None;
     }
+    let tls_identity = if use_ssl {
+        crate::platform::ClientIdentity::load(&settings.tls_client_cert, &settings.tls_client_key).ok()?
+    } else {
+        None
+    };
 
-    match TcpStream::connect(format!("{}:{}", host, port)).await {
-        Ok(tcp_stream) => {
+    // A world behind an SSH bastion is reached through the tunnel, which carries its TLS too
+    let transport = if settings.ssh_host.is_empty() {
+        crate::happy_eyeballs::connect(host, port).await.ok().map(|(tcp_stream, _)| MudTransport::Direct(tcp_stream))
+    } else {
+        crate::ssh::connect_world(settings, tls_identity.as_ref()).await.ok().map(|(r, w)| MudTransport::Tunneled(r, w))
+    };
+    let connected = match transport {
+        Some(MudTransport::Tunneled(r, w)) => Some((r, w)),
+        Some(MudTransport::Direct(tcp_stream)) => {
             let _ = tcp_stream.set_nodelay(true);
 
             // Enable TCP keepalive to detect dead connections faster
             enable_tcp_keepalive(&tcp_stream);
 
             // Handle SSL if needed
-            let (mut read_half, mut write_half): (StreamReader, StreamWriter) = if use_ssl {
+            let (read_half, write_half): (StreamReader, StreamWriter) = if use_ssl {
                 #[cfg(feature = "native-tls-backend")]
                 {
-                    let connector = match native_tls::TlsConnector::builder()
-                        .danger_accept_invalid_certs(true)
-                        .build()
-                    {
+                    let connector = match crate::platform::mud_native_tls_connector(settings.tls_verify, tls_identity.as_ref()) {
                         Ok(c) => c,
                         Err(_) => return None,
                     };
@@ -2634,7 +2909,7 @@ pub async fn connect_multiuser_world(
                     match connector.connect(host, tcp_stream).await {
                         Ok(tls_stream) => {
                             let peer_cert = tls_stream.get_ref().peer_certificate().ok().flatten();
-                            if crate::platform::check_native_tls_peer_pin(&format!("{}:{}", host, port), peer_cert).is_err() {
+                            if crate::platform::check_native_tls_peer_pin(&format!("{}:{}", host, port), settings.tls_verify, peer_cert).is_err() {
                                 return None;
                             }
                             let (r, w) = tokio::io::split(tls_stream);
@@ -2646,17 +2921,13 @@ pub async fn connect_multiuser_world(
 
                 #[cfg(feature = "rustls-backend")]
                 {
-                    use rustls::RootCertStore;
                     use tokio_rustls::TlsConnector;
                     use rustls::pki_types::ServerName;
 
-                    let mut root_store = RootCertStore::empty();
-                    root_store.roots = webpki_roots::TLS_SERVER_ROOTS.to_vec();
-
-                    let config = rustls::ClientConfig::builder()
-                        .dangerous()
-                        .with_custom_certificate_verifier(Arc::new(crate::platform::danger_rustls::TofuVerifier::new(format!("{}:{}", host, port))))
-                        .with_no_client_auth();
+                    let config = match crate::platform::danger_rustls::mud_client_config(&format!("{}:{}", host, port), settings.tls_verify, tls_identity.as_ref()) {
+                        Ok(c) => c,
+                        Err(_) => return None,
+                    };
 
                     let connector = TlsConnector::from(Arc::new(config));
                     let server_name = match ServerName::try_from(host.clone()) {
@@ -2681,21 +2952,17 @@ pub async fn connect_multiuser_world(
                 let (r, w) = tcp_stream.into_split();
                 (StreamReader::Plain(r), StreamWriter::Plain(w))
             };
-
+            Some((read_half, write_half))
+        }
+        None => None,
+    };
+    match connected {
+        Some((mut read_half, mut write_half)) => {
             let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
 
             // Send auto-login if configured
-            let user = settings.user.clone();
-            let password = settings.password.clone();
-            let auto_connect_type = settings.auto_connect_type;
-            if !user.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                let tx = cmd_tx.clone();
-                tokio::spawn(async move {
-                    tokio::time::sleep(Duration::from_millis(500)).await;
-                    let connect_cmd = format!("connect {} {}", user, password);
-                    let _ = tx.send(WriteCommand::Text(connect_cmd)).await;
-                });
-            }
+            let login = if settings.user.is_empty() { Vec::new() } else { settings.login_lines(&settings.user, &settings.password) };
+            spawn_login(&cmd_tx, login, settings.connect_commands_at_login(false, !settings.user.is_empty() && !settings.password.is_empty()));
 
             // Clone for reader task
             let telnet_tx = cmd_tx.clone();
@@ -2795,11 +3062,12 @@ pub async fn connect_multiuser_world(
             });
 
             // Spawn writer task
+            let encoding = settings.encoding;
             tokio::spawn(async move {
                 while let Some(cmd) = cmd_rx.recv().await {
                     match cmd {
                         WriteCommand::Text(text) => {
-                            let bytes = format!("{}\r\n", text).into_bytes();
+                            let bytes = encoding.line_bytes(&text);
                             if write_half.write_all(&bytes).await.is_err() {
                                 break;
                             }
@@ -2822,12 +3090,13 @@ pub async fn connect_multiuser_world(
 
             Some(cmd_tx)
         }
-        Err(_) => None,
+        None => None,
     }
 }
 
 /// Connect a world in daemon mode (non-multiuser)
 /// Returns (cmd_tx, socket_fd, is_tls, proxy_pid, proxy_socket_path) on success
+#[allow(clippy::too_many_arguments)]
 pub async fn connect_daemon_world(
     _world_index: usize,
     world_name: String,
@@ -2836,6 +3105,7 @@ pub async fn connect_daemon_world(
     connection_id: u64,
     skip_auto_login: bool,
     tls_proxy_enabled: bool,
+    send_encoding: SharedEncoding,
 ) -> Option<(mpsc::Sender<WriteCommand>, Option<SocketFd>, bool, Option<u32>, Option<std::path::PathBuf>)> {
     #[cfg(target_os = "android")]
     let _ = tls_proxy_enabled;
@@ -2846,13 +3116,18 @@ pub async fn connect_daemon_world(
     if host.is_empty() || port.is_empty() {
         return None;
     }
+    let tls_identity = if use_ssl {
+        crate::platform::ClientIdentity::load(&settings.tls_client_cert, &settings.tls_client_key).ok()?
+    } else {
+        None
+    };
 
     // TLS proxy path — spawn a separate proxy process that holds the TLS connection
     // so it survives hot reload. Platform-specific IPC: Unix sockets on Unix,
     // Named Pipes on Windows.
     #[cfg(all(unix, not(target_os = "android")))]
-    if use_ssl && tls_proxy_enabled {
-        if let Ok((proxy_pid, socket_path)) = spawn_tls_proxy(&world_name, host, port) {
+    if use_ssl && tls_proxy_enabled && settings.ssh_host.is_empty() {
+        if let Ok((proxy_pid, socket_path)) = spawn_tls_proxy(&world_name, host, port, settings.tls_verify, (&settings.tls_client_cert, &settings.tls_client_key)) {
             let mut connected = false;
             for attempt in 0..20 {
                 match tokio::net::UnixStream::connect(&socket_path).await {
@@ -2862,16 +3137,9 @@ pub async fn connect_daemon_world(
                         let mut write_half = StreamWriter::Proxy(w);
                         let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                         if !skip_auto_login {
-                            let user = settings.user.clone();
-                            let password = settings.password.clone();
-                            let auto_connect_type = settings.auto_connect_type;
-                            if !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                                let tx = cmd_tx.clone();
-                                tokio::spawn(async move {
-                                    tokio::time::sleep(Duration::from_millis(500)).await;
-                                    let _ = tx.send(WriteCommand::Text(format!("connect {} {}", user, password))).await;
-                                });
-                            }
+                            let has_credentials = !settings.user.is_empty() && !settings.password.is_empty();
+                            let login = if has_credentials { settings.login_lines(&settings.user, &settings.password) } else { Vec::new() };
+                            spawn_login(&cmd_tx, login, settings.connect_commands_at_login(false, has_credentials));
                         }
                         let telnet_tx = cmd_tx.clone();
                         let event_tx_read = event_tx.clone();
@@ -2899,6 +3167,7 @@ pub async fn connect_daemon_world(
                                             let result = process_telnet(&to_send);
                                             if !result.responses.is_empty() { let _ = telnet_tx.send(WriteCommand::Raw(result.responses)).await; }
                                             if result.telnet_detected { let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name_read.clone())).await; }
+                                            if let Some(on) = result.server_echo { let _ = event_tx_read.send(AppEvent::ServerEcho(world_name_read.clone(), on)).await; }
                                             if result.naws_requested { let _ = event_tx_read.send(AppEvent::NawsRequested(world_name_read.clone())).await; }
                                             if result.gmcp_negotiated { let _ = event_tx_read.send(AppEvent::GmcpNegotiated(world_name_read.clone())).await; }
                                             if result.msdp_negotiated { let _ = event_tx_read.send(AppEvent::MsdpNegotiated(world_name_read.clone())).await; }
@@ -2914,7 +3183,7 @@ pub async fn connect_daemon_world(
                         });
                         tokio::spawn(async move {
                             while let Some(cmd) = cmd_rx.recv().await {
-                                let bytes = match &cmd { WriteCommand::Text(t) => { let mut b = t.as_bytes().to_vec(); b.extend_from_slice(b"\r\n"); b } WriteCommand::Raw(r) => r.clone(), WriteCommand::Shutdown => break };
+                                let bytes = match &cmd { WriteCommand::Text(t) => send_encoding.get().line_bytes(t), WriteCommand::Raw(r) => r.clone(), WriteCommand::Shutdown => break };
                                 if tokio::io::AsyncWriteExt::write_all(&mut write_half, &bytes).await.is_err() { break; }
                             }
                         });
@@ -2935,8 +3204,8 @@ pub async fn connect_daemon_world(
     }
 
     #[cfg(windows)]
-    if use_ssl && tls_proxy_enabled {
-        if let Ok((proxy_pid, pipe_path)) = spawn_tls_proxy(&world_name, host, port) {
+    if use_ssl && tls_proxy_enabled && settings.ssh_host.is_empty() {
+        if let Ok((proxy_pid, pipe_path)) = spawn_tls_proxy(&world_name, host, port, settings.tls_verify, (&settings.tls_client_cert, &settings.tls_client_key)) {
             match connect_to_proxy_pipe(&pipe_path, 10).await {
                 Some(pipe_client) => {
                     let (r, w) = tokio::io::split(pipe_client);
@@ -2944,16 +3213,9 @@ pub async fn connect_daemon_world(
                     let mut write_half = StreamWriter::NamedPipeProxy(w);
                         let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                         if !skip_auto_login {
-                            let user = settings.user.clone();
-                            let password = settings.password.clone();
-                            let auto_connect_type = settings.auto_connect_type;
-                            if !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                                let tx = cmd_tx.clone();
-                                tokio::spawn(async move {
-                                    tokio::time::sleep(Duration::from_millis(500)).await;
-                                    let _ = tx.send(WriteCommand::Text(format!("connect {} {}", user, password))).await;
-                                });
-                            }
+                            let has_credentials = !settings.user.is_empty() && !settings.password.is_empty();
+                            let login = if has_credentials { settings.login_lines(&settings.user, &settings.password) } else { Vec::new() };
+                            spawn_login(&cmd_tx, login, settings.connect_commands_at_login(false, has_credentials));
                         }
                         let telnet_tx = cmd_tx.clone();
                         let event_tx_read = event_tx.clone();
@@ -2989,6 +3251,9 @@ pub async fn connect_daemon_world(
                                             if result.telnet_detected {
                                                 let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name_read.clone())).await;
                                             }
+                                            if let Some(on) = result.server_echo {
+                                                let _ = event_tx_read.send(AppEvent::ServerEcho(world_name_read.clone(), on)).await;
+                                            }
                                             if result.naws_requested {
                                                 let _ = event_tx_read.send(AppEvent::NawsRequested(world_name_read.clone())).await;
                                             }
@@ -3022,7 +3287,7 @@ pub async fn connect_daemon_world(
                         tokio::spawn(async move {
                             while let Some(cmd) = cmd_rx.recv().await {
                                 let bytes = match &cmd {
-                                    WriteCommand::Text(t) => { let mut b = t.as_bytes().to_vec(); b.extend_from_slice(b"\r\n"); b }
+                                    WriteCommand::Text(t) => send_encoding.get().line_bytes(t),
                                     WriteCommand::Raw(r) => r.clone(),
                                     WriteCommand::Shutdown => break,
                                 };
@@ -3039,8 +3304,15 @@ pub async fn connect_daemon_world(
         // Fall through to direct TLS
     }
 
-    match TcpStream::connect(format!("{}:{}", host, port)).await {
-        Ok(tcp_stream) => {
+    // A world behind an SSH bastion is reached through the tunnel, which carries its TLS too
+    let transport = if settings.ssh_host.is_empty() {
+        crate::happy_eyeballs::connect(host, port).await.ok().map(|(tcp_stream, _)| MudTransport::Direct(tcp_stream))
+    } else {
+        crate::ssh::connect_world(settings, tls_identity.as_ref()).await.ok().map(|(r, w)| MudTransport::Tunneled(r, w))
+    };
+    let connected = match transport {
+        Some(MudTransport::Tunneled(r, w)) => Some((None, true, r, w)),
+        Some(MudTransport::Direct(tcp_stream)) => {
             let _ = tcp_stream.set_nodelay(true);
 
             // Store the socket fd for hot reload (before splitting)
@@ -3062,14 +3334,11 @@ pub async fn connect_daemon_world(
 
             // Handle SSL if needed
             let is_tls;
-            let (mut read_half, mut write_half): (StreamReader, StreamWriter) = if use_ssl {
+            let (read_half, write_half): (StreamReader, StreamWriter) = if use_ssl {
                 is_tls = true;
                 #[cfg(feature = "native-tls-backend")]
                 {
-                    let connector = match native_tls::TlsConnector::builder()
-                        .danger_accept_invalid_certs(true)
-                        .build()
-                    {
+                    let connector = match crate::platform::mud_native_tls_connector(settings.tls_verify, tls_identity.as_ref()) {
                         Ok(c) => c,
                         Err(_) => return None,
                     };
@@ -3078,7 +3347,7 @@ pub async fn connect_daemon_world(
                     match connector.connect(host, tcp_stream).await {
                         Ok(tls_stream) => {
                             let peer_cert = tls_stream.get_ref().peer_certificate().ok().flatten();
-                            if crate::platform::check_native_tls_peer_pin(&format!("{}:{}", host, port), peer_cert).is_err() {
+                            if crate::platform::check_native_tls_peer_pin(&format!("{}:{}", host, port), settings.tls_verify, peer_cert).is_err() {
                                 return None;
                             }
                             let (r, w) = tokio::io::split(tls_stream);
@@ -3090,17 +3359,13 @@ pub async fn connect_daemon_world(
 
                 #[cfg(feature = "rustls-backend")]
                 {
-                    use rustls::RootCertStore;
                     use tokio_rustls::TlsConnector;
                     use rustls::pki_types::ServerName;
 
-                    let mut root_store = RootCertStore::empty();
-                    root_store.roots = webpki_roots::TLS_SERVER_ROOTS.to_vec();
-
-                    let config = rustls::ClientConfig::builder()
-                        .dangerous()
-                        .with_custom_certificate_verifier(Arc::new(crate::platform::danger_rustls::TofuVerifier::new(format!("{}:{}", host, port))))
-                        .with_no_client_auth();
+                    let config = match crate::platform::danger_rustls::mud_client_config(&format!("{}:{}", host, port), settings.tls_verify, tls_identity.as_ref()) {
+                        Ok(c) => c,
+                        Err(_) => return None,
+                    };
 
                     let connector = TlsConnector::from(Arc::new(config));
                     let server_name = match ServerName::try_from(host.clone()) {
@@ -3126,7 +3391,12 @@ pub async fn connect_daemon_world(
                 let (r, w) = tcp_stream.into_split();
                 (StreamReader::Plain(r), StreamWriter::Plain(w))
             };
-
+            Some((socket_fd, is_tls, read_half, write_half))
+        }
+        None => None,
+    };
+    match connected {
+        Some((socket_fd, is_tls, mut read_half, mut write_half)) => {
             // For TLS, socket_fd should be None (can't preserve across reload)
             let final_socket_fd = if is_tls { None } else { socket_fd };
 
@@ -3134,17 +3404,9 @@ pub async fn connect_daemon_world(
 
             // Send auto-login if configured (skip if /worlds -l was used)
             if !skip_auto_login {
-                let user = settings.user.clone();
-                let password = settings.password.clone();
-                let auto_connect_type = settings.auto_connect_type;
-                if !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                    let tx = cmd_tx.clone();
-                    tokio::spawn(async move {
-                        tokio::time::sleep(Duration::from_millis(500)).await;
-                        let connect_cmd = format!("connect {} {}", user, password);
-                        let _ = tx.send(WriteCommand::Text(connect_cmd)).await;
-                    });
-                }
+                let has_credentials = !settings.user.is_empty() && !settings.password.is_empty();
+                let login = if has_credentials { settings.login_lines(&settings.user, &settings.password) } else { Vec::new() };
+                spawn_login(&cmd_tx, login, settings.connect_commands_at_login(false, has_credentials));
             }
 
             // Clone for reader task
@@ -3172,6 +3434,9 @@ pub async fn connect_daemon_world(
                                 if result.telnet_detected {
                                     let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name_read.clone())).await;
                                 }
+                                if let Some(on) = result.server_echo {
+                                    let _ = event_tx_read.send(AppEvent::ServerEcho(world_name_read.clone(), on)).await;
+                                }
                                 if let Some(ref charsets) = result.charset_request {
                                     let _ = event_tx_read.send(AppEvent::CharsetRequested(world_name_read.clone(), charsets.clone())).await;
                                 }
@@ -3238,6 +3503,9 @@ pub async fn connect_daemon_world(
                                 if result.telnet_detected {
                                     let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name_read.clone())).await;
                                 }
+                                if let Some(on) = result.server_echo {
+                                    let _ = event_tx_read.send(AppEvent::ServerEcho(world_name_read.clone(), on)).await;
+                                }
                                 if result.naws_requested {
                                     let _ = event_tx_read.send(AppEvent::NawsRequested(world_name_read.clone())).await;
                                 }
@@ -3285,7 +3553,7 @@ pub async fn connect_daemon_world(
                 while let Some(cmd) = cmd_rx.recv().await {
                     match cmd {
                         WriteCommand::Text(text) => {
-                            let bytes = format!("{}\r\n", text).into_bytes();
+                            let bytes = send_encoding.get().line_bytes(&text);
                             if write_half.write_all(&bytes).await.is_err() {
                                 break;
                             }
@@ -3307,7 +3575,7 @@ pub async fn connect_daemon_world(
 
             Some((cmd_tx, final_socket_fd, is_tls, None, None))
         }
-        Err(_) => None,
+        None => None,
     }
 }
 
@@ -3430,13 +3698,40 @@ pub fn build_multiuser_initial_state(app: &App, username: &str) -> WsMessage {
                     password: String::new(),
                     has_password: is_owner && !world.settings.password.is_empty(),
                     use_ssl: world.settings.use_ssl,
+                    tls_verify: world.settings.tls_verify.name().to_string(),
+                    tls_client_cert: if is_owner { world.settings.tls_client_cert.clone() } else { String::new() },
+                    tls_client_key: if is_owner { world.settings.tls_client_key.clone() } else { String::new() },
+                    ssh_host: if is_owner { world.settings.ssh_host.clone() } else { String::new() },
+                    ssh_user: if is_owner { world.settings.ssh_user.clone() } else { String::new() },
+                    ssh_key: if is_owner { world.settings.ssh_key.clone() } else { String::new() },
                     log_enabled: world.settings.log_enabled,
+                    log_format: world.settings.log_format.name().to_string(),
+                    log_timestamps: world.settings.log_timestamps.name().to_string(),
+                    log_session_markers: world.settings.log_session_markers,
                     encoding: world.settings.encoding.name().to_string(),
                     auto_connect_type: world.settings.auto_connect_type.name().to_string(),
+                    login_script: if is_owner { world.settings.login_script.clone() } else { String::new() },
+                    connect_commands: if is_owner { world.settings.connect_commands.clone() } else { String::new() },
+                    input_prefix: world.settings.input_prefix.clone(),
+                    input_suffix: world.settings.input_suffix.clone(),
                     keep_alive_type: world.settings.keep_alive_type.name().to_string(),
                     keep_alive_cmd: if is_owner { world.settings.keep_alive_cmd.clone() } else { String::new() },
+                    keep_alive_interval_secs: world.settings.keep_alive_interval_secs,
+                    silence_alert_mins: world.settings.silence_alert_mins,
                     gmcp_packages: if is_owner { world.settings.gmcp_packages.clone() } else { String::new() },
                     auto_reconnect_secs: world.settings.auto_reconnect_display(),
+                    numpad_walk: world.settings.numpad_walk,
+                    timestamps: world.settings.timestamps.map_or("", |m| m.name()).to_string(),
+                    notify_muted: world.settings.notify_muted,
+                    notify_sound: world.settings.notify_sound.clone(),
+                    inline_images: world.settings.inline_images,
+                    bell: world.settings.bell.name().to_string(),
+                    emoji_shortcodes: world.settings.emoji_shortcodes,
+                    autocorrect: world.settings.autocorrect,
+                    palette: world.settings.palette.clone(),
+                    tint: world.settings.tint.clone(),
+                    spell_language: world.settings.spell_language.clone(),
+                    macros: world.settings.macros.clone(),
                 },
                 last_send_secs: last_send.map(|t| t.elapsed().as_secs()),
                 last_recv_secs: last_recv.map(|t| t.elapsed().as_secs()),
@@ -3448,6 +3743,8 @@ pub fn build_multiuser_initial_state(app: &App, username: &str) -> WsMessage {
                 gmcp_user_enabled: world.gmcp_user_enabled,
                 total_output_lines: world.output_lines.len(),
                 pending_count: world.pending_lines.len(),
+                bell_count: world.bell_count,
+                resumed: false,
             }
         }).collect();
 
@@ -3477,6 +3774,7 @@ pub fn build_multiuser_initial_state(app: &App, username: &str) -> WsMessage {
         current_world_index,
         actions,
         splash_lines,
+        emoji_names: crate::encoding::emoji_shortcodes_matching("").into_iter().map(str::to_string).collect(),
     }
 }
 
@@ -3751,7 +4049,7 @@ pub async fn handle_multiuser_ws_message(
             }
         }
         // Reject world editing in multiuser mode
-        WsMessage::UpdateWorldSettings { .. } | WsMessage::DeleteWorld { .. } | WsMessage::CreateWorld { .. } => {
+        WsMessage::UpdateWorldSettings { .. } | WsMessage::DeleteWorld { .. } | WsMessage::CreateWorld { .. } | WsMessage::MoveWorld { .. } => {
             // Silently reject - users can't edit worlds in multiuser mode
         }
         WsMessage::ReportSeqMismatch { world_index, expected_seq_gt, actual_seq, line_text, source } => {
@@ -3826,6 +4124,7 @@ mod change_password_tests {
             connected_at: std::time::Instant::now(),
             last_activity: std::time::Instant::now(),
             paused: false,
+            viewer: false,
         });
         rx
     }
@@ -0,0 +1,65 @@
+//! Desktop notifications for the console (requires the `desktop-notify` feature).
+//!
+//! `/notify <message>` (typed, or run by an action) shows a desktop notification
+//! from the console as well as sending it to web/mobile clients. The console also
+//! shows one for the first line of new output in each world while the terminal
+//! window is unfocused, when Notify Activity is on in /setup. That relies on the
+//! terminal's focus reporting (tmux needs `set -g focus-events on`); a terminal
+//! that never reports losing focus never gets activity notifications.
+//!
+//! `/notify -w<world> mute` silences a world's notifications of both kinds. The
+//! remote console shows the server's /notify notifications on its own desktop; a
+//! daemon has no desktop of its own and shows none.
+
+/// Most characters of an output line shown in an activity notification
+const BODY_MAX: usize = 200;
+
+/// Whether this build can show desktop notifications
+pub const AVAILABLE: bool = cfg!(feature = "desktop-notify");
+
+/// Show a desktop notification. Runs on its own thread, since delivery (D-Bus on
+/// Linux) can block; failures (no notification daemon, headless session) are
+/// ignored. Tests never reach the desktop.
+#[cfg(all(feature = "desktop-notify", not(test)))]
+pub fn show(title: &str, body: &str) {
+    let title = title.to_string();
+    let body = body.to_string();
+    std::thread::spawn(move || {
+        let _ = notify_rust::Notification::new()
+            .appname("Clay")
+            .summary(&title)
+            .body(&body)
+            .show();
+    });
+}
+
+#[cfg(any(not(feature = "desktop-notify"), test))]
+pub fn show(_title: &str, _body: &str) {}
+
+/// Body of an activity notification: the last non-blank line of `text`, without
+/// ANSI codes, cut to BODY_MAX characters (None when every line is blank)
+pub fn activity_body(text: &str) -> Option<String> {
+    let line = text
+        .lines()
+        .rev()
+        .map(|l| crate::util::strip_ansi_codes(l).trim().to_string())
+        .find(|l| !l.is_empty())?;
+    if line.chars().count() > BODY_MAX {
+        Some(format!("{}...", line.chars().take(BODY_MAX - 3).collect::<String>()))
+    } else {
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity_body() {
+        assert_eq!(activity_body("\x1b[1;33mBob says, \"hi\"\x1b[0m\n  \n").as_deref(), Some("Bob says, \"hi\""));
+        assert_eq!(activity_body(" \n\x1b[0m\n"), None);
+        let long = activity_body(&"x".repeat(500)).unwrap();
+        assert!(long.ends_with("...") && long.chars().count() == BODY_MAX);
+    }
+}
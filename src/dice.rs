@@ -0,0 +1,339 @@
+//! Arithmetic and dice expressions for `/calc`, `/roll` and `$[...]` in action
+//! commands.
+//!
+//! An expression takes `+ - * / % ^`, parentheses, decimals and dice terms.
+//! `NdM` rolls N M-sided dice (N defaults to 1, and `d%` is a d100). A `khK` or
+//! `klK` suffix keeps only the K highest or lowest dice, so `4d6kh3` drops the
+//! lowest of four.
+
+use std::ops::Range;
+
+/// Most dice one expression may roll, so `$[...]` in a trigger can't stall the client
+const MAX_DICE: u32 = 1000;
+const MAX_SIDES: u32 = 1_000_000;
+
+/// The result of an expression, and the expression with each dice term replaced
+/// by what it rolled (`[4,2,6]+2`)
+#[derive(Debug, PartialEq)]
+pub struct Evaluation {
+    pub value: f64,
+    pub shown: String,
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    roll: &'a mut dyn FnMut(u32) -> u32,
+    /// Source span of each dice term and how to show what it rolled
+    rolled: Vec<(Range<usize>, String)>,
+    dice_left: u32,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.src.as_bytes().get(self.pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn unexpected(&mut self) -> String {
+        match self.src[self.pos..].chars().next() {
+            Some(c) => format!("Unexpected '{}'", c),
+            None => "Unexpected end of expression".to_string(),
+        }
+    }
+
+    fn expr(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat(b'+') {
+                value += self.term()?;
+            } else if self.eat(b'-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(op @ (b'*' | b'/' | b'%')) => op,
+                _ => return Ok(value),
+            };
+            self.pos += 1;
+            let rhs = self.unary()?;
+            if op != b'*' && rhs == 0.0 {
+                return Err("Division by zero".to_string());
+            }
+            value = match op {
+                b'*' => value * rhs,
+                b'/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        if self.eat(b'-') {
+            return Ok(-self.unary()?);
+        }
+        if self.eat(b'+') {
+            return self.unary();
+        }
+        let base = self.primary()?;
+        if self.eat(b'^') {
+            return Ok(base.powf(self.unary()?));
+        }
+        Ok(base)
+    }
+
+    fn digits(&mut self) -> &str {
+        let start = self.pos;
+        while self.src.as_bytes().get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn primary(&mut self) -> Result<f64, String> {
+        match self.peek() {
+            Some(b'(') => {
+                self.pos += 1;
+                let value = self.expr()?;
+                if !self.eat(b')') {
+                    return Err("Missing ')'".to_string());
+                }
+                Ok(value)
+            }
+            Some(b'd' | b'D') => self.dice(self.pos, 1),
+            Some(c) if c.is_ascii_digit() || c == b'.' => {
+                let start = self.pos;
+                let whole = self.digits().to_string();
+                if matches!(self.src.as_bytes().get(self.pos), Some(b'd' | b'D')) {
+                    let count = whole.parse().map_err(|_| "Too many dice".to_string())?;
+                    return self.dice(start, count);
+                }
+                if self.src.as_bytes().get(self.pos) == Some(&b'.') {
+                    self.pos += 1;
+                    self.digits();
+                }
+                self.src[start..self.pos].parse().map_err(|_| format!("Bad number '{}'", &self.src[start..self.pos]))
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// A dice term from `start`, with the count already read and `pos` at the `d`
+    fn dice(&mut self, start: usize, count: u32) -> Result<f64, String> {
+        self.pos += 1;
+        let sides: u32 = if self.src.as_bytes().get(self.pos) == Some(&b'%') {
+            self.pos += 1;
+            100
+        } else {
+            self.digits().parse().map_err(|_| "Dice need a number of sides (3d6)".to_string())?
+        };
+        let mut keep: Option<(bool, u32)> = None;
+        if matches!(self.src.as_bytes().get(self.pos), Some(b'k' | b'K')) {
+            self.pos += 1;
+            let highest = match self.src.as_bytes().get(self.pos) {
+                Some(b'l' | b'L') => { self.pos += 1; false }
+                Some(b'h' | b'H') => { self.pos += 1; true }
+                _ => true,
+            };
+            let n = self.digits().parse().map_err(|_| "Keep needs a count (4d6kh3)".to_string())?;
+            keep = Some((highest, n));
+        }
+        if sides == 0 || sides > MAX_SIDES {
+            return Err(format!("Dice need 1 to {} sides", MAX_SIDES));
+        }
+        if count > self.dice_left {
+            return Err(format!("At most {} dice per roll", MAX_DICE));
+        }
+        self.dice_left -= count;
+
+        let rolls: Vec<u32> = (0..count).map(|_| (self.roll)(sides)).collect();
+        // Which dice count towards the total
+        let mut kept = vec![true; rolls.len()];
+        if let Some((highest, n)) = keep {
+            let mut order: Vec<usize> = (0..rolls.len()).collect();
+            order.sort_by_key(|&i| rolls[i]);
+            if highest {
+                order.reverse();
+            }
+            for &i in order.iter().skip(n as usize) {
+                kept[i] = false;
+            }
+        }
+        let shown: Vec<String> = rolls.iter().zip(&kept)
+            .map(|(r, k)| if *k { r.to_string() } else { format!("({})", r) })
+            .collect();
+        self.rolled.push((start..self.pos, format!("[{}]", shown.join(","))));
+        Ok(rolls.iter().zip(&kept).filter(|(_, k)| **k).map(|(r, _)| *r as f64).sum())
+    }
+}
+
+/// Evaluate `expr`, rolling dice with `roll(sides)` (which returns 1..=sides)
+fn evaluate_with(expr: &str, roll: &mut dyn FnMut(u32) -> u32) -> Result<Evaluation, String> {
+    let mut parser = Parser { src: expr, pos: 0, roll, rolled: Vec::new(), dice_left: MAX_DICE };
+    let value = parser.expr()?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected());
+    }
+    if !value.is_finite() {
+        return Err("Result is not a number".to_string());
+    }
+    let mut shown = String::new();
+    let mut last = 0;
+    for (span, rolls) in &parser.rolled {
+        shown.push_str(&expr[last..span.start]);
+        shown.push_str(rolls);
+        last = span.end;
+    }
+    shown.push_str(&expr[last..]);
+    Ok(Evaluation { value, shown: shown.trim().to_string() })
+}
+
+/// A fair roll of one `sides`-sided die
+fn random_roll(sides: u32) -> u32 {
+    // Reject the top partial range so every face is equally likely
+    let zone = u32::MAX - u32::MAX % sides;
+    loop {
+        let mut bytes = [0u8; 4];
+        let r = match getrandom::getrandom(&mut bytes) {
+            Ok(()) => u32::from_le_bytes(bytes),
+            Err(_) => crate::tf::expressions::simple_random(),
+        };
+        if r < zone {
+            return r % sides + 1;
+        }
+    }
+}
+
+/// Evaluate `expr` with real dice
+pub fn evaluate(expr: &str) -> Result<Evaluation, String> {
+    evaluate_with(expr, &mut random_roll)
+}
+
+/// A value as it is shown and substituted: whole numbers without a decimal point
+pub fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        let s = format!("{:.6}", value);
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// The line `/calc` or `/roll` shows (or sends with -s) for `expr`
+pub fn result_line(expr: &str, roll: bool) -> Result<String, String> {
+    let expr = expr.trim();
+    if expr.is_empty() {
+        return Err(if roll { "Usage: /roll [-s] <dice>  (e.g. /roll 3d6+2)" } else { "Usage: /calc [-s] <expression>" }.to_string());
+    }
+    let eval = evaluate(expr).map_err(|e| format!("{}: {}", expr, e))?;
+    let value = format_value(eval.value);
+    Ok(if eval.shown != expr {
+        format!("{}: {} = {}", expr, eval.shown, value)
+    } else {
+        format!("{} = {}", expr, value)
+    })
+}
+
+/// Replace each `$[expr]` in an action command with its value. One that doesn't
+/// evaluate (such as a TF expression) is left for the TF engine.
+pub fn expand(text: &str) -> String {
+    if !text.contains("$[") {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("$[") {
+        out.push_str(&rest[..start]);
+        let body = &rest[start + 2..];
+        // The matching ']' (brackets can nest)
+        let mut depth = 0;
+        let end = body.char_indices().find_map(|(i, c)| match c {
+            '[' => { depth += 1; None }
+            ']' if depth == 0 => Some(i),
+            ']' => { depth -= 1; None }
+            _ => None,
+        });
+        match end.and_then(|end| evaluate(&body[..end]).ok().map(|e| (end, e))) {
+            Some((end, eval)) => {
+                out.push_str(&format_value(eval.value));
+                rest = &body[end + 1..];
+            }
+            None => {
+                out.push_str("$[");
+                rest = body;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dice that roll 1, 2, 3, ... wrapping at each die's size
+    fn counting() -> impl FnMut(u32) -> u32 {
+        let mut n = 0;
+        move |sides| { n += 1; (n - 1) % sides + 1 }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let calc = |e: &str| evaluate_with(e, &mut counting()).map(|r| r.value);
+        assert_eq!(calc("2 * (3 + 4)"), Ok(14.0));
+        assert_eq!(calc("2^3^2"), Ok(512.0));
+        assert_eq!(calc("-2^2"), Ok(-4.0));
+        assert_eq!(calc("7 % 4 - 1.5"), Ok(1.5));
+        assert_eq!(calc("1/0"), Err("Division by zero".to_string()));
+        assert_eq!(calc("2 +"), Err("Unexpected end of expression".to_string()));
+        assert_eq!(calc("2 x"), Err("Unexpected 'x'".to_string()));
+        assert_eq!(format_value(14.0), "14");
+        assert_eq!(format_value(1.0 / 3.0), "0.333333");
+    }
+
+    #[test]
+    fn test_dice() {
+        let roll = |e: &str| evaluate_with(e, &mut counting());
+        assert_eq!(roll("3d6+2"), Ok(Evaluation { value: 8.0, shown: "[1,2,3]+2".to_string() }));
+        assert_eq!(roll("d%").unwrap().value, 1.0);
+        // Four dice roll 1,2,3,4; keep the highest three
+        assert_eq!(roll("4d6kh3"), Ok(Evaluation { value: 9.0, shown: "[(1),2,3,4]".to_string() }));
+        assert_eq!(roll("4d6kl1").unwrap().value, 1.0);
+        assert!(roll("2d0").is_err());
+        assert!(roll("1001d6").is_err());
+        assert!(roll("600d6+600d6").is_err(), "the cap covers the whole expression");
+        for _ in 0..100 {
+            let v = evaluate("1d6").unwrap().value;
+            assert!((1.0..=6.0).contains(&v));
+        }
+    }
+
+    #[test]
+    fn test_expand_and_result_lines() {
+        assert_eq!(expand("say I rolled $[2*(1d1+1)] and $[10/4]"), "say I rolled 4 and 2.5");
+        // Not ours (a TF expression) or unterminated: left alone
+        assert_eq!(expand("/echo $[strlen(x)] $[1+"), "/echo $[strlen(x)] $[1+");
+        assert_eq!(result_line("2*(3+4)", false), Ok("2*(3+4) = 14".to_string()));
+        assert!(result_line("1d1+1", true).unwrap().starts_with("1d1+1: [1]+1 = 2"));
+        assert!(result_line("", true).unwrap_err().starts_with("Usage: /roll"));
+    }
+}
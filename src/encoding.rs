@@ -1,4 +1,6 @@
 use ratatui::style::Color;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
 use crate::util::strip_ansi_codes;
 
 #[derive(Clone, Copy, PartialEq, Debug, Default)]
@@ -7,6 +9,13 @@ pub enum Encoding {
     Utf8,
     Latin1,
     Fansi,
+    Cp437,
+    Cp850,
+    Koi8r,
+    Windows1252,
+    Big5,
+    Gbk,
+    ShiftJis,
 }
 
 impl Encoding {
@@ -17,157 +26,12 @@ impl Encoding {
                 // ISO-8859-1: each byte maps directly to its Unicode codepoint
                 bytes.iter().map(|&b| b as char).collect()
             }
-            Encoding::Fansi => {
-                // FANSI: CP437 encoding used by many MUDs
-                // Full CP437 mapping to avoid C1 control character issues
-                bytes
-                    .iter()
-                    .map(|&b| {
-                        if b < 128 {
-                            b as char
-                        } else {
-                            // Complete CP437 high byte mapping (128-255)
-                            match b {
-                                // 0x80-0x9F: Accented letters and currency symbols
-                                // IMPORTANT: These MUST be mapped to avoid C1 control chars
-                                128 => '\u{00C7}', // Ç
-                                129 => '\u{00FC}', // ü
-                                130 => '\u{00E9}', // é
-                                131 => '\u{00E2}', // â
-                                132 => '\u{00E4}', // ä
-                                133 => '\u{00E0}', // à
-                                134 => '\u{00E5}', // å
-                                135 => '\u{00E7}', // ç
-                                136 => '\u{00EA}', // ê
-                                137 => '\u{00EB}', // ë
-                                138 => '\u{00E8}', // è
-                                139 => '\u{00EF}', // ï
-                                140 => '\u{00EE}', // î
-                                141 => '\u{00EC}', // ì
-                                142 => '\u{00C4}', // Ä
-                                143 => '\u{00C5}', // Å
-                                144 => '\u{00C9}', // É
-                                145 => '\u{00E6}', // æ
-                                146 => '\u{00C6}', // Æ
-                                147 => '\u{00F4}', // ô
-                                148 => '\u{00F6}', // ö
-                                149 => '\u{00F2}', // ò
-                                150 => '\u{00FB}', // û
-                                151 => '\u{00F9}', // ù
-                                152 => '\u{00FF}', // ÿ
-                                153 => '\u{00D6}', // Ö
-                                154 => '\u{00DC}', // Ü
-                                155 => '\u{00A2}', // ¢ (cent sign - was causing CSI bug!)
-                                156 => '\u{00A3}', // £ (pound sign)
-                                157 => '\u{00A5}', // ¥ (yen sign)
-                                158 => '\u{20A7}', // ₧ (peseta sign)
-                                159 => '\u{0192}', // ƒ (florin sign)
-                                // 0xA0-0xAF: More accented letters and symbols
-                                160 => '\u{00E1}', // á
-                                161 => '\u{00ED}', // í
-                                162 => '\u{00F3}', // ó
-                                163 => '\u{00FA}', // ú
-                                164 => '\u{00F1}', // ñ
-                                165 => '\u{00D1}', // Ñ
-                                166 => '\u{00AA}', // ª
-                                167 => '\u{00BA}', // º
-                                168 => '\u{00BF}', // ¿
-                                169 => '\u{2310}', // ⌐
-                                170 => '\u{00AC}', // ¬
-                                171 => '\u{00BD}', // ½
-                                172 => '\u{00BC}', // ¼
-                                173 => '\u{00A1}', // ¡
-                                174 => '\u{00AB}', // «
-                                175 => '\u{00BB}', // »
-                                // 0xB0-0xDF: Box drawing characters
-                                176 => '\u{2591}', // ░
-                                177 => '\u{2592}', // ▒
-                                178 => '\u{2593}', // ▓
-                                179 => '\u{2502}', // │
-                                180 => '\u{2524}', // ┤
-                                181 => '\u{2561}', // ╡
-                                182 => '\u{2562}', // ╢
-                                183 => '\u{2556}', // ╖
-                                184 => '\u{2555}', // ╕
-                                185 => '\u{2563}', // ╣
-                                186 => '\u{2551}', // ║
-                                187 => '\u{2557}', // ╗
-                                188 => '\u{255D}', // ╝
-                                189 => '\u{255C}', // ╜
-                                190 => '\u{255B}', // ╛
-                                191 => '\u{2510}', // ┐
-                                192 => '\u{2514}', // └
-                                193 => '\u{2534}', // ┴
-                                194 => '\u{252C}', // ┬
-                                195 => '\u{251C}', // ├
-                                196 => '\u{2500}', // ─
-                                197 => '\u{253C}', // ┼
-                                198 => '\u{255E}', // ╞
-                                199 => '\u{255F}', // ╟
-                                200 => '\u{255A}', // ╚
-                                201 => '\u{2554}', // ╔
-                                202 => '\u{2569}', // ╩
-                                203 => '\u{2566}', // ╦
-                                204 => '\u{2560}', // ╠
-                                205 => '\u{2550}', // ═
-                                206 => '\u{256C}', // ╬
-                                207 => '\u{2567}', // ╧
-                                208 => '\u{2568}', // ╨
-                                209 => '\u{2564}', // ╤
-                                210 => '\u{2565}', // ╥
-                                211 => '\u{2559}', // ╙
-                                212 => '\u{2558}', // ╘
-                                213 => '\u{2552}', // ╒
-                                214 => '\u{2553}', // ╓
-                                215 => '\u{256B}', // ╫
-                                216 => '\u{256A}', // ╪
-                                217 => '\u{2518}', // ┘
-                                218 => '\u{250C}', // ┌
-                                219 => '\u{2588}', // █
-                                220 => '\u{2584}', // ▄
-                                221 => '\u{258C}', // ▌
-                                222 => '\u{2590}', // ▐
-                                223 => '\u{2580}', // ▀
-                                // 0xE0-0xEF: Greek letters and math symbols
-                                224 => '\u{03B1}', // α
-                                225 => '\u{00DF}', // ß
-                                226 => '\u{0393}', // Γ
-                                227 => '\u{03C0}', // π
-                                228 => '\u{03A3}', // Σ
-                                229 => '\u{03C3}', // σ
-                                230 => '\u{00B5}', // µ
-                                231 => '\u{03C4}', // τ
-                                232 => '\u{03A6}', // Φ
-                                233 => '\u{0398}', // Θ
-                                234 => '\u{03A9}', // Ω
-                                235 => '\u{03B4}', // δ
-                                236 => '\u{221E}', // ∞
-                                237 => '\u{03C6}', // φ
-                                238 => '\u{03B5}', // ε
-                                239 => '\u{2229}', // ∩
-                                // 0xF0-0xFF: Math symbols and special chars
-                                240 => '\u{2261}', // ≡
-                                241 => '\u{00B1}', // ±
-                                242 => '\u{2265}', // ≥
-                                243 => '\u{2264}', // ≤
-                                244 => '\u{2320}', // ⌠
-                                245 => '\u{2321}', // ⌡
-                                246 => '\u{00F7}', // ÷
-                                247 => '\u{2248}', // ≈
-                                248 => '\u{00B0}', // °
-                                249 => '\u{2219}', // ∙
-                                250 => '\u{00B7}', // ·
-                                251 => '\u{221A}', // √
-                                252 => '\u{207F}', // ⁿ
-                                253 => '\u{00B2}', // ²
-                                254 => '\u{25A0}', // ■
-                                255 => '\u{00A0}', // NBSP
-                                _ => b as char, // Should never reach here
-                            }
-                        }
-                    })
-                    .collect()
+            // Double-byte (CJK) encodings
+            Encoding::Big5 | Encoding::Gbk | Encoding::ShiftJis => {
+                self.codec().map(|codec| codec.decode_without_bom_handling(bytes).0.into_owned()).unwrap_or_default()
             }
+            // Single-byte code pages: ASCII below 0x80, a table above
+            _ => bytes.iter().map(|&b| self.byte_char(b)).collect(),
         };
         // Strip control characters that could cause rendering issues
         // Keep: \t (tab), \n (newline), \x1b (escape for ANSI sequences), \x07 (BEL for OSC termination)
@@ -187,11 +51,121 @@ impl Encoding {
         result.chars().filter(|&c| (c >= ' ' && c != '\x7f') || c == '\t' || c == '\n' || c == '\x1b' || c == '\x0e').collect()
     }
 
+    /// Character for a byte in a single-byte encoding (ASCII below 0x80)
+    fn byte_char(&self, b: u8) -> char {
+        if b < 0x80 {
+            return b as char;
+        }
+        let high = (b - 0x80) as usize;
+        match self {
+            Encoding::Fansi | Encoding::Cp437 => cp437_high(b),
+            Encoding::Cp850 => CP850_HIGH[high],
+            Encoding::Koi8r => KOI8R_HIGH[high],
+            Encoding::Windows1252 if high < 0x20 => WINDOWS1252_C1[high],
+            Encoding::Utf8 | Encoding::Latin1 | Encoding::Windows1252
+            | Encoding::Big5 | Encoding::Gbk | Encoding::ShiftJis => b as char,
+        }
+    }
+
+    /// The encoding_rs codec for a double-byte encoding
+    fn codec(&self) -> Option<&'static encoding_rs::Encoding> {
+        match self {
+            Encoding::Big5 => Some(encoding_rs::BIG5),
+            Encoding::Gbk => Some(encoding_rs::GBK),
+            Encoding::ShiftJis => Some(encoding_rs::SHIFT_JIS),
+            _ => None,
+        }
+    }
+
+    /// How many bytes at the end of `bytes` begin a double-byte character whose
+    /// second byte hasn't arrived yet (always 0 for other encodings). Trail bytes
+    /// overlap ASCII, so the scan runs from the start.
+    pub fn incomplete_tail(&self, bytes: &[u8]) -> usize {
+        if self.codec().is_none() {
+            return 0;
+        }
+        let is_lead = |b: u8| match self {
+            Encoding::Big5 | Encoding::Gbk => (0x81..=0xFE).contains(&b),
+            Encoding::ShiftJis => (0x81..=0x9F).contains(&b) || (0xE0..=0xFC).contains(&b),
+            _ => false,
+        };
+        let mut i = 0;
+        while i < bytes.len() {
+            if !is_lead(bytes[i]) {
+                i += 1;
+                continue;
+            }
+            // GB18030 four-byte form: lead, digit, lead, digit
+            let len = if *self == Encoding::Gbk && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) { 4 } else { 2 };
+            if i + len > bytes.len() {
+                return bytes.len() - i;
+            }
+            i += len;
+        }
+        0
+    }
+
+    /// Encode outgoing text. In a single-byte encoding, typographic punctuation the code
+    /// page lacks is sent as its ASCII look-alike (see `typed_fallback`) and combining
+    /// accents it can't attach are dropped. Anything else it can't represent becomes '?',
+    /// and a resulting 0xFF byte is doubled so the server doesn't read it as telnet IAC.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        if *self == Encoding::Utf8 {
+            return text.as_bytes().to_vec();
+        }
+        if let Some(codec) = self.codec() {
+            let mut encoder = codec.new_encoder();
+            let mut bytes = Vec::with_capacity(text.len() + 8);
+            let mut rest = text;
+            loop {
+                bytes.reserve(encoder.max_buffer_length_from_utf8_without_replacement(rest.len()).unwrap_or(rest.len() * 4));
+                let (result, read) = encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut bytes, true);
+                rest = &rest[read..];
+                match result {
+                    encoding_rs::EncoderResult::InputEmpty => return bytes,
+                    encoding_rs::EncoderResult::OutputFull => {}
+                    encoding_rs::EncoderResult::Unmappable(_) => bytes.push(b'?'),
+                }
+            }
+        }
+        let mut bytes = Vec::with_capacity(text.len());
+        for c in text.chars() {
+            let byte = if (c as u32) < 0x80 {
+                Some(c as u8)
+            } else if *self == Encoding::Latin1 {
+                u8::try_from(c as u32).ok()
+            } else {
+                (0x80..=0xFF).find(|&b| self.byte_char(b) == c)
+            };
+            match byte {
+                Some(0xFF) => bytes.extend_from_slice(&[0xFF, 0xFF]),
+                Some(byte) => bytes.push(byte),
+                None if ('\u{0300}'..='\u{036F}').contains(&c) => {}
+                None => bytes.extend_from_slice(typed_fallback(c).unwrap_or("?").as_bytes()),
+            }
+        }
+        bytes
+    }
+
+    /// An outgoing line as written to the server, ending in CR LF
+    pub fn line_bytes(&self, text: &str) -> Vec<u8> {
+        let mut bytes = self.encode(text);
+        bytes.extend_from_slice(b"\r\n");
+        bytes
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Encoding::Utf8 => "utf8",
             Encoding::Latin1 => "latin1",
             Encoding::Fansi => "fansi",
+            Encoding::Cp437 => "cp437",
+            Encoding::Cp850 => "cp850",
+            Encoding::Koi8r => "koi8r",
+            Encoding::Windows1252 => "cp1252",
+            Encoding::Big5 => "big5",
+            Encoding::Gbk => "gbk",
+            Encoding::ShiftJis => "shiftjis",
         }
     }
 
@@ -199,6 +173,13 @@ impl Encoding {
         match name.to_lowercase().as_str() {
             "latin1" => Encoding::Latin1,
             "fansi" => Encoding::Fansi,
+            "cp437" => Encoding::Cp437,
+            "cp850" => Encoding::Cp850,
+            "koi8r" => Encoding::Koi8r,
+            "cp1252" => Encoding::Windows1252,
+            "big5" => Encoding::Big5,
+            "gbk" => Encoding::Gbk,
+            "shiftjis" => Encoding::ShiftJis,
             _ => Encoding::Utf8,
         }
     }
@@ -207,15 +188,29 @@ impl Encoding {
         match self {
             Encoding::Utf8 => Encoding::Latin1,
             Encoding::Latin1 => Encoding::Fansi,
-            Encoding::Fansi => Encoding::Utf8,
+            Encoding::Fansi => Encoding::Cp437,
+            Encoding::Cp437 => Encoding::Cp850,
+            Encoding::Cp850 => Encoding::Koi8r,
+            Encoding::Koi8r => Encoding::Windows1252,
+            Encoding::Windows1252 => Encoding::Big5,
+            Encoding::Big5 => Encoding::Gbk,
+            Encoding::Gbk => Encoding::ShiftJis,
+            Encoding::ShiftJis => Encoding::Utf8,
         }
     }
 
     pub fn prev(&self) -> Self {
         match self {
-            Encoding::Utf8 => Encoding::Fansi,
+            Encoding::Utf8 => Encoding::ShiftJis,
             Encoding::Latin1 => Encoding::Utf8,
             Encoding::Fansi => Encoding::Latin1,
+            Encoding::Cp437 => Encoding::Fansi,
+            Encoding::Cp850 => Encoding::Cp437,
+            Encoding::Koi8r => Encoding::Cp850,
+            Encoding::Windows1252 => Encoding::Koi8r,
+            Encoding::Big5 => Encoding::Windows1252,
+            Encoding::Gbk => Encoding::Big5,
+            Encoding::ShiftJis => Encoding::Gbk,
         }
     }
 
@@ -226,8 +221,14 @@ impl Encoding {
             "UTF-8" | "UTF8" => Some(Encoding::Utf8),
             "US-ASCII" | "ASCII" => Some(Encoding::Utf8), // ASCII is a subset of UTF-8
             "ISO-8859-1" | "ISO_8859-1" | "ISO_8859-1:1987" | "LATIN1" | "L1"
-            | "WINDOWS-1252" | "CP1252" | "ISO-8859-15" | "LATIN-9" => Some(Encoding::Latin1),
-            "IBM437" | "CP437" | "437" => Some(Encoding::Fansi),
+            | "ISO-8859-15" | "LATIN-9" => Some(Encoding::Latin1),
+            "WINDOWS-1252" | "CP1252" => Some(Encoding::Windows1252),
+            "IBM437" | "CP437" | "437" => Some(Encoding::Cp437),
+            "IBM850" | "CP850" | "850" => Some(Encoding::Cp850),
+            "KOI8-R" | "KOI8R" => Some(Encoding::Koi8r),
+            "BIG5" | "BIG-5" | "CN-BIG5" | "CSBIG5" => Some(Encoding::Big5),
+            "GBK" | "GB2312" | "CP936" | "EUC-CN" => Some(Encoding::Gbk),
+            "SHIFT_JIS" | "SHIFT-JIS" | "SJIS" | "MS_KANJI" | "CP932" | "WINDOWS-31J" => Some(Encoding::ShiftJis),
             _ => None,
         }
     }
@@ -237,11 +238,232 @@ impl Encoding {
         match self {
             Encoding::Utf8 => "UTF-8",
             Encoding::Latin1 => "ISO-8859-1",
-            Encoding::Fansi => "IBM437",
+            Encoding::Fansi | Encoding::Cp437 => "IBM437",
+            Encoding::Cp850 => "IBM850",
+            Encoding::Koi8r => "KOI8-R",
+            Encoding::Windows1252 => "windows-1252",
+            Encoding::Big5 => "Big5",
+            Encoding::Gbk => "GBK",
+            Encoding::ShiftJis => "Shift_JIS",
         }
     }
 }
 
+/// A world's outgoing encoding, shared with its connection's writer task so a
+/// TELNET CHARSET switch or a world edit applies to the open connection
+#[derive(Clone, Debug, Default)]
+pub struct SharedEncoding(Arc<AtomicU8>);
+
+impl SharedEncoding {
+    const ALL: [Encoding; 10] = [
+        Encoding::Utf8, Encoding::Latin1, Encoding::Fansi, Encoding::Cp437,
+        Encoding::Cp850, Encoding::Koi8r, Encoding::Windows1252,
+        Encoding::Big5, Encoding::Gbk, Encoding::ShiftJis,
+    ];
+
+    pub fn get(&self) -> Encoding {
+        Self::ALL.get(self.0.load(Ordering::Relaxed) as usize).copied().unwrap_or_default()
+    }
+
+    pub fn set(&self, encoding: Encoding) {
+        let idx = Self::ALL.iter().position(|&e| e == encoding).unwrap_or(0);
+        self.0.store(idx as u8, Ordering::Relaxed);
+    }
+}
+
+/// CP437 (IBM PC) character for a high byte, 0x80-0xFF. Every byte is mapped,
+/// so none decode to C1 control characters.
+/// ASCII stand-in for punctuation people type (or paste from a word processor or phone
+/// keyboard) that single-byte code pages lack
+fn typed_fallback(c: char) -> Option<&'static str> {
+    Some(match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{2032}' => "'",
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{2033}' => "\"",
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}' | '\u{2212}' => "-",
+        '\u{2026}' => "...",
+        '\u{2022}' => "*",
+        '\u{2039}' => "<",
+        '\u{203A}' => ">",
+        '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2009}' | '\u{202F}' => " ",
+        '\u{20AC}' => "EUR",
+        '\u{2122}' => "TM",
+        '\u{00A9}' => "(c)",
+        '\u{00AE}' => "(R)",
+        _ => return None,
+    })
+}
+
+fn cp437_high(b: u8) -> char {
+    match b {
+        // 0x80-0x9F: Accented letters and currency symbols
+        // IMPORTANT: These MUST be mapped to avoid C1 control chars
+        128 => '\u{00C7}', // Ç
+        129 => '\u{00FC}', // ü
+        130 => '\u{00E9}', // é
+        131 => '\u{00E2}', // â
+        132 => '\u{00E4}', // ä
+        133 => '\u{00E0}', // à
+        134 => '\u{00E5}', // å
+        135 => '\u{00E7}', // ç
+        136 => '\u{00EA}', // ê
+        137 => '\u{00EB}', // ë
+        138 => '\u{00E8}', // è
+        139 => '\u{00EF}', // ï
+        140 => '\u{00EE}', // î
+        141 => '\u{00EC}', // ì
+        142 => '\u{00C4}', // Ä
+        143 => '\u{00C5}', // Å
+        144 => '\u{00C9}', // É
+        145 => '\u{00E6}', // æ
+        146 => '\u{00C6}', // Æ
+        147 => '\u{00F4}', // ô
+        148 => '\u{00F6}', // ö
+        149 => '\u{00F2}', // ò
+        150 => '\u{00FB}', // û
+        151 => '\u{00F9}', // ù
+        152 => '\u{00FF}', // ÿ
+        153 => '\u{00D6}', // Ö
+        154 => '\u{00DC}', // Ü
+        155 => '\u{00A2}', // ¢ (cent sign - was causing CSI bug!)
+        156 => '\u{00A3}', // £ (pound sign)
+        157 => '\u{00A5}', // ¥ (yen sign)
+        158 => '\u{20A7}', // ₧ (peseta sign)
+        159 => '\u{0192}', // ƒ (florin sign)
+        // 0xA0-0xAF: More accented letters and symbols
+        160 => '\u{00E1}', // á
+        161 => '\u{00ED}', // í
+        162 => '\u{00F3}', // ó
+        163 => '\u{00FA}', // ú
+        164 => '\u{00F1}', // ñ
+        165 => '\u{00D1}', // Ñ
+        166 => '\u{00AA}', // ª
+        167 => '\u{00BA}', // º
+        168 => '\u{00BF}', // ¿
+        169 => '\u{2310}', // ⌐
+        170 => '\u{00AC}', // ¬
+        171 => '\u{00BD}', // ½
+        172 => '\u{00BC}', // ¼
+        173 => '\u{00A1}', // ¡
+        174 => '\u{00AB}', // «
+        175 => '\u{00BB}', // »
+        // 0xB0-0xDF: Box drawing characters
+        176 => '\u{2591}', // ░
+        177 => '\u{2592}', // ▒
+        178 => '\u{2593}', // ▓
+        179 => '\u{2502}', // │
+        180 => '\u{2524}', // ┤
+        181 => '\u{2561}', // ╡
+        182 => '\u{2562}', // ╢
+        183 => '\u{2556}', // ╖
+        184 => '\u{2555}', // ╕
+        185 => '\u{2563}', // ╣
+        186 => '\u{2551}', // ║
+        187 => '\u{2557}', // ╗
+        188 => '\u{255D}', // ╝
+        189 => '\u{255C}', // ╜
+        190 => '\u{255B}', // ╛
+        191 => '\u{2510}', // ┐
+        192 => '\u{2514}', // └
+        193 => '\u{2534}', // ┴
+        194 => '\u{252C}', // ┬
+        195 => '\u{251C}', // ├
+        196 => '\u{2500}', // ─
+        197 => '\u{253C}', // ┼
+        198 => '\u{255E}', // ╞
+        199 => '\u{255F}', // ╟
+        200 => '\u{255A}', // ╚
+        201 => '\u{2554}', // ╔
+        202 => '\u{2569}', // ╩
+        203 => '\u{2566}', // ╦
+        204 => '\u{2560}', // ╠
+        205 => '\u{2550}', // ═
+        206 => '\u{256C}', // ╬
+        207 => '\u{2567}', // ╧
+        208 => '\u{2568}', // ╨
+        209 => '\u{2564}', // ╤
+        210 => '\u{2565}', // ╥
+        211 => '\u{2559}', // ╙
+        212 => '\u{2558}', // ╘
+        213 => '\u{2552}', // ╒
+        214 => '\u{2553}', // ╓
+        215 => '\u{256B}', // ╫
+        216 => '\u{256A}', // ╪
+        217 => '\u{2518}', // ┘
+        218 => '\u{250C}', // ┌
+        219 => '\u{2588}', // █
+        220 => '\u{2584}', // ▄
+        221 => '\u{258C}', // ▌
+        222 => '\u{2590}', // ▐
+        223 => '\u{2580}', // ▀
+        // 0xE0-0xEF: Greek letters and math symbols
+        224 => '\u{03B1}', // α
+        225 => '\u{00DF}', // ß
+        226 => '\u{0393}', // Γ
+        227 => '\u{03C0}', // π
+        228 => '\u{03A3}', // Σ
+        229 => '\u{03C3}', // σ
+        230 => '\u{00B5}', // µ
+        231 => '\u{03C4}', // τ
+        232 => '\u{03A6}', // Φ
+        233 => '\u{0398}', // Θ
+        234 => '\u{03A9}', // Ω
+        235 => '\u{03B4}', // δ
+        236 => '\u{221E}', // ∞
+        237 => '\u{03C6}', // φ
+        238 => '\u{03B5}', // ε
+        239 => '\u{2229}', // ∩
+        // 0xF0-0xFF: Math symbols and special chars
+        240 => '\u{2261}', // ≡
+        241 => '\u{00B1}', // ±
+        242 => '\u{2265}', // ≥
+        243 => '\u{2264}', // ≤
+        244 => '\u{2320}', // ⌠
+        245 => '\u{2321}', // ⌡
+        246 => '\u{00F7}', // ÷
+        247 => '\u{2248}', // ≈
+        248 => '\u{00B0}', // °
+        249 => '\u{2219}', // ∙
+        250 => '\u{00B7}', // ·
+        251 => '\u{221A}', // √
+        252 => '\u{207F}', // ⁿ
+        253 => '\u{00B2}', // ²
+        254 => '\u{25A0}', // ■
+        255 => '\u{00A0}', // NBSP
+        _ => b as char, // Only called for 0x80-0xFF
+    }
+}
+
+/// CP850 (DOS Latin-1) characters for 0x80-0xFF
+const CP850_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', 'ø', '£', 'Ø', '×', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '®', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', 'Á', 'Â', 'À', '©', '╣', '║', '╗', '╝', '¢', '¥', '┐',
+    '└', '┴', '┬', '├', '─', '┼', 'ã', 'Ã', '╚', '╔', '╩', '╦', '╠', '═', '╬', '¤',
+    'ð', 'Ð', 'Ê', 'Ë', 'È', 'ı', 'Í', 'Î', 'Ï', '┘', '┌', '█', '▄', '¦', 'Ì', '▀',
+    'Ó', 'ß', 'Ô', 'Ò', 'õ', 'Õ', 'µ', 'þ', 'Þ', 'Ú', 'Û', 'Ù', 'ý', 'Ý', '¯', '´',
+    '\u{00AD}', '±', '‗', '¾', '¶', '§', '÷', '¸', '°', '¨', '·', '¹', '³', '²', '■', '\u{00A0}',
+];
+
+/// KOI8-R (Russian) characters for 0x80-0xFF
+const KOI8R_HIGH: [char; 128] = [
+    '─', '│', '┌', '┐', '└', '┘', '├', '┤', '┬', '┴', '┼', '▀', '▄', '█', '▌', '▐',
+    '░', '▒', '▓', '⌠', '■', '∙', '√', '≈', '≤', '≥', '\u{00A0}', '⌡', '°', '²', '·', '÷',
+    '═', '║', '╒', 'ё', '╓', '╔', '╕', '╖', '╗', '╘', '╙', '╚', '╛', '╜', '╝', '╞',
+    '╟', '╠', '╡', 'Ё', '╢', '╣', '╤', '╥', '╦', '╧', '╨', '╩', '╪', '╫', '╬', '©',
+    'ю', 'а', 'б', 'ц', 'д', 'е', 'ф', 'г', 'х', 'и', 'й', 'к', 'л', 'м', 'н', 'о',
+    'п', 'я', 'р', 'с', 'т', 'у', 'ж', 'в', 'ь', 'ы', 'з', 'ш', 'э', 'щ', 'ч', 'ъ',
+    'Ю', 'А', 'Б', 'Ц', 'Д', 'Е', 'Ф', 'Г', 'Х', 'И', 'Й', 'К', 'Л', 'М', 'Н', 'О',
+    'П', 'Я', 'Р', 'С', 'Т', 'У', 'Ж', 'В', 'Ь', 'Ы', 'З', 'Ш', 'Э', 'Щ', 'Ч', 'Ъ',
+];
+
+/// Windows-1252 characters for 0x80-0x9F (0xA0-0xFF match Latin-1). The five unused
+/// bytes keep their C1 code points, as browsers decode them.
+const WINDOWS1252_C1: [char; 32] = [
+    '€', '\u{0081}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{008D}', 'Ž', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{009D}', 'ž', 'Ÿ',
+];
+
 #[derive(Clone, Copy, PartialEq, Debug, Default)]
 pub enum Theme {
     #[default]
@@ -388,6 +610,55 @@ impl WorldSwitchMode {
     }
 }
 
+/// Time prefix on displayed output lines; stored lines and logs are unchanged
+/// (set with /timestamps, globally or per world)
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum TimestampMode {
+    #[default]
+    Off,
+    /// HH:MM
+    Minutes,
+    /// HH:MM:SS
+    Seconds,
+}
+
+impl TimestampMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimestampMode::Off => "off",
+            TimestampMode::Minutes => "hm",
+            TimestampMode::Seconds => "hms",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "off" => Some(TimestampMode::Off),
+            "hm" => Some(TimestampMode::Minutes),
+            "hms" => Some(TimestampMode::Seconds),
+            _ => None,
+        }
+    }
+
+    /// The mode after the runtime toggle key: hides configured timestamps, or shows
+    /// HH:MM where none are configured
+    pub fn toggled(self) -> Self {
+        match self {
+            TimestampMode::Off => TimestampMode::Minutes,
+            _ => TimestampMode::Off,
+        }
+    }
+
+    /// "12:34" or "12:34:56" (None when off)
+    pub fn format(&self, hour: i32, minute: i32, second: i32) -> Option<String> {
+        match self {
+            TimestampMode::Off => None,
+            TimestampMode::Minutes => Some(format!("{:02}:{:02}", hour, minute)),
+            TimestampMode::Seconds => Some(format!("{:02}:{:02}:{:02}", hour, minute, second)),
+        }
+    }
+}
+
 /// URL shortening service selection
 #[derive(Clone, Copy, PartialEq, Debug, Default)]
 pub enum UrlShortener {
@@ -853,6 +1124,33 @@ pub fn wrap_urls_with_osc8(s: &str) -> String {
     result
 }
 
+/// URLs in plain (ANSI-stripped) text, in order, using the same delimiters and trailing
+/// punctuation rules as `wrap_urls_with_osc8`
+pub fn find_urls(s: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut rest = s;
+    while let Some(pos) = rest.find("http") {
+        let candidate = &rest[pos..];
+        if !candidate.starts_with("http://") && !candidate.starts_with("https://") {
+            rest = &candidate[4..];
+            continue;
+        }
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c,
+                '"' | '\'' | '<' | '>' | '[' | ']' | '(' | ')' | '{' | '}' |
+                '\u{201C}' | '\u{201D}' | '\u{2018}' | '\u{2019}'))
+            .unwrap_or(candidate.len());
+        let url = candidate[..end]
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', '\u{200B}'])
+            .replace('\u{200B}', "");
+        if url.split_once("://").is_some_and(|(_, rest)| !rest.is_empty()) {
+            urls.push(url);
+        }
+        rest = &candidate[end..];
+    }
+    urls
+}
+
 /// Replace colored square emoji with ANSI-colored block characters for console display
 /// This ensures emoji like 🟩🟨 display in their proper colors in terminals
 /// (Emoji fonts typically ignore ANSI colors, so we use block characters instead)
@@ -949,291 +1247,334 @@ pub fn convert_discord_emojis_with_links(s: &str) -> String {
     }).to_string()
 }
 
+/// Shortcode names and the emoji they stand for, used for Discord emoji and for
+/// `:name:` typed with `/emoji` on (see `expand_emoji_shortcodes`)
+const EMOJI_SHORTCODES: &[(&[&str], &str)] = &[
+    // Smileys & Emotion
+    (&["smile", "smiley", "happy"], "😊"),
+    (&["grin", "grinning"], "😀"),
+    (&["joy", "laughing", "lol"], "😂"),
+    (&["rofl"], "🤣"),
+    (&["wink", "winking"], "😉"),
+    (&["blush"], "😊"),
+    (&["heart_eyes", "hearteyes"], "😍"),
+    (&["kissing_heart", "kissingheart"], "😘"),
+    (&["kiss"], "💋"),
+    (&["yum", "delicious"], "😋"),
+    (&["stuck_out_tongue", "tongue"], "😛"),
+    (&["crazy", "zany"], "🤪"),
+    (&["thinking", "think", "hmm"], "🤔"),
+    (&["shush", "shushing"], "🤫"),
+    (&["neutral", "meh"], "😐"),
+    (&["expressionless"], "😑"),
+    (&["unamused"], "😒"),
+    (&["roll_eyes", "rolleyes", "eyeroll"], "🙄"),
+    (&["grimace", "grimacing"], "😬"),
+    (&["relieved"], "😌"),
+    (&["pensive"], "😔"),
+    (&["sleepy"], "😪"),
+    (&["sleeping", "zzz"], "😴"),
+    (&["sick", "ill"], "🤢"),
+    (&["vomit", "puke"], "🤮"),
+    (&["sneeze", "sneezing"], "🤧"),
+    (&["hot", "overheated"], "🥵"),
+    (&["cold", "freezing"], "🥶"),
+    (&["woozy", "dizzy"], "🥴"),
+    (&["exploding_head", "mindblown"], "🤯"),
+    (&["cowboy"], "🤠"),
+    (&["sunglasses", "cool"], "😎"),
+    (&["nerd"], "🤓"),
+    (&["monocle"], "🧐"),
+    (&["confused"], "😕"),
+    (&["worried"], "😟"),
+    (&["frown", "frowning", "sad"], "☹️"),
+    (&["cry", "crying"], "😢"),
+    (&["sob", "sobbing"], "😭"),
+    (&["angry", "mad"], "😠"),
+    (&["rage", "furious"], "😡"),
+    (&["skull", "dead"], "💀"),
+    (&["poop", "poo", "shit"], "💩"),
+    (&["clown"], "🤡"),
+    (&["ghost"], "👻"),
+    (&["alien"], "👽"),
+    (&["robot"], "🤖"),
+    (&["cat", "smiley_cat"], "😺"),
+    (&["heart_eyes_cat"], "😻"),
+    (&["scream_cat"], "🙀"),
+    (&["crying_cat"], "😿"),
+    (&["pouting_cat"], "😾"),
+    (&["devil", "imp"], "😈"),
+    (&["angel"], "😇"),
+
+    // Gestures & Body
+    (&["wave", "waving"], "👋"),
+    (&["raised_hand", "hand"], "✋"),
+    (&["ok_hand", "ok"], "👌"),
+    (&["thumbs_up", "thumbsup", "+1", "like"], "👍"),
+    (&["thumbs_down", "thumbsdown", "-1", "dislike"], "👎"),
+    (&["clap", "clapping"], "👏"),
+    (&["handshake"], "🤝"),
+    (&["pray", "praying", "please", "thanks"], "🙏"),
+    (&["muscle", "flex", "strong"], "💪"),
+    (&["middle_finger", "fu"], "🖕"),
+    (&["point_up"], "☝️"),
+    (&["point_down"], "👇"),
+    (&["point_left"], "👈"),
+    (&["point_right"], "👉"),
+    (&["fist", "punch"], "👊"),
+    (&["raised_fist"], "✊"),
+    (&["v", "peace", "victory"], "✌️"),
+    (&["fingers_crossed", "crossed_fingers"], "🤞"),
+    (&["love_you", "ily"], "🤟"),
+    (&["metal", "rock", "horns"], "🤘"),
+    (&["call_me", "shaka"], "🤙"),
+    (&["eyes"], "👀"),
+    (&["eye"], "👁️"),
+    (&["brain"], "🧠"),
+
+    // Hearts & Love
+    (&["heart", "love", "red_heart"], "❤️"),
+    (&["orange_heart"], "🧡"),
+    (&["yellow_heart"], "💛"),
+    (&["green_heart"], "💚"),
+    (&["blue_heart"], "💙"),
+    (&["purple_heart"], "💜"),
+    (&["black_heart"], "🖤"),
+    (&["white_heart"], "🤍"),
+    (&["broken_heart"], "💔"),
+    (&["sparkling_heart"], "💖"),
+    (&["heartbeat"], "💓"),
+    (&["heartpulse"], "💗"),
+    (&["two_hearts"], "💕"),
+    (&["revolving_hearts"], "💞"),
+    (&["cupid"], "💘"),
+    (&["gift_heart"], "💝"),
+
+    // Nature & Animals
+    (&["dog", "puppy"], "🐕"),
+    (&["cat2", "kitty"], "🐈"),
+    (&["mouse"], "🐁"),
+    (&["hamster"], "🐹"),
+    (&["rabbit", "bunny"], "🐰"),
+    (&["fox"], "🦊"),
+    (&["bear"], "🐻"),
+    (&["panda"], "🐼"),
+    (&["koala"], "🐨"),
+    (&["tiger"], "🐯"),
+    (&["lion"], "🦁"),
+    (&["cow"], "🐄"),
+    (&["pig"], "🐷"),
+    (&["frog"], "🐸"),
+    (&["monkey"], "🐒"),
+    (&["chicken", "hen"], "🐔"),
+    (&["penguin"], "🐧"),
+    (&["bird"], "🐦"),
+    (&["eagle"], "🦅"),
+    (&["duck"], "🦆"),
+    (&["owl"], "🦉"),
+    (&["bat"], "🦇"),
+    (&["wolf"], "🐺"),
+    (&["horse"], "🐴"),
+    (&["unicorn"], "🦄"),
+    (&["bee"], "🐝"),
+    (&["bug", "beetle"], "🐛"),
+    (&["butterfly"], "🦋"),
+    (&["snail"], "🐌"),
+    (&["shell"], "🐚"),
+    (&["crab"], "🦀"),
+    (&["shrimp"], "🦐"),
+    (&["squid"], "🦑"),
+    (&["octopus"], "🐙"),
+    (&["fish"], "🐟"),
+    (&["dolphin"], "🐬"),
+    (&["whale"], "🐳"),
+    (&["shark"], "🦈"),
+    (&["crocodile", "alligator"], "🐊"),
+    (&["snake"], "🐍"),
+    (&["turtle"], "🐢"),
+    (&["dragon"], "🐉"),
+    (&["dragon_face"], "🐲"),
+    (&["t_rex", "trex", "dinosaur"], "🦖"),
+
+    // Food & Drink
+    (&["apple"], "🍎"),
+    (&["banana"], "🍌"),
+    (&["orange"], "🍊"),
+    (&["lemon"], "🍋"),
+    (&["watermelon"], "🍉"),
+    (&["grapes"], "🍇"),
+    (&["strawberry"], "🍓"),
+    (&["peach"], "🍑"),
+    (&["cherry", "cherries"], "🍒"),
+    (&["pizza"], "🍕"),
+    (&["hamburger", "burger"], "🍔"),
+    (&["fries", "french_fries"], "🍟"),
+    (&["hotdog", "hot_dog"], "🌭"),
+    (&["taco"], "🌮"),
+    (&["burrito"], "🌯"),
+    (&["popcorn"], "🍿"),
+    (&["icecream", "ice_cream"], "🍦"),
+    (&["donut", "doughnut"], "🍩"),
+    (&["cookie"], "🍪"),
+    (&["cake", "birthday"], "🎂"),
+    (&["pie"], "🥧"),
+    (&["chocolate"], "🍫"),
+    (&["candy"], "🍬"),
+    (&["coffee", "cafe"], "☕"),
+    (&["tea"], "🍵"),
+    (&["beer"], "🍺"),
+    (&["beers"], "🍻"),
+    (&["wine", "wine_glass"], "🍷"),
+    (&["cocktail", "martini"], "🍸"),
+    (&["champagne"], "🍾"),
+
+    // Activities & Sports
+    (&["soccer", "football"], "⚽"),
+    (&["basketball"], "🏀"),
+    (&["baseball"], "⚾"),
+    (&["tennis"], "🎾"),
+    (&["volleyball"], "🏐"),
+    (&["golf"], "⛳"),
+    (&["bowling"], "🎳"),
+    (&["trophy", "winner"], "🏆"),
+    (&["medal", "gold_medal"], "🥇"),
+    (&["silver_medal"], "🥈"),
+    (&["bronze_medal"], "🥉"),
+    (&["video_game", "gaming", "controller"], "🎮"),
+    (&["dice", "game_die"], "🎲"),
+    (&["dart", "bullseye"], "🎯"),
+
+    // Objects
+    (&["phone", "iphone", "mobile"], "📱"),
+    (&["computer", "laptop", "pc"], "💻"),
+    (&["keyboard"], "⌨️"),
+    (&["mouse2", "computer_mouse"], "🖱️"),
+    (&["printer"], "🖨️"),
+    (&["camera"], "📷"),
+    (&["tv", "television"], "📺"),
+    (&["radio"], "📻"),
+    (&["bulb", "lightbulb", "idea"], "💡"),
+    (&["flashlight", "torch"], "🔦"),
+    (&["book"], "📖"),
+    (&["books"], "📚"),
+    (&["money", "cash", "dollar"], "💵"),
+    (&["credit_card"], "💳"),
+    (&["gem", "diamond"], "💎"),
+    (&["hammer"], "🔨"),
+    (&["wrench"], "🔧"),
+    (&["gear", "cog", "settings"], "⚙️"),
+    (&["lock", "locked"], "🔒"),
+    (&["unlock", "unlocked"], "🔓"),
+    (&["key"], "🔑"),
+    (&["bell"], "🔔"),
+    (&["gift", "present"], "🎁"),
+    (&["balloon", "balloons"], "🎈"),
+    (&["tada", "party", "celebration"], "🎉"),
+    (&["confetti"], "🎊"),
+
+    // Symbols & Misc
+    (&["check", "checkmark", "yes"], "✅"),
+    (&["x", "cross", "no"], "❌"),
+    (&["warning", "warn"], "⚠️"),
+    (&["question", "?"], "❓"),
+    (&["exclamation", "!"], "❗"),
+    (&["100", "hundred"], "💯"),
+    (&["fire", "lit", "hot2"], "🔥"),
+    (&["star", "stars"], "⭐"),
+    (&["sparkles", "sparkle"], "✨"),
+    (&["boom", "explosion"], "💥"),
+    (&["zap", "lightning", "thunder"], "⚡"),
+    (&["rainbow"], "🌈"),
+    (&["sun", "sunny"], "☀️"),
+    (&["moon", "crescent_moon"], "🌙"),
+    (&["cloud", "cloudy"], "☁️"),
+    (&["rain", "rainy"], "🌧️"),
+    (&["snow", "snowy", "snowflake"], "❄️"),
+    (&["earth", "globe", "world"], "🌍"),
+    (&["rocket"], "🚀"),
+    (&["airplane", "plane"], "✈️"),
+    (&["car", "automobile"], "🚗"),
+    (&["bus"], "🚌"),
+    (&["train"], "🚃"),
+    (&["bike", "bicycle"], "🚲"),
+    (&["crown", "king", "queen"], "👑"),
+    (&["ring"], "💍"),
+    (&["clock", "time"], "🕐"),
+    (&["hourglass"], "⏳"),
+    (&["alarm", "alarm_clock"], "⏰"),
+    (&["music", "musical_note"], "🎵"),
+    (&["notes", "musical_notes"], "🎶"),
+    (&["microphone", "mic"], "🎤"),
+    (&["headphones", "headphone"], "🎧"),
+    (&["art", "palette"], "🎨"),
+    (&["movie", "film"], "🎬"),
+    (&["mask", "theater"], "🎭"),
+    (&["flag"], "🚩"),
+    (&["white_flag"], "🏳️"),
+    (&["skull_crossbones", "danger"], "☠️"),
+
+    // Arrows & Shapes
+    (&["arrow_up", "up"], "⬆️"),
+    (&["arrow_down", "down"], "⬇️"),
+    (&["arrow_left", "left"], "⬅️"),
+    (&["arrow_right", "right"], "➡️"),
+    (&["arrows_counterclockwise", "refresh", "reload"], "🔄"),
+    (&["plus", "add"], "➕"),
+    (&["minus", "subtract"], "➖"),
+];
+
 /// Map common emoji names to Unicode characters
 fn emoji_name_to_unicode(name: &str) -> Option<String> {
     let lower = name.to_lowercase();
-    let emoji = match lower.as_str() {
-        // Smileys & Emotion
-        "smile" | "smiley" | "happy" => "😊",
-        "grin" | "grinning" => "😀",
-        "joy" | "laughing" | "lol" => "😂",
-        "rofl" => "🤣",
-        "wink" | "winking" => "😉",
-        "blush" => "😊",
-        "heart_eyes" | "hearteyes" => "😍",
-        "kissing_heart" | "kissingheart" => "😘",
-        "kiss" => "💋",
-        "yum" | "delicious" => "😋",
-        "stuck_out_tongue" | "tongue" => "😛",
-        "crazy" | "zany" => "🤪",
-        "thinking" | "think" | "hmm" => "🤔",
-        "shush" | "shushing" => "🤫",
-        "neutral" | "meh" => "😐",
-        "expressionless" => "😑",
-        "unamused" => "😒",
-        "roll_eyes" | "rolleyes" | "eyeroll" => "🙄",
-        "grimace" | "grimacing" => "😬",
-        "relieved" => "😌",
-        "pensive" => "😔",
-        "sleepy" => "😪",
-        "sleeping" | "zzz" => "😴",
-        "sick" | "ill" => "🤢",
-        "vomit" | "puke" => "🤮",
-        "sneeze" | "sneezing" => "🤧",
-        "hot" | "overheated" => "🥵",
-        "cold" | "freezing" => "🥶",
-        "woozy" | "dizzy" => "🥴",
-        "exploding_head" | "mindblown" => "🤯",
-        "cowboy" => "🤠",
-        "sunglasses" | "cool" => "😎",
-        "nerd" => "🤓",
-        "monocle" => "🧐",
-        "confused" => "😕",
-        "worried" => "😟",
-        "frown" | "frowning" | "sad" => "☹️",
-        "cry" | "crying" => "😢",
-        "sob" | "sobbing" => "😭",
-        "angry" | "mad" => "😠",
-        "rage" | "furious" => "😡",
-        "skull" | "dead" => "💀",
-        "poop" | "poo" | "shit" => "💩",
-        "clown" => "🤡",
-        "ghost" => "👻",
-        "alien" => "👽",
-        "robot" => "🤖",
-        "cat" | "smiley_cat" => "😺",
-        "heart_eyes_cat" => "😻",
-        "scream_cat" => "🙀",
-        "crying_cat" => "😿",
-        "pouting_cat" => "😾",
-        "devil" | "imp" => "😈",
-        "angel" => "😇",
-
-        // Gestures & Body
-        "wave" | "waving" => "👋",
-        "raised_hand" | "hand" => "✋",
-        "ok_hand" | "ok" => "👌",
-        "thumbs_up" | "thumbsup" | "+1" | "like" => "👍",
-        "thumbs_down" | "thumbsdown" | "-1" | "dislike" => "👎",
-        "clap" | "clapping" => "👏",
-        "handshake" => "🤝",
-        "pray" | "praying" | "please" | "thanks" => "🙏",
-        "muscle" | "flex" | "strong" => "💪",
-        "middle_finger" | "fu" => "🖕",
-        "point_up" => "☝️",
-        "point_down" => "👇",
-        "point_left" => "👈",
-        "point_right" => "👉",
-        "fist" | "punch" => "👊",
-        "raised_fist" => "✊",
-        "v" | "peace" | "victory" => "✌️",
-        "fingers_crossed" | "crossed_fingers" => "🤞",
-        "love_you" | "ily" => "🤟",
-        "metal" | "rock" | "horns" => "🤘",
-        "call_me" | "shaka" => "🤙",
-        "eyes" => "👀",
-        "eye" => "👁️",
-        "brain" => "🧠",
-
-        // Hearts & Love
-        "heart" | "love" | "red_heart" => "❤️",
-        "orange_heart" => "🧡",
-        "yellow_heart" => "💛",
-        "green_heart" => "💚",
-        "blue_heart" => "💙",
-        "purple_heart" => "💜",
-        "black_heart" => "🖤",
-        "white_heart" => "🤍",
-        "broken_heart" => "💔",
-        "sparkling_heart" => "💖",
-        "heartbeat" => "💓",
-        "heartpulse" => "💗",
-        "two_hearts" => "💕",
-        "revolving_hearts" => "💞",
-        "cupid" => "💘",
-        "gift_heart" => "💝",
-
-        // Nature & Animals
-        "dog" | "puppy" => "🐕",
-        "cat2" | "kitty" => "🐈",
-        "mouse" => "🐁",
-        "hamster" => "🐹",
-        "rabbit" | "bunny" => "🐰",
-        "fox" => "🦊",
-        "bear" => "🐻",
-        "panda" => "🐼",
-        "koala" => "🐨",
-        "tiger" => "🐯",
-        "lion" => "🦁",
-        "cow" => "🐄",
-        "pig" => "🐷",
-        "frog" => "🐸",
-        "monkey" => "🐒",
-        "chicken" | "hen" => "🐔",
-        "penguin" => "🐧",
-        "bird" => "🐦",
-        "eagle" => "🦅",
-        "duck" => "🦆",
-        "owl" => "🦉",
-        "bat" => "🦇",
-        "wolf" => "🐺",
-        "horse" => "🐴",
-        "unicorn" => "🦄",
-        "bee" => "🐝",
-        "bug" | "beetle" => "🐛",
-        "butterfly" => "🦋",
-        "snail" => "🐌",
-        "shell" => "🐚",
-        "crab" => "🦀",
-        "shrimp" => "🦐",
-        "squid" => "🦑",
-        "octopus" => "🐙",
-        "fish" => "🐟",
-        "dolphin" => "🐬",
-        "whale" => "🐳",
-        "shark" => "🦈",
-        "crocodile" | "alligator" => "🐊",
-        "snake" => "🐍",
-        "turtle" => "🐢",
-        "dragon" => "🐉",
-        "dragon_face" => "🐲",
-        "t_rex" | "trex" | "dinosaur" => "🦖",
-
-        // Food & Drink
-        "apple" => "🍎",
-        "banana" => "🍌",
-        "orange" => "🍊",
-        "lemon" => "🍋",
-        "watermelon" => "🍉",
-        "grapes" => "🍇",
-        "strawberry" => "🍓",
-        "peach" => "🍑",
-        "cherry" | "cherries" => "🍒",
-        "pizza" => "🍕",
-        "hamburger" | "burger" => "🍔",
-        "fries" | "french_fries" => "🍟",
-        "hotdog" | "hot_dog" => "🌭",
-        "taco" => "🌮",
-        "burrito" => "🌯",
-        "popcorn" => "🍿",
-        "icecream" | "ice_cream" => "🍦",
-        "donut" | "doughnut" => "🍩",
-        "cookie" => "🍪",
-        "cake" | "birthday" => "🎂",
-        "pie" => "🥧",
-        "chocolate" => "🍫",
-        "candy" => "🍬",
-        "coffee" | "cafe" => "☕",
-        "tea" => "🍵",
-        "beer" => "🍺",
-        "beers" => "🍻",
-        "wine" | "wine_glass" => "🍷",
-        "cocktail" | "martini" => "🍸",
-        "champagne" => "🍾",
-
-        // Activities & Sports
-        "soccer" | "football" => "⚽",
-        "basketball" => "🏀",
-        "baseball" => "⚾",
-        "tennis" => "🎾",
-        "volleyball" => "🏐",
-        "golf" => "⛳",
-        "bowling" => "🎳",
-        "trophy" | "winner" => "🏆",
-        "medal" | "gold_medal" => "🥇",
-        "silver_medal" => "🥈",
-        "bronze_medal" => "🥉",
-        "video_game" | "gaming" | "controller" => "🎮",
-        "dice" | "game_die" => "🎲",
-        "dart" | "bullseye" => "🎯",
-
-        // Objects
-        "phone" | "iphone" | "mobile" => "📱",
-        "computer" | "laptop" | "pc" => "💻",
-        "keyboard" => "⌨️",
-        "mouse2" | "computer_mouse" => "🖱️",
-        "printer" => "🖨️",
-        "camera" => "📷",
-        "tv" | "television" => "📺",
-        "radio" => "📻",
-        "bulb" | "lightbulb" | "idea" => "💡",
-        "flashlight" | "torch" => "🔦",
-        "book" => "📖",
-        "books" => "📚",
-        "money" | "cash" | "dollar" => "💵",
-        "credit_card" => "💳",
-        "gem" | "diamond" => "💎",
-        "hammer" => "🔨",
-        "wrench" => "🔧",
-        "gear" | "cog" | "settings" => "⚙️",
-        "lock" | "locked" => "🔒",
-        "unlock" | "unlocked" => "🔓",
-        "key" => "🔑",
-        "bell" => "🔔",
-        "gift" | "present" => "🎁",
-        "balloon" | "balloons" => "🎈",
-        "tada" | "party" | "celebration" => "🎉",
-        "confetti" => "🎊",
-
-        // Symbols & Misc
-        "check" | "checkmark" | "yes" => "✅",
-        "x" | "cross" | "no" => "❌",
-        "warning" | "warn" => "⚠️",
-        "question" | "?" => "❓",
-        "exclamation" | "!" => "❗",
-        "100" | "hundred" => "💯",
-        "fire" | "lit" | "hot2" => "🔥",
-        "star" | "stars" => "⭐",
-        "sparkles" | "sparkle" => "✨",
-        "boom" | "explosion" => "💥",
-        "zap" | "lightning" | "thunder" => "⚡",
-        "rainbow" => "🌈",
-        "sun" | "sunny" => "☀️",
-        "moon" | "crescent_moon" => "🌙",
-        "cloud" | "cloudy" => "☁️",
-        "rain" | "rainy" => "🌧️",
-        "snow" | "snowy" | "snowflake" => "❄️",
-        "earth" | "globe" | "world" => "🌍",
-        "rocket" => "🚀",
-        "airplane" | "plane" => "✈️",
-        "car" | "automobile" => "🚗",
-        "bus" => "🚌",
-        "train" => "🚃",
-        "bike" | "bicycle" => "🚲",
-        "crown" | "king" | "queen" => "👑",
-        "ring" => "💍",
-        "clock" | "time" => "🕐",
-        "hourglass" => "⏳",
-        "alarm" | "alarm_clock" => "⏰",
-        "music" | "musical_note" => "🎵",
-        "notes" | "musical_notes" => "🎶",
-        "microphone" | "mic" => "🎤",
-        "headphones" | "headphone" => "🎧",
-        "art" | "palette" => "🎨",
-        "movie" | "film" => "🎬",
-        "mask" | "theater" => "🎭",
-        "flag" => "🚩",
-        "white_flag" => "🏳️",
-        "skull_crossbones" | "danger" => "☠️",
-
-        // Arrows & Shapes
-        "arrow_up" | "up" => "⬆️",
-        "arrow_down" | "down" => "⬇️",
-        "arrow_left" | "left" => "⬅️",
-        "arrow_right" | "right" => "➡️",
-        "arrows_counterclockwise" | "refresh" | "reload" => "🔄",
-        "plus" | "add" => "➕",
-        "minus" | "subtract" => "➖",
+    EMOJI_SHORTCODES.iter()
+        .find(|(names, _)| names.contains(&lower.as_str()))
+        .map(|(_, emoji)| emoji.to_string())
+}
 
-        _ => return None,
-    };
-    Some(emoji.to_string())
+/// Replace `:name:` shortcodes in a line typed to a world with their emoji; unknown
+/// names are left as typed
+pub fn expand_emoji_shortcodes(s: &str) -> String {
+    use regex::Regex;
+    use std::sync::OnceLock;
+
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r":([A-Za-z0-9_+-]+):").unwrap()
+    });
+
+    re.replace_all(s, |caps: &regex::Captures| {
+        emoji_name_to_unicode(&caps[1]).unwrap_or_else(|| caps[0].to_string())
+    }).to_string()
+}
+
+/// Every shortcode name starting with `prefix` (case-insensitive), sorted, for Tab
+/// completion of `:name:`
+pub fn emoji_shortcodes_matching(prefix: &str) -> Vec<&'static str> {
+    let prefix = prefix.to_lowercase();
+    let mut names: Vec<&'static str> = EMOJI_SHORTCODES.iter()
+        .flat_map(|(names, _)| names.iter().copied())
+        .filter(|name| name.starts_with(&prefix) && !name.contains(['?', '!']))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_timestamp_mode() {
+        assert_eq!(TimestampMode::from_name("HMS"), Some(TimestampMode::Seconds));
+        assert_eq!(TimestampMode::from_name("global"), None);
+        assert_eq!(TimestampMode::Minutes.format(9, 5, 7).as_deref(), Some("09:05"));
+        assert_eq!(TimestampMode::Seconds.format(23, 59, 1).as_deref(), Some("23:59:01"));
+        assert_eq!(TimestampMode::Off.format(9, 5, 7), None);
+        assert_eq!(TimestampMode::Off.toggled(), TimestampMode::Minutes);
+        assert_eq!(TimestampMode::Seconds.toggled(), TimestampMode::Off);
+    }
+
     #[test]
     fn test_from_iana_name_utf8() {
         assert_eq!(Encoding::from_iana_name("UTF-8"), Some(Encoding::Utf8));
@@ -1248,21 +1589,97 @@ mod tests {
         assert_eq!(Encoding::from_iana_name("ISO-8859-1"), Some(Encoding::Latin1));
         assert_eq!(Encoding::from_iana_name("iso-8859-1"), Some(Encoding::Latin1));
         assert_eq!(Encoding::from_iana_name("LATIN1"), Some(Encoding::Latin1));
-        assert_eq!(Encoding::from_iana_name("WINDOWS-1252"), Some(Encoding::Latin1));
-        assert_eq!(Encoding::from_iana_name("CP1252"), Some(Encoding::Latin1));
     }
 
     #[test]
-    fn test_from_iana_name_fansi() {
-        assert_eq!(Encoding::from_iana_name("IBM437"), Some(Encoding::Fansi));
-        assert_eq!(Encoding::from_iana_name("CP437"), Some(Encoding::Fansi));
-        assert_eq!(Encoding::from_iana_name("437"), Some(Encoding::Fansi));
+    fn test_from_iana_name_code_pages() {
+        assert_eq!(Encoding::from_iana_name("IBM437"), Some(Encoding::Cp437));
+        assert_eq!(Encoding::from_iana_name("CP437"), Some(Encoding::Cp437));
+        assert_eq!(Encoding::from_iana_name("437"), Some(Encoding::Cp437));
+        assert_eq!(Encoding::from_iana_name("ibm850"), Some(Encoding::Cp850));
+        assert_eq!(Encoding::from_iana_name("KOI8-R"), Some(Encoding::Koi8r));
+        assert_eq!(Encoding::from_iana_name("WINDOWS-1252"), Some(Encoding::Windows1252));
+        assert_eq!(Encoding::from_iana_name("CP1252"), Some(Encoding::Windows1252));
+    }
+
+    #[test]
+    fn test_fansi_round_trip() {
+        // Every printable byte the server can send comes back as the same byte
+        for b in (0x20..0x7F).chain(0x80..=0xFE) {
+            let text = Encoding::Fansi.decode(&[b]);
+            assert_eq!(Encoding::Fansi.encode(&text), vec![b], "byte {:#04x} ({:?})", b, text);
+        }
+        assert_eq!(Encoding::Fansi.encode(&Encoding::Fansi.decode(&[0xFF])), vec![0xFF, 0xFF], "0xFF goes out doubled as telnet IAC");
+        // What a user types: accented letters CP437 has, box drawing, and punctuation
+        // it lacks sent as ASCII
+        assert_eq!(Encoding::Fansi.encode("Über café ░▒▓ ½ £"), vec![0x9A, b'b', b'e', b'r', b' ', b'c', b'a', b'f', 0x82, b' ', 0xB0, 0xB1, 0xB2, b' ', 0xAB, b' ', 0x9C]);
+        assert_eq!(Encoding::Fansi.encode("“It’s—fine…”"), b"\"It's-fine...\"");
+        assert_eq!(Encoding::Fansi.encode("cafe\u{301} ☃"), b"cafe ?", "a combining accent is dropped");
+        assert_eq!(Encoding::Fansi.decode(&Encoding::Fansi.encode("Ça va? ±5°")), "Ça va? ±5°");
+    }
+
+    #[test]
+    fn test_code_page_round_trip() {
+        let cases = [
+            (Encoding::Cp437, "╔═╗ ░▒▓ Ç", vec![0xC9, 0xCD, 0xBB, b' ', 0xB0, 0xB1, 0xB2, b' ', 0x80]),
+            (Encoding::Fansi, "█▀", vec![0xDB, 0xDF]),
+            (Encoding::Cp850, "Ø¤Ý", vec![0x9D, 0xCF, 0xED]),
+            (Encoding::Koi8r, "Привет", vec![0xF0, 0xD2, 0xC9, 0xD7, 0xC5, 0xD4]),
+            (Encoding::Windows1252, "“€5” é", vec![0x93, 0x80, b'5', 0x94, b' ', 0xE9]),
+            (Encoding::Latin1, "café", vec![b'c', b'a', b'f', 0xE9]),
+        ];
+        for (encoding, text, bytes) in cases {
+            assert_eq!(encoding.encode(text), bytes, "{:?} encode", encoding);
+            assert_eq!(encoding.decode(&bytes), text, "{:?} decode", encoding);
+        }
+        // Unmappable characters become '?', and 0xFF is doubled as telnet IAC
+        assert_eq!(Encoding::Koi8r.encode("é→"), b"??");
+        assert_eq!(Encoding::Latin1.line_bytes("ÿ"), vec![0xFF, 0xFF, b'\r', b'\n']);
+        assert_eq!(Encoding::Utf8.line_bytes("é"), "é\r\n".as_bytes());
+    }
+
+    #[test]
+    fn test_cjk_encodings() {
+        let cases = [
+            (Encoding::Big5, "你好 hp", vec![0xA7, 0x41, 0xA6, 0x6E, b' ', b'h', b'p']),
+            (Encoding::Gbk, "你好", vec![0xC4, 0xE3, 0xBA, 0xC3]),
+            (Encoding::ShiftJis, "こんにちは", vec![0x82, 0xB1, 0x82, 0xF1, 0x82, 0xC9, 0x82, 0xBF, 0x82, 0xCD]),
+        ];
+        for (encoding, text, bytes) in cases {
+            assert_eq!(encoding.encode(text), bytes, "{:?} encode", encoding);
+            assert_eq!(encoding.decode(&bytes), text, "{:?} decode", encoding);
+        }
+        assert_eq!(Encoding::ShiftJis.encode("é好"), b"?\x8D\x44");
+        assert_eq!(Encoding::from_iana_name("Shift_JIS"), Some(Encoding::ShiftJis));
+
+        // A lead byte at the end waits for its trail byte, which may look like ASCII
+        assert_eq!(Encoding::Big5.incomplete_tail(&[b'a', 0xA7]), 1);
+        assert_eq!(Encoding::Big5.incomplete_tail(&[0xA7, 0x41]), 0);
+        assert_eq!(Encoding::Gbk.incomplete_tail(&[0x81, 0x30, 0x81]), 3);
+        assert_eq!(Encoding::ShiftJis.incomplete_tail(&[0xB1, 0x82]), 1, "0xB1 is a one-byte katakana");
+        assert_eq!(Encoding::Utf8.incomplete_tail(&[0xE4]), 0);
+    }
+
+    #[test]
+    fn test_encoding_names_and_shared() {
+        let mut encoding = Encoding::Utf8;
+        for _ in 0..10 {
+            assert_eq!(Encoding::from_name(encoding.name()), encoding);
+            assert_eq!(encoding.next().prev(), encoding);
+            encoding = encoding.next();
+        }
+        assert_eq!(encoding, Encoding::Utf8, "next() cycles through every encoding");
+        let shared = SharedEncoding::default();
+        let writer = shared.clone();
+        assert_eq!(writer.get(), Encoding::Utf8);
+        shared.set(Encoding::Koi8r);
+        assert_eq!(writer.get(), Encoding::Koi8r);
     }
 
     #[test]
     fn test_from_iana_name_unknown() {
         assert_eq!(Encoding::from_iana_name("EBCDIC"), None);
-        assert_eq!(Encoding::from_iana_name("SHIFT_JIS"), None);
+        assert_eq!(Encoding::from_iana_name("EUC-KR"), None);
         assert_eq!(Encoding::from_iana_name(""), None);
     }
 
@@ -1272,4 +1689,20 @@ mod tests {
         assert_eq!(Encoding::Latin1.iana_name(), "ISO-8859-1");
         assert_eq!(Encoding::Fansi.iana_name(), "IBM437");
     }
+
+    #[test]
+    fn test_emoji_shortcodes() {
+        assert_eq!(convert_discord_emojis("gg <:Thumbsup:123>"), "gg 👍");
+        assert_eq!(expand_emoji_shortcodes(":Heart: :+1: :-1: ::"), "❤️ 👍 👎 ::");
+        assert_eq!(emoji_shortcodes_matching("HEART"), vec!["heart", "heart_eyes", "heart_eyes_cat", "heartbeat", "hearteyes", "heartpulse"]);
+        assert!(!emoji_shortcodes_matching("").contains(&"?"), "names that can't be typed as :name: are left out");
+    }
+
+    #[test]
+    fn test_find_urls() {
+        assert_eq!(find_urls("see https://example.com/a?b=1, and (http://x.org/y)."),
+            vec!["https://example.com/a?b=1", "http://x.org/y"]);
+        assert_eq!(find_urls("httpd says http:// https://"), Vec::<String>::new());
+        assert_eq!(find_urls("\"https://q.com/\u{200B}long\"!"), vec!["https://q.com/long"]);
+    }
 }
@@ -0,0 +1,148 @@
+//! Lowercase, ANSI-stripped copy of a world's output for the F4 filter.
+//!
+//! A world's index is built the first time the filter is opened on it, then kept in
+//! step with its output (lines arriving, being evicted, archive lines prepended), so
+//! a keystroke in the filter only matches against ready-made text. `sync` finds those
+//! changes at the ends of the buffer, where line seqs run from oldest to newest, and
+//! rebuilds when the buffer was replaced some other way.
+
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+use crate::{strip_ansi_codes, OutputLine};
+
+struct Entry {
+    seq: u64,
+    hash: u64,
+    plain: String,
+}
+
+impl Entry {
+    fn new(line: &OutputLine) -> Self {
+        Self { seq: line.seq, hash: text_hash(&line.text), plain: strip_ansi_codes(&line.text).to_lowercase() }
+    }
+
+    fn matches(&self, line: &OutputLine) -> bool {
+        self.seq == line.seq && self.hash == text_hash(&line.text)
+    }
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Default)]
+pub struct FilterIndex {
+    entries: VecDeque<Entry>,
+    built: bool,
+    /// Bumped whenever the index changes, so the filter knows its matches are stale
+    generation: u64,
+}
+
+impl FilterIndex {
+    /// Whether the filter has been used on this world (only then is it kept up to date)
+    pub fn is_built(&self) -> bool {
+        self.built
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Lowercase plain text of output line `idx`
+    pub fn plain(&self, idx: usize) -> &str {
+        &self.entries[idx].plain
+    }
+
+    /// Bring the index in line with `lines`, indexing only what changed
+    pub fn sync(&mut self, lines: &VecDeque<OutputLine>) {
+        self.built = true;
+        let before = (self.entries.len(), self.entries.front().map(|e| e.seq), self.entries.back().map(|e| (e.seq, e.hash)));
+
+        // Lines evicted from the front
+        let first_seq = lines.front().map_or(u64::MAX, |l| l.seq);
+        while self.entries.front().is_some_and(|e| e.seq < first_seq) {
+            self.entries.pop_front();
+        }
+        // Archive lines prepended
+        if let Some(front_seq) = self.entries.front().map(|e| e.seq) {
+            let prepended = lines.iter().take_while(|l| l.seq < front_seq).count();
+            for line in lines.range(..prepended).rev() {
+                self.entries.push_front(Entry::new(line));
+            }
+        }
+        // Lines removed or rewritten at the end (a partial line is completed in place)
+        self.entries.truncate(lines.len());
+        while self.entries.back().is_some_and(|e| !e.matches(&lines[self.entries.len() - 1])) {
+            self.entries.pop_back();
+        }
+        // Anything else means the buffer was replaced
+        if self.entries.front().is_some_and(|e| !e.matches(&lines[0])) {
+            self.entries.clear();
+        }
+        for line in lines.range(self.entries.len()..) {
+            self.entries.push_back(Entry::new(line));
+        }
+
+        let after = (self.entries.len(), self.entries.front().map(|e| e.seq), self.entries.back().map(|e| (e.seq, e.hash)));
+        if before != after {
+            self.generation += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(texts: &[(&str, u64)]) -> VecDeque<OutputLine> {
+        texts.iter().map(|&(t, seq)| OutputLine::new(t.to_string(), seq)).collect()
+    }
+
+    fn plain(index: &FilterIndex) -> Vec<&str> {
+        (0..index.len()).map(|i| index.plain(i)).collect()
+    }
+
+    #[test]
+    fn test_sync_follows_appends_evictions_and_prepends() {
+        let mut index = FilterIndex::default();
+        let mut buf = lines(&[("\x1b[1mOne\x1b[0m", 1), ("Two", 2)]);
+        index.sync(&buf);
+        assert_eq!(plain(&index), vec!["one", "two"]);
+        let gen = index.generation();
+        index.sync(&buf);
+        assert_eq!(index.generation(), gen);
+
+        // New lines, the oldest evicted, and a partial line completed
+        buf.push_back(OutputLine::new("Thr".to_string(), 3));
+        index.sync(&buf);
+        buf.pop_front();
+        buf.back_mut().unwrap().text = "Three".to_string();
+        buf.push_back(OutputLine::new("Four".to_string(), 4));
+        index.sync(&buf);
+        assert_eq!(plain(&index), vec!["two", "three", "four"]);
+        assert!(index.generation() > gen);
+
+        // Archive lines go in front
+        buf.push_front(OutputLine::new("Zero".to_string(), 0));
+        index.sync(&buf);
+        assert_eq!(plain(&index), vec!["zero", "two", "three", "four"]);
+
+        // A replaced buffer is reindexed
+        let buf = lines(&[("Other", 2), ("World", 9)]);
+        index.sync(&buf);
+        assert_eq!(plain(&index), vec!["other", "world"]);
+        index.sync(&VecDeque::new());
+        assert!(index.is_empty() && index.is_built());
+    }
+}
@@ -0,0 +1,135 @@
+//! The managed gag list.
+//!
+//! A gag hides every line matching its wildcard pattern (`*` and `?`, matched
+//! case-insensitively against the whole line with ANSI codes removed), like an
+//! action that only gags but without having to build one. Gags can be scoped to
+//! one world, disabled without being forgotten, or made temporary: a temporary
+//! gag lasts until Clay exits and is never written to disk.
+//!
+//! Gags are managed with `/gag` and `/ungag` (or the popup opened by a bare
+//! `/gag`) and persisted in the `[gags]` section of the settings file, separate
+//! from actions.
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+/// A single gag. Sent to web/GUI clients as is; `compile` must run on one received
+/// from a client before it can match.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gag {
+    /// Wildcard pattern the whole line must match
+    pub pattern: String,
+    /// Only apply to lines from this world (empty = all worlds)
+    #[serde(default)]
+    pub world: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Session-only: skipped when saving settings
+    #[serde(default)]
+    pub temporary: bool,
+    /// `pattern` compiled by `compile`
+    #[serde(skip)]
+    regex: Option<Regex>,
+}
+
+impl PartialEq for Gag {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+            && self.world == other.world
+            && self.enabled == other.enabled
+            && self.temporary == other.temporary
+    }
+}
+
+impl Gag {
+    pub fn new(pattern: &str) -> Self {
+        let mut gag = Self {
+            pattern: pattern.trim().to_string(),
+            world: String::new(),
+            enabled: true,
+            temporary: false,
+            regex: None,
+        };
+        gag.compile();
+        gag
+    }
+
+    /// Compile `pattern`; a pattern that can't compile never matches
+    pub fn compile(&mut self) {
+        self.regex = RegexBuilder::new(&crate::actions::wildcard_to_regex(&self.pattern))
+            .case_insensitive(true)
+            .build()
+            .ok();
+    }
+
+    /// Whether this gag hides `plain_line` (ANSI already stripped) from `world_name`
+    pub fn matches(&self, plain_line: &str, world_name: &str) -> bool {
+        self.enabled
+            && (self.world.is_empty() || self.world.eq_ignore_ascii_case(world_name))
+            && self.regex.as_ref().is_some_and(|re| re.is_match(plain_line))
+    }
+
+    /// Serialize as `world|enabled|pattern` for the settings file (the pattern goes
+    /// last since it may itself contain `|`)
+    pub fn to_dat(&self) -> String {
+        format!("{}|{}|{}", self.world, self.enabled, self.pattern)
+    }
+
+    /// Parse the `to_dat` form. Returns None for malformed entries.
+    pub fn from_dat(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(3, '|');
+        let world = parts.next()?;
+        let enabled = parts.next()? != "false";
+        let pattern = parts.next()?;
+        if pattern.trim().is_empty() {
+            return None;
+        }
+        let mut gag = Self::new(pattern);
+        gag.world = world.to_string();
+        gag.enabled = enabled;
+        Some(gag)
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Whether any enabled gag hides `plain_line` (ANSI already stripped) from `world_name`
+pub fn is_gagged(gags: &[Gag], plain_line: &str, world_name: &str) -> bool {
+    gags.iter().any(|g| g.matches(plain_line, world_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gag_dat_round_trip() {
+        let mut gag = Gag::new("* says, \"a|b\"");
+        gag.world = "MyMud".to_string();
+        gag.enabled = false;
+        let dat = gag.to_dat();
+        assert_eq!(dat, "MyMud|false|* says, \"a|b\"");
+        assert_eq!(Gag::from_dat(&dat), Some(gag));
+        assert_eq!(Gag::from_dat("|true|"), None);
+        assert_eq!(Gag::from_dat("garbage"), None);
+    }
+
+    #[test]
+    fn test_gag_matching() {
+        let mut spam = Gag::new("* has arrived.");
+        spam.world = "mud".to_string();
+        let gags = vec![spam, Gag::new("[OOC]*")];
+
+        assert!(is_gagged(&gags, "Bob has arrived.", "MUD"));
+        assert!(is_gagged(&gags, "bob HAS ARRIVED.", "mud"));
+        assert!(!is_gagged(&gags, "Bob has arrived.", "other"));
+        assert!(!is_gagged(&gags, "Bob has arrived. Again.", "mud"));
+        assert!(is_gagged(&gags, "[OOC] Bob: hi", "other"));
+
+        let mut off = Gag::new("*");
+        off.enabled = false;
+        assert!(!is_gagged(&[off], "anything", "mud"));
+    }
+}
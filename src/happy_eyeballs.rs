@@ -0,0 +1,117 @@
+//! Connecting to a world host over IPv4 and IPv6 (RFC 8305 "happy eyeballs").
+//!
+//! Every A and AAAA record for the host is tried, address families alternating in
+//! the resolver's preference order (so IPv6 first where the system prefers it).
+//! Attempts are staggered: the next address starts when the previous attempt fails
+//! or has had 250 ms without an answer, and the first connection to complete wins.
+//! A broken IPv6 route costs a quarter second instead of a full connect timeout.
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+
+/// How long an attempt runs before the next address is tried alongside it
+const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Alternate address families, keeping each family's order and starting with the
+/// family of the resolver's first answer
+fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let mut unique: Vec<SocketAddr> = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        if !unique.contains(&addr) {
+            unique.push(addr);
+        }
+    }
+    let first_v6 = unique.first().is_some_and(|a| a.is_ipv6());
+    let (mut preferred, mut other): (Vec<SocketAddr>, Vec<SocketAddr>) =
+        unique.into_iter().partition(|a| a.is_ipv6() == first_v6);
+    preferred.reverse();
+    other.reverse();
+    let mut ordered = Vec::with_capacity(preferred.len() + other.len());
+    loop {
+        match (preferred.pop(), other.pop()) {
+            (None, None) => break,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+    ordered
+}
+
+/// Connect to `host:port`, racing its addresses, and return the stream and the
+/// address it reached. The error is the first address's when every attempt fails.
+pub async fn connect(host: &str, port: &str) -> io::Result<(TcpStream, SocketAddr)> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host(format!("{}:{}", host, port)).await?.collect();
+    if addrs.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::AddrNotAvailable, format!("Could not resolve {}", host)));
+    }
+    let mut pending = interleave(addrs).into_iter();
+    let mut attempts = JoinSet::new();
+    let mut first_err: Option<io::Error> = None;
+    loop {
+        if let Some(addr) = pending.next() {
+            attempts.spawn(async move { (addr, TcpStream::connect(addr).await) });
+        } else if attempts.is_empty() {
+            return Err(first_err.unwrap_or_else(|| io::Error::other("Connection failed")));
+        }
+        let finished = if !pending.as_slice().is_empty() {
+            match tokio::time::timeout(ATTEMPT_DELAY, attempts.join_next()).await {
+                Ok(finished) => finished,
+                Err(_) => continue, // still waiting: start the next address too
+            }
+        } else {
+            attempts.join_next().await
+        };
+        match finished {
+            // Dropping `attempts` aborts the ones still running
+            Some(Ok((addr, Ok(stream)))) => return Ok((stream, addr)),
+            Some(Ok((_, Err(e)))) => {
+                first_err.get_or_insert(e);
+            }
+            Some(Err(_)) | None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_interleave_alternates_families() {
+        let ordered = interleave(vec![
+            addr("[2001:db8::1]:23"),
+            addr("[2001:db8::2]:23"),
+            addr("192.0.2.1:23"),
+            addr("[2001:db8::1]:23"),
+            addr("192.0.2.2:23"),
+            addr("192.0.2.3:23"),
+        ]);
+        assert_eq!(ordered, vec![
+            addr("[2001:db8::1]:23"),
+            addr("192.0.2.1:23"),
+            addr("[2001:db8::2]:23"),
+            addr("192.0.2.2:23"),
+            addr("192.0.2.3:23"),
+        ]);
+        // IPv4 first when the resolver put it first
+        let ordered = interleave(vec![addr("192.0.2.1:23"), addr("[2001:db8::1]:23")]);
+        assert_eq!(ordered[0], addr("192.0.2.1:23"));
+    }
+
+    #[tokio::test]
+    async fn test_connect_reports_the_address_reached() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port().to_string();
+        let (_stream, reached) = connect("127.0.0.1", &port).await.unwrap();
+        assert_eq!(reached, listener.local_addr().unwrap());
+        // Nothing listening: the attempt's error comes back
+        drop(listener);
+        assert!(connect("127.0.0.1", &port).await.is_err());
+    }
+}
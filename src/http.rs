@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -64,9 +64,9 @@ impl<S: AsyncWrite + Unpin> AsyncWrite for PrefixedStream<S> {
 // Remote Connection Logging
 // ============================================================================
 
-/// Log a remote connection event to ~/.clay/remote.log
+/// Log a remote connection event to ~/.config/clay/remote.log
 /// `cargo test` was appending real BANNED/KNOCK-*/... lines to the user's live
-/// ~/.clay/remote.log — tests exercise the same gate/ban/knock code paths that
+/// ~/.config/clay/remote.log — tests exercise the same gate/ban/knock code paths that
 /// production does, but must never touch a real user's files, so this is a no-op in
 /// test builds (the `#[cfg(not(test))]` real implementation below never compiles in).
 #[cfg(test)]
@@ -125,6 +125,15 @@ const WEB_STYLE_CSS: &str = include_str!("web/style.css");
 /// Embedded JavaScript for the web interface
 const WEB_APP_JS: &str = include_str!("web/app.js");
 
+/// app.js and style.css gzipped at build time, with their ETags (build.rs write_web_assets)
+const WEB_APP_JS_GZ: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/app.js.gz"));
+const WEB_APP_JS_ETAG: &str = env!("WEB_APP_JS_ETAG");
+const WEB_STYLE_CSS_GZ: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/style.css.gz"));
+const WEB_STYLE_CSS_ETAG: &str = env!("WEB_STYLE_CSS_ETAG");
+
+/// Service worker for Web Push notifications (see web_push.rs)
+const WEB_SW_JS: &str = include_str!("web/sw.js");
+
 /// Embedded theme editor HTML
 const WEB_THEME_EDITOR_HTML: &str = include_str!("web/theme-editor.html");
 
@@ -230,7 +239,7 @@ async fn redirect_http_to_https<S: AsyncRead + AsyncWrite + Unpin>(
         }
         // Serve / legacy fallthrough: the original requested path is what would be
         // served (or 404'd/405'd) on the HTTPS side, so redirect to it unchanged.
-        RouteDecision::Serve(_) | RouteDecision::NotFoundLegacy | RouteDecision::MethodNotAllowedLegacy => {
+        RouteDecision::Serve(_) | RouteDecision::Api(_) | RouteDecision::NotFoundLegacy | RouteDecision::MethodNotAllowedLegacy => {
             let response = build_redirect_response(&build_location(path), false);
             let _ = stream.write_all(&response).await;
         }
@@ -246,6 +255,43 @@ fn parse_http_request(request: &str) -> Option<(&str, &str)> {
     Some((method, path))
 }
 
+/// The caching and compression headers of a request that the asset routes honor
+#[derive(Debug, Clone, Default, PartialEq)]
+struct AssetRequest {
+    /// `Accept-Encoding` lists gzip (without `q=0`)
+    accepts_gzip: bool,
+    /// Raw `If-None-Match` value
+    if_none_match: Option<String>,
+}
+
+impl AssetRequest {
+    fn from_request(request: &str) -> Self {
+        let mut prefs = AssetRequest::default();
+        for line in request.lines().skip(1) {
+            let Some((name, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("accept-encoding") {
+                prefs.accepts_gzip = value.split(',').any(|coding| {
+                    let mut parts = coding.split(';').map(str::trim);
+                    let name = parts.next().unwrap_or("");
+                    let refused = parts.any(|p| p.strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()) == Some(0.0));
+                    (name.eq_ignore_ascii_case("gzip") || name == "*") && !refused
+                });
+            } else if name.eq_ignore_ascii_case("if-none-match") {
+                prefs.if_none_match = Some(value.to_string());
+            }
+        }
+        prefs
+    }
+
+    /// `If-None-Match` names `etag` (a quoted entity tag), so a 304 will do
+    fn matches(&self, etag: &str) -> bool {
+        self.if_none_match.as_deref().is_some_and(|v| {
+            v.split(',').map(str::trim).any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+        })
+    }
+}
+
 /// Extract Host header from HTTP request (without port)
 fn get_host_from_request(request: &str) -> String {
     for line in request.lines() {
@@ -281,18 +327,21 @@ fn build_binary_http_response(status: u16, status_text: &str, content_type: &str
     response
 }
 
+/// Headers every text response carries
+const TEXT_SECURITY_HEADERS: &str = "X-Frame-Options: DENY\r\n\
+    X-Content-Type-Options: nosniff\r\n\
+    Referrer-Policy: no-referrer\r\n\
+    Content-Security-Policy: default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data: https://cdn.discordapp.com; connect-src 'self' ws: wss:; frame-ancestors 'none'\r\n";
+
 fn build_http_response(status: u16, status_text: &str, content_type: &str, body: &str, is_https: bool) -> Vec<u8> {
     let mut headers = format!(
         "HTTP/1.1 {} {}\r\n\
          Content-Type: {}; charset=utf-8\r\n\
          Content-Length: {}\r\n\
          Cache-Control: no-store\r\n\
-         X-Frame-Options: DENY\r\n\
-         X-Content-Type-Options: nosniff\r\n\
-         Referrer-Policy: no-referrer\r\n\
-         Content-Security-Policy: default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data: https://cdn.discordapp.com; connect-src 'self' ws: wss:; frame-ancestors 'none'\r\n\
+         {}\
          Connection: close\r\n",
-        status, status_text, content_type, body.len()
+        status, status_text, content_type, body.len(), TEXT_SECURITY_HEADERS
     );
     if is_https {
         headers.push_str("Strict-Transport-Security: max-age=31536000\r\n");
@@ -302,6 +351,76 @@ fn build_http_response(status: u16, status_text: &str, content_type: &str, body:
     headers.into_bytes()
 }
 
+/// Build the response for a web UI asset: `304 Not Modified` when the client already
+/// has this version, otherwise the body (the gzipped copy if the client takes gzip).
+/// `no-cache` makes browsers revalidate on each load, which costs one round trip and
+/// no body, so a rebuilt Clay is picked up right away.
+fn build_asset_response(
+    content_type: &str,
+    body: &[u8],
+    gzipped: &[u8],
+    etag: &str,
+    prefs: &AssetRequest,
+    is_https: bool,
+) -> Vec<u8> {
+    let use_gzip = prefs.accepts_gzip && !gzipped.is_empty();
+    // Each encoding is its own representation, so each gets its own entity tag
+    let etag = if use_gzip { format!("\"{}-gz\"", etag) } else { format!("\"{}\"", etag) };
+    let hsts = if is_https { "Strict-Transport-Security: max-age=31536000\r\n" } else { "" };
+    if prefs.matches(&etag) {
+        return format!(
+            "HTTP/1.1 304 Not Modified\r\n\
+             ETag: {}\r\n\
+             Cache-Control: no-cache\r\n\
+             Vary: Accept-Encoding\r\n\
+             {}\
+             Connection: close\r\n\r\n",
+            etag, hsts
+        ).into_bytes();
+    }
+    let body = if use_gzip { gzipped } else { body };
+    let mut response = format!(
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: {}; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         {}\
+         ETag: {}\r\n\
+         Cache-Control: no-cache\r\n\
+         Vary: Accept-Encoding\r\n\
+         {}\
+         {}\
+         Connection: close\r\n\r\n",
+        content_type, body.len(), if use_gzip { "Content-Encoding: gzip\r\n" } else { "" },
+        etag, TEXT_SECURITY_HEADERS, hsts
+    ).into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Gzip a response body built per request (index.html, which is templated)
+fn gzip_bytes(body: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    if encoder.write_all(body).is_err() {
+        return Vec::new();
+    }
+    encoder.finish().unwrap_or_default()
+}
+
+/// The headers of a response, for answering HEAD (Content-Length still gives the
+/// size GET would send)
+fn strip_response_body(mut response: Vec<u8>) -> Vec<u8> {
+    if let Some(end) = response.windows(4).position(|w| w == b"\r\n\r\n") {
+        response.truncate(end + 4);
+    }
+    response
+}
+
+/// GET, or HEAD (answered like GET without the body)
+fn is_read_method(method: &str) -> bool {
+    method == "GET" || method == "HEAD"
+}
+
 /// Route result from handle_http_routes - indicates whether a 404 violation occurred
 enum RouteResult {
     /// Route matched, response ready
@@ -332,10 +451,12 @@ fn is_websocket_upgrade(request: &str) -> bool {
 /// so any request that gets here on an already-knocked connection is a static request
 /// the knock does not authorize. Silently drop it, no violation (the key was already
 /// proven; don't self-ban a buggy client) — `route_connection` logs `KNOCK-HTTP-DENIED`
-/// for this case instead of the usual `HTTP-DROP`.
+/// for this case instead of the usual `HTTP-DROP`. The REST API (see `api_endpoint`)
+/// is routed here too, so it gets the same knock and path gating as static content.
 #[derive(Debug, Clone, PartialEq)]
 enum RouteDecision {
     Serve(String),
+    Api(ApiEndpoint),
     Redirect(String),
     SilentDrop { violation: Option<String> },
     NotFoundLegacy,
@@ -346,7 +467,7 @@ enum RouteDecision {
 /// `/{web_path}/` prefix. A single list so `decide_route`'s allow-check and
 /// `handle_http_routes`'s content dispatch can't drift apart.
 const KNOWN_ASSET_PATHS: &[&str] = &[
-    "/", "/index.html", "/style.css", "/app.js", "/theme-editor",
+    "/", "/index.html", "/style.css", "/app.js", "/sw.js", "/theme-editor",
     "/keybind-editor", "/action-editor",
     "/fonts/jetbrains-mono-latin-400.woff2", "/fonts/nunito-latin-400.woff2",
     "/favicon.ico", "/clay2.png",
@@ -396,12 +517,17 @@ fn decide_route(
         return RouteDecision::SilentDrop { violation: None };
     }
 
+    // The API checks its own methods and bearer token
+    if let Some(endpoint) = api_endpoint(path, web_path, is_localhost) {
+        return RouteDecision::Api(endpoint);
+    }
+
     let legacy_mode = web_path.is_empty();
 
     // Localhost: legacy roots AND /{web_path}/... both always work, unconditionally —
     // the GUI WebView (which always connects via 127.0.0.1) must never be gated.
     if is_localhost {
-        if method != "GET" {
+        if !is_read_method(method) {
             return RouteDecision::MethodNotAllowedLegacy;
         }
         if is_known_asset(path) {
@@ -422,7 +548,7 @@ fn decide_route(
 
     // Non-localhost, legacy mode (web_path == "") — unchanged pre-stealth behavior.
     if legacy_mode {
-        if method != "GET" {
+        if !is_read_method(method) {
             return RouteDecision::MethodNotAllowedLegacy;
         }
         if is_known_asset(path) {
@@ -432,7 +558,7 @@ fn decide_route(
     }
 
     // Non-localhost, stealth mode.
-    if method != "GET" {
+    if !is_read_method(method) {
         return RouteDecision::SilentDrop { violation: Some("method-not-allowed".to_string()) };
     }
 
@@ -512,6 +638,9 @@ fn build_redirect_response(location: &str, is_https: bool) -> Vec<u8> {
 /// `path` is the already-resolved inner path (query string stripped, `/{web_path}` prefix
 /// already stripped by the caller via `decide_route`/`strip_web_path_prefix`).
 /// WS_PORT is injected as 0 (sentinel) — the JS client uses window.location.port.
+/// `/`, `/style.css` and `/app.js` honor `prefs` (gzip, ETag revalidation); the
+/// caller strips the body for HEAD.
+#[allow(clippy::too_many_arguments)]
 fn handle_http_routes(
     method: &str,
     path: &str,
@@ -520,8 +649,9 @@ fn handle_http_routes(
     theme_css_vars: &str,
     is_https: bool,
     web_path: &str,
+    prefs: &AssetRequest,
 ) -> Option<RouteResult> {
-    if method != "GET" {
+    if !is_read_method(method) {
         return Some(RouteResult::MethodNotAllowed(
             build_http_response(405, "Method Not Allowed", "text/plain", "Method Not Allowed", is_https)
         ));
@@ -543,13 +673,18 @@ fn handle_http_routes(
                 .replace("{{CONNECTION_MODE}}", "auto")
                 .replace("{{SHOW_CONNECTION_WINDOW}}", "false")
                 .replace("{{THEME_CSS_VARS}}", theme_css_vars);
-            RouteResult::Ok(build_http_response(200, "OK", "text/html", &html, is_https))
+            let etag: String = Sha256::digest(html.as_bytes()).iter().take(8).map(|b| format!("{:02x}", b)).collect();
+            let gzipped = if prefs.accepts_gzip { gzip_bytes(html.as_bytes()) } else { Vec::new() };
+            RouteResult::Ok(build_asset_response("text/html", html.as_bytes(), &gzipped, &etag, prefs, is_https))
         }
         "/style.css" => {
-            RouteResult::Ok(build_http_response(200, "OK", "text/css", WEB_STYLE_CSS, is_https))
+            RouteResult::Ok(build_asset_response("text/css", WEB_STYLE_CSS.as_bytes(), WEB_STYLE_CSS_GZ, WEB_STYLE_CSS_ETAG, prefs, is_https))
         }
         "/app.js" => {
-            RouteResult::Ok(build_http_response(200, "OK", "application/javascript", WEB_APP_JS, is_https))
+            RouteResult::Ok(build_asset_response("application/javascript", WEB_APP_JS.as_bytes(), WEB_APP_JS_GZ, WEB_APP_JS_ETAG, prefs, is_https))
+        }
+        "/sw.js" => {
+            RouteResult::Ok(build_http_response(200, "OK", "application/javascript", WEB_SW_JS, is_https))
         }
         "/theme-editor" => {
             let html = WEB_THEME_EDITOR_HTML
@@ -612,7 +747,10 @@ enum GateResult {
 // Wire protocol, all on the same raw TCP socket, before TLS/HTTP:
 //   1. C->S HELLO     (6 bytes):  C7 4C 41 59 01 00   (magic + version + reserved)
 //   2. S->C CHALLENGE (34 bytes): C7 4B + 32 random bytes
-//   3. C->S RESPONSE  (32 bytes): raw SHA256(auth_key_utf8_bytes || challenge_bytes)
+//   3. C->S RESPONSE  (32 bytes): raw SHA256(secret_utf8_bytes || challenge_bytes), where
+//                                 the secret is the hex SHA-256 of the auth key or of a
+//                                 device token (/web tokens), which is all the server
+//                                 keeps of a token. Older clients use the auth key itself.
 //   4. S->C ACK       (2 bytes):  C7 06   (only on success)
 // The leading 0xC7 is not `0x16` (TLS ClientHello), not ASCII (no HTTP method
 // collision), and an invalid UTF-8 lead byte, so a 1-byte peek disambiguates all three
@@ -702,21 +840,20 @@ async fn run_knock_handshake(
         }
     }
 
-    // No stored key (unset, or multiuser mode where the Arc is always None) => every
+    // No stored key (unset, or multiuser mode where the list is always empty) => every
     // knock fails. Documented limitation (D4): multiuser hosts cannot be reached by a
     // non-allow-listed IP at all.
-    let key = gate.auth_key.read().unwrap().clone();
-    let key = match key {
-        Some(k) if !k.is_empty() => k,
-        _ => {
-            log_remote_event("KNOCK-FAIL", client_ip, "no auth key configured");
-            gate.strike(client_ip, "knock-no-key").await;
-            return KnockOutcome::Fail;
-        }
-    };
+    let keys: Vec<String> = gate.auth_keys.read().unwrap().iter().filter(|k| !k.is_empty()).cloned().collect();
+    if keys.is_empty() {
+        log_remote_event("KNOCK-FAIL", client_ip, "no auth key configured");
+        gate.strike(client_ip, "knock-no-key").await;
+        return KnockOutcome::Fail;
+    }
 
-    let expected = knock_expected_response(&key, &challenge);
-    if !crate::util::constant_time_eq(&response, &expected) {
+    // Every key is checked (no early exit), so timing doesn't tell which one matched
+    let matched = keys.iter()
+        .fold(false, |matched, key| crate::util::constant_time_eq(&response, &knock_expected_response(key, &challenge)) | matched);
+    if !matched {
         log_remote_event("KNOCK-FAIL", client_ip, "digest mismatch");
         gate.strike(client_ip, "knock-bad-digest").await;
         return KnockOutcome::Fail;
@@ -821,21 +958,13 @@ async fn gate_connection(
         }
     }
 
-    let allow_list = gate.allow_list.read().unwrap().clone();
-    let allow_list_empty = allow_list.is_empty();
+    // A Trusted Proxies address isn't the client: `route_connection` runs this check
+    // on the address it forwarded for once the request headers are in
+    if !is_localhost && !knocked && gate.trusts_proxy(client_ip) {
+        return GateResult::Proceed { knocked: false, in_allow_list: false };
+    }
 
-    let matched = if is_localhost || allow_list_empty {
-        false // unused by decide_gate on this branch
-    } else {
-        let whitelisted_host = gate.whitelisted_host.read().unwrap().clone();
-        let has_hostname_patterns = allow_list.iter().any(|p| crate::websocket::is_hostname_pattern(p));
-        let hostname = if has_hostname_patterns {
-            crate::websocket::reverse_dns_lookup(client_ip).await
-        } else {
-            None
-        };
-        gate_ip_matches(client_ip, whitelisted_host.as_deref(), &allow_list, hostname.as_deref())
-    };
+    let matched = allow_list_matches(client_ip, is_localhost, gate).await;
 
     if knocked {
         // A proven knock bypasses the allow-list Drop outcome entirely (D4: "Knocks are
@@ -845,6 +974,29 @@ async fn gate_connection(
         return GateResult::Proceed { knocked: true, in_allow_list: matched };
     }
 
+    admit(client_ip, is_localhost, matched, gate)
+}
+
+/// Whether `client_ip` is on the allow list (or is `whitelisted_host`). False without
+/// looking when it's localhost or the list is empty, which `decide_gate` doesn't ask.
+async fn allow_list_matches(client_ip: &str, is_localhost: bool, gate: &SecurityGate) -> bool {
+    let allow_list = gate.allow_list.read().unwrap().clone();
+    if is_localhost || allow_list.is_empty() {
+        return false;
+    }
+    let whitelisted_host = gate.whitelisted_host.read().unwrap().clone();
+    let has_hostname_patterns = allow_list.iter().any(|p| crate::websocket::is_hostname_pattern(p));
+    let hostname = if has_hostname_patterns {
+        crate::websocket::reverse_dns_lookup(client_ip).await
+    } else {
+        None
+    };
+    gate_ip_matches(client_ip, whitelisted_host.as_deref(), &allow_list, hostname.as_deref())
+}
+
+/// `decide_gate` for an un-knocked connection, logging a drop
+fn admit(client_ip: &str, is_localhost: bool, matched: bool, gate: &SecurityGate) -> GateResult {
+    let allow_list_empty = gate.allow_list.read().unwrap().is_empty();
     match decide_gate(is_localhost, allow_list_empty, matched) {
         GateDecision::Proceed { in_allow_list } => GateResult::Proceed { knocked: false, in_allow_list },
         GateDecision::Drop => {
@@ -854,6 +1006,44 @@ async fn gate_connection(
     }
 }
 
+/// The client a reverse proxy forwarded this request for: walking `X-Forwarded-For`
+/// from the right (each proxy appends the address it heard from), the first address
+/// that isn't itself a trusted proxy. Entries to its left were written by the client
+/// and can't be believed. None without the header; an error names an entry that isn't
+/// an address.
+fn forwarded_client(request: &str, trusts_proxy: impl Fn(&str) -> bool) -> Option<Result<std::net::IpAddr, String>> {
+    let headers = request.lines().skip(1).take_while(|line| !line.is_empty());
+    let entries: Vec<&str> = headers
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("x-forwarded-for").then_some(value)
+        })
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    let mut client = None;
+    for entry in entries.iter().rev() {
+        let Some(ip) = parse_forwarded_ip(entry) else {
+            return Some(Err(entry.to_string()));
+        };
+        client = Some(Ok(ip));
+        if !trusts_proxy(&ip.to_string()) {
+            break;
+        }
+    }
+    client
+}
+
+/// One `X-Forwarded-For` entry: an address, possibly with a port (`1.2.3.4:5`,
+/// `[2001:db8::1]:5`)
+fn parse_forwarded_ip(entry: &str) -> Option<std::net::IpAddr> {
+    if let Ok(ip) = entry.parse() {
+        return Some(ip);
+    }
+    entry.parse::<std::net::SocketAddr>().ok().map(|addr| addr.ip())
+}
+
 /// Route an incoming connection: detect WebSocket upgrades vs HTTP requests.
 /// If ws_state is provided and the request is a WebSocket upgrade, hands off to the WS handler.
 /// Otherwise, serves static HTTP content.
@@ -874,8 +1064,10 @@ async fn route_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
     // (never true merely because the allow list is empty/open-mode).
     in_allow_list: bool,
 ) {
-    let client_ip = client_addr.ip().to_string();
-    let is_localhost = client_addr.ip().is_loopback();
+    let mut client_ip = client_addr.ip().to_string();
+    let mut client_addr = client_addr;
+    let mut is_localhost = client_addr.ip().is_loopback();
+    let mut in_allow_list = in_allow_list;
 
     let mut buf = [0u8; 4096];
     let n = match tokio::time::timeout(
@@ -908,6 +1100,33 @@ async fn route_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
 
     let request = String::from_utf8_lossy(&buf[..n]);
 
+    // Behind a reverse proxy the client is who it forwarded for, and that address gets
+    // the ban check and allow-list gate the proxy's own connection skipped
+    if !knocked && gate.trusts_proxy(&client_ip) {
+        let forwarded = match forwarded_client(&request, |ip| gate.trusts_proxy(ip)) {
+            Some(Ok(ip)) => Some(ip),
+            Some(Err(entry)) => {
+                log_remote_event("HTTP-DROP", &client_ip, &format!("unreadable X-Forwarded-For entry '{}'", entry));
+                return;
+            }
+            None => None,
+        };
+        if forwarded.is_some() || !is_localhost {
+            let ip = forwarded.unwrap_or(client_addr.ip());
+            client_ip = ip.to_string();
+            client_addr = std::net::SocketAddr::new(ip, client_addr.port());
+            is_localhost = ip.is_loopback();
+            if gate.ban_list.is_banned(&client_ip) {
+                return;
+            }
+            let matched = allow_list_matches(&client_ip, is_localhost, gate).await;
+            match admit(&client_ip, is_localhost, matched, gate) {
+                GateResult::Proceed { in_allow_list: listed, .. } => in_allow_list = listed,
+                GateResult::Drop => return,
+            }
+        }
+    }
+
     // Check for WebSocket upgrade
     if is_websocket_upgrade(&request) {
         let ws_path = parse_http_request(&request)
@@ -927,6 +1146,10 @@ async fn route_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
             log_remote_event("WS-PATH-DROP", &client_ip, ws_path);
             return;
         }
+        if !gate.ban_list.allow_ws_connect(&client_ip) {
+            log_remote_event("WS-RATE-LIMIT", &client_ip, "too many new connections");
+            return;
+        }
         if let Some(ws_state) = ws_state {
             // Acquire a WebSocket slot before starting the session
             let ws_guard = match ws_counter.try_acquire(&client_ip, MAX_WS_CONNECTIONS_PER_IP) {
@@ -943,6 +1166,7 @@ async fn route_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
             let client_id = ws_state.next_client_id();
             let pw_hash = ws_state.password_hash.read().unwrap().clone();
             let pw_enabled = *ws_state.password_enabled.read().unwrap();
+            let viewer_hash = ws_state.viewer_password_hash.read().unwrap().clone();
             let prefixed = PrefixedStream::new(buf[..n].to_vec(), stream);
             let _ws_guard = ws_guard;
             let _ = crate::websocket::handle_ws_client(
@@ -951,6 +1175,7 @@ async fn route_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
                 ws_state.clients.clone(),
                 pw_hash,
                 pw_enabled,
+                viewer_hash,
                 ws_state.allow_list.clone(),
                 ws_state.whitelisted_host.clone(),
                 client_addr,
@@ -1004,12 +1229,17 @@ async fn route_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
             let _ = stream.write_all(&response).await;
             return;
         }
+        RouteDecision::Api(endpoint) => {
+            handle_api_request(&mut stream, endpoint, method, &request, &buf[..n], ws_state.as_deref(), gate, &client_ip, is_https).await;
+            return;
+        }
         RouteDecision::Serve(inner_path) => (method, inner_path),
         RouteDecision::NotFoundLegacy | RouteDecision::MethodNotAllowedLegacy => (method, path.to_string()),
     };
 
     // Normal HTTP request
-    if let Some(route_result) = handle_http_routes(route_method, &route_path, &host, ws_use_tls, theme_css_vars, is_https, &gate.web_path) {
+    let prefs = AssetRequest::from_request(&request);
+    if let Some(route_result) = handle_http_routes(route_method, &route_path, &host, ws_use_tls, theme_css_vars, is_https, &gate.web_path, &prefs) {
         let response = match route_result {
             RouteResult::Ok(r) => r,
             RouteResult::MethodNotAllowed(r) => {
@@ -1022,6 +1252,7 @@ async fn route_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
                 r
             }
         };
+        let response = if route_method == "HEAD" { strip_response_body(response) } else { response };
 
         if stream.write_all(&response).await.is_ok() {
             let _ = stream.shutdown().await;
@@ -1029,12 +1260,268 @@ async fn route_connection<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
     }
 }
 
+// ============================================================================
+// REST API — POST /api/send, GET /api/worlds
+// ============================================================================
+
+/// Largest request body `/api/send` reads
+const MAX_API_BODY: usize = 4096;
+
+/// How long an API call waits for the app to answer
+const API_TIMEOUT_SECS: u64 = 5;
+
+/// An API endpoint, once its path has been recognized
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ApiEndpoint {
+    Send,
+    Worlds,
+}
+
+/// Recognize an API path. The API lives beside the web UI: under `/{web_path}/api/`
+/// in stealth mode, at `/api/` in legacy mode, and at both for localhost. Anything
+/// else (including unknown `/api/...` paths) goes through `decide_route` as usual.
+fn api_endpoint(path: &str, web_path: &str, is_localhost: bool) -> Option<ApiEndpoint> {
+    let inner = match strip_web_path_prefix(path, web_path) {
+        Some(inner) => inner,
+        None if web_path.is_empty() || is_localhost => path.to_string(),
+        None => return None,
+    };
+    match inner.as_str() {
+        "/api/send" => Some(ApiEndpoint::Send),
+        "/api/worlds" => Some(ApiEndpoint::Worlds),
+        _ => None,
+    }
+}
+
+/// The token from an `Authorization: Bearer <token>` header
+fn bearer_token(request: &str) -> Option<&str> {
+    request.lines().skip(1).take_while(|line| !line.is_empty()).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if !name.trim().eq_ignore_ascii_case("authorization") {
+            return None;
+        }
+        let (scheme, token) = value.trim().split_once(' ')?;
+        scheme.eq_ignore_ascii_case("bearer").then(|| token.trim()).filter(|t| !t.is_empty())
+    })
+}
+
+/// The token is the auth key or one of the device tokens (`/web tokens`), found by its
+/// SHA-256 among the knock secrets (see `App::sync_auth_keys_shared`)
+fn api_token_valid(token: &str, auth_keys: &[String]) -> bool {
+    let token_hash = crate::websocket::hash_password(token);
+    !token.is_empty() && auth_keys.iter().any(|key| !key.is_empty() && crate::util::constant_time_eq(key.as_bytes(), token_hash.as_bytes()))
+}
+
+/// `POST /api/send` body. `world` is a world name (case-insensitive), `world_index`
+/// an index; with neither the command goes to the current world.
+#[derive(Debug, serde::Deserialize)]
+struct ApiSendRequest {
+    command: String,
+    #[serde(default)]
+    world: Option<String>,
+    #[serde(default)]
+    world_index: Option<usize>,
+}
+
+/// The request body: what arrived with the headers, plus the rest of
+/// `Content-Length` read from the stream. None if it's too large or never arrives.
+async fn read_api_body<S: AsyncRead + Unpin>(stream: &mut S, received: &[u8]) -> Option<Vec<u8>> {
+    let header_end = received.windows(4).position(|w| w == b"\r\n\r\n")? + 4;
+    let headers = String::from_utf8_lossy(&received[..header_end]);
+    let content_length = headers.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_API_BODY {
+        return None;
+    }
+    let mut body = received[header_end..].to_vec();
+    while body.len() < content_length {
+        let mut chunk = [0u8; 1024];
+        let n = match tokio::time::timeout(
+            std::time::Duration::from_secs(READ_TIMEOUT_SECS),
+            stream.read(&mut chunk),
+        ).await {
+            Ok(Ok(n)) if n > 0 => n,
+            _ => return None,
+        };
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+    Some(body)
+}
+
+fn build_json_response(status: u16, status_text: &str, body: &serde_json::Value, is_https: bool) -> Vec<u8> {
+    build_http_response(status, status_text, "application/json", &body.to_string(), is_https)
+}
+
+fn api_error(status: u16, status_text: &str, message: &str, is_https: bool) -> Vec<u8> {
+    build_json_response(status, status_text, &serde_json::json!({ "ok": false, "error": message }), is_https)
+}
+
+/// Add a header line (without its CRLF) after a response's status line
+fn with_header(response: Vec<u8>, header: &str) -> Vec<u8> {
+    let Some(status_end) = response.windows(2).position(|w| w == b"\r\n") else { return response };
+    let mut out = Vec::with_capacity(response.len() + header.len() + 2);
+    out.extend_from_slice(&response[..status_end + 2]);
+    out.extend_from_slice(header.as_bytes());
+    out.extend_from_slice(b"\r\n");
+    out.extend_from_slice(&response[status_end + 2..]);
+    out
+}
+
+/// `/api/worlds` entries from an InitialState
+fn api_worlds_json(worlds: &[crate::websocket::WorldStateMsg], current_world_index: usize) -> serde_json::Value {
+    let worlds: Vec<serde_json::Value> = worlds.iter().map(|w| serde_json::json!({
+        "index": w.index,
+        "name": w.name,
+        "connected": w.connected,
+        "current": w.index == current_world_index,
+        "unseen_lines": w.unseen_lines,
+        "pending_lines": w.pending_count,
+    })).collect();
+    serde_json::json!({ "ok": true, "current_world_index": current_world_index, "worlds": worlds })
+}
+
+/// The world a send goes to: by name, by index, or the current one
+fn api_send_target(request: &ApiSendRequest, worlds: &[crate::websocket::WorldStateMsg], current_world_index: usize) -> Option<usize> {
+    if let Some(name) = &request.world {
+        return worlds.iter().find(|w| w.name.eq_ignore_ascii_case(name)).map(|w| w.index);
+    }
+    let index = request.world_index.unwrap_or(current_world_index);
+    worlds.iter().any(|w| w.index == index).then_some(index)
+}
+
+/// Carry out an authenticated API call as a short-lived signed-in WebSocket client:
+/// it asks for the state the way a resyncing client does (RequestState), reads the
+/// worlds from the InitialState, and for a send issues SendCommand. Every event loop
+/// already answers those, so commands run exactly as if typed in the web client.
+async fn run_api_call(
+    ws_state: &WsConnectionState,
+    client_ip: &str,
+    send: Option<ApiSendRequest>,
+    is_https: bool,
+) -> Vec<u8> {
+    use crate::websocket::{RemoteClientType, WsClientInfo, WsMessage};
+
+    let client_id = ws_state.next_client_id();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<WsMessage>();
+    ws_state.clients.write().await.insert(client_id, WsClientInfo {
+        authenticated: true,
+        tx,
+        current_world: None,
+        username: None,
+        received_initial_state: false,
+        client_type: RemoteClientType::Web,
+        viewport_height: 24,
+        ip_address: client_ip.to_string(),
+        connected_at: std::time::Instant::now(),
+        last_activity: std::time::Instant::now(),
+        paused: false,
+        viewer: false,
+    });
+    let event_tx = &ws_state.event_tx;
+    let _ = event_tx.send(crate::AppEvent::WsClientConnected(client_id)).await;
+    let _ = event_tx.send(crate::AppEvent::WsClientMessage(client_id, Box::new(WsMessage::RequestState))).await;
+
+    let state = tokio::time::timeout(std::time::Duration::from_secs(API_TIMEOUT_SECS), async {
+        while let Some(msg) = rx.recv().await {
+            if let WsMessage::InitialState { worlds, current_world_index, .. } = msg {
+                return Some((worlds, current_world_index));
+            }
+        }
+        None
+    }).await.ok().flatten();
+
+    let response = match (state, send) {
+        (None, _) => api_error(503, "Service Unavailable", "no response from clay", is_https),
+        (Some((worlds, current)), None) => {
+            build_json_response(200, "OK", &api_worlds_json(&worlds, current), is_https)
+        }
+        (Some((worlds, current)), Some(send)) => match api_send_target(&send, &worlds, current) {
+            None => api_error(404, "Not Found", "no such world", is_https),
+            Some(world_index) => {
+                let name = worlds.iter().find(|w| w.index == world_index).map(|w| w.name.clone()).unwrap_or_default();
+                let msg = WsMessage::SendCommand { world_index, command: send.command };
+                let _ = event_tx.send(crate::AppEvent::WsClientMessage(client_id, Box::new(msg))).await;
+                build_json_response(200, "OK", &serde_json::json!({ "ok": true, "world_index": world_index, "world": name }), is_https)
+            }
+        },
+    };
+
+    ws_state.clients.write().await.remove(&client_id);
+    let _ = event_tx.send(crate::AppEvent::WsClientDisconnected(client_id)).await;
+    response
+}
+
+/// Answer an API request. Authenticated like the auth-key WebSocket sign-in: a
+/// bearer token that is the auth key or a device token. A wrong token counts as a
+/// failed sign-in toward a temporary ban.
+#[allow(clippy::too_many_arguments)]
+async fn handle_api_request<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    endpoint: ApiEndpoint,
+    method: &str,
+    request: &str,
+    received: &[u8],
+    ws_state: Option<&WsConnectionState>,
+    gate: &SecurityGate,
+    client_ip: &str,
+    is_https: bool,
+) {
+    let response = 'response: {
+        let allowed = match endpoint {
+            ApiEndpoint::Send => "POST",
+            ApiEndpoint::Worlds => "GET",
+        };
+        if method != allowed {
+            break 'response with_header(
+                api_error(405, "Method Not Allowed", "method not allowed", is_https),
+                &format!("Allow: {}", allowed),
+            );
+        }
+        let authorized = bearer_token(request)
+            .is_some_and(|token| api_token_valid(token, &gate.auth_keys.read().unwrap()));
+        if !authorized {
+            log_remote_event("API-AUTH-FAIL", client_ip, request.lines().next().unwrap_or(""));
+            gate.ban_list.record_auth_failure(client_ip, "api-auth");
+            break 'response with_header(
+                api_error(401, "Unauthorized", "a valid auth key or device token is required", is_https),
+                "WWW-Authenticate: Bearer",
+            );
+        }
+        gate.ban_list.clear_violations(client_ip);
+        let Some(ws_state) = ws_state.filter(|s| !s.multiuser_mode) else {
+            break 'response api_error(503, "Service Unavailable", "WebSocket server not running", is_https);
+        };
+        let send = match endpoint {
+            ApiEndpoint::Worlds => None,
+            ApiEndpoint::Send => {
+                let Some(body) = read_api_body(stream, received).await else {
+                    break 'response api_error(413, "Payload Too Large", "request body too large or incomplete", is_https);
+                };
+                match serde_json::from_slice::<ApiSendRequest>(&body) {
+                    Ok(send) => Some(send),
+                    Err(e) => break 'response api_error(400, "Bad Request", &format!("bad request body: {}", e), is_https),
+                }
+            }
+        };
+        log_remote_event("API", client_ip, request.lines().next().unwrap_or(""));
+        run_api_call(ws_state, client_ip, send, is_https).await
+    };
+    if stream.write_all(&response).await.is_ok() {
+        let _ = stream.shutdown().await;
+    }
+}
+
 /// Shared WebSocket connection state passed from WebSocketServer to the unified HTTP+WS server.
 pub struct WsConnectionState {
     pub clients: Arc<RwLock<HashMap<u64, crate::websocket::WsClientInfo>>>,
     pub next_client_id: Arc<std::sync::Mutex<u64>>,
     pub password_hash: Arc<std::sync::RwLock<String>>,
     pub password_enabled: Arc<std::sync::RwLock<bool>>,
+    pub viewer_password_hash: Arc<std::sync::RwLock<String>>,
     pub allow_list: Arc<std::sync::RwLock<Vec<String>>>,
     pub whitelisted_host: Arc<std::sync::RwLock<Option<String>>>,
     pub event_tx: tokio::sync::mpsc::Sender<crate::AppEvent>,
@@ -1057,16 +1544,18 @@ impl WsConnectionState {
 /// gate is always available at accept time. `allow_list`/`whitelisted_host` share the
 /// SAME Arc as WebSocketServer when one exists (so runtime allow-list edits and
 /// whitelisted-host auth are visible immediately); when no WebSocketServer exists yet,
-/// callers construct fresh Arcs seeded from settings. `auth_key` mirrors
-/// `App.ws_auth_key_shared`. `web_path` is fixed at server start (server restarts on
+/// callers construct fresh Arcs seeded from settings. `auth_keys` mirrors
+/// `App.ws_auth_keys_shared` (the knock secrets: the auth key and the device tokens' hashes). `web_path` is fixed at server start (server restarts on
 /// change) — sourced from `Settings.web_path` (sanitized, default `"clay"`; empty =
-/// legacy mode serving the UI at `/`).
+/// legacy mode serving the UI at `/`). `trusted_proxies` is fixed at start the same way
+/// (`Settings.web_trusted_proxies`, see `forwarded_client`).
 #[derive(Clone)]
 pub struct SecurityGate {
     pub allow_list: Arc<std::sync::RwLock<Vec<String>>>,
     pub whitelisted_host: Arc<std::sync::RwLock<Option<String>>>,
-    pub auth_key: Arc<std::sync::RwLock<Option<String>>>,
+    pub auth_keys: Arc<std::sync::RwLock<Vec<String>>>,
     pub web_path: String,
+    pub trusted_proxies: Vec<std::net::IpAddr>,
     pub ban_list: BanList,
 }
 
@@ -1104,12 +1593,25 @@ impl SecurityGate {
     /// `ban_list.record_violation` directly. Returns true if the violation caused (or
     /// the IP already was) a ban.
     pub async fn strike(&self, ip: &str, reason: &str) -> bool {
-        if self.is_ban_exempt(ip).await {
+        // Banning a reverse proxy would lock out everyone behind it
+        if self.trusts_proxy(ip) || self.is_ban_exempt(ip).await {
             return false;
         }
         self.ban_list.record_violation(ip, reason)
     }
 
+    /// Whether `ip` is a reverse proxy whose X-Forwarded-For is believed: loopback (which
+    /// includes the Unix socket) or a Trusted Proxies address
+    pub fn trusts_proxy(&self, ip: &str) -> bool {
+        ip.parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback() || self.trusted_proxies.contains(&ip))
+    }
+
+    /// Connections at once from `ip`: a reverse proxy carries many clients' connections
+    fn connection_limit(&self, ip: &str) -> usize {
+        if self.trusts_proxy(ip) { MAX_PROXY_CONNECTIONS } else { MAX_HTTP_CONNECTIONS_PER_IP }
+    }
+
     /// Localhost, or an IP matching a *specific* allow-list entry (exact IP, IP
     /// wildcard, or hostname pattern) — see `decide_ban_exempt` for the bare-`*`
     /// exclusion rationale. Reverse-DNS is only performed when the allow list actually
@@ -1139,15 +1641,17 @@ pub struct HttpsServer {
     pub running: Arc<RwLock<bool>>,
     pub shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
     pub port: u16,
+    pub listen: WebListen,
 }
 
 #[cfg(feature = "native-tls-backend")]
 impl HttpsServer {
-    pub fn new(port: u16) -> Self {
+    pub fn new(port: u16, listen: WebListen) -> Self {
         Self {
             running: Arc::new(RwLock::new(false)),
             shutdown_tx: None,
             port,
+            listen,
         }
     }
 }
@@ -1179,27 +1683,9 @@ pub async fn start_https_server(
     let tls_acceptor = tokio_native_tls::TlsAcceptor::from(tls_acceptor);
     let tls_acceptor = Arc::new(tls_acceptor);
 
-    let addr = format!("0.0.0.0:{}", server.port);
-    // Retry binding with delays — on reload, the previous process may still be releasing the port
-    let listener = {
-        let mut last_err = None;
-        let mut bound = None;
-        for attempt in 0..10 {
-            match tokio::net::TcpListener::bind(&addr).await {
-                Ok(l) => { bound = Some(l); break; }
-                Err(e) => {
-                    last_err = Some(e);
-                    if attempt < 9 {
-                        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
-                    }
-                }
-            }
-        }
-        match bound {
-            Some(l) => l,
-            None => return Err(format!("Failed to bind HTTPS to port {}: {}", server.port, last_err.unwrap()).into()),
-        }
-    };
+    let listener = bind_web_listener(&server.listen.bind_addr, server.port, "HTTPS").await?;
+    #[cfg(unix)]
+    let unix_task = spawn_unix_listener(&server.listen, ws_state.clone(), &theme_css_vars, gate.clone())?;
 
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
     server.shutdown_tx = Some(shutdown_tx);
@@ -1221,7 +1707,7 @@ pub async fn start_https_server(
                             if ban_list.is_banned(&client_ip) {
                                 continue;
                             }
-                            let guard = match conn_counter.try_acquire(&client_ip, MAX_HTTP_CONNECTIONS_PER_IP) {
+                            let guard = match conn_counter.try_acquire(&client_ip, gate.connection_limit(&client_ip)) {
                                 Some(g) => g,
                                 None => {
                                     log_remote_event("CONN-LIMIT", &client_ip, "connection limit reached");
@@ -1288,6 +1774,10 @@ pub async fn start_https_server(
                 }
             }
         }
+        #[cfg(unix)]
+        if let Some(task) = unix_task {
+            task.abort();
+        }
         *running.write().await = false;
     });
 
@@ -1304,15 +1794,17 @@ pub struct HttpsServer {
     pub running: Arc<RwLock<bool>>,
     pub shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
     pub port: u16,
+    pub listen: WebListen,
 }
 
 #[cfg(feature = "rustls-backend")]
 impl HttpsServer {
-    pub fn new(port: u16) -> Self {
+    pub fn new(port: u16, listen: WebListen) -> Self {
         Self {
             running: Arc::new(RwLock::new(false)),
             shutdown_tx: None,
             port,
+            listen,
         }
     }
 }
@@ -1376,27 +1868,9 @@ pub async fn start_https_server(
 
     let tls_acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
 
-    let addr = format!("0.0.0.0:{}", server.port);
-    // Retry binding with delays — on reload, the previous process may still be releasing the port
-    let listener = {
-        let mut last_err = None;
-        let mut bound = None;
-        for attempt in 0..10 {
-            match tokio::net::TcpListener::bind(&addr).await {
-                Ok(l) => { bound = Some(l); break; }
-                Err(e) => {
-                    last_err = Some(e);
-                    if attempt < 9 {
-                        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
-                    }
-                }
-            }
-        }
-        match bound {
-            Some(l) => l,
-            None => return Err(format!("Failed to bind HTTPS to port {}: {}", server.port, last_err.unwrap()).into()),
-        }
-    };
+    let listener = bind_web_listener(&server.listen.bind_addr, server.port, "HTTPS").await?;
+    #[cfg(unix)]
+    let unix_task = spawn_unix_listener(&server.listen, ws_state.clone(), &theme_css_vars, gate.clone())?;
 
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
     server.shutdown_tx = Some(shutdown_tx);
@@ -1418,7 +1892,7 @@ pub async fn start_https_server(
                             if ban_list.is_banned(&client_ip) {
                                 continue;
                             }
-                            let guard = match conn_counter.try_acquire(&client_ip, MAX_HTTP_CONNECTIONS_PER_IP) {
+                            let guard = match conn_counter.try_acquire(&client_ip, gate.connection_limit(&client_ip)) {
                                 Some(g) => g,
                                 None => {
                                     log_remote_event("CONN-LIMIT", &client_ip, "connection limit reached");
@@ -1485,6 +1959,10 @@ pub async fn start_https_server(
                 }
             }
         }
+        #[cfg(unix)]
+        if let Some(task) = unix_task {
+            task.abort();
+        }
         *running.write().await = false;
     });
 
@@ -1495,35 +1973,61 @@ pub async fn start_https_server(
 // Ban List for HTTP/WebSocket security
 // ============================================================================
 
-/// Tracks violations and bans for IP addresses.
-/// Bans are in-memory only and last until server restart.
+/// How long a ban for repeated failed WebSocket sign-ins lasts. Probe bans last until
+/// restart; a guessed-wrong password shouldn't lock a real user out for that long.
+pub const AUTH_BAN_DURATION: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+/// Failed WebSocket sign-ins from one IP before it's banned
+pub const AUTH_FAILURE_THRESHOLD: u32 = 5;
+/// New WebSocket connections one IP may open per `CONNECT_RATE_WINDOW`; the rest are
+/// dropped, so a script can't open a fresh socket per password guess
+pub const MAX_WS_CONNECTS_PER_WINDOW: usize = 30;
+pub const CONNECT_RATE_WINDOW: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Tracks violations and bans for IP addresses, and the WebSocket connection rate.
+/// Bans are in-memory only and last until server restart, except sign-in bans, which
+/// end after `AUTH_BAN_DURATION`.
 #[derive(Clone)]
 pub struct BanList {
-    /// Banned IPs — in-memory only, cleared on restart
-    banned: Arc<std::sync::RwLock<HashSet<String>>>,
+    /// Banned IPs, each with when its ban ends (None = until restart)
+    banned: Arc<std::sync::RwLock<HashMap<String, Option<std::time::Instant>>>>,
     /// Violation count per IP (reset when banned)
     violations: Arc<std::sync::RwLock<HashMap<String, u32>>>,
     /// Last URL/reason that triggered the ban
     ban_reasons: Arc<std::sync::RwLock<HashMap<String, String>>>,
+    /// Recent WebSocket connection times per IP, for the rate limit
+    connects: Arc<std::sync::Mutex<HashMap<String, std::collections::VecDeque<std::time::Instant>>>>,
+}
+
+fn is_localhost_ip(ip: &str) -> bool {
+    ip == "127.0.0.1" || ip == "::1" || ip == "localhost"
 }
 
 impl BanList {
     pub fn new() -> Self {
         Self {
-            banned: Arc::new(std::sync::RwLock::new(HashSet::new())),
+            banned: Arc::new(std::sync::RwLock::new(HashMap::new())),
             violations: Arc::new(std::sync::RwLock::new(HashMap::new())),
             ban_reasons: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            connects: Arc::new(std::sync::Mutex::new(HashMap::new())),
         }
     }
 
-    /// Check if an IP is currently banned
+    /// Check if an IP is currently banned (an ended ban is cleared here)
     pub fn is_banned(&self, ip: &str) -> bool {
-        self.banned.read().unwrap().contains(ip)
+        let until = match self.banned.read().unwrap().get(ip) {
+            None => return false,
+            Some(until) => *until,
+        };
+        if until.is_some_and(|t| t <= std::time::Instant::now()) {
+            self.remove_ban(ip);
+            return false;
+        }
+        true
     }
 
     /// Record a violation for an IP. Bans after 2 violations. Returns true if banned.
     pub fn record_violation(&self, ip: &str, reason: &str) -> bool {
-        self.record_with_threshold(ip, reason, 2)
+        self.record_with_threshold(ip, reason, 2, None)
     }
 
     /// Record a failed WebSocket password-auth attempt. Bans after 5 attempts instead
@@ -1531,21 +2035,21 @@ impl BanList {
     /// that actually protects something, so it earns room for a typo, and unlike probe
     /// strikes it applies to allow-listed IPs too (no allow-list exemption; a listed
     /// device brute-forcing the password is still a threat). Localhost stays exempt,
-    /// same as `record_violation`. Returns true if the attempt caused (or the IP
-    /// already was) a ban.
+    /// same as `record_violation`. The ban lasts `AUTH_BAN_DURATION`. Returns true if
+    /// the attempt caused (or the IP already was) a ban.
     pub fn record_auth_failure(&self, ip: &str, reason: &str) -> bool {
-        self.record_with_threshold(ip, reason, 5)
+        self.record_with_threshold(ip, reason, AUTH_FAILURE_THRESHOLD, Some(AUTH_BAN_DURATION))
     }
 
     /// Shared bookkeeping for `record_violation`/`record_auth_failure`: never ban
     /// localhost, track a per-IP counter, ban once it reaches `threshold`.
-    fn record_with_threshold(&self, ip: &str, reason: &str, threshold: u32) -> bool {
+    fn record_with_threshold(&self, ip: &str, reason: &str, threshold: u32, duration: Option<std::time::Duration>) -> bool {
         // Never ban localhost
-        if ip == "127.0.0.1" || ip == "::1" || ip == "localhost" {
+        if is_localhost_ip(ip) {
             return false;
         }
         // Already banned — no need to track further
-        if self.banned.read().unwrap().contains(ip) {
+        if self.is_banned(ip) {
             return true;
         }
 
@@ -1559,15 +2063,45 @@ impl BanList {
         };
 
         if count >= threshold {
-            self.banned.write().unwrap().insert(ip.to_string());
-            self.violations.write().unwrap().remove(ip);
-            log_ban(ip, "BANNED", reason);
+            self.ban(ip, duration.map(|d| std::time::Instant::now() + d));
+            log_ban(ip, if duration.is_some() { "BANNED-TEMP" } else { "BANNED" }, reason);
             true
         } else {
             false
         }
     }
 
+    fn ban(&self, ip: &str, until: Option<std::time::Instant>) {
+        self.banned.write().unwrap().insert(ip.to_string(), until);
+        self.violations.write().unwrap().remove(ip);
+    }
+
+    /// Count a new WebSocket connection from `ip`, returning false when it's over
+    /// `MAX_WS_CONNECTS_PER_WINDOW` in the last `CONNECT_RATE_WINDOW` (drop it).
+    /// Localhost is never limited. Not a strike: a reconnect storm after a restart
+    /// shouldn't ban a real device.
+    pub fn allow_ws_connect(&self, ip: &str) -> bool {
+        self.allow_ws_connect_at(ip, std::time::Instant::now())
+    }
+
+    fn allow_ws_connect_at(&self, ip: &str, now: std::time::Instant) -> bool {
+        if is_localhost_ip(ip) {
+            return true;
+        }
+        let mut connects = self.connects.lock().unwrap();
+        // Forget IPs that have gone quiet, so the map stays small
+        connects.retain(|_, times| times.back().is_some_and(|&t| now.duration_since(t) < CONNECT_RATE_WINDOW));
+        let times = connects.entry(ip.to_string()).or_default();
+        while times.front().is_some_and(|&t| now.duration_since(t) >= CONNECT_RATE_WINDOW) {
+            times.pop_front();
+        }
+        if times.len() >= MAX_WS_CONNECTS_PER_WINDOW {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+
     /// Add a ban directly (e.g. from persistence load — no-op, bans are not persisted)
     pub fn add_permanent_ban(&self, _ip: &str) {
         // Bans are in-memory only and not loaded from disk
@@ -1585,19 +2119,38 @@ impl BanList {
 
     /// Remove a ban for an IP. Returns true if a ban was removed.
     pub fn remove_ban(&self, ip: &str) -> bool {
-        let removed = self.banned.write().unwrap().remove(ip);
+        let removed = self.banned.write().unwrap().remove(ip).is_some();
         self.violations.write().unwrap().remove(ip);
         self.ban_reasons.write().unwrap().remove(ip);
         removed
     }
 
-    /// Get all current bans with reasons. Returns Vec of (ip, ban_type, reason).
+    /// Get all current bans with reasons. Returns Vec of (ip, ban_type, reason), the
+    /// type being how long the ban has left ("until restart" or e.g. "12m left").
     pub fn get_ban_info(&self) -> Vec<(String, String, String)> {
+        let now = std::time::Instant::now();
         let reasons = self.ban_reasons.read().unwrap();
-        self.banned.read().unwrap().iter().map(|ip| {
-            let reason = reasons.get(ip).cloned().unwrap_or_default();
-            (ip.clone(), "banned".to_string(), reason)
-        }).collect()
+        let mut bans: Vec<_> = self.banned.read().unwrap().iter()
+            .filter(|(_, until)| !until.is_some_and(|t| t <= now))
+            .map(|(ip, until)| {
+                let reason = reasons.get(ip).cloned().unwrap_or_default();
+                let ban_type = match until {
+                    None => "until restart".to_string(),
+                    Some(t) => format!("{}m left", t.duration_since(now).as_secs().div_ceil(60)),
+                };
+                (ip.clone(), ban_type, reason)
+            })
+            .collect();
+        bans.sort();
+        bans
+    }
+
+    /// IPs with strikes (failed sign-ins or probes) that aren't banned yet, with their
+    /// count, most first
+    pub fn get_strikes(&self) -> Vec<(String, u32)> {
+        let mut strikes: Vec<_> = self.violations.read().unwrap().iter().map(|(ip, &n)| (ip.clone(), n)).collect();
+        strikes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        strikes
     }
 }
 
@@ -1672,6 +2225,7 @@ pub struct HttpServer {
     pub running: Arc<RwLock<bool>>,
     pub shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
     pub port: u16,
+    pub listen: WebListen,
     /// Raw socket handle of the TCP listener (for passing to child process on reload)
     #[cfg(windows)]
     pub listener_handle: Option<u64>,
@@ -1680,26 +2234,141 @@ pub struct HttpServer {
 }
 
 impl HttpServer {
-    pub fn new(port: u16) -> Self {
+    pub fn new(port: u16, listen: WebListen) -> Self {
         Self {
             running: Arc::new(RwLock::new(false)),
             shutdown_tx: None,
             listener_handle: None,
             port,
+            listen,
         }
     }
 }
 
-/// Host to bind the HTTP+WS listener to. Loopback-only when running as `--local-server`
-/// (`LOCAL_SERVER_LOOPBACK_ONLY`), otherwise all interfaces as before.
-fn http_bind_host() -> &'static str {
+/// Web server bind address for new installs: this machine only
+pub const DEFAULT_BIND_ADDR: &str = "127.0.0.1";
+/// Bind address for every interface, which settings from before the Bind Address
+/// setting keep (see persistence::load_settings)
+pub const ALL_INTERFACES: &str = "0.0.0.0";
+/// Connections at once from a reverse proxy (the Unix socket or a Trusted Proxies
+/// address). They carry every client behind it, so this is far above the per-IP limit.
+const MAX_PROXY_CONNECTIONS: usize = 256;
+
+/// Where the web server listens on top of its port (Bind Address and Unix Socket in
+/// `/web`, `App::web_listen`)
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebListen {
+    pub bind_addr: String,
+    /// Also serve plain HTTP+WS on this Unix domain socket (Unix only)
+    pub unix_socket: Option<std::path::PathBuf>,
+}
+
+impl Default for WebListen {
+    fn default() -> Self {
+        Self { bind_addr: DEFAULT_BIND_ADDR.to_string(), unix_socket: None }
+    }
+}
+
+/// Check a Bind Address setting: an IPv4 or IPv6 address, with `*` for every interface
+/// and `localhost` for loopback. Empty means the default.
+pub fn parse_bind_addr(text: &str) -> Result<String, String> {
+    let text = text.trim().trim_start_matches('[').trim_end_matches(']');
+    match text {
+        "" => Ok(DEFAULT_BIND_ADDR.to_string()),
+        "*" => Ok(ALL_INTERFACES.to_string()),
+        _ if text.eq_ignore_ascii_case("localhost") => Ok(DEFAULT_BIND_ADDR.to_string()),
+        _ => text.parse::<std::net::IpAddr>()
+            .map(|ip| ip.to_string())
+            .map_err(|_| format!("'{}' isn't an IP address (use 127.0.0.1, 0.0.0.0 or ::).", text)),
+    }
+}
+
+/// Check a Trusted Proxies setting: comma-separated IP addresses of reverse proxies
+/// whose X-Forwarded-For is believed (loopback always is)
+pub fn parse_trusted_proxies(text: &str) -> Result<Vec<std::net::IpAddr>, String> {
+    crate::websocket::parse_allow_list_csv(text)
+        .iter()
+        .map(|entry| entry.parse().map_err(|_| format!("Trusted proxy '{}' isn't an IP address.", entry)))
+        .collect()
+}
+
+/// Host to bind the HTTP+WS listener to: the configured address, or loopback only when
+/// running as `--local-server` (`LOCAL_SERVER_LOOPBACK_ONLY`)
+fn http_bind_host(configured: &str) -> &str {
     if crate::LOCAL_SERVER_LOOPBACK_ONLY.load(std::sync::atomic::Ordering::SeqCst) {
         "127.0.0.1"
+    } else if configured.is_empty() {
+        DEFAULT_BIND_ADDR
     } else {
-        "0.0.0.0"
+        configured
     }
 }
 
+/// Bind the web server's TCP listener. Retries for a few seconds: on reload, the
+/// previous process may still be releasing the port.
+async fn bind_web_listener(
+    bind_addr: &str,
+    port: u16,
+    what: &str,
+) -> Result<tokio::net::TcpListener, Box<dyn std::error::Error + Send + Sync>> {
+    let host = http_bind_host(bind_addr);
+    let mut last_err = None;
+    for attempt in 0..10 {
+        match tokio::net::TcpListener::bind((host, port)).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) => {
+                last_err = Some(e);
+                if attempt < 9 {
+                    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                }
+            }
+        }
+    }
+    Err(format!("Failed to bind {} to {} port {}: {}", what, host, port, last_err.unwrap()).into())
+}
+
+/// Serve plain HTTP+WS on `listen.unix_socket`, if set, for a reverse proxy on this
+/// machine. The socket's permissions (owner and group) are the gate, so its connections
+/// count as local ones: no allow list, no TLS. Runs until the task is aborted.
+#[cfg(unix)]
+fn spawn_unix_listener(
+    listen: &WebListen,
+    ws_state: Option<Arc<WsConnectionState>>,
+    theme_css_vars: &str,
+    gate: SecurityGate,
+) -> Result<Option<tokio::task::JoinHandle<()>>, Box<dyn std::error::Error + Send + Sync>> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    let Some(path) = listen.unix_socket.clone() else { return Ok(None) };
+    // A socket left behind by an earlier run would make the bind fail
+    if std::fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_socket()) {
+        let _ = std::fs::remove_file(&path);
+    }
+    let listener = tokio::net::UnixListener::bind(&path)
+        .map_err(|e| format!("Failed to listen on {}: {}", path.display(), e))?;
+    let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o660));
+
+    let theme_css_vars = Arc::new(theme_css_vars.to_string());
+    let local_addr = std::net::SocketAddr::from(([127, 0, 0, 1], 0));
+    let conn_counter = ConnectionCounter::new();
+    let ws_counter = ConnectionCounter::new();
+    Ok(Some(tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let Some(guard) = conn_counter.try_acquire("unix", MAX_PROXY_CONNECTIONS) else {
+                log_remote_event("CONN-LIMIT", "unix", "unix socket connection limit reached");
+                continue;
+            };
+            let theme_css_vars = theme_css_vars.clone();
+            let ws_state = ws_state.clone();
+            let ws_counter = ws_counter.clone();
+            let gate = gate.clone();
+            tokio::spawn(async move {
+                route_connection(stream, ws_state, false, &theme_css_vars, local_addr, false, guard, &ws_counter, &gate, false, false).await;
+            });
+        }
+    })))
+}
+
 /// Start the HTTP server (plain TCP, no TLS, unified HTTP+WS)
 pub async fn start_http_server(
     server: &mut HttpServer,
@@ -1724,26 +2393,10 @@ pub async fn start_http_server(
             return Err("inherited_handle not supported on this platform".into());
         }
     } else {
-        let addr = format!("{}:{}", http_bind_host(), server.port);
-        // Retry binding with delays — on reload, the previous process may still be releasing the port
-        let mut last_err = None;
-        let mut bound = None;
-        for attempt in 0..10 {
-            match tokio::net::TcpListener::bind(&addr).await {
-                Ok(l) => { bound = Some(l); break; }
-                Err(e) => {
-                    last_err = Some(e);
-                    if attempt < 9 {
-                        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
-                    }
-                }
-            }
-        }
-        match bound {
-            Some(l) => l,
-            None => return Err(format!("Failed to bind HTTP to port {}: {}", server.port, last_err.unwrap()).into()),
-        }
+        bind_web_listener(&server.listen.bind_addr, server.port, "HTTP").await?
     };
+    #[cfg(unix)]
+    let unix_task = spawn_unix_listener(&server.listen, ws_state.clone(), &theme_css_vars, gate.clone())?;
 
     // Store the listener handle for passing to child process on reload
     #[cfg(windows)]
@@ -1774,7 +2427,7 @@ pub async fn start_http_server(
                                 drop(stream);
                                 continue;
                             }
-                            let guard = match conn_counter.try_acquire(&client_ip, MAX_HTTP_CONNECTIONS_PER_IP) {
+                            let guard = match conn_counter.try_acquire(&client_ip, gate.connection_limit(&client_ip)) {
                                 Some(g) => g,
                                 None => {
                                     log_remote_event("CONN-LIMIT", &client_ip, "connection limit reached");
@@ -1803,6 +2456,10 @@ pub async fn start_http_server(
                 }
             }
         }
+        #[cfg(unix)]
+        if let Some(task) = unix_task {
+            task.abort();
+        }
         *running.write().await = false;
     });
 
@@ -1824,13 +2481,46 @@ mod local_server_bind_tests {
         let prev = crate::LOCAL_SERVER_LOOPBACK_ONLY.load(Ordering::SeqCst);
 
         crate::LOCAL_SERVER_LOOPBACK_ONLY.store(true, Ordering::SeqCst);
-        assert_eq!(http_bind_host(), "127.0.0.1");
+        assert_eq!(http_bind_host("0.0.0.0"), "127.0.0.1");
 
         crate::LOCAL_SERVER_LOOPBACK_ONLY.store(false, Ordering::SeqCst);
-        assert_eq!(http_bind_host(), "0.0.0.0");
+        assert_eq!(http_bind_host("0.0.0.0"), "0.0.0.0");
+        assert_eq!(http_bind_host("192.168.1.5"), "192.168.1.5");
+        assert_eq!(http_bind_host(""), DEFAULT_BIND_ADDR);
 
         crate::LOCAL_SERVER_LOOPBACK_ONLY.store(prev, Ordering::SeqCst);
     }
+
+    #[test]
+    fn forwarded_client_is_rightmost_untrusted_address() {
+        let trusted = |ip: &str| ip == "127.0.0.1" || ip == "10.0.0.2";
+        let req = |xff: &str| format!("GET / HTTP/1.1\r\nHost: x\r\nX-Forwarded-For: {}\r\n\r\n", xff);
+        let ip = |s: &str| Some(Ok(s.parse::<std::net::IpAddr>().unwrap()));
+
+        assert_eq!(forwarded_client("GET / HTTP/1.1\r\nHost: x\r\n\r\n", trusted), None);
+        assert_eq!(forwarded_client(&req("203.0.113.9"), trusted), ip("203.0.113.9"));
+        // A client-written entry to the left of the proxy's own is ignored
+        assert_eq!(forwarded_client(&req("127.0.0.1, 203.0.113.9"), trusted), ip("203.0.113.9"));
+        // Trusted proxies in the chain are skipped
+        assert_eq!(forwarded_client(&req("198.51.100.7, 10.0.0.2"), trusted), ip("198.51.100.7"));
+        assert_eq!(forwarded_client(&req("[2001:db8::1]:4711"), trusted), ip("2001:db8::1"));
+        assert_eq!(forwarded_client(&req("unknown"), trusted), Some(Err("unknown".to_string())));
+    }
+
+    #[test]
+    fn bind_addr_setting_accepts_ip_addresses_only() {
+        assert_eq!(parse_bind_addr("").unwrap(), "127.0.0.1");
+        assert_eq!(parse_bind_addr("localhost").unwrap(), "127.0.0.1");
+        assert_eq!(parse_bind_addr("*").unwrap(), "0.0.0.0");
+        assert_eq!(parse_bind_addr(" 10.0.0.2 ").unwrap(), "10.0.0.2");
+        assert_eq!(parse_bind_addr("[::]").unwrap(), "::");
+        assert!(parse_bind_addr("example.com").is_err());
+        assert!(parse_bind_addr("10.0.0.256").is_err());
+
+        assert_eq!(parse_trusted_proxies(" 10.0.0.2, ::1 ").unwrap().len(), 2);
+        assert!(parse_trusted_proxies("").unwrap().is_empty());
+        assert!(parse_trusted_proxies("10.0.0.*").is_err());
+    }
 }
 
 // ============================================================================
@@ -2002,6 +2692,88 @@ mod decide_route_tests {
         assert_eq!(strip_web_path_prefix("/clay", ""), None);
         assert_eq!(strip_web_path_prefix("/other", "clay"), None);
     }
+
+    #[test]
+    fn head_routes_like_get() {
+        assert_eq!(stealth_open("HEAD", "/clay/app.js"), RouteDecision::Serve("/app.js".to_string()));
+        assert_eq!(decide_route("HEAD", "/", "", false, false, false), RouteDecision::Serve("/".to_string()));
+        assert_eq!(decide_route("HEAD", "/", P, true, false, false), RouteDecision::Serve("/".to_string()));
+    }
+}
+
+// ============================================================================
+// Web asset compression and caching tests
+// ============================================================================
+
+#[cfg(test)]
+mod asset_response_tests {
+    use super::*;
+    use std::io::Read;
+
+    fn route(path: &str, request: &str) -> Vec<u8> {
+        let prefs = AssetRequest::from_request(request);
+        match handle_http_routes("GET", path, "example.com", false, "", false, "", &prefs) {
+            Some(RouteResult::Ok(r)) => r,
+            _ => panic!("{} must be served", path),
+        }
+    }
+
+    fn split(response: &[u8]) -> (String, Vec<u8>) {
+        let end = response.windows(4).position(|w| w == b"\r\n\r\n").expect("headers end") + 4;
+        (String::from_utf8_lossy(&response[..end]).to_string(), response[end..].to_vec())
+    }
+
+    fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+        headers.lines().find_map(|l| l.strip_prefix(name).and_then(|v| v.strip_prefix(": ")))
+    }
+
+    #[test]
+    fn parses_accept_encoding_and_if_none_match() {
+        let prefs = AssetRequest::from_request("GET / HTTP/1.1\r\naccept-encoding: br, GZIP;q=0.8\r\nIf-None-Match: \"a\", W/\"b\"\r\n\r\n");
+        assert!(prefs.accepts_gzip);
+        assert!(prefs.matches("\"a\"") && prefs.matches("\"b\"") && !prefs.matches("\"c\""));
+        assert!(!AssetRequest::from_request("GET / HTTP/1.1\r\nAccept-Encoding: gzip;q=0\r\n\r\n").accepts_gzip);
+        assert!(!AssetRequest::from_request("GET / HTTP/1.1\r\nAccept-Encoding: deflate\r\n\r\n").accepts_gzip);
+        assert!(AssetRequest::from_request("GET / HTTP/1.1\r\nAccept-Encoding: *\r\n\r\n").accepts_gzip);
+    }
+
+    #[test]
+    fn serves_prebuilt_gzip_with_etag() {
+        let (headers, body) = split(&route("/app.js", "GET /app.js HTTP/1.1\r\nAccept-Encoding: gzip\r\n\r\n"));
+        assert!(headers.starts_with("HTTP/1.1 200 OK"));
+        assert_eq!(header(&headers, "Content-Encoding"), Some("gzip"));
+        assert_eq!(header(&headers, "Content-Length"), Some(body.len().to_string().as_str()));
+        assert_eq!(header(&headers, "Cache-Control"), Some("no-cache"));
+        let mut unzipped = String::new();
+        flate2::read::GzDecoder::new(&body[..]).read_to_string(&mut unzipped).unwrap();
+        assert_eq!(unzipped, WEB_APP_JS);
+
+        let (plain_headers, plain_body) = split(&route("/app.js", "GET /app.js HTTP/1.1\r\n\r\n"));
+        assert_eq!(header(&plain_headers, "Content-Encoding"), None);
+        assert_eq!(plain_body, WEB_APP_JS.as_bytes());
+        assert_ne!(header(&headers, "ETag"), header(&plain_headers, "ETag"), "each encoding has its own tag");
+    }
+
+    #[test]
+    fn matching_etag_gets_not_modified() {
+        for path in ["/", "/style.css"] {
+            let (headers, _) = split(&route(path, "GET / HTTP/1.1\r\nAccept-Encoding: gzip\r\n\r\n"));
+            let etag = header(&headers, "ETag").expect("ETag").to_string();
+            let request = format!("GET / HTTP/1.1\r\nAccept-Encoding: gzip\r\nIf-None-Match: {}\r\n\r\n", etag);
+            let (headers, body) = split(&route(path, &request));
+            assert!(headers.starts_with("HTTP/1.1 304 Not Modified"), "{}: {}", path, headers);
+            assert!(body.is_empty());
+        }
+    }
+
+    #[test]
+    fn head_keeps_length_without_body() {
+        let full = route("/style.css", "GET /style.css HTTP/1.1\r\n\r\n");
+        let (headers, body) = split(&strip_response_body(full.clone()));
+        assert!(body.is_empty());
+        assert_eq!(header(&headers, "Content-Length"), Some(WEB_STYLE_CSS.len().to_string().as_str()));
+        assert_eq!(split(&full).0, headers);
+    }
 }
 
 // ============================================================================
@@ -2102,8 +2874,9 @@ mod ban_exempt_tests {
         SecurityGate {
             allow_list: Arc::new(std::sync::RwLock::new(allow_list)),
             whitelisted_host: Arc::new(std::sync::RwLock::new(whitelisted.map(|s| s.to_string()))),
-            auth_key: Arc::new(std::sync::RwLock::new(None)),
+            auth_keys: Arc::new(std::sync::RwLock::new(Vec::new())),
             web_path: "clay".to_string(),
+            trusted_proxies: Vec::new(),
             ban_list: BanList::new(),
         }
     }
@@ -2210,6 +2983,41 @@ mod ban_exempt_tests {
         assert!(!ban_list.is_banned("127.0.0.1"));
     }
 
+    /// A sign-in ban ends after AUTH_BAN_DURATION; a probe ban lasts until restart
+    #[test]
+    fn auth_failure_ban_is_temporary() {
+        let ban_list = BanList::new();
+        for _ in 0..AUTH_FAILURE_THRESHOLD {
+            ban_list.record_auth_failure("203.0.113.5", "WebSocket: failed auth");
+        }
+        assert_eq!(ban_list.get_ban_info(), vec![("203.0.113.5".to_string(), "15m left".to_string(), "WebSocket: failed auth".to_string())]);
+        ban_list.ban("203.0.113.5", Some(std::time::Instant::now()));
+        assert!(!ban_list.is_banned("203.0.113.5"), "an ended ban no longer applies");
+        assert!(ban_list.get_ban_info().is_empty());
+
+        ban_list.record_violation("8.8.8.8", "probe");
+        assert_eq!(ban_list.get_strikes(), vec![("8.8.8.8".to_string(), 1)]);
+        ban_list.record_violation("8.8.8.8", "probe");
+        assert_eq!(ban_list.get_ban_info()[0].1, "until restart");
+        assert!(ban_list.get_strikes().is_empty());
+    }
+
+    #[test]
+    fn ws_connect_rate_limit() {
+        let ban_list = BanList::new();
+        let start = std::time::Instant::now();
+        for _ in 0..MAX_WS_CONNECTS_PER_WINDOW {
+            assert!(ban_list.allow_ws_connect_at("198.51.100.7", start));
+        }
+        assert!(!ban_list.allow_ws_connect_at("198.51.100.7", start), "one over the limit is dropped");
+        assert!(ban_list.allow_ws_connect_at("198.51.100.8", start), "other IPs have their own count");
+        assert!(ban_list.allow_ws_connect_at("198.51.100.7", start + CONNECT_RATE_WINDOW), "the window slides");
+        for _ in 0..MAX_WS_CONNECTS_PER_WINDOW * 2 {
+            assert!(ban_list.allow_ws_connect_at("127.0.0.1", start));
+        }
+        assert!(!ban_list.is_banned("198.51.100.7"), "going over the rate isn't a strike");
+    }
+
     #[test]
     fn record_violation_threshold_unchanged_at_two() {
         let ban_list = BanList::new();
@@ -2236,8 +3044,9 @@ mod redirect_parity_tests {
         SecurityGate {
             allow_list: Arc::new(std::sync::RwLock::new(allow_list)),
             whitelisted_host: Arc::new(std::sync::RwLock::new(None)),
-            auth_key: Arc::new(std::sync::RwLock::new(None)),
+            auth_keys: Arc::new(std::sync::RwLock::new(Vec::new())),
             web_path: "clay".to_string(),
+            trusted_proxies: Vec::new(),
             ban_list: BanList::new(),
         }
     }
@@ -2442,12 +3251,13 @@ mod knock_tests {
     // constant_time_eq now lives in util.rs (see constant_time_eq_sanity there) — it's
     // shared with websocket.rs/main.rs for the WS/API-key credential compares (B3).
 
-    fn test_gate(auth_key: Option<&str>) -> SecurityGate {
+    fn test_gate(auth_keys: &[&str]) -> SecurityGate {
         SecurityGate {
             allow_list: Arc::new(std::sync::RwLock::new(Vec::new())),
             whitelisted_host: Arc::new(std::sync::RwLock::new(None)),
-            auth_key: Arc::new(std::sync::RwLock::new(auth_key.map(|s| s.to_string()))),
+            auth_keys: Arc::new(std::sync::RwLock::new(auth_keys.iter().map(|s| s.to_string()).collect())),
             web_path: "clay".to_string(),
+            trusted_proxies: Vec::new(),
             ban_list: BanList::new(),
         }
     }
@@ -2471,7 +3281,7 @@ mod knock_tests {
 
     #[tokio::test]
     async fn good_knock_proceeds_and_acks() {
-        let gate = test_gate(Some("testkey"));
+        let gate = test_gate(&["testkey"]);
         let (mut client, handle) = spawn_gate_server(gate).await;
 
         client.write_all(&[0xC7, 0x4C, 0x41, 0x59, 0x01, 0x00]).await.unwrap();
@@ -2492,9 +3302,29 @@ mod knock_tests {
         assert_eq!(result, GateResult::Proceed { knocked: true, in_allow_list: false });
     }
 
+    #[tokio::test]
+    async fn device_token_knock_proceeds() {
+        // The auth key first, then a device token: knocking with the token's hash gets in too
+        let phone_hash = crate::websocket::hash_password("phonekey");
+        let gate = test_gate(&["testkey", &phone_hash]);
+        let (mut client, handle) = spawn_gate_server(gate).await;
+
+        client.write_all(&[0xC7, 0x4C, 0x41, 0x59, 0x01, 0x00]).await.unwrap();
+        let mut challenge_msg = [0u8; 34];
+        client.read_exact(&mut challenge_msg).await.unwrap();
+        let mut challenge = [0u8; 32];
+        challenge.copy_from_slice(&challenge_msg[2..34]);
+        client.write_all(&knock_expected_response(&phone_hash, &challenge)).await.unwrap();
+
+        let mut ack = [0u8; 2];
+        client.read_exact(&mut ack).await.unwrap();
+        assert_eq!(ack, KNOCK_ACK);
+        assert_eq!(handle.await.unwrap(), GateResult::Proceed { knocked: true, in_allow_list: false });
+    }
+
     #[tokio::test]
     async fn bad_digest_drops_no_ack() {
-        let gate = test_gate(Some("testkey"));
+        let gate = test_gate(&["testkey"]);
         let (mut client, handle) = spawn_gate_server(gate).await;
 
         client.write_all(&[0xC7, 0x4C, 0x41, 0x59, 0x01, 0x00]).await.unwrap();
@@ -2515,7 +3345,7 @@ mod knock_tests {
 
     #[tokio::test]
     async fn garbage_after_magic_byte_drops() {
-        let gate = test_gate(Some("testkey"));
+        let gate = test_gate(&["testkey"]);
         let (mut client, handle) = spawn_gate_server(gate).await;
 
         // First byte 0xC7 triggers the knock path, but the rest of the 6-byte hello
@@ -2529,8 +3359,8 @@ mod knock_tests {
 
     #[tokio::test]
     async fn no_stored_key_fails_knock() {
-        // Multiuser mode: the auth_key Arc holds None.
-        let gate = test_gate(None);
+        // Multiuser mode: the auth_keys list is empty.
+        let gate = test_gate(&[]);
         let (mut client, handle) = spawn_gate_server(gate).await;
 
         client.write_all(&[0xC7, 0x4C, 0x41, 0x59, 0x01, 0x00]).await.unwrap();
@@ -2547,7 +3377,7 @@ mod knock_tests {
         // First byte is not 0xC7 — gate_connection must fall straight through to the
         // normal allow-list gate without consuming any bytes (empty allow list here =>
         // open mode => Proceed{knocked:false}).
-        let gate = test_gate(Some("testkey"));
+        let gate = test_gate(&["testkey"]);
         let (mut client, handle) = spawn_gate_server(gate).await;
 
         client.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
@@ -2556,3 +3386,123 @@ mod knock_tests {
         assert_eq!(result, GateResult::Proceed { knocked: false, in_allow_list: false });
     }
 }
+
+#[cfg(test)]
+mod api_tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    /// A gate whose shared list holds the hashes of `tokens`, as `sync_auth_keys_shared` does
+    fn test_gate(tokens: &[&str]) -> SecurityGate {
+        SecurityGate {
+            allow_list: Arc::new(std::sync::RwLock::new(Vec::new())),
+            whitelisted_host: Arc::new(std::sync::RwLock::new(None)),
+            auth_keys: Arc::new(std::sync::RwLock::new(tokens.iter().map(|s| crate::websocket::hash_password(s)).collect())),
+            web_path: "clay".to_string(),
+            trusted_proxies: Vec::new(),
+            ban_list: BanList::new(),
+        }
+    }
+
+    /// Run `handle_api_request` for `request` and return the response text
+    async fn run_api(gate: &SecurityGate, request: &str, client_ip: &str) -> String {
+        let (mut client, mut server) = tokio::io::duplex(8192);
+        let (method, path) = parse_http_request(request).unwrap();
+        let endpoint = api_endpoint(path, &gate.web_path, false).unwrap();
+        handle_api_request(&mut server, endpoint, method, request, request.as_bytes(), None, gate, client_ip, true).await;
+        drop(server);
+        let mut out = String::new();
+        client.read_to_string(&mut out).await.unwrap();
+        out
+    }
+
+    fn world(index: usize, name: &str) -> crate::websocket::WorldStateMsg {
+        let json = serde_json::json!({
+            "index": index, "name": name, "connected": true, "output_lines": [], "pending_lines": [],
+            "scroll_offset": 0, "paused": false, "prompt": "", "unseen_lines": 3,
+            "settings": {
+                "hostname": "", "port": "", "user": "", "use_ssl": false, "log_enabled": false,
+                "encoding": "utf8", "auto_connect_type": "", "keep_alive_type": "", "keep_alive_cmd": "",
+            },
+            "last_send_secs": null, "last_recv_secs": null, "last_nop_secs": null, "keep_alive_type": "",
+        });
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn api_paths_follow_the_web_path() {
+        assert_eq!(api_endpoint("/clay/api/send", "clay", false), Some(ApiEndpoint::Send));
+        assert_eq!(api_endpoint("/clay/api/worlds", "clay", false), Some(ApiEndpoint::Worlds));
+        // Stealth mode: the bare root is only for localhost
+        assert_eq!(api_endpoint("/api/worlds", "clay", false), None);
+        assert_eq!(api_endpoint("/api/worlds", "clay", true), Some(ApiEndpoint::Worlds));
+        // Legacy mode
+        assert_eq!(api_endpoint("/api/send", "", false), Some(ApiEndpoint::Send));
+        assert_eq!(api_endpoint("/clay/api/other", "clay", false), None);
+    }
+
+    #[test]
+    fn api_is_routed_like_static_content() {
+        assert_eq!(decide_route("POST", "/clay/api/send", "clay", false, false, false), RouteDecision::Api(ApiEndpoint::Send));
+        assert_eq!(decide_route("GET", "/api/worlds", "clay", true, false, false), RouteDecision::Api(ApiEndpoint::Worlds));
+        // A knock doesn't open the API, nor does the bare root reveal it in stealth mode
+        assert_eq!(decide_route("POST", "/clay/api/send", "clay", false, true, true), RouteDecision::SilentDrop { violation: None });
+        assert!(matches!(decide_route("GET", "/api/worlds", "clay", false, true, false), RouteDecision::SilentDrop { violation: Some(_) }));
+    }
+
+    #[test]
+    fn bearer_token_is_read_from_the_headers() {
+        let request = "POST /api/send HTTP/1.1\r\nHost: x\r\nauthorization: Bearer  abc123 \r\n\r\n";
+        assert_eq!(bearer_token(request), Some("abc123"));
+        assert_eq!(bearer_token("GET /api/worlds HTTP/1.1\r\nAuthorization: Basic abc\r\n\r\n"), None);
+        // Not from the body
+        assert_eq!(bearer_token("POST /api/send HTTP/1.1\r\n\r\nAuthorization: Bearer abc"), None);
+        let hash = crate::websocket::hash_password("abc123");
+        assert!(api_token_valid("abc123", &["other".to_string(), hash.clone()]));
+        assert!(!api_token_valid(&hash, std::slice::from_ref(&hash)), "the hash itself is no token");
+        assert!(!api_token_valid("", &[String::new()]));
+    }
+
+    #[test]
+    fn send_target_by_name_index_or_current() {
+        let worlds = vec![world(0, "Alpha"), world(1, "Beta")];
+        let send = |world: Option<&str>, world_index: Option<usize>| ApiSendRequest {
+            command: "look".to_string(), world: world.map(str::to_string), world_index,
+        };
+        assert_eq!(api_send_target(&send(Some("beta"), None), &worlds, 0), Some(1));
+        assert_eq!(api_send_target(&send(None, Some(0)), &worlds, 1), Some(0));
+        assert_eq!(api_send_target(&send(None, None), &worlds, 1), Some(1));
+        assert_eq!(api_send_target(&send(Some("Gamma"), None), &worlds, 0), None);
+        assert_eq!(api_send_target(&send(None, Some(5)), &worlds, 0), None);
+
+        let json = api_worlds_json(&worlds, 1);
+        assert_eq!(json["worlds"][1]["name"], "Beta");
+        assert_eq!(json["worlds"][1]["current"], true);
+        assert_eq!(json["worlds"][0]["unseen_lines"], 3);
+    }
+
+    #[tokio::test]
+    async fn wrong_token_is_refused_and_counts_toward_a_ban() {
+        let gate = test_gate(&["secret"]);
+        let request = "GET /clay/api/worlds HTTP/1.1\r\nAuthorization: Bearer wrong\r\n\r\n";
+        let response = run_api(&gate, request, "203.0.113.5").await;
+        assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+        assert!(response.contains("WWW-Authenticate: Bearer\r\n"));
+        for _ in 1..AUTH_FAILURE_THRESHOLD {
+            run_api(&gate, request, "203.0.113.5").await;
+        }
+        assert!(gate.ban_list.is_banned("203.0.113.5"));
+    }
+
+    #[tokio::test]
+    async fn wrong_method_and_missing_server() {
+        let gate = test_gate(&["secret"]);
+        let response = run_api(&gate, "GET /clay/api/send HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n", "203.0.113.6").await;
+        assert!(response.starts_with("HTTP/1.1 405"), "{}", response);
+        assert!(response.contains("Allow: POST\r\n"));
+        // A good token, but no WebSocket server to carry the call
+        let response = run_api(&gate, "GET /clay/api/worlds HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n", "203.0.113.6").await;
+        assert!(response.starts_with("HTTP/1.1 503"), "{}", response);
+        assert!(!gate.ban_list.is_banned("203.0.113.6"));
+    }
+}
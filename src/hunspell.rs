@@ -0,0 +1,330 @@
+//! Hunspell dictionaries (`.dic` word list plus `.aff` affix rules) for the spell checker.
+//!
+//! A `.dic` entry like `walk/DGS` carries flags naming prefix and suffix rules in the
+//! `.aff` file (`SFX D 0 ed [^ey]`: add "ed" where the word doesn't end in e or y).
+//! The checker only needs to know whether a word is valid, so every entry is
+//! expanded into its forms up front: the word, each prefix and suffix form, and the
+//! prefix+suffix forms of rules that allow both. Compounding, continuation flags and
+//! morphology are ignored. Dictionaries are found by language name (`de_DE`) in the
+//! usual system directories and `~/.config/clay/dict/`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+/// How the flags after `word/` are written (the `.aff` file's FLAG line)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FlagType {
+    /// One character per flag (the default; also FLAG UTF-8)
+    Char,
+    /// Two characters per flag
+    Long,
+    /// Comma-separated numbers
+    Num,
+}
+
+/// One PFX/SFX rule
+#[derive(Debug)]
+struct Affix {
+    strip: String,
+    add: String,
+    condition: Option<Regex>,
+    cross_product: bool,
+}
+
+/// The parts of an `.aff` file word expansion uses
+#[derive(Debug)]
+struct AffixFile {
+    flag_type: FlagType,
+    /// AF flag-set aliases: `word/3` means the third AF line's flags
+    aliases: Vec<Vec<String>>,
+    prefixes: HashMap<String, Vec<Affix>>,
+    suffixes: HashMap<String, Vec<Affix>>,
+    need_affix: Option<String>,
+    forbidden: Option<String>,
+}
+
+/// Decode a dictionary file in the charset its `.aff` names with SET (UTF-8 or an
+/// ISO 8859 charset, read as Latin-1)
+fn decode(bytes: &[u8], charset: &str) -> String {
+    let charset = charset.to_uppercase();
+    if charset.starts_with("ISO8859") || charset.starts_with("ISO-8859") {
+        bytes.iter().map(|&b| b as char).collect()
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+fn split_flags(flags: &str, flag_type: FlagType) -> Vec<String> {
+    match flag_type {
+        FlagType::Char => flags.chars().map(String::from).collect(),
+        FlagType::Long => {
+            let chars: Vec<char> = flags.chars().collect();
+            chars.chunks(2).map(|pair| pair.iter().collect()).collect()
+        }
+        FlagType::Num => flags.split(',').map(|f| f.trim().to_string()).filter(|f| !f.is_empty()).collect(),
+    }
+}
+
+impl AffixFile {
+    fn parse(content: &str) -> Self {
+        let mut aff = AffixFile {
+            flag_type: FlagType::Char,
+            aliases: Vec::new(),
+            prefixes: HashMap::new(),
+            suffixes: HashMap::new(),
+            need_affix: None,
+            forbidden: None,
+        };
+        // Cross product of each PFX/SFX flag, from its header line
+        let mut cross: HashMap<(bool, String), bool> = HashMap::new();
+        // The first AF line is the alias count
+        let mut af_count_seen = false;
+        for line in content.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", kind, ..] => {
+                    aff.flag_type = match kind.to_lowercase().as_str() {
+                        "long" => FlagType::Long,
+                        "num" => FlagType::Num,
+                        _ => FlagType::Char,
+                    };
+                }
+                ["AF", flags, ..] => {
+                    if af_count_seen {
+                        aff.aliases.push(split_flags(flags, aff.flag_type));
+                    }
+                    af_count_seen = true;
+                }
+                ["NEEDAFFIX", flag, ..] => aff.need_affix = Some(flag.to_string()),
+                ["FORBIDDENWORD", flag, ..] => aff.forbidden = Some(flag.to_string()),
+                [kind @ ("PFX" | "SFX"), flag, yn, count] if count.parse::<usize>().is_ok() && (*yn == "Y" || *yn == "N") => {
+                    cross.insert((*kind == "PFX", flag.to_string()), *yn == "Y");
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let is_prefix = *kind == "PFX";
+                    let strip = if *strip == "0" { "" } else { strip };
+                    // "add/flags" continues with more affixes, which isn't supported
+                    let add = add.split('/').next().unwrap_or("");
+                    let add = if add == "0" { "" } else { add };
+                    let condition = match rest.first().copied() {
+                        None | Some(".") => None,
+                        Some(cond) if is_prefix => Regex::new(&format!("^{}", cond)).ok(),
+                        Some(cond) => Regex::new(&format!("{}$", cond)).ok(),
+                    };
+                    let affix = Affix {
+                        strip: strip.to_string(),
+                        add: add.to_string(),
+                        condition,
+                        cross_product: cross.get(&(is_prefix, flag.to_string())).copied().unwrap_or(false),
+                    };
+                    let rules = if is_prefix { &mut aff.prefixes } else { &mut aff.suffixes };
+                    rules.entry(flag.to_string()).or_default().push(affix);
+                }
+                _ => {}
+            }
+        }
+        aff
+    }
+
+    /// The flags of a `.dic` entry's flag field (an alias number when AF is used)
+    fn flags(&self, field: &str) -> Vec<String> {
+        if !self.aliases.is_empty() {
+            if let Ok(n) = field.parse::<usize>() {
+                return self.aliases.get(n.wrapping_sub(1)).cloned().unwrap_or_default();
+            }
+        }
+        split_flags(field, self.flag_type)
+    }
+
+    fn apply_suffix(word: &str, affix: &Affix) -> Option<String> {
+        let base = word.strip_suffix(affix.strip.as_str())?;
+        if affix.condition.as_ref().is_some_and(|c| !c.is_match(word)) {
+            return None;
+        }
+        Some(format!("{}{}", base, affix.add))
+    }
+
+    fn apply_prefix(word: &str, affix: &Affix) -> Option<String> {
+        let base = word.strip_prefix(affix.strip.as_str())?;
+        if affix.condition.as_ref().is_some_and(|c| !c.is_match(word)) {
+            return None;
+        }
+        Some(format!("{}{}", affix.add, base))
+    }
+
+    /// Every form of `word` its `flags` allow
+    fn expand(&self, word: &str, flags: &[String], out: &mut Vec<String>) {
+        let has = |flag: &Option<String>| flag.as_ref().is_some_and(|f| flags.contains(f));
+        if has(&self.forbidden) {
+            return;
+        }
+        if !has(&self.need_affix) {
+            out.push(word.to_string());
+        }
+        let mut suffixed = Vec::new();
+        for sfx in rules(&self.suffixes, flags) {
+            if let Some(form) = Self::apply_suffix(word, sfx) {
+                if sfx.cross_product {
+                    suffixed.push(form.clone());
+                }
+                out.push(form);
+            }
+        }
+        for pfx in rules(&self.prefixes, flags) {
+            if let Some(form) = Self::apply_prefix(word, pfx) {
+                out.push(form);
+            }
+            if pfx.cross_product {
+                out.extend(suffixed.iter().filter_map(|form| Self::apply_prefix(form, pfx)));
+            }
+        }
+    }
+}
+
+/// The rules of `table` under any of `flags`
+fn rules<'a>(table: &'a HashMap<String, Vec<Affix>>, flags: &'a [String]) -> impl Iterator<Item = &'a Affix> + 'a {
+    flags.iter().filter_map(move |f| table.get(f)).flatten()
+}
+
+/// The `.aff` file next to a `.dic` file
+fn aff_path(dic: &Path) -> PathBuf {
+    dic.with_extension("aff")
+}
+
+/// All words of the Hunspell dictionary `dic`, lowercased, with the affix rules of the
+/// `.aff` file beside it applied (just the listed words when there's none)
+pub fn load_words(dic: &Path) -> Option<HashSet<String>> {
+    let dic_bytes = std::fs::read(dic).ok()?;
+    let aff_bytes = std::fs::read(aff_path(dic)).unwrap_or_default();
+    let aff_text = String::from_utf8_lossy(&aff_bytes);
+    let charset = aff_text.lines()
+        .find_map(|l| l.strip_prefix("SET "))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| "UTF-8".to_string());
+    let aff = AffixFile::parse(&decode(&aff_bytes, &charset));
+
+    let mut words = HashSet::new();
+    let mut forms = Vec::new();
+    for (i, line) in decode(&dic_bytes, &charset).lines().enumerate() {
+        let entry = line.split(['\t', ' ']).next().unwrap_or("").trim();
+        // The first line is the word count
+        if entry.is_empty() || (i == 0 && entry.chars().all(|c| c.is_ascii_digit())) {
+            continue;
+        }
+        // A '/' escaped as "\/" belongs to the word
+        let (word, flags) = match entry.find('/').filter(|&p| p == 0 || !entry[..p].ends_with('\\')) {
+            Some(p) => (&entry[..p], aff.flags(&entry[p + 1..])),
+            None => (entry, Vec::new()),
+        };
+        forms.clear();
+        aff.expand(word, &flags, &mut forms);
+        words.extend(forms.drain(..)
+            .map(|w| w.to_lowercase())
+            .filter(|w| !w.is_empty() && w.chars().all(|c| c.is_alphabetic())));
+    }
+    Some(words)
+}
+
+/// Directories searched for `<language>.dic`: the one holding the configured
+/// dictionary, `dict/` in the config directory, then the system Hunspell directories
+pub fn dictionary_dirs(dictionary_path: &str) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = Path::new(dictionary_path).parent().filter(|d| !dictionary_path.is_empty() && d.is_dir()) {
+        dirs.push(dir.to_path_buf());
+    }
+    dirs.push(crate::clay_config_dir().join("dict"));
+    dirs.push(PathBuf::from(crate::get_home_dir()).join("Library/Spelling"));
+    dirs.extend(["/usr/share/hunspell", "/usr/share/myspell", "/usr/share/myspell/dicts",
+        "/usr/local/share/hunspell", "/data/data/com.termux/files/usr/share/hunspell"].map(PathBuf::from));
+    dirs
+}
+
+/// The `.dic` file of `language` (e.g. "de_DE"), if one is installed
+pub fn find_dictionary(language: &str, dictionary_path: &str) -> Option<PathBuf> {
+    if language.is_empty() || language.contains(['/', '\\']) {
+        return None;
+    }
+    dictionary_dirs(dictionary_path).into_iter()
+        .map(|dir| dir.join(format!("{}.dic", language)))
+        .find(|p| p.is_file())
+}
+
+/// Languages with an installed `.dic` file, sorted
+pub fn available_languages(dictionary_path: &str) -> Vec<String> {
+    let mut names: Vec<String> = dictionary_dirs(dictionary_path).iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            name.strip_suffix(".dic").map(str::to_string)
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "SET UTF-8\n\
+        NEEDAFFIX X\n\
+        FORBIDDENWORD Z\n\
+        PFX U Y 1\n\
+        PFX U 0 un .\n\
+        SFX D Y 3\n\
+        SFX D 0 ed [^ey]\n\
+        SFX D y ied [^aeiou]y\n\
+        SFX D 0 d e\n\
+        SFX S N 1\n\
+        SFX S 0 s/D .\n";
+
+    fn write_dict(name: &str, aff: &str, dic: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("clay-hunspell-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("xx_XX.aff"), aff).unwrap();
+        std::fs::write(dir.join("xx_XX.dic"), dic).unwrap();
+        dir.join("xx_XX.dic")
+    }
+
+    #[test]
+    fn test_affix_expansion() {
+        let dic = write_dict("expand", AFF, "5\nwalk/DSU\ncarry/D\nbake/D\nstem/X\nbadword/Z\nMüde\n");
+        let words = load_words(&dic).unwrap();
+        for w in ["walk", "walked", "walks", "unwalk", "unwalked", "carry", "carried", "bake", "baked", "müde"] {
+            assert!(words.contains(w), "{} missing", w);
+        }
+        // Forms the rules' conditions, NEEDAFFIX, FORBIDDENWORD and cross products rule out
+        for w in ["bakeed", "carryed", "stem", "badword", "unwalks"] {
+            assert!(!words.contains(w), "{} shouldn't be there", w);
+        }
+        let _ = std::fs::remove_dir_all(dic.parent().unwrap());
+    }
+
+    #[test]
+    fn test_flag_types_and_aliases() {
+        let aff = "FLAG long\nSFX Aa Y 1\nSFX Aa 0 ing .\n";
+        let dic = write_dict("long", aff, "1\nsing/AaBb\n");
+        assert!(load_words(&dic).unwrap().contains("singing"));
+
+        let aff = "FLAG num\nAF 1\nAF 12,7\nSFX 12 Y 1\nSFX 12 0 er .\n";
+        std::fs::write(aff_path(&dic), aff).unwrap();
+        std::fs::write(&dic, "1\nfast/1\n").unwrap();
+        assert!(load_words(&dic).unwrap().contains("faster"));
+
+        // Latin-1 dictionaries
+        std::fs::write(aff_path(&dic), "SET ISO8859-1\n").unwrap();
+        std::fs::write(&dic, b"1\nsch\xf6n\n").unwrap();
+        assert!(load_words(&dic).unwrap().contains("schön"));
+
+        let dir = dic.parent().unwrap().to_string_lossy().to_string();
+        let configured = format!("{}/other.dic", dir);
+        assert_eq!(find_dictionary("xx_XX", &configured), Some(dic.clone()));
+        assert_eq!(find_dictionary("../xx_XX", &configured), None);
+        assert!(available_languages(&configured).contains(&"xx_XX".to_string()));
+        let _ = std::fs::remove_dir_all(dic.parent().unwrap());
+    }
+}
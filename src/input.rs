@@ -1,25 +1,41 @@
-use unicode_width::UnicodeWidthChar;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Columns one grapheme cluster takes on screen. Measuring the whole cluster gets emoji
+/// sequences right (a ZWJ family or a VS16 emoji is 2 wide, not the sum of its chars) and
+/// keeps combining marks at 0. Control characters take no columns.
+pub fn grapheme_width(g: &str) -> usize {
+    if g.starts_with(char::is_control) {
+        0
+    } else {
+        UnicodeWidthStr::width(g)
+    }
+}
 
 /// Calculate display width of a string (handles zero-width characters and wide chars)
 pub fn display_width(s: &str) -> usize {
-    s.chars().map(|c| UnicodeWidthChar::width(c).unwrap_or(0)).sum()
+    s.graphemes(true).map(grapheme_width).sum()
 }
 
 /// Calculate display width of a char slice
 pub fn display_width_chars(chars: &[char]) -> usize {
-    chars.iter().map(|c| UnicodeWidthChar::width(*c).unwrap_or(0)).sum()
+    display_width(&chars.iter().collect::<String>())
 }
 
 /// Find the character index where display width reaches or exceeds the target width.
-/// Returns (char_index, actual_display_width_up_to_that_point)
+/// Returns (char_index, actual_display_width_up_to_that_point). A grapheme cluster is
+/// never split, so the index always falls between clusters.
 pub fn chars_for_display_width(chars: &[char], target_width: usize) -> (usize, usize) {
+    let text: String = chars.iter().collect();
     let mut width = 0;
-    for (i, c) in chars.iter().enumerate() {
-        let char_width = UnicodeWidthChar::width(*c).unwrap_or(0);
-        if width + char_width > target_width {
+    let mut i = 0;
+    for g in text.graphemes(true) {
+        let g_width = grapheme_width(g);
+        if width + g_width > target_width {
             return (i, width);
         }
-        width += char_width;
+        width += g_width;
+        i += g.chars().count();
     }
     (chars.len(), width)
 }
@@ -30,13 +46,24 @@ pub struct InputArea {
     pub viewport_start_line: usize,
     pub visible_height: u16,
     pub width: u16,
-    pub prompt_len: usize, // Length of prompt (reduces first line capacity)
+    pub prompt_len: usize, // Display width of prompt (reduces first line capacity)
     pub history: Vec<String>,
     pub history_index: Option<usize>,
     pub temp_input: String,
     pub search_prefix: Option<String>,  // Prefix being searched (set on first ^[p/^[n)
     pub search_index: Option<usize>,    // Position in history during search
     pub kill_ring: Vec<String>,         // Killed text history (for ^Y yank)
+    pub emoji_completion: Option<(String, usize)>, // Shortcode prefix typed and match shown (Tab)
+    pub reverse_search: Option<ReverseSearch>,     // Incremental history search in progress (^R)
+}
+
+/// An incremental reverse history search (^R): the text typed so far, the history entry
+/// it last matched (shown in the input) and the input to put back on cancel
+pub struct ReverseSearch {
+    pub query: String,
+    pub index: Option<usize>,
+    pub saved: String,
+    pub failed: bool,
 }
 
 impl InputArea {
@@ -54,6 +81,8 @@ impl InputArea {
             search_prefix: None,
             search_index: None,
             kill_ring: Vec::new(),
+            emoji_completion: None,
+            reverse_search: None,
         }
     }
 
@@ -76,14 +105,14 @@ impl InputArea {
         let mut col_width = 0; // display width consumed on current line
         let mut is_first_line = true;
 
-        for c in text_before_cursor.chars() {
-            if c == '\n' {
+        for g in text_before_cursor.graphemes(true) {
+            if g.ends_with('\n') {
                 line += 1;
                 col_width = 0;
                 is_first_line = false;
                 continue;
             }
-            let cw = display_width(&c.to_string());
+            let cw = grapheme_width(g);
             let capacity = if is_first_line { first_line_capacity } else { width };
             col_width += cw;
             if capacity > 0 && col_width >= capacity {
@@ -115,26 +144,33 @@ impl InputArea {
         }
     }
 
+    /// Byte offset of the grapheme cluster before the cursor, so an emoji sequence or a
+    /// letter with its accents moves and deletes as one character
+    fn prev_grapheme_start(&self) -> usize {
+        self.buffer[..self.cursor_position]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// Byte offset just past the grapheme cluster after the cursor
+    fn next_grapheme_end(&self) -> usize {
+        self.buffer[self.cursor_position..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor_position, |g| self.cursor_position + g.len())
+    }
+
     pub fn move_cursor_left(&mut self) {
         if self.cursor_position > 0 {
-            // Find the previous character boundary
-            let mut new_pos = self.cursor_position - 1;
-            while new_pos > 0 && !self.buffer.is_char_boundary(new_pos) {
-                new_pos -= 1;
-            }
-            self.cursor_position = new_pos;
+            self.cursor_position = self.prev_grapheme_start();
             self.adjust_viewport();
         }
     }
 
     pub fn move_cursor_right(&mut self) {
         if self.cursor_position < self.buffer.len() {
-            // Find the next character boundary
-            let mut new_pos = self.cursor_position + 1;
-            while new_pos < self.buffer.len() && !self.buffer.is_char_boundary(new_pos) {
-                new_pos += 1;
-            }
-            self.cursor_position = new_pos;
+            self.cursor_position = self.next_grapheme_end();
             self.adjust_viewport();
         }
     }
@@ -148,12 +184,8 @@ impl InputArea {
 
     pub fn delete_char(&mut self) {
         if self.cursor_position > 0 {
-            // Find the previous character boundary
-            let mut new_pos = self.cursor_position - 1;
-            while new_pos > 0 && !self.buffer.is_char_boundary(new_pos) {
-                new_pos -= 1;
-            }
-            self.buffer.remove(new_pos);
+            let new_pos = self.prev_grapheme_start();
+            self.buffer.replace_range(new_pos..self.cursor_position, "");
             self.cursor_position = new_pos;
             self.adjust_viewport();
         }
@@ -161,7 +193,8 @@ impl InputArea {
 
     pub fn delete_char_forward(&mut self) {
         if self.cursor_position < self.buffer.len() {
-            self.buffer.remove(self.cursor_position);
+            let end = self.next_grapheme_end();
+            self.buffer.replace_range(self.cursor_position..end, "");
         }
     }
 
@@ -213,13 +246,21 @@ impl InputArea {
 
     pub fn take_input(&mut self) -> String {
         let input = self.buffer.clone();
-        if !input.is_empty() {
+        if !input.is_empty() && !crate::secrets::is_secret_command(&input) {
             self.history.push(input.clone());
         }
         self.clear();
         input
     }
 
+    /// Take input typed while the server has echo off (a password): it stays out of
+    /// the history and the kill ring
+    pub fn take_secret(&mut self) -> String {
+        let input = std::mem::take(&mut self.buffer);
+        self.clear();
+        input
+    }
+
     pub fn history_prev(&mut self) {
         if self.history.is_empty() {
             return;
@@ -380,13 +421,13 @@ impl InputArea {
         let mut col_width = 0usize;
         let mut is_first_line = true;
 
-        for c in text_before_cursor.chars() {
-            if c == '\n' {
+        for g in text_before_cursor.graphemes(true) {
+            if g.ends_with('\n') {
                 col_width = 0;
                 is_first_line = false;
                 continue;
             }
-            let cw = UnicodeWidthChar::width(c).unwrap_or(0);
+            let cw = grapheme_width(g);
             let capacity = if is_first_line { first_line_capacity } else { width };
             col_width += cw;
             if capacity > 0 && col_width >= capacity {
@@ -411,32 +452,32 @@ impl InputArea {
         let mut is_first_line = true;
         let mut byte_pos = 0;
 
-        for c in self.buffer.chars() {
-            if c == '\n' {
-                byte_pos += c.len_utf8();
+        for g in self.buffer.graphemes(true) {
+            if g.ends_with('\n') {
+                byte_pos += g.len();
                 starts.push(byte_pos);
                 col_width = 0;
                 is_first_line = false;
                 continue;
             }
-            let cw = UnicodeWidthChar::width(c).unwrap_or(0);
+            let cw = grapheme_width(g);
             let capacity = if is_first_line { first_line_capacity } else { width };
             col_width += cw;
             if capacity > 0 && col_width >= capacity {
-                byte_pos += c.len_utf8();
+                byte_pos += g.len();
                 if col_width == capacity {
                     // Exact fill — next char starts a new line
                     starts.push(byte_pos);
                     col_width = 0;
                 } else {
                     // Overflow — this char starts a new line
-                    starts.push(byte_pos - c.len_utf8());
+                    starts.push(byte_pos - g.len());
                     col_width = cw;
                 }
                 is_first_line = false;
                 continue;
             }
-            byte_pos += c.len_utf8();
+            byte_pos += g.len();
         }
         starts
     }
@@ -468,12 +509,12 @@ impl InputArea {
             // Walk from line_start to find the byte position at target_col display width
             let mut col = 0;
             let mut pos = line_start;
-            for c in self.buffer[line_start..].chars() {
-                if c == '\n' { break; }
-                let cw = UnicodeWidthChar::width(c).unwrap_or(0);
+            for g in self.buffer[line_start..].graphemes(true) {
+                if g.ends_with('\n') { break; }
+                let cw = grapheme_width(g);
                 if col + cw > target_col { break; }
                 col += cw;
-                pos += c.len_utf8();
+                pos += g.len();
                 // Stop at line boundary (next line start)
                 if target_line + 1 < starts.len() && pos >= starts[target_line + 1] {
                     pos = starts[target_line + 1];
@@ -513,12 +554,12 @@ impl InputArea {
             let line_start = starts[target_line];
             let mut col = 0;
             let mut pos = line_start;
-            for c in self.buffer[line_start..].chars() {
-                if c == '\n' { break; }
-                let cw = UnicodeWidthChar::width(c).unwrap_or(0);
+            for g in self.buffer[line_start..].graphemes(true) {
+                if g.ends_with('\n') { break; }
+                let cw = grapheme_width(g);
                 if col + cw > target_col { break; }
                 col += cw;
-                pos += c.len_utf8();
+                pos += g.len();
                 // Stop at line boundary
                 if target_line + 1 < starts.len() && pos >= starts[target_line + 1] {
                     pos = starts[target_line + 1];
@@ -845,6 +886,122 @@ impl InputArea {
         self.adjust_viewport();
     }
 
+    /// Start an incremental reverse history search (^R), or while one is running, move
+    /// to the next older entry containing the search text
+    pub fn reverse_search(&mut self) {
+        match self.reverse_search {
+            None => {
+                self.reverse_search = Some(ReverseSearch {
+                    query: String::new(),
+                    index: None,
+                    saved: self.buffer.clone(),
+                    failed: false,
+                });
+            }
+            Some(ref s) => {
+                let before = s.index.unwrap_or(self.history.len());
+                self.reverse_search_find(before);
+            }
+        }
+    }
+
+    /// Show the newest history entry before `before` that contains the search text
+    fn reverse_search_find(&mut self, before: usize) {
+        let Some(search) = self.reverse_search.as_mut() else { return };
+        if search.query.is_empty() {
+            search.failed = false;
+            return;
+        }
+        let before = before.min(self.history.len());
+        match self.history[..before].iter().rposition(|h| h.contains(&search.query)) {
+            Some(i) => {
+                search.index = Some(i);
+                search.failed = false;
+                self.buffer = self.history[i].clone();
+                self.cursor_position = self.buffer.find(&search.query).unwrap_or(0);
+                self.adjust_viewport();
+            }
+            None => search.failed = true,
+        }
+    }
+
+    /// Feed a key to a running reverse search. Returns true if the search used it;
+    /// any other key ends the search with the match left in the input, to be handled
+    /// as usual (Enter sends it).
+    pub fn reverse_search_key(&mut self, code: crossterm::event::KeyCode, modifiers: crossterm::event::KeyModifiers) -> bool {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        let Some(search) = self.reverse_search.as_mut() else { return false };
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Char('r') if ctrl => self.reverse_search(),
+            KeyCode::Char('g') if ctrl => self.reverse_search_cancel(),
+            KeyCode::Esc => self.reverse_search_cancel(),
+            KeyCode::Char(c) if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                search.query.push(c);
+                // The entry shown may still match the longer text
+                let before = search.index.map(|i| i + 1).unwrap_or(self.history.len());
+                self.reverse_search_find(before);
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                let len = self.history.len();
+                self.reverse_search_find(len);
+            }
+            _ => {
+                self.reverse_search = None;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// End a reverse search, putting back the input it started from
+    pub fn reverse_search_cancel(&mut self) {
+        if let Some(search) = self.reverse_search.take() {
+            self.buffer = search.saved;
+            self.cursor_position = self.buffer.len();
+            self.adjust_viewport();
+        }
+    }
+
+    /// The prompt shown in place of the world's while a reverse search runs
+    pub fn reverse_search_prompt(&self) -> Option<String> {
+        self.reverse_search.as_ref().map(|s| {
+            format!("({}reverse-i-search)`{}': ", if s.failed { "failed " } else { "" }, s.query)
+        })
+    }
+
+    /// Tab-complete the `:name` before the cursor to `:name:`, given the shortcode names
+    /// matching a prefix. Tab again on the completion cycles through the other matches.
+    /// Returns false (leaving the input alone) when there is nothing to complete.
+    pub fn complete_emoji_shortcode(&mut self, matching: impl Fn(&str) -> Vec<&'static str>) -> bool {
+        let before = &self.buffer[..self.cursor_position];
+        let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &before[start..];
+        let cycled = self.emoji_completion.as_ref().and_then(|(prefix, idx)| {
+            let matches = matching(prefix);
+            let shown = matches.get(*idx)?;
+            (word == format!(":{}:", shown)).then(|| (prefix.clone(), (idx + 1) % matches.len(), matches))
+        });
+        let (prefix, idx, matches) = match cycled {
+            Some(found) => found,
+            None => {
+                let Some(prefix) = word.strip_prefix(':') else { return false };
+                if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric() || "_+-".contains(c)) {
+                    return false;
+                }
+                (prefix.to_string(), 0, matching(prefix))
+            }
+        };
+        let Some(name) = matches.get(idx) else { return false };
+        let completed = format!(":{}:", name);
+        self.buffer.replace_range(start..self.cursor_position, &completed);
+        self.cursor_position = start + completed.len();
+        self.emoji_completion = Some((prefix, idx));
+        self.adjust_viewport();
+        true
+    }
+
     pub fn replace_word(&mut self, start: usize, end: usize, new_word: &str) {
         // start and end are character indices
         let before: String = self.buffer.chars().take(start).collect();
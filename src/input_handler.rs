@@ -9,14 +9,13 @@ use std::time::Duration;
 
 use crate::{
     popup, persistence, keybindings,
-    SpellChecker,
     WsMessage,
     Theme, WorldSwitchMode,
     Encoding, AutoConnectType, KeepAliveType, WorldType,
     current_timestamp_secs,
     App, World, EditorFocus, EditorSide, DEBUG_ENABLED,
     handle_new_popup_key, NewPopupAction,
-    WorldSelectorAction, ActionsListAction, NotesListAction, RecentWorldsAction,
+    WorldSelectorAction, ActionsListAction, NotesListAction, MacrosAction, TagRoutesAction, GagsAction, RecentWorldsAction,
     web_settings_from_custom_data, apply_web_settings,
 };
 
@@ -247,6 +246,78 @@ pub(crate) fn handle_popup_mouse_highlight_end(app: &mut App) {
     }
 }
 
+/// Rebuild the open actions list's rows from `app.settings.actions` after a change, keeping
+/// its filter; `select` moves the selection to that action (by index into the actions).
+/// Shared by the console and remote console.
+pub(crate) fn refresh_actions_list_popup(app: &mut App, select: Option<usize>) {
+    use popup::definitions::actions::{ActionInfo, filter_actions, ACTIONS_FIELD_FILTER, ACTIONS_FIELD_LIST};
+    let all_actions: Vec<ActionInfo> = app.settings.actions
+        .iter()
+        .enumerate()
+        .map(|(i, a)| ActionInfo {
+            name: a.name.clone(),
+            world: a.world.clone(),
+            pattern: a.display_pattern().to_string(),
+            enabled: a.enabled,
+            index: i,
+        })
+        .collect();
+    let Some(state) = app.popup_manager.current_mut() else { return };
+    if state.definition.id != popup::PopupId("actions_list") {
+        return;
+    }
+    let filter_text = if state.editing && state.is_field_selected(ACTIONS_FIELD_FILTER) {
+        state.edit_buffer.clone()
+    } else {
+        state.get_text(ACTIONS_FIELD_FILTER).unwrap_or("").to_string()
+    };
+    // List order is trigger order, so no sorting
+    let filtered = filter_actions(&all_actions, &filter_text);
+    if let Some(field) = state.field_mut(ACTIONS_FIELD_LIST) {
+        if let popup::FieldKind::List { items, selected_index, scroll_offset, visible_height, .. } = &mut field.kind {
+            *items = filtered.iter().map(|info| {
+                #[cfg(not(windows))]
+                let status = if info.enabled { "[✓]" } else { "[ ]" };
+                #[cfg(windows)]
+                let status = if info.enabled { "[x]" } else { "[ ]" };
+                let world_part = if info.world.is_empty() {
+                    String::new()
+                } else {
+                    format!("({})", info.world)
+                };
+                let pattern_preview = if info.pattern.len() > 30 {
+                    format!("{}...", &info.pattern[..27])
+                } else {
+                    info.pattern.clone()
+                };
+                popup::ListItem {
+                    id: info.index.to_string(),
+                    columns: vec![
+                        format!("{} {}", status, info.name),
+                        world_part,
+                        pattern_preview,
+                    ],
+                    style: popup::ListItemStyle {
+                        is_disabled: !info.enabled,
+                        ..Default::default()
+                    },
+                }
+            }).collect();
+            let select = select.and_then(|idx| items.iter().position(|item| item.id == idx.to_string()));
+            if let Some(pos) = select {
+                *selected_index = pos;
+            } else if *selected_index >= items.len() {
+                *selected_index = items.len().saturating_sub(1);
+            }
+            if *selected_index < *scroll_offset {
+                *scroll_offset = *selected_index;
+            } else if *visible_height > 0 && *selected_index >= *scroll_offset + *visible_height {
+                *scroll_offset = *selected_index + 1 - *visible_height;
+            }
+        }
+    }
+}
+
 pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
 
     // Handle confirm dialog first (highest priority)
@@ -499,6 +570,9 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                 } else if data.contains_key(popup::definitions::confirm::IMPORT_RELOAD_OFFER) {
                     // Post-import "reload now?" offer confirmed.
                     return KeyAction::Reload;
+                } else if data.contains_key(popup::definitions::confirm::QUIT_CONFIRM) {
+                    app.quit_confirmed = true;
+                    return KeyAction::SendCommand("/quit".to_string());
                 }
             }
             NewPopupAction::ConfirmCancelled(data) => {
@@ -580,6 +654,7 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                             user: w.settings.user.clone(),
                             is_connected: w.connected,
                             is_current: idx == app.current_world_index,
+                            bell_count: w.bell_count,
                         })
                         .collect();
                     // Apply filter
@@ -606,7 +681,7 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                 app.settings.tls_proxy_enabled = settings.tls_proxy;
                 if app.settings.dictionary_path != settings.dictionary_path {
                     app.settings.dictionary_path = settings.dictionary_path.clone();
-                    app.spell_checker = SpellChecker::new(&app.settings.dictionary_path);
+                    app.reload_spell_checker();
                 }
                 app.settings.editor_side = EditorSide::from_name(&settings.editor_side);
                 // Update mouse setting; if disabled, turn off capture immediately
@@ -640,6 +715,16 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                     app.settings.wrapspace = new_wrapspace;
                     app.needs_output_redraw = true;
                 }
+                let unknown = crate::status_format::unknown_fields(&settings.status_format);
+                if !unknown.is_empty() {
+                    app.add_output(&format!("Status bar: unknown field %{} is shown as typed (see /help setup).", unknown.join(", %")));
+                }
+                app.settings.status_format = settings.status_format;
+                app.settings.tab_bar = settings.tab_bar;
+                app.settings.notify_activity = settings.notify_activity;
+                app.settings.scrollback_lines = (settings.scrollback_lines.max(0) as usize)
+                    .clamp(crate::MIN_SCROLLBACK_LINES, crate::MAX_SCROLLBACK_LINES);
+                app.settings.confirm_quit = settings.confirm_quit;
                 // Save settings to disk
                 let _ = persistence::save_settings(app);
             }
@@ -658,6 +743,11 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                     def.custom_data.insert("custom_cert".to_string(), settings.custom_cert.to_string());
                     def.custom_data.insert("ws_cert_file".to_string(), settings.ws_cert_file);
                     def.custom_data.insert("ws_key_file".to_string(), settings.ws_key_file);
+                    def.custom_data.insert("acme_domain".to_string(), settings.acme_domain);
+                    def.custom_data.insert("acme_email".to_string(), settings.acme_email);
+                    def.custom_data.insert("bind_addr".to_string(), settings.bind_addr);
+                    def.custom_data.insert("unix_socket".to_string(), settings.unix_socket);
+                    def.custom_data.insert("trusted_proxies".to_string(), settings.trusted_proxies);
                     def.custom_data.insert("remote_initial_lines".to_string(), settings.remote_initial_lines.to_string());
                     app.popup_manager.open(def);
                 } else {
@@ -686,72 +776,34 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                             app.open_delete_action_confirm(&name, idx);
                         }
                     }
+                    ActionsListAction::Duplicate(idx) => {
+                        if let Some(copy) = crate::actions::duplicate_action(&mut app.settings.actions, idx) {
+                            let _ = persistence::save_settings(app);
+                            refresh_actions_list_popup(app, Some(copy));
+                        }
+                    }
+                    ActionsListAction::Move(idx, up) => {
+                        if let Some(moved) = crate::actions::move_action(&mut app.settings.actions, idx, up) {
+                            let _ = persistence::save_settings(app);
+                            refresh_actions_list_popup(app, Some(moved));
+                        }
+                    }
+                    ActionsListAction::Import => {
+                        // The file name is typed in the input line, where the command runs
+                        app.input.buffer = "/actions import ".to_string();
+                        app.input.cursor_position = app.input.buffer.len();
+                    }
+                    ActionsListAction::Export => {
+                        app.input.buffer = "/actions export actions.json ".to_string();
+                        app.input.cursor_position = app.input.buffer.len();
+                    }
                     ActionsListAction::Toggle(idx) => {
                         // Toggle enable/disable for the action
                         if idx < app.settings.actions.len() {
                             app.settings.actions[idx].enabled = !app.settings.actions[idx].enabled;
                             app.settings.actions[idx].compile_regex();
                             let _ = persistence::save_settings(app);
-
-                            // Update the list display in the popup
-                            use popup::definitions::actions::{ActionInfo, filter_actions, ACTIONS_FIELD_FILTER, ACTIONS_FIELD_LIST};
-                            if let Some(state) = app.popup_manager.current_mut() {
-                                // Get current filter text
-                                let filter_text = if state.editing && state.is_field_selected(ACTIONS_FIELD_FILTER) {
-                                    state.edit_buffer.clone()
-                                } else {
-                                    state.get_text(ACTIONS_FIELD_FILTER).unwrap_or("").to_string()
-                                };
-                                // Build action info list
-                                let all_actions: Vec<ActionInfo> = app.settings.actions
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(i, a)| ActionInfo {
-                                        name: a.name.clone(),
-                                        world: a.world.clone(),
-                                        pattern: a.display_pattern().to_string(),
-                                        enabled: a.enabled,
-                                        index: i,
-                                    })
-                                    .collect();
-                                // Apply filter and sort alphabetically
-                                let mut filtered = filter_actions(&all_actions, &filter_text);
-                                filtered.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                                // Update the list in the popup state
-                                if let Some(field) = state.field_mut(ACTIONS_FIELD_LIST) {
-                                    if let popup::FieldKind::List { items, .. } = &mut field.kind {
-                                        // Rebuild items with indices
-                                        *items = filtered.iter().map(|info| {
-                                                #[cfg(not(windows))]
-                                                let status = if info.enabled { "[✓]" } else { "[ ]" };
-                                                #[cfg(windows)]
-                                                let status = if info.enabled { "[x]" } else { "[ ]" };
-                                                let world_part = if info.world.is_empty() {
-                                                    String::new()
-                                                } else {
-                                                    format!("({})", info.world)
-                                                };
-                                                let pattern_preview = if info.pattern.len() > 30 {
-                                                    format!("{}...", &info.pattern[..27])
-                                                } else {
-                                                    info.pattern.clone()
-                                                };
-                                                popup::ListItem {
-                                                    id: info.index.to_string(),
-                                                    columns: vec![
-                                                        format!("{} {}", status, info.name),
-                                                        world_part,
-                                                        pattern_preview,
-                                                    ],
-                                                    style: popup::ListItemStyle {
-                                                        is_disabled: !info.enabled,
-                                                        ..Default::default()
-                                                    },
-                                                }
-                                        }).collect();
-                                    }
-                                }
-                            }
+                            refresh_actions_list_popup(app, None);
                         }
                     }
                 }
@@ -778,9 +830,8 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                             index: i,
                         })
                         .collect();
-                    // Apply filter and sort alphabetically
-                    let mut filtered = filter_actions(&all_actions, &filter_text);
-                    filtered.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+                    // Apply filter (list order is trigger order)
+                    let filtered = filter_actions(&all_actions, &filter_text);
                     // Update the list in the popup state
                     if let Some(field) = state.field_mut(popup::definitions::actions::ACTIONS_FIELD_LIST) {
                         if let popup::FieldKind::List { items, selected_index, scroll_offset, .. } = &mut field.kind {
@@ -870,6 +921,7 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                 let idx = settings.world_index;
                 if idx < app.worlds.len() {
                     // Update world name
+                    crate::persistence::rename_locked_credentials(&app.worlds[idx].name, &settings.name);
                     app.worlds[idx].name = settings.name;
 
                     // Update world type
@@ -881,18 +933,24 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                     app.worlds[idx].settings.user = settings.user;
                     app.worlds[idx].settings.password = settings.password;
                     app.worlds[idx].settings.use_ssl = settings.use_ssl;
+                    app.worlds[idx].settings.tls_verify = crate::platform::TlsVerify::from_name(&settings.tls_verify).unwrap_or_default();
+                    app.worlds[idx].settings.tls_client_cert = settings.tls_client_cert;
+                    app.worlds[idx].settings.tls_client_key = settings.tls_client_key;
+                    app.worlds[idx].settings.ssh_host = settings.ssh_host;
+                    app.worlds[idx].settings.ssh_user = settings.ssh_user;
+                    app.worlds[idx].settings.ssh_key = settings.ssh_key;
                     app.worlds[idx].settings.log_enabled = settings.log_enabled;
+                    app.worlds[idx].settings.log_format = crate::logging::LogFormat::from_name(&settings.log_format).unwrap_or_default();
+                    app.worlds[idx].settings.log_timestamps = crate::logging::LogTimestamps::from_name(&settings.log_timestamps).unwrap_or_default();
+                    app.worlds[idx].settings.log_session_markers = settings.log_session_markers;
 
                     // Update encoding
                     app.worlds[idx].settings.encoding = Encoding::from_name(&settings.encoding);
+                    app.worlds[idx].sync_send_encoding();
 
                     // Update auto connect type
-                    app.worlds[idx].settings.auto_connect_type = match settings.auto_connect.as_str() {
-                        "prompt" => AutoConnectType::Prompt,
-                        "moo_prompt" => AutoConnectType::MooPrompt,
-                        "none" => AutoConnectType::NoLogin,
-                        _ => AutoConnectType::Connect,
-                    };
+                    app.worlds[idx].settings.auto_connect_type = AutoConnectType::from_name(&settings.auto_connect);
+                    app.worlds[idx].settings.login_script = settings.login_script;
 
                     // Update keep alive type
                     app.worlds[idx].settings.keep_alive_type = match settings.keep_alive.as_str() {
@@ -901,7 +959,12 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                         "generic" => KeepAliveType::Generic,
                         _ => KeepAliveType::Nop,
                     };
+                    app.worlds[idx].settings.connect_commands = settings.connect_commands;
+                    app.worlds[idx].settings.input_prefix = settings.input_prefix;
+                    app.worlds[idx].settings.input_suffix = settings.input_suffix;
                     app.worlds[idx].settings.keep_alive_cmd = settings.keep_alive_cmd;
+                    app.worlds[idx].settings.keep_alive_interval_secs = crate::WorldSettings::parse_keep_alive_interval(&settings.keep_alive_interval);
+                    app.worlds[idx].settings.silence_alert_mins = settings.silence_alert.trim().parse().unwrap_or(0);
                     app.worlds[idx].settings.gmcp_packages = settings.gmcp_packages;
                     let (ar_secs, ar_on_web) = crate::WorldSettings::parse_auto_reconnect(&settings.auto_reconnect_secs);
                     app.worlds[idx].settings.auto_reconnect_secs = ar_secs;
@@ -954,6 +1017,79 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                     }
                 }
             }
+            NewPopupAction::Macros(action) => {
+                let world_idx = app.current_world_index;
+                match action {
+                    MacrosAction::Add => {
+                        app.input.buffer = "/macro ".to_string();
+                        app.input.cursor_position = app.input.buffer.len();
+                    }
+                    MacrosAction::Edit(key) => {
+                        let text = app.worlds[world_idx].settings.macros.get(&key).cloned().unwrap_or_default();
+                        app.input.buffer = format!("/macro {} {}", key, text);
+                        app.input.cursor_position = app.input.buffer.len();
+                    }
+                    MacrosAction::Delete(key) => {
+                        // Rebuild the popup in place, keeping the selection position
+                        let pos = app.worlds[world_idx].settings.macros.keys().position(|k| *k == key).unwrap_or(0);
+                        app.worlds[world_idx].settings.macros.remove(&key);
+                        crate::commands::macros_changed(app, world_idx);
+                        app.popup_manager.close();
+                        app.open_macros_popup(pos);
+                    }
+                }
+            }
+            NewPopupAction::TagRoutes(action) => {
+                // Apply, then rebuild the popup in place with the same row selected
+                let idx = match action {
+                    TagRoutesAction::Toggle(idx) => {
+                        if let Some(route) = app.settings.tag_routes.get_mut(idx) {
+                            route.enabled = !route.enabled;
+                        }
+                        idx
+                    }
+                    TagRoutesAction::Delete(idx) => {
+                        if idx < app.settings.tag_routes.len() {
+                            app.settings.tag_routes.remove(idx);
+                        }
+                        idx
+                    }
+                };
+                crate::commands::tag_routes_changed(app);
+                app.popup_manager.close();
+                app.open_tag_routes_popup(idx);
+            }
+            NewPopupAction::Gags(action) => {
+                // Apply, then rebuild the popup in place with the same row selected
+                let idx = match action {
+                    GagsAction::Add => {
+                        app.input.buffer = "/gag ".to_string();
+                        app.input.cursor_position = app.input.buffer.len();
+                        return KeyAction::None;
+                    }
+                    GagsAction::Toggle(idx) => {
+                        if let Some(gag) = app.settings.gags.get_mut(idx) {
+                            gag.enabled = !gag.enabled;
+                        }
+                        idx
+                    }
+                    GagsAction::Keep(idx) => {
+                        if let Some(gag) = app.settings.gags.get_mut(idx) {
+                            gag.temporary = !gag.temporary;
+                        }
+                        idx
+                    }
+                    GagsAction::Delete(idx) => {
+                        if idx < app.settings.gags.len() {
+                            app.settings.gags.remove(idx);
+                        }
+                        idx
+                    }
+                };
+                crate::commands::gags_changed(app);
+                app.popup_manager.close();
+                app.open_gags_popup(idx);
+            }
             NewPopupAction::RecentWorlds(action) => {
                 match action {
                     RecentWorldsAction::Switch(name) => {
@@ -965,6 +1101,20 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                     RecentWorldsAction::Close => {}
                 }
             }
+            NewPopupAction::OpenUrl(url) => {
+                if let Err(e) = crate::platform::open_url(&url) {
+                    app.add_output(&format!("Could not open {}: {}", url, e));
+                }
+            }
+            NewPopupAction::SendUrl(url) => {
+                return KeyAction::SendCommand(url);
+            }
+            NewPopupAction::Spell(action) => {
+                app.apply_spell_popup(action);
+            }
+            NewPopupAction::RevokeDeviceToken(name) => {
+                app.apply_device_token_request(crate::commands::DeviceTokenRequest::Revoke(name));
+            }
             NewPopupAction::ImportSubmit { addr, password, auth_key } => {
                 return KeyAction::RunImport { addr, password, auth_key, allow_insecure: false };
             }
@@ -989,24 +1139,21 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
             KeyCode::F(2) => {
                 // F2 toggles show_tags while filter is open
                 app.show_tags = !app.show_tags;
-                let output_lines = app.current_world().output_lines.clone();
-                app.filter_popup.update_filter(&output_lines);
+                app.refresh_filter();
                 app.needs_output_redraw = true;
             }
             KeyCode::Backspace => {
                 if app.filter_popup.cursor > 0 {
                     app.filter_popup.cursor -= 1;
                     app.filter_popup.filter_text.remove(app.filter_popup.cursor);
-                    let output_lines = app.current_world().output_lines.clone();
-                    app.filter_popup.update_filter(&output_lines);
+                    app.refresh_filter();
                     app.needs_output_redraw = true;
                 }
             }
             KeyCode::Delete => {
                 if app.filter_popup.cursor < app.filter_popup.filter_text.len() {
                     app.filter_popup.filter_text.remove(app.filter_popup.cursor);
-                    let output_lines = app.current_world().output_lines.clone();
-                    app.filter_popup.update_filter(&output_lines);
+                    app.refresh_filter();
                     app.needs_output_redraw = true;
                 }
             }
@@ -1046,8 +1193,7 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
             KeyCode::Char(c) => {
                 app.filter_popup.filter_text.insert(app.filter_popup.cursor, c);
                 app.filter_popup.cursor += 1;
-                let output_lines = app.current_world().output_lines.clone();
-                app.filter_popup.update_filter(&output_lines);
+                app.refresh_filter();
                 app.needs_output_redraw = true;
             }
             _ => {}
@@ -1055,88 +1201,19 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
         return KeyAction::None;
     }
 
-    // Handle search popup (F5) input
-    if app.search_popup.visible {
-        match key.code {
-            KeyCode::Esc | KeyCode::F(5) => {
-                app.search_popup.close();
-                let w = app.current_world_mut();
-                w.search_active = false;
-                w.scroll_to_bottom();
-                app.needs_output_redraw = true;
-            }
-            KeyCode::Enter => {
-                let output_lines = app.current_world().output_lines.clone();
-                let show_tags = app.show_tags;
-                // Re-update in case lines changed
-                app.search_popup.update_search(&output_lines, show_tags);
-                if let Some(line_idx) = app.search_popup.advance() {
-                    app.current_world_mut().scroll_offset = line_idx;
-                    app.needs_output_redraw = true;
-                }
-            }
-            KeyCode::Backspace => {
-                if app.search_popup.cursor > 0 {
-                    app.search_popup.cursor -= 1;
-                    app.search_popup.search_text.remove(app.search_popup.cursor);
-                    let output_lines = app.current_world().output_lines.clone();
-                    let show_tags = app.show_tags;
-                    app.search_popup.update_search(&output_lines, show_tags);
-                    if let Some(line_idx) = app.search_popup.current_match_line() {
-                        app.current_world_mut().scroll_offset = line_idx;
-                    }
-                    app.needs_output_redraw = true;
-                }
-            }
-            KeyCode::Delete => {
-                if app.search_popup.cursor < app.search_popup.search_text.len() {
-                    app.search_popup.search_text.remove(app.search_popup.cursor);
-                    let output_lines = app.current_world().output_lines.clone();
-                    let show_tags = app.show_tags;
-                    app.search_popup.update_search(&output_lines, show_tags);
-                    if let Some(line_idx) = app.search_popup.current_match_line() {
-                        app.current_world_mut().scroll_offset = line_idx;
-                    }
-                    app.needs_output_redraw = true;
-                }
-            }
-            KeyCode::Up => {
-                if let Some(line_idx) = app.search_popup.advance() {
-                    app.current_world_mut().scroll_offset = line_idx;
-                    app.needs_output_redraw = true;
-                }
-            }
-            KeyCode::Down => {
-                if let Some(line_idx) = app.search_popup.retreat() {
-                    app.current_world_mut().scroll_offset = line_idx;
-                    app.needs_output_redraw = true;
-                }
-            }
-            KeyCode::Left => {
-                if app.search_popup.cursor > 0 {
-                    app.search_popup.cursor -= 1;
-                }
-            }
-            KeyCode::Right => {
-                if app.search_popup.cursor < app.search_popup.search_text.len() {
-                    app.search_popup.cursor += 1;
-                }
-            }
-            KeyCode::Home => { app.search_popup.cursor = 0; }
-            KeyCode::End => { app.search_popup.cursor = app.search_popup.search_text.len(); }
-            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.search_popup.search_text.insert(app.search_popup.cursor, c);
-                app.search_popup.cursor += 1;
-                let output_lines = app.current_world().output_lines.clone();
-                let show_tags = app.show_tags;
-                app.search_popup.update_search(&output_lines, show_tags);
-                if let Some(line_idx) = app.search_popup.current_match_line() {
-                    app.current_world_mut().scroll_offset = line_idx;
-                }
-                app.needs_output_redraw = true;
-            }
-            _ => {}
-        }
+    // Copy mode takes every key until it is left
+    if handle_copy_mode_key(app, &key) {
+        return KeyAction::None;
+    }
+
+    // Handle output search (F5 popup or Ctrl+F scrollback search) input
+    if handle_search_key(app, &key) {
+        return KeyAction::None;
+    }
+
+    // Reverse history search (Ctrl+R) takes keys until accepted; the accepting key then
+    // falls through and acts on the recalled line
+    if app.input.reverse_search.is_some() && app.input.reverse_search_key(key.code, key.modifiers) {
         return KeyAction::None;
     }
 
@@ -1224,8 +1301,8 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
                 let internal_commands = vec![
                     // Clay-specific commands
                     "/help", "/disconnect", "/dc", "/worlds", "/world", "/connections",
-                    "/setup", "/web", "/actions", "/reload", "/update", "/quit", "/gag",
-                    "/testmusic", "/dump", "/edit", "/tag", "/menu", "/notify",
+                    "/setup", "/web", "/actions", "/reload", "/detach", "/update", "/quit", "/gag",
+                    "/testmusic", "/dump", "/edit", "/tag", "/tagroute", "/profile", "/config", "/numpad", "/secret", "/lock", "/unlock", "/loopguard", "/clipboard", "/throttle", "/urls", "/capture", "/split", "/timestamps", "/bell", "/log", "/replay", "/export", "/macro", "/menu", "/notify", "/calc", "/roll", "/sendfile", "/shell", "/pipe", "/localecho", "/emoji", "/autocorrect", "/palette", "/tint", "/images", "/addword", "/spellignore", "/unignore", "/spelllang",
                     // TF commands (now available with / prefix)
                     "/set", "/unset", "/let", "/echo", "/send", "/beep", "/quote",
                     "/expr", "/test", "/eval", "/if", "/elseif", "/else", "/endif",
@@ -1291,6 +1368,14 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
         }
     }
 
+    // Handle Tab for :name: emoji shortcodes in a world with /emoji on
+    if key.code == KeyCode::Tab && key.modifiers.is_empty() && !is_command_prefix
+        && app.current_world().settings.emoji_shortcodes
+        && app.input.complete_emoji_shortcode(crate::encoding::emoji_shortcodes_matching)
+    {
+        return KeyAction::None;
+    }
+
     // Ctrl+V literal next: insert next character literally
     if app.literal_next {
         app.literal_next = false;
@@ -1327,6 +1412,21 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
         }
     }
 
+    // Numpad speedwalk (/numpad): keypad digits walk when enabled for this world
+    if app.current_world().settings.numpad_walk {
+        if let Some(cmd) = keybindings::numpad_walk_command(&key) {
+            return KeyAction::SendCommand(cmd.to_string());
+        }
+    }
+
+    // Per-world function key macros (/macro) override every other binding
+    if let KeyCode::F(n) = key.code {
+        let name = keybindings::fkey_macro_name(n, key.modifiers);
+        if let Some(text) = app.current_world().settings.macros.get(&name).cloned() {
+            return KeyAction::SendCommand(text);
+        }
+    }
+
     // Check TF /bind bindings first (runtime bindings from /bind command)
     if let Some(ref name) = key_name {
         // Map our canonical names to TF's key name format for lookup
@@ -1336,6 +1436,15 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
         }
     }
 
+    // Ctrl+F on an empty input line searches the scrollback (the default ^F binding,
+    // cursor_right, has nothing to do there)
+    if key_name.as_deref() == Some("^F")
+        && app.input.buffer.is_empty()
+        && app.keybindings.get_action("^F") == Some("cursor_right")
+    {
+        return dispatch_action("scrollback_search", app);
+    }
+
     // Check configurable action bindings
     if let Some(ref name) = key_name {
         if let Some(action_id) = app.keybindings.get_action(name).map(|s| s.to_string()) {
@@ -1345,7 +1454,7 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
 
     // Enter key (not bound by default via action system - always active)
     if key.code == KeyCode::Enter {
-        let input = app.input.take_input();
+        let input = if app.input_masked() { app.input.take_secret() } else { app.input.take_input() };
         if !input.is_empty() || app.current_world().connected {
             // /dump is passive — don't reset more-mode state
             let is_dump = input.trim().eq_ignore_ascii_case("/dump");
@@ -1375,6 +1484,270 @@ pub(crate) fn handle_key_event(key: KeyEvent, app: &mut App) -> KeyAction {
     KeyAction::None
 }
 
+/// End the output search: clear highlights and release the view. `to_bottom` jumps back
+/// to the newest output; otherwise the view stays where the search left it.
+pub(crate) fn end_search(app: &mut App, to_bottom: bool) {
+    app.search_popup.close();
+    let w = app.current_world_mut();
+    w.search_active = false;
+    if to_bottom {
+        w.scroll_to_bottom();
+    }
+    app.needs_output_redraw = true;
+}
+
+/// Enter copy mode with the cursor on the last line shown (see copy_mode.rs)
+pub(crate) fn start_copy_mode(app: &mut App) {
+    let show_tags = app.show_tags;
+    let (temp_convert_enabled, zwj_enabled) = (app.settings.temp_convert_enabled, app.settings.zwj_enabled);
+    let timestamps = app.timestamp_mode(app.current_world_index);
+    let world = app.current_world_mut();
+    let view = crate::copy_mode::CopyView { lines: &world.output_lines, show_tags, temp_convert_enabled, zwj_enabled, timestamps };
+    let Some(line) = view.nearest_line(world.scroll_offset) else { return };
+    world.search_active = true;
+    app.copy_mode.enter(line);
+    app.needs_output_redraw = true;
+}
+
+/// Leave copy mode, returning the view to the bottom of the output
+pub(crate) fn end_copy_mode(app: &mut App) {
+    app.copy_mode.exit();
+    let w = app.current_world_mut();
+    w.search_active = false;
+    w.scroll_to_bottom();
+    app.needs_output_redraw = true;
+}
+
+/// Key handling for copy mode, shared by the local and remote consoles. Returns true if
+/// the key was consumed (every key is, while copy mode is active).
+pub(crate) fn handle_copy_mode_key(app: &mut App, key: &KeyEvent) -> bool {
+    if !app.copy_mode.active {
+        return false;
+    }
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let page = (app.output_height as usize).max(2) - 1;
+    let show_tags = app.show_tags;
+    let (temp_convert_enabled, zwj_enabled) = (app.settings.temp_convert_enabled, app.settings.zwj_enabled);
+    let world_idx = app.current_world_index;
+    let timestamps = app.timestamp_mode(world_idx);
+    let view = crate::copy_mode::CopyView {
+        lines: &app.worlds[world_idx].output_lines, show_tags, temp_convert_enabled, zwj_enabled, timestamps,
+    };
+    // Lines may have been trimmed from the scrollback since the last key
+    let Some(mut line) = view.nearest_line(app.copy_mode.line) else {
+        end_copy_mode(app);
+        return true;
+    };
+    let mut col = app.copy_mode.col;
+    let step = |from: usize, n: usize, down: bool| {
+        let mut at = from;
+        for _ in 0..n {
+            match if down { view.next_line(at) } else { view.prev_line(at) } {
+                Some(next) => at = next,
+                None => break,
+            }
+        }
+        at
+    };
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            end_copy_mode(app);
+            return true;
+        }
+        KeyCode::Enter | KeyCode::Char('y') => {
+            let (start, end) = app.copy_mode.selection().unwrap_or(((line, 0), (line, usize::MAX - 1)));
+            let text = view.selected_text(start, end);
+            let command = app.settings.clipboard_command.clone();
+            end_copy_mode(app);
+            match crate::copy_mode::copy_to_clipboard(&text, &command) {
+                Ok(()) => app.add_output(&format!("Copied {} characters to the clipboard.", text.chars().count())),
+                Err(e) => app.add_output(&format!("Copy failed: {}", e)),
+            }
+            return true;
+        }
+        KeyCode::Char(' ') | KeyCode::Char('v') => {
+            app.copy_mode.line = line;
+            app.copy_mode.col = col;
+            app.copy_mode.toggle_selection();
+            app.needs_output_redraw = true;
+            return true;
+        }
+        KeyCode::Up | KeyCode::Char('k') => line = step(line, 1, false),
+        KeyCode::Down | KeyCode::Char('j') => line = step(line, 1, true),
+        KeyCode::Char('u') if ctrl => line = step(line, page / 2, false),
+        KeyCode::Char('d') if ctrl => line = step(line, page / 2, true),
+        KeyCode::PageUp | KeyCode::Char('b') if ctrl || key.code == KeyCode::PageUp => line = step(line, page, false),
+        KeyCode::PageDown | KeyCode::Char('f') if ctrl || key.code == KeyCode::PageDown => line = step(line, page, true),
+        KeyCode::Left | KeyCode::Char('h') => col = col.min(view.line_len(line)).saturating_sub(1),
+        KeyCode::Right | KeyCode::Char('l') => col += 1,
+        KeyCode::Home | KeyCode::Char('0') | KeyCode::Char('^') => col = 0,
+        KeyCode::End | KeyCode::Char('$') => col = usize::MAX,
+        KeyCode::Char('w') => col = crate::copy_mode::next_word(&view.text(line).unwrap_or_default(), col),
+        KeyCode::Char('b') => col = crate::copy_mode::prev_word(&view.text(line).unwrap_or_default(), col),
+        KeyCode::Char('g') => line = view.nearest_line(0).unwrap_or(line),
+        KeyCode::Char('G') => line = view.nearest_line(usize::MAX).unwrap_or(line),
+        _ => {}
+    }
+    app.copy_mode.line = line;
+    app.copy_mode.col = col.min(view.line_len(line).saturating_sub(1));
+
+    // Keep the cursor on screen: the view's bottom line is scroll_offset, so scroll down
+    // to a cursor below it, or up far enough that the cursor is the top line shown
+    let width = (app.output_width as usize).max(1);
+    let wrapspace = app.settings.wrapspace as usize;
+    let height = app.output_height as usize;
+    let scroll_offset = app.worlds[world_idx].scroll_offset;
+    let new_offset = if line >= scroll_offset {
+        Some(line)
+    } else {
+        let rows = |from: usize, to: usize| -> usize {
+            (from..=to).filter_map(|i| view.text(i)).map(|t| crate::util::visual_line_count(&t, width, wrapspace)).sum()
+        };
+        if rows(line, scroll_offset) > height {
+            let mut bottom = line;
+            while let Some(next) = view.next_line(bottom) {
+                if rows(line, next) > height {
+                    break;
+                }
+                bottom = next;
+            }
+            Some(bottom)
+        } else {
+            None
+        }
+    };
+    if let Some(offset) = new_offset {
+        let w = &mut app.worlds[world_idx];
+        w.scroll_offset = offset;
+        w.visual_line_offset = 0;
+    }
+    app.needs_output_redraw = true;
+    true
+}
+
+/// Key handling for the output search, shared by the local and remote consoles.
+/// Returns true if the key was consumed.
+///
+/// While the prompt is open, keys edit the query and Up/Down step through matches.
+/// After Enter in a scrollback search (Ctrl+F), the prompt closes but matches stay
+/// highlighted: n/N step to the next older/newer match and `/` edits the query again
+/// (both only while the input line is empty), Esc jumps back to the bottom, and any
+/// other key ends the search in place and is handled normally.
+pub(crate) fn handle_search_key(app: &mut App, key: &KeyEvent) -> bool {
+    if app.search_popup.navigating {
+        let plain = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let input_empty = app.input.buffer.is_empty();
+        let stepped = match key.code {
+            KeyCode::Char('n') if plain && input_empty => app.search_popup.advance(),
+            KeyCode::Char('N') if plain && input_empty => app.search_popup.retreat(),
+            KeyCode::Char('/') if plain && input_empty => {
+                app.search_popup.edit_query();
+                app.needs_output_redraw = true;
+                return true;
+            }
+            KeyCode::Esc => {
+                end_search(app, true);
+                return true;
+            }
+            _ => {
+                end_search(app, false);
+                return false;
+            }
+        };
+        if let Some(line_idx) = stepped {
+            app.current_world_mut().scroll_offset = line_idx;
+        }
+        app.needs_output_redraw = true;
+        return true;
+    }
+
+    if !app.search_popup.visible {
+        return false;
+    }
+    match key.code {
+        KeyCode::Esc | KeyCode::F(5) => {
+            end_search(app, true);
+        }
+        KeyCode::Enter if app.search_popup.in_place => {
+            // Scrollback search: keep the current match and hand over to n/N
+            let output_lines = app.current_world().output_lines.clone();
+            let show_tags = app.show_tags;
+            app.search_popup.update_search(&output_lines, show_tags);
+            if app.search_popup.search_text.is_empty() {
+                end_search(app, true);
+            } else {
+                app.search_popup.start_navigating();
+            }
+            app.needs_output_redraw = true;
+        }
+        KeyCode::Enter => {
+            let output_lines = app.current_world().output_lines.clone();
+            let show_tags = app.show_tags;
+            // Re-update in case lines changed
+            app.search_popup.update_search(&output_lines, show_tags);
+            if let Some(line_idx) = app.search_popup.advance() {
+                app.current_world_mut().scroll_offset = line_idx;
+                app.needs_output_redraw = true;
+            }
+        }
+        KeyCode::Backspace if app.search_popup.cursor > 0 => {
+            app.search_popup.cursor -= 1;
+            app.search_popup.search_text.remove(app.search_popup.cursor);
+            let output_lines = app.current_world().output_lines.clone();
+            let show_tags = app.show_tags;
+            app.search_popup.update_search(&output_lines, show_tags);
+            if let Some(line_idx) = app.search_popup.current_match_line() {
+                app.current_world_mut().scroll_offset = line_idx;
+            }
+            app.needs_output_redraw = true;
+        }
+        KeyCode::Delete if app.search_popup.cursor < app.search_popup.search_text.len() => {
+            app.search_popup.search_text.remove(app.search_popup.cursor);
+            let output_lines = app.current_world().output_lines.clone();
+            let show_tags = app.show_tags;
+            app.search_popup.update_search(&output_lines, show_tags);
+            if let Some(line_idx) = app.search_popup.current_match_line() {
+                app.current_world_mut().scroll_offset = line_idx;
+            }
+            app.needs_output_redraw = true;
+        }
+        KeyCode::Up => {
+            if let Some(line_idx) = app.search_popup.advance() {
+                app.current_world_mut().scroll_offset = line_idx;
+                app.needs_output_redraw = true;
+            }
+        }
+        KeyCode::Down => {
+            if let Some(line_idx) = app.search_popup.retreat() {
+                app.current_world_mut().scroll_offset = line_idx;
+                app.needs_output_redraw = true;
+            }
+        }
+        KeyCode::Left if app.search_popup.cursor > 0 => {
+            app.search_popup.cursor -= 1;
+        }
+        KeyCode::Right if app.search_popup.cursor < app.search_popup.search_text.len() => {
+            app.search_popup.cursor += 1;
+        }
+        KeyCode::Home => { app.search_popup.cursor = 0; }
+        KeyCode::End => { app.search_popup.cursor = app.search_popup.search_text.len(); }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.search_popup.search_text.insert(app.search_popup.cursor, c);
+            app.search_popup.cursor += 1;
+            let output_lines = app.current_world().output_lines.clone();
+            let show_tags = app.show_tags;
+            app.search_popup.update_search(&output_lines, show_tags);
+            if let Some(line_idx) = app.search_popup.current_match_line() {
+                app.current_world_mut().scroll_offset = line_idx;
+            }
+            app.needs_output_redraw = true;
+        }
+        _ => {}
+    }
+    true
+}
+
 /// Convert our canonical key names to TF's parse_key_name format for /bind lookup.
 pub(crate) fn canonical_to_tf_key_name(name: &str) -> String {
     // Our format -> TF format:
@@ -1392,6 +1765,13 @@ pub(crate) fn canonical_to_tf_key_name(name: &str) -> String {
 
 /// Dispatch a keybinding action ID to the corresponding behavior.
 pub(crate) fn dispatch_action(action: &str, app: &mut App) -> KeyAction {
+    // Alt+N: jump straight to world N
+    if let Some(idx) = crate::keybindings::world_number_action(action) {
+        if idx < app.worlds.len() {
+            app.switch_world(idx);
+        }
+        return KeyAction::SwitchedWorld(app.current_world_index);
+    }
     match action {
         // Cursor Movement
         "cursor_left" => {
@@ -1526,6 +1906,10 @@ pub(crate) fn dispatch_action(action: &str, app: &mut App) -> KeyAction {
             app.input.history_search_forward();
             KeyAction::None
         }
+        "history_search_reverse" => {
+            app.input.reverse_search();
+            KeyAction::None
+        }
 
         // Scrollback
         "scroll_page_up" => {
@@ -1739,6 +2123,16 @@ pub(crate) fn dispatch_action(action: &str, app: &mut App) -> KeyAction {
             app.open_recent_worlds_popup();
             KeyAction::None
         }
+        "world_switcher" => {
+            app.open_world_selector_new();
+            KeyAction::None
+        }
+        "split_focus" => {
+            if let Some(idx) = app.split.next_focus().and_then(|name| app.find_world(name)) {
+                app.switch_world(idx);
+            }
+            KeyAction::SwitchedWorld(app.current_world_index)
+        }
         "world_previous" => {
             app.prev_world();
             KeyAction::SwitchedWorld(app.current_world_index)
@@ -1759,6 +2153,11 @@ pub(crate) fn dispatch_action(action: &str, app: &mut App) -> KeyAction {
             // Double-press Ctrl+C logic
             if let Some(last_time) = app.last_ctrl_c {
                 if last_time.elapsed() < Duration::from_secs(15) {
+                    if app.quit_needs_confirm() {
+                        app.last_ctrl_c = None;
+                        app.open_quit_confirm();
+                        return KeyAction::None;
+                    }
                     return KeyAction::Quit;
                 }
             }
@@ -1775,6 +2174,22 @@ pub(crate) fn dispatch_action(action: &str, app: &mut App) -> KeyAction {
             app.handle_spell_check();
             KeyAction::None
         }
+        "spell_add_word" => {
+            app.spell_add_word();
+            KeyAction::None
+        }
+        "spell_ignore" => {
+            app.spell_ignore_word();
+            KeyAction::None
+        }
+        "spell_undo_autocorrect" => {
+            app.undo_autocorrect();
+            KeyAction::None
+        }
+        "spell_menu" => {
+            app.open_spell_popup();
+            KeyAction::None
+        }
 
         // Clay Extensions
         "toggle_tags" => {
@@ -1784,8 +2199,7 @@ pub(crate) fn dispatch_action(action: &str, app: &mut App) -> KeyAction {
         }
         "filter_popup" => {
             app.filter_popup.open();
-            let output_lines = app.current_world().output_lines.clone();
-            app.filter_popup.update_filter(&output_lines);
+            app.refresh_filter();
             app.needs_output_redraw = true;
             KeyAction::None
         }
@@ -1798,10 +2212,24 @@ pub(crate) fn dispatch_action(action: &str, app: &mut App) -> KeyAction {
             app.needs_output_redraw = true;
             KeyAction::None
         }
+        "scrollback_search" => {
+            app.search_popup.open_in_place();
+            app.current_world_mut().search_active = true;
+            app.needs_output_redraw = true;
+            KeyAction::None
+        }
+        "copy_mode" => {
+            start_copy_mode(app);
+            KeyAction::None
+        }
         "toggle_action_highlight" => {
             app.highlight_actions = !app.highlight_actions;
             KeyAction::Redraw
         }
+        "toggle_timestamps" => {
+            app.timestamps_toggled = !app.timestamps_toggled;
+            KeyAction::Redraw
+        }
         "toggle_gmcp_media" => {
             let idx = app.current_world_index;
             app.worlds[idx].gmcp_user_enabled = !app.worlds[idx].gmcp_user_enabled;
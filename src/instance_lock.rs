@@ -0,0 +1,155 @@
+//! Coordination between Clay instances sharing one data directory.
+//!
+//! Two instances on the same config directory would race on settings.toml and the hot
+//! reload state file, each silently overwriting the other's changes. Master
+//! instances (console, GUI, -D, --multiuser) therefore write `clay.lock` with their
+//! pid at startup. A second instance that finds a lock held by a live process
+//! offers to attach to it as a remote console (through the running instance's web
+//! server on localhost) instead of starting another master; headless modes refuse
+//! to start. `--force` skips the check.
+//!
+//! Hot reload and crash restart take the lock over from the previous process, as
+//! does the background process a `/detach` hands the session to (`clay --attach`
+//! then finds it here). A lock left by a process that has died is stale and
+//! simply replaced.
+
+use std::path::PathBuf;
+
+/// The instance recorded in `clay.lock`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockInfo {
+    pub pid: u32,
+    /// How the instance was started ("console", "gui", "daemon", "multiuser")
+    pub mode: String,
+}
+
+impl LockInfo {
+    pub fn to_dat(&self) -> String {
+        format!("pid={}\nmode={}\n", self.pid, self.mode)
+    }
+
+    pub fn from_dat(s: &str) -> Option<Self> {
+        let mut pid = None;
+        let mut mode = String::new();
+        for line in s.lines() {
+            match line.split_once('=') {
+                Some(("pid", v)) => pid = v.trim().parse().ok(),
+                Some(("mode", v)) => mode = v.trim().to_string(),
+                _ => {}
+            }
+        }
+        Some(Self { pid: pid?, mode })
+    }
+}
+
+pub fn lock_path() -> PathBuf {
+    crate::clay_state_path("clay.lock")
+}
+
+/// Another live instance holding the lock, if any
+pub fn running_instance() -> Option<LockInfo> {
+    let info = LockInfo::from_dat(&std::fs::read_to_string(lock_path()).ok()?)?;
+    (info.pid != std::process::id() && crate::platform::is_process_alive(info.pid)).then_some(info)
+}
+
+/// Held for the life of a master instance; removes `clay.lock` on drop if it is still ours
+pub struct InstanceLock;
+
+impl InstanceLock {
+    /// Write the lock for this process (replacing any stale or taken-over lock)
+    pub fn acquire(mode: &str) -> Self {
+        let info = LockInfo { pid: std::process::id(), mode: mode.to_string() };
+        let _ = std::fs::write(lock_path(), info.to_dat());
+        InstanceLock
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // After a Windows hot reload the new process has already rewritten the lock
+        let ours = std::fs::read_to_string(lock_path()).ok()
+            .and_then(|s| LockInfo::from_dat(&s))
+            .is_some_and(|info| info.pid == std::process::id());
+        if ours {
+            let _ = std::fs::remove_file(lock_path());
+        }
+    }
+}
+
+/// Port a remote console can attach on: the web server port from the shared
+/// settings, if the web server is enabled. `setting` looks up a `[global]` value.
+pub fn attach_port(setting: impl Fn(&str) -> Option<String>) -> Option<u16> {
+    if setting("http_enabled")? != "true" {
+        return None;
+    }
+    setting("http_port")?.parse().ok()
+}
+
+/// `--console=` address of the running instance, if its web server is enabled
+pub fn attach_addr() -> Option<String> {
+    attach_port(crate::persistence::read_global_setting).map(|port| format!("localhost:{}", port))
+}
+
+/// What to do about another instance, asked on the terminal before the TUI starts
+pub enum Choice {
+    Attach(String),
+    StartAnyway,
+    Quit,
+}
+
+/// Describe the running instance and ask whether to attach, start anyway or quit.
+/// Called before raw mode, so plain stdin/stdout are fine here.
+pub fn prompt(info: &LockInfo, attach_addr: Option<&str>) -> Choice {
+    use std::io::{BufRead, IsTerminal, Write};
+    println!("Another Clay instance (pid {}, {}) is using {}.", info.pid, info.mode,
+        crate::clay_config_dir().display());
+    println!("Running both would race on settings.toml and the reload state.");
+    match attach_addr {
+        Some(addr) => println!("  [a] Attach to it (same as --console={})", addr),
+        None => println!("  (Enable its web server to be able to attach with --console.)"),
+    }
+    println!("  [s] Start anyway");
+    println!("  [q] Quit");
+    if !std::io::stdin().is_terminal() {
+        return Choice::Quit;
+    }
+    let default = if attach_addr.is_some() { "a" } else { "q" };
+    print!("Choice [{}]: ", default);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    let _ = std::io::stdin().lock().read_line(&mut answer);
+    let answer = answer.trim().to_lowercase();
+    match if answer.is_empty() { default } else { answer.as_str() } {
+        "a" if attach_addr.is_some() => Choice::Attach(attach_addr.unwrap_or_default().to_string()),
+        "s" => Choice::StartAnyway,
+        _ => Choice::Quit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_info_round_trip() {
+        let info = LockInfo { pid: 4242, mode: "daemon".to_string() };
+        assert_eq!(LockInfo::from_dat(&info.to_dat()), Some(info));
+        assert_eq!(LockInfo::from_dat("mode=console\n"), None);
+        assert_eq!(LockInfo::from_dat("garbage"), None);
+    }
+
+    #[test]
+    fn test_attach_port_from_settings() {
+        let port = |text: &str| {
+            let global = crate::persistence::global_section(text);
+            attach_port(|key| global.get(key).cloned())
+        };
+        assert_eq!(port("[global]\nhttp_enabled=true\nhttp_port=9100\n[world:x]\nhttp_port=1\n"), Some(9100));
+        assert_eq!(port("[global]\nhttp_enabled=false\nhttp_port=9100\n"), None);
+        assert_eq!(port(""), None);
+        // settings.toml, as saved since the switch from settings.dat
+        let toml = "version = 1\n\n[global]\nhttp_enabled = true\nhttp_port = 9100\n\n[[world]]\nname = \"x\"\nhttp_port = 1\n";
+        assert_eq!(port(toml), Some(9100));
+        assert_eq!(port("[global]\nhttp_enabled = false\nhttp_port = 9100\n"), None);
+    }
+}
@@ -53,6 +53,7 @@ pub const ACTIONS: &[ActionInfo] = &[
     ActionInfo { id: "history_next", name: "History Next", category: "History" },
     ActionInfo { id: "history_search_backward", name: "History Search Back", category: "History" },
     ActionInfo { id: "history_search_forward", name: "History Search Forward", category: "History" },
+    ActionInfo { id: "history_search_reverse", name: "Reverse History Search", category: "History" },
 
     // Scrollback
     ActionInfo { id: "scroll_page_up", name: "Page Up", category: "Scrollback" },
@@ -71,6 +72,17 @@ pub const ACTIONS: &[ActionInfo] = &[
     ActionInfo { id: "world_previous", name: "Switch to Previous", category: "World" },
     ActionInfo { id: "world_forward", name: "Switch Forward", category: "World" },
     ActionInfo { id: "recent_worlds", name: "Recent Worlds", category: "World" },
+    ActionInfo { id: "world_switcher", name: "Quick Switch World", category: "World" },
+    ActionInfo { id: "split_focus", name: "Next Split Pane", category: "World" },
+    ActionInfo { id: "world_1", name: "Go to World 1", category: "World" },
+    ActionInfo { id: "world_2", name: "Go to World 2", category: "World" },
+    ActionInfo { id: "world_3", name: "Go to World 3", category: "World" },
+    ActionInfo { id: "world_4", name: "Go to World 4", category: "World" },
+    ActionInfo { id: "world_5", name: "Go to World 5", category: "World" },
+    ActionInfo { id: "world_6", name: "Go to World 6", category: "World" },
+    ActionInfo { id: "world_7", name: "Go to World 7", category: "World" },
+    ActionInfo { id: "world_8", name: "Go to World 8", category: "World" },
+    ActionInfo { id: "world_9", name: "Go to World 9", category: "World" },
 
     // System
     ActionInfo { id: "help", name: "Help", category: "System" },
@@ -80,12 +92,19 @@ pub const ACTIONS: &[ActionInfo] = &[
     ActionInfo { id: "suspend", name: "Suspend", category: "System" },
     ActionInfo { id: "bell", name: "Bell", category: "System" },
     ActionInfo { id: "spell_check", name: "Spell Check", category: "System" },
+    ActionInfo { id: "spell_add_word", name: "Add Word to Dictionary", category: "System" },
+    ActionInfo { id: "spell_ignore", name: "Ignore Word This Session", category: "System" },
+    ActionInfo { id: "spell_undo_autocorrect", name: "Undo Autocorrect", category: "System" },
+    ActionInfo { id: "spell_menu", name: "Spell Suggestions Menu", category: "System" },
 
     // Clay Extensions
     ActionInfo { id: "toggle_tags", name: "Toggle Tags (F2)", category: "Clay" },
     ActionInfo { id: "filter_popup", name: "Find (F4)", category: "Clay" },
     ActionInfo { id: "search_popup", name: "Search History (F5)", category: "Clay" },
+    ActionInfo { id: "scrollback_search", name: "Search Scrollback (^F)", category: "Clay" },
+    ActionInfo { id: "copy_mode", name: "Copy Mode (F7)", category: "Clay" },
     ActionInfo { id: "toggle_action_highlight", name: "Toggle Highlights (F8)", category: "Clay" },
+    ActionInfo { id: "toggle_timestamps", name: "Toggle Timestamps", category: "Clay" },
     ActionInfo { id: "toggle_gmcp_media", name: "Toggle GMCP Media (F9)", category: "Clay" },
     ActionInfo { id: "input_grow", name: "Grow Input Area", category: "Clay" },
     ActionInfo { id: "input_shrink", name: "Shrink Input Area", category: "Clay" },
@@ -141,6 +160,7 @@ impl KeyBindings {
         b.insert("Down".into(), "cursor_down".into());
         b.insert("Esc-p".into(), "history_search_backward".into());
         b.insert("Esc-n".into(), "history_search_forward".into());
+        b.insert("^R".into(), "history_search_reverse".into());
 
         // Scrollback
         b.insert("PageUp".into(), "scroll_page_up".into());
@@ -157,19 +177,31 @@ impl KeyBindings {
         b.insert("Shift-Up".into(), "world_all_next".into());
         b.insert("Shift-Down".into(), "world_all_prev".into());
         b.insert("Esc-w".into(), "world_activity".into());
+        b.insert("Esc-k".into(), "world_switcher".into());
+        b.insert("Esc-o".into(), "split_focus".into());
+        b.insert("Esc-t".into(), "toggle_timestamps".into());
+        // Alt+N jumps to world N (the numbers shown on the tab bar)
+        for n in 1..=9 {
+            b.insert(format!("Esc-{}", n), format!("world_{}", n));
+        }
 
         // System
         b.insert("F1".into(), "help".into());
         b.insert("^L".into(), "redraw".into());
-        b.insert("^R".into(), "reload".into());
+        b.insert("Esc-R".into(), "reload".into());
         b.insert("^G".into(), "bell".into());
         b.insert("^Z".into(), "suspend".into());
         b.insert("^Q".into(), "spell_check".into());
+        b.insert("Esc-q".into(), "spell_add_word".into());
+        b.insert("Esc-i".into(), "spell_ignore".into());
+        b.insert("Esc-r".into(), "spell_undo_autocorrect".into());
+        b.insert("Esc-s".into(), "spell_menu".into());
 
         // Clay Extensions
         b.insert("F2".into(), "toggle_tags".into());
         b.insert("F4".into(), "filter_popup".into());
         b.insert("F5".into(), "search_popup".into());
+        b.insert("F7".into(), "copy_mode".into());
         b.insert("F8".into(), "toggle_action_highlight".into());
         b.insert("F9".into(), "toggle_gmcp_media".into());
         b.insert("Alt-Up".into(), "input_grow".into());
@@ -381,12 +413,91 @@ impl KeyBindings {
     }
 }
 
+/// 0-based world index for the `world_1`..`world_9` actions
+pub fn world_number_action(action: &str) -> Option<usize> {
+    let n: usize = action.strip_prefix("world_")?.parse().ok()?;
+    (1..=9).contains(&n).then(|| n - 1)
+}
+
 impl Default for KeyBindings {
     fn default() -> Self {
         Self::tf_defaults()
     }
 }
 
+/// Name a function key press for /macro lookup: "F5", or "Shift-F5" when shifted.
+///
+/// Unlike `key_event_to_name` (which ignores Shift on F-keys so Shift+F1 still
+/// triggers the F1 binding), macros can be bound to shifted variants separately.
+pub fn fkey_macro_name(n: u8, modifiers: crossterm::event::KeyModifiers) -> String {
+    if modifiers.contains(crossterm::event::KeyModifiers::SHIFT) {
+        format!("Shift-F{}", n)
+    } else {
+        format!("F{}", n)
+    }
+}
+
+/// Normalize a user-typed /macro key ("f5", "shift-f5", "S-F5") to the
+/// `fkey_macro_name` form. Only F1-F12 are accepted.
+pub fn normalize_fkey_name(name: &str) -> Option<String> {
+    let lower = name.trim().to_lowercase();
+    let (shift, rest) = match lower.strip_prefix("shift-").or_else(|| lower.strip_prefix("s-")) {
+        Some(rest) => (true, rest),
+        None => (false, lower.as_str()),
+    };
+    let n: u8 = rest.strip_prefix('f')?.parse().ok()?;
+    if !(1..=12).contains(&n) {
+        return None;
+    }
+    Some(if shift { format!("Shift-F{}", n) } else { format!("F{}", n) })
+}
+
+/// Movement command for a keypad key when numpad speedwalk is enabled for a world:
+/// 1-9 walk the eight compass directions around 5, which sends "look".
+///
+/// Only keys the terminal flagged as keypad keys qualify, so the digit row still types
+/// digits. That flag needs the kitty keyboard protocol (see App::sync_keypad_mode);
+/// with Num Lock off the same keys arrive as Home/Up/PageUp etc. and map identically.
+pub fn numpad_walk_command(key: &crossterm::event::KeyEvent) -> Option<&'static str> {
+    use crossterm::event::{KeyCode, KeyEventState, KeyModifiers};
+
+    if !key.state.contains(KeyEventState::KEYPAD)
+        || key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    {
+        return None;
+    }
+    let digit = match key.code {
+        KeyCode::Char(c @ '1'..='9') => c,
+        KeyCode::End => '1',
+        KeyCode::Down => '2',
+        KeyCode::PageDown => '3',
+        KeyCode::Left => '4',
+        KeyCode::KeypadBegin => '5',
+        KeyCode::Right => '6',
+        KeyCode::Home => '7',
+        KeyCode::Up => '8',
+        KeyCode::PageUp => '9',
+        _ => return None,
+    };
+    numpad_digit_command(digit)
+}
+
+/// Movement command for keypad digit `digit` (shared with the web client's mapping)
+pub fn numpad_digit_command(digit: char) -> Option<&'static str> {
+    Some(match digit {
+        '1' => "sw",
+        '2' => "s",
+        '3' => "se",
+        '4' => "w",
+        '5' => "look",
+        '6' => "e",
+        '7' => "nw",
+        '8' => "n",
+        '9' => "ne",
+        _ => return None,
+    })
+}
+
 /// Convert a crossterm KeyEvent to canonical key name.
 ///
 /// Returns None if the key event doesn't map to a bindable name
@@ -532,6 +643,11 @@ mod tests {
         assert_eq!(kb.get_action("Esc-f"), Some("cursor_word_right"));
         assert_eq!(kb.get_action("^Y"), Some("yank"));
         assert_eq!(kb.get_action("F1"), Some("help"));
+        assert_eq!(kb.get_action("Esc-3"), Some("world_3"));
+        assert_eq!(kb.get_action("Esc-k"), Some("world_switcher"));
+        assert_eq!(world_number_action("world_3"), Some(2));
+        assert_eq!(world_number_action("world_10"), None);
+        assert_eq!(world_number_action("world_next"), None);
     }
 
     #[test]
@@ -612,4 +728,32 @@ mod tests {
         assert_eq!(key_event_to_name(KeyCode::Backspace, KeyModifiers::NONE), Some("Backspace".into()));
         assert_eq!(key_event_to_name(KeyCode::Tab, KeyModifiers::NONE), Some("Tab".into()));
     }
+
+    #[test]
+    fn test_fkey_macro_names() {
+        use crossterm::event::KeyModifiers;
+        assert_eq!(fkey_macro_name(5, KeyModifiers::NONE), "F5");
+        assert_eq!(fkey_macro_name(5, KeyModifiers::SHIFT), "Shift-F5");
+        assert_eq!(normalize_fkey_name("f5").as_deref(), Some("F5"));
+        assert_eq!(normalize_fkey_name("Shift-F12").as_deref(), Some("Shift-F12"));
+        assert_eq!(normalize_fkey_name("s-f1").as_deref(), Some("Shift-F1"));
+        assert_eq!(normalize_fkey_name("f13"), None);
+        assert_eq!(normalize_fkey_name("f0"), None);
+        assert_eq!(normalize_fkey_name("x5"), None);
+    }
+
+    #[test]
+    fn test_numpad_walk_command() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+        let key = |code, state| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state };
+        assert_eq!(numpad_walk_command(&key(KeyCode::Char('8'), KeyEventState::KEYPAD)), Some("n"));
+        assert_eq!(numpad_walk_command(&key(KeyCode::Char('5'), KeyEventState::KEYPAD)), Some("look"));
+        // Num Lock off: navigation keycodes from the keypad
+        assert_eq!(numpad_walk_command(&key(KeyCode::End, KeyEventState::KEYPAD)), Some("sw"));
+        assert_eq!(numpad_walk_command(&key(KeyCode::KeypadBegin, KeyEventState::KEYPAD)), Some("look"));
+        // Digit row and regular arrows are untouched
+        assert_eq!(numpad_walk_command(&key(KeyCode::Char('8'), KeyEventState::NONE)), None);
+        assert_eq!(numpad_walk_command(&key(KeyCode::Up, KeyEventState::NONE)), None);
+        assert_eq!(numpad_walk_command(&key(KeyCode::Char('0'), KeyEventState::KEYPAD)), None);
+    }
 }
@@ -0,0 +1,144 @@
+//! World and WebSocket passwords in the OS keyring (requires the `os-keyring` feature).
+//!
+//! With the feature, saving settings puts each world's password and the WebSocket
+//! password in the Secret Service (Linux), Keychain (macOS) or Credential Manager
+//! (Windows), and the settings file holds `KEYRING:<account>` where it would otherwise
+//! hold the `ENC:` value. When no keyring is reachable (no Secret Service daemon, a
+//! headless session, a locked keychain) the first failure switches this run back to
+//! the settings file's own encryption. `KEYRING:` values load through
+//! `persistence::decrypt_password` like any other stored secret.
+//!
+//! Accounts are `world/<name>/password` and `websocket_password` under the service
+//! `clay`, prefixed with `<profile>:` for a `--profile`. What this run has stored or
+//! read is remembered, so a save only talks to the keyring when a password changed,
+//! and entries for deleted worlds and cleared passwords are removed. Keyring calls run
+//! on their own thread: they can block (D-Bus) and mustn't run inside the tokio runtime.
+
+/// Prefix of a settings value that is held in the keyring
+pub const PREFIX: &str = "KEYRING:";
+
+pub fn world_account(world: &str) -> String {
+    format!("world/{}/password", world)
+}
+
+pub const WEBSOCKET_ACCOUNT: &str = "websocket_password";
+
+#[cfg(all(feature = "os-keyring", not(test)))]
+mod store {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Mutex, OnceLock};
+
+    const SERVICE: &str = "clay";
+
+    /// Set after the first keyring failure; the rest of the run uses the settings file
+    static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+    /// Account -> password, for what this run has stored in or read from the keyring
+    fn known() -> &'static Mutex<HashMap<String, String>> {
+        static KNOWN: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+        KNOWN.get_or_init(Default::default)
+    }
+
+    fn on_thread<T: Send + 'static>(account: &str, op: impl FnOnce(keyring::Entry) -> keyring::Result<T> + Send + 'static) -> keyring::Result<T> {
+        let account = account.to_string();
+        std::thread::spawn(move || op(keyring::Entry::new(SERVICE, &account)?))
+            .join()
+            .unwrap_or_else(|_| Err(keyring::Error::PlatformFailure("keyring thread panicked".into())))
+    }
+
+    fn give_up(what: &str, e: &keyring::Error) {
+        if !UNAVAILABLE.swap(true, Ordering::Relaxed) {
+            crate::debug_log(true, &format!("keyring: {} failed ({}); storing passwords in the settings file", what, e));
+        }
+    }
+
+    fn scoped(account: &str) -> String {
+        match crate::get_config_profile() {
+            Some(profile) => format!("{}:{}", profile, account),
+            None => account.to_string(),
+        }
+    }
+
+    pub fn store(account: &str, password: &str) -> Option<String> {
+        if UNAVAILABLE.load(Ordering::Relaxed) {
+            return None;
+        }
+        let account = scoped(account);
+        if known().lock().unwrap().get(&account).is_some_and(|p| p == password) {
+            return Some(account);
+        }
+        let value = password.to_string();
+        match on_thread(&account, move |entry| entry.set_password(&value)) {
+            Ok(()) => {
+                known().lock().unwrap().insert(account.clone(), password.to_string());
+                Some(account)
+            }
+            Err(e) => {
+                give_up("storing a password", &e);
+                None
+            }
+        }
+    }
+
+    pub fn load(account: &str) -> Option<String> {
+        if let Some(password) = known().lock().unwrap().get(account) {
+            return Some(password.clone());
+        }
+        match on_thread(account, |entry| entry.get_password()) {
+            Ok(password) => {
+                known().lock().unwrap().insert(account.to_string(), password.clone());
+                Some(password)
+            }
+            Err(keyring::Error::NoEntry) => None,
+            Err(e) => {
+                give_up("reading a password", &e);
+                None
+            }
+        }
+    }
+
+    pub fn retain(accounts: &[String]) {
+        let stale: Vec<String> = known().lock().unwrap().keys().filter(|a| !accounts.contains(a)).cloned().collect();
+        for account in stale {
+            known().lock().unwrap().remove(&account);
+            let _ = on_thread(&account, |entry| entry.delete_credential());
+        }
+    }
+}
+
+/// Put `password` in the keyring under `account` and return the value the settings file
+/// stores for it instead (`KEYRING:...`); None when there's no keyring to put it in.
+/// Tests never reach the keyring.
+#[cfg(all(feature = "os-keyring", not(test)))]
+pub fn store(account: &str, password: &str) -> Option<String> {
+    store::store(account, password).map(|account| format!("{}{}", PREFIX, account))
+}
+
+#[cfg(any(not(feature = "os-keyring"), test))]
+pub fn store(_account: &str, _password: &str) -> Option<String> {
+    None
+}
+
+/// The password a `KEYRING:...` settings value refers to. Empty (and logged) when it
+/// can't be read: no keyring, the entry was removed, or a build without the feature.
+pub fn load(stored: &str) -> String {
+    let account = stored.strip_prefix(PREFIX).unwrap_or(stored);
+    #[cfg(all(feature = "os-keyring", not(test)))]
+    if let Some(password) = store::load(account) {
+        return password;
+    }
+    crate::debug_log(true, &format!("keyring: no password for {} (set it again to store it)", account));
+    String::new()
+}
+
+/// After a save: remove keyring entries this run knows of that `accounts` (everything
+/// the save stored, as returned by `store`) no longer includes
+#[cfg(all(feature = "os-keyring", not(test)))]
+pub fn retain(stored: &[String]) {
+    let accounts: Vec<String> = stored.iter().filter_map(|s| s.strip_prefix(PREFIX)).map(str::to_string).collect();
+    store::retain(&accounts);
+}
+
+#[cfg(any(not(feature = "os-keyring"), test))]
+pub fn retain(_stored: &[String]) {}
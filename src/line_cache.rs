@@ -0,0 +1,170 @@
+//! Output lines as they were last drawn, so redraws skip re-parsing them.
+//!
+//! Turning an output line into screen rows (square emoji colors, timestamps, links,
+//! wrapping, and for the ratatui views parsing the ANSI codes into styled cells) is
+//! most of a frame's work. Each world keeps the rows of the lines on its last frame,
+//! found by a fingerprint of the line (text, timestamp and flags), so redrawing an
+//! unchanged screen, or one scrolled by a few lines, only builds the new lines.
+//!
+//! Everything else the rows depend on is the frame's `RenderKey` (width, F2 tags,
+//! timestamps, wrap space, theme, palette, ...); a different key drops the whole cache. Lines
+//! not drawn in a frame are dropped after it, so the cache holds about a screenful.
+//! Lines with search or copy mode highlighting are never cached.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::{theme::Palette, OutputLine, Theme, TimestampMode};
+
+/// Which renderer the rows are for (they differ in links and prefixes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Raw crossterm output of the current world
+    Console,
+    /// ratatui output: behind popups, the editor split and split panes
+    Pane,
+    /// The capture pane below the output
+    Capture,
+}
+
+/// Settings the rows of every line depend on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderKey {
+    pub view: View,
+    pub width: usize,
+    pub show_tags: bool,
+    pub temp_convert: bool,
+    pub zwj: bool,
+    pub timestamps: TimestampMode,
+    pub wrapspace: usize,
+    pub new_line_indicator: bool,
+    pub theme: Theme,
+    pub palette: Option<Palette>,
+}
+
+struct Entry<R> {
+    frame: u64,
+    rows: Vec<R>,
+}
+
+/// Rows of recently drawn lines for one world and renderer
+pub struct LineCache<R> {
+    key: Option<RenderKey>,
+    frame: u64,
+    entries: HashMap<u64, Entry<R>>,
+}
+
+impl<R> Default for LineCache<R> {
+    fn default() -> Self {
+        Self { key: None, frame: 0, entries: HashMap::new() }
+    }
+}
+
+impl<R: Clone> LineCache<R> {
+    /// Start a frame drawn with `key`, dropping everything if the key changed
+    pub fn begin_frame(&mut self, key: RenderKey) {
+        if self.key != Some(key) {
+            self.entries.clear();
+            self.key = Some(key);
+        }
+        self.frame += 1;
+    }
+
+    /// Rows for `line`, from the cache or made by `build`
+    pub fn rows(&mut self, line: &OutputLine, build: impl FnOnce() -> Vec<R>) -> Vec<R> {
+        let frame = self.frame;
+        let entry = self.entries.entry(fingerprint(line)).or_insert_with(|| Entry { frame, rows: build() });
+        entry.frame = frame;
+        entry.rows.clone()
+    }
+
+    /// Finish a frame, dropping lines that weren't drawn in it
+    pub fn end_frame(&mut self) {
+        let frame = self.frame;
+        self.entries.retain(|_, e| e.frame == frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Everything about a line that its rows depend on. Identical lines share rows.
+fn fingerprint(line: &OutputLine) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    line.text.hash(&mut hasher);
+    line.timestamp.hash(&mut hasher);
+    (line.from_server, line.gagged, line.marked_new, line.from_archive).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn line(text: &str) -> OutputLine {
+        OutputLine {
+            text: text.to_string(),
+            timestamp: std::time::UNIX_EPOCH,
+            from_server: true,
+            gagged: false,
+            seq: 0,
+            highlight_color: None,
+            marked_new: false,
+            from_archive: false,
+        }
+    }
+
+    fn key(width: usize) -> RenderKey {
+        RenderKey {
+            view: View::Console,
+            width,
+            show_tags: false,
+            temp_convert: false,
+            zwj: false,
+            timestamps: TimestampMode::Off,
+            wrapspace: 0,
+            new_line_indicator: true,
+            theme: Theme::Dark,
+            palette: None,
+        }
+    }
+
+    #[test]
+    fn test_rows_reused_until_key_or_line_changes() {
+        let builds = Cell::new(0);
+        let build = |text: &str| {
+            builds.set(builds.get() + 1);
+            vec![text.to_string()]
+        };
+        let mut cache: LineCache<String> = LineCache::default();
+        let hello = line("hello");
+
+        cache.begin_frame(key(80));
+        assert_eq!(cache.rows(&hello, || build("hello")), vec!["hello"]);
+        cache.end_frame();
+        cache.begin_frame(key(80));
+        cache.rows(&hello, || build("hello"));
+        cache.end_frame();
+        assert_eq!(builds.get(), 1);
+
+        // Marking the line new changes its rows (the NLI prefix narrows the wrap)
+        let mut marked = hello.clone();
+        marked.marked_new = true;
+        cache.begin_frame(key(80));
+        cache.rows(&marked, || build("hello"));
+        cache.end_frame();
+        assert_eq!((builds.get(), cache.len()), (2, 1));
+
+        // A resize rebuilds everything
+        cache.begin_frame(key(60));
+        assert!(cache.is_empty());
+        cache.rows(&marked, || build("hello"));
+        assert_eq!(builds.get(), 3);
+    }
+}
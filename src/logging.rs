@@ -0,0 +1,513 @@
+//! World log files (Logging in the world editor), written off the event loop.
+//!
+//! Each open log has a tokio writer task fed by an unbounded channel, so adding
+//! output never waits on the disk. The task owns the file: it buffers writes,
+//! flushes every `FLUSH_INTERVAL`, and flushes what is left when the world closes
+//! the log (dropping the `LogWriter` ends the task). Outside a tokio runtime (unit
+//! tests) lines are written straight to a buffered file instead.
+//!
+//! A log is written as received (`raw`, ANSI codes and all, in `world.DATE.log`),
+//! as plain text (`plain`, in `world.DATE.txt`: escape sequences, OSC links and
+//! stray control characters removed, for grep), or as HTML (`html`, in
+//! `world.DATE.html`): colors and attributes become styled `<span>`s under a small
+//! embedded stylesheet, so a log opens in any browser looking as it did on screen.
+//!
+//! Each line starts with the time (`log_timestamps`: off, time or date and time),
+//! and with `log_session_markers` a connect and a disconnect each write a
+//! `=== Session started/ended ... ===` line, so one day's file reads as sessions.
+//!
+//! `/log start [<file>]` and `/log stop` override the Logging setting until the
+//! world disconnects (`LogOverride`); `/log flush` writes out what is buffered.
+
+use std::io::Write;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+/// How often a writer task flushes buffered lines to disk
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `/log start` or `/log stop`, over the world's Logging setting until it disconnects
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LogOverride {
+    /// Log as the Logging setting says
+    #[default]
+    Setting,
+    /// Log to the dated file, or to the user's file (which never rolls over)
+    Started(Option<std::path::PathBuf>),
+    Stopped,
+}
+
+/// How a world's log file is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Raw,
+    Plain,
+    Html,
+}
+
+impl LogFormat {
+    pub const ALL: [LogFormat; 3] = [LogFormat::Raw, LogFormat::Plain, LogFormat::Html];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogFormat::Raw => "raw",
+            LogFormat::Plain => "plain",
+            LogFormat::Html => "html",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Log file extension
+    pub fn extension(&self) -> &'static str {
+        match self {
+            LogFormat::Raw => "log",
+            LogFormat::Plain => "txt",
+            LogFormat::Html => "html",
+        }
+    }
+
+    /// One log line: `stamp` (the time prefix) and then the output line
+    fn format_line(&self, stamp: &str, line: &str) -> String {
+        match self {
+            LogFormat::Raw => format!("{}{}\n", stamp, line),
+            LogFormat::Plain => format!("{}{}\n", stamp, plain_text(line)),
+            LogFormat::Html => format!("<div><span class=\"ts\">{}</span>{}</div>\n", html_escape(stamp), ansi_to_html(line)),
+        }
+    }
+}
+
+/// `line` without escape sequences (CSI, OSC hyperlinks and titles) or control
+/// characters other than tab
+pub fn plain_text(line: &str) -> String {
+    let mut without_osc = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&']') {
+            // OSC ends with BEL or ST (ESC \)
+            while let Some(c) = chars.next() {
+                if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                    break;
+                }
+            }
+            continue;
+        }
+        without_osc.push(c);
+    }
+    crate::strip_ansi_codes(&without_osc)
+        .chars()
+        .filter(|&c| c == '\t' || !c.is_control())
+        .collect()
+}
+
+/// The time prefix on each log line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogTimestamps {
+    Off,
+    /// `[HH:MM:SS] `
+    #[default]
+    Time,
+    /// `[YYYY-MM-DD HH:MM:SS] `
+    DateTime,
+}
+
+impl LogTimestamps {
+    pub const ALL: [LogTimestamps; 3] = [LogTimestamps::Off, LogTimestamps::Time, LogTimestamps::DateTime];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogTimestamps::Off => "off",
+            LogTimestamps::Time => "time",
+            LogTimestamps::DateTime => "datetime",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Prefix for a line logged at `lt`
+    pub fn stamp(&self, lt: &crate::util::LocalTime) -> String {
+        match self {
+            LogTimestamps::Off => String::new(),
+            LogTimestamps::Time => format!("[{:02}:{:02}:{:02}] ", lt.hour, lt.minute, lt.second),
+            LogTimestamps::DateTime => format!("[{}] ", date_time(lt)),
+        }
+    }
+}
+
+fn date_time(lt: &crate::util::LocalTime) -> String {
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", lt.year, lt.month, lt.day, lt.hour, lt.minute, lt.second)
+}
+
+/// The line written when a session starts (a connect) or ends (a disconnect)
+pub fn session_marker(started: bool, lt: &crate::util::LocalTime) -> String {
+    format!("=== Session {} {} ===", if started { "started" } else { "ended" }, date_time(lt))
+}
+
+/// Start of a new HTML log file
+const HTML_HEADER: &str = "<!DOCTYPE html>
+<html><head><meta charset=\"utf-8\"><title>Clay log</title>
+<style>
+body { background: #000; color: #c0c0c0; font-family: monospace; white-space: pre-wrap; }
+.ts { color: #808080; }
+.b { font-weight: bold; } .d { opacity: 0.6; } .i { font-style: italic; }
+.u { text-decoration: underline; } .s { text-decoration: line-through; }
+.f0 { color: #000000; } .f1 { color: #cd0000; } .f2 { color: #00cd00; } .f3 { color: #cdcd00; }
+.f4 { color: #0000ee; } .f5 { color: #cd00cd; } .f6 { color: #00cdcd; } .f7 { color: #e5e5e5; }
+.f8 { color: #7f7f7f; } .f9 { color: #ff0000; } .f10 { color: #00ff00; } .f11 { color: #ffff00; }
+.f12 { color: #5c5cff; } .f13 { color: #ff00ff; } .f14 { color: #00ffff; } .f15 { color: #ffffff; }
+.g0 { background: #000000; } .g1 { background: #cd0000; } .g2 { background: #00cd00; } .g3 { background: #cdcd00; }
+.g4 { background: #0000ee; } .g5 { background: #cd00cd; } .g6 { background: #00cdcd; } .g7 { background: #e5e5e5; }
+.g8 { background: #7f7f7f; } .g9 { background: #ff0000; } .g10 { background: #00ff00; } .g11 { background: #ffff00; }
+.g12 { background: #5c5cff; } .g13 { background: #ff00ff; } .g14 { background: #00ffff; } .g15 { background: #ffffff; }
+</style></head><body>
+";
+
+/// An open log file
+pub struct LogWriter {
+    format: LogFormat,
+    sink: Sink,
+}
+
+enum Sink {
+    Task(mpsc::UnboundedSender<Chunk>),
+    Direct(std::io::BufWriter<std::fs::File>),
+}
+
+impl LogWriter {
+    /// Start writing `format` lines to `file`, on a writer task when a runtime is
+    /// running. A new HTML file gets its header first.
+    pub fn start(file: std::fs::File, format: LogFormat) -> Self {
+        let is_new = file.metadata().map(|m| m.len() == 0).unwrap_or(false);
+        let sink = match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let (tx, rx) = mpsc::unbounded_channel();
+                handle.spawn(run_writer(tokio::fs::File::from_std(file), rx));
+                Sink::Task(tx)
+            }
+            Err(_) => Sink::Direct(std::io::BufWriter::new(file)),
+        };
+        let mut writer = Self { format, sink };
+        if is_new && format == LogFormat::Html {
+            writer.write(HTML_HEADER.to_string());
+        }
+        writer
+    }
+
+    pub fn format(&self) -> LogFormat {
+        self.format
+    }
+
+    /// Queue one output line, after the time prefix `stamp`
+    pub fn write_line(&mut self, stamp: &str, line: &str) {
+        let text = self.format.format_line(stamp, line);
+        self.write(text);
+    }
+
+    /// Write out everything queued so far (the task does so on its next turn)
+    pub fn flush(&mut self) {
+        match &mut self.sink {
+            Sink::Task(tx) => {
+                let _ = tx.send(Chunk::Flush);
+            }
+            Sink::Direct(out) => {
+                let _ = out.flush();
+            }
+        }
+    }
+
+    fn write(&mut self, text: String) {
+        match &mut self.sink {
+            Sink::Task(tx) => {
+                let _ = tx.send(Chunk::Text(text));
+            }
+            Sink::Direct(out) => {
+                let _ = out.write_all(text.as_bytes());
+            }
+        }
+    }
+}
+
+/// What a writer task is sent
+enum Chunk {
+    Text(String),
+    Flush,
+}
+
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Color {
+    Basic(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Attributes set by SGR codes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    strike: bool,
+    reverse: bool,
+}
+
+impl Style {
+    fn apply(&mut self, params: &str) {
+        let nums: Vec<u32> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let mut i = 0;
+        while i < nums.len() {
+            match nums[i] {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                9 => self.strike = true,
+                22 => { self.bold = false; self.dim = false; }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                29 => self.strike = false,
+                n @ 30..=37 => self.fg = Some(Color::Basic((n - 30) as u8)),
+                n @ 90..=97 => self.fg = Some(Color::Basic((n - 90 + 8) as u8)),
+                39 => self.fg = None,
+                n @ 40..=47 => self.bg = Some(Color::Basic((n - 40) as u8)),
+                n @ 100..=107 => self.bg = Some(Color::Basic((n - 100 + 8) as u8)),
+                49 => self.bg = None,
+                n @ (38 | 48) => {
+                    let color = match nums.get(i + 1) {
+                        Some(5) => {
+                            let c = nums.get(i + 2).map(|&c| palette_256(c as u8));
+                            i += 2;
+                            c
+                        }
+                        Some(2) if i + 4 < nums.len() => {
+                            let c = Color::Rgb(nums[i + 2] as u8, nums[i + 3] as u8, nums[i + 4] as u8);
+                            i += 4;
+                            Some(c)
+                        }
+                        _ => None,
+                    };
+                    if n == 38 { self.fg = color; } else { self.bg = color; }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Opening tag for text in this style (None for plain text)
+    fn span(&self) -> Option<String> {
+        if *self == Style::default() {
+            return None;
+        }
+        let (fg, bg) = if self.reverse {
+            (Some(self.bg.unwrap_or(Color::Basic(0))), Some(self.fg.unwrap_or(Color::Basic(7))))
+        } else {
+            (self.fg, self.bg)
+        };
+        let mut classes = Vec::new();
+        let mut styles = Vec::new();
+        for (flag, class) in [(self.bold, "b"), (self.dim, "d"), (self.italic, "i"), (self.underline, "u"), (self.strike, "s")] {
+            if flag {
+                classes.push(class.to_string());
+            }
+        }
+        for (color, class, prop) in [(fg, "f", "color"), (bg, "g", "background")] {
+            match color {
+                Some(Color::Basic(n)) => classes.push(format!("{}{}", class, n)),
+                Some(Color::Rgb(r, g, b)) => styles.push(format!("{}: #{:02x}{:02x}{:02x}", prop, r, g, b)),
+                None => {}
+            }
+        }
+        let mut tag = "<span".to_string();
+        if !classes.is_empty() {
+            tag.push_str(&format!(" class=\"{}\"", classes.join(" ")));
+        }
+        if !styles.is_empty() {
+            tag.push_str(&format!(" style=\"{}\"", styles.join("; ")));
+        }
+        tag.push('>');
+        Some(tag)
+    }
+}
+
+/// xterm 256-color palette entry (the first 16 use the stylesheet's classes)
+fn palette_256(n: u8) -> Color {
+    match n {
+        0..=15 => Color::Basic(n),
+        16..=231 => {
+            let n = n - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            Color::Rgb(level(n / 36), level((n / 6) % 6), level(n % 6))
+        }
+        _ => {
+            let v = 8 + (n - 232) * 10;
+            Color::Rgb(v, v, v)
+        }
+    }
+}
+
+/// Convert a line with ANSI SGR codes to HTML: text is escaped, runs of styled text
+/// become `<span>`s, and other escape sequences (cursor movement, OSC) are dropped
+pub fn ansi_to_html(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 16);
+    let mut style = Style::default();
+    let mut open = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut final_byte = None;
+                    for pc in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&pc) {
+                            final_byte = Some(pc);
+                            break;
+                        }
+                        params.push(pc);
+                    }
+                    if final_byte == Some('m') {
+                        let before = style;
+                        style.apply(&params);
+                        if style != before {
+                            if open {
+                                out.push_str("</span>");
+                            }
+                            let span = style.span();
+                            open = span.is_some();
+                            out.push_str(&span.unwrap_or_default());
+                        }
+                    }
+                }
+                Some(']') => {
+                    // OSC: skip to BEL or ST
+                    while let Some(oc) = chars.next() {
+                        if oc == '\x07' || (oc == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+    if open {
+        out.push_str("</span>");
+    }
+    out
+}
+
+async fn run_writer(file: tokio::fs::File, mut rx: mpsc::UnboundedReceiver<Chunk>) {
+    let mut out = tokio::io::BufWriter::new(file);
+    let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+    loop {
+        tokio::select! {
+            chunk = rx.recv() => match chunk {
+                Some(Chunk::Text(text)) => {
+                    if out.write_all(text.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Chunk::Flush) => {
+                    let _ = out.flush().await;
+                }
+                None => break,
+            },
+            _ = flush.tick() => {
+                let _ = out.flush().await;
+            }
+        }
+    }
+    let _ = out.flush().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writer_task_flushes_on_close() {
+        let path = std::env::temp_dir().join(format!("clay-log-test-{}.log", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let mut log = LogWriter::start(file, LogFormat::Raw);
+        assert!(matches!(log.sink, Sink::Task(_)));
+        log.write_line("[12:00:00] ", "one");
+        // /log flush writes it out while the log stays open
+        log.flush();
+        for _ in 0..50 {
+            if !std::fs::read_to_string(&path).unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[12:00:00] one\n");
+        log.write_line("[12:00:01] ", "two");
+        drop(log);
+        // The task flushes and exits once the channel closes
+        for _ in 0..50 {
+            if std::fs::read_to_string(&path).unwrap().lines().count() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[12:00:00] one\n[12:00:01] two\n");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_ansi_to_html() {
+        assert_eq!(ansi_to_html("a < b & c"), "a &lt; b &amp; c");
+        assert_eq!(
+            ansi_to_html("\x1b[1;31mRed\x1b[0m plain"),
+            "<span class=\"b f1\">Red</span> plain"
+        );
+        assert_eq!(
+            ansi_to_html("\x1b[38;5;196mX\x1b[48;2;0;0;255mY"),
+            "<span style=\"color: #ff0000\">X</span><span style=\"color: #ff0000; background: #0000ff\">Y</span>"
+        );
+        assert_eq!(ansi_to_html("\x1b]8;;http://x.org\x07link\x1b]8;;\x1b\\\x1b[2K"), "link");
+        assert_eq!(LogFormat::from_name("HTML"), Some(LogFormat::Html));
+        assert_eq!(LogFormat::from_name("plain"), Some(LogFormat::Plain));
+        let lt = crate::util::LocalTime { year: 2024, month: 3, day: 9, hour: 7, minute: 5, second: 1, weekday: 6 };
+        assert_eq!(LogTimestamps::Time.stamp(&lt), "[07:05:01] ");
+        assert_eq!(LogTimestamps::DateTime.stamp(&lt), "[2024-03-09 07:05:01] ");
+        assert_eq!(LogTimestamps::Off.stamp(&lt), "");
+        assert_eq!(session_marker(false, &lt), "=== Session ended 2024-03-09 07:05:01 ===");
+        assert_eq!(
+            LogFormat::Plain.format_line("[12:00] ", "\x1b[1;31mRed\x1b[0m \x1b]8;;http://x.org\x07link\x1b]8;;\x1b\\\r\x00\tend"),
+            "[12:00] Red link\tend\n"
+        );
+        assert_eq!(LogFormat::Html.format_line("[12:00] ", "<hi>"), "<div><span class=\"ts\">[12:00] </span>&lt;hi&gt;</div>\n");
+    }
+}
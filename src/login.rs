@@ -0,0 +1,162 @@
+//! Auto-login templates.
+//!
+//! A world's Auto Login setting picks how its credentials are sent. Connect,
+//! Lines and Script send their opening lines as soon as the connection is up;
+//! Diku and Script then wait for server output matching each `expect` step and
+//! answer it. (Prompt and MOO_prompt count GA/EOR prompts instead, see
+//! `App::handle_prompt`.)
+//!
+//! A login script is one line per step, with `<user>` and `<pass>` replaced by
+//! the world's credentials:
+//!
+//! ```text
+//! expect (?i)what name
+//! <user>
+//! expect (?i)password
+//! <pass>
+//! ```
+//!
+//! An `expect REGEX` line waits for a line or prompt matching REGEX; every other
+//! non-blank line is sent once the steps before it are done.
+
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+/// The name prompt of Diku-derived servers (Circle, ROM, Smaug, ...)
+const DIKU_NAME: &str = r"(?i)\b(name|login|account)\b[^.!]*[:?]\s*$";
+/// Their password prompt
+const DIKU_PASSWORD: &str = r"(?i)\bpassword\b[^.!]*[:?]\s*$";
+/// A login still waiting this long after connecting is given up, so a later
+/// line that happens to match can't send the password
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A login worked out from a template and the world's credentials
+#[derive(Debug)]
+pub struct LoginScript {
+    /// Lines sent as soon as the connection is up
+    pub on_connect: Vec<String>,
+    /// Each wait and the lines sent once server output matches it
+    pub steps: Vec<(Regex, Vec<String>)>,
+}
+
+impl LoginScript {
+    /// Parse a login script (see the module docs)
+    pub fn parse(script: &str, user: &str, password: &str) -> Result<Self, String> {
+        let mut login = LoginScript { on_connect: Vec::new(), steps: Vec::new() };
+        for line in script.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(pattern) = line.trim_start().strip_prefix("expect ") {
+                let regex = Regex::new(pattern.trim())
+                    .map_err(|e| format!("Bad expect pattern '{}': {}", pattern.trim(), e))?;
+                login.steps.push((regex, Vec::new()));
+                continue;
+            }
+            let text = line.replace("<user>", user).replace("<pass>", password);
+            match login.steps.last_mut() {
+                Some((_, sends)) => sends.push(text),
+                None => login.on_connect.push(text),
+            }
+        }
+        Ok(login)
+    }
+
+    /// The built-in Diku template: the name, then the password, each at its prompt
+    pub fn diku(user: &str, password: &str) -> Self {
+        let script = format!("expect {}\n<user>\nexpect {}\n<pass>", DIKU_NAME, DIKU_PASSWORD);
+        Self::parse(&script, user, password).expect("built-in Diku patterns are valid")
+    }
+
+    /// Lines to send for the wait at `step` if `text` satisfies it
+    pub fn answer(&self, step: usize, text: &str) -> Option<&[String]> {
+        let (regex, sends) = self.steps.get(step)?;
+        regex.is_match(text).then_some(sends.as_slice())
+    }
+}
+
+/// How far one connection's Diku or Script login has got
+#[derive(Debug)]
+pub struct LoginProgress {
+    /// The `World::connection_id` it belongs to
+    pub connection_id: u64,
+    /// None when there is nothing (left) to wait for
+    script: Option<LoginScript>,
+    step: usize,
+    started: Instant,
+}
+
+impl LoginProgress {
+    pub fn new(connection_id: u64, script: Option<LoginScript>) -> Self {
+        LoginProgress { connection_id, script, step: 0, started: Instant::now() }
+    }
+
+    /// The lines to send if `text` answers the current wait, and whether that
+    /// finished the login
+    pub fn answer(&mut self, text: &str) -> Option<(Vec<String>, bool)> {
+        if self.started.elapsed() > LOGIN_TIMEOUT {
+            self.script = None;
+        }
+        let script = self.script.as_ref()?;
+        let sends = script.answer(self.step, text)?.to_vec();
+        self.step += 1;
+        let done = self.step >= script.steps.len();
+        if done {
+            self.script = None;
+        }
+        Some((sends, done))
+    }
+}
+
+/// Whether `text` (a prompt or the last line of output) asks for a password
+pub fn is_password_prompt(text: &str) -> bool {
+    static PASSWORD: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    PASSWORD.get_or_init(|| Regex::new(DIKU_PASSWORD).expect("built-in pattern is valid")).is_match(text)
+}
+
+/// Whether a login script waits for the server at all
+pub fn script_waits(script: &str) -> bool {
+    script.lines().any(|l| l.trim_start().starts_with("expect "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_login_script() {
+        let login = LoginScript::parse("chat off\nexpect (?i)name\n<user>\n\nexpect word:\n<pass>\nlook\n", "bob", "pw").unwrap();
+        assert_eq!(login.on_connect, vec!["chat off"]);
+        assert_eq!(login.steps.len(), 2);
+        assert_eq!(login.answer(0, "What is your NAME?"), Some(&["bob".to_string()][..]));
+        assert_eq!(login.answer(0, "Welcome!"), None);
+        assert_eq!(login.answer(1, "Password:"), Some(&["pw".to_string(), "look".to_string()][..]));
+        assert_eq!(login.answer(2, "anything"), None);
+        assert!(LoginScript::parse("expect (unclosed", "u", "p").is_err());
+        assert!(script_waits("<user>\n  expect x"));
+        assert!(!script_waits("connect <user> <pass>"));
+    }
+
+    #[test]
+    fn test_diku_prompts() {
+        let login = LoginScript::diku("bob", "pw");
+        assert!(login.on_connect.is_empty());
+        assert!(login.answer(0, "By what name do you wish to be known? ").is_some());
+        assert!(login.answer(0, "Enter your account name:").is_some());
+        assert!(login.answer(0, "Welcome to the realm of names.").is_none());
+        assert!(login.answer(1, "Password: ").is_some());
+        assert!(login.answer(1, "Wrong password.").is_none());
+    }
+
+    #[test]
+    fn test_login_progress_steps_once() {
+        let mut progress = LoginProgress::new(3, Some(LoginScript::diku("bob", "pw")));
+        assert_eq!(progress.answer("Password:"), None, "name comes first");
+        assert_eq!(progress.answer("By what name do you wish to be known?"), Some((vec!["bob".to_string()], false)));
+        assert_eq!(progress.answer("Password: "), Some((vec!["pw".to_string()], true)));
+        assert_eq!(progress.answer("Password: "), None, "done");
+        assert_eq!(LoginProgress::new(4, None).answer("Password:"), None);
+    }
+}
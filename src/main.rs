@@ -2,17 +2,22 @@
 pub mod encoding;
 pub mod telnet;
 pub mod spell;
+pub mod hunspell;
 pub mod input;
 pub mod util;
 pub mod websocket;
+pub mod ws_deflate;
 pub mod ansi_music;
 pub mod tf;
 pub mod popup;
 pub mod actions;
 pub mod http;
+pub mod acme;
+pub mod web_push;
 pub mod persistence;
 pub mod daemon;
 pub mod theme;
+pub mod color_depth;
 pub mod keybindings;
 pub mod input_handler;
 pub mod rendering;
@@ -23,8 +28,36 @@ pub mod remote_client;
 pub mod ssh;
 pub mod tts;
 pub mod scrollback;
+pub mod tag_routes;
+pub mod gags;
+pub mod profiles;
+pub mod secrets;
+pub mod trigger_guard;
+pub mod copy_mode;
+pub mod instance_lock;
+pub mod throttle;
+pub mod status_format;
+pub mod tab_bar;
+pub mod capture;
+pub mod split;
+pub mod desktop_notify;
+pub mod bell;
+pub mod line_cache;
+pub mod filter_index;
+pub mod logging;
+pub mod replay;
+pub mod tf_import;
+pub mod xml_import;
+pub mod happy_eyeballs;
+pub mod login;
+pub mod dice;
+pub mod sendfile;
+pub mod shell;
+pub mod keyring_store;
 #[cfg(feature = "webview-gui")]
 pub mod webview_gui;
+#[cfg(feature = "tray")]
+pub mod tray;
 pub mod testserver;
 #[cfg(test)]
 pub mod testharness;
@@ -38,13 +71,17 @@ const BUILD_DATE: &str = env!("BUILD_DATE");
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 static CUSTOM_CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+// Config profile name (set via --profile=<name>); None is the default profile
+static CONFIG_PROFILE: OnceLock<String> = OnceLock::new();
+// telnet:// or mud:// link given on the command line, opened once startup is done
+static STARTUP_URI: OnceLock<String> = OnceLock::new();
 
 /// Global debug flag — set from settings, checked by debug_log and file writes
 pub(crate) static DEBUG_ENABLED: AtomicBool = AtomicBool::new(false);
 
-/// Tracks whether the startup header has been written to ~/.clay/debug.log
+/// Tracks whether the startup header has been written to ~/.config/clay/debug.log
 static DEBUG_LOG_HEADER_WRITTEN: AtomicBool = AtomicBool::new(false);
-/// Tracks whether the startup header has been written to ~/.clay/output.debug.log
+/// Tracks whether the startup header has been written to ~/.config/clay/output.debug.log
 static OUTPUT_DEBUG_HEADER_WRITTEN: AtomicBool = AtomicBool::new(false);
 /// Startup time stored as Unix timestamp (seconds since epoch)
 static STARTUP_TIME: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
@@ -52,6 +89,8 @@ static STARTUP_TIME: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64
 pub static GUI_RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
 /// Flag set by HTTP server after successful bind — checked by GUI readiness wait
 pub static GUI_HTTP_READY: AtomicBool = AtomicBool::new(false);
+/// Set by /detach once the background session is running — checked after the TUI exits
+pub static SESSION_DETACHED: AtomicBool = AtomicBool::new(false);
 /// Set by `--local-server` before startup. General "local-server mode is active" signal, used
 /// wherever headless-server behavior needs to differ from other headless modes (-D, --multiuser)
 /// or from interactive modes:
@@ -82,22 +121,41 @@ pub fn get_custom_config_path() -> Option<&'static PathBuf> {
     CUSTOM_CONFIG_PATH.get()
 }
 
+/// Select a config profile (call early in main before any directory is used).
+/// "default" selects the default profile.
+pub fn set_config_profile(name: &str) {
+    if !name.eq_ignore_ascii_case("default") {
+        let _ = CONFIG_PROFILE.set(name.to_string());
+    }
+}
+
+/// Get the config profile name if one other than the default was selected
+pub fn get_config_profile() -> Option<&'static str> {
+    CONFIG_PROFILE.get().map(String::as_str)
+}
+
+/// Whether `name` can be used as a config profile (it becomes a directory name)
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
 /// Get the full version string including build hash
 pub fn get_version_string() -> String {
     format!("Clay v{} (build {}-{})", VERSION, BUILD_DATE, BUILD_HASH)
 }
 
 // Re-export commonly used types from modules
-pub use encoding::{Encoding, Theme, WorldSwitchMode, convert_discord_emojis, convert_discord_emojis_with_links, colorize_square_emojis, is_visually_empty, is_ansi_only_line, has_background_color, strip_non_sgr_sequences, wrap_urls_with_osc8};
+pub use encoding::{Encoding, SharedEncoding, Theme, TimestampMode, WorldSwitchMode, convert_discord_emojis, convert_discord_emojis_with_links, colorize_square_emojis, is_visually_empty, is_ansi_only_line, has_background_color, strip_non_sgr_sequences, wrap_urls_with_osc8};
 pub use telnet::{
-    WriteCommand, StreamReader, StreamWriter, AutoConnectType, KeepAliveType,
+    WriteCommand, StreamReader, StreamWriter, MudTransport, AutoConnectType, KeepAliveType,
     process_telnet, find_safe_split_point, build_naws_subnegotiation, build_ttype_response, TelnetResult,
     build_gmcp_message, build_msdp_request, build_msdp_set,
     build_charset_accepted, build_charset_rejected,
     TELNET_IAC, TELNET_NOP, TELNET_GA, TELNET_OPT_NAWS, TELNET_OPT_CHARSET,
 };
-pub use spell::{SpellChecker, SpellState};
-pub use input::{InputArea, display_width, display_width_chars, chars_for_display_width};
+pub use spell::{IgnoreList, SpellChecker, SpellState};
+pub use input::{InputArea, display_width, display_width_chars, chars_for_display_width, grapheme_width};
 pub use util::{get_binary_name, strip_ansi_codes, visual_line_count, get_current_time_12hr, strip_mud_tag, truncate_str, convert_temperatures, parse_discord_timestamps, local_time_from_epoch, local_time_now, color_name_to_ansi_bg, nli_visual_rows, nli_wrap_width};
 pub use websocket::{
     WsMessage, WorldStateMsg, WorldSettingsMsg, GlobalSettingsMsg, TimestampedLine,
@@ -131,7 +189,7 @@ use rendering::*;
 use commands::*;
 use platform::*;
 
-use std::io::{self, stdout, Write as IoWrite};
+use std::io::{self, stdout};
 #[cfg(unix)]
 use std::os::unix::io::{FromRawFd, RawFd};
 #[cfg(windows)]
@@ -147,7 +205,8 @@ pub(crate) type SocketFd = i64;
 use bytes::BytesMut;
 use crossterm::{
     cursor,
-    event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEventKind, MouseButton, EnableMouseCapture, DisableMouseCapture},
+    event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEventKind, MouseButton, EnableMouseCapture, DisableMouseCapture,
+        KeyboardEnhancementFlags, PushKeyboardEnhancementFlags, PopKeyboardEnhancementFlags},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, Clear, ClearType},
 };
@@ -205,6 +264,7 @@ pub struct FilterPopup {
     cursor: usize,
     filtered_indices: Vec<usize>,  // Indices of matching lines in output_lines
     scroll_offset: usize,          // Scroll position within filtered results
+    matched: Option<(String, u64)>, // Plain-text filter and index generation filtered_indices came from
 }
 
 impl FilterPopup {
@@ -215,6 +275,7 @@ impl FilterPopup {
             cursor: 0,
             filtered_indices: Vec::new(),
             scroll_offset: 0,
+            matched: None,
         }
     }
 
@@ -224,6 +285,7 @@ impl FilterPopup {
         self.cursor = 0;
         self.filtered_indices.clear();
         self.scroll_offset = 0;
+        self.matched = None;
     }
 
     fn close(&mut self) {
@@ -231,43 +293,42 @@ impl FilterPopup {
         self.filter_text.clear();
         self.filtered_indices.clear();
         self.scroll_offset = 0;
+        self.matched = None;
     }
 
-    fn update_filter(&mut self, output_lines: &[OutputLine]) {
+    fn update_filter(&mut self, index: &filter_index::FilterIndex) {
+        let previous = self.matched.take();
         if self.filter_text.is_empty() {
-            self.filtered_indices = (0..output_lines.len()).collect();
+            self.filtered_indices = (0..index.len()).collect();
         } else {
             // Check if pattern has wildcards
             let has_wildcards = self.filter_text.contains('*') || self.filter_text.contains('?');
 
             if has_wildcards {
-                // Use wildcard matching with regex
+                // Use wildcard matching with regex (case-insensitive, so the lowercase text matches)
                 if let Some(regex) = filter_wildcard_to_regex(&self.filter_text) {
-                    self.filtered_indices = output_lines
-                        .iter()
-                        .enumerate()
-                        .filter(|(_, line)| {
-                            let plain = strip_ansi_codes(&line.text);
-                            regex.is_match(&plain)
-                        })
-                        .map(|(i, _)| i)
+                    self.filtered_indices = (0..index.len())
+                        .filter(|&i| regex.is_match(index.plain(i)))
                         .collect();
                 } else {
                     // Invalid regex, show no matches
                     self.filtered_indices.clear();
                 }
             } else {
-                // Simple substring matching (case-insensitive)
+                // Simple substring matching (case-insensitive). Typing onto the end of the
+                // filter only narrows the matches, so search those rather than everything.
                 let filter_lower = self.filter_text.to_lowercase();
-                self.filtered_indices = output_lines
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, line)| {
-                        let plain = strip_ansi_codes(&line.text);
-                        plain.to_lowercase().contains(&filter_lower)
-                    })
-                    .map(|(i, _)| i)
-                    .collect();
+                let narrowing = previous.is_some_and(|(text, generation)| {
+                    generation == index.generation() && filter_lower.contains(&text)
+                });
+                if narrowing {
+                    self.filtered_indices.retain(|&i| index.plain(i).contains(&filter_lower));
+                } else {
+                    self.filtered_indices = (0..index.len())
+                        .filter(|&i| index.plain(i).contains(&filter_lower))
+                        .collect();
+                }
+                self.matched = Some((filter_lower, index.generation()));
             }
         }
         // Reset scroll to end (most recent matches)
@@ -290,7 +351,8 @@ fn search_line_visible(line: &OutputLine, show_tags: bool) -> bool {
     true
 }
 
-/// F5 history search popup state
+/// Output search state: the F5 history search popup, and the in-place scrollback
+/// search (Ctrl+F) that keeps matches highlighted and steps through them with n/N
 pub struct SearchPopup {
     pub visible: bool,
     pub search_text: String,
@@ -298,6 +360,9 @@ pub struct SearchPopup {
     pub match_indices: Vec<usize>,  // output_lines indices that match (ascending)
     pub current_pos: usize,         // index into match_indices currently shown at bottom
     pub last_searched: String,      // query used to build current match_indices/current_pos
+    pub in_place: bool,             // opened as scrollback search: Enter hands over to n/N navigation
+    pub navigating: bool,           // prompt closed; matches stay highlighted, n/N step through them
+    pub regex: Option<regex::Regex>, // compiled query, used both to match lines and highlight hits
 }
 
 impl SearchPopup {
@@ -309,6 +374,9 @@ impl SearchPopup {
             match_indices: Vec::new(),
             current_pos: 0,
             last_searched: String::new(),
+            in_place: false,
+            navigating: false,
+            regex: None,
         }
     }
 
@@ -319,6 +387,15 @@ impl SearchPopup {
         self.match_indices.clear();
         self.current_pos = 0;
         self.last_searched.clear();
+        self.in_place = false;
+        self.navigating = false;
+        self.regex = None;
+    }
+
+    /// Open as an in-place scrollback search (Ctrl+F)
+    pub fn open_in_place(&mut self) {
+        self.open();
+        self.in_place = true;
     }
 
     pub fn close(&mut self) {
@@ -326,20 +403,58 @@ impl SearchPopup {
         self.search_text.clear();
         self.match_indices.clear();
         self.last_searched.clear();
+        self.navigating = false;
+        self.regex = None;
+    }
+
+    /// Hide the prompt but keep the query and matches for n/N navigation
+    pub fn start_navigating(&mut self) {
+        self.visible = false;
+        self.navigating = !self.search_text.is_empty();
+    }
+
+    /// Reopen the prompt on the current query (`/` while navigating)
+    pub fn edit_query(&mut self) {
+        self.visible = true;
+        self.navigating = false;
+        self.cursor = self.search_text.len();
+    }
+
+    /// Regex to highlight in the output, while a search is showing
+    pub fn highlight_regex(&self) -> Option<&regex::Regex> {
+        if self.visible || self.navigating {
+            self.regex.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// "match X of Y" for the status line, while a search is showing
+    pub fn status_text(&self) -> Option<String> {
+        if !(self.visible || self.navigating) || self.search_text.is_empty() {
+            return None;
+        }
+        if self.match_indices.is_empty() {
+            Some("no matches".to_string())
+        } else {
+            Some(format!("match {} of {}", self.current_pos + 1, self.match_indices.len()))
+        }
     }
 
-    pub fn update_search(&mut self, output_lines: &[OutputLine], show_tags: bool) {
+    pub fn update_search(&mut self, output_lines: &std::collections::VecDeque<OutputLine>, show_tags: bool) {
         if self.search_text.is_empty() {
             self.match_indices.clear();
             self.current_pos = 0;
             self.last_searched.clear();
+            self.regex = None;
             return;
         }
         let query_changed = self.search_text != self.last_searched;
         self.last_searched = self.search_text.clone();
         let has_wildcards = self.search_text.contains('*') || self.search_text.contains('?');
         if has_wildcards {
-            if let Some(regex) = filter_wildcard_to_regex(&self.search_text) {
+            self.regex = filter_wildcard_to_regex(&self.search_text);
+            if let Some(ref regex) = self.regex {
                 self.match_indices = output_lines
                     .iter()
                     .enumerate()
@@ -355,6 +470,10 @@ impl SearchPopup {
                 self.match_indices.clear();
             }
         } else {
+            self.regex = regex::RegexBuilder::new(&regex::escape(&self.search_text))
+                .case_insensitive(true)
+                .build()
+                .ok();
             let search_lower = self.search_text.to_lowercase();
             self.match_indices = output_lines
                 .iter()
@@ -895,6 +1014,12 @@ struct TriggerProcessingResult {
     pub messages: Vec<String>,
     /// Highlight color from action triggers
     pub highlight_color: Option<String>,
+    /// Capture buffer to copy the line into (action `/capture <name>`)
+    pub capture: Option<String>,
+    /// Programs to feed the line to (action `/pipe <program>`)
+    pub pipes: Vec<String>,
+    /// Name of the Clay action that fired and sent commands (for the loop watchdog)
+    pub fired_action: Option<String>,
 }
 
 /// Parsed BAMF portal information
@@ -966,13 +1091,21 @@ fn process_triggers(
         clay_commands: Vec::new(),
         messages: Vec::new(),
         highlight_color: None,
+        capture: None,
+        pipes: Vec::new(),
+        fired_action: None,
     };
 
     // Check Clay action triggers
     if let Some(action_result) = check_action_triggers(line, world_name, actions) {
+        if !action_result.commands.is_empty() || !action_result.pipes.is_empty() {
+            result.fired_action = Some(action_result.action_name);
+        }
+        result.pipes = action_result.pipes;
         result.send_commands.extend(action_result.commands);
         result.is_gagged = action_result.should_gag;
         result.highlight_color = action_result.highlight_color;
+        result.capture = action_result.capture;
     }
 
     // Check TF triggers
@@ -1225,8 +1358,10 @@ fn cert_needs_regeneration(cert_path: &std::path::Path) -> bool {
 /// Resolve which cert/key files the web server should use for this run, always
 /// returning usable file paths (materializing settings.dat's stored auto-cert to
 /// disk, generating a fresh one if missing/stale). User-provided certs take
-/// priority; otherwise an auto-generated self-signed cert is used, backed by
-/// `web_cert_pem`/`web_key_pem` in settings.dat so it survives a fresh `~/.clay`
+/// priority, then a Let's Encrypt cert for `acme_domain` (see acme.rs — the
+/// self-signed one stands in until the first is issued); otherwise an
+/// auto-generated self-signed cert is used, backed by
+/// `web_cert_pem`/`web_key_pem` in settings.dat so it survives a fresh config directory
 /// and travels with `/import` (the private key simply fails to decrypt on another
 /// machine's `secure.key` and gets silently regenerated — see persistence.rs).
 /// Returns `None` only if generation itself fails.
@@ -1235,11 +1370,19 @@ fn resolve_web_cert_files(app: &mut App) -> Option<(String, String)> {
         return Some((app.settings.websocket_cert_file.clone(), app.settings.websocket_key_file.clone()));
     }
 
+    if !app.settings.acme_domain.is_empty() {
+        app.start_acme_if_due(false);
+        let (cert_path, key_path) = (acme::cert_path(), acme::key_path());
+        if cert_path.exists() && key_path.exists() {
+            return Some((cert_path.to_string_lossy().to_string(), key_path.to_string_lossy().to_string()));
+        }
+    }
+
     let cert_path = clay_config_path("cert.pem");
     let key_path = clay_config_path("key.pem");
 
     // Materialize a settings-stored auto-cert to disk if the cache is missing
-    // (fresh ~/.clay, or settings imported from another machine).
+    // (fresh config directory, or settings imported from another machine).
     if !app.settings.web_cert_pem.is_empty() && !app.settings.web_key_pem.is_empty()
         && (!cert_path.exists() || !key_path.exists())
     {
@@ -1299,16 +1442,52 @@ pub fn clay_filename(name: &str) -> String {
     { name.to_string() }
 }
 
-/// Returns the path to the `~/.clay/` config directory, creating it if needed.
-/// On Windows the directory is `~/clay/` (no dot prefix).
-pub fn clay_config_dir() -> PathBuf {
-    let home = get_home_dir();
-    let dir = PathBuf::from(home).join(clay_filename("clay"));
+/// `$XDG_<...>_HOME/clay` (XDG base directories), or `~/<fallback>/clay` when the
+/// variable is unset or not an absolute path, as the spec says to treat it.
+#[cfg(unix)]
+fn xdg_clay_dir(var: &str, fallback: &str) -> PathBuf {
+    match std::env::var_os(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir.join("clay"),
+        _ => PathBuf::from(get_home_dir()).join(fallback).join("clay"),
+    }
+}
+
+/// `dir/profiles/<name>` when a config profile is selected, otherwise `dir`
+fn profile_dir(dir: PathBuf) -> PathBuf {
+    match get_config_profile() {
+        Some(name) => dir.join("profiles").join(name),
+        None => dir,
+    }
+}
+
+/// The default profile's config directory, without creating it
+fn clay_config_root() -> PathBuf {
+    #[cfg(unix)]
+    { xdg_clay_dir("XDG_CONFIG_HOME", ".config") }
+    #[cfg(not(unix))]
+    { PathBuf::from(get_home_dir()).join(clay_filename("clay")) }
+}
+
+/// Names of the config profiles that have a directory, sorted
+pub fn list_config_profiles() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(clay_config_root().join("profiles")) else { return Vec::new() };
+    let mut names: Vec<String> = entries.flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| is_valid_profile_name(name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Creates `dir` if needed and makes it owner-only
+fn private_dir(dir: PathBuf) -> PathBuf {
     let _ = std::fs::create_dir_all(&dir);
-    // B2 (security remediation): the directory holds secure.key, settings.dat (encrypted
-    // passwords/tokens), key.pem, etc. — restrict to owner-only. Applied unconditionally
-    // (not just at creation) so it self-heals a pre-existing dir with looser perms too;
-    // this is cheap (one syscall) and idempotent.
+    // B2 (security remediation): the config directory holds secure.key, settings.toml
+    // (encrypted passwords/tokens), key.pem, etc., and the others hold world logs and
+    // the reload state — restrict to owner-only. Applied unconditionally (not just at
+    // creation) so it self-heals a pre-existing dir with looser perms too; this is
+    // cheap (one syscall) and idempotent.
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
@@ -1317,16 +1496,95 @@ pub fn clay_config_dir() -> PathBuf {
     dir
 }
 
-/// Returns the path to a named file inside the `~/.clay/` config directory.
-/// e.g. `clay_config_path("settings.dat")` → `~/.clay/settings.dat`
+/// Returns the config directory, creating it if needed: `$XDG_CONFIG_HOME/clay/`
+/// (`~/.config/clay/`) on Unix, `~/clay/` on Windows. Settings, keys, themes and
+/// keybindings live here. With `--profile=<name>` this and the state and data
+/// directories each get a `profiles/<name>/` subdirectory instead.
+pub fn clay_config_dir() -> PathBuf {
+    private_dir(profile_dir(clay_config_root()))
+}
+
+/// Returns the state directory, creating it if needed: `$XDG_STATE_HOME/clay/`
+/// (`~/.local/state/clay/`) on Unix, the config directory on Windows. Holds the hot
+/// reload state and the instance lock.
+pub fn clay_state_dir() -> PathBuf {
+    #[cfg(unix)]
+    { private_dir(profile_dir(xdg_clay_dir("XDG_STATE_HOME", ".local/state"))) }
+    #[cfg(not(unix))]
+    { clay_config_dir() }
+}
+
+/// Returns the data directory, creating it if needed: `$XDG_DATA_HOME/clay/`
+/// (`~/.local/share/clay/`) on Unix, the config directory on Windows. World logs go
+/// in its `logs/`.
+pub fn clay_data_dir() -> PathBuf {
+    #[cfg(unix)]
+    { private_dir(profile_dir(xdg_clay_dir("XDG_DATA_HOME", ".local/share"))) }
+    #[cfg(not(unix))]
+    { clay_config_dir() }
+}
+
+/// Returns the path to a named file inside the config directory.
+/// e.g. `clay_config_path("settings.toml")` → `~/.config/clay/settings.toml`
 pub fn clay_config_path(name: &str) -> PathBuf {
     clay_config_dir().join(name)
 }
 
-/// One-time migration: move legacy `~/.clay.*` dotfiles into `~/.clay/` config dir.
-/// Safe to call repeatedly — skips files that already exist at the new location.
-/// Must be called at startup before any config is loaded.
+/// Returns the path to a named file inside the state directory
+pub fn clay_state_path(name: &str) -> PathBuf {
+    clay_state_dir().join(name)
+}
+
+/// Moves `from` to `to` unless `to` already exists, copying (then deleting) a file when
+/// a rename can't cross filesystems
+#[cfg(unix)]
+fn move_if_absent(from: &std::path::Path, to: &std::path::Path) {
+    if to.exists() {
+        return;
+    }
+    if std::fs::rename(from, to).is_err() && from.is_file() && std::fs::copy(from, to).is_ok() {
+        let _ = std::fs::remove_file(from);
+    }
+}
+
+/// One-time migration from `~/.clay/`, where everything lived before the XDG base
+/// directories: logs go to the data directory, the reload state and lock to the state
+/// directory, everything else to the config directory. The old directory is removed
+/// once empty.
+#[cfg(unix)]
+fn migrate_dot_clay_dir() {
+    let old_dir = PathBuf::from(get_home_dir()).join(".clay");
+    let config_dir = clay_config_dir();
+    if !old_dir.is_dir() || old_dir == config_dir {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(&old_dir) else { return };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name_str = name.to_string_lossy();
+        let target = if name_str == "logs" {
+            clay_data_dir().join("logs")
+        } else if name_str.starts_with("reload.") || name_str == "clay.lock" {
+            clay_state_dir().join(&name)
+        } else {
+            config_dir.join(&name)
+        };
+        move_if_absent(&entry.path(), &target);
+    }
+    let _ = std::fs::remove_dir(&old_dir);
+}
+
+/// One-time migration: move `~/.clay/` and the older `~/.clay.*` dotfiles into the
+/// config/state directories. Safe to call repeatedly — skips files that already exist
+/// at the new location. Must be called at startup before any config is loaded.
+/// Only the default profile has anything to migrate.
 pub fn migrate_legacy_config_files() {
+    if get_config_profile().is_some() {
+        return;
+    }
+    #[cfg(unix)]
+    migrate_dot_clay_dir();
+
     let home = get_home_dir();
     let home_path = std::path::Path::new(&home);
     let config_dir = clay_config_dir();
@@ -1342,6 +1600,7 @@ pub fn migrate_legacy_config_files() {
         ("clay.dmp.log",       "dump.log"),
         ("clay.cert.pem",      "cert.pem"),
         ("clay.key.pem",       "key.pem"),
+        ("clay.dict",          "personal.dict"),
     ];
 
     for (legacy_name, new_name) in migrations {
@@ -1370,7 +1629,7 @@ pub fn migrate_legacy_config_files() {
             if fname_str.starts_with(&prefix) {
                 let suffix = &fname_str[prefix.len()..];
                 let new_name = format!("reload.{}", suffix);
-                let new_path = config_dir.join(&new_name);
+                let new_path = clay_state_path(&new_name);
                 if !new_path.exists() {
                     let _ = std::fs::rename(entry.path(), &new_path);
                 }
@@ -1502,13 +1761,31 @@ impl AuthKey {
     }
 }
 
-/// Sanitize a `web_path` value: keep only `[A-Za-z0-9_-]`, trim leading/trailing slashes
-/// (an empty result means legacy mode — UI served at `/`).
+/// A revocable per-device sign-in key (/web tokens). Accepted wherever the shared auth
+/// key is (WebSocket login and the knock), so a lost device can be cut off alone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceToken {
+    pub name: String,
+    /// SHA-256 of the token (`hash_password`); the token itself is never stored. Clients
+    /// sign in with `hash_with_challenge(SHA-256(token), challenge)`, as for passwords.
+    pub key_hash: String,
+    pub created_at: u64,  // Unix timestamp
+    pub last_used: Option<u64>,  // Unix timestamp of the last sign-in with it
+}
+
+/// Sanitize a `web_path` value: one or more `/`-separated segments of `[A-Za-z0-9_-]`
+/// (several let a reverse proxy mount Clay at e.g. `/apps/clay/`), with empty segments
+/// and leading/trailing slashes dropped (an empty result means legacy mode — UI served at `/`).
 pub fn sanitize_web_path(raw: &str) -> String {
-    raw.trim_matches('/')
-        .chars()
-        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
-        .collect()
+    raw.split('/')
+        .map(|segment| {
+            segment.chars()
+                .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+                .collect::<String>()
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 #[derive(Clone)]
@@ -1517,7 +1794,7 @@ pub struct Settings {
     spell_check_enabled: bool,
     temp_convert_enabled: bool,  // Temperature conversion (e.g., 32F -> 32F (0C))
     world_switch_mode: WorldSwitchMode,
-    debug_enabled: bool,    // Debug logging to ~/.clay/debug.log
+    debug_enabled: bool,    // Debug logging to ~/.config/clay/debug.log
     ansi_music_enabled: bool, // Enable ANSI music playback (web/GUI only)
     theme: Theme,           // Console theme
     gui_theme: Theme,       // GUI theme (separate from console)
@@ -1550,21 +1827,58 @@ pub struct Settings {
     http_port: u16,                // Port for the web interface
     web_path: String,              // Stealth path prefix for web UI (default "clay"; empty = legacy mode at "/")
     websocket_password: String,
+    websocket_viewer_password: String,  // View-only web sign-in password (empty = off, see /web viewer)
     websocket_allow_list: String,  // CSV list of hosts that can be whitelisted
     websocket_whitelisted_host: Option<String>,  // Currently whitelisted host (authenticated from allow list)
     websocket_cert_file: String,   // Path to a user-provided TLS certificate file (PEM); empty = use the auto-generated cert
     websocket_key_file: String,    // Path to a user-provided TLS private key file (PEM); empty = use the auto-generated cert
     // Auto-generated self-signed cert/key, canonically stored here so it survives a
-    // fresh ~/.clay and travels with /import. Materialized to ~/.clay/cert.pem and
+    // fresh config directory and travels with /import. Materialized to ~/.config/clay/cert.pem and
     // key.pem on disk (a derived cache) for the file-based TLS server APIs to read.
     // Public cert stored in cleartext; the private key is encrypted at rest (see
     // persistence::secret()) the same way websocket_auth_key/world passwords are.
     web_cert_pem: String,
     web_key_pem: String,
+    // Domain to get a Let's Encrypt certificate for (empty = off, see /web acme and acme.rs)
+    acme_domain: String,
+    acme_email: String,            // Contact for the Let's Encrypt account (optional)
+    web_bind_addr: String,         // Address the web server listens on (http::parse_bind_addr)
+    web_unix_socket: String,       // Also listen on this Unix socket for a local reverse proxy (empty = off)
+    web_trusted_proxies: String,   // CSV of reverse proxy IPs whose X-Forwarded-For is believed
     // Single persistent auth key for passwordless device authentication
     websocket_auth_key: Option<AuthKey>,
+    // Per-device keys, each revocable on its own (encrypted at rest like the auth key)
+    device_tokens: Vec<DeviceToken>,
+    // Browsers that get Web Push activity notifications (/web push on, see web_push.rs)
+    push_subscriptions: Vec<web_push::PushSubscription>,
     // User-defined actions/triggers
     actions: Vec<Action>,
+    // Routing rules keyed on inline MUD tags (see tag_routes.rs)
+    pub tag_routes: Vec<tag_routes::TagRoute>,
+    // Managed /gag list, temporary gags included (see gags.rs)
+    pub gags: Vec<gags::Gag>,
+    // Time-scheduled behavior overrides (see profiles.rs)
+    pub profiles: Vec<profiles::Profile>,
+    // Named secrets for ${secret:NAME} references, shared by all worlds (see secrets.rs)
+    pub secrets: std::collections::BTreeMap<String, String>,
+    // Action fires per second before the loop watchdog disables them (0 = off, see trigger_guard.rs)
+    pub trigger_loop_limit: u32,
+    // Batching interval for low-priority background worlds in ms (0 = off, see throttle.rs)
+    pub background_batch_ms: u32,
+    // Shell command that receives copy mode text on stdin (empty = OSC 52, see copy_mode.rs)
+    pub clipboard_command: String,
+    // Console status bar template (empty = built-in layout, see status_format.rs)
+    pub status_format: String,
+    // Show the one-line world tab bar above the status bar (see tab_bar.rs)
+    pub tab_bar: bool,
+    // Time prefix on displayed output lines (worlds can override; set with /timestamps)
+    pub timestamps: TimestampMode,
+    // Desktop notification for new output while the terminal is unfocused (see desktop_notify.rs)
+    pub notify_activity: bool,
+    // Output lines kept per world before the oldest are evicted
+    pub scrollback_lines: usize,
+    // Ask before /quit or Ctrl+C closes connected worlds
+    pub confirm_quit: bool,
     // TLS proxy for connection preservation over hot reload
     tls_proxy_enabled: bool,
     // Custom dictionary path for spell checking (empty = use system defaults)
@@ -1617,14 +1931,35 @@ impl Default for Settings {
             http_port: 9000,
             web_path: "clay".to_string(),
             websocket_password: String::new(),
+            websocket_viewer_password: String::new(),
             websocket_allow_list: String::new(),
             websocket_whitelisted_host: None,
             websocket_cert_file: String::new(),
             websocket_key_file: String::new(),
             web_cert_pem: String::new(),
             web_key_pem: String::new(),
+            acme_domain: String::new(),
+            acme_email: String::new(),
+            web_bind_addr: http::DEFAULT_BIND_ADDR.to_string(),
+            web_unix_socket: String::new(),
+            web_trusted_proxies: String::new(),
             websocket_auth_key: None,
+            device_tokens: Vec::new(),
+            push_subscriptions: Vec::new(),
             actions: Vec::new(),
+            tag_routes: Vec::new(),
+            gags: Vec::new(),
+            profiles: Vec::new(),
+            secrets: std::collections::BTreeMap::new(),
+            trigger_loop_limit: trigger_guard::DEFAULT_LIMIT,
+            background_batch_ms: throttle::DEFAULT_BATCH_MS,
+            clipboard_command: String::new(),
+            status_format: String::new(),
+            tab_bar: false,
+            timestamps: TimestampMode::Off,
+            notify_activity: false,
+            scrollback_lines: DEFAULT_SCROLLBACK_LINES,
+            confirm_quit: true,
             tls_proxy_enabled: false,
             dictionary_path: String::new(),
             editor_side: EditorSide::Left,
@@ -1677,11 +2012,38 @@ pub struct WorldSettings {
     pub user: String,
     pub password: String,
     pub use_ssl: bool,
+    /// How the server's TLS certificate is checked when `use_ssl` is on
+    pub tls_verify: platform::TlsVerify,
+    /// PEM client certificate and key files presented to the server (empty: none;
+    /// no key file means the key is in the certificate file)
+    pub tls_client_cert: String,
+    pub tls_client_key: String,
+    /// Connect through an SSH bastion (`host[:port]`; empty: connect directly), as
+    /// `ssh_user` (empty: the local username) with the `ssh_key` file or ssh-agent
+    pub ssh_host: String,
+    pub ssh_user: String,
+    pub ssh_key: String,
     pub log_enabled: bool,
+    pub log_format: logging::LogFormat,
+    pub log_timestamps: logging::LogTimestamps,
+    /// Write "=== Session started/ended ===" lines on connect and disconnect
+    pub log_session_markers: bool,
     pub encoding: Encoding,
     pub auto_connect_type: AutoConnectType,
+    /// The Script auto-login's steps (see login.rs)
+    pub login_script: String,
+    /// Commands sent once login is done, one per line (see `connect_command_lines`)
+    pub connect_commands: String,
     pub keep_alive_type: KeepAliveType,
     pub keep_alive_cmd: String,
+    /// Idle seconds before a keepalive is sent (see `keep_alive_interval`)
+    pub keep_alive_interval_secs: u64,
+    /// Minutes without output before the silence watchdog alerts (0: off)
+    pub silence_alert_mins: u32,
+    /// Added before and after each line typed to the world, e.g. "say " in a
+    /// chat-only world (see `World::wrap_input`)
+    pub input_prefix: String,
+    pub input_suffix: String,
     // Slack settings
     slack_token: String,
     slack_channel: String,
@@ -1699,6 +2061,40 @@ pub struct WorldSettings {
     pub auto_reconnect_secs: u32,
     // Auto-reconnect when a web/Android client connects
     pub auto_reconnect_on_web: bool,
+    // Function key macros: "F5"/"Shift-F5" -> command or text (set with /macro)
+    pub macros: std::collections::BTreeMap<String, String>,
+    // Numpad speedwalk: keypad 1-9 send movement commands (set with /numpad)
+    pub numpad_walk: bool,
+    // Low priority: output is batched while in the background (set with /throttle)
+    pub low_priority: bool,
+    // Time prefix on displayed lines; None uses the global setting (set with /timestamps)
+    pub timestamps: Option<TimestampMode>,
+    // No desktop or mobile notifications from this world (set with /notify -w)
+    pub notify_muted: bool,
+    // Sound file played on activity or a /notify while unfocused (set with /notify -w sound)
+    pub notify_sound: String,
+    // Web/GUI clients show image URLs in the output inline (set with /images)
+    pub inline_images: bool,
+    // What a BEL in this world's output does (set with /bell, see bell.rs)
+    pub bell: bell::BellMode,
+    // Show the lines the user sends in the output, dim italic (set with /localecho)
+    pub local_echo: bool,
+    // Expand :name: emoji shortcodes in typed lines (set with /emoji)
+    pub emoji_shortcodes: bool,
+    // Replace a misspelled word with its top suggestion when a space is typed after it
+    // (set with /autocorrect; Esc-r undoes the last one)
+    pub autocorrect: bool,
+    // Theme-file palette that remaps the 16 ANSI colors of this world's output; empty
+    // uses the theme's own (set with /palette, see theme.rs)
+    pub palette: String,
+    // Background color ("#rrggbb") behind this world's output, for MUDs whose color-coded
+    // UIs assume one; empty uses the theme's (set with /tint)
+    pub tint: String,
+    // Hunspell language ("de_DE") for spell checking input typed to this world; empty
+    // uses the global dictionary (set with /spelllang, see hunspell.rs)
+    pub spell_language: String,
+    // Named secrets for ${secret:NAME} references; shadow global secrets (set with /secret)
+    pub secrets: std::collections::BTreeMap<String, String>,
 }
 
 impl Default for WorldSettings {
@@ -1710,11 +2106,26 @@ impl Default for WorldSettings {
             user: String::new(),
             password: String::new(),
             use_ssl: false,
+            tls_verify: platform::TlsVerify::Tofu,
+            tls_client_cert: String::new(),
+            tls_client_key: String::new(),
+            ssh_host: String::new(),
+            ssh_user: String::new(),
+            ssh_key: String::new(),
             log_enabled: false,
+            log_format: logging::LogFormat::Raw,
+            log_timestamps: logging::LogTimestamps::Time,
+            log_session_markers: true,
             encoding: Encoding::Utf8,
             auto_connect_type: AutoConnectType::Connect,
+            login_script: String::new(),
+            connect_commands: String::new(),
             keep_alive_type: KeepAliveType::Nop,
             keep_alive_cmd: String::new(),
+            keep_alive_interval_secs: DEFAULT_KEEPALIVE_SECS,
+            silence_alert_mins: 0,
+            input_prefix: String::new(),
+            input_suffix: String::new(),
             slack_token: String::new(),
             slack_channel: String::new(),
             slack_workspace: String::new(),
@@ -1726,11 +2137,76 @@ impl Default for WorldSettings {
             gmcp_packages: "Client.Media 1".to_string(),
             auto_reconnect_secs: 0,
             auto_reconnect_on_web: false,
+            macros: std::collections::BTreeMap::new(),
+            numpad_walk: false,
+            low_priority: false,
+            timestamps: None,
+            notify_muted: false,
+            notify_sound: String::new(),
+            inline_images: false,
+            bell: bell::BellMode::Ignore,
+            local_echo: false,
+            emoji_shortcodes: false,
+            autocorrect: false,
+            palette: String::new(),
+            tint: String::new(),
+            spell_language: String::new(),
+            secrets: std::collections::BTreeMap::new(),
         }
     }
 }
 
 impl WorldSettings {
+    /// The On Connect commands: each non-blank line, sent as typed once login is done
+    pub(crate) fn connect_command_lines(&self) -> Vec<String> {
+        self.connect_commands.lines()
+            .map(|l| l.trim_end_matches('\r'))
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.to_string())
+            .collect()
+    }
+
+    /// The On Connect commands to send along with the connection's login: none for
+    /// `/worlds -l` (`skip_login`), and none yet when the login waits for the
+    /// server, which sends them once it's done
+    pub(crate) fn connect_commands_at_login(&self, skip_login: bool, has_credentials: bool) -> Vec<String> {
+        if skip_login || (has_credentials && self.login_waits_for_server()) {
+            return Vec::new();
+        }
+        self.connect_command_lines()
+    }
+
+    /// Lines the auto-login sends as soon as the connection is up
+    pub(crate) fn login_lines(&self, user: &str, password: &str) -> Vec<String> {
+        match self.auto_connect_type {
+            AutoConnectType::Connect => vec![format!("connect {} {}", user, password)],
+            AutoConnectType::Lines => vec![user.to_string(), password.to_string()],
+            AutoConnectType::Script => login::LoginScript::parse(&self.login_script, user, password)
+                .map(|script| script.on_connect)
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The `expect` steps of a Diku or Script login (None for the other kinds)
+    pub(crate) fn login_waits(&self, user: &str, password: &str) -> Result<Option<login::LoginScript>, String> {
+        let script = match self.auto_connect_type {
+            AutoConnectType::Diku => login::LoginScript::diku(user, password),
+            AutoConnectType::Script => login::LoginScript::parse(&self.login_script, user, password)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(script).filter(|s| !s.steps.is_empty()))
+    }
+
+    /// Whether the auto-login waits for the server's prompts
+    pub(crate) fn login_waits_for_server(&self) -> bool {
+        match self.auto_connect_type {
+            AutoConnectType::Prompt | AutoConnectType::MooPrompt | AutoConnectType::Diku => true,
+            AutoConnectType::Script => login::script_waits(&self.login_script),
+            _ => false,
+        }
+    }
+
     /// Check if this world has enough settings to attempt a connection
     fn has_connection_settings(&self) -> bool {
         match self.world_type {
@@ -1756,6 +2232,17 @@ impl WorldSettings {
         (secs, on_web)
     }
 
+    /// Parse a keepalive interval in seconds, raised to the minimum; anything that
+    /// isn't a number gives the default.
+    pub(crate) fn parse_keep_alive_interval(s: &str) -> u64 {
+        s.trim().parse::<u64>().map_or(DEFAULT_KEEPALIVE_SECS, |n| n.max(MIN_KEEPALIVE_SECS))
+    }
+
+    /// Idle time after which this world is sent a keepalive
+    pub(crate) fn keep_alive_interval(&self) -> Duration {
+        Duration::from_secs(self.keep_alive_interval_secs.max(MIN_KEEPALIVE_SECS))
+    }
+
     /// Reconstruct the display string from the two internal fields.
     fn auto_reconnect_display(&self) -> String {
         match (self.auto_reconnect_on_web, self.auto_reconnect_secs) {
@@ -1830,12 +2317,24 @@ pub enum Command {
     Quit,
     /// /reload - hot reload binary
     Reload,
+    /// /detach - leave the session running in the background (reattach with clay --attach)
+    Detach,
     /// /update - check for and install updates from GitHub
     Update { force: bool },
     /// /setup - show global settings popup
     Setup,
     /// /web - show web settings popup
     Web,
+    /// /web tokens [add <name>|revoke <name>] - list, issue or revoke device tokens
+    WebTokens { args: String },
+    /// /web bans - banned hosts, strikes and the WebSocket limits
+    WebBans,
+    /// /web viewer [<password>|off] - password for view-only web sign-in
+    WebViewer { args: String },
+    /// /web acme [<domain> [email]|renew|off] - Let's Encrypt certificate for the web server
+    WebAcme { args: String },
+    /// /web push [off] - Web Push subscriptions (browsers turn their own on with /web push on)
+    WebPush { args: String },
     /// /actions [world] - show actions popup, optionally filtered by world
     Actions { world: Option<String> },
     /// /connections or /l - show connected worlds list
@@ -1855,10 +2354,31 @@ pub enum Command {
     /// /connect host:port | host port | --close | --cancel - attach to/detach from a remote
     /// Clay server instance (relaunches this process as a remote client, or back to master)
     RemoteAttach { addr: String, close: bool, cancel: bool },
+    /// /connect telnet://host:port (or mud://) - open a MUD link in a world for that
+    /// address, added if none has it yet
+    OpenUri { uri: util::MudUri },
     /// /import host[:port] - download settings/theme/keybindings from another Clay instance
     Import { addr: String },
+    /// /import <file> [passphrase] - merge a /export file
+    ImportFile { args: String },
+    /// /export <file> [passphrase] - write worlds, actions and keybindings to a file
+    Export { args: String },
+    /// /actions export <file> [world] - write actions (those for the world) as a trigger pack
+    ActionsExport { args: String },
+    /// /actions import <file> - merge a trigger pack's actions
+    ActionsImport { args: String },
+    /// /trigger test [-w<world>] "<line>" - show what actions a sample line would fire
+    TriggerTest { args: String },
+    /// /lock [set <passphrase> | off] - lock world credentials, or set the master passphrase
+    Lock { args: String },
+    /// /unlock <passphrase> - unlock world credentials
+    Unlock { args: String },
     /// /disconnect or /dc - disconnect current world
     Disconnect,
+    /// /disconnect -a - disconnect every world
+    DisconnectAll,
+    /// /connect -a - connect every world with auto-login enabled
+    ConnectAll,
     /// /flush - clear output buffer for current world
     Flush,
     /// /menu - show menu popup to select windows/popups
@@ -1879,7 +2399,7 @@ pub enum Command {
     Unban { host: String },
     /// /testmusic - play a test ANSI music sequence
     TestMusic,
-    /// /dump - dump all scrollback buffers to ~/.clay/dump.log
+    /// /dump - dump all scrollback buffers to ~/.config/clay/dump.log
     Dump,
     /// /notify <message> - send notification to mobile clients
     Notify { message: String },
@@ -1898,6 +2418,64 @@ pub enum Command {
     EditList,
     /// /tag - toggle MUD tag display (same as F2)
     Tag,
+    /// /macro [key [text] | -d key] - manage per-world function key macros (no args = popup)
+    Macro { args: String },
+    /// /tagroute [list|add|del|on|off ...] - manage tag routing rules (no args = popup)
+    TagRoute { args: String },
+    /// /gag [list|on|off ...|[-w<world>] [-t] <pattern>] - manage the gag list (no args = popup)
+    Gag { args: String },
+    /// /ungag <n|pattern> - remove a gag
+    Ungag { args: String },
+    /// /profile [list|add|del ...] - manage time-scheduled setting profiles
+    Profile { args: String },
+    /// /config profile - show the config profile in use and list the others
+    Config { args: String },
+    /// /numpad [on|off] - toggle numpad speedwalk for a world
+    Numpad { args: String },
+    /// /secret [list|set|del ...] - manage secrets for ${secret:NAME} references
+    Secret { args: String },
+    /// /loopguard [N|off] - show or set the runaway trigger loop limit
+    LoopGuard { args: String },
+    /// /clipboard [osc52|<command>] - show or set how copy mode reaches the clipboard
+    Clipboard { args: String },
+    /// /throttle [on|off|interval <ms>] - batch a background world's output
+    Throttle { args: String },
+    /// /localecho [-w[<world>]] [on|off] - show sent lines in a world's output
+    LocalEcho { args: String },
+    /// /addword <word> - add a word to the personal spell check dictionary
+    AddWord { word: String },
+    /// /spellignore [<word>] - never flag a word (no word: list the ignored ones)
+    SpellIgnore { word: String },
+    /// /unignore <word> - spell check an ignored word again
+    Unignore { word: String },
+    /// /emoji [-w[<world>]] [on|off] - expand :name: shortcodes in typed lines
+    Emoji { args: String },
+    /// /autocorrect [-w[<world>]] [on|off] - correct misspelled words as a space is typed
+    Autocorrect { args: String },
+    /// /palette [-w[<world>]] [<name>|off] - remap a world's 16 ANSI colors
+    Palette { args: String },
+    /// /tint [-w[<world>]] [#rrggbb|off] - background color behind a world's output
+    Tint { args: String },
+    /// /images [-w[<world>]] [on|off] - web/GUI show image URLs in a world's output inline
+    Images { args: String },
+    /// /spelllang [-w[<world>]] [<language>|off] - Hunspell language for a world's input
+    SpellLang { args: String },
+    /// /urls - list recent URLs in the current world (popup in the console)
+    Urls,
+    /// /capture [list|clear|pane|height|view] - capture buffers fed by actions
+    Capture { args: String },
+    /// /split [-h|-v] <world>|focus|close|off - show several worlds at once (console)
+    Split { args: String },
+    /// /timestamps [-w[<world>]] [off|hm|hms|global] - time prefix on displayed lines
+    Timestamps { args: String },
+    /// /bell [-w[<world>]] [ignore|flash|ring|notify] - what a BEL in output does
+    Bell { args: String },
+    Log { args: String },
+    Replay { args: String },
+    /// /sendfile [-w[<world>]] <file> | abort - send a file to a world line by line
+    SendFile { args: String },
+    /// /shell <command> shows a program's output; /pipe <command> sends it (see shell.rs)
+    Shell { command: String, pipe: bool },
     /// /dict <word> - look up word definition
     Dict { word: String },
     /// /dict usage error
@@ -1916,6 +2494,8 @@ pub enum Command {
     TinyUrlUsage,
     /// /say <text> - speak text aloud via TTS
     Say { text: String },
+    /// /calc [-s] <expr> or /roll [-s] <dice> - evaluate locally, or send the result with -s
+    Calc { expr: String, roll: bool, send: bool },
     /// /window [world] - open a new GUI/web window, optionally locked to a world
     Window { world: Option<String> },
     /// /<action_name> [args] - execute action
@@ -1954,12 +2534,37 @@ pub fn parse_command(input: &str) -> Command {
         "/version" => Command::Version,
         "/quit" => Command::Quit,
         "/reload" => Command::Reload,
+        "/detach" => Command::Detach,
         "/update" => {
             let force = args.first().map(|a| *a == "-f" || *a == "--force").unwrap_or(false);
             Command::Update { force }
         }
         "/setup" => Command::Setup,
+        "/web" if args.first().is_some_and(|a| a.eq_ignore_ascii_case("tokens")) => {
+            Command::WebTokens { args: args[1..].join(" ") }
+        }
+        "/web" if args.len() == 1 && args[0].eq_ignore_ascii_case("bans") => Command::WebBans,
+        "/web" if args.first().is_some_and(|a| a.eq_ignore_ascii_case("viewer")) => {
+            Command::WebViewer { args: args[1..].join(" ") }
+        }
+        "/web" if args.first().is_some_and(|a| a.eq_ignore_ascii_case("acme")) => {
+            Command::WebAcme { args: args[1..].join(" ") }
+        }
+        "/web" if args.first().is_some_and(|a| a.eq_ignore_ascii_case("push")) => {
+            Command::WebPush { args: args[1..].join(" ") }
+        }
         "/web" => Command::Web,
+        "/trigger" if args.first().is_some_and(|a| a.eq_ignore_ascii_case("test")) => {
+            // The sample is kept as typed; any other /trigger is TF's
+            let rest = trimmed[parts[0].len()..].trim_start();
+            Command::TriggerTest { args: rest[args[0].len()..].trim().to_string() }
+        }
+        "/actions" if args.first().is_some_and(|a| a.eq_ignore_ascii_case("export")) => {
+            Command::ActionsExport { args: args[1..].join(" ") }
+        }
+        "/actions" if args.first().is_some_and(|a| a.eq_ignore_ascii_case("import")) => {
+            Command::ActionsImport { args: args[1..].join(" ") }
+        }
         "/actions" => {
             let world = if args.is_empty() {
                 None
@@ -1971,8 +2576,16 @@ pub fn parse_command(input: &str) -> Command {
         "/connections" | "/l" => Command::WorldsList,
         "/worlds" | "/world" => parse_world_command(args),
         "/__connect" => parse_connect_command(args),  // Internal use only (Connect buttons)
-        "/connect" => parse_remote_attach_command(args),
+        "/connect" if args.first() == Some(&"-a") => Command::ConnectAll,
+        "/connect" => match args.first().and_then(|arg| util::parse_mud_uri(arg)) {
+            Some(uri) => Command::OpenUri { uri },
+            None => parse_remote_attach_command(args),
+        },
         "/import" => parse_import_command(args),
+        "/export" => Command::Export { args: args.join(" ") },
+        "/lock" => Command::Lock { args: trimmed[parts[0].len()..].trim().to_string() },
+        "/unlock" => Command::Unlock { args: trimmed[parts[0].len()..].trim().to_string() },
+        "/disconnect" | "/dc" if args.first() == Some(&"-a") => Command::DisconnectAll,
         "/disconnect" | "/dc" => Command::Disconnect,
         "/flush" => Command::Flush,
         "/menu" => Command::Menu,
@@ -2005,13 +2618,7 @@ pub fn parse_command(input: &str) -> Command {
         }
         "/testmusic" => Command::TestMusic,
         "/dump" => Command::Dump,
-        "/notify" => {
-            if args.is_empty() {
-                Command::Unknown { cmd: trimmed.to_string() }
-            } else {
-                Command::Notify { message: args.join(" ") }
-            }
-        }
+        "/notify" => Command::Notify { message: args.join(" ") },
         "/addworld" => parse_addworld_command(args),
         "/note" => {
             if args.first() == Some(&"-l") {
@@ -2023,6 +2630,40 @@ pub fn parse_command(input: &str) -> Command {
             }
         }
         "/tag" | "/tags" => Command::Tag,
+        "/macro" | "/macros" => Command::Macro { args: trimmed[parts[0].len()..].trim().to_string() },
+        "/tagroute" | "/tagroutes" => Command::TagRoute { args: args.join(" ") },
+        "/gag" | "/gags" => Command::Gag { args: trimmed[parts[0].len()..].trim().to_string() },
+        "/ungag" => Command::Ungag { args: trimmed[parts[0].len()..].trim().to_string() },
+        "/profile" | "/profiles" => Command::Profile { args: args.join(" ") },
+        "/config" => Command::Config { args: args.join(" ") },
+        "/numpad" => Command::Numpad { args: args.join(" ") },
+        "/secret" | "/secrets" => Command::Secret { args: trimmed[parts[0].len()..].trim().to_string() },
+        "/loopguard" => Command::LoopGuard { args: args.join(" ") },
+        "/clipboard" => Command::Clipboard { args: trimmed[parts[0].len()..].trim().to_string() },
+        "/throttle" => Command::Throttle { args: args.join(" ") },
+        "/localecho" => Command::LocalEcho { args: args.join(" ") },
+        "/emoji" => Command::Emoji { args: args.join(" ") },
+        "/autocorrect" => Command::Autocorrect { args: args.join(" ") },
+        "/addword" => Command::AddWord { word: args.join(" ") },
+        "/spellignore" => Command::SpellIgnore { word: args.join(" ") },
+        "/unignore" => Command::Unignore { word: args.join(" ") },
+        "/palette" => Command::Palette { args: args.join(" ") },
+        "/tint" => Command::Tint { args: args.join(" ") },
+        "/images" => Command::Images { args: args.join(" ") },
+        "/spelllang" => Command::SpellLang { args: args.join(" ") },
+        "/urls" => Command::Urls,
+        "/capture" => Command::Capture { args: args.join(" ") },
+        "/split" => Command::Split { args: args.join(" ") },
+        "/timestamps" => Command::Timestamps { args: args.join(" ") },
+        "/bell" => Command::Bell { args: args.join(" ") },
+        "/log" => Command::Log { args: args.join(" ") },
+        "/replay" => Command::Replay { args: args.join(" ") },
+        "/sendfile" => Command::SendFile { args: args.join(" ") },
+        // The command line is kept as typed, spacing and quotes included
+        "/shell" | "/pipe" => Command::Shell {
+            command: trimmed.split_once(char::is_whitespace).map(|x| x.1).unwrap_or("").trim().to_string(),
+            pipe: cmd == "/pipe",
+        },
         "/dict" => {
             if !args.is_empty() {
                 Command::Dict { word: args.join(" ") }
@@ -2054,6 +2695,11 @@ pub fn parse_command(input: &str) -> Command {
                 Command::TinyUrlUsage
             }
         }
+        "/calc" | "/roll" => {
+            let send = args.first() == Some(&"-s");
+            let expr = args[send as usize..].join(" ");
+            Command::Calc { expr, roll: cmd == "/roll", send }
+        }
         "/say" => {
             if !args.is_empty() {
                 // Preserve the original text after /say (not split by whitespace)
@@ -2162,12 +2808,17 @@ fn parse_remote_attach_command(args: &[&str]) -> Command {
     Command::RemoteAttach { addr, close: false, cancel: false }
 }
 
-/// Parse /import command (download settings/theme/keybindings from another Clay instance)
+/// Parse /import command (download settings/theme/keybindings from another Clay instance,
+/// or merge a /export file — see `commands::is_settings_file_arg`)
 ///
 /// Formats:
 ///   /import host:port
 ///   /import host port
+///   /import <file> [passphrase]
 fn parse_import_command(args: &[&str]) -> Command {
+    if args.first().is_some_and(|arg| commands::is_settings_file_arg(arg)) {
+        return Command::ImportFile { args: args.join(" ") };
+    }
     let addr = match args.len() {
         0 => String::new(),
         1 => args[0].to_string(),
@@ -2337,7 +2988,7 @@ pub(crate) fn get_reload_state_path() -> PathBuf {
         .ok()
         .and_then(|s| s.parse::<u32>().ok())
         .unwrap_or(std::process::id());
-    clay_config_path(&format!("reload.{}", pid))
+    clay_state_path(&format!("reload.{}", pid))
 }
 
 /// Get current time as seconds since Unix epoch (for WebSocket timestamps)
@@ -2381,11 +3032,22 @@ pub struct OutputLine {
 /// Maximum characters per output line (prevents performance issues with extremely long lines)
 const MAX_LINE_LENGTH: usize = 10_000;
 
+/// Output lines kept per world (Scrollback Lines in /setup); older lines are evicted
+/// (still searchable with /recall -D when Archive Output is on)
+pub(crate) const DEFAULT_SCROLLBACK_LINES: usize = 50_000;
+pub(crate) const MIN_SCROLLBACK_LINES: usize = 1_000;
+pub(crate) const MAX_SCROLLBACK_LINES: usize = 1_000_000;
+
 /// Remote client backfill: phase 2 (background round-robin deep fill) chunk size.
 /// Kept small and round-robin (one chunk per world per cycle) rather than draining
 /// one world fully, so a world with deep history doesn't block others from filling.
 const BACKFILL_PHASE2_CHUNK_SIZE: usize = 200;
 
+/// Default idle time, in seconds, after which a connected world is sent a keepalive
+pub(crate) const DEFAULT_KEEPALIVE_SECS: u64 = 5 * 60;
+/// Shortest keepalive interval a world can set; keepalives are checked once a minute
+pub(crate) const MIN_KEEPALIVE_SECS: u64 = 60;
+
 impl OutputLine {
     /// Truncate text if it exceeds MAX_LINE_LENGTH to prevent performance issues
     fn truncate_if_needed(text: String) -> String {
@@ -2459,6 +3121,16 @@ impl OutputLine {
         Self { text: Self::truncate_if_needed(text), timestamp, from_server: true, gagged: false, seq, highlight_color: None, marked_new: false, from_archive: false }
     }
 
+    /// Local time of the line for the timestamp prefix (None when off)
+    fn format_clock(&self, mode: TimestampMode) -> Option<String> {
+        if mode == TimestampMode::Off {
+            return None;
+        }
+        let ts_secs = self.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+        let lt = local_time_from_epoch(ts_secs);
+        mode.format(lt.hour, lt.minute, lt.second)
+    }
+
     /// Format timestamp using a pre-computed "now" value for batch rendering
     fn format_timestamp_with_now(&self, _now: &CachedNow) -> String {
         let ts_secs = self.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
@@ -2481,19 +3153,23 @@ impl CachedNow {
 
 pub struct World {
     pub name: String,
-    pub output_lines: Vec<OutputLine>,
+    pub output_lines: std::collections::VecDeque<OutputLine>,
     pub scroll_offset: usize,
     pub connected: bool,
     pub command_tx: Option<mpsc::Sender<WriteCommand>>,
     pub unseen_lines: usize,
     pub paused: bool,
-    pub search_active: bool,  // true while the F5 search popup is open for this world
+    pub search_active: bool,  // true while the F5 search popup or copy mode holds this world's view
     pub pending_lines: Vec<OutputLine>,
     pub pending_count: usize, // For remote client mode: daemon's pending line count (not in pending_lines)
     pub lines_since_pause: usize,
     pub settings: WorldSettings,
-    log_handle: Option<std::sync::Arc<std::sync::Mutex<std::fs::File>>>,
+    log_handle: Option<logging::LogWriter>, // Open log file, written by its own task (see logging.rs)
     log_date: Option<String>,    // Current log file date (MMDDYY) for day rollover detection
+    log_override: logging::LogOverride, // /log start|stop until the world disconnects
+    replay_task: Option<tokio::task::JoinHandle<()>>, // Running /replay (see replay.rs)
+    pub send_file: Option<sendfile::SendFile>, // Running /sendfile (see sendfile.rs)
+    pub server_echo: bool, // Server sent IAC WILL ECHO: input is masked and kept out of history
     pub scrollback_tx: Option<std::sync::mpsc::SyncSender<scrollback::ArchiveEntry>>,
     #[cfg(unix)]
     socket_fd: Option<RawFd>,    // Store fd for hot reload (plain TCP only)
@@ -2506,11 +3182,14 @@ pub struct World {
     is_tls: bool,                // Track if using TLS
     telnet_mode: bool,           // True if telnet negotiation detected
     pub negotiated_encoding: Option<Encoding>, // Encoding negotiated via TELNET CHARSET (RFC 2066)
+    send_encoding: SharedEncoding,   // Outgoing encoding, shared with the connection's writer task
+    decode_carry: Vec<u8>,           // Start of a double-byte character split across reads
     pub prompt: String,              // Current prompt detected via telnet GA
     pub prompt_count: usize,         // Number of prompts received since connect (for auto-login)
     last_send_time: Option<std::time::Instant>, // For keepalive timing
     last_receive_time: Option<std::time::Instant>, // Last time server data was received
     last_nop_time: Option<std::time::Instant>,     // Last time NOP keepalive was sent
+    silence_alerted_for: Option<std::time::Instant>, // last_receive_time the silence watchdog last fired for
     last_user_command_time: Option<std::time::Instant>, // Last time user sent a command
     pub partial_line: String,        // Buffer for incomplete lines (no trailing newline)
     pub partial_in_pending: bool,    // True if partial_line is in pending_lines (vs output_lines)
@@ -2525,6 +3204,9 @@ pub struct World {
     needs_redraw: bool,          // True when terminal needs full redraw (after splash clear)
     pending_since: Option<std::time::Instant>, // When pending output first appeared (for Alt-w)
     pub first_unseen_at: Option<std::time::Instant>, // When unseen output first arrived (for Unseen First switching)
+    pub activity_notified: bool, // Activity notification shown since the terminal lost focus (see desktop_notify.rs)
+    pub push_sent_at: Option<std::time::Instant>, // Last Web Push activity notification, until the world is seen (see web_push.rs)
+    pub bell_count: usize, // BELs in output since the world was last viewed (see bell.rs)
     last_pending_broadcast: Option<std::time::Instant>, // Last time pending count was broadcast (for 2s timer)
     last_pending_count_broadcast: usize, // Last pending count that was broadcast (to detect changes)
     owner: Option<String>,       // Username who owns this world (multiuser mode)
@@ -2549,9 +3231,15 @@ pub struct World {
     pub visual_line_offset: usize, // When > 0, show only first N visual lines of scroll_offset line (partial display for more-mode)
     pub watchdog_history: std::collections::VecDeque<String>,  // Rolling window of recent lines (stripped) for /watchdog
     pub watchname_history: std::collections::VecDeque<String>, // Rolling window of first-words for /watchname
+    pub trigger_guard: trigger_guard::TriggerGuard, // Recent action fires, for the runaway loop watchdog
+    pub background_batch: throttle::BackgroundBatch, // Output held back while a low-priority world is in the background
     fansi_detect_until: Option<std::time::Instant>,  // FANSI client detection window (2s after connect)
-    fansi_login_pending: Option<String>,             // Deferred login command for FANSI worlds
+    fansi_login_pending: Option<Vec<String>>,        // Deferred login lines for FANSI worlds
+    login_progress: Option<login::LoginProgress>,    // Diku/Script login waits for this connection
     pub reconnect_at: Option<std::time::Instant>,   // When to auto-reconnect (None = no reconnect scheduled)
+    pub console_rows: std::cell::RefCell<line_cache::LineCache<String>>, // Rows drawn last frame by the console (see line_cache.rs)
+    pub pane_rows: std::cell::RefCell<line_cache::LineCache<rendering::StyledRow>>, // Rows drawn last frame by ratatui views
+    pub filter_index: filter_index::FilterIndex, // Plain text of output_lines for the F4 filter (see filter_index.rs)
 }
 
 impl World {
@@ -2561,9 +3249,9 @@ impl World {
 
     pub fn new_with_splash(name: &str, show_splash: bool) -> Self {
         let output_lines = if show_splash {
-            Self::generate_splash_lines()
+            Self::generate_splash_lines().into()
         } else {
-            Vec::new()
+            std::collections::VecDeque::new()
         };
         let scroll_offset = output_lines.len().saturating_sub(1);
         Self {
@@ -2581,17 +3269,24 @@ impl World {
             settings: WorldSettings::default(),
             log_handle: None,
             log_date: None,
+            log_override: logging::LogOverride::Setting,
+            replay_task: None,
+            send_file: None,
+            server_echo: false,
             scrollback_tx: None,
             socket_fd: None,
             proxy_socket_fd: None,
             is_tls: false,
             telnet_mode: false,
             negotiated_encoding: None,
+            send_encoding: SharedEncoding::default(),
+            decode_carry: Vec::new(),
             prompt: String::new(),
             prompt_count: 0,
             last_send_time: None,
             last_receive_time: None,
             last_nop_time: None,
+            silence_alerted_for: None,
             last_user_command_time: None,
             partial_line: String::new(),
             partial_in_pending: false,
@@ -2608,6 +3303,9 @@ impl World {
             first_unseen_at: None,
             last_pending_broadcast: None,
             last_pending_count_broadcast: 0,
+            activity_notified: false,
+            push_sent_at: None,
+            bell_count: 0,
             owner: None,
             proxy_pid: None,
             proxy_socket_path: None,
@@ -2629,10 +3327,16 @@ impl World {
             first_marked_new_index: None,
             visual_line_offset: 0,
             watchdog_history: std::collections::VecDeque::new(),
+            trigger_guard: trigger_guard::TriggerGuard::default(),
+            background_batch: throttle::BackgroundBatch::default(),
             watchname_history: std::collections::VecDeque::new(),
             fansi_detect_until: None,
             fansi_login_pending: None,
+            login_progress: None,
             reconnect_at: None,
+            console_rows: Default::default(),
+            pane_rows: Default::default(),
+            filter_index: Default::default(),
         }
     }
 
@@ -2644,26 +3348,39 @@ impl World {
 
     /// Get the path to the logs directory, creating it if needed
     fn get_logs_dir() -> std::path::PathBuf {
-        let logs_dir = clay_config_dir().join("logs");
+        let logs_dir = clay_data_dir().join("logs");
         if !logs_dir.exists() {
             let _ = std::fs::create_dir_all(&logs_dir);
         }
         logs_dir
     }
 
+    /// Whether output is logged: the Logging setting, unless /log said otherwise
+    fn logging_on(&self) -> bool {
+        match self.log_override {
+            logging::LogOverride::Setting => self.settings.log_enabled,
+            logging::LogOverride::Started(_) => true,
+            logging::LogOverride::Stopped => false,
+        }
+    }
+
     /// Get the full path to this world's log file for the current date
+    /// (or the file given to /log start)
     fn get_log_path(&self) -> std::path::PathBuf {
+        if let logging::LogOverride::Started(Some(ref path)) = self.log_override {
+            return path.clone();
+        }
         let date_str = Self::get_current_date_string();
         // Sanitize world name for use in filename (replace invalid chars with _)
         let safe_name: String = self.name.chars()
             .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
             .collect();
-        Self::get_logs_dir().join(format!("{}.{}.log", safe_name, date_str))
+        Self::get_logs_dir().join(format!("{}.{}.{}", safe_name, date_str, self.settings.log_format.extension()))
     }
 
     /// Open the log file for this world (creates logs directory if needed)
     fn open_log_file(&mut self) -> bool {
-        if !self.settings.log_enabled {
+        if !self.logging_on() {
             return false;
         }
 
@@ -2676,7 +3393,7 @@ impl World {
             .open(&log_path)
         {
             Ok(file) => {
-                self.log_handle = Some(std::sync::Arc::new(std::sync::Mutex::new(file)));
+                self.log_handle = Some(logging::LogWriter::start(file, self.settings.log_format));
                 self.log_date = Some(date_str);
                 true
             }
@@ -2684,12 +3401,59 @@ impl World {
         }
     }
 
-    /// Close the log file
+    /// Close the log file (its writer task flushes what is queued and exits)
     fn close_log_file(&mut self) {
         self.log_handle = None;
         self.log_date = None;
     }
 
+    /// Open the log file for a new connection, marking the session start
+    fn start_log_session(&mut self) -> bool {
+        if !self.open_log_file() {
+            return false;
+        }
+        self.write_session_marker(true);
+        true
+    }
+
+    /// Mark the session end and close the log file. /log start|stop end with it.
+    fn end_log_session(&mut self) {
+        self.write_session_marker(false);
+        self.close_log_file();
+        self.log_override = logging::LogOverride::Setting;
+    }
+
+    fn write_session_marker(&mut self, started: bool) {
+        if !self.settings.log_session_markers {
+            return;
+        }
+        if let Some(ref mut log) = self.log_handle {
+            log.write_line("", &logging::session_marker(started, &local_time_now()));
+        }
+    }
+
+    /// Send the On Connect commands once login is done
+    fn send_connect_commands(&self) {
+        if let Some(tx) = &self.command_tx {
+            for cmd in self.settings.connect_command_lines() {
+                let _ = tx.try_send(WriteCommand::Text(cmd));
+            }
+        }
+    }
+
+    /// Send the On Connect commands right after the connection's login was sent,
+    /// unless a prompt login or a deferred FANSI login sends them later
+    fn start_connect_commands(&self, skip_login: bool, has_credentials: bool) {
+        if self.fansi_login_pending.is_some() {
+            return;
+        }
+        if let Some(tx) = &self.command_tx {
+            for cmd in self.settings.connect_commands_at_login(skip_login, has_credentials) {
+                let _ = tx.try_send(WriteCommand::Text(cmd));
+            }
+        }
+    }
+
     /// Clear connection state when disconnecting
     /// Optionally removes the proxy socket file and clears the prompt
     fn clear_connection_state(&mut self, remove_socket: bool, clear_prompt: bool) {
@@ -2705,7 +3469,10 @@ impl World {
         self.connected = false;
         self.socket_fd = None;
         self.telnet_mode = false;
+        self.server_echo = false;
         self.negotiated_encoding = None;
+        self.sync_send_encoding();
+        self.decode_carry.clear();
         self.naws_enabled = false;
         self.naws_sent_size = None;
         self.reader_name = None;
@@ -2713,6 +3480,7 @@ impl World {
         self.skip_auto_login = false;
         self.fansi_detect_until = None;
         self.fansi_login_pending = None;
+        self.login_progress = None;
         // Clear timing fields so /connections doesn't show stale times
         self.last_send_time = None;
         self.last_receive_time = None;
@@ -2721,41 +3489,72 @@ impl World {
         // Clear active media tracking (processes already killed by stop_world_media)
         self.active_media.clear();
         if clear_prompt {
-            self.close_log_file();
+            self.end_log_session();
             self.prompt.clear();
         }
     }
 
+    /// True when the next line sent is likely a password: the server has echo off, or
+    /// the prompt or last line asks for one
+    pub fn at_password_prompt(&self) -> bool {
+        self.server_echo
+            || login::is_password_prompt(&strip_ansi_codes(&self.prompt))
+            || self.output_lines.back().is_some_and(|l| login::is_password_prompt(&strip_ansi_codes(&l.text)))
+    }
+
+    /// A line typed to this world with its emoji shortcodes expanded (with /emoji on) and
+    /// its input prefix and suffix added; an empty line or a password goes out as typed
+    /// (`/` commands never get here)
+    pub fn wrap_input(&self, text: &str) -> String {
+        if text.is_empty() || self.at_password_prompt() {
+            return text.to_string();
+        }
+        let text = if self.settings.emoji_shortcodes {
+            encoding::expand_emoji_shortcodes(text)
+        } else {
+            text.to_string()
+        };
+        format!("{}{}{}", self.settings.input_prefix, text, self.settings.input_suffix)
+    }
+
     /// Return the effective encoding for this world: negotiated charset if available, otherwise configured encoding.
     pub fn effective_encoding(&self) -> Encoding {
         self.negotiated_encoding.unwrap_or(self.settings.encoding)
     }
 
+    /// Encode outgoing lines with the effective encoding from now on, on the open
+    /// connection too (call after changing either encoding)
+    pub fn sync_send_encoding(&self) {
+        self.send_encoding.set(self.effective_encoding());
+    }
+
+    /// The outgoing encoding for a new connection's writer task
+    pub fn writer_encoding(&self) -> SharedEncoding {
+        self.sync_send_encoding();
+        self.send_encoding.clone()
+    }
+
     /// Write a line to the log file with timestamp prefix
     /// Handles day rollover (opens new file if date changed)
     fn write_log_line(&mut self, line: &str) {
-        if !self.settings.log_enabled {
+        if !self.logging_on() {
             return;
         }
 
-        // Check for day rollover
+        // Check for day rollover (not for a /log start file), or a change of log format
         let current_date = Self::get_current_date_string();
-        if self.log_date.as_ref() != Some(&current_date) {
-            // Date changed, close old file and open new one
+        let dated = !matches!(self.log_override, logging::LogOverride::Started(Some(_)));
+        let format_changed = self.log_handle.as_ref().is_some_and(|l| l.format() != self.settings.log_format);
+        if (dated && self.log_date.as_ref() != Some(&current_date)) || format_changed || self.log_handle.is_none() {
+            // Date or format changed, close old file and open new one
             self.close_log_file();
             if !self.open_log_file() {
                 return;
             }
         }
 
-        if let Some(ref handle) = self.log_handle {
-            if let Ok(mut file) = handle.lock() {
-                let lt = local_time_now();
-
-                // Format: [HH:MM:SS] line
-                let _ = writeln!(file, "[{:02}:{:02}:{:02}] {}",
-                    lt.hour, lt.minute, lt.second, line);
-            }
+        if let Some(ref mut log) = self.log_handle {
+            log.write_line(&self.settings.log_timestamps.stamp(&local_time_now()), line);
         }
     }
 
@@ -2836,7 +3635,7 @@ impl World {
                 if partial_was_in_pending {
                     self.pending_lines.pop();
                 } else {
-                    self.output_lines.pop();
+                    self.output_lines.pop_back();
                     // Invalidate tracked index if we popped the last marked_new line
                     if let Some(idx) = self.first_marked_new_index {
                         if self.output_lines.len() <= idx {
@@ -2850,7 +3649,7 @@ impl World {
                     if let Some(last) = self.pending_lines.last_mut() {
                         last.text = completed_line.to_string();
                     }
-                } else if let Some(last) = self.output_lines.last_mut() {
+                } else if let Some(last) = self.output_lines.back_mut() {
                     last.text = completed_line.to_string();
                 }
             }
@@ -2943,7 +3742,7 @@ impl World {
                 if !is_current && self.first_marked_new_index.is_none() {
                     self.first_marked_new_index = Some(self.output_lines.len());
                 }
-                self.output_lines.push(new_line);
+                self.output_lines.push_back(new_line);
                 self.lines_since_pause += visual_lines;
                 if !is_current {
                     if self.unseen_lines == 0 && self.first_unseen_at.is_none() {
@@ -2970,7 +3769,7 @@ impl World {
                 if !is_current && self.first_marked_new_index.is_none() {
                     self.first_marked_new_index = Some(self.output_lines.len());
                 }
-                self.output_lines.push(new_line);
+                self.output_lines.push_back(new_line);
                 self.lines_since_pause += visual_lines;
                 if !is_current {
                     // Track when first unseen output arrived
@@ -2996,7 +3795,7 @@ impl World {
                         self.first_marked_new_index = Some(self.output_lines.len());
                     }
                 }
-                self.output_lines.append(&mut self.pending_lines);
+                self.output_lines.extend(self.pending_lines.drain(..));
             }
         }
         // Always scroll to bottom unless paused (and more mode is on), or search is holding the view
@@ -3017,7 +3816,7 @@ impl World {
             }
             self.pending_lines.push(line);
         } else {
-            self.output_lines.push(line);
+            self.output_lines.push_back(line);
             self.scroll_to_bottom();
         }
     }
@@ -3072,7 +3871,9 @@ impl World {
 
     pub fn mark_seen(&mut self) {
         self.unseen_lines = 0;
+        self.bell_count = 0;
         self.first_unseen_at = None;
+        self.push_sent_at = None;
         // Note: marked_new indicators are NOT cleared here. They persist while
         // viewing the world and are only cleared when switching AWAY from it
         // (via clear_new_line_indicators in switch_world).
@@ -3083,7 +3884,7 @@ impl World {
     /// Only iterates from the first marked_new line to avoid O(n) scan of large buffers.
     pub fn clear_new_line_indicators(&mut self) {
         if let Some(first) = self.first_marked_new_index {
-            for line in &mut self.output_lines[first..] {
+            for line in self.output_lines.range_mut(first..) {
                 line.marked_new = false;
             }
             self.first_marked_new_index = None;
@@ -3137,7 +3938,7 @@ impl World {
             if line.marked_new && self.first_marked_new_index.is_none() {
                 self.first_marked_new_index = Some(self.output_lines.len());
             }
-            self.output_lines.push(line);
+            self.output_lines.push_back(line);
         }
         if self.pending_lines.is_empty() {
             self.paused = false;
@@ -3160,7 +3961,7 @@ impl World {
         if self.first_marked_new_index.is_none() && self.pending_lines.iter().any(|l| l.marked_new) {
             self.first_marked_new_index = Some(self.output_lines.len());
         }
-        self.output_lines.append(&mut self.pending_lines);
+        self.output_lines.extend(self.pending_lines.drain(..));
         self.paused = false;
         self.lines_since_pause = 0;
         self.pending_since = None; // Clear pending timestamp
@@ -3172,6 +3973,23 @@ impl World {
         self.scroll_to_bottom();
     }
 
+    /// Evict the oldest output lines beyond `max_lines`, keeping the view on the same
+    /// lines (a view on an evicted line moves to the oldest kept). Returns how many
+    /// lines were evicted.
+    pub fn trim_scrollback(&mut self, max_lines: usize) -> usize {
+        let excess = self.output_lines.len().saturating_sub(max_lines.max(1));
+        if excess == 0 {
+            return 0;
+        }
+        self.output_lines.drain(..excess);
+        self.scroll_offset = self.scroll_offset.saturating_sub(excess);
+        if self.scroll_offset == 0 {
+            self.visual_line_offset = 0;
+        }
+        self.first_marked_new_index = self.first_marked_new_index.map(|i| i.saturating_sub(excess));
+        excess
+    }
+
     /// Filter output to hide client-generated lines (mark them as gagged instead of removing).
     /// They become visible again with F2 (show_tags mode).
     fn filter_to_server_output(&mut self) {
@@ -3214,8 +4032,7 @@ impl World {
             self.output_lines.len().saturating_sub(1).saturating_sub(self.scroll_offset)
         } else {
             // Count only non-gagged lines after scroll_offset
-            self.output_lines[(self.scroll_offset + 1)..]
-                .iter()
+            self.output_lines.range((self.scroll_offset + 1)..)
                 .filter(|l| !l.gagged)
                 .count()
         }
@@ -3257,15 +4074,32 @@ pub struct App {
     pub output_height: u16,
     pub output_width: u16,
     pub spell_checker: SpellChecker,
+    // Per-world language checkers (/spelllang), loaded on first use; None when the
+    // language has no dictionary here
+    pub spell_languages: std::collections::HashMap<String, Option<SpellChecker>>,
+    // Words spell check skips (/spellignore, and Esc-i for this session)
+    pub spell_ignore: IgnoreList,
     pub spell_state: SpellState,
     pub last_input_was_delete: bool, // Track if last input action was backspace/delete (for spell check)
     pub skip_temp_conversion: Option<String>, // Temperature to skip re-converting (after user undid conversion)
     pub cached_misspelled: Vec<(usize, usize)>, // Cached misspelled word ranges (char positions)
     pub suggestion_message: Option<String>,
+    pub last_autocorrect: Option<(usize, String, String)>, // Last /autocorrect change: (char position, typed word, correction)
+    pub skip_autocorrect: Option<String>, // Word to leave alone (after the user undid its correction)
     pub settings: Settings,
     pub confirm_dialog: ConfirmDialog,
     pub filter_popup: FilterPopup,
     pub search_popup: SearchPopup,
+    /// tmux-style selection over the scrollback (see copy_mode.rs)
+    pub copy_mode: copy_mode::CopyMode,
+    /// Where the world tab bar's tabs were drawn, for mouse clicks (see tab_bar.rs)
+    pub tab_bar_layout: tab_bar::TabLayout,
+    /// World whose tab the left button went down on, for drag-to-reorder
+    pub tab_drag_from: Option<usize>,
+    /// Capture buffers and the capture pane shown below the output (see capture.rs)
+    pub panes: capture::PaneManager,
+    /// Worlds shown side by side or stacked in the output area (see split.rs)
+    pub split: split::SplitView,
     /// Split-screen text editor for notes and files
     pub editor: EditorState,
     /// New unified popup manager (gradual migration from old popup types)
@@ -3274,13 +4108,17 @@ pub struct App {
     pub last_escape: Option<std::time::Instant>, // For Escape+key sequences (Alt emulation)
     pub literal_next: bool, // When true, next keypress inserts literally (Ctrl+V)
     pub show_tags: bool, // F2 toggles - false = hide tags (default), true = show tags
+    /// Runtime timestamp toggle (toggle_timestamps key); not saved
+    pub timestamps_toggled: bool,
     pub highlight_actions: bool, // F8 toggles - highlight lines matching action patterns
     // WebSocket server (ws:// or wss:// depending on web_secure setting)
     pub ws_server: Option<WebSocketServer>,
-    /// Plaintext auth key, shared with SecurityGate.auth_key for accept-time knock
-    /// verification (Phase 3). Mirrors `settings.websocket_auth_key`; kept in sync by
-    /// settings load/reload and by handle_ws_key_request/handle_ws_key_revoke.
-    pub ws_auth_key_shared: Arc<std::sync::RwLock<Option<String>>>,
+    /// Knock secrets, shared with SecurityGate.auth_keys for accept-time knock
+    /// verification (Phase 3): `settings.websocket_auth_key` (as is and its SHA-256)
+    /// and the device tokens' SHA-256. Kept in sync by sync_auth_keys_shared().
+    pub ws_auth_keys_shared: Arc<std::sync::RwLock<Vec<String>>>,
+    /// Device token (by name) each WS client signed in with, so revoking it signs them out
+    pub ws_client_tokens: std::collections::HashMap<u64, String>,
     // HTTP web interface server (no TLS)
     pub http_server: Option<HttpServer>,
     // HTTPS web interface server
@@ -3293,6 +4131,9 @@ pub struct App {
     /// Cache of each WS client's view state (for activity indicator and more-mode)
     /// Maps client_id -> ClientViewState (world_index + visible_lines)
     pub ws_client_worlds: std::collections::HashMap<u64, ClientViewState>,
+    /// Each WS client's own unseen line count per world (client_id -> world index -> lines),
+    /// so one client reading a world doesn't clear its activity for the others
+    pub ws_client_unseen: std::collections::HashMap<u64, std::collections::HashMap<usize, usize>>,
     /// True if this is the master client (runs WS server or WS disabled).
     /// Only master should save settings or initiate connections.
     pub is_master: bool,
@@ -3300,14 +4141,32 @@ pub struct App {
     pub web_reconnect_needed: bool,
     /// Set to true when http_port/http_enabled/web_secure changes, to restart the HTTP server.
     pub web_restart_needed: bool,
+    /// A Let's Encrypt order is running (see App::start_acme_if_due)
+    pub acme_in_progress: bool,
+    /// After a failed Let's Encrypt attempt, when to try again unasked
+    pub acme_retry_at: Option<std::time::Instant>,
     /// True if this session started from a hot reload (suppress server startup messages)
     pub is_reload: bool,
     /// True if the output area needs to be redrawn (optimization to avoid unnecessary redraws)
     pub needs_output_redraw: bool,
     /// True if terminal needs full clear (for Ctrl+L redraw in --console mode)
     pub needs_terminal_clear: bool,
+    /// Output rows drawn by ratatui rather than crossterm last frame; when ratatui takes
+    /// more of them over (capture pane, split panes) ui() sets `layout_reclaim` so the
+    /// frame is redrawn from a cleared screen
+    pub ratatui_output_rows: u16,
+    pub layout_reclaim: bool,
+    /// A console TUI is running in this process (master or remote console, not a
+    /// daemon), so desktop notifications have a desktop to go to
+    pub console_active: bool,
+    /// The terminal window has focus, as last reported (assumed until told otherwise)
+    pub terminal_focused: bool,
+    /// Status bar shown inverted until then, after a bell (see bell.rs)
+    pub bell_flash_until: Option<std::time::Instant>,
     /// True if mouse capture is currently active in the terminal
     pub mouse_capture_active: bool,
+    /// Whether keypad keys are being reported distinctly (numpad speedwalk)
+    pub keypad_mode_active: bool,
     /// True if running in multiuser mode (--multiuser flag)
     pub multiuser_mode: bool,
     /// User accounts (multiuser mode only)
@@ -3318,9 +4177,13 @@ pub struct App {
     pub user_connections: std::collections::HashMap<(usize, String), UserConnection>,
     /// TinyFugue scripting engine
     pub tf_engine: tf::TfEngine,
-    /// Loaded theme colors from ~/.clay/theme.dat
+    /// Loaded theme colors from ~/.config/clay/theme.dat
     pub theme_file: theme::ThemeFile,
-    /// Configurable keyboard bindings (TF defaults + user customizations from ~/.clay/keybindings.dat)
+    /// Colors the console's terminal shows; server colors beyond it are downgraded
+    pub color_depth: color_depth::ColorDepth,
+    /// The console's terminal draws curly underlines (misspelled input)
+    pub undercurl: bool,
+    /// Configurable keyboard bindings (TF defaults + user customizations from ~/.config/clay/keybindings.dat)
     pub keybindings: keybindings::KeyBindings,
     /// Remote client mode: WebSocket transmitter for sending commands to server
     pub ws_client_tx: Option<mpsc::UnboundedSender<WsMessage>>,
@@ -3328,6 +4191,10 @@ pub struct App {
     pub pending_update: Option<bool>,
     /// Remote client mode: pending /reload request (re-exec local binary)
     pub pending_reload: bool,
+    /// Remote client mode: a bare /gag asked for the gag list; open the popup when it comes
+    pub gags_popup_requested: bool,
+    /// Remote client mode: a bare /tagroute asked for the route list; open the popup when it comes
+    pub tag_routes_popup_requested: bool,
     /// Master mode: pending /connect confirmation (target addr, requested at). Cleared on
     /// confirm/cancel or when superseded by a different target.
     pub pending_remote_connect: Option<(String, std::time::Instant)>,
@@ -3339,6 +4206,10 @@ pub struct App {
     pub pending_console_import: Option<(String, Option<String>, Option<String>)>,
     /// Remote client mode: pending /connect --close request (re-exec as independent master)
     pub pending_remote_detach: bool,
+    /// Master mode: a /connect -a still waiting on background connects
+    pub bulk_connect: Option<BulkConnect>,
+    /// The quit confirmation was answered Yes, so the next /quit goes ahead
+    pub quit_confirmed: bool,
     /// Remote client mode: pending /connect host:port request (re-exec attached elsewhere)
     pub pending_remote_switch: Option<String>,
     /// Activity count from server (used in remote client mode, i.e. --console)
@@ -3388,6 +4259,9 @@ pub struct App {
     pub tts_backend: tts::TtsBackend,
     /// Long-term scrollback archive (SQLite). Present only when scrollback_enabled.
     pub scrollback: Option<scrollback::ScrollbackDb>,
+    /// Name of the scheduled profile currently in effect (see profiles.rs).
+    /// Remote clients mirror it from ActiveProfileChanged for their status bar.
+    pub active_profile: Option<String>,
     /// Test-only: log of all messages passed to ws_broadcast() and ws_broadcast_to_world()
     #[cfg(test)]
     pub ws_broadcast_log: std::sync::Arc<std::sync::Mutex<Vec<WsMessage>>>,
@@ -3404,6 +4278,29 @@ enum WsAsyncAction {
     Disconnect { world_index: usize, prev_index: usize },
     /// Need to trigger hot reload (exec_reload).
     Reload,
+    /// Need to run /connect -a.
+    ConnectAll,
+}
+
+/// Tally of a /connect -a, reported once every world's attempt has finished
+#[derive(Debug, Default)]
+pub struct BulkConnect {
+    /// Worlds whose connect is still running in the background
+    pub pending: Vec<String>,
+    pub connected: usize,
+    pub failed: Vec<String>,
+}
+
+impl BulkConnect {
+    pub fn summary(&self) -> String {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        if self.failed.is_empty() {
+            format!("Connected {} world{}.", self.connected, plural(self.connected))
+        } else {
+            format!("Connected {} world{}; {} failed: {}.", self.connected, plural(self.connected),
+                self.failed.len(), self.failed.join(", "))
+        }
+    }
 }
 
 impl App {
@@ -3418,24 +4315,35 @@ impl App {
             output_height: 20, // Will be updated by ui()
             output_width: 80,  // Will be updated by ui()
             spell_checker: SpellChecker::new(""),
+            spell_languages: std::collections::HashMap::new(),
+            spell_ignore: IgnoreList::load(clay_config_path(spell::IGNORE_LIST)),
             spell_state: SpellState::new(),
             last_input_was_delete: false,
             skip_temp_conversion: None,
             cached_misspelled: Vec::new(),
             suggestion_message: None,
+            last_autocorrect: None,
+            skip_autocorrect: None,
             settings: Settings::default(),
             confirm_dialog: ConfirmDialog::new(),
             filter_popup: FilterPopup::new(),
             search_popup: SearchPopup::new(),
+            copy_mode: copy_mode::CopyMode::default(),
+            tab_bar_layout: tab_bar::TabLayout::default(),
+            tab_drag_from: None,
+            panes: capture::PaneManager::default(),
+            split: split::SplitView::default(),
             editor: EditorState::new(),
             popup_manager: popup::PopupManager::new(),
             last_ctrl_c: None,
             last_escape: None,
             literal_next: false,
             show_tags: false, // Default: hide tags
+            timestamps_toggled: false,
             highlight_actions: false, // Default: don't highlight action matches
             ws_server: None,
-            ws_auth_key_shared: Arc::new(std::sync::RwLock::new(None)),
+            ws_auth_keys_shared: Arc::new(std::sync::RwLock::new(Vec::new())),
+            ws_client_tokens: std::collections::HashMap::new(),
             http_server: None,
             #[cfg(feature = "native-tls-backend")]
             https_server: None,
@@ -3443,26 +4351,41 @@ impl App {
             https_server: None,
             popup_was_visible: false,
             ws_client_worlds: std::collections::HashMap::new(),
+            ws_client_unseen: std::collections::HashMap::new(),
             is_master: true, // Console app is always master (remote GUI is separate execution path)
             web_reconnect_needed: false,
             web_restart_needed: false,
+            acme_in_progress: false,
+            acme_retry_at: None,
             is_reload: false, // Set to true in run_app if started from hot reload
             needs_output_redraw: true, // Start with true to ensure initial render
             needs_terminal_clear: false, // Set to true by Ctrl+L in --console mode
+            ratatui_output_rows: 0,
+            layout_reclaim: false,
+            console_active: false,
+            terminal_focused: true,
+            bell_flash_until: None,
             mouse_capture_active: false, // Toggled dynamically when popups open/close
+            keypad_mode_active: false,
             multiuser_mode: false, // Set to true in main if started with --multiuser
             users: Vec::new(),
             ban_list: BanList::new(),
             user_connections: std::collections::HashMap::new(),
             tf_engine: tf::TfEngine::new(),
             theme_file: theme::ThemeFile::with_defaults(),
+            color_depth: color_depth::ColorDepth::TrueColor,
+            undercurl: false,
             keybindings: keybindings::KeyBindings::tf_defaults(),
             ws_client_tx: None, // Set when running as remote client (--console mode)
             pending_update: None,
             pending_reload: false,
+            gags_popup_requested: false,
+            tag_routes_popup_requested: false,
             pending_remote_connect: None,
             pending_console_import: None,
             pending_remote_detach: false,
+            bulk_connect: None,
+            quit_confirmed: false,
             pending_remote_switch: None,
             server_activity_count: 0, // Activity count from server (remote client mode)
             backfill_phase: 1,
@@ -3484,6 +4407,7 @@ impl App {
             remote_ping_nonce: 0,
             tts_backend: tts::init_tts(),
             scrollback: None,
+            active_profile: None,
             #[cfg(test)]
             ws_broadcast_log: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
         }
@@ -3519,6 +4443,22 @@ impl App {
         self.theme_file.get(self.settings.theme.name())
     }
 
+    /// The palette remapping a world's ANSI colors (/palette), if it has one
+    pub fn world_palette(&self, world_idx: usize) -> Option<theme::Palette> {
+        self.worlds.get(world_idx).and_then(|w| self.theme_file.palette(&w.settings.palette)).copied()
+    }
+
+    /// The background color behind a world's output (/tint), if it has one
+    pub fn world_tint(&self, world_idx: usize) -> Option<theme::ThemeColor> {
+        self.worlds.get(world_idx).and_then(|w| theme::ThemeColor::from_hex(&w.settings.tint))
+    }
+
+    /// The prompt shown before the input: the reverse search prompt while Ctrl+R is
+    /// active, otherwise the current world's prompt
+    pub fn input_prompt(&self) -> String {
+        self.input.reverse_search_prompt().unwrap_or_else(|| self.current_world().prompt.clone())
+    }
+
     /// Get theme colors for the current GUI theme
     pub fn gui_theme_colors(&self) -> &theme::ThemeColors {
         self.theme_file.get(self.settings.gui_theme.name())
@@ -3569,9 +4509,46 @@ impl App {
             tts_speak_mode: self.settings.tts_speak_mode.name().to_string(),
             scrollback_enabled: self.settings.scrollback_enabled,
             theme_colors_json: self.gui_theme_colors().to_json(),
+            palettes_json: self.theme_file.palettes_json(),
             keybindings_json: self.keybindings.to_json(),
             auth_key: self.settings.websocket_auth_key.as_ref().map(|ak| ak.key.clone()).unwrap_or_default(),
             ws_password: self.settings.websocket_password.clone(),
+            active_profile: self.active_profile.clone().unwrap_or_default(),
+            status_format: self.settings.status_format.clone(),
+            tab_bar: self.settings.tab_bar,
+            timestamps: self.settings.timestamps.name().to_string(),
+            notify_activity: self.settings.notify_activity,
+            scrollback_lines: self.settings.scrollback_lines,
+            confirm_quit: self.settings.confirm_quit,
+            acme_domain: self.settings.acme_domain.clone(),
+            acme_email: self.settings.acme_email.clone(),
+            web_bind_addr: self.settings.web_bind_addr.clone(),
+            web_unix_socket: self.settings.web_unix_socket.clone(),
+            web_trusted_proxies: self.settings.web_trusted_proxies.clone(),
+            push_public_key: web_push::public_key(),
+        }
+    }
+
+    /// Remote console: show world `idx` and tell the server it has been seen
+    /// Timestamp prefix for a world's displayed lines: its own setting, else the global
+    /// one, inverted by the runtime toggle
+    pub(crate) fn timestamp_mode(&self, world_idx: usize) -> TimestampMode {
+        let mode = self.worlds.get(world_idx)
+            .and_then(|w| w.settings.timestamps)
+            .unwrap_or(self.settings.timestamps);
+        if self.timestamps_toggled { mode.toggled() } else { mode }
+    }
+
+    pub(crate) fn view_remote_world(&mut self, idx: usize) {
+        if idx < self.worlds.len() {
+            self.current_world_index = idx;
+            // Clear unseen for the world we're switching to
+            self.worlds[idx].unseen_lines = 0;
+            self.needs_output_redraw = true;
+            // Send MarkWorldSeen to notify server
+            if let Some(ref tx) = self.ws_client_tx {
+                let _ = tx.send(WsMessage::MarkWorldSeen { world_index: idx });
+            }
         }
     }
 
@@ -3625,8 +4602,7 @@ impl App {
             Some(AuthKey::new(settings.auth_key.clone()))
         };
         // Keep the shared auth-key mirror in sync (same invariant as persistence.rs load).
-        *self.ws_auth_key_shared.write().unwrap() =
-            self.settings.websocket_auth_key.as_ref().map(|ak| ak.key.clone());
+        self.sync_auth_keys_shared();
         self.settings.tls_proxy_enabled = settings.tls_proxy_enabled;
         self.settings.dictionary_path = settings.dictionary_path.clone();
         self.settings.mouse_enabled = settings.mouse_enabled;
@@ -3640,6 +4616,24 @@ impl App {
             self.settings.tts_muted = false;
         }
         self.settings.scrollback_enabled = settings.scrollback_enabled;
+        self.settings.status_format = settings.status_format.clone();
+        self.settings.tab_bar = settings.tab_bar;
+        self.settings.timestamps = TimestampMode::from_name(&settings.timestamps).unwrap_or_default();
+        self.settings.notify_activity = settings.notify_activity;
+        self.settings.confirm_quit = settings.confirm_quit;
+        self.settings.acme_domain = settings.acme_domain.clone();
+        self.settings.acme_email = settings.acme_email.clone();
+        self.settings.web_bind_addr = settings.web_bind_addr.clone();
+        self.settings.web_unix_socket = settings.web_unix_socket.clone();
+        self.settings.web_trusted_proxies = settings.web_trusted_proxies.clone();
+        if settings.scrollback_lines > 0 {
+            self.settings.scrollback_lines = settings.scrollback_lines;
+        }
+        self.active_profile = Some(settings.active_profile.clone()).filter(|p| !p.is_empty());
+        // Palettes come from the master's theme file
+        if !settings.palettes_json.is_empty() {
+            self.theme_file.set_palettes_json(&settings.palettes_json);
+        }
         // Sync keybindings from master
         if !settings.keybindings_json.is_empty() {
             self.keybindings = keybindings::KeyBindings::from_json(&settings.keybindings_json);
@@ -3671,7 +4665,7 @@ impl App {
             // Add splash to current world if it has no output yet
             let current = &mut self.worlds[self.current_world_index];
             if current.output_lines.is_empty() && !current.connected {
-                current.output_lines = World::generate_splash_lines();
+                current.output_lines = World::generate_splash_lines().into();
                 current.showing_splash = true;
                 current.scroll_offset = current.output_lines.len().saturating_sub(1);
             }
@@ -3725,6 +4719,11 @@ impl App {
             });
         }
 
+        // Sync built-in action bindings (for /bind conflict reporting)
+        self.tf_engine.action_bindings = self.keybindings.bindings.iter()
+            .map(|(key, action)| (canonical_to_tf_key_name(key), action.clone()))
+            .collect();
+
         // Sync keyboard buffer state
         self.tf_engine.keyboard_state = tf::KeyboardBufferState {
             buffer: self.input.buffer.clone(),
@@ -3836,9 +4835,26 @@ impl App {
         }
     }
 
+    /// Whether quitting should ask first: this instance owns connected worlds and
+    /// Confirm Quit is on
+    pub(crate) fn quit_needs_confirm(&self) -> bool {
+        self.is_master && self.settings.confirm_quit && !self.quit_confirmed
+            && self.worlds.iter().any(|w| w.connected)
+    }
+
+    /// Open the quit confirmation listing the connected worlds. Defaults to "No".
+    pub(crate) fn open_quit_confirm(&mut self) {
+        use popup::definitions::confirm::{create_quit_dialog, CONFIRM_BTN_NO};
+        let connected: Vec<String> = self.worlds.iter().filter(|w| w.connected).map(|w| w.name.clone()).collect();
+        self.popup_manager.open(create_quit_dialog(&connected));
+        if let Some(state) = self.popup_manager.current_mut() {
+            state.select_button(CONFIRM_BTN_NO);
+        }
+    }
+
     /// Open a warning dialog for a TLS certificate pin mismatch (trust-on-first-use).
     /// Called when a MUD world's certificate no longer matches the fingerprint
-    /// pinned in ~/.clay/known_hosts.dat. Defaults to "No" (keep blocking) for safety.
+    /// pinned in ~/.config/clay/known_hosts.dat. Defaults to "No" (keep blocking) for safety.
     fn open_cert_mismatch_confirm(&mut self, world_index: usize, mismatch: &platform::danger::CertMismatch) {
         use popup::definitions::confirm::{create_cert_mismatch_dialog, CONFIRM_BTN_NO};
         let def = create_cert_mismatch_dialog(world_index, &mismatch.host, &mismatch.old_fingerprint, &mismatch.new_fingerprint);
@@ -3860,6 +4876,7 @@ impl App {
                 user: w.settings.user.clone(),
                 is_connected: w.connected,
                 is_current: i == self.current_world_index,
+                bell_count: w.bell_count,
             }
         }).collect();
 
@@ -3935,6 +4952,43 @@ impl App {
         }
     }
 
+    /// URLs in `world_idx`'s displayed output, newest first, without duplicates
+    pub(crate) fn recent_urls(&self, world_idx: usize, limit: usize) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        let Some(world) = self.worlds.get(world_idx) else { return urls };
+        for line in world.output_lines.iter().rev().filter(|l| !l.gagged) {
+            if !line.text.contains("http") {
+                continue;
+            }
+            // Within a line, later URLs are newer too
+            for url in encoding::find_urls(&util::strip_ansi_codes(&line.text)).into_iter().rev() {
+                if !urls.contains(&url) {
+                    urls.push(url);
+                    if urls.len() >= limit {
+                        return urls;
+                    }
+                }
+            }
+        }
+        urls
+    }
+
+    /// Open the /urls popup listing recent URLs in the current world's output
+    pub(crate) fn open_urls_popup(&mut self) {
+        use popup::definitions::urls::{create_urls_popup, URLS_FIELD_LIST};
+
+        let urls = self.recent_urls(self.current_world_index, 50);
+        if urls.is_empty() {
+            self.add_output("No URLs in this world's output.");
+            return;
+        }
+        let visible_height = 12.min(urls.len());
+        self.popup_manager.open(create_urls_popup(&urls, visible_height));
+        if let Some(state) = self.popup_manager.current_mut() {
+            state.select_field(URLS_FIELD_LIST);
+        }
+    }
+
     /// Open the notes list popup showing worlds with notes
     fn open_notes_list_popup(&mut self) {
         use popup::definitions::notes_list::{create_notes_list_popup, NoteInfo, NOTES_FIELD_LIST};
@@ -3978,6 +5032,74 @@ impl App {
         }
     }
 
+    /// Open the current world's function key macros popup, with `selected` (clamped) highlighted
+    fn open_macros_popup(&mut self, selected: usize) {
+        use popup::definitions::macros::{create_macros_popup, MACROS_FIELD_LIST};
+
+        let world = self.current_world();
+        let visible_height = 10.min(world.settings.macros.len().max(3));
+        let def = create_macros_popup(&world.name, &world.settings.macros, visible_height);
+        let count = world.settings.macros.len();
+        self.popup_manager.open(def);
+
+        if let Some(state) = self.popup_manager.current_mut() {
+            state.select_field(MACROS_FIELD_LIST);
+            if let Some(field) = state.field_mut(MACROS_FIELD_LIST) {
+                if let popup::FieldKind::List { selected_index, .. } = &mut field.kind {
+                    *selected_index = selected.min(count.saturating_sub(1));
+                }
+            }
+        }
+    }
+
+    /// Open the tag routes popup, with `selected` (clamped) highlighted in the list
+    fn open_tag_routes_popup(&mut self, selected: usize) {
+        use popup::definitions::tag_routes::{create_tag_routes_popup, TAG_ROUTES_FIELD_LIST};
+
+        if self.settings.tag_routes.is_empty() {
+            self.add_output("No tag routes defined. Use /tagroute add <tag> <action> (see /help tagroute).");
+            return;
+        }
+
+        let visible_height = 10.min(self.settings.tag_routes.len().max(3));
+        let def = create_tag_routes_popup(&self.settings.tag_routes, visible_height);
+        let selected = selected.min(self.settings.tag_routes.len() - 1);
+        self.popup_manager.open(def);
+
+        if let Some(state) = self.popup_manager.current_mut() {
+            state.select_field(TAG_ROUTES_FIELD_LIST);
+            if let Some(field) = state.field_mut(TAG_ROUTES_FIELD_LIST) {
+                if let popup::FieldKind::List { selected_index, .. } = &mut field.kind {
+                    *selected_index = selected;
+                }
+            }
+        }
+    }
+
+    /// Open the gags popup, with `selected` (clamped) highlighted in the list
+    fn open_gags_popup(&mut self, selected: usize) {
+        use popup::definitions::gags::{create_gags_popup, GAGS_FIELD_LIST};
+
+        if self.settings.gags.is_empty() {
+            self.add_output("No gags defined. Use /gag <pattern> (see /help gag).");
+            return;
+        }
+
+        let visible_height = 10.min(self.settings.gags.len().max(3));
+        let def = create_gags_popup(&self.settings.gags, visible_height);
+        let selected = selected.min(self.settings.gags.len() - 1);
+        self.popup_manager.open(def);
+
+        if let Some(state) = self.popup_manager.current_mut() {
+            state.select_field(GAGS_FIELD_LIST);
+            if let Some(field) = state.field_mut(GAGS_FIELD_LIST) {
+                if let popup::FieldKind::List { selected_index, .. } = &mut field.kind {
+                    *selected_index = selected;
+                }
+            }
+        }
+    }
+
     /// Open the new setup popup for global settings
     fn open_setup_popup_new(&mut self) {
         use popup::definitions::setup::{create_setup_popup, SETUP_FIELD_MORE_MODE};
@@ -4006,6 +5128,11 @@ impl App {
             self.settings.tts_speak_mode.name(),
             self.settings.scrollback_enabled,
             self.settings.wrapspace as i64,
+            &self.settings.status_format,
+            self.settings.tab_bar,
+            self.settings.notify_activity,
+            self.settings.scrollback_lines as i64,
+            self.settings.confirm_quit,
         );
         self.popup_manager.open(def);
 
@@ -4031,6 +5158,11 @@ impl App {
             &self.settings.websocket_allow_list,
             &self.settings.websocket_cert_file,
             &self.settings.websocket_key_file,
+            &self.settings.acme_domain,
+            &self.settings.acme_email,
+            &self.settings.web_bind_addr,
+            &self.settings.web_unix_socket,
+            &self.settings.web_trusted_proxies,
             &auth_key_str,
             self.settings.remote_initial_lines as i64,
         );
@@ -4058,8 +5190,11 @@ impl App {
         // Map auto_connect type to lowercase value for popup
         let auto_connect = match world.settings.auto_connect_type {
             AutoConnectType::Connect => "connect",
+            AutoConnectType::Lines => "lines",
             AutoConnectType::Prompt => "prompt",
             AutoConnectType::MooPrompt => "moo_prompt",
+            AutoConnectType::Diku => "diku",
+            AutoConnectType::Script => "script",
             AutoConnectType::NoLogin => "none",
         };
 
@@ -4079,11 +5214,26 @@ impl App {
             user: world.settings.user.clone(),
             password: world.settings.password.clone(),
             use_ssl: world.settings.use_ssl,
+            tls_verify: world.settings.tls_verify.name().to_string(),
+            tls_client_cert: world.settings.tls_client_cert.clone(),
+            tls_client_key: world.settings.tls_client_key.clone(),
+            ssh_host: world.settings.ssh_host.clone(),
+            ssh_user: world.settings.ssh_user.clone(),
+            ssh_key: world.settings.ssh_key.clone(),
             log_enabled: world.settings.log_enabled,
+            log_format: world.settings.log_format.name().to_string(),
+            log_timestamps: world.settings.log_timestamps.name().to_string(),
+            log_session_markers: world.settings.log_session_markers,
             encoding: world.settings.encoding.name().to_string(),
             auto_connect: auto_connect.to_string(),
+            login_script: world.settings.login_script.clone(),
+            connect_commands: world.settings.connect_commands.clone(),
+            input_prefix: world.settings.input_prefix.clone(),
+            input_suffix: world.settings.input_suffix.clone(),
             keep_alive: keep_alive.to_string(),
             keep_alive_cmd: world.settings.keep_alive_cmd.clone(),
+            keep_alive_interval: world.settings.keep_alive_interval_secs.to_string(),
+            silence_alert: world.settings.silence_alert_mins.to_string(),
             gmcp_packages: world.settings.gmcp_packages.clone(),
             auto_reconnect_secs: world.settings.auto_reconnect_display(),
             slack_token: world.settings.slack_token.clone(),
@@ -4120,7 +5270,7 @@ impl App {
         use popup::definitions::actions::ACTIONS_FIELD_LIST;
 
         // Build items with indices for the popup
-        let mut items: Vec<popup::ListItem> = self.settings.actions.iter().enumerate()
+        let items: Vec<popup::ListItem> = self.settings.actions.iter().enumerate()
             .filter(|(_, a)| {
                 if world_filter.is_empty() {
                     true
@@ -4158,14 +5308,6 @@ impl App {
             })
             .collect();
 
-        // Sort alphabetically by action name (case-insensitive)
-        items.sort_by(|a, b| {
-            // Extract name from first column: "[x] name" or "[ ] name"
-            let name_a = a.columns[0].get(4..).unwrap_or("").to_lowercase();
-            let name_b = b.columns[0].get(4..).unwrap_or("").to_lowercase();
-            name_a.cmp(&name_b)
-        });
-
         let visible_height = 10.min(items.len().max(3));
 
         // Create the popup with the filtered actions
@@ -4181,7 +5323,10 @@ impl App {
                 popup::FieldKind::list(items, visible_height),
             ))
             .with_button(popup::Button::new(popup::definitions::actions::ACTIONS_BTN_DELETE, "Delete").danger().with_shortcut('D').left_align())
+            .with_button(popup::Button::new(popup::definitions::actions::ACTIONS_BTN_IMPORT, "Import").with_shortcut('I').left_align())
+            .with_button(popup::Button::new(popup::definitions::actions::ACTIONS_BTN_EXPORT, "Export").with_shortcut('X').left_align())
             .with_button(popup::Button::new(popup::definitions::actions::ACTIONS_BTN_ADD, "Add").with_shortcut('A'))
+            .with_button(popup::Button::new(popup::definitions::actions::ACTIONS_BTN_DUPLICATE, "Duplicate").with_shortcut('U'))
             .with_button(popup::Button::new(popup::definitions::actions::ACTIONS_BTN_EDIT, "Edit").with_shortcut('E'))
             .with_button(popup::Button::new(popup::definitions::actions::ACTIONS_BTN_CANCEL, "Ok").primary().with_shortcut('O'))
             .with_layout(popup::PopupLayout {
@@ -4261,6 +5406,10 @@ impl App {
     fn handle_remote_ws_message(&mut self, msg: WsMessage) {
         match msg {
             WsMessage::ServerData { world_index, data, from_server, seq: msg_seq, marked_new, flush, gagged, .. } => {
+                let duplicate = self.worlds.get(world_index).is_some_and(|w| msg_seq > 0 && msg_seq <= w.max_received_seq);
+                if from_server && !gagged && !duplicate {
+                    self.notify_activity(world_index, &data);
+                }
                 if let Some(world) = self.worlds.get_mut(world_index) {
                     // Flush: clear output buffer before appending new lines
                     // (e.g., splash screen cleared — combined with data to avoid race condition)
@@ -4311,7 +5460,7 @@ impl App {
                             if marked_new && world.first_marked_new_index.is_none() {
                                 world.first_marked_new_index = Some(world.output_lines.len());
                             }
-                            world.output_lines.push(output_line);
+                            world.output_lines.push_back(output_line);
                             // Track max received seq from server
                             if msg_seq > 0 {
                                 world.max_received_seq = msg_seq + i as u64;
@@ -4347,6 +5496,9 @@ impl App {
                 self.current_world_index = new_index;
                 self.needs_output_redraw = true;
             }
+            WsMessage::WorldMoved { from, to } => {
+                self.reorder_world(from, to);
+            }
             WsMessage::PromptUpdate { world_index, prompt } => {
                 if let Some(world) = self.worlds.get_mut(world_index) {
                     world.prompt = prompt;
@@ -4355,6 +5507,7 @@ impl App {
             WsMessage::UnseenCleared { world_index } => {
                 if let Some(world) = self.worlds.get_mut(world_index) {
                     world.unseen_lines = 0;
+                    world.bell_count = 0;
                 }
             }
             WsMessage::UnseenUpdate { world_index, count } => {
@@ -4362,6 +5515,16 @@ impl App {
                     world.unseen_lines = count;
                 }
             }
+            WsMessage::WorldBell { world_index, bell_count, mode } => {
+                if let Some(world) = self.worlds.get_mut(world_index) {
+                    world.bell_count = bell_count;
+                }
+                match bell::BellMode::from_name(&mode) {
+                    Some(bell::BellMode::Flash) => self.flash_bell(),
+                    Some(bell::BellMode::Ring) if self.console_active => print!("\x07"),
+                    _ => {}
+                }
+            }
             WsMessage::PendingLinesUpdate { world_index, count } => {
                 if let Some(world) = self.worlds.get_mut(world_index) {
                     // Track pending count from daemon (no actual lines stored client-side)
@@ -4391,7 +5554,7 @@ impl App {
                         }
                         let seq = world.next_seq;
                         world.next_seq += 1;
-                        world.output_lines.push(OutputLine {
+                        world.output_lines.push_back(OutputLine {
                             text: tl.text,
                             timestamp: std::time::UNIX_EPOCH + std::time::Duration::from_secs(tl.ts),
                             from_server: true,
@@ -4422,18 +5585,7 @@ impl App {
             }
             WsMessage::CalculatedWorld { index: Some(idx) } => {
                 // Server calculated next/prev world for us - switch to it
-                if idx < self.worlds.len() {
-                    self.current_world_index = idx;
-                    // Clear unseen for the world we're switching to
-                    if let Some(world) = self.worlds.get_mut(idx) {
-                        world.unseen_lines = 0;
-                    }
-                    self.needs_output_redraw = true;
-                    // Send MarkWorldSeen to notify server
-                    if let Some(ref tx) = self.ws_client_tx {
-                        let _ = tx.send(WsMessage::MarkWorldSeen { world_index: idx });
-                    }
-                }
+                self.view_remote_world(idx);
             }
             WsMessage::CalculatedWorld { index: None } => {}
             WsMessage::ActivityUpdate { count } => {
@@ -4446,6 +5598,49 @@ impl App {
                 self.show_tags = show_tags;
                 self.needs_output_redraw = true;
             }
+            WsMessage::WorldSettingsUpdated { world_index, settings, .. } => {
+                // Only numpad_walk and macros (key handling), timestamps (display),
+                // notify_muted and notify_sound (activity notifications), bell, emoji_shortcodes
+                // (Tab completion), palette, tint, spell_language and autocorrect (the
+                // input's spell check) are used locally; the rest is master-side
+                if let Some(world) = self.worlds.get_mut(world_index) {
+                    world.settings.numpad_walk = settings.numpad_walk;
+                    world.settings.macros = settings.macros.clone();
+                    world.settings.timestamps = TimestampMode::from_name(&settings.timestamps);
+                    world.settings.notify_muted = settings.notify_muted;
+                    world.settings.notify_sound = settings.notify_sound.clone();
+                    world.settings.inline_images = settings.inline_images;
+                    world.settings.bell = bell::BellMode::from_name(&settings.bell).unwrap_or_default();
+                    world.settings.emoji_shortcodes = settings.emoji_shortcodes;
+                    world.settings.autocorrect = settings.autocorrect;
+                    world.settings.palette = settings.palette.clone();
+                    world.settings.tint = settings.tint.clone();
+                    world.settings.spell_language = settings.spell_language.clone();
+                }
+                self.needs_output_redraw = true;
+            }
+            WsMessage::DeviceTokens { tokens, new_token, message } => {
+                self.show_device_tokens(&tokens, new_token.as_ref(), message);
+                self.needs_output_redraw = true;
+            }
+            WsMessage::ViewOnly { message } => {
+                self.add_output(&message);
+                self.needs_output_redraw = true;
+            }
+            WsMessage::ActiveProfileChanged { profile } => {
+                self.active_profile = if profile.is_empty() { None } else { Some(profile) };
+                self.needs_output_redraw = true;
+            }
+            WsMessage::ServerEcho { world_index, on } if world_index < self.worlds.len() => {
+                self.worlds[world_index].server_echo = on;
+                self.needs_output_redraw = true;
+            }
+            WsMessage::SendFileProgress { world_index, sent, total } if world_index < self.worlds.len() => {
+                self.worlds[world_index].send_file = (total > 0).then(|| sendfile::SendFile {
+                    task: None, file: String::new(), sent, total,
+                });
+                self.needs_output_redraw = true;
+            }
             WsMessage::GmcpUserToggled { world_index, enabled } => {
                 if world_index < self.worlds.len() {
                     self.worlds[world_index].gmcp_user_enabled = enabled;
@@ -4464,7 +5659,7 @@ impl App {
                     let seq = self.current_world().next_seq;
                     let world = self.current_world_mut();
                     world.next_seq = seq + 1;
-                    world.output_lines.push(OutputLine::new_client(line, seq));
+                    world.output_lines.push_back(OutputLine::new_client(line, seq));
                 }
                 if was_at_bottom {
                     self.current_world_mut().scroll_to_bottom();
@@ -4502,6 +5697,14 @@ impl App {
                     Command::Web => {
                         self.open_web_popup_new();
                     }
+                    Command::WebTokens { ref args } => {
+                        match commands::DeviceTokenRequest::parse(args) {
+                            Ok(request) => if let Some(ref tx) = self.ws_client_tx {
+                                let _ = tx.send(request.into_ws());
+                            },
+                            Err(usage) => self.add_output(&usage),
+                        }
+                    }
                     Command::Actions { world } => {
                         if let Some(world_name) = world {
                             self.open_actions_list_popup_with_filter(&world_name);
@@ -4509,6 +5712,20 @@ impl App {
                             self.open_actions_list_popup();
                         }
                     }
+                    Command::Gag { .. } => {
+                        // The server bounces only a bare /gag; the popup opens on GagsUpdated
+                        if let Some(ref tx) = self.ws_client_tx {
+                            self.gags_popup_requested = true;
+                            let _ = tx.send(WsMessage::RequestGags);
+                        }
+                    }
+                    Command::TagRoute { .. } => {
+                        // The server bounces only a bare /tagroute; the popup opens on TagRoutesUpdated
+                        if let Some(ref tx) = self.ws_client_tx {
+                            self.tag_routes_popup_requested = true;
+                            let _ = tx.send(WsMessage::RequestTagRoutes);
+                        }
+                    }
                     Command::Menu => {
                         self.open_menu_popup_new();
                     }
@@ -4523,6 +5740,9 @@ impl App {
                     Command::Quit => {
                         // Handled by the outer run_console_client loop
                     }
+                    Command::WebPush { .. } => {
+                        self.add_output(commands::WEB_PUSH_BROWSER_ONLY);
+                    }
                     _ => {
                         // Unknown local command - ignore or log
                     }
@@ -4555,7 +5775,7 @@ impl App {
                             marked_new: line.marked_new,
                             from_archive: line.from_archive,
                         };
-                        world.output_lines.push(output_line);
+                        world.output_lines.push_back(output_line);
                         if line.seq >= world.next_seq {
                             world.next_seq = line.seq + 1;
                         }
@@ -4591,7 +5811,7 @@ impl App {
                         }
                     }).collect();
                     let prepended_count = new_lines.len();
-                    let mut combined = new_lines;
+                    let mut combined: std::collections::VecDeque<OutputLine> = new_lines.into();
                     combined.append(&mut world.output_lines);
                     world.output_lines = combined;
                     // Adjust scroll_offset to keep viewing the same content
@@ -4627,12 +5847,60 @@ impl App {
                 self.settings.actions = actions;
                 compile_all_action_regexes(&mut self.settings.actions);
             }
+            WsMessage::TagRoutesUpdated { routes } => {
+                self.settings.tag_routes = routes;
+                // Rebuild an open tag routes popup in place, or open the one a bare /tagroute asked for
+                let open_at = if self.popup_manager.current().is_some_and(|s| s.definition.id == popup::PopupId("tag_routes")) {
+                    let selected = self.popup_manager.current()
+                        .and_then(|s| s.get_selected_list_item())
+                        .and_then(|item| item.id.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    self.popup_manager.close();
+                    Some(selected)
+                } else if std::mem::take(&mut self.tag_routes_popup_requested) {
+                    Some(0)
+                } else {
+                    None
+                };
+                if let Some(selected) = open_at {
+                    self.open_tag_routes_popup(selected);
+                }
+            }
+            WsMessage::GagsUpdated { mut gags } => {
+                for gag in &mut gags {
+                    gag.compile();
+                }
+                self.settings.gags = gags;
+                // Rebuild an open gags popup in place, or open the one a bare /gag asked for
+                let open_at = if self.popup_manager.current().is_some_and(|s| s.definition.id == popup::PopupId("gags")) {
+                    let selected = self.popup_manager.current()
+                        .and_then(|s| s.get_selected_list_item())
+                        .and_then(|item| item.id.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    self.popup_manager.close();
+                    Some(selected)
+                } else if std::mem::take(&mut self.gags_popup_requested) {
+                    Some(0)
+                } else {
+                    None
+                };
+                if let Some(selected) = open_at {
+                    self.open_gags_popup(selected);
+                }
+            }
             WsMessage::PingCheck { nonce } => {
                 // Server liveness check for /remote command - respond immediately
                 if let Some(ref tx) = self.ws_client_tx {
                     let _ = tx.send(WsMessage::PongCheck { nonce });
                 }
             }
+            WsMessage::Notification { title, message, sound } => {
+                // The server's /notify notifications go to this console's desktop
+                desktop_notify::show(&title, &message);
+                if !self.terminal_focused && !sound.is_empty() {
+                    audio::play_notify_sound(&sound);
+                }
+            }
             _ => {}
         }
     }
@@ -4698,17 +5966,45 @@ impl App {
             world.prompt = w.prompt;
             world.showing_splash = w.showing_splash;
             world.gmcp_user_enabled = w.gmcp_user_enabled;
+            world.bell_count = w.bell_count;
             world.settings = WorldSettings {
                 hostname: w.settings.hostname,
                 port: w.settings.port,
                 user: w.settings.user,
                 password: String::new(), // Don't receive passwords from server
                 use_ssl: w.settings.use_ssl,
+                tls_verify: platform::TlsVerify::from_name(&w.settings.tls_verify).unwrap_or_default(),
+                tls_client_cert: w.settings.tls_client_cert,
+                tls_client_key: w.settings.tls_client_key,
+                ssh_host: w.settings.ssh_host,
+                ssh_user: w.settings.ssh_user,
+                ssh_key: w.settings.ssh_key,
                 log_enabled: w.settings.log_enabled,
+                log_format: logging::LogFormat::from_name(&w.settings.log_format).unwrap_or_default(),
+                log_timestamps: logging::LogTimestamps::from_name(&w.settings.log_timestamps).unwrap_or_default(),
+                log_session_markers: w.settings.log_session_markers,
                 encoding: Encoding::from_name(&w.settings.encoding),
                 auto_connect_type: AutoConnectType::from_name(&w.settings.auto_connect_type),
+                login_script: w.settings.login_script,
+                connect_commands: w.settings.connect_commands,
+                input_prefix: w.settings.input_prefix,
+                input_suffix: w.settings.input_suffix,
                 keep_alive_type: KeepAliveType::from_name(&w.settings.keep_alive_type),
                 keep_alive_cmd: w.settings.keep_alive_cmd,
+                keep_alive_interval_secs: w.settings.keep_alive_interval_secs,
+                silence_alert_mins: w.settings.silence_alert_mins,
+                numpad_walk: w.settings.numpad_walk,
+                timestamps: TimestampMode::from_name(&w.settings.timestamps),
+                notify_muted: w.settings.notify_muted,
+                notify_sound: w.settings.notify_sound.clone(),
+                inline_images: w.settings.inline_images,
+                bell: bell::BellMode::from_name(&w.settings.bell).unwrap_or_default(),
+                emoji_shortcodes: w.settings.emoji_shortcodes,
+                autocorrect: w.settings.autocorrect,
+                palette: w.settings.palette.clone(),
+                tint: w.settings.tint.clone(),
+                spell_language: w.settings.spell_language.clone(),
+                macros: w.settings.macros.clone(),
                 ..WorldSettings::default()
             };
             world
@@ -4756,7 +6052,7 @@ impl App {
                 // they stay permanently empty until the user manually focuses + scrolls
                 // them. Only a genuinely missing world (removed since being queued) falls
                 // through to try the next queue entry below.
-                let oldest_seq = world.output_lines.first().map(|l| l.seq);
+                let oldest_seq = world.output_lines.front().map(|l| l.seq);
                 let received = world.output_lines.len();
                 let count = if self.backfill_phase == 1 {
                     self.backfill_phase1_target.saturating_sub(received).max(1)
@@ -4816,7 +6112,9 @@ impl App {
         self.backfill_phase = 1;
         self.backfill_phase1_target = phase1_target.max(1);
         let per_world_cap = self.settings.remote_initial_lines.max(1) as usize;
-        self.backfill_total_target = per_world_cap.max(self.backfill_phase1_target);
+        // Never backfill past the scrollback cap, or trimming would evict what was fetched
+        self.backfill_total_target = per_world_cap.max(self.backfill_phase1_target)
+            .min(self.settings.scrollback_lines);
 
         // Build the phase 1 queue: current world first, then others. Only worlds
         // still short of a screenful belong in phase 1 - a world that already has
@@ -4850,6 +6148,60 @@ impl App {
             })
     }
 
+    /// Worlds /connect -a connects: set up, not connected, and with auto-login enabled
+    pub fn auto_connect_worlds(&self) -> Vec<usize> {
+        (0..self.worlds.len())
+            .filter(|&idx| {
+                let world = &self.worlds[idx];
+                !world.connected
+                    && world.settings.has_connection_settings()
+                    && world.settings.auto_connect_type != AutoConnectType::NoLogin
+            })
+            .collect()
+    }
+
+    /// Count a finished connect towards a running /connect -a, printing the
+    /// summary when it was the last
+    pub fn note_bulk_connect(&mut self, world_name: &str, connected: bool) {
+        let Some(bulk) = self.bulk_connect.as_mut() else { return };
+        let Some(pos) = bulk.pending.iter().position(|n| n == world_name) else { return };
+        bulk.pending.remove(pos);
+        if connected {
+            bulk.connected += 1;
+        } else {
+            bulk.failed.push(world_name.to_string());
+        }
+        if bulk.pending.is_empty() {
+            let summary = bulk.summary();
+            self.bulk_connect = None;
+            self.add_output(&summary);
+        }
+    }
+
+    /// /disconnect -a: close every connection, returning how many there were
+    pub fn disconnect_all_worlds(&mut self) -> usize {
+        (0..self.worlds.len()).filter(|&world_index| self.disconnect_world(world_index)).count()
+    }
+
+    /// Disconnect one world (e.g. a middle-clicked tab). Returns false if it wasn't connected.
+    pub fn disconnect_world(&mut self, world_index: usize) -> bool {
+        if !self.worlds.get(world_index).is_some_and(|w| w.connected) {
+            return false;
+        }
+        #[cfg(unix)]
+        if let Some(proxy_pid) = self.worlds[world_index].proxy_pid {
+            unsafe { libc::kill(proxy_pid as libc::pid_t, libc::SIGTERM); }
+        }
+        #[cfg(windows)]
+        if let Some(proxy_pid) = self.worlds[world_index].proxy_pid {
+            crate::platform::kill_proxy_process(proxy_pid);
+        }
+        self.worlds[world_index].clear_connection_state(true, true);
+        self.add_output_to_world(world_index, "Disconnected.");
+        self.ws_broadcast(WsMessage::WorldDisconnected { world_index });
+        true
+    }
+
     /// Find world index by name (case-insensitive), also checks reader_name for renamed worlds
     pub fn find_world_index(&self, name: &str) -> Option<usize> {
         self.worlds.iter().position(|w| {
@@ -4910,7 +6262,7 @@ impl App {
                 .ok_or_else(|| format!("No world named '{}'", name))?,
             _ => fallback_idx,
         };
-        Ok(self.worlds[idx].output_lines.clone())
+        Ok(self.worlds[idx].output_lines.iter().cloned().collect())
     }
 
     pub fn switch_world(&mut self, index: usize) {
@@ -4938,6 +6290,50 @@ impl App {
         }
     }
 
+    /// Move the world at `from` to position `to` (a dragged tab), keeping every
+    /// index-keyed piece of state pointing at the same world. Returns false if
+    /// either index is out of range or they are equal.
+    pub fn reorder_world(&mut self, from: usize, to: usize) -> bool {
+        if from == to || from >= self.worlds.len() || to >= self.worlds.len() {
+            return false;
+        }
+        let remap = |i: usize| tab_bar::moved_index(i, from, to);
+        let world = self.worlds.remove(from);
+        self.worlds.insert(to, world);
+        self.current_world_index = remap(self.current_world_index);
+        self.previous_world_index = self.previous_world_index.map(remap);
+        let mut views = Vec::new();
+        for (client_id, view) in self.ws_client_worlds.iter_mut() {
+            view.world_index = remap(view.world_index);
+            views.push((*client_id, view.world_index));
+        }
+        for (client_id, world_index) in views {
+            self.ws_set_client_world(client_id, Some(world_index));
+        }
+        for unseen in self.ws_client_unseen.values_mut() {
+            *unseen = unseen.drain().map(|(i, n)| (remap(i), n)).collect();
+        }
+        self.user_connections = std::mem::take(&mut self.user_connections)
+            .into_iter()
+            .map(|((i, user), conn)| ((remap(i), user), conn))
+            .collect();
+        for (idx, _) in self.media_processes.values_mut() {
+            *idx = remap(*idx);
+        }
+        if let Some((idx, _)) = self.media_music_key.as_mut() {
+            *idx = remap(*idx);
+        }
+        for idx in self.backfill_queue.iter_mut() {
+            *idx = remap(*idx);
+        }
+        self.backfill_exhausted = self.backfill_exhausted.drain().map(remap).collect();
+        if let Some((idx, _, _)) = self.backfill_next.as_mut() {
+            *idx = remap(*idx);
+        }
+        self.needs_output_redraw = true;
+        true
+    }
+
     /// Switch to a world with activity (Alt-w)
     /// Priority: 1) oldest pending output, 2) any unseen output, 3) previous world
     /// Returns true if switched, false if nowhere to switch
@@ -5108,32 +6504,65 @@ impl App {
         }
     }
 
-    /// Calculate the next world index from a given starting point (without switching)
-    fn calculate_next_world_from(&self, from_index: usize) -> Option<usize> {
-        let world_info: Vec<crate::util::WorldSwitchInfo> = self.worlds.iter()
-            .map(|w| crate::util::WorldSwitchInfo {
-                name: w.name.clone(),
-                connected: w.connected,
-                unseen_lines: w.unseen_lines,
-                pending_lines: w.pending_lines.len(),
-                first_unseen_at: w.first_unseen_at,
-            })
-            .collect();
-        crate::util::calculate_next_world(&world_info, from_index, self.settings.world_switch_mode)
+    /// The world a `telnet://` or `mud://` link opens: the first MUD world with that
+    /// host and port, or a new one named after the host (saved like /addworld's)
+    fn world_for_uri(&mut self, uri: &util::MudUri) -> usize {
+        if let Some(idx) = self.worlds.iter().position(|w| {
+            w.settings.world_type == WorldType::Mud
+                && w.settings.hostname.eq_ignore_ascii_case(&uri.host)
+                && w.settings.port == uri.port
+        }) {
+            return idx;
+        }
+        let mut name = uri.host.clone();
+        let mut n = 1;
+        while self.find_world(&name).is_some() {
+            n += 1;
+            name = format!("{}-{}", uri.host, n);
+        }
+        let idx = self.find_or_create_world(&name);
+        self.worlds[idx].settings.hostname = uri.host.clone();
+        self.worlds[idx].settings.port = uri.port.clone();
+        self.worlds[idx].settings.use_ssl = uri.tls;
+        let _ = persistence::save_settings(self);
+        let world_state = self.new_world_state_msg(idx);
+        self.ws_broadcast(WsMessage::WorldAdded { world: Box::new(world_state) });
+        idx
     }
 
-    /// Calculate the previous world index from a given starting point (without switching)
-    fn calculate_prev_world_from(&self, from_index: usize) -> Option<usize> {
-        let world_info: Vec<crate::util::WorldSwitchInfo> = self.worlds.iter()
-            .map(|w| crate::util::WorldSwitchInfo {
+    /// Turn Command::OpenUri into a switch to (and so a connect of) the world for its
+    /// address; every other command passes through unchanged
+    pub(crate) fn resolve_open_uri(&mut self, cmd: Command) -> Command {
+        match cmd {
+            Command::OpenUri { uri } => {
+                let idx = self.world_for_uri(&uri);
+                Command::WorldSwitch { name: self.worlds[idx].name.clone() }
+            }
+            other => other,
+        }
+    }
+
+    /// World switching info as a WS client sees it (its own unseen counts)
+    fn client_world_switch_info(&self, client_id: u64) -> Vec<crate::util::WorldSwitchInfo> {
+        self.worlds.iter().enumerate()
+            .map(|(i, w)| crate::util::WorldSwitchInfo {
                 name: w.name.clone(),
                 connected: w.connected,
-                unseen_lines: w.unseen_lines,
+                unseen_lines: self.client_unseen(client_id, i),
                 pending_lines: w.pending_lines.len(),
                 first_unseen_at: w.first_unseen_at,
             })
-            .collect();
-        crate::util::calculate_prev_world(&world_info, from_index, self.settings.world_switch_mode)
+            .collect()
+    }
+
+    /// Calculate a WS client's next world from a given starting point (without switching)
+    fn calculate_next_world_from(&self, client_id: u64, from_index: usize) -> Option<usize> {
+        crate::util::calculate_next_world(&self.client_world_switch_info(client_id), from_index, self.settings.world_switch_mode)
+    }
+
+    /// Calculate a WS client's previous world from a given starting point (without switching)
+    fn calculate_prev_world_from(&self, client_id: u64, from_index: usize) -> Option<usize> {
+        crate::util::calculate_prev_world(&self.client_world_switch_info(client_id), from_index, self.settings.world_switch_mode)
     }
 
     /// Activity count excluding a specific world (for per-client counts)
@@ -5145,6 +6574,94 @@ impl App {
             .count()
     }
 
+    /// A WS client's activity count: worlds other than `exclude_world` with pending
+    /// lines or lines it hasn't seen
+    fn client_activity_count(&self, client_id: u64, exclude_world: Option<usize>) -> usize {
+        self.worlds
+            .iter()
+            .enumerate()
+            .filter(|(i, w)| Some(*i) != exclude_world
+                && (self.client_unseen(client_id, *i) > 0 || !w.pending_lines.is_empty()))
+            .count()
+    }
+
+    /// Lines of a world a WS client hasn't seen (the world's own count until it signs in)
+    pub(crate) fn client_unseen(&self, client_id: u64, world_idx: usize) -> usize {
+        match self.ws_client_unseen.get(&client_id) {
+            Some(unseen) => unseen.get(&world_idx).copied().unwrap_or(0),
+            None => self.worlds.get(world_idx).map_or(0, |w| w.unseen_lines),
+        }
+    }
+
+    /// Start a newly signed-in WS client's unseen counts from the worlds' own
+    pub(crate) fn ws_seed_client_unseen(&mut self, client_id: u64) {
+        let unseen = self.worlds.iter().enumerate()
+            .filter(|(_, w)| w.unseen_lines > 0)
+            .map(|(i, w)| (i, w.unseen_lines))
+            .collect();
+        self.ws_client_unseen.insert(client_id, unseen);
+    }
+
+    /// Count `lines` new lines of a world as unseen. The console counts them when it
+    /// isn't looking even if a remote client is (add_output only counted them when no
+    /// one was), and each WS client not viewing the world is sent its own new count.
+    pub(crate) fn note_unseen_lines(&mut self, world_idx: usize, lines: usize, console_viewing: bool, is_current: bool) {
+        if lines == 0 || world_idx >= self.worlds.len() {
+            return;
+        }
+        if is_current && !console_viewing {
+            let world = &mut self.worlds[world_idx];
+            if world.unseen_lines == 0 && world.first_unseen_at.is_none() {
+                world.first_unseen_at = Some(std::time::Instant::now());
+            }
+            world.unseen_lines += lines;
+        }
+        #[cfg(test)]
+        {
+            // Log the console's count for test assertions
+            if let Ok(mut log) = self.ws_broadcast_log.lock() {
+                log.push(WsMessage::UnseenUpdate { world_index: world_idx, count: self.worlds[world_idx].unseen_lines });
+            }
+        }
+        let mut updates = Vec::new();
+        for (&client_id, unseen) in self.ws_client_unseen.iter_mut() {
+            let viewing = self.ws_client_worlds.get(&client_id)
+                .is_some_and(|v| v.world_index == world_idx && !v.paused);
+            if !viewing {
+                let count = unseen.entry(world_idx).or_insert(0);
+                *count += lines;
+                updates.push((client_id, *count));
+            }
+        }
+        for (client_id, count) in updates {
+            self.ws_send_to_client(client_id, WsMessage::UnseenUpdate { world_index: world_idx, count });
+        }
+    }
+
+    /// Point one WS client at a world without moving the console or any other client,
+    /// and tell it (only it) that it switched
+    pub(crate) fn ws_focus_client_world(&mut self, client_id: u64, world_index: usize) {
+        let prev = self.ws_client_worlds.get(&client_id);
+        let dimensions = prev.and_then(|s| s.dimensions);
+        let visible_lines = prev.map(|v| v.visible_lines).unwrap_or(0);
+        let visible_columns = prev.map(|v| v.visible_columns).unwrap_or(0);
+        let paused = prev.map(|v| v.paused).unwrap_or(false);
+        self.ws_client_worlds.insert(client_id, ClientViewState { world_index, visible_lines, visible_columns, dimensions, paused });
+        self.ws_set_client_world(client_id, Some(world_index));
+        self.ws_send_to_client(client_id, WsMessage::WorldSwitched { new_index: world_index });
+    }
+
+    /// A WS client has seen a world: clear only its own count and tell only it
+    pub(crate) fn ws_mark_client_seen(&mut self, client_id: u64, world_idx: usize) {
+        if let Some(unseen) = self.ws_client_unseen.get_mut(&client_id) {
+            unseen.remove(&world_idx);
+        }
+        if let Some(world) = self.worlds.get_mut(world_idx) {
+            world.push_sent_at = None;
+        }
+        self.ws_send_to_client(client_id, WsMessage::UnseenCleared { world_index: world_idx });
+    }
+
     pub fn activity_count(&self) -> usize {
         self.activity_count_excluding(Some(self.current_world_index))
     }
@@ -5163,11 +6680,11 @@ impl App {
         }
         if let Some(ref server) = self.ws_server {
             if let Ok(clients_guard) = server.clients.try_read() {
-                for client in clients_guard.values() {
+                for (&client_id, client) in clients_guard.iter() {
                     if client.authenticated {
                         // Paused clients get full activity count (their world is no longer excluded)
                         let exclude = if client.paused { None } else { client.current_world };
-                        let count = self.activity_count_excluding(exclude);
+                        let count = self.client_activity_count(client_id, exclude);
                         let _ = client.tx.send(WsMessage::ActivityUpdate { count });
                     }
                 }
@@ -5230,6 +6747,310 @@ impl App {
         }
     }
 
+    /// Expand `${env:VAR}` and `${secret:NAME}` references (see secrets.rs) in text about
+    /// to be sent to `world_idx`
+    pub fn expand_secrets(&self, world_idx: usize, text: &str) -> String {
+        let empty = std::collections::BTreeMap::new();
+        let world = self.worlds.get(world_idx).map(|w| &w.settings.secrets).unwrap_or(&empty);
+        secrets::expand(text, world, &self.settings.secrets)
+    }
+
+    /// Actions to run triggers against for `world_idx`, with secret references in their
+    /// commands expanded. Expansion happens before capture substitution so text from the
+    /// MUD can never pull a secret into a command.
+    pub fn trigger_actions(&self, world_idx: usize) -> Vec<actions::Action> {
+        let mut actions = self.settings.actions.clone();
+        for action in actions.iter_mut() {
+            if action.command.contains("${") {
+                action.command = self.expand_secrets(world_idx, &action.command);
+            }
+        }
+        actions
+    }
+
+    /// Batching interval for `world_idx`'s output if it is a low-priority world that
+    /// nobody is viewing (see throttle.rs)
+    fn background_throttle(&self, world_idx: usize) -> Option<Duration> {
+        let world = &self.worlds[world_idx];
+        let viewed = world_idx == self.current_world_index || self.ws_client_viewing(world_idx);
+        (world.settings.low_priority && !viewed && self.settings.background_batch_ms > 0)
+            .then(|| Duration::from_millis(self.settings.background_batch_ms as u64))
+    }
+
+    /// Earliest time a background world's held output is due, for the event loop timers
+    pub fn next_background_release(&self) -> Option<std::time::Instant> {
+        self.worlds.iter().filter_map(|w| w.background_batch.release_at()).min()
+    }
+
+    /// Names of worlds whose held output is due at `now`. The event loops re-queue an
+    /// empty ServerData for each, which releases the batch through the normal path.
+    pub fn due_background_batches(&self, now: std::time::Instant) -> Vec<String> {
+        self.worlds.iter()
+            .filter(|w| w.background_batch.release_at().is_some_and(|at| at <= now))
+            .map(|w| w.name.clone())
+            .collect()
+    }
+
+    /// Feed an action fire into `world_idx`'s loop watchdog (see trigger_guard.rs). When
+    /// the action is looping, disables every action in the chain and returns their names
+    /// along with the report to show in the world.
+    pub fn guard_trigger_loop(&mut self, world_idx: usize, action_name: &str) -> Option<(Vec<String>, String)> {
+        let limit = self.settings.trigger_loop_limit;
+        let chain = self.worlds[world_idx].trigger_guard.record(action_name, std::time::Instant::now(), limit)?;
+        for action in self.settings.actions.iter_mut() {
+            if chain.contains(&action.name) {
+                action.enabled = false;
+            }
+        }
+        let _ = persistence::save_settings(self);
+        self.ws_broadcast(WsMessage::ActionsUpdated { actions: self.settings.actions.clone() });
+        let report = format!(
+            "Trigger loop detected ({}, over {} fires/sec); disabled: {}. Re-enable in /actions.",
+            trigger_guard::describe_chain(&chain), limit, chain.join(", "));
+        Some((chain, report))
+    }
+
+    /// World user and password with secret references expanded, for auto-login
+    pub fn login_credentials(&self, world_idx: usize) -> (String, String) {
+        let settings = &self.worlds[world_idx].settings;
+        (self.expand_secrets(world_idx, &settings.user), self.expand_secrets(world_idx, &settings.password))
+    }
+
+    /// Copy of a world's settings for handing to a connection task, with the login
+    /// credentials already expanded (the task has no access to global secrets)
+    pub fn login_settings(&self, world_idx: usize) -> WorldSettings {
+        let mut settings = self.worlds[world_idx].settings.clone();
+        (settings.user, settings.password) = self.login_credentials(world_idx);
+        settings
+    }
+
+    /// The scheduled profile currently in effect, if any
+    pub fn current_profile(&self) -> Option<&profiles::Profile> {
+        let name = self.active_profile.as_ref()?;
+        self.settings.profiles.iter().find(|p| &p.name == name)
+    }
+
+    /// Send a /notify notification from world `world_idx` to web/mobile clients and
+    /// the console's desktop, unless the world is muted or the scheduled profile
+    /// silences them. `sound` overrides the world's notification sound, which
+    /// unfocused clients play. Returns the message to report back to the user.
+    pub fn send_notification(&mut self, world_idx: usize, message: &str, sound: Option<&str>) -> String {
+        let title = self.worlds.get(world_idx).map_or("Clay".to_string(), |w| w.name.clone());
+        if self.worlds.get(world_idx).is_some_and(|w| w.settings.notify_muted) {
+            return format!("Notification muted for {}: {}", title, message);
+        }
+        if let Some(profile) = self.current_profile().filter(|p| !p.notify) {
+            return format!("Notification suppressed by profile '{}': {}", profile.name, message);
+        }
+        let sound = sound.map(str::to_string).unwrap_or_else(|| {
+            self.worlds.get(world_idx).map(|w| w.settings.notify_sound.clone()).unwrap_or_default()
+        });
+        if self.console_active {
+            desktop_notify::show(&title, message);
+            if !self.terminal_focused && !sound.is_empty() {
+                audio::play_notify_sound(&sound);
+            }
+        }
+        self.ws_broadcast(WsMessage::Notification {
+            title,
+            message: message.to_string(),
+            sound,
+        });
+        format!("Notification sent: {}", message)
+    }
+
+    /// Desktop notification for new output in a world while the terminal is unfocused
+    /// (Notify Activity in /setup): once per world until the terminal regains focus
+    pub(crate) fn notify_activity(&mut self, world_idx: usize, text: &str) {
+        if !self.console_active || self.terminal_focused || !self.settings.notify_activity {
+            return;
+        }
+        let Some(world) = self.worlds.get(world_idx) else { return };
+        if world.activity_notified || world.settings.notify_muted
+            || self.current_profile().is_some_and(|p| !p.notify)
+        {
+            return;
+        }
+        let Some(body) = desktop_notify::activity_body(text) else { return };
+        desktop_notify::show(&world.name, &body);
+        if !world.settings.notify_sound.is_empty() {
+            audio::play_notify_sound(&world.settings.notify_sound);
+        }
+        self.worlds[world_idx].activity_notified = true;
+    }
+
+    /// Web Push activity notification for subscribed browsers (web_push.rs): at most
+    /// once per world every ACTIVITY_INTERVAL until the world is seen, and not while
+    /// the focused console is showing it. Muted worlds and profiles that silence
+    /// notifications are skipped, as for notify_activity.
+    pub(crate) fn push_activity(&mut self, world_idx: usize, text: &str) {
+        if self.settings.push_subscriptions.is_empty() {
+            return;
+        }
+        let console_watching = self.console_active && self.terminal_focused && world_idx == self.current_world_index;
+        let Some(world) = self.worlds.get(world_idx) else { return };
+        if console_watching || world.settings.notify_muted
+            || world.push_sent_at.is_some_and(|at| at.elapsed() < web_push::ACTIVITY_INTERVAL)
+            || self.current_profile().is_some_and(|p| !p.notify)
+        {
+            return;
+        }
+        let Some(body) = desktop_notify::activity_body(text) else { return };
+        let Some(event_tx) = self.event_tx.clone() else { return };
+        let payload = web_push::activity_payload(&world.name, &body);
+        let subscriptions = self.settings.push_subscriptions.clone();
+        self.worlds[world_idx].push_sent_at = Some(std::time::Instant::now());
+        tokio::spawn(async move {
+            let gone = web_push::send(subscriptions, payload).await;
+            if !gone.is_empty() {
+                let _ = event_tx.send(AppEvent::PushGone(gone)).await;
+            }
+        });
+    }
+
+    /// Store a browser's Web Push subscription (`PushSubscribe`), replacing an earlier
+    /// one for the same endpoint. Returns the line to show that client.
+    pub(crate) fn add_push_subscription(&mut self, endpoint: &str, p256dh: &str, auth: &str) -> String {
+        let subscription = match web_push::PushSubscription::new(endpoint, p256dh, auth) {
+            Ok(subscription) => subscription,
+            Err(e) => return format!("Push notifications not turned on: {}", e),
+        };
+        self.settings.push_subscriptions.retain(|s| s.endpoint != subscription.endpoint);
+        self.settings.push_subscriptions.push(subscription);
+        let _ = persistence::save_settings(self);
+        "Push notifications on: this browser is told about activity in your worlds.".to_string()
+    }
+
+    /// Forget Web Push subscriptions: the given endpoints (`PushUnsubscribe`, or those a
+    /// push service reported gone), or every one if `endpoints` is empty. Returns how
+    /// many were removed.
+    pub(crate) fn remove_push_subscriptions(&mut self, endpoints: &[String]) -> usize {
+        let before = self.settings.push_subscriptions.len();
+        self.settings.push_subscriptions.retain(|s| !endpoints.is_empty() && !endpoints.contains(&s.endpoint));
+        let removed = before - self.settings.push_subscriptions.len();
+        if removed > 0 {
+            let _ = persistence::save_settings(self);
+        }
+        removed
+    }
+
+    /// Silence watchdog: when a connected world with an Alert After setting has sent
+    /// nothing for that many minutes, say so in the world and send a notification -
+    /// it catches dead connections TCP keepalive hasn't noticed. Once per silence: the
+    /// next output from the world rearms it.
+    pub(crate) fn check_silence_watchdogs(&mut self) {
+        for idx in 0..self.worlds.len() {
+            let world = &self.worlds[idx];
+            let mins = world.settings.silence_alert_mins;
+            let Some(last) = world.last_receive_time else { continue };
+            if !world.connected || mins == 0 || world.silence_alerted_for == Some(last)
+                || last.elapsed() < Duration::from_secs(u64::from(mins) * 60)
+            {
+                continue;
+            }
+            self.worlds[idx].silence_alerted_for = Some(last);
+            let message = format!(
+                "No output for {} minute{}; the connection may have dropped.",
+                mins, if mins == 1 { "" } else { "s" }
+            );
+            self.add_output_to_world(idx, &message);
+            self.send_notification(idx, &message, None);
+        }
+    }
+
+    /// Count `count` BELs in world `world_idx`'s output and react per its /bell mode.
+    /// Clients are told too, so they can flash or ring themselves.
+    fn ring_bells(&mut self, world_idx: usize, count: usize) {
+        let world = &mut self.worlds[world_idx];
+        world.bell_count += count;
+        let (mode, bell_count) = (world.settings.bell, world.bell_count);
+        match mode {
+            bell::BellMode::Ignore => {}
+            bell::BellMode::Flash => self.flash_bell(),
+            bell::BellMode::Ring => {
+                if self.console_active {
+                    print!("\x07");
+                }
+            }
+            bell::BellMode::Notify => {
+                self.send_notification(world_idx, "Bell", None);
+            }
+        }
+        self.ws_broadcast(WsMessage::WorldBell {
+            world_index: world_idx,
+            bell_count,
+            mode: mode.name().to_string(),
+        });
+    }
+
+    /// Invert the console status bar for a moment (the draw loop clears it)
+    pub(crate) fn flash_bell(&mut self) {
+        if self.console_active {
+            self.bell_flash_until = Some(std::time::Instant::now() + bell::FLASH_DURATION);
+        }
+    }
+
+    /// Hold every world's output to the Scrollback Lines cap. Worlds whose line
+    /// numbers are in use (search, copy mode, the F4 filter) are left until it ends.
+    /// Filter indexes already built are brought up to date with the new output.
+    pub(crate) fn trim_scrollback(&mut self) {
+        let cap = self.settings.scrollback_lines;
+        let filtering = self.filter_popup.visible.then_some(self.current_world_index);
+        let copying = self.copy_mode.active.then_some(self.current_world_index);
+        for (idx, world) in self.worlds.iter_mut().enumerate() {
+            if world.output_lines.len() > cap && !world.search_active
+                && filtering != Some(idx) && copying != Some(idx) {
+                world.trim_scrollback(cap);
+                if idx == self.current_world_index {
+                    self.needs_output_redraw = true;
+                }
+            }
+            if world.filter_index.is_built() {
+                world.filter_index.sync(&world.output_lines);
+            }
+        }
+    }
+
+    /// Rerun the F4 filter over the current world's output
+    pub(crate) fn refresh_filter(&mut self) {
+        let world = &mut self.worlds[self.current_world_index];
+        world.filter_index.sync(&world.output_lines);
+        self.filter_popup.update_filter(&world.filter_index);
+    }
+
+    /// Terminal focus report (crossterm FocusGained/FocusLost). Regaining focus
+    /// re-arms the activity notification for every world.
+    pub(crate) fn set_terminal_focus(&mut self, focused: bool) {
+        self.terminal_focused = focused;
+        if focused {
+            for world in &mut self.worlds {
+                world.activity_notified = false;
+            }
+        }
+    }
+
+    /// Re-evaluate profile schedules against the local clock, announcing and
+    /// broadcasting any change. Cheap enough to call on every tick and packet.
+    pub fn refresh_active_profile(&mut self) {
+        let now = util::local_time_now();
+        let minute = (now.hour * 60 + now.minute) as u16;
+        let active = profiles::active_profile(&self.settings.profiles, now.weekday as usize, minute)
+            .map(|p| p.name.clone());
+        if active == self.active_profile {
+            return;
+        }
+        let msg = match &active {
+            Some(name) => format!("Profile '{}' is now active.", name),
+            None => format!("Profile '{}' ended.", self.active_profile.as_deref().unwrap_or("")),
+        };
+        self.active_profile = active;
+        self.add_output(&msg);
+        self.ws_broadcast(WsMessage::ActiveProfileChanged {
+            profile: self.active_profile.clone().unwrap_or_default(),
+        });
+        self.needs_output_redraw = true;
+    }
+
     fn add_output(&mut self, text: &str) {
         let is_current = true;
         let settings = self.settings.clone();
@@ -5335,6 +7156,87 @@ impl App {
         Some(mismatch)
     }
 
+    /// WorldAdded payload for a freshly created (never connected) world
+    /// World settings as sent to authenticated web/GUI/remote clients
+    pub fn world_settings_msg(&self, idx: usize) -> WorldSettingsMsg {
+        let world = &self.worlds[idx];
+        WorldSettingsMsg {
+            hostname: world.settings.hostname.clone(),
+            port: world.settings.port.clone(),
+            user: world.settings.user.clone(),
+            password: {
+                let p = persistence::decrypt_password(&world.settings.password);
+                if p.starts_with("ENC:") { String::new() } else { p }
+            },
+            has_password: !world.settings.password.is_empty(),
+            use_ssl: world.settings.use_ssl,
+            tls_verify: world.settings.tls_verify.name().to_string(),
+            tls_client_cert: world.settings.tls_client_cert.clone(),
+            tls_client_key: world.settings.tls_client_key.clone(),
+            ssh_host: world.settings.ssh_host.clone(),
+            ssh_user: world.settings.ssh_user.clone(),
+            ssh_key: world.settings.ssh_key.clone(),
+            log_enabled: world.settings.log_enabled,
+            log_format: world.settings.log_format.name().to_string(),
+            log_timestamps: world.settings.log_timestamps.name().to_string(),
+            log_session_markers: world.settings.log_session_markers,
+            encoding: world.settings.encoding.name().to_string(),
+            auto_connect_type: world.settings.auto_connect_type.name().to_string(),
+            login_script: world.settings.login_script.clone(),
+            connect_commands: world.settings.connect_commands.clone(),
+            input_prefix: world.settings.input_prefix.clone(),
+            input_suffix: world.settings.input_suffix.clone(),
+            keep_alive_type: world.settings.keep_alive_type.name().to_string(),
+            keep_alive_cmd: world.settings.keep_alive_cmd.clone(),
+            keep_alive_interval_secs: world.settings.keep_alive_interval_secs,
+            silence_alert_mins: world.settings.silence_alert_mins,
+            gmcp_packages: world.settings.gmcp_packages.clone(),
+            auto_reconnect_secs: world.settings.auto_reconnect_display(),
+            numpad_walk: world.settings.numpad_walk,
+            timestamps: world.settings.timestamps.map_or("", |m| m.name()).to_string(),
+            notify_muted: world.settings.notify_muted,
+            notify_sound: world.settings.notify_sound.clone(),
+            inline_images: world.settings.inline_images,
+            bell: world.settings.bell.name().to_string(),
+            emoji_shortcodes: world.settings.emoji_shortcodes,
+            autocorrect: world.settings.autocorrect,
+            palette: world.settings.palette.clone(),
+            tint: world.settings.tint.clone(),
+            spell_language: world.settings.spell_language.clone(),
+            macros: world.settings.macros.clone(),
+        }
+    }
+
+    fn new_world_state_msg(&self, idx: usize) -> WorldStateMsg {
+        let world = &self.worlds[idx];
+        WorldStateMsg {
+            index: idx,
+            name: world.name.clone(),
+            connected: false,
+            output_lines: Vec::new(),
+            pending_lines: Vec::new(),
+            output_lines_ts: Vec::new(),
+            pending_lines_ts: Vec::new(),
+            prompt: String::new(),
+            scroll_offset: 0,
+            paused: false,
+            unseen_lines: 0,
+            settings: self.world_settings_msg(idx),
+            last_send_secs: None,
+            last_recv_secs: None,
+            last_nop_secs: None,
+            keep_alive_type: world.settings.keep_alive_type.name().to_string(),
+            showing_splash: world.showing_splash,
+            was_connected: false,
+            is_proxy: false,
+            gmcp_user_enabled: world.gmcp_user_enabled,
+            total_output_lines: 0,
+            pending_count: 0,
+            bell_count: 0,
+            resumed: false,
+        }
+    }
+
     /// Add output to a specific world by index (for background connection events)
     /// Also broadcasts to WebSocket clients viewing the world
     fn add_output_to_world(&mut self, world_idx: usize, text: &str) {
@@ -5624,6 +7526,26 @@ impl App {
         }
     }
 
+    /// Show one client a command's output (newline-separated lines) in `world_index`
+    pub(crate) fn send_command_output(&self, client_id: u64, world_index: usize, lines: impl Into<String>) {
+        self.ws_send_to_client(client_id, WsMessage::ServerData {
+            world_index,
+            data: lines.into(),
+            is_viewed: false,
+            ts: current_timestamp_secs(),
+            from_server: false,
+            seq: 0,
+            marked_new: false,
+            flush: false, gagged: false,
+        });
+    }
+
+    /// Show one client a line in the world it's viewing (replies to its requests)
+    pub(crate) fn ws_send_client_line(&self, client_id: u64, text: &str) {
+        let world_index = self.ws_client_worlds.get(&client_id).map_or(self.current_world_index, |v| v.world_index);
+        self.send_command_output(client_id, world_index, text);
+    }
+
     /// Send active media for a world to a specific client (for world switch restart)
     fn ws_send_active_media_to_client(&self, client_id: u64, world_idx: usize) {
         if world_idx >= self.worlds.len() || !self.worlds[world_idx].gmcp_user_enabled {
@@ -5752,42 +7674,146 @@ impl App {
                 log.push(msg.clone());
             }
         }
-        // Send to embedded GUI if it's viewing this world
-        if let Some(ref tx) = self.gui_tx {
-            // For embedded GUI, always send (it tracks its own current world)
-            let _ = tx.send(msg.clone());
+        // Send to embedded GUI if it's viewing this world
+        if let Some(ref tx) = self.gui_tx {
+            // For embedded GUI, always send (it tracks its own current world)
+            let _ = tx.send(msg.clone());
+        }
+        // Broadcast to WebSocket clients viewing this world
+        if let Some(ref server) = self.ws_server {
+            server.broadcast_to_world_viewers(world_index, msg);
+        }
+    }
+
+    /// Check if any non-paused WS client is currently viewing a specific world.
+    /// Paused clients don't suppress activity notices for their world.
+    fn ws_client_viewing(&self, world_index: usize) -> bool {
+        self.ws_client_worlds.values().any(|v| v.world_index == world_index && !v.paused)
+    }
+
+    /// Get the minimum visible lines among all viewers of a world (for more-mode threshold)
+    /// Returns None if no WS clients are viewing the world (use console output_height)
+    fn min_viewer_lines(&self, world_index: usize) -> Option<usize> {
+        let ws_min = self.ws_client_worlds
+            .values()
+            .filter(|v| v.world_index == world_index && v.visible_lines > 0)
+            .map(|v| v.visible_lines)
+            .min();
+        ws_min
+    }
+
+    /// Get the minimum visible columns among WS clients viewing a world (for wrap width)
+    /// Returns None if no WS clients have reported their width
+    fn min_viewer_width(&self, world_index: usize) -> Option<usize> {
+        self.ws_client_worlds
+            .values()
+            .filter(|v| v.world_index == world_index && v.visible_columns > 0)
+            .map(|v| v.visible_columns)
+            .min()
+    }
+
+    /// With Local Echo on, show a line the user sent in `world_idx`'s output, dim
+    /// italic so it stands apart from the server's. A line answering a password
+    /// prompt, or matching the world's password, is left out.
+    pub fn echo_sent(&mut self, world_idx: usize, text: &str) {
+        let Some(world) = self.worlds.get(world_idx) else { return };
+        if !world.settings.local_echo || text.is_empty() {
+            return;
+        }
+        let secret = (!world.settings.password.is_empty() && text == world.settings.password)
+            || world.at_password_prompt();
+        if !secret {
+            self.add_output_to_world(world_idx, &format!("\x1b[3;90m{}\x1b[0m", text));
+        }
+    }
+
+    /// Send the next line of a /sendfile (see sendfile.rs), ending it after the last
+    /// line or once the world has disconnected
+    pub fn send_file_line(&mut self, world_idx: usize, line: String) {
+        let world = &mut self.worlds[world_idx];
+        let Some(job) = world.send_file.as_mut() else {
+            return; // aborted with lines still queued
+        };
+        let sent = world.connected
+            && world.command_tx.as_ref().is_some_and(|tx| tx.try_send(WriteCommand::Text(line)).is_ok());
+        let notice = if !sent {
+            Some(format!("Stopped sending {} to {}: not connected ({} of {} lines sent).", job.file, world.name, job.sent, job.total))
+        } else {
+            world.last_send_time = Some(std::time::Instant::now());
+            job.sent += 1;
+            (job.sent >= job.total).then(|| format!("Sent {} ({} lines) to {}.", job.file, job.total, world.name))
+        };
+        if notice.is_some() {
+            if let Some(job) = world.send_file.take() {
+                job.abort();
+            }
+        }
+        self.broadcast_send_progress(world_idx);
+        if let Some(notice) = notice {
+            self.add_output_to_world(world_idx, &notice);
+        }
+        if world_idx == self.current_world_index {
+            self.needs_output_redraw = true;
+        }
+    }
+
+    /// Show or send a line printed by a /shell or /pipe program (see shell.rs)
+    pub fn shell_output(&mut self, world_idx: usize, output: shell::Output, line: String) {
+        let notice = if output == shell::Output::Send {
+            let world = &mut self.worlds[world_idx];
+            let sent = world.connected
+                && world.command_tx.as_ref().is_some_and(|tx| tx.try_send(WriteCommand::Text(line.clone())).is_ok());
+            if sent {
+                world.last_send_time = Some(std::time::Instant::now());
+                return;
+            }
+            format!("Not connected; not sent: {}", line)
+        } else {
+            line
+        };
+        self.add_output_to_world(world_idx, &notice);
+        if world_idx == self.current_world_index {
+            self.needs_output_redraw = true;
+        }
+    }
+
+    /// Track the server's IAC WILL/WONT ECHO for `world_idx`; while it echoes, every
+    /// interface masks the input area (see `input_masked`)
+    pub fn set_server_echo(&mut self, world_idx: usize, on: bool) {
+        if self.worlds[world_idx].server_echo == on {
+            return;
         }
-        // Broadcast to WebSocket clients viewing this world
-        if let Some(ref server) = self.ws_server {
-            server.broadcast_to_world_viewers(world_index, msg);
+        self.worlds[world_idx].server_echo = on;
+        self.ws_broadcast(WsMessage::ServerEcho { world_index: world_idx, on });
+        if world_idx == self.current_world_index {
+            self.needs_output_redraw = true;
         }
     }
 
-    /// Check if any non-paused WS client is currently viewing a specific world.
-    /// Paused clients don't suppress activity notices for their world.
-    fn ws_client_viewing(&self, world_index: usize) -> bool {
-        self.ws_client_worlds.values().any(|v| v.world_index == world_index && !v.paused)
+    /// True while the current world's server has echo suppressed (a password prompt)
+    pub fn input_masked(&self) -> bool {
+        self.current_world().server_echo
     }
 
-    /// Get the minimum visible lines among all viewers of a world (for more-mode threshold)
-    /// Returns None if no WS clients are viewing the world (use console output_height)
-    fn min_viewer_lines(&self, world_index: usize) -> Option<usize> {
-        let ws_min = self.ws_client_worlds
-            .values()
-            .filter(|v| v.world_index == world_index && v.visible_lines > 0)
-            .map(|v| v.visible_lines)
-            .min();
-        ws_min
+    /// Tell clients how far `world_idx`'s /sendfile has got (total 0 = none running)
+    pub fn broadcast_send_progress(&mut self, world_idx: usize) {
+        let (sent, total) = self.worlds[world_idx].send_file.as_ref().map(|j| (j.sent, j.total)).unwrap_or((0, 0));
+        self.ws_broadcast(WsMessage::SendFileProgress { world_index: world_idx, sent, total });
     }
 
-    /// Get the minimum visible columns among WS clients viewing a world (for wrap width)
-    /// Returns None if no WS clients have reported their width
-    fn min_viewer_width(&self, world_index: usize) -> Option<usize> {
-        self.ws_client_worlds
-            .values()
-            .filter(|v| v.world_index == world_index && v.visible_columns > 0)
-            .map(|v| v.visible_columns)
-            .min()
+    /// Show replayed log text (see replay.rs) as if the server had sent it: actions gag
+    /// and highlight it, but it isn't logged again and their commands aren't run
+    pub fn replay_output(&mut self, world_idx: usize, text: &str, console_height: u16, console_width: u16, is_daemon_mode: bool) {
+        let world = &mut self.worlds[world_idx];
+        let saved_log = std::mem::replace(&mut world.log_override, logging::LogOverride::Stopped);
+        // Logs are UTF-8 whatever the world's encoding
+        let saved_encoding = world.negotiated_encoding.replace(Encoding::Utf8);
+        let last_receive = world.last_receive_time;
+        let _ = self.process_server_data(world_idx, text.as_bytes(), console_height, console_width, is_daemon_mode);
+        let world = &mut self.worlds[world_idx];
+        world.log_override = saved_log;
+        world.negotiated_encoding = saved_encoding;
+        world.last_receive_time = last_receive;
     }
 
     /// Process incoming server data - shared logic for both console and daemon modes
@@ -5802,6 +7828,44 @@ impl App {
     ) -> Vec<String> {
         self.worlds[world_idx].last_receive_time = Some(std::time::Instant::now());
 
+        // Low-priority background worlds are processed in batches (see throttle.rs)
+        let throttle = self.background_throttle(world_idx);
+        let batch;
+        let bytes = if throttle.is_some() || self.worlds[world_idx].background_batch.is_holding() {
+            match self.worlds[world_idx].background_batch.admit(bytes, throttle, std::time::Instant::now()) {
+                Some(b) => {
+                    batch = b;
+                    batch.as_slice()
+                }
+                None => return Vec::new(),
+            }
+        } else {
+            bytes
+        };
+
+        // A double-byte (CJK) character split across reads is held until its second byte
+        let carried;
+        let encoding = self.worlds[world_idx].effective_encoding();
+        let bytes = if !self.worlds[world_idx].decode_carry.is_empty() || encoding.incomplete_tail(bytes) > 0 {
+            let mut all = std::mem::take(&mut self.worlds[world_idx].decode_carry);
+            all.extend_from_slice(bytes);
+            let keep = encoding.incomplete_tail(&all);
+            self.worlds[world_idx].decode_carry = all.split_off(all.len() - keep);
+            carried = all;
+            carried.as_slice()
+        } else {
+            bytes
+        };
+
+        // Daemon mode has no periodic tick, so incoming data also re-checks the schedule
+        self.refresh_active_profile();
+
+        // Bells are counted from the raw bytes, since decoding strips them (see bell.rs)
+        let bells = bell::count_bells(bytes);
+        if bells > 0 {
+            self.ring_bells(world_idx, bells);
+        }
+
         // FANSI client detection: check for "Detecting client..." within 2s window
         if let Some(deadline) = self.worlds[world_idx].fansi_detect_until {
             if std::time::Instant::now() < deadline {
@@ -5820,10 +7884,13 @@ impl App {
                 }
             } else {
                 // Detection window expired — send deferred login now
-                if let Some(login_cmd) = self.worlds[world_idx].fansi_login_pending.take() {
+                if let Some(login) = self.worlds[world_idx].fansi_login_pending.take() {
                     if let Some(tx) = &self.worlds[world_idx].command_tx {
-                        let _ = tx.try_send(WriteCommand::Text(login_cmd));
+                        for line in login {
+                            let _ = tx.try_send(WriteCommand::Text(line));
+                        }
                     }
+                    self.worlds[world_idx].send_connect_commands();
                 }
                 self.worlds[world_idx].fansi_detect_until = None;
             }
@@ -5856,7 +7923,7 @@ impl App {
         }
 
         let world_name_for_triggers = self.worlds[world_idx].name.clone();
-        let actions = self.settings.actions.clone();
+        let mut actions = self.trigger_actions(world_idx);
 
         // Combine with any partial line from previous data chunk
         let had_trigger_partial = !self.worlds[world_idx].trigger_partial_line.is_empty();
@@ -5874,6 +7941,9 @@ impl App {
         let mut commands_to_execute: Vec<String> = Vec::new();
         let mut tf_commands_to_execute: Vec<String> = Vec::new();
         let mut tf_messages: Vec<String> = Vec::new();
+        let mut tag_route_copies: Vec<(String, String)> = Vec::new();
+        let mut shell_pipes: Vec<(String, String)> = Vec::new();
+        let mut loop_reports: Vec<String> = Vec::new();
         let ends_with_newline = combined_data.ends_with('\n');
         let lines: Vec<&str> = combined_data.lines().collect();
         let line_count = lines.len();
@@ -5952,10 +8022,86 @@ impl App {
                 }
 
                 let tr = process_triggers(line, &world_name_for_triggers, &actions, &mut self.tf_engine);
-                commands_to_execute.extend(tr.send_commands);
+                // Runaway loop watchdog: drop this fire's commands and stop the chain
+                let looping = tr.fired_action.as_deref()
+                    .and_then(|name| self.guard_trigger_loop(world_idx, name));
+                if let Some((chain, report)) = looping {
+                    actions.retain(|a| !chain.contains(&a.name));
+                    loop_reports.push(report);
+                } else {
+                    commands_to_execute.extend(tr.send_commands);
+                    // Action /pipe <program>: the line goes to the program's stdin
+                    shell_pipes.extend(tr.pipes.into_iter().map(|program| (program, stripped.clone())));
+                }
                 tf_commands_to_execute.extend(tr.clay_commands);
                 tf_messages.extend(tr.messages);
-                processed_lines.push((line, tr.is_gagged || watchdog_gagged, tr.highlight_color));
+                // Action /capture <name>: copied even when the same action gags the line
+                if let Some(target) = tr.capture {
+                    if !watchdog_gagged {
+                        tag_route_copies.push((target, line.to_string()));
+                    }
+                }
+                let mut gagged = tr.is_gagged || watchdog_gagged;
+                let mut highlight = tr.highlight_color;
+
+                // Tag routes (after triggers, so a trigger gag still wins)
+                if let Some(route) = tag_routes::match_route(&self.settings.tag_routes, line, &world_name_for_triggers) {
+                    match &route.action {
+                        tag_routes::TagRouteAction::Gag => gagged = true,
+                        tag_routes::TagRouteAction::GagIdle(mins) => {
+                            let idle = self.worlds[world_idx].last_user_command_time
+                                .map(|t| t.elapsed().as_secs() >= *mins as u64 * 60)
+                                .unwrap_or(true);
+                            gagged = gagged || idle;
+                        }
+                        tag_routes::TagRouteAction::Copy(target) => {
+                            if !gagged {
+                                tag_route_copies.push((target.clone(), line.to_string()));
+                            }
+                        }
+                        tag_routes::TagRouteAction::Move(target) => {
+                            if !gagged {
+                                tag_route_copies.push((target.clone(), line.to_string()));
+                            }
+                            gagged = true;
+                        }
+                        tag_routes::TagRouteAction::Highlight(color) => {
+                            highlight = highlight.or_else(|| Some(color.clone()));
+                        }
+                    }
+                }
+                // Tags gagged by the scheduled profile (see profiles.rs)
+                if let (Some(profile), Some(tag)) = (self.current_profile(), util::mud_tag_name(line)) {
+                    if profile.gag_tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                        gagged = true;
+                    }
+                }
+                if gags::is_gagged(&self.settings.gags, &stripped, &world_name_for_triggers) {
+                    gagged = true;
+                }
+                processed_lines.push((line, gagged, highlight));
+            }
+        }
+
+        // Deliver tag-routed and captured copies, creating connectionless capture worlds on first use
+        for (target, line) in tag_route_copies {
+            self.panes.note_capture(&target);
+            let target_idx = match self.find_world(&target) {
+                Some(idx) => idx,
+                None => {
+                    let idx = self.find_or_create_world(&target);
+                    let world_state = self.new_world_state_msg(idx);
+                    self.ws_broadcast(WsMessage::WorldAdded { world: Box::new(world_state) });
+                    idx
+                }
+            };
+            if target_idx != world_idx {
+                self.add_output_to_world(target_idx, &line);
+            }
+        }
+        if let Some(event_tx) = self.event_tx.clone() {
+            for (program, line) in shell_pipes {
+                shell::spawn(world_name_for_triggers.clone(), program, Some(line), shell::Output::Send, event_tx.clone());
             }
         }
 
@@ -5988,6 +8134,11 @@ impl App {
         // Save the idler filter state for next packet
         self.worlds[world_idx].just_filtered_idler = just_filtered_idler;
 
+        // A Diku or Script auto-login answers the lines (or unterminated prompt) it waits for
+        for line in &lines {
+            self.advance_login(world_idx, line);
+        }
+
         // If we have a partial line and world uses WONT ECHO prompts, start timeout
         if has_partial && self.worlds[world_idx].prompt.is_empty()
             && self.worlds[world_idx].uses_wont_echo_prompt {
@@ -6025,6 +8176,9 @@ impl App {
 
         // Add non-gagged output to world
         if !filtered_data.is_empty() {
+            if !is_daemon_mode {
+                self.notify_activity(world_idx, &filtered_data);
+            }
             let settings = self.settings.clone();
 
             // Calculate minimum visible lines among all viewers for synchronized more-mode
@@ -6190,14 +8344,10 @@ impl App {
                 self.ws_broadcast(WsMessage::PendingLinesUpdate { world_index: world_idx, count: pending_after });
             }
 
-            // Broadcast updated unseen count so all clients stay in sync
-            let unseen_count = self.worlds[world_idx].unseen_lines;
-            if unseen_count > 0 {
-                self.ws_broadcast(WsMessage::UnseenUpdate {
-                    world_index: world_idx,
-                    count: unseen_count,
-                });
-            }
+            // Count the new lines as unseen by the console and each client not viewing them
+            let new_lines = output_after.saturating_sub(output_before) + lines_to_pending;
+            self.note_unseen_lines(world_idx, new_lines, console_viewing, is_current);
+            self.push_activity(world_idx, &filtered_data);
 
             // Broadcast activity count to keep all clients in sync
             self.broadcast_activity();
@@ -6205,6 +8355,7 @@ impl App {
             // Text-to-speech: speak non-gagged MUD output when TTS is enabled and not muted
             // Only speak output from the currently visible world
             if self.settings.tts_mode != tts::TtsMode::Off && !self.settings.tts_muted
+                && self.current_profile().map_or(true, |p| p.tts)
                 && (world_idx == self.current_world_index || self.ws_client_viewing(world_idx))
             {
                 // Filter lines based on speak mode
@@ -6249,7 +8400,7 @@ impl App {
             self.worlds[world_idx].next_seq += 1;
             let mut output_line = OutputLine::new_gagged(line.to_string(), seq);
             output_line.highlight_color = highlight;
-            self.worlds[world_idx].output_lines.push(output_line);
+            self.worlds[world_idx].output_lines.push_back(output_line);
             // Archive gagged server lines to long-term scrollback (with gagged=true)
             if let Some(ref tx) = self.worlds[world_idx].scrollback_tx {
                 let ts_ms = std::time::SystemTime::now()
@@ -6287,6 +8438,9 @@ impl App {
         for msg in tf_messages {
             self.add_tf_output(&msg);
         }
+        for report in loop_reports {
+            self.add_output_to_world(world_idx, &report);
+        }
 
         // Merge TF commands into commands_to_execute
         commands_to_execute.extend(tf_commands_to_execute);
@@ -6305,6 +8459,7 @@ impl App {
             let _ = self.tf_engine.execute(&cmd);
         }
 
+        self.set_server_echo(world_idx, false);
         let more_mode = self.settings.more_mode_enabled;
         // Push prompt to output before clearing
         if !self.worlds[world_idx].prompt.is_empty() {
@@ -6425,7 +8580,7 @@ impl App {
     /// Handle CharsetRequested event (RFC 2066 TELNET CHARSET).
     /// Selects the best charset from the offered list that Clay supports.
     fn handle_charset_requested(&mut self, world_idx: usize, charsets: &[String]) {
-        // Priority order: UTF-8 > Latin1 > Fansi
+        // Priority order: UTF-8, then the first other charset offered
         // First pass: look for UTF-8 (highest capability)
         // Second pass: accept first supported charset from offered list
         let mut best: Option<(Encoding, &str)> = None;
@@ -6439,11 +8594,7 @@ impl App {
                     }
                     _ => {
                         if best.is_none() {
-                            best = Some((enc, match enc {
-                                Encoding::Latin1 => "ISO-8859-1",
-                                Encoding::Fansi => "IBM437",
-                                Encoding::Utf8 => unreachable!(),
-                            }));
+                            best = Some((enc, enc.iana_name()));
                         }
                     }
                 }
@@ -6455,6 +8606,7 @@ impl App {
                 let response = build_charset_accepted(iana_name);
                 let _ = tx.try_send(WriteCommand::Raw(response));
                 self.worlds[world_idx].negotiated_encoding = Some(enc);
+                self.worlds[world_idx].sync_send_encoding();
             } else {
                 let response = build_charset_rejected();
                 let _ = tx.try_send(WriteCommand::Raw(response));
@@ -6473,7 +8625,7 @@ impl App {
         if !self.worlds[world_idx].connected {
             let seq = self.worlds[world_idx].next_seq;
             self.worlds[world_idx].next_seq += 1;
-            self.worlds[world_idx].output_lines.push(OutputLine::new(prompt_normalized.trim().to_string(), seq));
+            self.worlds[world_idx].output_lines.push_back(OutputLine::new(prompt_normalized.trim().to_string(), seq));
             self.worlds[world_idx].scroll_to_bottom();
             self.worlds[world_idx].prompt.clear();
             if world_idx == self.current_world_index {
@@ -6488,6 +8640,8 @@ impl App {
             prompt: prompt_normalized,
         });
 
+        self.advance_login(world_idx, &prompt_text);
+
         let world = &mut self.worlds[world_idx];
         world.prompt_count += 1;
 
@@ -6497,8 +8651,8 @@ impl App {
         }
 
         let auto_type = world.settings.auto_connect_type;
-        let user = world.settings.user.clone();
-        let password = world.settings.password.clone();
+        let user = secrets::expand(&world.settings.user, &world.settings.secrets, &self.settings.secrets);
+        let password = secrets::expand(&world.settings.password, &world.settings.secrets, &self.settings.secrets);
         let prompt_num = world.prompt_count;
 
         if !user.is_empty() && !password.is_empty() {
@@ -6518,7 +8672,7 @@ impl App {
                         _ => None,
                     }
                 }
-                AutoConnectType::Connect | AutoConnectType::NoLogin => None,
+                _ => None,
             };
 
             if let Some(cmd) = cmd_to_send {
@@ -6528,6 +8682,47 @@ impl App {
                     // Clear prompt since we auto-answered it
                     world.prompt.clear();
                 }
+                if prompt_num == auto_type.login_prompts() {
+                    world.send_connect_commands();
+                }
+            }
+        }
+    }
+
+    /// Step a Diku or Script auto-login with a line (or prompt) from the server,
+    /// sending whatever the step it matches answers with
+    pub(crate) fn advance_login(&mut self, world_idx: usize, text: &str) {
+        if !self.worlds[world_idx].connected {
+            return; // replayed output
+        }
+        let connection_id = self.worlds[world_idx].connection_id;
+        if self.worlds[world_idx].login_progress.as_ref().map(|p| p.connection_id) != Some(connection_id) {
+            let (user, password) = self.login_credentials(world_idx);
+            let script = if self.worlds[world_idx].skip_auto_login || user.is_empty() || password.is_empty() {
+                None
+            } else {
+                match self.worlds[world_idx].settings.login_waits(&user, &password) {
+                    Ok(script) => script,
+                    Err(e) => {
+                        self.add_output_to_world(world_idx, &format!("Login script: {}", e));
+                        None
+                    }
+                }
+            };
+            self.worlds[world_idx].login_progress = Some(login::LoginProgress::new(connection_id, script));
+        }
+        let world = &mut self.worlds[world_idx];
+        let answer = world.login_progress.as_mut().and_then(|p| p.answer(&util::strip_ansi_codes(text)));
+        if let Some((sends, done)) = answer {
+            if let Some(tx) = &world.command_tx {
+                for line in sends {
+                    let _ = tx.try_send(WriteCommand::Text(line));
+                }
+                world.last_send_time = Some(std::time::Instant::now());
+                world.prompt.clear();
+            }
+            if done {
+                world.send_connect_commands();
             }
         }
     }
@@ -6636,6 +8831,8 @@ impl App {
 
     /// Handle WsClientDisconnected event.
     fn handle_ws_client_disconnected(&mut self, client_id: u64) {
+        self.ws_client_tokens.remove(&client_id);
+        self.ws_client_unseen.remove(&client_id);
         // Check if this client had NAWS dimensions, and recalculate if needed
         if let Some(state) = self.ws_client_worlds.get(&client_id) {
             if state.dimensions.is_some() {
@@ -6655,27 +8852,40 @@ impl App {
 
     /// Handle WsAuthKeyValidation event.
     fn handle_ws_auth_key_validation(&mut self, client_id: u64, msg: WsMessage, client_ip: &str, challenge: &str) {
-        if let WsMessage::AuthRequest { auth_key: Some(key), current_world, challenge_response: uses_challenge, .. } = msg {
-            let has_key = self.settings.websocket_auth_key.is_some();
+        if let WsMessage::AuthRequest { auth_key: Some(key), current_world, challenge_response: uses_challenge, resume_seqs, .. } = msg {
+            let has_key = self.settings.websocket_auth_key.is_some() || !self.settings.device_tokens.is_empty();
 
             crate::http::log_remote_event("WS-KEY", client_ip,
                 &format!("challenge={}, has_stored_key={}", uses_challenge, has_key));
 
-            // If challenge_response, client sent SHA256(auth_key + challenge)
-            // We compute SHA256(stored_key + challenge) and compare
-            let is_valid = if let Some(ref ak) = self.settings.websocket_auth_key {
+            // If challenge_response, client sent SHA256(SHA256(auth_key) + challenge), as
+            // for password login; we compute it from the stored hash and compare. Clients
+            // from before device tokens sent SHA256(auth_key + challenge), which the shared
+            // key (the only one kept in full) still accepts.
+            let matches = |key_hash: &str| {
                 if uses_challenge {
-                    hash_with_challenge(&ak.key, challenge) == key
+                    hash_with_challenge(key_hash, challenge) == key
                 } else {
                     // B3 (security remediation): constant-time compare — static stored-secret
                     // vs. client-supplied-secret compare (no per-connection challenge salt).
-                    crate::util::constant_time_eq(ak.key.as_bytes(), key.as_bytes())
+                    crate::util::constant_time_eq(key_hash.as_bytes(), hash_password(&key).as_bytes())
                 }
-            } else {
-                false
             };
+            let shared_key = self.settings.websocket_auth_key.as_ref().is_some_and(|ak| {
+                matches(&hash_password(&ak.key)) || (uses_challenge && hash_with_challenge(&ak.key, challenge) == key)
+            });
+            let token = if shared_key { None } else { self.settings.device_tokens.iter().position(|t| matches(&t.key_hash)) };
+            let is_valid = shared_key || token.is_some();
             if is_valid {
-                crate::http::log_remote_event("WS-KEY-OK", client_ip, "accepted");
+                if let Some(i) = token {
+                    let token = &mut self.settings.device_tokens[i];
+                    token.last_used = Some(std::time::SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs());
+                    crate::http::log_remote_event("WS-KEY-OK", client_ip, &format!("accepted (device token '{}')", token.name));
+                    self.ws_client_tokens.insert(client_id, token.name.clone());
+                    let _ = persistence::save_settings(self);
+                } else {
+                    crate::http::log_remote_event("WS-KEY-OK", client_ip, "accepted");
+                }
                 // Clear any accumulated violations so reconnect failures don't ban the client
                 self.ban_list.clear_violations(client_ip);
                 self.ws_set_client_authenticated(client_id, true);
@@ -6685,7 +8895,7 @@ impl App {
                     username: None,
                     multiuser_mode: false,
                 });
-                let initial_state = self.build_initial_state();
+                let initial_state = self.build_resumed_initial_state(&resume_seqs);
                 self.ws_send_initial_state_and_mark(client_id, initial_state);
                 let world_idx = current_world
                     .filter(|&w| w < self.worlds.len())
@@ -6724,11 +8934,12 @@ impl App {
             self.ws_broadcast(WsMessage::Notification {
                 title: "Auth Key".to_string(),
                 message: "Failed to generate a new auth key: secure random number generator unavailable".to_string(),
+                sound: String::new(),
             });
             return;
         };
         self.settings.websocket_auth_key = Some(AuthKey::new(key.clone()));
-        *self.ws_auth_key_shared.write().unwrap() = Some(key.clone());
+        self.sync_auth_keys_shared();
         let _ = persistence::save_settings(self);
         // Broadcast to ALL clients so every web UI updates its displayed key
         self.ws_broadcast(WsMessage::KeyGenerated { auth_key: key });
@@ -6737,8 +8948,125 @@ impl App {
     /// Handle WsKeyRevoke event — clear the single auth key.
     fn handle_ws_key_revoke(&mut self, _key: &str) {
         self.settings.websocket_auth_key = None;
-        *self.ws_auth_key_shared.write().unwrap() = None;
+        self.sync_auth_keys_shared();
+        let _ = persistence::save_settings(self);
+    }
+
+    /// Mirror every accepted key into ws_auth_keys_shared for the knock: clients knock
+    /// with SHA-256 of their key (the auth key or a device token), older ones with the
+    /// auth key itself
+    pub(crate) fn sync_auth_keys_shared(&self) {
+        let keys = self.settings.websocket_auth_key.iter().flat_map(|ak| [ak.key.clone(), hash_password(&ak.key)])
+            .chain(self.settings.device_tokens.iter().map(|t| t.key_hash.clone()))
+            .collect();
+        *self.ws_auth_keys_shared.write().unwrap() = keys;
+    }
+
+    /// Issue a device token named `name` (/web tokens add), returning its key. The key
+    /// is only ever shown to the one who created it; only its hash is kept.
+    pub(crate) fn create_device_token(&mut self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().any(char::is_control) {
+            return Err("Give the device token a name, e.g. /web tokens add phone".to_string());
+        }
+        if self.settings.device_tokens.iter().any(|t| t.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("There's already a device token named '{}'.", name));
+        }
+        let Some(key) = App::generate_auth_key() else {
+            // RNG failure: fail closed, as handle_ws_key_request does
+            return Err("Failed to generate a device token: secure random number generator unavailable".to_string());
+        };
+        let created = AuthKey::new(key.clone());
+        self.settings.device_tokens.push(DeviceToken {
+            name: name.to_string(),
+            key_hash: hash_password(&created.key),
+            created_at: created.created_at,
+            last_used: None,
+        });
+        self.sync_auth_keys_shared();
+        let _ = persistence::save_settings(self);
+        Ok(key)
+    }
+
+    /// Revoke the device token named `name`, signing out the clients that used it.
+    /// Returns the token's name as stored, or None if there's no such token.
+    pub(crate) fn revoke_device_token(&mut self, name: &str) -> Option<String> {
+        let pos = self.settings.device_tokens.iter().position(|t| t.name.eq_ignore_ascii_case(name.trim()))?;
+        let token = self.settings.device_tokens.remove(pos);
+        self.sync_auth_keys_shared();
         let _ = persistence::save_settings(self);
+        let signed_in: Vec<u64> = self.ws_client_tokens.iter()
+            .filter(|(_, used)| **used == token.name)
+            .map(|(&client_id, _)| client_id)
+            .collect();
+        for client_id in signed_in {
+            self.ws_client_tokens.remove(&client_id);
+            if let Some(ref server) = self.ws_server {
+                server.clear_client_auth(client_id);
+            }
+            self.ws_send_to_client(client_id, WsMessage::AuthResponse {
+                success: false,
+                error: Some("This device's key was revoked".to_string()),
+                username: None,
+                multiuser_mode: false,
+            });
+        }
+        Some(token.name)
+    }
+
+    /// The device tokens as clients see them (no keys)
+    pub(crate) fn device_token_infos(&self) -> Vec<websocket::DeviceTokenInfo> {
+        self.settings.device_tokens.iter()
+            .map(|t| websocket::DeviceTokenInfo { name: t.name.clone(), created_at: t.created_at, last_used: t.last_used })
+            .collect()
+    }
+
+    /// Carry out a /web tokens request, answering with the token list (and the new
+    /// key, sent only to the client that asked)
+    pub(crate) fn device_tokens_reply(&mut self, request: commands::DeviceTokenRequest) -> WsMessage {
+        use commands::DeviceTokenRequest;
+        let (mut new_token, mut message) = (None, None);
+        match request {
+            DeviceTokenRequest::List => {}
+            DeviceTokenRequest::Create(name) => match self.create_device_token(&name) {
+                Ok(token) => new_token = Some(websocket::NewDeviceToken { name: name.trim().to_string(), token }),
+                Err(e) => message = Some(e),
+            },
+            DeviceTokenRequest::Revoke(name) => message = Some(match self.revoke_device_token(&name) {
+                Some(name) => format!("Revoked the device token '{}'.", name),
+                None => format!("There's no device token named '{}'.", name.trim()),
+            }),
+        }
+        WsMessage::DeviceTokens { tokens: self.device_token_infos(), new_token, message }
+    }
+
+    /// /web tokens on the console
+    pub(crate) fn apply_device_token_request(&mut self, request: commands::DeviceTokenRequest) {
+        if let WsMessage::DeviceTokens { tokens, new_token, message } = self.device_tokens_reply(request) {
+            self.show_device_tokens(&tokens, new_token.as_ref(), message);
+        }
+    }
+
+    /// Show a DeviceTokens answer: its message in the output, and the popup (refreshed
+    /// if it's up; opened unless the answer is just a message)
+    pub(crate) fn show_device_tokens(&mut self, tokens: &[websocket::DeviceTokenInfo], new_token: Option<&websocket::NewDeviceToken>, message: Option<String>) {
+        use popup::definitions::device_tokens::{create_device_tokens_popup, TOKENS_FIELD_LIST};
+
+        let is_open = self.popup_manager.current().is_some_and(|s| s.definition.id == popup::PopupId("device_tokens"));
+        let show_popup = is_open || message.is_none();
+        if let Some(message) = message {
+            self.add_output(&message);
+        }
+        if !show_popup {
+            return;
+        }
+        if is_open {
+            self.popup_manager.close();
+        }
+        self.popup_manager.open(create_device_tokens_popup(tokens, new_token, 10.min(tokens.len().max(1))));
+        if let Some(state) = self.popup_manager.current_mut() {
+            state.select_field(TOKENS_FIELD_LIST);
+        }
     }
 
     /// Generate a new auth key string. Fails closed (returns `None`) rather than
@@ -6771,7 +9099,7 @@ impl App {
     }
 
     /// Handle initial WsClientMessage (AuthRequest) after authentication.
-    fn handle_ws_auth_initial_state(&mut self, client_id: u64, current_world: Option<usize>) {
+    fn handle_ws_auth_initial_state(&mut self, client_id: u64, current_world: Option<usize>, resume_seqs: &std::collections::HashMap<String, u64>) {
         // Debug: log current world state for reload message diagnosis
         let cw = self.current_world_index;
         if cw < self.worlds.len() {
@@ -6782,15 +9110,16 @@ impl App {
             ));
             // Log last 3 lines of output to verify reload message is present
             let start = world.output_lines.len().saturating_sub(3);
-            for (i, line) in world.output_lines[start..].iter().enumerate() {
+            for (i, line) in world.output_lines.range(start..).enumerate() {
                 debug_log(is_debug_enabled(), &format!(
                     "AUTH_INITIAL_STATE: output_line[{}] from_server={} text='{}'",
                     start + i, line.from_server, line.text.trim()
                 ));
             }
         }
-        let initial_state = self.build_initial_state();
+        let initial_state = self.build_resumed_initial_state(resume_seqs);
         self.ws_send_initial_state_and_mark(client_id, initial_state);
+        self.ws_seed_client_unseen(client_id);
         let world_idx = current_world
             .filter(|&w| w < self.worlds.len())
             .unwrap_or(self.current_world_index);
@@ -6835,7 +9164,7 @@ impl App {
 
             // Open log file if enabled
             if self.worlds[world_idx].settings.log_enabled {
-                if self.worlds[world_idx].open_log_file() {
+                if self.worlds[world_idx].start_log_session() {
                     let log_path = self.worlds[world_idx].get_log_path();
                     self.add_output_to_world(world_idx, &format!("Logging to: {}", log_path.display()));
                 } else {
@@ -6854,27 +9183,33 @@ impl App {
 
             // Send auto-login if configured
             let skip_login = self.worlds[world_idx].skip_auto_login;
-            let user = self.worlds[world_idx].settings.user.clone();
-            let password = self.worlds[world_idx].settings.password.clone();
-            let auto_connect_type = self.worlds[world_idx].settings.auto_connect_type;
-            // Only clear skip flag here for Connect type; Prompt/MooPrompt check it later in handle_prompt
-            if auto_connect_type == AutoConnectType::Connect {
+            let (user, password) = self.login_credentials(world_idx);
+            // Only clear skip flag here when the login is sent now; logins that wait
+            // for prompts check it later (handle_prompt, advance_login)
+            if !self.worlds[world_idx].settings.login_waits_for_server() {
                 self.worlds[world_idx].skip_auto_login = false;
             }
+            let login = if !skip_login && !user.is_empty() && !password.is_empty() {
+                self.worlds[world_idx].settings.login_lines(&user, &password)
+            } else {
+                Vec::new()
+            };
             // FANSI worlds: always set up client detection window
             if self.worlds[world_idx].settings.encoding == Encoding::Fansi {
                 self.worlds[world_idx].fansi_detect_until = Some(std::time::Instant::now() + Duration::from_secs(2));
-                if !skip_login && !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                    let connect_cmd = format!("connect {} {}", user, password);
-                    self.worlds[world_idx].fansi_login_pending = Some(connect_cmd);
+                if !login.is_empty() {
+                    self.worlds[world_idx].fansi_login_pending = Some(login);
+                }
+            } else {
+                for line in login {
+                    let _ = cmd_tx.try_send(WriteCommand::Text(line));
                 }
-            } else if !skip_login && !user.is_empty() && !password.is_empty() && auto_connect_type == AutoConnectType::Connect {
-                let connect_cmd = format!("connect {} {}", user, password);
-                let _ = cmd_tx.try_send(WriteCommand::Text(connect_cmd));
             }
+            self.worlds[world_idx].start_connect_commands(skip_login, !user.is_empty() && !password.is_empty());
 
             // Broadcast connection status
             self.ws_broadcast(WsMessage::WorldConnected { world_index: world_idx, name: self.worlds[world_idx].name.clone() });
+            self.note_bulk_connect(world_name, true);
         }
     }
 
@@ -6922,9 +9257,11 @@ impl App {
         let rewritten = rewrite_slashless_action(command, &self.settings.actions, &world_name);
         let command = rewritten.as_deref().unwrap_or(command);
         // Use shared command parsing
-        let parsed = parse_command(command);
+        let parsed = self.resolve_open_uri(parse_command(command));
 
         match parsed {
+            // Already turned into a WorldSwitch by resolve_open_uri
+            Command::OpenUri { .. } => {}
             // Commands handled locally on server
             Command::ActionCommand { name, args } => {
                 // Execute action if it exists (respects the action's world field).
@@ -6932,10 +9269,11 @@ impl App {
                     let commands = split_action_commands(&action.command);
                     let mut sent_to_server = false;
                     for cmd in commands {
-                            // Substitute $1-$9 and $* with arguments
-                            let cmd = substitute_action_args(&cmd, &args);
+                            // Expand secrets first so arguments can't reference one, then $1-$9 and $*,
+                            // then $[...] rolls
+                            let cmd = crate::dice::expand(&substitute_action_args(&self.expand_secrets(world_index, &cmd), &args));
 
-                            if cmd.eq_ignore_ascii_case("/gag") || cmd.to_lowercase().starts_with("/gag ") {
+                            if cmd.eq_ignore_ascii_case("/gag") || cmd.to_lowercase().starts_with("/gag ") || crate::capture::action_target(&cmd).is_some() {
                                 continue;
                             }
                             // Unified command system - route through TF parser
@@ -7116,8 +9454,10 @@ impl App {
             Command::NotACommand { text } => {
                 // Regular text - send to MUD
                 if world_index < self.worlds.len() {
+                    let text = self.worlds[world_index].wrap_input(&text);
                     if let Some(tx) = &self.worlds[world_index].command_tx {
-                        if tx.try_send(WriteCommand::Text(text)).is_ok() {
+                        if tx.try_send(WriteCommand::Text(text.clone())).is_ok() {
+                            self.echo_sent(world_index, &text);
                             self.worlds[world_index].last_send_time = Some(std::time::Instant::now());
                             self.worlds[world_index].prompt.clear();
                         }
@@ -7128,16 +9468,7 @@ impl App {
                 // WS clients must collect the password/auth-key client-side and send a
                 // dedicated ImportSettings message instead (see plan) - a bounced /import
                 // typed on the command line can't carry secrets, so it's rejected here.
-                self.ws_send_to_client(client_id, WsMessage::ServerData {
-                    world_index,
-                    data: "Use the /import dialog (not the command line) so your password/auth-key aren't sent unprotected.".to_string(),
-                    is_viewed: false,
-                    ts: current_timestamp_secs(),
-                    from_server: false,
-                    seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                });
+                self.send_command_output(client_id, world_index, "Use the /import dialog (not the command line) so your password/auth-key aren't sent unprotected.");
             }
             Command::Edit { .. } | Command::EditList => {
                 // Edit command is handled locally on the client, not on server
@@ -7149,6 +9480,179 @@ impl App {
                 self.show_tags = !self.show_tags;
                 self.ws_broadcast(WsMessage::ShowTagsChanged { show_tags: self.show_tags });
             }
+            Command::Macro { args } => {
+                // No popup on web/GUI - bare /macro lists the world's macros
+                let output = commands::macro_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::TagRoute { args } if args.is_empty() => {
+                // Bare /tagroute opens the client's own tag routes popup
+                self.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.to_string() });
+            }
+            Command::TagRoute { args } => {
+                let output = commands::tag_route_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Gag { args } if args.is_empty() => {
+                // Bare /gag opens the client's own gags popup
+                self.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.to_string() });
+            }
+            Command::Gag { args } => {
+                let output = commands::gag_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Ungag { args } => {
+                let output = commands::ungag_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Profile { args } => {
+                let output = commands::profile_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Config { args } => {
+                let output = commands::config_command(&args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Numpad { args } => {
+                let output = commands::numpad_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Secret { args } => {
+                let output = commands::secret_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::LoopGuard { args } => {
+                let output = commands::loopguard_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Clipboard { args } => {
+                let output = commands::clipboard_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Timestamps { args } => {
+                let output = commands::timestamps_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Bell { args } => {
+                let output = commands::bell_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Replay { args } => {
+                let event_tx = self.event_tx.clone();
+                let output = commands::replay_command(self, world_index, &args, event_tx.as_ref()).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::SendFile { args } => {
+                let event_tx = self.event_tx.clone();
+                let output = commands::sendfile_command(self, world_index, &args, event_tx.as_ref()).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Shell { command, pipe } => {
+                let event_tx = self.event_tx.clone();
+                let output = commands::shell_command(self, world_index, &command, pipe, event_tx.as_ref());
+                if !output.is_empty() {
+                    self.send_command_output(client_id, world_index, output.join("\n"));
+                }
+            }
+            Command::Lock { args } => {
+                let output = commands::lock_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Unlock { args } => {
+                let output = commands::unlock_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Export { args } => {
+                let output = commands::export_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::TriggerTest { args } => {
+                let output = commands::trigger_test_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::ActionsExport { args } => {
+                let output = commands::actions_export_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::ActionsImport { args } => {
+                let output = commands::actions_import_command(self, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::ImportFile { args } => {
+                let output = commands::import_file_command(self, &args).join("\n");
+                // Like a finished network import: resync the client so imported worlds show up
+                let initial_state = self.build_initial_state();
+                self.ws_send_initial_state_and_mark(client_id, initial_state);
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Log { args } => {
+                let output = commands::log_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Split { .. } => {
+                self.send_command_output(client_id, world_index, "/split is for the console; in the browser, open another window for the other world.");
+            }
+            Command::Capture { args } => {
+                let output = if capture::is_console_subcommand(&args) {
+                    "/capture pane, view and height lay out the console; in the browser, capture buffers are worlds of their own.".to_string()
+                } else {
+                    commands::capture_command(self, &args).join("\n")
+                };
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Throttle { args } => {
+                let output = commands::throttle_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::LocalEcho { args } => {
+                let output = commands::localecho_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Emoji { args } => {
+                let output = commands::emoji_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Autocorrect { args } => {
+                let output = commands::autocorrect_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::AddWord { word } => {
+                let output = commands::addword_command(self, &word).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::SpellIgnore { word } => {
+                let output = commands::spellignore_command(self, &word).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Unignore { word } => {
+                let output = commands::unignore_command(self, &word).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Palette { args } => {
+                let output = commands::palette_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Tint { args } => {
+                let output = commands::tint_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Images { args } => {
+                let output = commands::images_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::SpellLang { args } => {
+                let output = commands::spelllang_command(self, world_index, &args).join("\n");
+                self.send_command_output(client_id, world_index, output);
+            }
+            Command::Urls => {
+                let urls = self.recent_urls(world_index, 20);
+                let output = if urls.is_empty() {
+                    "No URLs in this world's output.".to_string()
+                } else {
+                    format!("Recent URLs (newest first):\n{}", urls.join("\n"))
+                };
+                self.send_command_output(client_id, world_index, output);
+            }
             Command::Unknown { cmd } => {
                 self.ws_broadcast(WsMessage::ServerData {
                     world_index,
@@ -7236,16 +9740,7 @@ impl App {
                         .unwrap_or(false);
 
                 if !is_local_gui_master {
-                    self.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: "/connect is only available from the local console or GUI.".to_string(),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                        marked_new: false,
-                        flush: false, gagged: false,
-                    });
+                    self.send_command_output(client_id, world_index, "/connect is only available from the local console or GUI.");
                 } else if cancel {
                     if self.pending_remote_connect.take().is_some() {
                         self.add_output("Cancelled.");
@@ -7368,6 +9863,24 @@ impl App {
                     seq: 0, marked_new: false, flush: false, gagged: false,
                 });
             }
+            Command::WebBans => {
+                self.send_command_output(client_id, world_index, commands::web_bans_lines(&self.ban_list).join("\n"));
+            }
+            Command::WebViewer { ref args } => {
+                let data = commands::web_viewer_command(self, args);
+                self.send_command_output(client_id, world_index, data);
+            }
+            Command::WebAcme { ref args } => {
+                let data = commands::web_acme_command(self, args);
+                self.send_command_output(client_id, world_index, data);
+            }
+            Command::WebPush { ref args } if commands::web_push_is_local(args) => {
+                self.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.to_string() });
+            }
+            Command::WebPush { ref args } => {
+                let data = commands::web_push_command(self, args);
+                self.send_command_output(client_id, world_index, data);
+            }
             Command::BanList => {
                 // Send current ban list
                 let bans = self.ban_list.get_ban_info();
@@ -7386,12 +9899,12 @@ impl App {
                     let mut output = String::new();
                     output.push_str("\nBanned Hosts:\n");
                     output.push_str(&"\u{2500}".repeat(70));
-                    output.push_str(&format!("\n{:<20} {:<12} {}\n", "Host", "Type", "Last URL/Reason"));
+                    output.push_str(&format!("\n{:<20} {:<14} {}\n", "Host", "Ends", "Last URL/Reason"));
                     output.push_str(&"\u{2500}".repeat(70));
                     output.push('\n');
                     for (ip, ban_type, reason) in &bans {
                         let reason_display = if reason.is_empty() { "(unknown)" } else { reason };
-                        output.push_str(&format!("{:<20} {:<12} {}\n", ip, ban_type, reason_display));
+                        output.push_str(&format!("{:<20} {:<14} {}\n", ip, ban_type, reason_display));
                     }
                     output.push_str(&"\u{2500}".repeat(70));
                     output.push_str("\nUse /unban <host> to remove a ban.");
@@ -7446,31 +9959,14 @@ impl App {
                     world_index,
                     notes: test_notes,
                 });
-                self.ws_send_to_client(client_id, WsMessage::ServerData {
-                    world_index,
-                    data: "Playing test music (Super Mario Bros)...".to_string(),
-                    is_viewed: false,
-                    ts: current_timestamp_secs(),
-                    from_server: false,
-                    seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                });
+                self.send_command_output(client_id, world_index, "Playing test music (Super Mario Bros)...");
             }
             Command::Notify { message } => {
-                // Send notification to mobile clients
-                let title = if world_index < self.worlds.len() {
-                    self.worlds[world_index].name.clone()
-                } else {
-                    "Clay".to_string()
-                };
-                self.ws_broadcast(WsMessage::Notification {
-                    title,
-                    message: message.clone(),
-                });
+                // Send notification to mobile clients and the desktop
+                let data = commands::notify_command(self, world_index, &message).join("\n");
                 self.ws_broadcast(WsMessage::ServerData {
                     world_index,
-                    data: format!("Notification sent: {}", message),
+                    data,
                     is_viewed: false,
                     ts: current_timestamp_secs(),
                     from_server: false,
@@ -7479,6 +9975,23 @@ impl App {
                     flush: false, gagged: false,
                 });
             }
+            Command::Calc { expr, roll, send } => {
+                let reply = match crate::dice::result_line(&expr, roll) {
+                    Ok(line) if send => {
+                        let tx = self.worlds.get(world_index).and_then(|w| w.command_tx.as_ref());
+                        if tx.is_some_and(|tx| tx.try_send(WriteCommand::Text(line)).is_ok()) {
+                            self.worlds[world_index].last_send_time = Some(std::time::Instant::now());
+                            None
+                        } else {
+                            Some("Not connected.".to_string())
+                        }
+                    }
+                    Ok(line) | Err(line) => Some(line),
+                };
+                if let Some(data) = reply {
+                    self.send_command_output(client_id, world_index, data);
+                }
+            }
             Command::Say { text } => {
                 // Speak text via TTS (console subprocess + broadcast to web clients)
                 tts::speak(&self.tts_backend, &text, self.settings.tts_mode);
@@ -7499,7 +10012,7 @@ impl App {
                 });
             }
             Command::Dump => {
-                // Dump comprehensive debug state to ~/.clay/dump.log
+                // Dump comprehensive debug state to ~/.config/clay/dump.log
                 use std::io::Write;
                 let ts = current_timestamp_secs();
 
@@ -7635,25 +10148,27 @@ impl App {
             Command::Reload => {
                 return WsAsyncAction::Reload;
             }
+            Command::ConnectAll => {
+                return WsAsyncAction::ConnectAll;
+            }
+            Command::DisconnectAll => {
+                let count = self.disconnect_all_worlds();
+                let plural = if count == 1 { "" } else { "s" };
+                self.send_command_output(client_id, world_index, format!("Disconnected {} world{}.", count, plural));
+            }
+            Command::Detach => {
+                self.send_command_output(client_id, world_index, "/detach is only available from the console.");
+            }
             Command::Help => {
                 self.handle_ws_help_via_tf(client_id, world_index, "/help");
             }
             // UI popup commands - send back to client for local handling
-            Command::Menu | Command::Font | Command::Setup | Command::Web | Command::Actions { .. } |
-            Command::WorldsList | Command::WorldSelector | Command::WorldEdit { .. } => {
+            Command::Menu | Command::Font | Command::Setup | Command::Web | Command::WebTokens { .. } |
+            Command::Actions { .. } | Command::WorldsList | Command::WorldSelector | Command::WorldEdit { .. } => {
                 self.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.to_string() });
             }
             Command::Version => {
-                self.ws_send_to_client(client_id, WsMessage::ServerData {
-                    world_index,
-                    data: get_version_string(),
-                    is_viewed: false,
-                    ts: current_timestamp_secs(),
-                    from_server: false,
-                    seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                });
+                self.send_command_output(client_id, world_index, get_version_string());
             }
             // AddWorld - add or update world definition
             Command::AddWorld { name, host, port, user, password, use_ssl } => {
@@ -7729,33 +10244,17 @@ impl App {
                     if !self.worlds[idx].connected && self.worlds[idx].settings.has_connection_settings() {
                         let prev_index = self.current_world_index;
                         self.current_world_index = idx;
-                        return WsAsyncAction::Connect { world_index: idx, prev_index, broadcast: false };
-                    }
-                } else {
-                    self.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: format!("World '{}' not found.", name),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                        marked_new: false,
-                        flush: false, gagged: false,
-                    });
+                        return WsAsyncAction::Connect { world_index: idx, prev_index, broadcast: false };
+                    }
+                } else {
+                    self.send_command_output(client_id, world_index, format!("World '{}' not found.", name));
                 }
             }
             // WorldSwitch and WorldConnectNoLogin need proper handling
             Command::WorldSwitch { ref name } | Command::WorldConnectNoLogin { ref name } => {
                 if let Some(idx) = self.worlds.iter().position(|w| w.name.eq_ignore_ascii_case(name)) {
                     // Switch only the requesting client's world, not the console
-                    let prev = self.ws_client_worlds.get(&client_id);
-                    let dimensions = prev.and_then(|s| s.dimensions);
-                    let visible_lines = prev.map(|v| v.visible_lines).unwrap_or(0);
-                    let visible_columns = prev.map(|v| v.visible_columns).unwrap_or(0);
-                    let paused = prev.map(|v| v.paused).unwrap_or(false);
-                    self.ws_client_worlds.insert(client_id, ClientViewState { world_index: idx, visible_lines, visible_columns, dimensions, paused });
-                    self.ws_set_client_world(client_id, Some(idx));
-                    self.ws_send_to_client(client_id, WsMessage::WorldSwitched { new_index: idx });
+                    self.ws_focus_client_world(client_id, idx);
                     // Also send ExecuteLocalCommand so web clients can switch their local view
                     self.ws_send_to_client(client_id, WsMessage::ExecuteLocalCommand { command: command.to_string() });
                     // Connect if not connected and has settings
@@ -7771,68 +10270,23 @@ impl App {
                         return WsAsyncAction::Connect { world_index: idx, prev_index, broadcast: false };
                     }
                 } else {
-                    self.ws_send_to_client(client_id, WsMessage::ServerData {
-                        world_index,
-                        data: format!("World '{}' not found.", name),
-                        is_viewed: false,
-                        ts: current_timestamp_secs(),
-                        from_server: false,
-                        seq: 0,
-                        marked_new: false,
-                        flush: false, gagged: false,
-                    });
+                    self.send_command_output(client_id, world_index, format!("World '{}' not found.", name));
                 }
             }
             Command::Dict { .. } | Command::Urban { .. } | Command::Translate { .. } | Command::TinyUrl { .. } => {
                 spawn_api_lookup(event_tx.clone(), client_id, world_index, parsed);
             }
             Command::DictUsage => {
-                self.ws_send_to_client(client_id, WsMessage::ServerData {
-                    world_index,
-                    data: "Usage: /dict <word>".to_string(),
-                    is_viewed: false,
-                    ts: current_timestamp_secs(),
-                    from_server: false,
-                    seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                });
+                self.send_command_output(client_id, world_index, "Usage: /dict <word>");
             }
             Command::UrbanUsage => {
-                self.ws_send_to_client(client_id, WsMessage::ServerData {
-                    world_index,
-                    data: "Usage: /urban <word>".to_string(),
-                    is_viewed: false,
-                    ts: current_timestamp_secs(),
-                    from_server: false,
-                    seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                });
+                self.send_command_output(client_id, world_index, "Usage: /urban <word>");
             }
             Command::TranslateUsage => {
-                self.ws_send_to_client(client_id, WsMessage::ServerData {
-                    world_index,
-                    data: "Usage: /translate <lang> <text>".to_string(),
-                    is_viewed: false,
-                    ts: current_timestamp_secs(),
-                    from_server: false,
-                    seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                });
+                self.send_command_output(client_id, world_index, "Usage: /translate <lang> <text>");
             }
             Command::TinyUrlUsage => {
-                self.ws_send_to_client(client_id, WsMessage::ServerData {
-                    world_index,
-                    data: "Usage: /url <url>".to_string(),
-                    is_viewed: false,
-                    ts: current_timestamp_secs(),
-                    from_server: false,
-                    seq: 0,
-                    marked_new: false,
-                    flush: false, gagged: false,
-                });
+                self.send_command_output(client_id, world_index, "Usage: /url <url>");
             }
             Command::HelpTopic { ref topic } => {
                 // Try Clay help first, then TF help via engine
@@ -7840,11 +10294,7 @@ impl App {
                 if let Some(lines) = get_topic_help(topic) {
                     let help_text = lines.join("\n");
                     for line in help_text.lines() {
-                        self.ws_send_to_client(client_id, WsMessage::ServerData {
-                            world_index, data: line.to_string(), is_viewed: false,
-                            ts: current_timestamp_secs(), from_server: false, seq: 0,
-                            marked_new: false, flush: false, gagged: false,
-                        });
+                        self.send_command_output(client_id, world_index, line.to_string());
                     }
                 } else {
                     self.handle_ws_help_via_tf(client_id, world_index, &format!("/help {}", topic));
@@ -7862,11 +10312,7 @@ impl App {
             _ => "No help available. Try /help commands".to_string(),
         };
         for line in help_text.lines() {
-            self.ws_send_to_client(client_id, WsMessage::ServerData {
-                world_index, data: line.to_string(), is_viewed: false,
-                ts: current_timestamp_secs(), from_server: false, seq: 0,
-                marked_new: false, flush: false, gagged: false,
-            });
+            self.send_command_output(client_id, world_index, line.to_string());
         }
     }
 
@@ -8040,14 +10486,9 @@ impl App {
             }
         }
 
-        let auth_current_world = if let WsMessage::AuthRequest { ref current_world, .. } = msg {
-            *current_world
-        } else {
-            None
-        };
         match msg {
-            WsMessage::AuthRequest { .. } => {
-                self.handle_ws_auth_initial_state(client_id, auth_current_world);
+            WsMessage::AuthRequest { current_world, resume_seqs, .. } => {
+                self.handle_ws_auth_initial_state(client_id, current_world, &resume_seqs);
             }
             WsMessage::SendCommand { world_index, command } => {
                 // Reset more-mode counter when ANY client sends a command
@@ -8074,14 +10515,7 @@ impl App {
             WsMessage::SwitchWorld { world_index } => {
                 // Switch only the requesting client's world, not the console
                 if world_index < self.worlds.len() {
-                    let prev = self.ws_client_worlds.get(&client_id);
-                    let dimensions = prev.and_then(|s| s.dimensions);
-                    let visible_lines = prev.map(|v| v.visible_lines).unwrap_or(0);
-                    let visible_columns = prev.map(|v| v.visible_columns).unwrap_or(0);
-                    let paused = prev.map(|v| v.paused).unwrap_or(false);
-                    self.ws_client_worlds.insert(client_id, ClientViewState { world_index, visible_lines, visible_columns, dimensions, paused });
-                    self.ws_set_client_world(client_id, Some(world_index));
-                    self.ws_send_to_client(client_id, WsMessage::WorldSwitched { new_index: world_index });
+                    self.ws_focus_client_world(client_id, world_index);
                     // Send active media for the new world
                     self.ws_send_active_media_to_client(client_id, world_index);
                 }
@@ -8135,48 +10569,7 @@ impl App {
                 let new_world = World::new(&name);
                 self.worlds.push(new_world);
                 let idx = self.worlds.len() - 1;
-                let world = &self.worlds[idx];
-                let world_state = WorldStateMsg {
-                    index: idx,
-                    name: world.name.clone(),
-                    connected: false,
-                    output_lines: Vec::new(),
-                    pending_lines: Vec::new(),
-                    output_lines_ts: Vec::new(),
-                    pending_lines_ts: Vec::new(),
-                    prompt: String::new(),
-                    scroll_offset: 0,
-                    paused: false,
-                    unseen_lines: 0,
-                    settings: WorldSettingsMsg {
-                        hostname: world.settings.hostname.clone(),
-                        port: world.settings.port.clone(),
-                        user: world.settings.user.clone(),
-                        password: {
-                            let p = persistence::decrypt_password(&world.settings.password);
-                            if p.starts_with("ENC:") { String::new() } else { p }
-                        },
-                        has_password: !world.settings.password.is_empty(),
-                        use_ssl: world.settings.use_ssl,
-                        log_enabled: world.settings.log_enabled,
-                        encoding: world.settings.encoding.name().to_string(),
-                        auto_connect_type: world.settings.auto_connect_type.name().to_string(),
-                        keep_alive_type: world.settings.keep_alive_type.name().to_string(),
-                        keep_alive_cmd: world.settings.keep_alive_cmd.clone(),
-                        gmcp_packages: world.settings.gmcp_packages.clone(),
-                        auto_reconnect_secs: world.settings.auto_reconnect_display(),
-                    },
-                    last_send_secs: None,
-                    last_recv_secs: None,
-                    last_nop_secs: None,
-                    keep_alive_type: world.settings.keep_alive_type.name().to_string(),
-                    showing_splash: world.showing_splash,
-                    was_connected: false,
-                    is_proxy: false,
-                    gmcp_user_enabled: world.gmcp_user_enabled,
-                    total_output_lines: 0,
-                    pending_count: 0,
-                };
+                let world_state = self.new_world_state_msg(idx);
                 self.ws_broadcast(WsMessage::WorldAdded { world: Box::new(world_state) });
                 let _ = persistence::save_settings(self);
                 // Send the new world's index back to the requesting client
@@ -8207,6 +10600,11 @@ impl App {
                     let _ = persistence::save_settings(self);
                 }
             }
+            // A client dragged a world's tab to a new position
+            WsMessage::MoveWorld { from, to } if self.reorder_world(from, to) => {
+                self.ws_broadcast(WsMessage::WorldMoved { from, to });
+                let _ = persistence::save_settings(self);
+            }
             WsMessage::MarkWorldSeen { world_index } => {
                 // A remote client has viewed this world - update their current_world
                 if world_index < self.worlds.len() {
@@ -8233,9 +10631,11 @@ impl App {
                     // Update client's world in WebSocket server (async state)
                     self.ws_set_client_world(client_id, Some(world_index));
 
-                    self.worlds[world_index].mark_seen();
-                    // Broadcast to all clients so they update their UI
-                    self.ws_broadcast(WsMessage::UnseenCleared { world_index });
+                    // Only this client has seen it; with no console, it's also seen overall
+                    if !self.console_active {
+                        self.worlds[world_index].mark_seen();
+                    }
+                    self.ws_mark_client_seen(client_id, world_index);
                     // Broadcast activity count since a world was just marked as seen
                     self.broadcast_activity();
                     // Trigger console redraw to update activity indicator
@@ -8354,9 +10754,10 @@ impl App {
                     }
                 }
             }
-            WsMessage::UpdateWorldSettings { world_index, name, hostname, port, user, password, use_ssl, log_enabled, encoding, auto_login, keep_alive_type, keep_alive_cmd, gmcp_packages, auto_reconnect_secs } => {
+            WsMessage::UpdateWorldSettings { world_index, name, hostname, port, user, password, use_ssl, log_enabled, encoding, auto_login, login_script, connect_commands, input_prefix, input_suffix, keep_alive_type, keep_alive_cmd, keep_alive_interval_secs, silence_alert_mins, gmcp_packages, auto_reconnect_secs, log_format, log_timestamps, log_session_markers, tls_verify, tls_client_cert, tls_client_key, ssh_host, ssh_user, ssh_key } => {
                 // Update world settings from remote client
                 if world_index < self.worlds.len() {
+                    persistence::rename_locked_credentials(&self.worlds[world_index].name, &name);
                     self.worlds[world_index].name = name.clone();
                     self.worlds[world_index].settings.hostname = hostname.clone();
                     self.worlds[world_index].settings.port = port.clone();
@@ -8366,15 +10767,43 @@ impl App {
                         self.worlds[world_index].settings.password = password.clone();
                     }
                     self.worlds[world_index].settings.use_ssl = use_ssl;
+                    if let Some(verify) = platform::TlsVerify::from_name(&tls_verify) {
+                        self.worlds[world_index].settings.tls_verify = verify;
+                    }
+                    self.worlds[world_index].settings.tls_client_cert = tls_client_cert.clone();
+                    self.worlds[world_index].settings.tls_client_key = tls_client_key.clone();
+                    self.worlds[world_index].settings.ssh_host = ssh_host.clone();
+                    self.worlds[world_index].settings.ssh_user = ssh_user.clone();
+                    self.worlds[world_index].settings.ssh_key = ssh_key.clone();
                     self.worlds[world_index].settings.log_enabled = log_enabled;
-                    self.worlds[world_index].settings.encoding = match encoding.as_str() {
-                        "latin1" => Encoding::Latin1,
-                        "fansi" => Encoding::Fansi,
-                        _ => Encoding::Utf8,
-                    };
+                    if let Some(format) = logging::LogFormat::from_name(&log_format) {
+                        self.worlds[world_index].settings.log_format = format;
+                    }
+                    if let Some(stamps) = logging::LogTimestamps::from_name(&log_timestamps) {
+                        self.worlds[world_index].settings.log_timestamps = stamps;
+                    }
+                    if let Some(markers) = log_session_markers {
+                        self.worlds[world_index].settings.log_session_markers = markers;
+                    }
+                    self.worlds[world_index].settings.encoding = Encoding::from_name(&encoding);
+                    self.worlds[world_index].sync_send_encoding();
                     self.worlds[world_index].settings.auto_connect_type = AutoConnectType::from_name(&auto_login);
+                    if let Some(script) = login_script {
+                        self.worlds[world_index].settings.login_script = script;
+                    }
+                    if let Some(commands) = connect_commands {
+                        self.worlds[world_index].settings.connect_commands = commands;
+                    }
+                    if let Some(prefix) = input_prefix {
+                        self.worlds[world_index].settings.input_prefix = prefix;
+                    }
+                    if let Some(suffix) = input_suffix {
+                        self.worlds[world_index].settings.input_suffix = suffix;
+                    }
                     self.worlds[world_index].settings.keep_alive_type = KeepAliveType::from_name(&keep_alive_type);
                     self.worlds[world_index].settings.keep_alive_cmd = keep_alive_cmd.clone();
+                    self.worlds[world_index].settings.keep_alive_interval_secs = keep_alive_interval_secs.max(MIN_KEEPALIVE_SECS);
+                    self.worlds[world_index].settings.silence_alert_mins = silence_alert_mins;
                     self.worlds[world_index].settings.gmcp_packages = gmcp_packages.clone();
                     let (ar_secs, ar_on_web) = WorldSettings::parse_auto_reconnect(&auto_reconnect_secs);
                     self.worlds[world_index].settings.auto_reconnect_secs = ar_secs;
@@ -8392,13 +10821,40 @@ impl App {
                         has_password: !password.is_empty(),
                         password: String::new(),
                         use_ssl,
+                        tls_verify: self.worlds[world_index].settings.tls_verify.name().to_string(),
+                        tls_client_cert,
+                        tls_client_key,
+                        ssh_host,
+                        ssh_user,
+                        ssh_key,
                         log_enabled,
+                        log_format: self.worlds[world_index].settings.log_format.name().to_string(),
+                        log_timestamps: self.worlds[world_index].settings.log_timestamps.name().to_string(),
+                        log_session_markers: self.worlds[world_index].settings.log_session_markers,
                         encoding,
                         auto_connect_type: auto_login,
+                        login_script: self.worlds[world_index].settings.login_script.clone(),
+                        connect_commands: self.worlds[world_index].settings.connect_commands.clone(),
+                        input_prefix: self.worlds[world_index].settings.input_prefix.clone(),
+                        input_suffix: self.worlds[world_index].settings.input_suffix.clone(),
                         keep_alive_type,
                         keep_alive_cmd,
+                        keep_alive_interval_secs,
+                        silence_alert_mins,
                         gmcp_packages,
                         auto_reconnect_secs,
+                        numpad_walk: self.worlds[world_index].settings.numpad_walk,
+                        timestamps: self.worlds[world_index].settings.timestamps.map_or("", |m| m.name()).to_string(),
+                        notify_muted: self.worlds[world_index].settings.notify_muted,
+                        notify_sound: self.worlds[world_index].settings.notify_sound.clone(),
+                        inline_images: self.worlds[world_index].settings.inline_images,
+                        bell: self.worlds[world_index].settings.bell.name().to_string(),
+                        emoji_shortcodes: self.worlds[world_index].settings.emoji_shortcodes,
+                        autocorrect: self.worlds[world_index].settings.autocorrect,
+                        palette: self.worlds[world_index].settings.palette.clone(),
+                        tint: self.worlds[world_index].settings.tint.clone(),
+                        spell_language: self.worlds[world_index].settings.spell_language.clone(),
+                        macros: self.worlds[world_index].settings.macros.clone(),
                     };
                     // Broadcast update to all clients
                     self.ws_broadcast(WsMessage::WorldSettingsUpdated {
@@ -8408,7 +10864,7 @@ impl App {
                     });
                 }
             }
-            WsMessage::UpdateGlobalSettings { more_mode_enabled, spell_check_enabled, temp_convert_enabled, world_switch_mode, show_tags, debug_enabled, ansi_music_enabled, console_theme, gui_theme, gui_transparency, color_offset_percent, wrapspace, remote_initial_lines, input_height, font_name, font_size, web_font_size_phone, web_font_size_tablet, web_font_size_desktop, web_font_weight, web_font_line_height, web_font_letter_spacing, web_font_word_spacing, ws_allow_list, web_secure, http_enabled, http_port, web_path, ws_enabled: _, ws_port: _, ws_cert_file, ws_key_file, ws_password, tls_proxy_enabled, dictionary_path, mouse_enabled, zwj_enabled, new_line_indicator, tts_mode, tts_speak_mode, scrollback_enabled } => {
+            WsMessage::UpdateGlobalSettings { more_mode_enabled, spell_check_enabled, temp_convert_enabled, world_switch_mode, show_tags, debug_enabled, ansi_music_enabled, console_theme, gui_theme, gui_transparency, color_offset_percent, wrapspace, remote_initial_lines, input_height, font_name, font_size, web_font_size_phone, web_font_size_tablet, web_font_size_desktop, web_font_weight, web_font_line_height, web_font_letter_spacing, web_font_word_spacing, ws_allow_list, web_secure, http_enabled, http_port, web_path, ws_enabled: _, ws_port: _, ws_cert_file, ws_key_file, ws_password, tls_proxy_enabled, dictionary_path, mouse_enabled, zwj_enabled, new_line_indicator, tts_mode, tts_speak_mode, scrollback_enabled, status_format, tab_bar, notify_activity, scrollback_lines, confirm_quit, acme_domain, acme_email, web_bind_addr, web_unix_socket, web_trusted_proxies } => {
                 // Update global settings from remote client
                 self.settings.more_mode_enabled = more_mode_enabled;
                 self.settings.spell_check_enabled = spell_check_enabled;
@@ -8481,13 +10937,52 @@ impl App {
                 self.settings.tts_speak_mode = tts::TtsSpeakMode::from_name(&tts_speak_mode);
                 if self.settings.dictionary_path != dictionary_path {
                     self.settings.dictionary_path = dictionary_path;
-                    self.spell_checker = SpellChecker::new(&self.settings.dictionary_path);
+                    self.reload_spell_checker();
                 }
                 let scrollback_changed = self.settings.scrollback_enabled != scrollback_enabled;
                 self.settings.scrollback_enabled = scrollback_enabled;
                 if scrollback_changed {
                     self.init_scrollback();
                 }
+                if let Some(status_format) = status_format {
+                    self.settings.status_format = status_format;
+                }
+                if let Some(tab_bar) = tab_bar {
+                    self.settings.tab_bar = tab_bar;
+                }
+                if let Some(notify_activity) = notify_activity {
+                    self.settings.notify_activity = notify_activity;
+                }
+                if let Some(lines) = scrollback_lines {
+                    self.settings.scrollback_lines = lines.clamp(MIN_SCROLLBACK_LINES, MAX_SCROLLBACK_LINES);
+                }
+                if let Some(confirm_quit) = confirm_quit {
+                    self.settings.confirm_quit = confirm_quit;
+                }
+                if let Some(domain) = acme_domain {
+                    match self.set_acme_domain(&domain, acme_email.as_deref().unwrap_or("")) {
+                        // Turned off: back to the self-signed certificate
+                        Ok(true) if domain.is_empty() => self.web_restart_needed = self.settings.http_enabled,
+                        Ok(_) => {}
+                        Err(e) => self.add_output(&e),
+                    }
+                }
+                if web_bind_addr.is_some() || web_unix_socket.is_some() {
+                    let bind_addr = web_bind_addr.unwrap_or_else(|| self.settings.web_bind_addr.clone());
+                    let unix_socket = web_unix_socket.unwrap_or_else(|| self.settings.web_unix_socket.clone());
+                    match self.set_web_listen(&bind_addr, &unix_socket) {
+                        Ok(true) => self.web_restart_needed = true,
+                        Ok(false) => {}
+                        Err(e) => self.add_output(&e),
+                    }
+                }
+                if let Some(proxies) = web_trusted_proxies {
+                    match self.set_trusted_proxies(&proxies) {
+                        Ok(true) => self.web_restart_needed = true,
+                        Ok(false) => {}
+                        Err(e) => self.add_output(&e),
+                    }
+                }
                 // Save settings to persist changes. Tag the (debug-mode-only) audit log
                 // with which kind of client pushed this, so a future settings-loss report
                 // can be traced back to its source (web/gui/console/android).
@@ -8515,17 +11010,49 @@ impl App {
                     actions,
                 });
             }
+            WsMessage::RequestTagRoutes => {
+                self.ws_send_to_client(client_id, WsMessage::TagRoutesUpdated { routes: self.settings.tag_routes.clone() });
+            }
+            WsMessage::UpdateTagRoutes { routes } => {
+                self.settings.tag_routes = routes;
+                commands::tag_routes_changed(self);
+            }
+            WsMessage::RequestGags => {
+                self.ws_send_to_client(client_id, WsMessage::GagsUpdated { gags: self.settings.gags.clone() });
+            }
+            WsMessage::UpdateGags { mut gags } => {
+                for gag in &mut gags {
+                    gag.compile();
+                }
+                self.settings.gags = gags;
+                commands::gags_changed(self);
+            }
+            WsMessage::DuplicateAction { index }
+                if crate::actions::duplicate_action(&mut self.settings.actions, index).is_some() =>
+            {
+                let _ = persistence::save_settings(self);
+                self.ws_broadcast(WsMessage::ActionsUpdated { actions: self.settings.actions.clone() });
+            }
+            WsMessage::MoveAction { index, up }
+                if crate::actions::move_action(&mut self.settings.actions, index, up).is_some() =>
+            {
+                let _ = persistence::save_settings(self);
+                self.ws_broadcast(WsMessage::ActionsUpdated { actions: self.settings.actions.clone() });
+            }
+            WsMessage::ImportActions { text } => {
+                let reply = match commands::import_action_pack(self, &text, "the imported file") {
+                    Ok(lines) => WsMessage::ImportResult { success: true, summary: lines.join("\n") },
+                    Err(e) => WsMessage::ImportResult { success: false, summary: e },
+                };
+                self.ws_send_to_client(client_id, reply);
+            }
+            WsMessage::TestActionPatterns { match_type, patterns, command, sample } => {
+                let reply = WsMessage::action_patterns_tested(&match_type, &patterns, &command, &sample);
+                self.ws_send_to_client(client_id, reply);
+            }
             WsMessage::CalculateNextWorld { current_index } => {
                 // Calculate next world using shared logic
-                let world_info: Vec<crate::util::WorldSwitchInfo> = self.worlds.iter()
-                    .map(|w| crate::util::WorldSwitchInfo {
-                        name: w.name.clone(),
-                        connected: w.connected,
-                        unseen_lines: w.unseen_lines,
-                        pending_lines: w.pending_lines.len(),
-                        first_unseen_at: w.first_unseen_at,
-                    })
-                    .collect();
+                let world_info = self.client_world_switch_info(client_id);
                 let next_idx = crate::util::calculate_next_world(
                     &world_info,
                     current_index,
@@ -8535,15 +11062,7 @@ impl App {
             }
             WsMessage::CalculatePrevWorld { current_index } => {
                 // Calculate prev world using shared logic
-                let world_info: Vec<crate::util::WorldSwitchInfo> = self.worlds.iter()
-                    .map(|w| crate::util::WorldSwitchInfo {
-                        name: w.name.clone(),
-                        connected: w.connected,
-                        unseen_lines: w.unseen_lines,
-                        pending_lines: w.pending_lines.len(),
-                        first_unseen_at: w.first_unseen_at,
-                    })
-                    .collect();
+                let world_info = self.client_world_switch_info(client_id);
                 let prev_idx = crate::util::calculate_prev_world(
                     &world_info,
                     current_index,
@@ -8572,12 +11091,8 @@ impl App {
 
                 // If no pending, check for unseen output
                 if oldest_idx.is_none() {
-                    for (idx, world) in self.worlds.iter().enumerate() {
-                        if idx != current_index && world.unseen_lines > 0 {
-                            oldest_idx = Some(idx);
-                            break;
-                        }
-                    }
+                    oldest_idx = (0..self.worlds.len())
+                        .find(|&idx| idx != current_index && self.client_unseen(client_id, idx) > 0);
                 }
 
                 // If still none, use previous world
@@ -8809,14 +11324,33 @@ impl App {
                     bindings_json: self.keybindings.to_json(),
                 });
             }
+            WsMessage::RequestDeviceTokens => {
+                let reply = self.device_tokens_reply(commands::DeviceTokenRequest::List);
+                self.ws_send_to_client(client_id, reply);
+            }
+            WsMessage::CreateDeviceToken { name } => {
+                let reply = self.device_tokens_reply(commands::DeviceTokenRequest::Create(name));
+                self.ws_send_to_client(client_id, reply);
+            }
+            WsMessage::RevokeDeviceToken { name } => {
+                let reply = self.device_tokens_reply(commands::DeviceTokenRequest::Revoke(name));
+                self.ws_send_to_client(client_id, reply);
+            }
+            WsMessage::PushSubscribe { endpoint, p256dh, auth } => {
+                let reply = self.add_push_subscription(&endpoint, &p256dh, &auth);
+                self.ws_send_client_line(client_id, &reply);
+            }
+            WsMessage::PushUnsubscribe { endpoint } => {
+                self.remove_push_subscriptions(&[endpoint]);
+                self.ws_send_client_line(client_id, "Push notifications off for this browser.");
+            }
             WsMessage::RequestConnectionsList => {
                 // Generate connections list using same format as master console
                 let current_idx = self.current_world_index;
-                const KEEPALIVE_SECS: u64 = 5 * 60;
                 let worlds_info: Vec<util::WorldListInfo> = self.worlds.iter().enumerate().map(|(idx, world)| {
                     let now = std::time::Instant::now();
                     let next_nop = if world.connected {
-                        world.last_send_time.map(|t| KEEPALIVE_SECS.saturating_sub(t.elapsed().as_secs()))
+                        world.last_send_time.map(|t| world.settings.keep_alive_interval().as_secs().saturating_sub(t.elapsed().as_secs()))
                     } else {
                         None
                     };
@@ -8865,9 +11399,9 @@ impl App {
                     .unwrap_or(self.current_world_index);
 
                 let new_index = if direction == "up" {
-                    self.calculate_prev_world_from(current)
+                    self.calculate_prev_world_from(client_id, current)
                 } else {
-                    self.calculate_next_world_from(current)
+                    self.calculate_next_world_from(client_id, current)
                 };
 
                 if let Some(idx) = new_index {
@@ -8918,7 +11452,7 @@ impl App {
 
                         if lines_to_send > 0 {
                             let start = total_lines.saturating_sub(lines_to_send);
-                            let lines: Vec<TimestampedLine> = world.output_lines[start..].iter()
+                            let lines: Vec<TimestampedLine> = world.output_lines.range(start..)
                                 .map(|line| {
                                     let ts = line.timestamp
                                         .duration_since(std::time::UNIX_EPOCH)
@@ -8944,11 +11478,13 @@ impl App {
                             });
                         }
 
-                        // Also mark world as seen if it had unseen output
-                        if self.worlds[idx].unseen_lines > 0 {
-                            self.worlds[idx].unseen_lines = 0;
-                            self.worlds[idx].first_unseen_at = None;
-                            self.ws_broadcast(WsMessage::UnseenCleared { world_index: idx });
+                        // Also mark world as seen by this client if it had unseen output
+                        if self.ws_client_unseen.get(&client_id).is_some_and(|u| u.contains_key(&idx)) {
+                            if !self.console_active {
+                                self.worlds[idx].unseen_lines = 0;
+                                self.worlds[idx].first_unseen_at = None;
+                            }
+                            self.ws_mark_client_seen(client_id, idx);
                             self.broadcast_activity();
                         }
                     }
@@ -8956,91 +11492,7 @@ impl App {
             }
             WsMessage::RequestScrollback { world_index, count, before_seq, after_seq } => {
                 // Console client requests scrollback from master
-                if world_index < self.worlds.len() {
-                    let world = &self.worlds[world_index];
-
-                    // Find lines to send based on before_seq/after_seq
-                    let lines: Vec<TimestampedLine> = if let Some(seq) = before_seq {
-                        // Send lines with seq < before_seq (older than what client has)
-                        let eligible: Vec<_> = world.output_lines.iter()
-                            .filter(|l| l.seq < seq)
-                            .collect();
-                        let start = eligible.len().saturating_sub(count);
-                        eligible[start..].iter()
-                            .map(|line| {
-                                let ts = line.timestamp
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .map(|d| d.as_secs())
-                                    .unwrap_or(0);
-                                TimestampedLine {
-                                    text: line.text.clone(),
-                                    ts,
-                                    gagged: line.gagged,
-                                    from_server: line.from_server,
-                                    seq: line.seq,
-                                    highlight_color: line.highlight_color.clone(),
-                                    marked_new: line.marked_new,
-                                    from_archive: line.from_archive,
-                                }
-                            })
-                            .collect()
-                    } else if let Some(seq) = after_seq {
-                        // Reconnect gap-fill: the client kept its buffer across the
-                        // reconnect and only wants lines newer than the highest seq it
-                        // already has. Oldest-first (unlike before_seq's newest-first
-                        // slice) so the client can append+dedup in order.
-                        let eligible: Vec<_> = world.output_lines.iter()
-                            .filter(|l| l.seq > seq)
-                            .collect();
-                        let take = count.min(eligible.len());
-                        eligible[..take].iter()
-                            .map(|line| {
-                                let ts = line.timestamp
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .map(|d| d.as_secs())
-                                    .unwrap_or(0);
-                                TimestampedLine {
-                                    text: line.text.clone(),
-                                    ts,
-                                    gagged: line.gagged,
-                                    from_server: line.from_server,
-                                    seq: line.seq,
-                                    highlight_color: line.highlight_color.clone(),
-                                    marked_new: line.marked_new,
-                                    from_archive: line.from_archive,
-                                }
-                            })
-                            .collect()
-                    } else {
-                        // No before_seq/after_seq - send last N lines (backwards compatible)
-                        let total_lines = world.output_lines.len();
-                        let start = total_lines.saturating_sub(count);
-                        world.output_lines[start..].iter()
-                            .map(|line| {
-                                let ts = line.timestamp
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .map(|d| d.as_secs())
-                                    .unwrap_or(0);
-                                TimestampedLine {
-                                    text: line.text.clone(),
-                                    ts,
-                                    gagged: line.gagged,
-                                    from_server: line.from_server,
-                                    seq: line.seq,
-                                    highlight_color: line.highlight_color.clone(),
-                                    marked_new: line.marked_new,
-                                    from_archive: line.from_archive,
-                                }
-                            })
-                            .collect()
-                    };
-
-                    // Works for both directions: before_seq slices the newest `count`
-                    // eligible lines (so `lines.len() < count` means we ran out of
-                    // older history); after_seq takes `count.min(eligible.len())` (so
-                    // `lines.len() < count` means we returned every newer line
-                    // available, i.e. the gap is fully closed).
-                    let backfill_complete = lines.len() < count;
+                if let Some((lines, backfill_complete)) = self.scrollback_range(world_index, count, before_seq, after_seq) {
                     self.ws_send_to_client(client_id, WsMessage::ScrollbackLines {
                         world_index,
                         lines,
@@ -9109,6 +11561,122 @@ impl App {
     /// every event loop that can receive a `WsClientMessage` or a console `KeyEvent`
     /// (`run_app`'s two loops, `run_app_headless`), since that's everywhere an import could
     /// have been started from in the first place.
+    /// Start a Let's Encrypt order for `acme_domain` if the web server is on and its
+    /// certificate is missing or due for renewal. `force` (from `/web acme`, which
+    /// reports it itself) skips those checks and the retry delay. The outcome arrives
+    /// as `AppEvent::AcmeResult`.
+    pub(crate) fn start_acme_if_due(&mut self, force: bool) {
+        if self.settings.acme_domain.is_empty() || self.acme_in_progress {
+            return;
+        }
+        if !force {
+            let waiting = self.acme_retry_at.is_some_and(|at| std::time::Instant::now() < at);
+            if waiting || !self.settings.http_enabled || !acme::renewal_due(&acme::cert_path()) {
+                return;
+            }
+        }
+        let Some(event_tx) = self.event_tx.clone() else { return };
+        let (domain, email) = (self.settings.acme_domain.clone(), self.settings.acme_email.clone());
+        self.acme_in_progress = true;
+        if !force {
+            self.add_output(&format!("Requesting a Let's Encrypt certificate for {}...", domain));
+        }
+        tokio::spawn(async move {
+            let result = acme::obtain_certificate(domain.clone(), email).await;
+            let _ = event_tx.send(AppEvent::AcmeResult(domain, result)).await;
+        });
+    }
+
+    /// Report a finished Let's Encrypt order. Returns true if the web server should
+    /// restart to pick up the new certificate.
+    pub(crate) fn handle_acme_result(&mut self, domain: &str, result: &Result<(), String>) -> bool {
+        self.acme_in_progress = false;
+        match result {
+            Ok(()) if domain == self.settings.acme_domain => {
+                self.acme_retry_at = None;
+                self.add_output(&format!("Let's Encrypt certificate issued for {}.", domain));
+                self.settings.http_enabled
+            }
+            // The domain changed (or /web acme off) while the order ran
+            Ok(()) => {
+                acme::remove_certificate();
+                self.start_acme_if_due(false);
+                false
+            }
+            Err(e) => {
+                self.acme_retry_at = Some(std::time::Instant::now() + acme::RETRY_AFTER);
+                self.add_output(&format!("Let's Encrypt certificate for {} failed: {}", domain, e));
+                false
+            }
+        }
+    }
+
+    /// Where the web server listens: the Bind Address, plus the Unix Socket if one is set
+    pub(crate) fn web_listen(&self) -> http::WebListen {
+        let unix_socket = self.settings.web_unix_socket.trim();
+        http::WebListen {
+            bind_addr: self.settings.web_bind_addr.clone(),
+            unix_socket: (!unix_socket.is_empty()).then(|| std::path::PathBuf::from(unix_socket)),
+        }
+    }
+
+    /// Set the web server's bind address and Unix socket path (empty = off). Returns
+    /// whether either changed, i.e. whether the server needs a restart. Shared by the
+    /// web settings popups.
+    pub(crate) fn set_web_listen(&mut self, bind_addr: &str, unix_socket: &str) -> Result<bool, String> {
+        let bind_addr = http::parse_bind_addr(bind_addr)?;
+        let unix_socket = unix_socket.trim();
+        if !unix_socket.is_empty() && !std::path::Path::new(unix_socket).is_absolute() {
+            return Err(format!("Unix socket path '{}' must be absolute.", unix_socket));
+        }
+        let changed = bind_addr != self.settings.web_bind_addr || unix_socket != self.settings.web_unix_socket;
+        self.settings.web_bind_addr = bind_addr;
+        self.settings.web_unix_socket = unix_socket.to_string();
+        Ok(changed)
+    }
+
+    /// Reverse proxies whose X-Forwarded-For the web server believes, besides loopback
+    pub(crate) fn web_trusted_proxies(&self) -> Vec<std::net::IpAddr> {
+        http::parse_trusted_proxies(&self.settings.web_trusted_proxies).unwrap_or_default()
+    }
+
+    /// Set Trusted Proxies (comma-separated IP addresses). Returns whether it changed,
+    /// i.e. whether the server needs a restart.
+    pub(crate) fn set_trusted_proxies(&mut self, text: &str) -> Result<bool, String> {
+        let proxies = http::parse_trusted_proxies(text)?;
+        let text = proxies.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ");
+        let changed = text != self.settings.web_trusted_proxies;
+        self.settings.web_trusted_proxies = text;
+        Ok(changed)
+    }
+
+    /// Point Let's Encrypt at `domain` (empty = off) with contact `email`, replacing any
+    /// custom cert files and starting an order if the certificate is missing. Returns
+    /// whether the domain changed. Shared by `/web acme` and the web settings popups.
+    pub(crate) fn set_acme_domain(&mut self, domain: &str, email: &str) -> Result<bool, String> {
+        let domain = if domain.trim().is_empty() { String::new() } else { acme::validate_domain(domain)? };
+        let email = email.trim();
+        if !email.is_empty() && !email.contains('@') {
+            return Err(format!("'{}' isn't an email address.", email));
+        }
+        self.settings.acme_email = email.to_string();
+        let changed = domain != self.settings.acme_domain;
+        if changed {
+            acme::remove_certificate();
+            self.settings.acme_domain = domain;
+            self.acme_retry_at = None;
+        }
+        if !self.settings.acme_domain.is_empty() {
+            self.settings.websocket_cert_file.clear();
+            self.settings.websocket_key_file.clear();
+            self.start_acme_if_due(changed);
+            if changed && self.acme_in_progress {
+                self.add_output(&format!("Requesting a Let's Encrypt certificate for {}...", self.settings.acme_domain));
+            }
+        }
+        Ok(changed)
+    }
+
     fn handle_import_result(
         &mut self,
         client_id: u64,
@@ -9180,10 +11748,56 @@ impl App {
         }
     }
 
+    /// Lines for a RequestScrollback, and whether they reach the end of the history in
+    /// the direction asked for. None for a world that doesn't exist.
+    pub(crate) fn scrollback_range(&self, world_index: usize, count: usize, before_seq: Option<u64>, after_seq: Option<u64>) -> Option<(Vec<TimestampedLine>, bool)> {
+        let world = self.worlds.get(world_index)?;
+        let eligible: Vec<&OutputLine> = if let Some(seq) = before_seq {
+            // Lines with seq < before_seq (older than what the client has), the
+            // newest `count` of them
+            let older: Vec<_> = world.output_lines.iter().filter(|l| l.seq < seq).collect();
+            older[older.len().saturating_sub(count)..].to_vec()
+        } else if let Some(seq) = after_seq {
+            // Reconnect gap-fill: the client kept its buffer across the reconnect and
+            // only wants lines newer than the highest seq it already has. Oldest-first
+            // (unlike before_seq's newest-first slice) so the client can append+dedup
+            // in order.
+            world.output_lines.iter().filter(|l| l.seq > seq).take(count).collect()
+        } else {
+            // No before_seq/after_seq - the last N lines (backwards compatible)
+            world.output_lines.range(world.output_lines.len().saturating_sub(count)..).collect()
+        };
+        let lines: Vec<TimestampedLine> = eligible.into_iter()
+            .map(|line| TimestampedLine {
+                text: line.text.clone(),
+                ts: line.timestamp.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                gagged: line.gagged,
+                from_server: line.from_server,
+                seq: line.seq,
+                highlight_color: line.highlight_color.clone(),
+                marked_new: line.marked_new,
+                from_archive: line.from_archive,
+            })
+            .collect();
+        // Works for both directions: before_seq slices the newest `count` eligible
+        // lines (so `lines.len() < count` means we ran out of older history); after_seq
+        // takes at most `count` (so `lines.len() < count` means we returned every newer
+        // line available, i.e. the gap is fully closed).
+        let backfill_complete = lines.len() < count;
+        Some((lines, backfill_complete))
+    }
+
     /// Build initial state message for a newly authenticated client.
     /// Only sends output_lines (not pending_lines) - clients see the More indicator
     /// and release pending via PgDn/Tab, avoiding duplicate line bugs.
     fn build_initial_state(&self) -> WsMessage {
+        self.build_resumed_initial_state(&std::collections::HashMap::new())
+    }
+
+    /// InitialState for a client reconnecting with lines it already holds (AuthRequest's
+    /// resume_seqs): those worlds get only the lines after the client's seq, so a
+    /// reconnect doesn't ship the same scrollback again.
+    fn build_resumed_initial_state(&self, resume_seqs: &std::collections::HashMap<String, u64>) -> WsMessage {
         // Send only the most recent lines in InitialState for fast initial load.
         // Clients backfill remaining history via RequestScrollback after rendering.
         let per_world_cap = self.settings.remote_initial_lines.max(1) as usize;
@@ -9220,7 +11834,13 @@ impl App {
             // lines, not one - the loop below only breaks *after* counting a line, so
             // without this guard `visible_count >= 0` is trivially true right after the
             // first line and one line slips through per exhausted world.
-            let skip = if max_initial_lines == 0 {
+            let resumed = resume_seqs.contains_key(&world.name);
+            let skip = if let Some(&held) = resume_seqs.get(&world.name) {
+                // Everything after the client's newest line, or nothing if that's more
+                // than fits and the client's gap-fill has to page it in anyway
+                let first_new = world.output_lines.iter().position(|l| l.seq > held).unwrap_or(total_lines).min(total_lines);
+                if total_lines - first_new <= max_initial_lines { first_new } else { total_lines }
+            } else if max_initial_lines == 0 {
                 total_lines
             } else {
                 let mut visible_count = 0;
@@ -9276,24 +11896,7 @@ impl App {
                 // Report server's paused state so client shows More indicator
                 paused: world.paused,
                 unseen_lines: world.unseen_lines,
-                settings: WorldSettingsMsg {
-                    hostname: world.settings.hostname.clone(),
-                    port: world.settings.port.clone(),
-                    user: world.settings.user.clone(),
-                    password: {
-                        let p = persistence::decrypt_password(&world.settings.password);
-                        if p.starts_with("ENC:") { String::new() } else { p }
-                    },
-                    has_password: !world.settings.password.is_empty(),
-                    use_ssl: world.settings.use_ssl,
-                    log_enabled: world.settings.log_enabled,
-                    encoding: world.settings.encoding.name().to_string(),
-                    auto_connect_type: world.settings.auto_connect_type.name().to_string(),
-                    keep_alive_type: world.settings.keep_alive_type.name().to_string(),
-                    keep_alive_cmd: world.settings.keep_alive_cmd.clone(),
-                    gmcp_packages: world.settings.gmcp_packages.clone(),
-                    auto_reconnect_secs: world.settings.auto_reconnect_display(),
-                },
+                settings: self.world_settings_msg(idx),
                 last_send_secs: world.last_send_time.map(|t| t.elapsed().as_secs()),
                 last_recv_secs: world.last_receive_time.map(|t| t.elapsed().as_secs()),
                 last_nop_secs: world.last_nop_time.map(|t| t.elapsed().as_secs()),
@@ -9304,6 +11907,8 @@ impl App {
                 gmcp_user_enabled: world.gmcp_user_enabled,
                 total_output_lines: world.output_lines.len(),
                 pending_count: world.pending_lines.len(),
+                bell_count: world.bell_count,
+                resumed,
             }
         }).collect();
 
@@ -9315,6 +11920,7 @@ impl App {
             current_world_index: self.current_world_index,
             actions: self.settings.actions.clone(),
             splash_lines: generate_splash_strings(),
+            emoji_names: encoding::emoji_shortcodes_matching("").into_iter().map(str::to_string).collect(),
         }
     }
 
@@ -9336,8 +11942,10 @@ impl App {
     fn handle_spell_check(&mut self) {
         if !self.spell_state.showing_suggestions {
             if let Some((start, end, word)) = self.input.current_word() {
-                if !self.spell_checker.is_valid(&word) {
-                    let mut suggestions = self.spell_checker.suggestions(&word, 6);
+                self.load_spell_language();
+                let checker = self.world_spell_checker();
+                if !checker.is_valid(&word) && !self.spell_ignore.contains(&word) {
+                    let mut suggestions = checker.suggestions(&word, 6);
                     if !suggestions.is_empty() {
                         // Store original word and add it to the end for cycling
                         self.spell_state.original_word = word.clone();
@@ -9346,7 +11954,7 @@ impl App {
                         // Output suggestions to the output area (excluding the original word)
                         let display_suggestions: Vec<_> = suggestions[..suggestions.len()-1].to_vec();
                         self.add_output(&format!(
-                            "Suggestions for '{}': {}",
+                            "Suggestions for '{}': {} (Esc-q adds it to your dictionary, Esc-i ignores it)",
                             self.spell_state.original_word,
                             display_suggestions.join(", ")
                         ));
@@ -9363,33 +11971,208 @@ impl App {
                     }
                     // If no suggestions found or word is correctly spelled, do nothing
                 }
-                // If word is spelled correctly, do nothing
+                // If word is spelled correctly, do nothing
+            }
+            // If no word at cursor, do nothing
+        } else if !self.spell_state.suggestions.is_empty() {
+            // Cycle through suggestions (including original word at the end)
+            let replacement = self.spell_state.suggestions[self.spell_state.suggestion_index].clone();
+            self.input.replace_word(
+                self.spell_state.word_start,
+                self.spell_state.word_end,
+                &replacement,
+            );
+            self.spell_state.word_end = self.spell_state.word_start + replacement.chars().count();
+            self.spell_state.suggestion_index =
+                (self.spell_state.suggestion_index + 1) % self.spell_state.suggestions.len();
+
+            let next_word = &self.spell_state.suggestions[self.spell_state.suggestion_index];
+            if next_word == &self.spell_state.original_word {
+                self.suggestion_message = Some(format!(
+                    "Applied '{}'. Next: '{}' (original)",
+                    replacement, next_word
+                ));
+            } else {
+                self.suggestion_message = Some(format!(
+                    "Applied '{}'. Next: '{}'",
+                    replacement, next_word
+                ));
+            }
+        }
+    }
+
+    /// Reload the global dictionary after the dictionary path changes; language
+    /// dictionaries are found next to it, so they load again too
+    pub fn reload_spell_checker(&mut self) {
+        self.spell_checker = SpellChecker::new(&self.settings.dictionary_path);
+        self.spell_languages.clear();
+        self.cached_misspelled.clear();
+    }
+
+    /// Load the current world's spell language (/spelllang) the first time it's needed
+    fn load_spell_language(&mut self) {
+        let Some(world) = self.worlds.get(self.current_world_index) else { return };
+        let language = &world.settings.spell_language;
+        if !language.is_empty() && !self.spell_languages.contains_key(language) {
+            let checker = SpellChecker::for_language(language, &self.settings.dictionary_path);
+            self.spell_languages.insert(language.clone(), checker);
+        }
+    }
+
+    /// Checker for input to the current world: its language's, else the global one
+    fn world_spell_checker(&self) -> &SpellChecker {
+        self.worlds.get(self.current_world_index)
+            .and_then(|world| self.spell_languages.get(&world.settings.spell_language))
+            .and_then(Option::as_ref)
+            .unwrap_or(&self.spell_checker)
+    }
+
+    /// Add the word at the cursor to the personal dictionary (the spell_add_word key).
+    /// While Ctrl+Q suggestions show, the word goes back to how it was typed first.
+    fn spell_add_word(&mut self) {
+        if let Some(word) = self.take_spell_word() {
+            for line in commands::addword_command(self, &word) {
+                self.add_output(&line);
+            }
+        }
+    }
+
+    /// Ignore the word at the cursor until Clay exits (the spell_ignore key), for a
+    /// name that's only around for now; /spellignore keeps ignoring it.
+    fn spell_ignore_word(&mut self) {
+        if let Some(word) = self.take_spell_word() {
+            self.ignore_spell_word(&word);
+        }
+    }
+
+    fn ignore_spell_word(&mut self, word: &str) {
+        let line = match self.spell_ignore.ignore(word, false) {
+            Ok(_) => format!("Ignoring '{}' until Clay exits (/spellignore {} keeps it ignored).", word, word),
+            Err(e) => e,
+        };
+        self.add_output(&line);
+        self.cached_misspelled.clear();
+    }
+
+    /// With /autocorrect on, replace a misspelled word with its correction when a space
+    /// is typed right after it. Command lines and passwords are left alone.
+    fn check_autocorrect(&mut self) {
+        if !self.current_world().settings.autocorrect
+            || self.last_input_was_delete
+            || self.spell_state.showing_suggestions
+            || self.input_masked()
+        {
+            return;
+        }
+        let chars: Vec<char> = self.input.buffer.chars().collect();
+        let cursor_char_pos = self.input.buffer[..self.input.cursor_position].chars().count();
+        if cursor_char_pos != chars.len() || chars.last() != Some(&' ') || chars.first() == Some(&'/') {
+            return;
+        }
+        let end = chars.len() - 1;
+        let start = chars[..end].iter().rposition(|c| c.is_whitespace()).map_or(0, |p| p + 1);
+        let word: String = chars[start..end].iter().collect();
+        if word.chars().count() < 3 || !word.chars().all(char::is_alphabetic) || self.skip_autocorrect.as_ref() == Some(&word) {
+            return;
+        }
+        self.load_spell_language();
+        let checker = self.world_spell_checker();
+        if checker.is_valid(&word) || self.spell_ignore.contains(&word) {
+            return;
+        }
+        let Some(correction) = checker.correction(&word) else { return };
+        // Keep the typed capitalization: "Thier" -> "Their", "THIER" -> "THEIR"
+        let correction = if word.chars().all(char::is_uppercase) {
+            correction.to_uppercase()
+        } else if word.starts_with(char::is_uppercase) {
+            let mut c = correction.chars();
+            c.next().map(|first| first.to_uppercase().chain(c).collect()).unwrap_or_default()
+        } else {
+            correction
+        };
+        self.input.replace_word(start, end, &correction);
+        self.input.cursor_position = self.input.buffer.len();
+        self.last_autocorrect = Some((start, word, correction));
+        self.cached_misspelled.clear();
+    }
+
+    /// Put back the word /autocorrect last replaced (the spell_undo_autocorrect key), if
+    /// it's still there, and leave that word alone from then on
+    fn undo_autocorrect(&mut self) {
+        let Some((start, typed, correction)) = self.last_autocorrect.take() else { return };
+        let chars: Vec<char> = self.input.buffer.chars().collect();
+        let end = start + correction.chars().count();
+        if end > chars.len() || chars[start..end].iter().collect::<String>() != correction {
+            return;
+        }
+        let cursor_char_pos = self.input.buffer[..self.input.cursor_position].chars().count();
+        self.input.replace_word(start, end, &typed);
+        let cursor = if cursor_char_pos >= end { cursor_char_pos - end + start + typed.chars().count() } else { cursor_char_pos };
+        self.input.cursor_position = self.input.buffer.char_indices().nth(cursor).map_or(self.input.buffer.len(), |(i, _)| i);
+        self.skip_autocorrect = Some(typed);
+        self.cached_misspelled.clear();
+    }
+
+    /// Open the spell suggestions popup (the spell_menu key) for the misspelled word at
+    /// the cursor, just above the input where the word is
+    pub(crate) fn open_spell_popup(&mut self) {
+        use popup::definitions::spell::{create_spell_popup, SPELL_FIELD_LIST, SPELL_MAX_SUGGESTIONS};
+
+        let Some((start, end, word)) = self.input.current_word() else { return };
+        self.load_spell_language();
+        let checker = self.world_spell_checker();
+        if checker.is_valid(&word) || self.spell_ignore.contains(&word) {
+            return;
+        }
+        let suggestions = checker.suggestions(&word, SPELL_MAX_SUGGESTIONS);
+        if suggestions.is_empty() {
+            self.add_output(&format!("No suggestions for '{}'.", word));
+            return;
+        }
+        self.spell_state.reset();
+        self.spell_state.word_start = start;
+        self.spell_state.word_end = end;
+        self.spell_state.original_word = word.clone();
+        // The word's column on its input line; the first line follows the prompt
+        let before: String = self.input.buffer.chars().take(start).collect();
+        let column = match before.rfind('\n') {
+            Some(nl) => display_width(&before[nl + 1..]),
+            None => display_width(&strip_ansi_codes(&self.current_world().prompt)) + display_width(&before),
+        };
+        let anchor_x = column % (self.output_width as usize).max(1);
+        self.popup_manager.open(create_spell_popup(&word, &suggestions, anchor_x as u16));
+        if let Some(state) = self.popup_manager.current_mut() {
+            state.select_field(SPELL_FIELD_LIST);
+        }
+    }
+
+    /// Carry out the spell suggestions popup's choice on the word it was opened for
+    pub(crate) fn apply_spell_popup(&mut self, action: SpellPopupAction) {
+        let word = std::mem::take(&mut self.spell_state.original_word);
+        match action {
+            SpellPopupAction::Replace(suggestion) => {
+                self.input.replace_word(self.spell_state.word_start, self.spell_state.word_end, &suggestion);
+                self.cached_misspelled.clear();
+            }
+            SpellPopupAction::AddWord => {
+                for line in commands::addword_command(self, &word) {
+                    self.add_output(&line);
+                }
             }
-            // If no word at cursor, do nothing
-        } else if !self.spell_state.suggestions.is_empty() {
-            // Cycle through suggestions (including original word at the end)
-            let replacement = self.spell_state.suggestions[self.spell_state.suggestion_index].clone();
-            self.input.replace_word(
-                self.spell_state.word_start,
-                self.spell_state.word_end,
-                &replacement,
-            );
-            self.spell_state.word_end = self.spell_state.word_start + replacement.chars().count();
-            self.spell_state.suggestion_index =
-                (self.spell_state.suggestion_index + 1) % self.spell_state.suggestions.len();
+            SpellPopupAction::Ignore => self.ignore_spell_word(&word),
+        }
+    }
 
-            let next_word = &self.spell_state.suggestions[self.spell_state.suggestion_index];
-            if next_word == &self.spell_state.original_word {
-                self.suggestion_message = Some(format!(
-                    "Applied '{}'. Next: '{}' (original)",
-                    replacement, next_word
-                ));
-            } else {
-                self.suggestion_message = Some(format!(
-                    "Applied '{}'. Next: '{}'",
-                    replacement, next_word
-                ));
-            }
+    /// The word the spell keys act on: the one at the cursor, put back to how it
+    /// was typed if Ctrl+Q suggestions are showing
+    fn take_spell_word(&mut self) -> Option<String> {
+        if self.spell_state.showing_suggestions {
+            let original = self.spell_state.original_word.clone();
+            self.input.replace_word(self.spell_state.word_start, self.spell_state.word_end, &original);
+            self.spell_state.reset();
+            Some(original)
+        } else {
+            self.input.current_word().map(|(_, _, word)| word)
         }
     }
 
@@ -9551,6 +12334,10 @@ impl App {
     }
 
     fn find_misspelled_words(&mut self) -> Vec<(usize, usize)> {
+        self.load_spell_language();
+        let checker = self.world_spell_checker();
+        let ignore = &self.spell_ignore;
+        let is_misspelled = |word: &str| !checker.is_valid(word) && !ignore.contains(word);
         let mut misspelled = Vec::new();
         let chars: Vec<char> = self.input.buffer.chars().collect();
         let mut i = 0;
@@ -9630,12 +12417,12 @@ impl App {
                 // If not in cache, don't flag - user is typing a fresh word
             } else if at_end_of_input {
                 // Word at end of input but cursor moved away - check spelling
-                if !self.spell_checker.is_valid(&word) {
+                if is_misspelled(&word) {
                     misspelled.push((start, end));
                 }
             } else if has_separator(end) {
                 // Word followed by separator - check spelling
-                if !self.spell_checker.is_valid(&word) {
+                if is_misspelled(&word) {
                     misspelled.push((start, end));
                 }
             }
@@ -9657,7 +12444,7 @@ impl App {
         // Need oldest timestamp from this world's buffer
         let (world_name, oldest_ts_ms) = {
             let world = &self.worlds[world_idx];
-            let ts = world.output_lines.first().map(|l| {
+            let ts = world.output_lines.front().map(|l| {
                 l.timestamp
                     .duration_since(std::time::UNIX_EPOCH)
                     .map(|d| d.as_millis() as i64)
@@ -9680,7 +12467,7 @@ impl App {
         let count = archive_lines.len();
         let seq_start = {
             let world = &self.worlds[world_idx];
-            world.output_lines.first().map(|l| l.seq).unwrap_or(0).saturating_sub(count as u64)
+            world.output_lines.front().map(|l| l.seq).unwrap_or(0).saturating_sub(count as u64)
         };
 
         // Build separator then archive OutputLines (oldest first)
@@ -9702,17 +12489,14 @@ impl App {
         {
             let world = &mut self.worlds[world_idx];
             let inserted = prepend.len();
-            world.output_lines.splice(0..0, prepend);
+            for line in prepend.into_iter().rev() {
+                world.output_lines.push_front(line);
+            }
             world.scroll_offset += inserted;
+            world.first_marked_new_index = world.first_marked_new_index.map(|i| i + inserted);
 
-            // Cap buffer size: keep the newest 10_000 lines to avoid unbounded growth.
-            // output_lines is ordered oldest→newest, so drain from the FRONT (oldest).
-            const MAX_LINES: usize = 10_000;
-            if world.output_lines.len() > MAX_LINES {
-                let excess = world.output_lines.len() - MAX_LINES;
-                world.output_lines.drain(0..excess);
-                world.scroll_offset = world.scroll_offset.saturating_sub(excess);
-            }
+            // Keep the buffer within the scrollback cap
+            world.trim_scrollback(self.settings.scrollback_lines);
         }
 
         self.needs_output_redraw = true;
@@ -9730,7 +12514,7 @@ impl App {
 
         self.worlds[world_idx].visual_line_offset = 0;
 
-        let calc_min_offset = |output_lines: &[OutputLine]| -> usize {
+        let calc_min_offset = |output_lines: &std::collections::VecDeque<OutputLine>| -> usize {
             let mut min = 0usize;
             let mut vlines = 0usize;
             for (idx, line) in output_lines.iter().enumerate() {
@@ -9793,7 +12577,7 @@ impl App {
 
         self.worlds[world_idx].visual_line_offset = 0;
 
-        let calc_min_offset = |output_lines: &[OutputLine]| -> usize {
+        let calc_min_offset = |output_lines: &std::collections::VecDeque<OutputLine>| -> usize {
             let mut min = 0usize;
             let mut vlines = 0usize;
             for (idx, line) in output_lines.iter().enumerate() {
@@ -10012,7 +12796,7 @@ impl App {
         if world.first_marked_new_index.is_none() && world.pending_lines.iter().any(|l| l.marked_new) {
             world.first_marked_new_index = Some(world.output_lines.len());
         }
-        world.output_lines.append(&mut world.pending_lines);
+        world.output_lines.extend(world.pending_lines.drain(..));
         world.pending_since = None;
         world.paused = false;
         // If partial was in pending, it's now in output
@@ -10031,11 +12815,13 @@ pub enum AppEvent {
     TelnetDetected(String),       // world_name - telnet negotiation detected
     Prompt(String, Vec<u8>),      // world_name, prompt bytes (from telnet GA)
     WontEchoSeen(String),         // world_name - IAC WONT ECHO detected (for timeout-based prompts)
+    ServerEcho(String, bool),     // world_name, on - IAC WILL ECHO (true) / WONT ECHO (false)
     NawsRequested(String),        // world_name - server sent DO NAWS (we should send window size)
     TtypeRequested(String),       // world_name - server sent SB TTYPE SEND (we should send terminal type)
     CharsetRequested(String, Vec<String>), // world_name, offered charsets - server sent CHARSET REQUEST (RFC 2066)
     SystemMessage(String),       // message to display in current world's output
     Sigusr1Received,             // SIGUSR1 received - trigger hot reload (not available on Android)
+    Hangup,                      // SIGHUP received - the terminal went away (console mode detaches)
     // Background connection events
     ConnectionSuccess(String, mpsc::Sender<WriteCommand>, Option<SocketFd>, bool),  // world_name, cmd_tx, socket_fd, is_tls
     ConnectionFailed(String, String),  // world_name, error_message
@@ -10054,6 +12840,9 @@ pub enum AppEvent {
     MultiuserPrompt(usize, String, Vec<u8>),      // world_index, username, prompt bytes
     // Slack/Discord events
     SlackMessage(String, String), // world_name, formatted message
+    ReplayData(String, String),   // world_name, replayed log lines (see replay.rs)
+    SendFileLine(String, String), // world_name, next line of a /sendfile (see sendfile.rs)
+    ShellOutput(String, shell::Output, String), // world_name, where it goes, a line from a /shell or /pipe program
     DiscordMessage(String, String), // world_name, formatted message
     // GMCP/MSDP events
     GmcpNegotiated(String),                   // world_name
@@ -10072,6 +12861,10 @@ pub enum AppEvent {
     // Ok((settings_dat, theme_dat, keybindings_dat)) or the failure reason. See
     // handle_import_result and plan `i-d-like-to-make-snuggly-rain.md`.
     ImportResult(u64, String, Result<(String, String, String), remote_client::ImportClientError>),
+    // Result of a Let's Encrypt order: the domain it was for, Ok or the failure reason
+    AcmeResult(String, Result<(), String>),
+    // Web Push endpoints the push service reported gone (unsubscribed or expired)
+    PushGone(Vec<String>),
 }
 
 /// Successful update download ready to install
@@ -10131,6 +12924,15 @@ impl UserConnection {
 
 pub fn get_settings_path() -> PathBuf {
     // Use custom config path if set via --conf=<path>
+    if let Some(custom_path) = get_custom_config_path() {
+        return custom_path.clone();
+    }
+    clay_config_path("settings.toml")
+}
+
+/// The settings file from before settings.toml, loaded (and converted) when there is
+/// no settings.toml yet. A `--conf` file may be in either format.
+pub fn get_legacy_settings_path() -> PathBuf {
     if let Some(custom_path) = get_custom_config_path() {
         return custom_path.clone();
     }
@@ -10160,7 +12962,7 @@ fn write_debug_header(file: &mut std::fs::File) {
     let _ = writeln!(file, "=== {} — started {} ===", get_version_string(), startup_ts);
 }
 
-/// Write a debug message to ~/.clay/debug.log if debug is enabled
+/// Write a debug message to ~/.config/clay/debug.log if debug is enabled
 fn debug_log(debug_enabled: bool, message: &str) {
     if !debug_enabled {
         return;
@@ -10191,7 +12993,7 @@ fn debug_log(debug_enabled: bool, message: &str) {
     }
 }
 
-/// Write a debug message to ~/.clay/output.debug.log (output/seq debugging)
+/// Write a debug message to ~/.config/clay/output.debug.log (output/seq debugging)
 pub(crate) fn output_debug_log(message: &str) {
     if !is_debug_enabled() {
         return;
@@ -10210,7 +13012,7 @@ pub(crate) fn output_debug_log(message: &str) {
     }
 }
 
-/// Load theme file from ~/.clay/theme.dat into app.theme_file
+/// Load theme file from ~/.config/clay/theme.dat into app.theme_file
 /// If the file doesn't exist, generates a default one and loads defaults
 fn load_theme_file(app: &mut App) {
     let theme_path = clay_config_path("theme.dat");
@@ -10261,8 +13063,22 @@ pub(crate) enum NewPopupAction {
     WorldEditorConnect(usize),
     /// Notes list action
     NotesList(NotesListAction),
+    /// Tag routes popup action
+    TagRoutes(TagRoutesAction),
+    /// Gags popup action
+    Gags(GagsAction),
+    /// Macros popup action
+    Macros(MacrosAction),
     /// Recent worlds popup action
     RecentWorlds(RecentWorldsAction),
+    /// URL chosen in the /urls popup, to open in the browser
+    OpenUrl(String),
+    /// URL chosen in the /urls popup, to send to the MUD as a line of input
+    SendUrl(String),
+    /// Spell suggestions popup action
+    Spell(SpellPopupAction),
+    /// Device token picked for Revoke in the /web tokens popup
+    RevokeDeviceToken(String),
     /// /import popup submitted (plan i-d-like-to-make-snuggly-rain.md, step 8)
     ImportSubmit { addr: String, password: Option<String>, auth_key: Option<String> },
 }
@@ -10287,6 +13103,11 @@ pub(crate) struct SetupSettings {
     pub(crate) tts_speak_mode: String,
     pub(crate) scrollback: bool,
     pub(crate) wrapspace: i64,
+    pub(crate) status_format: String,
+    pub(crate) tab_bar: bool,
+    pub(crate) notify_activity: bool,
+    pub(crate) scrollback_lines: i64,
+    pub(crate) confirm_quit: bool,
 }
 
 /// Settings from the web popup. The auth key is NOT included here — it's
@@ -10304,6 +13125,12 @@ pub(crate) struct WebSettings {
     pub(crate) custom_cert: bool,
     pub(crate) ws_cert_file: String,
     pub(crate) ws_key_file: String,
+    /// Let's Encrypt domain/contact; empty unless Certificate is Let's Encrypt
+    pub(crate) acme_domain: String,
+    pub(crate) acme_email: String,
+    pub(crate) bind_addr: String,
+    pub(crate) unix_socket: String,
+    pub(crate) trusted_proxies: String,
     pub(crate) remote_initial_lines: i64,
 }
 
@@ -10329,6 +13156,29 @@ pub(crate) fn apply_web_settings(app: &mut App, settings: &WebSettings) {
     app.settings.websocket_cert_file = new_cert_file;
     app.settings.websocket_key_file = new_key_file;
     app.settings.remote_initial_lines = settings.remote_initial_lines.clamp(10, 5000) as u16;
+    // Turning Let's Encrypt off goes back to the self-signed cert; a new domain's
+    // cert restarts the server itself once it's issued
+    let acme_off = match app.set_acme_domain(&settings.acme_domain, &settings.acme_email) {
+        Ok(changed) => changed && app.settings.acme_domain.is_empty(),
+        Err(e) => {
+            app.add_output(&e);
+            false
+        }
+    };
+    let listen_changed = match app.set_web_listen(&settings.bind_addr, &settings.unix_socket) {
+        Ok(changed) => changed,
+        Err(e) => {
+            app.add_output(&e);
+            false
+        }
+    };
+    let proxies_changed = match app.set_trusted_proxies(&settings.trusted_proxies) {
+        Ok(changed) => changed,
+        Err(e) => {
+            app.add_output(&e);
+            false
+        }
+    };
 
     // Update the running server's allow list and password immediately (no reload needed)
     if let Some(ref server) = app.ws_server {
@@ -10340,7 +13190,7 @@ pub(crate) fn apply_web_settings(app: &mut App, settings: &WebSettings) {
 
     let _ = persistence::save_settings(app);
 
-    if port_changed || http_changed || cert_changed || web_path_changed {
+    if port_changed || http_changed || cert_changed || web_path_changed || acme_off || listen_changed || proxies_changed {
         app.web_restart_needed = true;
         app.add_output("Web settings saved. Restarting web server...");
     } else {
@@ -10359,6 +13209,11 @@ pub(crate) fn web_settings_from_custom_data(data: &std::collections::HashMap<Str
         custom_cert: data.get("custom_cert").map(|v| v == "true").unwrap_or(false),
         ws_cert_file: data.get("ws_cert_file").cloned().unwrap_or_default(),
         ws_key_file: data.get("ws_key_file").cloned().unwrap_or_default(),
+        acme_domain: data.get("acme_domain").cloned().unwrap_or_default(),
+        acme_email: data.get("acme_email").cloned().unwrap_or_default(),
+        bind_addr: data.get("bind_addr").cloned().unwrap_or_default(),
+        unix_socket: data.get("unix_socket").cloned().unwrap_or_default(),
+        trusted_proxies: data.get("trusted_proxies").cloned().unwrap_or_default(),
         remote_initial_lines: data.get("remote_initial_lines")
             .and_then(|v| v.parse::<i64>().ok())
             .unwrap_or(100),
@@ -10377,11 +13232,26 @@ pub(crate) struct WorldEditorSettings {
     pub(crate) user: String,
     pub(crate) password: String,
     pub(crate) use_ssl: bool,
+    pub(crate) tls_verify: String,
+    pub(crate) tls_client_cert: String,
+    pub(crate) tls_client_key: String,
+    pub(crate) ssh_host: String,
+    pub(crate) ssh_user: String,
+    pub(crate) ssh_key: String,
     pub(crate) log_enabled: bool,
+    pub(crate) log_format: String,
+    pub(crate) log_timestamps: String,
+    pub(crate) log_session_markers: bool,
     pub(crate) encoding: String,
     pub(crate) auto_connect: String,
+    pub(crate) login_script: String,
+    pub(crate) connect_commands: String,
+    pub(crate) input_prefix: String,
+    pub(crate) input_suffix: String,
     pub(crate) keep_alive: String,
     pub(crate) keep_alive_cmd: String,
+    pub(crate) keep_alive_interval: String,
+    pub(crate) silence_alert: String,
     pub(crate) gmcp_packages: String,
     pub(crate) auto_reconnect_secs: String,
     // Slack fields
@@ -10409,6 +13279,10 @@ pub(crate) enum ActionsListAction {
     Edit(usize),          // Edit action at index
     Delete(usize),        // Delete action at index
     Toggle(usize),        // Toggle enable/disable action at index
+    Duplicate(usize),     // Copy action at index, right below it
+    Move(usize, bool),    // Move action at index up (true) or down one place
+    Import,               // Start an /actions import in the input line
+    Export,               // Start an /actions export in the input line
 }
 
 /// Actions from the notes list popup
@@ -10416,6 +13290,34 @@ pub(crate) enum NotesListAction {
     Open(String),         // Open notes for world by name
 }
 
+/// Actions from the macros popup (key names like "F5"/"Shift-F5")
+pub(crate) enum MacrosAction {
+    Add,                  // Start a new /macro command in the input line
+    Edit(String),         // Put "/macro <key> <text>" in the input line
+    Delete(String),       // Remove the macro on key
+}
+
+/// Actions from the tag routes popup (route index into settings.tag_routes)
+pub(crate) enum TagRoutesAction {
+    Toggle(usize),        // Enable/disable route
+    Delete(usize),        // Delete route
+}
+
+/// Actions from the gags popup (gag index into settings.gags)
+pub(crate) enum GagsAction {
+    Add,                  // Start a new /gag command in the input line
+    Toggle(usize),        // Enable/disable gag
+    Keep(usize),          // Switch gag between saved and session-only
+    Delete(usize),        // Delete gag
+}
+
+/// Actions from the spell suggestions popup, on the word it was opened for
+pub(crate) enum SpellPopupAction {
+    Replace(String),      // Replace the word with this suggestion
+    AddWord,              // Add the word to the personal dictionary
+    Ignore,               // Ignore the word until Clay exits
+}
+
 /// Actions from the recent worlds popup
 pub(crate) enum RecentWorldsAction {
     Switch(String),       // Switch to world by name
@@ -10438,14 +13340,15 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
         SETUP_FIELD_INPUT_HEIGHT, SETUP_FIELD_GUI_THEME, SETUP_FIELD_TLS_PROXY,
         SETUP_FIELD_DICTIONARY, SETUP_FIELD_EDITOR_SIDE, SETUP_FIELD_MOUSE, SETUP_FIELD_ZWJ, SETUP_FIELD_ANSI_MUSIC,
         SETUP_FIELD_NEW_LINE_INDICATOR, SETUP_FIELD_TTS, SETUP_FIELD_TTS_SPEAK_MODE,
-        SETUP_FIELD_SCROLLBACK, SETUP_FIELD_WRAPSPACE,
+        SETUP_FIELD_SCROLLBACK, SETUP_FIELD_WRAPSPACE, SETUP_FIELD_STATUS_FORMAT, SETUP_FIELD_TAB_BAR, SETUP_FIELD_NOTIFY_ACTIVITY, SETUP_FIELD_SCROLLBACK_LINES, SETUP_FIELD_CONFIRM_QUIT,
         SETUP_BTN_SAVE, SETUP_BTN_CANCEL,
     };
     use popup::definitions::web::{
         WEB_FIELD_PORT, WEB_FIELD_CUSTOM_PORT, WEB_FIELD_WEB_PATH,
         WEB_FIELD_WS_PASSWORD, WEB_FIELD_AUTH_KEY,
         WEB_FIELD_WS_ALLOW_LIST, WEB_FIELD_CUSTOM_CERT, WEB_FIELD_WS_CERT_FILE, WEB_FIELD_WS_KEY_FILE,
-        WEB_FIELD_REMOTE_LINES,
+        WEB_FIELD_ACME_DOMAIN, WEB_FIELD_ACME_EMAIL, WEB_FIELD_REMOTE_LINES,
+        WEB_FIELD_BIND_ADDR, WEB_FIELD_UNIX_SOCKET, WEB_FIELD_TRUSTED_PROXIES,
         WEB_BTN_SAVE, WEB_BTN_CANCEL, WEB_BTN_MODIFY_KEY,
         update_web_visibility,
     };
@@ -10457,15 +13360,21 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
     use popup::definitions::actions::{
         ACTIONS_FIELD_FILTER, ACTIONS_FIELD_LIST,
         ACTIONS_BTN_ADD, ACTIONS_BTN_EDIT, ACTIONS_BTN_DELETE, ACTIONS_BTN_CANCEL,
+        ACTIONS_BTN_IMPORT, ACTIONS_BTN_EXPORT, ACTIONS_BTN_DUPLICATE,
         EDITOR_FIELD_NAME, EDITOR_FIELD_WORLD, EDITOR_FIELD_MATCH_TYPE,
         EDITOR_FIELD_PATTERNS, EDITOR_FIELD_COMMAND, EDITOR_FIELD_ENABLED, EDITOR_FIELD_STARTUP,
-        EDITOR_BTN_SAVE, EDITOR_BTN_CANCEL, EDITOR_BTN_DELETE,
+        EDITOR_BTN_SAVE, EDITOR_BTN_CANCEL, EDITOR_BTN_DELETE, refresh_action_test,
     };
     use popup::definitions::world_editor::{
         WORLD_FIELD_NAME, WORLD_FIELD_TYPE, WORLD_FIELD_HOSTNAME, WORLD_FIELD_PORT,
         WORLD_FIELD_USER, WORLD_FIELD_PASSWORD, WORLD_FIELD_USE_SSL, WORLD_FIELD_LOG_ENABLED,
         WORLD_FIELD_ENCODING, WORLD_FIELD_AUTO_CONNECT, WORLD_FIELD_KEEP_ALIVE, WORLD_FIELD_KEEP_ALIVE_CMD,
-        WORLD_FIELD_GMCP_PACKAGES, WORLD_FIELD_AUTO_RECONNECT,
+        WORLD_FIELD_KEEP_ALIVE_INTERVAL, WORLD_FIELD_SILENCE_ALERT, WORLD_FIELD_CONNECT_COMMANDS,
+        WORLD_FIELD_LOGIN_SCRIPT, WORLD_FIELD_INPUT_PREFIX, WORLD_FIELD_INPUT_SUFFIX,
+        WORLD_FIELD_GMCP_PACKAGES, WORLD_FIELD_AUTO_RECONNECT, WORLD_FIELD_LOG_FORMAT,
+        WORLD_FIELD_LOG_TIMESTAMPS, WORLD_FIELD_LOG_SESSION_MARKERS, WORLD_FIELD_TLS_VERIFY,
+        WORLD_FIELD_TLS_CLIENT_CERT, WORLD_FIELD_TLS_CLIENT_KEY,
+        WORLD_FIELD_SSH_HOST, WORLD_FIELD_SSH_USER, WORLD_FIELD_SSH_KEY,
         WORLD_FIELD_SLACK_TOKEN, WORLD_FIELD_SLACK_CHANNEL, WORLD_FIELD_SLACK_WORKSPACE,
         WORLD_FIELD_DISCORD_TOKEN, WORLD_FIELD_DISCORD_GUILD, WORLD_FIELD_DISCORD_CHANNEL, WORLD_FIELD_DISCORD_DM_USER,
         WORLD_BTN_SAVE, WORLD_BTN_CANCEL, WORLD_BTN_DELETE, WORLD_BTN_CONNECT,
@@ -10513,7 +13422,13 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
     let is_action_editor = popup_id == Some(popup::PopupId("action_editor"));
     let is_world_editor = popup_id == Some(popup::PopupId("world_editor"));
     let is_notes_list = popup_id == Some(popup::PopupId("notes_list"));
+    let is_tag_routes = popup_id == Some(popup::PopupId("tag_routes"));
+    let is_gags = popup_id == Some(popup::PopupId("gags"));
+    let is_macros = popup_id == Some(popup::PopupId("macros"));
     let is_recent_worlds = popup_id == Some(popup::PopupId("recent_worlds"));
+    let is_urls = popup_id == Some(popup::PopupId("urls"));
+    let is_spell = popup_id == Some(popup::PopupId("spell"));
+    let is_device_tokens = popup_id == Some(popup::PopupId("device_tokens"));
     let is_import = popup_id == Some(popup::PopupId("import"));
 
     if let Some(state) = app.popup_manager.current_mut() {
@@ -10688,6 +13603,13 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                     tts_speak_mode: state.get_selected(SETUP_FIELD_TTS_SPEAK_MODE).unwrap_or("all").to_string(),
                     scrollback: state.get_bool(SETUP_FIELD_SCROLLBACK).unwrap_or(false),
                     wrapspace: state.get_number(SETUP_FIELD_WRAPSPACE).unwrap_or(0),
+                    status_format: state.get_text(SETUP_FIELD_STATUS_FORMAT)
+                        .unwrap_or("").trim().to_string(),
+                    tab_bar: state.get_bool(SETUP_FIELD_TAB_BAR).unwrap_or(false),
+                    notify_activity: state.get_bool(SETUP_FIELD_NOTIFY_ACTIVITY).unwrap_or(false),
+                    scrollback_lines: state.get_number(SETUP_FIELD_SCROLLBACK_LINES)
+                        .unwrap_or(DEFAULT_SCROLLBACK_LINES as i64),
+                    confirm_quit: state.get_bool(SETUP_FIELD_CONFIRM_QUIT).unwrap_or(true),
                 }
             };
 
@@ -10824,6 +13746,8 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
         if is_web {
             // Helper to extract settings before closing
             let extract_settings = || -> WebSettings {
+                let acme = state.get_selected(WEB_FIELD_CUSTOM_CERT) == Some("acme");
+                let acme_text = |id| if acme { state.get_text(id).unwrap_or("").trim().to_string() } else { String::new() };
                 let port_selected = state.get_selected(WEB_FIELD_PORT).unwrap_or("disabled");
                 let (http_enabled, http_port) = match port_selected {
                     "disabled" => (false, "9000".to_string()),
@@ -10839,6 +13763,11 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                     custom_cert: state.get_selected(WEB_FIELD_CUSTOM_CERT) == Some("yes"),
                     ws_cert_file: state.get_text(WEB_FIELD_WS_CERT_FILE).unwrap_or("").to_string(),
                     ws_key_file: state.get_text(WEB_FIELD_WS_KEY_FILE).unwrap_or("").to_string(),
+                    acme_domain: acme_text(WEB_FIELD_ACME_DOMAIN),
+                    acme_email: acme_text(WEB_FIELD_ACME_EMAIL),
+                    bind_addr: state.get_text(WEB_FIELD_BIND_ADDR).unwrap_or("").to_string(),
+                    unix_socket: state.get_text(WEB_FIELD_UNIX_SOCKET).unwrap_or("").to_string(),
+                    trusted_proxies: state.get_text(WEB_FIELD_TRUSTED_PROXIES).unwrap_or("").to_string(),
                     remote_initial_lines: state.get_text(WEB_FIELD_REMOTE_LINES)
                         .and_then(|s| s.trim().parse::<i64>().ok())
                         .unwrap_or(100),
@@ -11199,12 +14128,356 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                 }
                 Char(c) => {
                     if let Some(btn_id) = state.find_button_by_shortcut(c) {
-                        if btn_id == NOTES_BTN_CANCEL {
+                        if btn_id == NOTES_BTN_CANCEL {
+                            app.popup_manager.close();
+                            return NewPopupAction::None;
+                        } else if let Some(name) = get_selected_name() {
+                            app.popup_manager.close();
+                            return NewPopupAction::NotesList(NotesListAction::Open(name));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return NewPopupAction::None;
+        }
+
+        // URLs popup handling
+        if is_urls {
+            use popup::definitions::urls::{URLS_BTN_CLOSE, URLS_BTN_COPY, URLS_BTN_OPEN, URLS_BTN_SEND};
+
+            let button = match key.code {
+                Esc => Some(URLS_BTN_CLOSE),
+                // Enter on the list opens the highlighted URL
+                Enter => [URLS_BTN_CLOSE, URLS_BTN_COPY, URLS_BTN_SEND].into_iter()
+                    .find(|&id| state.is_button_focused(id))
+                    .or(Some(URLS_BTN_OPEN)),
+                Char(c) => state.find_button_by_shortcut(c),
+                Up => { state.list_select_up(); None }
+                Down => { state.list_select_down(); None }
+                Tab => { state.cycle_field_buttons(); None }
+                BackTab => { state.cycle_field_buttons_rev(); None }
+                _ => None,
+            };
+            let url = state.get_selected_list_item().map(|item| item.id.clone());
+            match (button, url) {
+                (Some(id), _) if id == URLS_BTN_CLOSE => {
+                    app.popup_manager.close();
+                }
+                (Some(id), Some(url)) if id == URLS_BTN_COPY => {
+                    // OSC 52, as the device tokens popup copies
+                    use std::io::Write;
+                    let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, url.as_bytes());
+                    let _ = std::io::stdout().write_all(format!("\x1b]52;c;{}\x07", encoded).as_bytes());
+                    let _ = std::io::stdout().flush();
+                    state.error = Some("URL copied to clipboard".to_string());
+                    state.error_at = Some(std::time::Instant::now());
+                }
+                (Some(id), Some(url)) if id == URLS_BTN_SEND => {
+                    app.popup_manager.close();
+                    return NewPopupAction::SendUrl(url);
+                }
+                (Some(_), Some(url)) => {
+                    app.popup_manager.close();
+                    return NewPopupAction::OpenUrl(url);
+                }
+                _ => {}
+            }
+            return NewPopupAction::None;
+        }
+
+        // Device tokens popup handling
+        if is_device_tokens {
+            use popup::definitions::device_tokens::{new_token_key, TOKENS_BTN_CLOSE, TOKENS_BTN_COPY, TOKENS_BTN_REVOKE};
+
+            let button = match key.code {
+                Esc => Some(TOKENS_BTN_CLOSE),
+                Enter | Char(' ') => [TOKENS_BTN_REVOKE, TOKENS_BTN_COPY, TOKENS_BTN_CLOSE].into_iter()
+                    .find(|&id| state.is_button_focused(id)),
+                Char(c) => state.find_button_by_shortcut(c),
+                Up => { state.list_select_up(); None }
+                Down => { state.list_select_down(); None }
+                Tab => { state.cycle_field_buttons(); None }
+                BackTab => { state.cycle_field_buttons_rev(); None }
+                _ => None,
+            };
+            match button {
+                Some(id) if id == TOKENS_BTN_CLOSE => {
+                    app.popup_manager.close();
+                }
+                Some(id) if id == TOKENS_BTN_COPY => {
+                    // OSC 52, as the Modify Key popup copies
+                    if let Some(key_text) = new_token_key(state) {
+                        use std::io::Write;
+                        let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, key_text.as_bytes());
+                        let _ = std::io::stdout().write_all(format!("\x1b]52;c;{}\x07", encoded).as_bytes());
+                        let _ = std::io::stdout().flush();
+                        state.error = Some("Device key copied to clipboard".to_string());
+                        state.error_at = Some(std::time::Instant::now());
+                    }
+                }
+                Some(id) if id == TOKENS_BTN_REVOKE => {
+                    if let Some(item) = state.get_selected_list_item() {
+                        return NewPopupAction::RevokeDeviceToken(item.id.clone());
+                    }
+                }
+                _ => {}
+            }
+            return NewPopupAction::None;
+        }
+
+        // Spell suggestions popup handling
+        if is_spell {
+            use popup::definitions::spell::{SPELL_BTN_ADD, SPELL_BTN_IGNORE, SPELL_FIELD_LIST};
+
+            match key.code {
+                Esc => {
+                    app.popup_manager.close();
+                }
+                Enter => {
+                    let action = if state.is_button_focused(SPELL_BTN_ADD) {
+                        Some(SpellPopupAction::AddWord)
+                    } else if state.is_button_focused(SPELL_BTN_IGNORE) {
+                        Some(SpellPopupAction::Ignore)
+                    } else {
+                        state.get_selected_list_item().map(|item| SpellPopupAction::Replace(item.id.clone()))
+                    };
+                    if let Some(action) = action {
+                        app.popup_manager.close();
+                        return NewPopupAction::Spell(action);
+                    }
+                }
+                Up => {
+                    state.list_select_up();
+                }
+                Down => {
+                    state.list_select_down();
+                }
+                Tab => {
+                    state.cycle_field_buttons();
+                }
+                BackTab => {
+                    state.cycle_field_buttons_rev();
+                }
+                Char(c @ '1'..='9') => {
+                    let n = c as usize - '1' as usize;
+                    let picked = match state.field(SPELL_FIELD_LIST).map(|f| &f.kind) {
+                        Some(popup::FieldKind::List { items, .. }) => items.get(n).map(|item| item.id.clone()),
+                        _ => None,
+                    };
+                    if let Some(word) = picked {
+                        app.popup_manager.close();
+                        return NewPopupAction::Spell(SpellPopupAction::Replace(word));
+                    }
+                }
+                Char(c) => {
+                    if let Some(btn_id) = state.find_button_by_shortcut(c) {
+                        app.popup_manager.close();
+                        let action = if btn_id == SPELL_BTN_ADD { SpellPopupAction::AddWord } else { SpellPopupAction::Ignore };
+                        return NewPopupAction::Spell(action);
+                    }
+                }
+                _ => {}
+            }
+            return NewPopupAction::None;
+        }
+
+        // Macros popup handling
+        if is_macros {
+            use popup::definitions::macros::{
+                MACROS_BTN_ADD, MACROS_BTN_EDIT, MACROS_BTN_DELETE, MACROS_BTN_CLOSE,
+            };
+
+            let get_selected_key = || state.get_selected_list_item().map(|item| item.id.clone());
+
+            match key.code {
+                Esc => {
+                    app.popup_manager.close();
+                }
+                Enter => {
+                    if state.is_button_focused(MACROS_BTN_CLOSE) {
+                        app.popup_manager.close();
+                    } else if state.is_button_focused(MACROS_BTN_ADD) {
+                        app.popup_manager.close();
+                        return NewPopupAction::Macros(MacrosAction::Add);
+                    } else if state.is_button_focused(MACROS_BTN_DELETE) {
+                        if let Some(key) = get_selected_key() {
+                            return NewPopupAction::Macros(MacrosAction::Delete(key));
+                        }
+                    } else if let Some(key) = get_selected_key() {
+                        // Edit button or list item
+                        app.popup_manager.close();
+                        return NewPopupAction::Macros(MacrosAction::Edit(key));
+                    }
+                }
+                Delete => {
+                    if let Some(key) = get_selected_key() {
+                        return NewPopupAction::Macros(MacrosAction::Delete(key));
+                    }
+                }
+                Up => {
+                    state.list_select_up();
+                }
+                Down => {
+                    state.list_select_down();
+                }
+                Tab => {
+                    state.cycle_field_buttons();
+                }
+                BackTab => {
+                    state.cycle_field_buttons_rev();
+                }
+                Char(c) => {
+                    if let Some(btn_id) = state.find_button_by_shortcut(c) {
+                        if btn_id == MACROS_BTN_CLOSE {
+                            app.popup_manager.close();
+                        } else if btn_id == MACROS_BTN_ADD {
+                            app.popup_manager.close();
+                            return NewPopupAction::Macros(MacrosAction::Add);
+                        } else if let Some(key) = get_selected_key() {
+                            if btn_id == MACROS_BTN_EDIT {
+                                app.popup_manager.close();
+                                return NewPopupAction::Macros(MacrosAction::Edit(key));
+                            } else if btn_id == MACROS_BTN_DELETE {
+                                return NewPopupAction::Macros(MacrosAction::Delete(key));
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return NewPopupAction::None;
+        }
+
+        // Tag routes popup handling
+        if is_tag_routes {
+            use popup::definitions::tag_routes::{
+                TAG_ROUTES_BTN_TOGGLE, TAG_ROUTES_BTN_DELETE, TAG_ROUTES_BTN_CLOSE,
+            };
+
+            let get_selected_index = || state.get_selected_list_item().and_then(|item| item.id.parse::<usize>().ok());
+
+            match key.code {
+                Esc => {
+                    app.popup_manager.close();
+                }
+                Enter => {
+                    if state.is_button_focused(TAG_ROUTES_BTN_DELETE) {
+                        if let Some(idx) = get_selected_index() {
+                            return NewPopupAction::TagRoutes(TagRoutesAction::Delete(idx));
+                        }
+                    } else if state.is_button_focused(TAG_ROUTES_BTN_CLOSE) {
+                        app.popup_manager.close();
+                    } else if let Some(idx) = get_selected_index() {
+                        // Toggle button or list item
+                        return NewPopupAction::TagRoutes(TagRoutesAction::Toggle(idx));
+                    }
+                }
+                Char(' ') => {
+                    if let Some(idx) = get_selected_index() {
+                        return NewPopupAction::TagRoutes(TagRoutesAction::Toggle(idx));
+                    }
+                }
+                Delete => {
+                    if let Some(idx) = get_selected_index() {
+                        return NewPopupAction::TagRoutes(TagRoutesAction::Delete(idx));
+                    }
+                }
+                Up => {
+                    state.list_select_up();
+                }
+                Down => {
+                    state.list_select_down();
+                }
+                Tab => {
+                    state.cycle_field_buttons();
+                }
+                BackTab => {
+                    state.cycle_field_buttons_rev();
+                }
+                Char(c) => {
+                    if let Some(btn_id) = state.find_button_by_shortcut(c) {
+                        if btn_id == TAG_ROUTES_BTN_CLOSE {
+                            app.popup_manager.close();
+                        } else if let Some(idx) = get_selected_index() {
+                            if btn_id == TAG_ROUTES_BTN_TOGGLE {
+                                return NewPopupAction::TagRoutes(TagRoutesAction::Toggle(idx));
+                            } else if btn_id == TAG_ROUTES_BTN_DELETE {
+                                return NewPopupAction::TagRoutes(TagRoutesAction::Delete(idx));
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return NewPopupAction::None;
+        }
+
+        // Gags popup handling
+        if is_gags {
+            use popup::definitions::gags::{
+                GAGS_BTN_ADD, GAGS_BTN_TOGGLE, GAGS_BTN_KEEP, GAGS_BTN_DELETE, GAGS_BTN_CLOSE,
+            };
+
+            let get_selected_index = || state.get_selected_list_item().and_then(|item| item.id.parse::<usize>().ok());
+
+            match key.code {
+                Esc => {
+                    app.popup_manager.close();
+                }
+                Enter => {
+                    if state.is_button_focused(GAGS_BTN_CLOSE) {
+                        app.popup_manager.close();
+                    } else if state.is_button_focused(GAGS_BTN_ADD) {
+                        app.popup_manager.close();
+                        return NewPopupAction::Gags(GagsAction::Add);
+                    } else if let Some(idx) = get_selected_index() {
+                        if state.is_button_focused(GAGS_BTN_DELETE) {
+                            return NewPopupAction::Gags(GagsAction::Delete(idx));
+                        } else if state.is_button_focused(GAGS_BTN_KEEP) {
+                            return NewPopupAction::Gags(GagsAction::Keep(idx));
+                        }
+                        // Toggle button or list item
+                        return NewPopupAction::Gags(GagsAction::Toggle(idx));
+                    }
+                }
+                Char(' ') => {
+                    if let Some(idx) = get_selected_index() {
+                        return NewPopupAction::Gags(GagsAction::Toggle(idx));
+                    }
+                }
+                Delete => {
+                    if let Some(idx) = get_selected_index() {
+                        return NewPopupAction::Gags(GagsAction::Delete(idx));
+                    }
+                }
+                Up => {
+                    state.list_select_up();
+                }
+                Down => {
+                    state.list_select_down();
+                }
+                Tab => {
+                    state.cycle_field_buttons();
+                }
+                BackTab => {
+                    state.cycle_field_buttons_rev();
+                }
+                Char(c) => {
+                    if let Some(btn_id) = state.find_button_by_shortcut(c) {
+                        if btn_id == GAGS_BTN_CLOSE {
                             app.popup_manager.close();
-                            return NewPopupAction::None;
-                        } else if let Some(name) = get_selected_name() {
+                        } else if btn_id == GAGS_BTN_ADD {
                             app.popup_manager.close();
-                            return NewPopupAction::NotesList(NotesListAction::Open(name));
+                            return NewPopupAction::Gags(GagsAction::Add);
+                        } else if let Some(idx) = get_selected_index() {
+                            if btn_id == GAGS_BTN_TOGGLE {
+                                return NewPopupAction::Gags(GagsAction::Toggle(idx));
+                            } else if btn_id == GAGS_BTN_KEEP {
+                                return NewPopupAction::Gags(GagsAction::Keep(idx));
+                            } else if btn_id == GAGS_BTN_DELETE {
+                                return NewPopupAction::Gags(GagsAction::Delete(idx));
+                            }
                         }
                     }
                 }
@@ -11434,6 +14707,16 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                                 app.popup_manager.close();
                                 return NewPopupAction::ActionsList(ActionsListAction::Delete(idx));
                             }
+                        } else if state.is_button_focused(ACTIONS_BTN_DUPLICATE) {
+                            if let Some(idx) = get_selected_index() {
+                                return NewPopupAction::ActionsList(ActionsListAction::Duplicate(idx));
+                            }
+                        } else if state.is_button_focused(ACTIONS_BTN_IMPORT) {
+                            app.popup_manager.close();
+                            return NewPopupAction::ActionsList(ActionsListAction::Import);
+                        } else if state.is_button_focused(ACTIONS_BTN_EXPORT) {
+                            app.popup_manager.close();
+                            return NewPopupAction::ActionsList(ActionsListAction::Export);
                         } else if state.is_button_focused(ACTIONS_BTN_CANCEL) {
                             app.popup_manager.close();
                         }
@@ -11448,6 +14731,12 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                         }
                     }
                 }
+                Up | Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                    // Shift+Up/Down moves the selected action, which is checked in list order
+                    if let Some(idx) = get_selected_index() {
+                        return NewPopupAction::ActionsList(ActionsListAction::Move(idx, key.code == Up));
+                    }
+                }
                 Up => {
                     state.list_select_up();
                 }
@@ -11488,6 +14777,16 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                                 app.popup_manager.close();
                                 return NewPopupAction::ActionsList(ActionsListAction::Delete(idx));
                             }
+                        } else if btn_id == ACTIONS_BTN_DUPLICATE {
+                            if let Some(idx) = get_selected_index() {
+                                return NewPopupAction::ActionsList(ActionsListAction::Duplicate(idx));
+                            }
+                        } else if btn_id == ACTIONS_BTN_IMPORT {
+                            app.popup_manager.close();
+                            return NewPopupAction::ActionsList(ActionsListAction::Import);
+                        } else if btn_id == ACTIONS_BTN_EXPORT {
+                            app.popup_manager.close();
+                            return NewPopupAction::ActionsList(ActionsListAction::Export);
                         } else if btn_id == ACTIONS_BTN_CANCEL {
                             app.popup_manager.close();
                         }
@@ -11540,6 +14839,10 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                         state.next_field();
                     } else if state.is_on_button() {
                         if state.is_button_focused(EDITOR_BTN_SAVE) {
+                            if refresh_action_test(state).is_some() {
+                                // The tester line names the pattern that doesn't compile
+                                return NewPopupAction::None;
+                            }
                             // Extract action data and save
                             let name = state.get_text(EDITOR_FIELD_NAME).unwrap_or("").to_string();
                             let world = state.get_text(EDITOR_FIELD_WORLD).unwrap_or("").to_string();
@@ -11721,6 +15024,9 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                         // Not editing: bare letters are button hotkeys only (never
                         // implicit text entry - Enter is required to start editing).
                         if btn_id == EDITOR_BTN_SAVE {
+                            if refresh_action_test(state).is_some() {
+                                return NewPopupAction::None;
+                            }
                             let name = state.get_text(EDITOR_FIELD_NAME).unwrap_or("").to_string();
                             let world = state.get_text(EDITOR_FIELD_WORLD).unwrap_or("").to_string();
                             let command = state.get_text(EDITOR_FIELD_COMMAND).unwrap_or("").to_string();
@@ -11768,6 +15074,12 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                 }
                 _ => {}
             }
+            // Keep the pattern tester's verdict in step with what's being typed
+            if let Some(state) = app.popup_manager.current_mut() {
+                if state.definition.id == popup::PopupId("action_editor") {
+                    refresh_action_test(state);
+                }
+            }
             return NewPopupAction::None;
         }
 
@@ -11795,11 +15107,26 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                     user: state.get_text(WORLD_FIELD_USER).unwrap_or("").to_string(),
                     password: state.get_text(WORLD_FIELD_PASSWORD).unwrap_or("").to_string(),
                     use_ssl: state.get_bool(WORLD_FIELD_USE_SSL).unwrap_or(false),
+                    tls_verify: state.get_selected(WORLD_FIELD_TLS_VERIFY).unwrap_or("tofu").to_string(),
+                    tls_client_cert: state.get_text(WORLD_FIELD_TLS_CLIENT_CERT).unwrap_or("").to_string(),
+                    tls_client_key: state.get_text(WORLD_FIELD_TLS_CLIENT_KEY).unwrap_or("").to_string(),
+                    ssh_host: state.get_text(WORLD_FIELD_SSH_HOST).unwrap_or("").to_string(),
+                    ssh_user: state.get_text(WORLD_FIELD_SSH_USER).unwrap_or("").to_string(),
+                    ssh_key: state.get_text(WORLD_FIELD_SSH_KEY).unwrap_or("").to_string(),
                     log_enabled: state.get_bool(WORLD_FIELD_LOG_ENABLED).unwrap_or(false),
+                    log_format: state.get_selected(WORLD_FIELD_LOG_FORMAT).unwrap_or("raw").to_string(),
+                    log_timestamps: state.get_selected(WORLD_FIELD_LOG_TIMESTAMPS).unwrap_or("time").to_string(),
+                    log_session_markers: state.get_bool(WORLD_FIELD_LOG_SESSION_MARKERS).unwrap_or(true),
                     encoding: state.get_selected(WORLD_FIELD_ENCODING).unwrap_or("utf8").to_string(),
                     auto_connect: state.get_selected(WORLD_FIELD_AUTO_CONNECT).unwrap_or("connect").to_string(),
                     keep_alive: state.get_selected(WORLD_FIELD_KEEP_ALIVE).unwrap_or("nop").to_string(),
                     keep_alive_cmd: state.get_text(WORLD_FIELD_KEEP_ALIVE_CMD).unwrap_or("").to_string(),
+                    keep_alive_interval: state.get_text(WORLD_FIELD_KEEP_ALIVE_INTERVAL).unwrap_or("300").to_string(),
+                    silence_alert: state.get_text(WORLD_FIELD_SILENCE_ALERT).unwrap_or("0").to_string(),
+                    connect_commands: state.get_text(WORLD_FIELD_CONNECT_COMMANDS).unwrap_or("").to_string(),
+                    input_prefix: state.get_text(WORLD_FIELD_INPUT_PREFIX).unwrap_or("").to_string(),
+                    input_suffix: state.get_text(WORLD_FIELD_INPUT_SUFFIX).unwrap_or("").to_string(),
+                    login_script: state.get_text(WORLD_FIELD_LOGIN_SCRIPT).unwrap_or("").to_string(),
                     gmcp_packages: state.get_text(WORLD_FIELD_GMCP_PACKAGES).unwrap_or("Client.Media 1").to_string(),
                     auto_reconnect_secs: state.get_text(WORLD_FIELD_AUTO_RECONNECT).unwrap_or("0").to_string(),
                     slack_token: state.get_text(WORLD_FIELD_SLACK_TOKEN).unwrap_or("").to_string(),
@@ -11822,6 +15149,9 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
             let is_select = state.selected_field()
                 .map(|f| matches!(&f.kind, popup::FieldKind::Select { .. }))
                 .unwrap_or(false);
+            let is_multiline = state.selected_field()
+                .map(|f| matches!(&f.kind, popup::FieldKind::MultilineText { .. }))
+                .unwrap_or(false);
 
             // Macro to update visibility when type or keep_alive changes
             // (We can't use a closure because of borrow conflicts)
@@ -11830,10 +15160,12 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                     // Extract values first to avoid borrow conflicts
                     let world_type_str = $state.get_selected(WORLD_FIELD_TYPE).unwrap_or("mud").to_string();
                     let keep_alive_str = $state.get_selected(WORLD_FIELD_KEEP_ALIVE).unwrap_or("nop").to_string();
+                    let auto_connect_str = $state.get_selected(WORLD_FIELD_AUTO_CONNECT).unwrap_or("connect").to_string();
                     update_field_visibility(
                         &mut $state.definition,
                         PopupWorldType::parse(&world_type_str),
                         keep_alive_str == "custom",
+                        auto_connect_str == "script",
                     );
                 }};
             }
@@ -11848,7 +15180,11 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                     }
                 }
                 Enter => {
-                    if state.editing {
+                    if state.editing && is_multiline {
+                        // On Connect list: Enter starts the next command line
+                        state.insert_newline();
+                        state.ensure_multiline_cursor_visible();
+                    } else if state.editing {
                         // Single-line: commit and exit edit mode, staying on this field
                         state.commit_edit();
                     } else if state.is_on_button() {
@@ -11903,18 +15239,28 @@ pub(crate) fn handle_new_popup_key(app: &mut App, key: KeyEvent) -> NewPopupActi
                     state.cycle_field_buttons_rev();
                 }
                 Up => {
-                    if state.editing {
-                        state.commit_edit();
+                    if state.editing && is_multiline {
+                        state.cursor_up();
+                        state.ensure_multiline_cursor_visible();
+                    } else {
+                        if state.editing {
+                            state.commit_edit();
+                        }
+                        // Full order-of-moving cycle: fields -> buttons -> wrap.
+                        // Highlighting a field never auto-enters edit mode.
+                        state.prev_item();
                     }
-                    // Full order-of-moving cycle: fields -> buttons -> wrap.
-                    // Highlighting a field never auto-enters edit mode.
-                    state.prev_item();
                 }
                 Down => {
-                    if state.editing {
-                        state.commit_edit();
+                    if state.editing && is_multiline {
+                        state.cursor_down();
+                        state.ensure_multiline_cursor_visible();
+                    } else {
+                        if state.editing {
+                            state.commit_edit();
+                        }
+                        state.next_item();
                     }
-                    state.next_item();
                 }
                 Left => {
                     if state.editing {
@@ -12130,12 +15476,14 @@ async fn main() -> io::Result<()> {
     let mut show_version = false;
     let mut show_help = false;
     let mut conf_path: Option<String> = None;
+    let mut profile_arg: Option<String> = None;
     let mut daemon_mode = false;
     let mut multiuser_mode = false;
     let mut local_server_mode = false;
     let mut local_server_port: Option<u16> = None;
     let mut is_reload_arg = false;
     let mut is_crash_arg = false;
+    let mut force_arg = false;
     #[allow(unused_variables)]
     let mut tls_proxy_config: Option<String> = None;
     let mut console_arg: Option<Option<String>> = None;  // None=not set, Some(None)=bare, Some(Some(addr))=with addr
@@ -12151,6 +15499,9 @@ async fn main() -> io::Result<()> {
     let mut grep_archive_mode = false;
     let mut dump_mode = false;
     let mut dump_out_dir: Option<String> = None;
+    let mut uri_arg: Option<String> = None;
+    let mut attach_mode = false;
+    let mut detached_mode = false;
 
     #[allow(unused_assignments)]
     {
@@ -12179,8 +15530,11 @@ async fn main() -> io::Result<()> {
                 "-D" => daemon_mode = true,
                 "--multiuser" => multiuser_mode = true,
                 "--local-server" => local_server_mode = true,
+                "--attach" => attach_mode = true,
+                "--detached" => detached_mode = true,
                 "--reload" => is_reload_arg = true,
                 "--crash" => is_crash_arg = true,
+                "--force" => force_arg = true,
                 "--console" => console_arg = Some(None),
                 "--gui" => gui_arg = Some(None),
                 "--ssh" => ssh_mode = true,
@@ -12188,6 +15542,15 @@ async fn main() -> io::Result<()> {
                 "--dump" => dump_mode = true,
                 _ if arg.starts_with("--dump=") => { dump_mode = true; dump_out_dir = Some(arg[7..].to_string()); },
                 _ if arg.starts_with("--conf=") => conf_path = Some(arg[7..].to_string()),
+                _ if arg.starts_with("--profile=") => profile_arg = Some(arg[10..].to_string()),
+                "--profile" => {
+                    let Some(name) = args.get(i + 1) else {
+                        eprintln!("Error: --profile requires a profile name. Use -h for help.");
+                        std::process::exit(1);
+                    };
+                    profile_arg = Some(name.clone());
+                    i += 1;
+                }
                 _ if arg.starts_with("--target=") => ssh_proxy_target = Some(arg[9..].to_string()),
                 _ if arg.starts_with("--listen-port=") => {
                     match arg[14..].parse::<u16>() {
@@ -12210,6 +15573,7 @@ async fn main() -> io::Result<()> {
                 _ if arg.starts_with("--console=") => console_arg = Some(Some(arg[10..].to_string())),
                 _ if arg.starts_with("--gui=") => gui_arg = Some(Some(arg[6..].to_string())),
                 _ if arg.starts_with("--tls-proxy=") => tls_proxy_config = Some(arg[12..].to_string()),
+                _ if util::parse_mud_uri(arg).is_some() => uri_arg = Some(arg.clone()),
                 _ => {
                     eprintln!("Error: Unknown option '{}'. Use -h for help.", arg);
                     std::process::exit(1);
@@ -12223,7 +15587,7 @@ async fn main() -> io::Result<()> {
     if show_help {
         println!("Clay MUD Client v{}", VERSION);
         println!();
-        println!("Usage: clay [OPTIONS]");
+        println!("Usage: clay [OPTIONS] [telnet://host:port]");
         println!();
         println!("Options:");
         println!("    --console            Run in console (TUI) mode");
@@ -12237,6 +15601,8 @@ async fn main() -> io::Result<()> {
         println!("                         the local OS username. Tries ssh-agent, then the");
         println!("                         default ~/.ssh/id_* key files (console mode may");
         println!("                         prompt for a passphrase; GUI mode fails closed).");
+        println!("    --attach             Reattach the console to a session left running by /detach");
+        println!("                         (or to any running instance with its web server enabled)");
         println!("    -D                   Run as headless daemon server");
         println!("    --multiuser          Run as multiuser server");
         println!("    --local-server       Run headless, loopback-only, for an embedding client");
@@ -12250,19 +15616,25 @@ async fn main() -> io::Result<()> {
         println!("    --listen-port=<N>    Local port to accept on (with --ssh-proxy)");
         println!("                         Credentials via CLAY_SSH_KEY/CLAY_SSH_KEY_PASSPHRASE/");
         println!("                         CLAY_SSH_PASSWORD env vars (at least one required).");
-        println!("    --conf=<path>        Use custom config file (default: ~/.clay/settings.dat)");
+        println!("    --conf=<path>        Use custom config file (default: ~/.config/clay/settings.toml)");
+        println!("    --profile=<name>     Use a separate config profile: its own worlds, settings,");
+        println!("                         logs and reload state (~/.config/clay/profiles/<name>/)");
+        println!("    --force              Start even if another instance is using the same data");
+        println!("                         directory (~/.local/state/clay/clay.lock)");
         println!("    --grep=host[:port] <pattern>  Search world output (default port: 9000)");
         println!("      -w <world>              Limit to specific world");
         println!("      --regexp                Use regex (default: glob with * and ? wildcards)");
         println!("      --noesc                 Strip ANSI color codes from output");
         println!("      -f                      Follow mode (match new output, runs until Ctrl+C)");
         println!("      Password via CLAY_PASSWORD environment variable");
-        println!("    --grep-archive <pattern>  Search long-term archive (~/.clay/scrollback.db)");
+        println!("    --grep-archive <pattern>  Search long-term archive (~/.config/clay/scrollback.db)");
         println!("      -w <world>              Limit to specific world");
         println!("      --regexp                Use regex (default: glob with * and ? wildcards)");
         println!("      --noesc                 Strip ANSI color codes from output");
         println!("    --dump[=<dir>]       Export the offline scrollback archive to scrollback_dump.csv");
         println!("                         Default output directory: current working directory");
+        println!("    telnet://host[:port][?tls=1]  Open a MUD link (also mud://) in a world for");
+        println!("                         that address, adding one if needed");
         println!("    -v, --version        Show version and build information");
         println!("    -h, --help           Show this help message");
         println!();
@@ -12278,6 +15650,7 @@ async fn main() -> io::Result<()> {
         if cfg!(feature = "rustls-backend") { features.push("rustls"); }
         if cfg!(feature = "native-tls-backend") { features.push("native-tls"); }
         if cfg!(feature = "webview-gui") { features.push("webview-gui"); }
+        if cfg!(feature = "tray") { features.push("tray"); }
         let features_str = if features.is_empty() {
             "none".to_string()
         } else {
@@ -12288,11 +15661,38 @@ async fn main() -> io::Result<()> {
         return Ok(());
     }
 
+    // Select the config profile before any directory is used
+    if let Some(ref name) = profile_arg {
+        if !is_valid_profile_name(name) {
+            eprintln!("Error: invalid profile name '{}' (use letters, digits, '-' and '_').", name);
+            std::process::exit(1);
+        }
+        set_config_profile(name);
+    }
+
     // Set custom config path if specified
     if let Some(ref path) = conf_path {
         set_custom_config_path(PathBuf::from(path));
     }
 
+    if let Some(uri) = uri_arg {
+        let _ = STARTUP_URI.set(uri);
+    }
+
+    // --attach: a console client of the running instance (e.g. a /detach-ed session)
+    if attach_mode {
+        let Some(other) = instance_lock::running_instance() else {
+            eprintln!("Error: no running Clay session to attach to.");
+            std::process::exit(1);
+        };
+        let Some(addr) = instance_lock::attach_addr() else {
+            eprintln!("Error: Clay (pid {}, {}) is running but its web server is disabled, so it can't be attached to.",
+                other.pid, other.mode);
+            std::process::exit(1);
+        };
+        console_arg = Some(Some(addr));
+    }
+
     // Record startup time and reset debug log header flags for this session
     STARTUP_TIME.store(
         std::time::SystemTime::now()
@@ -12304,7 +15704,7 @@ async fn main() -> io::Result<()> {
     DEBUG_LOG_HEADER_WRITTEN.store(false, Ordering::Relaxed);
     OUTPUT_DEBUG_HEADER_WRITTEN.store(false, Ordering::Relaxed);
 
-    // Migrate legacy ~/.clay.* dotfiles into ~/.clay/ before any config is loaded
+    // Migrate ~/.clay/ and legacy ~/.clay.* dotfiles into the XDG directories before any config is loaded
     migrate_legacy_config_files();
 
     // Always log startup (not gated by debug flag) for reload/crash diagnostics
@@ -12414,6 +15814,10 @@ async fn main() -> io::Result<()> {
     // Handle --tls-proxy (internal, used when spawning TLS proxy processes)
     #[cfg(not(target_os = "android"))]
     if let Some(ref config_path) = tls_proxy_config {
+        // The proxy outlives the terminal of the clay that started it (hot reload,
+        // /detach); the owning instance stops it with SIGTERM when done
+        #[cfg(unix)]
+        unsafe { libc::signal(libc::SIGHUP, libc::SIG_IGN); }
         if let Ok(contents) = std::fs::read_to_string(config_path) {
             let lines: Vec<&str> = contents.lines().collect();
             if lines.len() >= 2 {
@@ -12422,8 +15826,15 @@ async fn main() -> io::Result<()> {
                     let host = host_port[0];
                     let port = host_port[1];
                     let socket_path = PathBuf::from(lines[1]);
+                    let verify = lines.get(2).and_then(|l| platform::TlsVerify::from_name(l)).unwrap_or_default();
+                    let identity = platform::ClientIdentity::load(
+                        lines.get(3).copied().unwrap_or(""),
+                        lines.get(4).copied().unwrap_or(""),
+                    );
                     let _ = std::fs::remove_file(config_path);
-                    run_tls_proxy_async(host, port, &socket_path).await;
+                    if let Ok(identity) = identity {
+                        run_tls_proxy_async(host, port, &socket_path, verify, identity).await;
+                    }
                 }
             }
         }
@@ -12531,13 +15942,44 @@ async fn main() -> io::Result<()> {
         }
     }
 
+    // Another master instance on the same data directory would race on settings.dat and
+    // the reload state; headless modes refuse to start (hot reload and crash restart take
+    // the lock over from the previous process)
+    let takeover = is_reload_arg || is_crash_arg || force_arg;
+    if (multiuser_mode || daemon_mode) && !takeover {
+        if let Some(other) = instance_lock::running_instance() {
+            eprintln!("Error: another Clay instance (pid {}, {}) is using {}.",
+                other.pid, other.mode, clay_config_dir().display());
+            if let Some(addr) = instance_lock::attach_addr() {
+                eprintln!("Attach to it with: clay --console={}", addr);
+            }
+            eprintln!("Use --force to start anyway.");
+            std::process::exit(1);
+        }
+    }
+
     // Handle --multiuser mode
     if multiuser_mode {
+        let _lock = instance_lock::InstanceLock::acquire("multiuser");
         return run_multiuser_server().await;
     }
 
+    // The master passphrase is asked for before settings load; hot reload and crash
+    // restart carry its key over in the reload state
+    let ask_master_passphrase = !is_reload_arg && !is_crash_arg;
+
+    // Handle --detached (a console session handed off by /detach)
+    if detached_mode {
+        let _lock = instance_lock::InstanceLock::acquire("detached");
+        return daemon::run_detached_session().await;
+    }
+
     // Handle -D (daemon mode)
     if daemon_mode {
+        if ask_master_passphrase {
+            persistence::unlock_at_startup();
+        }
+        let _lock = instance_lock::InstanceLock::acquire("daemon");
         return run_daemon_server().await;
     }
 
@@ -12594,6 +16036,25 @@ async fn main() -> io::Result<()> {
         unsafe { FreeConsole(); }
     }
 
+    // A master GUI or console finding another instance offers to attach to it instead
+    let mut remote_addr = remote_addr;
+    if remote_addr.is_none() && !takeover {
+        if let Some(other) = instance_lock::running_instance() {
+            let addr = instance_lock::attach_addr();
+            match (use_gui, addr) {
+                // No terminal to ask on (the console is detached on Windows); just attach
+                (true, Some(addr)) => remote_addr = Some(addr),
+                (true, None) => {}
+                (false, addr) => match instance_lock::prompt(&other, addr.as_deref()) {
+                    instance_lock::Choice::Attach(addr) => remote_addr = Some(addr),
+                    instance_lock::Choice::StartAnyway => {}
+                    instance_lock::Choice::Quit => return Ok(()),
+                },
+            }
+        }
+    }
+    let _lock = remote_addr.is_none().then(|| instance_lock::InstanceLock::acquire(if use_gui { "gui" } else { "console" }));
+
     // Dispatch based on (interface, connection_mode)
     match (use_gui, remote_addr) {
         // Remote GUI: connect to a running Clay instance via WebSocket
@@ -12613,6 +16074,9 @@ async fn main() -> io::Result<()> {
         (true, None) => {
             #[cfg(feature = "webview-gui")]
             {
+                if ask_master_passphrase {
+                    persistence::unlock_at_startup();
+                }
                 return webview_gui::run_master_webgui();
             }
             #[cfg(not(feature = "webview-gui"))]
@@ -12625,7 +16089,11 @@ async fn main() -> io::Result<()> {
             return remote_client::run_console_client(addr, ssh_target).await;
         }
         // Master console: default TUI mode (falls through to existing code below)
-        (false, None) => {}
+        (false, None) => {
+            if ask_master_passphrase {
+                persistence::unlock_at_startup();
+            }
+        }
     }
 
     // Set up signal handlers for crash debugging (not available on Android or Windows)
@@ -12702,6 +16170,7 @@ async fn main() -> io::Result<()> {
         stdout,
         EnterAlternateScreen,
         crossterm::event::EnableBracketedPaste,
+        crossterm::event::EnableFocusChange,
         Clear(ClearType::All),
         cursor::MoveTo(0, 0)
     )?;
@@ -12732,13 +16201,16 @@ async fn main() -> io::Result<()> {
     let result = run_app(&mut terminal).await;
 
     disable_raw_mode()?;
-    let _ = execute!(terminal.backend_mut(), DisableMouseCapture);
+    let _ = execute!(terminal.backend_mut(), DisableMouseCapture, crossterm::event::DisableFocusChange);
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
     if let Err(err) = result {
         eprintln!("Error: {err}");
     }
+    if SESSION_DETACHED.load(Ordering::Relaxed) {
+        println!("Detached. Your worlds stay connected; run 'clay --attach' to get back.");
+    }
 
     Ok(())
 }
@@ -12758,8 +16230,9 @@ pub async fn restart_http_server(app: &mut App, event_tx: mpsc::Sender<AppEvent>
         SecurityGate {
             allow_list: server.allow_list.clone(),
             whitelisted_host: server.whitelisted_host.clone(),
-            auth_key: app.ws_auth_key_shared.clone(),
+            auth_keys: app.ws_auth_keys_shared.clone(),
             web_path: app.settings.web_path.clone(),
+            trusted_proxies: app.web_trusted_proxies(),
             ban_list: app.ban_list.clone(),
         }
     } else {
@@ -12768,8 +16241,9 @@ pub async fn restart_http_server(app: &mut App, event_tx: mpsc::Sender<AppEvent>
                 websocket::parse_allow_list_csv(&app.settings.websocket_allow_list)
             )),
             whitelisted_host: Arc::new(std::sync::RwLock::new(app.settings.websocket_whitelisted_host.clone())),
-            auth_key: app.ws_auth_key_shared.clone(),
+            auth_keys: app.ws_auth_keys_shared.clone(),
             web_path: app.settings.web_path.clone(),
+            trusted_proxies: app.web_trusted_proxies(),
             ban_list: app.ban_list.clone(),
         }
     };
@@ -12799,7 +16273,7 @@ pub async fn restart_http_server(app: &mut App, event_tx: mpsc::Sender<AppEvent>
     {
         match resolve_web_cert_files(app) {
             Some((cert_file, key_file)) => {
-                let mut https_server = HttpsServer::new(http_port);
+                let mut https_server = HttpsServer::new(http_port, app.web_listen());
                 match start_https_server(&mut https_server, &cert_file, &key_file, ws_state, ban_list, theme_css, gate.clone()).await {
                     Ok(()) => {
                         app.add_output(&format!("HTTPS web interface restarted on port {http_port}"));
@@ -12825,6 +16299,7 @@ pub async fn run_app_headless(
     ws_override: Option<String>,  // password for auto-started WS (GUI master mode)
     gui_repaint: Option<std::sync::Arc<dyn Fn() + Send + Sync>>,
     port_override: Option<u16>,   // force http_port (e.g. --local-server on Android)
+    local_link: Option<tokio::sync::oneshot::Sender<websocket::LocalLink>>,  // GUI local mode: sessions without the web server
 ) -> io::Result<()> {
     let mut app = App::new();
     app.gui_tx = Some(gui_tx.clone());
@@ -12892,10 +16367,10 @@ pub async fn run_app_headless(
     // Pre-compile action regexes after loading settings or reload state
     compile_all_action_regexes(&mut app.settings.actions);
 
-    // Load theme file (~/.clay/theme.dat)
+    // Load theme file (~/.config/clay/theme.dat)
     load_theme_file(&mut app);
 
-    // Load keyboard bindings (~/.clay/keybindings.dat)
+    // Load keyboard bindings (~/.config/clay/keybindings.dat)
     {
         let key_path = clay_config_path("keybindings.dat");
         app.keybindings = keybindings::KeyBindings::load(&key_path);
@@ -12945,7 +16420,7 @@ pub async fn run_app_headless(
             app.worlds[world_idx].socket_fd = None;
             let seq = app.worlds[world_idx].next_seq;
             app.worlds[world_idx].next_seq += 1;
-            app.worlds[world_idx].output_lines.push(OutputLine::new_client(tls_msg.to_string(), seq));
+            app.worlds[world_idx].output_lines.push_back(OutputLine::new_client(tls_msg.to_string(), seq));
             if world_idx != app.current_world_index {
                 if app.worlds[world_idx].unseen_lines == 0 {
                     app.worlds[world_idx].first_unseen_at = Some(std::time::Instant::now());
@@ -12971,6 +16446,7 @@ pub async fn run_app_headless(
                 let mut write_half = StreamWriter::Plain(w);
                 let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                 app.worlds[world_idx].command_tx = Some(cmd_tx.clone());
+                let send_encoding = app.worlds[world_idx].writer_encoding();
                 app.worlds[world_idx].skip_auto_login = true;
                 app.worlds[world_idx].open_log_file();
                 let _telnet_tx = cmd_tx;
@@ -13007,8 +16483,8 @@ pub async fn run_app_headless(
                     while let Some(cmd) = cmd_rx.recv().await {
                         match cmd {
                             WriteCommand::Text(text) => {
-                                let data = format!("{}\r\n", text);
-                                if write_half.write_all(data.as_bytes()).await.is_err() {
+                                let data = send_encoding.get().line_bytes(&text);
+                                if write_half.write_all(&data).await.is_err() {
                                     break;
                                 }
                             }
@@ -13037,6 +16513,7 @@ pub async fn run_app_headless(
 
                                 let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                                 app.worlds[world_idx].command_tx = Some(cmd_tx.clone());
+                                let send_encoding = app.worlds[world_idx].writer_encoding();
                                 app.worlds[world_idx].skip_auto_login = true;
                                 app.worlds[world_idx].open_log_file();
                                 let world_name = app.worlds[world_idx].name.clone();
@@ -13070,8 +16547,8 @@ pub async fn run_app_headless(
                                     while let Some(cmd) = cmd_rx.recv().await {
                                         match cmd {
                                             WriteCommand::Text(text) => {
-                                                let data = format!("{}\r\n", text);
-                                                if write_half.write_all(data.as_bytes()).await.is_err() {
+                                                let data = send_encoding.get().line_bytes(&text);
+                                                if write_half.write_all(&data).await.is_err() {
                                                     break;
                                                 }
                                             }
@@ -13122,6 +16599,7 @@ pub async fn run_app_headless(
                                 let mut write_half = StreamWriter::NamedPipeProxy(w);
                                 let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                                 app.worlds[world_idx].command_tx = Some(cmd_tx.clone());
+                                let send_encoding = app.worlds[world_idx].writer_encoding();
                                 app.worlds[world_idx].skip_auto_login = true;
                                 app.worlds[world_idx].open_log_file();
                                 let world_name = app.worlds[world_idx].name.clone();
@@ -13154,8 +16632,8 @@ pub async fn run_app_headless(
                                     while let Some(cmd) = cmd_rx.recv().await {
                                         match cmd {
                                             WriteCommand::Text(text) => {
-                                                let data = format!("{}\r\n", text);
-                                                if tokio::io::AsyncWriteExt::write_all(&mut write_half, data.as_bytes()).await.is_err() { break; }
+                                                let data = send_encoding.get().line_bytes(&text);
+                                                if tokio::io::AsyncWriteExt::write_all(&mut write_half, &data).await.is_err() { break; }
                                             }
                                             WriteCommand::Raw(data) => {
                                                 if tokio::io::AsyncWriteExt::write_all(&mut write_half, &data).await.is_err() { break; }
@@ -13193,7 +16671,7 @@ pub async fn run_app_headless(
                 world.paused = false;
                 let seq = world.next_seq;
                 world.next_seq += 1;
-                world.output_lines.push(OutputLine::new(
+                world.output_lines.push_back(OutputLine::new(
                     "Connection was not restored during reload. Use /worlds to reconnect.".to_string(), seq
                 ));
             }
@@ -13222,8 +16700,11 @@ pub async fn run_app_headless(
     }
 
     // Create WebSocket server state (for client management, no standalone listener).
-    // Needed when a password is set OR when an auth key is set (auth-key-only mode).
-    let ws_state = if !app.settings.websocket_password.is_empty() || app.settings.websocket_auth_key.is_some() {
+    // Needed when a password is set OR when an auth key is set (auth-key-only mode),
+    // and in GUI local mode, whose sessions are clients of it with no listener at all.
+    let ws_state = if !app.settings.websocket_password.is_empty() || app.settings.websocket_auth_key.is_some()
+        || local_link.is_some()
+    {
         let server = WebSocketServer::new(
             &app.settings.websocket_password,
             app.settings.http_port,
@@ -13232,6 +16713,7 @@ pub async fn run_app_headless(
             false,
             app.ban_list.clone(),
         );
+        server.update_viewer_password(&app.settings.websocket_viewer_password);
         let state = Arc::new(server.connection_state(event_tx.clone()));
         app.ws_server = Some(server);
         Some(state)
@@ -13242,8 +16724,9 @@ pub async fn run_app_headless(
         SecurityGate {
             allow_list: server.allow_list.clone(),
             whitelisted_host: server.whitelisted_host.clone(),
-            auth_key: app.ws_auth_key_shared.clone(),
+            auth_keys: app.ws_auth_keys_shared.clone(),
             web_path: app.settings.web_path.clone(),
+            trusted_proxies: app.web_trusted_proxies(),
             ban_list: app.ban_list.clone(),
         }
     } else {
@@ -13252,8 +16735,9 @@ pub async fn run_app_headless(
                 websocket::parse_allow_list_csv(&app.settings.websocket_allow_list)
             )),
             whitelisted_host: Arc::new(std::sync::RwLock::new(app.settings.websocket_whitelisted_host.clone())),
-            auth_key: app.ws_auth_key_shared.clone(),
+            auth_keys: app.ws_auth_keys_shared.clone(),
             web_path: app.settings.web_path.clone(),
+            trusted_proxies: app.web_trusted_proxies(),
             ban_list: app.ban_list.clone(),
         }
     };
@@ -13268,7 +16752,7 @@ pub async fn run_app_headless(
         if let Some((cert_file, key_file)) = resolve_web_cert_files(&mut app) {
             #[cfg(any(feature = "native-tls-backend", feature = "rustls-backend"))]
             {
-                let mut https_server = HttpsServer::new(app.settings.http_port);
+                let mut https_server = HttpsServer::new(app.settings.http_port, app.web_listen());
                 match start_https_server(
                     &mut https_server,
                     &cert_file,
@@ -13294,6 +16778,11 @@ pub async fn run_app_headless(
         }
     }
 
+    // GUI local mode: the GUI opens its sessions through this link
+    if let (Some(link_tx), Some(server)) = (local_link, app.ws_server.as_ref()) {
+        let _ = link_tx.send(server.local_link(event_tx.clone()));
+    }
+
     // Re-set gui_tx and repaint callback after potential reload state load (reload clears them)
     app.gui_tx = Some(gui_tx);
     app.gui_repaint = gui_repaint_clone;
@@ -13303,7 +16792,6 @@ pub async fn run_app_headless(
     app.ws_broadcast(initial_state);
 
     // Keepalive interval
-    const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
     let mut keepalive_interval = tokio::time::interval(Duration::from_secs(60));
     keepalive_interval.tick().await;
 
@@ -13322,6 +16810,10 @@ pub async fn run_app_headless(
     let pending_update_sleep = tokio::time::sleep(FAR_FUTURE);
     tokio::pin!(pending_update_sleep);
 
+    // Background world batch release — only active while low-priority worlds hold output
+    let background_batch_sleep = tokio::time::sleep(FAR_FUTURE);
+    tokio::pin!(background_batch_sleep);
+
     // Auto-reconnect timer — fires when a world is due for reconnection
     let reconnect_sleep = tokio::time::sleep(FAR_FUTURE);
     tokio::pin!(reconnect_sleep);
@@ -13374,6 +16866,14 @@ pub async fn run_app_headless(
         }
     }
 
+    // Open a command-line MUD link the way the GUI would send it (not after a reload,
+    // which has its worlds back already)
+    if let Some(uri) = STARTUP_URI.get().filter(|_| !should_load_state) {
+        let command = format!("/connect {}", uri);
+        let world_index = app.current_world_index;
+        daemon::handle_daemon_ws_message(&mut app, 0, WsMessage::SendCommand { world_index, command }, &event_tx).await;
+    }
+
     debug_log(is_debug_enabled(), "HEADLESS: Entering main event loop");
 
     // Main event loop
@@ -13381,10 +16881,28 @@ pub async fn run_app_headless(
         #[cfg(all(unix, not(target_os = "android")))]
         reap_zombie_children();
 
+        // Hold each world to the Scrollback Lines cap
+        app.trim_scrollback();
+
         tokio::select! {
             // App events (server data, disconnects, WS client messages)
             Some(event) = event_rx.recv() => {
                 match event {
+                    AppEvent::ReplayData(ref world_name, text) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.replay_output(world_idx, &text, 24, 1000, true);
+                        }
+                    }
+                    AppEvent::SendFileLine(ref world_name, line) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.send_file_line(world_idx, line);
+                        }
+                    }
+                    AppEvent::ShellOutput(ref world_name, output, line) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.shell_output(world_idx, output, line);
+                        }
+                    }
                     AppEvent::ServerData(ref world_name, ref bytes) => {
                         if let Some(world_idx) = app.find_world_index(world_name) {
                             // Use large width in daemon mode so text is not pre-wrapped —
@@ -13392,6 +16910,9 @@ pub async fn run_app_headless(
                             let commands = app.process_server_data(
                                 world_idx, bytes, 24, 1000, true,
                             );
+                            if let Some(at) = app.next_background_release() {
+                                background_batch_sleep.as_mut().reset(tokio::time::Instant::from_std(at));
+                            }
                             // Activate prompt check if server data set wont_echo_time
                             if app.worlds[world_idx].wont_echo_time.is_some() {
                                 prompt_check_sleep.as_mut().reset(tokio::time::Instant::now() + Duration::from_millis(150));
@@ -13431,15 +16952,7 @@ pub async fn run_app_headless(
                                                     }
                                                 }
                                                 Command::Notify { message } => {
-                                                    let title = if world_idx < app.worlds.len() {
-                                                        app.worlds[world_idx].name.clone()
-                                                    } else {
-                                                        "Clay".to_string()
-                                                    };
-                                                    app.ws_broadcast(WsMessage::Notification {
-                                                        title,
-                                                        message: message.clone(),
-                                                    });
+                                                    commands::notify_command(&mut app, world_idx, &message);
                                                 }
                                                 Command::Say { text } => {
                                                     tts::speak(&app.tts_backend, &text, app.settings.tts_mode);
@@ -13475,8 +16988,8 @@ pub async fn run_app_headless(
                         }
                     }
                     AppEvent::WsClientMessage(client_id, msg) => {
-                        if let WsMessage::AuthRequest { current_world, .. } = &*msg {
-                            app.handle_ws_auth_initial_state(client_id, *current_world);
+                        if let WsMessage::AuthRequest { current_world, resume_seqs, .. } = &*msg {
+                            app.handle_ws_auth_initial_state(client_id, *current_world, resume_seqs);
                             if app.web_reconnect_needed {
                                 app.web_reconnect_needed = false;
                                 if app.trigger_web_reconnects() {
@@ -13521,7 +17034,7 @@ pub async fn run_app_headless(
                         let seq = app.current_world().next_seq;
                         let world_idx = app.current_world_index;
                         app.worlds[world_idx].next_seq += 1;
-                        app.worlds[world_idx].output_lines.push(OutputLine::new_client(msg.clone(), seq));
+                        app.worlds[world_idx].output_lines.push_back(OutputLine::new_client(msg.clone(), seq));
                         app.ws_broadcast(WsMessage::ServerData {
                             world_index: world_idx,
                             data: format!("{}\n", msg),
@@ -13553,6 +17066,7 @@ pub async fn run_app_headless(
                         app.handle_connection_success(&world_name, cmd_tx, socket_fd, is_tls);
                     }
                     AppEvent::ConnectionFailed(world_name, error) => {
+                        app.note_bulk_connect(&world_name, false);
                         if let Some(world_idx) = app.find_world_index(&world_name) {
                             app.add_output_to_world(world_idx, &format!("Connection failed: {}", error));
                             // A pin mismatch needs the user to explicitly trust the new
@@ -13580,6 +17094,11 @@ pub async fn run_app_headless(
                             app.handle_wont_echo_seen(world_idx);
                         }
                     }
+                    AppEvent::ServerEcho(ref world_name, on) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.set_server_echo(world_idx, on);
+                        }
+                    }
                     AppEvent::NawsRequested(ref world_name) => {
                         if let Some(world_idx) = app.find_world_index(world_name) {
                             app.handle_naws_requested(world_idx);
@@ -13622,16 +17141,7 @@ pub async fn run_app_headless(
                     AppEvent::ApiLookupResult(client_id, world_index, result, cursor_start) => {
                         match result {
                             Ok(text) => app.ws_send_to_client(client_id, WsMessage::SetInputBuffer { text, cursor_start }),
-                            Err(e) => app.ws_send_to_client(client_id, WsMessage::ServerData {
-                                world_index,
-                                data: e,
-                                is_viewed: false,
-                                ts: current_timestamp_secs(),
-                                from_server: false,
-                                seq: 0,
-                                marked_new: false,
-                                flush: false, gagged: false,
-                            }),
+                            Err(e) => app.send_command_output(client_id, world_index, e),
                         }
                     }
                     AppEvent::RemoteListResult(requesting_client_id, world_index, lines) => {
@@ -13642,16 +17152,7 @@ pub async fn run_app_headless(
                             }
                         } else {
                             for line in &lines {
-                                app.ws_send_to_client(requesting_client_id, WsMessage::ServerData {
-                                    world_index,
-                                    data: line.clone(),
-                                    is_viewed: false,
-                                    ts: current_timestamp_secs(),
-                                    from_server: false,
-                                    seq: 0,
-                                    marked_new: false,
-                                    flush: false, gagged: false,
-                                });
+                                app.send_command_output(requesting_client_id, world_index, line.clone());
                             }
                         }
                     }
@@ -13750,6 +17251,12 @@ pub async fn run_app_headless(
                     AppEvent::ImportResult(client_id, addr, result) => {
                         app.handle_import_result(client_id, addr, result);
                     }
+                    AppEvent::AcmeResult(domain, result) if app.handle_acme_result(&domain, &result) => {
+                        restart_http_server(&mut app, event_tx.clone()).await;
+                    }
+                    AppEvent::PushGone(endpoints) => {
+                        app.remove_push_subscriptions(&endpoints);
+                    }
                     AppEvent::Prompt(ref world_name, ref prompt_bytes) => {
                         if let Some(world_idx) = app.find_world_index(world_name) {
                             app.handle_prompt(world_idx, prompt_bytes);
@@ -13767,11 +17274,14 @@ pub async fn run_app_headless(
 
             // Keepalive timer
             _ = keepalive_interval.tick() => {
+                app.refresh_active_profile();
+                app.check_silence_watchdogs();
+                app.start_acme_if_due(false);
                 for world in &mut app.worlds {
                     if world.connected {
                         // Only check last_send_time: server kicks us when WE go idle.
                         let should_send = match world.last_send_time {
-                            Some(t) => t.elapsed() >= KEEPALIVE_INTERVAL,
+                            Some(t) => t.elapsed() >= world.settings.keep_alive_interval(),
                             None => true,
                         };
                         if should_send {
@@ -13825,7 +17335,7 @@ pub async fn run_app_headless(
                                 world.clear_connection_state(false, false);
                                 let seq = world.next_seq;
                                 world.next_seq += 1;
-                                world.output_lines.push(OutputLine::new("TLS proxy terminated. Connection lost.".to_string(), seq));
+                                world.output_lines.push_back(OutputLine::new("TLS proxy terminated. Connection lost.".to_string(), seq));
                             }
                         }
                     }
@@ -13846,7 +17356,7 @@ pub async fn run_app_headless(
                                 if !world.connected {
                                     let seq = world.next_seq;
                                     world.next_seq += 1;
-                                    world.output_lines.push(OutputLine::new(normalized.trim().to_string(), seq));
+                                    world.output_lines.push_back(OutputLine::new(normalized.trim().to_string(), seq));
                                     world.wont_echo_time = None;
                                     continue;
                                 }
@@ -13857,8 +17367,8 @@ pub async fn run_app_headless(
                                 // Handle auto-login
                                 if !world.skip_auto_login {
                                     let auto_type = world.settings.auto_connect_type;
-                                    let user = world.settings.user.clone();
-                                    let password = world.settings.password.clone();
+                                    let user = secrets::expand(&world.settings.user, &world.settings.secrets, &app.settings.secrets);
+                                    let password = secrets::expand(&world.settings.password, &world.settings.secrets, &app.settings.secrets);
                                     let prompt_num = world.prompt_count;
 
                                     if !user.is_empty() && !password.is_empty() {
@@ -13878,7 +17388,7 @@ pub async fn run_app_headless(
                                                     _ => None,
                                                 }
                                             }
-                                            AutoConnectType::Connect | AutoConnectType::NoLogin => None,
+                                            _ => None,
                                         };
 
                                         if let Some(cmd) = cmd_to_send {
@@ -13886,6 +17396,9 @@ pub async fn run_app_headless(
                                             if let Some(tx) = &world.command_tx {
                                                 let _ = tx.try_send(WriteCommand::Text(cmd));
                                             }
+                                            if prompt_num == auto_type.login_prompts() {
+                                                world.send_connect_commands();
+                                            }
                                         }
                                     }
                                 }
@@ -13899,10 +17412,13 @@ pub async fn run_app_headless(
                 for world in &mut app.worlds {
                     if let Some(deadline) = world.fansi_detect_until {
                         if now_fansi >= deadline {
-                            if let Some(login_cmd) = world.fansi_login_pending.take() {
+                            if let Some(login) = world.fansi_login_pending.take() {
                                 if let Some(tx) = &world.command_tx {
-                                    let _ = tx.try_send(WriteCommand::Text(login_cmd));
+                                    for line in login {
+                                        let _ = tx.try_send(WriteCommand::Text(line));
+                                    }
                                 }
+                                world.send_connect_commands();
                             }
                             world.fansi_detect_until = None;
                         }
@@ -13951,7 +17467,7 @@ pub async fn run_app_headless(
                             tf::TfCommandResult::Success(Some(msg)) => {
                                 let seq = app.worlds[world_idx].next_seq;
                                 app.worlds[world_idx].next_seq += 1;
-                                app.worlds[world_idx].output_lines.push(OutputLine::new_client(msg.clone(), seq));
+                                app.worlds[world_idx].output_lines.push_back(OutputLine::new_client(msg.clone(), seq));
                                 app.ws_broadcast(WsMessage::ServerData {
                                     world_index: world_idx,
                                     data: format!("{}\n", msg),
@@ -13967,7 +17483,7 @@ pub async fn run_app_headless(
                                 let seq = app.worlds[world_idx].next_seq;
                                 app.worlds[world_idx].next_seq += 1;
                                 let err_msg = format!("Error: {}", err);
-                                app.worlds[world_idx].output_lines.push(OutputLine::new_client(err_msg.clone(), seq));
+                                app.worlds[world_idx].output_lines.push_back(OutputLine::new_client(err_msg.clone(), seq));
                                 app.ws_broadcast(WsMessage::ServerData {
                                     world_index: world_idx,
                                     data: format!("{}\n", err_msg),
@@ -14033,6 +17549,16 @@ pub async fn run_app_headless(
             }
 
             // Pending count update — only fires when worlds have pending lines
+            // Release held output of low-priority background worlds (see throttle.rs)
+            _ = &mut background_batch_sleep => {
+                let mut retry = false;
+                for name in app.due_background_batches(std::time::Instant::now()) {
+                    retry |= event_tx.try_send(AppEvent::ServerData(name, Vec::new())).is_err();
+                }
+                let next = if retry { Duration::from_millis(100) } else { FAR_FUTURE };
+                background_batch_sleep.as_mut().reset(tokio::time::Instant::now() + next);
+            }
+
             _ = &mut pending_update_sleep => {
                 let now = std::time::Instant::now();
                 for world in app.worlds.iter_mut() {
@@ -14083,7 +17609,7 @@ pub async fn run_app_headless(
                     if let Some(idx) = app.find_world_index(&world_name) {
                         app.worlds[idx].reconnect_at = None;
                         if !app.worlds[idx].connected && app.worlds[idx].settings.has_connection_settings() {
-                            let settings = app.worlds[idx].settings.clone();
+                            let settings = app.login_settings(idx);
                             app.worlds[idx].connection_id += 1;
                             let connection_id = app.worlds[idx].connection_id;
                             let ssl_msg = if settings.use_ssl { " with SSL" } else { "" };
@@ -14092,7 +17618,7 @@ pub async fn run_app_headless(
                             // send auto-login; handle_connection_success handles that instead.
                             match daemon::connect_daemon_world(
                                 idx, world_name.clone(), &settings, event_tx.clone(), connection_id, true,
-                                app.settings.tls_proxy_enabled,
+                                app.settings.tls_proxy_enabled, app.worlds[idx].writer_encoding(),
                             ).await {
                                 Some((cmd_tx, socket_fd, is_tls, proxy_pid, proxy_socket_path)) => {
                                     app.handle_connection_success(&world_name, cmd_tx, socket_fd, is_tls);
@@ -14140,6 +17666,9 @@ pub async fn run_app_headless(
 
 async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
     let mut app = App::new();
+    app.console_active = true;
+    app.color_depth = color_depth::ColorDepth::detect();
+    app.undercurl = color_depth::undercurl_supported();
 
     // Check if we're in reload mode (via --reload command line argument)
     let is_reload = std::env::args().any(|a| a == "--reload");
@@ -14206,10 +17735,10 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
     // Pre-compile action regexes after loading settings or reload state
     compile_all_action_regexes(&mut app.settings.actions);
 
-    // Load theme file (~/.clay/theme.dat)
+    // Load theme file (~/.config/clay/theme.dat)
     load_theme_file(&mut app);
 
-    // Load keyboard bindings (~/.clay/keybindings.dat)
+    // Load keyboard bindings (~/.config/clay/keybindings.dat)
     {
         let key_path = clay_config_path("keybindings.dat");
         app.keybindings = keybindings::KeyBindings::load(&key_path);
@@ -14267,7 +17796,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
             app.worlds[world_idx].socket_fd = None;
             let seq = app.worlds[world_idx].next_seq;
             app.worlds[world_idx].next_seq += 1;
-            app.worlds[world_idx].output_lines.push(OutputLine::new_client(tls_msg.to_string(), seq));
+            app.worlds[world_idx].output_lines.push_back(OutputLine::new_client(tls_msg.to_string(), seq));
             // If not the current world, set unseen_lines for activity indicator
             if world_idx != app.current_world_index {
                 if app.worlds[world_idx].unseen_lines == 0 {
@@ -14297,6 +17826,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
 
                 let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                 app.worlds[world_idx].command_tx = Some(cmd_tx.clone());
+                let send_encoding = app.worlds[world_idx].writer_encoding();
                 // Skip auto-login for restored connections (only fresh connects should auto-login)
                 app.worlds[world_idx].skip_auto_login = true;
 
@@ -14332,6 +17862,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                     if result.telnet_detected {
                                         let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name.clone())).await;
                                     }
+                                    if let Some(on) = result.server_echo {
+                                        let _ = event_tx_read.send(AppEvent::ServerEcho(world_name.clone(), on)).await;
+                                    }
                                     if result.gmcp_negotiated {
                                         let _ = event_tx_read.send(AppEvent::GmcpNegotiated(world_name.clone())).await;
                                     }
@@ -14418,6 +17951,11 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                             .send(AppEvent::TelnetDetected(world_name.clone()))
                                             .await;
                                     }
+                                    if let Some(on) = result.server_echo {
+                                        let _ = event_tx_read
+                                            .send(AppEvent::ServerEcho(world_name.clone(), on))
+                                            .await;
+                                    }
 
                                     // Notify if NAWS was requested (server sent DO NAWS)
                                     if result.naws_requested {
@@ -14487,7 +18025,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     while let Some(cmd) = cmd_rx.recv().await {
                         match cmd {
                             WriteCommand::Text(text) => {
-                                let bytes = format!("{}\r\n", text).into_bytes();
+                                let bytes = send_encoding.get().line_bytes(&text);
                                 if write_half.write_all(&bytes).await.is_err() {
                                     break;
                                 }
@@ -14526,7 +18064,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     app.worlds[world_idx].clear_connection_state(false, false);
                     let seq = app.worlds[world_idx].next_seq;
                     app.worlds[world_idx].next_seq += 1;
-                    app.worlds[world_idx].output_lines.push(OutputLine::new(
+                    app.worlds[world_idx].output_lines.push_back(OutputLine::new(
                         "TLS proxy terminated during reload. Use /worlds to reconnect.".to_string(), seq
                     ));
                     continue;
@@ -14574,6 +18112,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
 
                         let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                         app.worlds[world_idx].command_tx = Some(cmd_tx.clone());
+                        let send_encoding = app.worlds[world_idx].writer_encoding();
                         app.worlds[world_idx].skip_auto_login = true;
 
                         // Re-open log file if enabled
@@ -14602,6 +18141,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                             if result.telnet_detected {
                                                 let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name.clone())).await;
                                             }
+                                            if let Some(on) = result.server_echo {
+                                                let _ = event_tx_read.send(AppEvent::ServerEcho(world_name.clone(), on)).await;
+                                            }
                                             if result.gmcp_negotiated {
                                                 let _ = event_tx_read.send(AppEvent::GmcpNegotiated(world_name.clone())).await;
                                             }
@@ -14657,6 +18199,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                             if result.telnet_detected {
                                                 let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name.clone())).await;
                                             }
+                                            if let Some(on) = result.server_echo {
+                                                let _ = event_tx_read.send(AppEvent::ServerEcho(world_name.clone(), on)).await;
+                                            }
                                             if result.naws_requested {
                                                 let _ = event_tx_read.send(AppEvent::NawsRequested(world_name.clone())).await;
                                             }
@@ -14698,11 +18243,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                         tokio::spawn(async move {
                             while let Some(cmd) = cmd_rx.recv().await {
                                 let bytes = match &cmd {
-                                    WriteCommand::Text(text) => {
-                                        let mut b = text.as_bytes().to_vec();
-                                        b.extend_from_slice(b"\r\n");
-                                        b
-                                    }
+                                    WriteCommand::Text(text) => send_encoding.get().line_bytes(text),
                                     WriteCommand::Raw(raw) => raw.clone(),
                                     WriteCommand::Shutdown => break,
                                 };
@@ -14717,7 +18258,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                         app.worlds[world_idx].clear_connection_state(false, false);
                         let seq = app.worlds[world_idx].next_seq;
                         app.worlds[world_idx].next_seq += 1;
-                        app.worlds[world_idx].output_lines.push(OutputLine::new(
+                        app.worlds[world_idx].output_lines.push_back(OutputLine::new(
                             "Failed to reconnect to TLS proxy. Use /worlds to reconnect.".to_string(), seq
                         ));
                     }
@@ -14762,6 +18303,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
 
                         let (cmd_tx, mut cmd_rx) = mpsc::channel::<WriteCommand>(100);
                         app.worlds[world_idx].command_tx = Some(cmd_tx.clone());
+                        let send_encoding = app.worlds[world_idx].writer_encoding();
                         app.worlds[world_idx].skip_auto_login = true;
                         app.worlds[world_idx].open_log_file();
 
@@ -14787,6 +18329,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                             if result.telnet_detected {
                                                 let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name.clone())).await;
                                             }
+                                            if let Some(on) = result.server_echo {
+                                                let _ = event_tx_read.send(AppEvent::ServerEcho(world_name.clone(), on)).await;
+                                            }
                                             if result.gmcp_negotiated {
                                                 let _ = event_tx_read.send(AppEvent::GmcpNegotiated(world_name.clone())).await;
                                             }
@@ -14842,6 +18387,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                             if result.telnet_detected {
                                                 let _ = event_tx_read.send(AppEvent::TelnetDetected(world_name.clone())).await;
                                             }
+                                            if let Some(on) = result.server_echo {
+                                                let _ = event_tx_read.send(AppEvent::ServerEcho(world_name.clone(), on)).await;
+                                            }
                                             if result.naws_requested {
                                                 let _ = event_tx_read.send(AppEvent::NawsRequested(world_name.clone())).await;
                                             }
@@ -14882,11 +18430,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                         tokio::spawn(async move {
                             while let Some(cmd) = cmd_rx.recv().await {
                                 let bytes = match &cmd {
-                                    WriteCommand::Text(text) => {
-                                        let mut b = text.as_bytes().to_vec();
-                                        b.extend_from_slice(b"\r\n");
-                                        b
-                                    }
+                                    WriteCommand::Text(text) => send_encoding.get().line_bytes(text),
                                     WriteCommand::Raw(raw) => raw.clone(),
                                     WriteCommand::Shutdown => break,
                                 };
@@ -14917,13 +18461,13 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 let seq = world.next_seq;
                 world.next_seq += 1;
                 world.output_lines
-                    .push(OutputLine::new("Connection was not restored during reload. Use /worlds to reconnect.".to_string(), seq));
+                    .push_back(OutputLine::new("Connection was not restored during reload. Use /worlds to reconnect.".to_string(), seq));
             }
 
             // For ALL worlds: flush pending_lines to output_lines so content isn't lost,
             // then clear more-mode state. This prevents stale activity indicators after reload.
             if !world.pending_lines.is_empty() {
-                world.output_lines.append(&mut world.pending_lines);
+                world.output_lines.extend(world.pending_lines.drain(..));
                 // Update scroll_offset to include the newly appended lines
                 world.scroll_offset = world.output_lines.len().saturating_sub(1);
             } else if world.scroll_offset >= world.output_lines.len() {
@@ -15015,6 +18559,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
             app.multiuser_mode,
             app.ban_list.clone(),
         );
+        server.update_viewer_password(&app.settings.websocket_viewer_password);
         let state = Arc::new(server.connection_state(event_tx.clone()));
         app.ws_server = Some(server);
         Some(state)
@@ -15025,8 +18570,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
         SecurityGate {
             allow_list: server.allow_list.clone(),
             whitelisted_host: server.whitelisted_host.clone(),
-            auth_key: app.ws_auth_key_shared.clone(),
+            auth_keys: app.ws_auth_keys_shared.clone(),
             web_path: app.settings.web_path.clone(),
+            trusted_proxies: app.web_trusted_proxies(),
             ban_list: app.ban_list.clone(),
         }
     } else {
@@ -15035,8 +18581,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 websocket::parse_allow_list_csv(&app.settings.websocket_allow_list)
             )),
             whitelisted_host: Arc::new(std::sync::RwLock::new(app.settings.websocket_whitelisted_host.clone())),
-            auth_key: app.ws_auth_key_shared.clone(),
+            auth_keys: app.ws_auth_keys_shared.clone(),
             web_path: app.settings.web_path.clone(),
+            trusted_proxies: app.web_trusted_proxies(),
             ban_list: app.ban_list.clone(),
         }
     };
@@ -15050,7 +18597,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
         if let Some((cert_file, key_file)) = resolve_web_cert_files(&mut app) {
             #[cfg(feature = "native-tls-backend")]
             {
-                let mut https_server = HttpsServer::new(app.settings.http_port);
+                let mut https_server = HttpsServer::new(app.settings.http_port, app.web_listen());
                 match start_https_server(
                     &mut https_server,
                     &cert_file,
@@ -15076,7 +18623,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
             }
             #[cfg(feature = "rustls-backend")]
             {
-                let mut https_server = HttpsServer::new(app.settings.http_port);
+                let mut https_server = HttpsServer::new(app.settings.http_port, app.web_listen());
                 match start_https_server(
                     &mut https_server,
                     &cert_file,
@@ -15105,8 +18652,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
         }
     }
 
-    // Keepalive: send NOP every 5 minutes if telnet mode and idle
-    const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+    // Keepalive: send NOP once a world has been idle for its keepalive interval
 
     // Use async event stream instead of polling to reduce CPU usage
     let mut event_stream = EventStream::new();
@@ -15128,6 +18674,14 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 let _ = sigusr1_tx.send(AppEvent::Sigusr1Received).await;
             }
         });
+
+        // SIGHUP: the terminal (e.g. an SSH session) went away
+        let sighup_tx = event_tx.clone();
+        tokio::spawn(async move {
+            let Ok(mut sighup) = signal(SignalKind::hangup()) else { return };
+            sighup.recv().await;
+            let _ = sighup_tx.send(AppEvent::Hangup).await;
+        });
     }
 
     // Reset terminal state after reload — clear stale mouse capture, etc.
@@ -15166,6 +18720,14 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
     let pending_update_sleep = tokio::time::sleep(FAR_FUTURE);
     tokio::pin!(pending_update_sleep);
 
+    // Background world batch release — only active while low-priority worlds hold output
+    let background_batch_sleep = tokio::time::sleep(FAR_FUTURE);
+    tokio::pin!(background_batch_sleep);
+
+    // Bell flash end — only active while the status bar is inverted (see bell.rs)
+    let bell_flash_sleep = tokio::time::sleep(FAR_FUTURE);
+    tokio::pin!(bell_flash_sleep);
+
     // Auto-reconnect timer — fires when a world is due for reconnection
     let reconnect_sleep = tokio::time::sleep(FAR_FUTURE);
     tokio::pin!(reconnect_sleep);
@@ -15216,6 +18778,11 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
         }
     }
 
+    // Open a command-line MUD link (not after a reload, which has its worlds back already)
+    if let Some(uri) = STARTUP_URI.get().filter(|_| !should_load_state) {
+        handle_command(&format!("/connect {}", uri), &mut app, event_tx.clone()).await;
+    }
+
     debug_log(is_debug_enabled(), "STARTUP: Entering main event loop");
 
     // Counter for debugging first few loop iterations
@@ -15242,6 +18809,11 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
             maybe_event = event_stream.next() => {
                 needs_draw = true;
                 if let Some(Ok(event)) = maybe_event {
+                // Terminal focus reports (for activity notifications, see desktop_notify.rs)
+                if let Event::FocusGained | Event::FocusLost = event {
+                    app.set_terminal_focus(event == Event::FocusGained);
+                    continue;
+                }
                 // Handle mouse events
                 if let Event::Mouse(mouse) = event {
                     if !app.settings.mouse_enabled {
@@ -15298,6 +18870,30 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             }
                             _ => { continue; }
                         }
+                    } else if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                        // World tab bar clicks (mouse capture is held while the bar is shown)
+                        match app.tab_bar_layout.hit(mouse.column, mouse.row) {
+                            Some(idx) => {
+                                app.tab_drag_from = Some(idx);
+                                app.switch_world(idx);
+                            }
+                            None => continue,
+                        }
+                    } else if mouse.kind == MouseEventKind::Up(MouseButton::Left) {
+                        // Releasing over another tab drops the dragged world there
+                        let from = app.tab_drag_from.take();
+                        match (from, app.tab_bar_layout.hit(mouse.column, mouse.row)) {
+                            (Some(from), Some(to)) if app.reorder_world(from, to) => {
+                                app.ws_broadcast(WsMessage::WorldMoved { from, to });
+                                let _ = persistence::save_settings(&app);
+                            }
+                            _ => continue,
+                        }
+                    } else if mouse.kind == MouseEventKind::Down(MouseButton::Middle) {
+                        match app.tab_bar_layout.hit(mouse.column, mouse.row) {
+                            Some(idx) if app.disconnect_world(idx) => {}
+                            _ => continue,
+                        }
                     } else {
                         continue;
                     }
@@ -15335,14 +18931,16 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             // Always disable mouse capture to clear any stuck state
                             let _ = execute!(std::io::stdout(), DisableMouseCapture);
                             app.mouse_capture_active = false;
-                            let _ = execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste);
+                            let _ = execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste, crossterm::event::DisableFocusChange);
                             disable_raw_mode()?;
                             execute!(std::io::stdout(), LeaveAlternateScreen)?;
+                            app.keypad_mode_active = false;
                             enable_raw_mode()?;
                             execute!(
                                 std::io::stdout(),
                                 EnterAlternateScreen,
                                 crossterm::event::EnableBracketedPaste,
+                                crossterm::event::EnableFocusChange,
                                 Clear(ClearType::All),
                                 cursor::MoveTo(0, 0)
                             )?;
@@ -15387,9 +18985,10 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 if app.mouse_capture_active {
                                     let _ = execute!(std::io::stdout(), DisableMouseCapture);
                                 }
-                                let _ = execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste);
+                                let _ = execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste, crossterm::event::DisableFocusChange);
                                 disable_raw_mode()?;
                                 execute!(std::io::stdout(), LeaveAlternateScreen)?;
+                                app.keypad_mode_active = false;
 
                                 // Send SIGTSTP to self to suspend
                                 unsafe {
@@ -15398,7 +18997,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
 
                                 // When we resume (after fg), re-enter raw mode and redraw
                                 enable_raw_mode()?;
-                                execute!(std::io::stdout(), EnterAlternateScreen, crossterm::event::EnableBracketedPaste)?;
+                                execute!(std::io::stdout(), EnterAlternateScreen, crossterm::event::EnableBracketedPaste, crossterm::event::EnableFocusChange)?;
                                 if app.mouse_capture_active {
                                     let _ = execute!(std::io::stdout(), EnableMouseCapture);
                                 }
@@ -15687,10 +19286,13 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 // Process any pending keyboard operations from TF functions like kbgoto()
                                 app.process_pending_keyboard_ops();
                             } else if app.current_world().connected {
+                                let cmd = app.current_world().wrap_input(&cmd);
                                 if let Some(tx) = &app.current_world().command_tx {
-                                    if tx.send(WriteCommand::Text(cmd)).await.is_err() {
+                                    if tx.send(WriteCommand::Text(cmd.clone())).await.is_err() {
                                         app.add_output("Failed to send command");
                                     } else {
+                                        let world_idx = app.current_world_index;
+                                        app.echo_sent(world_idx, &cmd);
                                         let now = std::time::Instant::now();
                                         app.current_world_mut().last_send_time = Some(now);
                                         app.current_world_mut().last_user_command_time = Some(now);
@@ -15715,6 +19317,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     }
                     app.check_word_ended();
                     app.check_temp_conversion();
+                    app.check_autocorrect();
 
                 }
                 } // close if let Some(Ok(event))
@@ -15736,6 +19339,12 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 console_width,
                                 false, // not daemon mode
                             );
+                            if let Some(at) = app.next_background_release() {
+                                background_batch_sleep.as_mut().reset(tokio::time::Instant::from_std(at));
+                            }
+                            if let Some(at) = app.bell_flash_until {
+                                bell_flash_sleep.as_mut().reset(tokio::time::Instant::from_std(at));
+                            }
 
                             // Activate prompt check if server data set wont_echo_time
                             if app.worlds[world_idx].wont_echo_time.is_some() {
@@ -15807,6 +19416,11 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             app.handle_wont_echo_seen(world_idx);
                         }
                     }
+                    AppEvent::ServerEcho(ref world_name, on) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.set_server_echo(world_idx, on);
+                        }
+                    }
                     AppEvent::NawsRequested(ref world_name) => {
                         if let Some(world_idx) = app.find_world_index(world_name) {
                             app.handle_naws_requested(world_idx);
@@ -15859,6 +19473,17 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             return Ok(());
                         }
                     }
+                    AppEvent::Hangup => {
+                        // Keep the worlds connected in the background if the session can be
+                        // reattached, rather than dropping them with the terminal
+                        debug_log(is_debug_enabled(), "LOOP: Received SIGHUP, detaching");
+                        if handle_command("/detach", &mut app, event_tx.clone()).await {
+                            return Ok(());
+                        }
+                        app.quit_confirmed = true;
+                        handle_command("/quit", &mut app, event_tx.clone()).await;
+                        return Ok(());
+                    }
                     // ConnectWorldRequest: a remote/WebSocket client executed a world-switch or
                     // connect ClayCommand (e.g. via an action like /common); the master must
                     // connect the world itself since it owns the sockets. Mirrors the
@@ -15887,12 +19512,28 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     AppEvent::MultiuserTelnetDetected(_, _) => {}
                     AppEvent::MultiuserPrompt(_, _, _) => {}
                     // Slack/Discord events
+                    AppEvent::ReplayData(ref world_name, text) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            let (height, width) = (app.output_height, app.output_width);
+                            app.replay_output(world_idx, &text, height, width, false);
+                        }
+                    }
+                    AppEvent::SendFileLine(ref world_name, line) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.send_file_line(world_idx, line);
+                        }
+                    }
+                    AppEvent::ShellOutput(ref world_name, output, line) => {
+                        if let Some(world_idx) = app.find_world_index(world_name) {
+                            app.shell_output(world_idx, output, line);
+                        }
+                    }
                     AppEvent::SlackMessage(ref world_name, message) | AppEvent::DiscordMessage(ref world_name, message) => {
                         if let Some(world_idx) = app.find_world_index(world_name) {
                             app.worlds[world_idx].last_receive_time = Some(std::time::Instant::now());
                             let is_current = world_idx == app.current_world_index || app.ws_client_viewing(world_idx);
                             let world_name_for_triggers = world_name.clone();
-                            let actions = app.settings.actions.clone();
+                            let actions = app.trigger_actions(world_idx);
 
                             // Check action and TF triggers on the message
                             let tr = process_triggers(&message, &world_name_for_triggers, &actions, &mut app.tf_engine);
@@ -15908,7 +19549,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 // Add as gagged line (only visible with F2)
                                 let seq = app.worlds[world_idx].next_seq;
                                 app.worlds[world_idx].next_seq += 1;
-                                app.worlds[world_idx].output_lines.push(OutputLine::new_gagged(message.clone(), seq));
+                                app.worlds[world_idx].output_lines.push_back(OutputLine::new_gagged(message.clone(), seq));
                                 if !app.worlds[world_idx].paused {
                                     app.worlds[world_idx].scroll_to_bottom();
                                 }
@@ -15990,14 +19631,8 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                     app.ws_broadcast(WsMessage::PendingLinesUpdate { world_index: world_idx, count: pending_after });
                                 }
 
-                                // Broadcast updated unseen count so all clients stay in sync
-                                let unseen_count = app.worlds[world_idx].unseen_lines;
-                                if unseen_count > 0 {
-                                    app.ws_broadcast(WsMessage::UnseenUpdate {
-                                        world_index: world_idx,
-                                        count: unseen_count,
-                                    });
-                                }
+                                // Count the new lines as unseen by the console and each client not viewing them
+                                app.note_unseen_lines(world_idx, lines_to_output + lines_to_pending, console_viewing, is_current);
 
                                 // Broadcast activity count to keep all clients in sync
                                 app.broadcast_activity();
@@ -16113,6 +19748,11 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                     app.current_world_index = prev_index;
                                 }
                             }
+                            WsAsyncAction::ConnectAll => {
+                                if handle_command("/connect -a", &mut app, event_tx.clone()).await {
+                                    return Ok(());
+                                }
+                            }
                             WsAsyncAction::Disconnect { world_index, prev_index } => {
                                 let _ = world_index;
                                 if handle_command("/disconnect", &mut app, event_tx.clone()).await {
@@ -16136,6 +19776,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                         app.handle_connection_success(&world_name, cmd_tx, socket_fd, is_tls);
                     }
                     AppEvent::ConnectionFailed(world_name, error) => {
+                        app.note_bulk_connect(&world_name, false);
                         if let Some(world_idx) = app.find_world_index(&world_name) {
                             app.add_output_to_world(world_idx, &format!("Connection failed: {}", error));
                             if let Some(mismatch) = app.check_and_broadcast_cert_mismatch(world_idx) {
@@ -16168,16 +19809,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     AppEvent::ApiLookupResult(client_id, world_index, result, cursor_start) => {
                         match result {
                             Ok(text) => app.ws_send_to_client(client_id, WsMessage::SetInputBuffer { text, cursor_start }),
-                            Err(e) => app.ws_send_to_client(client_id, WsMessage::ServerData {
-                                world_index,
-                                data: e,
-                                is_viewed: false,
-                                ts: current_timestamp_secs(),
-                                from_server: false,
-                                seq: 0,
-                                marked_new: false,
-                                flush: false, gagged: false,
-                            }),
+                            Err(e) => app.send_command_output(client_id, world_index, e),
                         }
                     }
                     AppEvent::RemoteListResult(requesting_client_id, world_index, lines) => {
@@ -16188,16 +19820,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             }
                         } else {
                             for line in &lines {
-                                app.ws_send_to_client(requesting_client_id, WsMessage::ServerData {
-                                    world_index,
-                                    data: line.clone(),
-                                    is_viewed: false,
-                                    ts: current_timestamp_secs(),
-                                    from_server: false,
-                                    seq: 0,
-                                    marked_new: false,
-                                    flush: false, gagged: false,
-                                });
+                                app.send_command_output(requesting_client_id, world_index, line.clone());
                             }
                         }
                     }
@@ -16301,12 +19924,23 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     AppEvent::ImportResult(client_id, addr, result) => {
                         app.handle_import_result(client_id, addr, result);
                     }
+                    AppEvent::AcmeResult(domain, result) => {
+                        if app.handle_acme_result(&domain, &result) {
+                            restart_http_server(&mut app, event_tx.clone()).await;
+                        }
+                    }
+                    AppEvent::PushGone(endpoints) => {
+                        app.remove_push_subscriptions(&endpoints);
+                    }
                 }
             }
 
             // Periodic timer for clock updates and keepalive checks (once per minute)
             _ = keepalive_interval.tick() => {
                 needs_draw = true; // Clock display updates every minute
+                app.refresh_active_profile();
+                app.check_silence_watchdogs();
+                app.start_acme_if_due(false);
 
                 // Clear popup error messages after timeout
                 if let Some(state) = app.popup_manager.current_mut() {
@@ -16318,13 +19952,13 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     }
                 }
 
-                // Check keepalive for all connected worlds (send NOP if no activity in 5 min)
+                // Check keepalive for all connected worlds (send NOP once idle for the world's interval)
                 for world in &mut app.worlds {
                     if world.connected {
                         // Only check last_send_time: server kicks us when WE go idle,
                         // server-side data doesn't reset the server's idle timer for our side.
                         let should_send = match world.last_send_time {
-                            Some(t) => t.elapsed() >= KEEPALIVE_INTERVAL,
+                            Some(t) => t.elapsed() >= world.settings.keep_alive_interval(),
                             None => true,
                         };
                         if should_send {
@@ -16384,7 +20018,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 world.clear_connection_state(false, false);
                                 let seq = world.next_seq;
                                 world.next_seq += 1;
-                                world.output_lines.push(OutputLine::new("TLS proxy terminated. Connection lost.".to_string(), seq));
+                                world.output_lines.push_back(OutputLine::new("TLS proxy terminated. Connection lost.".to_string(), seq));
                             }
                         }
                     }
@@ -16412,7 +20046,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 if !world.connected {
                                     let seq = world.next_seq;
                                     world.next_seq += 1;
-                                    world.output_lines.push(OutputLine::new(normalized.trim().to_string(), seq));
+                                    world.output_lines.push_back(OutputLine::new(normalized.trim().to_string(), seq));
                                     world.wont_echo_time = None;
                                     continue;
                                 }
@@ -16423,8 +20057,8 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 // Handle auto-login (same logic as AppEvent::Prompt handler)
                                 if !world.skip_auto_login {
                                     let auto_type = world.settings.auto_connect_type;
-                                    let user = world.settings.user.clone();
-                                    let password = world.settings.password.clone();
+                                    let user = secrets::expand(&world.settings.user, &world.settings.secrets, &app.settings.secrets);
+                                    let password = secrets::expand(&world.settings.password, &world.settings.secrets, &app.settings.secrets);
                                     let prompt_num = world.prompt_count;
 
                                     if !user.is_empty() && !password.is_empty() {
@@ -16444,7 +20078,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                                     _ => None,
                                                 }
                                             }
-                                            AutoConnectType::Connect | AutoConnectType::NoLogin => None,
+                                            _ => None,
                                         };
 
                                         if let Some(cmd) = cmd_to_send {
@@ -16452,6 +20086,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                             if let Some(tx) = &world.command_tx {
                                                 let _ = tx.try_send(WriteCommand::Text(cmd));
                                             }
+                                            if prompt_num == auto_type.login_prompts() {
+                                                world.send_connect_commands();
+                                            }
                                         }
                                     }
                                 }
@@ -16465,10 +20102,13 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 for world in &mut app.worlds {
                     if let Some(deadline) = world.fansi_detect_until {
                         if now_fansi >= deadline {
-                            if let Some(login_cmd) = world.fansi_login_pending.take() {
+                            if let Some(login) = world.fansi_login_pending.take() {
                                 if let Some(tx) = &world.command_tx {
-                                    let _ = tx.try_send(WriteCommand::Text(login_cmd));
+                                    for line in login {
+                                        let _ = tx.try_send(WriteCommand::Text(line));
+                                    }
                                 }
+                                world.send_connect_commands();
                             }
                             world.fansi_detect_until = None;
                         }
@@ -16519,7 +20159,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             tf::TfCommandResult::Success(Some(msg)) => {
                                 let seq = app.worlds[world_idx].next_seq;
                                 app.worlds[world_idx].next_seq += 1;
-                                app.worlds[world_idx].output_lines.push(OutputLine::new_client(msg.clone(), seq));
+                                app.worlds[world_idx].output_lines.push_back(OutputLine::new_client(msg.clone(), seq));
                                 app.ws_broadcast(WsMessage::ServerData {
                                     world_index: world_idx,
                                     data: msg,
@@ -16535,7 +20175,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 let err_msg = format!("Error: {}", err);
                                 let seq = app.worlds[world_idx].next_seq;
                                 app.worlds[world_idx].next_seq += 1;
-                                app.worlds[world_idx].output_lines.push(OutputLine::new_client(err_msg.clone(), seq));
+                                app.worlds[world_idx].output_lines.push_back(OutputLine::new_client(err_msg.clone(), seq));
                                 app.ws_broadcast(WsMessage::ServerData {
                                     world_index: world_idx,
                                     data: err_msg,
@@ -16587,6 +20227,23 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
             }
 
             // Pending count update — only fires when worlds have pending lines
+            // Release held output of low-priority background worlds (see throttle.rs)
+            _ = &mut background_batch_sleep => {
+                let mut retry = false;
+                for name in app.due_background_batches(std::time::Instant::now()) {
+                    retry |= event_tx.try_send(AppEvent::ServerData(name, Vec::new())).is_err();
+                }
+                let next = if retry { Duration::from_millis(100) } else { FAR_FUTURE };
+                background_batch_sleep.as_mut().reset(tokio::time::Instant::now() + next);
+            }
+
+            // End a bell flash (see bell.rs)
+            _ = &mut bell_flash_sleep, if app.bell_flash_until.is_some() => {
+                app.bell_flash_until = None;
+                bell_flash_sleep.as_mut().reset(tokio::time::Instant::now() + FAR_FUTURE);
+                needs_draw = true;
+            }
+
             _ = &mut pending_update_sleep => {
                 let now = std::time::Instant::now();
                 for world in app.worlds.iter_mut() {
@@ -16637,7 +20294,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     if let Some(idx) = app.find_world_index(&world_name) {
                         app.worlds[idx].reconnect_at = None;
                         if !app.worlds[idx].connected && app.worlds[idx].settings.has_connection_settings() {
-                            let settings = app.worlds[idx].settings.clone();
+                            let settings = app.login_settings(idx);
                             app.worlds[idx].connection_id += 1;
                             let connection_id = app.worlds[idx].connection_id;
                             let ssl_msg = if settings.use_ssl { " with SSL" } else { "" };
@@ -16646,7 +20303,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             // send auto-login; handle_connection_success handles that instead.
                             match daemon::connect_daemon_world(
                                 idx, world_name.clone(), &settings, event_tx.clone(), connection_id, true,
-                                app.settings.tls_proxy_enabled,
+                                app.settings.tls_proxy_enabled, app.worlds[idx].writer_encoding(),
                             ).await {
                                 Some((cmd_tx, socket_fd, is_tls, proxy_pid, proxy_socket_path)) => {
                                     app.handle_connection_success(&world_name, cmd_tx, socket_fd, is_tls);
@@ -16703,6 +20360,12 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             console_width,
                             false, // not daemon mode
                         );
+                        if let Some(at) = app.next_background_release() {
+                            background_batch_sleep.as_mut().reset(tokio::time::Instant::from_std(at));
+                        }
+                        if let Some(at) = app.bell_flash_until {
+                            bell_flash_sleep.as_mut().reset(tokio::time::Instant::from_std(at));
+                        }
 
                         // Activate prompt check if server data set wont_echo_time
                         if app.worlds[world_idx].wont_echo_time.is_some() {
@@ -16768,6 +20431,11 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                         app.handle_wont_echo_seen(world_idx);
                     }
                 }
+                AppEvent::ServerEcho(ref world_name, on) => {
+                    if let Some(world_idx) = app.find_world_index(world_name) {
+                        app.set_server_echo(world_idx, on);
+                    }
+                }
                 AppEvent::NawsRequested(ref world_name) => {
                     if let Some(world_idx) = app.find_world_index(world_name) {
                         app.handle_naws_requested(world_idx);
@@ -16818,7 +20486,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 AppEvent::MultiuserDisconnected(_, _) => {}
                 AppEvent::MultiuserTelnetDetected(_, _) => {}
                 AppEvent::MultiuserPrompt(_, _, _) => {}
-                AppEvent::Sigusr1Received => {}
+                AppEvent::Sigusr1Received | AppEvent::Hangup => {}
                 AppEvent::UpdateResult(result) => {
                     match result {
                         Ok(_success) => {
@@ -16831,6 +20499,14 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 AppEvent::ImportResult(client_id, addr, result) => {
                     app.handle_import_result(client_id, addr, result);
                 }
+                AppEvent::AcmeResult(domain, result) => {
+                    if app.handle_acme_result(&domain, &result) {
+                        restart_http_server(&mut app, event_tx.clone()).await;
+                    }
+                }
+                AppEvent::PushGone(endpoints) => {
+                    app.remove_push_subscriptions(&endpoints);
+                }
                 AppEvent::GmcpNegotiated(ref world_name) => {
                     if let Some(world_idx) = app.find_world_index(world_name) {
                         app.handle_gmcp_negotiated(world_idx);
@@ -16852,12 +20528,28 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                     }
                 }
                 // Slack/Discord events
+                AppEvent::ReplayData(ref world_name, text) => {
+                    if let Some(world_idx) = app.find_world_index(world_name) {
+                        let (height, width) = (app.output_height, app.output_width);
+                        app.replay_output(world_idx, &text, height, width, false);
+                    }
+                }
+                AppEvent::SendFileLine(ref world_name, line) => {
+                    if let Some(world_idx) = app.find_world_index(world_name) {
+                        app.send_file_line(world_idx, line);
+                    }
+                }
+                AppEvent::ShellOutput(ref world_name, output, line) => {
+                    if let Some(world_idx) = app.find_world_index(world_name) {
+                        app.shell_output(world_idx, output, line);
+                    }
+                }
                 AppEvent::SlackMessage(ref world_name, message) | AppEvent::DiscordMessage(ref world_name, message) => {
                     if let Some(world_idx) = app.find_world_index(world_name) {
                         app.worlds[world_idx].last_receive_time = Some(std::time::Instant::now());
                         let is_current = world_idx == app.current_world_index || app.ws_client_viewing(world_idx);
                         let world_name_for_triggers = world_name.clone();
-                        let actions = app.settings.actions.clone();
+                        let actions = app.trigger_actions(world_idx);
 
                         // Check action and TF triggers on the message
                         let tr = process_triggers(&message, &world_name_for_triggers, &actions, &mut app.tf_engine);
@@ -16873,7 +20565,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                             // Add as gagged line (only visible with F2)
                             let seq = app.worlds[world_idx].next_seq;
                             app.worlds[world_idx].next_seq += 1;
-                            app.worlds[world_idx].output_lines.push(OutputLine::new_gagged(message.clone(), seq));
+                            app.worlds[world_idx].output_lines.push_back(OutputLine::new_gagged(message.clone(), seq));
                             if !app.worlds[world_idx].paused {
                                 app.worlds[world_idx].scroll_to_bottom();
                             }
@@ -17006,6 +20698,11 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                                 app.current_world_index = prev_index;
                             }
                         }
+                        WsAsyncAction::ConnectAll => {
+                            if handle_command("/connect -a", &mut app, event_tx.clone()).await {
+                                return Ok(());
+                            }
+                        }
                         WsAsyncAction::Disconnect { world_index, prev_index } => {
                             let _ = world_index;
                             if handle_command("/disconnect", &mut app, event_tx.clone()).await {
@@ -17032,6 +20729,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 }
                 // Background connection failed
                 AppEvent::ConnectionFailed(world_name, error) => {
+                    app.note_bulk_connect(&world_name, false);
                     if let Some(world_idx) = app.find_world_index(&world_name) {
                         app.add_output_to_world(world_idx, &format!("Connection failed: {}", error));
                         if let Some(mismatch) = app.check_and_broadcast_cert_mismatch(world_idx) {
@@ -17053,31 +20751,13 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 AppEvent::ApiLookupResult(client_id, world_index, result, cursor_start) => {
                     match result {
                         Ok(text) => app.ws_send_to_client(client_id, WsMessage::SetInputBuffer { text, cursor_start }),
-                        Err(e) => app.ws_send_to_client(client_id, WsMessage::ServerData {
-                            world_index,
-                            data: e,
-                            is_viewed: false,
-                            ts: current_timestamp_secs(),
-                            from_server: false,
-                            seq: 0,
-                            marked_new: false,
-                            flush: false, gagged: false,
-                        }),
+                        Err(e) => app.send_command_output(client_id, world_index, e),
                     }
                 }
                 AppEvent::RemoteListResult(requesting_client_id, world_index, lines) => {
                     app.remote_ping_responses = None;
                     for line in &lines {
-                        app.ws_send_to_client(requesting_client_id, WsMessage::ServerData {
-                            world_index,
-                            data: line.clone(),
-                            is_viewed: false,
-                            ts: current_timestamp_secs(),
-                            from_server: false,
-                            seq: 0,
-                            marked_new: false,
-                            flush: false, gagged: false,
-                        });
+                        app.send_command_output(requesting_client_id, world_index, line.clone());
                     }
                 }
             }
@@ -17102,6 +20782,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
             needs_draw = true;
         }
 
+        // Hold each world to the Scrollback Lines cap (before drawing, so the view is stable)
+        app.trim_scrollback();
+
         // Skip drawing when no visible state changed (e.g., pending update WS broadcast)
         if needs_draw {
             // Check if any popup is now visible
@@ -17121,9 +20804,10 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
             let popup_visibility_changed = any_popup_visible != app.popup_was_visible;
             app.popup_was_visible = any_popup_visible;
 
-            // Toggle mouse capture when popup visibility changes
+            // Toggle mouse capture when popup visibility changes (always on while the
+            // tab bar is shown, so its tabs can be clicked)
             if app.settings.mouse_enabled {
-                let want_mouse = any_popup_visible;
+                let want_mouse = any_popup_visible || app.settings.tab_bar;
                 if want_mouse && !app.mouse_capture_active {
                     let _ = execute!(std::io::stdout(), EnableMouseCapture);
                     app.mouse_capture_active = true;
@@ -17133,6 +20817,18 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 }
             }
 
+            // Keypad key reporting for numpad speedwalk (kitty keyboard protocol; ignored by
+            // terminals without it). Leaving the alternate screen drops it, so teardown
+            // paths just clear keypad_mode_active and this re-pushes it.
+            let want_keypad = app.worlds.iter().any(|w| w.settings.numpad_walk);
+            if want_keypad && !app.keypad_mode_active {
+                let _ = execute!(std::io::stdout(), PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES));
+                app.keypad_mode_active = true;
+            } else if !want_keypad && app.keypad_mode_active {
+                let _ = execute!(std::io::stdout(), PopKeyboardEnhancementFlags);
+                app.keypad_mode_active = false;
+            }
+
             // Handle terminal clear request (e.g., after closing editor)
             if app.needs_terminal_clear {
                 execute!(
@@ -17145,7 +20841,13 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
 
             // Use ratatui for everything, but render output area with raw crossterm
             // after the ratatui draw (ratatui's Paragraph has rendering bugs)
-            terminal.draw(|f| ui(f, &mut app))?;
+            let mut frame = terminal.draw(|f| ui(f, &mut app))?;
+            if std::mem::take(&mut app.layout_reclaim) {
+                terminal.clear()?;
+                frame = terminal.draw(|f| ui(f, &mut app))?;
+                app.needs_output_redraw = true;
+            }
+            undercurl_misspellings(frame.buffer, &app);
 
             // Render output area with crossterm only when needed (optimization)
             // Also redraw when popup visibility changes (including popup open/close)
@@ -17156,9 +20858,9 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::R
                 // Mark current world as seen since its output was just displayed
                 let has_unseen = app.current_world().unseen_lines > 0;
                 if has_unseen {
+                    // Only the console has seen it: WebSocket clients keep their own
+                    // counts (App::ws_client_unseen)
                     app.current_world_mut().mark_seen();
-                    // Broadcast to WebSocket clients
-                    app.ws_broadcast(WsMessage::UnseenCleared { world_index: app.current_world_index });
                     // Broadcast activity count since a world was just marked as seen
                     app.broadcast_activity();
                 }
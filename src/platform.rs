@@ -40,6 +40,71 @@ use crate::UpdateSuccess;
 // key matching the certificate it presented). That combination means an
 // on-path attacker could simply replay whatever certificate they wanted —
 // signature checks are what prove possession of the private key.
+//
+// A world's `tls_verify` setting can ask for more or less than that: full CA and
+// host name validation, or (for servers whose certificate changes on every restart)
+// any certificate at all — still with the handshake signature checked.
+
+/// How a world's TLS certificate is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TlsVerify {
+    /// Signed by a known CA for the host name
+    Full,
+    /// Pinned on first connect; a different certificate later blocks the connection
+    #[default]
+    Tofu,
+    /// Any certificate
+    None,
+}
+
+impl TlsVerify {
+    pub const ALL: [TlsVerify; 3] = [TlsVerify::Full, TlsVerify::Tofu, TlsVerify::None];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TlsVerify::Full => "full",
+            TlsVerify::Tofu => "tofu",
+            TlsVerify::None => "none",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// A client certificate a world presents to the server (some MUSHes log in by
+/// certificate): the PEM certificate chain and its private key
+#[derive(Clone)]
+pub struct ClientIdentity {
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+}
+
+impl ClientIdentity {
+    /// Read a world's `tls_client_cert` and `tls_client_key` files (`~/` for home).
+    /// None when no certificate is set; with no key file the key is read from the
+    /// certificate file.
+    pub fn load(cert_path: &str, key_path: &str) -> Result<Option<Self>, String> {
+        let cert_path = cert_path.trim();
+        if cert_path.is_empty() {
+            return Ok(None);
+        }
+        let key_path = match key_path.trim() {
+            "" => cert_path,
+            path => path,
+        };
+        let read = |path: &str| {
+            let full = match path.strip_prefix("~/") {
+                Some(home_rel) => PathBuf::from(crate::get_home_dir()).join(home_rel),
+                None => PathBuf::from(path),
+            };
+            std::fs::read(&full).map_err(|e| format!("Client certificate: can't read {}: {}", full.display(), e))
+        };
+        Ok(Some(ClientIdentity { cert_pem: read(cert_path)?, key_pem: read(key_path)? }))
+    }
+}
+
 pub mod danger {
     use sha2::{Digest, Sha256};
     use std::sync::{Mutex, OnceLock};
@@ -114,11 +179,12 @@ pub mod danger {
 #[cfg(feature = "rustls-backend")]
 pub mod danger_rustls {
     use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
-    use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
     use rustls::crypto::CryptoProvider;
     use rustls::{DigitallySignedStruct, Error, SignatureScheme};
-    use std::sync::OnceLock;
+    use std::sync::{Arc, OnceLock};
     use super::danger::{check_pin, sha256_hex};
+    use super::{ClientIdentity, TlsVerify};
 
     /// The default rustls crypto provider (ring), cached — used to perform *real*
     /// handshake signature verification (see `TofuVerifier` below) rather than
@@ -199,6 +265,113 @@ pub mod danger_rustls {
             crypto_provider().signature_verification_algorithms.supported_schemes()
         }
     }
+
+    /// Accepts any certificate (`TlsVerify::None`), but like `TofuVerifier` checks the
+    /// handshake signatures, so the server must hold the key of whatever it presents
+    #[derive(Debug)]
+    pub struct AnyCertVerifier;
+
+    impl ServerCertVerifier for AnyCertVerifier {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            rustls::crypto::verify_tls12_signature(message, cert, dss, &crypto_provider().signature_verification_algorithms)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            rustls::crypto::verify_tls13_signature(message, cert, dss, &crypto_provider().signature_verification_algorithms)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            crypto_provider().signature_verification_algorithms.supported_schemes()
+        }
+    }
+
+    /// Client config for a MUD connection to `host_port`, checking the server's
+    /// certificate as the world's `tls_verify` says and presenting `identity` if the
+    /// world has a client certificate
+    pub fn mud_client_config(
+        host_port: &str,
+        verify: TlsVerify,
+        identity: Option<&ClientIdentity>,
+    ) -> Result<rustls::ClientConfig, String> {
+        let builder = match verify {
+            TlsVerify::Full => {
+                let mut roots = rustls::RootCertStore::empty();
+                roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+                rustls::ClientConfig::builder().with_root_certificates(roots)
+            }
+            TlsVerify::Tofu => rustls::ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(TofuVerifier::new(host_port.to_string()))),
+            TlsVerify::None => rustls::ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(AnyCertVerifier)),
+        };
+        let identity = match identity {
+            Some(identity) => identity,
+            None => return Ok(builder.with_no_client_auth()),
+        };
+        let certs: Vec<CertificateDer<'static>> = rustls_pemfile::certs(&mut &identity.cert_pem[..])
+            .map_err(|e| format!("Client certificate: {}", e))?
+            .into_iter()
+            .map(CertificateDer::from)
+            .collect();
+        if certs.is_empty() {
+            return Err("Client certificate: no certificate in the file".to_string());
+        }
+        let key = rustls_pemfile::read_all(&mut &identity.key_pem[..])
+            .map_err(|e| format!("Client certificate key: {}", e))?
+            .into_iter()
+            .find_map(|item| match item {
+                rustls_pemfile::Item::PKCS8Key(k) => Some(PrivateKeyDer::Pkcs8(k.into())),
+                rustls_pemfile::Item::RSAKey(k) => Some(PrivateKeyDer::Pkcs1(k.into())),
+                rustls_pemfile::Item::ECKey(k) => Some(PrivateKeyDer::Sec1(k.into())),
+                _ => None,
+            })
+            .ok_or_else(|| "Client certificate: no private key found".to_string())?;
+        builder.with_client_auth_cert(certs, key).map_err(|e| format!("Client certificate: {}", e))
+    }
+}
+
+/// native-tls connector for a MUD connection: `TlsVerify::Full` keeps native-tls's own
+/// CA and host name checks; TOFU and None accept any certificate in the handshake, TOFU
+/// then pinning it with `check_native_tls_peer_pin`. A client certificate's key must
+/// be PKCS#8 (`BEGIN PRIVATE KEY`) here.
+#[cfg(feature = "native-tls-backend")]
+pub fn mud_native_tls_connector(
+    verify: TlsVerify,
+    identity: Option<&ClientIdentity>,
+) -> Result<native_tls::TlsConnector, String> {
+    let mut builder = native_tls::TlsConnector::builder();
+    if verify != TlsVerify::Full {
+        builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(identity) = identity {
+        let identity = native_tls::Identity::from_pkcs8(&identity.cert_pem, &identity.key_pem)
+            .map_err(|e| format!("Client certificate: {}", e))?;
+        builder.identity(identity);
+    }
+    builder.build().map_err(|e| e.to_string())
 }
 
 /// Post-handshake TOFU pin check for the native-tls MUD path.
@@ -206,7 +379,7 @@ pub mod danger_rustls {
 /// native-tls has no pluggable certificate-verifier callback like rustls's
 /// `ServerCertVerifier` (that's a rustls-specific extension point), so it cannot
 /// reject a mismatched certificate *during* the handshake the way `TofuVerifier`
-/// does. Instead, connections still use `danger_accept_invalid_certs(true)` to
+/// does. Instead, TOFU connections still use `danger_accept_invalid_certs(true)` to
 /// complete the handshake (self-signed MUD certs are expected), and this function
 /// is called immediately afterwards with the peer's certificate. On a pin
 /// mismatch it records the same `CertMismatch` the rustls path would and returns
@@ -216,11 +389,17 @@ pub mod danger_rustls {
 /// the way the old `NoCertificateVerification` skipped rustls's), a replayed
 /// certificate without the matching private key still fails the handshake before
 /// this function is ever reached.
+///
+/// Only `TlsVerify::Tofu` pins; with the other modes this accepts the connection.
 #[cfg(feature = "native-tls-backend")]
 pub fn check_native_tls_peer_pin(
     host_port: &str,
+    verify: TlsVerify,
     cert: Option<native_tls::Certificate>,
 ) -> Result<(), String> {
+    if verify != TlsVerify::Tofu {
+        return Ok(());
+    }
     let cert = match cert {
         Some(c) => c,
         None => return Err(format!("no peer certificate presented by {}", host_port)),
@@ -232,6 +411,44 @@ pub fn check_native_tls_peer_pin(
     danger::check_pin(host_port, &fingerprint)
 }
 
+/// Negotiate a MUD's TLS inside an already-open tunnel, with the same checks and
+/// client certificate as a direct connection to `host:port`
+pub async fn mud_tls_over_tunnel(
+    tunnel: Box<dyn crate::telnet::TunnelIo>,
+    host: &str,
+    port: &str,
+    verify: TlsVerify,
+    identity: Option<&ClientIdentity>,
+) -> Result<Box<dyn crate::telnet::TunnelIo>, String> {
+    let host_port = format!("{}:{}", host, port);
+    #[cfg(feature = "native-tls-backend")]
+    {
+        let connector = tokio_native_tls::TlsConnector::from(mud_native_tls_connector(verify, identity)?);
+        let tls_stream = connector.connect(host, tunnel).await
+            .map_err(|e| format!("SSL handshake failed: {}", e))?;
+        let peer_cert = tls_stream.get_ref().peer_certificate().ok().flatten();
+        check_native_tls_peer_pin(&host_port, verify, peer_cert)?;
+        Ok(Box::new(tls_stream))
+    }
+
+    #[cfg(feature = "rustls-backend")]
+    {
+        let config = danger_rustls::mud_client_config(&host_port, verify, identity)?;
+        let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+            .map_err(|e| format!("Invalid server name: {}", e))?;
+        let tls_stream = tokio_rustls::TlsConnector::from(std::sync::Arc::new(config))
+            .connect(server_name, tunnel).await
+            .map_err(|e| format!("SSL handshake failed: {}", e))?;
+        Ok(Box::new(tls_stream))
+    }
+
+    #[cfg(not(any(feature = "native-tls-backend", feature = "rustls-backend")))]
+    {
+        let _ = (tunnel, host_port, verify, identity);
+        Err("No TLS backend available".to_string())
+    }
+}
+
 pub fn enable_tcp_keepalive(tcp_stream: &TcpStream) {
     use socket2::SockRef;
     let keepalive = socket2::TcpKeepalive::new()
@@ -304,6 +521,7 @@ pub(crate) fn crash_restart() {
         if let Err(e) = persistence::save_reload_state(app) {
             eprintln!("Failed to save state during crash: {}", e);
         }
+        let _ = persistence::hand_over_master_key();
 
         // Clear CLOEXEC on socket fds so they survive exec
         for world in &app.worlds {
@@ -496,6 +714,66 @@ pub(crate) fn is_process_alive(pid: u32) -> bool {
     }
 }
 
+/// Open a URL in the system's default browser (xdg-open, open, or start)
+pub fn open_url(url: &str) -> io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let mut cmd = std::process::Command::new("xdg-open");
+    #[cfg(target_os = "macos")]
+    let mut cmd = std::process::Command::new("open");
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut c = std::process::Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", windows)))]
+    let mut cmd = std::process::Command::new("xdg-open");
+    cmd.arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// Families of the installed monospace fonts, from fontconfig's `fc-list`, for the GUI's
+/// font picker. Empty where fontconfig isn't installed (Windows, most macOS systems).
+pub fn monospace_font_families() -> Vec<String> {
+    match std::process::Command::new("fc-list")
+        .args([":spacing=mono", "family"])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+    {
+        Ok(out) if out.status.success() => parse_fc_families(&String::from_utf8_lossy(&out.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse `fc-list ... family` output: one font per line, its family names comma-separated
+/// (the first is the primary one; a literal comma is `\,`). Sorted, without duplicates.
+pub(crate) fn parse_fc_families(output: &str) -> Vec<String> {
+    let mut families: Vec<String> = output
+        .lines()
+        .filter_map(|line| {
+            let mut primary = String::new();
+            let mut chars = line.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => primary.extend(chars.next()),
+                    ',' => break,
+                    c => primary.push(c),
+                }
+            }
+            let primary = primary.trim();
+            (!primary.is_empty()).then(|| primary.to_string())
+        })
+        .collect();
+    families.sort_by_key(|f| f.to_lowercase());
+    families.dedup();
+    families
+}
+
 /// Reap any zombie child processes to prevent defunct processes from accumulating.
 /// This should be called periodically from the main event loop.
 #[cfg(all(unix, not(target_os = "android")))]
@@ -556,6 +834,8 @@ pub(crate) fn spawn_tls_proxy(
     world_name: &str,
     host: &str,
     port: &str,
+    verify: TlsVerify,
+    client_cert: (&str, &str),
 ) -> io::Result<(u32, PathBuf)> {
     use std::process::{Command, Stdio};
     use std::io::Write;
@@ -572,6 +852,9 @@ pub(crate) fn spawn_tls_proxy(
         let mut file = std::fs::File::create(&config_path)?;
         writeln!(file, "{}:{}", host, port)?;
         writeln!(file, "{}", socket_path.display())?;
+        writeln!(file, "{}", verify.name())?;
+        writeln!(file, "{}", client_cert.0)?;
+        writeln!(file, "{}", client_cert.1)?;
     }
 
     // Get the current executable path
@@ -621,7 +904,7 @@ pub(crate) fn spawn_tls_proxy(
 
 /// Async implementation of the TLS proxy main loop (runs in separate process via --tls-proxy)
 #[cfg(all(unix, not(target_os = "android")))]
-pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, socket_path: &PathBuf) {
+pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, socket_path: &PathBuf, verify: TlsVerify, identity: Option<ClientIdentity>) {
     use tokio::net::UnixListener;
 
     // Ignore SIGUSR1 - the main clay process uses this for reload, but the proxy
@@ -631,8 +914,8 @@ pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, socket_path: &Pa
     }
 
     // Step 1: Connect to the MUD server with TLS
-    let tcp_stream = match TcpStream::connect(format!("{}:{}", host, port)).await {
-        Ok(s) => s,
+    let tcp_stream = match crate::happy_eyeballs::connect(host, port).await {
+        Ok((s, _)) => s,
         Err(_) => return,
     };
 
@@ -642,18 +925,13 @@ pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, socket_path: &Pa
     // Establish TLS connection
     #[cfg(feature = "rustls-backend")]
     let tls_stream = {
-        use rustls::RootCertStore;
         use tokio_rustls::TlsConnector;
         use rustls::pki_types::ServerName;
 
-        // Create a config that accepts invalid certs (common for MUD servers)
-        let mut root_store = RootCertStore::empty();
-        root_store.roots = webpki_roots::TLS_SERVER_ROOTS.to_vec();
-
-        let config = rustls::ClientConfig::builder()
-            .dangerous()
-            .with_custom_certificate_verifier(Arc::new(danger_rustls::TofuVerifier::new(format!("{}:{}", host, port))))
-            .with_no_client_auth();
+        let config = match danger_rustls::mud_client_config(&format!("{}:{}", host, port), verify, identity.as_ref()) {
+            Ok(c) => c,
+            Err(_) => return,
+        };
 
         let connector = TlsConnector::from(Arc::new(config));
         let server_name = match ServerName::try_from(host.to_string()) {
@@ -669,10 +947,7 @@ pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, socket_path: &Pa
 
     #[cfg(feature = "native-tls-backend")]
     let tls_stream = {
-        let connector = match native_tls::TlsConnector::builder()
-            .danger_accept_invalid_certs(true)
-            .build()
-        {
+        let connector = match mud_native_tls_connector(verify, identity.as_ref()) {
             Ok(c) => c,
             Err(_) => return,
         };
@@ -681,7 +956,7 @@ pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, socket_path: &Pa
         match connector.connect(host, tcp_stream).await {
             Ok(s) => {
                 let peer_cert = s.get_ref().peer_certificate().ok().flatten();
-                if check_native_tls_peer_pin(&format!("{}:{}", host, port), peer_cert).is_err() {
+                if check_native_tls_peer_pin(&format!("{}:{}", host, port), verify, peer_cert).is_err() {
                     return;
                 }
                 s
@@ -859,6 +1134,8 @@ pub(crate) fn spawn_tls_proxy(
     world_name: &str,
     host: &str,
     port: &str,
+    verify: TlsVerify,
+    client_cert: (&str, &str),
 ) -> io::Result<(u32, PathBuf)> {
     use std::process::{Command, Stdio};
     use std::io::Write;
@@ -872,6 +1149,9 @@ pub(crate) fn spawn_tls_proxy(
         let mut file = std::fs::File::create(&config_path)?;
         writeln!(file, "{}:{}", host, port)?;
         writeln!(file, "{}", pipe_path.display())?;
+        writeln!(file, "{}", verify.name())?;
+        writeln!(file, "{}", client_cert.0)?;
+        writeln!(file, "{}", client_cert.1)?;
     }
 
     let exe_path = std::env::current_exe()?;
@@ -950,14 +1230,14 @@ pub(crate) fn spawn_tls_proxy(
 /// then accepts Named Pipe clients (one at a time) and relays data.
 /// Survives hot reload because it is a separate process.
 #[cfg(windows)]
-pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, pipe_path: &PathBuf) {
+pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, pipe_path: &PathBuf, verify: TlsVerify, identity: Option<ClientIdentity>) {
     use tokio::net::windows::named_pipe::{ServerOptions, PipeMode};
 
     debug_log(is_debug_enabled(), &format!("TLS-PROXY: starting for {}:{} pipe={}", host, port, pipe_path.display()));
 
     // Step 1: Connect to the MUD server with TLS
-    let tcp_stream = match TcpStream::connect(format!("{}:{}", host, port)).await {
-        Ok(s) => s,
+    let tcp_stream = match crate::happy_eyeballs::connect(host, port).await {
+        Ok((s, _)) => s,
         Err(e) => { debug_log(is_debug_enabled(), &format!("TLS-PROXY: TCP connect failed: {}", e)); return; }
     };
 
@@ -966,17 +1246,13 @@ pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, pipe_path: &Path
     // Establish TLS connection (same backends as Unix version)
     #[cfg(feature = "rustls-backend")]
     let tls_stream = {
-        use rustls::RootCertStore;
         use tokio_rustls::TlsConnector;
         use rustls::pki_types::ServerName;
 
-        let mut root_store = RootCertStore::empty();
-        root_store.roots = webpki_roots::TLS_SERVER_ROOTS.to_vec();
-
-        let config = rustls::ClientConfig::builder()
-            .dangerous()
-            .with_custom_certificate_verifier(Arc::new(danger_rustls::TofuVerifier::new(format!("{}:{}", host, port))))
-            .with_no_client_auth();
+        let config = match danger_rustls::mud_client_config(&format!("{}:{}", host, port), verify, identity.as_ref()) {
+            Ok(c) => c,
+            Err(_) => return,
+        };
 
         let connector = TlsConnector::from(Arc::new(config));
         let server_name = match ServerName::try_from(host.to_string()) {
@@ -992,10 +1268,7 @@ pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, pipe_path: &Path
 
     #[cfg(feature = "native-tls-backend")]
     let tls_stream = {
-        let connector = match native_tls::TlsConnector::builder()
-            .danger_accept_invalid_certs(true)
-            .build()
-        {
+        let connector = match mud_native_tls_connector(verify, identity.as_ref()) {
             Ok(c) => c,
             Err(_) => return,
         };
@@ -1004,7 +1277,7 @@ pub(crate) async fn run_tls_proxy_async(host: &str, port: &str, pipe_path: &Path
         match connector.connect(host, tcp_stream).await {
             Ok(s) => {
                 let peer_cert = s.get_ref().peer_certificate().ok().flatten();
-                if check_native_tls_peer_pin(&format!("{}:{}", host, port), peer_cert).is_err() {
+                if check_native_tls_peer_pin(&format!("{}:{}", host, port), verify, peer_cert).is_err() {
                     return;
                 }
                 s
@@ -1409,14 +1682,14 @@ pub async fn check_and_download_update(force: bool) -> Result<UpdateSuccess, Str
     })
 }
 
+/// Save the reload state and let the world sockets pass to a process taking
+/// this session over (hot reload, /detach). Returns the executable to run.
 #[cfg(all(unix, not(target_os = "android")))]
-pub fn exec_reload(app: &mut App) -> io::Result<()> {
-    // Always log reload (not gated by debug flag) so we can trace issues
-    debug_log(is_debug_enabled(), "RELOAD: Starting exec_reload");
-
+fn prepare_handoff(app: &mut App) -> io::Result<PathBuf> {
     // Save the current state
     debug_log(is_debug_enabled(), "RELOAD: Saving state...");
     persistence::save_reload_state(app)?;
+    persistence::hand_over_master_key()?;
     debug_log(is_debug_enabled(), "RELOAD: State saved successfully");
 
     // Collect socket fds that need to survive exec (plain TCP only)
@@ -1454,12 +1727,20 @@ pub fn exec_reload(app: &mut App) -> io::Result<()> {
     std::env::set_var(RELOAD_FDS_ENV, &fds_str);
     // Tell new process which reload file to load (PID-specific)
     std::env::set_var("CLAY_RELOAD_PID", std::process::id().to_string());
+    Ok(exe)
+}
+
+#[cfg(all(unix, not(target_os = "android")))]
+pub fn exec_reload(app: &mut App) -> io::Result<()> {
+    // Always log reload (not gated by debug flag) so we can trace issues
+    debug_log(is_debug_enabled(), "RELOAD: Starting exec_reload");
+    let exe = prepare_handoff(app)?;
 
     // Execute the new binary with --reload argument
     use std::os::unix::process::CommandExt;
     let mut args: Vec<String> = std::env::args().skip(1).filter(|a| a != "--reload" && a != "--crash").collect();
     args.push("--reload".to_string());
-    debug_log(is_debug_enabled(), &format!("RELOAD: About to exec {} with args={:?} fds={}", exe.display(), args, fds_str));
+    debug_log(is_debug_enabled(), &format!("RELOAD: About to exec {} with args={:?}", exe.display(), args));
     let err = std::process::Command::new(&exe)
         .args(&args)
         .exec();
@@ -1468,6 +1749,37 @@ pub fn exec_reload(app: &mut App) -> io::Result<()> {
     Err(io::Error::other(format!("exec failed: {} (path: {})", err, exe.display())))
 }
 
+/// Hand this session to a background `clay --detached` process that keeps the
+/// connections running with no terminal (see /detach). It starts in a session of
+/// its own, so closing the terminal doesn't take it down.
+#[cfg(all(unix, not(target_os = "android")))]
+pub fn spawn_detached_session(app: &mut App) -> io::Result<()> {
+    debug_log(is_debug_enabled(), "DETACH: Handing the session to a background process");
+    let exe = prepare_handoff(app)?;
+
+    use std::os::unix::process::CommandExt;
+    let mut args = vec!["--detached".to_string(), "--reload".to_string()];
+    if let Some(path) = crate::get_custom_config_path() {
+        args.push(format!("--conf={}", path.display()));
+    }
+    if let Some(profile) = crate::get_config_profile() {
+        args.push(format!("--profile={}", profile));
+    }
+    let mut command = std::process::Command::new(&exe);
+    command.args(&args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    // SAFETY: setsid is async-signal-safe, as required between fork and exec
+    unsafe {
+        command.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+    command.spawn().map(|_| ())
+}
+
 /// Hot reload on Windows: spawn new process and exit old one.
 /// Uses a named event to synchronize so the old process stays alive until
 /// the new one has taken over the console (prevents shell prompt flash).
@@ -1570,6 +1882,7 @@ pub fn exec_reload(app: &mut App) -> io::Result<()> {
         std::io::stdout(),
         crossterm::event::DisableMouseCapture,
         crossterm::event::DisableBracketedPaste,
+        crossterm::event::DisableFocusChange,
         crossterm::terminal::LeaveAlternateScreen
     );
 
@@ -1665,7 +1978,7 @@ fn exec_relaunch_with_args(args: Vec<String>, use_gui: bool) -> io::Result<()> {
     // process starts from a clean screen (GUI/headless processes don't touch the terminal).
     if !use_gui {
         let _ = execute!(std::io::stdout(), crossterm::event::DisableMouseCapture);
-        let _ = execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste);
+        let _ = execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste, crossterm::event::DisableFocusChange);
         let _ = disable_raw_mode();
         let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
     }
@@ -1679,7 +1992,8 @@ fn exec_relaunch_with_args(args: Vec<String>, use_gui: bool) -> io::Result<()> {
         let _ = execute!(
             std::io::stdout(),
             crossterm::terminal::EnterAlternateScreen,
-            crossterm::event::EnableBracketedPaste
+            crossterm::event::EnableBracketedPaste,
+            crossterm::event::EnableFocusChange
         );
     }
     Err(io::Error::other(format!("exec failed: {} (path: {})", err, exe.display())))
@@ -1708,6 +2022,13 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_parse_fc_families() {
+        let out = "DejaVu Sans Mono\nNoto Sans Mono,Noto Sans Mono Regular\nbad\\, name,alias\n\nDejaVu Sans Mono\nFira Code\n";
+        assert_eq!(parse_fc_families(out), vec!["bad, name", "DejaVu Sans Mono", "Fira Code", "Noto Sans Mono"]);
+        assert!(parse_fc_families("").is_empty());
+    }
+
     #[test]
     fn test_is_newer_version_basic() {
         assert!(is_newer_version("1.0.1", "1.0.0"));
@@ -2003,5 +2324,81 @@ mod tests {
 
         crate::persistence::remove_pin(host_port_key);
     }
-}
 
+    #[test]
+    fn test_tls_verify_names_round_trip() {
+        for mode in TlsVerify::ALL {
+            assert_eq!(TlsVerify::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(TlsVerify::from_name("TOFU"), Some(TlsVerify::Tofu));
+        assert_eq!(TlsVerify::from_name("bogus"), None);
+        assert_eq!(TlsVerify::default(), TlsVerify::Tofu);
+    }
+
+    /// A self-signed certificate fails `Full`, and passes `None` without being pinned
+    #[cfg(feature = "rustls-backend")]
+    #[tokio::test]
+    async fn test_mud_client_config_full_rejects_self_signed_none_accepts() {
+        use rcgen::{CertificateParams, KeyPair};
+        use rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+        use tokio::net::TcpListener;
+
+        let key_pair = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec!["localhost".to_string()]).unwrap().self_signed(&key_pair).unwrap();
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der()));
+        let server_config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert.der().clone()], key)
+            .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(std::sync::Arc::new(server_config));
+
+        let host_port_key = "clay-tls-verify-test.invalid:0";
+        crate::persistence::remove_pin(host_port_key);
+        for (mode, should_connect) in [(TlsVerify::Full, false), (TlsVerify::None, true)] {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let acceptor = acceptor.clone();
+            let server = tokio::spawn(async move {
+                if let Ok((stream, _)) = listener.accept().await {
+                    let _ = acceptor.accept(stream).await;
+                }
+            });
+            let config = danger_rustls::mud_client_config(host_port_key, mode, None).unwrap();
+            let connector = tokio_rustls::TlsConnector::from(std::sync::Arc::new(config));
+            let tcp = tokio::net::TcpStream::connect(addr).await.unwrap();
+            let connected = connector.connect(ServerName::try_from("localhost").unwrap(), tcp).await.is_ok();
+            let _ = server.await;
+            assert_eq!(connected, should_connect, "{:?}", mode);
+        }
+        assert_eq!(crate::persistence::get_pin(host_port_key), None, "only TOFU pins");
+    }
+
+    #[cfg(feature = "rustls-backend")]
+    #[test]
+    fn test_client_identity_load_and_config() {
+        use rcgen::{CertificateParams, KeyPair};
+
+        assert!(ClientIdentity::load("", "").unwrap().is_none());
+        assert!(ClientIdentity::load("/nonexistent/clay-client.pem", "").is_err());
+
+        let key_pair = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec!["player".to_string()]).unwrap().self_signed(&key_pair).unwrap();
+        let dir = std::env::temp_dir().join(format!("clay_test_client_cert_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert_path = dir.join("client.pem");
+        let key_path = dir.join("client.key");
+        std::fs::write(&cert_path, cert.pem()).unwrap();
+        std::fs::write(&key_path, key_pair.serialize_pem()).unwrap();
+
+        let identity = ClientIdentity::load(cert_path.to_str().unwrap(), key_path.to_str().unwrap())
+            .unwrap()
+            .unwrap();
+        assert!(danger_rustls::mud_client_config("unused:0", TlsVerify::None, Some(&identity)).is_ok());
+
+        // No key file: the key is expected in the certificate file, which has none
+        let cert_only = ClientIdentity::load(cert_path.to_str().unwrap(), "").unwrap().unwrap();
+        assert!(danger_rustls::mud_client_config("unused:0", TlsVerify::None, Some(&cert_only)).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
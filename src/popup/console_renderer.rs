@@ -14,6 +14,7 @@ use super::{
     ButtonStyle, ContentArea, ElementSelection, FieldKind, PopupLayout, PopupState,
 };
 use crate::encoding::Theme;
+use crate::util::truncate_str;
 
 /// Render a popup to the console
 pub fn render_popup(f: &mut Frame, state: &mut PopupState, theme: &Theme) {
@@ -1466,19 +1467,6 @@ fn get_button_style(button_style: ButtonStyle, is_selected: bool, theme: &Theme)
     }
 }
 
-/// Truncate a string to fit within max_width
-fn truncate_str(s: &str, max_width: usize) -> String {
-    let chars: Vec<char> = s.chars().collect();
-    if chars.len() <= max_width {
-        s.to_string()
-    } else if max_width > 3 {
-        let truncated: String = chars[..max_width - 3].iter().collect();
-        format!("{}...", truncated)
-    } else {
-        chars[..max_width].iter().collect()
-    }
-}
-
 // ============================================================================
 // Direct crossterm popup content renderer (bypasses ratatui for fast scroll)
 // ============================================================================
@@ -2,9 +2,10 @@
 //!
 //! Allows viewing and editing action triggers.
 
+use crate::actions::{describe_pattern_test, test_patterns, MatchType};
 use crate::popup::{
     Button, ButtonId, Field, FieldId, FieldKind, ListItem, ListItemStyle,
-    PopupDefinition, PopupId, PopupLayout, SelectOption,
+    PopupDefinition, PopupId, PopupLayout, PopupState, SelectOption,
 };
 
 
@@ -21,6 +22,9 @@ pub const ACTIONS_BTN_ADD: ButtonId = ButtonId(1);
 pub const ACTIONS_BTN_EDIT: ButtonId = ButtonId(2);
 pub const ACTIONS_BTN_DELETE: ButtonId = ButtonId(3);
 pub const ACTIONS_BTN_CANCEL: ButtonId = ButtonId(4);
+pub const ACTIONS_BTN_IMPORT: ButtonId = ButtonId(5);
+pub const ACTIONS_BTN_EXPORT: ButtonId = ButtonId(6);
+pub const ACTIONS_BTN_DUPLICATE: ButtonId = ButtonId(7);
 
 // ============================================================================
 // Action Editor View
@@ -34,6 +38,8 @@ pub const EDITOR_FIELD_PATTERNS: FieldId = FieldId(13);    // EditableList of pa
 pub const EDITOR_FIELD_COMMAND: FieldId = FieldId(14);
 pub const EDITOR_FIELD_ENABLED: FieldId = FieldId(15);
 pub const EDITOR_FIELD_STARTUP: FieldId = FieldId(16);
+pub const EDITOR_FIELD_TEST: FieldId = FieldId(17);         // Sample line for the pattern tester
+pub const EDITOR_FIELD_TEST_RESULT: FieldId = FieldId(18);  // Tester verdict (label)
 
 // Button IDs - Editor view
 pub const EDITOR_BTN_SAVE: ButtonId = ButtonId(10);
@@ -60,11 +66,8 @@ pub fn match_type_options() -> Vec<SelectOption> {
 
 /// Create the actions list popup definition
 pub fn create_actions_list_popup(actions: &[ActionInfo], visible_height: usize) -> PopupDefinition {
-    // Sort alphabetically by action name (case-insensitive)
-    let mut sorted_actions: Vec<&ActionInfo> = actions.iter().collect();
-    sorted_actions.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-
-    let items: Vec<ListItem> = sorted_actions
+    // List order is the order triggers are checked in (Shift+Up/Down moves an action)
+    let items: Vec<ListItem> = actions
         .iter()
         .map(|a| {
             // Format: "[✓] name   world   pattern" (or "[x]" on Windows)
@@ -117,7 +120,10 @@ pub fn create_actions_list_popup(actions: &[ActionInfo], visible_height: usize)
             ),
         ))
         .with_button(Button::new(ACTIONS_BTN_DELETE, "Delete").danger().with_shortcut('D').left_align())
+        .with_button(Button::new(ACTIONS_BTN_IMPORT, "Import").with_shortcut('I').left_align())
+        .with_button(Button::new(ACTIONS_BTN_EXPORT, "Export").with_shortcut('X').left_align())
         .with_button(Button::new(ACTIONS_BTN_ADD, "Add").with_shortcut('A'))
+        .with_button(Button::new(ACTIONS_BTN_DUPLICATE, "Duplicate").with_shortcut('U'))
         .with_button(Button::new(ACTIONS_BTN_EDIT, "Edit").with_shortcut('E'))
         .with_button(Button::new(ACTIONS_BTN_CANCEL, "Ok").primary().with_shortcut('O'))
         .with_layout(PopupLayout {
@@ -155,13 +161,21 @@ fn actions_list_help_text() -> Vec<String> {
         "  Up/Down   Navigate the action list",
         "  Enter     Edit the selected action",
         "  Space     Toggle enabled/disabled",
+        "  Shift+Up/Down  Move the selected action up/down",
+        "            (actions are checked top to bottom)",
         "  Tab       Cycle between buttons",
         "  Esc       Close this popup",
         "",
         "Buttons:",
         "  Add (A)    Create a new action",
         "  Edit (E)   Edit the selected action",
+        "  Duplicate (U) Copy the selected action",
         "  Delete (D) Remove the selected action",
+        "  Import (I) Merge a trigger pack file",
+        "             (/actions import <file>)",
+        "  Export (X) Write actions to a trigger pack file",
+        "             (/actions export <file> [world];",
+        "             .toml for TOML, JSON otherwise)",
         "  Ok (O)     Close this popup",
         "",
         "Use the filter field at the top to search actions",
@@ -235,6 +249,16 @@ pub fn create_action_editor_popup(settings: &ActionSettings, is_new: bool) -> Po
             "Startup",
             FieldKind::toggle(settings.startup),
         ))
+        .with_field(Field::new(
+            EDITOR_FIELD_TEST,
+            "Test",
+            FieldKind::text_with_placeholder("", "(sample line to try the patterns on)"),
+        ))
+        .with_field(Field::new(
+            EDITOR_FIELD_TEST_RESULT,
+            "",
+            FieldKind::label(""),
+        ))
         .with_button_if(!is_new, Button::new(EDITOR_BTN_DELETE, "Delete").danger().with_shortcut('D').left_align())
         .with_button(Button::new(EDITOR_BTN_CANCEL, "Cancel").with_shortcut('C'))
         .with_button(Button::new(EDITOR_BTN_SAVE, "Save").primary().with_shortcut('S'))
@@ -291,9 +315,48 @@ fn action_editor_help_text() -> Vec<String> {
         "",
         "Startup: Run this action's command when Clay starts",
         "  or hot-reloads (useful for initialization scripts).",
+        "",
+        "Test: Type a sample line to see, as you type, which",
+        "  pattern matches it, its $0..$9 captures, and whether",
+        "  /gag would hide it. A pattern that doesn't compile is",
+        "  reported here, and Save refuses it.",
     ].into_iter().map(|s| s.to_string()).collect()
 }
 
+/// Check the editor's patterns and run its Test line through them, putting the verdict
+/// under the Test field. Reads a field being typed in from the edit buffer, so it can run
+/// after every key. Returns the pattern error, if any, for Save to refuse.
+pub fn refresh_action_test(state: &mut PopupState) -> Option<String> {
+    let editing = if state.editing { state.selected_field().map(|f| f.id) } else { None };
+    let live = |state: &PopupState, id: FieldId| -> String {
+        if editing == Some(id) {
+            state.edit_buffer.clone()
+        } else {
+            state.get_text(id).unwrap_or("").to_string()
+        }
+    };
+    let mut patterns = state.get_editable_list_items(EDITOR_FIELD_PATTERNS);
+    if editing == Some(EDITOR_FIELD_PATTERNS) {
+        if let Some(FieldKind::EditableList { selected_index, .. }) = state.field(EDITOR_FIELD_PATTERNS).map(|f| &f.kind) {
+            if let Some(item) = patterns.get_mut(*selected_index) {
+                *item = state.edit_buffer.clone();
+            }
+        }
+    }
+    let match_type = MatchType::parse(state.get_selected(EDITOR_FIELD_MATCH_TYPE).unwrap_or("regexp"));
+    let command = live(state, EDITOR_FIELD_COMMAND);
+    let sample = live(state, EDITOR_FIELD_TEST);
+    let result = test_patterns(&patterns, match_type, &command, &sample);
+    let text = match &result {
+        Ok(_) if sample.is_empty() => String::new(),
+        _ => describe_pattern_test(&result),
+    };
+    if let Some(field) = state.field_mut(EDITOR_FIELD_TEST_RESULT) {
+        field.kind = FieldKind::label(text);
+    }
+    result.err()
+}
+
 /// Filter actions based on filter text
 pub fn filter_actions(all_actions: &[ActionInfo], filter: &str) -> Vec<ActionInfo> {
     if filter.is_empty() {
@@ -315,7 +378,6 @@ pub fn filter_actions(all_actions: &[ActionInfo], filter: &str) -> Vec<ActionInf
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::popup::PopupState;
 
     fn sample_actions() -> Vec<ActionInfo> {
         vec![
@@ -344,6 +406,9 @@ mod tests {
 
         assert_eq!(state.definition.id, PopupId("actions_list"));
         assert_eq!(state.definition.title, "Actions");
+        assert!(state.definition.buttons.iter().any(|b| b.id == ACTIONS_BTN_IMPORT && b.shortcut == Some('I')));
+        assert!(state.definition.buttons.iter().any(|b| b.id == ACTIONS_BTN_EXPORT && b.shortcut == Some('X')));
+        assert!(state.definition.buttons.iter().any(|b| b.id == ACTIONS_BTN_DUPLICATE && b.shortcut == Some('U')));
     }
 
     #[test]
@@ -405,4 +470,24 @@ mod tests {
         let filtered = filter_actions(&actions, "");
         assert_eq!(filtered.len(), 2);
     }
+
+    #[test]
+    fn test_action_editor_tester() {
+        let settings = ActionSettings {
+            name: "tell".to_string(),
+            match_type: "regexp".to_string(),
+            patterns: vec!["^(\\w+) tells you".to_string()],
+            command: "/gag".to_string(),
+            enabled: true,
+            ..Default::default()
+        };
+        let mut state = PopupState::new(create_action_editor_popup(&settings, false));
+        state.set_text(EDITOR_FIELD_TEST, "Bob tells you hi".to_string());
+        assert_eq!(refresh_action_test(&mut state), None);
+        let verdict = state.field(EDITOR_FIELD_TEST_RESULT).map(|f| f.kind.clone());
+        assert!(matches!(verdict, Some(FieldKind::Label { ref text }) if text.contains("$1=\"Bob\"") && text.ends_with("(gagged)")));
+
+        state.set_text(EDITOR_FIELD_PATTERNS, "(oops".to_string());
+        assert!(refresh_action_test(&mut state).is_some_and(|e| e.starts_with("Pattern 1:")));
+    }
 }
@@ -80,7 +80,7 @@ pub const CERT_MISMATCH_NEW_FP: &str = "cert_mismatch_new_fp";
 
 /// Create a TLS certificate pin-mismatch warning dialog (trust-on-first-use).
 /// Shown when a world's TLS certificate no longer matches the fingerprint
-/// pinned in `~/.clay/known_hosts.dat`. "Yes" trusts the new certificate
+/// pinned in `~/.config/clay/known_hosts.dat`. "Yes" trusts the new certificate
 /// (replaces the pin) and reconnects; "No" leaves the old pin in place and the
 /// connection blocked.
 pub fn create_cert_mismatch_dialog(
@@ -144,6 +144,23 @@ pub fn create_import_reload_dialog(summary: &str) -> PopupDefinition {
     def
 }
 
+// Marker custom_data key for the quit confirmation — the connected worlds are only shown
+pub const QUIT_CONFIRM: &str = "quit_confirm";
+
+/// Create the dialog /quit and double Ctrl+C show while worlds are connected (turned off
+/// with Confirm Quit in /setup). "Yes" quits, closing the listed connections.
+pub fn create_quit_dialog(connected: &[String]) -> PopupDefinition {
+    let message = format!(
+        "Quit and disconnect {} world{}?\n\n{}",
+        connected.len(),
+        if connected.len() == 1 { "" } else { "s" },
+        connected.join(", ")
+    );
+    let mut def = create_confirm_dialog("quit_confirm", "Confirm Quit", &message);
+    def.custom_data.insert(QUIT_CONFIRM.to_string(), "1".to_string());
+    def
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,4 +204,17 @@ mod tests {
             panic!("Expected message field");
         }
     }
+
+    #[test]
+    fn test_quit_dialog_lists_worlds() {
+        let def = create_quit_dialog(&["Alpha".to_string(), "Beta".to_string()]);
+        assert!(def.custom_data.contains_key(QUIT_CONFIRM));
+        match def.get_field(CONFIRM_FIELD_MESSAGE).map(|f| &f.kind) {
+            Some(FieldKind::Label { text }) => {
+                assert!(text.starts_with("Quit and disconnect 2 worlds?"));
+                assert!(text.contains("Alpha, Beta"));
+            }
+            _ => panic!("Expected message label"),
+        }
+    }
 }
@@ -45,11 +45,10 @@ pub fn format_elapsed(secs: Option<u64>) -> String {
     }
 }
 
-/// Format time until next NOP (based only on last send time)
-pub fn format_next_nop(last_send_secs: Option<u64>, _last_recv_secs: Option<u64>) -> String {
-    const KEEPALIVE_SECS: u64 = 5 * 60;
-    let elapsed = last_send_secs.unwrap_or(KEEPALIVE_SECS);
-    let remaining = KEEPALIVE_SECS.saturating_sub(elapsed);
+/// Format time until next NOP (based only on last send time and the world's interval)
+pub fn format_next_nop(last_send_secs: Option<u64>, _last_recv_secs: Option<u64>, interval_secs: u64) -> String {
+    let elapsed = last_send_secs.unwrap_or(interval_secs);
+    let remaining = interval_secs.saturating_sub(elapsed);
     if remaining < 60 {
         format!("{}s", remaining)
     } else {
@@ -0,0 +1,116 @@
+//! Device tokens popup definition (/web tokens)
+//!
+//! Lists the per-device sign-in keys by name with when each was created and last
+//! used. Revoke cuts one device off without touching the others. A newly created
+//! token's key is shown here once, for copying onto the device; it is never listed
+//! again.
+
+use crate::popup::{
+    Button, ButtonId, Field, FieldId, FieldKind, ListItem, ListItemStyle,
+    PopupDefinition, PopupId, PopupLayout,
+};
+use crate::websocket::{DeviceTokenInfo, NewDeviceToken};
+
+// Field IDs
+pub const TOKENS_FIELD_LIST: FieldId = FieldId(1);
+pub const TOKENS_FIELD_NEW_KEY: FieldId = FieldId(2);
+
+// Button IDs
+pub const TOKENS_BTN_REVOKE: ButtonId = ButtonId(1);
+pub const TOKENS_BTN_COPY: ButtonId = ButtonId(2);
+pub const TOKENS_BTN_CLOSE: ButtonId = ButtonId(3);
+
+/// Create the device tokens popup. `new_token` is the one just created, if any.
+pub fn create_device_tokens_popup(tokens: &[DeviceTokenInfo], new_token: Option<&NewDeviceToken>, visible_height: usize) -> PopupDefinition {
+    let items: Vec<ListItem> = tokens
+        .iter()
+        .map(|token| ListItem {
+            id: token.name.clone(),
+            columns: vec![
+                token.name.clone(),
+                format_day(token.created_at),
+                token.last_used.map_or_else(|| "never".to_string(), format_day),
+            ],
+            style: ListItemStyle {
+                is_current: new_token.is_some_and(|t| t.name == token.name),
+                is_connected: false,
+                is_disabled: false,
+            },
+        })
+        .collect();
+    let name_width = items.iter().map(|i| i.columns[0].chars().count()).max().unwrap_or(0).max(6);
+
+    let mut def = PopupDefinition::new(PopupId("device_tokens"), "Device Tokens");
+    if items.is_empty() {
+        def = def.with_field(Field::new(
+            TOKENS_FIELD_LIST,
+            "",
+            FieldKind::label("No device tokens. Add one with /web tokens add <name>."),
+        ));
+    } else {
+        def = def.with_field(Field::new(
+            TOKENS_FIELD_LIST,
+            "",
+            FieldKind::list_with_headers_and_widths(items, visible_height, &["Device", "Created", "Last used"], vec![name_width, 10, 10]),
+        ));
+    }
+    if let Some(token) = new_token {
+        def = def
+            .with_field(Field::new(TOKENS_FIELD_NEW_KEY, "New key", FieldKind::text(token.token.clone())).disabled())
+            .with_button(Button::new(TOKENS_BTN_COPY, "Copy").with_shortcut('P'));
+    }
+    def.with_button(Button::new(TOKENS_BTN_REVOKE, "Revoke").danger().with_shortcut('R'))
+        .with_button(Button::new(TOKENS_BTN_CLOSE, "Close").primary().with_shortcut('C'))
+        .with_layout(PopupLayout {
+            label_width: 10,
+            min_width: 50,
+            max_width_percent: 90,
+            center_horizontal: true,
+            center_vertical: true,
+            modal: true,
+            buttons_right_align: true,
+            blank_line_before_list: false,
+            tab_buttons_only: false,
+            anchor_bottom_left: false,
+            anchor_x: 0,
+        })
+}
+
+/// The new key shown in the popup, if one was just created
+pub fn new_token_key(state: &crate::popup::PopupState) -> Option<String> {
+    state.get_text(TOKENS_FIELD_NEW_KEY).map(str::to_string)
+}
+
+/// A Unix timestamp as its local date
+fn format_day(secs: u64) -> String {
+    crate::util::format_local_time(&crate::util::local_time_from_epoch(secs as i64), "%Y-%m-%d")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::popup::PopupState;
+
+    #[test]
+    fn test_device_tokens_popup() {
+        let tokens = vec![
+            DeviceTokenInfo { name: "phone".to_string(), created_at: 1_700_000_000, last_used: None },
+            DeviceTokenInfo { name: "laptop".to_string(), created_at: 0, last_used: Some(951_782_400) },
+        ];
+        let new = NewDeviceToken { name: "phone".to_string(), token: "abc123".to_string() };
+        let state = PopupState::new(create_device_tokens_popup(&tokens, Some(&new), 5));
+        if let Some(FieldKind::List { items, .. }) = state.field(TOKENS_FIELD_LIST).map(|f| &f.kind) {
+            assert_eq!(items[0].columns[0], "phone");
+            assert_eq!(items[0].columns[2], "never");
+            assert_eq!(items[1].columns[2].len(), "2000-02-29".len());
+            assert!(items[0].style.is_current && !items[1].style.is_current);
+        } else {
+            panic!("TOKENS_FIELD_LIST is not a List field");
+        }
+        assert_eq!(new_token_key(&state).as_deref(), Some("abc123"));
+
+        let state = PopupState::new(create_device_tokens_popup(&tokens, None, 5));
+        assert_eq!(new_token_key(&state), None);
+        assert!(!state.definition.buttons.iter().any(|b| b.id == TOKENS_BTN_COPY));
+    }
+}
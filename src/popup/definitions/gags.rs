@@ -0,0 +1,99 @@
+//! Gags popup definition
+//!
+//! Lists the managed gags (see gags.rs), letting the user enable/disable them,
+//! keep a temporary gag for good, or delete them. Add starts a /gag command.
+
+use crate::popup::{
+    Button, ButtonId, Field, FieldId, FieldKind, ListItem, ListItemStyle,
+    PopupDefinition, PopupId, PopupLayout,
+};
+
+// Field IDs
+pub const GAGS_FIELD_LIST: FieldId = FieldId(1);
+
+// Button IDs
+pub const GAGS_BTN_ADD: ButtonId = ButtonId(1);
+pub const GAGS_BTN_TOGGLE: ButtonId = ButtonId(2);
+pub const GAGS_BTN_KEEP: ButtonId = ButtonId(3);
+pub const GAGS_BTN_DELETE: ButtonId = ButtonId(4);
+pub const GAGS_BTN_CLOSE: ButtonId = ButtonId(5);
+
+/// Column headers for the gags list
+pub const GAGS_HEADERS: &[&str] = &["On", "World", "Kept", "Pattern"];
+
+/// Create the gags popup definition. List item ids are the gag's index.
+pub fn create_gags_popup(gags: &[crate::gags::Gag], visible_height: usize) -> PopupDefinition {
+    let items: Vec<ListItem> = gags
+        .iter()
+        .enumerate()
+        .map(|(i, g)| {
+            #[cfg(not(windows))]
+            let status = if g.enabled { "[✓]" } else { "[ ]" };
+            #[cfg(windows)]
+            let status = if g.enabled { "[x]" } else { "[ ]" };
+            let world = if g.world.is_empty() { "(all)".to_string() } else { g.world.clone() };
+            let kept = if g.temporary { "session" } else { "saved" };
+            ListItem {
+                id: i.to_string(),
+                columns: vec![status.to_string(), world, kept.to_string(), g.pattern.clone()],
+                style: ListItemStyle {
+                    is_current: false,
+                    is_connected: false,
+                    is_disabled: !g.enabled,
+                },
+            }
+        })
+        .collect();
+
+    // Calculate column widths from headers and all items
+    let num_columns = GAGS_HEADERS.len();
+    let mut column_widths: Vec<usize> = GAGS_HEADERS.iter().map(|h| h.len()).collect();
+    for item in &items {
+        for (i, col) in item.columns.iter().enumerate() {
+            if i < num_columns {
+                column_widths[i] = column_widths[i].max(col.chars().count());
+            }
+        }
+    }
+
+    PopupDefinition::new(PopupId("gags"), "Gags")
+        .with_field(Field::new(
+            GAGS_FIELD_LIST,
+            "",
+            FieldKind::list_with_headers_and_widths(items, visible_height, GAGS_HEADERS, column_widths),
+        ))
+        .with_button(Button::new(GAGS_BTN_ADD, "Add").with_shortcut('A'))
+        .with_button(Button::new(GAGS_BTN_TOGGLE, "Toggle").with_shortcut('T'))
+        .with_button(Button::new(GAGS_BTN_KEEP, "Keep").with_shortcut('K'))
+        .with_button(Button::new(GAGS_BTN_DELETE, "Delete").with_shortcut('D'))
+        .with_button(Button::new(GAGS_BTN_CLOSE, "Close").primary().with_shortcut('C'))
+        .with_layout(PopupLayout {
+            label_width: 8,
+            min_width: 50,
+            max_width_percent: 80,
+            center_horizontal: true,
+            center_vertical: false,
+            modal: true,
+            buttons_right_align: true,
+            blank_line_before_list: true,
+            tab_buttons_only: false,
+            anchor_bottom_left: false,
+            anchor_x: 0,
+        })
+        .with_help(vec![
+            "Gags".to_string(),
+            "".to_string(),
+            "Hides lines matching a wildcard pattern".to_string(),
+            "(* and ?, whole line, any case).".to_string(),
+            "".to_string(),
+            "A         Add a gag (starts /gag)".to_string(),
+            "Space/T   Enable or disable selected gag".to_string(),
+            "K         Save a session gag, or make a".to_string(),
+            "          saved one session-only".to_string(),
+            "Del/D     Delete selected gag".to_string(),
+            "".to_string(),
+            "Add gags with:".to_string(),
+            "  /gag [-w<world>] [-t] <pattern>".to_string(),
+            "-t makes a session gag, forgotten on exit.".to_string(),
+        ])
+}
@@ -62,8 +62,10 @@ pub fn get_topic_help(topic: &str) -> Option<Vec<String>> {
         ],
         "disconnect" | "dc" => vec![
             "/disconnect (or /dc)       Disconnect current world",
+            "/disconnect -a             Disconnect every world",
             "",
             "Closes the connection and log file for the current world.",
+            "With -a, closes every connected world and says how many.",
         ],
         "connections" | "l" => vec![
             "/connections (or /l)       List connected worlds",
@@ -77,6 +79,17 @@ pub fn get_topic_help(topic: &str) -> Option<Vec<String>> {
             "/connect <host> <port>     Attach to a remote Clay server",
             "/connect --close           Detach and become an independent master",
             "/connect --cancel          Cancel a pending confirmation",
+            "/connect telnet://host:port  Open a MUD link (also mud://)",
+            "/connect -a                Connect every world with auto-login on",
+            "",
+            "-a connects each configured world whose Auto Login isn't None",
+            "and, once they have all finished, reports how many connected",
+            "and which failed.",
+            "",
+            "A telnet:// or mud:// link connects the world for that host and",
+            "port, adding one named after the host if there is none. Add",
+            "?tls=1 to the link to connect with TLS. Links also work as a",
+            "command-line argument: clay telnet://host:port",
             "",
             "Relaunches this process as a remote client of the given Clay",
             "server (like starting with --console=host:port / --gui=host:port).",
@@ -96,10 +109,22 @@ pub fn get_topic_help(topic: &str) -> Option<Vec<String>> {
             "  No flags: Send to current world",
         ],
         "notify" => vec![
-            "/notify <message>",
+            "/notify [-s<sound>] <message>",
+            "/notify -w[<world>] [mute|unmute]",
+            "/notify -w[<world>] sound [<file>|off]",
             "",
-            "Send a push notification to the Android app.",
+            "Send a notification to the Android app, web clients and",
+            "the console's desktop.",
             "Can be used in action commands: /notify Page from $1",
+            "  -w[<world>]  Show, mute or unmute a world's notifications",
+            "  sound        Sound file played on the world's activity or",
+            "               a /notify while the window or terminal is",
+            "               unfocused (a URL in a browser)",
+            "  -s<sound>    Play this sound instead of the world's",
+            "  No args:     Show the Notify Activity setting and muted worlds",
+            "",
+            "With Notify Activity on in /setup, the console notifies the",
+            "first new line in each world while its terminal is unfocused.",
         ],
         "actions" => vec![
             "/actions [world]           Open actions editor",
@@ -107,27 +132,58 @@ pub fn get_topic_help(topic: &str) -> Option<Vec<String>> {
             "Actions match incoming MUD output and execute commands.",
             "  Pattern: regex or wildcard (empty = manual-only)",
             "  Command: semicolon-separated, $1-$9 for captures",
+            "  $[3d6+2] in a command rolls dice or does arithmetic",
             "  /gag in commands hides matched line",
+            "  /pipe <program> sends it the matched line (/help pipe)",
             "  Enable 'Startup' to run on Clay start/reload",
-        ],
-        "gag" => vec![
-            "/gag <pattern>             Gag lines matching pattern",
             "",
-            "Creates a gag action that hides lines matching the",
-            "regex pattern. Gagged lines are still visible with F2.",
+            "/actions export <file> [world]  Save actions (those",
+            "                           for the world) as a trigger pack;",
+            "                           TOML for .toml, JSON otherwise",
+            "/actions import <file>     Merge a trigger pack (same-named",
+            "                           actions are replaced)",
+            "/trigger test [-w<world>] \"<line>\"  Show which actions",
+            "                           the line matches, their captures",
+            "                           and commands, without running them",
         ],
         "setup" => vec![
             "/setup                     Open global settings",
             "",
             "Settings: more mode, spell check, temp convert,",
             "world switching, show tags, input height, themes,",
-            "mouse, ZWJ, ANSI music, TLS proxy",
+            "mouse, ZWJ, ANSI music, TLS proxy, status bar,",
+            "confirm quit",
+            "",
+            "Status Bar is a template for the console status line,",
+            "e.g. %status %conn%world%tags %activity%fill%time",
+            "  %status   More/Hist count    %conn     ● ball",
+            "  %world    world name         %state    connected?",
+            "  %tags     [tag] [g]          %activity worlds w/ output",
+            "  %unseen   unseen lines       %keepalive time to next",
+            "  %capture  [chat:N] routes    %prompt   MUD prompt",
+            "  %profile  active profile     %search   search/copy",
+            "  %sending  /sendfile progress",
+            "  %time     clock              %fill     underscores",
+            "  %worlds   per world, e.g. mume(12) ach(3*)",
+            "            (* = more-mode lines waiting for Alt-w)",
+            "%% is a literal %. Empty = the built-in layout.",
         ],
         "web" => vec![
             "/web                       Open web/WebSocket settings",
+            "/web tokens                List device tokens",
+            "/web tokens add <name>     Issue a key for one device",
+            "/web tokens revoke <name>  Cut that device off",
+            "/web bans                  Bans, strikes and limits",
+            "/web viewer <pw>|off       View-only sign-in password",
+            "/web acme <domain>|renew|off  Let's Encrypt certificate",
+            "/web push on|off           Push activity to this browser",
+            "/web push [clear]          Browsers getting pushes",
             "",
             "Configure WebSocket (ws/wss), HTTP/HTTPS servers,",
             "TLS certificates, passwords, and allow lists.",
+            "A device token is entered as the device's auth key;",
+            "its key is shown once, when it's added.",
+            "A viewer sees output but can't send or change anything.",
         ],
         "menu" => vec![
             "/menu                      Open menu popup",
@@ -170,16 +226,30 @@ pub fn get_topic_help(topic: &str) -> Option<Vec<String>> {
             "",
             "Saves state, exec()s new binary, restores state.",
             "TCP connections are preserved (TLS needs proxy).",
-            "Also: Ctrl+R or kill -USR1 $(pgrep clay)",
+            "Also: Alt+Shift+R or kill -USR1 $(pgrep clay)",
+        ],
+        "detach" => vec![
+            "/detach                    Leave session in background",
+            "",
+            "Hands the connections to a background Clay and",
+            "exits the console; 'clay --attach' reconnects.",
+            "Needs the web server enabled with a password.",
+            "Also happens when the terminal hangs up (SSH).",
         ],
         "quit" => vec![
             "/quit                      Exit the client",
+            "",
+            "With worlds connected, /quit and a double Ctrl+C first",
+            "ask to confirm, listing them. Turn this off with",
+            "Confirm Quit in /setup.",
         ],
         "ban" => vec![
             "/ban                       Show banned hosts",
             "",
             "Lists all hosts currently banned from connecting",
             "to the WebSocket server.",
+            "Five failed sign-ins ban a host for 15 minutes;",
+            "/web bans also shows hosts collecting strikes.",
         ],
         "unban" => vec![
             "/unban <host>              Remove host from ban list",
@@ -194,6 +264,257 @@ pub fn get_topic_help(topic: &str) -> Option<Vec<String>> {
             "",
             "Shows/hides MUD tags and timestamps on lines.",
             "Tags: [name:] or [name(content)] at line start.",
+            "See /help tagroute to route tagged lines.",
+        ],
+        "macro" | "macros" => vec![
+            "/macro                     Open macros popup",
+            "/macro list                List this world's macros",
+            "/macro <key> <text>        Bind key to command/text",
+            "/macro <key>               Show key's macro",
+            "/macro -d <key>            Remove key's macro",
+            "/macro -w<world> ...       Operate on another world",
+            "",
+            "Keys: F1-F12 and Shift-F1..Shift-F12.",
+            "Example: /macro f5 \"cast heal\"",
+            "Macros are per world and override F-key bindings.",
+            "They fire in web and GUI clients too, where a bare",
+            "/macro lists them.",
+        ],
+        "tagroute" | "tagroutes" => vec![
+            "/tagroute                  Open tag routes popup",
+            "/tagroute list             List tag routes",
+            "/tagroute add [-w<world>] <tag> <action>",
+            "/tagroute del <n>          Delete route n",
+            "/tagroute on|off <n>       Enable/disable route n",
+            "",
+            "Routes lines by their MUD tag ([public:] -> public).",
+            "Actions: gag, gag-idle:MINS (gag when idle),",
+            "copy:WORLD, move:WORLD, hilite:COLOR.",
+            "Unknown target worlds are created connectionless.",
+        ],
+        "gag" | "gags" | "ungag" => vec![
+            "/gag                       Open gags popup",
+            "/gag list                  List gags",
+            "/gag [-w<world>] [-t] <pattern>",
+            "/gag on|off <n>            Enable/disable gag n",
+            "/ungag <n|pattern>         Remove a gag",
+            "",
+            "Hides lines matching a wildcard pattern (* and ?,",
+            "whole line, any case). -w alone: current world.",
+            "-t: temporary, forgotten when Clay exits.",
+            "Gagged lines are still visible with F2.",
+            "Example: /gag -t * has arrived.",
+        ],
+        "profile" | "profiles" => vec![
+            "/profile                   Show active profile and list",
+            "/profile add <name> <days> <times> [options]",
+            "/profile del <name>        Delete a profile",
+            "",
+            "Days: * or mon-fri, sat,sun, mon,wed-fri ...",
+            "Times: * (all day) or HH:MM-HH:MM (may wrap",
+            "midnight, e.g. 22:00-07:00). First match wins.",
+            "Options: notify=off  tts=off  gag=tag1,tag2",
+            "Example: /profile add work mon-fri 09:00-17:00",
+            "         notify=off gag=public,ooc",
+        ],
+        "config" => vec![
+            "/config profile            Show config profiles",
+            "",
+            "Config profiles are separate sets of worlds,",
+            "settings, logs and reload state, chosen at",
+            "startup with clay --profile=<name>. The one in",
+            "use is marked with *.",
+        ],
+        "numpad" => vec![
+            "/numpad                    Show speedwalk state and layout",
+            "/numpad on|off             Enable/disable for this world",
+            "/numpad -w<world> on|off   Operate on another world",
+            "",
+            "Keypad 1-9 send sw s se w look e nw n ne.",
+            "The digit row still types digits. In the console",
+            "this needs a terminal with the kitty keyboard",
+            "protocol (kitty, foot, WezTerm, Ghostty...).",
+        ],
+        "secret" | "secrets" => vec![
+            "/secret                    List secret names",
+            "/secret set <name> <value> Store a secret for this world",
+            "/secret set -g <name> <value>  ...for all worlds",
+            "/secret del [-g] <name>    Delete a secret",
+            "  -w<world>                Operate on another world",
+            "",
+            "World user, password and action commands may use",
+            "${secret:NAME} and ${env:VAR}; they are expanded",
+            "only when sent, so the saved text stays literal.",
+            "World secrets shadow global ones. Values are",
+            "encrypted in settings.dat and never displayed.",
+        ],
+        "loopguard" => vec![
+            "/loopguard                 Show the trigger loop limit",
+            "/loopguard <N>             Disable looping actions over",
+            "                           N fires/sec (default 20)",
+            "/loopguard on|off          Default limit / no watchdog",
+            "",
+            "When an action's commands make the MUD send output",
+            "that fires it again (directly or through other",
+            "actions) faster than the limit, every action in the",
+            "chain is disabled and the chain is reported.",
+        ],
+        "clipboard" | "copy" => vec![
+            "/clipboard                 Show how copy mode copies",
+            "/clipboard <command>       Pipe copied text to a command",
+            "/clipboard osc52           Use the terminal (default)",
+            "",
+            "Copy mode (F7): move with arrows/hjkl, w/b, 0/$,",
+            "g/G, PgUp/PgDn. Space or v starts a selection,",
+            "Enter or y copies it (or the cursor line) as plain",
+            "text, Esc or q leaves.",
+            "Example: /clipboard xclip -selection clipboard",
+        ],
+        "throttle" => vec![
+            "/throttle                  Show this world's priority",
+            "/throttle on|off           Mark this world low priority",
+            "/throttle -w<world> on|off Operate on another world",
+            "/throttle interval <ms>    Batch interval (0 = off)",
+            "",
+            "While nobody is viewing a low-priority world, its",
+            "output is processed in batches (default every",
+            "1000 ms), so its triggers run up to one interval",
+            "late and the world you're playing stays responsive.",
+        ],
+        "urls" => vec![
+            "/urls                      List recent URLs in output",
+            "",
+            "Newest first. Enter or O opens the selected URL in",
+            "the browser (xdg-open/open/start), P copies it and",
+            "S sends it to the MUD. URLs in console output are",
+            "also OSC 8 hyperlinks, clickable in terminals that",
+            "support them. In the web and GUI clients /urls prints",
+            "the list as links; right-click a link in the output",
+            "to open, copy or send it.",
+        ],
+        "capture" => vec![
+            "/capture                   List capture buffers",
+            "/capture clear <name>      Empty a capture buffer",
+            "/capture pane <name>|off   Show a capture below output",
+            "/capture height <rows>     Pane height (2-30, def. 8)",
+            "/capture view [<name>]     Switch to a capture / back",
+            "",
+            "Put /capture <name> in an action's commands to copy",
+            "each matching line into that buffer; add /gag to",
+            "take it out of the world. Buffers are worlds without",
+            "a connection, so the web and GUI list them too.",
+            "Example: action pattern '* tells you*' with",
+            "commands '/capture tells; /gag'",
+        ],
+        "split" => vec![
+            "/split <world>             Show a world alongside",
+            "/split -v <world>          ... side by side",
+            "/split -h|-v               Stack or side by side",
+            "/split focus               Focus next pane (Alt+O)",
+            "/split close [<world>]     Close a pane",
+            "/split off                 Back to one world",
+            "/split                     Show the panes",
+            "",
+            "Up to 4 worlds at once. The focused pane is the",
+            "current world: input, scrolling and more-mode act",
+            "on it, and each world keeps its own scroll position.",
+            "Switching worlds shows the new world in the focused",
+            "pane. Console only; in the browser open a second",
+            "window.",
+        ],
+        "timestamps" => vec![
+            "/timestamps                Show the settings",
+            "/timestamps off|hm|hms     Set the global prefix",
+            "/timestamps -w[<world>] off|hm|hms|global",
+            "                           Set a world's (this one",
+            "                           without a name)",
+            "",
+            "Prefixes each displayed line with the time it",
+            "arrived, HH:MM (hm) or HH:MM:SS (hms), in grey.",
+            "Stored lines and logs are unchanged. Alt+T",
+            "(toggle_timestamps) hides them, or shows HH:MM",
+            "where none are set, for this session.",
+        ],
+        "log" => vec![
+            "/log [-w[<world>]] [status]  Where a world is",
+            "                           logging to",
+            "/log start [<file>]        Start logging, to the",
+            "                           dated file or to <file>",
+            "/log stop                  Stop logging",
+            "/log flush                 Write out buffered lines",
+            "",
+            "Start and stop last until the world disconnects;",
+            "after that the world editor's Log File setting",
+            "applies again. <file> is relative to the logs",
+            "directory unless absolute or ~/. TF's /log <file>",
+            "and /log off work too.",
+        ],
+        "lock" | "unlock" => vec![
+            "/lock set <passphrase>     Set or change the master",
+            "                           passphrase",
+            "/lock off                  Remove it",
+            "/lock                      Lock world credentials",
+            "/unlock <passphrase>       Unlock them",
+            "",
+            "With a master passphrase, world passwords, Slack",
+            "and Discord tokens and /secret values are",
+            "encrypted with a key derived from it (Argon2id),",
+            "and Clay asks for it at startup (Enter starts",
+            "locked). Locked credentials aren't sent on",
+            "login; they're kept as stored until /unlock.",
+        ],
+        "export" | "import" => vec![
+            "/export <file> [passphrase]  Save worlds, actions",
+            "                           and keybindings to a file",
+            "/import <file> [passphrase]  Merge such a file in",
+            "/import [host[:port]]      Pull settings from another",
+            "                           running Clay instance",
+            "",
+            "Without a passphrase passwords and tokens are left",
+            "out; with one they're encrypted with it, and the",
+            "same passphrase is needed to import them. Imported",
+            "worlds and actions replace local ones of the same",
+            "name. <file> is relative to your home directory",
+            "unless absolute; /import takes it as a file when",
+            "it has a / or ends in .toml, .tfrc, .tf or .xml.",
+            "",
+            "/import ~/.tfrc turns a TinyFugue config's",
+            "/addworld, /def -t, /trig, /gag, /hilite and",
+            "/alias lines into worlds and actions, and lists",
+            "the lines it couldn't translate.",
+            "",
+            "A Mudlet package or MUSHclient triggers/aliases",
+            "file (.xml) imports the same way: triggers become",
+            "actions with regex patterns, highlights and gags",
+            "become /highlight and /gag, aliases /name actions.",
+        ],
+        "replay" => vec![
+            "/replay [-w[<world>]] <file> [speed]",
+            "                           Show a log in this (or",
+            "                           the named) world",
+            "/replay stop               Stop the world's replay",
+            "",
+            "Lines go through actions as if the server sent",
+            "them, so gags and highlights apply, but they",
+            "aren't logged again and actions send nothing.",
+            "Without a speed the whole log is shown at once;",
+            "with one (e.g. 2 or 0.5x) its line times pace it,",
+            "pauses cut to 10 seconds. <file> is relative to",
+            "the logs directory unless absolute or ~/.",
+        ],
+        "bell" => vec![
+            "/bell                      List each world's mode",
+            "/bell [-w[<world>]] ignore|flash|ring|notify",
+            "                           Set this world's (or the",
+            "                           named world's) mode",
+            "",
+            "What a bell (Ctrl-G) in a world's output does:",
+            "  ignore  Nothing (the default)",
+            "  flash   Invert the status bar for a moment",
+            "  ring    Ring the terminal's (or browser's) bell",
+            "  notify  Send a notification, as /notify does",
+            "Bells are counted until the world is viewed; the",
+            "/worlds popup shows the count.",
         ],
         "dict" => vec![
             "/dict <word>",
@@ -264,6 +585,118 @@ pub fn get_topic_help(topic: &str) -> Option<Vec<String>> {
             "height window with a live filter — type a regex to show",
             "only matching lines from the current world.",
         ],
+        "localecho" => vec![
+            "/localecho [-w[<world>]] [on|off]  Echo sent lines",
+            "",
+            "Shows each line you send to the world in its output,",
+            "dim italic. Off by default. Not shown at a password",
+            "prompt or when the line is the world's password.",
+        ],
+        "emoji" => vec![
+            "/emoji [-w[<world>]] [on|off]  Expand emoji shortcodes",
+            "",
+            "Turns :name: shortcodes in the lines you type to the",
+            "world into emoji, e.g. :smile: or :thumbsup:. Unknown",
+            "names are sent as typed. While on, Tab after :na",
+            "completes the name; Tab again cycles the matches.",
+            "Off by default.",
+        ],
+        "addword" => vec![
+            "/addword <word>            Add to your dictionary",
+            "",
+            "Stops spell check flagging a word, e.g. a character",
+            "name or MUD jargon. Words are kept one per line in",
+            "~/.config/clay/personal.dict. Esc-q adds the word at",
+            "the cursor, also while Ctrl+Q suggestions show.",
+            "Esc-s opens a menu of numbered suggestions instead:",
+            "a digit or Enter picks one, A adds, I ignores.",
+        ],
+        "spelllang" => vec![
+            "/spelllang [-w[<world>]] [<language>|off]  Spell language",
+            "",
+            "Spell checks input to the world with a Hunspell",
+            "dictionary, e.g. /spelllang de_DE for de_DE.dic and",
+            "de_DE.aff from /usr/share/hunspell, ~/.config/clay/dict/",
+            "or the Dictionary Path's directory. off goes back to",
+            "the global dictionary. Without a language, shows the",
+            "world's and the installed ones.",
+        ],
+        "spellignore" | "unignore" => vec![
+            "/spellignore [<word>]      Never flag a word",
+            "/unignore <word>           Spell check it again",
+            "",
+            "Ignored words are kept apart from your dictionary, in",
+            "~/.config/clay/spell_ignore.txt, so suggestions never",
+            "offer them. Esc-i ignores the word at the cursor until",
+            "Clay exits, also while Ctrl+Q suggestions show. Without",
+            "a word, /spellignore lists the ignored ones.",
+        ],
+        "autocorrect" => vec![
+            "/autocorrect [-w[<world>]] [on|off]  Correct as you type",
+            "",
+            "When a space follows a misspelled word, it's replaced",
+            "with the top spell suggestion if that one is close and",
+            "clear (\"thier \" becomes \"their \"). Esc-r puts back the",
+            "word you typed and leaves it alone. Command lines and",
+            "passwords aren't corrected. Off by default, per world.",
+        ],
+        "palette" => vec![
+            "/palette [-w[<world>]] [<name>|off]  Remap ANSI colors",
+            "",
+            "Shows the world's output with the 16 base ANSI colors",
+            "taken from a palette (solarized and gruvbox are built",
+            "in), so a low-contrast color scheme becomes readable.",
+            "Add palettes as [palette:name] sections with ansi.0",
+            "to ansi.15 in ~/.config/clay/theme.dat. Without a name,",
+            "shows the world's palette and the ones available.",
+        ],
+        "tint" => vec![
+            "/tint [-w[<world>]] [#rrggbb|off]  Output background",
+            "",
+            "Paints a background color behind the world's output",
+            "wherever the MUD doesn't set one with ANSI codes, for",
+            "games whose color-coded screens assume a dark blue or",
+            "black backdrop. Without a color, shows the world's tint.",
+        ],
+        "images" => vec![
+            "/images [-w[<world>]] [on|off]  Inline images",
+            "",
+            "Web and GUI clients show image links (.png, .jpg, .gif,",
+            ".webp) in the world's output as pictures below the line,",
+            "capped in size; click one to open it. Off by default, since",
+            "showing one means fetching it from its server. The console",
+            "keeps showing the links.",
+        ],
+        "sendfile" => vec![
+            "/sendfile [-w[<world>]] <file>  Send a file line by line",
+            "/sendfile [-w[<world>]] abort   Stop sending it",
+            "",
+            "Sends one line every 100 ms, e.g. MUSH softcode or a",
+            "mail draft. Relative paths are from your home directory.",
+            "Progress shows in the status bar; a disconnect stops it.",
+        ],
+        "shell" | "pipe" => vec![
+            "/shell <command>           Run a program, show its output",
+            "/pipe <command>            Run a program, send its output",
+            "",
+            "Runs under sh -c (cmd /C on Windows) in the background.",
+            "Stderr and a failing exit status are always shown, and",
+            "a program still running after 60s is stopped. As an",
+            "action command, /pipe <program> gets the matched line",
+            "on stdin; $1-$9 are not substituted into the program.",
+            "In an action's /shell command, $1-$9 are each quoted",
+            "as one word, so the shell doesn't interpret them.",
+        ],
+        "calc" | "roll" => vec![
+            "/calc [-s] <expr>          Evaluate arithmetic",
+            "/roll [-s] <dice>          Roll dice",
+            "",
+            "Shows the result locally; -s sends it to the world.",
+            "  + - * / % ^ and parentheses, e.g. 2*(3+4)",
+            "  NdM rolls N M-sided dice; d% is a d100",
+            "  khK / klK keeps the K highest or lowest: 4d6kh3",
+            "In action commands $[expr] becomes its value.",
+        ],
         "say" => vec![
             "/say <text>                Speak text aloud (TTS)",
             "",
@@ -0,0 +1,76 @@
+//! Macros popup definition
+//!
+//! Lists the current world's function key macros (/macro). Add/Edit drop a
+//! `/macro` command into the input line for editing; Delete removes the macro.
+
+use crate::popup::{
+    Button, ButtonId, Field, FieldId, FieldKind, ListItem, ListItemStyle,
+    PopupDefinition, PopupId, PopupLayout,
+};
+
+// Field IDs
+pub const MACROS_FIELD_LIST: FieldId = FieldId(1);
+
+// Button IDs
+pub const MACROS_BTN_ADD: ButtonId = ButtonId(1);
+pub const MACROS_BTN_EDIT: ButtonId = ButtonId(2);
+pub const MACROS_BTN_DELETE: ButtonId = ButtonId(3);
+pub const MACROS_BTN_CLOSE: ButtonId = ButtonId(4);
+
+/// Column headers for the macros list
+pub const MACROS_HEADERS: &[&str] = &["Key", "Command"];
+
+/// Create the macros popup definition. List item ids are the macro key names.
+pub fn create_macros_popup(
+    world_name: &str,
+    macros: &std::collections::BTreeMap<String, String>,
+    visible_height: usize,
+) -> PopupDefinition {
+    let items: Vec<ListItem> = macros
+        .iter()
+        .map(|(key, text)| ListItem {
+            id: key.clone(),
+            columns: vec![key.clone(), text.clone()],
+            style: ListItemStyle {
+                is_current: false,
+                is_connected: false,
+                is_disabled: false,
+            },
+        })
+        .collect();
+
+    // Calculate column widths from headers and all items
+    let num_columns = MACROS_HEADERS.len();
+    let mut column_widths: Vec<usize> = MACROS_HEADERS.iter().map(|h| h.len()).collect();
+    for item in &items {
+        for (i, col) in item.columns.iter().enumerate() {
+            if i < num_columns {
+                column_widths[i] = column_widths[i].max(col.chars().count());
+            }
+        }
+    }
+
+    PopupDefinition::new(PopupId("macros"), format!("Macros: {}", world_name))
+        .with_field(Field::new(
+            MACROS_FIELD_LIST,
+            "",
+            FieldKind::list_with_headers_and_widths(items, visible_height, MACROS_HEADERS, column_widths),
+        ))
+        .with_button(Button::new(MACROS_BTN_ADD, "Add").with_shortcut('A'))
+        .with_button(Button::new(MACROS_BTN_EDIT, "Edit").with_shortcut('E'))
+        .with_button(Button::new(MACROS_BTN_DELETE, "Delete").with_shortcut('D'))
+        .with_button(Button::new(MACROS_BTN_CLOSE, "Close").primary().with_shortcut('C'))
+        .with_layout(PopupLayout {
+            label_width: 8,
+            min_width: 50,
+            max_width_percent: 80,
+            center_horizontal: true,
+            center_vertical: false,
+            modal: true,
+            buttons_right_align: true,
+            blank_line_before_list: true,
+            tab_buttons_only: false,
+            anchor_bottom_left: false,
+            anchor_x: 0,
+        })
+}
@@ -6,13 +6,19 @@
 pub mod actions;
 pub mod confirm;
 pub mod connections;
+pub mod device_tokens;
 pub mod filter;
+pub mod gags;
 pub mod help;
 pub mod import;
+pub mod macros;
 pub mod menu;
 pub mod modify_key;
 pub mod recent_worlds;
 pub mod setup;
+pub mod spell;
+pub mod tag_routes;
+pub mod urls;
 pub mod web;
 pub mod notes_list;
 pub mod world_editor;
@@ -21,14 +27,20 @@ pub mod world_selector;
 pub use actions::*;
 pub use confirm::*;
 pub use connections::*;
+pub use device_tokens::*;
 pub use filter::*;
+pub use gags::*;
 pub use help::*;
 pub use import::*;
+pub use macros::*;
 pub use menu::*;
 pub use modify_key::*;
 pub use notes_list::*;
 pub use recent_worlds::*;
 pub use setup::*;
+pub use spell::*;
+pub use tag_routes::*;
+pub use urls::*;
 pub use web::*;
 pub use world_editor::*;
 pub use world_selector::*;
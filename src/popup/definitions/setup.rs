@@ -28,6 +28,11 @@ pub const SETUP_FIELD_TTS_SPEAK_MODE: FieldId = FieldId(19);
 pub const SETUP_FIELD_SCROLLBACK: FieldId = FieldId(20);
 // Note: FieldId(21) was url_shortener, removed - /url now falls back across services automatically
 pub const SETUP_FIELD_WRAPSPACE: FieldId = FieldId(22);
+pub const SETUP_FIELD_STATUS_FORMAT: FieldId = FieldId(23);
+pub const SETUP_FIELD_TAB_BAR: FieldId = FieldId(24);
+pub const SETUP_FIELD_NOTIFY_ACTIVITY: FieldId = FieldId(25);
+pub const SETUP_FIELD_SCROLLBACK_LINES: FieldId = FieldId(26);
+pub const SETUP_FIELD_CONFIRM_QUIT: FieldId = FieldId(27);
 
 // Button IDs
 pub const SETUP_BTN_SAVE: ButtonId = ButtonId(1);
@@ -95,6 +100,11 @@ pub fn create_setup_popup(
     tts_speak_mode: &str,
     scrollback: bool,
     wrapspace: i64,
+    status_format: &str,
+    tab_bar: bool,
+    notify_activity: bool,
+    scrollback_lines: i64,
+    confirm_quit: bool,
 ) -> PopupDefinition {
     let world_switching_idx = if world_switching == "alphabetical" { 1 } else { 0 };
     let gui_theme_idx = if gui_theme == "light" { 1 } else { 0 };
@@ -192,11 +202,36 @@ pub fn create_setup_popup(
             "Archive Output",
             FieldKind::toggle(scrollback),
         ))
+        .with_field(Field::new(
+            SETUP_FIELD_SCROLLBACK_LINES,
+            "Scrollback Lines",
+            FieldKind::number_range(scrollback_lines, crate::MIN_SCROLLBACK_LINES as i64, crate::MAX_SCROLLBACK_LINES as i64),
+        ))
         .with_field(Field::new(
             SETUP_FIELD_WRAPSPACE,
             "Wrap Space",
             FieldKind::number_range(wrapspace, 0, 20),
         ))
+        .with_field(Field::new(
+            SETUP_FIELD_STATUS_FORMAT,
+            "Status Bar",
+            FieldKind::text(status_format),
+        ))
+        .with_field(Field::new(
+            SETUP_FIELD_TAB_BAR,
+            "Tab Bar",
+            FieldKind::toggle(tab_bar),
+        ))
+        .with_field(Field::new(
+            SETUP_FIELD_NOTIFY_ACTIVITY,
+            "Notify Activity",
+            FieldKind::toggle(notify_activity),
+        ))
+        .with_field(Field::new(
+            SETUP_FIELD_CONFIRM_QUIT,
+            "Confirm Quit",
+            FieldKind::toggle(confirm_quit),
+        ))
         .with_button(Button::new(SETUP_BTN_CANCEL, "Cancel").with_shortcut('C'))
         .with_button(Button::new(SETUP_BTN_SAVE, "Save").primary().with_shortcut('S'))
         .with_layout(PopupLayout {
@@ -206,7 +241,7 @@ pub fn create_setup_popup(
             center_horizontal: true,
             // Top-aligned (matches world_selector.rs) so the popup's height budget reserves
             // the separator+input rows at the bottom instead of centering blind to them —
-            // this popup has 19 fields and can otherwise size/position itself to overlap
+            // this popup has 22 fields and can otherwise size/position itself to overlap
             // the input pane on a short terminal.
             center_vertical: false,
             modal: true,
@@ -238,7 +273,7 @@ fn setup_help_text() -> Vec<String> {
         "  'Unseen First' prioritizes worlds with new activity.",
         "  'Alphabetical' cycles worlds in name order.",
         "",
-        "Debug: Enables debug logging to ~/.clay/debug.log.",
+        "Debug: Enables debug logging to ~/.config/clay/debug.log.",
         "",
         "Input Height: Number of input lines visible (1-10).",
         "",
@@ -248,7 +283,9 @@ fn setup_help_text() -> Vec<String> {
         "  during hot reload so TLS connections survive.",
         "",
         "Dictionary Path: Path to a custom dictionary file",
-        "  for the spell checker.",
+        "  for the spell checker: a word list, or a Hunspell",
+        "  .dic file with its .aff beside it. /spelllang picks",
+        "  other .dic files from the same directory per world.",
         "",
         "Editor Side: Which side the split-screen editor opens.",
         "",
@@ -271,14 +308,33 @@ fn setup_help_text() -> Vec<String> {
         "  Use /say <text> to speak manually when TTS is off.",
         "",
         "Archive Output: Saves all world output to",
-        "  ~/.clay/scrollback.db for permanent storage.",
+        "  ~/.config/clay/scrollback.db for permanent storage.",
         "  Enables /recall -D <pattern> to search the archive",
         "  and pg-up past the top of the scrollback buffer.",
         "  Changes take effect on next restart or /reload.",
         "",
+        "Scrollback Lines: Output lines kept per world",
+        "  (1000-1000000, default 50000). The oldest lines",
+        "  are dropped beyond this; with Archive Output on",
+        "  they stay in the archive.",
+        "",
         "Wrap Space: Number of spaces to hang-indent wrapped",
         "  continuation lines of long MUD output (0 = off).",
         "  Like TinyFugue's wrapspace, but defaults to 0.",
+        "",
+        "Status Bar: Template for the console status bar,",
+        "  e.g. %status %conn%world %activity%fill%time",
+        "  Empty keeps the built-in layout. See /help setup.",
+        "",
+        "Tab Bar: Shows a tab per world above the status bar,",
+        "  with unseen counts. Alt+1..Alt+9 jump to world N;",
+        "  with Console Mouse on, click a tab to switch (use",
+        "  Shift+drag for terminal selection while it's shown).",
+        "",
+        "Notify Activity: Desktop notification for the first",
+        "  new line in each world while the terminal window is",
+        "  unfocused (needs terminal focus reporting; in tmux,",
+        "  set -g focus-events on). Mute worlds with /notify -w.",
     ].into_iter().map(|s| s.to_string()).collect()
 }
 
@@ -292,13 +348,13 @@ mod tests {
         let def = create_setup_popup(
             true, true, false, "unseen_first",
             false, 3, "dark", false, "", "left", false, false, true,
-            false, "off", "words", false, 0,
+            false, "off", "words", false, 0, "", false, false, 50_000, true,
         );
         let state = PopupState::new(def);
 
         assert_eq!(state.definition.id, PopupId("setup"));
         assert_eq!(state.definition.title, "Setup");
-        assert_eq!(state.definition.fields.len(), 18);
+        assert_eq!(state.definition.fields.len(), 23);
         assert_eq!(state.definition.buttons.len(), 3); // ?, Cancel, Save
     }
 
@@ -307,7 +363,7 @@ mod tests {
         let def = create_setup_popup(
             true, false, true, "alphabetical",
             true, 5, "light", true, "/custom/dict", "left", true, true, true,
-            false, "edge", "sentences", true, 4,
+            false, "edge", "sentences", true, 4, "%world%fill%time", true, true, 20_000, false,
         );
         let state = PopupState::new(def);
 
@@ -322,5 +378,10 @@ mod tests {
         assert_eq!(state.get_bool(SETUP_FIELD_MOUSE), Some(true));
         assert_eq!(state.get_bool(SETUP_FIELD_ZWJ), Some(true));
         assert_eq!(state.get_number(SETUP_FIELD_WRAPSPACE), Some(4));
+        assert_eq!(state.get_text(SETUP_FIELD_STATUS_FORMAT), Some("%world%fill%time"));
+        assert_eq!(state.get_bool(SETUP_FIELD_TAB_BAR), Some(true));
+        assert_eq!(state.get_bool(SETUP_FIELD_NOTIFY_ACTIVITY), Some(true));
+        assert_eq!(state.get_number(SETUP_FIELD_SCROLLBACK_LINES), Some(20_000));
+        assert_eq!(state.get_bool(SETUP_FIELD_CONFIRM_QUIT), Some(false));
     }
 }
@@ -0,0 +1,75 @@
+//! Spell suggestions popup definition
+//!
+//! Lists numbered suggestions for the misspelled word at the cursor, just above the
+//! input where the word is, as a faster alternative to cycling them with Ctrl+Q.
+//! A digit or the arrows and Enter pick one.
+
+use crate::popup::{
+    Button, ButtonId, Field, FieldId, FieldKind, ListItem, ListItemStyle,
+    PopupDefinition, PopupId, PopupLayout,
+};
+
+// Field IDs
+pub const SPELL_FIELD_LIST: FieldId = FieldId(1);
+
+// Button IDs
+pub const SPELL_BTN_ADD: ButtonId = ButtonId(1);
+pub const SPELL_BTN_IGNORE: ButtonId = ButtonId(2);
+
+/// Most suggestions listed, so each has a digit
+pub const SPELL_MAX_SUGGESTIONS: usize = 9;
+
+/// Create the spell suggestions popup for `word`, at column `anchor_x`. `suggestions`
+/// must be non-empty and at most `SPELL_MAX_SUGGESTIONS` long.
+pub fn create_spell_popup(word: &str, suggestions: &[String], anchor_x: u16) -> PopupDefinition {
+    let items: Vec<ListItem> = suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, suggestion)| ListItem {
+            id: suggestion.clone(),
+            columns: vec![format!("{} {}", i + 1, suggestion)],
+            style: ListItemStyle {
+                is_current: false,
+                is_connected: false,
+                is_disabled: false,
+            },
+        })
+        .collect();
+
+    PopupDefinition::new(PopupId("spell"), format!("'{}'", word))
+        .with_field(Field::new(SPELL_FIELD_LIST, "", FieldKind::list(items, suggestions.len())))
+        .with_button(Button::new(SPELL_BTN_ADD, "Add").with_shortcut('A'))
+        .with_button(Button::new(SPELL_BTN_IGNORE, "Ignore").with_shortcut('I'))
+        .with_layout(PopupLayout {
+            label_width: 0,
+            min_width: 20,
+            max_width_percent: 60,
+            center_horizontal: false,
+            center_vertical: false,
+            modal: true,
+            buttons_right_align: false,
+            blank_line_before_list: false,
+            tab_buttons_only: false,
+            anchor_bottom_left: true,
+            anchor_x,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::popup::PopupState;
+
+    #[test]
+    fn test_spell_popup_numbers_suggestions() {
+        let def = create_spell_popup("thier", &["their".to_string(), "tier".to_string()], 7);
+        assert_eq!(def.layout.anchor_x, 7);
+        let state = PopupState::new(def);
+        if let Some(FieldKind::List { items, .. }) = state.field(SPELL_FIELD_LIST).map(|f| &f.kind) {
+            assert_eq!(items.iter().map(|i| i.columns[0].as_str()).collect::<Vec<_>>(), vec!["1 their", "2 tier"]);
+            assert_eq!(items[1].id, "tier");
+        } else {
+            panic!("SPELL_FIELD_LIST is not a List field");
+        }
+    }
+}
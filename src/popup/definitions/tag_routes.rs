@@ -0,0 +1,92 @@
+//! Tag routes popup definition
+//!
+//! Lists the routing rules keyed on inline MUD tags (see tag_routes.rs), letting the
+//! user enable/disable or delete them. New routes are added with /tagroute add.
+
+use crate::popup::{
+    Button, ButtonId, Field, FieldId, FieldKind, ListItem, ListItemStyle,
+    PopupDefinition, PopupId, PopupLayout,
+};
+
+// Field IDs
+pub const TAG_ROUTES_FIELD_LIST: FieldId = FieldId(1);
+
+// Button IDs
+pub const TAG_ROUTES_BTN_TOGGLE: ButtonId = ButtonId(1);
+pub const TAG_ROUTES_BTN_DELETE: ButtonId = ButtonId(2);
+pub const TAG_ROUTES_BTN_CLOSE: ButtonId = ButtonId(3);
+
+/// Column headers for the tag routes list
+pub const TAG_ROUTES_HEADERS: &[&str] = &["On", "Tag", "World", "Action"];
+
+/// Create the tag routes popup definition. List item ids are the route's index.
+pub fn create_tag_routes_popup(routes: &[crate::tag_routes::TagRoute], visible_height: usize) -> PopupDefinition {
+    let items: Vec<ListItem> = routes
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            #[cfg(not(windows))]
+            let status = if r.enabled { "[✓]" } else { "[ ]" };
+            #[cfg(windows)]
+            let status = if r.enabled { "[x]" } else { "[ ]" };
+            let world = if r.world.is_empty() { "(all)".to_string() } else { r.world.clone() };
+            ListItem {
+                id: i.to_string(),
+                columns: vec![status.to_string(), r.tag.clone(), world, r.action.spec()],
+                style: ListItemStyle {
+                    is_current: false,
+                    is_connected: false,
+                    is_disabled: !r.enabled,
+                },
+            }
+        })
+        .collect();
+
+    // Calculate column widths from headers and all items
+    let num_columns = TAG_ROUTES_HEADERS.len();
+    let mut column_widths: Vec<usize> = TAG_ROUTES_HEADERS.iter().map(|h| h.len()).collect();
+    for item in &items {
+        for (i, col) in item.columns.iter().enumerate() {
+            if i < num_columns {
+                column_widths[i] = column_widths[i].max(col.chars().count());
+            }
+        }
+    }
+
+    PopupDefinition::new(PopupId("tag_routes"), "Tag Routes")
+        .with_field(Field::new(
+            TAG_ROUTES_FIELD_LIST,
+            "",
+            FieldKind::list_with_headers_and_widths(items, visible_height, TAG_ROUTES_HEADERS, column_widths),
+        ))
+        .with_button(Button::new(TAG_ROUTES_BTN_TOGGLE, "Toggle").with_shortcut('T'))
+        .with_button(Button::new(TAG_ROUTES_BTN_DELETE, "Delete").with_shortcut('D'))
+        .with_button(Button::new(TAG_ROUTES_BTN_CLOSE, "Close").primary().with_shortcut('C'))
+        .with_layout(PopupLayout {
+            label_width: 8,
+            min_width: 50,
+            max_width_percent: 80,
+            center_horizontal: true,
+            center_vertical: false,
+            modal: true,
+            buttons_right_align: true,
+            blank_line_before_list: true,
+            tab_buttons_only: false,
+            anchor_bottom_left: false,
+            anchor_x: 0,
+        })
+        .with_help(vec![
+            "Tag Routes".to_string(),
+            "".to_string(),
+            "Routes lines by their leading MUD tag,".to_string(),
+            "e.g. [public:] or [chat(Bob)].".to_string(),
+            "".to_string(),
+            "Space/T   Enable or disable selected route".to_string(),
+            "Del/D     Delete selected route".to_string(),
+            "".to_string(),
+            "Add routes with:".to_string(),
+            "  /tagroute add [-w<world>] <tag> <action>".to_string(),
+            "Actions: gag, gag-idle:MINS, copy:WORLD,".to_string(),
+            "         move:WORLD, hilite:COLOR".to_string(),
+        ])
+}
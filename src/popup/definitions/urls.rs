@@ -0,0 +1,92 @@
+//! URLs popup definition
+//!
+//! Lists the URLs seen most recently in the current world's output (newest first)
+//! so one can be opened in the browser, copied, or sent to the MUD without reaching
+//! for the mouse. The web and GUI clients offer the same three on a link's
+//! right-click menu.
+
+use crate::popup::{
+    Button, ButtonId, Field, FieldId, FieldKind, ListItem, ListItemStyle,
+    PopupDefinition, PopupId, PopupLayout,
+};
+
+// Field IDs
+pub const URLS_FIELD_LIST: FieldId = FieldId(1);
+
+// Button IDs
+pub const URLS_BTN_OPEN: ButtonId = ButtonId(1);
+pub const URLS_BTN_CLOSE: ButtonId = ButtonId(2);
+pub const URLS_BTN_COPY: ButtonId = ButtonId(3);
+pub const URLS_BTN_SEND: ButtonId = ButtonId(4);
+
+/// Longest URL column shown; longer URLs are cut with "..." (the full URL is opened)
+const URL_COLUMN_MAX: usize = 76;
+
+/// Create the URLs popup definition. `urls` must be non-empty, newest first.
+pub fn create_urls_popup(urls: &[String], visible_height: usize) -> PopupDefinition {
+    let items: Vec<ListItem> = urls
+        .iter()
+        .map(|url| ListItem {
+            id: url.clone(),
+            columns: vec![if url.chars().count() > URL_COLUMN_MAX {
+                format!("{}...", url.chars().take(URL_COLUMN_MAX - 3).collect::<String>())
+            } else {
+                url.clone()
+            }],
+            style: ListItemStyle {
+                is_current: false,
+                is_connected: false,
+                is_disabled: false,
+            },
+        })
+        .collect();
+
+    let width = items.iter().map(|i| i.columns[0].chars().count()).max().unwrap_or(0).max(3);
+
+    PopupDefinition::new(PopupId("urls"), "Recent URLs")
+        .with_field(Field::new(
+            URLS_FIELD_LIST,
+            "",
+            FieldKind::list_with_headers_and_widths(items, visible_height, &["URL"], vec![width]),
+        ))
+        .with_button(Button::new(URLS_BTN_CLOSE, "Close").with_shortcut('C'))
+        .with_button(Button::new(URLS_BTN_COPY, "Copy").with_shortcut('P'))
+        .with_button(Button::new(URLS_BTN_SEND, "Send").with_shortcut('S'))
+        .with_button(Button::new(URLS_BTN_OPEN, "Open").primary().with_shortcut('O'))
+        .with_layout(PopupLayout {
+            label_width: 0,
+            min_width: 40,
+            max_width_percent: 90,
+            center_horizontal: true,
+            center_vertical: true,
+            modal: true,
+            buttons_right_align: true,
+            blank_line_before_list: false,
+            tab_buttons_only: false,
+            anchor_bottom_left: false,
+            anchor_x: 0,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::popup::PopupState;
+
+    #[test]
+    fn test_urls_popup_keeps_full_url_as_id() {
+        let long = format!("https://example.com/{}", "a".repeat(100));
+        let def = create_urls_popup(&["https://x.org".to_string(), long.clone()], 5);
+        let state = PopupState::new(def);
+        assert_eq!(state.definition.id, PopupId("urls"));
+        if let Some(FieldKind::List { items, .. }) = state.field(URLS_FIELD_LIST).map(|f| &f.kind) {
+            assert_eq!(items[0].columns[0], "https://x.org");
+            assert_eq!(items[1].id, long);
+            assert!(items[1].columns[0].ends_with("...") && items[1].columns[0].chars().count() == URL_COLUMN_MAX);
+        } else {
+            panic!("URLS_FIELD_LIST is not a List field");
+        }
+        assert_eq!(state.find_button_by_shortcut('p'), Some(URLS_BTN_COPY));
+        assert_eq!(state.find_button_by_shortcut('s'), Some(URLS_BTN_SEND));
+    }
+}
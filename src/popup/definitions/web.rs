@@ -21,6 +21,11 @@ pub const WEB_FIELD_WS_KEY_FILE: FieldId = FieldId(9);
 pub const WEB_FIELD_AUTH_KEY: FieldId = FieldId(10);
 pub const WEB_FIELD_WEB_PATH: FieldId = FieldId(11);
 pub const WEB_FIELD_REMOTE_LINES: FieldId = FieldId(12);
+pub const WEB_FIELD_ACME_DOMAIN: FieldId = FieldId(13);
+pub const WEB_FIELD_ACME_EMAIL: FieldId = FieldId(14);
+pub const WEB_FIELD_BIND_ADDR: FieldId = FieldId(15);
+pub const WEB_FIELD_UNIX_SOCKET: FieldId = FieldId(16);
+pub const WEB_FIELD_TRUSTED_PROXIES: FieldId = FieldId(17);
 
 // Button IDs
 pub const WEB_BTN_SAVE: ButtonId = ButtonId(1);
@@ -37,12 +42,13 @@ pub fn port_options() -> Vec<SelectOption> {
     ]
 }
 
-/// Custom Cert File options: No (use the auto-generated cert) or Yes (reveals
-/// the cert/key file path fields).
+/// Certificate options: the auto-generated cert, your own files (reveals the
+/// cert/key file path fields) or Let's Encrypt (reveals the domain and email fields).
 pub fn custom_cert_options() -> Vec<SelectOption> {
     vec![
-        SelectOption::new("no", "No"),
-        SelectOption::new("yes", "Yes"),
+        SelectOption::new("no", "Self-signed"),
+        SelectOption::new("yes", "Custom Files"),
+        SelectOption::new("acme", "Let's Encrypt"),
     ]
 }
 
@@ -56,6 +62,11 @@ pub fn create_web_popup(
     ws_allow_list: &str,
     ws_cert_file: &str,
     ws_key_file: &str,
+    acme_domain: &str,
+    acme_email: &str,
+    bind_addr: &str,
+    unix_socket: &str,
+    trusted_proxies: &str,
     auth_key: &str,
     remote_initial_lines: i64,
 ) -> PopupDefinition {
@@ -67,8 +78,13 @@ pub fn create_web_popup(
         "custom"
     };
     let port_idx = port_options().iter().position(|o| o.value == port_selected).unwrap_or(0);
-    let has_custom_cert = !ws_cert_file.is_empty() && !ws_key_file.is_empty();
-    let cert_idx = if has_custom_cert { 1 } else { 0 };
+    let cert_idx = if !acme_domain.is_empty() {
+        2
+    } else if !ws_cert_file.is_empty() && !ws_key_file.is_empty() {
+        1
+    } else {
+        0
+    };
 
     let mut def = PopupDefinition::new(PopupId("web"), "Web Settings")
         .with_field(Field::new(
@@ -81,6 +97,21 @@ pub fn create_web_popup(
             "Custom Port",
             FieldKind::text(http_port.to_string()),
         ))
+        .with_field(Field::new(
+            WEB_FIELD_BIND_ADDR,
+            "Bind Address",
+            FieldKind::text(bind_addr),
+        ))
+        .with_field(Field::new(
+            WEB_FIELD_UNIX_SOCKET,
+            "Unix Socket",
+            FieldKind::text(unix_socket),
+        ))
+        .with_field(Field::new(
+            WEB_FIELD_TRUSTED_PROXIES,
+            "Trusted Proxies",
+            FieldKind::text(trusted_proxies),
+        ))
         .with_field(Field::new(
             WEB_FIELD_WEB_PATH,
             "Web Path",
@@ -103,7 +134,7 @@ pub fn create_web_popup(
         ))
         .with_field(Field::new(
             WEB_FIELD_CUSTOM_CERT,
-            "Custom Cert File",
+            "Certificate",
             FieldKind::select(custom_cert_options(), cert_idx),
         ))
         .with_field(Field::new(
@@ -116,6 +147,16 @@ pub fn create_web_popup(
             "Key File",
             FieldKind::text(ws_key_file),
         ))
+        .with_field(Field::new(
+            WEB_FIELD_ACME_DOMAIN,
+            "Domain",
+            FieldKind::text(acme_domain),
+        ))
+        .with_field(Field::new(
+            WEB_FIELD_ACME_EMAIL,
+            "Email",
+            FieldKind::text(acme_email),
+        ))
         .with_field(
             Field::new(
                 WEB_FIELD_AUTH_KEY,
@@ -156,8 +197,8 @@ fn web_help_text() -> Vec<String> {
         "These settings let you access Clay from a web",
         "browser or mobile device on your network. The",
         "server is always TLS-encrypted for remote clients",
-        "(auto-generated certificate, or your own — see",
-        "Custom Cert File below); connections from this",
+        "(auto-generated certificate, Let's Encrypt, or your",
+        "own — see Certificate below); connections from this",
         "same machine (localhost) are always unencrypted,",
         "so the desktop app never shows a certificate prompt.",
         "",
@@ -168,13 +209,34 @@ fn web_help_text() -> Vec<String> {
         "so there is nothing to configure for that to work.",
         "A plain web browser will show a one-time \"not",
         "secure\" warning for the self-signed certificate;",
-        "use Custom Cert File to supply a CA-signed one and",
-        "avoid that.",
+        "use Certificate to get or supply a CA-signed one",
+        "and avoid that.",
         "",
         "Port: Disabled turns the web server off. 9000 is",
         "  the default port. Custom lets you pick your own",
         "  (shown in the Custom Port field below).",
         "",
+        "Bind Address: Where the server listens. 127.0.0.1",
+        "  (the default) is this machine only; 0.0.0.0 or ::",
+        "  is every network interface, which phones and other",
+        "  computers need; or one interface's address. The",
+        "  desktop GUI connects to 127.0.0.1, so use one of",
+        "  those three for it to keep working.",
+        "",
+        "Unix Socket: A file path to also serve plain HTTP and",
+        "  WebSocket on, for a reverse proxy (nginx, Caddy) on",
+        "  this machine. The file is readable by your user and",
+        "  group only; anything that can open it counts as a",
+        "  local connection. Empty = off. Unix only.",
+        "",
+        "Trusted Proxies: Comma-separated IPs of reverse",
+        "  proxies on other machines. Behind a proxy, Clay",
+        "  takes the client's address from X-Forwarded-For",
+        "  for the Allow List, bans and logs. It always does",
+        "  for proxies on this machine (localhost or the Unix",
+        "  Socket); list others here. Have the proxy pass the",
+        "  Web Path through unchanged, e.g. /clay/ to /clay/.",
+        "",
         "Web Path: Stealth path prefix for the web UI (default",
         "  \"clay\" — UI served only at /clay/, everything else",
         "  is silently dropped for non-localhost connections).",
@@ -185,7 +247,8 @@ fn web_help_text() -> Vec<String> {
         "  needed. Android app with an auth key can knock to",
         "  connect from anywhere even in stealth mode; without",
         "  a knock, non-localhost devices need to be on the WS",
-        "  Allow List.",
+        "  Allow List. Can have several parts (apps/clay) to",
+        "  match where a reverse proxy mounts it.",
         "",
         "Remote Lines: Number of scrollback lines sent to web/",
         "  remote clients on initial connect (10-5000, default",
@@ -215,10 +278,16 @@ fn web_help_text() -> Vec<String> {
         "  bare \"*\" entry does NOT get this protection (it",
         "  means \"let everyone in\", not \"never ban anyone\").",
         "",
-        "Custom Cert File: No (default) uses an automatically",
-        "  generated, self-signed certificate — nothing to",
-        "  configure. Yes lets you supply your own cert/key",
-        "  PEM files (e.g. a CA-signed certificate) instead.",
+        "Certificate: Self-signed (default) uses an",
+        "  automatically generated certificate — nothing to",
+        "  configure. Custom Files lets you supply your own",
+        "  cert/key PEM files instead. Let's Encrypt gets a",
+        "  free certificate for Domain (a DNS name pointing",
+        "  at this machine) and renews it every 60 days;",
+        "  Email (optional) gets expiry warnings. Port 80",
+        "  on Domain must reach this machine while it's",
+        "  validated — Clay listens there only for that.",
+        "  Same as /web acme <domain> [email].",
         "",
         "Auth Key: Device authentication key for passwordless",
         "  login from the Android app or trusted devices.",
@@ -230,23 +299,32 @@ fn web_help_text() -> Vec<String> {
     ].into_iter().map(|s| s.to_string()).collect()
 }
 
-/// Update visibility of the Custom Port and cert/key fields based on the
-/// current Port / Custom Cert File selections.
+/// Update visibility of the Custom Port, cert/key and Let's Encrypt fields based
+/// on the current Port / Certificate selections.
 pub fn update_web_visibility(state: &mut crate::popup::PopupState) {
     let show_custom_port = state.get_selected(WEB_FIELD_PORT) == Some("custom");
     if let Some(field) = state.field_mut(WEB_FIELD_CUSTOM_PORT) {
         field.visible = show_custom_port;
     }
 
-    let show_cert_fields = state.get_selected(WEB_FIELD_CUSTOM_CERT) == Some("yes");
-    if let Some(field) = state.field_mut(WEB_FIELD_WS_CERT_FILE) {
-        field.visible = show_cert_fields;
-    }
-    if let Some(field) = state.field_mut(WEB_FIELD_WS_KEY_FILE) {
-        field.visible = show_cert_fields;
+    let cert = state.get_selected(WEB_FIELD_CUSTOM_CERT).unwrap_or("no").to_string();
+    for (id, visible) in cert_field_visibility(&cert) {
+        if let Some(field) = state.field_mut(id) {
+            field.visible = visible;
+        }
     }
 }
 
+/// Which cert/key and Let's Encrypt fields show for a Certificate selection
+fn cert_field_visibility(cert: &str) -> [(FieldId, bool); 4] {
+    [
+        (WEB_FIELD_WS_CERT_FILE, cert == "yes"),
+        (WEB_FIELD_WS_KEY_FILE, cert == "yes"),
+        (WEB_FIELD_ACME_DOMAIN, cert == "acme"),
+        (WEB_FIELD_ACME_EMAIL, cert == "acme"),
+    ]
+}
+
 /// Same as `update_web_visibility` but operates directly on a `PopupDefinition`
 /// (used at creation time, before a `PopupState` wraps it).
 fn update_web_visibility_def(def: &mut PopupDefinition) {
@@ -259,16 +337,15 @@ fn update_web_visibility_def(def: &mut PopupDefinition) {
         field.visible = show_custom_port;
     }
 
-    let show_cert_fields = def.get_field(WEB_FIELD_CUSTOM_CERT)
+    let cert = def.get_field(WEB_FIELD_CUSTOM_CERT)
         .and_then(|f| if let FieldKind::Select { options, selected_index } = &f.kind {
-            options.get(*selected_index).map(|o| o.value == "yes")
+            options.get(*selected_index).map(|o| o.value.clone())
         } else { None })
-        .unwrap_or(false);
-    if let Some(field) = def.get_field_mut(WEB_FIELD_WS_CERT_FILE) {
-        field.visible = show_cert_fields;
-    }
-    if let Some(field) = def.get_field_mut(WEB_FIELD_WS_KEY_FILE) {
-        field.visible = show_cert_fields;
+        .unwrap_or_default();
+    for (id, visible) in cert_field_visibility(&cert) {
+        if let Some(field) = def.get_field_mut(id) {
+            field.visible = visible;
+        }
     }
 }
 
@@ -283,19 +360,21 @@ mod tests {
             true, 9000, "clay",
             "secret", "",
             "/path/to/cert", "/path/to/key",
+            "", "",
+            "127.0.0.1", "", "",
             "testkey123", 100,
         );
         let state = PopupState::new(def);
 
         assert_eq!(state.definition.id, PopupId("web"));
         assert_eq!(state.definition.title, "Web Settings");
-        assert_eq!(state.definition.fields.len(), 10);
+        assert_eq!(state.definition.fields.len(), 15);
     }
 
     #[test]
     fn test_web_popup_port_selection() {
         // Disabled
-        let def = create_web_popup(false, 9000, "clay", "", "", "", "", "", 100);
+        let def = create_web_popup(false, 9000, "clay", "", "", "", "", "", "", "", "", "", "", 100);
         assert_eq!(
             def.get_field(WEB_FIELD_PORT).and_then(|f| if let FieldKind::Select { options, selected_index } = &f.kind {
                 Some(options[*selected_index].value.clone())
@@ -305,39 +384,64 @@ mod tests {
         assert!(!def.get_field(WEB_FIELD_CUSTOM_PORT).unwrap().visible);
 
         // Default port
-        let def = create_web_popup(true, 9000, "clay", "", "", "", "", "", 100);
+        let def = create_web_popup(true, 9000, "clay", "", "", "", "", "", "", "", "", "", "", 100);
         assert!(!def.get_field(WEB_FIELD_CUSTOM_PORT).unwrap().visible);
 
         // Custom port
-        let def = create_web_popup(true, 1234, "clay", "", "", "", "", "", 100);
+        let def = create_web_popup(true, 1234, "clay", "", "", "", "", "", "", "", "", "", "", 100);
         assert!(def.get_field(WEB_FIELD_CUSTOM_PORT).unwrap().visible);
     }
 
     #[test]
     fn test_web_popup_cert_visibility() {
         // No custom cert configured — fields hidden
-        let def = create_web_popup(true, 9000, "clay", "secret", "", "", "", "", 100);
+        let def = create_web_popup(true, 9000, "clay", "secret", "", "", "", "", "", "", "", "", "", 100);
         let state = PopupState::new(def);
         assert!(!state.field(WEB_FIELD_WS_CERT_FILE).unwrap().visible);
         assert!(!state.field(WEB_FIELD_WS_KEY_FILE).unwrap().visible);
 
         // Custom cert configured — fields visible
-        let def = create_web_popup(true, 9000, "clay", "secret", "", "/c", "/k", "", 100);
+        let def = create_web_popup(true, 9000, "clay", "secret", "", "/c", "/k", "", "", "", "", "", "", 100);
         let state = PopupState::new(def);
         assert!(state.field(WEB_FIELD_WS_CERT_FILE).unwrap().visible);
         assert!(state.field(WEB_FIELD_WS_KEY_FILE).unwrap().visible);
+        assert!(!state.field(WEB_FIELD_ACME_DOMAIN).unwrap().visible);
+
+        // Let's Encrypt configured — domain/email shown, cert files hidden
+        let def = create_web_popup(true, 9000, "clay", "secret", "", "", "", "mud.example.com", "", "", "", "", "", 100);
+        let mut state = PopupState::new(def);
+        assert_eq!(state.get_selected(WEB_FIELD_CUSTOM_CERT), Some("acme"));
+        assert!(state.field(WEB_FIELD_ACME_DOMAIN).unwrap().visible);
+        assert!(state.field(WEB_FIELD_ACME_EMAIL).unwrap().visible);
+        assert!(!state.field(WEB_FIELD_WS_CERT_FILE).unwrap().visible);
+
+        // Switching back to Self-signed hides them again
+        state.select_field(WEB_FIELD_CUSTOM_CERT);
+        state.toggle_current();
+        update_web_visibility(&mut state);
+        assert_eq!(state.get_selected(WEB_FIELD_CUSTOM_CERT), Some("no"));
+        assert!(!state.field(WEB_FIELD_ACME_DOMAIN).unwrap().visible);
     }
 
     #[test]
     fn test_web_popup_remote_lines() {
-        let def = create_web_popup(true, 9000, "clay", "", "", "", "", "", 250);
+        let def = create_web_popup(true, 9000, "clay", "", "", "", "", "", "", "", "", "", "", 250);
         let state = PopupState::new(def);
         assert_eq!(state.get_text(WEB_FIELD_REMOTE_LINES), Some("250"));
     }
 
+    #[test]
+    fn test_web_popup_listen_fields() {
+        let def = create_web_popup(true, 9000, "clay", "", "", "", "", "", "", "0.0.0.0", "/run/clay.sock", "", "", 100);
+        let state = PopupState::new(def);
+        assert_eq!(state.get_text(WEB_FIELD_BIND_ADDR), Some("0.0.0.0"));
+        assert_eq!(state.get_text(WEB_FIELD_UNIX_SOCKET), Some("/run/clay.sock"));
+        assert_eq!(state.get_text(WEB_FIELD_TRUSTED_PROXIES), Some(""));
+    }
+
     #[test]
     fn test_web_popup_auth_key_readonly() {
-        let def = create_web_popup(true, 9000, "clay", "", "", "", "", "testkey", 100);
+        let def = create_web_popup(true, 9000, "clay", "", "", "", "", "", "", "", "", "", "testkey", 100);
         let field = def.get_field(WEB_FIELD_AUTH_KEY).unwrap();
         assert!(!field.is_focusable(), "Auth Key must be read-only (not focusable)");
     }
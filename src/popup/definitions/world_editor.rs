@@ -23,6 +23,14 @@ pub const WORLD_FIELD_KEEP_ALIVE: FieldId = FieldId(18);
 pub const WORLD_FIELD_KEEP_ALIVE_CMD: FieldId = FieldId(19);
 pub const WORLD_FIELD_GMCP_PACKAGES: FieldId = FieldId(20);
 pub const WORLD_FIELD_AUTO_RECONNECT: FieldId = FieldId(21);
+pub const WORLD_FIELD_LOG_FORMAT: FieldId = FieldId(22);
+pub const WORLD_FIELD_LOG_TIMESTAMPS: FieldId = FieldId(23);
+pub const WORLD_FIELD_LOG_SESSION_MARKERS: FieldId = FieldId(24);
+pub const WORLD_FIELD_TLS_VERIFY: FieldId = FieldId(25);
+pub const WORLD_FIELD_TLS_CLIENT_CERT: FieldId = FieldId(26);
+pub const WORLD_FIELD_TLS_CLIENT_KEY: FieldId = FieldId(27);
+pub const WORLD_FIELD_KEEP_ALIVE_INTERVAL: FieldId = FieldId(28);
+pub const WORLD_FIELD_SILENCE_ALERT: FieldId = FieldId(29);
 // Field IDs - Slack
 pub const WORLD_FIELD_SLACK_TOKEN: FieldId = FieldId(30);
 pub const WORLD_FIELD_SLACK_CHANNEL: FieldId = FieldId(31);
@@ -32,6 +40,16 @@ pub const WORLD_FIELD_DISCORD_TOKEN: FieldId = FieldId(40);
 pub const WORLD_FIELD_DISCORD_GUILD: FieldId = FieldId(41);
 pub const WORLD_FIELD_DISCORD_CHANNEL: FieldId = FieldId(42);
 pub const WORLD_FIELD_DISCORD_DM_USER: FieldId = FieldId(43);
+// Field IDs - MUD via an SSH bastion
+pub const WORLD_FIELD_SSH_HOST: FieldId = FieldId(50);
+pub const WORLD_FIELD_SSH_USER: FieldId = FieldId(51);
+pub const WORLD_FIELD_SSH_KEY: FieldId = FieldId(52);
+// Field IDs - MUD commands sent after login
+pub const WORLD_FIELD_CONNECT_COMMANDS: FieldId = FieldId(60);
+pub const WORLD_FIELD_LOGIN_SCRIPT: FieldId = FieldId(61);
+// Field IDs - MUD text added around typed lines
+pub const WORLD_FIELD_INPUT_PREFIX: FieldId = FieldId(62);
+pub const WORLD_FIELD_INPUT_SUFFIX: FieldId = FieldId(63);
 
 // Button IDs
 pub const WORLD_BTN_SAVE: ButtonId = ButtonId(1);
@@ -80,6 +98,40 @@ pub fn encoding_options() -> Vec<SelectOption> {
         SelectOption::new("utf8", "UTF-8"),
         SelectOption::new("latin1", "Latin-1"),
         SelectOption::new("fansi", "FANSI"),
+        SelectOption::new("cp437", "CP437"),
+        SelectOption::new("cp850", "CP850"),
+        SelectOption::new("koi8r", "KOI8-R"),
+        SelectOption::new("cp1252", "Windows-1252"),
+        SelectOption::new("big5", "Big5"),
+        SelectOption::new("gbk", "GBK"),
+        SelectOption::new("shiftjis", "Shift-JIS"),
+    ]
+}
+
+/// TLS certificate check options (see platform::TlsVerify)
+pub fn tls_verify_options() -> Vec<SelectOption> {
+    vec![
+        SelectOption::new("full", "Full"),
+        SelectOption::new("tofu", "TOFU"),
+        SelectOption::new("none", "None"),
+    ]
+}
+
+/// Log format options (see logging.rs)
+pub fn log_format_options() -> Vec<SelectOption> {
+    vec![
+        SelectOption::new("raw", "Raw"),
+        SelectOption::new("plain", "Plain"),
+        SelectOption::new("html", "HTML"),
+    ]
+}
+
+/// Log line timestamp options
+pub fn log_timestamps_options() -> Vec<SelectOption> {
+    vec![
+        SelectOption::new("off", "Off"),
+        SelectOption::new("time", "Time"),
+        SelectOption::new("datetime", "Date+Time"),
     ]
 }
 
@@ -87,8 +139,11 @@ pub fn encoding_options() -> Vec<SelectOption> {
 pub fn auto_connect_options() -> Vec<SelectOption> {
     vec![
         SelectOption::new("connect", "Connect"),
+        SelectOption::new("lines", "Lines"),
         SelectOption::new("prompt", "Prompt"),
         SelectOption::new("moo_prompt", "MOO Prompt"),
+        SelectOption::new("diku", "Diku"),
+        SelectOption::new("script", "Script"),
         SelectOption::new("none", "None"),
     ]
 }
@@ -114,11 +169,26 @@ pub struct WorldSettings {
     pub user: String,
     pub password: String,
     pub use_ssl: bool,
+    pub tls_verify: String,
+    pub tls_client_cert: String,
+    pub tls_client_key: String,
+    pub ssh_host: String,
+    pub ssh_user: String,
+    pub ssh_key: String,
     pub log_enabled: bool,
+    pub log_format: String,
+    pub log_timestamps: String,
+    pub log_session_markers: bool,
     pub encoding: String,
     pub auto_connect: String,
+    pub login_script: String,
+    pub connect_commands: String,
+    pub input_prefix: String,
+    pub input_suffix: String,
     pub keep_alive: String,
     pub keep_alive_cmd: String,
+    pub keep_alive_interval: String,
+    pub silence_alert: String,
     pub gmcp_packages: String,
     pub auto_reconnect_secs: String,
     // Slack
@@ -141,19 +211,32 @@ pub fn create_world_editor_popup(settings: &WorldSettings) -> PopupDefinition {
         WorldType::Discord => 2,
     };
 
-    let encoding_idx = match settings.encoding.as_str() {
-        "latin1" => 1,
-        "fansi" => 2,
-        _ => 0,
+    let encoding_idx = encoding_options().iter()
+        .position(|o| o.value == settings.encoding)
+        .unwrap_or(0);
+
+    let tls_verify_idx = match settings.tls_verify.as_str() {
+        "full" => 0,
+        "none" => 2,
+        _ => 1,
     };
 
-    let auto_connect_idx = match settings.auto_connect.as_str() {
-        "prompt" => 1,
-        "moo_prompt" => 2,
-        "none" => 3,
+    let log_format_idx = match settings.log_format.as_str() {
+        "plain" => 1,
+        "html" => 2,
         _ => 0,
     };
 
+    let log_timestamps_idx = match settings.log_timestamps.as_str() {
+        "off" => 0,
+        "datetime" => 2,
+        _ => 1,
+    };
+
+    let auto_connect_idx = auto_connect_options().iter()
+        .position(|o| o.value == settings.auto_connect)
+        .unwrap_or(0);
+
     let keep_alive_idx = match settings.keep_alive.as_str() {
         "custom" => 1,
         "generic" => 2,
@@ -161,6 +244,7 @@ pub fn create_world_editor_popup(settings: &WorldSettings) -> PopupDefinition {
     };
 
     let show_keep_alive_cmd = settings.keep_alive == "custom";
+    let show_login_script = settings.auto_connect == "script";
 
     let mut def = PopupDefinition::new(PopupId("world_editor"), "World Settings")
         // Common fields
@@ -200,11 +284,56 @@ pub fn create_world_editor_popup(settings: &WorldSettings) -> PopupDefinition {
             "Use SSL",
             FieldKind::toggle(settings.use_ssl),
         ))
+        .with_field(Field::new(
+            WORLD_FIELD_TLS_VERIFY,
+            "TLS Verify",
+            FieldKind::select(tls_verify_options(), tls_verify_idx),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_TLS_CLIENT_CERT,
+            "Client Cert",
+            FieldKind::text(&settings.tls_client_cert),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_TLS_CLIENT_KEY,
+            "Client Key",
+            FieldKind::text(&settings.tls_client_key),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_SSH_HOST,
+            "SSH Host",
+            FieldKind::text(&settings.ssh_host),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_SSH_USER,
+            "SSH User",
+            FieldKind::text(&settings.ssh_user),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_SSH_KEY,
+            "SSH Key",
+            FieldKind::text(&settings.ssh_key),
+        ))
         .with_field(Field::new(
             WORLD_FIELD_LOG_ENABLED,
             "Log File",
             FieldKind::toggle(settings.log_enabled),
         ))
+        .with_field(Field::new(
+            WORLD_FIELD_LOG_FORMAT,
+            "Log Format",
+            FieldKind::select(log_format_options(), log_format_idx),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_LOG_TIMESTAMPS,
+            "Log Times",
+            FieldKind::select(log_timestamps_options(), log_timestamps_idx),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_LOG_SESSION_MARKERS,
+            "Log Sessions",
+            FieldKind::toggle(settings.log_session_markers),
+        ))
         .with_field(Field::new(
             WORLD_FIELD_ENCODING,
             "Encoding",
@@ -215,6 +344,26 @@ pub fn create_world_editor_popup(settings: &WorldSettings) -> PopupDefinition {
             "Auto Login",
             FieldKind::select(auto_connect_options(), auto_connect_idx),
         ))
+        .with_field(Field::new(
+            WORLD_FIELD_LOGIN_SCRIPT,
+            "Login Script",
+            FieldKind::multiline(&settings.login_script, 4),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_CONNECT_COMMANDS,
+            "On Connect",
+            FieldKind::multiline(&settings.connect_commands, 3),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_INPUT_PREFIX,
+            "Cmd Prefix",
+            FieldKind::text(&settings.input_prefix),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_INPUT_SUFFIX,
+            "Cmd Suffix",
+            FieldKind::text(&settings.input_suffix),
+        ))
         .with_field(Field::new(
             WORLD_FIELD_KEEP_ALIVE,
             "Keep Alive",
@@ -225,6 +374,16 @@ pub fn create_world_editor_popup(settings: &WorldSettings) -> PopupDefinition {
             "KA Command",
             FieldKind::text(&settings.keep_alive_cmd),
         ))
+        .with_field(Field::new(
+            WORLD_FIELD_KEEP_ALIVE_INTERVAL,
+            "KA Interval",
+            FieldKind::text(&settings.keep_alive_interval),
+        ))
+        .with_field(Field::new(
+            WORLD_FIELD_SILENCE_ALERT,
+            "Alert After",
+            FieldKind::text(&settings.silence_alert),
+        ))
         .with_field(Field::new(
             WORLD_FIELD_GMCP_PACKAGES,
             "GMCP",
@@ -285,7 +444,7 @@ pub fn create_world_editor_popup(settings: &WorldSettings) -> PopupDefinition {
             min_width: 50,
             max_width_percent: 70,
             center_horizontal: true,
-            // Top-aligned (matches world_selector.rs / setup.rs) — this popup has 21 fields
+            // Top-aligned (matches world_selector.rs / setup.rs) — this popup has 24 fields
             // and can otherwise size/position itself to overlap the input pane on a short
             // terminal (same fix as the /setup popup, see plan
             // `the-android-app-is-steady-sphinx.md`).
@@ -299,7 +458,7 @@ pub fn create_world_editor_popup(settings: &WorldSettings) -> PopupDefinition {
         });
 
     // Set field visibility based on world type
-    update_field_visibility(&mut def, world_type, show_keep_alive_cmd);
+    update_field_visibility(&mut def, world_type, show_keep_alive_cmd, show_login_script);
 
     def.with_help(world_editor_help_text())
 }
@@ -326,24 +485,86 @@ fn world_editor_help_text() -> Vec<String> {
         "Use SSL: Enable TLS/SSL encryption for the connection.",
         "  Required if the MUD uses a secure port.",
         "",
+        "TLS Verify: How the server's certificate is checked.",
+        "  Full: Must be signed by a known CA for the hostname.",
+        "  TOFU: Remembered on first connect; a different",
+        "    certificate later blocks the connection.",
+        "  None: Any certificate (still encrypted, not verified).",
+        "",
+        "Client Cert: PEM certificate file to log in with, for",
+        "  servers that authenticate by certificate (~/ for home).",
+        "Client Key: Its private key file (PKCS#8 for native-tls",
+        "  builds). Leave empty if the key is in the cert file.",
+        "",
+        "SSH Host: Connect through this SSH server (host[:port]),",
+        "  for MUDs only reachable from it. Empty: connect directly.",
+        "SSH User: Login name there (empty: your local username).",
+        "SSH Key: Private key file (~/ for home). ssh-agent and",
+        "  ~/.ssh/id_* are also tried; a key with a passphrase",
+        "  needs ssh-agent.",
+        "",
         "Log File: Save all output to a timestamped log file.",
         "",
+        "Log Format: How the log file is written.",
+        "  Raw: Output as received, color codes included (.log).",
+        "  Plain: Color and control codes removed, for grep (.txt).",
+        "  HTML: Colors kept as styled text, for a browser (.html).",
+        "",
+        "Log Times: Time prefix on each logged line.",
+        "  Off, Time ([HH:MM:SS]) or Date+Time ([YYYY-MM-DD HH:MM:SS]).",
+        "",
+        "Log Sessions: Write \"=== Session started/ended ===\" lines",
+        "  to the log on connect and disconnect.",
+        "",
         "Encoding: Character encoding for the connection.",
         "  UTF-8: Modern standard (most MUDs).",
         "  Latin-1: Western European (older MUDs).",
         "  FANSI: Extended ANSI art encoding.",
+        "  CP437: IBM PC box drawing (BBS-style games).",
+        "  CP850, KOI8-R, Windows-1252: DOS Western,",
+        "    Russian and Windows Western code pages.",
+        "  Big5, GBK, Shift-JIS: Traditional Chinese,",
+        "    Simplified Chinese and Japanese.",
+        "  What you type is sent in the same encoding; smart",
+        "  quotes and dashes a code page lacks go as ASCII.",
         "",
         "Auto Login: How to send your credentials on connect.",
         "  Connect: Send 'connect user password'.",
+        "  Lines: Send user, then password, as two lines.",
         "  Prompt: Wait for prompts, send user then password.",
         "  MOO Prompt: Like Prompt but for MOO-style servers.",
+        "  Diku: Answer the name and password prompts of",
+        "    Diku-style servers (By what name...?  Password:).",
+        "  Script: Your own Login Script (below).",
         "  None: Don't auto-login.",
         "",
+        "Login Script: One step per line; <user> and <pass> are",
+        "  replaced by your login. 'expect REGEX' waits for output",
+        "  matching REGEX; other lines are sent in turn. E.g.",
+        "    expect (?i)account:",
+        "    <user>",
+        "    expect (?i)password",
+        "    <pass>",
+        "",
+        "On Connect: Commands sent once you're logged in, one per",
+        "  line (join channels, set a prompt). Enter adds a line.",
+        "",
+        "Cmd Prefix / Cmd Suffix: Added before / after each line",
+        "  you type, unless it starts with / (e.g. 'say ' for a",
+        "  chat-only world, or ';look'). Not added to a password.",
+        "",
         "Keep Alive: Prevents idle disconnects.",
         "  NOP: Sends a telnet NOP (invisible to server).",
         "  Custom: Sends a custom command you specify.",
         "  Generic: Sends a generic keep-alive packet.",
         "",
+        "KA Interval: Seconds idle before a keep-alive is sent",
+        "  (default 300, at least 60).",
+        "",
+        "Alert After: Minutes without output before you're told",
+        "  the world has gone quiet (catches silent disconnects).",
+        "  0: off.",
+        "",
         "GMCP: Space-separated GMCP packages to request from",
         "  the server (e.g. Char.Items Room.Info).",
         "",
@@ -356,12 +577,17 @@ fn world_editor_help_text() -> Vec<String> {
 }
 
 /// Update field visibility based on world type
-pub fn update_field_visibility(def: &mut PopupDefinition, world_type: WorldType, show_keep_alive_cmd: bool) {
+pub fn update_field_visibility(def: &mut PopupDefinition, world_type: WorldType, show_keep_alive_cmd: bool, show_login_script: bool) {
     // MUD fields
     let mud_fields = [
         WORLD_FIELD_HOSTNAME, WORLD_FIELD_PORT, WORLD_FIELD_USER, WORLD_FIELD_PASSWORD,
-        WORLD_FIELD_USE_SSL, WORLD_FIELD_LOG_ENABLED, WORLD_FIELD_ENCODING,
-        WORLD_FIELD_AUTO_CONNECT, WORLD_FIELD_KEEP_ALIVE, WORLD_FIELD_GMCP_PACKAGES,
+        WORLD_FIELD_USE_SSL, WORLD_FIELD_TLS_VERIFY, WORLD_FIELD_TLS_CLIENT_CERT,
+        WORLD_FIELD_TLS_CLIENT_KEY, WORLD_FIELD_SSH_HOST, WORLD_FIELD_SSH_USER,
+        WORLD_FIELD_SSH_KEY, WORLD_FIELD_LOG_ENABLED, WORLD_FIELD_ENCODING,
+        WORLD_FIELD_AUTO_CONNECT, WORLD_FIELD_CONNECT_COMMANDS, WORLD_FIELD_INPUT_PREFIX,
+        WORLD_FIELD_INPUT_SUFFIX, WORLD_FIELD_KEEP_ALIVE,
+        WORLD_FIELD_KEEP_ALIVE_INTERVAL,
+        WORLD_FIELD_SILENCE_ALERT, WORLD_FIELD_GMCP_PACKAGES,
         WORLD_FIELD_AUTO_RECONNECT,
     ];
 
@@ -388,9 +614,16 @@ pub fn update_field_visibility(def: &mut PopupDefinition, world_type: WorldType,
         field.visible = world_type == WorldType::Mud && show_keep_alive_cmd;
     }
 
+    // Login script only visible for MUD with the Script auto-login
+    if let Some(field) = def.get_field_mut(WORLD_FIELD_LOGIN_SCRIPT) {
+        field.visible = world_type == WorldType::Mud && show_login_script;
+    }
+
     // Log file visible for all types
-    if let Some(field) = def.get_field_mut(WORLD_FIELD_LOG_ENABLED) {
-        field.visible = true;
+    for id in [WORLD_FIELD_LOG_ENABLED, WORLD_FIELD_LOG_FORMAT, WORLD_FIELD_LOG_TIMESTAMPS, WORLD_FIELD_LOG_SESSION_MARKERS] {
+        if let Some(field) = def.get_field_mut(id) {
+            field.visible = true;
+        }
     }
 
     for id in slack_fields {
@@ -27,22 +27,29 @@ pub struct WorldInfo {
     pub user: String,
     pub is_connected: bool,
     pub is_current: bool,
+    /// Bells since the world was last viewed (see bell.rs)
+    pub bell_count: usize,
 }
 
 /// Column headers for the world list
-pub const WORLD_LIST_HEADERS: &[&str] = &["World", "Hostname", "Port", "User"];
+pub const WORLD_LIST_HEADERS: &[&str] = &["World", "Hostname", "Port", "User", "Bells"];
+
+fn bell_column(bell_count: usize) -> String {
+    if bell_count > 0 { bell_count.to_string() } else { String::new() }
+}
 
 /// Create the world selector popup definition
 pub fn create_world_selector_popup(worlds: &[WorldInfo], visible_height: usize) -> PopupDefinition {
     let items: Vec<ListItem> = worlds
         .iter()
         .map(|w| {
-            // Columns: World, Hostname, Port, User
+            // Columns: World, Hostname, Port, User, Bells (blank when none)
             let columns = vec![
                 w.name.clone(),
                 w.hostname.clone(),
                 w.port.clone(),
                 w.user.clone(),
+                bell_column(w.bell_count),
             ];
 
             ListItem {
@@ -108,7 +115,8 @@ fn world_selector_help_text() -> Vec<String> {
         "Shows all configured worlds. Connected worlds are",
         "highlighted, and the current world is marked with *.",
         "",
-        "Columns: World name, Hostname, Port, Username.",
+        "Columns: World name, Hostname, Port, Username, and",
+        "bells rung since the world was last viewed.",
         "",
         "Navigation:",
         "  Up/Down     Navigate the world list",
@@ -154,12 +162,13 @@ pub fn update_world_list(state: &mut crate::popup::PopupState, worlds: &[WorldIn
             let new_items: Vec<ListItem> = worlds
                 .iter()
                 .map(|w| {
-                    // Columns: World, Hostname, Port, User
+                    // Columns: World, Hostname, Port, User, Bells (blank when none)
                     let columns = vec![
                         w.name.clone(),
                         w.hostname.clone(),
                         w.port.clone(),
                         w.user.clone(),
+                        bell_column(w.bell_count),
                     ];
 
                     ListItem {
@@ -200,6 +209,7 @@ mod tests {
                 user: "player1".to_string(),
                 is_connected: true,
                 is_current: true,
+                bell_count: 0,
             },
             WorldInfo {
                 name: "AnotherMUD".to_string(),
@@ -208,6 +218,7 @@ mod tests {
                 user: "player2".to_string(),
                 is_connected: false,
                 is_current: false,
+                bell_count: 3,
             },
         ]
     }
@@ -221,6 +232,14 @@ mod tests {
         assert_eq!(state.definition.id, PopupId("world_selector"));
         assert_eq!(state.definition.title, "World Selector");
         assert_eq!(state.definition.buttons.len(), 6); // ? + 5 original
+
+        // Bells column is blank for a world with none
+        if let Some(FieldKind::List { items, .. }) = state.field(SELECTOR_FIELD_LIST).map(|f| &f.kind) {
+            assert_eq!(items[0].columns[4], "");
+            assert_eq!(items[1].columns[4], "3");
+        } else {
+            panic!("world list missing");
+        }
     }
 
     #[test]
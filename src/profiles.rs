@@ -0,0 +1,267 @@
+//! Time-based setting profiles.
+//!
+//! A profile is a named set of behavior overrides that is active while its
+//! schedule matches the local clock — e.g. a `work` profile that silences
+//! notifications and gags chatty channels on weekdays 09:00-17:00. The first
+//! matching profile wins; with none active, Clay behaves normally.
+//!
+//! Schedules use a small cron-like syntax: `<days> <times>`, where days is
+//! `*` or a comma list of day names/ranges (`mon-fri`, `sat,sun`, `mon,wed-fri`)
+//! and times is `*` (all day) or `HH:MM-HH:MM` (may wrap past midnight, e.g.
+//! `22:00-07:00`).
+//!
+//! Profiles are managed with `/profile` and persisted in the `[profiles]`
+//! section of settings.dat. The active profile is shown in the status bar.
+
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// When a profile is active
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// Indexed by weekday, 0 = Sunday (matches LocalTime::weekday)
+    pub days: [bool; 7],
+    /// Minutes since midnight; start == end means all day
+    pub start: u16,
+    pub end: u16,
+}
+
+impl Schedule {
+    /// Parse `<days> <times>` (see module docs)
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.split_whitespace();
+        let days_spec = parts.next().ok_or("missing days")?;
+        let times_spec = parts.next().unwrap_or("*");
+        if parts.next().is_some() {
+            return Err(format!("unexpected text after '{}'", times_spec));
+        }
+
+        let mut days = [false; 7];
+        if days_spec == "*" {
+            days = [true; 7];
+        } else {
+            for item in days_spec.split(',') {
+                let (from, to) = item.split_once('-').unwrap_or((item, item));
+                let from = parse_day(from)?;
+                let to = parse_day(to)?;
+                // Ranges may wrap the week (fri-mon)
+                let mut d = from;
+                loop {
+                    days[d] = true;
+                    if d == to {
+                        break;
+                    }
+                    d = (d + 1) % 7;
+                }
+            }
+        }
+
+        let (start, end) = if times_spec == "*" {
+            (0, 0)
+        } else {
+            let (s, e) = times_spec.split_once('-').ok_or_else(|| format!("bad time range '{}'", times_spec))?;
+            (parse_hhmm(s)?, parse_hhmm(e)?)
+        };
+        Ok(Self { days, start, end })
+    }
+
+    /// Inverse of `parse` (normalized: day ranges are written out as lists)
+    pub fn spec(&self) -> String {
+        let days = if self.days.iter().all(|d| *d) {
+            "*".to_string()
+        } else {
+            DAY_NAMES.iter().zip(self.days.iter())
+                .filter(|(_, on)| **on)
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let times = if self.start == self.end {
+            "*".to_string()
+        } else {
+            format!("{:02}:{:02}-{:02}:{:02}", self.start / 60, self.start % 60, self.end / 60, self.end % 60)
+        };
+        format!("{} {}", days, times)
+    }
+
+    /// Does the schedule cover `weekday` (0 = Sunday) at `minute` (minutes since midnight)?
+    /// For ranges that wrap midnight, the early-morning part belongs to the previous day's
+    /// schedule ("fri 22:00-02:00" covers Saturday 01:00).
+    pub fn matches(&self, weekday: usize, minute: u16) -> bool {
+        let weekday = weekday % 7;
+        if self.start == self.end {
+            self.days[weekday]
+        } else if self.start < self.end {
+            self.days[weekday] && minute >= self.start && minute < self.end
+        } else if minute >= self.start {
+            self.days[weekday]
+        } else {
+            minute < self.end && self.days[(weekday + 6) % 7]
+        }
+    }
+}
+
+fn parse_day(s: &str) -> Result<usize, String> {
+    let lower = s.trim().to_lowercase();
+    DAY_NAMES.iter()
+        .position(|d| lower.starts_with(d) && d.len() >= 3)
+        .ok_or_else(|| format!("unknown day '{}'", s))
+}
+
+fn parse_hhmm(s: &str) -> Result<u16, String> {
+    let (h, m) = s.split_once(':').ok_or_else(|| format!("bad time '{}' (use HH:MM)", s))?;
+    let h: u16 = h.parse().map_err(|_| format!("bad time '{}'", s))?;
+    let m: u16 = m.parse().map_err(|_| format!("bad time '{}'", s))?;
+    // 24:00 is allowed as an end-of-day marker
+    if h > 24 || m > 59 || (h == 24 && m > 0) {
+        return Err(format!("bad time '{}'", s));
+    }
+    Ok(h * 60 + m)
+}
+
+/// A named set of overrides applied while its schedule matches
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub schedule: Schedule,
+    /// Allow /notify notifications to reach web/mobile clients
+    pub notify: bool,
+    /// Allow text-to-speech of incoming output
+    pub tts: bool,
+    /// MUD tags (see tag_routes.rs) whose lines are gagged while active
+    pub gag_tags: Vec<String>,
+}
+
+impl Profile {
+    /// Build a profile from `/profile add` options: `<days> <times> [notify=on|off]
+    /// [tts=on|off] [gag=tag1,tag2]`
+    pub fn from_args(name: &str, args: &[&str]) -> Result<Self, String> {
+        if args.len() < 2 {
+            return Err("expected <days> <times>".to_string());
+        }
+        let schedule = Schedule::parse(&format!("{} {}", args[0], args[1]))?;
+        let mut profile = Self {
+            name: name.to_string(),
+            schedule,
+            notify: true,
+            tts: true,
+            gag_tags: Vec::new(),
+        };
+        for opt in &args[2..] {
+            let (key, value) = opt.split_once('=').ok_or_else(|| format!("bad option '{}'", opt))?;
+            let on = || match value.to_lowercase().as_str() {
+                "on" | "true" | "yes" | "1" => Ok(true),
+                "off" | "false" | "no" | "0" => Ok(false),
+                _ => Err(format!("bad value '{}' for {}", value, key)),
+            };
+            match key.to_lowercase().as_str() {
+                "notify" => profile.notify = on()?,
+                "tts" => profile.tts = on()?,
+                "gag" => {
+                    profile.gag_tags = value.split(',')
+                        .map(crate::tag_routes::normalize_tag)
+                        .filter(|t| !t.is_empty())
+                        .collect();
+                }
+                _ => return Err(format!("unknown option '{}'", key)),
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Human-readable summary of what the profile changes
+    pub fn describe(&self) -> String {
+        let mut effects = Vec::new();
+        if !self.notify {
+            effects.push("notify=off".to_string());
+        }
+        if !self.tts {
+            effects.push("tts=off".to_string());
+        }
+        if !self.gag_tags.is_empty() {
+            effects.push(format!("gag={}", self.gag_tags.join(",")));
+        }
+        if effects.is_empty() {
+            effects.push("(no overrides)".to_string());
+        }
+        format!("{} {}", self.schedule.spec(), effects.join(" "))
+    }
+
+    /// Serialize as `name|schedule|notify|tts|gag_tags` for settings.dat
+    pub fn to_dat(&self) -> String {
+        format!("{}|{}|{}|{}|{}", self.name, self.schedule.spec(), self.notify, self.tts, self.gag_tags.join(","))
+    }
+
+    /// Parse the `to_dat` form. Returns None for malformed entries.
+    pub fn from_dat(s: &str) -> Option<Self> {
+        let parts: Vec<&str> = s.splitn(5, '|').collect();
+        if parts.len() < 4 || parts[0].is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: parts[0].to_string(),
+            schedule: Schedule::parse(parts[1]).ok()?,
+            notify: parts[2] != "false",
+            tts: parts[3] != "false",
+            gag_tags: parts.get(4)
+                .map(|g| g.split(',').filter(|t| !t.is_empty()).map(|t| t.to_string()).collect())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// The first profile whose schedule covers `weekday`/`minute`
+pub fn active_profile(profiles: &[Profile], weekday: usize, minute: u16) -> Option<&Profile> {
+    profiles.iter().find(|p| p.schedule.matches(weekday, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_parse_and_match() {
+        let work = Schedule::parse("mon-fri 09:00-17:00").unwrap();
+        assert_eq!(work.spec(), "mon,tue,wed,thu,fri 09:00-17:00");
+        assert!(work.matches(1, 9 * 60));
+        assert!(work.matches(5, 16 * 60 + 59));
+        assert!(!work.matches(5, 17 * 60));
+        assert!(!work.matches(0, 12 * 60));
+
+        let weekend = Schedule::parse("sat,sun").unwrap();
+        assert!(weekend.matches(6, 0) && weekend.matches(0, 23 * 60));
+        assert!(!weekend.matches(3, 12 * 60));
+
+        // Wrapping past midnight: Friday night's range covers early Saturday
+        let late = Schedule::parse("fri 22:00-02:00").unwrap();
+        assert!(late.matches(5, 23 * 60));
+        assert!(late.matches(6, 60));
+        assert!(!late.matches(5, 60));
+
+        // Day ranges may wrap the week
+        assert_eq!(Schedule::parse("fri-mon *").unwrap().spec(), "sun,mon,fri,sat *");
+
+        assert!(Schedule::parse("funday 09:00-10:00").is_err());
+        assert!(Schedule::parse("* 25:00-26:00").is_err());
+        assert!(Schedule::parse("* 9-5").is_err());
+    }
+
+    #[test]
+    fn test_profile_args_and_dat_round_trip() {
+        let p = Profile::from_args("work", &["mon-fri", "09:00-17:00", "notify=off", "gag=[public:],ooc"]).unwrap();
+        assert!(!p.notify && p.tts);
+        assert_eq!(p.gag_tags, vec!["public", "ooc"]);
+        assert_eq!(Profile::from_dat(&p.to_dat()), Some(p));
+        assert!(Profile::from_args("x", &["*"]).is_err());
+        assert!(Profile::from_args("x", &["*", "*", "volume=11"]).is_err());
+    }
+
+    #[test]
+    fn test_first_matching_profile_wins() {
+        let profiles = vec![
+            Profile::from_args("night", &["*", "22:00-07:00"]).unwrap(),
+            Profile::from_args("always", &["*", "*"]).unwrap(),
+        ];
+        assert_eq!(active_profile(&profiles, 2, 23 * 60).map(|p| p.name.as_str()), Some("night"));
+        assert_eq!(active_profile(&profiles, 2, 12 * 60).map(|p| p.name.as_str()), Some("always"));
+    }
+}
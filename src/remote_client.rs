@@ -28,7 +28,7 @@ use crate::{
     get_version_string, parse_command,
     UpdateSuccess,
     NewPopupAction, WebSettings,
-    ActionsListAction, RecentWorldsAction,
+    ActionsListAction, GagsAction, MacrosAction, RecentWorldsAction, TagRoutesAction,
     EditorSide, AutoConnectType, KeepAliveType,
     web_settings_from_custom_data, handle_new_popup_key,
     websocket, popup, keybindings, tf, platform, persistence,
@@ -133,7 +133,7 @@ pub(crate) async fn run_grep_client(
                     mismatch.host, mismatch.old_fingerprint, mismatch.new_fingerprint
                 );
                 eprintln!("Refusing to connect (possible MITM, or the server was reinstalled/re-keyed).");
-                eprintln!("If you trust the new certificate, remove its old pin from ~/.clay/known_hosts.dat and reconnect.");
+                eprintln!("If you trust the new certificate, remove its old pin from ~/.config/clay/known_hosts.dat and reconnect.");
                 std::process::exit(1);
             }
             eprintln!("Failed to connect to {}: {}", ws_url, e);
@@ -183,6 +183,7 @@ pub(crate) async fn run_grep_client(
         auth_key: None,
         request_key: false,
         challenge_response: true,
+        resume_seqs: Default::default(),
     });
 
     // Wait for auth response
@@ -550,16 +551,18 @@ pub(crate) async fn run_import_client(
 
     // Authenticate: auth-key path takes precedence when both are supplied, matching the
     // server's own routing in websocket.rs (checks `auth_key.is_some()` first). The stored
-    // auth-key is never sent in the clear — only its challenge-response hash — mirroring
-    // `handle_ws_auth_key_validation`'s `hash_with_challenge(&ak.key, challenge) == key` check.
+    // auth-key is never sent in the clear — only its challenge-response hash, over the key's
+    // SHA-256 as for passwords — mirroring `handle_ws_auth_key_validation`'s
+    // `hash_with_challenge(key_hash, challenge) == key` check.
     let auth_request = if let Some(key) = auth_key {
         WsMessage::AuthRequest {
             password_hash: String::new(),
             username: None,
             current_world: None,
-            auth_key: Some(hash_with_challenge(key, &server_challenge)),
+            auth_key: Some(hash_with_challenge(&hash_password(key), &server_challenge)),
             request_key: false,
             challenge_response: true,
+            resume_seqs: Default::default(),
         }
     } else {
         let password_hash = hash_password(password.unwrap_or_default());
@@ -570,6 +573,7 @@ pub(crate) async fn run_import_client(
             auth_key: None,
             request_key: false,
             challenge_response: true,
+            resume_seqs: Default::default(),
         }
     };
     let _ = ws_tx.send(auth_request);
@@ -697,7 +701,7 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
             .to_string();
 
         // Connect to WebSocket server - for wss:// we need to configure TLS to accept self-signed
-        // certs (trust-on-first-use pinned via ~/.clay/known_hosts.dat, see platform::danger_rustls).
+        // certs (trust-on-first-use pinned via ~/.config/clay/known_hosts.dat, see platform::danger_rustls).
         #[cfg(feature = "rustls-backend")]
         async fn try_wss(ws_url: &str, host_port_key: &str) -> Result<
             (tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
@@ -869,7 +873,7 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
                             // Send authentication with challenge-response
                             let password_hash = hash_password(&password);
                             let challenge_hash = hash_with_challenge(&password_hash, &server_challenge);
-                            let _ = ws_tx.send(WsMessage::AuthRequest { password_hash: challenge_hash, username, current_world: None, auth_key: None, request_key: false, challenge_response: true });
+                            let _ = ws_tx.send(WsMessage::AuthRequest { password_hash: challenge_hash, username, current_world: None, auth_key: None, request_key: false, challenge_response: true, resume_seqs: Default::default() });
                             break;
                         }
                         KeyCode::Char(c) => {
@@ -927,6 +931,9 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
     let mut app = App::new();
     app.ws_client_tx = Some(ws_tx.clone());
     app.is_master = false;
+    app.console_active = true;
+    app.color_depth = crate::color_depth::ColorDepth::detect();
+    app.undercurl = crate::color_depth::undercurl_supported();
 
     // Now set up the terminal for the main UI
     enable_raw_mode()?;
@@ -935,6 +942,7 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
         stdout,
         EnterAlternateScreen,
         crossterm::event::EnableBracketedPaste,
+        crossterm::event::EnableFocusChange,
         crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
         crossterm::cursor::MoveTo(0, 0)
     )?;
@@ -993,7 +1001,7 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
             Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => {}
             Some(Ok(Message::Close(frame))) => {
                 disable_raw_mode()?;
-                let _ = execute!(terminal.backend_mut(), crossterm::event::DisableBracketedPaste);
+                let _ = execute!(terminal.backend_mut(), crossterm::event::DisableBracketedPaste, crossterm::event::DisableFocusChange);
                 execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
                 match frame.map(|f| f.reason.to_string()).filter(|r| !r.is_empty()) {
                     Some(reason) => eprintln!("Connection closed while waiting for initial state: {reason}"),
@@ -1004,14 +1012,14 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
             Some(Ok(Message::Binary(_) | Message::Frame(_))) => {}
             Some(Err(e)) => {
                 disable_raw_mode()?;
-                let _ = execute!(terminal.backend_mut(), crossterm::event::DisableBracketedPaste);
+                let _ = execute!(terminal.backend_mut(), crossterm::event::DisableBracketedPaste, crossterm::event::DisableFocusChange);
                 execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
                 eprintln!("Connection error while waiting for initial state: {e}");
                 return Ok(());
             }
             None => {
                 disable_raw_mode()?;
-                let _ = execute!(terminal.backend_mut(), crossterm::event::DisableBracketedPaste);
+                let _ = execute!(terminal.backend_mut(), crossterm::event::DisableBracketedPaste, crossterm::event::DisableFocusChange);
                 execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
                 eprintln!("Connection closed while waiting for initial state");
                 return Ok(());
@@ -1027,10 +1035,16 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
     let mut backfill_timer = std::pin::pin!(tokio::time::sleep(std::time::Duration::from_millis(500)));
     let mut backfill_timer_active = app.backfill_needed();
 
+    // Bell flash end — only active while the status bar is inverted (see bell.rs)
+    let mut bell_flash_timer = std::pin::pin!(tokio::time::sleep(std::time::Duration::ZERO));
+
     // Channel for local /update results
     let (update_tx, mut update_rx) = mpsc::channel::<Result<UpdateSuccess, String>>(1);
 
     loop {
+        // Hold each world to the Scrollback Lines cap
+        app.trim_scrollback();
+
         // Draw if needed
         if needs_redraw || app.needs_output_redraw {
             // Check current popup visibility
@@ -1044,9 +1058,10 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
             }
             app.popup_was_visible = any_popup_visible;
 
-            // Toggle mouse capture when popup visibility changes
+            // Toggle mouse capture when popup visibility changes (always on while the
+            // tab bar is shown, so its tabs can be clicked)
             if app.settings.mouse_enabled {
-                let want_mouse = any_popup_visible;
+                let want_mouse = any_popup_visible || app.settings.tab_bar;
                 if want_mouse && !app.mouse_capture_active {
                     let _ = execute!(std::io::stdout(), EnableMouseCapture);
                     app.mouse_capture_active = true;
@@ -1056,19 +1071,32 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
                 }
             }
 
+            // Keypad key reporting for numpad speedwalk (see the master console loop)
+            let want_keypad = app.worlds.iter().any(|w| w.settings.numpad_walk);
+            if want_keypad && !app.keypad_mode_active {
+                let _ = execute!(std::io::stdout(), crossterm::event::PushKeyboardEnhancementFlags(
+                    crossterm::event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES));
+                app.keypad_mode_active = true;
+            } else if !want_keypad && app.keypad_mode_active {
+                let _ = execute!(std::io::stdout(), crossterm::event::PopKeyboardEnhancementFlags);
+                app.keypad_mode_active = false;
+            }
+
             // Handle Ctrl+L terminal reset and redraw request
             if app.needs_terminal_clear {
                 // Full terminal reset: unconditionally tear down and re-setup
                 let _ = execute!(std::io::stdout(), crossterm::event::DisableMouseCapture);
                 app.mouse_capture_active = false;
-                let _ = execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste);
+                let _ = execute!(std::io::stdout(), crossterm::event::DisableBracketedPaste, crossterm::event::DisableFocusChange);
                 let _ = crossterm::terminal::disable_raw_mode();
                 let _ = execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen);
+                app.keypad_mode_active = false;
                 crossterm::terminal::enable_raw_mode()?;
                 execute!(
                     std::io::stdout(),
                     crossterm::terminal::EnterAlternateScreen,
                     crossterm::event::EnableBracketedPaste,
+                    crossterm::event::EnableFocusChange,
                     crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
                     crossterm::cursor::MoveTo(0, 0)
                 )?;
@@ -1081,7 +1109,12 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
                 app.needs_terminal_clear = false;
             }
 
-            terminal.draw(|f| ui(f, &mut app))?;
+            let mut frame = terminal.draw(|f| ui(f, &mut app))?;
+            if std::mem::take(&mut app.layout_reclaim) {
+                terminal.clear()?;
+                frame = terminal.draw(|f| ui(f, &mut app))?;
+            }
+            crate::rendering::undercurl_misspellings(frame.buffer, &app);
             // Render output with crossterm (bypasses ratatui's buggy ANSI handling)
             render_output_crossterm(&app);
             needs_redraw = false;
@@ -1092,6 +1125,8 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
             maybe_event = event_stream.next() => {
                 if let Some(Ok(event)) = maybe_event {
                     match event {
+                        Event::FocusGained => app.set_terminal_focus(true),
+                        Event::FocusLost => app.set_terminal_focus(false),
                         Event::Mouse(mouse) if app.settings.mouse_enabled && app.has_new_popup() => {
                             match mouse.kind {
                                 MouseEventKind::Down(MouseButton::Left) => {
@@ -1137,6 +1172,30 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
                             }
                             needs_redraw = true;
                         }
+                        Event::Mouse(mouse) if app.settings.mouse_enabled => {
+                            // World tab bar clicks (mouse capture is held while the bar is shown)
+                            let hit = app.tab_bar_layout.hit(mouse.column, mouse.row);
+                            match (mouse.kind, hit) {
+                                (MouseEventKind::Down(MouseButton::Left), Some(idx)) => {
+                                    app.tab_drag_from = Some(idx);
+                                    app.view_remote_world(idx);
+                                    needs_redraw = true;
+                                }
+                                (MouseEventKind::Up(MouseButton::Left), _) => {
+                                    // Dropped on another tab: the server moves it and
+                                    // echoes WorldMoved back to every client
+                                    if let (Some(from), Some(to)) = (app.tab_drag_from.take(), hit) {
+                                        if from != to {
+                                            let _ = ws_tx.send(WsMessage::MoveWorld { from, to });
+                                        }
+                                    }
+                                }
+                                (MouseEventKind::Down(MouseButton::Middle), Some(idx)) => {
+                                    let _ = ws_tx.send(WsMessage::DisconnectWorld { world_index: idx });
+                                }
+                                _ => {}
+                            }
+                        }
                         Event::Paste(text) => {
                             for c in text.chars() {
                                 if c == '\n' || c == '\r' {
@@ -1162,7 +1221,7 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
                                 world.showing_splash = false; // Clear splash when adding output
                                 let seq = world.next_seq;
                                 world.next_seq += 1;
-                                world.output_lines.push(
+                                world.output_lines.push_back(
                                     OutputLine::new_client("Press Ctrl+C again within 15 seconds to exit, or use /quit".to_string(), seq)
                                 );
                                 // Keep scroll at bottom
@@ -1194,7 +1253,8 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
                                         let _ = crossterm::execute!(
                                             std::io::stdout(),
                                             crossterm::terminal::EnterAlternateScreen,
-                                            crossterm::event::EnableBracketedPaste
+                                            crossterm::event::EnableBracketedPaste,
+                                            crossterm::event::EnableFocusChange
                                         );
                                         app.add_output(&format!("Reload failed: {}", err));
                                     } else {
@@ -1258,6 +1318,9 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
                     Some(Ok(Message::Text(text))) => {
                         if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
                             app.handle_remote_ws_message(ws_msg);
+                            if let Some(at) = app.bell_flash_until {
+                                bell_flash_timer.as_mut().reset(tokio::time::Instant::from_std(at));
+                            }
                             // After processing ScrollbackLines, backfill_next may be set
                             if let Some((world_idx, before_seq, count)) = app.backfill_next.take() {
                                 let _ = ws_tx.send(WsMessage::RequestScrollback {
@@ -1345,6 +1408,10 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
                 }
             }
             // Backfill timer: start requesting scrollback history after initial delay
+            () = &mut bell_flash_timer, if app.bell_flash_until.is_some() => {
+                app.bell_flash_until = None;
+                needs_redraw = true;
+            }
             () = &mut backfill_timer, if backfill_timer_active => {
                 backfill_timer_active = false;
                 // Start backfill from the first world in the queue
@@ -1365,7 +1432,7 @@ pub(crate) async fn run_console_client(addr: &str, ssh: Option<crate::ssh::SshTa
     ws_write_handle.abort();
     disable_raw_mode()?;
     let _ = execute!(terminal.backend_mut(), DisableMouseCapture);
-    let _ = execute!(terminal.backend_mut(), crossterm::event::DisableBracketedPaste);
+    let _ = execute!(terminal.backend_mut(), crossterm::event::DisableBracketedPaste, crossterm::event::DisableFocusChange);
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     Ok(())
 }
@@ -1412,6 +1479,13 @@ pub(crate) fn handle_remote_client_key(
                     Command::Web => {
                         app.open_web_popup_new();
                     }
+                    Command::WebTokens { ref args } => {
+                        // The tokens are the server's; the popup opens on its answer
+                        match crate::commands::DeviceTokenRequest::parse(args) {
+                            Ok(request) => { let _ = ws_tx.send(request.into_ws()); }
+                            Err(usage) => app.add_output(&usage),
+                        }
+                    }
                     Command::WorldSelector => {
                         app.open_world_selector_new();
                     }
@@ -1496,7 +1570,7 @@ pub(crate) fn handle_remote_client_key(
                 app.settings.tls_proxy_enabled = settings.tls_proxy;
                 if app.settings.dictionary_path != settings.dictionary_path {
                     app.settings.dictionary_path = settings.dictionary_path.clone();
-                    app.spell_checker = SpellChecker::new(&app.settings.dictionary_path);
+                    app.reload_spell_checker();
                 }
                 app.settings.editor_side = EditorSide::from_name(&settings.editor_side);
                 // Update mouse setting; if disabled, turn off capture immediately
@@ -1514,6 +1588,12 @@ pub(crate) fn handle_remote_client_key(
                 // master (which owns rendering for its own TUI) applies its own redraw when
                 // it processes the resulting UpdateGlobalSettings message below.
                 app.settings.wrapspace = settings.wrapspace.clamp(0, 20) as u8;
+                app.settings.status_format = settings.status_format;
+                app.settings.tab_bar = settings.tab_bar;
+                app.settings.notify_activity = settings.notify_activity;
+                app.settings.scrollback_lines = (settings.scrollback_lines.max(0) as usize)
+                    .clamp(crate::MIN_SCROLLBACK_LINES, crate::MAX_SCROLLBACK_LINES);
+                app.settings.confirm_quit = settings.confirm_quit;
 
                 // Send UpdateGlobalSettings to daemon
                 let _ = ws_tx.send(WsMessage::UpdateGlobalSettings {
@@ -1562,6 +1642,16 @@ pub(crate) fn handle_remote_client_key(
                     tts_mode: app.settings.tts_mode.name().to_string(),
                     tts_speak_mode: app.settings.tts_speak_mode.name().to_string(),
                     scrollback_enabled: app.settings.scrollback_enabled,
+                    status_format: Some(app.settings.status_format.clone()),
+                    tab_bar: Some(app.settings.tab_bar),
+                    notify_activity: Some(app.settings.notify_activity),
+                    scrollback_lines: Some(app.settings.scrollback_lines),
+                    confirm_quit: Some(app.settings.confirm_quit),
+                    acme_domain: None,  // web popup setting, unchanged here
+                    acme_email: None,
+                    web_bind_addr: None,
+                    web_unix_socket: None,
+                    web_trusted_proxies: None,
                 });
             }
             NewPopupAction::WebSaved(settings) => {
@@ -1578,6 +1668,11 @@ pub(crate) fn handle_remote_client_key(
                     def.custom_data.insert("custom_cert".to_string(), settings.custom_cert.to_string());
                     def.custom_data.insert("ws_cert_file".to_string(), settings.ws_cert_file);
                     def.custom_data.insert("ws_key_file".to_string(), settings.ws_key_file);
+                    def.custom_data.insert("acme_domain".to_string(), settings.acme_domain);
+                    def.custom_data.insert("acme_email".to_string(), settings.acme_email);
+                    def.custom_data.insert("bind_addr".to_string(), settings.bind_addr);
+                    def.custom_data.insert("unix_socket".to_string(), settings.unix_socket);
+                    def.custom_data.insert("trusted_proxies".to_string(), settings.trusted_proxies);
                     def.custom_data.insert("remote_initial_lines".to_string(), settings.remote_initial_lines.to_string());
                     app.popup_manager.open(def);
                 } else {
@@ -1603,6 +1698,27 @@ pub(crate) fn handle_remote_client_key(
                             app.open_delete_action_confirm(&name, idx);
                         }
                     }
+                    ActionsListAction::Duplicate(idx) => {
+                        if let Some(copy) = crate::actions::duplicate_action(&mut app.settings.actions, idx) {
+                            let _ = ws_tx.send(WsMessage::DuplicateAction { index: idx });
+                            refresh_actions_list_popup(app, Some(copy));
+                        }
+                    }
+                    ActionsListAction::Move(idx, up) => {
+                        if let Some(moved) = crate::actions::move_action(&mut app.settings.actions, idx, up) {
+                            let _ = ws_tx.send(WsMessage::MoveAction { index: idx, up });
+                            refresh_actions_list_popup(app, Some(moved));
+                        }
+                    }
+                    ActionsListAction::Import => {
+                        // The file name is typed in the input line, where the command runs
+                        app.input.buffer = "/actions import ".to_string();
+                        app.input.cursor_position = app.input.buffer.len();
+                    }
+                    ActionsListAction::Export => {
+                        app.input.buffer = "/actions export actions.json ".to_string();
+                        app.input.cursor_position = app.input.buffer.len();
+                    }
                     ActionsListAction::Toggle(idx) => {
                         if idx < app.settings.actions.len() {
                             app.settings.actions[idx].enabled = !app.settings.actions[idx].enabled;
@@ -1611,60 +1727,7 @@ pub(crate) fn handle_remote_client_key(
                             let _ = ws_tx.send(WsMessage::UpdateActions {
                                 actions: app.settings.actions.clone()
                             });
-                            // Update the list display in the popup
-                            use popup::definitions::actions::{ActionInfo, filter_actions, ACTIONS_FIELD_FILTER, ACTIONS_FIELD_LIST};
-                            if let Some(state) = app.popup_manager.current_mut() {
-                                let filter_text = if state.editing && state.is_field_selected(ACTIONS_FIELD_FILTER) {
-                                    state.edit_buffer.clone()
-                                } else {
-                                    state.get_text(ACTIONS_FIELD_FILTER).unwrap_or("").to_string()
-                                };
-                                let all_actions: Vec<ActionInfo> = app.settings.actions
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(i, a)| ActionInfo {
-                                        name: a.name.clone(),
-                                        world: a.world.clone(),
-                                        pattern: a.display_pattern().to_string(),
-                                        enabled: a.enabled,
-                                        index: i,
-                                    })
-                                    .collect();
-                                let mut filtered = filter_actions(&all_actions, &filter_text);
-                                filtered.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                                if let Some(field) = state.field_mut(ACTIONS_FIELD_LIST) {
-                                    if let popup::FieldKind::List { items, .. } = &mut field.kind {
-                                        *items = filtered.iter().map(|info| {
-                                                #[cfg(not(windows))]
-                                                let status = if info.enabled { "[✓]" } else { "[ ]" };
-                                                #[cfg(windows)]
-                                                let status = if info.enabled { "[x]" } else { "[ ]" };
-                                                let world_part = if info.world.is_empty() {
-                                                    String::new()
-                                                } else {
-                                                    format!("({})", info.world)
-                                                };
-                                                let pattern_preview = if info.pattern.len() > 30 {
-                                                    format!("{}...", &info.pattern[..27])
-                                                } else {
-                                                    info.pattern.clone()
-                                                };
-                                                popup::ListItem {
-                                                    id: info.index.to_string(),
-                                                    columns: vec![
-                                                        format!("{} {}", status, info.name),
-                                                        world_part,
-                                                        pattern_preview,
-                                                    ],
-                                                    style: popup::ListItemStyle {
-                                                        is_disabled: !info.enabled,
-                                                        ..Default::default()
-                                                    },
-                                                }
-                                        }).collect();
-                                    }
-                                }
-                            }
+                            refresh_actions_list_popup(app, None);
                         }
                     }
                 }
@@ -1689,8 +1752,7 @@ pub(crate) fn handle_remote_client_key(
                             index: i,
                         })
                         .collect();
-                    let mut filtered = filter_actions(&all_actions, &filter_text);
-                    filtered.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+                    let filtered = filter_actions(&all_actions, &filter_text);
                     if let Some(field) = state.field_mut(ACTIONS_FIELD_LIST) {
                         if let popup::FieldKind::List { items, selected_index, scroll_offset, .. } = &mut field.kind {
                             let old_len = items.len();
@@ -1767,11 +1829,28 @@ pub(crate) fn handle_remote_client_key(
                     app.worlds[idx].settings.user = settings.user.clone();
                     app.worlds[idx].settings.password = settings.password.clone();
                     app.worlds[idx].settings.use_ssl = settings.use_ssl;
+                    app.worlds[idx].settings.tls_verify = crate::platform::TlsVerify::from_name(&settings.tls_verify).unwrap_or_default();
+                    app.worlds[idx].settings.tls_client_cert = settings.tls_client_cert.clone();
+                    app.worlds[idx].settings.tls_client_key = settings.tls_client_key.clone();
+                    app.worlds[idx].settings.ssh_host = settings.ssh_host.clone();
+                    app.worlds[idx].settings.ssh_user = settings.ssh_user.clone();
+                    app.worlds[idx].settings.ssh_key = settings.ssh_key.clone();
                     app.worlds[idx].settings.log_enabled = settings.log_enabled;
+                    app.worlds[idx].settings.log_format = crate::logging::LogFormat::from_name(&settings.log_format).unwrap_or_default();
+                    app.worlds[idx].settings.log_timestamps = crate::logging::LogTimestamps::from_name(&settings.log_timestamps).unwrap_or_default();
+                    app.worlds[idx].settings.log_session_markers = settings.log_session_markers;
                     app.worlds[idx].settings.encoding = Encoding::from_name(&settings.encoding);
                     app.worlds[idx].settings.auto_connect_type = AutoConnectType::from_name(&settings.auto_connect);
+                    app.worlds[idx].settings.login_script = settings.login_script.clone();
+                    app.worlds[idx].settings.connect_commands = settings.connect_commands.clone();
+                    app.worlds[idx].settings.input_prefix = settings.input_prefix.clone();
+                    app.worlds[idx].settings.input_suffix = settings.input_suffix.clone();
                     app.worlds[idx].settings.keep_alive_type = KeepAliveType::from_name(&settings.keep_alive);
                     app.worlds[idx].settings.keep_alive_cmd = settings.keep_alive_cmd.clone();
+                    let keep_alive_interval_secs = crate::WorldSettings::parse_keep_alive_interval(&settings.keep_alive_interval);
+                    app.worlds[idx].settings.keep_alive_interval_secs = keep_alive_interval_secs;
+                    let silence_alert_mins = settings.silence_alert.trim().parse::<u32>().unwrap_or(0);
+                    app.worlds[idx].settings.silence_alert_mins = silence_alert_mins;
                     app.worlds[idx].settings.gmcp_packages = settings.gmcp_packages.clone();
                     let (ar_secs, ar_on_web) = crate::WorldSettings::parse_auto_reconnect(&settings.auto_reconnect_secs);
                     app.worlds[idx].settings.auto_reconnect_secs = ar_secs;
@@ -1789,10 +1868,25 @@ pub(crate) fn handle_remote_client_key(
                         log_enabled: settings.log_enabled,
                         encoding: settings.encoding,
                         auto_login: settings.auto_connect,
+                        login_script: Some(settings.login_script),
+                        connect_commands: Some(settings.connect_commands),
+                        input_prefix: Some(settings.input_prefix),
+                        input_suffix: Some(settings.input_suffix),
                         keep_alive_type: settings.keep_alive,
                         keep_alive_cmd: settings.keep_alive_cmd,
+                        keep_alive_interval_secs,
+                        silence_alert_mins,
                         gmcp_packages: settings.gmcp_packages,
                         auto_reconnect_secs: settings.auto_reconnect_secs,
+                        log_format: settings.log_format,
+                        log_timestamps: settings.log_timestamps,
+                        log_session_markers: Some(settings.log_session_markers),
+                        tls_verify: settings.tls_verify,
+                        tls_client_cert: settings.tls_client_cert,
+                        tls_client_key: settings.tls_client_key,
+                        ssh_host: settings.ssh_host,
+                        ssh_user: settings.ssh_user,
+                        ssh_key: settings.ssh_key,
                     });
                 }
             }
@@ -1807,6 +1901,85 @@ pub(crate) fn handle_remote_client_key(
             NewPopupAction::NotesList(_action) => {
                 // Notes list not used in remote client
             }
+            NewPopupAction::Macros(action) => {
+                let world_idx = app.current_world_index;
+                match action {
+                    MacrosAction::Add => {
+                        app.input.buffer = "/macro ".to_string();
+                        app.input.cursor_position = app.input.buffer.len();
+                    }
+                    MacrosAction::Edit(key) => {
+                        let text = app.worlds[world_idx].settings.macros.get(&key).cloned().unwrap_or_default();
+                        app.input.buffer = format!("/macro {} {}", key, text);
+                        app.input.cursor_position = app.input.buffer.len();
+                    }
+                    MacrosAction::Delete(key) => {
+                        // Remove locally and rebuild the popup in place; the daemon's
+                        // WorldSettingsUpdated brings the same list back
+                        let pos = app.worlds[world_idx].settings.macros.keys().position(|k| *k == key).unwrap_or(0);
+                        app.worlds[world_idx].settings.macros.remove(&key);
+                        let _ = ws_tx.send(WsMessage::SendCommand {
+                            world_index: world_idx,
+                            command: format!("/macro -d {}", key),
+                        });
+                        app.popup_manager.close();
+                        app.open_macros_popup(pos);
+                    }
+                }
+            }
+            NewPopupAction::TagRoutes(action) => {
+                // Apply locally and rebuild the popup, then send the whole list to the
+                // daemon (its TagRoutesUpdated reply rebuilds the popup again, in place)
+                let idx = match action {
+                    TagRoutesAction::Toggle(idx) => {
+                        if let Some(route) = app.settings.tag_routes.get_mut(idx) {
+                            route.enabled = !route.enabled;
+                        }
+                        idx
+                    }
+                    TagRoutesAction::Delete(idx) => {
+                        if idx < app.settings.tag_routes.len() {
+                            app.settings.tag_routes.remove(idx);
+                        }
+                        idx
+                    }
+                };
+                let _ = ws_tx.send(WsMessage::UpdateTagRoutes { routes: app.settings.tag_routes.clone() });
+                app.popup_manager.close();
+                app.open_tag_routes_popup(idx);
+            }
+            NewPopupAction::Gags(action) => {
+                // Apply locally and rebuild the popup, then send the whole list to the
+                // daemon (its GagsUpdated reply rebuilds the popup again, in place)
+                let idx = match action {
+                    GagsAction::Add => {
+                        app.input.buffer = "/gag ".to_string();
+                        app.input.cursor_position = app.input.buffer.len();
+                        return false;
+                    }
+                    GagsAction::Toggle(idx) => {
+                        if let Some(gag) = app.settings.gags.get_mut(idx) {
+                            gag.enabled = !gag.enabled;
+                        }
+                        idx
+                    }
+                    GagsAction::Keep(idx) => {
+                        if let Some(gag) = app.settings.gags.get_mut(idx) {
+                            gag.temporary = !gag.temporary;
+                        }
+                        idx
+                    }
+                    GagsAction::Delete(idx) => {
+                        if idx < app.settings.gags.len() {
+                            app.settings.gags.remove(idx);
+                        }
+                        idx
+                    }
+                };
+                let _ = ws_tx.send(WsMessage::UpdateGags { gags: app.settings.gags.clone() });
+                app.popup_manager.close();
+                app.open_gags_popup(idx);
+            }
             NewPopupAction::RecentWorlds(action) => {
                 match action {
                     RecentWorldsAction::Switch(name) => {
@@ -1818,6 +1991,26 @@ pub(crate) fn handle_remote_client_key(
                     RecentWorldsAction::Close => {}
                 }
             }
+            NewPopupAction::OpenUrl(url) => {
+                // Opened on this machine, where the remote console runs
+                if let Err(e) = crate::platform::open_url(&url) {
+                    app.add_output(&format!("Could not open {}: {}", url, e));
+                }
+            }
+            NewPopupAction::SendUrl(url) => {
+                let _ = ws_tx.send(WsMessage::SendCommand {
+                    world_index: app.current_world_index,
+                    command: url,
+                });
+            }
+            NewPopupAction::Spell(action) => {
+                // The input and its dictionary are this console's own
+                app.apply_spell_popup(action);
+            }
+            NewPopupAction::RevokeDeviceToken(name) => {
+                // The tokens are the server's; its DeviceTokens answer refreshes the popup
+                let _ = ws_tx.send(WsMessage::RevokeDeviceToken { name });
+            }
             NewPopupAction::ImportSubmit { .. } => {
                 // /import's in-process driver (plan i-d-like-to-make-snuggly-rain.md, step 8)
                 // is master-console-only for now — this popup is never opened from a
@@ -1834,6 +2027,15 @@ pub(crate) fn handle_remote_client_key(
         handle_remote_filter_popup_key(app, key);
         return false;
     }
+    if handle_copy_mode_key(app, &key) {
+        return false;
+    }
+    if handle_search_key(app, &key) {
+        return false;
+    }
+    if app.input.reverse_search.is_some() && app.input.reverse_search_key(key.code, key.modifiers) {
+        return false;
+    }
 
     // Ctrl+V literal next: insert next character literally
     if app.literal_next {
@@ -1871,6 +2073,29 @@ pub(crate) fn handle_remote_client_key(
         }
     }
 
+    // Numpad speedwalk (/numpad): keypad digits walk when enabled for this world
+    if app.current_world().settings.numpad_walk {
+        if let Some(cmd) = keybindings::numpad_walk_command(&key) {
+            let _ = ws_tx.send(WsMessage::SendCommand {
+                world_index: app.current_world_index,
+                command: cmd.to_string(),
+            });
+            return false;
+        }
+    }
+
+    // Per-world function key macros (/macro) override every other binding
+    if let KeyCode::F(n) = key.code {
+        let name = keybindings::fkey_macro_name(n, key.modifiers);
+        if let Some(text) = app.current_world().settings.macros.get(&name).cloned() {
+            let _ = ws_tx.send(WsMessage::SendCommand {
+                world_index: app.current_world_index,
+                command: text,
+            });
+            return false;
+        }
+    }
+
     // Check TF /bind bindings first (runtime bindings from /bind command)
     if let Some(ref name) = key_name {
         let tf_name = canonical_to_tf_key_name(name);
@@ -1883,6 +2108,14 @@ pub(crate) fn handle_remote_client_key(
         }
     }
 
+    // Ctrl+F on an empty input line searches the scrollback
+    if key_name.as_deref() == Some("^F")
+        && app.input.buffer.is_empty()
+        && app.keybindings.get_action("^F") == Some("cursor_right")
+    {
+        return dispatch_remote_action("scrollback_search", app, ws_tx);
+    }
+
     // Check configurable action bindings
     if let Some(ref name) = key_name {
         if let Some(action_id) = app.keybindings.get_action(name).map(|s| s.to_string()) {
@@ -1892,7 +2125,7 @@ pub(crate) fn handle_remote_client_key(
 
     // Enter key (always active, not bound via action system)
     if key.code == Enter {
-            let cmd = app.input.take_input();
+            let cmd = if app.input_masked() { app.input.take_secret() } else { app.input.take_input() };
             if cmd.is_empty() {
                 // Send empty command to server (some MUDs use this for "look")
                 let _ = ws_tx.send(WsMessage::SendCommand {
@@ -1904,6 +2137,12 @@ pub(crate) fn handle_remote_client_key(
                 let parsed = parse_command(&cmd);
                 match parsed {
                     Command::Quit => return true,
+                    // The server keeps running without this client anyway
+                    Command::Detach => return true,
+                    // Bare /macro opens the popup, from the macros WorldSettingsUpdated sent
+                    Command::Macro { ref args } if args.is_empty() => {
+                        app.open_macros_popup(0);
+                    }
                     Command::Help => {
                         app.open_help_popup_new();
                     }
@@ -1922,6 +2161,37 @@ pub(crate) fn handle_remote_client_key(
                     Command::Menu => {
                         app.open_menu_popup_new();
                     }
+                    Command::Urls => {
+                        // From the locally mirrored output, so links open on this machine
+                        app.open_urls_popup();
+                    }
+                    Command::AddWord { ref word } => {
+                        // Into this console's dictionary, which checks its input
+                        for line in crate::commands::addword_command(app, word) {
+                            app.add_output(&line);
+                        }
+                    }
+                    Command::SpellIgnore { ref word } => {
+                        for line in crate::commands::spellignore_command(app, word) {
+                            app.add_output(&line);
+                        }
+                    }
+                    Command::Unignore { ref word } => {
+                        for line in crate::commands::unignore_command(app, word) {
+                            app.add_output(&line);
+                        }
+                    }
+                    Command::Split { ref args } => {
+                        for line in crate::commands::split_command(app, args, App::view_remote_world) {
+                            app.add_output(&line);
+                        }
+                    }
+                    Command::Capture { ref args } if crate::capture::is_console_subcommand(args) => {
+                        // Pane and view are local layout; list and clear go to the master
+                        for line in crate::commands::capture_console_command(app, args, App::view_remote_world) {
+                            app.add_output(&line);
+                        }
+                    }
                     Command::Font => {
                         app.add_output("Font settings are available in the web and GUI interfaces.");
                     }
@@ -1931,6 +2201,13 @@ pub(crate) fn handle_remote_client_key(
                     Command::Web => {
                         app.open_web_popup_new();
                     }
+                    Command::WebTokens { ref args } => {
+                        // The tokens are the server's; the popup opens on its answer
+                        match crate::commands::DeviceTokenRequest::parse(args) {
+                            Ok(request) => { let _ = ws_tx.send(request.into_ws()); }
+                            Err(usage) => app.add_output(&usage),
+                        }
+                    }
                     Command::WorldSelector => {
                         app.open_world_selector_new();
                     }
@@ -1952,7 +2229,7 @@ pub(crate) fn handle_remote_client_key(
                             let ci = app.current_world_index;
                             let seq = app.worlds[ci].next_seq;
                             app.worlds[ci].next_seq += 1;
-                            app.worlds[ci].output_lines.push(
+                            app.worlds[ci].output_lines.push_back(
                                 OutputLine::new_client(format!("World '{}' not found.", name), seq)
                             );
                         }
@@ -1973,7 +2250,7 @@ pub(crate) fn handle_remote_client_key(
                             let ci = app.current_world_index;
                             let seq = app.worlds[ci].next_seq;
                             app.worlds[ci].next_seq += 1;
-                            app.worlds[ci].output_lines.push(
+                            app.worlds[ci].output_lines.push_back(
                                 OutputLine::new_client(format!("World '{}' not found.", name), seq)
                             );
                         }
@@ -2070,6 +2347,7 @@ pub(crate) fn handle_remote_client_key(
             app.input.insert_char(c);
             app.last_input_was_delete = false;
             app.check_temp_conversion();
+            app.check_autocorrect();
         }
     }
     false
@@ -2082,6 +2360,11 @@ pub(crate) fn dispatch_remote_action(
     app: &mut App,
     ws_tx: &mpsc::UnboundedSender<WsMessage>,
 ) -> bool {
+    // Alt+N: jump straight to world N
+    if let Some(idx) = crate::keybindings::world_number_action(action) {
+        app.view_remote_world(idx);
+        return false;
+    }
     match action {
         // Cursor Movement
         "cursor_left" => { app.input.move_cursor_left(); }
@@ -2124,6 +2407,7 @@ pub(crate) fn dispatch_remote_action(
         "history_next" => { app.input.history_next(); }
         "history_search_backward" => { app.input.history_search_backward(); }
         "history_search_forward" => { app.input.history_search_forward(); }
+        "history_search_reverse" => { app.input.reverse_search(); }
 
         // Scrollback
         "scroll_page_up" => {
@@ -2132,7 +2416,7 @@ pub(crate) fn dispatch_remote_action(
             let new_offset = current_offset.saturating_sub(scroll_amount.max(1));
             app.current_world_mut().scroll_offset = new_offset;
             if new_offset == 0 {
-                let before_seq = app.current_world().output_lines.first().map(|l| l.seq);
+                let before_seq = app.current_world().output_lines.front().map(|l| l.seq);
                 let _ = ws_tx.send(WsMessage::RequestScrollback {
                     world_index: app.current_world_index,
                     count: scroll_amount.max(1),
@@ -2181,6 +2465,11 @@ pub(crate) fn dispatch_remote_action(
             });
         }
         "tab_key" => {
+            if app.current_world().settings.emoji_shortcodes
+                && app.input.complete_emoji_shortcode(crate::encoding::emoji_shortcodes_matching)
+            {
+                return false;
+            }
             let has_pending = !app.current_world().pending_lines.is_empty() || app.current_world().pending_count > 0;
             if app.current_world().paused && has_pending {
                 let release_count = app.output_height.saturating_sub(2) as usize;
@@ -2215,6 +2504,14 @@ pub(crate) fn dispatch_remote_action(
         "recent_worlds" => {
             app.open_recent_worlds_popup();
         }
+        "world_switcher" => {
+            app.open_world_selector_new();
+        }
+        "split_focus" => {
+            if let Some(idx) = app.split.next_focus().and_then(|name| app.find_world(name)) {
+                app.view_remote_world(idx);
+            }
+        }
         "world_previous" => {
             let _ = ws_tx.send(WsMessage::CalculatePrevWorld { current_index: app.current_world_index });
         }
@@ -2252,6 +2549,19 @@ pub(crate) fn dispatch_remote_action(
         "spell_check" => {
             // Spell check not easily supported in remote mode
         }
+        "spell_add_word" => {
+            // The input is checked against this console's own dictionary
+            app.spell_add_word();
+        }
+        "spell_ignore" => {
+            app.spell_ignore_word();
+        }
+        "spell_undo_autocorrect" => {
+            app.undo_autocorrect();
+        }
+        "spell_menu" => {
+            app.open_spell_popup();
+        }
 
         // Clay Extensions
         "toggle_tags" => {
@@ -2266,10 +2576,24 @@ pub(crate) fn dispatch_remote_action(
                 app.filter_popup.open();
             }
         }
+        "search_popup" | "scrollback_search" => {
+            if action == "search_popup" {
+                app.search_popup.open();
+            } else {
+                app.search_popup.open_in_place();
+            }
+            app.current_world_mut().search_active = true;
+            app.needs_output_redraw = true;
+        }
+        "copy_mode" => start_copy_mode(app),
         "toggle_action_highlight" => {
             app.highlight_actions = !app.highlight_actions;
             app.needs_output_redraw = true;
         }
+        "toggle_timestamps" => {
+            app.timestamps_toggled = !app.timestamps_toggled;
+            app.needs_output_redraw = true;
+        }
         "toggle_gmcp_media" => {
             let idx = app.current_world_index;
             app.worlds[idx].gmcp_user_enabled = !app.worlds[idx].gmcp_user_enabled;
@@ -2312,6 +2636,11 @@ pub(crate) fn apply_remote_web_settings(
         app.settings.websocket_key_file = String::new();
     }
     app.settings.remote_initial_lines = settings.remote_initial_lines.clamp(10, 5000) as u16;
+    app.settings.acme_domain = settings.acme_domain.clone();
+    app.settings.acme_email = settings.acme_email.clone();
+    app.settings.web_bind_addr = settings.bind_addr.trim().to_string();
+    app.settings.web_unix_socket = settings.unix_socket.trim().to_string();
+    app.settings.web_trusted_proxies = settings.trusted_proxies.trim().to_string();
 
     let _ = ws_tx.send(crate::websocket::WsMessage::UpdateGlobalSettings {
         more_mode_enabled: app.settings.more_mode_enabled,
@@ -2357,6 +2686,16 @@ pub(crate) fn apply_remote_web_settings(
         tts_mode: app.settings.tts_mode.name().to_string(),
         tts_speak_mode: app.settings.tts_speak_mode.name().to_string(),
         scrollback_enabled: app.settings.scrollback_enabled,
+        status_format: None,  // unchanged — this function only touches web settings
+        tab_bar: None,
+        notify_activity: None,
+        scrollback_lines: None,
+        confirm_quit: None,
+        acme_domain: Some(settings.acme_domain.clone()),
+        acme_email: Some(settings.acme_email.clone()),
+        web_bind_addr: Some(settings.bind_addr.trim().to_string()),
+        web_unix_socket: Some(settings.unix_socket.trim().to_string()),
+        web_trusted_proxies: Some(settings.trusted_proxies.trim().to_string()),
     });
 }
 pub(crate) fn handle_remote_filter_popup_key(app: &mut App, key: KeyEvent) {
@@ -11,10 +11,10 @@ use ratatui::{
 };
 
 use crate::{
-    App, World, OutputLine, CachedNow, Settings,
+    App, World, OutputLine, CachedNow, Settings, TimestampMode,
     EditorSide, EditorFocus,
     WsMessage,
-    strip_ansi_codes, display_width, chars_for_display_width,
+    strip_ansi_codes, display_width, chars_for_display_width, grapheme_width,
     is_ansi_only_line, is_visually_empty, has_background_color,
     colorize_square_emojis, wrap_urls_with_osc8, convert_discord_emojis_with_links,
     strip_mud_tag, convert_temperatures, get_current_time_12hr, color_name_to_ansi_bg,
@@ -23,6 +23,8 @@ use crate::{
     popup,
 };
 use crate::util::{NLI_PREFIX_WIDTH, ARCHIVE_PREFIX_WIDTH};
+use crate::line_cache::{RenderKey, View};
+use unicode_segmentation::UnicodeSegmentation;
 
 // Break characters for word wrapping within long words
 const BREAK_CHARS: &[char] = &[']', ')', ',', '\\', '/', '-', '_', '&', '=', '?', ';'];
@@ -90,6 +92,15 @@ pub(crate) fn wrap_ansi_line(line: &str, max_width: usize, indent: usize) -> Vec
     };
 
     let chars: Vec<char> = line.chars().collect();
+    // Where each grapheme cluster starts, so a wrap never splits an emoji sequence or a
+    // letter from its combining marks. ESC is a control char and always starts a cluster.
+    let mut cluster_start = vec![false; chars.len() + 1];
+    let mut char_idx = 0;
+    for g in line.graphemes(true) {
+        cluster_start[char_idx] = true;
+        char_idx += g.chars().count();
+    }
+    cluster_start[chars.len()] = true;
     let mut i = 0;
 
     while i < chars.len() {
@@ -184,11 +195,15 @@ pub(crate) fn wrap_ansi_line(line: &str, max_width: usize, indent: usize) -> Vec
             i += 1;
             continue;
         } else {
+            // The visible cluster starting here, ending at the next cluster or escape
+            let mut end = i + 1;
+            while !cluster_start[end] && chars[end] != '\x1b' {
+                end += 1;
+            }
+            let cluster: String = chars[i..end].iter().collect();
             // Tab characters are rendered as 8 spaces (see process_output_line),
             // but unicode_width returns None/0 for tabs. Use 8 to match rendering.
-            let char_width = if c == '\t' { 8 } else {
-                unicode_width::UnicodeWidthChar::width(c).unwrap_or(0)
-            };
+            let char_width = if c == '\t' { 8 } else { grapheme_width(&cluster) };
 
             // Check if we need to wrap before adding this character
             if current_width + char_width > max_width && current_width > 0 {
@@ -237,7 +252,7 @@ pub(crate) fn wrap_ansi_line(line: &str, max_width: usize, indent: usize) -> Vec
             }
 
             // Add the character
-            current_line.push(c);
+            current_line.push_str(&cluster);
             current_width += char_width;
 
             // Track break opportunities - save byte position (not string clone)
@@ -259,7 +274,7 @@ pub(crate) fn wrap_ansi_line(line: &str, max_width: usize, indent: usize) -> Vec
                 last_break_hyperlink = active_hyperlink.clone();
             }
 
-            i += 1;
+            i = end;
         }
     }
 
@@ -275,14 +290,48 @@ pub(crate) fn wrap_ansi_line(line: &str, max_width: usize, indent: usize) -> Vec
 pub(crate) fn ui(f: &mut Frame, app: &mut App) {
     let total_height = f.size().height.max(3);  // Minimum 3 lines for output + separator + input
 
-    // Layout: output area, separator bar (1 line), input area
+    // Layout: output area, optional capture pane, optional world tab bar (1 line),
+    // separator bar (1 line), input area
+    let tab_bar_height = u16::from(app.settings.tab_bar);
     let separator_height = 1;
     let input_total_height = app.input_height;
-    let output_height = total_height.saturating_sub(separator_height + input_total_height);
+    let available = total_height.saturating_sub(tab_bar_height + separator_height + input_total_height);
+    let pane_world = capture_pane_world(app);
+    let pane_height = app.panes.pane_rows(pane_world.is_some(), available);
+    let output_height = available - pane_height;
+
+    // Split panes (see split.rs) follow the current world; scrolling and more-mode
+    // then work on the focused pane's rows
+    let current_name = app.current_world().name.clone();
+    let worlds = &app.worlds;
+    app.split.sync(&current_name, |name| worlds.iter().any(|w| w.name.eq_ignore_ascii_case(name)));
+    let split_active = app.split.is_active() && !app.editor.visible;
+    let focused_height = if split_active && !app.split.vertical {
+        crate::split::split_lengths(output_height, app.split.panes.len(), 0)[app.split.focus].saturating_sub(1)
+    } else if split_active {
+        output_height.saturating_sub(1)
+    } else {
+        output_height
+    };
+
+    // Output rows ratatui draws this frame (capture pane, split panes, editor); if it
+    // takes over rows crossterm drew last frame, ratatui's buffer doesn't know what's on
+    // screen there, so the frame is redrawn from a cleared screen
+    if !(app.confirm_dialog.visible || app.has_new_popup()) {
+        let ratatui_rows = if split_active || app.editor.visible || app.current_world().showing_splash {
+            available
+        } else {
+            pane_height
+        };
+        if ratatui_rows > app.ratatui_output_rows {
+            app.layout_reclaim = true;
+        }
+        app.ratatui_output_rows = ratatui_rows;
+    }
 
     // Store output dimensions for scrolling and more-mode calculations
     // Use max(1) to prevent any division by zero elsewhere
-    let new_output_height = output_height.max(1);
+    let new_output_height = focused_height.max(1);
     let new_output_width = f.size().width.max(1);
     // Mark output for redraw if dimensions changed (terminal resize)
     let dimensions_changed = new_output_height != app.output_height || new_output_width != app.output_width;
@@ -307,18 +356,22 @@ pub(crate) fn ui(f: &mut Frame, app: &mut App) {
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(output_height),
+            Constraint::Length(pane_height),
+            Constraint::Length(tab_bar_height),
             Constraint::Length(separator_height),
             Constraint::Length(input_total_height),
         ])
         .split(f.size());
 
     let output_area = chunks[0];
-    let separator_area = chunks[1];
-    let input_area = chunks[2];
+    let pane_area = chunks[1];
+    let tab_bar_area = chunks[2];
+    let separator_area = chunks[3];
+    let input_area = chunks[4];
 
     // Update input dimensions and prompt length for viewport calculation
     app.input.set_dimensions(input_area.width, app.input_height);
-    app.input.prompt_len = strip_ansi_codes(&app.current_world().prompt).chars().count();
+    app.input.prompt_len = display_width(&strip_ansi_codes(&app.input_prompt()));
 
     // Check if editor is visible - split the output area if so
     if app.editor.visible {
@@ -362,11 +415,25 @@ pub(crate) fn ui(f: &mut Frame, app: &mut App) {
 
         // Render world output on the other half
         render_output_area(f, app, world_area);
+    } else if split_active {
+        render_split_panes(f, app, output_area);
     } else {
         // Normal full-width output area
         render_output_area(f, app, output_area);
     }
 
+    // Render capture pane (see capture.rs)
+    if let (Some(idx), true) = (pane_world, pane_height > 0) {
+        render_capture_pane(f, app, idx, pane_area);
+    }
+
+    // Render world tab bar (see tab_bar.rs)
+    if app.settings.tab_bar {
+        render_tab_bar(f, app, tab_bar_area);
+    } else {
+        app.tab_bar_layout = crate::tab_bar::TabLayout::default();
+    }
+
     // Render separator bar
     render_separator_bar(f, app, separator_area);
 
@@ -383,7 +450,7 @@ pub(crate) fn ui(f: &mut Frame, app: &mut App) {
 
 /// Process an output line for display. Returns None if the line should be skipped.
 /// Returns Some(processed_text) with emoji colorization, client prefix, timestamp/tags, and tab expansion applied.
-pub(crate) fn process_output_line(line: &OutputLine, show_tags: bool, temp_convert_enabled: bool, zwj_enabled: bool, cached_now: &CachedNow) -> Option<String> {
+pub(crate) fn process_output_line(line: &OutputLine, show_tags: bool, temp_convert_enabled: bool, zwj_enabled: bool, timestamps: TimestampMode, cached_now: &CachedNow) -> Option<String> {
     // Skip gagged lines unless show_tags (F2) is enabled
     if line.gagged && !show_tags {
         return None;
@@ -412,6 +479,9 @@ pub(crate) fn process_output_line(line: &OutputLine, show_tags: bool, temp_conve
             text
         };
         format!("\x1b[36m{}\x1b[0m {}", line.format_timestamp_with_now(cached_now), text_with_temps)
+    } else if let Some(clock) = line.format_clock(timestamps) {
+        // Timestamp prefix (display only; see /timestamps) in dim grey
+        format!("\x1b[90m{}\x1b[0m {}", clock, strip_mud_tag(&text))
     } else {
         strip_mud_tag(&text)
     };
@@ -446,12 +516,13 @@ pub fn build_display_lines(
     let temp_convert_enabled = settings.temp_convert_enabled;
     let zwj_enabled = settings.zwj_enabled;
     let new_line_indicator = settings.new_line_indicator;
+    let timestamps = world.settings.timestamps.unwrap_or(settings.timestamps);
     let nli_prefix_width: usize = NLI_PREFIX_WIDTH;
     let min_old_context: usize = if new_line_indicator { 2 } else { 0 };
     let cached_now = CachedNow::new();
 
     let expand_and_wrap = |line: &OutputLine, term_width: usize, show_tags: bool, highlight_f8: bool, cached_now: &CachedNow| -> Vec<(String, bool, Option<String>, bool)> {
-        let expanded = match process_output_line(line, show_tags, temp_convert_enabled, zwj_enabled, cached_now) {
+        let expanded = match process_output_line(line, show_tags, temp_convert_enabled, zwj_enabled, timestamps, cached_now) {
             Some(text) if text.is_empty() => return vec![("".to_string(), false, None, false)],
             Some(text) => text,
             None => return Vec::new(),
@@ -571,9 +642,9 @@ pub(crate) fn render_output_crossterm(app: &App) {
     use std::io::Write;
     use crossterm::{style::Print, QueueableCommand};
 
-    // Skip if showing splash screen or editor is visible
-    // When editor is visible, ratatui handles all rendering for the split-screen layout
-    if app.current_world().showing_splash || app.editor.visible {
+    // Skip if showing splash screen, editor is visible or the output is split
+    // In those cases ratatui handles all rendering for the split-screen layout
+    if app.current_world().showing_splash || app.editor.visible || app.split.is_active() {
         return;
     }
 
@@ -653,6 +724,12 @@ pub(crate) fn render_output_crossterm(app: &App) {
     let show_tags = app.show_tags;
     let temp_convert_enabled = app.settings.temp_convert_enabled;
     let zwj_enabled = app.settings.zwj_enabled;
+    let timestamps = app.timestamp_mode(app.current_world_index);
+    let palette = app.world_palette(app.current_world_index);
+    // /tint: the world's background color, behind whatever the MUD doesn't color itself
+    let tint_code = app.world_tint(app.current_world_index).map(|c| {
+        crate::color_depth::downgrade(&format!("\x1b[48;2;{};{};{}m", c.r, c.g, c.b), app.color_depth)
+    });
     let highlight_actions = app.highlight_actions;
     let world_name = &world.name;
     // Pre-compile action patterns once (not per-line)
@@ -670,12 +747,41 @@ pub(crate) fn render_output_crossterm(app: &App) {
     let nli_prefix_width: usize = NLI_PREFIX_WIDTH;
     // Minimum old (non-new) context lines to show at top when switching worlds
     let min_old_context: usize = if new_line_indicator { 2 } else { 0 };
-    let expand_and_wrap = |line: &OutputLine, term_width: usize, show_tags: bool, highlight_f8: bool, cached_now: &CachedNow| -> Vec<(String, bool, Option<String>, bool, bool)> {
-        let expanded = match process_output_line(line, show_tags, temp_convert_enabled, zwj_enabled, cached_now) {
-            Some(text) if text.is_empty() => return vec![("".to_string(), false, None, false, false)],
+    let search_regex = app.search_popup.highlight_regex();
+    let copy_mode = &app.copy_mode;
+    world.console_rows.borrow_mut().begin_frame(RenderKey {
+        view: View::Console,
+        width: term_width,
+        show_tags,
+        temp_convert: temp_convert_enabled,
+        zwj: zwj_enabled,
+        timestamps,
+        wrapspace: app.settings.wrapspace as usize,
+        new_line_indicator,
+        theme: app.settings.theme,
+        palette,
+    });
+    let build_rows = |line_idx: usize, line: &OutputLine, term_width: usize, show_tags: bool, cached_now: &CachedNow| -> Vec<String> {
+        let expanded = match process_output_line(line, show_tags, temp_convert_enabled, zwj_enabled, timestamps, cached_now) {
             Some(text) => text,
             None => return Vec::new(),
         };
+        let expanded = match &palette {
+            Some(p) => crate::theme::remap_ansi_colors(&expanded, p),
+            None => expanded,
+        };
+        let expanded = crate::color_depth::downgrade(&expanded, app.color_depth);
+        // Copy mode: cursor cell or selected columns in reverse video (a space stands in
+        // for the cursor past the end of the line)
+        let len = if copy_mode.active { strip_ansi_codes(&expanded).chars().count() } else { 0 };
+        let expanded = match copy_mode.highlight_range(line_idx, len) {
+            Some((start, end)) if start < end.min(len) => crate::util::highlight_columns(&expanded, start, end),
+            Some(_) if line_idx == copy_mode.line => format!("{}\x1b[7m \x1b[27m", expanded),
+            _ => expanded,
+        };
+        if expanded.is_empty() {
+            return vec![String::new()];
+        }
         // Wrap URLs with OSC 8 hyperlink sequences for terminal clickability
         let with_links = wrap_urls_with_osc8(&expanded);
         // Convert Discord custom emojis to clickable :name: links (after URL wrapping to avoid conflicts)
@@ -685,7 +791,11 @@ pub(crate) fn render_output_crossterm(app: &App) {
         } else {
             convert_discord_emojis_with_links(&with_links)
         };
-        let hl_color = line.highlight_color.clone();
+        // Scrollback search: show matches in reverse video
+        let with_emoji_links = match search_regex {
+            Some(re) => crate::util::highlight_matches(&with_emoji_links, re),
+            None => with_emoji_links,
+        };
         let mn = line.marked_new;
         let fa = line.from_archive;
         // Reduce wrap width to reserve space for any prefixes printed separately in the draw loop.
@@ -702,8 +812,20 @@ pub(crate) fn render_output_crossterm(app: &App) {
             wrap_width = wrap_width.saturating_sub(ARCHIVE_PREFIX_WIDTH);
         }
         wrap_ansi_line(&with_emoji_links, wrap_width, app.settings.wrapspace as usize)
-            .into_iter()
-            .map(|s| (s, highlight_f8, hl_color.clone(), mn, fa))
+    };
+    let expand_and_wrap = |line_idx: usize, line: &OutputLine, term_width: usize, show_tags: bool, highlight_f8: bool, cached_now: &CachedNow| -> Vec<(String, bool, Option<String>, bool, bool)> {
+        // Search and copy mode highlighting change with every keypress, so skip the cache
+        let rows = if search_regex.is_none() && copy_mode.highlight_range(line_idx, 0).is_none() {
+            world.console_rows.borrow_mut().rows(line, || build_rows(line_idx, line, term_width, show_tags, cached_now))
+        } else {
+            build_rows(line_idx, line, term_width, show_tags, cached_now)
+        };
+        // A blank line is drawn without the new-line or archive prefix
+        if rows.len() == 1 && rows[0].is_empty() {
+            return vec![(String::new(), false, None, false, false)];
+        }
+        rows.into_iter()
+            .map(|s| (s, highlight_f8, line.highlight_color.clone(), line.marked_new, line.from_archive))
             .collect()
     };
 
@@ -729,7 +851,7 @@ pub(crate) fn render_output_crossterm(app: &App) {
                 if line_idx < world.output_lines.len() {
                     let line = &world.output_lines[line_idx];
                     let highlight = should_highlight(line);
-                    let wrapped = expand_and_wrap(line, term_width, show_tags, highlight, &cached_now);
+                    let wrapped = expand_and_wrap(line_idx, line, term_width, show_tags, highlight, &cached_now);
 
                     for w in wrapped.into_iter().rev() {
                         rev_lines.push(w);
@@ -749,7 +871,7 @@ pub(crate) fn render_output_crossterm(app: &App) {
                     if line_idx < world.output_lines.len() {
                         let line = &world.output_lines[line_idx];
                         let highlight = should_highlight(line);
-                        let wrapped = expand_and_wrap(line, term_width, show_tags, highlight, &cached_now);
+                        let wrapped = expand_and_wrap(line_idx, line, term_width, show_tags, highlight, &cached_now);
 
                         for w in wrapped {
                             visual_lines.push(w);
@@ -775,7 +897,7 @@ pub(crate) fn render_output_crossterm(app: &App) {
             first_line_idx = line_idx;
             let line = &world.output_lines[line_idx];
             let highlight = should_highlight(line);
-            let mut wrapped = expand_and_wrap(line, term_width, show_tags, highlight, &cached_now);
+            let mut wrapped = expand_and_wrap(line_idx, line, term_width, show_tags, highlight, &cached_now);
 
             // Partial line display: truncate the first visible line encountered from the end.
             // This may not be end_line itself if gagged lines were appended after the trigger.
@@ -813,7 +935,7 @@ pub(crate) fn render_output_crossterm(app: &App) {
             for line_idx in (end_line + 1)..world.output_lines.len() {
                 let line = &world.output_lines[line_idx];
                 let highlight = should_highlight(line);
-                let wrapped = expand_and_wrap(line, term_width, show_tags, highlight, &cached_now);
+                let wrapped = expand_and_wrap(line_idx, line, term_width, show_tags, highlight, &cached_now);
 
                 for w in wrapped {
                     visual_lines.push(w);
@@ -826,6 +948,8 @@ pub(crate) fn render_output_crossterm(app: &App) {
         }
     }
 
+    world.console_rows.borrow_mut().end_frame();
+
     // Debug: verify output line sequence order (only check visible range, log mismatches)
     if is_debug_enabled() && !world.output_lines.is_empty() {
         let check_start = first_line_idx;
@@ -906,14 +1030,15 @@ pub(crate) fn render_output_crossterm(app: &App) {
             let _ = stdout.queue(Print("🛢️ "));
         }
 
-        // Determine background color: /highlight color takes priority, then F8 highlight
+        // Determine background color: /highlight color takes priority, then F8 highlight,
+        // then the world's /tint
         let bg_code = if let Some(color) = hl_color {
             Some(color_name_to_ansi_bg(color))
         } else if *highlight_f8 {
             // Dark yellow/brown background for F8 action-matched lines
             Some("\x1b[48;5;58m".to_string())
         } else {
-            None
+            tint_code.clone()
         };
 
         if let Some(ref bg) = bg_code {
@@ -939,7 +1064,14 @@ pub(crate) fn render_output_crossterm(app: &App) {
     for row_idx in lines_to_show.len()..visible_height {
         let row_y = row_idx as u16;
         let _ = stdout.queue(cursor::MoveTo(0, row_y));
-        let _ = stdout.queue(Print("\x1b[K"));
+        match &tint_code {
+            Some(tint) => {
+                let _ = stdout.queue(Print(format!("{}{}\x1b[0m", tint, " ".repeat(term_width))));
+            }
+            None => {
+                let _ = stdout.queue(Print("\x1b[K"));
+            }
+        }
     }
 
     // Render filter popup if visible (must be after output so it's on top)
@@ -974,7 +1106,7 @@ pub(crate) fn render_output_crossterm(app: &App) {
     if app.search_popup.visible {
         let popup_width = 44usize.min(term_width);
         let x = term_width.saturating_sub(popup_width) as u16;
-        let title = " History [Enter=older, Esc] ";
+        let title = if app.search_popup.in_place { " Scrollback [Enter=n/N, Esc] " } else { " History [Enter=older, Esc] " };
         let dashes_needed = popup_width.saturating_sub(title.len() + 2);
 
         let _ = stdout.queue(cursor::MoveTo(x, 0));
@@ -1007,8 +1139,8 @@ pub(crate) fn render_output_crossterm(app: &App) {
 
     // Calculate and set cursor position in input area
     // This replicates the logic from render_input_area to avoid Save/Restore timing issues
-    let prompt = &app.current_world().prompt;
-    let prompt_len = strip_ansi_codes(prompt).chars().count();
+    let prompt = app.input_prompt();
+    let prompt_len = display_width(&strip_ansi_codes(&prompt));
     let cursor_line = app.input.cursor_line();
     let viewport_line = cursor_line.saturating_sub(app.input.viewport_start_line);
 
@@ -1020,17 +1152,19 @@ pub(crate) fn render_output_crossterm(app: &App) {
         // Calculate cursor column accounting for newlines in the buffer
         let first_line_capacity = input_area_width.saturating_sub(prompt_len);
         let text_before_cursor = &app.input.buffer[..app.input.cursor_position];
+        let masked = app.input_masked();
 
         let mut col_width = 0usize;
         let mut is_first_line = true;
 
-        for c in text_before_cursor.chars() {
-            if c == '\n' {
+        for g in text_before_cursor.graphemes(true) {
+            if g.ends_with('\n') {
                 col_width = 0;
                 is_first_line = false;
                 continue;
             }
-            let cw = display_width(&c.to_string());
+            // Masked input shows one asterisk per char
+            let cw = if masked { g.chars().count() } else { grapheme_width(g) };
             let capacity = if is_first_line { first_line_capacity } else { input_area_width };
             col_width += cw;
             if capacity > 0 && col_width >= capacity {
@@ -1059,21 +1193,21 @@ pub(crate) fn render_output_crossterm(app: &App) {
     let _ = stdout.flush();
 }
 
-/// Write an ANSI-colored string directly to a ratatui buffer at (x, y).
-/// Parses ANSI SGR escape sequences and sets cell styles accordingly.
-/// This bypasses ansi_to_tui and Paragraph for reliable color reproduction.
-pub(crate) fn ansi_string_to_buffer(buf: &mut ratatui::buffer::Buffer, x: u16, y: u16, s: &str, max_width: u16) {
+/// A screen row parsed from ANSI text: each visible character with its style
+pub(crate) type StyledRow = Vec<(String, Style)>;
+
+/// Parse ANSI SGR escape sequences into styled grapheme clusters, for writing straight
+/// to a ratatui buffer (this bypasses ansi_to_tui and Paragraph for reliable color
+/// reproduction). ZWJ, variation selectors and combining marks stay with the cluster
+/// they belong to; other escape sequences and stray zero-width characters are dropped.
+pub(crate) fn parse_ansi_row(s: &str) -> StyledRow {
     use ratatui::style::Color;
 
-    let mut col = 0u16;
+    let mut row = StyledRow::new();
     let mut style = Style::default();
     let mut chars = s.chars().peekable();
 
     while let Some(c) = chars.next() {
-        if col >= max_width {
-            break;
-        }
-
         if c == '\x1b' {
             // Parse escape sequence
             if chars.peek() == Some(&'[') {
@@ -1182,17 +1316,37 @@ pub(crate) fn ansi_string_to_buffer(buf: &mut ratatui::buffer::Buffer, x: u16, y
             continue;
         }
 
-        // Regular character - write to buffer
+        if !c.is_ascii() {
+            if let Some((last, _)) = row.last_mut() {
+                let joined = format!("{}{}", last, c);
+                if joined.graphemes(true).nth(1).is_none() {
+                    *last = joined;
+                    continue;
+                }
+            }
+        }
         let char_width = unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
         if char_width == 0 && c != ' ' {
-            // Zero-width character (ZWJ, combining marks, etc.) - skip
             continue;
         }
+        row.push((c.to_string(), style));
+    }
+    row
+}
 
+/// Write a parsed row to a ratatui buffer at (x, y), cut off at `max_width` columns
+pub(crate) fn styled_row_to_buffer(buf: &mut ratatui::buffer::Buffer, x: u16, y: u16, row: &[(String, Style)], max_width: u16) {
+    let mut col = 0u16;
+    for (cluster, style) in row {
+        let style = *style;
+        if col >= max_width {
+            break;
+        }
+        let char_width = grapheme_width(cluster);
         let cell_x = x + col;
         if cell_x < x + max_width {
             let cell = buf.get_mut(cell_x, y);
-            cell.set_char(c);
+            cell.set_symbol(cluster);
             cell.set_style(style);
             col += char_width as u16;
             // For wide characters, mark the next cell as continuation
@@ -1234,16 +1388,39 @@ pub(crate) fn render_output_area(f: &mut Frame, app: &App, area: Rect) {
 
     // Overlay popup or editor is visible - render output with ratatui
     // (crossterm is skipped when popups are shown to avoid bleed-through)
-    // Write directly to the ratatui buffer, bypassing Paragraph widget,
-    // with manual ANSI parsing for proper color reproduction.
+    render_world_output(f, app, app.current_world_index, area);
+}
+
+/// Render one world's output into `area` with ratatui, at that world's own scroll
+/// position (the editor split, split panes, and output behind popups).
+/// Writes directly to the ratatui buffer, bypassing Paragraph widget,
+/// with manual ANSI parsing for proper color reproduction.
+pub(crate) fn render_world_output(f: &mut Frame, app: &App, world_idx: usize, area: Rect) {
+    let world = &app.worlds[world_idx];
+    let visible_height = area.height as usize;
+    let area_width = area.width as usize;
 
     // Fill the entire output area with background first
     f.render_widget(ratatui::widgets::Clear, area);
 
-    // Build visual lines (wrapped ANSI strings) by working backwards from scroll_offset
-    let mut wrapped_lines: Vec<String> = Vec::new();
+    // Build visual lines (parsed rows) by working backwards from scroll_offset
+    let mut wrapped_lines: Vec<StyledRow> = Vec::new();
     let new_line_indicator = app.settings.new_line_indicator;
     let nli_prefix_width: usize = NLI_PREFIX_WIDTH; // "▶ " = 2 columns
+    let palette = app.world_palette(world_idx);
+    let mut pane_rows = world.pane_rows.borrow_mut();
+    pane_rows.begin_frame(RenderKey {
+        view: View::Pane,
+        width: area_width,
+        show_tags: app.show_tags,
+        temp_convert: app.settings.temp_convert_enabled,
+        zwj: app.settings.zwj_enabled,
+        timestamps: app.timestamp_mode(world_idx),
+        wrapspace: app.settings.wrapspace as usize,
+        new_line_indicator,
+        theme: app.settings.theme,
+        palette,
+    });
 
     if !world.output_lines.is_empty() {
         let end_line = world.scroll_offset.min(world.output_lines.len().saturating_sub(1));
@@ -1256,36 +1433,44 @@ pub(crate) fn render_output_area(f: &mut Frame, app: &App, area: Rect) {
             let is_new = new_line_indicator && line.marked_new;
             let is_archive = line.from_archive;
 
-            let expanded = match process_output_line(line, app.show_tags, app.settings.temp_convert_enabled, app.settings.zwj_enabled, &cached_now) {
-                Some(text) if text.is_empty() => {
-                    let prefix = if is_new { "\x1b[32m▶\x1b[0m ".to_string() } else { String::new() };
-                    let prefix = if is_archive { format!("{}🛢️ ", prefix) } else { prefix };
-                    wrapped_lines.insert(0, prefix);
-                    if wrapped_lines.len() >= visible_height {
-                        break;
+            let rows = pane_rows.rows(line, || {
+                let expanded = match process_output_line(line, app.show_tags, app.settings.temp_convert_enabled, app.settings.zwj_enabled, app.timestamp_mode(world_idx), &cached_now) {
+                    Some(text) if text.is_empty() => {
+                        let prefix = if is_new { "\x1b[32m▶\x1b[0m ".to_string() } else { String::new() };
+                        let prefix = if is_archive { format!("{}🛢️ ", prefix) } else { prefix };
+                        return vec![parse_ansi_row(&prefix)];
                     }
-                    continue;
-                }
-                Some(text) => text,
-                None => continue,
-            };
-
-            // Wrap the line to fit the output area width (narrower if NLI or archive prefix)
-            // ARCHIVE_PREFIX_WIDTH is hardcoded (not computed via unicode_width) because the
-            // VS16 emoji "🛢️" measures as 1 col but renders as 2 cells in terminals.
-            let mut wrap_width = area_width;
-            if is_new { wrap_width = wrap_width.saturating_sub(nli_prefix_width); }
-            if is_archive { wrap_width = wrap_width.saturating_sub(ARCHIVE_PREFIX_WIDTH); }
-            let wrapped = wrap_ansi_line(&expanded, wrap_width, app.settings.wrapspace as usize);
-
-            for w in wrapped.into_iter().rev() {
-                let prefixed = match (is_new, is_archive) {
-                    (true, true)   => format!("\x1b[32m▶\x1b[0m 🛢️ {}", w),
-                    (true, false)  => format!("\x1b[32m▶\x1b[0m {}", w),
-                    (false, true)  => format!("🛢️ {}", w),
-                    (false, false) => w,
+                    Some(text) => text,
+                    None => return Vec::new(),
+                };
+                let expanded = match &palette {
+                    Some(p) => crate::theme::remap_ansi_colors(&expanded, p),
+                    None => expanded,
                 };
-                wrapped_lines.insert(0, prefixed);
+                let expanded = crate::color_depth::downgrade(&expanded, app.color_depth);
+
+                // Wrap the line to fit the output area width (narrower if NLI or archive prefix)
+                // ARCHIVE_PREFIX_WIDTH is hardcoded (not computed via unicode_width) because the
+                // VS16 emoji "🛢️" measures as 1 col but renders as 2 cells in terminals.
+                let mut wrap_width = area_width;
+                if is_new { wrap_width = wrap_width.saturating_sub(nli_prefix_width); }
+                if is_archive { wrap_width = wrap_width.saturating_sub(ARCHIVE_PREFIX_WIDTH); }
+                wrap_ansi_line(&expanded, wrap_width, app.settings.wrapspace as usize)
+                    .into_iter()
+                    .map(|w| {
+                        let prefixed = match (is_new, is_archive) {
+                            (true, true)   => format!("\x1b[32m▶\x1b[0m 🛢️ {}", w),
+                            (true, false)  => format!("\x1b[32m▶\x1b[0m {}", w),
+                            (false, true)  => format!("🛢️ {}", w),
+                            (false, false) => w,
+                        };
+                        parse_ansi_row(&prefixed)
+                    })
+                    .collect()
+            });
+
+            for row in rows.into_iter().rev() {
+                wrapped_lines.insert(0, row);
             }
 
             if wrapped_lines.len() >= visible_height {
@@ -1293,6 +1478,7 @@ pub(crate) fn render_output_area(f: &mut Frame, app: &App, area: Rect) {
             }
         }
     }
+    pane_rows.end_frame();
 
     // Trim to visible_height from the bottom (keep the most recent lines)
     if wrapped_lines.len() > visible_height {
@@ -1307,7 +1493,7 @@ pub(crate) fn render_output_area(f: &mut Frame, app: &App, area: Rect) {
             break;
         }
         let y = area.y + row_idx as u16;
-        ansi_string_to_buffer(buf, area.x, y, line, area.width);
+        styled_row_to_buffer(buf, area.x, y, line, area.width);
     }
 }
 
@@ -1571,34 +1757,186 @@ pub(crate) fn format_more_count(count: usize) -> String {
     }
 }
 
+/// World tab bar: one tab per world, current world reversed, coloured by
+/// connection state, with unseen counts
+/// Draw the split panes (see split.rs): a title row over each world's output, the
+/// focused (current) world's title in reverse video
+pub(crate) fn render_split_panes(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.settings.theme;
+    let split = &app.split;
+    let gap = u16::from(split.vertical);
+    let total = if split.vertical { area.width } else { area.height };
+    let lengths = crate::split::split_lengths(total, split.panes.len(), gap);
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    let mut offset = 0;
+    for (slot, (name, len)) in split.panes.iter().zip(lengths).enumerate() {
+        let pane = if split.vertical {
+            Rect { x: area.x + offset, width: len, ..area }
+        } else {
+            Rect { y: area.y + offset, height: len, ..area }
+        };
+        offset += len + gap;
+        if pane.width == 0 || pane.height < 2 {
+            continue;
+        }
+        if split.vertical && slot > 0 {
+            let divider = Rect { x: pane.x - 1, width: 1, ..pane };
+            let bar: Vec<Line> = (0..divider.height).map(|_| Line::from("│")).collect();
+            f.render_widget(Paragraph::new(bar).style(Style::default().fg(theme.fg_dim()).bg(theme.bg())), divider);
+        }
+
+        let title = format!("── {} ", name);
+        let title = format!("{}{}", title, "─".repeat((pane.width as usize).saturating_sub(display_width(&title))));
+        let title_style = if slot == split.focus {
+            Style::default().fg(theme.fg()).add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.fg_dim())
+        };
+        f.render_widget(Paragraph::new(title).style(title_style.bg(theme.bg())), Rect { height: 1, ..pane });
+
+        if let Some(idx) = app.worlds.iter().position(|w| w.name.eq_ignore_ascii_case(name)) {
+            render_world_output(f, app, idx, Rect { y: pane.y + 1, height: pane.height - 1, ..pane });
+        }
+    }
+}
+
+/// World shown in the capture pane: the pane's capture, unless it doesn't exist
+/// yet or is the world being viewed
+pub(crate) fn capture_pane_world(app: &App) -> Option<usize> {
+    let name = app.panes.pane.as_deref()?;
+    app.worlds.iter()
+        .position(|w| w.name.eq_ignore_ascii_case(name))
+        .filter(|&idx| idx != app.current_world_index)
+}
+
+/// Draw the capture pane: a title row, then the newest lines of the capture world
+pub(crate) fn render_capture_pane(f: &mut Frame, app: &App, world_idx: usize, area: Rect) {
+    let world = &app.worlds[world_idx];
+    let theme = app.settings.theme;
+    let width = area.width as usize;
+    let visible_height = area.height.saturating_sub(1) as usize;
+
+    let title = format!("── {} ", world.name);
+    let title = format!("{}{}", title, "─".repeat(width.saturating_sub(display_width(&title))));
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(
+        Paragraph::new(title).style(Style::default().fg(theme.fg_dim()).bg(theme.bg())),
+        Rect { height: 1, ..area },
+    );
+
+    let cached_now = CachedNow::new();
+    let mut wrapped_lines: Vec<StyledRow> = Vec::new();
+    let palette = app.world_palette(world_idx);
+    let mut pane_rows = world.pane_rows.borrow_mut();
+    pane_rows.begin_frame(RenderKey {
+        view: View::Capture,
+        width,
+        show_tags: app.show_tags,
+        temp_convert: app.settings.temp_convert_enabled,
+        zwj: app.settings.zwj_enabled,
+        timestamps: app.timestamp_mode(world_idx),
+        wrapspace: app.settings.wrapspace as usize,
+        new_line_indicator: false,
+        theme,
+        palette,
+    });
+    for line in world.output_lines.iter().rev() {
+        if wrapped_lines.len() >= visible_height {
+            break;
+        }
+        let rows = pane_rows.rows(line, || {
+            let Some(text) = process_output_line(line, app.show_tags, app.settings.temp_convert_enabled, app.settings.zwj_enabled, app.timestamp_mode(world_idx), &cached_now) else {
+                return Vec::new();
+            };
+            let text = match &palette {
+                Some(p) => crate::theme::remap_ansi_colors(&text, p),
+                None => text,
+            };
+            let text = crate::color_depth::downgrade(&text, app.color_depth);
+            wrap_ansi_line(&text, width, app.settings.wrapspace as usize).iter().map(|w| parse_ansi_row(w)).collect()
+        });
+        for row in rows.into_iter().rev() {
+            wrapped_lines.insert(0, row);
+        }
+    }
+    pane_rows.end_frame();
+    if wrapped_lines.len() > visible_height {
+        let excess = wrapped_lines.len() - visible_height;
+        wrapped_lines.drain(0..excess);
+    }
+
+    let buf = f.buffer_mut();
+    for (row_idx, line) in wrapped_lines.iter().enumerate() {
+        styled_row_to_buffer(buf, area.x, area.y + 1 + row_idx as u16, line, area.width);
+    }
+}
+
+pub(crate) fn render_tab_bar(f: &mut Frame, app: &mut App, area: Rect) {
+    use crate::tab_bar::{self, TabInfo};
+
+    let theme = app.settings.theme;
+    let labels: Vec<String> = app.worlds.iter().enumerate()
+        .map(|(i, w)| tab_bar::tab_label(i, &TabInfo { name: &w.name, unseen: w.unseen_lines }))
+        .collect();
+    let widths: Vec<usize> = labels.iter().map(|l| display_width(l)).collect();
+    let layout = tab_bar::layout(&widths, app.current_world_index, area.width as usize, area.y);
+
+    let dim = Style::default().fg(theme.fg_dim());
+    let mut spans = Vec::new();
+    if layout.more_left {
+        spans.push(Span::styled("<", dim));
+    }
+    for &(_, _, idx) in &layout.tabs {
+        let world = &app.worlds[idx];
+        let color = if world.connected {
+            theme.fg_success()
+        } else if world.was_connected {
+            theme.fg_error()
+        } else {
+            theme.fg_dim()
+        };
+        let mut style = Style::default().fg(color);
+        if idx == app.current_world_index {
+            style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+        } else if world.unseen_lines > 0 {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        spans.push(Span::styled(labels[idx].clone(), style));
+    }
+    if layout.more_right {
+        let used: u16 = layout.tabs.last().map_or(0, |(_, end, _)| *end);
+        spans.push(Span::styled(" ".repeat(area.width.saturating_sub(used + 1) as usize), dim));
+        spans.push(Span::styled(">", dim));
+    }
+
+    app.tab_bar_layout = layout;
+    f.render_widget(Paragraph::new(Line::from(spans)).style(Style::default().bg(theme.bg())), area);
+}
+
 pub(crate) fn render_separator_bar(f: &mut Frame, app: &App, area: Rect) {
     let width = area.width as usize;
     let world = app.current_world();
     let theme = app.settings.theme;
+    // The whole bar is inverted for a moment after a bell in a world set to flash (see bell.rs)
+    let bar_style = if app.bell_flash_until.is_some() {
+        Style::default().bg(theme.bg()).add_modifier(Modifier::REVERSED)
+    } else {
+        Style::default().bg(theme.bg())
+    };
+
+    // A template from /setup replaces the built-in layout (see status_format.rs)
+    if !app.settings.status_format.is_empty() {
+        let line = status_template_line(app, width);
+        f.render_widget(Paragraph::new(line).style(bar_style), area);
+        return;
+    }
 
     // Build bar components
     let time_str = get_current_time_12hr();
 
-    // Status indicator - always reserve space for "More XXXX" or "Hist XXXX" (9 chars)
-    // Priority: Hist (when scrolled back) > More (when paused) > underscores
-    const STATUS_INDICATOR_LEN: usize = 9;
-    let (status_str, status_active) = if !world.is_at_bottom() {
-        // Show History indicator when scrolled back (takes precedence over More)
-        let lines_back = world.lines_from_bottom(app.show_tags);
-        (format!("Hist {}", format_more_count(lines_back)), true)
-    } else if let Some(count) = more_indicator_count(
-        world,
-        app.output_width as usize,
-        app.settings.new_line_indicator,
-        app.settings.wrapspace as usize,
-    ) {
-        // Show More indicator when paused with pending lines, or when
-        // visual_line_offset truncation is hiding rows of the current world
-        (format!("More {}", format_more_count(count)), true)
-    } else {
-        // Fill with underscores when nothing to show
-        ("_".repeat(STATUS_INDICATOR_LEN), false)
-    };
+    // Status indicator: "More XXXX" / "Hist XXXX" or underscores (always 9 chars)
+    let (status_str, status_active) = status_indicator(app);
 
     // World name
     let world_display = world.name.clone();
@@ -1611,31 +1949,34 @@ pub(crate) fn render_separator_bar(f: &mut Frame, app: &App, area: Rect) {
 
     // Activity indicator - positioned at column 24
     const ACTIVITY_POSITION: usize = 24;
-    // In remote client mode, use the server's activity count
-    let activity_count = if app.is_master {
-        app.activity_count()
-    } else {
-        app.server_activity_count
-    };
-
-    // Determine activity string based on available space
-    // Full format: "(Activity: X)", Short format: "(Act X)"
-    let activity_str = if activity_count > 0 {
-        let full_format = format!("(Activity: {})", activity_count);
-        let short_format = format!("(Act {})", activity_count);
-        // Use short format if screen is narrow (less than 60 chars)
-        if width < 60 {
-            short_format
-        } else {
-            full_format
-        }
-    } else {
-        String::new()
-    };
 
     // Time on the right (no space before it, underscores fill to it)
     let time_display = time_str.clone();
 
+    // Scheduled profile indicator, just left of the time (see profiles.rs)
+    let profile_display = profile_indicator(app);
+
+    // Output search position ("match 3 of 12"), left of the profile
+    // Copy mode shows in the same place (the two don't overlap)
+    let search_display = search_indicator(app);
+
+    // /sendfile progress, left of the search position
+    let sending_display = sending_indicator(app);
+
+    // Per-world summary when it fits between column 24 and the right-hand fields
+    // (with a few underscores to spare), else the "(Activity: N)" count
+    let name_len = if world.was_connected {
+        2 + world.name.len() + tag_indicator.len() + gmcp_indicator.len()
+    } else {
+        0
+    };
+    let summary_room = width.saturating_sub(
+        ACTIVITY_POSITION.max(STATUS_INDICATOR_LEN + name_len)
+            + sending_display.len() + search_display.len() + profile_display.chars().count() + time_display.len() + 6,
+    );
+    let activity_str = world_activity_summary(app, summary_room)
+        .unwrap_or_else(|| activity_indicator(app, width));
+
     // Create styled spans
     let mut spans = Vec::new();
 
@@ -1716,13 +2057,27 @@ pub(crate) fn render_separator_bar(f: &mut Frame, app: &App, area: Rect) {
         ACTIVITY_POSITION.max(current_pos) + activity_str.len()
     };
     // Subtract 2 for the fixed underscores before time
-    let underscore_count = width.saturating_sub(used_len + time_display.len() + 2);
+    let underscore_count = width.saturating_sub(
+        used_len + sending_display.len() + search_display.len() + profile_display.chars().count() + time_display.len() + 2,
+    );
 
     spans.push(Span::styled(
         "_".repeat(underscore_count),
         Style::default().fg(theme.fg_dim()),
     ));
 
+    if !sending_display.is_empty() {
+        spans.push(Span::styled(sending_display, Style::default().fg(theme.fg_accent())));
+    }
+
+    if !search_display.is_empty() {
+        spans.push(Span::styled(search_display, Style::default().fg(theme.fg_highlight())));
+    }
+
+    if !profile_display.is_empty() {
+        spans.push(Span::styled(profile_display, Style::default().fg(theme.fg_accent())));
+    }
+
     // Underscore separator before time (2 chars for extra spacing)
     spans.push(Span::styled("__", Style::default().fg(theme.fg_dim())));
 
@@ -1730,16 +2085,235 @@ pub(crate) fn render_separator_bar(f: &mut Frame, app: &App, area: Rect) {
     spans.push(Span::styled(time_display, Style::default().fg(theme.fg())));
 
     let line = Line::from(spans);
-    let paragraph = Paragraph::new(line).style(Style::default().bg(theme.bg()));
+    let paragraph = Paragraph::new(line).style(bar_style);
 
     f.render_widget(paragraph, area);
 }
 
+/// Width of the More/Hist indicator
+const STATUS_INDICATOR_LEN: usize = 9;
+
+/// More/Hist indicator for the status bar, always STATUS_INDICATOR_LEN chars.
+/// Priority: Hist (when scrolled back) > More (when paused) > underscores.
+/// Returns the text and whether it is active (highlighted).
+fn status_indicator(app: &App) -> (String, bool) {
+    let world = app.current_world();
+    if !world.is_at_bottom() {
+        // Show History indicator when scrolled back (takes precedence over More)
+        let lines_back = world.lines_from_bottom(app.show_tags);
+        (format!("Hist {}", format_more_count(lines_back)), true)
+    } else if let Some(count) = more_indicator_count(
+        world,
+        app.output_width as usize,
+        app.settings.new_line_indicator,
+        app.settings.wrapspace as usize,
+    ) {
+        // Show More indicator when paused with pending lines, or when
+        // visual_line_offset truncation is hiding rows of the current world
+        (format!("More {}", format_more_count(count)), true)
+    } else {
+        // Fill with underscores when nothing to show
+        ("_".repeat(STATUS_INDICATOR_LEN), false)
+    }
+}
+
+/// "(Activity: N)" for worlds with unseen output, "(Act N)" on narrow screens
+fn activity_indicator(app: &App, width: usize) -> String {
+    // In remote client mode, use the server's activity count
+    let activity_count = if app.is_master {
+        app.activity_count()
+    } else {
+        app.server_activity_count
+    };
+    if activity_count == 0 {
+        String::new()
+    } else if width < 60 {
+        format!("(Act {})", activity_count)
+    } else {
+        format!("(Activity: {})", activity_count)
+    }
+}
+
+/// "mume(12) ach(3*)" for the other worlds with unseen or more-mode lines, None
+/// when there are none or it is wider than `width` (see status_format.rs)
+fn world_activity_summary(app: &App, width: usize) -> Option<String> {
+    let worlds: Vec<crate::status_format::WorldActivity> = app.worlds.iter().enumerate()
+        .filter(|(i, _)| *i != app.current_world_index)
+        .map(|(_, w)| crate::status_format::WorldActivity {
+            name: &w.name,
+            unseen: w.unseen_lines,
+            // The remote console mirrors the daemon's count in pending_count
+            pending: w.pending_lines.len().max(w.pending_count),
+        })
+        .collect();
+    crate::status_format::world_summary(&worlds, width)
+}
+
+fn profile_indicator(app: &App) -> String {
+    app.active_profile.as_ref()
+        .map(|name| format!("[{}]", name))
+        .unwrap_or_default()
+}
+
+fn sending_indicator(app: &App) -> String {
+    app.current_world().send_file.as_ref().map(|job| job.indicator()).unwrap_or_default()
+}
+
+fn search_indicator(app: &App) -> String {
+    app.search_popup.status_text()
+        .map(|s| format!("[{}]", s))
+        .or_else(|| app.copy_mode.active.then(|| {
+            if app.copy_mode.anchor.is_some() { "[COPY: selecting]" } else { "[COPY]" }.to_string()
+        }))
+        .unwrap_or_default()
+}
+
+/// Status bar built from the `status_format` template
+fn status_template_line(app: &App, width: usize) -> Line<'static> {
+    use crate::status_format::{self, Field, Segment};
+
+    let world = app.current_world();
+    let theme = app.settings.theme;
+    let dim = Style::default().fg(theme.fg_dim());
+    let accent = Style::default().fg(theme.fg_accent());
+    let highlight = Style::default().fg(theme.fg_highlight()).add_modifier(Modifier::BOLD);
+    let connected_color = if world.connected { theme.fg_success() } else { theme.fg_error() };
+
+    // None marks a %fill, sized once the rest is known
+    let mut parts: Vec<Option<Span<'static>>> = Vec::new();
+    for segment in status_format::parse(&app.settings.status_format) {
+        let field = match segment {
+            Segment::Text(text) => {
+                parts.push(Some(Span::styled(text, dim)));
+                continue;
+            }
+            Segment::Field(Field::Fill) => {
+                parts.push(None);
+                continue;
+            }
+            Segment::Field(field) => field,
+        };
+        let span = match field {
+            Field::Status => {
+                let (text, active) = status_indicator(app);
+                Span::styled(text, if active {
+                    Style::default().fg(theme.button_selected_fg()).bg(theme.fg_error())
+                } else {
+                    dim
+                })
+            }
+            Field::Conn if world.was_connected => Span::styled("●", Style::default().fg(connected_color)),
+            Field::World => Span::styled(world.name.clone(), Style::default().fg(theme.fg())),
+            Field::State if world.was_connected => Span::styled(
+                if world.connected { "connected" } else { "disconnected" },
+                Style::default().fg(connected_color),
+            ),
+            Field::Tags => {
+                let mut tags = Vec::new();
+                if app.show_tags {
+                    tags.push("[tag]");
+                }
+                if world.gmcp_user_enabled {
+                    tags.push("[g]");
+                }
+                Span::styled(tags.join(" "), accent)
+            }
+            Field::Activity => Span::styled(activity_indicator(app, width), highlight),
+            Field::Worlds => match world_activity_summary(app, width / 2) {
+                Some(summary) => Span::styled(summary, highlight),
+                None => continue,
+            },
+            Field::Unseen => {
+                let unseen: usize = app.worlds.iter().enumerate()
+                    .filter(|(i, _)| *i != app.current_world_index)
+                    .map(|(_, w)| w.unseen_lines)
+                    .sum();
+                if unseen == 0 {
+                    continue;
+                }
+                Span::styled(format!("(Unseen: {})", unseen), highlight)
+            }
+            Field::Keepalive => match keepalive_remaining(app) {
+                Some(secs) => Span::styled(format!("KA {}", status_format::format_countdown(secs)), dim),
+                None => continue,
+            },
+            Field::Capture => {
+                let captures = capture_indicators(app);
+                if captures.is_empty() {
+                    continue;
+                }
+                Span::styled(captures, accent)
+            }
+            Field::Prompt => Span::styled(strip_ansi_codes(&world.prompt).trim().to_string(), accent),
+            Field::Profile => Span::styled(profile_indicator(app), accent),
+            Field::Search => Span::styled(search_indicator(app), Style::default().fg(theme.fg_highlight())),
+            Field::Sending => Span::styled(sending_indicator(app), accent),
+            Field::Time => Span::styled(get_current_time_12hr(), Style::default().fg(theme.fg())),
+            // A world that never connected shows no ball or state
+            Field::Conn | Field::State | Field::Fill => continue,
+        };
+        parts.push(Some(span));
+    }
+
+    let used: usize = parts.iter().flatten().map(|span| display_width(&span.content)).sum();
+    let mut fills = parts.iter().filter(|p| p.is_none()).count();
+    if fills == 0 {
+        parts.push(None);
+        fills = 1;
+    }
+    let mut widths = status_format::fill_widths(used, fills, width).into_iter();
+    let spans: Vec<Span<'static>> = parts
+        .into_iter()
+        .map(|part| part.unwrap_or_else(|| Span::styled("_".repeat(widths.next().unwrap_or(0)), dim)))
+        .collect();
+    Line::from(spans)
+}
+
+/// Seconds until the current world is sent a keepalive, if it will be sent one.
+/// Only the master knows when each world last sent.
+fn keepalive_remaining(app: &App) -> Option<u64> {
+    let world = app.current_world();
+    if !app.is_master || !world.connected || world.settings.keep_alive_type == crate::KeepAliveType::None {
+        return None;
+    }
+    let interval = world.settings.keep_alive_interval();
+    let idle = world.last_send_time.map_or(interval, |t| t.elapsed());
+    Some(interval.saturating_sub(idle).as_secs())
+}
+
+/// "[chat:3]" for each capture world (tag route targets and action /capture
+/// buffers) that has unseen output, other than the current world and the capture
+/// shown in the pane
+fn capture_indicators(app: &App) -> String {
+    use crate::tag_routes::TagRouteAction;
+    let route_targets = app.settings.tag_routes.iter().filter_map(|route| match &route.action {
+        TagRouteAction::Copy(target) | TagRouteAction::Move(target) => Some(target.as_str()),
+        _ => None,
+    });
+    let pane_world = capture_pane_world(app);
+    let mut seen: Vec<&str> = Vec::new();
+    let mut out = Vec::new();
+    for target in route_targets.chain(app.panes.captures.iter().map(String::as_str)) {
+        if seen.iter().any(|s| s.eq_ignore_ascii_case(target)) {
+            continue;
+        }
+        seen.push(target);
+        let unseen = app.worlds.iter().enumerate()
+            .find(|(_, w)| w.name.eq_ignore_ascii_case(target))
+            .filter(|(i, _)| *i != app.current_world_index && Some(*i) != pane_world)
+            .map_or(0, |(_, w)| w.unseen_lines);
+        if unseen > 0 {
+            out.push(format!("[{}:{}]", target, unseen));
+        }
+    }
+    out.join(" ")
+}
+
 pub(crate) fn render_input_area(f: &mut Frame, app: &mut App, area: Rect) {
-    // Get prompt for current world only (clone to avoid borrow conflict)
-    let prompt = app.current_world().prompt.clone();
+    // Get prompt for current world only, or the Ctrl+R search prompt (owned to avoid borrow conflict)
+    let prompt = app.input_prompt();
     // Use visible length (without ANSI codes) for cursor positioning
-    let prompt_len = strip_ansi_codes(&prompt).chars().count();
+    let prompt_len = display_width(&strip_ansi_codes(&prompt));
 
     let input_text = render_input(app, area.width as usize, &prompt);
 
@@ -1754,17 +2328,19 @@ pub(crate) fn render_input_area(f: &mut Frame, app: &mut App, area: Rect) {
         let inner_width = area.width.max(1) as usize;
         let first_line_capacity = inner_width.saturating_sub(prompt_len);
         let text_before_cursor = &app.input.buffer[..app.input.cursor_position];
+        let masked = app.input_masked();
 
         let mut col_width = 0usize;
         let mut is_first_line = true;
 
-        for c in text_before_cursor.chars() {
-            if c == '\n' {
+        for g in text_before_cursor.graphemes(true) {
+            if g.ends_with('\n') {
                 col_width = 0;
                 is_first_line = false;
                 continue;
             }
-            let cw = display_width(&c.to_string());
+            // Masked input shows one asterisk per char
+            let cw = if masked { g.chars().count() } else { grapheme_width(g) };
             let capacity = if is_first_line { first_line_capacity } else { inner_width };
             col_width += cw;
             if capacity > 0 && col_width >= capacity {
@@ -1803,13 +2379,64 @@ pub(crate) fn chars_for_line(chars: &[char], width: usize) -> (usize, bool) {
     }
 }
 
+/// Redraw the misspelled input ratatui just drew with a curly underline, which it has
+/// no style for. Runs after each draw, like `render_output_crossterm`; ratatui only
+/// rewrites cells that change, so the rest stay curly.
+pub(crate) fn undercurl_misspellings(buffer: &ratatui::buffer::Buffer, app: &App) {
+    use std::io::Write;
+    use crossterm::style::{Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor, SetUnderlineColor};
+    use crossterm::QueueableCommand;
+
+    if !app.undercurl {
+        return;
+    }
+    let error = app.settings.theme.fg_error();
+    let mut stdout = std::io::stdout();
+    let mut drawn = false;
+    let area = buffer.area;
+    for y in area.top()..area.bottom() {
+        let mut x = area.left();
+        while x < area.right() {
+            let cell = buffer.get(x, y);
+            if cell.modifier.contains(Modifier::UNDERLINED) && cell.underline_color == error {
+                if !drawn {
+                    let _ = stdout.queue(cursor::SavePosition);
+                    drawn = true;
+                }
+                let _ = stdout.queue(cursor::MoveTo(x, y))
+                    .and_then(|s| s.queue(SetForegroundColor(cell.fg.into())))
+                    .and_then(|s| s.queue(SetBackgroundColor(cell.bg.into())))
+                    .and_then(|s| s.queue(SetUnderlineColor(error.into())))
+                    .and_then(|s| s.queue(SetAttribute(Attribute::Undercurled)))
+                    .and_then(|s| s.queue(Print(cell.symbol())))
+                    .and_then(|s| s.queue(SetAttribute(Attribute::Reset)));
+            }
+            // A wide character's second cell isn't printed
+            x += (grapheme_width(cell.symbol()) as u16).max(1);
+        }
+    }
+    if drawn {
+        let _ = stdout.queue(cursor::RestorePosition);
+        let _ = stdout.flush();
+    }
+}
+
 pub(crate) fn render_input(app: &mut App, width: usize, prompt: &str) -> Text<'static> {
-    let tc = app.settings.theme;
-    let misspelled = app.find_misspelled_words();
-    let chars: Vec<char> = app.input.buffer.chars().collect();
+    // Misspelled words are underlined in the error color (curly where the terminal
+    // has it, see undercurl_misspellings)
+    let error = app.settings.theme.fg_error();
+    let misspelled_style = Style::default().fg(error).underline_color(error).add_modifier(Modifier::UNDERLINED);
+    // A password typed while the server has echo off shows as asterisks
+    let masked = app.input_masked();
+    let misspelled = if masked { Vec::new() } else { app.find_misspelled_words() };
+    let chars: Vec<char> = if masked {
+        app.input.buffer.chars().map(|c| if c == '\n' { c } else { '*' }).collect()
+    } else {
+        app.input.buffer.chars().collect()
+    };
 
     // Calculate visible prompt length (without ANSI codes)
-    let prompt_visible_len = strip_ansi_codes(prompt).chars().count();
+    let prompt_visible_len = display_width(&strip_ansi_codes(prompt));
 
     if width == 0 {
         return Text::default();
@@ -1887,7 +2514,7 @@ pub(crate) fn render_input(app: &mut App, width: usize, prompt: &str) -> Text<'s
                         }
                         if s < e {
                             let text: String = chars[s..e].iter().collect();
-                            new_spans.push(Span::styled(text, Style::default().fg(tc.fg_error()).add_modifier(Modifier::BOLD)));
+                            new_spans.push(Span::styled(text, misspelled_style));
                         }
                         pos = e;
                     }
@@ -1927,7 +2554,7 @@ pub(crate) fn render_input(app: &mut App, width: usize, prompt: &str) -> Text<'s
                         let mis_start = word_start.max(char_pos);
                         let mis_end = word_end.min(line_end);
                         let text: String = chars[mis_start..mis_end].iter().collect();
-                        spans.push(Span::styled(text, Style::default().fg(tc.fg_error()).add_modifier(Modifier::BOLD)));
+                        spans.push(Span::styled(text, misspelled_style));
                         current_pos = mis_end;
                     } else {
                         let next_mis = misspelled
@@ -1993,7 +2620,7 @@ pub(crate) fn render_input(app: &mut App, width: usize, prompt: &str) -> Text<'s
                     let mis_start = word_start.max(char_pos);
                     let mis_end = word_end.min(line_end);
                     let text: String = chars[mis_start..mis_end].iter().collect();
-                    spans.push(Span::styled(text, Style::default().fg(tc.fg_error()).add_modifier(Modifier::BOLD)));
+                    spans.push(Span::styled(text, misspelled_style));
                     current_pos = mis_end;
                 } else {
                     let next_mis = misspelled
@@ -2061,7 +2688,7 @@ pub(crate) fn render_input(app: &mut App, width: usize, prompt: &str) -> Text<'s
                     let mis_start = word_start.max(char_pos);
                     let mis_end = word_end.min(line_end);
                     let text: String = chars[mis_start..mis_end].iter().collect();
-                    spans.push(Span::styled(text, Style::default().fg(tc.fg_error()).add_modifier(Modifier::BOLD)));
+                    spans.push(Span::styled(text, misspelled_style));
                     current_pos = mis_end;
                 } else {
                     let next_mis = misspelled
@@ -2234,7 +2861,7 @@ pub(crate) fn render_search_popup(f: &mut Frame, app: &App) {
         ]),
     ];
 
-    let title = " History [Enter=older, Esc] ";
+    let title = if search.in_place { " Scrollback [Enter=n/N, Esc] " } else { " History [Enter=older, Esc] " };
     let popup_block = Block::default()
         .title(title)
         .borders(Borders::ALL)
@@ -0,0 +1,118 @@
+//! `/replay <file> [speed]`: a world log shown again as if the server sent it.
+//!
+//! Lines go through the normal output path (`App::replay_output`), so colors, gags,
+//! highlights and tag routes apply as they would live, but nothing is logged again
+//! or sent to the server. Without a speed the whole file is shown at once; with one,
+//! the time prefixes the log was written with (see logging.rs) pace it, `speed`
+//! times as fast, with quiet spells shortened to `MAX_GAP`. A world has at most one
+//! replay running; `/replay stop` ends it.
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::AppEvent;
+
+/// The longest pause between replayed lines, before speeding up
+pub const MAX_GAP: Duration = Duration::from_secs(10);
+
+/// A log line's time prefix (`[HH:MM:SS] ` or `[YYYY-MM-DD HH:MM:SS] `) as seconds,
+/// and the rest of the line. Times of day only order lines within one day.
+pub fn split_stamp(line: &str) -> (Option<i64>, &str) {
+    let parsed = line.strip_prefix('[').and_then(|rest| {
+        let (stamp, text) = rest.split_once("] ")?;
+        let (date, time) = match stamp.split_once(' ') {
+            Some((date, time)) => (Some(date), time),
+            None => (None, stamp),
+        };
+        let secs = fields(time, ':').filter(|t| t.len() == 3 && t[0] < 24 && t[1] < 60 && t[2] < 62)
+            .map(|t| t[0] * 3600 + t[1] * 60 + t[2])?;
+        let days = match date {
+            Some(date) => {
+                let d = fields(date, '-').filter(|d| d.len() == 3)?;
+                days_from_civil(d[0], d[1], d[2])
+            }
+            None => 0,
+        };
+        Some((days * 86400 + secs, text))
+    });
+    match parsed {
+        Some((secs, text)) => (Some(secs), text),
+        None => (None, line),
+    }
+}
+
+fn fields(s: &str, sep: char) -> Option<Vec<i64>> {
+    s.split(sep).map(|f| if f.bytes().all(|b| b.is_ascii_digit()) { f.parse().ok() } else { None }).collect()
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// The log's lines, without time prefixes, in chunks with the pause before each.
+/// `speed` None puts everything in one chunk.
+pub fn plan(text: &str, speed: Option<f64>) -> Vec<(Duration, String)> {
+    let mut chunks: Vec<(Duration, String)> = Vec::new();
+    let mut last_secs = None;
+    for line in text.lines() {
+        let (secs, line) = split_stamp(line);
+        let gap = match (speed, last_secs, secs) {
+            (Some(speed), Some(last), Some(now)) if now > last => {
+                Duration::from_secs((now - last) as u64).min(MAX_GAP).div_f64(speed)
+            }
+            _ => Duration::ZERO,
+        };
+        if secs.is_some() {
+            last_secs = secs;
+        }
+        match chunks.last_mut() {
+            Some(chunk) if gap.is_zero() => chunk.1.push_str(line),
+            _ => chunks.push((gap, line.to_string())),
+        }
+        if let Some(chunk) = chunks.last_mut() {
+            chunk.1.push('\n');
+        }
+    }
+    chunks
+}
+
+/// Send `chunks` to `world_name` as `AppEvent::ReplayData`, each after its pause
+pub fn spawn(world_name: String, chunks: Vec<(Duration, String)>, event_tx: mpsc::Sender<AppEvent>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        for (gap, text) in chunks {
+            if !gap.is_zero() {
+                tokio::time::sleep(gap).await;
+            }
+            if event_tx.send(AppEvent::ReplayData(world_name.clone(), text)).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_strips_stamps_and_paces() {
+        assert_eq!(split_stamp("[07:05:01] hi"), (Some(7 * 3600 + 5 * 60 + 1), "hi"));
+        assert_eq!(split_stamp("[2024-03-01 00:00:00] x").0.unwrap() - split_stamp("[2024-02-29 23:59:59] x").0.unwrap(), 1);
+        assert_eq!(split_stamp("[not a stamp] hi"), (None, "[not a stamp] hi"));
+
+        let log = "[10:00:00] one\n[10:00:00] two\n[10:00:04] three\n=== Session ended ===\n[11:00:00] four\n";
+        assert_eq!(plan(log, None), vec![(Duration::ZERO, "one\ntwo\nthree\n=== Session ended ===\nfour\n".to_string())]);
+        assert_eq!(plan(log, Some(2.0)), vec![
+            (Duration::ZERO, "one\ntwo\n".to_string()),
+            (Duration::from_secs(2), "three\n=== Session ended ===\n".to_string()),
+            (MAX_GAP / 2, "four\n".to_string()),
+        ]);
+    }
+}
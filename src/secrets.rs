@@ -0,0 +1,109 @@
+//! `${env:VAR}` and `${secret:NAME}` references in login fields and action commands.
+//!
+//! Credentials and tokens don't have to appear literally in a world's user/password
+//! fields or in action command strings saved to settings.dat. Instead they can
+//! reference an environment variable (`${env:MUD_PASSWORD}`) or a named secret
+//! (`${secret:mudpw}`, alias `${keyring:mudpw}`) set with `/secret`. Secrets are
+//! stored encrypted at rest, per world or globally; a world secret shadows a global
+//! one of the same name.
+//!
+//! Expansion happens only at the moment text is sent, never when it is saved or
+//! shown in editors. Unresolved references are left as written so a typo is visible
+//! instead of silently sending an empty string.
+
+use std::collections::BTreeMap;
+
+/// Replace `${env:VAR}`, `${secret:NAME}` and `${keyring:NAME}` references in `text`.
+/// Secrets are looked up in `world` first, then `global`.
+pub fn expand(text: &str, world: &BTreeMap<String, String>, global: &BTreeMap<String, String>) -> String {
+    if !text.contains("${") {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let reference = &after[..end];
+        match resolve(reference, world, global) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + 2 + end + 1]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn resolve(reference: &str, world: &BTreeMap<String, String>, global: &BTreeMap<String, String>) -> Option<String> {
+    let (kind, name) = reference.split_once(':')?;
+    match kind {
+        "env" => std::env::var(name).ok(),
+        "secret" | "keyring" => world.get(name).or_else(|| global.get(name)).cloned(),
+        _ => None,
+    }
+}
+
+/// Secret names are used as settings.dat keys (`secret.NAME=`), so keep them simple
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// `/secret set ...`, `/lock set ...` and `/unlock ...` carry a secret value or passphrase
+/// on the command line, so they are kept out of input history
+pub fn is_secret_command(line: &str) -> bool {
+    let mut words = line.split_whitespace().map(|w| w.to_lowercase());
+    matches!(
+        (words.next().as_deref(), words.next().as_deref()),
+        (Some("/secret" | "/secrets"), Some("set" | "add")) | (Some("/lock"), Some("set")) | (Some("/unlock"), Some(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_secrets_world_shadows_global() {
+        let mut world = BTreeMap::new();
+        world.insert("pw".to_string(), "hunter2".to_string());
+        let mut global = BTreeMap::new();
+        global.insert("pw".to_string(), "global".to_string());
+        global.insert("token".to_string(), "abc".to_string());
+
+        assert_eq!(expand("connect bob ${secret:pw}", &world, &global), "connect bob hunter2");
+        assert_eq!(expand("${keyring:token}/${secret:token}", &world, &global), "abc/abc");
+        assert_eq!(expand("${secret:pw}", &BTreeMap::new(), &global), "global");
+    }
+
+    #[test]
+    fn test_expand_env_and_unresolved() {
+        std::env::set_var("CLAY_TEST_SECRET_ENV", "fromenv");
+        let empty = BTreeMap::new();
+        assert_eq!(expand("x${env:CLAY_TEST_SECRET_ENV}y", &empty, &empty), "xfromenvy");
+        // Unknown references and unterminated ones stay literal
+        assert_eq!(expand("${secret:nope} ${env:CLAY_TEST_NO_SUCH_VAR}", &empty, &empty),
+            "${secret:nope} ${env:CLAY_TEST_NO_SUCH_VAR}");
+        assert_eq!(expand("${foo} $x ${secret:open", &empty, &empty), "${foo} $x ${secret:open");
+        assert_eq!(expand("plain text", &empty, &empty), "plain text");
+    }
+
+    #[test]
+    fn test_secret_names() {
+        assert!(is_valid_name("mud_pw-2.alt"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("has space"));
+        assert!(!is_valid_name("a=b"));
+        assert!(is_secret_command("/secret set pw hunter2"));
+        assert!(is_secret_command("/SECRETS add -g pw x"));
+        assert!(!is_secret_command("/secret list"));
+        assert!(!is_secret_command("say /secret set"));
+        assert!(is_secret_command("/lock set my pass phrase"));
+        assert!(is_secret_command("/unlock my pass phrase"));
+        assert!(!is_secret_command("/lock"));
+    }
+}
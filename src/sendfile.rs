@@ -0,0 +1,75 @@
+//! `/sendfile [-w[<world>]] <file>`: a local file sent to a world line by line.
+//!
+//! Lines go out one every `LINE_INTERVAL` (`App::send_file_line` sends each), so a
+//! long softcode upload or mail draft doesn't trip the server's flood limits.
+//! Progress shows in the status bar (`%sending` in a custom layout) and on web
+//! clients. A world sends one file at a time; `/sendfile abort` or a disconnect
+//! stops it.
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::AppEvent;
+
+/// The pause between lines
+pub const LINE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A file being sent to a world
+#[derive(Debug)]
+pub struct SendFile {
+    /// The pacing task (None on a remote console, which only mirrors progress)
+    pub task: Option<tokio::task::JoinHandle<()>>,
+    pub file: String,
+    pub sent: usize,
+    pub total: usize,
+}
+
+impl SendFile {
+    /// Stop the pacing task
+    pub fn abort(&self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+
+    /// Status bar text, "[Sending 12/40]"
+    pub fn indicator(&self) -> String {
+        format!("[Sending {}/{}]", self.sent, self.total)
+    }
+}
+
+/// Send `lines` to `world_name` as `AppEvent::SendFileLine`, one per `LINE_INTERVAL`
+pub fn spawn(world_name: String, lines: Vec<String>, event_tx: mpsc::Sender<AppEvent>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(LINE_INTERVAL);
+        for line in lines {
+            tick.tick().await;
+            if event_tx.send(AppEvent::SendFileLine(world_name.clone(), line)).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spawn_sends_lines_in_order() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let start = std::time::Instant::now();
+        spawn("mush".to_string(), vec!["&cmd me=$+hi:@pemit %#=Hi".to_string(), "".to_string(), "@set me=!halt".to_string()], tx);
+        let mut got = Vec::new();
+        while let Some(event) = rx.recv().await {
+            if let AppEvent::SendFileLine(world, line) = event {
+                assert_eq!(world, "mush");
+                got.push(line);
+            }
+        }
+        assert_eq!(got, vec!["&cmd me=$+hi:@pemit %#=Hi", "", "@set me=!halt"]);
+        assert!(start.elapsed() >= LINE_INTERVAL * 2, "lines are paced");
+        let job = SendFile { task: None, file: "f".to_string(), sent: 12, total: 40 };
+        assert_eq!(job.indicator(), "[Sending 12/40]");
+    }
+}
@@ -0,0 +1,180 @@
+//! `/shell <command>` and `/pipe <command>`: external programs run in the background.
+//!
+//! The command runs under `sh -c` (`cmd /C` on Windows) and each line it prints comes
+//! back as `AppEvent::ShellOutput`, so a slow program never holds up the UI. `/shell`
+//! shows stdout in the world's output; `/pipe` sends it to the world. As an action
+//! command, `/pipe` writes the line that fired the action to the program's stdin (see
+//! `actions::check_action_triggers`). Stderr is only ever shown, and a program still
+//! running after `TIMEOUT` is killed.
+
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+use crate::AppEvent;
+
+/// How long a program may run
+pub const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Where a program's stdout goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Output {
+    /// Shown in the world's output (/shell)
+    Show,
+    /// Sent to the world (/pipe)
+    Send,
+}
+
+fn shell_command(command: &str) -> tokio::process::Command {
+    #[cfg(windows)]
+    let mut shell = tokio::process::Command::new("cmd");
+    #[cfg(windows)]
+    shell.args(["/C", command]);
+    #[cfg(not(windows))]
+    let mut shell = tokio::process::Command::new("sh");
+    #[cfg(not(windows))]
+    shell.args(["-c", command]);
+    shell
+}
+
+/// Run `command` for `world_name`, writing `stdin` (plus a newline) to it first
+pub fn spawn(world_name: String, command: String, stdin: Option<String>, output: Output, event_tx: mpsc::Sender<AppEvent>) {
+    tokio::spawn(async move {
+        let emit = |output: Output, line: String| {
+            let event_tx = event_tx.clone();
+            let world_name = world_name.clone();
+            async move { event_tx.send(AppEvent::ShellOutput(world_name, output, line)).await.is_ok() }
+        };
+        let child = shell_command(&command)
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                emit(Output::Show, format!("{}: {}", command, e)).await;
+                return;
+            }
+        };
+        if let (Some(text), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            // Dropping the pipe afterwards gives the program its EOF
+            let _ = pipe.write_all(format!("{}\n", text).as_bytes()).await;
+        }
+        let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+            return;
+        };
+
+        let run = async {
+            let mut out = BufReader::new(stdout).lines();
+            let mut err = BufReader::new(stderr).lines();
+            let (mut out_done, mut err_done) = (false, false);
+            while !(out_done && err_done) {
+                tokio::select! {
+                    line = out.next_line(), if !out_done => match line {
+                        Ok(Some(line)) => if !emit(output, line).await { return None },
+                        _ => out_done = true,
+                    },
+                    line = err.next_line(), if !err_done => match line {
+                        Ok(Some(line)) => if !emit(Output::Show, line).await { return None },
+                        _ => err_done = true,
+                    },
+                }
+            }
+            child.wait().await.ok()
+        };
+        match tokio::time::timeout(TIMEOUT, run).await {
+            Ok(Some(status)) if !status.success() => {
+                let code = status.code().map_or_else(|| "a signal".to_string(), |c| format!("status {}", c));
+                emit(Output::Show, format!("{} exited with {}.", command, code)).await;
+            }
+            Err(_) => {
+                let _ = child.start_kill();
+                emit(Output::Show, format!("{} was still running after {}s and was stopped.", command, TIMEOUT.as_secs())).await;
+            }
+            _ => {}
+        }
+    });
+}
+
+/// Whether an action command hands its text to the shell: `/shell <command>`, or
+/// TF's `/sh <command>` and `/quote !<command>`
+pub fn runs_shell(cmd: &str) -> bool {
+    let mut words = cmd.split_whitespace();
+    match words.next().map(str::to_lowercase).as_deref() {
+        Some("/shell") | Some("/sh") => true,
+        Some("/quote") => words.find(|w| !w.starts_with('-')).is_some_and(|w| w.starts_with('!')),
+        _ => false,
+    }
+}
+
+/// `text` as a single word for the shell, so nothing in it is interpreted
+pub fn quote(text: &str) -> String {
+    // cmd.exe has no escape inside quotes; drop what could end the quoting or expand
+    #[cfg(windows)]
+    return format!("\"{}\"", text.replace(['"', '%'], ""));
+    #[cfg(not(windows))]
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Program named by an action command `/pipe <program>` (None for other commands)
+pub fn pipe_target(cmd: &str) -> Option<&str> {
+    let (head, rest) = cmd.trim().split_once(char::is_whitespace)?;
+    let program = rest.trim();
+    (head.eq_ignore_ascii_case("/pipe") && !program.is_empty()).then_some(program)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    async fn collect(command: &str, stdin: Option<&str>, output: Output) -> Vec<(Output, String)> {
+        let (tx, mut rx) = mpsc::channel(16);
+        spawn("mud".to_string(), command.to_string(), stdin.map(str::to_string), output, tx);
+        let mut got = Vec::new();
+        while let Some(event) = rx.recv().await {
+            if let AppEvent::ShellOutput(world, output, line) = event {
+                assert_eq!(world, "mud");
+                got.push((output, line));
+            }
+        }
+        got
+    }
+
+    #[tokio::test]
+    async fn test_shell_output_and_pipe_stdin() {
+        assert_eq!(collect("echo one; echo two", None, Output::Show).await,
+            vec![(Output::Show, "one".to_string()), (Output::Show, "two".to_string())]);
+        assert_eq!(collect("tr a-z A-Z", Some("Bob says hi"), Output::Send).await,
+            vec![(Output::Send, "BOB SAYS HI".to_string())]);
+        let got = collect("echo oops >&2; exit 3", None, Output::Send).await;
+        assert_eq!(got, vec![
+            (Output::Show, "oops".to_string()),
+            (Output::Show, "echo oops >&2; exit 3 exited with status 3.".to_string()),
+        ], "stderr and the exit status are shown, not sent");
+    }
+
+    #[test]
+    fn test_pipe_target() {
+        assert_eq!(pipe_target("/pipe  tr a-z A-Z "), Some("tr a-z A-Z"));
+        assert_eq!(pipe_target("/PIPE ./notify.sh"), Some("./notify.sh"));
+        assert_eq!(pipe_target("/pipe"), None);
+        assert_eq!(pipe_target("/piped cat"), None);
+        assert_eq!(pipe_target("/shell cat"), None);
+    }
+
+    #[tokio::test]
+    async fn test_quote_is_one_word() {
+        let text = "it's $(id); `id` | x \\ \"y\"";
+        assert_eq!(collect(&format!("printf '%s\\n' {}", quote(text)), None, Output::Show).await,
+            vec![(Output::Show, text.to_string())]);
+        assert!(runs_shell("/SHELL notify $1"));
+        assert!(runs_shell("/sh notify $1"));
+        assert!(runs_shell("/quote -S !notify $1"));
+        assert!(!runs_shell("/quote -S 'notify $1"));
+        assert!(!runs_shell("/pipe notify"));
+        assert!(!runs_shell("say /shell"));
+    }
+}
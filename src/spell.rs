@@ -1,6 +1,8 @@
 use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
 use std::process::Command;
-use strsim::levenshtein;
+use strsim::{levenshtein, osa_distance};
 
 // System dictionary paths to try (in order of preference)
 const SYSTEM_DICT_PATHS: &[&str] = &[
@@ -38,6 +40,17 @@ fn parse_dict_word(line: &str) -> Option<String> {
     }
 }
 
+// Words of the dictionary file at `path`. Hunspell .dic files get their .aff
+// affix rules applied; anything else is a plain word list.
+fn read_words(path: &std::path::Path) -> HashSet<String> {
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("dic")) {
+        return crate::hunspell::load_words(path).unwrap_or_default();
+    }
+    std::fs::read_to_string(path)
+        .map(|content| content.lines().filter_map(parse_dict_word).collect())
+        .unwrap_or_default()
+}
+
 // Find LibreOffice Hunspell dictionaries on Windows
 #[cfg(target_os = "windows")]
 fn find_hunspell_dict_paths() -> Vec<std::path::PathBuf> {
@@ -100,19 +113,91 @@ const CONTRACTION_SUFFIXES: &[&str] = &[
     "'m",    // I'm
 ];
 
+// Personal dictionary in the config directory (/addword), one word per line
+pub const PERSONAL_DICT: &str = "personal.dict";
+
+// Words spell check skips without adding them to the dictionary (/spellignore,
+// /unignore), in the config directory, one per line
+pub const IGNORE_LIST: &str = "spell_ignore.txt";
+
+// Lowercased `word`, or why it can't go in a dictionary
+fn dictionary_word(word: &str) -> Result<String, String> {
+    let word = word.trim().to_lowercase();
+    if word.is_empty() || !word.chars().all(|c| c.is_alphabetic()) {
+        return Err(format!("'{}' isn't a word (letters only).", word));
+    }
+    Ok(word)
+}
+
 pub struct SpellChecker {
     words: HashSet<String>,
+    // Words the user added: character names, MUD jargon
+    personal: HashSet<String>,
+    personal_path: Option<PathBuf>,
 }
 
 impl SpellChecker {
     pub fn new(custom_path: &str) -> Self {
+        let mut checker = Self {
+            words: Self::load_dictionary(custom_path),
+            personal: HashSet::new(),
+            personal_path: None,
+        };
+        checker.load_personal(crate::clay_config_path(PERSONAL_DICT));
+        checker
+    }
+
+    // Checker for a per-world language (e.g. "de_DE"), from its installed
+    // Hunspell dictionary. None if there's no such dictionary.
+    pub fn for_language(language: &str, dictionary_path: &str) -> Option<Self> {
+        let dic = crate::hunspell::find_dictionary(language, dictionary_path)?;
+        let mut checker = Self {
+            words: read_words(&dic),
+            personal: HashSet::new(),
+            personal_path: None,
+        };
+        checker.load_personal(crate::clay_config_path(PERSONAL_DICT));
+        Some(checker)
+    }
+
+    // Read the personal dictionary at `path`, which /addword then appends to
+    pub fn load_personal(&mut self, path: PathBuf) {
+        self.personal = std::fs::read_to_string(&path)
+            .map(|content| content.lines().filter_map(parse_dict_word).collect())
+            .unwrap_or_default();
+        self.personal_path = Some(path);
+    }
+
+    // Add `word` to the personal dictionary and its file. Ok(false) if it was
+    // already there.
+    pub fn add_word(&mut self, word: &str) -> Result<bool, String> {
+        let word = dictionary_word(word)?;
+        if self.personal.contains(&word) {
+            return Ok(false);
+        }
+        if let Some(path) = &self.personal_path {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{}", word))
+                .map_err(|e| format!("Couldn't save {}: {}", path.display(), e))?;
+        }
+        self.personal.insert(word);
+        Ok(true)
+    }
+
+    // Know `word` without saving it (another checker already added it to the file)
+    pub fn remember_word(&mut self, word: &str) {
+        self.personal.insert(word.trim().to_lowercase());
+    }
+
+    fn load_dictionary(custom_path: &str) -> HashSet<String> {
         let mut words = HashSet::new();
 
         // Try custom dictionary path first
         if !custom_path.is_empty() {
-            if let Ok(content) = std::fs::read_to_string(custom_path) {
-                words = content.lines().filter_map(parse_dict_word).collect();
-            }
+            words = read_words(std::path::Path::new(custom_path));
         }
 
         // Try system dictionary paths
@@ -128,11 +213,9 @@ impl SpellChecker {
         // Try Hunspell dictionaries (LibreOffice on Windows)
         if words.is_empty() {
             for path in find_hunspell_dict_paths() {
-                if let Ok(content) = std::fs::read_to_string(&path) {
-                    words = content.lines().filter_map(parse_dict_word).collect();
-                    if !words.is_empty() {
-                        break;
-                    }
+                words = read_words(&path);
+                if !words.is_empty() {
+                    break;
                 }
             }
         }
@@ -156,7 +239,11 @@ impl SpellChecker {
             }
         }
 
-        Self { words }
+        words
+    }
+
+    fn knows(&self, word: &str) -> bool {
+        self.words.contains(word) || self.personal.contains(word)
     }
 
     pub fn has_dictionary(&self) -> bool {
@@ -177,7 +264,7 @@ impl SpellChecker {
         let word_lower = word.to_lowercase();
 
         // Check if word is in dictionary
-        if self.words.contains(&word_lower) {
+        if self.knows(&word_lower) {
             return true;
         }
 
@@ -185,13 +272,13 @@ impl SpellChecker {
         if word_lower.contains('\'') {
             // Special case for irregular contractions
             if word_lower == "won't" {
-                return self.words.contains("will");
+                return self.knows("will");
             }
 
             for suffix in CONTRACTION_SUFFIXES {
                 if word_lower.ends_with(suffix) {
                     let base = &word_lower[..word_lower.len() - suffix.len()];
-                    if !base.is_empty() && self.words.contains(base) {
+                    if !base.is_empty() && self.knows(base) {
                         return true;
                     }
                 }
@@ -201,11 +288,29 @@ impl SpellChecker {
         false
     }
 
+    // What autocorrect changes `word` to: the closest suggestion, counting swapped
+    // letters as one edit, when it's one edit away (two for words over four letters)
+    // and no other suggestion is as close
+    pub fn correction(&self, word: &str) -> Option<String> {
+        let word_lower = word.to_lowercase();
+        let max = if word_lower.chars().count() <= 4 { 1 } else { 2 };
+        let mut candidates: Vec<(usize, String)> = self.suggestions(word, 20).into_iter()
+            .map(|w| (osa_distance(&word_lower, &w), w))
+            .collect();
+        candidates.sort_by_key(|(dist, _)| *dist);
+        let mut best = candidates.into_iter();
+        let (dist, correction) = best.next()?;
+        if dist > max || best.next().is_some_and(|(next, _)| next == dist) {
+            return None;
+        }
+        Some(correction)
+    }
+
     pub fn suggestions(&self, word: &str, count: usize) -> Vec<String> {
         let word_lower = word.to_lowercase();
         let mut candidates: Vec<(String, usize)> = self
             .words
-            .iter()
+            .union(&self.personal)
             .filter(|w| {
                 let len_diff = (w.len() as i32 - word_lower.len() as i32).abs();
                 len_diff <= 3
@@ -217,7 +322,8 @@ impl SpellChecker {
             .filter(|(_, dist)| *dist <= 3)
             .collect();
 
-        candidates.sort_by_key(|(_, dist)| *dist);
+        // Ties in word order, so the numbers in the spell menu don't shuffle between runs
+        candidates.sort_by(|(a, a_dist), (b, b_dist)| a_dist.cmp(b_dist).then_with(|| a.cmp(b)));
         candidates.truncate(count);
         candidates.into_iter().map(|(w, _)| w).collect()
     }
@@ -229,6 +335,73 @@ impl Default for SpellChecker {
     }
 }
 
+// Words never flagged, kept apart from the dictionary so suggestions don't offer
+// them: the saved list, plus ones ignored until Clay exits (Ctrl+Q then Esc-i)
+#[derive(Default)]
+pub struct IgnoreList {
+    words: HashSet<String>,
+    session: HashSet<String>,
+    path: Option<PathBuf>,
+}
+
+impl IgnoreList {
+    pub fn load(path: PathBuf) -> Self {
+        let words = std::fs::read_to_string(&path)
+            .map(|content| content.lines().filter_map(parse_dict_word).collect())
+            .unwrap_or_default();
+        Self { words, session: HashSet::new(), path: Some(path) }
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.words.contains(&word) || self.session.contains(&word)
+    }
+
+    // Ignore `word`, saving it to the list file when `save` (else for this session).
+    // Ok(false) if it was already ignored that way.
+    pub fn ignore(&mut self, word: &str, save: bool) -> Result<bool, String> {
+        let word = dictionary_word(word)?;
+        if self.words.contains(&word) || (!save && self.session.contains(&word)) {
+            return Ok(false);
+        }
+        if !save {
+            self.session.insert(word);
+            return Ok(true);
+        }
+        self.session.remove(&word);
+        self.words.insert(word);
+        self.save().map(|_| true)
+    }
+
+    // Check `word` again. Ok(false) if it wasn't ignored.
+    pub fn unignore(&mut self, word: &str) -> Result<bool, String> {
+        let word = dictionary_word(word)?;
+        let in_session = self.session.remove(&word);
+        if !self.words.remove(&word) {
+            return Ok(in_session);
+        }
+        self.save().map(|_| true)
+    }
+
+    // Saved and session words, each sorted
+    pub fn words(&self) -> (Vec<&str>, Vec<&str>) {
+        (sorted_words(&self.words), sorted_words(&self.session))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let (words, _) = self.words();
+        let content: String = words.iter().map(|w| format!("{}\n", w)).collect();
+        std::fs::write(path, content).map_err(|e| format!("Couldn't save {}: {}", path.display(), e))
+    }
+}
+
+fn sorted_words(set: &HashSet<String>) -> Vec<&str> {
+    let mut words: Vec<&str> = set.iter().map(String::as_str).collect();
+    words.sort_unstable();
+    words
+}
+
 pub struct SpellState {
     pub suggestions: Vec<String>,  // Includes original word at the end for cycling
     pub suggestion_index: usize,
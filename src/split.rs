@@ -0,0 +1,142 @@
+//! Split output: several worlds visible at once in the console.
+//!
+//! `/split <world>` adds a pane showing another world next to the current one,
+//! stacked (the default, `-h`) or side by side (`-v`). Each pane has a title row
+//! with its world's name; the focused pane is the current world, so input, scroll
+//! keys and more-mode act on it. `/split focus` (or the `split_focus` action, Alt+O)
+//! moves focus to the next pane by switching worlds, and each world keeps its own
+//! scroll position. Switching worlds any other way shows the new world in the
+//! focused pane, or moves focus to its pane if it already has one.
+//!
+//! The panes are drawn through ratatui (as with the editor split), so OSC 8 links,
+//! search and F8 highlighting in the output are only shown while unsplit.
+
+/// Most panes on screen at once
+pub const MAX_PANES: usize = 4;
+
+#[derive(Debug, Default)]
+pub struct SplitView {
+    /// World names, in screen order (top to bottom, or left to right)
+    pub panes: Vec<String>,
+    /// Slot of the pane showing the current world
+    pub focus: usize,
+    /// Side by side rather than stacked
+    pub vertical: bool,
+}
+
+impl SplitView {
+    pub fn is_active(&self) -> bool {
+        self.panes.len() > 1
+    }
+
+    fn slot(&self, name: &str) -> Option<usize> {
+        self.panes.iter().position(|p| p.eq_ignore_ascii_case(name))
+    }
+
+    /// Add a pane for `name` after the focused one (the first split also makes a pane
+    /// for the current world). Returns false when `name` is already shown or the
+    /// screen is full.
+    pub fn add(&mut self, current: &str, name: &str) -> bool {
+        if self.panes.is_empty() {
+            self.panes.push(current.to_string());
+            self.focus = 0;
+        }
+        if self.slot(name).is_some() || self.panes.len() >= MAX_PANES {
+            return false;
+        }
+        self.panes.insert(self.focus + 1, name.to_string());
+        true
+    }
+
+    /// Close the pane showing `name`. Returns the world now focused when the closed
+    /// pane had focus, so the caller can switch to it.
+    pub fn close(&mut self, name: &str) -> Option<String> {
+        let slot = self.slot(name)?;
+        self.panes.remove(slot);
+        let had_focus = slot == self.focus;
+        if slot < self.focus || self.focus >= self.panes.len() {
+            self.focus = self.focus.saturating_sub(1);
+        }
+        let focused = if had_focus { self.panes.get(self.focus).cloned() } else { None };
+        if !self.is_active() {
+            self.panes.clear();
+            self.focus = 0;
+        }
+        focused
+    }
+
+    /// World of the pane after the focused one (wrapping)
+    pub fn next_focus(&self) -> Option<&str> {
+        if !self.is_active() {
+            return None;
+        }
+        Some(&self.panes[(self.focus + 1) % self.panes.len()])
+    }
+
+    /// Follow the current world: focus its pane, or show it in the focused pane.
+    /// Panes whose world no longer exists (`exists` is false) are dropped.
+    pub fn sync(&mut self, current: &str, exists: impl Fn(&str) -> bool) {
+        if self.panes.is_empty() {
+            return;
+        }
+        if let Some(slot) = self.slot(current) {
+            self.focus = slot;
+        } else if let Some(pane) = self.panes.get_mut(self.focus) {
+            *pane = current.to_string();
+        }
+        let focused = self.panes[self.focus].clone();
+        self.panes.retain(|p| p.eq_ignore_ascii_case(&focused) || exists(p));
+        self.focus = self.slot(&focused).unwrap_or(0);
+        if !self.is_active() {
+            self.panes.clear();
+            self.focus = 0;
+        }
+    }
+}
+
+/// Share `total` rows or columns between `n` panes with `gap` between each (earlier
+/// panes get the remainder)
+pub fn split_lengths(total: u16, n: usize, gap: u16) -> Vec<u16> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let usable = total.saturating_sub(gap * (n as u16 - 1));
+    let base = usable / n as u16;
+    let extra = (usable % n as u16) as usize;
+    (0..n).map(|i| base + u16::from(i < extra)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_close_and_focus() {
+        let mut split = SplitView::default();
+        assert!(split.add("main", "chat"));
+        assert!(!split.add("main", "CHAT"));
+        assert_eq!(split.panes, vec!["main", "chat"]);
+        assert_eq!(split.next_focus(), Some("chat"));
+        // Switching to chat moves focus to its pane rather than replacing main
+        split.sync("chat", |_| true);
+        assert_eq!((split.focus, split.panes.len()), (1, 2));
+        // A world without a pane replaces the focused one
+        split.sync("other", |_| true);
+        assert_eq!(split.panes, vec!["main", "other"]);
+        assert_eq!(split.close("other"), Some("main".to_string()));
+        assert!(!split.is_active() && split.panes.is_empty());
+    }
+
+    #[test]
+    fn test_sync_drops_deleted_worlds_and_lengths() {
+        let mut split = SplitView::default();
+        split.add("a", "b");
+        split.add("a", "c");
+        assert_eq!(split.panes, vec!["a", "c", "b"]);
+        split.sync("a", |name| name != "c");
+        assert_eq!(split.panes, vec!["a", "b"]);
+        assert_eq!(split_lengths(21, 2, 0), vec![11, 10]);
+        assert_eq!(split_lengths(80, 3, 1), vec![26, 26, 26]);
+        assert!(split_lengths(10, 0, 1).is_empty());
+    }
+}
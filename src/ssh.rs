@@ -40,6 +40,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 #[cfg(feature = "ssh-transport")]
 use crate::platform;
+use crate::telnet::{StreamReader, StreamWriter};
 
 /// Parsed `[user@]host[:clay_port[:ssh_port]]` target.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -177,9 +178,15 @@ impl SshSession {
     /// Open a new `direct-tcpip` channel to `127.0.0.1:<clay_port>` on the remote
     /// host over this session.
     pub async fn open_tunnel(&self, clay_port: u16) -> Result<SshTunnel, SshError> {
+        self.open_channel("127.0.0.1", clay_port).await
+    }
+
+    /// Open a `direct-tcpip` channel to `host:port` as the remote host sees it -
+    /// a world reached through a bastion (see [`connect_world`]).
+    pub async fn open_channel(&self, host: &str, port: u16) -> Result<SshTunnel, SshError> {
         let channel = self
             .handle
-            .channel_open_direct_tcpip("127.0.0.1", clay_port as u32, "127.0.0.1", 0)
+            .channel_open_direct_tcpip(host, port as u32, "127.0.0.1", 0)
             .await
             .map_err(|e| SshError::ChannelRefused(format!("{e}")))?;
         Ok(SshTunnel { stream: channel.into_stream(), _session: self.clone() })
@@ -261,6 +268,39 @@ pub fn parse_ssh_target(spec: &str) -> Result<SshTarget, String> {
     Ok(SshTarget { user, host: host.to_string(), clay_port, ssh_port })
 }
 
+/// Parse a world's SSH bastion settings: `ssh_host` is `host[:ssh_port]` (22 if
+/// omitted, `[addr]:port` for an IPv6 literal) and an empty `ssh_user` means the
+/// local OS username. `clay_port` is unused on this path - the channel goes to the
+/// world's own host and port (see [`connect_world`]).
+pub fn parse_bastion(ssh_host: &str, ssh_user: &str) -> Result<SshTarget, String> {
+    let spec = ssh_host.trim();
+    let (host, ssh_port) = if let Some(rest) = spec.strip_prefix('[') {
+        match rest.split_once(']') {
+            Some((host, "")) => (host, 22u16),
+            Some((host, port)) => {
+                let port = port.strip_prefix(':').unwrap_or(port);
+                (host, port.parse::<u16>().map_err(|_| format!("invalid ssh port '{port}'"))?)
+            }
+            None => return Err(format!("missing ']' in SSH host '{spec}'")),
+        }
+    } else {
+        match spec.split_once(':') {
+            Some((host, port)) if !port.contains(':') => {
+                (host, port.parse::<u16>().map_err(|_| format!("invalid ssh port '{port}'"))?)
+            }
+            _ => (spec, 22u16),
+        }
+    };
+    if host.is_empty() {
+        return Err("missing SSH host".to_string());
+    }
+    let user = match ssh_user.trim() {
+        "" => local_os_username(),
+        user => user.to_string(),
+    };
+    Ok(SshTarget { user, host: host.to_string(), clay_port: 0, ssh_port })
+}
+
 /// Heuristic check for `--console=`/`--gui=` addresses passed without `--ssh`: the
 /// direct (non-SSH) grammar is always `[ws://|wss://]host[:port]` - a bare host, at
 /// most one port, never a `user@` prefix. A `[user@]host[:clayport[:sshport]]`
@@ -298,7 +338,7 @@ fn host_key_pin_key(host: &str, ssh_port: u16) -> String {
 }
 
 /// `russh::client::Handler` that does host-key TOFU verification against
-/// `~/.clay/known_hosts.dat` via the existing `platform::danger::check_pin`
+/// `~/.config/clay/known_hosts.dat` via the existing `platform::danger::check_pin`
 /// (the same silent-first-pin / hard-block-on-mismatch logic the TLS TofuVerifier
 /// uses), and records a mismatch for the caller to read back via
 /// `platform::danger::take_cert_mismatch()`.
@@ -437,6 +477,69 @@ pub async fn establish_tunnel(
     session.open_tunnel(target.clay_port).await
 }
 
+/// Open a world's connection through the SSH bastion in its `ssh_host`/`ssh_user`/
+/// `ssh_key` settings, for servers only reachable from that host. Logs in with
+/// ssh-agent, the world's key file and the default `~/.ssh/id_*` files (never
+/// prompting - a passphrase-protected key needs the agent), pins the bastion's host
+/// key like `--ssh` does, then opens a `direct-tcpip` channel from the bastion to
+/// the world's host and port. With `use_ssl`, TLS runs inside the tunnel, end to end
+/// to the MUD. The tunnel has no socket to hand across a hot reload, so callers
+/// treat it like a direct TLS connection there (reconnect afterwards).
+#[cfg(feature = "ssh-transport")]
+pub async fn connect_world(
+    settings: &crate::WorldSettings,
+    identity: Option<&platform::ClientIdentity>,
+) -> Result<(StreamReader, StreamWriter), String> {
+    let bastion = parse_bastion(&settings.ssh_host, &settings.ssh_user)?;
+    // Only the primary of a "primary,fallback" host pair; the bastion is the one route
+    let host = settings.hostname.split(',').next().unwrap_or("").trim();
+    let port: u16 = settings.port.trim().parse().map_err(|_| format!("Invalid port: {}", settings.port))?;
+    let key_pem = if settings.ssh_key.is_empty() {
+        None
+    } else {
+        let path = match settings.ssh_key.strip_prefix("~/") {
+            Some(home_rel) => PathBuf::from(crate::get_home_dir()).join(home_rel),
+            None => PathBuf::from(&settings.ssh_key),
+        };
+        Some(std::fs::read_to_string(&path).map_err(|e| format!("SSH key {}: {}", path.display(), e))?)
+    };
+    let creds = SshCredentials { key_pem, ..SshCredentials::desktop_default() };
+    let via = format!("{}@{}:{}", bastion.user, bastion.host, bastion.ssh_port);
+
+    let session = establish_session(&bastion, &creds, AuthContext::NonInteractive)
+        .await
+        .map_err(|e| match e {
+            SshError::Auth(msg) => format!(
+                "SSH login to {via} failed: {msg}. Tried ssh-agent, the world's SSH key and ~/.ssh/id_*; \
+                 a passphrase-protected key needs a running ssh-agent."
+            ),
+            other => render_ssh_error(&other),
+        })?;
+    let tunnel = session
+        .open_channel(host, port)
+        .await
+        .map_err(|e| match e {
+            SshError::ChannelRefused(msg) => format!("SSH bastion {via} could not reach {host}:{port}: {msg}"),
+            other => render_ssh_error(&other),
+        })?;
+
+    let mut stream: Box<dyn crate::telnet::TunnelIo> = Box::new(tunnel);
+    if settings.use_ssl {
+        stream = platform::mud_tls_over_tunnel(stream, host, &settings.port, settings.tls_verify, identity).await?;
+    }
+    let (r, w) = tokio::io::split(stream);
+    Ok((StreamReader::Tunnel(r), StreamWriter::Tunnel(w)))
+}
+
+/// Without `ssh-transport` a world set to connect via SSH fails with a clear message
+#[cfg(not(feature = "ssh-transport"))]
+pub async fn connect_world(
+    _settings: &crate::WorldSettings,
+    _identity: Option<&crate::platform::ClientIdentity>,
+) -> Result<(StreamReader, StreamWriter), String> {
+    Err("This build has no SSH support (built without the ssh-transport feature)".to_string())
+}
+
 #[cfg(feature = "ssh-transport")]
 async fn authenticate(
     handle: &mut Handle<TofuHandler>,
@@ -685,6 +788,21 @@ mod tests {
         assert_eq!(t.ssh_port, 2222);
     }
 
+    #[test]
+    fn test_parse_bastion() {
+        let t = parse_bastion("bastion.example.com", "alice").unwrap();
+        assert_eq!((t.user.as_str(), t.host.as_str(), t.ssh_port), ("alice", "bastion.example.com", 22));
+        let t = parse_bastion("bastion.example.com:2222", "").unwrap();
+        assert_eq!((t.host.as_str(), t.ssh_port), ("bastion.example.com", 2222));
+        assert_eq!(t.user, local_os_username());
+        let t = parse_bastion("[2001:db8::1]:2222", "bob").unwrap();
+        assert_eq!((t.host.as_str(), t.ssh_port), ("2001:db8::1", 2222));
+        let t = parse_bastion("2001:db8::1", "bob").unwrap();
+        assert_eq!((t.host.as_str(), t.ssh_port), ("2001:db8::1", 22));
+        assert!(parse_bastion("bastion:notaport", "").is_err());
+        assert!(parse_bastion("", "").is_err());
+    }
+
     #[test]
     fn test_parse_rejects_ws_prefix() {
         assert!(parse_ssh_target("ws://example.com").is_err());
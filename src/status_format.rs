@@ -0,0 +1,210 @@
+//! Template for the console status bar (the separator line above the input).
+//!
+//! The setting `status_format` (Status Bar in /setup) replaces the built-in layout
+//! with a template of `%field` tokens and literal text, e.g.
+//! `%status %conn%world%tags %activity%fill%keepalive %time`. Fields that have
+//! nothing to show expand to nothing; `%fill` expands to underscores so the bar
+//! spans the terminal (with several fills the space is shared out, and with none
+//! the bar is padded at the end). `%%` is a literal `%`, and an unknown `%name` is
+//! shown as typed. An empty template keeps the built-in layout.
+//!
+//! `%worlds` (and the built-in layout, when there is room) lists the other worlds
+//! with output waiting, e.g. `mume(12) ach(3*)`, where `*` marks a world with
+//! more-mode lines held back; Alt-w goes to the oldest of those first.
+
+/// A field that can appear in the template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// More/Hist indicator (always 9 columns)
+    Status,
+    /// Connection ball, green when connected
+    Conn,
+    /// Current world name
+    World,
+    /// "connected" / "disconnected"
+    State,
+    /// [tag] and [g] toggles
+    Tags,
+    /// Worlds with unseen output, "(Activity: N)"
+    Activity,
+    /// Per-world activity, "mume(12) ach(3*)"
+    Worlds,
+    /// Unseen lines in other worlds
+    Unseen,
+    /// Countdown to the next keepalive
+    Keepalive,
+    /// Capture worlds (tag route targets, /capture buffers) with unseen output
+    Capture,
+    /// Current world's prompt
+    Prompt,
+    /// Scheduled profile
+    Profile,
+    /// Search and copy mode position
+    Search,
+    /// Current world's /sendfile progress
+    Sending,
+    Time,
+    /// Underscores to the terminal width
+    Fill,
+}
+
+/// Field names, as written after `%`
+pub const FIELD_NAMES: &[(&str, Field)] = &[
+    ("status", Field::Status),
+    ("conn", Field::Conn),
+    ("world", Field::World),
+    ("state", Field::State),
+    ("tags", Field::Tags),
+    ("activity", Field::Activity),
+    ("worlds", Field::Worlds),
+    ("unseen", Field::Unseen),
+    ("keepalive", Field::Keepalive),
+    ("capture", Field::Capture),
+    ("prompt", Field::Prompt),
+    ("profile", Field::Profile),
+    ("search", Field::Search),
+    ("sending", Field::Sending),
+    ("time", Field::Time),
+    ("fill", Field::Fill),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Text(String),
+    Field(Field),
+}
+
+/// Split a template into literal text and fields
+pub fn parse(template: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(pos) = rest.find('%') {
+        text.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        if let Some(after) = rest.strip_prefix('%') {
+            text.push('%');
+            rest = after;
+            continue;
+        }
+        let name_len = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        match FIELD_NAMES.iter().find(|(name, _)| *name == &rest[..name_len]) {
+            Some((_, field)) => {
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Field(*field));
+            }
+            None => {
+                text.push('%');
+                text.push_str(&rest[..name_len]);
+            }
+        }
+        rest = &rest[name_len..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    segments
+}
+
+/// Unknown field names in a template, for reporting in /setup
+pub fn unknown_fields(template: &str) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(pos) = rest.find('%') {
+        rest = &rest[pos + 1..];
+        if let Some(after) = rest.strip_prefix('%') {
+            rest = after;
+            continue;
+        }
+        let name_len = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        if !name.is_empty() && !FIELD_NAMES.iter().any(|(n, _)| *n == name) {
+            unknown.push(name.to_string());
+        }
+        rest = &rest[name_len..];
+    }
+    unknown
+}
+
+/// Widths of `fills` fill fields sharing what is left of `width` after `used`
+/// columns of content (earlier fills get the remainder)
+pub fn fill_widths(used: usize, fills: usize, width: usize) -> Vec<usize> {
+    if fills == 0 {
+        return Vec::new();
+    }
+    let spare = width.saturating_sub(used);
+    (0..fills).map(|i| spare / fills + usize::from(i < spare % fills)).collect()
+}
+
+/// One world's entry in the activity summary
+pub struct WorldActivity<'a> {
+    pub name: &'a str,
+    pub unseen: usize,
+    /// Lines held back by more-mode
+    pub pending: usize,
+}
+
+/// Compact per-world activity, "mume(12) ach(3*)", for worlds with unseen or
+/// pending lines (None when there are none, or the summary is wider than `width`)
+pub fn world_summary(worlds: &[WorldActivity], width: usize) -> Option<String> {
+    let summary = worlds
+        .iter()
+        .filter(|w| w.unseen > 0 || w.pending > 0)
+        .map(|w| {
+            let marker = if w.pending > 0 { "*" } else { "" };
+            format!("{}({}{})", w.name, w.unseen.max(w.pending), marker)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!summary.is_empty() && summary.chars().count() <= width).then_some(summary)
+}
+
+/// Keepalive countdown in whole minutes, rounded up (the keepalive check runs once
+/// a minute, so seconds would only be noise)
+pub fn format_countdown(secs: u64) -> String {
+    format!("{}m", secs.div_ceil(60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fields_and_literals() {
+        assert_eq!(parse("%world %time"), vec![
+            Segment::Field(Field::World),
+            Segment::Text(" ".to_string()),
+            Segment::Field(Field::Time),
+        ]);
+        assert_eq!(parse("100%% [%bogus] %fill"), vec![
+            Segment::Text("100% [%bogus] ".to_string()),
+            Segment::Field(Field::Fill),
+        ]);
+        assert_eq!(parse("%worldx%"), vec![Segment::Text("%worldx%".to_string())]);
+        assert_eq!(unknown_fields("%world %bogus 5%% %x"), vec!["bogus", "x"]);
+    }
+
+    #[test]
+    fn test_fill_widths_share_the_spare_columns() {
+        assert_eq!(fill_widths(10, 2, 15), vec![3, 2]);
+        assert_eq!(fill_widths(20, 1, 15), vec![0]);
+        assert!(fill_widths(0, 0, 80).is_empty());
+        assert_eq!(format_countdown(252), "5m");
+        assert_eq!(format_countdown(0), "0m");
+    }
+
+    #[test]
+    fn test_world_summary_marks_pending() {
+        let worlds = [
+            WorldActivity { name: "mume", unseen: 12, pending: 0 },
+            WorldActivity { name: "quiet", unseen: 0, pending: 0 },
+            WorldActivity { name: "ach", unseen: 3, pending: 40 },
+        ];
+        assert_eq!(world_summary(&worlds, 80).as_deref(), Some("mume(12) ach(40*)"));
+        assert_eq!(world_summary(&worlds, 10), None);
+        assert_eq!(world_summary(&worlds[1..2], 80), None);
+    }
+}
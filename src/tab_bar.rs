@@ -0,0 +1,140 @@
+//! One-line world tab bar for the console, shown above the status bar.
+//!
+//! Enabled with Tab Bar in /setup. Each world gets a tab `N:name` with its unseen
+//! line count, coloured by connection state (see rendering.rs). Alt+1..Alt+9
+//! (actions `world_1`..`world_9`) jump to world N, and with Console Mouse on a
+//! click on a tab switches to it; the console then keeps mouse capture while the
+//! bar is shown, so terminal text selection needs Shift+drag (or copy mode, F7).
+//!
+//! When the tabs don't fit, the bar scrolls to keep the current world's tab in
+//! view, with `<` / `>` marking the hidden ends.
+//!
+//! Dragging a tab onto another moves the world there (the order is saved and
+//! shared with every client, see App::reorder_world), and a middle-click on a
+//! tab disconnects its world. The web and GUI clients draw the same strip.
+
+/// What a tab shows for one world
+pub struct TabInfo<'a> {
+    pub name: &'a str,
+    pub unseen: usize,
+}
+
+/// Label of the tab for world `idx` (0-based; the tab shows it 1-based)
+pub fn tab_label(idx: usize, tab: &TabInfo) -> String {
+    if tab.unseen > 0 {
+        format!(" {}:{}({}) ", idx + 1, tab.name, tab.unseen)
+    } else {
+        format!(" {}:{} ", idx + 1, tab.name)
+    }
+}
+
+/// Placement of the tabs on the bar, kept by the renderer for mouse clicks
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TabLayout {
+    /// Screen row of the bar
+    pub row: u16,
+    /// (first column, end column, world index) of each visible tab
+    pub tabs: Vec<(u16, u16, usize)>,
+    /// Tabs are hidden to the left / right of the visible ones
+    pub more_left: bool,
+    pub more_right: bool,
+}
+
+impl TabLayout {
+    /// World whose tab is at (column, row), if any
+    pub fn hit(&self, column: u16, row: u16) -> Option<usize> {
+        if row != self.row {
+            return None;
+        }
+        self.tabs
+            .iter()
+            .find(|(start, end, _)| column >= *start && column < *end)
+            .map(|(_, _, idx)| *idx)
+    }
+}
+
+/// Lay tabs of the given widths out on a bar `width` columns wide, scrolled so
+/// that tab `current` is visible. One column at each end is kept for `<` / `>`.
+pub fn layout(widths: &[usize], current: usize, width: usize, row: u16) -> TabLayout {
+    let total: usize = widths.iter().sum();
+    let mut out = TabLayout { row, ..TabLayout::default() };
+    if widths.is_empty() {
+        return out;
+    }
+    let (first, avail, offset) = if total <= width {
+        (0, width, 0)
+    } else {
+        // Start at the current tab and pull earlier tabs in while they fit
+        let avail = width.saturating_sub(2);
+        let current = current.min(widths.len() - 1);
+        let mut first = current;
+        let mut used = widths[current];
+        while first > 0 && used + widths[first - 1] <= avail {
+            first -= 1;
+            used += widths[first];
+        }
+        (first, avail, 1)
+    };
+    let mut col = 0;
+    for (idx, &w) in widths.iter().enumerate().skip(first) {
+        if col + w > avail {
+            out.more_right = true;
+            break;
+        }
+        out.tabs.push(((offset + col) as u16, (offset + col + w) as u16, idx));
+        col += w;
+    }
+    out.more_left = first > 0;
+    out
+}
+
+/// Where the world at index `i` ends up after the world at `from` is moved to `to`
+pub fn moved_index(i: usize, from: usize, to: usize) -> usize {
+    if i == from {
+        to
+    } else if from < to && i > from && i <= to {
+        i - 1
+    } else if to < from && i >= to && i < from {
+        i + 1
+    } else {
+        i
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_fits_and_hits() {
+        let l = layout(&[5, 6, 4], 1, 40, 20);
+        assert_eq!(l.tabs, vec![(0, 5, 0), (5, 11, 1), (11, 15, 2)]);
+        assert!(!l.more_left && !l.more_right);
+        assert_eq!(l.hit(7, 20), Some(1));
+        assert_eq!(l.hit(7, 19), None);
+        assert_eq!(l.hit(30, 20), None);
+        assert_eq!(tab_label(0, &TabInfo { name: "mud", unseen: 3 }), " 1:mud(3) ");
+    }
+
+    #[test]
+    fn test_layout_scrolls_to_current() {
+        // 4 tabs of 10 on a 22-wide bar: 20 usable columns between the markers
+        let l = layout(&[10, 10, 10, 10], 3, 22, 0);
+        assert_eq!(l.tabs, vec![(1, 11, 2), (11, 21, 3)]);
+        assert!(l.more_left && !l.more_right);
+        let l = layout(&[10, 10, 10, 10], 0, 22, 0);
+        assert_eq!(l.tabs, vec![(1, 11, 0), (11, 21, 1)]);
+        assert!(!l.more_left && l.more_right);
+    }
+
+    #[test]
+    fn test_moved_index() {
+        // Moving 1 -> 3 in [a b c d e] gives [a c d b e]
+        let after: Vec<usize> = (0..5).map(|i| moved_index(i, 1, 3)).collect();
+        assert_eq!(after, vec![0, 3, 1, 2, 4]);
+        // Moving 3 -> 0 gives [d a b c e]
+        let after: Vec<usize> = (0..5).map(|i| moved_index(i, 3, 0)).collect();
+        assert_eq!(after, vec![1, 2, 3, 0, 4]);
+        assert_eq!(moved_index(2, 2, 2), 2);
+    }
+}
@@ -0,0 +1,198 @@
+//! Routing rules keyed on inline MUD tags.
+//!
+//! Lines like `[public:] Bob says hi` or `[chat(Bob)] hi` carry a tag that
+//! `strip_mud_tag` hides when show_tags (F2) is off. A tag route turns that
+//! display-only tag into an actual routing decision: gag the line, gag it only
+//! while the user is idle, highlight it, or copy/move it into another world
+//! (typically a connectionless "capture" world such as `chat`).
+//!
+//! Routes are configured with `/tagroute` (or its popup) and persisted in the
+//! `[tag_routes]` section of settings.dat.
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::mud_tag_name;
+
+/// What to do with a line whose tag matches a route. Sent to web/GUI clients as
+/// its `spec` string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum TagRouteAction {
+    /// Hide the line (still revealed by F2, like any gagged line)
+    Gag,
+    /// Hide the line only when the user hasn't sent a command in N minutes
+    GagIdle(u32),
+    /// Show the line normally and also copy it to the named world
+    Copy(String),
+    /// Copy the line to the named world and gag it in the source world
+    Move(String),
+    /// Show the line with a highlight color (same names as /hilite)
+    Highlight(String),
+}
+
+impl TagRouteAction {
+    /// Parse the action spec used by `/tagroute add` and settings.dat:
+    /// `gag`, `gag-idle:MINS`, `copy:WORLD`, `move:WORLD`, `hilite:COLOR`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (kind, arg) = match spec.split_once(':') {
+            Some((k, a)) => (k, a.trim()),
+            None => (spec, ""),
+        };
+        match kind.trim().to_lowercase().as_str() {
+            "gag" if arg.is_empty() => Some(TagRouteAction::Gag),
+            "gag-idle" | "gagidle" => {
+                let mins = if arg.is_empty() { 5 } else { arg.parse().ok()? };
+                Some(TagRouteAction::GagIdle(mins))
+            }
+            "copy" if !arg.is_empty() => Some(TagRouteAction::Copy(arg.to_string())),
+            "move" if !arg.is_empty() => Some(TagRouteAction::Move(arg.to_string())),
+            "hilite" | "highlight" => {
+                let color = if arg.is_empty() { "yellow" } else { arg };
+                Some(TagRouteAction::Highlight(color.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Inverse of `parse`
+    pub fn spec(&self) -> String {
+        match self {
+            TagRouteAction::Gag => "gag".to_string(),
+            TagRouteAction::GagIdle(mins) => format!("gag-idle:{}", mins),
+            TagRouteAction::Copy(world) => format!("copy:{}", world),
+            TagRouteAction::Move(world) => format!("move:{}", world),
+            TagRouteAction::Highlight(color) => format!("hilite:{}", color),
+        }
+    }
+}
+
+impl From<TagRouteAction> for String {
+    fn from(action: TagRouteAction) -> Self {
+        action.spec()
+    }
+}
+
+impl TryFrom<String> for TagRouteAction {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        TagRouteAction::parse(&spec).ok_or_else(|| format!("bad tag route action: {}", spec))
+    }
+}
+
+/// A single routing rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagRoute {
+    /// Tag name without brackets/colon, matched case-insensitively ("public" matches `[public:]`)
+    pub tag: String,
+    /// Only apply to lines from this world (empty = all worlds)
+    pub world: String,
+    pub action: TagRouteAction,
+    pub enabled: bool,
+}
+
+impl TagRoute {
+    pub fn new(tag: &str, action: TagRouteAction) -> Self {
+        Self {
+            tag: normalize_tag(tag),
+            world: String::new(),
+            action,
+            enabled: true,
+        }
+    }
+
+    /// Serialize as `tag|world|action|enabled` for settings.dat
+    pub fn to_dat(&self) -> String {
+        format!("{}|{}|{}|{}", self.tag, self.world, self.action.spec(), self.enabled)
+    }
+
+    /// Parse the `to_dat` form. Returns None for malformed entries.
+    pub fn from_dat(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(4, '|');
+        let tag = parts.next()?;
+        let world = parts.next()?;
+        let action = TagRouteAction::parse(parts.next()?)?;
+        let enabled = parts.next().map(|v| v != "false").unwrap_or(true);
+        if tag.is_empty() {
+            return None;
+        }
+        Some(Self {
+            tag: normalize_tag(tag),
+            world: world.to_string(),
+            action,
+            enabled,
+        })
+    }
+}
+
+/// Accept `public`, `[public]`, `[public:]` and `public:` as the same tag name
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches(':')
+        .to_lowercase()
+}
+
+/// Find the first enabled route matching this line's tag (if it has one) for `world_name`
+pub fn match_route<'a>(routes: &'a [TagRoute], line: &str, world_name: &str) -> Option<&'a TagRoute> {
+    if routes.is_empty() {
+        return None;
+    }
+    let tag = mud_tag_name(line)?.to_lowercase();
+    routes.iter().find(|r| {
+        r.enabled
+            && r.tag == tag
+            && (r.world.is_empty() || r.world.eq_ignore_ascii_case(world_name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_spec_round_trip() {
+        for spec in ["gag", "gag-idle:10", "copy:chat", "move:chat", "hilite:red"] {
+            let action = TagRouteAction::parse(spec).unwrap();
+            assert_eq!(action.spec(), spec);
+        }
+        assert_eq!(TagRouteAction::parse("gag-idle"), Some(TagRouteAction::GagIdle(5)));
+        assert_eq!(TagRouteAction::parse("copy"), None);
+        assert_eq!(TagRouteAction::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_route_dat_round_trip() {
+        let mut route = TagRoute::new("[Public:]", TagRouteAction::Move("chat".to_string()));
+        route.world = "MyMud".to_string();
+        route.enabled = false;
+        assert_eq!(route.tag, "public");
+        let dat = route.to_dat();
+        assert_eq!(dat, "public|MyMud|move:chat|false");
+        assert_eq!(TagRoute::from_dat(&dat), Some(route));
+        assert_eq!(TagRoute::from_dat("|x|gag|true"), None);
+    }
+
+    #[test]
+    fn test_route_json_round_trip() {
+        let route = TagRoute::new("chat", TagRouteAction::GagIdle(10));
+        let json = serde_json::to_string(&route).unwrap();
+        assert!(json.contains(r#""action":"gag-idle:10""#), "{}", json);
+        assert_eq!(serde_json::from_str::<TagRoute>(&json).unwrap(), route);
+        assert!(serde_json::from_str::<TagRoute>(&json.replace("gag-idle:10", "bogus")).is_err());
+    }
+
+    #[test]
+    fn test_match_route() {
+        let mut combat = TagRoute::new("combat", TagRouteAction::GagIdle(5));
+        combat.world = "mud".to_string();
+        let routes = vec![combat, TagRoute::new("chat", TagRouteAction::Copy("chat".to_string()))];
+
+        assert_eq!(match_route(&routes, "[chat(Bob)] hello", "other").map(|r| r.tag.as_str()), Some("chat"));
+        assert_eq!(match_route(&routes, "[Combat:] You hit", "MUD").map(|r| r.tag.as_str()), Some("combat"));
+        assert!(match_route(&routes, "[combat:] You hit", "other").is_none());
+        assert!(match_route(&routes, "chat is quiet", "mud").is_none());
+        assert!(match_route(&routes, "  [chat:] indented", "mud").is_none());
+    }
+}
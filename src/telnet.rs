@@ -59,6 +59,19 @@ pub enum WriteCommand {
     Shutdown,         // Close the connection gracefully
 }
 
+/// Any byte stream a world connection can run over besides TCP and the TLS proxy;
+/// in practice an SSH tunnel, with or without TLS inside it
+pub trait TunnelIo: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> TunnelIo for T {}
+
+/// How a world's connection was opened: a direct TCP connection (TLS, if any, still
+/// to be negotiated) or an SSH tunnel already carrying TLS if the world uses it
+pub enum MudTransport {
+    Direct(TcpStream),
+    Tunneled(StreamReader, StreamWriter),
+}
+
 /// Stream wrapper enums for supporting both plain TCP and TLS connections
 pub enum StreamReader {
     Plain(tokio::net::tcp::OwnedReadHalf),
@@ -67,6 +80,7 @@ pub enum StreamReader {
     Proxy(tokio::net::unix::OwnedReadHalf),  // Unix socket for TLS proxy
     #[cfg(windows)]
     NamedPipeProxy(tokio::io::ReadHalf<tokio::net::windows::named_pipe::NamedPipeClient>),
+    Tunnel(ReadHalf<Box<dyn TunnelIo>>),  // SSH tunnel (see ssh::connect_world)
 }
 
 pub enum StreamWriter {
@@ -76,6 +90,7 @@ pub enum StreamWriter {
     Proxy(tokio::net::unix::OwnedWriteHalf),  // Unix socket for TLS proxy
     #[cfg(windows)]
     NamedPipeProxy(tokio::io::WriteHalf<tokio::net::windows::named_pipe::NamedPipeClient>),
+    Tunnel(WriteHalf<Box<dyn TunnelIo>>),
 }
 
 impl AsyncRead for StreamReader {
@@ -91,6 +106,7 @@ impl AsyncRead for StreamReader {
             StreamReader::Proxy(s) => Pin::new(s).poll_read(cx, buf),
             #[cfg(windows)]
             StreamReader::NamedPipeProxy(s) => Pin::new(s).poll_read(cx, buf),
+            StreamReader::Tunnel(s) => Pin::new(s).poll_read(cx, buf),
         }
     }
 }
@@ -108,6 +124,7 @@ impl AsyncWrite for StreamWriter {
             StreamWriter::Proxy(s) => Pin::new(s).poll_write(cx, buf),
             #[cfg(windows)]
             StreamWriter::NamedPipeProxy(s) => Pin::new(s).poll_write(cx, buf),
+            StreamWriter::Tunnel(s) => Pin::new(s).poll_write(cx, buf),
         }
     }
 
@@ -119,6 +136,7 @@ impl AsyncWrite for StreamWriter {
             StreamWriter::Proxy(s) => Pin::new(s).poll_flush(cx),
             #[cfg(windows)]
             StreamWriter::NamedPipeProxy(s) => Pin::new(s).poll_flush(cx),
+            StreamWriter::Tunnel(s) => Pin::new(s).poll_flush(cx),
         }
     }
 
@@ -130,6 +148,7 @@ impl AsyncWrite for StreamWriter {
             StreamWriter::Proxy(s) => Pin::new(s).poll_shutdown(cx),
             #[cfg(windows)]
             StreamWriter::NamedPipeProxy(s) => Pin::new(s).poll_shutdown(cx),
+            StreamWriter::Tunnel(s) => Pin::new(s).poll_shutdown(cx),
         }
     }
 }
@@ -141,6 +160,7 @@ pub struct TelnetResult {
     pub telnet_detected: bool,  // True if any telnet IAC sequences were found
     pub prompt: Option<Vec<u8>>, // Text from last newline to GA/EOR/WONT_ECHO, if found
     pub wont_echo_seen: bool,   // True if IAC WONT ECHO was received
+    pub server_echo: Option<bool>, // Some(true) on IAC WILL ECHO (mask input), Some(false) on WONT ECHO; last one wins
     pub naws_requested: bool,   // True if server sent DO NAWS (we responded WILL NAWS)
     pub ttype_requested: bool,  // True if server sent SB TTYPE SEND (we need to send terminal type)
     pub gmcp_data: Vec<(String, String)>,  // (package.message, json_data)
@@ -160,6 +180,7 @@ pub fn process_telnet(data: &[u8]) -> TelnetResult {
     let mut telnet_detected = false;
     let mut prompt: Option<Vec<u8>> = None;
     let mut wont_echo_seen = false;
+    let mut server_echo: Option<bool> = None;
     let mut naws_requested = false;
     let mut ttype_requested = false;
     let mut gmcp_data = Vec::new();
@@ -210,6 +231,11 @@ pub fn process_telnet(data: &[u8]) -> TelnetResult {
                             } else if option == TELNET_OPT_CHARSET {
                                 // Accept CHARSET negotiation (RFC 2066)
                                 responses.extend_from_slice(&[TELNET_IAC, TELNET_DO, option]);
+                            } else if option == TELNET_OPT_ECHO {
+                                // Server takes over echo, usually for a password prompt:
+                                // the input area is masked until WONT ECHO
+                                responses.extend_from_slice(&[TELNET_IAC, TELNET_DO, option]);
+                                server_echo = Some(true);
                             } else {
                                 responses.extend_from_slice(&[TELNET_IAC, TELNET_DONT, option]);
                             }
@@ -234,6 +260,7 @@ pub fn process_telnet(data: &[u8]) -> TelnetResult {
                             // WONT ECHO often precedes login/password prompts
                             // Mark that we saw it - we'll extract prompt at end
                             wont_echo_seen = true;
+                            server_echo = Some(false);
                         }
                         _ => {} // Other WONT/DONT - no response needed
                     }
@@ -354,6 +381,7 @@ pub fn process_telnet(data: &[u8]) -> TelnetResult {
         telnet_detected,
         prompt,
         wont_echo_seen,
+        server_echo,
         naws_requested,
         ttype_requested,
         gmcp_data,
@@ -723,6 +751,9 @@ pub enum AutoConnectType {
     Prompt,    // Send username on 1st prompt, password on 2nd prompt
     MooPrompt, // Like Prompt but also send username on 3rd prompt
     NoLogin,   // No auto-login even if credentials are set
+    Lines,     // Send username and password as two lines after connection
+    Diku,      // Answer name and password prompts matched by pattern (see login.rs)
+    Script,    // The world's own login script (see login.rs)
 }
 
 impl AutoConnectType {
@@ -732,14 +763,20 @@ impl AutoConnectType {
             AutoConnectType::Prompt => "Prompt",
             AutoConnectType::MooPrompt => "MOO_prompt",
             AutoConnectType::NoLogin => "None",
+            AutoConnectType::Lines => "Lines",
+            AutoConnectType::Diku => "Diku",
+            AutoConnectType::Script => "Script",
         }
     }
 
     pub fn next(&self) -> Self {
         match self {
-            AutoConnectType::Connect => AutoConnectType::Prompt,
+            AutoConnectType::Connect => AutoConnectType::Lines,
+            AutoConnectType::Lines => AutoConnectType::Prompt,
             AutoConnectType::Prompt => AutoConnectType::MooPrompt,
-            AutoConnectType::MooPrompt => AutoConnectType::NoLogin,
+            AutoConnectType::MooPrompt => AutoConnectType::Diku,
+            AutoConnectType::Diku => AutoConnectType::Script,
+            AutoConnectType::Script => AutoConnectType::NoLogin,
             AutoConnectType::NoLogin => AutoConnectType::Connect,
         }
     }
@@ -747,9 +784,21 @@ impl AutoConnectType {
     pub fn prev(&self) -> Self {
         match self {
             AutoConnectType::Connect => AutoConnectType::NoLogin,
-            AutoConnectType::Prompt => AutoConnectType::Connect,
+            AutoConnectType::Lines => AutoConnectType::Connect,
+            AutoConnectType::Prompt => AutoConnectType::Lines,
             AutoConnectType::MooPrompt => AutoConnectType::Prompt,
-            AutoConnectType::NoLogin => AutoConnectType::MooPrompt,
+            AutoConnectType::Diku => AutoConnectType::MooPrompt,
+            AutoConnectType::Script => AutoConnectType::Diku,
+            AutoConnectType::NoLogin => AutoConnectType::Script,
+        }
+    }
+
+    /// The prompt whose answer completes a Prompt/MOO_prompt login (0: no prompt login)
+    pub fn login_prompts(&self) -> usize {
+        match self {
+            AutoConnectType::Prompt => 2,
+            AutoConnectType::MooPrompt => 3,
+            _ => 0,
         }
     }
 
@@ -758,6 +807,9 @@ impl AutoConnectType {
             "prompt" => AutoConnectType::Prompt,
             "moo_prompt" | "mooprompt" => AutoConnectType::MooPrompt,
             "none" | "nologin" | "no_login" => AutoConnectType::NoLogin,
+            "lines" => AutoConnectType::Lines,
+            "diku" => AutoConnectType::Diku,
+            "script" => AutoConnectType::Script,
             _ => AutoConnectType::Connect,
         }
     }
@@ -887,6 +939,20 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_will_echo_masks_until_wont_echo() {
+        let mut data = b"Password: ".to_vec();
+        data.extend_from_slice(&[TELNET_IAC, TELNET_WILL, TELNET_OPT_ECHO]);
+        let result = process_telnet(&data);
+        assert_eq!(result.responses, vec![TELNET_IAC, TELNET_DO, TELNET_OPT_ECHO]);
+        assert_eq!(result.server_echo, Some(true));
+
+        let result = process_telnet(&[TELNET_IAC, TELNET_WONT, TELNET_OPT_ECHO, b'\n']);
+        assert_eq!(result.server_echo, Some(false));
+        assert!(result.wont_echo_seen);
+        assert_eq!(process_telnet(b"Welcome!\n").server_echo, None);
+    }
+
     #[test]
     fn test_mccp2_will_negotiation() {
         // Server sends IAC WILL MCCP2 → client should respond IAC DO MCCP2
@@ -642,7 +642,7 @@ pub async fn run_test_scenario(
                                             _ => None,
                                         }
                                     }
-                                    AutoConnectType::Connect | AutoConnectType::NoLogin => None,
+                                    _ => None,
                                 };
 
                                 if let Some(cmd) = cmd_to_send {
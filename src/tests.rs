@@ -22,6 +22,94 @@
         assert_eq!(input.cursor_position, 5);
     }
 
+    #[test]
+    fn test_input_wraps_double_width() {
+        let mut input = InputArea::new(3);
+        input.set_dimensions(10, 3);
+        for c in "你好世界你".chars() {
+            input.insert_char(c);
+        }
+        assert_eq!(input.cursor_line(), 1, "10 columns fill the first line exactly");
+        input.clear();
+        // A CJK prompt is measured in columns too: "生命>" takes 5 of the 10
+        input.prompt_len = display_width("生命>");
+        for c in "你好世".chars() {
+            input.insert_char(c);
+        }
+        assert_eq!(input.cursor_line(), 1);
+    }
+
+    #[test]
+    fn test_input_cursor_moves_by_grapheme() {
+        let mut input = InputArea::new(3);
+        input.set_dimensions(10, 3);
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        for c in format!("e\u{301}{}{}{}{}x", family, family, family, family).chars() {
+            input.insert_char(c);
+        }
+        // é is 1 column and each family 2, so with the x the line is exactly full
+        assert_eq!(input.cursor_line(), 1);
+        input.move_cursor_left();
+        assert_eq!(input.cursor_line(), 0);
+        input.move_cursor_left();
+        assert_eq!(input.cursor_position, input.buffer.len() - 1 - family.len(), "left steps over a whole family");
+        input.delete_char();
+        assert_eq!(input.buffer, format!("e\u{301}{}{}{}x", family, family, family));
+        input.home();
+        input.delete_char_forward();
+        assert_eq!(input.buffer, format!("{}{}{}x", family, family, family), "e and its accent go together");
+        input.move_cursor_right();
+        assert_eq!(input.cursor_position, family.len());
+    }
+
+    #[test]
+    fn test_reverse_history_search() {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        let mut input = InputArea::new(3);
+        input.history = vec!["look".into(), "say hello".into(), "look at sign".into(), "north".into()];
+        input.buffer = "draft".into();
+        input.reverse_search();
+        assert_eq!(input.reverse_search_prompt().as_deref(), Some("(reverse-i-search)`': "));
+        for c in "look".chars() {
+            assert!(input.reverse_search_key(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert_eq!(input.buffer, "look at sign", "newest entry containing the text");
+        assert_eq!(input.cursor_position, 0);
+        // Ctrl+R again goes further back
+        assert!(input.reverse_search_key(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(input.buffer, "look");
+        assert!(input.reverse_search_key(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(input.buffer, "look");
+        assert_eq!(input.reverse_search_prompt().as_deref(), Some("(failed reverse-i-search)`look': "));
+        // Backspace searches again from the newest entry
+        assert!(input.reverse_search_key(KeyCode::Backspace, KeyModifiers::NONE));
+        assert_eq!(input.buffer, "look at sign");
+        // Escape puts back what was being typed
+        assert!(input.reverse_search_key(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(input.reverse_search.is_none());
+        assert_eq!(input.buffer, "draft");
+
+        // Any other key accepts the match and is left for the caller (Enter sends it)
+        input.reverse_search();
+        for c in "hel".chars() {
+            input.reverse_search_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        assert!(!input.reverse_search_key(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(input.reverse_search.is_none());
+        assert_eq!(input.buffer, "say hello");
+    }
+
+    #[test]
+    fn test_wrap_keeps_grapheme_clusters_whole() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let line = format!("\x1b[31m{}{}{}\x1b[0m", family, family, family);
+        let rows: Vec<String> = crate::rendering::wrap_ansi_line(&line, 4, 0).iter().map(|r| strip_ansi_codes(r)).collect();
+        assert_eq!(rows, vec![format!("{}{}", family, family), family.to_string()]);
+        let parsed = crate::rendering::parse_ansi_row(&format!("a{}e\u{301}", family));
+        let clusters: Vec<&str> = parsed.iter().map(|(g, _)| g.as_str()).collect();
+        assert_eq!(clusters, vec!["a", family, "e\u{301}"]);
+    }
+
     #[test]
     fn test_insert_char_mixed() {
         let mut input = InputArea::new(3);
@@ -392,7 +480,7 @@
         let client_password = "test";
         let client_hash = hash_password(client_password);
         println!("Client sending hash: {}", client_hash);
-        let auth_msg = WsMessage::AuthRequest { password_hash: client_hash, username: None, current_world: None, auth_key: None, request_key: false, challenge_response: false };
+        let auth_msg = WsMessage::AuthRequest { password_hash: client_hash, username: None, current_world: None, auth_key: None, request_key: false, challenge_response: false, resume_seqs: Default::default() };
         let json = serde_json::to_string(&auth_msg).unwrap();
         ws_sink.send(WsRawMessage::Text(json)).await.unwrap();
 
@@ -462,6 +550,121 @@
             "After prev_world from zeta, should be on cave");
     }
 
+    #[test]
+    fn test_ws_clients_keep_their_own_world_focus() {
+        // A phone reading one world must not move the console or another client
+        let mut app = App::new();
+        app.worlds.clear();
+        for name in ["alpha", "beta"] {
+            let mut world = World::new(name);
+            world.connected = true;
+            app.worlds.push(world);
+        }
+        app.current_world_index = 0;
+        for client_id in [1, 2] {
+            app.ws_client_worlds.insert(client_id, ClientViewState {
+                world_index: 0, visible_lines: 20, visible_columns: 80, dimensions: None, paused: false,
+            });
+            app.ws_seed_client_unseen(client_id);
+        }
+
+        app.ws_focus_client_world(1, 1);
+        assert_eq!(app.current_world_index, 0, "a client switching worlds must not move the console");
+        assert_eq!(app.ws_client_worlds[&1].world_index, 1);
+        assert_eq!(app.ws_client_worlds[&2].world_index, 0);
+
+        // New lines in beta: client 1 is reading them, client 2 and the console are not
+        app.note_unseen_lines(1, 3, false, true);
+        assert_eq!(app.client_unseen(1, 1), 0);
+        assert_eq!(app.client_unseen(2, 1), 3);
+        assert_eq!(app.worlds[1].unseen_lines, 3);
+        assert_eq!(app.client_activity_count(2, Some(0)), 1);
+        assert_eq!(app.client_activity_count(1, Some(1)), 0);
+
+        // Client 2 catching up clears only its own count
+        app.ws_mark_client_seen(2, 1);
+        assert_eq!(app.client_unseen(2, 1), 0);
+        assert_eq!(app.worlds[1].unseen_lines, 3, "the console hasn't seen beta yet");
+
+        // A client that hasn't signed in yet falls back to the world's own count
+        assert_eq!(app.client_unseen(9, 1), 3);
+        app.handle_ws_client_disconnected(2);
+        assert!(!app.ws_client_unseen.contains_key(&2));
+    }
+
+    #[test]
+    fn test_macros_reach_remote_clients() {
+        // Clients fire macros themselves, so the world settings they get must carry them
+        let mut app = App::new();
+        app.is_master = false; // keep save_settings away from the real settings file
+        app.worlds.clear();
+        app.worlds.push(World::new("mud"));
+        assert_eq!(commands::macro_command(&mut app, 0, "shift-f5 \"cast heal\""), vec!["Shift-F5 (mud) -> cast heal"]);
+        let settings = app.world_settings_msg(0);
+        assert_eq!(settings.macros.get("Shift-F5").map(String::as_str), Some("cast heal"));
+
+        let mut remote = App::new();
+        remote.worlds.clear();
+        remote.worlds.push(World::new("mud"));
+        remote.handle_remote_ws_message(WsMessage::WorldSettingsUpdated { world_index: 0, settings, name: "mud".to_string() });
+        assert_eq!(remote.worlds[0].settings.macros, app.worlds[0].settings.macros);
+    }
+
+    #[test]
+    fn test_tag_routes_popup_opens_on_remote_clients() {
+        use crate::tag_routes::{TagRoute, TagRouteAction};
+        let routes = vec![
+            TagRoute::new("public", TagRouteAction::Gag),
+            TagRoute::new("chat", TagRouteAction::Copy("chat".to_string())),
+        ];
+        let is_open = |app: &App| app.popup_manager.current().is_some_and(|s| s.definition.id == popup::PopupId("tag_routes"));
+
+        // An unasked-for update only syncs the list
+        let mut remote = App::new();
+        remote.handle_remote_ws_message(WsMessage::TagRoutesUpdated { routes: routes.clone() });
+        assert_eq!(remote.settings.tag_routes, routes);
+        assert!(!is_open(&remote));
+
+        // A bare /tagroute's reply opens the popup, and later updates rebuild it in place
+        remote.tag_routes_popup_requested = true;
+        remote.handle_remote_ws_message(WsMessage::TagRoutesUpdated { routes: routes.clone() });
+        assert!(is_open(&remote));
+        assert!(!remote.tag_routes_popup_requested);
+        remote.handle_remote_ws_message(WsMessage::TagRoutesUpdated { routes: routes[1..].to_vec() });
+        assert!(is_open(&remote));
+        assert_eq!(remote.settings.tag_routes.len(), 1);
+    }
+
+    #[test]
+    fn test_reorder_world_keeps_state_with_its_world() {
+        // Dragging gamma's tab onto alpha's: [alpha beta gamma] -> [gamma alpha beta]
+        let mut app = App::new();
+        app.worlds.clear();
+        for name in ["alpha", "beta", "gamma"] {
+            app.worlds.push(World::new(name));
+        }
+        app.current_world_index = 1;
+        app.previous_world_index = Some(2);
+        app.ws_client_worlds.insert(1, ClientViewState {
+            world_index: 2, visible_lines: 20, visible_columns: 80, dimensions: None, paused: false,
+        });
+        app.ws_client_unseen.insert(1, [(0, 4), (2, 7)].into_iter().collect());
+        app.backfill_queue = vec![0, 2];
+
+        assert!(app.reorder_world(2, 0));
+        let names: Vec<&str> = app.worlds.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["gamma", "alpha", "beta"]);
+        assert_eq!(app.current_world_index, 2, "still on beta");
+        assert_eq!(app.previous_world_index, Some(0));
+        assert_eq!(app.ws_client_worlds[&1].world_index, 0);
+        assert_eq!(app.client_unseen(1, 1), 4);
+        assert_eq!(app.client_unseen(1, 0), 7);
+        assert_eq!(app.backfill_queue, vec![1, 0]);
+
+        assert!(!app.reorder_world(1, 1));
+        assert!(!app.reorder_world(0, 3));
+    }
+
     #[test]
     fn test_world_cycling_with_disconnected() {
         // Test that disconnected worlds without unseen output are skipped
@@ -712,6 +915,107 @@
         assert_eq!(KeepAliveType::from_name(""), KeepAliveType::Nop);
     }
 
+    #[test]
+    fn test_keep_alive_interval() {
+        assert_eq!(WorldSettings::parse_keep_alive_interval("120"), 120);
+        assert_eq!(WorldSettings::parse_keep_alive_interval(" 90 "), 90);
+        // Raised to the minimum; junk gives the default
+        assert_eq!(WorldSettings::parse_keep_alive_interval("5"), crate::MIN_KEEPALIVE_SECS);
+        assert_eq!(WorldSettings::parse_keep_alive_interval("soon"), crate::DEFAULT_KEEPALIVE_SECS);
+        let settings = WorldSettings { keep_alive_interval_secs: 0, ..WorldSettings::default() };
+        assert_eq!(settings.keep_alive_interval(), Duration::from_secs(crate::MIN_KEEPALIVE_SECS));
+        assert_eq!(WorldSettings::default().keep_alive_interval(), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn test_connect_commands_follow_login() {
+        let settings = WorldSettings {
+            connect_commands: "chan on\r\n\n  \nsay hi\n".to_string(),
+            ..WorldSettings::default()
+        };
+        assert_eq!(settings.connect_command_lines(), vec!["chan on", "say hi"]);
+        // Connect-type login: sent with the connection; /worlds -l sends nothing
+        assert_eq!(settings.connect_commands_at_login(false, true).len(), 2);
+        assert!(settings.connect_commands_at_login(true, true).is_empty());
+
+        // A prompt login sends them after answering its last prompt
+        let mut app = App::new();
+        let mut world = World::new("moo");
+        world.settings = WorldSettings { auto_connect_type: AutoConnectType::Prompt, ..settings };
+        world.settings.user = "bob".to_string();
+        world.settings.password = "pw".to_string();
+        assert!(world.settings.connect_commands_at_login(false, true).is_empty());
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<WriteCommand>(10);
+        world.command_tx = Some(tx);
+        world.connected = true;
+        app.worlds.push(world);
+        let mut sent = || {
+            let mut texts = Vec::new();
+            while let Ok(WriteCommand::Text(t)) = rx.try_recv() {
+                texts.push(t);
+            }
+            texts
+        };
+        app.handle_prompt(0, b"Name: ");
+        assert_eq!(sent(), vec!["bob"]);
+        app.handle_prompt(0, b"Password: ");
+        assert_eq!(sent(), vec!["pw", "chan on", "say hi"]);
+        app.handle_prompt(0, b"> ");
+        assert!(sent().is_empty());
+    }
+
+    #[test]
+    fn test_diku_login_answers_its_prompts() {
+        let mut app = App::new();
+        let mut world = World::new("diku");
+        world.settings = WorldSettings {
+            auto_connect_type: AutoConnectType::Diku,
+            user: "bob".to_string(),
+            password: "pw".to_string(),
+            connect_commands: "save".to_string(),
+            ..WorldSettings::default()
+        };
+        assert!(world.settings.login_lines("bob", "pw").is_empty());
+        assert!(world.settings.connect_commands_at_login(false, true).is_empty());
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<WriteCommand>(10);
+        world.command_tx = Some(tx);
+        world.connected = true;
+        world.connection_id = 1;
+        app.worlds.push(world);
+        let mut sent = || {
+            let mut texts = Vec::new();
+            while let Ok(WriteCommand::Text(t)) = rx.try_recv() {
+                texts.push(t);
+            }
+            texts
+        };
+        app.process_server_data(0, b"Welcome to the realm!\r\nBy what name do you wish to be known? ", 24, 80, false);
+        assert_eq!(sent(), vec!["bob"]);
+        app.process_server_data(0, b"\r\nPassword: ", 24, 80, false);
+        assert_eq!(sent(), vec!["pw", "save"]);
+        app.process_server_data(0, b"\r\nYour name? Password: ", 24, 80, false);
+        assert!(sent().is_empty(), "login is done");
+
+        // /worlds -l: a fresh connection with the skip flag answers nothing
+        app.worlds[0].connection_id = 2;
+        app.worlds[0].skip_auto_login = true;
+        app.process_server_data(0, b"\r\nBy what name do you wish to be known? ", 24, 80, false);
+        assert!(sent().is_empty());
+
+        // Script: opening lines go with the connection, the rest answer its waits
+        let script = WorldSettings {
+            auto_connect_type: AutoConnectType::Script,
+            login_script: "hello\nexpect ^Account:\n<user>\nexpect ^Secret:\n<pass>".to_string(),
+            ..WorldSettings::default()
+        };
+        assert_eq!(script.login_lines("bob", "pw"), vec!["hello"]);
+        assert!(script.login_waits_for_server());
+        assert_eq!(script.login_waits("bob", "pw").unwrap().unwrap().steps.len(), 2);
+        let lines = WorldSettings { auto_connect_type: AutoConnectType::Lines, ..WorldSettings::default() };
+        assert_eq!(lines.login_lines("bob", "pw"), vec!["bob", "pw"]);
+        assert!(!lines.login_waits_for_server());
+    }
+
     #[test]
     fn test_idler_message_filter() {
         // Test that lines containing idler message pattern are detected
@@ -957,6 +1261,58 @@
         assert_eq!(strip_mud_tag("[channel:] "), "");
     }
 
+    #[test]
+    fn test_mud_tag_name() {
+        use super::util::mud_tag_name;
+
+        assert_eq!(mud_tag_name("[channel:] hello").as_deref(), Some("channel"));
+        assert_eq!(mud_tag_name("[chat(Bob)extra] text").as_deref(), Some("chat"));
+        assert_eq!(mud_tag_name("\x1b[31m[public:] hi").as_deref(), Some("public"));
+        assert_eq!(mud_tag_name("  [channel:] hello"), None);
+        assert_eq!(mud_tag_name("[hello] world"), None);
+        assert_eq!(mud_tag_name("[channel:]hello"), None);
+    }
+
+    /// GUI local mode: a session opened in-process is a signed-in client of the App
+    #[tokio::test]
+    async fn test_local_client_session() {
+        use crate::websocket::{WebSocketServer, WsMessage};
+
+        let server = WebSocketServer::new("", 9000, "", None, false, BanList::new());
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::channel::<AppEvent>(100);
+        let link = server.local_link(event_tx);
+
+        let (client, mut outgoing) = link.connect().await;
+        assert!(server.clients.read().await.get(&client.id).is_some_and(|c| c.authenticated));
+        assert!(matches!(event_rx.recv().await, Some(AppEvent::WsClientConnected(id)) if id == client.id));
+
+        // Its AuthRequest needs no password and goes on to the App for the InitialState
+        client.send(WsMessage::AuthRequest {
+            username: None,
+            password_hash: String::new(),
+            current_world: None,
+            auth_key: None,
+            request_key: false,
+            challenge_response: false,
+            resume_seqs: Default::default(),
+        }).await;
+        assert!(matches!(outgoing.recv().await, Some(WsMessage::AuthResponse { success: true, .. })));
+        assert!(matches!(event_rx.recv().await,
+            Some(AppEvent::WsClientMessage(id, msg)) if id == client.id && matches!(*msg, WsMessage::AuthRequest { .. })));
+
+        client.send(WsMessage::Ping).await;
+        assert!(matches!(outgoing.recv().await, Some(WsMessage::Pong)));
+
+        // A second session gets its own id; closing one leaves the other
+        let (other, _other_outgoing) = link.connect().await;
+        assert_ne!(other.id, client.id);
+        let id = client.id;
+        client.close().await;
+        assert!(server.clients.read().await.get(&id).is_none());
+        assert!(server.clients.read().await.get(&other.id).is_some());
+        assert!(outgoing.recv().await.is_none());
+    }
+
     // ============================================================================
     // Security regression tests
     // ============================================================================
@@ -998,6 +1354,7 @@
                 server_clients,
                 password_hash,
                 true, // password_enabled
+                String::new(), // no viewer password
                 allow_list,
                 whitelisted,
                 client_addr,
@@ -1072,7 +1429,7 @@
         let server_task = tokio::spawn(async move {
             let (stream, client_addr) = listener.accept().await.unwrap();
             crate::websocket::handle_ws_client(
-                stream, 1, server_clients, password_hash, true,
+                stream, 1, server_clients, password_hash, true, String::new(),
                 allow_list, whitelisted, client_addr, event_tx,
                 false, users, ban_list,
                 false, // knocked: not exercised by this test
@@ -1106,6 +1463,147 @@
         server_task.abort();
     }
 
+    /// Test: a client signed in with the viewer password is refused commands, gets no
+    /// key and doesn't whitelist its address
+    #[tokio::test]
+    async fn test_security_viewer_is_read_only() {
+        use tokio::net::TcpListener;
+        use tokio_tungstenite::{connect_async, tungstenite::Message as WsRawMessage};
+        use futures::{SinkExt, StreamExt};
+        use crate::websocket::{WsMessage, WsClientInfo};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::channel::<AppEvent>(100);
+        let clients: Arc<RwLock<std::collections::HashMap<u64, WsClientInfo>>> =
+            Arc::new(RwLock::new(std::collections::HashMap::new()));
+        let allow_list: Arc<std::sync::RwLock<Vec<String>>> =
+            Arc::new(std::sync::RwLock::new(vec!["*".to_string()]));
+        let whitelisted: Arc<std::sync::RwLock<Option<String>>> =
+            Arc::new(std::sync::RwLock::new(None));
+        let users: Arc<std::sync::RwLock<std::collections::HashMap<String, crate::websocket::UserCredential>>> =
+            Arc::new(std::sync::RwLock::new(std::collections::HashMap::new()));
+
+        let server_clients = Arc::clone(&clients);
+        let server_whitelisted = Arc::clone(&whitelisted);
+        let server_task = tokio::spawn(async move {
+            let (stream, client_addr) = listener.accept().await.unwrap();
+            crate::websocket::handle_ws_client(
+                stream, 1, server_clients, hash_password("fullpass"), true, hash_password("watchpass"),
+                allow_list, server_whitelisted, client_addr, event_tx,
+                false, users, BanList::new(),
+                false, // knocked: not exercised by this test
+            ).await.ok();
+        });
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        let (ws_stream, _) = connect_async(&format!("ws://127.0.0.1:{}", port)).await.unwrap();
+        let (mut ws_sink, mut ws_source) = ws_stream.split();
+        let _ = ws_source.next().await; // ServerHello
+
+        let send = |msg: WsMessage| WsRawMessage::Text(serde_json::to_string(&msg).unwrap());
+        ws_sink.send(send(WsMessage::AuthRequest {
+            password_hash: hash_password("watchpass"),
+            username: None,
+            current_world: None,
+            auth_key: None,
+            request_key: true,
+            challenge_response: false,
+            resume_seqs: Default::default(),
+        })).await.unwrap();
+        match ws_source.next().await {
+            Some(Ok(WsRawMessage::Text(text))) => {
+                assert!(matches!(serde_json::from_str(&text).unwrap(), WsMessage::AuthResponse { success: true, .. }));
+            }
+            other => panic!("expected AuthResponse, got {:?}", other),
+        }
+        assert!(clients.read().await.get(&1).is_some_and(|c| c.authenticated && c.viewer));
+        assert_eq!(*whitelisted.read().unwrap(), None, "a viewer must not whitelist its address");
+
+        ws_sink.send(send(WsMessage::SendCommand { world_index: 0, command: "look".to_string() })).await.unwrap();
+        match ws_source.next().await {
+            Some(Ok(WsRawMessage::Text(text))) => {
+                assert!(matches!(serde_json::from_str(&text).unwrap(), WsMessage::ViewOnly { .. }));
+            }
+            other => panic!("expected ViewOnly, got {:?}", other),
+        }
+        ws_sink.send(send(WsMessage::RequestState)).await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        let mut forwarded = Vec::new();
+        while let Ok(event) = event_rx.try_recv() {
+            match event {
+                AppEvent::WsClientMessage(_, msg) => forwarded.push(*msg),
+                AppEvent::WsKeyRequest(_) => panic!("a viewer must not be issued a key"),
+                _ => {}
+            }
+        }
+        assert!(!forwarded.iter().any(|m| matches!(m, WsMessage::SendCommand { .. })));
+        assert!(forwarded.iter().any(|m| matches!(m, WsMessage::RequestState)));
+
+        // World credentials and login scripts never reach a viewer
+        let mut app = App::new();
+        let mut world = World::new("mush");
+        world.settings.password = "hunter2".to_string();
+        world.settings.login_script = "connect bob hunter2".to_string();
+        app.worlds.push(world);
+        let settings = app.world_settings_msg(0);
+        assert_eq!(settings.password, "hunter2");
+        let tx = clients.read().await.get(&1).unwrap().tx.clone();
+        tx.send(WsMessage::WorldSettingsUpdated { world_index: 0, settings, name: "mush".to_string() }).unwrap();
+        match ws_source.next().await {
+            Some(Ok(WsRawMessage::Text(text))) => match serde_json::from_str(&text).unwrap() {
+                WsMessage::WorldSettingsUpdated { settings, .. } => {
+                    assert!(settings.password.is_empty());
+                    assert!(settings.has_password);
+                    assert!(settings.login_script.is_empty());
+                }
+                other => panic!("expected WorldSettingsUpdated, got {:?}", other),
+            },
+            other => panic!("expected WorldSettingsUpdated, got {:?}", other),
+        }
+
+        server_task.abort();
+    }
+
+    /// Test: viewers are signed out even when the client list is busy at the time
+    #[tokio::test]
+    async fn test_security_sign_out_viewers_waits_for_busy_clients() {
+        use crate::websocket::{WsMessage, WsClientInfo, WebSocketServer, RemoteClientType};
+
+        let server = WebSocketServer::new("", 9000, "", None, false, BanList::new());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<WsMessage>();
+        let viewer = |tx| WsClientInfo {
+            authenticated: true,
+            tx,
+            current_world: None,
+            username: None,
+            received_initial_state: true,
+            client_type: RemoteClientType::Web,
+            viewport_height: 24,
+            ip_address: "127.0.0.1".to_string(),
+            connected_at: std::time::Instant::now(),
+            last_activity: std::time::Instant::now(),
+            paused: false,
+            viewer: true,
+        };
+        server.clients.write().await.insert(1, viewer(tx.clone()));
+
+        let busy = server.clients.read().await;
+        assert_eq!(server.sign_out_viewers(), None);
+        drop(busy);
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        assert!(server.clients.read().await.get(&1).is_some_and(|c| !c.authenticated && !c.viewer));
+        assert!(matches!(rx.try_recv(), Ok(WsMessage::LoggedOut)));
+
+        server.clients.write().await.insert(2, viewer(tx));
+        assert_eq!(server.sign_out_viewers(), Some(1));
+    }
+
     /// Test: Failed password auth triggers ban violation
     #[tokio::test]
     async fn test_security_failed_auth_records_violation() {
@@ -1136,7 +1634,7 @@
         let server_task = tokio::spawn(async move {
             let (stream, client_addr) = listener.accept().await.unwrap();
             crate::websocket::handle_ws_client(
-                stream, 1, server_clients, password_hash, true,
+                stream, 1, server_clients, password_hash, true, String::new(),
                 allow_list, whitelisted, client_addr, event_tx,
                 false, users, ban_list,
                 false, // knocked: not exercised by this test
@@ -1161,6 +1659,7 @@
             auth_key: None,
             request_key: false,
             challenge_response: false,
+            resume_seqs: Default::default(),
         };
         let json = serde_json::to_string(&auth_msg).unwrap();
         ws_sink.send(WsRawMessage::Text(json)).await.unwrap();
@@ -1229,7 +1728,7 @@
         let server_task1 = tokio::spawn(async move {
             let (stream, client_addr) = listener.accept().await.unwrap();
             crate::websocket::handle_ws_client(
-                stream, 1, server_clients, password_hash, true,
+                stream, 1, server_clients, password_hash, true, String::new(),
                 allow_list, whitelisted, client_addr, event_tx,
                 true, // multiuser mode
                 users, ban_list,
@@ -1245,7 +1744,7 @@
         let server_task2 = tokio::spawn(async move {
             let (stream, client_addr) = listener2.accept().await.unwrap();
             crate::websocket::handle_ws_client(
-                stream, 2, clients2, password_hash2, true,
+                stream, 2, clients2, password_hash2, true, String::new(),
                 allow_list2, whitelisted2, client_addr, event_tx2,
                 true, users2, ban_list2,
                 false, // knocked: not exercised by this test
@@ -1267,6 +1766,7 @@
             auth_key: None,
             request_key: false,
             challenge_response: false,
+            resume_seqs: Default::default(),
         };
         sink1.send(WsRawMessage::Text(serde_json::to_string(&auth1).unwrap())).await.unwrap();
         let error1 = if let Some(Ok(WsRawMessage::Text(text))) = source1.next().await {
@@ -1287,6 +1787,7 @@
             auth_key: None,
             request_key: false,
             challenge_response: false,
+            resume_seqs: Default::default(),
         };
         sink2.send(WsRawMessage::Text(serde_json::to_string(&auth2).unwrap())).await.unwrap();
         let error2 = if let Some(Ok(WsRawMessage::Text(text))) = source2.next().await {
@@ -1414,6 +1915,7 @@
             auth_key: Some("test_key".to_string()),
             request_key: false,
             challenge_response: false,
+            resume_seqs: Default::default(),
         };
         let event = AppEvent::WsAuthKeyValidation(1, Box::new(msg), "10.0.0.1".to_string(), "test_challenge".to_string());
 
@@ -1456,7 +1958,7 @@
         let server_task = tokio::spawn(async move {
             let (stream, client_addr) = listener.accept().await.unwrap();
             crate::websocket::handle_ws_client(
-                stream, 1, server_clients, ph, true,
+                stream, 1, server_clients, ph, true, String::new(),
                 allow_list, whitelisted, client_addr, event_tx,
                 false, users, ban_list,
                 false, // knocked: not exercised by this test
@@ -1479,6 +1981,7 @@
             auth_key: None,
             request_key: false,
             challenge_response: false,
+            resume_seqs: Default::default(),
         };
         sink.send(WsRawMessage::Text(serde_json::to_string(&auth).unwrap())).await.unwrap();
 
@@ -1496,6 +1999,15 @@
         server_task.abort();
     }
 
+    /// Test: web_path keeps several segments for a reverse proxy mount point
+    #[test]
+    fn test_sanitize_web_path_segments() {
+        assert_eq!(sanitize_web_path("/clay/"), "clay");
+        assert_eq!(sanitize_web_path("apps//clay/"), "apps/clay");
+        assert_eq!(sanitize_web_path("a b/../c?"), "ab/c");
+        assert_eq!(sanitize_web_path("/"), "");
+    }
+
     /// Test: Allow list IP matching with wildcards
     #[test]
     fn test_security_allow_list_matching() {
@@ -1591,7 +2103,7 @@
         let server_task = tokio::spawn(async move {
             let (stream, client_addr) = listener.accept().await.unwrap();
             crate::websocket::handle_ws_client(
-                stream, 1, server_clients, ph, true,
+                stream, 1, server_clients, ph, true, String::new(),
                 allow_list, whitelisted, client_addr, event_tx,
                 false, users, ban_list,
                 false, // knocked: not exercised by this test
@@ -1657,7 +2169,7 @@
     fn reset_more_mode_on_send_preserves_scrollback() {
         let mut world = World::new("test");
         for i in 0..50 {
-            world.output_lines.push(OutputLine::new(format!("line {}", i), i as u64));
+            world.output_lines.push_back(OutputLine::new(format!("line {}", i), i as u64));
         }
         // Scrolled up into history in more-mode: paused, viewport above bottom, nothing held.
         world.paused = true;
@@ -1671,11 +2183,248 @@
         assert_eq!(world.scroll_offset, 10, "viewport must not move when sending from scrollback");
     }
 
+    #[test]
+    fn test_trim_scrollback_evicts_oldest_and_keeps_view() {
+        let mut world = World::new("test");
+        for i in 0..1500 {
+            world.output_lines.push_back(OutputLine::new(format!("line {}", i), i as u64));
+        }
+        // Scrolled up to line 1200, with the new-line indicator starting at line 1300
+        world.scroll_offset = 1200;
+        world.first_marked_new_index = Some(1300);
+
+        assert_eq!(world.trim_scrollback(1000), 500);
+        assert_eq!(world.output_lines.len(), 1000);
+        assert_eq!(world.output_lines[0].text, "line 500");
+        assert_eq!(world.output_lines[world.scroll_offset].text, "line 1200", "view stays on the same line");
+        assert_eq!(world.first_marked_new_index, Some(800));
+        assert_eq!(world.trim_scrollback(1000), 0, "nothing to evict within the cap");
+
+        // Following the bottom stays at the bottom
+        for i in 1500..1600 {
+            world.output_lines.push_back(OutputLine::new(format!("line {}", i), i as u64));
+        }
+        world.scroll_offset = world.output_lines.len() - 1;
+        world.trim_scrollback(1000);
+        assert!(world.is_at_bottom());
+        assert_eq!(world.output_lines.back().map(|l| l.text.as_str()), Some("line 1599"));
+
+        // A copy mode selection holds line indices, so its world isn't trimmed until it ends
+        let mut app = App::new();
+        app.settings.scrollback_lines = 1000;
+        app.worlds.push(world);
+        for i in 1600..1700 {
+            app.worlds[0].output_lines.push_back(OutputLine::new(format!("line {}", i), i as u64));
+        }
+        app.copy_mode.enter(10);
+        app.trim_scrollback();
+        assert_eq!(app.worlds[0].output_lines.len(), 1100);
+        app.copy_mode.exit();
+        app.trim_scrollback();
+        assert_eq!(app.worlds[0].output_lines.len(), 1000);
+    }
+
+    #[test]
+    fn test_scrollback_range_pages_both_ways() {
+        let mut app = App::new();
+        let mut world = World::new("test");
+        for i in 0..10 {
+            world.output_lines.push_back(OutputLine::new(format!("line {}", i), i as u64));
+        }
+        app.worlds.push(world);
+        let seqs = |range: Option<(Vec<TimestampedLine>, bool)>| {
+            range.map(|(lines, complete)| (lines.iter().map(|l| l.seq).collect::<Vec<_>>(), complete))
+        };
+
+        assert_eq!(seqs(app.scrollback_range(0, 3, None, None)), Some((vec![7, 8, 9], false)));
+        assert_eq!(seqs(app.scrollback_range(0, 3, Some(7), None)), Some((vec![4, 5, 6], false)));
+        assert_eq!(seqs(app.scrollback_range(0, 3, Some(2), None)), Some((vec![0, 1], true)), "ran out of older lines");
+        assert_eq!(seqs(app.scrollback_range(0, 3, None, Some(5))), Some((vec![6, 7, 8], false)));
+        assert_eq!(seqs(app.scrollback_range(0, 3, None, Some(8))), Some((vec![9], true)), "gap closed");
+        assert_eq!(seqs(app.scrollback_range(0, 3, Some(7), Some(1))), Some((vec![4, 5, 6], false)), "before_seq wins");
+        assert_eq!(seqs(app.scrollback_range(1, 3, None, None)), None);
+    }
+
+    #[test]
+    fn test_filter_narrows_but_sees_new_output() {
+        let mut app = App::new();
+        app.worlds.push(World::new("test"));
+        let world = app.current_world_mut();
+        for (i, text) in ["Bob says hi", "\x1b[31mBOB\x1b[0m waves", "Alice says bye"].iter().enumerate() {
+            world.output_lines.push_back(OutputLine::new(text.to_string(), i as u64));
+        }
+        app.filter_popup.open();
+        app.filter_popup.filter_text = "bo".to_string();
+        app.refresh_filter();
+        assert_eq!(app.filter_popup.filtered_indices, vec![0, 1]);
+        app.filter_popup.filter_text = "bob s".to_string();
+        app.refresh_filter();
+        assert_eq!(app.filter_popup.filtered_indices, vec![0]);
+
+        // Output arriving between keystrokes is searched too
+        app.current_world_mut().output_lines.push_back(OutputLine::new("bob says more".to_string(), 3));
+        app.filter_popup.filter_text = "bob sa".to_string();
+        app.refresh_filter();
+        assert_eq!(app.filter_popup.filtered_indices, vec![0, 3]);
+        app.filter_popup.filter_text = "b*e".to_string();
+        app.refresh_filter();
+        assert_eq!(app.filter_popup.filtered_indices, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_trigger_test_reports_matches_without_running() {
+        let mut app = App::new();
+        app.worlds.push(World::new("mush"));
+        let mut tell = Action::new();
+        tell.name = "tell".to_string();
+        tell.match_type = MatchType::Wildcard;
+        tell.patterns.push(MatchPattern { pattern: "* tells you: *".to_string(), compiled_regex: None });
+        tell.command = "reply $2;/highlight red".to_string();
+        let mut gag = Action::new();
+        gag.name = "quiet".to_string();
+        gag.patterns.push(MatchPattern { pattern: "tells you".to_string(), compiled_regex: None });
+        gag.command = "/gag".to_string();
+        app.settings.actions = vec![tell, gag];
+        compile_all_action_regexes(&mut app.settings.actions);
+
+        match parse_command("/trigger test  \"Bob tells you: hi  there\"") {
+            Command::TriggerTest { args } => {
+                assert_eq!(args, "\"Bob tells you: hi  there\"");
+                assert_eq!(commands::trigger_test_command(&app, 0, &args), vec![
+                    "'tell' fires: $0=\"Bob tells you: hi  there\" $1=\"Bob\" $2=\"hi  there\"",
+                    "  highlights the line red",
+                    "  would run: reply hi  there",
+                    "Also matches 'quiet' (doesn't fire; the first match wins): $0=\"tells you\"",
+                ]);
+            }
+            other => panic!("Expected TriggerTest, got {:?}", other),
+        }
+        // Anything else is TF's /trigger
+        assert!(matches!(parse_command("/trigger hungry"), Command::ActionCommand { .. }));
+        assert_eq!(commands::trigger_test_command(&app, 0, "nothing here"), vec!["No action matches that line in mush."]);
+        assert_eq!(commands::trigger_test_command(&app, 0, "-wnowhere x"), vec!["World 'nowhere' not found."]);
+        assert!(app.worlds[0].output_lines.is_empty());
+    }
+
+    #[test]
+    fn test_gag_list_commands_and_output() {
+        let mut app = App::new();
+        app.is_master = false; // keep save_settings away from the real settings file
+        app.worlds.push(World::new("mush"));
+        app.worlds.push(World::new("moo"));
+
+        match parse_command("/gag -w -t  * has  arrived.") {
+            Command::Gag { args } => {
+                assert_eq!(commands::gag_command(&mut app, 0, &args), vec!["Added gag 1: * has  arrived. in mush (temporary)"]);
+            }
+            other => panic!("Expected Gag, got {:?}", other),
+        }
+        assert_eq!(commands::gag_command(&mut app, 0, "[OOC]*"), vec!["Added gag 2: [OOC]*"]);
+        assert!(matches!(parse_command("/gag"), Command::Gag { ref args } if args.is_empty()));
+        assert!(matches!(parse_command("/ungag 2"), Command::Ungag { .. }));
+        assert_eq!(commands::gag_command(&mut app, 0, "-wnowhere x"), vec!["World 'nowhere' not found."]);
+        assert_eq!(commands::gag_command(&mut app, 0, "list"), vec![
+            "#    World            Enabled  Pattern",
+            "1    mush             yes      * has  arrived.  (temporary)",
+            "2    (all)            yes      [OOC]*",
+        ]);
+
+        // Gagged lines stay in the buffer, hidden unless F2 shows them. A chunk's
+        // gagged lines are stored after its shown ones.
+        app.process_server_data(0, b"Bob has  arrived.\r\n[OOC] Bob: hi\r\nBob waves.\r\n", 24, 80, false);
+        app.process_server_data(1, b"Bob has  arrived.\r\n", 24, 80, false);
+        let gagged = |app: &App, w: usize| app.worlds[w].output_lines.iter().map(|l| l.gagged).collect::<Vec<_>>();
+        assert_eq!(gagged(&app, 0), vec![false, true, true]);
+        assert_eq!(app.worlds[0].output_lines[0].text, "Bob waves.");
+        assert_eq!(gagged(&app, 1), vec![false]);
+
+        // Only saved gags reach the settings file
+        let saved: Vec<String> = app.settings.gags.iter().filter(|g| !g.temporary).map(|g| g.to_dat()).collect();
+        assert_eq!(saved, vec!["|true|[OOC]*"]);
+
+        assert_eq!(commands::gag_command(&mut app, 0, "off 2"), vec!["Gag 2 disabled."]);
+        assert!(!app.settings.gags[1].enabled);
+        assert_eq!(commands::gag_command(&mut app, 0, "off 3"), vec!["No gag 3. See /gag list."]);
+        assert_eq!(commands::ungag_command(&mut app, "1"), vec!["Removed gag: * has  arrived."]);
+        assert_eq!(commands::ungag_command(&mut app, "[OOC]*"), vec!["Removed gag: [OOC]*"]);
+        assert!(app.settings.gags.is_empty());
+        assert_eq!(commands::ungag_command(&mut app, "nothing*"), vec!["No gag matches 'nothing*'. See /gag list."]);
+    }
+
+    #[test]
+    fn test_actions_export_and_import_trigger_packs() {
+        let mut app = App::new();
+        app.is_master = false; // keep save_settings away from the real settings file
+        let mut action = Action::new();
+        action.name = "heal".to_string();
+        action.world = "aardwolf".to_string();
+        action.command = "cast heal".to_string();
+        app.settings.actions.push(action);
+        let mut other = Action::new();
+        other.name = "wave".to_string();
+        other.world = "moo".to_string();
+        app.settings.actions.push(other);
+
+        assert!(matches!(parse_command("/actions export pack.toml aardwolf"),
+            Command::ActionsExport { ref args } if args == "pack.toml aardwolf"));
+        assert!(matches!(parse_command("/actions import pack.json"),
+            Command::ActionsImport { ref args } if args == "pack.json"));
+        assert!(matches!(parse_command("/actions aardwolf"), Command::Actions { world: Some(_) }));
+
+        for ext in ["toml", "json"] {
+            let path = std::env::temp_dir().join(format!("clay-actions-pack-{}.{}", std::process::id(), ext));
+            let file = path.display().to_string();
+            assert_eq!(commands::actions_export_command(&app, &format!("{} AardWolf", file)),
+                vec![format!("Exported 1 actions to {}.", file)]);
+            let text = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(text.trim_start().starts_with('{'), ext == "json");
+
+            let mut other_app = App::new();
+            other_app.is_master = false;
+            assert_eq!(commands::actions_import_command(&mut other_app, &file),
+                vec![format!("Imported 1 actions from {}.", file)]);
+            assert_eq!(other_app.settings.actions.len(), 1);
+            assert_eq!(other_app.settings.actions[0].name, "heal");
+            // Importing again replaces the same-named action
+            commands::actions_import_command(&mut other_app, &file);
+            assert_eq!(other_app.settings.actions.len(), 1);
+            let _ = std::fs::remove_file(&path);
+        }
+        assert_eq!(commands::actions_export_command(&app, "pack.json nowhere"), vec!["No actions for world 'nowhere'."]);
+    }
+
+    #[test]
+    fn test_log_command_starts_and_stops_until_disconnect() {
+        let mut app = App::new();
+        app.worlds.push(World::new("test"));
+        let path = std::env::temp_dir().join(format!("clay-log-command-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(commands::log_command(&mut app, 0, ""), vec!["Not logging test."]);
+        assert_eq!(
+            commands::log_command(&mut app, 0, &format!("start {}", path.display())),
+            vec![format!("Logging test to {}.", path.display())]
+        );
+        app.worlds[0].settings.log_timestamps = logging::LogTimestamps::Off;
+        app.worlds[0].write_log_line("hello");
+        commands::log_command(&mut app, 0, "flush");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello\n");
+
+        // TF's /log off; stopped holds while connected, the setting applies after
+        assert_eq!(commands::log_command(&mut app, 0, "off"), vec!["Stopped logging test."]);
+        app.worlds[0].settings.log_enabled = true;
+        app.worlds[0].write_log_line("not logged");
+        assert!(app.worlds[0].log_handle.is_none());
+        app.worlds[0].end_log_session();
+        assert!(app.worlds[0].logging_on());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello\n");
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[test]
     fn reset_more_mode_on_send_releases_at_bottom() {
         let mut world = World::new("test");
         for i in 0..50 {
-            world.output_lines.push(OutputLine::new(format!("line {}", i), i as u64));
+            world.output_lines.push_back(OutputLine::new(format!("line {}", i), i as u64));
         }
         // Following live at the bottom, no held output.
         world.paused = true;
@@ -1818,7 +2567,7 @@
         // Simulate what the gagged lines handler does: append gagged lines + scroll_to_bottom
         let seq = world.next_seq;
         world.next_seq += 1;
-        world.output_lines.push(OutputLine::new_gagged("gagged line".to_string(), seq));
+        world.output_lines.push_back(OutputLine::new_gagged("gagged line".to_string(), seq));
         // The fix: save/restore visual_line_offset around scroll_to_bottom
         let saved = world.visual_line_offset;
         world.scroll_to_bottom();
@@ -2037,6 +2786,83 @@
         assert!(!app.release_orphaned_pending(), "Second call should report nothing released");
     }
 
+    #[test]
+    fn test_activity_notification_once_per_unfocused_world() {
+        let mut app = App::new();
+        app.worlds.push(World::new("mume"));
+        app.worlds.push(World::new("quiet"));
+        app.console_active = true;
+        app.settings.notify_activity = true;
+        app.worlds[1].settings.notify_muted = true;
+
+        // Focused terminal: nothing
+        app.notify_activity(0, "Bob says, hi\n");
+        assert!(!app.worlds[0].activity_notified);
+
+        app.set_terminal_focus(false);
+        app.notify_activity(0, "  \n");
+        assert!(!app.worlds[0].activity_notified, "blank output is not notified");
+        app.notify_activity(0, "Bob says, hi\n");
+        app.notify_activity(1, "Muted world\n");
+        assert!(app.worlds[0].activity_notified);
+        assert!(!app.worlds[1].activity_notified, "muted worlds are skipped");
+        assert_eq!(app.send_notification(1, "page", None), "Notification muted for quiet: page");
+
+        app.set_terminal_focus(true);
+        assert!(!app.worlds[0].activity_notified, "regaining focus re-arms every world");
+    }
+
+    #[test]
+    fn test_push_subscriptions_replace_by_endpoint_and_turn_off() {
+        let mut app = App::new();
+        let p256dh = "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4";
+        let auth = "BTBZMqHH6r4Tts7J_aSIgg";
+        assert!(app.add_push_subscription("http://push.example.net/a", p256dh, auth).starts_with("Push notifications not turned on"));
+        assert!(app.settings.push_subscriptions.is_empty());
+
+        app.add_push_subscription("https://push.example.net/a", p256dh, auth);
+        app.add_push_subscription("https://push.example.net/a", p256dh, auth);
+        app.add_push_subscription("https://push.example.net/b", p256dh, auth);
+        assert_eq!(app.settings.push_subscriptions.len(), 2, "a resubscribe replaces its endpoint");
+        assert_eq!(commands::web_push_command(&mut app, ""), "2 browsers get push notifications. Use /web push clear to stop them all.");
+
+        assert_eq!(app.remove_push_subscriptions(&["https://push.example.net/a".to_string()]), 1);
+        assert_eq!(app.settings.push_subscriptions[0].endpoint, "https://push.example.net/b");
+        assert!(commands::web_push_is_local(" On ") && !commands::web_push_is_local("clear"));
+        assert_eq!(commands::web_push_command(&mut app, "clear"), "Push notifications turned off for 1 browser.");
+        assert!(app.settings.push_subscriptions.is_empty());
+    }
+
+    #[test]
+    fn test_silence_watchdog_alerts_once_per_silence() {
+        let mut app = App::new();
+        app.worlds.push(World::new("chatty"));
+        app.worlds[0].connected = true;
+        app.worlds[0].settings.silence_alert_mins = 1;
+        let alerts = |app: &App| app.worlds[0].output_lines.iter()
+            .filter(|l| l.text.contains("No output for 1 minute;")).count();
+
+        app.worlds[0].last_receive_time = Some(std::time::Instant::now());
+        app.check_silence_watchdogs();
+        assert_eq!(alerts(&app), 0, "not quiet long enough");
+
+        let Some(quiet_since) = std::time::Instant::now().checked_sub(Duration::from_secs(61)) else { return };
+        app.worlds[0].last_receive_time = Some(quiet_since);
+        app.check_silence_watchdogs();
+        app.check_silence_watchdogs();
+        assert_eq!(alerts(&app), 1, "one alert per silence");
+
+        // New output rearms it
+        app.worlds[0].last_receive_time = quiet_since.checked_sub(Duration::from_secs(1));
+        app.check_silence_watchdogs();
+        assert_eq!(alerts(&app), 2);
+
+        app.worlds[0].settings.silence_alert_mins = 0;
+        app.worlds[0].last_receive_time = quiet_since.checked_sub(Duration::from_secs(2));
+        app.check_silence_watchdogs();
+        assert_eq!(alerts(&app), 2, "0 turns the watchdog off");
+    }
+
     #[test]
     fn test_switch_to_oldest_pending_finds_vlo_only_world() {
         // Issue C: switch_to_oldest_pending's tiers only checked pending_lines/
@@ -2426,6 +3252,42 @@
         assert!(!second.starts_with("\x1b"), "Indent spaces must precede any color code: {:?}", second);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_xdg_clay_dir_falls_back_to_home() {
+        // A variable of its own, so other tests reading the real XDG variables aren't affected
+        let var = "CLAY_TEST_XDG_DIR_HOME";
+        std::env::set_var(var, "/tmp/xdg-config");
+        assert_eq!(xdg_clay_dir(var, ".config"), PathBuf::from("/tmp/xdg-config/clay"));
+        // Relative values are invalid per the spec and ignored, like an unset variable
+        std::env::set_var(var, "relative/dir");
+        assert_eq!(xdg_clay_dir(var, ".config"), PathBuf::from(get_home_dir()).join(".config/clay"));
+        std::env::remove_var(var);
+        assert_eq!(xdg_clay_dir(var, ".local/state"), PathBuf::from(get_home_dir()).join(".local/state/clay"));
+    }
+
+    #[test]
+    fn test_config_profile_names_are_plain_directory_names() {
+        for name in ["work", "test-2", "my_profile"] {
+            assert!(is_valid_profile_name(name), "{}", name);
+        }
+        for name in ["", "..", "a/b", "a b", "work.bak", &"x".repeat(65)] {
+            assert!(!is_valid_profile_name(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_config_profile_command_is_separate_from_schedules() {
+        assert!(matches!(parse_command("/config profile"), Command::Config { args } if args == "profile"));
+        let out = commands::config_command("profile");
+        assert_eq!(out[0], "Config profile: default");
+        assert_eq!(out[1], "* default");
+        assert_eq!(commands::config_command(""), vec!["Usage: /config profile".to_string()]);
+        // /profile is left to time-scheduled profiles
+        let mut app = App::new();
+        assert!(commands::profile_command(&mut app, "config")[0].starts_with("Usage: /profile"));
+    }
+
     #[test]
     fn test_wrap_ansi_line_indent_pathological_still_progresses() {
         // indent >= max_width must not stall/loop — it should clamp internally and still
@@ -3700,11 +4562,11 @@
         // These are the command strings (without /) that parse_command() matches on.
         // When adding a new command to parse_command(), add it here too.
         let mut rust_commands: Vec<String> = vec![
-            "help", "version", "quit", "reload", "update", "setup", "web", "actions",
-            "connections", "l", "worlds", "world", "disconnect", "dc", "connect", "import",
+            "help", "version", "quit", "reload", "detach", "update", "setup", "web", "actions",
+            "connections", "l", "worlds", "world", "disconnect", "dc", "connect", "import", "export", "lock", "unlock",
             "flush", "menu", "send", "remote", "ban", "unban",
             "testmusic", "dump", "notify", "addworld", "note", "tag", "tags",
-            "dict", "urban", "translate", "tr", "font", "window",
+            "tagroute", "tagroutes", "gag", "gags", "ungag", "profile", "profiles", "config", "numpad", "secret", "secrets", "loopguard", "clipboard", "throttle", "urls", "capture", "split", "timestamps", "bell", "log", "replay", "macro", "macros", "dict", "urban", "translate", "tr", "font", "window", "calc", "roll", "sendfile", "shell", "pipe", "localecho", "emoji", "autocorrect", "palette", "tint", "images", "addword", "spellignore", "unignore", "spelllang",
         ].into_iter().map(|s| s.to_string()).collect();
         rust_commands.sort();
         rust_commands.dedup();
@@ -3770,11 +4632,11 @@
         let mut world = World::new("test");
         // 2 old (is_current=true, so marked_new=false)
         for i in 0..2 {
-            world.output_lines.push(make_output_line(&format!("Old line {}", i + 1), false));
+            world.output_lines.push_back(make_output_line(&format!("Old line {}", i + 1), false));
         }
         // 20 new lines (marked_new=true)
         for i in 0..20 {
-            world.output_lines.push(make_output_line(&format!("New line {}", i + 1), true));
+            world.output_lines.push_back(make_output_line(&format!("New line {}", i + 1), true));
         }
         // scroll_offset at the end
         world.scroll_offset = world.output_lines.len() - 1;
@@ -3805,11 +4667,11 @@
         let mut world = World::new("test");
         // 2 old lines
         for i in 0..2 {
-            world.output_lines.push(make_output_line(&format!("Old {}", i + 1), false));
+            world.output_lines.push_back(make_output_line(&format!("Old {}", i + 1), false));
         }
         // 21 new lines
         for i in 0..21 {
-            world.output_lines.push(make_output_line(&format!("New {}", i + 1), true));
+            world.output_lines.push_back(make_output_line(&format!("New {}", i + 1), true));
         }
         world.scroll_offset = world.output_lines.len() - 1;
 
@@ -3840,11 +4702,11 @@
         let mut world = World::new("test");
         // 2 old lines
         for i in 0..2 {
-            world.output_lines.push(make_output_line(&format!("Old {}", i + 1), false));
+            world.output_lines.push_back(make_output_line(&format!("Old {}", i + 1), false));
         }
         // 100 new lines — far more than visible_height * 2
         for i in 0..100 {
-            world.output_lines.push(make_output_line(&format!("New {}", i + 1), true));
+            world.output_lines.push_back(make_output_line(&format!("New {}", i + 1), true));
         }
         world.scroll_offset = world.output_lines.len() - 1;
 
@@ -3870,10 +4732,10 @@
         let mut world = World::new("test");
         // 2 old lines + 20 new lines
         for i in 0..2 {
-            world.output_lines.push(make_output_line(&format!("Old {}", i + 1), false));
+            world.output_lines.push_back(make_output_line(&format!("Old {}", i + 1), false));
         }
         for i in 0..20 {
-            world.output_lines.push(make_output_line(&format!("New {}", i + 1), true));
+            world.output_lines.push_back(make_output_line(&format!("New {}", i + 1), true));
         }
         world.scroll_offset = world.output_lines.len() - 1;
 
@@ -3905,7 +4767,7 @@
     fn test_build_display_fewer_than_visible_height() {
         let mut world = World::new("test");
         for i in 0..5 {
-            world.output_lines.push(make_output_line(&format!("Line {}", i + 1), false));
+            world.output_lines.push_back(make_output_line(&format!("Line {}", i + 1), false));
         }
         world.scroll_offset = world.output_lines.len() - 1;
 
@@ -3923,9 +4785,9 @@
         let mut world = World::new("test");
         // Add a line that wraps to multiple visual lines (long text)
         let long_text = "A".repeat(200); // At width 80, wraps to 3 visual lines
-        world.output_lines.push(make_output_line(&long_text, false));
+        world.output_lines.push_back(make_output_line(&long_text, false));
         for i in 0..5 {
-            world.output_lines.push(make_output_line(&format!("Line {}", i + 1), false));
+            world.output_lines.push_back(make_output_line(&format!("Line {}", i + 1), false));
         }
         world.scroll_offset = world.output_lines.len() - 1;
         world.visual_line_offset = 0; // No truncation
@@ -3958,7 +4820,7 @@ if you're more curious.\"";
         let width = 80;
 
         let mut world = World::new("test");
-        world.output_lines.push(make_output_line(text, false));
+        world.output_lines.push_back(make_output_line(text, false));
         world.scroll_offset = 0;
 
         // wrapspace=0 — must match current (pre-feature) behavior exactly.
@@ -4104,11 +4966,11 @@ if you're more curious.\"";
 
         // Simulate: 2 old lines already in output, then 30 pending get partially released
         for i in 0..2 {
-            world.output_lines.push(make_output_line(&format!("Old {}", i + 1), false));
+            world.output_lines.push_back(make_output_line(&format!("Old {}", i + 1), false));
         }
         // Release 19 lines from pending (they become output with marked_new=true)
         for i in 0..19 {
-            world.output_lines.push(make_output_line(&format!("Pending {}", i + 1), true));
+            world.output_lines.push_back(make_output_line(&format!("Pending {}", i + 1), true));
         }
         world.scroll_offset = world.output_lines.len() - 1;
         // Still have 11 more in pending
@@ -4189,6 +5051,510 @@ if you're more curious.\"";
         }
     }
 
+    #[test]
+    fn test_connect_mud_uri_opens_world_for_address() {
+        assert!(matches!(parse_command("/connect telnet://mud.example.com:4000?tls=1"),
+            Command::OpenUri { uri } if uri.host == "mud.example.com" && uri.port == "4000" && uri.tls));
+        // host:port without a scheme is still a remote Clay attach
+        assert!(matches!(parse_command("/connect mud.example.com:4000"), Command::RemoteAttach { .. }));
+
+        let mut app = App::new();
+        app.is_master = false; // keep the test from saving settings
+        let mut existing = World::new("Example");
+        existing.settings.hostname = "MUD.example.com".to_string();
+        existing.settings.port = "4000".to_string();
+        app.worlds.push(existing);
+        let mut clash = World::new("mud.example.com");
+        clash.settings.hostname = "other.example.com".to_string();
+        app.worlds.push(clash);
+        let worlds_before = app.worlds.len();
+
+        // A world already set up for the address is reused
+        let cmd = app.resolve_open_uri(parse_command("/connect telnet://mud.example.com:4000/"));
+        assert_eq!(cmd, Command::WorldSwitch { name: "Example".to_string() });
+        assert_eq!(app.worlds.len(), worlds_before);
+
+        // A new address gets a world named after the host, made unique
+        let cmd = app.resolve_open_uri(parse_command("/connect mud://mud.example.com:5000?tls=1"));
+        assert_eq!(cmd, Command::WorldSwitch { name: "mud.example.com-2".to_string() });
+        let added = app.worlds.last().unwrap();
+        assert_eq!((added.settings.hostname.as_str(), added.settings.port.as_str(), added.settings.use_ssl), ("mud.example.com", "5000", true));
+
+        // Other commands pass through
+        assert_eq!(app.resolve_open_uri(Command::Quit), Command::Quit);
+    }
+
+    #[test]
+    fn test_parse_detach() {
+        assert_eq!(parse_command("/detach"), Command::Detach);
+        assert_eq!(parse_command("/DETACH"), Command::Detach);
+    }
+
+    #[test]
+    fn test_parse_calc_and_roll() {
+        assert_eq!(parse_command("/calc 2 * (3 + 4)"), Command::Calc { expr: "2 * (3 + 4)".to_string(), roll: false, send: false });
+        assert_eq!(parse_command("/roll -s 3d6+2"), Command::Calc { expr: "3d6+2".to_string(), roll: true, send: true });
+        assert_eq!(parse_command("/roll"), Command::Calc { expr: String::new(), roll: true, send: false });
+    }
+
+    #[test]
+    fn test_parse_sendfile() {
+        assert_eq!(parse_command("/sendfile -wmush ~/code/cmds.mush"), Command::SendFile { args: "-wmush ~/code/cmds.mush".to_string() });
+        assert_eq!(parse_command("/sendfile abort"), Command::SendFile { args: "abort".to_string() });
+
+        let mut app = App::new();
+        let mut world = World::new("mush");
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<WriteCommand>(10);
+        world.command_tx = Some(tx);
+        world.connected = true;
+        world.send_file = Some(crate::sendfile::SendFile { task: None, file: "cmds.mush".to_string(), sent: 0, total: 2 });
+        app.worlds.push(world);
+        app.send_file_line(0, "@create Widget".to_string());
+        assert_eq!(app.worlds[0].send_file.as_ref().map(|j| j.sent), Some(1));
+        app.send_file_line(0, "@lock Widget=me".to_string());
+        assert!(app.worlds[0].send_file.is_none(), "done after the last line");
+        let mut texts = Vec::new();
+        while let Ok(WriteCommand::Text(t)) = rx.try_recv() {
+            texts.push(t);
+        }
+        assert_eq!(texts, vec!["@create Widget", "@lock Widget=me"]);
+        assert!(app.worlds[0].output_lines.iter().any(|l| l.text.contains("Sent cmds.mush (2 lines) to mush.")));
+        // A line still queued after an abort is dropped
+        app.send_file_line(0, "stray".to_string());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_double_byte_character_split_across_reads() {
+        let mut app = App::new();
+        let mut world = World::new("mud");
+        world.settings.encoding = Encoding::Big5;
+        app.worlds.push(world);
+        // 你 is A7 41; a read ending after A7 holds it for the next one
+        app.process_server_data(0, b"hp \xA7", 24, 80, false);
+        app.process_server_data(0, b"\x41\xA6\x6E\n", 24, 80, false);
+        assert!(app.worlds[0].output_lines.iter().any(|l| l.text == "hp 你好"));
+    }
+
+    #[test]
+    fn test_shell_and_pipe() {
+        assert_eq!(parse_command("/shell grep -c  'x y' ~/notes"), Command::Shell { command: "grep -c  'x y' ~/notes".to_string(), pipe: false });
+        assert_eq!(parse_command("/pipe fortune"), Command::Shell { command: "fortune".to_string(), pipe: true });
+        assert_eq!(parse_command("/shell"), Command::Shell { command: String::new(), pipe: false });
+
+        let mut app = App::new();
+        let mut world = World::new("mush");
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<WriteCommand>(10);
+        world.command_tx = Some(tx);
+        app.worlds.push(world);
+        assert_eq!(commands::shell_command(&mut app, 0, "", true, None), vec!["Usage: /pipe <command>"]);
+        assert_eq!(commands::shell_command(&mut app, 0, "fortune", true, None), vec!["mush is not connected."]);
+        app.shell_output(0, crate::shell::Output::Send, "say hi".to_string());
+        assert!(rx.try_recv().is_err());
+        assert!(app.worlds[0].output_lines.iter().any(|l| l.text == "Not connected; not sent: say hi"));
+        app.worlds[0].connected = true;
+        app.shell_output(0, crate::shell::Output::Send, "say hi".to_string());
+        app.shell_output(0, crate::shell::Output::Show, "3".to_string());
+        assert!(matches!(rx.try_recv(), Ok(WriteCommand::Text(t)) if t == "say hi"));
+        assert!(rx.try_recv().is_err(), "shown lines aren't sent");
+        assert_eq!(app.worlds[0].output_lines.back().map(|l| l.text.as_str()), Some("3"));
+    }
+
+    #[test]
+    fn test_emoji_shortcodes() {
+        assert_eq!(parse_command("/emoji -wmush on"), Command::Emoji { args: "-wmush on".to_string() });
+
+        let mut app = App::new();
+        app.worlds.push(World::new("mush"));
+        assert_eq!(app.worlds[0].wrap_input("hi :smile:"), "hi :smile:", "off by default");
+        assert_eq!(commands::emoji_command(&mut app, 0, "on"), vec!["Emoji shortcodes enabled for mush."]);
+        app.worlds[0].settings.input_prefix = "say ".to_string();
+        assert_eq!(app.worlds[0].wrap_input("hi :smile: :nosuch: 10:30:00"), "say hi 😊 :nosuch: 10:30:00");
+        app.worlds[0].prompt = "Password: ".to_string();
+        assert_eq!(app.worlds[0].wrap_input(":fire:"), ":fire:", "a password goes out as typed");
+
+        let mut input = InputArea::new(3);
+        let matching = crate::encoding::emoji_shortcodes_matching;
+        for c in "nice :thumbs".chars() {
+            input.insert_char(c);
+        }
+        assert!(input.complete_emoji_shortcode(matching));
+        assert_eq!(input.buffer, "nice :thumbs_down:");
+        assert!(input.complete_emoji_shortcode(matching));
+        assert_eq!(input.buffer, "nice :thumbs_up:", "Tab again cycles");
+        assert!(input.complete_emoji_shortcode(matching));
+        assert_eq!(input.buffer, "nice :thumbsdown:");
+        input.insert_char(' ');
+        assert!(!input.complete_emoji_shortcode(matching), "nothing to complete after a space");
+        for c in ":zzzq".chars() {
+            input.insert_char(c);
+        }
+        assert!(!input.complete_emoji_shortcode(matching));
+    }
+
+    #[test]
+    fn test_addword() {
+        assert_eq!(parse_command("/addword Zanthor"), Command::AddWord { word: "Zanthor".to_string() });
+
+        let path = std::env::temp_dir().join(format!("clay-personal-dict-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut app = App::new();
+        app.spell_checker.load_personal(path.clone());
+        assert_eq!(commands::addword_command(&mut app, "Zanthor"), vec!["Added 'Zanthor' to your dictionary."]);
+        assert_eq!(commands::addword_command(&mut app, "zanthor"), vec!["'zanthor' is already in your dictionary."]);
+        assert_eq!(commands::addword_command(&mut app, "x2"), vec!["'x2' isn't a word (letters only)."]);
+        assert_eq!(commands::addword_command(&mut app, ""), vec!["Usage: /addword <word>"]);
+        assert!(app.spell_checker.is_valid("Zanthor's") || !app.spell_checker.has_dictionary());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "zanthor\n");
+
+        // Loaded again at startup
+        let mut checker = crate::SpellChecker::new("");
+        checker.load_personal(path.clone());
+        assert_eq!(checker.add_word("ZANTHOR"), Ok(false));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_autocorrect() {
+        assert_eq!(parse_command("/autocorrect -wmush on"), Command::Autocorrect { args: "-wmush on".to_string() });
+
+        let dir = std::env::temp_dir().join(format!("clay-autocorrect-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("xx_XX.dic"), "4\ni\nsaid\ntheir\nthe\n").unwrap();
+        let mut app = App::new();
+        app.settings.dictionary_path = dir.join("words").to_string_lossy().to_string();
+        app.worlds.push(World::new("mush"));
+        app.worlds[0].settings.spell_language = "xx_XX".to_string();
+        let type_text = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                app.input.insert_char(c);
+                app.check_autocorrect();
+            }
+        };
+        type_text(&mut app, "thier ");
+        assert_eq!(app.input.buffer, "thier ", "off by default");
+        assert_eq!(commands::autocorrect_command(&mut app, 0, "on"), vec!["Autocorrect enabled for mush (Esc-r puts back the word you typed)."]);
+        assert!(app.world_settings_msg(0).autocorrect);
+
+        app.input.clear();
+        type_text(&mut app, "I said thier. Thier hte xyzzy THIER ");
+        assert_eq!(app.input.buffer, "I said thier. Their the xyzzy THEIR ", "only bare words with one close correction");
+        app.undo_autocorrect();
+        assert_eq!(app.input.buffer, "I said thier. Their the xyzzy THIER ");
+        assert_eq!(app.input.cursor_position, app.input.buffer.len());
+        type_text(&mut app, "THIER ");
+        assert_eq!(app.input.buffer, "I said thier. Their the xyzzy THIER THIER ", "left alone once undone");
+
+        app.input.clear();
+        type_text(&mut app, "/addword thier ");
+        assert_eq!(app.input.buffer, "/addword thier ", "commands aren't corrected");
+        assert_eq!(commands::autocorrect_command(&mut app, 0, ""), vec!["Autocorrect for mush is on."]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_spell_popup() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let dir = std::env::temp_dir().join(format!("clay-spell-popup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("xx_XX.dic"), "3\nsays\ntheir\nthere\n").unwrap();
+        let mut app = App::new();
+        app.settings.dictionary_path = dir.join("words").to_string_lossy().to_string();
+        app.worlds.push(World::new("mush"));
+        app.worlds[0].settings.spell_language = "xx_XX".to_string();
+        for c in "says thier".chars() {
+            app.input.insert_char(c);
+        }
+
+        app.open_spell_popup();
+        let state = app.popup_manager.current().expect("popup opened for the misspelled word");
+        assert_eq!(state.definition.id, popup::PopupId("spell"));
+        assert_eq!(state.definition.title, "'thier'");
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert!(matches!(handle_new_popup_key(&mut app, key(KeyCode::Char('9'))), NewPopupAction::None), "no ninth suggestion");
+        match handle_new_popup_key(&mut app, key(KeyCode::Char('1'))) {
+            NewPopupAction::Spell(action) => {
+                assert!(matches!(&action, SpellPopupAction::Replace(word) if word == "their"));
+                app.apply_spell_popup(action);
+            }
+            _ => panic!("1 picks the first suggestion"),
+        }
+        assert!(!app.popup_manager.is_open());
+        assert_eq!(app.input.buffer, "says their");
+
+        app.open_spell_popup();
+        assert!(!app.popup_manager.is_open(), "nothing to fix");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_spell_ignore() {
+        assert_eq!(parse_command("/spellignore Zanthor"), Command::SpellIgnore { word: "Zanthor".to_string() });
+        assert_eq!(parse_command("/unignore Zanthor"), Command::Unignore { word: "Zanthor".to_string() });
+
+        let path = std::env::temp_dir().join(format!("clay-spell-ignore-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut app = App::new();
+        app.worlds.push(World::new("mud"));
+        app.spell_ignore = crate::IgnoreList::load(path.clone());
+        assert_eq!(commands::spellignore_command(&mut app, ""), vec!["No words are ignored. Usage: /spellignore <word>"]);
+
+        // Esc-i after Ctrl+Q: the typed word comes back and is ignored for the session
+        app.input.buffer = "hi Qzxv".to_string();
+        app.input.cursor_position = app.input.buffer.len();
+        app.spell_state.showing_suggestions = true;
+        app.spell_state.original_word = "Qzxv".to_string();
+        app.spell_state.word_start = 3;
+        app.spell_state.word_end = 7;
+        app.input.replace_word(3, 7, "quiz");
+        app.spell_ignore_word();
+        assert_eq!(app.input.buffer, "hi Qzxv");
+        assert!(app.spell_ignore.contains("qzxv"));
+        assert!(!path.exists(), "session ignores aren't saved");
+
+        assert_eq!(commands::spellignore_command(&mut app, "Zanthor"), vec!["Ignoring 'Zanthor' in spell check (/unignore Zanthor undoes it)."]);
+        assert_eq!(commands::spellignore_command(&mut app, "zanthor"), vec!["'zanthor' is already ignored."]);
+        assert_eq!(commands::spellignore_command(&mut app, ""), vec!["Ignored words: zanthor", "Ignored until Clay exits: qzxv"]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "zanthor\n");
+        assert!(crate::IgnoreList::load(path.clone()).contains("Zanthor"), "loaded again at startup");
+        assert!(app.spell_checker.suggestions("zanthar", 6).iter().all(|w| w != "zanthor"), "not in the dictionary");
+
+        assert_eq!(commands::unignore_command(&mut app, "Zanthor"), vec!["'Zanthor' is spell checked again."]);
+        assert_eq!(commands::unignore_command(&mut app, "qzxv"), vec!["'qzxv' is spell checked again."]);
+        assert_eq!(commands::unignore_command(&mut app, "qzxv"), vec!["'qzxv' isn't ignored."]);
+        assert_eq!(commands::unignore_command(&mut app, ""), vec!["Usage: /unignore <word>"]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_spelllang_command() {
+        assert_eq!(parse_command("/spelllang -wmush de_DE"), Command::SpellLang { args: "-wmush de_DE".to_string() });
+
+        let dir = std::env::temp_dir().join(format!("clay-spelllang-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("xx_XX.aff"), "SFX S Y 1\nSFX S en st en\n").unwrap();
+        std::fs::write(dir.join("xx_XX.dic"), "1\ngehen/S\n").unwrap();
+        let mut app = App::new();
+        app.settings.dictionary_path = dir.join("words").to_string_lossy().to_string();
+        app.worlds.push(World::new("mush"));
+        let shown = commands::spelllang_command(&mut app, 0, "");
+        assert_eq!(shown[0], "Spell checking for mush uses the global dictionary.");
+        assert!(shown[1].starts_with("Languages: ") && shown[1].contains("xx_XX"), "{}", shown[1]);
+        assert!(commands::spelllang_command(&mut app, 0, "yy_YY")[0].starts_with("No Hunspell dictionary for yy_YY."));
+        assert_eq!(commands::spelllang_command(&mut app, 0, "xx_XX"), vec!["Spell checking for mush uses xx_XX."]);
+        assert_eq!(app.world_settings_msg(0).spell_language, "xx_XX");
+        assert!(app.world_spell_checker().is_valid("gehst"));
+        assert!(!app.world_spell_checker().is_valid("gehts"));
+        assert_eq!(commands::spelllang_command(&mut app, 0, "off"), vec!["Spell checking for mush uses the global dictionary."]);
+        assert!(std::ptr::eq(app.world_spell_checker(), &app.spell_checker));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_palette_command() {
+        assert_eq!(parse_command("/palette -wmush gruvbox"), Command::Palette { args: "-wmush gruvbox".to_string() });
+
+        let mut app = App::new();
+        app.worlds.push(World::new("mush"));
+        assert_eq!(app.world_palette(0), None, "off by default");
+        assert_eq!(commands::palette_command(&mut app, 0, ""), vec![
+            "Palette for mush is off.",
+            "Palettes: gruvbox, solarized (add more as [palette:name] in theme.dat)",
+        ]);
+        assert_eq!(commands::palette_command(&mut app, 0, "nosuch"), vec!["No palette named nosuch. Palettes: gruvbox, solarized"]);
+        assert_eq!(commands::palette_command(&mut app, 0, "Solarized"), vec!["Palette solarized for mush."]);
+        assert_eq!(app.world_palette(0).as_ref(), app.theme_file.palette("solarized"));
+        assert_eq!(app.world_settings_msg(0).palette, "solarized");
+        assert_eq!(commands::palette_command(&mut app, 0, "off"), vec!["Palette off for mush."]);
+        assert_eq!(app.world_palette(0), None);
+    }
+
+    #[test]
+    fn test_tint_command() {
+        assert_eq!(parse_command("/tint -wmush #1A1020"), Command::Tint { args: "-wmush #1A1020".to_string() });
+
+        let mut app = App::new();
+        app.worlds.push(World::new("mush"));
+        assert_eq!(app.world_tint(0), None, "off by default");
+        assert_eq!(commands::tint_command(&mut app, 0, ""), vec!["Tint for mush is off."]);
+        assert_eq!(commands::tint_command(&mut app, 0, "blue"), vec!["Usage: /tint [-w[<world>]] [#rrggbb|off]"]);
+        assert_eq!(commands::tint_command(&mut app, 0, "#1A1020"), vec!["Tint #1a1020 for mush."]);
+        assert_eq!(app.world_tint(0), Some(theme::ThemeColor::new(0x1a, 0x10, 0x20)));
+        assert_eq!(app.world_settings_msg(0).tint, "#1a1020");
+        assert_eq!(commands::tint_command(&mut app, 0, "off"), vec!["Tint off for mush."]);
+        assert_eq!(app.world_tint(0), None);
+    }
+
+    #[test]
+    fn test_images_command() {
+        assert_eq!(parse_command("/images -wmush on"), Command::Images { args: "-wmush on".to_string() });
+
+        let mut app = App::new();
+        app.worlds.push(World::new("mush"));
+        assert_eq!(commands::images_command(&mut app, 0, ""), vec!["Inline images for mush are off."]);
+        assert_eq!(commands::images_command(&mut app, 0, "maybe"), vec!["Usage: /images [-w[<world>]] [on|off]"]);
+        commands::images_command(&mut app, 0, "on");
+        assert!(app.worlds[0].settings.inline_images);
+        assert!(app.world_settings_msg(0).inline_images);
+        assert_eq!(commands::images_command(&mut app, 0, "off"), vec!["Inline images off for mush."]);
+        assert!(!app.worlds[0].settings.inline_images);
+    }
+
+    #[test]
+    fn test_notify_sound_command() {
+        let mut app = App::new();
+        app.worlds.push(World::new("mush"));
+        app.worlds.push(World::new("moo"));
+        assert_eq!(commands::notify_command(&mut app, 0, "-w sound"), vec!["mush has no notification sound."]);
+        assert_eq!(commands::notify_command(&mut app, 0, "-wmoo sound ~/sounds/page.wav"),
+            vec!["moo notification sound set to ~/sounds/page.wav (played where the window is unfocused)."]);
+        assert_eq!(app.worlds[1].settings.notify_sound, "~/sounds/page.wav");
+        assert_eq!(app.world_settings_msg(1).notify_sound, "~/sounds/page.wav");
+        assert_eq!(commands::notify_command(&mut app, 1, "-w"), vec!["Notifications from moo are on, with sound ~/sounds/page.wav."]);
+        // Mute still works alongside it, and a stray word after mute is a usage error
+        assert_eq!(commands::notify_command(&mut app, 1, "-w mute"), vec!["Notifications from moo muted."]);
+        assert!(commands::notify_command(&mut app, 1, "-w mute now")[0].starts_with("Usage:"));
+        assert_eq!(commands::notify_command(&mut app, 1, "-w sound off"), vec!["moo has no notification sound now."]);
+        assert!(app.worlds[1].settings.notify_sound.is_empty());
+        assert!(commands::notify_command(&mut app, 0, "-s~/ding.wav")[0].starts_with("Usage:"), "-s needs a message");
+    }
+
+    #[test]
+    fn test_local_echo() {
+        assert_eq!(parse_command("/localecho -wmush on"), Command::LocalEcho { args: "-wmush on".to_string() });
+
+        let mut app = App::new();
+        let mut world = World::new("mush");
+        world.settings.password = "hunter2".to_string();
+        app.worlds.push(world);
+        app.echo_sent(0, "look");
+        assert!(app.worlds[0].output_lines.is_empty(), "off by default");
+        app.worlds[0].settings.local_echo = true;
+        app.echo_sent(0, "look");
+        assert_eq!(app.worlds[0].output_lines.back().map(|l| l.text.as_str()), Some("\x1b[3;90mlook\x1b[0m"));
+        app.echo_sent(0, "hunter2");
+        assert_eq!(app.worlds[0].output_lines.len(), 1, "the world's password");
+        app.worlds[0].prompt = "Password: ".to_string();
+        app.echo_sent(0, "s3cret");
+        assert_eq!(app.worlds[0].output_lines.len(), 1, "typed at a password prompt");
+    }
+
+    #[test]
+    fn test_input_prefix_and_suffix() {
+        let mut world = World::new("chat");
+        assert_eq!(world.wrap_input("hello"), "hello");
+        world.settings.input_prefix = "say ".to_string();
+        world.settings.input_suffix = ";look".to_string();
+        assert_eq!(world.wrap_input("hello"), "say hello;look");
+        assert_eq!(world.wrap_input(""), "", "an empty line goes out as is");
+        world.prompt = "Password: ".to_string();
+        assert_eq!(world.wrap_input("hunter2"), "hunter2", "nor is a password wrapped");
+    }
+
+    #[test]
+    fn test_misspelled_input_underlined() {
+        let dir = std::env::temp_dir().join(format!("clay-underline-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("xx_XX.dic"), "2\nhello\nthere\n").unwrap();
+        let mut app = App::new();
+        app.settings.dictionary_path = dir.join("words").to_string_lossy().to_string();
+        app.worlds.push(World::new("mud"));
+        app.worlds[0].settings.spell_language = "xx_XX".to_string();
+        for c in "hello thier there".chars() {
+            app.input.insert_char(c);
+        }
+        let text = crate::rendering::render_input(&mut app, 80, "");
+        let underlined: Vec<String> = text.lines.iter().flat_map(|l| l.spans.iter())
+            .filter(|s| s.style.add_modifier.contains(ratatui::style::Modifier::UNDERLINED))
+            .map(|s| s.content.to_string())
+            .collect();
+        assert_eq!(underlined, vec!["thier"]);
+        let span = text.lines[0].spans.iter().find(|s| s.content == "thier").unwrap();
+        assert_eq!(span.style.underline_color, Some(app.settings.theme.fg_error()));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_server_echo_masks_input() {
+        let mut app = App::new();
+        app.worlds.push(World::new("mud"));
+        app.worlds[0].settings.local_echo = true;
+        app.set_server_echo(0, true);
+        assert!(app.input_masked());
+        for c in "hunter2".chars() {
+            app.input.insert_char(c);
+        }
+        let text = crate::rendering::render_input(&mut app, 80, "");
+        let shown: String = text.lines.iter().flat_map(|l| l.spans.iter().map(|s| s.content.to_string())).collect();
+        assert_eq!(shown, "*******");
+        let typed = app.input.take_secret();
+        assert_eq!(typed, "hunter2");
+        assert!(app.input.history.is_empty() && app.input.kill_ring.is_empty());
+        app.echo_sent(0, &typed);
+        assert!(app.worlds[0].output_lines.is_empty(), "not echoed or logged");
+        app.set_server_echo(0, false);
+        assert!(!app.input_masked());
+    }
+
+    #[test]
+    fn test_quit_confirms_while_connected() {
+        let mut app = App::new();
+        app.worlds.push(World::new("Alpha"));
+        assert!(!app.quit_needs_confirm(), "nothing connected");
+        app.worlds[0].connected = true;
+        assert!(app.quit_needs_confirm());
+        app.open_quit_confirm();
+        assert!(app.popup_manager.current().is_some_and(|s| s.definition.custom_data.contains_key(popup::definitions::confirm::QUIT_CONFIRM)));
+        app.quit_confirmed = true;
+        assert!(!app.quit_needs_confirm(), "answered Yes");
+        app.quit_confirmed = false;
+        app.settings.confirm_quit = false;
+        assert!(!app.quit_needs_confirm(), "turned off in /setup");
+        app.settings.confirm_quit = true;
+        app.is_master = false;
+        assert!(!app.quit_needs_confirm(), "a remote client's worlds stay up");
+    }
+
+    #[test]
+    fn test_bulk_connect_and_disconnect() {
+        assert_eq!(parse_command("/connect -a"), Command::ConnectAll);
+        assert_eq!(parse_command("/disconnect -a"), Command::DisconnectAll);
+        assert_eq!(parse_command("/dc -a"), Command::DisconnectAll);
+        assert_eq!(parse_command("/dc"), Command::Disconnect);
+
+        let mut app = App::new();
+        app.is_master = false;
+        for (name, host, login) in [
+            ("one", "one.example.com", AutoConnectType::Connect),
+            ("two", "two.example.com", AutoConnectType::Diku),
+            ("manual", "three.example.com", AutoConnectType::NoLogin),
+            ("unset", "", AutoConnectType::Connect),
+        ] {
+            let mut world = World::new(name);
+            world.settings.hostname = host.to_string();
+            world.settings.port = "23".to_string();
+            world.settings.auto_connect_type = login;
+            app.worlds.push(world);
+        }
+        assert_eq!(app.auto_connect_worlds(), vec![0, 1]);
+
+        // The summary waits for the last background connect
+        app.bulk_connect = Some(BulkConnect { pending: vec!["one".into(), "two".into()], ..Default::default() });
+        app.note_bulk_connect("one", true);
+        app.note_bulk_connect("manual", false);
+        assert_eq!(app.bulk_connect.as_ref().map(|b| b.pending.len()), Some(1));
+        app.note_bulk_connect("two", false);
+        assert!(app.bulk_connect.is_none());
+        assert!(app.current_world().output_lines.iter().any(|l| l.text.contains("Connected 1 world; 1 failed: two.")));
+
+        app.worlds[0].connected = true;
+        app.worlds[2].connected = true;
+        assert_eq!(app.disconnect_all_worlds(), 2);
+        assert!(app.worlds.iter().all(|w| !w.connected));
+    }
+
     /// Serializes tests that toggle the process-wide LOCAL_SERVER_LOOPBACK_ONLY static so
     /// they can't race each other's set/restore when cargo test runs them concurrently.
     static LOOPBACK_ONLY_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
@@ -4264,7 +5630,7 @@ if you're more curious.\"";
         for i in 0..world_count {
             let mut world = World::new(&format!("world{i}"));
             for line in 0..300 {
-                world.output_lines.push(OutputLine::new(format!("line {line}"), line as u64));
+                world.output_lines.push_back(OutputLine::new(format!("line {line}"), line as u64));
             }
             app.worlds.push(world);
         }
@@ -4297,6 +5663,106 @@ if you're more curious.\"";
             "a world past the aggregate budget should get zero lines in InitialState, not a per-world floor");
     }
 
+    /// A reconnecting client that names the newest seq it holds gets only the lines
+    /// after it, or none when there are more than the per-world cap (it gap-fills those)
+    #[test]
+    fn test_resumed_initial_state_sends_only_newer_lines() {
+        let mut app = App::new();
+        for name in ["kept", "away", "fresh"] {
+            let mut world = World::new(name);
+            for i in 0..300 {
+                world.output_lines.push_back(OutputLine::new(format!("line {}", i), i as u64));
+            }
+            app.worlds.push(world);
+        }
+        let per_world_cap = app.settings.remote_initial_lines.max(1) as usize;
+        let resume_seqs = std::collections::HashMap::from([
+            ("kept".to_string(), 296),
+            ("away".to_string(), 10),
+        ]);
+
+        let WsMessage::InitialState { worlds, .. } = app.build_resumed_initial_state(&resume_seqs) else {
+            panic!("build_resumed_initial_state() must return WsMessage::InitialState");
+        };
+        let seqs: Vec<u64> = worlds[0].output_lines_ts.iter().map(|l| l.seq).collect();
+        assert_eq!(seqs, vec![297, 298, 299]);
+        assert!(worlds[0].resumed);
+        assert!(worlds[1].output_lines_ts.is_empty(), "289 newer lines is more than the {} cap", per_world_cap);
+        assert!(worlds[1].resumed);
+        assert_eq!(worlds[2].output_lines_ts.len(), per_world_cap, "worlds the client doesn't hold are sent as usual");
+        assert!(!worlds[2].resumed);
+    }
+
+    /// /web tokens: each device token is another accepted key, named uniquely, and
+    /// revoking one leaves the shared key and the other tokens working
+    #[test]
+    fn test_device_tokens_add_and_revoke() {
+        use crate::commands::DeviceTokenRequest;
+
+        assert_eq!(parse_command("/web tokens add my phone"), Command::WebTokens { args: "add my phone".to_string() });
+        assert_eq!(parse_command("/web"), Command::Web);
+        assert_eq!(DeviceTokenRequest::parse(""), Ok(DeviceTokenRequest::List));
+        assert_eq!(DeviceTokenRequest::parse("ADD  my phone "), Ok(DeviceTokenRequest::Create("my phone".to_string())));
+        assert_eq!(DeviceTokenRequest::parse("revoke tablet"), Ok(DeviceTokenRequest::Revoke("tablet".to_string())));
+        assert!(DeviceTokenRequest::parse("add").is_err());
+        assert!(DeviceTokenRequest::parse("list tablet").is_err());
+
+        let mut app = App::new();
+        app.is_master = false; // keep the test from saving settings
+        app.settings.websocket_auth_key = Some(crate::AuthKey::new("shared".to_string()));
+        let phone = app.create_device_token("phone").unwrap();
+        let tablet = app.create_device_token(" tablet ").unwrap();
+        assert_ne!(phone, tablet);
+        assert!(app.create_device_token("Phone").is_err(), "names are unique regardless of case");
+        assert!(app.create_device_token("  ").is_err());
+        assert_eq!(*app.ws_auth_keys_shared.read().unwrap(),
+            vec!["shared".to_string(), hash_password("shared"), hash_password(&phone), hash_password(&tablet)]);
+
+        let WsMessage::DeviceTokens { tokens, new_token, message } = app.device_tokens_reply(DeviceTokenRequest::Create("laptop".to_string())) else {
+            panic!("device_tokens_reply() must answer with DeviceTokens");
+        };
+        assert_eq!(tokens.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), vec!["phone", "tablet", "laptop"]);
+        assert_eq!(new_token.map(|t| t.name), Some("laptop".to_string()));
+        assert_eq!(message, None);
+
+        app.ws_client_tokens.insert(7, "phone".to_string());
+        let WsMessage::DeviceTokens { tokens, new_token, message } = app.device_tokens_reply(DeviceTokenRequest::Revoke("PHONE".to_string())) else {
+            panic!("device_tokens_reply() must answer with DeviceTokens");
+        };
+        assert_eq!(tokens.len(), 2);
+        assert!(new_token.is_none());
+        assert_eq!(message.as_deref(), Some("Revoked the device token 'phone'."));
+        assert!(!app.ws_client_tokens.contains_key(&7), "its signed-in client is forgotten");
+        let keys = app.ws_auth_keys_shared.read().unwrap().clone();
+        assert!(!keys.contains(&hash_password(&phone)) && keys.contains(&hash_password(&tablet)) && keys.contains(&"shared".to_string()));
+        assert_eq!(app.revoke_device_token("phone"), None);
+    }
+
+    /// Only a device token's SHA-256 is kept, so clients answer the challenge with
+    /// SHA256(SHA256(key) + challenge); the shared key also takes the older form
+    #[test]
+    fn test_device_token_sign_in_uses_its_hash() {
+        let mut app = App::new();
+        app.is_master = false; // keep the test from saving settings
+        app.settings.websocket_auth_key = Some(crate::AuthKey::new("shared".to_string()));
+        let token = app.create_device_token("phone").unwrap();
+        assert_eq!(app.settings.device_tokens[0].key_hash, hash_password(&token));
+
+        let sign_in = |app: &mut App, client_id: u64, auth_key: String, challenge_response: bool| {
+            let msg = WsMessage::AuthRequest { password_hash: String::new(), username: None, current_world: None, auth_key: Some(auth_key), request_key: false, challenge_response, resume_seqs: Default::default() };
+            app.handle_ws_auth_key_validation(client_id, msg, "127.0.0.1", "challenge");
+            app.ws_client_worlds.contains_key(&client_id)
+        };
+        assert!(sign_in(&mut app, 1, hash_with_challenge(&hash_password(&token), "challenge"), true));
+        assert_eq!(app.ws_client_tokens.get(&1).map(String::as_str), Some("phone"));
+        assert!(!sign_in(&mut app, 2, hash_with_challenge(&token, "challenge"), true), "the token itself isn't kept");
+        assert!(sign_in(&mut app, 3, token.clone(), false));
+        assert!(!sign_in(&mut app, 4, hash_password(&token), false), "the hash itself is no key");
+        assert!(sign_in(&mut app, 5, hash_with_challenge(&hash_password("shared"), "challenge"), true));
+        assert!(sign_in(&mut app, 6, hash_with_challenge("shared", "challenge"), true));
+        assert!(!app.ws_client_tokens.contains_key(&5) && !app.ws_client_tokens.contains_key(&6));
+    }
+
     /// Regression test for the follow-on bug where a budget-starved world (real history
     /// server-side, but zero lines locally after InitialState - see the aggregate-budget
     /// test above) was silently dropped from the auto-backfill queue instead of being
@@ -4312,7 +5778,7 @@ if you're more curious.\"";
         // case, unaffected by the bug/fix - included here to confirm it still works).
         let mut world0 = World::new("world0");
         for line in 0..10 {
-            world0.output_lines.push(OutputLine::new(format!("line {line}"), line as u64));
+            world0.output_lines.push_back(OutputLine::new(format!("line {line}"), line as u64));
         }
         app.worlds.push(world0);
 
@@ -4388,7 +5854,7 @@ if you're more curious.\"";
             let lines: Vec<OutputLine> = (0..count as u64)
                 .map(|i| OutputLine::new(format!("line {i}"), before_seq.unwrap_or(1000).wrapping_sub(i + 1)))
                 .collect();
-            let mut combined = lines;
+            let mut combined: std::collections::VecDeque<OutputLine> = lines.into();
             combined.append(&mut app.worlds[world_idx].output_lines);
             app.worlds[world_idx].output_lines = combined;
         }
@@ -4425,7 +5891,7 @@ if you're more curious.\"";
             let lines: Vec<OutputLine> = (0..give as u64)
                 .map(|i| OutputLine::new(format!("line {i}"), before_seq.unwrap_or(1000).wrapping_sub(i + 1)))
                 .collect();
-            let mut combined = lines;
+            let mut combined: std::collections::VecDeque<OutputLine> = lines.into();
             combined.append(&mut app.worlds[world_idx].output_lines);
             app.worlds[world_idx].output_lines = combined;
 
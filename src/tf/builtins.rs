@@ -552,12 +552,48 @@ fn looks_like_time(s: &str) -> bool {
     s.contains(':') && s.chars().all(|c| c.is_ascii_digit() || c == ':' || c == '.')
 }
 
+/// Parse a duration with unit suffixes ("90s", "10m", "2h", "1d", "1h30m") into seconds
+fn parse_duration_units(s: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut num = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() || c == '.' {
+            num.push(c);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1.0,
+            'm' => 60.0,
+            'h' => 3600.0,
+            'd' => 86400.0,
+            _ => return None,
+        };
+        total += num.parse::<f64>().ok()? * unit;
+        num.clear();
+    }
+    (num.is_empty() && total > 0.0).then_some(total)
+}
+
+/// Split the next whitespace-separated token (a "..." span counts as one) off `s`
+fn next_token(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start();
+    if s.is_empty() {
+        return None;
+    }
+    let end = if let Some(quoted) = s.strip_prefix('"') {
+        quoted.find('"').map(|i| i + 2).unwrap_or(s.len())
+    } else {
+        s.find(char::is_whitespace).unwrap_or(s.len())
+    };
+    Some((&s[..end], &s[end..]))
+}
+
 pub fn cmd_recall(args: &str) -> TfCommandResult {
     let args = args.trim();
 
     if args.is_empty() {
         return TfCommandResult::Success(Some(
-            "Usage: /recall [-wworld] [-ligv] [-t[format]] [-aattrs] [-mstyle] [-An] [-Bn] [-Cn] [#]range [pattern]".to_string()
+            "Usage: /recall [-w[ ]world] [-ligv] [-t[format]] [-t since] [-aattrs] [-mstyle] [-A[ ]n] [-B[ ]n] [-C[ ]n] [#]range [pattern]".to_string()
         ));
     }
 
@@ -623,6 +659,17 @@ pub fn cmd_recall(args: &str) -> TfCommandResult {
                     } else {
                         opts.source = RecallSource::CurrentWorld;
                         i += 1;
+                        // "-w world pattern": a separate name, as long as it can't be
+                        // the range and something is left to search for
+                        if i == opt_chars.len() {
+                            if let Some((world, rest)) = next_token(remaining) {
+                                let range_like = world.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '/' || c == '#');
+                                if !range_like && !rest.trim().is_empty() {
+                                    opts.source = RecallSource::World(strip_quotes(world));
+                                    remaining = rest;
+                                }
+                            }
+                        }
                     }
                 }
                 'l' => {
@@ -654,6 +701,15 @@ pub fn cmd_recall(args: &str) -> TfCommandResult {
                         i = opt_chars.len();
                     } else {
                         i += 1;
+                        // "-t 10m": only lines from the last ten minutes
+                        if i == opt_chars.len() {
+                            if let Some((since, rest)) = next_token(remaining) {
+                                if let Some(secs) = parse_duration_units(since) {
+                                    opts.range = RecallRange::TimePeriod(secs);
+                                    remaining = rest;
+                                }
+                            }
+                        }
                     }
                 }
                 'a' => {
@@ -681,35 +737,27 @@ pub fn cmd_recall(args: &str) -> TfCommandResult {
                         i += 1;
                     }
                 }
-                'A' => {
-                    // -An context after
-                    let num: String = opt_chars[i+1..].iter().take_while(|c| c.is_ascii_digit()).collect();
-                    if let Ok(n) = num.parse::<usize>() {
-                        opts.context_after = n;
-                        i += 1 + num.len();
-                    } else {
-                        i += 1;
-                    }
-                }
-                'B' => {
-                    // -Bn context before
-                    let num: String = opt_chars[i+1..].iter().take_while(|c| c.is_ascii_digit()).collect();
-                    if let Ok(n) = num.parse::<usize>() {
-                        opts.context_before = n;
-                        i += 1 + num.len();
-                    } else {
-                        i += 1;
+                c @ ('A' | 'B' | 'C') => {
+                    // -An / -Bn / -Cn context after, before or both; "-C 2" also
+                    // works when a pattern follows the count
+                    let attached: String = opt_chars[i+1..].iter().take_while(|c| c.is_ascii_digit()).collect();
+                    i += 1 + attached.len();
+                    let mut count = attached.parse::<usize>().ok();
+                    if count.is_none() && i == opt_chars.len() {
+                        if let Some((num, rest)) = next_token(remaining) {
+                            if num.chars().all(|c| c.is_ascii_digit()) && !rest.trim().is_empty() {
+                                count = num.parse().ok();
+                                remaining = rest;
+                            }
+                        }
                     }
-                }
-                'C' => {
-                    // -Cn context both
-                    let num: String = opt_chars[i+1..].iter().take_while(|c| c.is_ascii_digit()).collect();
-                    if let Ok(n) = num.parse::<usize>() {
-                        opts.context_before = n;
-                        opts.context_after = n;
-                        i += 1 + num.len();
-                    } else {
-                        i += 1;
+                    if let Some(n) = count {
+                        if c != 'B' {
+                            opts.context_after = n;
+                        }
+                        if c != 'A' {
+                            opts.context_before = n;
+                        }
                     }
                 }
                 'D' => {
@@ -1903,27 +1951,18 @@ pub fn cmd_histsize(engine: &mut TfEngine, args: &str) -> TfCommandResult {
     }
 }
 
-/// /localecho [on|off] - Toggle local echo mode
+/// /localecho [-wworld] [on|off] - Clay's per-world local echo. The `localecho`
+/// variable follows the last on/off given here.
 pub fn cmd_localecho(engine: &mut TfEngine, args: &str) -> TfCommandResult {
-    let arg = args.trim().to_lowercase();
-
-    match arg.as_str() {
-        "" => {
-            let val = engine.get_var("localecho")
-                .map(|v| v.to_string_value())
-                .unwrap_or_else(|| "off".to_string());
-            TfCommandResult::Success(Some(format!("localecho={}", val)))
-        }
-        "on" | "1" => {
-            engine.set_global("localecho", super::TfValue::Integer(1));
-            TfCommandResult::Success(Some("localecho=on".to_string()))
-        }
-        "off" | "0" => {
-            engine.set_global("localecho", super::TfValue::Integer(0));
-            TfCommandResult::Success(Some("localecho=off".to_string()))
-        }
-        _ => TfCommandResult::Error("Usage: /localecho [on|off]".to_string()),
+    let words: Vec<String> = args.split_whitespace()
+        .map(|w| match w { "1" => "on", "0" => "off", w => w }.to_lowercase())
+        .collect();
+    match words.last().map(String::as_str) {
+        Some("on") => engine.set_global("localecho", super::TfValue::Integer(1)),
+        Some("off") => engine.set_global("localecho", super::TfValue::Integer(0)),
+        _ => {}
     }
+    TfCommandResult::ClayCommand(format!("/localecho {}", words.join(" ")).trim_end().to_string())
 }
 
 /// /sub [off|on|full] - Set substitution mode
@@ -2380,6 +2419,28 @@ mod tests {
         assert_eq!(opts.range, RecallRange::LastMatching(3));
     }
 
+    #[test]
+    fn test_recall_separate_world_since_and_context() {
+        let opts = recall_opts("-w Alpha -t 1h30m -C 2 *dragon*");
+        assert_eq!(opts.source, RecallSource::World("Alpha".to_string()));
+        assert!(opts.show_timestamps);
+        assert_eq!(opts.range, RecallRange::TimePeriod(5400.0));
+        assert_eq!((opts.context_before, opts.context_after), (2, 2));
+        assert_eq!(opts.pattern.as_deref(), Some("*dragon*"));
+        // A world name needs something after it; a range or lone pattern isn't one
+        let opts = recall_opts("-w dragon");
+        assert_eq!(opts.source, RecallSource::CurrentWorld);
+        assert_eq!(opts.pattern.as_deref(), Some("dragon"));
+        let opts = recall_opts("-w 20 dragon");
+        assert_eq!(opts.source, RecallSource::CurrentWorld);
+        assert_eq!(opts.range, RecallRange::Last(20));
+        let opts = recall_opts(r#"-w "Big World" -A 3 orc"#);
+        assert_eq!(opts.source, RecallSource::World("Big World".to_string()));
+        assert_eq!((opts.context_before, opts.context_after), (0, 3));
+        assert_eq!(parse_duration_units("10x"), None);
+        assert_eq!(parse_duration_units("10"), None);
+    }
+
     #[test]
     fn test_recall_no_args_returns_usage() {
         match cmd_recall("") {
@@ -204,6 +204,18 @@ pub fn bind_key(engine: &mut TfEngine, key: &str, command: String) -> Result<(),
     Ok(())
 }
 
+/// Describe what binding `key` would replace, if anything: an existing /bind
+/// command or a built-in action from keybindings.dat. Used by /bind to warn
+/// before the new binding silently takes precedence.
+pub fn binding_conflict(engine: &TfEngine, key: &str) -> Option<String> {
+    let normalized = parse_key_name(key).ok()?;
+    if let Some(cmd) = engine.keybindings.get(&normalized) {
+        return Some(format!("{} was bound to: {}", normalized, cmd));
+    }
+    engine.action_bindings.get(&normalized)
+        .map(|action| format!("{} overrides built-in action: {}", normalized, action))
+}
+
 /// Remove a keybinding
 pub fn unbind_key(engine: &mut TfEngine, key: &str) -> Result<bool, String> {
     let normalized = parse_key_name(key)?;
@@ -312,6 +324,20 @@ mod tests {
         assert_eq!(get_binding(&engine, "F1"), None);
     }
 
+    #[test]
+    fn test_binding_conflict() {
+        let mut engine = TfEngine::new();
+        engine.action_bindings.insert("PageUp".to_string(), "scroll_page_up".to_string());
+
+        assert_eq!(binding_conflict(&engine, "F5"), None);
+        assert_eq!(binding_conflict(&engine, "pgup"),
+            Some("PageUp overrides built-in action: scroll_page_up".to_string()));
+
+        bind_key(&mut engine, "F5", "cast heal".to_string()).unwrap();
+        assert_eq!(binding_conflict(&engine, "f5"),
+            Some("F5 was bound to: cast heal".to_string()));
+    }
+
     #[test]
     fn test_register_hook() {
         let mut engine = TfEngine::new();
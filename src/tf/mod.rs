@@ -342,6 +342,9 @@ pub struct TfEngine {
     pub hooks: HashMap<TfHookEvent, Vec<String>>,  // event -> macro names
     /// Key bindings (key sequence -> macro name or command)
     pub keybindings: HashMap<String, String>,
+    /// Built-in action bindings keyed by TF key name (synced from App.keybindings),
+    /// used by /bind to report which built-in action a new binding shadows
+    pub action_bindings: HashMap<String, String>,
     /// Current working directory for /lcd
     pub current_dir: Option<String>,
     /// Current control flow state (for multi-line if/while/for)
@@ -1172,7 +1172,7 @@ Unlike /expr, /test does not display the result automatically.
 The result is stored in %? for later use."#.to_string()
             )),
             "bind" => TfCommandResult::Success(Some(
-                "/bind key = command\n\nBind a key to execute a command.\nKey names: F1-F12, ^A-^Z (Ctrl), @a-@z (Alt), PgUp, PgDn, Home, End, Insert, Delete\nExample: /bind F5 = cast heal".to_string()
+                "/bind key = command\n/bind key command\n\nBind a key to execute a command.\nKey names: F1-F12, ^A-^Z (Ctrl), @a-@z (Alt), PgUp, PgDn, Home, End, Insert, Delete\nWarns when the key was already bound or shadows a built-in action.\nExample: /bind F5 = cast heal".to_string()
             )),
             "hook" | "hooks" => TfCommandResult::Success(Some(
                 "Hooks fire macros on events. Use /def -h<event> to register.\n\nEvents:\n  CONNECT     - When connected to MUD\n  DISCONNECT  - When disconnected\n  LOGIN       - After login\n  PROMPT      - On prompt received\n  SEND        - Before sending command\n\nExample: /def -hCONNECT auto_look = look".to_string()
@@ -1536,7 +1536,7 @@ Customize with /keybinds or /bind:
                 "/histsize [-i] [size]\n\nGet or set the history buffer size.\n-i: Input history (default)\n\nExample: /histsize 500".to_string()
             )),
             "localecho" => TfCommandResult::Success(Some(
-                "/localecho [on|off]\n\nGet or set local echo mode.\nWhen on, typed commands are displayed locally.".to_string()
+                "/localecho [-wworld] [on|off]\n\nGet or set local echo for a world.\nWhen on, lines you send are shown in its output, dim italic.".to_string()
             )),
             "sub" => TfCommandResult::Success(Some(
                 "/sub [off|on|full]\n\nGet or set the substitution mode.\n  off  - No variable substitution\n  on   - Normal substitution (default)\n  full - Full substitution".to_string()
@@ -1617,18 +1617,20 @@ Examples:
 Search output history.
 
 Options:
-  -w[world]   Search specific world (default: current)
+  -w[world]   Search specific world (default: current); -w name also works
   -l          Search local (TF) output only
   -g          Search all worlds + local
   -i          Search input history
-  -D          Search long-term archive (~/.clay/scrollback.db)
+  -D          Search long-term archive (~/.config/clay/scrollback.db)
   -t[format]  Show timestamps
+  -t since    Show timestamps, lines from the last since (90s, 10m, 2h, 1d)
   -v          Invert match (show non-matching)
   -q          Quiet (set %? but don't display)
   -mtype      Match type: simple, glob (default), regexp
   -ag         Include gagged lines
   -An         Show n lines after each match
   -Bn         Show n lines before each match
+  -Cn         Show n lines before and after each match (-C n also works)
   #           Show line numbers
 
 Range: N (last N), -N (Nth previous), N-M, N-
@@ -1637,6 +1639,7 @@ Examples:
   /recall 20                       - Last 20 lines
   /recall -i /def                  - Input history matching /def
   /recall -mregexp \d{3}-\d{4}     - Regex match
+  /recall -w Alpha -t 1h -C 2 orc  - Last hour of Alpha with orc, 2 lines of context
   /recall -D dragon                - Search archive for "dragon"
   /recall -D -wmud.example.com *   - All archived lines for a world"#.to_string()
             )),
@@ -2182,6 +2185,7 @@ fn cmd_unhook(engine: &mut TfEngine, args: &str) -> TfCommandResult {
 }
 
 /// /bind [key [= command]] - Register or list keybindings
+/// Also accepts `/bind key command` (no `=`) when a command follows the key.
 fn cmd_bind(engine: &mut TfEngine, args: &str) -> TfCommandResult {
     let args = args.trim();
 
@@ -2190,13 +2194,22 @@ fn cmd_bind(engine: &mut TfEngine, args: &str) -> TfCommandResult {
         return TfCommandResult::Success(Some(hooks::list_bindings(engine)));
     }
 
-    // Parse key = command
-    if let Some(eq_pos) = args.find('=') {
-        let key = args[..eq_pos].trim();
-        let command = args[eq_pos + 1..].trim();
+    // Parse key command when a key leads and no `=` follows it (the command may
+    // contain `=` itself), otherwise key = command
+    let key_first = args.split_once(char::is_whitespace).filter(|(key, command)| {
+        !key.contains('=') && !command.trim_start().starts_with('=') && hooks::parse_key_name(key).is_ok()
+    });
+    let binding = match (key_first, args.split_once('=')) {
+        (Some((key, command)), _) => Some((key, command.trim())),
+        (None, Some((key, command))) => Some((key.trim(), command.trim())),
+        (None, None) => args.split_once(char::is_whitespace)
+            .map(|(key, command)| (key, command.trim())),
+    };
 
+    if let Some((key, command)) = binding {
+        let conflict = hooks::binding_conflict(engine, key);
         match hooks::bind_key(engine, key, command.to_string()) {
-            Ok(()) => TfCommandResult::Success(None),
+            Ok(()) => TfCommandResult::Success(conflict.map(|c| format!("Warning: {}", c))),
             Err(e) => TfCommandResult::Error(e),
         }
     } else {
@@ -2398,6 +2411,19 @@ mod tests {
         assert!(matches!(result, TfCommandResult::Error(_)));
     }
 
+    #[test]
+    fn test_cmd_bind_forms() {
+        let mut engine = TfEngine::new();
+
+        cmd_bind(&mut engine, "F5 say a=b");
+        assert_eq!(hooks::get_binding(&engine, "F5"), Some("say a=b".to_string()));
+        cmd_bind(&mut engine, "F6 = cast heal");
+        assert_eq!(hooks::get_binding(&engine, "F6"), Some("cast heal".to_string()));
+        cmd_bind(&mut engine, "^S=/set x=1");
+        assert_eq!(hooks::get_binding(&engine, "^S"), Some("/set x=1".to_string()));
+        assert!(matches!(cmd_bind(&mut engine, "F13 say a=b"), TfCommandResult::Error(_)));
+    }
+
     #[test]
     fn test_cmd_unset() {
         let mut engine = TfEngine::new();
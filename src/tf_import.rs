@@ -0,0 +1,327 @@
+//! `/import <file>` of a TinyFugue config (`.tfrc`), made into Clay worlds and actions.
+//!
+//! `/addworld` lines become worlds. `/def -t` triggers, `/trig`, `/gag` and `/hilite`
+//! become actions with a pattern (gag and hilite attributes turn into `/gag` and
+//! `/highlight`), and `/alias` becomes an action without one, which is how Clay spells
+//! a `/name` command. Bodies are rewritten for action commands: `%;` separates commands,
+//! `%1`..`%9` and `%*` become `$1`..`$9` and `$*`, and regexp captures `%P0`..`%P9`
+//! become `$0`..`$9`. Everything else is listed in the notes rather than guessed at;
+//! `/load` still runs a TF file as TF, for what an import can't carry over.
+
+use crate::actions::{Action, MatchPattern, MatchType};
+use crate::tf::macros::parse_def;
+use crate::tf::{TfAttributes, TfMatchMode};
+
+/// A world from an `/addworld` line
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedWorld {
+    pub name: String,
+    pub host: Option<String>,
+    pub port: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub use_ssl: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct TfImport {
+    pub worlds: Vec<ImportedWorld>,
+    pub actions: Vec<Action>,
+    /// `line N: ...` for each line that wasn't translated, or only in part
+    pub notes: Vec<String>,
+}
+
+/// Whether `text` reads as a TF config: its first line that isn't blank or a comment
+/// is a command
+pub fn is_tf_config(text: &str) -> bool {
+    logical_lines(text).next().is_some_and(|(_, line)| line.starts_with('/'))
+}
+
+/// Lines with `\` continuations joined (leading space of the next line dropped), blank
+/// lines and `;`/`#` comments skipped, each with its first line number
+fn logical_lines(text: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    let mut lines = text.lines().enumerate();
+    std::iter::from_fn(move || loop {
+        let (idx, first) = lines.next()?;
+        let mut line = first.to_string();
+        while line.ends_with('\\') && !line.ends_with("\\\\") {
+            line.pop();
+            match lines.next() {
+                Some((_, next)) => line.push_str(next.trim_start()),
+                None => break,
+            }
+        }
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with(';') && !trimmed.starts_with('#') {
+            return Some((idx + 1, trimmed.to_string()));
+        }
+    })
+}
+
+pub fn parse(text: &str) -> TfImport {
+    let mut import = TfImport::default();
+    for (line_no, line) in logical_lines(text) {
+        let (cmd, args) = line.split_once(char::is_whitespace).unwrap_or((&line, ""));
+        let args = args.trim();
+        let mut note = |what: &str| import.notes.push(format!("line {}: {}: {}", line_no, what, preview(&line)));
+        match cmd.to_lowercase().as_str() {
+            "/addworld" => match crate::parse_command(&line) {
+                crate::Command::AddWorld { name, host, port, user, password, use_ssl } => {
+                    import.worlds.push(ImportedWorld { name, host, port, user, password, use_ssl });
+                }
+                _ => note("not a world definition"),
+            },
+            "/def" => match parse_def_named(args, import.actions.len()) {
+                Ok(def) => {
+                    let mut notes = Vec::new();
+                    if let Some(action) = def_action(def, &mut notes) {
+                        import.actions.push(action);
+                    }
+                    for n in notes {
+                        note(&n);
+                    }
+                }
+                Err(e) => note(&format!("not translated ({})", e)),
+            },
+            "/trig" => match args.split_once('=') {
+                Some((pattern, body)) => {
+                    let mut notes = Vec::new();
+                    let name = format!("tf_trigger_{}", import.actions.len() + 1);
+                    let mut action = trigger_action(&name, unquote(pattern.trim()), TfMatchMode::Glob, None, &TfAttributes::default());
+                    push_command(&mut action, &translate_body(body.trim(), true, &mut notes));
+                    import.actions.push(action);
+                    for n in notes {
+                        note(&n);
+                    }
+                }
+                None => note("not translated (no body)"),
+            },
+            "/gag" | "/hilite" if !args.is_empty() => {
+                let gag = cmd.eq_ignore_ascii_case("/gag");
+                let attrs = TfAttributes { gag, hilite: (!gag).then(String::new), ..Default::default() };
+                let name = format!("tf_{}_{}", &cmd[1..].to_lowercase(), import.actions.len() + 1);
+                import.actions.push(trigger_action(&name, unquote(args), TfMatchMode::Glob, None, &attrs));
+            }
+            "/alias" => match args.split_once(char::is_whitespace) {
+                Some((name, body)) => {
+                    let mut notes = Vec::new();
+                    let mut action = Action::new();
+                    action.name = name.trim_start_matches('/').to_string();
+                    action.command = translate_body(body.trim(), false, &mut notes);
+                    import.actions.push(action);
+                    for n in notes {
+                        note(&n);
+                    }
+                }
+                None => note("not translated (no body)"),
+            },
+            _ if line.starts_with('/') => note("not translated"),
+            _ => note("not translated (text, not a command)"),
+        }
+    }
+    import
+}
+
+/// `parse_def`, naming a nameless definition (TF allows `/def -t"x" = body`) by trying
+/// the name before each `=` until the options parse
+fn parse_def_named(args: &str, count: usize) -> Result<crate::tf::TfMacro, String> {
+    match parse_def(args) {
+        Err(e) if e.contains("name cannot be empty") => {
+            let name = format!("tf_trigger_{}", count + 1);
+            args.match_indices('=')
+                .find_map(|(i, _)| {
+                    parse_def(&format!("{} {} {}", &args[..i], name, &args[i..])).ok().filter(|def| def.name == name)
+                })
+                .ok_or(e)
+        }
+        result => result,
+    }
+}
+
+/// The action for a `/def`, or None (with a note) for what has no Clay equivalent
+fn def_action(def: crate::tf::TfMacro, notes: &mut Vec<String>) -> Option<Action> {
+    if def.hook.is_some() {
+        notes.push("not translated (hook)".to_string());
+        return None;
+    }
+    if def.keybinding.is_some() {
+        notes.push("not translated (key binding; see /bind and keybindings.dat)".to_string());
+        return None;
+    }
+    if def.condition.is_some() || def.probability.is_some() {
+        notes.push("condition (-E) or chance (-c) dropped".to_string());
+    }
+    if def.one_shot.is_some() {
+        notes.push("fire count (-1/-n) dropped; the action always fires".to_string());
+    }
+    if def.partial_hilite {
+        notes.push("partial hilite (-P) highlights the whole line".to_string());
+    }
+    let Some(trigger) = def.trigger.filter(|t| !t.pattern.is_empty()) else {
+        notes.push("not translated (a macro, not a trigger; /alias or /load it)".to_string());
+        return None;
+    };
+    let mut action = trigger_action(&def.name, &trigger.pattern, trigger.match_mode, def.world.as_deref(), &def.attributes);
+    let body = translate_body(&def.body, true, notes);
+    push_command(&mut action, &body);
+    Some(action)
+}
+
+fn trigger_action(name: &str, pattern: &str, mode: TfMatchMode, world: Option<&str>, attrs: &TfAttributes) -> Action {
+    let (match_type, pattern) = match mode {
+        TfMatchMode::Glob => (MatchType::Wildcard, pattern.to_string()),
+        TfMatchMode::Simple => (MatchType::Regexp, regex::escape(pattern)),
+        // TF writes the end-of-line anchor `$$` in macro text
+        TfMatchMode::Regexp => (MatchType::Regexp, pattern.replace("$$", "$")),
+    };
+    let mut action = Action::new();
+    action.name = name.to_string();
+    action.world = world.unwrap_or_default().to_string();
+    action.match_type = match_type;
+    action.patterns = vec![MatchPattern { pattern, compiled_regex: None }];
+    if attrs.gag {
+        push_command(&mut action, "/gag");
+    }
+    if attrs.hilite.is_some() || attrs.bold || attrs.underline || attrs.reverse || attrs.flash {
+        match attrs.hilite.as_deref().filter(|c| !c.is_empty()) {
+            Some(color) => push_command(&mut action, &format!("/highlight {}", color)),
+            None => push_command(&mut action, "/highlight"),
+        }
+    }
+    action
+}
+
+fn push_command(action: &mut Action, command: &str) {
+    if command.is_empty() {
+        return;
+    }
+    if !action.command.is_empty() {
+        action.command.push(';');
+    }
+    action.command.push_str(command);
+}
+
+/// A TF body as an action command. Triggers get `%P0`..`%P9` as captures (and `%*`,
+/// the whole line, as `$0`); aliases get `%1`..`%9` and `%*` as their arguments.
+/// Other substitutions are kept as written, with a note.
+fn translate_body(body: &str, trigger: bool, notes: &mut Vec<String>) -> String {
+    enum Var {
+        All,
+        Capture(u32),
+        Arg(u32),
+    }
+    let mut out = String::with_capacity(body.len());
+    let mut kept = false;
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            (';', _) => out.push_str("\\;"),
+            ('%', Some(';')) => {
+                chars.next();
+                out.push(';');
+            }
+            ('%', Some('%')) => {
+                chars.next();
+                out.push('%');
+            }
+            ('%', _) => {
+                // %N, %{N}, %*, %P1, %{P1}
+                let mut probe = chars.clone();
+                let braced = probe.next_if_eq(&'{').is_some();
+                let var = match probe.next() {
+                    Some('*') => Some(Var::All),
+                    Some('P' | 'p') => probe.next().and_then(|d| d.to_digit(10)).map(Var::Capture),
+                    Some(d) => d.to_digit(10).map(Var::Arg),
+                    None => None,
+                };
+                let closed = !braced || probe.next_if_eq(&'}').is_some();
+                let replacement = match var.filter(|_| closed) {
+                    Some(Var::All) if trigger => Some("$0".to_string()),
+                    Some(Var::All) => Some("$*".to_string()),
+                    Some(Var::Capture(n)) if trigger => Some(format!("${}", n)),
+                    Some(Var::Arg(n)) if !trigger && n > 0 => Some(format!("${}", n)),
+                    _ => None,
+                };
+                match replacement {
+                    Some(replacement) => {
+                        out.push_str(&replacement);
+                        chars = probe;
+                    }
+                    None => {
+                        kept = true;
+                        out.push('%');
+                    }
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    if kept {
+        notes.push("TF substitutions kept as written; check the action".to_string());
+    }
+    out
+}
+
+/// The text of a possibly quoted argument
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(s)
+}
+
+/// A line shortened for a note
+fn preview(line: &str) -> String {
+    match line.char_indices().nth(60) {
+        Some((i, _)) => format!("{}...", &line[..i]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tfrc_worlds_triggers_and_aliases() {
+        let tfrc = "\
+; my tf config
+/addworld -x Ether hex secret ether.example.com 4000
+/def -mregexp -t\"^(\\\\w+) tells you: (.*)$$\" -wEther tell = /echo %P1 said %P2%;say hi; there
+/def -ag -t\"* has arrived.\" nosee
+/trig *You are hungry* = eat bread
+/alias k kill %1 with %*
+/hilite *pages you*
+/def -h\"CONNECT\" greet = say hello
+/set wrapspace=4
+/def -t\"x\" = \\
+    say %L
+";
+        assert!(is_tf_config(tfrc));
+        let import = parse(tfrc);
+        assert_eq!(import.worlds, vec![ImportedWorld {
+            name: "Ether".to_string(),
+            host: Some("ether.example.com".to_string()),
+            port: Some("4000".to_string()),
+            user: Some("hex".to_string()),
+            password: Some("secret".to_string()),
+            use_ssl: true,
+        }]);
+
+        let summary: Vec<(&str, &str, &str, &str)> = import.actions.iter()
+            .map(|a| (a.name.as_str(), a.world.as_str(), a.display_pattern(), a.command.as_str()))
+            .collect();
+        assert_eq!(summary, vec![
+            ("tell", "Ether", "^(\\w+) tells you: (.*)$", "/echo $1 said $2;say hi\\; there"),
+            ("nosee", "", "* has arrived.", "/gag"),
+            ("tf_trigger_3", "", "*You are hungry*", "eat bread"),
+            ("k", "", "", "kill $1 with $*"),
+            ("tf_hilite_5", "", "*pages you*", "/highlight"),
+            ("tf_trigger_6", "", "x", "say %L"),
+        ]);
+        assert_eq!(import.actions[0].match_type, MatchType::Regexp);
+        assert_eq!(import.actions[1].match_type, MatchType::Wildcard);
+
+        assert_eq!(import.notes.len(), 3, "{:?}", import.notes);
+        assert!(import.notes[0].starts_with("line 8: not translated (hook)"));
+        assert!(import.notes[1].starts_with("line 9: not translated: /set"));
+        assert!(import.notes[2].starts_with("line 10: TF substitutions kept"));
+    }
+}
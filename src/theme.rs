@@ -1,5 +1,5 @@
 // Theme system for Clay MUD client
-// Loads theme colors from ~/.clay/theme.dat and provides them to all renderers
+// Loads theme colors from ~/.config/clay/theme.dat and provides them to all renderers
 
 use std::collections::HashMap;
 use std::fs;
@@ -88,7 +88,7 @@ pub struct ThemeColors {
 }
 
 impl ThemeColors {
-    /// Default dark theme - imported from ~/.clay/theme.dat
+    /// Default dark theme - imported from ~/.config/clay/theme.dat
     pub fn dark_default() -> Self {
         Self {
             // Background hierarchy
@@ -403,10 +403,111 @@ impl ThemeColors {
     }
 }
 
+/// Replacement colors for the 16 base ANSI colors, applied per world
+/// (`[palette:name]` sections of the theme file, selected with /palette)
+pub type Palette = [ThemeColor; 16];
+
+/// Built-in palettes, overridable by a `[palette:name]` section of the same name
+fn builtin_palettes() -> Vec<(&'static str, Palette)> {
+    let hex = |s: &str| ThemeColor::from_hex(s).expect("built-in palette color");
+    let palette = |colors: [&str; 16]| -> Palette { colors.map(hex) };
+    vec![
+        ("solarized", palette([
+            "#073642", "#dc322f", "#859900", "#b58900", "#268bd2", "#d33682", "#2aa198", "#eee8d5",
+            "#586e75", "#cb4b16", "#93a1a1", "#fdf6e3", "#839496", "#6c71c4", "#2aa198", "#fdf6e3",
+        ])),
+        ("gruvbox", palette([
+            "#282828", "#cc241d", "#98971a", "#d79921", "#458588", "#b16286", "#689d6a", "#a89984",
+            "#928374", "#fb4934", "#b8bb26", "#fabd2f", "#83a598", "#d3869b", "#8ec07c", "#ebdbb2",
+        ])),
+    ]
+}
+
+/// Rewrite the 16 base colors in the SGR codes of `s` (30-37, 90-97, 40-47, 100-107
+/// and 38;5/48;5 below 16) as truecolor from `palette`. Bold standard foreground
+/// colors take the bright entry, as they do on the web client.
+pub fn remap_ansi_colors(s: &str, palette: &Palette) -> String {
+    if !s.contains('\x1b') {
+        return s.to_string();
+    }
+    let rgb = |layer: &str, idx: usize| {
+        let c = palette[idx];
+        format!("{};2;{};{};{}", layer, c.r, c.g, c.b)
+    };
+    let mut out = String::with_capacity(s.len() + 32);
+    let mut bold = false;
+    // Standard foreground color in effect, brightened if bold comes after it
+    let mut fg_std: Option<usize> = None;
+    let mut rest = s;
+    while let Some(pos) = rest.find("\x1b[") {
+        out.push_str(&rest[..pos]);
+        let seq = &rest[pos + 2..];
+        let params_len = seq.find(|c: char| !(c.is_ascii_digit() || c == ';')).unwrap_or(seq.len());
+        if !seq[params_len..].starts_with('m') {
+            out.push_str("\x1b[");
+            rest = seq;
+            continue;
+        }
+        let params: Vec<&str> = seq[..params_len].split(';').collect();
+        let mut codes: Vec<String> = Vec::with_capacity(params.len());
+        let mut i = 0;
+        while i < params.len() {
+            let n: u32 = params[i].parse().unwrap_or(0);
+            match n {
+                0 => { bold = false; fg_std = None; codes.push(params[i].to_string()); }
+                1 => {
+                    bold = true;
+                    codes.push(params[i].to_string());
+                    if let Some(idx) = fg_std.take() {
+                        codes.push(rgb("38", idx + 8));
+                    }
+                }
+                22 => { bold = false; codes.push(params[i].to_string()); }
+                30..=37 => {
+                    let idx = (n - 30) as usize;
+                    fg_std = if bold { None } else { Some(idx) };
+                    codes.push(rgb("38", if bold { idx + 8 } else { idx }));
+                }
+                90..=97 => { fg_std = None; codes.push(rgb("38", (n - 90) as usize + 8)); }
+                40..=47 => codes.push(rgb("48", (n - 40) as usize)),
+                100..=107 => codes.push(rgb("48", (n - 100) as usize + 8)),
+                38 | 48 => {
+                    let layer = params[i];
+                    // 256-color and truecolor codes pass through
+                    let (len, mapped) = match (params.get(i + 1).copied(), params.get(i + 2).and_then(|p| p.parse::<usize>().ok())) {
+                        (Some("5"), Some(idx)) if idx < 16 => (3, Some(rgb(layer, idx))),
+                        (Some("5"), _) => (3, None),
+                        (Some("2"), _) => (5, None),
+                        _ => (1, None),
+                    };
+                    match mapped {
+                        Some(code) => codes.push(code),
+                        None => codes.extend(params[i..(i + len).min(params.len())].iter().map(|p| p.to_string())),
+                    }
+                    if layer == "38" {
+                        fg_std = None;
+                    }
+                    i += len - 1;
+                }
+                39 => { fg_std = None; codes.push(params[i].to_string()); }
+                _ => codes.push(params[i].to_string()),
+            }
+            i += 1;
+        }
+        out.push_str("\x1b[");
+        out.push_str(&codes.join(";"));
+        out.push('m');
+        rest = &seq[params_len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
 /// Container for all themes loaded from the theme file
 #[derive(Clone, Debug)]
 pub struct ThemeFile {
     pub themes: HashMap<String, ThemeColors>,
+    pub palettes: HashMap<String, Palette>,
 }
 
 impl ThemeFile {
@@ -415,7 +516,8 @@ impl ThemeFile {
         let mut themes = HashMap::new();
         themes.insert("dark".to_string(), ThemeColors::dark_default());
         themes.insert("light".to_string(), ThemeColors::light_default());
-        Self { themes }
+        let palettes = builtin_palettes().into_iter().map(|(name, p)| (name.to_string(), p)).collect();
+        Self { themes, palettes }
     }
 
     /// Load themes from a file path, merging with defaults.
@@ -433,6 +535,7 @@ impl ThemeFile {
     pub fn parse(content: &str) -> Self {
         let mut file = Self::with_defaults();
         let mut current_theme: Option<String> = None;
+        let mut current_palette: Option<String> = None;
 
         for line in content.lines() {
             let line = line.trim();
@@ -445,7 +548,16 @@ impl ThemeFile {
             // Section header: [theme:name]
             if line.starts_with('[') && line.ends_with(']') {
                 let inner = &line[1..line.len() - 1];
-                if let Some(name) = inner.strip_prefix("theme:") {
+                current_theme = None;
+                current_palette = None;
+                if let Some(name) = inner.strip_prefix("palette:") {
+                    let name = name.trim().to_lowercase();
+                    // A new palette starts from the dark theme's colors
+                    if !file.palettes.contains_key(&name) {
+                        file.palettes.insert(name.clone(), ThemeColors::dark_default().ansi);
+                    }
+                    current_palette = Some(name);
+                } else if let Some(name) = inner.strip_prefix("theme:") {
                     let name = name.trim().to_string();
                     // If this theme doesn't exist yet, start from dark defaults
                     if !file.themes.contains_key(&name) {
@@ -457,7 +569,7 @@ impl ThemeFile {
             }
 
             // Key = value pair
-            if let Some(ref theme_name) = current_theme {
+            if current_theme.is_some() || current_palette.is_some() {
                 if let Some((key, value)) = line.split_once('=') {
                     let key = key.trim();
                     let value = value.trim();
@@ -473,9 +585,15 @@ impl ThemeFile {
                         value
                     };
                     if let Some(color) = ThemeColor::from_hex(value) {
-                        if let Some(theme) = file.themes.get_mut(theme_name) {
+                        if let Some(theme) = current_theme.as_ref().and_then(|name| file.themes.get_mut(name)) {
                             theme.set_var(key, color);
                         }
+                        let idx = key.strip_prefix("ansi.").and_then(|n| n.parse::<usize>().ok());
+                        if let (Some(palette), Some(idx)) = (current_palette.as_ref().and_then(|name| file.palettes.get_mut(name)), idx) {
+                            if idx < 16 {
+                                palette[idx] = color;
+                            }
+                        }
                     }
                 }
             }
@@ -494,6 +612,9 @@ impl ThemeFile {
         for (name, colors) in remote.themes {
             self.themes.insert(name, colors);
         }
+        for (name, palette) in remote.palettes {
+            self.palettes.insert(name, palette);
+        }
     }
 
     /// Get a theme by name, falling back to dark default
@@ -514,6 +635,40 @@ impl ThemeFile {
         serde_json::Value::Object(map).to_string()
     }
 
+    /// A palette by name (case-insensitive); None for "" or an unknown name
+    pub fn palette(&self, name: &str) -> Option<&Palette> {
+        self.palettes.get(&name.to_lowercase())
+    }
+
+    /// Palette names, sorted
+    pub fn palette_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.palettes.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Serialize all palettes as JSON: {"solarized": ["#073642", ...16 colors], ...}
+    pub fn palettes_json(&self) -> String {
+        let map: serde_json::Map<String, serde_json::Value> = self.palettes.iter()
+            .map(|(name, p)| (name.clone(), serde_json::Value::from(p.iter().map(|c| c.to_css()).collect::<Vec<_>>())))
+            .collect();
+        serde_json::Value::Object(map).to_string()
+    }
+
+    /// Replace the palettes with ones from `palettes_json` (a remote console mirroring
+    /// the master's theme file). Malformed entries are skipped.
+    pub fn set_palettes_json(&mut self, palettes_json: &str) {
+        let Ok(map) = serde_json::from_str::<HashMap<String, Vec<String>>>(palettes_json) else {
+            return;
+        };
+        self.palettes = map.into_iter()
+            .filter_map(|(name, colors)| {
+                let colors: Vec<ThemeColor> = colors.iter().filter_map(|c| ThemeColor::from_hex(c)).collect();
+                Some((name, <Palette>::try_from(colors).ok()?))
+            })
+            .collect();
+    }
+
     /// Generate complete .ini file content from current state
     pub fn generate_file_content(&self) -> String {
         let mut s = String::new();
@@ -528,6 +683,10 @@ impl ThemeFile {
             s.push_str(&format!("\n[theme:{}]\n", name));
             s.push_str(&self.themes[name].to_theme_file_section());
         }
+        for name in self.palette_names() {
+            s.push_str(&format!("\n[palette:{}]\n", name));
+            s.push_str(&palette_section(&self.palettes[&name]));
+        }
         s
     }
 
@@ -555,10 +714,21 @@ impl ThemeFile {
         s.push_str(&ThemeColors::dark_default().to_theme_file_section());
         s.push_str("\n[theme:light]\n");
         s.push_str(&ThemeColors::light_default().to_theme_file_section());
+        s.push_str("\n# Palettes remap the 16 ANSI colors servers send, per world (/palette <name>).\n");
+        s.push_str("# A [palette:name] section lists ansi.0 through ansi.15; missing ones come from the dark theme.\n");
+        for (name, palette) in builtin_palettes() {
+            s.push_str(&format!("\n[palette:{}]\n", name));
+            s.push_str(&palette_section(&palette));
+        }
         s
     }
 }
 
+/// The `ansi.N = #rrggbb` lines of a `[palette:name]` section
+fn palette_section(palette: &Palette) -> String {
+    palette.iter().enumerate().map(|(i, c)| format!("ansi.{} = {}\n", i, c.to_css())).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -647,6 +817,42 @@ ansi.15 = #eeeeee
         assert_eq!(custom.ansi[1], ThemeColor::new(170, 0, 0));
     }
 
+    #[test]
+    fn test_parse_palettes() {
+        let file = ThemeFile::parse("[palette:Mine]\nansi.1 = #ff0000\nansi.16 = #000000\n[palette:gruvbox]\nansi.0 = #101010\n");
+        let mine = file.palette("mine").expect("palette names are case-insensitive");
+        assert_eq!(mine[1], ThemeColor::new(255, 0, 0));
+        assert_eq!(mine[2], ThemeColors::dark_default().ansi[2]);
+        assert_eq!(file.palette("gruvbox").unwrap()[0], ThemeColor::new(0x10, 0x10, 0x10));
+        assert_eq!(file.palette("solarized").unwrap()[1], ThemeColor::new(0xdc, 0x32, 0x2f));
+        assert!(file.palette("").is_none());
+        assert_eq!(file.palette_names(), vec!["gruvbox", "mine", "solarized"]);
+
+        let reparsed = ThemeFile::parse(&file.generate_file_content());
+        assert_eq!(reparsed.palettes, file.palettes);
+        let mut remote = ThemeFile::with_defaults();
+        remote.set_palettes_json(&file.palettes_json());
+        assert_eq!(remote.palettes, file.palettes);
+        assert_eq!(ThemeFile::parse(&ThemeFile::generate_default_file()).palettes, ThemeFile::with_defaults().palettes);
+    }
+
+    #[test]
+    fn test_remap_ansi_colors() {
+        let mut p: Palette = [ThemeColor::new(0, 0, 0); 16];
+        for (i, c) in p.iter_mut().enumerate() {
+            *c = ThemeColor::new(i as u8, 0, 0);
+        }
+        assert_eq!(remap_ansi_colors("plain", &p), "plain");
+        assert_eq!(remap_ansi_colors("\x1b[31mred\x1b[0m", &p), "\x1b[38;2;1;0;0mred\x1b[0m");
+        assert_eq!(remap_ansi_colors("\x1b[1;34mA\x1b[94;44mB", &p), "\x1b[1;38;2;12;0;0mA\x1b[38;2;12;0;0;48;2;4;0;0mB");
+        // Bold after a standard color brightens it, even in a later sequence
+        assert_eq!(remap_ansi_colors("\x1b[32m\x1b[1mC", &p), "\x1b[38;2;2;0;0m\x1b[1;38;2;10;0;0mC");
+        assert_eq!(remap_ansi_colors("\x1b[38;5;9;48;5;200mD\x1b[38;2;1;2;3mE\x1b[103m", &p),
+            "\x1b[38;2;9;0;0;48;5;200mD\x1b[38;2;1;2;3mE\x1b[48;2;11;0;0m");
+        // Other CSI sequences and the reset of bold are left alone
+        assert_eq!(remap_ansi_colors("\x1b[2K\x1b[m\x1b[22;33m", &p), "\x1b[2K\x1b[m\x1b[22;38;2;3;0;0m");
+    }
+
     #[test]
     fn test_parse_with_inline_comments() {
         let content = "[theme:dark]\nbg = #aabbcc  # main background\n";
@@ -0,0 +1,189 @@
+//! System tray icon for the GUI (`tray` feature): the clay dot with a red badge while any
+//! world has unseen output, the total in its tooltip (and title, where the tray shows one),
+//! and a menu to bring the window up, jump to a world or mute notifications.
+//!
+//! The page reports its worlds over the `tray:` IPC (app.js `updateTray`) whenever they
+//! change; menu picks come back as `WvEvent::TrayAction` and go to `window.trayAction`.
+
+use serde::Deserialize;
+use tray_icon::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder, TrayIconEvent};
+
+/// Side of the generated icon, in pixels
+const ICON_SIZE: u32 = 32;
+
+/// A world as the page reports it for the tray
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct TrayWorld {
+    pub name: String,
+    #[serde(default)]
+    pub unseen: usize,
+    #[serde(default)]
+    pub muted: bool,
+}
+
+/// What a tray menu pick asks the GUI to do
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrayAction {
+    /// Bring the main window up
+    Show,
+    /// Bring the window up on this world
+    World(usize),
+    /// Mute every world's notifications, or unmute them all if they already are
+    ToggleMute,
+}
+
+/// The action for a menu item id (see `build_menu`)
+pub fn parse_menu_id(id: &str) -> Option<TrayAction> {
+    match id {
+        "show" => Some(TrayAction::Show),
+        "mute" => Some(TrayAction::ToggleMute),
+        _ => id.strip_prefix("world:").and_then(|i| i.parse().ok()).map(TrayAction::World),
+    }
+}
+
+/// Tooltip text: the unseen total and the worlds that have some
+pub fn tooltip(worlds: &[TrayWorld]) -> String {
+    let busy: Vec<String> = worlds.iter()
+        .filter(|w| w.unseen > 0)
+        .map(|w| format!("{} ({})", w.name, w.unseen))
+        .collect();
+    if busy.is_empty() {
+        "Clay".to_string()
+    } else {
+        let total: usize = worlds.iter().map(|w| w.unseen).sum();
+        format!("Clay - {} unseen: {}", total, busy.join(", "))
+    }
+}
+
+/// RGBA pixels of the icon: a clay-colored disc, with a red badge at the top right
+pub fn icon_rgba(badge: bool) -> Vec<u8> {
+    let size = ICON_SIZE as f32;
+    let inside = |x: u32, y: u32, cx: f32, cy: f32, r: f32| {
+        let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+        dx * dx + dy * dy <= r * r
+    };
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let pixel = if badge && inside(x, y, size * 0.75, size * 0.25, size * 0.25) {
+                [0xe0, 0x1b, 0x24, 0xff]
+            } else if inside(x, y, size / 2.0, size / 2.0, size / 2.0 - 1.0) {
+                [0xff, 0x87, 0xff, 0xff]
+            } else {
+                [0, 0, 0, 0]
+            };
+            rgba.extend_from_slice(&pixel);
+        }
+    }
+    rgba
+}
+
+fn icon(badge: bool) -> Option<Icon> {
+    Icon::from_rgba(icon_rgba(badge), ICON_SIZE, ICON_SIZE).ok()
+}
+
+/// Show, one item per world ("name (unseen)"), then the mute toggle
+fn build_menu(worlds: &[TrayWorld]) -> Menu {
+    let menu = Menu::new();
+    let _ = menu.append(&MenuItem::with_id("show", "Show Clay", true, None));
+    if !worlds.is_empty() {
+        let _ = menu.append(&PredefinedMenuItem::separator());
+        for (i, world) in worlds.iter().enumerate() {
+            let label = if world.unseen > 0 {
+                format!("{} ({})", world.name, world.unseen)
+            } else {
+                world.name.clone()
+            };
+            let _ = menu.append(&MenuItem::with_id(format!("world:{}", i), label, true, None));
+        }
+    }
+    let _ = menu.append(&PredefinedMenuItem::separator());
+    let all_muted = !worlds.is_empty() && worlds.iter().all(|w| w.muted);
+    let _ = menu.append(&CheckMenuItem::with_id("mute", "Mute notifications", !worlds.is_empty(), all_muted, None));
+    menu
+}
+
+/// The tray icon and the worlds it last showed
+pub struct Tray {
+    icon: TrayIcon,
+    worlds: Vec<TrayWorld>,
+}
+
+impl Tray {
+    /// Put the icon in the tray; `on_action` gets each menu pick (and a click on the
+    /// icon, where the platform reports one). Must run on the GUI thread.
+    pub fn new(on_action: impl Fn(TrayAction) + Send + Sync + 'static) -> Result<Self, String> {
+        let on_action = std::sync::Arc::new(on_action);
+        let on_menu = on_action.clone();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if let Some(action) = parse_menu_id(&event.id.0) {
+                on_menu(action);
+            }
+        }));
+        TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+            if let TrayIconEvent::Click { button: tray_icon::MouseButton::Left, .. } = event {
+                on_action(TrayAction::Show);
+            }
+        }));
+        let mut builder = TrayIconBuilder::new()
+            .with_menu(Box::new(build_menu(&[])))
+            .with_tooltip("Clay");
+        if let Some(icon) = icon(false) {
+            builder = builder.with_icon(icon);
+        }
+        let icon = builder.build().map_err(|e| e.to_string())?;
+        Ok(Self { icon, worlds: Vec::new() })
+    }
+
+    /// Show these worlds: badge, tooltip, title and menu
+    pub fn update(&mut self, worlds: Vec<TrayWorld>) {
+        if worlds == self.worlds {
+            return;
+        }
+        let total: usize = worlds.iter().map(|w| w.unseen).sum();
+        let had_unseen = self.worlds.iter().any(|w| w.unseen > 0);
+        if (total > 0) != had_unseen {
+            let _ = self.icon.set_icon(icon(total > 0));
+        }
+        let _ = self.icon.set_tooltip(Some(tooltip(&worlds)));
+        self.icon.set_title(if total > 0 { Some(total.to_string()) } else { None });
+        self.icon.set_menu(Some(Box::new(build_menu(&worlds))));
+        self.worlds = worlds;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world(name: &str, unseen: usize) -> TrayWorld {
+        TrayWorld { name: name.to_string(), unseen, muted: false }
+    }
+
+    #[test]
+    fn test_parse_menu_id() {
+        assert_eq!(parse_menu_id("show"), Some(TrayAction::Show));
+        assert_eq!(parse_menu_id("mute"), Some(TrayAction::ToggleMute));
+        assert_eq!(parse_menu_id("world:3"), Some(TrayAction::World(3)));
+        assert_eq!(parse_menu_id("world:x"), None);
+        assert_eq!(parse_menu_id("other"), None);
+    }
+
+    #[test]
+    fn test_tooltip_and_badge() {
+        assert_eq!(tooltip(&[]), "Clay");
+        assert_eq!(tooltip(&[world("mush", 0)]), "Clay");
+        assert_eq!(tooltip(&[world("mush", 3), world("moo", 0), world("rpi", 12)]),
+            "Clay - 15 unseen: mush (3), rpi (12)");
+
+        // The badge's center is red only when there's something unseen
+        let pixel = |rgba: &[u8]| {
+            let at = ((ICON_SIZE / 4 * ICON_SIZE + ICON_SIZE * 3 / 4) * 4) as usize;
+            rgba[at..at + 4].to_vec()
+        };
+        assert_eq!(icon_rgba(false).len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+        assert_eq!(pixel(&icon_rgba(true)), vec![0xe0, 0x1b, 0x24, 0xff]);
+        assert_ne!(pixel(&icon_rgba(false)), vec![0xe0, 0x1b, 0x24, 0xff]);
+    }
+}
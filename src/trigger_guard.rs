@@ -0,0 +1,105 @@
+//! Watchdog for runaway trigger loops.
+//!
+//! An action whose command provokes output that matches the action again (or
+//! another action that answers back) can flood a MUD with commands. Each world keeps
+//! a one-second window of the action fires that sent commands; when any action fires
+//! more than the limit within that window, every action taking part in the burst is
+//! disabled and the chain is reported.
+//!
+//! The limit is set with `/loopguard <N>` (fires per second, default 20) and
+//! persisted as `trigger_loop_limit` in settings.dat; `/loopguard off` disables it.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Default fires per second before an action counts as looping
+pub const DEFAULT_LIMIT: u32 = 20;
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Recent action fires for one world
+#[derive(Debug, Default)]
+pub struct TriggerGuard {
+    fires: VecDeque<(Instant, String)>,
+}
+
+impl TriggerGuard {
+    /// Record that `action` fired and sent commands at `now`. Returns the actions that
+    /// make up the loop (in order of first fire) once `action` exceeds `limit` fires in
+    /// the last second; `limit` 0 disables the check.
+    pub fn record(&mut self, action: &str, now: Instant, limit: u32) -> Option<Vec<String>> {
+        if limit == 0 {
+            return None;
+        }
+        while self.fires.front().is_some_and(|(t, _)| now.duration_since(*t) >= WINDOW) {
+            self.fires.pop_front();
+        }
+        self.fires.push_back((now, action.to_string()));
+        if self.count(action) <= limit as usize {
+            return None;
+        }
+
+        // Actions that fired only once or twice in the burst are bystanders, not part
+        // of the loop
+        let threshold = (limit as usize / 2).max(2);
+        let mut chain: Vec<String> = Vec::new();
+        for (_, name) in &self.fires {
+            if !chain.contains(name) && self.count(name) >= threshold {
+                chain.push(name.clone());
+            }
+        }
+        self.fires.clear();
+        Some(chain)
+    }
+
+    fn count(&self, action: &str) -> usize {
+        self.fires.iter().filter(|(_, name)| name == action).count()
+    }
+}
+
+/// Show a loop as `a -> b -> a` (a lone action as `a -> a`)
+pub fn describe_chain(chain: &[String]) -> String {
+    match chain.first() {
+        Some(first) => format!("{} -> {}", chain.join(" -> "), first),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_trigger_trips_after_limit() {
+        let mut guard = TriggerGuard::default();
+        let start = Instant::now();
+        for i in 0..5 {
+            assert_eq!(guard.record("echo", start + Duration::from_millis(i * 10), 5), None);
+        }
+        let chain = guard.record("echo", start + Duration::from_millis(60), 5).unwrap();
+        assert_eq!(chain, vec!["echo"]);
+        assert_eq!(describe_chain(&chain), "echo -> echo");
+        // The window is cleared after tripping
+        assert_eq!(guard.record("echo", start + Duration::from_millis(70), 5), None);
+    }
+
+    #[test]
+    fn test_chain_excludes_bystanders_and_old_fires() {
+        let mut guard = TriggerGuard::default();
+        let start = Instant::now();
+        // Fires older than the window don't count
+        for i in 0..3 {
+            guard.record("ping", start + Duration::from_millis(i), 4);
+        }
+        let later = start + Duration::from_secs(2);
+        guard.record("greet", later, 4);
+        let mut tripped = None;
+        for i in 0..5 {
+            let t = later + Duration::from_millis(10 + i * 10);
+            tripped = tripped.or(guard.record("ping", t, 4));
+            tripped = tripped.or(guard.record("pong", t, 4));
+        }
+        assert_eq!(tripped, Some(vec!["ping".to_string(), "pong".to_string()]));
+        assert_eq!(guard.record("x", later, 0), None);
+    }
+}
@@ -1,5 +1,7 @@
 use ansi_to_tui::IntoText;
-use unicode_width::UnicodeWidthStr;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::input::{display_width, grapheme_width};
 
 // ============================================================================
 // Security helpers: constant-time comparison, owner-only file permissions
@@ -31,7 +33,7 @@ pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
 ///
 /// Unix: the file is created with mode `0o600` directly via `O_CREAT` — there is no
 /// window where it's briefly world-readable (mirrors the pattern already used for
-/// `~/.clay/secure.key` in `persistence.rs`).
+/// `~/.config/clay/secure.key` in `persistence.rs`).
 /// Windows: created normally, then best-effort restricted to the owner (see
 /// `restrict_to_owner_windows` below — currently a documented no-op; matches prior
 /// behavior rather than blocking the build on a Windows ACL API).
@@ -140,6 +142,96 @@ pub fn strip_ansi_codes(s: &str) -> String {
     result
 }
 
+/// Show every match of `regex` in the visible text of `s` in reverse video, leaving ANSI
+/// (CSI) and OSC sequences intact. Matches may span color changes; reverse is re-applied
+/// after each escape sequence inside a match so a reset can't end it early.
+pub fn highlight_matches(s: &str, regex: &regex::Regex) -> String {
+    reverse_visible_ranges(s, |plain| {
+        regex.find_iter(plain)
+            .filter(|m| !m.is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect()
+    })
+}
+
+/// Show visible columns `start..end` (in chars, escapes not counted) of `s` in reverse
+/// video, the same way as `highlight_matches`. Used for the copy mode cursor/selection.
+pub fn highlight_columns(s: &str, start: usize, end: usize) -> String {
+    reverse_visible_ranges(s, |plain| {
+        let offset = |col: usize| plain.char_indices().nth(col).map_or(plain.len(), |(i, _)| i);
+        vec![(offset(start), offset(end))]
+    })
+}
+
+/// Reverse-video the byte ranges of the visible text of `s` picked by `ranges`
+fn reverse_visible_ranges(s: &str, ranges: impl FnOnce(&str) -> Vec<(usize, usize)>) -> String {
+    // Split into escape sequences and visible chars, remembering each char's offset in
+    // the visible text so matches found there can be mapped back
+    let mut tokens: Vec<(&str, Option<usize>)> = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+    let bytes = s.as_bytes();
+    while i < s.len() {
+        if bytes[i] == 0x1b && i + 1 < s.len() && (bytes[i + 1] == b'[' || bytes[i + 1] == b']') {
+            let start = i;
+            let osc = bytes[i + 1] == b']';
+            i += 2;
+            while i < s.len() {
+                if osc {
+                    if bytes[i] == 0x07 {
+                        i += 1;
+                        break;
+                    }
+                    if bytes[i] == 0x1b && i + 1 < s.len() && bytes[i + 1] == b'\\' {
+                        i += 2;
+                        break;
+                    }
+                } else if bytes[i].is_ascii_alphabetic() || bytes[i] == b'~' {
+                    i += 1;
+                    break;
+                }
+                i += 1;
+            }
+            tokens.push((&s[start..i], None));
+        } else {
+            let len = s[i..].chars().next().map_or(1, |c| c.len_utf8());
+            tokens.push((&s[i..i + len], Some(plain.len())));
+            plain.push_str(&s[i..i + len]);
+            i += len;
+        }
+    }
+
+    let matches: Vec<(usize, usize)> = ranges(&plain).into_iter().filter(|(a, b)| a < b).collect();
+    if matches.is_empty() {
+        return s.to_string();
+    }
+
+    let mut out = String::with_capacity(s.len() + matches.len() * 10);
+    let mut on = false;
+    for (text, pos) in tokens {
+        match pos {
+            Some(pos) => {
+                let hit = matches.iter().any(|&(a, b)| pos >= a && pos < b);
+                if hit != on {
+                    out.push_str(if hit { "\x1b[7m" } else { "\x1b[27m" });
+                    on = hit;
+                }
+                out.push_str(text);
+            }
+            None => {
+                out.push_str(text);
+                if on {
+                    out.push_str("\x1b[7m");
+                }
+            }
+        }
+    }
+    if on {
+        out.push_str("\x1b[27m");
+    }
+    out
+}
+
 /// Normalize a prompt string: strip CR/LF, strip trailing spaces, add exactly one space
 pub fn normalize_prompt(text: &str) -> String {
     let clean = text.replace('\r', "").replace('\n', " ");
@@ -261,12 +353,12 @@ pub fn visual_line_count(line: &str, width: usize, indent: usize) -> usize {
         Ok(text) => {
             let mut total = 0;
             for l in text.lines {
-                let line_width: usize = l.spans.iter().map(|s| s.content.width()).sum();
+                let line_width: usize = l.spans.iter().map(|s| display_width(&s.content)).sum();
                 total += rows_for(line_width);
             }
             total.max(1)
         }
-        Err(_) => rows_for(line.width()),
+        Err(_) => rows_for(display_width(line)),
     }
 }
 
@@ -441,29 +533,23 @@ pub fn get_current_time_12hr() -> String {
     format!("{}:{:02}", hours_12, minutes)
 }
 
-/// Strip MUD tags from a line (tags like [channel:] or [channel(player)])
-pub fn strip_mud_tag(text: &str) -> String {
-    // First, find any leading whitespace
-    let trimmed = text.trim_start();
-    let leading_ws_len = text.len() - trimmed.len();
-    let leading_ws = &text[..leading_ws_len];
-
-    // Don't strip tags from indented lines - real MUD tags are never indented
-    if leading_ws_len > 0 {
-        return text.to_string();
+/// Locate a leading MUD tag (tags like [channel:] or [channel(player)]) and return
+/// (byte offset where the tag's '[' starts, tag body between the brackets, text after "] ").
+/// ANSI codes before the tag are allowed; indented lines never carry a tag.
+fn find_mud_tag(text: &str) -> Option<(usize, &str, &str)> {
+    // Don't match tags on indented lines - real MUD tags are never indented
+    if text.len() != text.trim_start().len() {
+        return None;
     }
 
     // Check if line starts with [ (possibly after ANSI codes)
-    let mut chars = trimmed.chars().peekable();
-    let mut ansi_prefix = String::new();
+    let mut chars = text.char_indices().peekable();
     let mut in_ansi = false;
 
-    while let Some(c) = chars.next() {
-        if c == '\x1b' && chars.peek() == Some(&'[') {
-            ansi_prefix.push(c);
+    while let Some((i, c)) = chars.next() {
+        if c == '\x1b' && chars.peek().map(|(_, n)| *n) == Some('[') {
             in_ansi = true;
         } else if in_ansi {
-            ansi_prefix.push(c);
             if c.is_ascii_alphabetic() {
                 in_ansi = false;
             }
@@ -472,57 +558,124 @@ pub fn strip_mud_tag(text: &str) -> String {
             // Match two specific MUD tag patterns:
             //   [name(content)optional] - paren group inside brackets
             //   [name:] - colon immediately before closing bracket
-            let rest: String = chars.collect();
-            if let Some(end) = rest.find(']') {
-                let tag = &rest[..end];
-                let is_tag = if let Some(paren_start) = tag.find('(') {
-                    // Pattern 1: [name(content)optional]
-                    // Must have content before '(', non-empty content inside parens,
-                    // and a closing ')'
-                    if let Some(paren_end) = tag[paren_start..].find(')') {
-                        paren_start > 0 && paren_end > 1
-                    } else {
-                        false
-                    }
+            let rest = &text[i + 1..];
+            let end = rest.find(']')?;
+            let tag = &rest[..end];
+            let is_tag = if let Some(paren_start) = tag.find('(') {
+                // Pattern 1: [name(content)optional]
+                // Must have content before '(', non-empty content inside parens,
+                // and a closing ')'
+                if let Some(paren_end) = tag[paren_start..].find(')') {
+                    paren_start > 0 && paren_end > 1
                 } else {
-                    // Pattern 2: [name:]
-                    // Must end with ':' and have content before it
-                    tag.len() > 1 && tag.ends_with(':')
-                };
-                if is_tag {
-                    let after_tag = &rest[end + 1..];
-                    // Perl patterns require a space after '] '
-                    if let Some(after_space) = after_tag.strip_prefix(' ') {
-                        return format!("{}{}{}", leading_ws, ansi_prefix, after_space);
-                    }
+                    false
                 }
+            } else {
+                // Pattern 2: [name:]
+                // Must end with ':' and have content before it
+                tag.len() > 1 && tag.ends_with(':')
+            };
+            if !is_tag {
+                return None;
             }
-            return text.to_string();
+            // Perl patterns require a space after '] '
+            let after_space = rest[end + 1..].strip_prefix(' ')?;
+            return Some((i, tag, after_space));
         } else {
-            // Not a tag start, return original
-            return text.to_string();
+            // Not a tag start
+            return None;
         }
     }
-    text.to_string()
+    None
 }
 
-/// Truncate a string to max_len, adding "..." if truncated
-pub fn truncate_str(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else if max_len > 3 {
-        let mut end = max_len - 3;
-        while end > 0 && !s.is_char_boundary(end) {
-            end -= 1;
-        }
-        format!("{}...", &s[..end])
+/// Strip MUD tags from a line (tags like [channel:] or [channel(player)])
+pub fn strip_mud_tag(text: &str) -> String {
+    match find_mud_tag(text) {
+        Some((tag_start, _, after)) => format!("{}{}", &text[..tag_start], after),
+        None => text.to_string(),
+    }
+}
+
+/// Name of a line's leading MUD tag, if any: "channel" for `[channel:]`,
+/// "chat" for `[chat(Bob)]`. Used to key tag routes (see tag_routes.rs).
+pub fn mud_tag_name(text: &str) -> Option<String> {
+    let (_, tag, _) = find_mud_tag(text)?;
+    let name = match tag.find('(') {
+        Some(paren) => &tag[..paren],
+        None => tag.trim_end_matches(':'),
+    };
+    Some(name.to_string())
+}
+
+/// A world address from a `telnet://` or `mud://` link, as MUD listing sites publish them
+#[derive(Debug, Clone, PartialEq)]
+pub struct MudUri {
+    pub host: String,
+    pub port: String,
+    pub tls: bool,
+}
+
+/// Parse `telnet://host[:port][/][?tls=1]` (or `mud://`). The port defaults to 23, and
+/// `tls=1` (also `true`, `yes`, `on`, or an `ssl=` key) asks for TLS.
+pub fn parse_mud_uri(text: &str) -> Option<MudUri> {
+    let (scheme, rest) = text.trim().split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("telnet") && !scheme.eq_ignore_ascii_case("mud") {
+        return None;
+    }
+    let (address, query) = match rest.split_once('?') {
+        Some((address, query)) => (address, query),
+        None => (rest, ""),
+    };
+    // Anything after the authority (a path, a #fragment) and any user@ are ignored
+    let address = address.split(['/', '#']).next().unwrap_or("");
+    let address = address.rsplit('@').next().unwrap_or("");
+    let (host, port) = if let Some(bracketed) = address.strip_prefix('[') {
+        let (host, after) = bracketed.split_once(']')?;
+        (host, after.strip_prefix(':'))
     } else {
-        let mut end = max_len;
-        while end > 0 && !s.is_char_boundary(end) {
-            end -= 1;
+        match address.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (address, None),
+        }
+    };
+    let port = match port {
+        Some(port) => port.parse::<u16>().ok().filter(|p| *p != 0)?.to_string(),
+        None => "23".to_string(),
+    };
+    if host.is_empty() || host.contains(char::is_whitespace) {
+        return None;
+    }
+    let tls = query.split(['&', '#']).any(|param| {
+        let (key, value) = param.split_once('=').unwrap_or((param, "1"));
+        (key.eq_ignore_ascii_case("tls") || key.eq_ignore_ascii_case("ssl"))
+            && matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+    });
+    Some(MudUri { host: host.to_string(), port, tls })
+}
+
+/// Truncate a string to max_len display columns, adding "..." if truncated. Whole
+/// grapheme clusters are kept or dropped, so a wide emoji or CJK character is never cut
+/// in half.
+pub fn truncate_str(s: &str, max_len: usize) -> String {
+    if display_width(s) <= max_len {
+        return s.to_string();
+    }
+    let budget = if max_len > 3 { max_len - 3 } else { max_len };
+    let mut out = String::new();
+    let mut width = 0;
+    for g in s.graphemes(true) {
+        let g_width = grapheme_width(g);
+        if width + g_width > budget {
+            break;
         }
-        s[..end].to_string()
+        width += g_width;
+        out.push_str(g);
+    }
+    if max_len > 3 {
+        out.push_str("...");
     }
+    out
 }
 
 /// Parse Discord timestamps in text and convert them to human-readable format
@@ -1066,6 +1219,30 @@ mod tests {
         assert!(!constant_time_eq(b"short", b"a much longer string"));
     }
 
+    // --- highlight_matches ---
+
+    #[test]
+    fn highlight_matches_spans_color_changes() {
+        let re = regex::RegexBuilder::new("ell").case_insensitive(true).build().unwrap();
+        assert_eq!(highlight_matches("hELLo", &re), "h\x1b[7mELL\x1b[27mo");
+        // A color reset inside the match re-applies reverse video
+        assert_eq!(highlight_matches("h\x1b[31me\x1b[0mllo", &re),
+            "h\x1b[31m\x1b[7me\x1b[0m\x1b[7mll\x1b[27mo");
+        // OSC 8 link targets aren't searched, only the visible text
+        let link = "\x1b]8;;http://ell.example\x1b\\x\x1b]8;;\x1b\\";
+        assert_eq!(highlight_matches(link, &re), link);
+        assert_eq!(highlight_matches("no match", &re), "no match");
+    }
+
+    #[test]
+    fn highlight_columns_counts_visible_chars() {
+        assert_eq!(highlight_columns("\x1b[32mabcdef", 1, 3), "\x1b[32ma\x1b[7mbc\x1b[27mdef");
+        assert_eq!(highlight_columns("héllo", 1, 2), "h\x1b[7mé\x1b[27mllo");
+        // Ranges past the end are clipped; empty ranges change nothing
+        assert_eq!(highlight_columns("ab", 1, 9), "a\x1b[7mb\x1b[27m");
+        assert_eq!(highlight_columns("ab", 5, 9), "ab");
+    }
+
     // --- secure_create_file / write_secret_file ---
 
     #[test]
@@ -1218,6 +1395,16 @@ mod tests {
         assert_eq!(visual_line_count("hello", 0, 0), 1);
     }
 
+    #[test]
+    fn test_visual_lines_double_width() {
+        // Six CJK characters are 12 columns
+        assert_eq!(visual_line_count("你好世界你好", 10, 0), 2);
+        assert_eq!(visual_line_count("你好世界你", 10, 0), 1);
+        // A ZWJ family and a VS16 emoji are one 2-column cluster each
+        assert_eq!(visual_line_count("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{1F6E2}\u{FE0F}abcdef", 10, 0), 1);
+        assert_eq!(visual_line_count("cafe\u{301}", 4, 0), 1, "a combining mark takes no column");
+    }
+
     #[test]
     fn test_visual_lines_with_indent() {
         // 20 columns of content at width 10: first row holds 10, remaining 10 needs
@@ -1250,6 +1437,16 @@ mod tests {
         assert_eq!(truncate_str("hello", 3), "hel");
     }
 
+    #[test]
+    fn test_truncate_by_columns() {
+        assert_eq!(truncate_str("你好世界", 8), "你好世界");
+        assert_eq!(truncate_str("你好世界", 7), "你好...", "a wide char is never cut in half");
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(truncate_str(&format!("{}{}ok", family, family), 6), format!("{}{}ok", family, family));
+        assert_eq!(truncate_str(&format!("{}{}ok!", family, family), 6), format!("{}...", family));
+        assert_eq!(truncate_str("cafe\u{301}!", 5), "cafe\u{301}!");
+    }
+
     // --- strip_mud_tag ---
 
     #[test]
@@ -1566,4 +1763,22 @@ mod tests {
         // Trailing bare % passes through
         assert_eq!(super::format_local_time(&lt, "%H%"), "14%");
     }
+
+    // --- parse_mud_uri ---
+
+    #[test]
+    fn test_parse_mud_uri() {
+        let uri = |host: &str, port: &str, tls: bool| Some(MudUri { host: host.to_string(), port: port.to_string(), tls });
+        assert_eq!(parse_mud_uri("telnet://mud.example.com:4000"), uri("mud.example.com", "4000", false));
+        assert_eq!(parse_mud_uri("TELNET://mud.example.com:4000/"), uri("mud.example.com", "4000", false));
+        assert_eq!(parse_mud_uri("mud://mud.example.com"), uri("mud.example.com", "23", false));
+        assert_eq!(parse_mud_uri("telnet://mud.example.com:4443?tls=1"), uri("mud.example.com", "4443", true));
+        assert_eq!(parse_mud_uri("mud://mud.example.com:4443/?foo=bar&ssl=true"), uri("mud.example.com", "4443", true));
+        assert_eq!(parse_mud_uri("telnet://mud.example.com:4000?tls=0"), uri("mud.example.com", "4000", false));
+        assert_eq!(parse_mud_uri("telnet://guest@[2001:db8::1]:4000"), uri("2001:db8::1", "4000", false));
+        assert_eq!(parse_mud_uri("telnet://mud.example.com:notaport"), None);
+        assert_eq!(parse_mud_uri("telnet://:4000"), None);
+        assert_eq!(parse_mud_uri("https://mud.example.com"), None);
+        assert_eq!(parse_mud_uri("mud.example.com:4000"), None);
+    }
 }
@@ -59,10 +59,15 @@
         moreCount: document.getElementById('more-count'),
         activityIndicator: document.getElementById('activity-indicator'),
         activityCount: document.getElementById('activity-count'),
+        activityWorlds: document.getElementById('activity-worlds'),
         statusScrollback: document.getElementById('status-scrollback'),
         statusScrollbackPct: document.getElementById('status-scrollback-pct'),
         statusTime: document.getElementById('status-time'),
+        statusProfile: document.getElementById('status-profile'),
+        statusSearch: document.getElementById('status-search'),
+        statusSending: document.getElementById('status-sending'),
         statusBar: document.getElementById('status-bar'),
+        tabBar: document.getElementById('tab-bar'),
         inputContainer: document.getElementById('input-container'),
         prompt: document.getElementById('prompt'),
         input: document.getElementById('input'),
@@ -79,11 +84,10 @@
         // Connection log modal
         connectionLogRetryBtn: document.getElementById('connection-log-retry-btn'),
         connectionLogCancelBtn: document.getElementById('connection-log-cancel-btn'),
-        // Reconnect modal (shown when send fails)
-        reconnectModal: document.getElementById('reconnect-modal'),
-        reconnectText: document.getElementById('reconnect-text'),
-        reconnectBtn: document.getElementById('reconnect-btn'),
-        reconnectCancelBtn: document.getElementById('reconnect-cancel-btn'),
+        // Offline banner (shown while a dropped connection is retried)
+        offlineBanner: document.getElementById('offline-banner'),
+        offlineBannerText: document.getElementById('offline-banner-text'),
+        offlineRetryBtn: document.getElementById('offline-retry-btn'),
         // Device mode selector (long-press menu)
         deviceModeModal: document.getElementById('device-mode-modal'),
         deviceModeList: document.getElementById('device-mode-list'),
@@ -98,6 +102,7 @@
         // Menu
         menuBtn: document.getElementById('menu-btn'),
         menuDropdown: document.getElementById('menu-dropdown'),
+        linkMenu: document.getElementById('link-menu'),
         // Font slider (status bar)
         fontSliderInput: document.getElementById('font-slider'),
         fontSliderLabel: document.getElementById('font-slider-label'),
@@ -120,8 +125,31 @@
         actionAddBtn: document.getElementById('action-add-btn'),
         actionEditBtn: document.getElementById('action-edit-btn'),
         actionDeleteBtn: document.getElementById('action-delete-btn'),
+        actionDuplicateBtn: document.getElementById('action-duplicate-btn'),
+        actionMoveUpBtn: document.getElementById('action-move-up-btn'),
+        actionMoveDownBtn: document.getElementById('action-move-down-btn'),
+        actionImportBtn: document.getElementById('action-import-btn'),
+        actionExportBtn: document.getElementById('action-export-btn'),
+        actionImportFile: document.getElementById('action-import-file'),
         actionCancelBtn: document.getElementById('action-cancel-btn'),
         actionsListCloseBtn: document.getElementById('actions-list-close-btn'),
+        // Gags popup
+        gagsModal: document.getElementById('gags-modal'),
+        gagsList: document.getElementById('gags-list'),
+        gagAddBtn: document.getElementById('gag-add-btn'),
+        gagToggleBtn: document.getElementById('gag-toggle-btn'),
+        gagKeepBtn: document.getElementById('gag-keep-btn'),
+        gagDeleteBtn: document.getElementById('gag-delete-btn'),
+        gagsOkBtn: document.getElementById('gags-ok-btn'),
+        gagsCloseBtn: document.getElementById('gags-close-btn'),
+        // Tag routes popup
+        tagRoutesModal: document.getElementById('tag-routes-modal'),
+        tagRoutesList: document.getElementById('tag-routes-list'),
+        tagRouteAddBtn: document.getElementById('tag-route-add-btn'),
+        tagRouteToggleBtn: document.getElementById('tag-route-toggle-btn'),
+        tagRouteDeleteBtn: document.getElementById('tag-route-delete-btn'),
+        tagRoutesOkBtn: document.getElementById('tag-routes-ok-btn'),
+        tagRoutesCloseBtn: document.getElementById('tag-routes-close-btn'),
         // Actions Editor popup
         actionsEditorModal: document.getElementById('actions-editor-modal'),
         actionEditorTitle: document.getElementById('action-editor-title'),
@@ -132,6 +160,8 @@
         actionAddPatternBtn: document.getElementById('action-add-pattern-btn'),
         actionEditorPageBtn: document.getElementById('action-editor-page-btn'),
         actionCommand: document.getElementById('action-command'),
+        actionTest: document.getElementById('action-test'),
+        actionTestResult: document.getElementById('action-test-result'),
         actionEnabled: document.getElementById('action-enabled'),
         actionStartup: document.getElementById('action-startup'),
         actionError: document.getElementById('action-error'),
@@ -151,6 +181,10 @@
         worldsListCloseBtn: document.getElementById('worlds-list-close-btn'),
         // World selector popup
         worldSelectorModal: document.getElementById('world-selector-modal'),
+        // Quick world switcher (Alt+K)
+        worldSwitcherModal: document.getElementById('world-switcher-modal'),
+        worldSwitcherInput: document.getElementById('world-switcher-input'),
+        worldSwitcherList: document.getElementById('world-switcher-list'),
         worldFilter: document.getElementById('world-filter'),
         worldSelectorTableBody: document.getElementById('world-selector-table-body'),
         worldSelectorOnlyConnected: document.getElementById('world-selector-only-connected'),
@@ -172,12 +206,28 @@
         worldEditUser: document.getElementById('world-edit-user'),
         worldEditPassword: document.getElementById('world-edit-password'),
         worldEditSslToggle: document.getElementById('world-edit-ssl-toggle'),
+        worldEditTlsVerifySelect: document.getElementById('world-edit-tls-verify-select'),
+        worldEditTlsClientCert: document.getElementById('world-edit-tls-client-cert'),
+        worldEditTlsClientKey: document.getElementById('world-edit-tls-client-key'),
+        worldEditSshHost: document.getElementById('world-edit-ssh-host'),
+        worldEditSshUser: document.getElementById('world-edit-ssh-user'),
+        worldEditSshKey: document.getElementById('world-edit-ssh-key'),
         worldEditAutoLoginSelect: document.getElementById('world-edit-auto-login-select'),
+        worldEditLoginScriptField: document.getElementById('world-edit-login-script-field'),
+        worldEditLoginScript: document.getElementById('world-edit-login-script'),
+        worldEditConnectCommands: document.getElementById('world-edit-connect-commands'),
+        worldEditInputPrefix: document.getElementById('world-edit-input-prefix'),
+        worldEditInputSuffix: document.getElementById('world-edit-input-suffix'),
         worldEditKeepAliveSelect: document.getElementById('world-edit-keep-alive-select'),
         worldEditKeepAliveCmdField: document.getElementById('world-edit-keep-alive-cmd-field'),
         worldEditKeepAliveCmd: document.getElementById('world-edit-keep-alive-cmd'),
+        worldEditKeepAliveInterval: document.getElementById('world-edit-keep-alive-interval'),
+        worldEditSilenceAlert: document.getElementById('world-edit-silence-alert'),
         worldEditEncodingSelect: document.getElementById('world-edit-encoding-select'),
         worldEditLoggingToggle: document.getElementById('world-edit-logging-toggle'),
+        worldEditLogFormatSelect: document.getElementById('world-edit-log-format-select'),
+        worldEditLogTimestampsSelect: document.getElementById('world-edit-log-timestamps-select'),
+        worldEditLogSessionsToggle: document.getElementById('world-edit-log-sessions-toggle'),
         worldEditGmcpPackages: document.getElementById('world-edit-gmcp-packages'),
         worldEditAutoReconnect: document.getElementById('world-edit-auto-reconnect'),
         worldEditCloseBtn: document.getElementById('world-edit-close-btn'),
@@ -189,6 +239,9 @@
         webPortSelect: document.getElementById('web-port-select'),
         webCustomPortField: document.getElementById('web-custom-port-field'),
         webCustomPort: document.getElementById('web-custom-port'),
+        webBindAddr: document.getElementById('web-bind-addr'),
+        webUnixSocket: document.getElementById('web-unix-socket'),
+        webTrustedProxies: document.getElementById('web-trusted-proxies'),
         webPath: document.getElementById('web-path'),
         webAllowList: document.getElementById('web-allow-list'),
         webWsPassword: document.getElementById('web-ws-password'),
@@ -197,6 +250,10 @@
         webKeyFile: document.getElementById('web-key-file'),
         tlsCertField: document.getElementById('tls-cert-field'),
         tlsKeyField: document.getElementById('tls-key-field'),
+        webAcmeDomain: document.getElementById('web-acme-domain'),
+        webAcmeEmail: document.getElementById('web-acme-email'),
+        acmeDomainField: document.getElementById('acme-domain-field'),
+        acmeEmailField: document.getElementById('acme-email-field'),
         // Combined settings popup (/setup + /web)
         settingsModal: document.getElementById('settings-modal'),
         settingsCloseBtn: document.getElementById('settings-close-btn'),
@@ -216,7 +273,9 @@
         setupTtsSelect: document.getElementById('setup-tts-select'),
         setupTtsSpeakModeSelect: document.getElementById('setup-tts-speak-mode-select'),
         setupTlsProxyToggle: document.getElementById('setup-tls-proxy-toggle'),
+        setupConfirmQuitToggle: document.getElementById('setup-confirm-quit-toggle'),
         setupNewLineIndicatorToggle: document.getElementById('setup-new-line-indicator-toggle'),
+        setupTabBarToggle: document.getElementById('setup-tab-bar-toggle'),
         setupDebugToggle: document.getElementById('setup-debug-toggle'),
         setupArchiveToggle: document.getElementById('setup-archive-toggle'),
         setupWorldSwitchSelect: document.getElementById('setup-world-switch-select'),
@@ -230,6 +289,7 @@
         setupWrapspaceMinus: document.getElementById('setup-wrapspace-minus'),
         setupWrapspacePlus: document.getElementById('setup-wrapspace-plus'),
         setupRemoteLinesInput: document.getElementById('setup-remote-lines-input'),
+        setupScrollbackLinesInput: document.getElementById('setup-scrollback-lines-input'),
         setupThemeSelect: document.getElementById('setup-theme-select'),
         setupTransparencyRow: document.getElementById('setup-transparency-row'),
         setupTransparencySlider: document.getElementById('setup-transparency-slider'),
@@ -243,6 +303,7 @@
         searchInput: document.getElementById('search-input'),
         searchMatchInfo: document.getElementById('search-match-info'),
         searchCloseBtn: document.getElementById('search-close-btn'),
+        searchPopupTitle: document.getElementById('search-popup-title'),
         // Help popup (/help)
         helpModal: document.getElementById('help-modal'),
         helpContent: document.getElementById('help-content'),
@@ -253,7 +314,11 @@
         menuList: document.getElementById('menu-list'),
         // Font fields (inside combined settings modal)
         settingsFontSection: document.getElementById('settings-font'),
+        settingsSoundsSection: document.getElementById('settings-sounds'),
+        soundsList: document.getElementById('sounds-list'),
         fontFamilyList: document.getElementById('font-family-list'),
+        fontFamilySearch: document.getElementById('font-family-search'),
+        fontFamilyPreview: document.getElementById('font-family-preview'),
         fontPhoneMinus: document.getElementById('font-phone-minus'),
         fontPhonePlus: document.getElementById('font-phone-plus'),
         fontPhoneValue: document.getElementById('font-phone-value'),
@@ -286,6 +351,8 @@
         worldEditHelpBtn: document.getElementById('world-edit-help-btn'),
         worldSelectorHelpBtn: document.getElementById('world-selector-help-btn'),
         actionsListHelpBtn: document.getElementById('actions-list-help-btn'),
+        gagsHelpBtn: document.getElementById('gags-help-btn'),
+        tagRoutesHelpBtn: document.getElementById('tag-routes-help-btn'),
         actionEditorHelpBtn: document.getElementById('action-editor-help-btn'),
         connectionsHelpBtn: document.getElementById('connections-help-btn'),
         menuHelpBtn: document.getElementById('menu-help-btn')
@@ -343,10 +410,16 @@
             grepRegex = null;
         }
     }
-    let pendingReconnectCommand = null;  // Command to resend after reconnect
-    let pendingReconnectWorldIndex = null;  // World index to switch to after reconnect
+    let everAuthenticated = false;  // Signed in once: a drop retries with backoff under the offline banner
+    let reconnectTimer = null;  // Pending backoff retry (see scheduleReconnect)
+    let reconnectAt = 0;  // When it fires, for the banner's countdown
+    let offlineBannerInterval = null;
+    let offlineCommands = [];  // Lines typed while offline, { world, command }, sent on reconnect
+    const MAX_OFFLINE_COMMANDS = 50;
     let commandHistory = [];
     let historyIndex = -1;
+    let reverseSearch = null;  // Ctrl+R incremental search: { query, index, saved, failed }
+    let activitySoundWorlds = new Set();  // Worlds whose sound played since the window lost focus
     let connectionFailures = 0;
     let reloadReconnect = false;
     let reloadReconnectAttempts = 0;
@@ -418,12 +491,33 @@
     let actionsConfirmPopupOpen = false;
     let selectedActionIndex = -1;
     let editingActionIndex = -1;  // -1 = new action, >=0 = editing existing
+    // Action editor's pattern tester: the server runs the sample line through the
+    // patterns with its own regex engine and answers ActionPatternsTested
+    let actionTestTimer = null;
+    let actionTestsInFlight = 0;
+    let actionPatternError = null;  // Last verdict's compile error, blocks Save
+    let saveActionAfterTest = false;
     let actionsWorldFilter = '';  // Filter by world from /actions <world>
 
+    // Gags state: the server's gag list, asked for when the popup opens (RequestGags)
+    // and resent as GagsUpdated whenever it changes
+    let gags = [];
+    let gagsPopupOpen = false;
+    let selectedGagIndex = -1;
+
+    // Tag routes state: the server's route list, asked for when the popup opens
+    // (RequestTagRoutes) and resent as TagRoutesUpdated whenever it changes
+    let tagRoutes = [];
+    let tagRoutesPopupOpen = false;
+    let selectedTagRouteIndex = -1;
+
     // Tag display state
     let showTags = false;
     let highlightActions = false;
 
+    // Scheduled profile currently in effect on the server ('' = none)
+    let activeProfile = '';
+
     // Color offset percentage (0 = disabled, 1-100 = adjustment percentage)
     let colorOffsetPercent = 0;
 
@@ -437,18 +531,26 @@
     // Command completion state
     let lastCompletionPrefix = '';
     let lastCompletionIndex = -1;
+    let emojiNames = [];  // Shortcode names for :name: completion (from InitialState)
+    let palettes = {};  // /palette name -> its 16 ANSI colors (from global settings)
+    let appliedPalette = null;  // Palette whose colors are set on the output element
 
     // World popup state
     let worldsPopupOpen = false;
     let worldSelectorPopupOpen = false;
     let worldConfirmPopupOpen = false;
     let worldSelectorOnlyConnected = false;
+    let worldSwitcherOpen = false;
+    let worldSwitcherMatches = [];  // { index, name, score, positions }, best first
+    let worldSwitcherSelected = 0;
     let worldEditorPopupOpen = false;
     let worldEditorIndex = -1;  // Index of world being edited
 
     // /import dialog state (see the worldEditorPopupOpen guard below for why this is needed)
     let importDialogOpen = false;
     let importInsecureDialogOpen = false;
+    // /web tokens dialog (a form too, so the same guard applies)
+    let deviceTokensDialogOpen = false;
 
     // Web settings popup state (global state from server)
     let settingsPopupOpen = false;
@@ -466,6 +568,12 @@
     let wsKeyFile = '';
     let wsPassword = '';
     let tlsConfigured = false;  // True if server has a custom (user-provided) TLS cert+key configured
+    let acmeDomain = '';  // Let's Encrypt domain ('' = off)
+    let acmeEmail = '';
+    let webBindAddr = '';  // Address the web server listens on ('' = not sent by the server)
+    let webUnixSocket = '';  // Unix socket for a local reverse proxy ('' = off)
+    let webTrustedProxies = '';  // Reverse proxy IPs whose X-Forwarded-For is believed
+    let pushPublicKey = '';  // Server's VAPID key for /web push on ('' = unavailable)
     let serverAuthKey = '';  // Auth key from server (for display in web settings)
     // Guards against pushing a full UpdateGlobalSettings snapshot before this client
     // has received the server's real values (InitialState / GlobalSettingsUpdated).
@@ -474,7 +582,7 @@
     let settingsSynced = false;
     // Temporary editing state for web popup (only saved on Save button)
     let editPortMode = 'disabled';  // 'disabled' | '9000' | 'custom'
-    let editCustomCert = false;
+    let editCertMode = 'no';  // 'no' (self-signed) | 'yes' (custom files) | 'acme' (Let's Encrypt)
     let editWsEnabled = false;
     let selectedWorldIndex = -1;
     let selectedWorldsRowIndex = -1; // For worlds list popup (/connections)
@@ -489,7 +597,9 @@
     let setupZwj = false;
     let setupTtsMode = 'Off';
     let setupTlsProxy = false;
+    let setupConfirmQuit = true;
     let setupNewLineIndicator = false;
+    let setupTabBar = false;
     let setupArchive = false;
     let setupDebug = false;
     let setupInputHeightValue = 1;
@@ -506,6 +616,9 @@
     let searchText = '';
     let searchMatchIndices = [];  // indices into output_lines that match
     let searchCurrentPos = -1;    // which match is currently shown at bottom
+    let searchInPlace = false;    // opened as scrollback search (Ctrl+F): Enter hands over to n/N
+    let searchNavigating = false; // prompt hidden; matches stay highlighted, n/N step through them
+    let searchHighlightRegex = null; // global regex for marking hits in rendered output
 
     // Font popup state (/font)
     // fontPopupOpen removed — merged into settingsPopupOpen
@@ -539,6 +652,9 @@
         ['Courier New', 'Courier New'],
         ['Consolas', 'Consolas'],
     ];
+    // Installed monospace fonts, added to the picker in the GUI (fontconfig, via list-fonts IPC)
+    let systemFonts = [];
+    let systemFontsRequested = false;
 
     // Help popup state (/help)
     let helpPopupOpen = false;
@@ -588,18 +704,25 @@
     let ttsMode = 'off';  // Will be synced from server settings ('off', 'local', 'edge')
     let ttsSpeakMode = 'all';  // 'all' or 'limit'
     let newLineIndicator = false;  // Will be synced from server settings
+    let tabBar = false;  // World tab strip (synced from server settings, shared with the console)
+    let tabDragFrom = null;  // Index of the world tab being dragged
+    let timestampsMode = 'off';  // Time prefix on lines: 'off', 'hm', 'hms' (synced; worlds can override)
+    let timestampsToggled = false;  // toggle_timestamps key, this session only
 
     // MCMP (MUD Client Media Protocol) state
     let mcmpDefaultUrl = '';
     let mcmpMusicPlayer = null;    // { audio, key, name } - one music track at a time
     let mcmpSoundPlayers = {};     // key -> { audio, name }
     let mcmpMusicFadeTimer = null;
+    let bellFlashTimer = null; // Ends a /bell status bar flash
 
     let tlsProxyEnabled = false;  // TLS proxy for connection preservation over hot reload
+    let confirmQuit = true;  // Console asks before quitting with worlds connected
     let tempConvertEnabled = false;  // Temperature conversion (32F -> 32F(0C))
     let mouseEnabled = true;  // Console mouse support
     let debugEnabled = false;  // Debug logging
     let scrollbackEnabled = false;  // Long-term archive output
+    let scrollbackLines = 50000;  // Output lines the server keeps per world
     let dictionaryPath = '';  // Custom dictionary path
     let spellCheckEnabled = true;  // Spell checking
     let prevInputLen = 0;  // Track previous input length for temp conversion
@@ -637,6 +760,32 @@
         }
     }
 
+    // Palettes for /palette, from the server's theme file
+    function setPalettes(jsonStr) {
+        try {
+            palettes = JSON.parse(jsonStr) || {};
+            appliedPalette = null;
+        } catch (e) {
+            // ignore parse errors
+        }
+    }
+
+    // Remap the output's 16 ANSI colors to the world's /palette (or back to the theme's)
+    function applyWorldPalette(world) {
+        const name = (world && world.settings && world.settings.palette) || '';
+        const colors = name && palettes[name] && palettes[name].length === 16 ? palettes[name] : null;
+        const key = colors ? name : '';
+        if (key === appliedPalette) return;
+        appliedPalette = key;
+        for (let i = 0; i < 16; i++) {
+            if (colors) {
+                elements.output.style.setProperty('--theme-ansi-' + i, colors[i]);
+            } else {
+                elements.output.style.removeProperty('--theme-ansi-' + i);
+            }
+        }
+    }
+
     // Apply window transparency (webview mode only)
     // Uses GTK window opacity via IPC — sets _NET_WM_WINDOW_OPACITY on X11.
     // This is compositor-managed (instant, reliable), unlike per-pixel alpha through
@@ -693,11 +842,11 @@
     // Internal commands for tab completion (must match Rust parse_command match arms)
     // This list is verified by test_command_parity_js_vs_rust in main.rs
     const INTERNAL_COMMANDS = [
-        'help', 'version', 'quit', 'reload', 'update', 'setup', 'web', 'actions',
-        'worlds', 'world', 'connections', 'l', 'disconnect', 'dc', 'connect', 'import',
+        'help', 'version', 'quit', 'reload', 'detach', 'update', 'setup', 'web', 'actions',
+        'worlds', 'world', 'connections', 'l', 'disconnect', 'dc', 'connect', 'import', 'export',
         'flush', 'menu', 'send', 'remote', 'ban', 'unban',
         'testmusic', 'dump', 'notify', 'addworld', 'note', 'tag', 'tags',
-        'dict', 'urban', 'translate', 'tr', 'font', 'window',
+        'tagroute', 'tagroutes', 'gag', 'gags', 'ungag', 'profile', 'profiles', 'config', 'numpad', 'secret', 'secrets', 'lock', 'unlock', 'loopguard', 'clipboard', 'throttle', 'urls', 'capture', 'split', 'timestamps', 'bell', 'log', 'replay', 'macro', 'macros', 'dict', 'urban', 'translate', 'tr', 'font', 'window', 'calc', 'roll', 'sendfile', 'shell', 'pipe', 'localecho', 'emoji', 'autocorrect', 'palette', 'tint', 'images', 'addword', 'spellignore', 'unignore', 'spelllang',
     ];
 
     function isInternalCommand(name) {
@@ -758,6 +907,37 @@
         return matches[nextIndex] + args;
     }
 
+    // :name: emoji shortcode completion for the word before the cursor - returns
+    // true if the input was completed. Tab again on a completion cycles the matches.
+    function completeEmojiShortcode() {
+        const input = elements.input.value;
+        const cursor = elements.input.selectionStart;
+        const before = input.substring(0, cursor);
+        const start = before.search(/\S*$/);
+        const word = before.substring(start);
+        let prefix;
+        let nextIndex = 0;
+        const lastMatches = emojiNames.filter(n => (':' + n).startsWith(lastCompletionPrefix));
+        if (lastCompletionPrefix.startsWith(':') && lastCompletionIndex >= 0
+            && word === ':' + lastMatches[lastCompletionIndex] + ':') {
+            prefix = lastCompletionPrefix;
+            nextIndex = (lastCompletionIndex + 1) % lastMatches.length;
+        } else {
+            const m = word.match(/^:([A-Za-z0-9_+-]+)$/);
+            if (!m) return false;
+            prefix = ':' + m[1].toLowerCase();
+        }
+        const matches = emojiNames.filter(n => (':' + n).startsWith(prefix));
+        if (matches.length === 0) return false;
+        const completed = ':' + matches[nextIndex] + ':';
+        elements.input.value = input.substring(0, start) + completed + input.substring(cursor);
+        const pos = start + completed.length;
+        elements.input.setSelectionRange(pos, pos);
+        lastCompletionPrefix = prefix;
+        lastCompletionIndex = nextIndex;
+        return true;
+    }
+
     // Reset completion state (call when input changes by typing)
     function resetCompletion() {
         lastCompletionPrefix = '';
@@ -1224,7 +1404,7 @@
         const path = window.location.pathname || '/';
         const segments = path.split('/').filter(Boolean);
         if (segments.length === 0) return '';
-        const knownLegacyRoots = ['index.html', 'style.css', 'app.js', 'theme-editor',
+        const knownLegacyRoots = ['index.html', 'style.css', 'app.js', 'sw.js', 'theme-editor',
             'keybind-editor', 'action-editor', 'favicon.ico', 'fonts'];
         return knownLegacyRoots.indexOf(segments[0]) === -1 ? '/' + segments[0] : '';
     }
@@ -1271,6 +1451,26 @@
         return result;
     }
 
+    // GUI local mode (master GUI with the web interface off): messages go to the App
+    // over IPC (ws:) and come back through window.clayLocalDeliver. This stands in for
+    // the socket, like the native Android one in handleAttemptWin. The AuthRequest
+    // opens a session that is signed in already, and brings the InitialState.
+    function connectLocal() {
+        ws = {
+            readyState: WebSocket.OPEN,
+            send: function(data) {
+                sendIpc('ws:' + data);
+            },
+            close: function() {
+                this.readyState = WebSocket.CLOSED;
+            }
+        };
+        window.clayLocalDeliver = function(msg) {
+            handleMessage(msg);
+        };
+        handleSocketOpen();
+    }
+
     // Post-open logic shared by native and browser WebSocket winners.
     function handleSocketOpen() {
         if (connectionTimeout) { clearTimeout(connectionTimeout); connectionTimeout = null; }
@@ -1279,7 +1479,7 @@
         setTimeout(hideConnectionLog, 800);
 
         if (window.AUTO_PASSWORD) {
-            ws.send(JSON.stringify({ type: 'AuthRequest', password_hash: window.AUTO_PASSWORD, request_key: false }));
+            ws.send(JSON.stringify({ type: 'AuthRequest', password_hash: window.AUTO_PASSWORD, request_key: false, resume_seqs: resumeSeqs() }));
             return;
         }
 
@@ -1427,13 +1627,7 @@
             if (window.Android && window.Android.stopBackgroundService) {
                 window.Android.stopBackgroundService();
             }
-            const maxFailures = window.WEBVIEW_MODE ? 5 : 2;
-            if (connectionFailures >= maxFailures) {
-                showConnectionLog();
-                enableConnectionLogRetry();
-            } else {
-                setTimeout(connect, 2000);
-            }
+            scheduleReconnect();
         }
     }
 
@@ -1474,13 +1668,68 @@
             return;
         }
 
-        const maxFailures = window.WEBVIEW_MODE ? 5 : 2;
-        if (connectionFailures >= maxFailures) {
-            showConnectionLog();
-            enableConnectionLogRetry();
+        scheduleReconnect();
+    }
+
+    // Retry after a failed attempt or a drop. Before the first sign-in: every 2s, then
+    // the connection log after a few failures. After it: exponential backoff (1s, 2s,
+    // 4s ... 30s) for as long as it takes, under the offline banner - the server resends
+    // only what was missed (resume_seqs), so nothing needs restarting.
+    function scheduleReconnect() {
+        if (!everAuthenticated) {
+            const maxFailures = window.WEBVIEW_MODE ? 5 : 2;
+            if (connectionFailures >= maxFailures) {
+                showConnectionLog();
+                enableConnectionLogRetry();
+            } else {
+                setTimeout(connect, 2000);
+            }
+            return;
+        }
+        if (reconnectTimer) clearTimeout(reconnectTimer);
+        const delay = Math.min(30000, 1000 * Math.pow(2, Math.max(0, connectionFailures - 1)));
+        reconnectAt = Date.now() + delay;
+        reconnectTimer = setTimeout(function() {
+            reconnectTimer = null;
+            connect();
+        }, delay);
+        showOfflineBanner();
+    }
+
+    function showOfflineBanner() {
+        elements.offlineBanner.style.display = '';
+        updateOfflineBanner();
+        if (!offlineBannerInterval) offlineBannerInterval = setInterval(updateOfflineBanner, 1000);
+    }
+
+    function updateOfflineBanner() {
+        let text = 'Connection to the server lost. ';
+        if (reconnectTimer) {
+            const secs = Math.max(0, Math.ceil((reconnectAt - Date.now()) / 1000));
+            text += 'Reconnecting in ' + secs + 's (attempt ' + (connectionFailures + 1) + ').';
         } else {
-            setTimeout(connect, 2000);
+            text += 'Reconnecting...';
         }
+        if (offlineCommands.length > 0) {
+            text += ' ' + offlineCommands.length + (offlineCommands.length === 1 ? ' line' : ' lines') + ' will be sent.';
+        }
+        elements.offlineBannerText.textContent = text;
+    }
+
+    function hideOfflineBanner() {
+        if (reconnectTimer) { clearTimeout(reconnectTimer); reconnectTimer = null; }
+        if (offlineBannerInterval) { clearInterval(offlineBannerInterval); offlineBannerInterval = null; }
+        elements.offlineBanner.style.display = 'none';
+    }
+
+    // Send the lines typed while offline, each to the world it was typed in
+    function flushOfflineCommands() {
+        const queued = offlineCommands;
+        offlineCommands = [];
+        queued.forEach(function(q) {
+            const idx = worlds.findIndex(function(w) { return w && w.name === q.world; });
+            send({ type: 'SendCommand', world_index: idx >= 0 ? idx : currentWorldIndex, command: q.command });
+        });
     }
 
     // Set up native WebSocket callbacks (id-scoped for parallel racing)
@@ -1702,6 +1951,12 @@
             return;
         }
 
+        // GUI local mode: the App is in this process, with no socket to dial
+        if (window.LOCAL_MODE) {
+            connectLocal();
+            return;
+        }
+
         // SSH-tunnel mode (Android only): verify the local tunnel process is actually up
         // before dialing it. If it's dead, Android kicks off a restart (fresh ephemeral
         // port) and pushes it to us via updateSshTunnelPort() -> forceReconnect(); defer
@@ -1803,6 +2058,8 @@
             case 'AuthResponse':
                 if (msg.success) {
                     authenticated = true;
+                    everAuthenticated = true;
+                    hideOfflineBanner();
                     authKeyPending = false;  // Clear key-based auth flag
                     keyAuthFailed = false;   // Reset so key auth works on next fresh connect
                     reloadReconnect = false;
@@ -1811,7 +2068,6 @@
                     multiuserMode = msg.multiuser_mode || false;
                     showAuthModal(false);
                     hideConnectionLog();
-                    hideReconnectModal();
                     elements.authError.textContent = '';
                     elements.input.focus();
                     // Update UI based on multiuser mode
@@ -1906,10 +2162,16 @@
                 actions = [];
                 splashLines = [];
                 authenticated = false;
+                everAuthenticated = false;
+                offlineCommands = [];
+                hideOfflineBanner();
                 // Clear output display
                 if (elements.output) {
                     elements.output.innerHTML = '';
                 }
+                if (elements.input) {
+                    elements.input.placeholder = '';
+                }
                 // Update status bar to show no world
                 updateStatusBar();
                 // Show auth modal again
@@ -1978,6 +2240,7 @@
 
                 actions = msg.actions || [];
                 splashLines = msg.splash_lines || [];
+                emojiNames = msg.emoji_names || [];
                 // Reset client-side more-mode state (each client handles more locally)
                 paused = false;
                 pendingLines = [];
@@ -2000,11 +2263,13 @@
                         // least as complete as the fresh InitialState's front-loaded
                         // slice, and losing it is exactly the bug this preserves.
                         world.output_lines = priorWorld.output_lines;
+                        if (world.resumed) appendNewerLines(world.output_lines, world.output_lines_ts);
                     } else if (cachedWorld && cachedWorld.lines && cachedWorld.lines.length > 0) {
                         // Cold start / full reload with a persistent cache hit: seed
                         // from the cache, then gap-fill (see startBackfill()) to pick
                         // up whatever arrived on the server while we were gone.
                         world.output_lines = cachedWorld.lines.slice();
+                        if (world.resumed) appendNewerLines(world.output_lines, world.output_lines_ts);
                     } else if (world.output_lines_ts && world.output_lines_ts.length > 0) {
                         // Use output_lines_ts if available (has timestamps)
                         world.output_lines = world.output_lines_ts;
@@ -2055,6 +2320,7 @@
                     if (msg.settings.show_tags !== undefined) {
                         showTags = msg.settings.show_tags;
                     }
+                    activeProfile = msg.settings.active_profile || '';
                     if (msg.settings.ansi_music_enabled !== undefined) {
                         ansiMusicEnabled = msg.settings.ansi_music_enabled;
                     }
@@ -2066,9 +2332,18 @@
                     if (msg.settings.new_line_indicator !== undefined) {
                         newLineIndicator = msg.settings.new_line_indicator;
                     }
+                    if (msg.settings.tab_bar !== undefined) {
+                        tabBar = msg.settings.tab_bar;
+                    }
+                    if (msg.settings.timestamps !== undefined) {
+                        timestampsMode = msg.settings.timestamps || 'off';
+                    }
                     if (msg.settings.tls_proxy_enabled !== undefined) {
                         tlsProxyEnabled = msg.settings.tls_proxy_enabled;
                     }
+                    if (msg.settings.confirm_quit !== undefined) {
+                        confirmQuit = msg.settings.confirm_quit;
+                    }
                     if (msg.settings.temp_convert_enabled !== undefined) {
                         tempConvertEnabled = msg.settings.temp_convert_enabled;
                     }
@@ -2081,6 +2356,9 @@
                     if (msg.settings.scrollback_enabled !== undefined) {
                         scrollbackEnabled = msg.settings.scrollback_enabled;
                     }
+                    if (msg.settings.scrollback_lines) {
+                        scrollbackLines = msg.settings.scrollback_lines;
+                    }
                     if (msg.settings.dictionary_path !== undefined) {
                         dictionaryPath = msg.settings.dictionary_path;
                     }
@@ -2128,6 +2406,18 @@
                     if (msg.settings.tls_configured !== undefined) {
                         tlsConfigured = msg.settings.tls_configured;
                     }
+                    if (msg.settings.acme_domain !== undefined) {
+                        acmeDomain = msg.settings.acme_domain;
+                        acmeEmail = msg.settings.acme_email || '';
+                    }
+                    if (msg.settings.web_bind_addr !== undefined) {
+                        webBindAddr = msg.settings.web_bind_addr;
+                        webUnixSocket = msg.settings.web_unix_socket || '';
+                        webTrustedProxies = msg.settings.web_trusted_proxies || '';
+                    }
+                    if (msg.settings.push_public_key !== undefined) {
+                        pushPublicKey = msg.settings.push_public_key;
+                    }
                     if (msg.settings.auth_key !== undefined) {
                         serverAuthKey = msg.settings.auth_key;
                     }
@@ -2151,6 +2441,9 @@
                             if (el) window.Android.saveThemeCss(el.textContent);
                         }
                     }
+                    if (msg.settings.palettes_json) {
+                        setPalettes(msg.settings.palettes_json);
+                    }
                     if (msg.settings.color_offset_percent !== undefined) {
                         colorOffsetPercent = msg.settings.color_offset_percent;
                     }
@@ -2229,35 +2522,8 @@
                     renderOutput();
                 }
 
-                // Handle pending reconnect command (resend after reconnection)
-                if (pendingReconnectCommand !== null) {
-                    // Switch to the world that was active when the command failed
-                    if (pendingReconnectWorldIndex !== null && pendingReconnectWorldIndex !== currentWorldIndex) {
-                        if (pendingReconnectWorldIndex >= 0 && pendingReconnectWorldIndex < worlds.length) {
-                            currentWorldIndex = pendingReconnectWorldIndex;
-                            renderOutput();
-                            updateStatusBar();
-                        }
-                    }
-                    // Resend the command
-                    send({
-                        type: 'SendCommand',
-                        world_index: currentWorldIndex,
-                        command: pendingReconnectCommand
-                    });
-                    // Add to history
-                    if (pendingReconnectCommand.length > 0) {
-                        commandHistory.push(pendingReconnectCommand);
-                        if (commandHistory.length > 1000) {
-                            commandHistory.shift();
-                        }
-                    }
-                    // Clear pending state
-                    pendingReconnectCommand = null;
-                    pendingReconnectWorldIndex = null;
-                    elements.input.value = '';
-                    elements.prompt.textContent = '';
-                }
+                // Lines typed while the connection was down go out now
+                flushOfflineCommands();
                 break;
 
             case 'ServerData':
@@ -2314,6 +2580,7 @@
                         // Client-generated messages (from_server: false) are always complete
                         // Only use partial line handling for MUD server data
                         const isFromServer = msg.from_server !== false;
+                        if (isFromServer && msg.data.trim()) playActivitySound(msg.world_index);
 
                         // Prepend any partial line from previous read (only for server data)
                         let data = msg.data;
@@ -2436,7 +2703,7 @@
 
             case 'CertMismatch':
                 // The MUD server's TLS certificate no longer matches the
-                // trust-on-first-use pin in ~/.clay/known_hosts.dat. The
+                // trust-on-first-use pin in ~/.config/clay/known_hosts.dat. The
                 // connection was blocked server-side; show old vs new
                 // fingerprints and offer to trust the new certificate.
                 showCertMismatchDialog(msg.world_index, msg.host, msg.old_fingerprint, msg.new_fingerprint);
@@ -2518,6 +2785,30 @@
                 }
                 break;
 
+            case 'WorldMoved':
+                // A world tab was dragged to a new position (by any client)
+                if (msg.from < worlds.length && msg.to < worlds.length && msg.from !== msg.to) {
+                    const moved = function(i) { return movedIndex(i, msg.from, msg.to); };
+                    const movedWorld = worlds.splice(msg.from, 1)[0];
+                    worlds.splice(msg.to, 0, movedWorld);
+                    const movedCache = worldOutputCache.splice(msg.from, 1)[0];
+                    worldOutputCache.splice(msg.to, 0, movedCache);
+                    const oldPartial = partialLines;
+                    partialLines = {};
+                    Object.keys(oldPartial).forEach(function(k) {
+                        partialLines[moved(Number(k))] = oldPartial[k];
+                    });
+                    currentWorldIndex = moved(currentWorldIndex);
+                    if (selectedWorldIndex >= 0) selectedWorldIndex = moved(selectedWorldIndex);
+                    backfillWorldQueue = backfillWorldQueue.map(moved);
+                    if (backfillCurrentWorld !== null) backfillCurrentWorld = moved(backfillCurrentWorld);
+                    updateStatusBar();
+                    if (worldSelectorPopupOpen) {
+                        renderWorldSelectorList();
+                    }
+                }
+                break;
+
             case 'WorldSwitched':
                 // Console switched worlds - we ignore this to maintain independent view
                 // Web interface tracks its own current world separately
@@ -2551,8 +2842,8 @@
                 if (msg.world_index >= 0 && msg.world_index < worlds.length) {
                     worlds[msg.world_index].prompt = msg.prompt || '';
                 }
-                // Update display if it's the current world
-                if (msg.world_index === currentWorldIndex) {
+                // Update display if it's the current world (a Ctrl+R search keeps its own)
+                if (msg.world_index === currentWorldIndex && !reverseSearch) {
                     if (msg.prompt) {
                         elements.prompt.innerHTML = sanitizeHtml(parseAnsi(msg.prompt));
                     } else {
@@ -2594,6 +2885,7 @@
                     if (msg.settings.more_mode_enabled !== undefined) {
                         moreModeEnabled = msg.settings.more_mode_enabled;
                     }
+                    activeProfile = msg.settings.active_profile || '';
                     if (msg.settings.show_tags !== undefined) {
                         const oldShowTags = showTags;
                         showTags = msg.settings.show_tags;
@@ -2616,9 +2908,20 @@
                             renderOutput();
                         }
                     }
+                    if (msg.settings.tab_bar !== undefined && msg.settings.tab_bar !== tabBar) {
+                        tabBar = msg.settings.tab_bar;
+                        renderTabBar();
+                    }
+                    if (msg.settings.timestamps !== undefined && msg.settings.timestamps !== timestampsMode) {
+                        timestampsMode = msg.settings.timestamps || 'off';
+                        renderOutput();
+                    }
                     if (msg.settings.tls_proxy_enabled !== undefined) {
                         tlsProxyEnabled = msg.settings.tls_proxy_enabled;
                     }
+                    if (msg.settings.confirm_quit !== undefined) {
+                        confirmQuit = msg.settings.confirm_quit;
+                    }
                     if (msg.settings.temp_convert_enabled !== undefined) {
                         tempConvertEnabled = msg.settings.temp_convert_enabled;
                     }
@@ -2631,6 +2934,9 @@
                     if (msg.settings.scrollback_enabled !== undefined) {
                         scrollbackEnabled = msg.settings.scrollback_enabled;
                     }
+                    if (msg.settings.scrollback_lines) {
+                        scrollbackLines = msg.settings.scrollback_lines;
+                    }
                     if (msg.settings.dictionary_path !== undefined) {
                         dictionaryPath = msg.settings.dictionary_path;
                     }
@@ -2677,6 +2983,18 @@
                     if (msg.settings.tls_configured !== undefined) {
                         tlsConfigured = msg.settings.tls_configured;
                     }
+                    if (msg.settings.acme_domain !== undefined) {
+                        acmeDomain = msg.settings.acme_domain;
+                        acmeEmail = msg.settings.acme_email || '';
+                    }
+                    if (msg.settings.web_bind_addr !== undefined) {
+                        webBindAddr = msg.settings.web_bind_addr;
+                        webUnixSocket = msg.settings.web_unix_socket || '';
+                        webTrustedProxies = msg.settings.web_trusted_proxies || '';
+                    }
+                    if (msg.settings.push_public_key !== undefined) {
+                        pushPublicKey = msg.settings.push_public_key;
+                    }
                     if (msg.settings.auth_key !== undefined) {
                         serverAuthKey = msg.settings.auth_key;
                     }
@@ -2697,6 +3015,9 @@
                             if (el) window.Android.saveThemeCss(el.textContent);
                         }
                     }
+                    if (msg.settings.palettes_json) {
+                        setPalettes(msg.settings.palettes_json);
+                    }
                     if (msg.settings.color_offset_percent !== undefined) {
                         const oldOffset = colorOffsetPercent;
                         colorOffsetPercent = msg.settings.color_offset_percent;
@@ -2799,6 +3120,26 @@
                 }
                 break;
 
+            case 'ActionPatternsTested':
+                showActionTestResult(msg);
+                break;
+
+            case 'GagsUpdated':
+                gags = msg.gags || [];
+                if (gagsPopupOpen) {
+                    selectedGagIndex = Math.min(Math.max(selectedGagIndex, 0), gags.length - 1);
+                    renderGagsList();
+                }
+                break;
+
+            case 'TagRoutesUpdated':
+                tagRoutes = msg.routes || [];
+                if (tagRoutesPopupOpen) {
+                    selectedTagRouteIndex = Math.min(Math.max(selectedTagRouteIndex, 0), tagRoutes.length - 1);
+                    renderTagRoutesList();
+                }
+                break;
+
             case 'CalculatedWorld':
                 // Server calculated next/prev world - switch to it
                 if (msg.index !== null && msg.index !== undefined && msg.index !== currentWorldIndex) {
@@ -2810,7 +3151,27 @@
                 // Another client (console, web, or GUI) has viewed this world
                 if (msg.world_index !== undefined && worlds[msg.world_index]) {
                     worlds[msg.world_index].unseen_lines = 0;
+                    worlds[msg.world_index].bell_count = 0;
                     updateStatusBar();
+                    if (worldSelectorPopupOpen) {
+                        renderWorldSelectorList();
+                    }
+                }
+                break;
+
+            case 'WorldBell':
+                // A bell in a world's output; flash or ring here per its /bell mode
+                // ("notify" arrives separately as a Notification)
+                if (msg.world_index !== undefined && worlds[msg.world_index]) {
+                    worlds[msg.world_index].bell_count = msg.bell_count || 0;
+                    if (worldSelectorPopupOpen) {
+                        renderWorldSelectorList();
+                    }
+                }
+                if (msg.mode === 'flash') {
+                    flashStatusBar();
+                } else if (msg.mode === 'ring') {
+                    ringBell();
                 }
                 break;
 
@@ -2840,6 +3201,38 @@
                 renderOutput();
                 break;
 
+            case 'WorldSettingsUpdated':
+                if (worlds[msg.world_index]) {
+                    const oldSettings = worlds[msg.world_index].settings || {};
+                    if (!!oldSettings.inline_images !== !!(msg.settings && msg.settings.inline_images)) {
+                        worldOutputCache[msg.world_index] = [];
+                    }
+                    worlds[msg.world_index].settings = msg.settings;
+                    if (msg.world_index === currentWorldIndex) renderOutput();
+                }
+                break;
+
+            case 'ActiveProfileChanged':
+                activeProfile = msg.profile || '';
+                updateStatusBar();
+                break;
+
+            case 'ServerEcho':
+                // The server turned its echo off/on (IAC WILL/WONT ECHO): mask a password
+                if (worlds[msg.world_index]) {
+                    worlds[msg.world_index].serverEcho = msg.on;
+                    if (msg.world_index === currentWorldIndex) updateStatusBar();
+                }
+                break;
+
+            case 'SendFileProgress':
+                // /sendfile progress (total 0 = finished or stopped)
+                if (worlds[msg.world_index]) {
+                    worlds[msg.world_index].sendProgress = msg.total > 0 ? { sent: msg.sent, total: msg.total } : null;
+                    if (msg.world_index === currentWorldIndex) updateStatusBar();
+                }
+                break;
+
             case 'PendingLinesUpdate':
                 // Update pending count for a world (used for activity indicator)
                 if (msg.world_index !== undefined && worlds[msg.world_index]) {
@@ -2858,6 +3251,24 @@
                 }
                 break;
 
+            case 'ViewOnly':
+                // Signed in with the viewer password, or something we sent was refused for it
+                appendClientLine(msg.message, currentWorldIndex, 'system');
+                if (elements.input) {
+                    elements.input.placeholder = 'View only';
+                }
+                break;
+
+            case 'DeviceTokens':
+                // Answer to a /web tokens request; new_token is only ever sent to us
+                if (msg.message) {
+                    appendClientLine(msg.message, currentWorldIndex, 'system');
+                }
+                if (!msg.message || deviceTokensDialogOpen) {
+                    showDeviceTokensDialog(msg.tokens || [], msg.new_token || null);
+                }
+                break;
+
             case 'ExecuteLocalCommand':
                 // Server wants us to execute a command locally (from action)
                 if (msg.command) {
@@ -2949,10 +3360,18 @@
                 break;
 
             case 'Notification':
-                // Send notification to Android app if available
+                // Send notification to Android app if available, else a browser
+                // notification while the page is hidden (permission is asked once)
                 if (window.Android && window.Android.showNotification) {
                     window.Android.showNotification(msg.title || 'Clay', msg.message || '');
+                } else if ('Notification' in window && document.hidden) {
+                    if (Notification.permission === 'granted') {
+                        new Notification(msg.title || 'Clay', { body: msg.message || '' });
+                    } else if (Notification.permission === 'default') {
+                        Notification.requestPermission();
+                    }
                 }
+                if (msg.sound && windowUnfocused()) playNotifySound(msg.sound);
                 break;
 
             case 'ServerSpeak':
@@ -3585,6 +4004,37 @@
         });
     }
 
+    // The newest seq held for each world, by name, sent with AuthRequest so a
+    // reconnect's InitialState carries only the lines added since (the world then
+    // comes back with `resumed` set). The same buffers the InitialState handler
+    // keeps: the in-memory one, else the persistent cache.
+    function resumeSeqs() {
+        const seqs = {};
+        worlds.forEach((w) => {
+            if (w && w.name && w.output_lines && w.output_lines.length > 0 && w._max_seq) {
+                seqs[w.name] = w._max_seq;
+            }
+        });
+        Object.keys(worldCacheLoaded).forEach((name) => {
+            const cached = worldCacheLoaded[name];
+            if (!(name in seqs) && cached && cached.lines && cached.lines.length > 0 && cached.maxSeq) {
+                seqs[name] = cached.maxSeq;
+            }
+        });
+        return seqs;
+    }
+
+    // Append a resumed world's InitialState lines to the buffer they carry on from
+    function appendNewerLines(lines, fresh) {
+        let maxSeq = 0;
+        for (const line of lines) {
+            if (line.seq !== undefined && line.seq > maxSeq) maxSeq = line.seq;
+        }
+        (fresh || []).forEach((line) => {
+            if (line.seq === undefined || line.seq > maxSeq) lines.push(line);
+        });
+    }
+
     // Fold a single world into the normal backfill queue (used as a fallback
     // when gap-fill isn't applicable). Safe to call whether or not a backfill
     // pass is already under way for other worlds.
@@ -3605,15 +4055,16 @@
 
         debugLog('tryAuthWithKey: attempting key-based auth');
         authKeyPending = true;
-        // Challenge-response: send SHA256(auth_key + challenge) instead of raw key
+        // Challenge-response: send SHA256(SHA256(auth_key) + challenge) instead of raw key,
+        // as for passwords (the server keeps only a device token's SHA-256)
         let keyValue = authKey;
         let usesChallenge = false;
         if (serverChallenge) {
             try {
-                keyValue = await hashPassword(authKey + serverChallenge);
+                keyValue = await hashPassword(await hashPassword(authKey) + serverChallenge);
                 usesChallenge = true;
             } catch (e) {
-                keyValue = sha256Fallback(authKey + serverChallenge);
+                keyValue = sha256Fallback(sha256Fallback(authKey) + serverChallenge);
                 usesChallenge = true;
             }
         }
@@ -3622,7 +4073,8 @@
             password_hash: '',  // Empty - using key instead
             auth_key: keyValue,
             challenge_response: usesChallenge,
-            request_key: false
+            request_key: false,
+            resume_seqs: resumeSeqs()
         };
         if (currentWorldIndex !== undefined) {
             msg.current_world = currentWorldIndex;
@@ -3691,7 +4143,7 @@
         hashPassword(password).then(async hash => {
             // Challenge-response: SHA256(SHA256(password) + challenge)
             const challengeHash = serverChallenge ? await hashPassword(hash + serverChallenge) : hash;
-            const msg = { type: 'AuthRequest', password_hash: challengeHash, request_key: false, challenge_response: !!serverChallenge };
+            const msg = { type: 'AuthRequest', password_hash: challengeHash, request_key: false, challenge_response: !!serverChallenge, resume_seqs: resumeSeqs() };
             if (username) {
                 msg.username = username;
             }
@@ -3704,7 +4156,7 @@
             // Try fallback directly if hashPassword somehow failed
             const hash = sha256Fallback(password);
             const challengeHash = serverChallenge ? sha256Fallback(hash + serverChallenge) : hash;
-            const msg = { type: 'AuthRequest', password_hash: challengeHash, request_key: false, challenge_response: !!serverChallenge };
+            const msg = { type: 'AuthRequest', password_hash: challengeHash, request_key: false, challenge_response: !!serverChallenge, resume_seqs: resumeSeqs() };
             if (username) {
                 msg.username = username;
             }
@@ -3834,7 +4286,17 @@
     // ExecuteLocalCommand for UI/popup commands.
     function sendCommand() {
         const cmd = elements.input.value;
-        if (!authenticated) return;
+        if (!authenticated && !everAuthenticated) return;
+
+        // Lines entered with Shift+Enter go out as separate commands, in order
+        if (cmd.includes('\n')) {
+            for (const line of cmd.split('\n')) {
+                elements.input.value = line;
+                sendCommand();
+            }
+            fitInputRows();
+            return;
+        }
 
         // Only release held output / reset more-mode state when following live output at
         // the bottom. If the user has scrolled up to read history, keep their position —
@@ -3930,8 +4392,10 @@
         // Intercept /connect in remote WebView mode — this client attaches to/detaches
         // from remote Clay servers directly; never forwarded to the currently-attached
         // server (the master WebView's /connect is handled server-side instead).
+        // MUD links (telnet://, mud://) and /connect -a go to the server like any command.
         if (window.WEBVIEW_MODE && !window.AUTO_PASSWORD &&
-            (cmdTrimmed === '/connect' || cmdTrimmed.startsWith('/connect '))) {
+            (cmdTrimmed === '/connect' || cmdTrimmed.startsWith('/connect ')) &&
+            !/^\/connect\s+((telnet|mud):\/\/|-a$)/i.test(cmdTrimmed)) {
             elements.input.value = '';
             var connectArgs = cmdTrimmed.length > 9 ? cmdTrimmed.substring(9).trim().split(/\s+/).filter(Boolean) : [];
             if (connectArgs.length === 0) {
@@ -3949,13 +4413,17 @@
 
         // Intercept /import — the password/auth-key must be collected client-side (never
         // sent as a bounced command line) and delivered via a dedicated ImportSettings
-        // message instead. See plan i-d-like-to-make-snuggly-rain.md.
+        // message instead. See plan i-d-like-to-make-snuggly-rain.md. /import <file> (a
+        // path, or a .toml/.tfrc/.tf name — same test as is_settings_file_arg in commands.rs) merges
+        // an /export file on the server and goes through as a normal command.
         if (cmdTrimmed === '/import' || cmdTrimmed.startsWith('/import ')) {
-            elements.input.value = '';
             var importArgs = cmdTrimmed.length > 7 ? cmdTrimmed.substring(7).trim().split(/\s+/).filter(Boolean) : [];
-            var importAddr = importArgs.length > 1 ? (importArgs[0] + ':' + importArgs[1]) : (importArgs[0] || '');
-            showImportDialog(importAddr);
-            return;
+            if (!(importArgs.length > 0 && /[\/\\]|\.(toml|tfrc|tf|xml)$/.test(importArgs[0]))) {
+                elements.input.value = '';
+                var importAddr = importArgs.length > 1 ? (importArgs[0] + ':' + importArgs[1]) : (importArgs[0] || '');
+                showImportDialog(importAddr);
+                return;
+            }
         }
 
         const sent = send({
@@ -3965,14 +4433,21 @@
         });
 
         if (!sent) {
-            // Connection lost - show reconnect popup
-            pendingReconnectCommand = cmd;
-            pendingReconnectWorldIndex = currentWorldIndex;
-            showReconnectModal();
-            return;
+            // Offline: keep the line for when the connection is back (flushOfflineCommands)
+            if (offlineCommands.length >= MAX_OFFLINE_COMMANDS) {
+                appendClientLine('Still offline: ' + MAX_OFFLINE_COMMANDS + ' lines are already waiting to be sent.');
+                return;
+            }
+            offlineCommands.push({ world: worlds[currentWorldIndex] ? worlds[currentWorldIndex].name : '', command: cmd });
+            // Authenticated but unable to send: the socket died without a close event
+            if (authenticated) forceReconnect();
+            showOfflineBanner();
         }
 
-        if (cmd.length > 0) {
+        // /secret set, /lock set and /unlock carry a secret value, and a line typed at a
+        // password prompt (server echo off) is one; keep them out of history
+        const masked = !!(worlds[currentWorldIndex] && worlds[currentWorldIndex].serverEcho);
+        if (cmd.length > 0 && !masked && !/^\/(secrets?\s+(set|add)|lock\s+set|unlock\s)/i.test(cmd)) {
             commandHistory.push(cmd);
             if (commandHistory.length > 1000) {
                 commandHistory.shift();
@@ -4203,6 +4678,90 @@
         searchIndex = commandHistory.length;
     }
 
+    // Incremental reverse history search (Ctrl+R), as in the console: start one, or
+    // while one runs, move to the next older entry containing the search text
+    function reverseHistorySearch() {
+        if (!reverseSearch) {
+            reverseSearch = { query: '', index: -1, saved: elements.input.value, failed: false };
+            updateReverseSearchPrompt();
+            return;
+        }
+        reverseSearchFind(reverseSearch.index === -1 ? commandHistory.length : reverseSearch.index);
+    }
+
+    // Show the newest history entry before `before` that contains the search text
+    function reverseSearchFind(before) {
+        const search = reverseSearch;
+        search.failed = false;
+        if (search.query !== '') {
+            search.failed = true;
+            for (let i = Math.min(before, commandHistory.length) - 1; i >= 0; i--) {
+                if (commandHistory[i].includes(search.query)) {
+                    search.index = i;
+                    search.failed = false;
+                    elements.input.value = commandHistory[i];
+                    const at = commandHistory[i].indexOf(search.query);
+                    elements.input.selectionStart = elements.input.selectionEnd = at;
+                    fitInputRows();
+                    break;
+                }
+            }
+        }
+        updateReverseSearchPrompt();
+    }
+
+    function updateReverseSearchPrompt() {
+        elements.prompt.textContent = '(' + (reverseSearch.failed ? 'failed ' : '') +
+            'reverse-i-search)`' + reverseSearch.query + "': ";
+    }
+
+    // End a reverse search, keeping the match (accept) or putting back the input it
+    // started from
+    function endReverseSearch(accept) {
+        if (!reverseSearch) return;
+        if (!accept) {
+            elements.input.value = reverseSearch.saved;
+            elements.input.selectionStart = elements.input.selectionEnd = elements.input.value.length;
+            fitInputRows();
+        }
+        reverseSearch = null;
+        elements.prompt.textContent = '';
+        redisplayCurrentPrompt();
+    }
+
+    // Feed a keydown to a running reverse search. Returns true if the search used it;
+    // any other key ends the search with the match left in the input, to be handled as
+    // usual (Enter sends it).
+    function reverseSearchKey(e, keyName) {
+        if (keyName === '^R') {
+            reverseHistorySearch();
+        } else if (keyName === '^G' || e.key === 'Escape') {
+            endReverseSearch(false);
+        } else if (e.key === 'Backspace' && !e.ctrlKey && !e.altKey) {
+            reverseSearch.query = Array.from(reverseSearch.query).slice(0, -1).join('');
+            reverseSearchFind(commandHistory.length);
+        } else if (e.key.length === 1 && !e.ctrlKey && !e.altKey && !e.metaKey) {
+            reverseSearch.query += e.key;
+            // The entry shown may still match the longer text
+            reverseSearchFind(reverseSearch.index === -1 ? commandHistory.length : reverseSearch.index + 1);
+        } else if (e.key !== 'Shift' && e.key !== 'Control' && e.key !== 'Alt' && e.key !== 'Meta') {
+            endReverseSearch(true);
+            return false;
+        }
+        return true;
+    }
+
+    // Grow the input to show every line typed with Shift+Enter (up to 15 rows), and
+    // back down to the /setup input height once they're sent
+    function fitInputRows() {
+        const rows = Math.max(inputHeight, Math.min(15, elements.input.value.split('\n').length));
+        if (elements.input.rows !== rows) {
+            const lineHeight = 1.2 * (currentFontSize || 14);
+            elements.input.style.height = (rows * lineHeight) + 'px';
+            elements.input.rows = rows;
+        }
+    }
+
     // Send selective flush command
     function selectiveFlush() {
         if (ws && ws.readyState === WebSocket.OPEN) {
@@ -4237,8 +4796,27 @@
                 openActionsListPopup(args.join(' ') || null);
                 break;
 
+            case '/gag':
+            case '/gags':
+                // The server bounces only a bare /gag here
+                openGagsPopup();
+                break;
+
+            case '/tagroute':
+            case '/tagroutes':
+                // The server bounces only a bare /tagroute here
+                openTagRoutesPopup();
+                break;
+
             case '/web':
-                openSettingsPopup('web');
+                if (args.length > 0 && args[0].toLowerCase() === 'tokens') {
+                    webTokensCommand(args.slice(1));
+                } else if (args.length === 2 && args[0].toLowerCase() === 'push') {
+                    // The server bounces only /web push on|off here
+                    webPushCommand(args[1].toLowerCase() === 'on');
+                } else {
+                    openSettingsPopup('web');
+                }
                 break;
 
             case '/setup':
@@ -4414,11 +4992,18 @@
     }
 
     // Search popup functions (F5)
-    function openSearchPopup() {
+    function openSearchPopup(inPlace) {
         searchPopupOpen = true;
+        searchInPlace = !!inPlace;
+        searchNavigating = false;
+        searchHighlightRegex = null;
         searchText = '';
         searchMatchIndices = [];
         searchCurrentPos = -1;
+        if (elements.searchPopupTitle) {
+            elements.searchPopupTitle.textContent = searchInPlace
+                ? 'Search Scrollback [Enter=n/N]' : 'Search History [Enter=older]';
+        }
         elements.searchPopup.style.display = 'block';
         elements.searchInput.value = '';
         if (elements.searchMatchInfo) elements.searchMatchInfo.textContent = '';
@@ -4427,12 +5012,71 @@
 
     function closeSearchPopup() {
         searchPopupOpen = false;
+        searchNavigating = false;
+        searchHighlightRegex = null;
         searchText = '';
         searchMatchIndices = [];
         searchCurrentPos = -1;
         elements.searchPopup.style.display = 'none';
         elements.input.focus();
         renderOutput();
+        updateStatusBar();
+    }
+
+    // Enter in a scrollback search: hide the prompt, keep the hits, and let n/N step
+    function startSearchNavigation() {
+        if (!searchText) { closeSearchPopup(); return; }
+        searchNavigating = true;
+        elements.searchPopup.style.display = 'none';
+        elements.input.focus();
+        updateStatusBar();
+    }
+
+    // Keys while navigating a scrollback search. n/N and / only act on an empty input
+    // line; Esc ends the search; any other key ends it and is handled normally.
+    function handleSearchNavKey(e) {
+        if (['Shift', 'Control', 'Alt', 'Meta'].includes(e.key)) return false;
+        const plain = !e.ctrlKey && !e.altKey && !e.metaKey;
+        const empty = elements.input.value === '';
+        if (plain && empty && e.key === 'n') {
+            advanceSearch();
+        } else if (plain && empty && e.key === 'N') {
+            retreatSearch();
+        } else if (plain && empty && e.key === '/') {
+            searchNavigating = false;
+            elements.searchPopup.style.display = 'block';
+            elements.searchInput.focus();
+        } else if (e.key === 'Escape') {
+            closeSearchPopup();
+        } else {
+            closeSearchPopup();
+            return false;
+        }
+        e.preventDefault();
+        e.stopPropagation();
+        return true;
+    }
+
+    // Regex marking search hits in rendered HTML. Long words get zero-width spaces from
+    // insertWordBreaks, so allow one between any two characters of a plain query.
+    function buildSearchHighlightRegex(text) {
+        if (!text || /[<>&"']/.test(text)) return null;
+        if (text.includes('*') || text.includes('?')) {
+            const re = filterWildcardToRegex(text);
+            return re ? new RegExp(re.source, 'gi') : null;
+        }
+        const source = Array.from(text)
+            .map(c => c.replace(/[.*+?^${}()|[\]\\]/g, '\\$&'))
+            .join('\u200B?');
+        return new RegExp(source, 'gi');
+    }
+
+    // Wrap search hits in <mark>, touching only text between tags and entities
+    function highlightSearchHits(html) {
+        if (!searchHighlightRegex) return html;
+        return html.split(/(<[^>]*>|&[#\w]+;)/).map((part, i) =>
+            i % 2 === 1 ? part : part.replace(searchHighlightRegex, '<mark class="search-hit">$&</mark>')
+        ).join('');
     }
 
     function computeSearchMatches() {
@@ -4454,7 +5098,15 @@
         searchCurrentPos = searchMatchIndices.length > 0 ? searchMatchIndices.length - 1 : -1;
     }
 
+    // "match X of Y" for the status bar while a search is showing
+    function searchStatusText() {
+        if (!searchPopupOpen || !searchText) return '';
+        if (searchMatchIndices.length === 0) return 'no matches';
+        return 'match ' + (searchCurrentPos + 1) + ' of ' + searchMatchIndices.length;
+    }
+
     function updateSearchMatchInfo() {
+        updateStatusBar();
         if (!elements.searchMatchInfo) return;
         if (!searchText) {
             elements.searchMatchInfo.textContent = '';
@@ -4467,6 +5119,7 @@
 
     function updateSearch() {
         searchText = elements.searchInput.value;
+        searchHighlightRegex = buildSearchHighlightRegex(searchText);
         computeSearchMatches();
         updateSearchMatchInfo();
         renderOutput();
@@ -4483,6 +5136,18 @@
         renderOutput();
     }
 
+    // Step to the next newer match (N while navigating)
+    function retreatSearch() {
+        if (searchMatchIndices.length === 0) return;
+        if (searchCurrentPos < searchMatchIndices.length - 1) {
+            searchCurrentPos++;
+        } else {
+            searchCurrentPos = 0;
+        }
+        updateSearchMatchInfo();
+        renderOutput();
+    }
+
     // Help popup functions (/help)
     // Help content as structured sections: [heading, [left, right], ...]
     // Empty right = continuation line; null right = section heading
@@ -4494,25 +5159,69 @@
             { l: '/worlds -e [name]', r: 'Edit world settings' },
             { l: '/worlds -l &lt;name&gt;', r: 'Connect without auto-login' },
             { l: '/disconnect (or /dc)', r: 'Disconnect from server' },
+            { l: '/disconnect -a', r: 'Disconnect every world' },
+            { l: '/connect -a', r: 'Connect every world with auto-login on' },
             { l: '/connections (or /l)', r: 'List connected worlds' },
             { l: '/connect &lt;host[:port]&gt;', r: 'Attach to a remote Clay server' },
             { l: '/connect --close', r: 'Detach and become an independent master' },
+            { l: '/connect telnet://host:port', r: 'Open a MUD link (also mud://, ?tls=1)' },
             { heading: 'Communication' },
             { l: '/send [-W] [-w&lt;world&gt;] [-n] &lt;text&gt;', r: 'Send text to world(s)' },
             { l: '', r: '-W=all worlds, -n=no newline' },
-            { l: '/notify &lt;message&gt;', r: 'Send notification to mobile' },
+            { l: '/notify &lt;message&gt;', r: 'Send notification to mobile/desktop' },
+            { l: '/notify -w[world] mute|unmute', r: 'Mute a world\'s notifications' },
+            { l: '/notify -w[world] sound [file|off]', r: 'Sound for a world\'s activity while unfocused' },
             { heading: 'Lookup &amp; Translation' },
             { l: '/dict &lt;prefix&gt; &lt;word&gt;', r: 'Look up word definition' },
             { l: '/urban &lt;prefix&gt; &lt;word&gt;', r: 'Look up Urban Dictionary' },
             { l: '/translate &lt;lang&gt; &lt;prefix&gt; &lt;text&gt;', r: 'Translate text (or /tr)' },
+            { l: '/calc [-s] &lt;expr&gt;', r: 'Evaluate arithmetic (-s sends the result)' },
+            { l: '/roll [-s] &lt;dice&gt;', r: 'Roll dice, e.g. 3d6+2 or 4d6kh3' },
             { heading: 'Actions &amp; Triggers' },
             { l: '/actions [world]', r: 'Open actions editor' },
-            { l: '/gag [pattern]', r: 'List gags, or gag lines matching pattern' },
+            { l: '/gag [-w&lt;world&gt;] [-t] &lt;pattern&gt;', r: 'Gag matching lines (-t: this session only)' },
+            { l: '/gag', r: 'Open gags list (toggle, keep, delete)' },
+            { l: '/ungag &lt;n|pattern&gt;', r: 'Remove a gag' },
             { l: '/&lt;action_name&gt; [args]', r: 'Execute named action' },
             { heading: 'Settings' },
             { l: '/setup', r: 'Open global settings' },
             { l: '/web', r: 'Open web/WebSocket settings' },
+            { l: '/web tokens [add|revoke]', r: 'Revocable per-device auth keys' },
             { l: '/tag', r: 'Toggle MUD tag display (F2)' },
+            { l: '/tagroute [add|del|on|off]', r: 'Route lines by MUD tag' },
+            { l: '/tagroute', r: 'Open tag routes list (toggle, delete)' },
+            { l: '/profile [add|del]', r: 'Time-scheduled setting profiles' },
+            { l: '/config profile', r: 'Show config profiles (--profile=<name>)' },
+            { l: '/numpad [on|off]', r: 'Keypad 1-9 speedwalk' },
+            { l: '/secret [set|del]', r: 'Secrets for ${secret:NAME} in logins/actions' },
+            { l: '/loopguard [N|off]', r: 'Disable actions looping over N fires/sec' },
+            { l: '/clipboard [cmd|osc52]', r: 'How console copy mode (F7) copies' },
+            { l: '/throttle [on|off]', r: 'Batch a low-priority background world' },
+            { l: '/urls', r: 'List recent URLs in this world' },
+            { l: '/capture [clear <name>]', r: 'Capture buffers filled by actions' },
+            { l: '/split <world>', r: 'Show worlds side by side (console)' },
+            { l: '/timestamps [off|hm|hms]', r: 'Time prefix on output lines' },
+            { l: '/bell [ignore|flash|ring|notify]', r: 'What a bell in output does' },
+            { l: '/log [start [file]|stop|flush]', r: 'Start or stop logging a world' },
+            { l: '/replay <file> [speed]', r: 'Show a log as if received again' },
+            { l: '/sendfile <file> | abort', r: 'Send a file to the world line by line' },
+            { l: '/shell <command>', r: 'Run a program and show its output' },
+            { l: '/pipe <command>', r: 'Run a program and send its output' },
+            { l: '/localecho [on|off]', r: 'Show the lines you send in the output' },
+            { l: '/emoji [on|off]', r: 'Turn typed :name: shortcodes into emoji (Tab completes)' },
+            { l: '/autocorrect [on|off]', r: 'Correct a misspelled word when you type a space after it' },
+            { l: '/addword <word>', r: 'Add a word to the spell check dictionary' },
+            { l: '/spellignore [word]', r: 'Never flag a word, without adding it to the dictionary' },
+            { l: '/unignore <word>', r: 'Spell check an ignored word again' },
+            { l: '/spelllang [language|off]', r: 'Spell check this world\'s input in another language (de_DE, ...)' },
+            { l: '/palette [name|off]', r: 'Remap this world\'s 16 ANSI colors (solarized, gruvbox, ...)' },
+            { l: '/tint [#rrggbb|off]', r: 'Background color behind this world\'s output' },
+            { l: '/images [on|off]', r: 'Show image links in this world\'s output inline' },
+            { l: '/export <file> [passphrase]', r: 'Save worlds, actions, keybindings' },
+            { l: '/import <file> [passphrase]', r: 'Merge an /export file' },
+            { l: '/lock [set <passphrase>|off]', r: 'Lock world credentials (master passphrase)' },
+            { l: '/unlock <passphrase>', r: 'Unlock world credentials' },
+            { l: '/macro [key [text]]', r: 'Per-world function key macros' },
             { heading: 'Display' },
             { l: '/menu', r: 'Open menu popup' },
             { l: '/flush', r: 'Clear output buffer' },
@@ -4522,10 +5231,15 @@
             { l: '/help [topic]', r: 'Show help (topic = command)' },
             { l: '/version', r: 'Show version info' },
             { l: '/reload', r: 'Hot reload binary' },
+            { l: '/detach', r: 'Leave console session in background' },
             { l: '/testmusic', r: 'Test ANSI music playback' },
             { l: '/quit', r: 'Exit client' },
             { heading: 'Security' },
             { l: '/ban', r: 'Show banned hosts' },
+            { l: '/web bans', r: 'Bans, strikes, and limits' },
+            { l: '/web viewer &lt;pw&gt;|off', r: 'View-only sign-in password' },
+            { l: '/web acme &lt;domain&gt;|renew|off', r: "Let's Encrypt certificate" },
+            { l: '/web push on|off', r: 'Activity pushes to this browser' },
             { l: '/unban &lt;host&gt;', r: 'Remove host from ban list' },
         ]},
         { heading: 'TF Commands', rows: [
@@ -4564,6 +5278,7 @@
             { l: 'F2', r: 'Toggle MUD tag display' },
             { l: 'F4', r: 'Filter output' },
             { l: 'F5', r: 'Search history' },
+            { l: 'Ctrl+F', r: 'Search scrollback (n/N to step)' },
             { l: 'F8', r: 'Highlight action matches' },
             { l: 'F9', r: 'Toggle GMCP media audio' },
         ]},
@@ -4628,12 +5343,24 @@
             '  arrives. Keeps you from missing important text.', '',
             'TLS Proxy: Keeps a proxy alive during hot reload', '  so TLS connections survive.', '',
             'New Indicator: Show a marker on new lines arriving', '  while scrolled up in the output buffer.', '',
-            'Debug: Enables debug logging to ~/.clay/debug.log.', '',
+            'Debug: Enables debug logging to ~/.config/clay/debug.log.', '',
             'ANSI Music: Play ANSI music sequences from MUDs.', '',
             'ZWJ Sequence: For terminals that support combined',
             '  emoji (ZWJ). If unsupported, shows two separate',
             '  emoji instead of one combined one.'
         ],
+        sounds: [
+            'Sounds - Per-World Notification Sounds', '',
+            'Each world can have a sound that plays when it has',
+            'output while this window is unfocused (once until',
+            'you come back), and when /notify fires for it.', '',
+            'The GUI plays a file on this computer (~/ is your',
+            'home directory); a browser needs an http(s) URL.',
+            'Clear the field to turn a world\'s sound off.', '',
+            'A trigger can play its own sound instead:',
+            '  /notify -s<file> <message>', '',
+            'Same as /notify -w<world> sound <file>|off.'
+        ],
         web: [
             'Web Settings - Remote Access', '',
             'These settings let you access Clay from a web',
@@ -4663,7 +5390,11 @@
             'Keep Alive: Prevents idle disconnects.',
             '  NOP: Sends a telnet NOP (invisible to server).',
             '  Custom: Sends a custom command you specify.', '',
-            'Encoding: UTF-8 (modern), Latin-1 (older MUDs), FANSI.', '',
+            'Encoding: UTF-8 (modern), Latin-1 (older MUDs), FANSI,',
+            '  CP437 (box drawing), CP850, KOI8-R, Windows-1252,',
+            '  Big5, GBK (Chinese), Shift-JIS (Japanese).',
+            '  Typed text is sent in it too; smart quotes and',
+            '  dashes a code page lacks go as ASCII.', '',
             'GMCP: Space-separated GMCP packages to request.'
         ],
         worldSelector: [
@@ -4684,11 +5415,40 @@
             'action\'s command is executed.', '',
             'Click an action to edit it. Use the toggle to',
             'enable or disable actions.', '',
+            'Actions are checked top to bottom: move the selected',
+            'one with \u25B2/\u25BC or Shift+Up/Down.', '',
             'Add: Create a new action.',
+            'Duplicate: Copy the selected action.',
             'Edit: Edit the selected action.',
-            'Delete: Remove the selected action.', '',
+            'Delete: Remove the selected action.',
+            'Import: Merge a JSON or TOML trigger pack.',
+            'Export: Save the listed actions as a JSON pack.', '',
             'Use the filter to search by name, world, or pattern.'
         ],
+        gags: [
+            'Gags - Hide Matching Lines', '',
+            'Each gag hides lines matching its wildcard pattern',
+            '(* and ?, whole line, any case). Gagged lines are',
+            'still visible with F2.', '',
+            'Kept: saved gags last; session gags are forgotten',
+            'when Clay exits (/gag -t).', '',
+            'Add: Start a /gag [-w<world>] [-t] <pattern> command.',
+            'Toggle: Enable or disable the selected gag (Space).',
+            'Keep: Save a session gag, or make a saved one',
+            '  session-only.',
+            'Delete: Remove the selected gag (Del).'
+        ],
+        tagRoutes: [
+            'Tag Routes - Route Lines by MUD Tag', '',
+            'Each route matches lines by their leading MUD tag,',
+            'e.g. [public:] or [chat(Bob)].', '',
+            'Actions: gag, gag-idle:MINS, copy:WORLD,',
+            '  move:WORLD, hilite:COLOR', '',
+            'Add: Start a /tagroute add [-w<world>] <tag> <action>',
+            '  command.',
+            'Toggle: Enable or disable the selected route (Space).',
+            'Delete: Remove the selected route (Del).'
+        ],
         actionEditor: [
             'Action Editor - Configure a Trigger', '',
             'Name: A unique name for this action.', '',
@@ -4704,7 +5464,11 @@
             '  /gag hides the matched line.',
             '  /notify sends a push notification.', '',
             'Enabled: Whether this action is active.', '',
-            'Startup: Run command when Clay starts/hot-reloads.'
+            'Startup: Run command when Clay starts/hot-reloads.', '',
+            'Test: A sample line to try the patterns on as you',
+            '  type: shows the matching pattern, its $0..$9 captures',
+            '  and whether /gag would hide it. A pattern that',
+            "  doesn't compile is shown in red and can't be saved."
         ],
         connections: [
             'Connected Worlds - Active Connections', '',
@@ -4943,7 +5707,21 @@
             return;
         }
 
+        applyWorldPalette(world);
+        // The world's /tint shows behind any text the MUD doesn't give a background
+        const tint = (world && world.settings && world.settings.tint) || '';
+        if ((elements.output.dataset.tint || '') !== tint) {
+            elements.output.dataset.tint = tint;
+            elements.output.style.backgroundColor = tint;
+        }
+        // The browser spell-checks the input in the world's /spelllang language
+        const spellLang = ((world && world.settings && world.settings.spell_language) || '').replace(/_/g, '-');
+        if (elements.input.lang !== spellLang) elements.input.lang = spellLang;
+        // Browsers that autocorrect (Safari, mobile keyboards) follow /autocorrect
+        const autocorrect = world && world.settings && world.settings.autocorrect ? 'on' : 'off';
+        if (elements.input.getAttribute('autocorrect') !== autocorrect) elements.input.setAttribute('autocorrect', autocorrect);
         const lines = world.output_lines || [];
+        const tsMode = timestampModeFor(currentWorldIndex);
 
         // When search popup is active and has a match, truncate output at the match line
         // so the matched line appears at the bottom of the output area
@@ -4997,13 +5775,13 @@
                 }
             }
 
-            // Format timestamp prefix if showTags is enabled
-            const tsPrefix = showTags && lineTs ? `<span class="timestamp">${formatTimestamp(lineTs)}</span>` : '';
+            // Format timestamp prefix if showTags is enabled, else the /timestamps clock
+            const tsPrefix = showTags && lineTs ? `<span class="timestamp">${formatTimestamp(lineTs)}</span>` : formatClockPrefix(lineTs, tsMode);
 
             const strippedText = showTags ? cleanLine : stripMudTag(cleanLine);
             const displayText = showTags && tempConvertEnabled ? convertTemperatures(strippedText) : strippedText;
             // Skip Discord emoji conversion when showTags is enabled so users can see original text
-            const processed = linkifyUrls(parseAnsi(insertWordBreaks(displayText)));
+            const processed = highlightSearchHits(inlineImages(linkifyUrls(parseAnsi(insertWordBreaks(displayText))), currentWorldIndex));
             const newLinePrefix = (newLineIndicator && lineMarkedNew) ? '<span style="color:#00ff00;">▶</span> ' : '';
             const archivePrefix = lineFromArchive ? '🛢️ ' : '';
             let html = tsPrefix + newLinePrefix + archivePrefix + (showTags ? processed : convertDiscordEmojis(processed));
@@ -5043,7 +5821,7 @@
         const strippedText = showTags ? text : stripMudTag(text);
         const displayText = showTags && tempConvertEnabled ? convertTemperatures(strippedText) : strippedText;
         // Skip Discord emoji conversion when showTags is enabled so users can see original text
-        const processed = linkifyUrls(parseAnsi(insertWordBreaks(displayText)));
+        const processed = inlineImages(linkifyUrls(parseAnsi(insertWordBreaks(displayText))), worldIndex);
         const html = sanitizeHtml(showTags ? processed : convertDiscordEmojis(processed));
         worldOutputCache[worldIndex][lineIndex] = { html, showTags };
         return html;
@@ -5078,13 +5856,13 @@
         // Strip newlines/carriage returns
         const cleanText = String(text).replace(/[\r\n]+/g, '');
 
-        // Format timestamp prefix if showTags is enabled
-        const tsPrefix = showTags && ts ? `<span class="timestamp">${formatTimestamp(ts)}</span>` : '';
+        // Format timestamp prefix if showTags is enabled, else the /timestamps clock
+        const tsPrefix = showTags && ts ? `<span class="timestamp">${formatTimestamp(ts)}</span>` : formatClockPrefix(ts, timestampModeFor(worldIndex));
 
         const strippedText = showTags ? cleanText : stripMudTag(cleanText);
         const displayText = showTags && tempConvertEnabled ? convertTemperatures(strippedText) : strippedText;
         // Skip Discord emoji conversion when showTags is enabled so users can see original text
-        const processed = linkifyUrls(parseAnsi(insertWordBreaks(displayText)));
+        const processed = inlineImages(linkifyUrls(parseAnsi(insertWordBreaks(displayText))), worldIndex);
         const newLinePrefix = (newLineIndicator && markedNew) ? '<span style="color:#00ff00;">▶</span> ' : '';
         const html = tsPrefix + newLinePrefix + (showTags ? processed : convertDiscordEmojis(processed));
 
@@ -5120,7 +5898,8 @@
             return ''; // Strip other CSI sequences
         });
 
-        // Read ANSI 16-color palette from CSS theme variables (set by server)
+        // Read ANSI 16-color palette from CSS theme variables (set by server, and
+        // overridden on the output element by the world's /palette)
         function getThemeAnsiPalette() {
             const fallback = [
                 [0, 0, 0], [170, 0, 0], [68, 170, 68], [170, 85, 0],
@@ -5128,7 +5907,7 @@
                 [119, 119, 119], [255, 135, 135], [76, 230, 76], [222, 216, 44],
                 [41, 95, 204], [204, 88, 204], [76, 204, 230], [255, 255, 255]
             ];
-            const style = getComputedStyle(document.documentElement);
+            const style = getComputedStyle(elements.output);
             const palette = [];
             for (let i = 0; i < 16; i++) {
                 const val = style.getPropertyValue('--theme-ansi-' + i).trim();
@@ -5668,6 +6447,53 @@
         return true;
     }
 
+    function windowUnfocused() {
+        return document.hidden || !document.hasFocus();
+    }
+
+    // Play a notification sound (/notify -w sound, /notify -s): the GUI plays files on
+    // its own machine, a browser only http(s) URLs
+    function playNotifySound(sound) {
+        if (/^https?:\/\//i.test(sound)) {
+            const audio = new Audio(sound);
+            audio.play().catch(function() {});
+        } else if (window.WEBVIEW_MODE) {
+            sendIpc('play-sound:' + sound);
+        }
+    }
+
+    // A world's sound for output while the window is unfocused: once per world until
+    // the window has focus again, as the console's Notify Activity does
+    function playActivitySound(worldIndex) {
+        const world = worlds[worldIndex];
+        const settings = world && world.settings;
+        if (!settings || !settings.notify_sound || settings.notify_muted) return;
+        if (!windowUnfocused() || activitySoundWorlds.has(worldIndex)) return;
+        activitySoundWorlds.add(worldIndex);
+        playNotifySound(settings.notify_sound);
+    }
+
+    // Bell (see /bell): invert the status bar briefly, or play a short beep
+    function flashStatusBar() {
+        if (!elements.statusBar) return;
+        elements.statusBar.classList.add('bell-flash');
+        clearTimeout(bellFlashTimer);
+        bellFlashTimer = setTimeout(() => elements.statusBar.classList.remove('bell-flash'), 600);
+    }
+
+    function ringBell() {
+        if (!ensureAudioContext()) return;
+        const osc = audioContext.createOscillator();
+        const gain = audioContext.createGain();
+        osc.frequency.value = 880;
+        gain.gain.setValueAtTime(0.15, audioContext.currentTime);
+        gain.gain.exponentialRampToValueAtTime(0.001, audioContext.currentTime + 0.15);
+        osc.connect(gain);
+        gain.connect(audioContext.destination);
+        osc.start();
+        osc.stop(audioContext.currentTime + 0.15);
+    }
+
     function handleMcmpMedia(action, dataStr, defaultUrl) {
         let data;
         try {
@@ -5865,6 +6691,19 @@
         });
     }
 
+    // In a world with /images on, follow each image link (from linkifyUrls) with the
+    // picture itself: size-capped by style.css and, being a link too, opened on click
+    const IMAGE_URL_PATTERN = /\.(?:png|jpe?g|gif|webp|bmp)(?:[?#][^"]*)?$/i;
+    function inlineImages(html, worldIndex) {
+        const world = worlds[worldIndex];
+        if (!world || !world.settings || !world.settings.inline_images) return html;
+        return html.replace(/<a href="(https?:\/\/[^"]+)" target="_blank" rel="noopener" class="output-link">.*?<\/a>/g, function(link, href) {
+            if (!IMAGE_URL_PATTERN.test(href)) return link;
+            return link + `<a href="${href}" target="_blank" rel="noopener" class="output-link inline-image-link">` +
+                `<img class="inline-image" src="${href}" loading="lazy" alt=""></a>`;
+        });
+    }
+
     // Format a timestamp for display
     // Returns "MM/DD HH:MM>" timestamp prefix
     function formatTimestamp(ts) {
@@ -5882,6 +6721,24 @@
         return `${month}/${day} ${hours}:${minutes}> `;
     }
 
+    // Timestamp prefix mode for a world: its own setting, else the global one,
+    // inverted by the toggle_timestamps key (mirrors App::timestamp_mode)
+    function timestampModeFor(worldIndex) {
+        const w = worlds[worldIndex];
+        const mode = (w && w.settings && w.settings.timestamps) || timestampsMode || 'off';
+        if (timestampsToggled) return mode === 'off' ? 'hm' : 'off';
+        return mode;
+    }
+
+    // "HH:MM" / "HH:MM:SS" prefix for a line (display only), or '' when off
+    function formatClockPrefix(ts, mode) {
+        if (!ts || mode === 'off') return '';
+        const date = new Date(ts * 1000);
+        let clock = date.getHours().toString().padStart(2, '0') + ':' + date.getMinutes().toString().padStart(2, '0');
+        if (mode === 'hms') clock += ':' + date.getSeconds().toString().padStart(2, '0');
+        return `<span class="clock-prefix">${clock}</span> `;
+    }
+
     // Convert a color name to CSS color value (for /highlight command)
     // Supports named colors, RGB values, and xterm 256-color codes
     function colorNameToCss(color) {
@@ -6112,29 +6969,191 @@
         if (serverActivityCount > 0) {
             elements.activityCount.textContent = serverActivityCount;
             elements.activityIndicator.style.display = '';
-            // Build tooltip listing worlds with activity
+            // Build tooltip listing worlds with activity, and the same summary as
+            // the console's status bar: "mume(12) ach(3*)", * = more-mode lines
             const activeWorlds = worlds
-                .filter((w, i) => i !== currentWorldIndex && ((w.unseen_lines || 0) > 0 || (w.pending_count || 0) > 0))
-                .map(w => w.name);
+                .filter((w, i) => i !== currentWorldIndex && ((w.unseen_lines || 0) > 0 || (w.pending_count || 0) > 0));
             elements.activityIndicator.title = activeWorlds.length > 0
-                ? 'Unseen: ' + activeWorlds.join(', ')
+                ? 'Unseen: ' + activeWorlds.map(w => w.name).join(', ')
                 : '';
+            if (elements.activityWorlds) {
+                elements.activityWorlds.textContent = activeWorlds.map(w => {
+                    const pending = w.pending_count || 0;
+                    return w.name + '(' + Math.max(w.unseen_lines || 0, pending) + (pending > 0 ? '*' : '') + ')';
+                }).join(' ');
+            }
         } else {
             elements.activityIndicator.style.display = 'none';
             elements.activityIndicator.title = '';
+            if (elements.activityWorlds) elements.activityWorlds.textContent = '';
         }
 
+        // A password prompt (server echo off) masks the input
+        const masked = !!(worlds[currentWorldIndex] && worlds[currentWorldIndex].serverEcho);
+        elements.input.classList.toggle('masked', masked);
+        elements.input.spellcheck = !masked;
+
+        // /sendfile progress for this world
+        if (elements.statusSending) {
+            const world = worlds[currentWorldIndex];
+            const progress = world && world.sendProgress;
+            elements.statusSending.textContent = progress ? '[Sending ' + progress.sent + '/' + progress.total + ']' : '';
+            elements.statusSending.style.display = progress ? '' : 'none';
+        }
+
+        // Output search position (Ctrl+F / F5)
+        if (elements.statusSearch) {
+            const searchStatus = searchStatusText();
+            elements.statusSearch.textContent = searchStatus ? '[' + searchStatus + ']' : '';
+            elements.statusSearch.style.display = searchStatus ? '' : 'none';
+        }
+
+        // Scheduled profile badge (see /profile)
+        if (elements.statusProfile) {
+            elements.statusProfile.textContent = activeProfile ? '[' + activeProfile + ']' : '';
+            elements.statusProfile.style.display = activeProfile ? '' : 'none';
+        }
+
+        renderTabBar();
+        updateTray();
         updateScrollbackProgress();
     }
 
-    // Update time (12-hour format H:MM, no AM/PM)
-    function updateTime() {
-        const now = new Date();
-        let hours = now.getHours() % 12;
-        if (hours === 0) hours = 12;
-        const minutes = now.getMinutes().toString().padStart(2, '0');
-        elements.statusTime.textContent = `${hours}:${minutes}`;
-    }
+    // GUI tray icon (tray feature): report each world's unseen count and notification
+    // mute when they change; its menu calls back through window.trayAction
+    let lastTrayState = '';
+    function updateTray() {
+        if (!window.WEBVIEW_MODE || grepMode) return;
+        const state = JSON.stringify(worlds.map(function(w, i) {
+            return {
+                name: w.name,
+                unseen: i === currentWorldIndex ? 0 : Math.max(w.unseen_lines || 0, w.pending_count || 0),
+                muted: !!(w.settings && w.settings.notify_muted),
+            };
+        }));
+        if (state === lastTrayState) return;
+        lastTrayState = state;
+        sendIpc('tray:' + state);
+    }
+
+    window.trayAction = function(action, index) {
+        if (action === 'world' && index >= 0 && index < worlds.length) {
+            switchWorldLocal(index);
+        } else if (action === 'mute') {
+            // Mute every world, or unmute them all when they already are (/notify -w)
+            const mute = !worlds.every(function(w) { return w.settings && w.settings.notify_muted; });
+            worlds.forEach(function(w, i) {
+                send({ type: 'SendCommand', world_index: i, command: '/notify -w ' + (mute ? 'mute' : 'unmute') });
+            });
+        }
+    };
+
+    // Where world i ends up after the world at `from` moves to `to` (tab_bar::moved_index)
+    function movedIndex(i, from, to) {
+        if (i === from) return to;
+        if (from < to && i > from && i <= to) return i - 1;
+        if (to < from && i >= to && i < from) return i + 1;
+        return i;
+    }
+
+    // World tab strip above the status bar, the console's tab bar (Tab Bar in /setup).
+    // Each tab shows the world's unseen count and is coloured by connection state.
+    // Click switches, middle-click disconnects, and dropping a dragged tab onto another
+    // asks the server to move that world there; it echoes WorldMoved to every client.
+    function renderTabBar() {
+        const bar = elements.tabBar;
+        if (!bar) return;
+        const show = tabBar && !lockedWorld && worlds.length > 0;
+        bar.style.display = show ? '' : 'none';
+        if (!show) return;
+        bar.innerHTML = '';
+        worlds.forEach(function(w, i) {
+            const tab = document.createElement('div');
+            tab.className = 'world-tab'
+                + (i === currentWorldIndex ? ' current' : '')
+                + (w.connected ? ' connected' : (w.was_connected ? ' dropped' : ''));
+            tab.textContent = (i + 1) + ':' + w.name;
+            tab.title = w.connected ? 'Connected (middle-click to disconnect)' : 'Not connected';
+            tab.draggable = true;
+            const unseen = i === currentWorldIndex ? 0 : Math.max(w.unseen_lines || 0, w.pending_count || 0);
+            if (unseen > 0) {
+                const badge = document.createElement('span');
+                badge.className = 'world-tab-unseen';
+                badge.textContent = formatCount(unseen);
+                tab.appendChild(badge);
+            }
+            tab.onclick = function() {
+                switchWorldLocal(i);
+            };
+            tab.onmousedown = function(e) {
+                if (e.button === 1) e.preventDefault();  // no autoscroll
+            };
+            tab.onauxclick = function(e) {
+                if (e.button === 1 && w.connected) {
+                    e.preventDefault();
+                    send({ type: 'DisconnectWorld', world_index: i });
+                }
+            };
+            tab.ondragstart = function(e) {
+                tabDragFrom = i;
+                e.dataTransfer.effectAllowed = 'move';
+                e.dataTransfer.setData('text/plain', w.name);
+            };
+            tab.ondragover = function(e) {
+                if (tabDragFrom !== null && tabDragFrom !== i) {
+                    e.preventDefault();
+                    tab.classList.add('drop-target');
+                }
+            };
+            tab.ondragleave = function() {
+                tab.classList.remove('drop-target');
+            };
+            tab.ondrop = function(e) {
+                e.preventDefault();
+                const from = tabDragFrom;
+                tabDragFrom = null;
+                if (from !== null && from !== i) {
+                    send({ type: 'MoveWorld', from: from, to: i });
+                }
+            };
+            tab.ondragend = function() {
+                tabDragFrom = null;
+            };
+            bar.appendChild(tab);
+        });
+        const current = bar.children[currentWorldIndex];
+        if (current) current.scrollIntoView({ block: 'nearest', inline: 'nearest' });
+    }
+
+    // Movement command for a keypad key, or null (mirrors keybindings::numpad_digit_command)
+    const NUMPAD_WALK = { 1: 'sw', 2: 's', 3: 'se', 4: 'w', 5: 'look', 6: 'e', 7: 'nw', 8: 'n', 9: 'ne' };
+    function numpadWalkCommand(e) {
+        const world = worlds[currentWorldIndex];
+        if (!world || !world.settings || !world.settings.numpad_walk) return null;
+        if (e.ctrlKey || e.altKey || e.metaKey) return null;
+        const m = /^Numpad([1-9])$/.exec(e.code || '');
+        return m ? NUMPAD_WALK[m[1]] : null;
+    }
+
+    // The current world's /macro command for a function key, or null (key names as in
+    // keybindings::fkey_macro_name: "F5", "Shift-F5")
+    function fkeyMacroCommand(e) {
+        const world = worlds[currentWorldIndex];
+        const macros = world && world.settings && world.settings.macros;
+        if (!macros) return null;
+        const m = /^F([1-9]|1[0-2])$/.exec(e.key);
+        if (!m) return null;
+        return macros[(e.shiftKey ? 'Shift-F' : 'F') + m[1]] || null;
+    }
+
+    // Update time (12-hour format H:MM, no AM/PM)
+    function updateTime() {
+        const now = new Date();
+        let hours = now.getHours() % 12;
+        if (hours === 0) hours = 12;
+        const minutes = now.getMinutes().toString().padStart(2, '0');
+        elements.statusTime.textContent = `${hours}:${minutes}`;
+    }
 
     // Set input area height (number of lines)
     function setInputHeight(lines) {
@@ -6203,18 +7222,6 @@
         if (btn) btn.disabled = false;
     }
 
-    // Show/hide reconnect modal
-    function showReconnectModal() {
-        elements.reconnectModal.className = 'modal visible';
-        elements.reconnectModal.style.display = 'flex';
-        forceRepaint(elements.reconnectModal);
-    }
-
-    function hideReconnectModal() {
-        elements.reconnectModal.className = 'modal';
-        elements.reconnectModal.style.display = 'none';
-    }
-
     // Show/hide auth modal
     function showAuthModal(show) {
         elements.authModal.className = 'modal' + (show ? ' visible' : '');
@@ -6462,8 +7469,272 @@
                 }
                 return true;
             })
-            .sort((a, b) => a.action.name.toLowerCase().localeCompare(b.action.name.toLowerCase()))
-            .map(({ index }) => index);
+            .map(({ index }) => index);  // list order is the order triggers are checked in
+    }
+
+    // Duplicate: the server inserts the copy right below the original (see
+    // actions::duplicate_action), so select that spot for the ActionsUpdated to come
+    function duplicateSelectedAction() {
+        if (selectedActionIndex < 0 || selectedActionIndex >= actions.length) return;
+        ws.send(JSON.stringify({ type: 'DuplicateAction', index: selectedActionIndex }));
+        selectedActionIndex += 1;
+    }
+
+    // Move the selected action one place up or down; triggers are checked in list order.
+    // Swaps with the neighbor in the full list, which is what the server does too.
+    function moveSelectedAction(up) {
+        const target = selectedActionIndex + (up ? -1 : 1);
+        if (selectedActionIndex < 0 || target < 0 || target >= actions.length) return;
+        ws.send(JSON.stringify({ type: 'MoveAction', index: selectedActionIndex, up: up }));
+        const moved = actions[selectedActionIndex];
+        actions[selectedActionIndex] = actions[target];
+        actions[target] = moved;
+        selectedActionIndex = target;
+        renderActionsList();
+    }
+
+    // Import: send the picked trigger pack (JSON or TOML) to the server, which merges it
+    // (same-named actions are replaced) and answers with ImportResult and ActionsUpdated
+    function importActions() {
+        const file = elements.actionImportFile.files[0];
+        elements.actionImportFile.value = '';
+        if (!file) return;
+        file.text().then(function(text) {
+            ws.send(JSON.stringify({ type: 'ImportActions', text: text }));
+        }).catch(function(e) {
+            appendClientLine('Could not read ' + file.name + ': ' + e.message, currentWorldIndex, 'system');
+        });
+    }
+
+    // Export: save the listed actions as a JSON trigger pack (what /actions export writes);
+    // owners stay behind, they only mean something on this server
+    async function exportActions() {
+        const listed = getFilteredActionIndices().map(i => actions[i]);
+        if (listed.length === 0) return;
+        const pack = { actions: listed.map(a => Object.assign({}, a, { owner: null })) };
+        const json = JSON.stringify(pack, null, 2);
+        const name = (actionsWorldFilter ? actionsWorldFilter.replace(/[^A-Za-z0-9._-]+/g, '_') + '-' : '') + 'actions.json';
+
+        if (window.WEBVIEW_MODE) {
+            sendIpc('save-actions:' + JSON.stringify({ name: name, json: json }));
+            return;
+        }
+        if (window.showSaveFilePicker) {
+            try {
+                const handle = await window.showSaveFilePicker({
+                    suggestedName: name,
+                    types: [{ description: 'JSON', accept: { 'application/json': ['.json'] } }],
+                });
+                const writable = await handle.createWritable();
+                await writable.write(json);
+                await writable.close();
+                actionsExported(true, handle.name);
+            } catch (e) {
+                if (e.name !== 'AbortError') actionsExported(false, e.message);
+            }
+            return;
+        }
+        const url = URL.createObjectURL(new Blob([json], { type: 'application/json' }));
+        const a = document.createElement('a');
+        a.href = url;
+        a.download = name;
+        document.body.appendChild(a);
+        a.click();
+        a.remove();
+        setTimeout(function() { URL.revokeObjectURL(url); }, 1000);
+    }
+
+    // Called by the GUI once the save dialog's file is written (or failed)
+    function actionsExported(ok, detail) {
+        appendClientLine(ok ? 'Actions exported to ' + detail : 'Actions export failed: ' + detail, currentWorldIndex, 'system');
+    }
+    window.actionsExported = actionsExported;
+
+    // Gags popup: the managed gag list (see gags.rs). Edits send the whole list back
+    // (UpdateGags); the server saves it and answers every client with GagsUpdated.
+    function openGagsPopup() {
+        gagsPopupOpen = true;
+        selectedGagIndex = 0;
+        elements.gagsModal.className = 'modal visible';
+        renderGagsList();
+        ws.send(JSON.stringify({ type: 'RequestGags' }));
+    }
+
+    function closeGagsPopup() {
+        gagsPopupOpen = false;
+        elements.gagsModal.className = 'modal';
+        elements.input.focus();
+    }
+
+    // Apply edit(gag) to a copy of the selected gag (or delete it when edit is null)
+    function updateSelectedGag(edit) {
+        if (selectedGagIndex < 0 || selectedGagIndex >= gags.length) return;
+        const updated = gags.slice();
+        if (edit) {
+            updated[selectedGagIndex] = edit(Object.assign({}, gags[selectedGagIndex]));
+        } else {
+            updated.splice(selectedGagIndex, 1);
+        }
+        ws.send(JSON.stringify({ type: 'UpdateGags', gags: updated }));
+    }
+
+    function toggleSelectedGag() {
+        updateSelectedGag(g => { g.enabled = !g.enabled; return g; });
+    }
+
+    function keepSelectedGag() {
+        updateSelectedGag(g => { g.temporary = !g.temporary; return g; });
+    }
+
+    function deleteSelectedGag() {
+        updateSelectedGag(null);
+    }
+
+    // Add: start a /gag command in the input line, like the console popup
+    function addGag() {
+        closeGagsPopup();
+        elements.input.value = '/gag ';
+        elements.input.setSelectionRange(5, 5);
+    }
+
+    // Tag routes popup: the route list (see tag_routes.rs). Edits send the whole list
+    // back (UpdateTagRoutes); the server saves it and answers with TagRoutesUpdated.
+    function openTagRoutesPopup() {
+        tagRoutesPopupOpen = true;
+        selectedTagRouteIndex = 0;
+        elements.tagRoutesModal.className = 'modal visible';
+        renderTagRoutesList();
+        ws.send(JSON.stringify({ type: 'RequestTagRoutes' }));
+    }
+
+    function closeTagRoutesPopup() {
+        tagRoutesPopupOpen = false;
+        elements.tagRoutesModal.className = 'modal';
+        elements.input.focus();
+    }
+
+    function toggleSelectedTagRoute() {
+        if (selectedTagRouteIndex < 0 || selectedTagRouteIndex >= tagRoutes.length) return;
+        const updated = tagRoutes.slice();
+        const route = tagRoutes[selectedTagRouteIndex];
+        updated[selectedTagRouteIndex] = Object.assign({}, route, { enabled: !route.enabled });
+        ws.send(JSON.stringify({ type: 'UpdateTagRoutes', routes: updated }));
+    }
+
+    function deleteSelectedTagRoute() {
+        if (selectedTagRouteIndex < 0 || selectedTagRouteIndex >= tagRoutes.length) return;
+        const updated = tagRoutes.slice();
+        updated.splice(selectedTagRouteIndex, 1);
+        ws.send(JSON.stringify({ type: 'UpdateTagRoutes', routes: updated }));
+    }
+
+    // Add: start a /tagroute add command in the input line
+    function addTagRoute() {
+        closeTagRoutesPopup();
+        elements.input.value = '/tagroute add ';
+        elements.input.setSelectionRange(14, 14);
+    }
+
+    // Render the tag routes list with On, Tag, World, Action columns
+    function renderTagRoutesList() {
+        elements.tagRoutesList.innerHTML = '';
+        if (tagRoutes.length === 0) {
+            const div = document.createElement('div');
+            div.style.padding = '8px';
+            div.style.color = '#888';
+            div.textContent = 'No tag routes defined. Use /tagroute add <tag> <action>.';
+            elements.tagRoutesList.appendChild(div);
+            return;
+        }
+
+        const columns = [['action-world', 'On'], ['action-world', 'Tag'], ['action-world', 'World'], ['action-pattern', 'Action']];
+        const headerDiv = document.createElement('div');
+        headerDiv.className = 'actions-list-header';
+        columns.forEach(([cls, title]) => {
+            const span = document.createElement('span');
+            span.className = cls;
+            span.textContent = title;
+            headerDiv.appendChild(span);
+        });
+        elements.tagRoutesList.appendChild(headerDiv);
+
+        tagRoutes.forEach((route, index) => {
+            const div = document.createElement('div');
+            div.className = 'actions-list-item' + (index === selectedTagRouteIndex ? ' selected' : '');
+            if (!route.enabled) div.style.opacity = '0.5';
+            const cells = [
+                route.enabled ? '[\u2713]' : '[ ]',
+                route.tag,
+                route.world || '(all)',
+                route.action,
+            ];
+            cells.forEach((text, i) => {
+                const span = document.createElement('span');
+                span.className = columns[i][0];
+                span.textContent = text;
+                div.appendChild(span);
+            });
+            div.onclick = () => {
+                selectedTagRouteIndex = index;
+                renderTagRoutesList();
+            };
+            div.ondblclick = () => {
+                selectedTagRouteIndex = index;
+                toggleSelectedTagRoute();
+            };
+            elements.tagRoutesList.appendChild(div);
+        });
+    }
+
+    // Render the gags list with On, World, Kept, Pattern columns
+    function renderGagsList() {
+        elements.gagsList.innerHTML = '';
+        if (gags.length === 0) {
+            const div = document.createElement('div');
+            div.style.padding = '8px';
+            div.style.color = '#888';
+            div.textContent = 'No gags defined. Use /gag <pattern>.';
+            elements.gagsList.appendChild(div);
+            return;
+        }
+
+        const columns = [['action-world', 'On'], ['action-world', 'World'], ['action-world', 'Kept'], ['action-pattern', 'Pattern']];
+        const headerDiv = document.createElement('div');
+        headerDiv.className = 'actions-list-header';
+        columns.forEach(([cls, title]) => {
+            const span = document.createElement('span');
+            span.className = cls;
+            span.textContent = title;
+            headerDiv.appendChild(span);
+        });
+        elements.gagsList.appendChild(headerDiv);
+
+        gags.forEach((gag, index) => {
+            const div = document.createElement('div');
+            div.className = 'actions-list-item' + (index === selectedGagIndex ? ' selected' : '');
+            if (!gag.enabled) div.style.opacity = '0.5';
+            const cells = [
+                gag.enabled ? '[\u2713]' : '[ ]',
+                gag.world || '(all)',
+                gag.temporary ? 'session' : 'saved',
+                gag.pattern,
+            ];
+            cells.forEach((text, i) => {
+                const span = document.createElement('span');
+                span.className = columns[i][0];
+                span.textContent = text;
+                div.appendChild(span);
+            });
+            div.onclick = () => {
+                selectedGagIndex = index;
+                renderGagsList();
+            };
+            div.ondblclick = () => {
+                selectedGagIndex = index;
+                toggleSelectedGag();
+            };
+            elements.gagsList.appendChild(div);
+        });
     }
 
     // Render actions list with Name, World, Pattern columns
@@ -6582,7 +7853,10 @@
             inp.value = pat;
             inp.placeholder = '^pattern$';
             inp.autocomplete = 'off';
-            inp.addEventListener('input', function() { patterns[idx] = inp.value; });
+            inp.addEventListener('input', function() {
+                patterns[idx] = inp.value;
+                scheduleActionTest();
+            });
 
             const del = document.createElement('button');
             del.className = 'btn btn-danger';
@@ -6591,6 +7865,7 @@
             del.addEventListener('click', function() {
                 patterns.splice(idx, 1);
                 renderActionPatternRows(patterns);
+                scheduleActionTest();
             });
 
             row.appendChild(inp);
@@ -6657,10 +7932,61 @@
         elements.actionPatternsContainer._editPatterns = editPatterns;
 
         elements.actionError.textContent = '';
+        elements.actionTest.value = '';
+        saveActionAfterTest = false;
+        runActionTest();
         elements.actionEditorDeleteBtn.style.display = (editIndex >= 0) ? '' : 'none';
         elements.actionName.focus();
     }
 
+    // Re-run the pattern tester shortly after the patterns, match type, command or
+    // sample line change
+    function scheduleActionTest() {
+        if (actionTestTimer !== null) clearTimeout(actionTestTimer);
+        actionTestTimer = setTimeout(runActionTest, 150);
+    }
+
+    function runActionTest() {
+        if (actionTestTimer !== null) {
+            clearTimeout(actionTestTimer);
+            actionTestTimer = null;
+        }
+        const sent = send({
+            type: 'TestActionPatterns',
+            match_type: elements.actionMatchType.value || 'Regexp',
+            patterns: elements.actionPatternsContainer._editPatterns || [],
+            command: elements.actionCommand.value,
+            sample: elements.actionTest.value
+        });
+        if (sent) actionTestsInFlight++;
+    }
+
+    // Show a tester verdict (same wording as the console's, actions::describe_pattern_test)
+    function showActionTestResult(msg) {
+        actionTestsInFlight = Math.max(0, actionTestsInFlight - 1);
+        actionPatternError = msg.error || null;
+        const el = elements.actionTestResult;
+        el.classList.toggle('bad', !!actionPatternError);
+        if (actionPatternError) {
+            el.textContent = actionPatternError;
+        } else if (!elements.actionTest.value) {
+            el.textContent = '';
+        } else if (msg.pattern === null || msg.pattern === undefined) {
+            el.textContent = 'No match';
+        } else {
+            let text = 'Pattern ' + (msg.pattern + 1) + ' matches';
+            (msg.captures || []).slice(0, 10).forEach(function(cap, i) {
+                text += ' $' + i + '="' + cap + '"';
+            });
+            if (msg.gag) text += ' (gagged)';
+            el.textContent = text;
+        }
+        if (saveActionAfterTest && actionTestsInFlight === 0 && actionTestTimer === null) {
+            saveActionAfterTest = false;
+            if (!actionPatternError && actionsEditorPopupOpen) saveAction();
+        }
+    }
+
     // Close Actions Editor popup (return to list)
     function closeActionsEditorPopup() {
         actionsEditorPopupOpen = false;
@@ -6732,6 +8058,25 @@
             elements.actionError.textContent = error;
             return;
         }
+        // Patterns are only saved once the server has compiled them; wait for a
+        // verdict still on its way and save from there
+        if (actionTestTimer !== null) runActionTest();
+        if (actionTestsInFlight > 0) {
+            saveActionAfterTest = true;
+            // A server without the tester never answers; don't hold the save hostage
+            setTimeout(function() {
+                if (saveActionAfterTest && actionsEditorPopupOpen) {
+                    saveActionAfterTest = false;
+                    actionTestsInFlight = 0;
+                    saveAction();
+                }
+            }, 2000);
+            return;
+        }
+        if (actionPatternError) {
+            elements.actionError.textContent = actionPatternError;
+            return;
+        }
 
         // Collect patterns from live array (set up in openActionsEditorPopup)
         // Patterns are now simple strings; filter out empty ones
@@ -6794,10 +8139,11 @@
         elements.settingsGeneralSection.classList.toggle('active', tab === 'general');
         elements.settingsWebSection.classList.toggle('active', tab === 'web');
         elements.settingsFontSection.classList.toggle('active', tab === 'font');
+        elements.settingsSoundsSection.classList.toggle('active', tab === 'sounds');
         if (elements.settingsClayServerSection) {
             elements.settingsClayServerSection.classList.toggle('active', tab === 'clay-server');
         }
-        var titles = { general: 'General', web: 'Web', font: 'Font', 'clay-server': 'Clay Server' };
+        var titles = { general: 'General', web: 'Web', font: 'Font', sounds: 'Sounds', 'clay-server': 'Clay Server' };
         elements.settingsTitle.textContent = titles[tab] || tab;
         // Rename Save button when on clay-server tab (will reconnect)
         if (elements.settingsSaveBtn) {
@@ -6809,6 +8155,11 @@
     }
 
     function openEditorPage(page) {
+        // The editors are pages of the web interface, which local mode runs without
+        if (window.LOCAL_MODE) {
+            appendClientLine('The editors are served by the web interface: turn on HTTP in /web to use them.');
+            return;
+        }
         var url;
         var pagePath = basePath() + (page ? '/' + page : '/');
         if (window.SERVER_URL) {
@@ -6819,6 +8170,11 @@
             var port = (window.WS_PORT && window.WS_PORT !== 0) ? window.WS_PORT : window.location.port;
             url = proto + '://' + host + ':' + port + pagePath;
         }
+        openExternalUrl(url);
+    }
+
+    // Open a URL in the system browser (the GUI and Android hand it to the host)
+    function openExternalUrl(url) {
         if (window.WEBVIEW_MODE) {
             sendIpc('open-url:' + url);
         } else if (typeof Android !== 'undefined' && Android.openExternalUrl) {
@@ -6828,6 +8184,40 @@
         }
     }
 
+    // Right-click menu on a link in the output: open, copy, or send it to the MUD
+    // (the console's /urls popup has the same three buttons)
+    let linkMenuUrl = null;
+    function showLinkMenu(url, x, y) {
+        linkMenuUrl = url;
+        const menu = elements.linkMenu;
+        menu.style.bottom = 'auto';
+        menu.style.left = x + 'px';
+        menu.style.top = y + 'px';
+        menu.classList.add('visible');
+        // Keep it on screen near the right and bottom edges
+        const rect = menu.getBoundingClientRect();
+        if (rect.right > window.innerWidth) menu.style.left = Math.max(0, x - rect.width) + 'px';
+        if (rect.bottom > window.innerHeight) menu.style.top = Math.max(0, y - rect.height) + 'px';
+    }
+
+    function hideLinkMenu() {
+        linkMenuUrl = null;
+        elements.linkMenu.classList.remove('visible');
+    }
+
+    function handleLinkMenuItem(action) {
+        const url = linkMenuUrl;
+        hideLinkMenu();
+        if (!url) return;
+        if (action === 'open') {
+            openExternalUrl(url);
+        } else if (action === 'copy') {
+            copyTextToClipboard(url);
+        } else if (action === 'send') {
+            send({ type: 'SendCommand', world_index: currentWorldIndex, command: url });
+        }
+    }
+
     function openSettingsPopup(tab) {
         if (tab === 'web' && multiuserMode) {
             appendClientLine('Web settings are disabled in multiuser mode.', currentWorldIndex, 'system');
@@ -6841,7 +8231,9 @@
         setupZwj = zwjEnabled;
         setupTtsMode = ttsMode === 'off' ? 'Off' : ttsMode === 'local' ? 'Local' : ttsMode === 'edge' ? 'Edge' : 'Off';
         setupTlsProxy = tlsProxyEnabled;
+        setupConfirmQuit = confirmQuit;
         setupNewLineIndicator = newLineIndicator;
+        setupTabBar = tabBar;
         setupDebug = debugEnabled;
         setupArchive = scrollbackEnabled;
         setupInputHeightValue = inputHeight;
@@ -6851,10 +8243,13 @@
         setupTransparency = guiTransparency;
         // Load web edit state
         editPortMode = !httpEnabled ? 'disabled' : (httpPort === 9000 ? '9000' : 'custom');
-        editCustomCert = tlsConfigured;
+        editCertMode = acmeDomain ? 'acme' : (tlsConfigured ? 'yes' : 'no');
         if (elements.setupRemoteLinesInput) {
             elements.setupRemoteLinesInput.value = remoteInitialLines;
         }
+        if (elements.setupScrollbackLinesInput) {
+            elements.setupScrollbackLinesInput.value = scrollbackLines;
+        }
         // Load font edit state
         fontEditName = fontName;
         fontEditSizePhone = Math.round(webFontSizePhone);
@@ -6874,8 +8269,63 @@
         switchSettingsTab(tab || 'general');
         updateSetupPopupUI();
         updateWebPopupUI();
+        if (elements.fontFamilySearch) elements.fontFamilySearch.value = '';
         renderFontFamilyList();
         updateFontPopupUI();
+        renderSoundsList();
+    }
+
+    // Sounds tab: a sound file per world, with a button to try it
+    function renderSoundsList() {
+        const list = elements.soundsList;
+        if (!list) return;
+        list.innerHTML = '';
+        if (worlds.length === 0) {
+            list.textContent = 'No worlds yet.';
+            return;
+        }
+        worlds.forEach(function(world, i) {
+            const row = document.createElement('div');
+            row.className = 'setting-row sounds-row';
+            const label = document.createElement('span');
+            label.className = 'setting-label';
+            label.textContent = world.name;
+            label.title = world.name;
+            const input = document.createElement('input');
+            input.type = 'text';
+            input.className = 'web-input';
+            input.placeholder = window.WEBVIEW_MODE ? '~/sounds/ping.wav' : 'https://...';
+            input.value = (world.settings && world.settings.notify_sound) || '';
+            input.dataset.world = String(i);
+            input.spellcheck = false;
+            const test = document.createElement('button');
+            test.className = 'stepper-btn';
+            test.textContent = 'Test';
+            test.onclick = function() {
+                if (input.value.trim()) playNotifySound(input.value.trim());
+            };
+            row.appendChild(label);
+            row.appendChild(input);
+            row.appendChild(test);
+            list.appendChild(row);
+        });
+    }
+
+    // Store changed sounds through /notify -w sound, which persists and syncs them
+    function saveSoundSettings() {
+        if (!elements.soundsList) return;
+        elements.soundsList.querySelectorAll('input[data-world]').forEach(function(input) {
+            const index = parseInt(input.dataset.world, 10);
+            const world = worlds[index];
+            if (!world) return;
+            const sound = input.value.trim();
+            if (sound === ((world.settings && world.settings.notify_sound) || '')) return;
+            send({
+                type: 'SendCommand',
+                world_index: index,
+                command: '/notify -w sound ' + (sound || 'off')
+            });
+        });
     }
 
     function closeSettingsPopup() {
@@ -6914,11 +8364,13 @@
         } else {
             elements.setupTlsProxyToggle.classList.remove('active');
         }
+        elements.setupConfirmQuitToggle.classList.toggle('active', setupConfirmQuit);
         if (setupNewLineIndicator) {
             elements.setupNewLineIndicatorToggle.classList.add('active');
         } else {
             elements.setupNewLineIndicatorToggle.classList.remove('active');
         }
+        elements.setupTabBarToggle.classList.toggle('active', setupTabBar);
         if (setupDebug) {
             elements.setupDebugToggle.classList.add('active');
         } else {
@@ -6988,15 +8440,24 @@
             ws_cert_file: wsCertFile,
             ws_key_file: wsKeyFile,
             ws_password: wsPassword,
+            acme_domain: acmeDomain,
+            acme_email: acmeEmail,
+            // null leaves them unchanged on a server that didn't send them
+            web_bind_addr: webBindAddr || null,
+            web_unix_socket: webBindAddr ? webUnixSocket : null,
+            web_trusted_proxies: webBindAddr ? webTrustedProxies : null,
             tls_proxy_enabled: tlsProxyEnabled,
             zwj_enabled: zwjEnabled,
             tts_mode: ttsMode,
             tts_speak_mode: ttsSpeakMode,
             new_line_indicator: newLineIndicator,
+            tab_bar: tabBar,
             mouse_enabled: mouseEnabled,
             debug_enabled: debugEnabled,
             dictionary_path: dictionaryPath,
-            scrollback_enabled: scrollbackEnabled
+            scrollback_enabled: scrollbackEnabled,
+            scrollback_lines: scrollbackLines,
+            confirm_quit: confirmQuit
         };
     }
 
@@ -7004,7 +8465,7 @@
     // client has synced real values from the server at least once. Every global in
     // buildUpdateGlobalSettings() defaults to false/'' until a sync lands, so sending
     // before that would silently reset unrelated globals on the server (and, since
-    // the server persists immediately, in ~/.clay/settings.dat). See CLAUDE.md /
+    // the server persists immediately, in ~/.config/clay/settings.toml). See CLAUDE.md /
     // settings-audit investigation for the incident this guards against.
     function sendGlobalSettings() {
         if (!settingsSynced) {
@@ -7093,6 +8554,10 @@
         if (setupColorOffset > 100) setupColorOffset = 100;
         if (setupWrapspace < 0) setupWrapspace = 0;
         if (setupWrapspace > 20) setupWrapspace = 20;
+        var setupScrollbackLines = parseInt(elements.setupScrollbackLinesInput ? elements.setupScrollbackLinesInput.value : '', 10);
+        if (Number.isFinite(setupScrollbackLines)) {
+            scrollbackLines = Math.max(1000, Math.min(1000000, setupScrollbackLines));
+        }
 
         moreModeEnabled = setupMoreMode;
         worldSwitchMode = setupWorldSwitchMode;
@@ -7100,7 +8565,9 @@
         zwjEnabled = setupZwj;
         ttsMode = setupTtsMode.toLowerCase();
         tlsProxyEnabled = setupTlsProxy;
+        confirmQuit = setupConfirmQuit;
         newLineIndicator = setupNewLineIndicator;
+        tabBar = setupTabBar;
         debugEnabled = setupDebug;
         scrollbackEnabled = setupArchive;
         guiTheme = setupGuiTheme;
@@ -7116,12 +8583,19 @@
         if (!multiuserMode) {
             httpEnabled = editPortMode !== 'disabled';
             httpPort = editPortMode === 'custom' ? (parseInt(elements.webCustomPort.value) || 9000) : 9000;
-            webPath = elements.webPath ? elements.webPath.value.replace(/^\/+|\/+$/g, '').replace(/[^A-Za-z0-9_-]/g, '') : webPath;
+            webPath = elements.webPath ? elements.webPath.value.split('/').map(function(s) { return s.replace(/[^A-Za-z0-9_-]/g, ''); }).filter(Boolean).join('/') : webPath;
             wsAllowList = elements.webAllowList.value;
             wsPassword = elements.webWsPassword ? elements.webWsPassword.value : wsPassword;
-            tlsConfigured = editCustomCert;
-            wsCertFile = editCustomCert ? elements.webCertFile.value : '';
-            wsKeyFile = editCustomCert ? elements.webKeyFile.value : '';
+            tlsConfigured = editCertMode === 'yes';
+            wsCertFile = tlsConfigured ? elements.webCertFile.value : '';
+            wsKeyFile = tlsConfigured ? elements.webKeyFile.value : '';
+            acmeDomain = editCertMode === 'acme' ? elements.webAcmeDomain.value.trim() : '';
+            acmeEmail = editCertMode === 'acme' ? elements.webAcmeEmail.value.trim() : '';
+            if (webBindAddr) {
+                webBindAddr = elements.webBindAddr.value.trim() || '127.0.0.1';
+                webUnixSocket = elements.webUnixSocket.value.trim();
+                webTrustedProxies = elements.webTrustedProxies.value.trim();
+            }
 
             var setupRemoteInitialLines = parseInt(elements.setupRemoteLinesInput ? elements.setupRemoteLinesInput.value : '', 10);
             if (!Number.isFinite(setupRemoteInitialLines)) setupRemoteInitialLines = 100;
@@ -7131,6 +8605,9 @@
         // Save font settings
         _saveFontSettingsInline();
 
+        // Save per-world sounds
+        saveSoundSettings();
+
         // Send combined update to server
         if (settingsSynced) {
             const msg = buildUpdateGlobalSettings();
@@ -7149,12 +8626,15 @@
         elements.webCustomPortField.style.display = editPortMode === 'custom' ? 'flex' : 'none';
         elements.webCustomPort.value = httpPort;
 
+        elements.webBindAddr.value = webBindAddr;
+        elements.webUnixSocket.value = webUnixSocket;
+        elements.webTrustedProxies.value = webTrustedProxies;
         if (elements.webPath) elements.webPath.value = webPath;
         elements.webAllowList.value = wsAllowList;
         if (elements.webWsPassword) elements.webWsPassword.value = wsPassword;
 
-        // Custom Cert File select (use edit state)
-        elements.webCustomCertSelect.value = editCustomCert ? 'yes' : 'no';
+        // Certificate select (use edit state)
+        elements.webCustomCertSelect.value = editCertMode;
         // Show placeholder if a custom cert is configured but paths not sent from server
         if (tlsConfigured && !wsCertFile) {
             elements.webCertFile.value = '';
@@ -7171,9 +8651,14 @@
             elements.webKeyFile.placeholder = '';
         }
 
-        // Show/hide cert/key fields based on Custom Cert File selection
-        elements.tlsCertField.style.display = editCustomCert ? 'flex' : 'none';
-        elements.tlsKeyField.style.display = editCustomCert ? 'flex' : 'none';
+        elements.webAcmeDomain.value = acmeDomain;
+        elements.webAcmeEmail.value = acmeEmail;
+
+        // Show/hide cert/key and Let's Encrypt fields based on the Certificate selection
+        elements.tlsCertField.style.display = editCertMode === 'yes' ? 'flex' : 'none';
+        elements.tlsKeyField.style.display = editCertMode === 'yes' ? 'flex' : 'none';
+        elements.acmeDomainField.style.display = editCertMode === 'acme' ? 'flex' : 'none';
+        elements.acmeEmailField.style.display = editCertMode === 'acme' ? 'flex' : 'none';
 
         // Populate auth key field (read-only — use Modify Key to change it)
         if (elements.webAuthKey) {
@@ -7185,10 +8670,35 @@
 
     // openFontPopup/closeFontPopup removed — merged into openSettingsPopup/closeSettingsPopup
 
+    // Called by the native WebView GUI with the installed monospace font families
+    window.setSystemFonts = function(families) {
+        systemFonts = Array.isArray(families) ? families : [];
+        if (elements.settingsModal.classList.contains('visible')) renderFontFamilyList();
+    };
+
+    // Show the sample text in the font being picked
+    function updateFontPreview() {
+        if (!elements.fontFamilyPreview) return;
+        elements.fontFamilyPreview.style.fontFamily = fontEditName ? "'" + fontEditName + "', monospace" : '';
+    }
+
     function renderFontFamilyList() {
         const list = elements.fontFamilyList;
         list.innerHTML = '';
-        FONT_FAMILIES.forEach(function(entry) {
+        if (window.WEBVIEW_MODE && !systemFontsRequested) {
+            systemFontsRequested = true;
+            sendIpc('list-fonts');
+        }
+        const entries = FONT_FAMILIES.slice();
+        systemFonts.forEach(function(family) {
+            if (!entries.some(function(entry) { return entry[0].toLowerCase() === family.toLowerCase(); })) {
+                entries.push([family, family]);
+            }
+        });
+        const query = elements.fontFamilySearch ? elements.fontFamilySearch.value.trim().toLowerCase() : '';
+        entries.filter(function(entry) {
+            return !query || entry[1].toLowerCase().includes(query);
+        }).forEach(function(entry) {
             const value = entry[0];
             const label = entry[1];
             const item = document.createElement('div');
@@ -7204,6 +8714,7 @@
                     el.classList.remove('selected');
                 });
                 item.classList.add('selected');
+                updateFontPreview();
             });
             list.appendChild(item);
         });
@@ -7212,6 +8723,7 @@
         if (selected) {
             selected.scrollIntoView({ block: 'nearest' });
         }
+        updateFontPreview();
     }
 
     function updateFontPopupUI() {
@@ -7329,8 +8841,8 @@
     }
 
     // Calculate next keepalive time (based only on last send time)
-    function formatNextKA(lastSendSecs, lastRecvSecs) {
-        const KEEPALIVE_SECS = 5 * 60; // 5 minutes
+    function formatNextKA(lastSendSecs, lastRecvSecs, intervalSecs) {
+        const KEEPALIVE_SECS = intervalSecs || 5 * 60; // world's interval, 5 minutes by default
         const elapsed = lastSendSecs !== null && lastSendSecs !== undefined ? lastSendSecs : KEEPALIVE_SECS;
         const remaining = Math.max(0, KEEPALIVE_SECS - elapsed);
         if (remaining < 60) return remaining + 's';
@@ -7389,7 +8901,7 @@
 
             // KA (last/next)
             const tdKA = document.createElement('td');
-            tdKA.textContent = formatElapsed(world.last_nop_secs) + '/' + formatNextKA(world.last_send_secs, world.last_recv_secs);
+            tdKA.textContent = formatElapsed(world.last_nop_secs) + '/' + formatNextKA(world.last_send_secs, world.last_recv_secs, world.settings?.keep_alive_interval_secs);
             tr.appendChild(tdKA);
 
             // Buffer
@@ -7415,6 +8927,118 @@
     }
 
     // World selector popup functions (/worlds)
+    // Quick world switcher (Alt+K): type to fuzzy-filter the world names, Enter
+    // switches. The keyboard counterpart of the /worlds selector.
+    function fuzzyWorldMatch(query, name) {
+        const q = query.toLowerCase();
+        const n = name.toLowerCase();
+        const positions = [];
+        let score = 0;
+        let prev = -2;
+        for (let i = 0; i < n.length && positions.length < q.length; i++) {
+            if (n[i] !== q[positions.length]) continue;
+            score += 1;
+            if (i === prev + 1) score += 3;                          // runs of characters
+            if (i === 0 || /[\s_.:-]/.test(n[i - 1])) score += 2;    // word starts
+            positions.push(i);
+            prev = i;
+        }
+        if (positions.length < q.length) return null;
+        return { score: score - n.length * 0.01, positions: positions };
+    }
+
+    function openWorldSwitcher() {
+        if (lockedWorld || worlds.length === 0) return;
+        worldSwitcherOpen = true;
+        elements.worldSwitcherInput.value = '';
+        elements.worldSwitcherModal.className = 'modal visible';
+        updateWorldSwitcher();
+        elements.worldSwitcherInput.focus();
+    }
+
+    function closeWorldSwitcher() {
+        worldSwitcherOpen = false;
+        elements.worldSwitcherModal.className = 'modal';
+        elements.input.focus();
+    }
+
+    // Re-filter on each keystroke: best matches first, selecting the first one that
+    // isn't the current world
+    function updateWorldSwitcher() {
+        const query = elements.worldSwitcherInput.value.trim();
+        worldSwitcherMatches = [];
+        worlds.forEach((world, index) => {
+            const name = stripAnsi(world.name || '(unnamed)').trim();
+            const match = query ? fuzzyWorldMatch(query, name) : { score: 0, positions: [] };
+            if (match) worldSwitcherMatches.push({ index: index, name: name, score: match.score, positions: match.positions });
+        });
+        worldSwitcherMatches.sort((a, b) => b.score - a.score || a.index - b.index);
+        const firstOther = worldSwitcherMatches.findIndex(m => m.index !== currentWorldIndex);
+        worldSwitcherSelected = firstOther >= 0 ? firstOther : 0;
+        renderWorldSwitcher();
+    }
+
+    function renderWorldSwitcher() {
+        const list = elements.worldSwitcherList;
+        list.innerHTML = '';
+        if (worldSwitcherMatches.length === 0) {
+            const empty = document.createElement('div');
+            empty.className = 'world-switcher-empty';
+            empty.textContent = 'No matching worlds';
+            list.appendChild(empty);
+            return;
+        }
+        worldSwitcherMatches.forEach((match, i) => {
+            const world = worlds[match.index];
+            const item = document.createElement('div');
+            item.className = 'world-switcher-item' + (i === worldSwitcherSelected ? ' selected' : '');
+
+            const status = document.createElement('span');
+            status.className = world.connected ? 'status-connected' : 'status-disconnected';
+            status.textContent = world.connected ? '●' : '○';
+            item.appendChild(status);
+
+            const name = document.createElement('span');
+            name.className = 'world-switcher-name';
+            let html = '';
+            for (let c = 0; c < match.name.length; c++) {
+                const ch = escapeHtml(match.name[c]);
+                html += match.positions.includes(c) ? '<b>' + ch + '</b>' : ch;
+            }
+            name.innerHTML = html + (match.index === currentWorldIndex ? ' (current)' : '');
+            item.appendChild(name);
+
+            const unseen = world.unseen_lines || 0;
+            if (unseen > 0) {
+                const count = document.createElement('span');
+                count.textContent = unseen;
+                item.appendChild(count);
+            }
+
+            item.addEventListener('mousedown', function(e) {
+                e.preventDefault();
+                worldSwitcherSelected = i;
+                selectWorldSwitcherEntry();
+            });
+            list.appendChild(item);
+        });
+        const selected = list.children[worldSwitcherSelected];
+        if (selected) selected.scrollIntoView({ block: 'nearest' });
+    }
+
+    function moveWorldSwitcherSelection(delta) {
+        const count = worldSwitcherMatches.length;
+        if (count === 0) return;
+        worldSwitcherSelected = (worldSwitcherSelected + delta + count) % count;
+        renderWorldSwitcher();
+    }
+
+    function selectWorldSwitcherEntry() {
+        const match = worldSwitcherMatches[worldSwitcherSelected];
+        closeWorldSwitcher();
+        if (match) switchWorldLocal(match.index);
+    }
+
     function openWorldSelectorPopup() {
         worldSelectorPopupOpen = true;
         selectedWorldIndex = currentWorldIndex;
@@ -7502,6 +9126,11 @@
             tdAddress.textContent = host ? (port ? host + ':' + port : host) : '';
             tr.appendChild(tdAddress);
 
+            // Bells since the world was last viewed (blank when none)
+            const tdBells = document.createElement('td');
+            tdBells.textContent = world.bell_count ? String(world.bell_count) : '';
+            tr.appendChild(tdBells);
+
             tr.onclick = () => selectWorld(index);
             tr.ondblclick = () => {
                 selectWorld(index);
@@ -7642,6 +9271,22 @@
             elements.worldEditLoggingToggle.classList.remove('active');
         }
         elements.worldEditKeepAliveCmd.value = world.settings?.keep_alive_cmd || '';
+        if (elements.worldEditLoginScript) {
+            elements.worldEditLoginScript.value = world.settings?.login_script || '';
+        }
+        if (elements.worldEditConnectCommands) {
+            elements.worldEditConnectCommands.value = world.settings?.connect_commands || '';
+        }
+        if (elements.worldEditInputPrefix) {
+            elements.worldEditInputPrefix.value = world.settings?.input_prefix || '';
+            elements.worldEditInputSuffix.value = world.settings?.input_suffix || '';
+        }
+        if (elements.worldEditKeepAliveInterval) {
+            elements.worldEditKeepAliveInterval.value = world.settings?.keep_alive_interval_secs ?? 300;
+        }
+        if (elements.worldEditSilenceAlert) {
+            elements.worldEditSilenceAlert.value = world.settings?.silence_alert_mins ?? 0;
+        }
         if (elements.worldEditGmcpPackages) {
             elements.worldEditGmcpPackages.value = world.settings?.gmcp_packages || '';
         }
@@ -7656,9 +9301,25 @@
         } else {
             elements.worldEditSslToggle.classList.remove('active');
         }
+        if (elements.worldEditTlsVerifySelect) {
+            elements.worldEditTlsVerifySelect.value = world.settings?.tls_verify || 'tofu';
+            updateCustomDropdown(elements.worldEditTlsVerifySelect);
+        }
+        if (elements.worldEditTlsClientCert) {
+            elements.worldEditTlsClientCert.value = world.settings?.tls_client_cert || '';
+        }
+        if (elements.worldEditTlsClientKey) {
+            elements.worldEditTlsClientKey.value = world.settings?.tls_client_key || '';
+        }
+        if (elements.worldEditSshHost) {
+            elements.worldEditSshHost.value = world.settings?.ssh_host || '';
+            elements.worldEditSshUser.value = world.settings?.ssh_user || '';
+            elements.worldEditSshKey.value = world.settings?.ssh_key || '';
+        }
 
         const autoLogin = world.settings?.auto_connect_type || world.settings?.auto_login || 'Connect';
         elements.worldEditAutoLoginSelect.value = autoLogin;
+        updateLoginScriptVisibility(autoLogin);
         updateCustomDropdown(elements.worldEditAutoLoginSelect);
 
         const keepAlive = world.settings?.keep_alive_type || 'NOP';
@@ -7670,6 +9331,18 @@
         elements.worldEditEncodingSelect.value = encoding;
         updateCustomDropdown(elements.worldEditEncodingSelect);
 
+        if (elements.worldEditLogFormatSelect) {
+            elements.worldEditLogFormatSelect.value = world.settings?.log_format || 'raw';
+            updateCustomDropdown(elements.worldEditLogFormatSelect);
+        }
+        if (elements.worldEditLogTimestampsSelect) {
+            elements.worldEditLogTimestampsSelect.value = world.settings?.log_timestamps || 'time';
+            updateCustomDropdown(elements.worldEditLogTimestampsSelect);
+        }
+        if (elements.worldEditLogSessionsToggle) {
+            elements.worldEditLogSessionsToggle.classList.toggle('active', world.settings?.log_session_markers ?? true);
+        }
+
         elements.worldEditorModal.className = 'modal visible';
         elements.worldEditorModal.style.display = 'flex';
         elements.worldEditName.focus();
@@ -7683,6 +9356,15 @@
         focusInputWithKeyboard();
     }
 
+    function updateLoginScriptVisibility(autoLoginType) {
+        if (!elements.worldEditLoginScriptField) return;
+        if (autoLoginType === 'Script') {
+            elements.worldEditLoginScriptField.classList.add('visible');
+        } else {
+            elements.worldEditLoginScriptField.classList.remove('visible');
+        }
+    }
+
     function updateKeepAliveCmdVisibility(keepAliveType) {
         if (keepAliveType === 'Custom') {
             elements.worldEditKeepAliveCmdField.classList.add('visible');
@@ -7694,6 +9376,13 @@
     function saveWorldEditor() {
         if (worldEditorIndex < 0 || worldEditorIndex >= worlds.length) return;
 
+        // Keepalive interval: whole seconds, at least 60 (300 if not a number)
+        const kaInterval = parseInt(elements.worldEditKeepAliveInterval ? elements.worldEditKeepAliveInterval.value : '', 10);
+        const keepAliveIntervalSecs = isNaN(kaInterval) ? 300 : Math.max(60, kaInterval);
+        // Silence watchdog: whole minutes, 0 (off) if not a number
+        const silenceAlert = parseInt(elements.worldEditSilenceAlert ? elements.worldEditSilenceAlert.value : '', 10);
+        const silenceAlertMins = isNaN(silenceAlert) ? 0 : Math.max(0, silenceAlert);
+
         // Send update to server
         send({
             type: 'UpdateWorldSettings',
@@ -7704,11 +9393,26 @@
             user: elements.worldEditUser.value,
             password: elements.worldEditPassword.value,  // Empty means "not changed" (server preserves existing)
             use_ssl: elements.worldEditSslToggle.classList.contains('active'),
+            tls_verify: elements.worldEditTlsVerifySelect ? elements.worldEditTlsVerifySelect.value : 'tofu',
+            tls_client_cert: elements.worldEditTlsClientCert ? elements.worldEditTlsClientCert.value.trim() : '',
+            tls_client_key: elements.worldEditTlsClientKey ? elements.worldEditTlsClientKey.value.trim() : '',
+            ssh_host: elements.worldEditSshHost ? elements.worldEditSshHost.value.trim() : '',
+            ssh_user: elements.worldEditSshUser ? elements.worldEditSshUser.value.trim() : '',
+            ssh_key: elements.worldEditSshKey ? elements.worldEditSshKey.value.trim() : '',
             log_enabled: elements.worldEditLoggingToggle.classList.contains('active'),
+            log_format: elements.worldEditLogFormatSelect ? elements.worldEditLogFormatSelect.value : 'raw',
+            log_timestamps: elements.worldEditLogTimestampsSelect ? elements.worldEditLogTimestampsSelect.value : 'time',
+            log_session_markers: elements.worldEditLogSessionsToggle ? elements.worldEditLogSessionsToggle.classList.contains('active') : true,
             encoding: elements.worldEditEncodingSelect.value,
             auto_login: elements.worldEditAutoLoginSelect.value,
+            login_script: elements.worldEditLoginScript ? elements.worldEditLoginScript.value : undefined,
+            connect_commands: elements.worldEditConnectCommands ? elements.worldEditConnectCommands.value : undefined,
+            input_prefix: elements.worldEditInputPrefix ? elements.worldEditInputPrefix.value : undefined,
+            input_suffix: elements.worldEditInputSuffix ? elements.worldEditInputSuffix.value : undefined,
             keep_alive_type: elements.worldEditKeepAliveSelect.value,
             keep_alive_cmd: elements.worldEditKeepAliveCmd.value,
+            keep_alive_interval_secs: keepAliveIntervalSecs,
+            silence_alert_mins: silenceAlertMins,
             gmcp_packages: elements.worldEditGmcpPackages ? elements.worldEditGmcpPackages.value : '',
             auto_reconnect_secs: elements.worldEditAutoReconnect ? elements.worldEditAutoReconnect.value.trim() : '0'
         });
@@ -7722,11 +9426,46 @@
         world.settings.user = elements.worldEditUser.value;
         world.settings.password = elements.worldEditPassword.value;
         world.settings.use_ssl = elements.worldEditSslToggle.classList.contains('active');
+        if (elements.worldEditTlsVerifySelect) {
+            world.settings.tls_verify = elements.worldEditTlsVerifySelect.value;
+        }
+        if (elements.worldEditTlsClientCert) {
+            world.settings.tls_client_cert = elements.worldEditTlsClientCert.value.trim();
+            world.settings.tls_client_key = elements.worldEditTlsClientKey.value.trim();
+        }
+        if (elements.worldEditSshHost) {
+            world.settings.ssh_host = elements.worldEditSshHost.value.trim();
+            world.settings.ssh_user = elements.worldEditSshUser.value.trim();
+            world.settings.ssh_key = elements.worldEditSshKey.value.trim();
+        }
         world.settings.log_enabled = elements.worldEditLoggingToggle.classList.contains('active');
+        if (elements.worldEditLogFormatSelect) {
+            world.settings.log_format = elements.worldEditLogFormatSelect.value;
+        }
+        if (elements.worldEditLogTimestampsSelect) {
+            world.settings.log_timestamps = elements.worldEditLogTimestampsSelect.value;
+        }
+        if (elements.worldEditLogSessionsToggle) {
+            world.settings.log_session_markers = elements.worldEditLogSessionsToggle.classList.contains('active');
+        }
         world.settings.encoding = elements.worldEditEncodingSelect.value;
         world.settings.auto_connect_type = elements.worldEditAutoLoginSelect.value;
         world.settings.keep_alive_type = elements.worldEditKeepAliveSelect.value;
         world.settings.keep_alive_cmd = elements.worldEditKeepAliveCmd.value;
+        if (elements.worldEditLoginScript) {
+            world.settings.login_script = elements.worldEditLoginScript.value;
+        }
+        if (elements.worldEditConnectCommands) {
+            world.settings.connect_commands = elements.worldEditConnectCommands.value;
+        }
+        if (elements.worldEditInputPrefix) {
+            world.settings.input_prefix = elements.worldEditInputPrefix.value;
+            world.settings.input_suffix = elements.worldEditInputSuffix.value;
+        }
+        if (elements.worldEditKeepAliveInterval) {
+            world.settings.keep_alive_interval_secs = keepAliveIntervalSecs;
+        }
+        world.settings.silence_alert_mins = silenceAlertMins;
         if (elements.worldEditGmcpPackages) {
             world.settings.gmcp_packages = elements.worldEditGmcpPackages.value;
         }
@@ -7839,7 +9578,7 @@
 
     // Check if any popup is open
     function isAnyPopupOpen() {
-        return actionsListPopupOpen || actionsEditorPopupOpen || actionsConfirmPopupOpen || worldsPopupOpen || worldSelectorPopupOpen || worldConfirmPopupOpen || settingsPopupOpen;
+        return actionsListPopupOpen || actionsEditorPopupOpen || actionsConfirmPopupOpen || gagsPopupOpen || tagRoutesPopupOpen || worldsPopupOpen || worldSelectorPopupOpen || worldConfirmPopupOpen || settingsPopupOpen;
     }
 
     // Check if a world should be included in cycling (connected OR has activity)
@@ -8049,6 +9788,12 @@
 
     // Dispatch a keybinding action by ID. Returns true if handled.
     function dispatchAction(actionId) {
+        // Alt+N: jump straight to world N
+        const worldNumber = /^world_([1-9])$/.exec(actionId);
+        if (worldNumber) {
+            switchWorldLocal(parseInt(worldNumber[1], 10) - 1);
+            return true;
+        }
         switch (actionId) {
             // Cursor
             case 'cursor_left': {
@@ -8168,6 +9913,9 @@
             case 'history_search_forward':
                 historySearchForward();
                 return true;
+            case 'history_search_reverse':
+                reverseHistorySearch();
+                return true;
 
             // Scrollback
             case 'scroll_page_up': {
@@ -8225,6 +9973,9 @@
                     }
                 }
                 const world = worlds[currentWorldIndex];
+                if (world && world.settings && world.settings.emoji_shortcodes && completeEmojiShortcode()) {
+                    return true;
+                }
                 const serverPending = world ? (world.pending_count || 0) : 0;
                 if (pendingLines.length > 0 || serverPending > 0) {
                     releaseScreenful();
@@ -8256,6 +10007,9 @@
             case 'world_forward':
                 requestNextWorld();
                 return true;
+            case 'world_switcher':
+                if (worldSwitcherOpen) closeWorldSwitcher(); else openWorldSwitcher();
+                return true;
 
             // System
             case 'help':
@@ -8287,7 +10041,14 @@
                 // No-op in browser
                 return true;
             case 'spell_check':
-                // No-op in web (no spell checker)
+            case 'spell_add_word':
+            case 'spell_ignore':
+            case 'spell_undo_autocorrect':
+            case 'spell_menu':
+                // No-op in web: the browser spell-checks the input itself (see the
+                // spellcheck attribute set in updateStatusBar and lang in renderOutput),
+                // suggesting for the word under the caret or pointer wherever it is in
+                // the line
                 return true;
 
             // Clay Extensions
@@ -8301,10 +10062,17 @@
             case 'search_popup':
                 if (searchPopupOpen) closeSearchPopup(); else openSearchPopup();
                 return true;
+            case 'scrollback_search':
+                if (searchPopupOpen) closeSearchPopup(); else openSearchPopup(true);
+                return true;
             case 'toggle_action_highlight':
                 highlightActions = !highlightActions;
                 renderOutput();
                 return true;
+            case 'toggle_timestamps':
+                timestampsToggled = !timestampsToggled;
+                renderOutput();
+                return true;
             case 'toggle_gmcp_media':
                 send({ type: 'ToggleWorldGmcp', world_index: currentWorldIndex });
                 return true;
@@ -8339,6 +10107,92 @@
     }
 
     // Handle menu item click
+    // Export Log (menu): save what this client has of the current world's output, as
+    // plain text or as a standalone HTML page keeping the colors. The GUI asks for a
+    // file with a native save dialog; a browser uses its own picker or a download.
+    function buildLogExport(world) {
+        const textLines = [];
+        const htmlLines = [];
+        for (const lineObj of world.output_lines || []) {
+            if (lineObj === undefined || lineObj === null) continue;
+            const rawLine = typeof lineObj === 'string' ? lineObj : lineObj.text;
+            if (typeof lineObj === 'object' && lineObj.gagged && !showTags) continue;
+            const cleanLine = String(rawLine).replace(/[\r\n]+/g, '');
+            const shown = showTags ? cleanLine : stripMudTag(cleanLine);
+            textLines.push(stripAnsiForFilter(shown));
+            htmlLines.push(parseAnsi(shown));
+        }
+
+        // The ANSI classes, with this theme's palette and the output's colors and font
+        const css = [];
+        for (const sheet of Array.from(document.styleSheets)) {
+            let rules;
+            try { rules = sheet.cssRules; } catch (e) { continue; }
+            for (const rule of Array.from(rules || [])) {
+                if (rule.selectorText && rule.selectorText.startsWith('.ansi-')) css.push(rule.cssText);
+            }
+        }
+        const outStyle = getComputedStyle(elements.output);
+        const vars = [];
+        for (let i = 0; i < 16; i++) {
+            const value = outStyle.getPropertyValue('--theme-ansi-' + i).trim();
+            if (value) vars.push('--theme-ansi-' + i + ': ' + value + ';');
+        }
+        const title = escapeHtml(world.name || 'Clay');
+        const html = '<!DOCTYPE html>\n<html><head><meta charset="utf-8"><title>' + title + '</title>\n<style>\n' +
+            ':root { ' + vars.join(' ') + ' }\n' +
+            'body { background: ' + outStyle.backgroundColor + '; color: ' + outStyle.color + '; }\n' +
+            'pre { font-family: ' + outStyle.fontFamily + '; font-size: ' + outStyle.fontSize + '; white-space: pre-wrap; }\n' +
+            css.join('\n') + '\n</style></head>\n<body><pre>' + htmlLines.join('\n') + '</pre></body></html>\n';
+        return { text: textLines.join('\n') + '\n', html: html };
+    }
+
+    async function exportLog() {
+        const world = worlds[currentWorldIndex];
+        if (!world) return;
+        const log = buildLogExport(world);
+        const now = new Date();
+        const date = now.getFullYear() + '-' + String(now.getMonth() + 1).padStart(2, '0') + '-' + String(now.getDate()).padStart(2, '0');
+        const base = (world.name || 'clay').replace(/[^A-Za-z0-9._-]+/g, '_') + '-' + date;
+
+        if (window.WEBVIEW_MODE) {
+            sendIpc('save-log:' + JSON.stringify({ name: base + '.txt', text: log.text, html: log.html }));
+            return;
+        }
+        if (window.showSaveFilePicker) {
+            try {
+                const handle = await window.showSaveFilePicker({
+                    suggestedName: base + '.txt',
+                    types: [
+                        { description: 'Plain text', accept: { 'text/plain': ['.txt', '.log'] } },
+                        { description: 'HTML', accept: { 'text/html': ['.html', '.htm'] } },
+                    ],
+                });
+                const writable = await handle.createWritable();
+                await writable.write(/\.html?$/i.test(handle.name) ? log.html : log.text);
+                await writable.close();
+                logExported(true, handle.name);
+            } catch (e) {
+                if (e.name !== 'AbortError') logExported(false, e.message);
+            }
+            return;
+        }
+        const url = URL.createObjectURL(new Blob([log.text], { type: 'text/plain' }));
+        const a = document.createElement('a');
+        a.href = url;
+        a.download = base + '.txt';
+        document.body.appendChild(a);
+        a.click();
+        a.remove();
+        setTimeout(function() { URL.revokeObjectURL(url); }, 1000);
+    }
+
+    // Called by the GUI once the save dialog's file is written (or failed)
+    function logExported(ok, detail) {
+        appendClientLine(ok ? 'Log exported to ' + detail : 'Log export failed: ' + detail);
+    }
+    window.logExported = logExported;
+
     function handleMenuItem(action) {
         closeMenu();
         switch (action) {
@@ -8384,6 +10238,9 @@
             case 'search':
                 if (searchPopupOpen) closeSearchPopup(); else openSearchPopup();
                 break;
+            case 'export-log':
+                exportLog();
+                break;
             case 'reload':
                 // Local only — never restart the remote server
                 if (window.WEBVIEW_MODE) {
@@ -8558,6 +10415,34 @@
             menuLongPressed = false;
         }, { passive: false });
 
+        // An inline image (/images) grows its line as it loads; stay at the bottom if the
+        // output was there before it did
+        elements.output.addEventListener('load', function(e) {
+            if (!e.target.classList || !e.target.classList.contains('inline-image')) return;
+            const container = elements.outputContainer;
+            const gap = container.scrollHeight - container.scrollTop - container.clientHeight;
+            if (gap <= e.target.offsetHeight + 5) scheduleScrollToBottom();
+        }, true);
+
+        // Link right-click menu
+        elements.output.addEventListener('contextmenu', function(e) {
+            const link = e.target.closest('a.output-link');
+            if (!link) return;
+            e.preventDefault();
+            showLinkMenu(link.href, e.clientX, e.clientY);
+        });
+        elements.linkMenu.onclick = function(e) {
+            e.stopPropagation();
+            const item = e.target.closest('.menu-item');
+            if (item) handleLinkMenuItem(item.dataset.action);
+        };
+        document.addEventListener('click', function(e) {
+            if (linkMenuUrl !== null && !e.target.closest('#link-menu')) hideLinkMenu();
+        });
+        window.addEventListener('blur', hideLinkMenu);
+        // Coming back re-arms every world's activity sound
+        window.addEventListener('focus', function() { activitySoundWorlds.clear(); });
+
         // Menu items (unified dropdown)
         elements.menuDropdown.onclick = function(e) {
             e.stopPropagation();
@@ -8879,12 +10764,15 @@
                 !elements.actionsListModal.classList.contains('visible') &&
                 !elements.actionsEditorModal.classList.contains('visible') &&
                 !elements.actionConfirmModal.classList.contains('visible') &&
+                !elements.gagsModal.classList.contains('visible') &&
+                !elements.tagRoutesModal.classList.contains('visible') &&
                 !elements.worldsModal.classList.contains('visible') &&
                 !elements.worldSelectorModal.classList.contains('visible') &&
                 !elements.settingsModal?.classList.contains('visible') &&
                 !elements.worldEditorModal?.classList.contains('visible') &&
                 !importDialogOpen &&
                 !importInsecureDialogOpen &&
+                !deviceTokensDialogOpen &&
                 !e.target.closest('#status-bar') &&
                 !e.target.closest('#nav-bar') &&
                 !e.target.closest('.menu-dropdown') &&
@@ -8901,6 +10789,8 @@
                     elements.actionsListModal.classList.contains('visible') ||
                     elements.actionsEditorModal.classList.contains('visible') ||
                     elements.actionConfirmModal.classList.contains('visible') ||
+                    elements.gagsModal.classList.contains('visible') ||
+                    elements.tagRoutesModal.classList.contains('visible') ||
                     elements.worldsModal.classList.contains('visible') ||
                     elements.worldSelectorModal.classList.contains('visible') ||
                     elements.settingsModal.classList.contains('visible') ||
@@ -8910,6 +10800,7 @@
                     activeCustomDropdown !== null ||
                     importDialogOpen ||
                     importInsecureDialogOpen ||
+                    deviceTokensDialogOpen ||
                     menuOpen;
             }
 
@@ -9037,7 +10928,7 @@
                 closeSearchPopup();
             } else if (e.key === 'Enter') {
                 e.preventDefault();
-                advanceSearch();
+                if (searchInPlace) startSearchNavigation(); else advanceSearch();
             }
         });
 
@@ -9083,14 +10974,27 @@
                 return;
             }
 
+            // Per-world function key macros (/macro) override every other binding.
+            // Skipped for a key a popup's own handler already took (F4 closing the filter).
+            if (!e.defaultPrevented) {
+                const macroCmd = fkeyMacroCommand(e);
+                if (macroCmd) {
+                    e.preventDefault();
+                    e.stopPropagation();
+                    send({ type: 'SendCommand', world_index: currentWorldIndex, command: macroCmd });
+                    return;
+                }
+            }
+
             // Handle F-keys and shortcuts globally via keybinding system
             // (before popup checks which have early returns)
             {
                 const keyName = keyEventToName(e);
                 const action = lookupBinding(keyName);
                 if (action === 'help' || action === 'toggle_tags' || action === 'filter_popup' ||
-                    action === 'search_popup' ||
-                    action === 'toggle_action_highlight' || action === 'toggle_gmcp_media') {
+                    action === 'search_popup' || action === 'scrollback_search' ||
+                    action === 'toggle_action_highlight' || action === 'toggle_gmcp_media' ||
+                    action === 'world_switcher') {
                     e.preventDefault();
                     e.stopPropagation();
                     dispatchAction(action);
@@ -9098,6 +11002,24 @@
                 }
             }
 
+            // Handle the quick world switcher (typing goes to its filter box)
+            if (worldSwitcherOpen) {
+                if (e.key === 'Escape') {
+                    e.preventDefault();
+                    closeWorldSwitcher();
+                } else if (e.key === 'Enter') {
+                    e.preventDefault();
+                    selectWorldSwitcherEntry();
+                } else if (e.key === 'ArrowUp' || (e.key === 'Tab' && e.shiftKey) || (e.ctrlKey && e.key === 'p')) {
+                    e.preventDefault();
+                    moveWorldSwitcherSelection(-1);
+                } else if (e.key === 'ArrowDown' || e.key === 'Tab' || (e.ctrlKey && e.key === 'n')) {
+                    e.preventDefault();
+                    moveWorldSwitcherSelection(1);
+                }
+                return;
+            }
+
             // Handle help popup
             if (helpPopupOpen) {
                 if (e.key === 'Escape' || e.key === 'Enter') {
@@ -9162,6 +11084,10 @@
                 if (e.key === 'Escape') {
                     e.preventDefault();
                     closeActionsListPopup();
+                } else if (e.shiftKey && (e.key === 'ArrowUp' || e.key === 'ArrowDown')) {
+                    e.preventDefault();
+                    e.stopPropagation();
+                    moveSelectedAction(e.key === 'ArrowUp');
                 } else if (e.key === 'ArrowUp') {
                     e.preventDefault();
                     e.stopPropagation();
@@ -9196,6 +11122,54 @@
                 return;
             }
 
+            // Handle gags popup
+            if (gagsPopupOpen) {
+                if (e.key === 'Escape') {
+                    e.preventDefault();
+                    closeGagsPopup();
+                } else if (e.key === 'ArrowUp' || e.key === 'ArrowDown') {
+                    e.preventDefault();
+                    e.stopPropagation();
+                    if (gags.length > 0) {
+                        // Wrap at either end
+                        const step = e.key === 'ArrowUp' ? gags.length - 1 : 1;
+                        selectedGagIndex = (selectedGagIndex + step) % gags.length;
+                        renderGagsList();
+                    }
+                } else if (e.key === ' ' && e.target.tagName !== 'BUTTON') {
+                    e.preventDefault();
+                    toggleSelectedGag();
+                } else if (e.key === 'Delete') {
+                    e.preventDefault();
+                    deleteSelectedGag();
+                }
+                return;
+            }
+
+            // Handle tag routes popup
+            if (tagRoutesPopupOpen) {
+                if (e.key === 'Escape') {
+                    e.preventDefault();
+                    closeTagRoutesPopup();
+                } else if (e.key === 'ArrowUp' || e.key === 'ArrowDown') {
+                    e.preventDefault();
+                    e.stopPropagation();
+                    if (tagRoutes.length > 0) {
+                        // Wrap at either end
+                        const step = e.key === 'ArrowUp' ? tagRoutes.length - 1 : 1;
+                        selectedTagRouteIndex = (selectedTagRouteIndex + step) % tagRoutes.length;
+                        renderTagRoutesList();
+                    }
+                } else if (e.key === ' ' && e.target.tagName !== 'BUTTON') {
+                    e.preventDefault();
+                    toggleSelectedTagRoute();
+                } else if (e.key === 'Delete') {
+                    e.preventDefault();
+                    deleteSelectedTagRoute();
+                }
+                return;
+            }
+
             // Handle worlds list popup
             if (worldsPopupOpen) {
                 // Get connected worlds for navigation
@@ -9294,6 +11268,15 @@
                 return;
             }
 
+            // Handle /web tokens dialog — same reasoning (its name field is a real <input>)
+            if (deviceTokensDialogOpen) {
+                if (e.key === 'Escape') {
+                    e.preventDefault();
+                    hideDeviceTokensDialog();
+                }
+                return;
+            }
+
             // Handle /import insecure-transport confirm — no text inputs, but still needs a
             // guard so its own Escape/click handling isn't shadowed by the catch-all below.
             if (importInsecureDialogOpen) {
@@ -9373,14 +11356,59 @@
             // Clear history search state on non-search keys
             const keyName = keyEventToName(e);
             const action = lookupBinding(keyName);
+
+            // Ctrl+R reverse history search takes keys until one ends it
+            if (reverseSearch && reverseSearchKey(e, keyName)) {
+                e.preventDefault();
+                e.stopPropagation();
+                return;
+            }
+
+            // Scrollback search navigation (n/N after Enter in a Ctrl+F search)
+            if (searchNavigating && handleSearchNavKey(e)) return;
+
+            // Ctrl+F on an empty input line searches the scrollback (the default ^F
+            // binding, cursor_right, has nothing to do there)
+            if (keyName === '^F' && action === 'cursor_right' && elements.input.value === '') {
+                e.preventDefault();
+                e.stopPropagation();
+                openSearchPopup(true);
+                return;
+            }
             if (e.key !== 'Escape' && action !== 'history_search_backward' && action !== 'history_search_forward') {
                 clearHistorySearch();
             }
 
-            // Enter is always handled directly (not configurable)
+            // Numpad speedwalk (/numpad): keypad digits walk when enabled for this world.
+            // e.code tells keypad keys apart from the digit row, with Num Lock on or off.
+            const numpadCmd = numpadWalkCommand(e);
+            if (numpadCmd) {
+                e.preventDefault();
+                e.stopPropagation();
+                send({ type: 'SendCommand', world_index: currentWorldIndex, command: numpadCmd });
+                return;
+            }
+
+            // Per-world function key macros (/macro) override every other binding
+            const macroCmd = fkeyMacroCommand(e);
+            if (macroCmd) {
+                e.preventDefault();
+                e.stopPropagation();
+                send({ type: 'SendCommand', world_index: currentWorldIndex, command: macroCmd });
+                return;
+            }
+
+            // Enter is always handled directly (not configurable); Shift+Enter starts a
+            // new line, each line going out as its own command
             if (e.key === 'Enter') {
                 e.preventDefault();
                 e.stopPropagation();
+                if (e.shiftKey) {
+                    const input = elements.input;
+                    input.setRangeText('\n', input.selectionStart, input.selectionEnd, 'end');
+                    fitInputRows();
+                    return;
+                }
                 sendCommand();
                 return;
             }
@@ -9408,6 +11436,7 @@
         elements.input.addEventListener('input', function() {
             resetCompletion();
             checkTempConversion();
+            fitInputRows();
         });
 
         // Auth submit
@@ -9442,16 +11471,11 @@
             }
         };
 
-        // Reconnect modal buttons (shown when send fails due to disconnection)
-        elements.reconnectBtn.onclick = function() {
-            hideReconnectModal();
+        // Offline banner: retry now instead of waiting out the backoff
+        elements.offlineRetryBtn.onclick = function() {
+            if (reconnectTimer) { clearTimeout(reconnectTimer); reconnectTimer = null; }
             forceReconnect();
-        };
-        elements.reconnectCancelBtn.onclick = function() {
-            hideReconnectModal();
-            // Clear pending command
-            pendingReconnectCommand = null;
-            pendingReconnectWorldIndex = null;
+            updateOfflineBanner();
         };
 
         // Auth username field Enter key handler (multiuser mode)
@@ -9487,8 +11511,29 @@
             }
         };
         elements.actionDeleteBtn.onclick = openActionsConfirmPopup;
+        elements.actionDuplicateBtn.onclick = duplicateSelectedAction;
+        elements.actionMoveUpBtn.onclick = () => moveSelectedAction(true);
+        elements.actionMoveDownBtn.onclick = () => moveSelectedAction(false);
+        elements.actionImportBtn.onclick = () => elements.actionImportFile.click();
+        elements.actionImportFile.onchange = importActions;
+        elements.actionExportBtn.onclick = exportActions;
         elements.actionCancelBtn.onclick = closeActionsListPopup;
         elements.actionsListCloseBtn.onclick = closeActionsListPopup;
+
+        // Gags popup
+        elements.gagAddBtn.onclick = addGag;
+        elements.gagToggleBtn.onclick = toggleSelectedGag;
+        elements.gagKeepBtn.onclick = keepSelectedGag;
+        elements.gagDeleteBtn.onclick = deleteSelectedGag;
+        elements.gagsOkBtn.onclick = closeGagsPopup;
+        elements.gagsCloseBtn.onclick = closeGagsPopup;
+
+        // Tag routes popup
+        elements.tagRouteAddBtn.onclick = addTagRoute;
+        elements.tagRouteToggleBtn.onclick = toggleSelectedTagRoute;
+        elements.tagRouteDeleteBtn.onclick = deleteSelectedTagRoute;
+        elements.tagRoutesOkBtn.onclick = closeTagRoutesPopup;
+        elements.tagRoutesCloseBtn.onclick = closeTagRoutesPopup;
         elements.actionFilter.oninput = function() {
             // Update selection if current selection is filtered out
             const visibleIndices = getFilteredActionIndices();
@@ -9500,6 +11545,9 @@
 
         // Actions Editor popup
         elements.actionSaveBtn.onclick = saveAction;
+        elements.actionMatchType.addEventListener('change', scheduleActionTest);
+        elements.actionCommand.addEventListener('input', scheduleActionTest);
+        elements.actionTest.addEventListener('input', scheduleActionTest);
         elements.actionEditorDeleteBtn.onclick = function() {
             if (editingActionIndex >= 0 && editingActionIndex < actions.length) {
                 selectedActionIndex = editingActionIndex;
@@ -9553,10 +11601,23 @@
         elements.worldEditLoggingToggle.onclick = function() {
             this.classList.toggle('active');
         };
+        if (elements.worldEditLogSessionsToggle) {
+            elements.worldEditLogSessionsToggle.onclick = function() {
+                this.classList.toggle('active');
+            };
+        }
+        elements.worldEditAutoLoginSelect.onchange = function() {
+            updateLoginScriptVisibility(this.value);
+        };
         elements.worldEditKeepAliveSelect.onchange = function() {
             updateKeepAliveCmdVisibility(this.value);
         };
 
+        elements.worldSwitcherInput.oninput = updateWorldSwitcher;
+        elements.worldSwitcherInput.onblur = function() {
+            if (worldSwitcherOpen) closeWorldSwitcher();
+        };
+
         elements.worldFilter.oninput = function() {
             // Update selection if current selection is filtered out
             const visibleIndices = getFilteredWorldIndices();
@@ -9611,10 +11672,18 @@
             setupTlsProxy = !setupTlsProxy;
             updateSetupPopupUI();
         };
+        elements.setupConfirmQuitToggle.onclick = function() {
+            setupConfirmQuit = !setupConfirmQuit;
+            updateSetupPopupUI();
+        };
         elements.setupNewLineIndicatorToggle.onclick = function() {
             setupNewLineIndicator = !setupNewLineIndicator;
             updateSetupPopupUI();
         };
+        elements.setupTabBarToggle.onclick = function() {
+            setupTabBar = !setupTabBar;
+            updateSetupPopupUI();
+        };
         elements.setupDebugToggle.onclick = function() {
             setupDebug = !setupDebug;
             updateSetupPopupUI();
@@ -9701,7 +11770,7 @@
             updateWebPopupUI();
         };
         elements.webCustomCertSelect.onchange = function() {
-            editCustomCert = this.value === 'yes';
+            editCertMode = this.value;
             updateWebPopupUI();
         };
         // Modify Key button — opens the copy/regen/delete dialog
@@ -9714,6 +11783,9 @@
 
         // Font popup
         // Font close/cancel/save handled by unified settings buttons
+        if (elements.fontFamilySearch) {
+            elements.fontFamilySearch.oninput = renderFontFamilyList;
+        }
         elements.fontWeightMinus.onclick = function() {
             fontEditWeight = Math.max(1, fontEditWeight - 50);
             updateFontPopupUI();
@@ -9835,12 +11907,15 @@
         if (elements.settingsHelpBtn) elements.settingsHelpBtn.onclick = function() {
             var helpTab = settingsActiveTab === 'web' ? 'web' :
                           settingsActiveTab === 'font' ? 'font' :
+                          settingsActiveTab === 'sounds' ? 'sounds' :
                           settingsActiveTab === 'clay-server' ? 'clay-server' : 'setup';
             openPopupHelp(helpTab);
         };
         if (elements.worldEditHelpBtn) elements.worldEditHelpBtn.onclick = function() { openPopupHelp('worldEditor'); };
         if (elements.worldSelectorHelpBtn) elements.worldSelectorHelpBtn.onclick = function() { openPopupHelp('worldSelector'); };
         if (elements.actionsListHelpBtn) elements.actionsListHelpBtn.onclick = function() { openPopupHelp('actionsList'); };
+        if (elements.gagsHelpBtn) elements.gagsHelpBtn.onclick = function() { openPopupHelp('gags'); };
+        if (elements.tagRoutesHelpBtn) elements.tagRoutesHelpBtn.onclick = function() { openPopupHelp('tagRoutes'); };
         if (elements.actionEditorHelpBtn) elements.actionEditorHelpBtn.onclick = function() { openPopupHelp('actionEditor'); };
         if (elements.connectionsHelpBtn) elements.connectionsHelpBtn.onclick = function() { openPopupHelp('connections'); };
         if (elements.menuHelpBtn) elements.menuHelpBtn.onclick = function() { openPopupHelp('menu'); };
@@ -9906,6 +11981,15 @@
             }
         }, 30000);
 
+        // A clicked push notification (sw.js) brings this page forward: show its world
+        if ('serviceWorker' in navigator) {
+            navigator.serviceWorker.addEventListener('message', function(event) {
+                if (!event.data || event.data.type !== 'clay-push-open') return;
+                const idx = worlds.findIndex(w => w.name === event.data.world);
+                if (idx >= 0 && idx !== currentWorldIndex) switchWorldLocal(idx);
+            });
+        }
+
         // Handle visibility change (browser sleep/wake)
         // When page becomes visible, ping the server to verify the connection is alive.
         // If pong arrives in time, resync. If not, reconnect.
@@ -10107,6 +12191,152 @@
         return !!(dlg && dlg.style.display !== 'none');
     }
 
+    // /web tokens [add <name>|revoke <name>]: the server keeps the tokens and answers
+    // with DeviceTokens, which opens the dialog below
+    function webTokensCommand(args) {
+        const sub = (args[0] || '').toLowerCase();
+        const name = args.slice(1).join(' ');
+        if ((sub === '' || sub === 'list') && !name) {
+            send({ type: 'RequestDeviceTokens' });
+        } else if (sub === 'add' && name) {
+            send({ type: 'CreateDeviceToken', name: name });
+        } else if (sub === 'revoke' && name) {
+            send({ type: 'RevokeDeviceToken', name: name });
+        } else {
+            appendClientLine('Usage: /web tokens [add <name>|revoke <name>]', currentWorldIndex, 'system');
+        }
+    }
+
+    // /web push on|off: this browser's Web Push subscription (web_push.rs). sw.js shows
+    // the pushes, so activity is reported with the tab in the background or the phone
+    // locked. The server answers PushSubscribe/PushUnsubscribe with a line of its own.
+    function webPushCommand(on) {
+        const say = function(text) { appendClientLine(text, currentWorldIndex, 'system'); };
+        if (!('serviceWorker' in navigator) || !('PushManager' in window)) {
+            say(window.isSecureContext === false
+                ? 'Push notifications need HTTPS with a trusted certificate (see /web acme).'
+                : "This browser doesn't support push notifications.");
+            return;
+        }
+        navigator.serviceWorker.register(basePath() + '/sw.js').then(function(reg) {
+            return reg.pushManager.getSubscription().then(function(existing) {
+                if (!on) {
+                    if (!existing) {
+                        say('Push notifications are already off for this browser.');
+                        return;
+                    }
+                    send({ type: 'PushUnsubscribe', endpoint: existing.endpoint });
+                    return existing.unsubscribe();
+                }
+                if (!pushPublicKey) {
+                    say("The server has no push key (it couldn't write vapid.key to its config directory).");
+                    return;
+                }
+                return Notification.requestPermission().then(function(permission) {
+                    if (permission !== 'granted') {
+                        say('Notifications are blocked for this site; allow them in the browser first.');
+                        return;
+                    }
+                    return existing || reg.pushManager.subscribe({
+                        userVisibleOnly: true,
+                        applicationServerKey: base64UrlToBytes(pushPublicKey)
+                    });
+                }).then(function(subscription) {
+                    if (!subscription) return;
+                    const json = subscription.toJSON();
+                    send({ type: 'PushSubscribe', endpoint: json.endpoint, p256dh: json.keys.p256dh, auth: json.keys.auth });
+                });
+            });
+        }).catch(function(e) {
+            say('Push notifications failed: ' + (e && e.message ? e.message : e));
+        });
+    }
+
+    function base64UrlToBytes(text) {
+        const b64 = (text + '='.repeat((4 - text.length % 4) % 4)).replace(/-/g, '+').replace(/_/g, '/');
+        return Uint8Array.from(atob(b64), function(c) { return c.charCodeAt(0); });
+    }
+
+    // Device tokens dialog: one row per token with its own Revoke, a name field to add
+    // one, and a just-created token's key (shown this once) with Copy. Styled like the
+    // Modify Key dialog.
+    function showDeviceTokensDialog(tokens, newToken) {
+        deviceTokensDialogOpen = true;
+        let dlg = document.getElementById('device-tokens-dialog');
+        if (!dlg) {
+            dlg = document.createElement('div');
+            dlg.id = 'device-tokens-dialog';
+            dlg.style.cssText = 'position:fixed;top:0;left:0;width:100%;height:100%;background:rgba(0,0,0,0.6);z-index:3000;display:flex;align-items:center;justify-content:center;';
+            document.body.appendChild(dlg);
+        }
+        const day = function(secs) { return new Date(secs * 1000).toLocaleDateString(); };
+        const rows = tokens.map(function(t, i) {
+            return `<tr>
+                <td style="padding:4px 8px 4px 0;">${escapeHtml(t.name)}</td>
+                <td style="padding:4px 8px;opacity:0.8;">${escapeHtml(day(t.created_at))}</td>
+                <td style="padding:4px 8px;opacity:0.8;">${t.last_used ? escapeHtml(day(t.last_used)) : 'never'}</td>
+                <td style="padding:4px 0;text-align:right;"><button class="device-token-revoke" data-index="${i}" style="padding:4px 10px;background:#c00;color:#fff;border:none;border-radius:4px;">Revoke</button></td>
+            </tr>`;
+        }).join('');
+        const list = tokens.length > 0
+            ? `<table style="width:100%;border-collapse:collapse;margin-bottom:12px;">
+                <tr style="opacity:0.7;text-align:left;"><th style="padding:4px 8px 4px 0;">Device</th><th style="padding:4px 8px;">Created</th><th style="padding:4px 8px;">Last used</th><th></th></tr>
+                ${rows}
+            </table>`
+            : '<div style="margin-bottom:12px;opacity:0.85;">No device tokens yet.</div>';
+        const fresh = newToken
+            ? `<div style="margin-bottom:12px;padding:8px;border:1px solid #555;border-radius:4px;">
+                <div style="margin-bottom:6px;">Key for ${escapeHtml(newToken.name)} (shown only now; enter it as the device's auth key):</div>
+                <div style="font-family:monospace;font-size:0.85em;word-break:break-all;margin-bottom:8px;">${escapeHtml(newToken.token)}</div>
+                <button id="device-tokens-copy" style="padding:6px 14px;">Copy</button>
+            </div>`
+            : '';
+        dlg.innerHTML = sanitizeHtml(`
+            <div style="background:#1a1a1a;color:#eee;border:2px solid #555;border-radius:8px;padding:20px;max-width:560px;width:90%;max-height:90%;overflow-y:auto;">
+                <div style="font-weight:bold;font-size:1.1em;margin-bottom:10px;">Device Tokens</div>
+                ${list}
+                ${fresh}
+                <div style="display:flex;gap:10px;margin-bottom:16px;">
+                    <input id="device-tokens-name" type="text" placeholder="New device name" style="flex:1;padding:6px;" autocomplete="off">
+                    <button id="device-tokens-add" style="padding:8px 16px;">Add</button>
+                </div>
+                <div style="display:flex;justify-content:flex-end;">
+                    <button id="device-tokens-close" style="padding:8px 16px;">Close</button>
+                </div>
+            </div>
+        `);
+        dlg.style.display = 'flex';
+
+        function add() {
+            const name = document.getElementById('device-tokens-name').value.trim();
+            if (name) send({ type: 'CreateDeviceToken', name: name });
+        }
+        dlg.querySelectorAll('.device-token-revoke').forEach(function(btn) {
+            btn.onclick = function() {
+                const token = tokens[parseInt(btn.getAttribute('data-index'), 10)];
+                if (token) send({ type: 'RevokeDeviceToken', name: token.name });
+            };
+        });
+        if (newToken) {
+            document.getElementById('device-tokens-copy').onclick = function() {
+                copyTextToClipboard(newToken.token);
+            };
+        }
+        document.getElementById('device-tokens-add').onclick = add;
+        document.getElementById('device-tokens-name').onkeydown = function(e) {
+            if (e.key === 'Enter') add();
+        };
+        document.getElementById('device-tokens-close').onclick = hideDeviceTokensDialog;
+    }
+
+    function hideDeviceTokensDialog() {
+        deviceTokensDialogOpen = false;
+        const dlg = document.getElementById('device-tokens-dialog');
+        if (dlg) {
+            dlg.style.display = 'none';
+        }
+    }
+
     // /import dialogs (plan i-d-like-to-make-snuggly-rain.md, step 7). Same pattern as
     // showCertMismatchDialog above: a dynamically created full-screen overlay, styled
     // inline, content run through sanitizeHtml/escapeHtml.
@@ -0,0 +1,34 @@
+// Clay service worker: Web Push activity notifications (see web_push.rs).
+// A push carries { title, body, world }. It's shown unless a focused Clay page is
+// already in front; one notification per world, replaced by the next. Clicking it
+// brings the web interface forward on that world.
+self.addEventListener('push', function(event) {
+    let data = {};
+    try {
+        data = event.data ? event.data.json() : {};
+    } catch (e) {
+        data = {};
+    }
+    event.waitUntil(self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then(function(list) {
+        if (list.some(function(c) { return c.focused && c.visibilityState === 'visible'; })) return;
+        return self.registration.showNotification(data.title || 'Clay', {
+            body: data.body || '',
+            tag: 'clay-activity-' + (data.world || ''),
+            icon: 'clay2.png',
+            data: { world: data.world || '' }
+        });
+    }));
+});
+
+self.addEventListener('notificationclick', function(event) {
+    event.notification.close();
+    const world = (event.notification.data && event.notification.data.world) || '';
+    event.waitUntil(self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then(function(list) {
+        const page = list[0];
+        if (page) {
+            page.postMessage({ type: 'clay-push-open', world: world });
+            return page.focus();
+        }
+        return self.clients.openWindow(self.registration.scope);
+    }));
+});
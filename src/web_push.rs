@@ -0,0 +1,276 @@
+//! Web Push for the browser client: "activity in world X" notifications that arrive
+//! while the tab is in the background or the phone is locked.
+//!
+//! The web client subscribes through its service worker (`/sw.js`) with the server's
+//! VAPID public key (RFC 8292) from `GlobalSettingsMsg::push_public_key`, and hands the
+//! subscription over as `WsMessage::PushSubscribe`; subscriptions are kept in
+//! `Settings::push_subscriptions`. A push is a small JSON payload encrypted per
+//! RFC 8291 (aes128gcm) and POSTed to the subscription's endpoint with a VAPID JWT.
+//! The VAPID key is an ES256 key in `vapid.key` in the config directory; replacing it
+//! invalidates every subscription.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
+use base64::Engine as _;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_128_GCM};
+use ring::agreement::{self, EphemeralPrivateKey, UnparsedPublicKey, ECDH_P256};
+use ring::hkdf;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+/// Minimum time between two activity pushes for the same world, unless it's seen in between
+pub const ACTIVITY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// How long a push service holds a push for an offline device
+const TTL_SECS: u64 = 12 * 60 * 60;
+/// VAPID JWT lifetime (push services refuse more than 24 hours)
+const JWT_LIFETIME_SECS: u64 = 12 * 60 * 60;
+/// Contact for push service operators, required in the JWT's `sub` claim
+const VAPID_SUBJECT: &str = "https://github.com/c-hudson/clay";
+/// aes128gcm record size; a push payload is always a single record
+const RECORD_SIZE: u32 = 4096;
+
+/// One browser's push subscription (the PushSubscription JSON's endpoint and keys)
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushSubscription {
+    pub endpoint: String,
+    /// The browser's P-256 public key, base64url (uncompressed point)
+    pub p256dh: String,
+    /// The browser's 16-byte auth secret, base64url
+    pub auth: String,
+}
+
+impl PushSubscription {
+    /// Check a subscription from `WsMessage::PushSubscribe`
+    pub fn new(endpoint: &str, p256dh: &str, auth: &str) -> Result<Self, String> {
+        let url = url::Url::parse(endpoint).map_err(|_| "invalid push endpoint".to_string())?;
+        if url.scheme() != "https" || url.host_str().is_none() {
+            return Err("push endpoints must be https URLs".to_string());
+        }
+        let key = b64_decode(p256dh).filter(|k| k.len() == 65 && k[0] == 0x04);
+        let secret = b64_decode(auth).filter(|a| a.len() == 16);
+        if key.is_none() || secret.is_none() {
+            return Err("invalid push subscription keys".to_string());
+        }
+        Ok(Self { endpoint: endpoint.to_string(), p256dh: p256dh.to_string(), auth: auth.to_string() })
+    }
+}
+
+fn vapid_key_path() -> PathBuf {
+    crate::clay_config_path("vapid.key")
+}
+
+fn b64(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+/// Browsers hand keys out base64url, with or without padding
+fn b64_decode(text: &str) -> Option<Vec<u8>> {
+    URL_SAFE_NO_PAD.decode(text.trim().trim_end_matches('=')).ok()
+}
+
+/// The VAPID key, created (owner-only) on first use
+fn vapid_key() -> Option<&'static EcdsaKeyPair> {
+    static KEY: OnceLock<Option<EcdsaKeyPair>> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let rng = SystemRandom::new();
+        let path = vapid_key_path();
+        let stored = std::fs::read_to_string(&path).ok()
+            .and_then(|text| BASE64.decode(text.trim()).ok());
+        let pkcs8 = match stored {
+            Some(pkcs8) => pkcs8,
+            None => {
+                let doc = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).ok()?;
+                crate::util::write_secret_file(&path, BASE64.encode(doc.as_ref()).as_bytes()).ok()?;
+                doc.as_ref().to_vec()
+            }
+        };
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &pkcs8, &rng).ok()
+    }).as_ref()
+}
+
+/// The VAPID public key for `PushManager.subscribe` (base64url uncompressed point),
+/// or empty if no key could be loaded or created
+pub fn public_key() -> String {
+    vapid_key().map(|key| b64(key.public_key().as_ref())).unwrap_or_default()
+}
+
+/// The JWT's audience: the endpoint's origin
+fn audience(endpoint: &str) -> Option<String> {
+    let url = url::Url::parse(endpoint).ok()?;
+    Some(url.origin().ascii_serialization())
+}
+
+/// A signed VAPID JWT for `audience`, expiring JWT_LIFETIME_SECS after `now`
+fn vapid_jwt(key: &EcdsaKeyPair, audience: &str, now: u64) -> Option<String> {
+    let header = b64(br#"{"typ":"JWT","alg":"ES256"}"#);
+    let claims = serde_json::json!({ "aud": audience, "exp": now + JWT_LIFETIME_SECS, "sub": VAPID_SUBJECT });
+    let signing_input = format!("{}.{}", header, b64(claims.to_string().as_bytes()));
+    let signature = key.sign(&SystemRandom::new(), signing_input.as_bytes()).ok()?;
+    Some(format!("{}.{}", signing_input, b64(signature.as_ref())))
+}
+
+/// Output length for ring's HKDF expand
+struct Len(usize);
+
+impl hkdf::KeyType for Len {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> Vec<u8> {
+    let mut out = vec![0u8; len];
+    hkdf::Salt::new(hkdf::HKDF_SHA256, salt)
+        .extract(ikm)
+        .expand(&[info], Len(len))
+        .and_then(|okm| okm.fill(&mut out))
+        .expect("HKDF output within SHA-256 limits");
+    out
+}
+
+/// RFC 8291 body from the ECDH secret and both public keys: the aes128gcm header
+/// (salt, record size, our public key) followed by the single encrypted record
+fn encrypt_with(
+    ecdh_secret: &[u8],
+    ua_public: &[u8],
+    auth_secret: &[u8],
+    as_public: &[u8],
+    salt: &[u8; 16],
+    payload: &[u8],
+) -> Result<Vec<u8>, String> {
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(ua_public);
+    key_info.extend_from_slice(as_public);
+    let ikm = hkdf_sha256(auth_secret, ecdh_secret, &key_info, 32);
+    let cek = hkdf_sha256(salt, &ikm, b"Content-Encoding: aes128gcm\0", 16);
+    let nonce = hkdf_sha256(salt, &ikm, b"Content-Encoding: nonce\0", 12);
+
+    let key = UnboundKey::new(&AES_128_GCM, &cek).map_err(|_| "bad content key".to_string())?;
+    let nonce = Nonce::try_assume_unique_for_key(&nonce).map_err(|_| "bad nonce".to_string())?;
+    let mut record = payload.to_vec();
+    record.push(0x02); // padding delimiter of the last record
+    LessSafeKey::new(key)
+        .seal_in_place_append_tag(nonce, Aad::empty(), &mut record)
+        .map_err(|_| "encryption failed".to_string())?;
+
+    let mut body = Vec::with_capacity(21 + as_public.len() + record.len());
+    body.extend_from_slice(salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public.len() as u8);
+    body.extend_from_slice(as_public);
+    body.extend_from_slice(&record);
+    Ok(body)
+}
+
+/// Encrypt `payload` for one subscription with a fresh key pair and salt
+fn encrypt(subscription: &PushSubscription, payload: &[u8]) -> Result<Vec<u8>, String> {
+    let rng = SystemRandom::new();
+    let ua_public = b64_decode(&subscription.p256dh).ok_or("invalid p256dh key")?;
+    let auth_secret = b64_decode(&subscription.auth).ok_or("invalid auth secret")?;
+    let private = EphemeralPrivateKey::generate(&ECDH_P256, &rng).map_err(|_| "key generation failed")?;
+    let as_public = private.compute_public_key().map_err(|_| "key generation failed")?;
+    let mut salt = [0u8; 16];
+    rng.fill(&mut salt).map_err(|_| "random number generator unavailable")?;
+    let ecdh_secret = agreement::agree_ephemeral(private, &UnparsedPublicKey::new(&ECDH_P256, &ua_public), |s| s.to_vec())
+        .map_err(|_| "invalid p256dh key".to_string())?;
+    encrypt_with(&ecdh_secret, &ua_public, &auth_secret, as_public.as_ref(), &salt, payload)
+}
+
+/// The notification a push carries; the service worker shows it
+pub fn activity_payload(world: &str, body: &str) -> String {
+    serde_json::json!({ "title": world, "body": body, "world": world }).to_string()
+}
+
+/// Send `payload` to every subscription. Returns the endpoints the push service says
+/// are gone (404/410), which should be forgotten.
+pub async fn send(subscriptions: Vec<PushSubscription>, payload: String) -> Vec<String> {
+    let Some(key) = vapid_key() else { return Vec::new() };
+    let Ok(http) = reqwest::Client::builder()
+        .user_agent(concat!("clay/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(30))
+        .build()
+    else {
+        return Vec::new();
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let public_key = b64(key.public_key().as_ref());
+    let mut gone = Vec::new();
+    for subscription in subscriptions {
+        let Some(jwt) = audience(&subscription.endpoint).and_then(|aud| vapid_jwt(key, &aud, now)) else { continue };
+        let Ok(body) = encrypt(&subscription, payload.as_bytes()) else { continue };
+        let response = http.post(&subscription.endpoint)
+            .header("TTL", TTL_SECS.to_string())
+            .header("Urgency", "normal")
+            .header("Content-Encoding", "aes128gcm")
+            .header("Content-Type", "application/octet-stream")
+            .header("Authorization", format!("vapid t={}, k={}", jwt, public_key))
+            .body(body)
+            .send()
+            .await;
+        if let Ok(response) = response {
+            if matches!(response.status().as_u16(), 404 | 410) {
+                gone.push(subscription.endpoint);
+            }
+        }
+    }
+    gone
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(text: &str) -> Vec<u8> {
+        b64_decode(text).unwrap()
+    }
+
+    #[test]
+    fn encrypts_the_rfc_8291_example() {
+        // RFC 8291 Appendix A, with the sender's ECDH secret and salt fixed
+        let salt: [u8; 16] = d("DGv6ra1nlYgDCS1FRnbzlw").try_into().unwrap();
+        let body = encrypt_with(
+            &d("kyrL1jIIOHEzg3sM2ZWRHDRB62YACZhhSlknJ672kSs"),
+            &d("BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4"),
+            &d("BTBZMqHH6r4Tts7J_aSIgg"),
+            &d("BP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A8"),
+            &salt,
+            b"When I grow up, I want to be a watermelon",
+        ).unwrap();
+        assert_eq!(b64(&body), "DGv6ra1nlYgDCS1FRnbzlwAAEABBBP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A_yl95bQpu6cVPTpK4Mqgkf1CXztLVBSt2Ks3oZwbuwXPXLWyouBWLVWGNWQexSgSxsj_Qulcy4a-fN");
+    }
+
+    #[test]
+    fn subscriptions_are_checked() {
+        let p256dh = "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4";
+        let auth = "BTBZMqHH6r4Tts7J_aSIgg";
+        assert!(PushSubscription::new("https://push.example.net/send/abc", p256dh, auth).is_ok());
+        assert!(PushSubscription::new("https://push.example.net/send/abc", p256dh, "BTBZMqHH6r4Tts7J_aSIgg==").is_ok());
+        assert!(PushSubscription::new("http://push.example.net/send/abc", p256dh, auth).is_err());
+        assert!(PushSubscription::new("not a url", p256dh, auth).is_err());
+        assert!(PushSubscription::new("https://push.example.net/x", "BCVx", auth).is_err());
+        assert!(PushSubscription::new("https://push.example.net/x", p256dh, "short").is_err());
+    }
+
+    #[test]
+    fn vapid_jwt_is_signed_for_the_endpoint_origin() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+        let aud = audience("https://fcm.googleapis.com:443/fcm/send/xyz").unwrap();
+        assert_eq!(aud, "https://fcm.googleapis.com");
+        let jwt = vapid_jwt(&key, &aud, 1_000).unwrap();
+        let parts: Vec<&str> = jwt.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        let claims: serde_json::Value = serde_json::from_slice(&d(parts[1])).unwrap();
+        assert_eq!(claims["aud"], "https://fcm.googleapis.com");
+        assert_eq!(claims["exp"], 1_000 + JWT_LIFETIME_SECS);
+        let public = ring::signature::UnparsedPublicKey::new(&ring::signature::ECDSA_P256_SHA256_FIXED, key.public_key().as_ref());
+        assert!(public.verify(format!("{}.{}", parts[0], parts[1]).as_bytes(), &d(parts[2])).is_ok());
+    }
+}
@@ -5,7 +5,8 @@ use tokio::sync::mpsc;
 use tokio::net::TcpListener;
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
-use tokio_tungstenite::{accept_async_with_config, tungstenite::Message as WsRawMessage, tungstenite::protocol::WebSocketConfig};
+use tokio_tungstenite::{accept_hdr_async_with_config, tungstenite::Message as WsRawMessage, tungstenite::protocol::WebSocketConfig};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
 
 // Import AppEvent and Action from the main crate
 use crate::{AppEvent, Action, BanList, debug_log, is_debug_enabled};
@@ -51,6 +52,10 @@ pub enum WsMessage {
         request_key: bool,  // If true, request a new auth key after successful password auth
         #[serde(default)]
         challenge_response: bool,  // If true, password_hash is SHA256(SHA256(password) + challenge)
+        /// Reconnecting client: the newest seq it already holds, by world name. Those
+        /// worlds' InitialState lines are only the ones after it (see WorldStateMsg::resumed)
+        #[serde(default)]
+        resume_seqs: HashMap<String, u64>,
     },
     AuthResponse {
         success: bool,
@@ -74,6 +79,23 @@ pub enum WsMessage {
     },
     // Client requests auth key regeneration (from web settings UI)
     RegenerateAuthKey,
+    // Device tokens (/web tokens): each request is answered with DeviceTokens
+    RequestDeviceTokens,
+    CreateDeviceToken { name: String },
+    RevokeDeviceToken { name: String },
+    // Web Push (web_push.rs): a browser's subscription after /web push on, and its
+    // removal after /web push off; each is answered with a ServerData line
+    PushSubscribe { endpoint: String, p256dh: String, auth: String },
+    PushUnsubscribe { endpoint: String },
+    DeviceTokens {
+        tokens: Vec<DeviceTokenInfo>,
+        /// The key just created (CreateDeviceToken), sent only to the client that asked
+        #[serde(default)]
+        new_token: Option<NewDeviceToken>,
+        /// Why a create/revoke failed, or what it did
+        #[serde(default)]
+        message: Option<String>,
+    },
 
     // Password change (multiuser mode)
     ChangePassword {
@@ -89,6 +111,9 @@ pub enum WsMessage {
     Logout,
     // Logout response (server -> client)
     LoggedOut,
+    /// Server -> client: this connection signed in with the viewer password and only
+    /// watches, or something it sent was refused for that reason
+    ViewOnly { message: String },
 
     // Initial state (server -> client after auth)
     InitialState {
@@ -98,6 +123,9 @@ pub enum WsMessage {
         actions: Vec<Action>,
         #[serde(default)]
         splash_lines: Vec<String>,
+        /// Shortcode names for Tab-completing `:name:` (see /emoji)
+        #[serde(default)]
+        emoji_names: Vec<String>,
     },
 
     // Real-time updates (server -> client)
@@ -111,6 +139,9 @@ pub enum WsMessage {
     /// Response to CreateWorld - tells the requesting client the index of the new world
     WorldCreated { world_index: usize },
     WorldRemoved { world_index: usize },
+    /// A world was dragged to a new position in the world list (see App::reorder_world);
+    /// clients move their copy the same way
+    WorldMoved { from: usize, to: usize },
     WorldSwitched { new_index: usize },
     PromptUpdate { world_index: usize, prompt: String },
     PendingLinesUpdate { world_index: usize, count: usize },
@@ -118,12 +149,22 @@ pub enum WsMessage {
     PendingReleased { world_index: usize, count: usize },
     UnseenCleared { world_index: usize },
     UnseenUpdate { world_index: usize, count: usize },
+    /// BELs in a world's output (see bell.rs): its count since last viewed, and its
+    /// /bell mode so clients can flash or ring ("notify" arrives as a Notification)
+    WorldBell { world_index: usize, bell_count: usize, mode: String },
     /// Broadcast server's activity count (number of worlds with activity)
     ActivityUpdate { count: usize },
     /// Sent to a specific client when its server-side pause state changes
     PausedState { paused: bool },
     /// Broadcast when show_tags setting changes (F2 or /tag command)
     ShowTagsChanged { show_tags: bool },
+    /// Broadcast when the scheduled profile changes (empty = none active; see profiles.rs)
+    ActiveProfileChanged { profile: String },
+    /// Broadcast when a world's server turns its echo on or off (IAC WILL/WONT ECHO);
+    /// clients mask the input while `on`
+    ServerEcho { world_index: usize, on: bool },
+    /// Broadcast as a /sendfile progresses (total 0 = finished or stopped; see sendfile.rs)
+    SendFileProgress { world_index: usize, sent: usize, total: usize },
     /// Server is about to reload - clients should auto-reconnect
     ServerReloading,
     /// Clear all output for a world (from /flush command)
@@ -135,8 +176,9 @@ pub enum WsMessage {
     /// Set client's input buffer (server -> client, for API lookup results like /dict, /urban, /translate)
     SetInputBuffer { text: String, #[serde(default)] cursor_start: bool },
 
-    /// Notification for mobile clients (server -> client)
-    Notification { title: String, message: String },
+    /// Notification for mobile clients (server -> client); `sound` is the file clients
+    /// play while unfocused (the /notify -s sound, else the world's), empty for none
+    Notification { title: String, message: String, #[serde(default)] sound: String },
 
     /// Text-to-speech: speak text aloud on client (server -> client)
     /// Console uses espeak/say subprocess; web/Android uses Web Speech API
@@ -165,7 +207,7 @@ pub enum WsMessage {
     SwitchWorld { world_index: usize },
     ConnectWorld { world_index: usize },
     /// Server -> client: a MUD world's TLS certificate no longer matches the
-    /// trust-on-first-use pin recorded in ~/.clay/known_hosts.dat. The connection
+    /// trust-on-first-use pin recorded in ~/.config/clay/known_hosts.dat. The connection
     /// was blocked; the client should show old/new fingerprints and offer a
     /// "Trust new certificate" action that replies with TrustCertificate.
     CertMismatch { world_index: usize, host: String, old_fingerprint: String, new_fingerprint: String },
@@ -175,6 +217,8 @@ pub enum WsMessage {
     DisconnectWorld { world_index: usize },
     DeleteWorld { world_index: usize },
     CreateWorld { name: String },
+    /// Client -> server: move a world to a new position (a dragged tab)
+    MoveWorld { from: usize, to: usize },
     /// Request to release pending lines (count = number to release, 0 = all)
     ReleasePending { world_index: usize, count: usize },
     /// Selective flush: release only highlighted pending lines, discard rest
@@ -209,12 +253,46 @@ pub enum WsMessage {
         log_enabled: bool,
         encoding: String,
         auto_login: String,
+        /// Script auto-login steps; None (older clients) leaves them unchanged
+        #[serde(default)]
+        login_script: Option<String>,
+        /// On Connect command lines; None (older clients) leaves them unchanged
+        #[serde(default)]
+        connect_commands: Option<String>,
+        /// Input prefix and suffix; None (older clients) leaves them unchanged
+        #[serde(default)]
+        input_prefix: Option<String>,
+        #[serde(default)]
+        input_suffix: Option<String>,
         keep_alive_type: String,
         keep_alive_cmd: String,
+        #[serde(default = "default_keep_alive_interval_secs")]
+        keep_alive_interval_secs: u64,
+        #[serde(default)]
+        silence_alert_mins: u32,
         #[serde(default)]
         gmcp_packages: String,
         #[serde(default)]
         auto_reconnect_secs: String,
+        #[serde(default)]
+        log_format: String,
+        #[serde(default)]
+        log_timestamps: String,
+        #[serde(default)]
+        log_session_markers: Option<bool>,
+        /// "full", "tofu" or "none"; empty leaves it unchanged
+        #[serde(default)]
+        tls_verify: String,
+        #[serde(default)]
+        tls_client_cert: String,
+        #[serde(default)]
+        tls_client_key: String,
+        #[serde(default)]
+        ssh_host: String,
+        #[serde(default)]
+        ssh_user: String,
+        #[serde(default)]
+        ssh_key: String,
     },
     UpdateGlobalSettings {
         more_mode_enabled: bool,
@@ -278,6 +356,36 @@ pub enum WsMessage {
         tts_speak_mode: String,
         #[serde(default)]
         scrollback_enabled: bool,
+        /// Console status bar template; None (web/GUI, which don't edit it) leaves it unchanged
+        #[serde(default)]
+        status_format: Option<String>,
+        /// Console world tab bar; None leaves it unchanged, as for status_format
+        #[serde(default)]
+        tab_bar: Option<bool>,
+        /// Console activity notifications; None leaves it unchanged
+        #[serde(default)]
+        notify_activity: Option<bool>,
+        /// Output lines kept per world; None leaves it unchanged
+        #[serde(default)]
+        scrollback_lines: Option<usize>,
+        /// Ask before quitting with worlds connected; None leaves it unchanged
+        #[serde(default)]
+        confirm_quit: Option<bool>,
+        /// Let's Encrypt domain ("" = off); None leaves it unchanged
+        #[serde(default)]
+        acme_domain: Option<String>,
+        /// Let's Encrypt account contact, applied with acme_domain
+        #[serde(default)]
+        acme_email: Option<String>,
+        /// Web server bind address; None leaves it unchanged
+        #[serde(default)]
+        web_bind_addr: Option<String>,
+        /// Unix socket path for a local reverse proxy ("" = off); None leaves it unchanged
+        #[serde(default)]
+        web_unix_socket: Option<String>,
+        /// Reverse proxy IPs whose X-Forwarded-For is believed; None leaves it unchanged
+        #[serde(default)]
+        web_trusted_proxies: Option<String>,
     },
 
     // Settings update confirmations (server -> client)
@@ -287,6 +395,37 @@ pub enum WsMessage {
     // Actions (triggers)
     ActionsUpdated { actions: Vec<Action> },
     UpdateActions { actions: Vec<Action> },
+    /// Client -> server: copy an action in the actions list (see actions::duplicate_action)
+    DuplicateAction { index: usize },
+    /// Client -> server: move an action one place up or down in the list, which is the
+    /// order triggers are checked in (see actions::move_action)
+    MoveAction { index: usize, up: bool },
+    /// Client -> server: merge a trigger pack file picked in the actions list's Import (see
+    /// actions::parse_action_pack); answered with ImportResult
+    ImportActions { text: String },
+    /// Client -> server: run a sample line through an action's patterns as the server
+    /// would (the action editor's tester; see actions::test_patterns)
+    TestActionPatterns { match_type: String, patterns: Vec<String>, command: String, sample: String },
+    /// Server -> client: tester verdict. `error` names a pattern that doesn't compile;
+    /// otherwise `pattern` is the first one matching the sample (None = no match)
+    ActionPatternsTested { error: Option<String>, pattern: Option<usize>, captures: Vec<String>, gag: bool },
+
+    // Gags (see gags.rs)
+    /// Server -> client: the whole gag list, sent whenever it changes and in answer to RequestGags
+    GagsUpdated { gags: Vec<crate::gags::Gag> },
+    /// Client -> server: send GagsUpdated (the web gags popup opening)
+    RequestGags,
+    /// Client -> server: replace the gag list (the web gags popup's edits)
+    UpdateGags { gags: Vec<crate::gags::Gag> },
+
+    // Tag routes (see tag_routes.rs)
+    /// Server -> client: the whole tag route list, sent whenever it changes and in
+    /// answer to RequestTagRoutes
+    TagRoutesUpdated { routes: Vec<crate::tag_routes::TagRoute> },
+    /// Client -> server: send TagRoutesUpdated (the web tag routes popup opening)
+    RequestTagRoutes,
+    /// Client -> server: replace the tag route list (the web tag routes popup's edits)
+    UpdateTagRoutes { routes: Vec<crate::tag_routes::TagRoute> },
 
     // Ban list management
     /// Request current ban list (client -> server)
@@ -378,7 +517,7 @@ pub enum WsMessage {
     /// Client should show an explicit "passwords will be sent unencrypted" confirmation
     /// and, if accepted, resend ImportSettings with allow_insecure: true.
     ImportNeedsInsecureConfirm { addr: String },
-    /// Local server -> client: final outcome of an import attempt.
+    /// Local server -> client: final outcome of an import attempt (or of ImportActions).
     ImportResult { success: bool, summary: String },
     /// Importer -> target, sent over the outbound connection opened for the import: request
     /// the target's settings/theme/keybindings with all secrets decrypted.
@@ -427,6 +566,96 @@ pub enum WsMessage {
     PongCheck { nonce: u64 },
 }
 
+impl WsMessage {
+    /// ActionPatternsTested reply for a TestActionPatterns request
+    pub fn action_patterns_tested(match_type: &str, patterns: &[String], command: &str, sample: &str) -> Self {
+        let match_type = crate::actions::MatchType::parse(match_type);
+        match crate::actions::test_patterns(patterns, match_type, command, sample) {
+            Err(error) => WsMessage::ActionPatternsTested { error: Some(error), pattern: None, captures: Vec::new(), gag: false },
+            Ok(None) => WsMessage::ActionPatternsTested { error: None, pattern: None, captures: Vec::new(), gag: false },
+            Ok(Some(t)) => WsMessage::ActionPatternsTested { error: None, pattern: Some(t.pattern), captures: t.captures, gag: t.gag },
+        }
+    }
+
+    /// The message's "type" tag as sent on the wire, for logs
+    pub fn kind(&self) -> String {
+        serde_json::to_value(self).ok()
+            .and_then(|v| v.get("type").and_then(|t| t.as_str()).map(str::to_string))
+            .unwrap_or_default()
+    }
+
+    /// Whether a viewer (signed in with the viewer password) may send this. Viewers
+    /// can look around — switch and scroll worlds, resync — but nothing that reaches a
+    /// MUD or changes settings, worlds, actions, keys or bans.
+    pub fn viewer_allowed(&self) -> bool {
+        matches!(self,
+            WsMessage::Ping | WsMessage::PongCheck { .. } | WsMessage::Logout |
+            WsMessage::ClientTypeDeclaration { .. } |
+            WsMessage::SwitchWorld { .. } | WsMessage::CycleWorld { .. } |
+            WsMessage::MarkWorldSeen { .. } |
+            WsMessage::UpdateViewState { .. } | WsMessage::UpdateDimensions { .. } |
+            WsMessage::RequestState | WsMessage::RequestWorldState { .. } |
+            WsMessage::RequestScrollback { .. } |
+            WsMessage::CalculateNextWorld { .. } | WsMessage::CalculatePrevWorld { .. } |
+            WsMessage::CalculateOldestPending { .. } |
+            WsMessage::RequestConnectionsList |
+            WsMessage::ReportSeqMismatch { .. } | WsMessage::ReportDuplicate { .. }
+        )
+    }
+
+    /// This message as a viewer may see it: the web password, auth key and world
+    /// passwords and login scripts blanked (has_password still says whether there
+    /// is one), and auth keys, device tokens and settings exports not sent at all
+    pub fn for_viewer(self) -> Option<WsMessage> {
+        fn redact_world(settings: &mut WorldSettingsMsg) {
+            settings.password.clear();
+            settings.login_script.clear();
+            settings.connect_commands.clear();
+        }
+        fn redact_global(settings: &mut GlobalSettingsMsg) {
+            settings.auth_key.clear();
+            settings.ws_password.clear();
+        }
+        match self {
+            WsMessage::KeyGenerated { .. } | WsMessage::DeviceTokens { .. } | WsMessage::SettingsExport { .. } => None,
+            WsMessage::InitialState { mut worlds, mut settings, current_world_index, actions, splash_lines, emoji_names } => {
+                worlds.iter_mut().for_each(|w| redact_world(&mut w.settings));
+                redact_global(&mut settings);
+                Some(WsMessage::InitialState { worlds, settings, current_world_index, actions, splash_lines, emoji_names })
+            }
+            WsMessage::WorldAdded { mut world } => {
+                redact_world(&mut world.settings);
+                Some(WsMessage::WorldAdded { world })
+            }
+            WsMessage::WorldSettingsUpdated { world_index, mut settings, name } => {
+                redact_world(&mut settings);
+                Some(WsMessage::WorldSettingsUpdated { world_index, settings, name })
+            }
+            WsMessage::GlobalSettingsUpdated { mut settings, input_height } => {
+                redact_global(&mut settings);
+                Some(WsMessage::GlobalSettingsUpdated { settings, input_height })
+            }
+            msg => Some(msg),
+        }
+    }
+}
+
+/// A device token as listed in /web tokens (never its key)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DeviceTokenInfo {
+    pub name: String,
+    pub created_at: u64,
+    #[serde(default)]
+    pub last_used: Option<u64>,
+}
+
+/// A freshly created device token's key, shown once
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NewDeviceToken {
+    pub name: String,
+    pub token: String,
+}
+
 /// A line of output with timestamp
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TimestampedLine {
@@ -487,6 +716,14 @@ pub struct WorldStateMsg {
     // Number of pending lines on the server (for More indicator on connect)
     #[serde(default)]
     pub pending_count: usize,
+    // BELs since the world was last viewed (for the /worlds popup)
+    #[serde(default)]
+    pub bell_count: usize,
+    // output_lines_ts carry straight on from the client's AuthRequest resume_seqs
+    // entry, to be appended to what it has (none if there were too many to send;
+    // the client gap-fills the rest with RequestScrollback after_seq)
+    #[serde(default)]
+    pub resumed: bool,
 }
 
 /// World settings for WebSocket protocol
@@ -500,17 +737,93 @@ pub struct WorldSettingsMsg {
     #[serde(default)]
     pub password: String,  // Empty from server; encrypted when client sends updates
     pub use_ssl: bool,
+    /// "full", "tofu" or "none" (see platform::TlsVerify)
+    #[serde(default)]
+    pub tls_verify: String,
+    /// Client certificate and key file paths (empty: none)
+    #[serde(default)]
+    pub tls_client_cert: String,
+    #[serde(default)]
+    pub tls_client_key: String,
+    /// SSH bastion host, user and key file (empty host: connect directly)
+    #[serde(default)]
+    pub ssh_host: String,
+    #[serde(default)]
+    pub ssh_user: String,
+    #[serde(default)]
+    pub ssh_key: String,
     pub log_enabled: bool,
+    /// "raw", "plain" or "html" (see logging.rs)
+    #[serde(default)]
+    pub log_format: String,
+    /// "off", "time" or "datetime"
+    #[serde(default)]
+    pub log_timestamps: String,
+    #[serde(default = "default_true")]
+    pub log_session_markers: bool,
     pub encoding: String,
     pub auto_connect_type: String,
+    /// Steps of the Script auto-login, one per line
+    #[serde(default)]
+    pub login_script: String,
+    /// Commands sent once login is done, one per line
+    #[serde(default)]
+    pub connect_commands: String,
+    /// Added before and after each line typed to the world
+    #[serde(default)]
+    pub input_prefix: String,
+    #[serde(default)]
+    pub input_suffix: String,
     pub keep_alive_type: String,
     pub keep_alive_cmd: String,
+    /// Idle seconds before a keepalive is sent
+    #[serde(default = "default_keep_alive_interval_secs")]
+    pub keep_alive_interval_secs: u64,
+    /// Minutes without output before the silence watchdog alerts (0: off)
+    #[serde(default)]
+    pub silence_alert_mins: u32,
     #[serde(default)]
     pub gmcp_packages: String,
     #[serde(default)]
     pub has_password: bool,  // True if a password is configured (password field is empty)
     #[serde(default)]
     pub auto_reconnect_secs: String,
+    /// Keypad 1-9 send movement commands (/numpad)
+    #[serde(default)]
+    pub numpad_walk: bool,
+    /// Time prefix override: "off", "hm", "hms", or empty for the global setting
+    #[serde(default)]
+    pub timestamps: String,
+    /// No notifications from this world (see desktop_notify.rs)
+    #[serde(default)]
+    pub notify_muted: bool,
+    /// Sound file played by clients on activity or a /notify while unfocused; empty for none
+    #[serde(default)]
+    pub notify_sound: String,
+    /// Web/GUI show image URLs in the world's output inline (/images)
+    #[serde(default)]
+    pub inline_images: bool,
+    /// What a BEL does: "ignore", "flash", "ring" or "notify" (see bell.rs)
+    #[serde(default)]
+    pub bell: String,
+    /// Typed `:name:` shortcodes become emoji (/emoji); clients Tab-complete them
+    #[serde(default)]
+    pub emoji_shortcodes: bool,
+    /// Misspelled words are corrected when a space follows them (/autocorrect)
+    #[serde(default)]
+    pub autocorrect: bool,
+    /// Palette remapping the 16 ANSI colors (/palette); empty for the theme's own
+    #[serde(default)]
+    pub palette: String,
+    /// Background color behind the world's output (/tint), "#rrggbb"; empty for the theme's
+    #[serde(default)]
+    pub tint: String,
+    /// Hunspell language for spell checking input (/spelllang); empty for the global one
+    #[serde(default)]
+    pub spell_language: String,
+    /// Function key macros (/macro): "F5"/"Shift-F5" -> command; clients fire them
+    #[serde(default)]
+    pub macros: std::collections::BTreeMap<String, String>,
 }
 
 /// Global settings for WebSocket protocol
@@ -584,9 +897,12 @@ pub struct GlobalSettingsMsg {
     pub tts_speak_mode: String,
     #[serde(default)]
     pub scrollback_enabled: bool,
-    /// Theme colors from ~/.clay/theme.dat (serialized as hex strings)
+    /// Theme colors from ~/.config/clay/theme.dat (serialized as hex strings)
     #[serde(default)]
     pub theme_colors_json: String,
+    /// Palettes from the theme file for /palette: {"name": ["#rrggbb", ...16]}
+    #[serde(default)]
+    pub palettes_json: String,
     /// Keyboard bindings (serialized as JSON object: key -> action)
     #[serde(default)]
     pub keybindings_json: String,
@@ -596,6 +912,43 @@ pub struct GlobalSettingsMsg {
     /// WebSocket password (plaintext, sent to authenticated clients for display in settings)
     #[serde(default)]
     pub ws_password: String,
+    /// Scheduled profile currently in effect (empty = none; see profiles.rs)
+    #[serde(default)]
+    pub active_profile: String,
+    /// Console status bar template (empty = built-in layout; see status_format.rs)
+    #[serde(default)]
+    pub status_format: String,
+    /// Console world tab bar shown (see tab_bar.rs)
+    #[serde(default)]
+    pub tab_bar: bool,
+    /// Time prefix on output lines: "off", "hm" or "hms" (worlds can override)
+    #[serde(default)]
+    pub timestamps: String,
+    /// Console desktop notifications for output while unfocused (see desktop_notify.rs)
+    #[serde(default)]
+    pub notify_activity: bool,
+    /// Output lines kept per world before the oldest are evicted (0 from an older server)
+    #[serde(default)]
+    pub scrollback_lines: usize,
+    /// Ask before /quit or Ctrl+C closes connected worlds
+    #[serde(default = "default_true")]
+    pub confirm_quit: bool,
+    /// Domain the web server gets a Let's Encrypt certificate for (empty = off, see acme.rs)
+    #[serde(default)]
+    pub acme_domain: String,
+    #[serde(default)]
+    pub acme_email: String,
+    /// Address the web server listens on, and the Unix socket it also serves (empty = off)
+    #[serde(default)]
+    pub web_bind_addr: String,
+    #[serde(default)]
+    pub web_unix_socket: String,
+    /// Reverse proxy addresses whose X-Forwarded-For is believed (http::forwarded_client)
+    #[serde(default)]
+    pub web_trusted_proxies: String,
+    /// VAPID public key for Web Push subscriptions (see web_push.rs)
+    #[serde(default)]
+    pub push_public_key: String,
 }
 
 fn default_gui_transparency() -> f32 {
@@ -610,6 +963,10 @@ fn default_remote_initial_lines() -> u16 {
     100
 }
 
+fn default_keep_alive_interval_secs() -> u64 {
+    crate::DEFAULT_KEEPALIVE_SECS
+}
+
 fn default_web_font_size_phone() -> f32 {
     10.0
 }
@@ -684,6 +1041,8 @@ pub struct WsClientInfo {
     /// True when this session has been paused via /remote --pause.
     /// Paused sessions don't suppress activity notices for their world.
     pub paused: bool,
+    /// Signed in with the viewer password: receives output, sends nothing that acts
+    pub viewer: bool,
 }
 
 /// User credential for multiuser authentication
@@ -700,6 +1059,9 @@ pub struct WebSocketServer {
     /// True when a non-empty password is configured; false for auth-key-only mode.
     /// When false, password-based authentication is rejected even if allow list matches.
     pub password_enabled: Arc<std::sync::RwLock<bool>>,
+    /// Hash of the viewer password (empty: no viewer sign-in). Clients signing in with
+    /// it get output and world state but can't send commands or change anything.
+    pub viewer_password_hash: Arc<std::sync::RwLock<String>>,
     pub running: Arc<RwLock<bool>>,
     pub shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
     pub port: u16,
@@ -740,6 +1102,7 @@ impl WebSocketServer {
             next_client_id: Arc::new(std::sync::Mutex::new(1)),
             password_hash: Arc::new(std::sync::RwLock::new(password_hash)),
             password_enabled: Arc::new(std::sync::RwLock::new(!password.is_empty())),
+            viewer_password_hash: Arc::new(std::sync::RwLock::new(String::new())),
             running: Arc::new(RwLock::new(false)),
             shutdown_tx: None,
             port,
@@ -756,6 +1119,15 @@ impl WebSocketServer {
         }
     }
 
+    /// What an in-process client (the local-mode GUI) needs to open sessions here.
+    pub fn local_link(&self, event_tx: mpsc::Sender<crate::AppEvent>) -> LocalLink {
+        LocalLink {
+            clients: self.clients.clone(),
+            next_client_id: self.next_client_id.clone(),
+            event_tx,
+        }
+    }
+
     /// Extract shared connection state for the unified HTTP+WS server.
     /// The HTTP server uses this to hand off WebSocket upgrade requests.
     pub fn connection_state(&self, event_tx: mpsc::Sender<crate::AppEvent>) -> crate::http::WsConnectionState {
@@ -764,6 +1136,7 @@ impl WebSocketServer {
             next_client_id: self.next_client_id.clone(),
             password_hash: self.password_hash.clone(),
             password_enabled: self.password_enabled.clone(),
+            viewer_password_hash: self.viewer_password_hash.clone(),
             allow_list: self.allow_list.clone(),
             whitelisted_host: self.whitelisted_host.clone(),
             event_tx,
@@ -1126,6 +1499,42 @@ impl WebSocketServer {
         *self.password_enabled.write().unwrap() = !password.is_empty();
     }
 
+    /// Set the viewer password on the running server; empty turns viewer sign-in off
+    pub fn update_viewer_password(&self, password: &str) {
+        *self.viewer_password_hash.write().unwrap() =
+            if password.is_empty() { String::new() } else { hash_password(password) };
+    }
+
+    /// Sign out everyone who signed in with the viewer password (it changed or was
+    /// turned off). Their connections close when the sign-in grace period runs out.
+    /// Returns how many were signed out, or None if the client list was busy and the
+    /// sign-out was handed to a task that waits for it (like `send_initial_state_and_mark`).
+    pub fn sign_out_viewers(&self) -> Option<usize> {
+        fn sign_out(clients: &mut HashMap<u64, WsClientInfo>) -> usize {
+            let mut count = 0;
+            for client in clients.values_mut().filter(|c| c.viewer && c.authenticated) {
+                let _ = client.tx.send(WsMessage::LoggedOut);
+                client.authenticated = false;
+                client.viewer = false;
+                count += 1;
+            }
+            count
+        }
+        if let Ok(mut clients) = self.clients.try_write() {
+            return Some(sign_out(&mut clients));
+        }
+        let clients = self.clients.clone();
+        tokio::spawn(async move {
+            sign_out(&mut *clients.write().await);
+        });
+        None
+    }
+
+    /// Whether a connected client signed in with the viewer password
+    pub fn is_viewer(&self, client_id: u64) -> bool {
+        self.clients.try_read().map(|clients| clients.get(&client_id).is_some_and(|c| c.viewer)).unwrap_or(false)
+    }
+
     pub fn stop(&mut self) {
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(());
@@ -1292,6 +1701,7 @@ pub async fn start_websocket_server(
     let next_client_id = Arc::clone(&server.next_client_id);
     let password_hash = server.password_hash.clone();
     let password_enabled = server.password_enabled.clone();
+    let viewer_password_hash = server.viewer_password_hash.clone();
     let allow_list = server.allow_list.clone();
     let whitelisted_host = server.whitelisted_host.clone();
     let running = Arc::clone(&server.running);
@@ -1317,6 +1727,10 @@ pub async fn start_websocket_server(
                                 // Silently drop connection for banned IPs
                                 continue;
                             }
+                            if !ban_list.allow_ws_connect(&client_ip) {
+                                crate::http::log_remote_event("WS-RATE-LIMIT", &client_ip, "too many new connections");
+                                continue;
+                            }
 
                             // Disable Nagle's algorithm for lower latency
                             let _ = stream.set_nodelay(true);
@@ -1337,6 +1751,7 @@ pub async fn start_websocket_server(
                             let users = users.clone();
                             let ban_list = ban_list.clone();
                             let password_enabled = *password_enabled.read().unwrap();
+                            let viewer_password_hash = viewer_password_hash.read().unwrap().clone();
                             #[cfg(feature = "native-tls-backend")]
                             let tls_acceptor = tls_acceptor.clone();
                             #[cfg(feature = "rustls-backend")]
@@ -1354,6 +1769,7 @@ pub async fn start_websocket_server(
                                                 clients,
                                                 password_hash,
                                                 password_enabled,
+                                                viewer_password_hash,
                                                 allow_list,
                                                 whitelisted_host,
                                                 client_addr,
@@ -1376,6 +1792,7 @@ pub async fn start_websocket_server(
                                     clients,
                                     password_hash,
                                     password_enabled,
+                                    viewer_password_hash,
                                     allow_list,
                                     whitelisted_host,
                                     client_addr,
@@ -1399,6 +1816,7 @@ pub async fn start_websocket_server(
                                                 clients,
                                                 password_hash,
                                                 password_enabled,
+                                                viewer_password_hash,
                                                 allow_list,
                                                 whitelisted_host,
                                                 client_addr,
@@ -1423,6 +1841,7 @@ pub async fn start_websocket_server(
                                     clients,
                                     password_hash,
                                     password_enabled,
+                                    viewer_password_hash,
                                     allow_list,
                                     whitelisted_host,
                                     client_addr,
@@ -1462,6 +1881,7 @@ pub async fn handle_ws_client<S>(
     clients: Arc<RwLock<HashMap<u64, WsClientInfo>>>,
     password_hash: String,
     password_enabled: bool,
+    viewer_password_hash: String,
     allow_list: Arc<std::sync::RwLock<Vec<String>>>,
     whitelisted_host: Arc<std::sync::RwLock<Option<String>>>,
     client_addr: std::net::SocketAddr,
@@ -1532,12 +1952,34 @@ where
         max_frame_size: Some(256 * 1024),
         ..Default::default()
     };
-    let ws_stream = match accept_async_with_config(stream, Some(ws_config)).await {
+    // permessage-deflate: answer the client's offer in the handshake response and
+    // let the stream compress from there on (see ws_deflate.rs). The size caps above
+    // apply to the inflated messages.
+    let (stream, deflate) = crate::ws_deflate::DeflateStream::new(stream);
+    let offer_switch = deflate.clone();
+    // The signature is fixed by tungstenite's handshake Callback trait, so the
+    // (large) ErrorResponse can't be boxed
+    #[allow(clippy::result_large_err)]
+    let negotiate =move |req: &Request, mut resp: Response| -> Result<Response, ErrorResponse> {
+        let offers: Vec<&str> = req.headers().get_all("sec-websocket-extensions")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+        if let Some(params) = crate::ws_deflate::DeflateParams::negotiate(&offers.join(",")) {
+            if let Ok(value) = params.response_header().parse() {
+                resp.headers_mut().insert("sec-websocket-extensions", value);
+                offer_switch.agree(params);
+            }
+        }
+        Ok(resp)
+    };
+    let ws_stream = match accept_hdr_async_with_config(stream, negotiate, Some(ws_config)).await {
         Ok(ws) => ws,
         Err(e) => {
             return Err(e.into());
         }
     };
+    deflate.start();
     let (mut ws_sink, mut ws_source) = ws_stream.split();
 
     // Create channel for sending messages to this client
@@ -1574,6 +2016,7 @@ where
             connected_at: std::time::Instant::now(),
             last_activity: std::time::Instant::now(),
             paused: false,
+            viewer: false,
         });
     }
 
@@ -1594,7 +2037,7 @@ where
         };
         let _ = tx.send(response);
         // Create a fake AuthRequest to trigger initial state send
-        let _ = event_tx.send(AppEvent::WsClientMessage(client_id, Box::new(WsMessage::AuthRequest { username: None, password_hash: String::new(), current_world: None, auth_key: None, request_key: false, challenge_response: false }))).await;
+        let _ = event_tx.send(AppEvent::WsClientMessage(client_id, Box::new(WsMessage::AuthRequest { username: None, password_hash: String::new(), current_world: None, auth_key: None, request_key: false, challenge_response: false, resume_seqs: Default::default() }))).await;
     }
 
     // Combined receive/send/keepalive loop.
@@ -1603,6 +2046,9 @@ where
     // WS_KEEPALIVE_INTERVAL_SECS; no Pong within WS_PONG_TIMEOUT_SECS = dead peer, disconnect.
     let auth_deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(WS_AUTH_TIMEOUT_SECS);
     let mut awaiting_pong = false;
+    // Signed in with the viewer password: what it's sent is redacted (WsMessage::for_viewer)
+    // and what it sends is limited to WsMessage::viewer_allowed
+    let mut is_viewer = false;
 
     loop {
         let authed = {
@@ -1625,6 +2071,21 @@ where
 
         tokio::select! {
             Some(msg) = rx.recv() => {
+                // A viewer is told what it is right after its InitialState, which would
+                // otherwise clear the notice from its output
+                if is_viewer && matches!(msg, WsMessage::InitialState { .. }) {
+                    let _ = tx.send(WsMessage::ViewOnly {
+                        message: "Signed in as a viewer: you can watch, but not send commands or change settings.".to_string(),
+                    });
+                }
+                let msg = if is_viewer {
+                    match msg.for_viewer() {
+                        Some(msg) => msg,
+                        None => continue,
+                    }
+                } else {
+                    msg
+                };
                 if let Ok(json) = serde_json::to_string(&msg) {
                     let msg_len = json.len();
                     if let Err(e) = ws_sink.send(WsRawMessage::Text(json)).await {
@@ -1656,12 +2117,16 @@ where
                             if auth_key.is_some() && !auth_key.as_ref().unwrap().is_empty() {
                                 // Forward to app for key validation
                                 // App will send AuthResponse directly
+                                is_viewer = false;
+                                if let Some(client) = clients.write().await.get_mut(&client_id) {
+                                    client.viewer = false;
+                                }
                                 let _ = event_tx.send(AppEvent::WsAuthKeyValidation(client_id, Box::new(ws_msg.clone()), client_ip.clone(), challenge.clone())).await;
                                 continue;
                             }
 
-                            // Password-based auth: reject if no password is configured
-                            if !password_enabled {
+                            // Password-based auth: reject if no password (nor viewer password) is configured
+                            if !password_enabled && viewer_password_hash.is_empty() {
                                 crate::http::log_remote_event("WS-REJECT", &client_ip,
                                     "no password configured, auth key required");
                                 let _ = tx.send(WsMessage::AuthResponse {
@@ -1703,6 +2168,7 @@ where
                             // Password-based authentication
                             // If challenge_response is true, client sent SHA256(SHA256(password) + challenge)
                             // We compare by computing SHA256(stored_hash + challenge) on our side
+                            let mut as_viewer = false;
                             let (auth_success, auth_error, auth_username) = if multiuser_mode {
                                 // Multiuser mode: require username and validate against users map
                                 match username {
@@ -1730,14 +2196,17 @@ where
                                     _ => (false, Some("Username required".to_string()), None),
                                 }
                             } else {
-                                // Single-user mode: just validate password
-                                let matches = if *uses_challenge {
-                                    hash_with_challenge(&password_hash, &challenge) == *client_hash
+                                // Single-user mode: validate the password, then the viewer password
+                                let matches = |stored: &str| if *uses_challenge {
+                                    hash_with_challenge(stored, &challenge) == *client_hash
                                 } else {
                                     // B3 (security remediation): constant-time compare (see note above).
-                                    crate::util::constant_time_eq(client_hash.as_bytes(), password_hash.as_bytes())
+                                    crate::util::constant_time_eq(client_hash.as_bytes(), stored.as_bytes())
                                 };
-                                if matches {
+                                if password_enabled && matches(&password_hash) {
+                                    (true, None, None)
+                                } else if !viewer_password_hash.is_empty() && matches(&viewer_password_hash) {
+                                    as_viewer = true;
                                     (true, None, None)
                                 } else {
                                     (false, Some("Authentication failed".to_string()), None)
@@ -1756,11 +2225,17 @@ where
                                 if let Some(client) = clients_guard.get_mut(&client_id) {
                                     client.authenticated = true;
                                     client.username = auth_username.clone();
+                                    client.viewer = as_viewer;
+                                }
+                                is_viewer = as_viewer;
+                                if as_viewer {
+                                    crate::http::log_remote_event("WS-VIEWER", &client_ip, "signed in with the viewer password");
                                 }
 
                                 // If client IP is in allow list, whitelist this host (single-user mode only)
-                                // This clears any previously whitelisted host
-                                if !multiuser_mode {
+                                // This clears any previously whitelisted host. A viewer never is:
+                                // that would let the next connection from there in with full access.
+                                if !multiuser_mode && !as_viewer {
                                     let in_allow_list = {
                                         let allow_list_guard = allow_list.read().unwrap();
                                         is_ip_in_allow_list(&client_ip, &allow_list_guard)
@@ -1778,6 +2253,9 @@ where
                                 // `BanList::record_auth_failure` (D6, SECURITY-ROADMAP.md).
                                 ban_list.record_auth_failure(&client_ip, "WebSocket: failed auth");
                             }
+                            // A connection whose guesses just got its IP banned is closed
+                            // after the answer, so it can't keep guessing on the open socket
+                            let now_banned = !auth_success && ban_list.is_banned(&client_ip);
                             // Send auth response
                             let response = WsMessage::AuthResponse {
                                 success: auth_success,
@@ -1786,10 +2264,19 @@ where
                                 multiuser_mode,
                             };
                             let _ = tx.send(response);
+                            if now_banned {
+                                while let Ok(msg) = rx.try_recv() {
+                                    if let Ok(json) = serde_json::to_string(&msg) {
+                                        let _ = ws_sink.send(WsRawMessage::Text(json)).await;
+                                    }
+                                }
+                                crate::http::log_remote_event("WS-BANNED-CLOSE", &client_ip, "too many failed sign-ins");
+                                break;
+                            }
 
                             if auth_success {
-                                // Extract request_key before moving ws_msg
-                                let wants_key = *request_key;
+                                // Extract request_key before moving ws_msg (viewers get no key)
+                                let wants_key = *request_key && !as_viewer;
 
                                 // Forward to app to send initial state (and generate key if requested)
                                 let _ = event_tx.send(AppEvent::WsClientMessage(client_id, Box::new(ws_msg))).await;
@@ -1817,6 +2304,13 @@ where
                                         client.last_activity = std::time::Instant::now();
                                     }
                                 }
+                                if is_viewer && !ws_msg.viewer_allowed() {
+                                    crate::http::log_remote_event("WS-VIEWER-DENY", &client_ip, &ws_msg.kind());
+                                    let _ = tx.send(WsMessage::ViewOnly {
+                                        message: "This session is view-only.".to_string(),
+                                    });
+                                    continue;
+                                }
                                 // Handle RevokeKey and RegenerateAuthKey inside auth check
                                 if let WsMessage::RevokeKey { ref auth_key } = ws_msg {
                                     let _ = event_tx.send(AppEvent::WsKeyRevoke(client_id, auth_key.clone())).await;
@@ -1876,3 +2370,96 @@ where
 
     Ok(())
 }
+
+/// Opens sessions for clients in the App's own process: the master GUI in local mode,
+/// whose page passes messages over IPC instead of dialing the web server. A local
+/// session is a client like any other (broadcasts, replies, disconnect cleanup), minus
+/// the socket, the sign-in and the keepalive.
+#[derive(Clone)]
+pub struct LocalLink {
+    clients: Arc<RwLock<HashMap<u64, WsClientInfo>>>,
+    next_client_id: Arc<std::sync::Mutex<u64>>,
+    event_tx: mpsc::Sender<AppEvent>,
+}
+
+/// One local session (see `LocalLink::connect`)
+pub struct LocalClient {
+    pub id: u64,
+    tx: mpsc::UnboundedSender<WsMessage>,
+    clients: Arc<RwLock<HashMap<u64, WsClientInfo>>>,
+    event_tx: mpsc::Sender<AppEvent>,
+}
+
+impl LocalLink {
+    /// Open a session, already signed in. Messages for it arrive on the receiver.
+    pub async fn connect(&self) -> (LocalClient, mpsc::UnboundedReceiver<WsMessage>) {
+        let id = {
+            let mut next = self.next_client_id.lock().unwrap();
+            let id = *next;
+            *next += 1;
+            id
+        };
+        let (tx, rx) = mpsc::unbounded_channel::<WsMessage>();
+        self.clients.write().await.insert(id, WsClientInfo {
+            authenticated: true,
+            tx: tx.clone(),
+            current_world: None,
+            username: None,
+            received_initial_state: false,
+            client_type: RemoteClientType::Web,  // Updated by ClientTypeDeclaration
+            viewport_height: 24,
+            ip_address: "local".to_string(),
+            connected_at: std::time::Instant::now(),
+            last_activity: std::time::Instant::now(),
+            paused: false,
+            viewer: false,
+        });
+        let _ = self.event_tx.send(AppEvent::WsClientConnected(id)).await;
+        let client = LocalClient {
+            id,
+            tx,
+            clients: self.clients.clone(),
+            event_tx: self.event_tx.clone(),
+        };
+        (client, rx)
+    }
+}
+
+impl LocalClient {
+    /// A message from the page, handled as handle_ws_client would for a signed-in client.
+    /// Its AuthRequest needs no password: it's answered and starts the InitialState.
+    pub async fn send(&self, msg: WsMessage) {
+        match msg {
+            WsMessage::AuthRequest { .. } => {
+                let _ = self.tx.send(WsMessage::AuthResponse {
+                    success: true,
+                    error: None,
+                    username: None,
+                    multiuser_mode: false,
+                });
+                let _ = self.event_tx.send(AppEvent::WsClientMessage(self.id, Box::new(msg))).await;
+            }
+            WsMessage::Ping => {
+                let _ = self.tx.send(WsMessage::Pong);
+            }
+            WsMessage::RevokeKey { auth_key } => {
+                let _ = self.event_tx.send(AppEvent::WsKeyRevoke(self.id, auth_key)).await;
+            }
+            WsMessage::RegenerateAuthKey => {
+                let _ = self.event_tx.send(AppEvent::WsKeyRequest(self.id)).await;
+            }
+            msg => {
+                if let Some(client) = self.clients.write().await.get_mut(&self.id) {
+                    client.last_activity = std::time::Instant::now();
+                }
+                let _ = self.event_tx.send(AppEvent::WsClientMessage(self.id, Box::new(msg))).await;
+            }
+        }
+    }
+
+    /// End the session (window closed or page reloaded)
+    pub async fn close(self) {
+        self.clients.write().await.remove(&self.id);
+        let _ = self.event_tx.send(AppEvent::WsClientDisconnected(self.id)).await;
+    }
+}
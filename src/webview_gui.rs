@@ -1,7 +1,8 @@
 // WebView GUI client using wry (native WebView window)
 //
 // Provides two modes:
-// --gui            Master mode: runs App headlessly + opens WebView window (local WS connection)
+// --gui            Master mode: runs App headlessly + opens WebView window (over IPC, or a
+//                  loopback WS connection when the web interface is enabled)
 // --gui=host:port  Remote mode: opens WebView window connected to remote Clay instance
 
 use std::borrow::Cow;
@@ -64,19 +65,171 @@ enum WvEvent {
     NewWindow(Option<String>),
     /// Open a grep results window (half height, no status/input, filtered output)
     GrepWindow { pattern: String, world: Option<String>, use_regex: bool },
+    /// Installed monospace font families for the font picker (see list-fonts IPC)
+    FontList(Vec<String>),
+    /// Export Log from the menu: ask where to save the world's output (see save-log: IPC)
+    SaveLog(LogExport),
+    /// Export in the actions list: ask where to save the trigger pack (see save-actions: IPC)
+    SaveActions(ActionsExport),
+    /// Local mode: a message from the App for this window's page (JSON)
+    LocalDeliver(WindowId, String),
+    /// The page's worlds, for the tray icon's badge and menu (see tray: IPC)
+    #[cfg(feature = "tray")]
+    TrayUpdate(Vec<crate::tray::TrayWorld>),
+    /// A pick from the tray icon's menu
+    #[cfg(feature = "tray")]
+    TrayAction(crate::tray::TrayAction),
 }
 
 use crate::theme::ThemeFile;
 use crate::websocket::hash_password;
 
+/// Local mode: what a page's session does, on its way to the App (see spawn_local_bridge)
+enum LocalEvent {
+    /// A message from the page: ws: IPC. Its AuthRequest starts a new session.
+    Message(WindowId, crate::WsMessage),
+    /// The window closed
+    Close(WindowId),
+}
+
+/// Local mode: the link from one window's page to the bridge
+#[derive(Clone)]
+struct LocalSession {
+    window: WindowId,
+    tx: tokio::sync::mpsc::UnboundedSender<LocalEvent>,
+}
+
+/// Local mode: run the pages' sessions with the App in this process, with no web server
+/// between them (see websocket::LocalLink). Each window's page opens one with its
+/// AuthRequest, again after a reload; what the App sends it comes back as
+/// WvEvent::LocalDeliver.
+fn spawn_local_bridge(
+    link: crate::websocket::LocalLink,
+    handle: &tokio::runtime::Handle,
+    proxy: &EventLoopProxy<WvEvent>,
+) -> tokio::sync::mpsc::UnboundedSender<LocalEvent> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<LocalEvent>();
+    let proxy = proxy.clone();
+    handle.spawn(async move {
+        let mut sessions: HashMap<WindowId, crate::websocket::LocalClient> = HashMap::new();
+        while let Some(event) = rx.recv().await {
+            match event {
+                LocalEvent::Message(window, msg @ crate::WsMessage::AuthRequest { .. }) => {
+                    if let Some(old) = sessions.remove(&window) {
+                        old.close().await;
+                    }
+                    let (client, mut outgoing) = link.connect().await;
+                    let proxy = proxy.clone();
+                    tokio::spawn(async move {
+                        while let Some(msg) = outgoing.recv().await {
+                            let Ok(json) = serde_json::to_string(&msg) else { continue };
+                            if proxy.send_event(WvEvent::LocalDeliver(window, json)).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                    client.send(msg).await;
+                    sessions.insert(window, client);
+                }
+                LocalEvent::Message(window, msg) => {
+                    if let Some(client) = sessions.get(&window) {
+                        client.send(msg).await;
+                    }
+                }
+                LocalEvent::Close(window) => {
+                    if let Some(client) = sessions.remove(&window) {
+                        client.close().await;
+                    }
+                }
+            }
+        }
+    });
+    tx
+}
+
+/// A world's output as the page renders it for Export Log (app.js `exportLog`)
+#[derive(Debug, serde::Deserialize)]
+struct LogExport {
+    /// Suggested file name
+    name: String,
+    /// Plain text, one line per output line
+    text: String,
+    /// Standalone HTML document keeping the colors
+    html: String,
+}
+
+/// Ask where to save an exported log and write it there: the HTML version when the
+/// chosen name ends in .html/.htm, plain text otherwise. None if the dialog was cancelled.
+fn save_log_export(export: &LogExport, parent: Option<&tao::window::Window>) -> Option<Result<std::path::PathBuf, String>> {
+    #[cfg(not(target_os = "android"))]
+    {
+        let mut dialog = rfd::FileDialog::new()
+            .set_title("Export Log")
+            .set_file_name(&export.name)
+            .add_filter("Plain text", &["txt", "log"])
+            .add_filter("HTML", &["html", "htm"]);
+        if let Some(win) = parent {
+            dialog = dialog.set_parent(win);
+        }
+        let path = dialog.save_file()?;
+        let is_html = path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("html") || e.eq_ignore_ascii_case("htm"));
+        let body = if is_html { &export.html } else { &export.text };
+        Some(std::fs::write(&path, body).map(|_| path).map_err(|e| e.to_string()))
+    }
+    #[cfg(target_os = "android")]
+    {
+        let _ = (export, parent);
+        Some(Err("no save dialog on this platform".to_string()))
+    }
+}
+
+/// A trigger pack as the page builds it for the actions list's Export (app.js `exportActions`)
+#[derive(Debug, serde::Deserialize)]
+struct ActionsExport {
+    /// Suggested file name
+    name: String,
+    /// The pack, as JSON
+    json: String,
+}
+
+/// Ask where to save an exported trigger pack and write it there. None if the dialog was
+/// cancelled.
+fn save_actions_export(export: &ActionsExport, parent: Option<&tao::window::Window>) -> Option<Result<std::path::PathBuf, String>> {
+    #[cfg(not(target_os = "android"))]
+    {
+        let mut dialog = rfd::FileDialog::new()
+            .set_title("Export Actions")
+            .set_file_name(&export.name)
+            .add_filter("JSON", &["json"]);
+        if let Some(win) = parent {
+            dialog = dialog.set_parent(win);
+        }
+        let path = dialog.save_file()?;
+        Some(std::fs::write(&path, &export.json).map(|_| path).map_err(|e| e.to_string()))
+    }
+    #[cfg(target_os = "android")]
+    {
+        let _ = (export, parent);
+        Some(Err("no save dialog on this platform".to_string()))
+    }
+}
+
+/// The script telling the page how a save dialog went (`window.<callback>(ok, detail)`);
+/// None if the dialog was cancelled
+fn saved_file_script(callback: &str, result: Option<Result<std::path::PathBuf, String>>) -> Option<String> {
+    let (ok, detail) = match result? {
+        Ok(path) => (true, path.display().to_string()),
+        Err(e) => (false, e),
+    };
+    Some(format!("window.{0} && window.{0}({1}, {2})", callback, ok,
+        serde_json::to_string(&detail).unwrap_or_default()))
+}
+
 /// Open a URL in the system's default browser (platform-specific).
 fn open_url_in_browser(url: &str) {
-    #[cfg(any(target_os = "linux", target_os = "android"))]
-    { let _ = std::process::Command::new("xdg-open").arg(url).spawn(); }
-    #[cfg(target_os = "macos")]
-    { let _ = std::process::Command::new("open").arg(url).spawn(); }
-    #[cfg(windows)]
-    { let _ = std::process::Command::new("cmd").args(["/C", "start", "", url]).spawn(); }
+    let _ = crate::platform::open_url(url);
 }
 
 const WEB_INDEX_HTML: &str = include_str!("web/index.html");
@@ -98,28 +251,17 @@ struct WebViewParams {
     server_secure: bool,
 }
 
-/// Read the gui_theme name from ~/.clay/settings.dat (defaults to "dark").
+/// Read the gui_theme name from ~/.config/clay/settings.toml (defaults to "dark").
 fn load_gui_theme_name() -> String {
-    let settings_path = crate::clay_config_path("settings.dat");
-    if !settings_path.exists() {
-        return "dark".to_string();
-    }
-    std::fs::read_to_string(&settings_path)
-        .ok()
-        .and_then(|content| {
-            content.lines()
-                .find(|l| l.starts_with("gui_theme="))
-                .map(|l| l.trim_start_matches("gui_theme=").to_string())
-        })
-        .unwrap_or_else(|| "dark".to_string())
+    crate::persistence::read_global_setting("gui_theme").unwrap_or_else(|| "dark".to_string())
 }
 
 /// Load the user's GUI theme CSS vars for initial HTML rendering.
-/// Reads gui_theme name from ~/.clay/settings.dat and theme colors from ~/.clay/theme.dat.
+/// Reads gui_theme name from ~/.config/clay/settings.toml and theme colors from ~/.config/clay/theme.dat.
 fn load_user_theme_css() -> String {
     let gui_theme_name = load_gui_theme_name();
 
-    // Load theme colors from ~/.clay/theme.dat
+    // Load theme colors from ~/.config/clay/theme.dat
     let theme_path = crate::clay_config_path("theme.dat");
     let theme_file = ThemeFile::load(&theme_path);
     theme_file.get(&gui_theme_name).to_css_vars()
@@ -170,11 +312,12 @@ pub fn run_master_webgui() -> io::Result<()> {
     }
 
     // Read the configured HTTP port from settings (default 9000)
-    // The GUI uses the same port as the web interface
-    let port = {
+    // The GUI uses the same port as the web interface. With the web interface off, it
+    // runs in local mode instead: the page talks to the App over IPC, and nothing listens.
+    let (port, local_mode) = {
         let mut tmp_app = crate::App::new();
         let _ = crate::persistence::load_settings(&mut tmp_app);
-        tmp_app.settings.http_port
+        (tmp_app.settings.http_port, !tmp_app.settings.http_enabled)
     };
 
     // Detect duplicate instances: probe the port before spawning anything.
@@ -186,7 +329,7 @@ pub fn run_master_webgui() -> io::Result<()> {
     // always-TLS-capable start_https_server — see SECURITY-ROADMAP.md D8); the
     // --reload flag is set on every reload exec regardless of platform, so use that.
     let is_reload = std::env::args().any(|a| a == "--reload");
-    if !is_reload {
+    if !is_reload && !local_mode {
         if let Err(e) = std::net::TcpListener::bind(format!("127.0.0.1:{}", port)) {
             let msg = if e.kind() == io::ErrorKind::AddrInUse {
                 format!(
@@ -231,19 +374,46 @@ pub fn run_master_webgui() -> io::Result<()> {
     let (app_to_gui_tx, _app_to_gui_rx) = tokio::sync::mpsc::unbounded_channel::<crate::WsMessage>();
     let (gui_to_app_tx, gui_to_app_rx) = tokio::sync::mpsc::unbounded_channel::<crate::WsMessage>();
 
+    // Local mode: no WS override (so no server), and the App hands back its session link
+    let (link_tx, link_rx) = tokio::sync::oneshot::channel::<crate::websocket::LocalLink>();
+    let link_tx = local_mode.then_some(link_tx);
+
     // Spawn the headless App with WS override
-    let ws_password = password.clone();
+    let ws_password = (!local_mode).then(|| password.clone());
     handle.spawn(async move {
         if let Err(_e) = crate::run_app_headless(
             app_to_gui_tx,
             gui_to_app_rx,
-            Some(ws_password),
+            ws_password,
             None, // No GUI repaint callback (webview is event-driven)
             None, // Port comes from settings.http_port (already probed above)
+            link_tx,
         ).await {
         }
     });
 
+    let params = WebViewParams {
+        ws_host: "127.0.0.1".to_string(),
+        ws_port: port,
+        ws_protocol: "ws".to_string(),
+        // Marks the page as the master GUI's; a local session doesn't check it
+        auto_password: Some(password_hash),
+        theme_css: load_user_theme_css(),
+        server_host: None, // Master mode — no separate server URL needed
+        server_port: None,
+        server_secure: false,
+    };
+
+    if local_mode {
+        let Ok(link) = link_rx.blocking_recv() else {
+            runtime.shutdown_background();
+            return Err(io::Error::other("Clay failed to start"));
+        };
+        let result = create_webview_window("Clay", &params, Some(gui_to_app_tx), Some((link, handle)));
+        runtime.shutdown_background();
+        return result;
+    }
+
     // Wait for the HTTP server to signal it has bound the port.
     // Uses an atomic flag set by start_http_server after successful bind,
     // avoiding the race where TCP connect succeeds against a dying old socket.
@@ -267,18 +437,7 @@ pub fn run_master_webgui() -> io::Result<()> {
         return Err(io::Error::other("WebSocket server did not start within 3 seconds"));
     }
 
-    let params = WebViewParams {
-        ws_host: "127.0.0.1".to_string(),
-        ws_port: port,
-        ws_protocol: "ws".to_string(),
-        auto_password: Some(password_hash),
-        theme_css: load_user_theme_css(),
-        server_host: None, // Master mode — no separate server URL needed
-        server_port: None,
-        server_secure: false,
-    };
-
-    let result = create_webview_window("Clay", &params, Some(gui_to_app_tx));
+    let result = create_webview_window("Clay", &params, Some(gui_to_app_tx), None);
 
     // Shut down the tokio runtime when the window closes
     runtime.shutdown_background();
@@ -352,7 +511,7 @@ pub fn run_remote_webgui(addr: &str, ssh: Option<crate::ssh::SshTarget>) -> io::
             server_secure: true,
         };
 
-        let result = create_webview_window("Clay", &params, None);
+        let result = create_webview_window("Clay", &params, None, None);
         runtime.shutdown_background();
         return result;
     }
@@ -423,7 +582,7 @@ pub fn run_remote_webgui(addr: &str, ssh: Option<crate::ssh::SshTarget>) -> io::
             server_secure: true,
         };
 
-        let result = create_webview_window("Clay", &params, None);
+        let result = create_webview_window("Clay", &params, None, None);
         runtime.shutdown_background();
         result
     } else {
@@ -439,7 +598,7 @@ pub fn run_remote_webgui(addr: &str, ssh: Option<crate::ssh::SshTarget>) -> io::
             server_secure: false,
         };
 
-        create_webview_window("Clay", &params, None)
+        create_webview_window("Clay", &params, None, None)
     }
 }
 
@@ -564,7 +723,7 @@ async fn ws_proxy_bridge(
                         if let Some(mismatch) = crate::platform::danger::take_cert_mismatch() {
                             return Err(format!(
                                 "TLS certificate for {} changed (was {}, now {}); refusing to fall back to an unencrypted connection. \
-                                 Delete its entry from ~/.clay/known_hosts.dat if you trust the new certificate.",
+                                 Delete its entry from ~/.config/clay/known_hosts.dat if you trust the new certificate.",
                                 mismatch.host, mismatch.old_fingerprint, mismatch.new_fingerprint
                             ).into());
                         }
@@ -859,8 +1018,13 @@ fn dispatch_ipc_message(
     is_master: bool,
     proxy: &EventLoopProxy<WvEvent>,
     reload_tx: &Option<tokio::sync::mpsc::UnboundedSender<crate::WsMessage>>,
+    local: &Option<LocalSession>,
 ) {
-    if let Some(url) = body.strip_prefix("open-url:") {
+    if let Some(json_str) = body.strip_prefix("ws:") {
+        if let (Some(local), Ok(msg)) = (local, serde_json::from_str::<crate::WsMessage>(json_str)) {
+            let _ = local.tx.send(LocalEvent::Message(local.window, msg));
+        }
+    } else if let Some(url) = body.strip_prefix("open-url:") {
         open_url_in_browser(url);
     } else if let Some(rest) = body.strip_prefix("new-window:") {
         let world_name = rest.trim().to_string();
@@ -875,6 +1039,28 @@ fn dispatch_ipc_message(
                 let _ = proxy.send_event(WvEvent::GrepWindow { pattern, world, use_regex });
             }
         }
+    } else if body == "list-fonts" {
+        // fc-list can take a moment on a cold font cache; keep it off the event loop
+        let proxy = proxy.clone();
+        std::thread::spawn(move || {
+            let _ = proxy.send_event(WvEvent::FontList(crate::platform::monospace_font_families()));
+        });
+    } else if let Some(file) = body.strip_prefix("play-sound:") {
+        // A notification sound (/notify -w sound) is a file on this machine
+        crate::audio::play_notify_sound(file.trim());
+    } else if let Some(json_str) = body.strip_prefix("save-log:") {
+        if let Ok(export) = serde_json::from_str::<LogExport>(json_str) {
+            let _ = proxy.send_event(WvEvent::SaveLog(export));
+        }
+    } else if let Some(json_str) = body.strip_prefix("save-actions:") {
+        if let Ok(export) = serde_json::from_str::<ActionsExport>(json_str) {
+            let _ = proxy.send_event(WvEvent::SaveActions(export));
+        }
+    } else if let Some(_json_str) = body.strip_prefix("tray:") {
+        #[cfg(feature = "tray")]
+        if let Ok(worlds) = serde_json::from_str::<Vec<crate::tray::TrayWorld>>(_json_str) {
+            let _ = proxy.send_event(WvEvent::TrayUpdate(worlds));
+        }
     } else if body == "quit" {
         let _ = proxy.send_event(WvEvent::Quit);
     } else if body == "update" || body == "update-force" {
@@ -958,12 +1144,22 @@ fn build_webview(
     params: &WebViewParams,
     proxy: &EventLoopProxy<WvEvent>,
     reload_tx: &Option<tokio::sync::mpsc::UnboundedSender<crate::WsMessage>>,
+    local_tx: &Option<tokio::sync::mpsc::UnboundedSender<LocalEvent>>,
     world_lock: Option<&str>,
     extra_js: Option<&str>,
 ) -> io::Result<wry::WebView> {
     // Build HTML with WS params baked into template placeholders
     let mut html_content = build_html(params);
 
+    // Local mode: the page's session goes over IPC (app.js connectLocal)
+    let local = local_tx.as_ref().map(|tx| LocalSession { window: window.id(), tx: tx.clone() });
+    if local.is_some() {
+        html_content = html_content.replace(
+            &format!("window.WS_PROTOCOL = '{}';", params.ws_protocol),
+            &format!("window.WS_PROTOCOL = '{}';\n        window.LOCAL_MODE = true;", params.ws_protocol),
+        );
+    }
+
     // Inject world lock if provided (for new windows locked to a specific world)
     if let Some(world_name) = world_lock {
         html_content = html_content.replace(
@@ -990,6 +1186,7 @@ fn build_webview(
     // window.webkit.messageHandlers is unavailable, e.g. on Termux WebKit2GTK)
     let proxy_for_protocol = proxy.clone();
     let reload_tx_for_protocol = reload_tx.clone();
+    let local_for_protocol = local.clone();
 
     let builder = WebViewBuilder::new()
         .with_custom_protocol("clay".into(), move |_id, request| {
@@ -999,7 +1196,7 @@ fn build_webview(
             // window.webkit.messageHandlers is unavailable (e.g. Termux WebKit2GTK).
             if path == "/ipc" {
                 let msg = String::from_utf8_lossy(request.body()).to_string();
-                dispatch_ipc_message(&msg, is_master, &proxy_for_protocol, &reload_tx_for_protocol);
+                dispatch_ipc_message(&msg, is_master, &proxy_for_protocol, &reload_tx_for_protocol, &local_for_protocol);
                 return wry::http::Response::builder()
                     .header("Content-Type", "text/plain")
                     .header("Access-Control-Allow-Origin", "*")
@@ -1027,7 +1224,7 @@ fn build_webview(
             let proxy = proxy.clone();
             let reload_tx = reload_tx.clone();
             move |req| {
-                dispatch_ipc_message(req.body(), is_master, &proxy, &reload_tx);
+                dispatch_ipc_message(req.body(), is_master, &proxy, &reload_tx, &local);
             }
         })
         // Open external links in the system browser instead of navigating the WebView.
@@ -1117,9 +1314,11 @@ fn create_webview_window(
     title: &str,
     params: &WebViewParams,
     reload_tx: Option<tokio::sync::mpsc::UnboundedSender<crate::WsMessage>>,
+    local: Option<(crate::websocket::LocalLink, tokio::runtime::Handle)>,
 ) -> io::Result<()> {
     let event_loop = EventLoopBuilder::<WvEvent>::with_user_event().build();
     let proxy: EventLoopProxy<WvEvent> = event_loop.create_proxy();
+    let local_tx = local.map(|(link, handle)| spawn_local_bridge(link, &handle, &proxy));
 
     let window_theme = if load_gui_theme_name() == "light" {
         Some(tao::window::Theme::Light)
@@ -1158,7 +1357,7 @@ fn create_webview_window(
     let _stderr_guard = StderrSuppress::new();
 
     // Build the first webview (initial_world_lock is handled inside build_html via env var)
-    let webview = build_webview(&window, params, &proxy, &reload_tx, None, None)?;
+    let webview = build_webview(&window, params, &proxy, &reload_tx, &local_tx, None, None)?;
 
     #[cfg(unix)]
     drop(_stderr_guard);
@@ -1177,6 +1376,20 @@ fn create_webview_window(
     // Clone params for use inside the event loop closure (needed for creating new windows)
     let params = params.clone();
 
+    // Tray icon: menu picks come back through the event loop (a Mutex makes the proxy Sync)
+    #[cfg(feature = "tray")]
+    let mut tray = {
+        let tray_proxy = std::sync::Mutex::new(proxy.clone());
+        crate::tray::Tray::new(move |action| {
+            if let Ok(p) = tray_proxy.lock() {
+                let _ = p.send_event(WvEvent::TrayAction(action));
+            }
+        })
+        .ok()
+    };
+    #[cfg(feature = "tray")]
+    let main_window_id = window_id;
+
     event_loop.run(move |event, event_loop_target, control_flow| {
         *control_flow = ControlFlow::Wait;
 
@@ -1189,6 +1402,9 @@ fn create_webview_window(
                 // Remove the closed window and its webview
                 windows.remove(&window_id);
                 webviews.remove(&window_id);
+                if let Some(ref tx) = local_tx {
+                    let _ = tx.send(LocalEvent::Close(window_id));
+                }
                 // Only exit if all windows are closed
                 if windows.is_empty() {
                     *control_flow = ControlFlow::Exit;
@@ -1237,6 +1453,69 @@ fn create_webview_window(
                     let _ = wv.evaluate_script(&script);
                 }
             }
+            Event::UserEvent(WvEvent::FontList(ref families)) => {
+                let json = serde_json::to_string(families).unwrap_or_else(|_| "[]".to_string());
+                let script = format!("window.setSystemFonts && window.setSystemFonts({})", json);
+                for wv in webviews.values() {
+                    let _ = wv.evaluate_script(&script);
+                }
+            }
+            Event::UserEvent(WvEvent::LocalDeliver(id, ref json)) => {
+                if let Some(wv) = webviews.get(&id) {
+                    let _ = wv.evaluate_script(&format!("window.clayLocalDeliver && window.clayLocalDeliver({})", json));
+                }
+            }
+            Event::UserEvent(WvEvent::SaveLog(ref export)) => {
+                // The dialog belongs to the window the menu was used in
+                let id = windows.iter()
+                    .find(|(_, w)| w.is_focused())
+                    .or_else(|| windows.iter().next())
+                    .map(|(id, _)| *id);
+                let script = saved_file_script("logExported", save_log_export(export, id.and_then(|id| windows.get(&id))));
+                if let (Some(script), Some(wv)) = (script, id.and_then(|id| webviews.get(&id))) {
+                    let _ = wv.evaluate_script(&script);
+                }
+            }
+            Event::UserEvent(WvEvent::SaveActions(ref export)) => {
+                let id = windows.iter()
+                    .find(|(_, w)| w.is_focused())
+                    .or_else(|| windows.iter().next())
+                    .map(|(id, _)| *id);
+                let script = saved_file_script("actionsExported", save_actions_export(export, id.and_then(|id| windows.get(&id))));
+                if let (Some(script), Some(wv)) = (script, id.and_then(|id| webviews.get(&id))) {
+                    let _ = wv.evaluate_script(&script);
+                }
+            }
+            #[cfg(feature = "tray")]
+            Event::UserEvent(WvEvent::TrayUpdate(ref worlds)) => {
+                if let Some(ref mut tray) = tray {
+                    tray.update(worlds.clone());
+                }
+            }
+            #[cfg(feature = "tray")]
+            Event::UserEvent(WvEvent::TrayAction(action)) => {
+                // Bring the main window (or any left) up, then hand the pick to its page
+                let id = if windows.contains_key(&main_window_id) {
+                    Some(main_window_id)
+                } else {
+                    windows.keys().next().copied()
+                };
+                if let Some(id) = id {
+                    if let Some(win) = windows.get(&id) {
+                        win.set_visible(true);
+                        win.set_minimized(false);
+                        win.set_focus();
+                    }
+                    let script = match action {
+                        crate::tray::TrayAction::Show => None,
+                        crate::tray::TrayAction::World(i) => Some(format!("window.trayAction && window.trayAction('world', {})", i)),
+                        crate::tray::TrayAction::ToggleMute => Some("window.trayAction && window.trayAction('mute')".to_string()),
+                    };
+                    if let (Some(script), Some(wv)) = (script, webviews.get(&id)) {
+                        let _ = wv.evaluate_script(&script);
+                    }
+                }
+            }
             Event::UserEvent(WvEvent::Reload) => {
                 // Remote GUI reload: restart the binary (no state to save — state lives on server)
                 // Show error in first available webview if exec fails
@@ -1325,7 +1604,7 @@ fn create_webview_window(
                 };
 
                 let world_lock = world.as_deref();
-                match build_webview(&new_window, &params, &proxy, &reload_tx, world_lock, None) {
+                match build_webview(&new_window, &params, &proxy, &reload_tx, &local_tx, world_lock, None) {
                     Ok(wv) => {
                         let id = new_window.id();
                         windows.insert(id, new_window);
@@ -1356,7 +1635,7 @@ fn create_webview_window(
                     escaped_pattern, use_regex
                 );
                 let world_lock = world.as_deref();
-                if let Ok(wv) = build_webview(&new_window, &params, &proxy, &reload_tx, world_lock, Some(&grep_js)) {
+                if let Ok(wv) = build_webview(&new_window, &params, &proxy, &reload_tx, &local_tx, world_lock, Some(&grep_js)) {
                     let id = new_window.id();
                     windows.insert(id, new_window);
                     webviews.insert(id, wv);
@@ -0,0 +1,622 @@
+//! permessage-deflate (RFC 7692) for the WebSocket server.
+//!
+//! InitialState and busy ServerData messages are large, repetitive JSON, and they
+//! compress several times over - which matters for web and Android clients on
+//! cellular links. Browsers and OkHttp offer the extension on every connection.
+//!
+//! tungstenite 0.24 has no extension support and rejects frames with RSV1 set, so
+//! the extension lives underneath it: `DeflateStream` wraps the client's byte
+//! stream and rewrites frames on the way through. Inbound compressed messages are
+//! inflated back into plain frames before tungstenite parses them; outbound data
+//! messages are deflated and marked RSV1 after tungstenite writes them. The offer
+//! is answered from the handshake callback (`DeflateParams::negotiate`), and the
+//! stream starts rewriting once the handshake is over (`DeflateSwitch::start`).
+
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Largest message accepted inflated or compressed; matches the WebSocketConfig
+/// `max_message_size` in handle_ws_client
+const MAX_MESSAGE: usize = 2 * 1024 * 1024;
+
+/// Inflated messages are handed to tungstenite in frames of at most this size,
+/// under its `max_frame_size`
+const REEMIT_FRAGMENT: usize = 64 * 1024;
+
+/// Outgoing messages shorter than this go uncompressed (Ping replies, small updates)
+const MIN_COMPRESS: usize = 128;
+
+/// Output grows by this much at a time while inflating or deflating
+const CODEC_CHUNK: usize = 32 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+
+/// Trailer every sync flush ends with, removed on send and restored on receive
+const SYNC_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The agreed extension parameters
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeflateParams {
+    /// The client asked us to compress each message on its own
+    pub server_no_context_takeover: bool,
+    /// The client offered to compress each message on its own
+    pub client_no_context_takeover: bool,
+}
+
+impl DeflateParams {
+    /// Pick the first permessage-deflate offer in a `Sec-WebSocket-Extensions`
+    /// header we can accept. Offers that limit our window below 32 KiB or carry
+    /// unknown parameters are passed over, as RFC 7692 allows.
+    pub fn negotiate(header: &str) -> Option<Self> {
+        'offers: for offer in header.split(',') {
+            let mut parts = offer.split(';').map(str::trim);
+            if !parts.next().is_some_and(|name| name.eq_ignore_ascii_case("permessage-deflate")) {
+                continue;
+            }
+            let mut params = DeflateParams::default();
+            for param in parts {
+                let (name, value) = match param.split_once('=') {
+                    Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                    None => (param, None),
+                };
+                match (name.to_ascii_lowercase().as_str(), value) {
+                    ("server_no_context_takeover", None) => params.server_no_context_takeover = true,
+                    ("client_no_context_takeover", None) => params.client_no_context_takeover = true,
+                    // Leaving the client at its default 15-bit window is always allowed
+                    ("client_max_window_bits", None) => {}
+                    ("client_max_window_bits", Some(bits)) if is_window_bits(bits) => {}
+                    // Our compressor always uses a 15-bit window
+                    ("server_max_window_bits", Some("15")) => {}
+                    _ => continue 'offers,
+                }
+            }
+            return Some(params);
+        }
+        None
+    }
+
+    /// The `Sec-WebSocket-Extensions` value accepting these parameters
+    pub fn response_header(&self) -> String {
+        let mut header = String::from("permessage-deflate");
+        if self.server_no_context_takeover {
+            header.push_str("; server_no_context_takeover");
+        }
+        if self.client_no_context_takeover {
+            header.push_str("; client_no_context_takeover");
+        }
+        header
+    }
+}
+
+fn is_window_bits(value: &str) -> bool {
+    value.parse::<u8>().is_ok_and(|bits| (8..=15).contains(&bits))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("permessage-deflate: {}", msg))
+}
+
+/// A frame header parsed from the front of a buffer
+struct FrameHead {
+    fin: bool,
+    rsv1: bool,
+    opcode: u8,
+    mask: Option<[u8; 4]>,
+    header_len: usize,
+    payload_len: usize,
+}
+
+/// Parse the frame header at the start of `buf`; None until all of it has arrived
+fn parse_head(buf: &[u8]) -> io::Result<Option<FrameHead>> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+    let (b0, b1) = (buf[0], buf[1]);
+    let (payload_len, mut header_len) = match b1 & 0x7f {
+        126 if buf.len() < 4 => return Ok(None),
+        126 => (u16::from_be_bytes([buf[2], buf[3]]) as u64, 4),
+        127 if buf.len() < 10 => return Ok(None),
+        127 => {
+            let mut len = [0u8; 8];
+            len.copy_from_slice(&buf[2..10]);
+            (u64::from_be_bytes(len), 10)
+        }
+        len => (len as u64, 2),
+    };
+    if payload_len > MAX_MESSAGE as u64 {
+        return Err(invalid_data("frame too large"));
+    }
+    let mask = if b1 & 0x80 != 0 {
+        if buf.len() < header_len + 4 {
+            return Ok(None);
+        }
+        let mut key = [0u8; 4];
+        key.copy_from_slice(&buf[header_len..header_len + 4]);
+        header_len += 4;
+        Some(key)
+    } else {
+        None
+    };
+    Ok(Some(FrameHead {
+        fin: b0 & 0x80 != 0,
+        rsv1: b0 & 0x40 != 0,
+        opcode: b0 & 0x0f,
+        mask,
+        header_len,
+        payload_len: payload_len as usize,
+    }))
+}
+
+fn write_head(out: &mut Vec<u8>, fin: bool, rsv1: bool, opcode: u8, mask: Option<[u8; 4]>, len: usize) {
+    out.push(if fin { 0x80 } else { 0 } | if rsv1 { 0x40 } else { 0 } | opcode);
+    let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+    if len < 126 {
+        out.push(mask_bit | len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(mask_bit | 126);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(mask_bit | 127);
+        out.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+    if let Some(key) = mask {
+        out.extend_from_slice(&key);
+    }
+}
+
+fn apply_mask(payload: &mut [u8], key: [u8; 4]) {
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= key[i % 4];
+    }
+}
+
+/// The frame rewriting for one connection. `decode` handles frames from the peer,
+/// `encode` frames we send; each consumes whole frames from the front of `raw`
+/// and appends the rewritten frames to `out`, leaving a partial frame in `raw`.
+pub(crate) struct PerMessageDeflate {
+    params: DeflateParams,
+    compress: flate2::Compress,
+    decompress: flate2::Decompress,
+    /// A compressed message from the peer still arriving: opcode, payload so far,
+    /// and whether its frames were masked
+    inbound: Option<(u8, Vec<u8>, bool)>,
+}
+
+impl PerMessageDeflate {
+    pub(crate) fn new(params: DeflateParams) -> Self {
+        Self {
+            params,
+            compress: flate2::Compress::new(flate2::Compression::default(), false),
+            decompress: flate2::Decompress::new(false),
+            inbound: None,
+        }
+    }
+
+    pub(crate) fn decode(&mut self, raw: &mut Vec<u8>, out: &mut Vec<u8>) -> io::Result<()> {
+        let mut pos = 0;
+        while let Some(head) = parse_head(&raw[pos..])? {
+            let frame_len = head.header_len + head.payload_len;
+            if raw.len() - pos < frame_len {
+                break;
+            }
+            let frame = &raw[pos..pos + frame_len];
+            pos += frame_len;
+            // Control frames may arrive between the fragments of a message
+            let is_control = head.opcode & 0x08 != 0;
+            if is_control || (!head.rsv1 && self.inbound.is_none()) {
+                out.extend_from_slice(frame);
+                continue;
+            }
+            match (&self.inbound, head.opcode) {
+                (None, OPCODE_CONTINUATION) => return Err(invalid_data("continuation without a message")),
+                (Some(_), opcode) if opcode != OPCODE_CONTINUATION => {
+                    return Err(invalid_data("new message inside a fragmented one"));
+                }
+                _ => {}
+            }
+            let mut payload = frame[head.header_len..].to_vec();
+            if let Some(key) = head.mask {
+                apply_mask(&mut payload, key);
+            }
+            let (_, data, _) = self.inbound.get_or_insert_with(|| (head.opcode, Vec::new(), head.mask.is_some()));
+            if data.len() + payload.len() > MAX_MESSAGE {
+                return Err(invalid_data("message too large"));
+            }
+            data.extend_from_slice(&payload);
+            if head.fin {
+                let (opcode, data, masked) = self.inbound.take().unwrap_or_default();
+                let plain = self.inflate(data)?;
+                write_message(out, opcode, &plain, masked);
+            }
+        }
+        raw.drain(..pos);
+        Ok(())
+    }
+
+    pub(crate) fn encode(&mut self, raw: &mut Vec<u8>, out: &mut Vec<u8>) -> io::Result<()> {
+        let mut pos = 0;
+        while let Some(head) = parse_head(&raw[pos..])? {
+            let frame_len = head.header_len + head.payload_len;
+            if raw.len() - pos < frame_len {
+                break;
+            }
+            let frame = &raw[pos..pos + frame_len];
+            pos += frame_len;
+            // Fragmented messages (which tungstenite doesn't write) stay uncompressed,
+            // as every control frame must
+            let is_control = head.opcode & 0x08 != 0;
+            if is_control || head.opcode == OPCODE_CONTINUATION || !head.fin || head.rsv1
+                || head.payload_len < MIN_COMPRESS
+            {
+                out.extend_from_slice(frame);
+                continue;
+            }
+            let mut payload = frame[head.header_len..].to_vec();
+            if let Some(key) = head.mask {
+                apply_mask(&mut payload, key);
+            }
+            let mut compressed = self.deflate(&payload)?;
+            if let Some(key) = head.mask {
+                apply_mask(&mut compressed, key);
+            }
+            write_head(out, true, true, head.opcode, head.mask, compressed.len());
+            out.extend_from_slice(&compressed);
+        }
+        raw.drain(..pos);
+        Ok(())
+    }
+
+    fn inflate(&mut self, mut data: Vec<u8>) -> io::Result<Vec<u8>> {
+        data.extend_from_slice(&SYNC_TRAILER);
+        let mut plain = Vec::with_capacity((data.len() * 4).min(MAX_MESSAGE));
+        let mut input = data.as_slice();
+        loop {
+            if plain.capacity() - plain.len() < CODEC_CHUNK {
+                plain.reserve(CODEC_CHUNK);
+            }
+            let (in_before, out_before) = (self.decompress.total_in(), plain.len());
+            self.decompress
+                .decompress_vec(input, &mut plain, flate2::FlushDecompress::Sync)
+                .map_err(|e| invalid_data(&format!("bad compressed message: {}", e)))?;
+            input = &input[(self.decompress.total_in() - in_before) as usize..];
+            if plain.len() > MAX_MESSAGE {
+                return Err(invalid_data("message too large"));
+            }
+            let stalled = plain.len() == out_before && self.decompress.total_in() == in_before;
+            if stalled || (input.is_empty() && plain.len() < plain.capacity()) {
+                break;
+            }
+        }
+        if self.params.client_no_context_takeover {
+            self.decompress.reset(false);
+        }
+        Ok(plain)
+    }
+
+    fn deflate(&mut self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        let mut compressed = Vec::with_capacity(payload.len() / 2 + 64);
+        let mut input = payload;
+        loop {
+            if compressed.capacity() - compressed.len() < 64 {
+                compressed.reserve(CODEC_CHUNK);
+            }
+            let in_before = self.compress.total_in();
+            self.compress
+                .compress_vec(input, &mut compressed, flate2::FlushCompress::Sync)
+                .map_err(|e| invalid_data(&format!("compression failed: {}", e)))?;
+            input = &input[(self.compress.total_in() - in_before) as usize..];
+            if input.is_empty() && compressed.len() < compressed.capacity() {
+                break;
+            }
+        }
+        if compressed.ends_with(&SYNC_TRAILER) {
+            compressed.truncate(compressed.len() - SYNC_TRAILER.len());
+        }
+        if self.params.server_no_context_takeover {
+            self.compress.reset();
+        }
+        Ok(compressed)
+    }
+}
+
+/// Write an inflated message as plain frames. Frames from a client must stay masked
+/// for tungstenite; an all-zero key leaves the payload as it is.
+fn write_message(out: &mut Vec<u8>, opcode: u8, payload: &[u8], masked: bool) {
+    let mask = masked.then_some([0u8; 4]);
+    if payload.is_empty() {
+        write_head(out, true, false, opcode, mask, 0);
+        return;
+    }
+    let count = payload.len().div_ceil(REEMIT_FRAGMENT);
+    for (i, chunk) in payload.chunks(REEMIT_FRAGMENT).enumerate() {
+        let opcode = if i == 0 { opcode } else { OPCODE_CONTINUATION };
+        write_head(out, i + 1 == count, false, opcode, mask, chunk.len());
+        out.extend_from_slice(chunk);
+    }
+}
+
+#[derive(Debug, Default)]
+struct SwitchState {
+    agreed: Option<DeflateParams>,
+    started: bool,
+}
+
+/// Shared between a `DeflateStream` and the handshake that negotiates for it
+#[derive(Debug, Clone, Default)]
+pub struct DeflateSwitch(Arc<Mutex<SwitchState>>);
+
+impl DeflateSwitch {
+    /// Record the parameters accepted in the handshake response
+    pub fn agree(&self, params: DeflateParams) {
+        if let Ok(mut state) = self.0.lock() {
+            state.agreed = Some(params);
+        }
+    }
+
+    /// The handshake is over: compress from here on if the extension was agreed
+    pub fn start(&self) {
+        if let Ok(mut state) = self.0.lock() {
+            state.started = true;
+        }
+    }
+}
+
+enum Mode {
+    Handshake,
+    Plain,
+    Deflate(Box<PerMessageDeflate>),
+}
+
+/// A WebSocket server stream with permessage-deflate applied beneath tungstenite
+pub struct DeflateStream<S> {
+    inner: S,
+    switch: DeflateSwitch,
+    mode: Mode,
+    read_raw: Vec<u8>,
+    read_ready: Vec<u8>,
+    read_pos: usize,
+    write_raw: Vec<u8>,
+    write_ready: Vec<u8>,
+}
+
+impl<S> DeflateStream<S> {
+    pub fn new(inner: S) -> (Self, DeflateSwitch) {
+        let switch = DeflateSwitch::default();
+        let stream = Self {
+            inner,
+            switch: switch.clone(),
+            mode: Mode::Handshake,
+            read_raw: Vec::new(),
+            read_ready: Vec::new(),
+            read_pos: 0,
+            write_raw: Vec::new(),
+            write_ready: Vec::new(),
+        };
+        (stream, switch)
+    }
+
+    fn refresh_mode(&mut self) {
+        if !matches!(self.mode, Mode::Handshake) {
+            return;
+        }
+        let Ok(state) = self.switch.0.lock() else { return };
+        if state.started {
+            self.mode = match state.agreed {
+                Some(params) => Mode::Deflate(Box::new(PerMessageDeflate::new(params))),
+                None => Mode::Plain,
+            };
+        }
+    }
+}
+
+/// Write out everything in `pending`
+fn poll_drain<S: AsyncWrite + Unpin>(inner: &mut S, pending: &mut Vec<u8>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    while !pending.is_empty() {
+        let n = ready!(Pin::new(&mut *inner).poll_write(cx, pending))?;
+        if n == 0 {
+            return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+        }
+        pending.drain(..n);
+    }
+    Poll::Ready(Ok(()))
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DeflateStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.refresh_mode();
+        let Mode::Deflate(codec) = &mut this.mode else {
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        };
+        loop {
+            if this.read_pos < this.read_ready.len() {
+                let available = &this.read_ready[this.read_pos..];
+                let n = available.len().min(buf.remaining());
+                buf.put_slice(&available[..n]);
+                this.read_pos += n;
+                if this.read_pos == this.read_ready.len() {
+                    this.read_ready.clear();
+                    this.read_pos = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+            let mut chunk = [0u8; 8192];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+            if chunk_buf.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            this.read_raw.extend_from_slice(chunk_buf.filled());
+            codec.decode(&mut this.read_raw, &mut this.read_ready)?;
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for DeflateStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.refresh_mode();
+        let Mode::Deflate(codec) = &mut this.mode else {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        };
+        // Hold tungstenite back while earlier frames are still going out
+        ready!(poll_drain(&mut this.inner, &mut this.write_ready, cx))?;
+        this.write_raw.extend_from_slice(buf);
+        codec.encode(&mut this.write_raw, &mut this.write_ready)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(poll_drain(&mut this.inner, &mut this.write_ready, cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(poll_drain(&mut this.inner, &mut this.write_ready, cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPCODE_TEXT: u8 = 0x1;
+
+    fn frame(opcode: u8, payload: &[u8], mask: Option<[u8; 4]>) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_head(&mut out, true, false, opcode, mask, payload.len());
+        let mut payload = payload.to_vec();
+        if let Some(key) = mask {
+            apply_mask(&mut payload, key);
+        }
+        out.extend_from_slice(&payload);
+        out
+    }
+
+    /// Every frame in `buf` as (fin, rsv1, opcode, unmasked payload)
+    fn frames(buf: &[u8]) -> Vec<(bool, bool, u8, Vec<u8>)> {
+        let mut pos = 0;
+        let mut result = Vec::new();
+        while let Some(head) = parse_head(&buf[pos..]).unwrap() {
+            let start = pos + head.header_len;
+            let mut payload = buf[start..start + head.payload_len].to_vec();
+            if let Some(key) = head.mask {
+                apply_mask(&mut payload, key);
+            }
+            result.push((head.fin, head.rsv1, head.opcode, payload));
+            pos = start + head.payload_len;
+        }
+        assert_eq!(pos, buf.len());
+        result
+    }
+
+    fn json_line(n: usize) -> Vec<u8> {
+        format!("{{\"type\":\"ServerData\",\"world_index\":0,\"data\":\"line {} of the output\"}}", n).into_bytes()
+    }
+
+    #[test]
+    fn test_negotiate_offers() {
+        assert_eq!(DeflateParams::negotiate("permessage-deflate"), Some(DeflateParams::default()));
+        // Chrome's offer
+        assert_eq!(DeflateParams::negotiate("permessage-deflate; client_max_window_bits"), Some(DeflateParams::default()));
+        let params = DeflateParams::negotiate("x-webkit-deflate-frame, permessage-deflate; server_no_context_takeover; client_no_context_takeover").unwrap();
+        assert!(params.server_no_context_takeover && params.client_no_context_takeover);
+        assert_eq!(params.response_header(), "permessage-deflate; server_no_context_takeover; client_no_context_takeover");
+        // A smaller server window is passed over for the next offer
+        assert_eq!(
+            DeflateParams::negotiate("permessage-deflate; server_max_window_bits=10, permessage-deflate; client_max_window_bits=12"),
+            Some(DeflateParams::default())
+        );
+        assert_eq!(DeflateParams::negotiate("permessage-deflate; server_max_window_bits=10"), None);
+        assert_eq!(DeflateParams::negotiate("permessage-deflate; unknown_param"), None);
+        assert_eq!(DeflateParams::negotiate(""), None);
+    }
+
+    #[test]
+    fn test_outgoing_message_round_trips_compressed() {
+        let mut server = PerMessageDeflate::new(DeflateParams::default());
+        let mut client = PerMessageDeflate::new(DeflateParams::default());
+        let payload: Vec<u8> = (0..200).flat_map(json_line).collect();
+        // Two messages, the second relying on the first's context
+        for _ in 0..2 {
+            let mut raw = frame(OPCODE_TEXT, &payload, None);
+            let mut sent = Vec::new();
+            server.encode(&mut raw, &mut sent).unwrap();
+            assert!(raw.is_empty());
+            assert!(sent.len() < payload.len() / 4);
+            let (fin, rsv1, opcode, _) = frames(&sent).remove(0);
+            assert!(fin && rsv1 && opcode == OPCODE_TEXT);
+
+            let mut received = Vec::new();
+            client.decode(&mut sent, &mut received).unwrap();
+            let rebuilt: Vec<u8> = frames(&received).into_iter().flat_map(|(_, rsv1, _, p)| {
+                assert!(!rsv1);
+                p
+            }).collect();
+            assert_eq!(rebuilt, payload);
+        }
+    }
+
+    #[test]
+    fn test_small_and_control_frames_pass_through() {
+        let mut server = PerMessageDeflate::new(DeflateParams::default());
+        let mut raw = frame(OPCODE_TEXT, br#"{"type":"Pong"}"#, None);
+        raw.extend(frame(0x9, b"ping", None));
+        let expected = raw.clone();
+        let mut sent = Vec::new();
+        server.encode(&mut raw, &mut sent).unwrap();
+        assert_eq!(sent, expected);
+    }
+
+    #[test]
+    fn test_masked_compressed_client_message_is_inflated() {
+        // A client compresses and masks its frames; split the compressed message
+        // over two frames with a ping between them, delivered a byte at a time
+        let mut browser = PerMessageDeflate::new(DeflateParams::default());
+        let payload: Vec<u8> = (0..50).flat_map(json_line).collect();
+        let mut raw = frame(OPCODE_TEXT, &payload, Some([1, 2, 3, 4]));
+        let mut compressed_frame = Vec::new();
+        browser.encode(&mut raw, &mut compressed_frame).unwrap();
+        let (_, rsv1, _, compressed) = frames(&compressed_frame).remove(0);
+        assert!(rsv1);
+        let (first, second) = compressed.split_at(compressed.len() / 2);
+        let key = Some([9, 8, 7, 6]);
+        let mut wire = Vec::new();
+        write_head(&mut wire, false, true, OPCODE_TEXT, key, first.len());
+        wire.extend(first.iter().enumerate().map(|(i, b)| b ^ [9, 8, 7, 6][i % 4]));
+        wire.extend(frame(0x9, b"", key));
+        write_head(&mut wire, true, false, OPCODE_CONTINUATION, key, second.len());
+        wire.extend(second.iter().enumerate().map(|(i, b)| b ^ [9, 8, 7, 6][i % 4]));
+
+        let mut server = PerMessageDeflate::new(DeflateParams::default());
+        let (mut raw, mut out) = (Vec::new(), Vec::new());
+        for byte in wire {
+            raw.push(byte);
+            server.decode(&mut raw, &mut out).unwrap();
+        }
+        assert!(raw.is_empty());
+        let received = frames(&out);
+        assert_eq!(received[0].2, 0x9);
+        assert_eq!(received[1], (true, false, OPCODE_TEXT, payload));
+        // Still masked (after the 6-byte ping), as tungstenite requires of client frames
+        assert_eq!(parse_head(&out[6..]).unwrap().unwrap().mask, Some([0; 4]));
+    }
+
+    #[test]
+    fn test_oversized_inflated_message_is_refused() {
+        let mut client = PerMessageDeflate::new(DeflateParams::default());
+        let bomb = client.deflate(&vec![b'a'; MAX_MESSAGE + 1]).unwrap();
+        assert!(bomb.len() < 64 * 1024);
+        let mut wire = Vec::new();
+        write_head(&mut wire, true, true, OPCODE_TEXT, Some([0; 4]), bomb.len());
+        wire.extend_from_slice(&bomb);
+        let mut server = PerMessageDeflate::new(DeflateParams::default());
+        assert!(server.decode(&mut wire, &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,550 @@
+//! `/import <file>` of Mudlet packages and MUSHclient trigger/alias files (XML), made
+//! into Clay actions.
+//!
+//! Patterns become regular expressions: Mudlet's substring, start-of-line and exact
+//! match types are escaped and anchored, and MUSHclient's `*` wildcards (the only
+//! wildcard it has) become `(.*)`. Highlights (Mudlet's colorizer, MUSHclient's custom
+//! colour) become `/highlight`, and gags (`deleteLine()`, omit from output) `/gag`.
+//! Commands come from MUSHclient's send text and Mudlet's command field; Mudlet Lua is
+//! only read for `send(...)`, `expandAlias(...)` and `deleteLine()`. Aliases become
+//! actions without a pattern (Clay's `/name` commands), named after the first word they
+//! match. Anything else goes in the notes.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::actions::{Action, MatchPattern, MatchType};
+
+#[derive(Debug, Default)]
+pub struct XmlImport {
+    pub actions: Vec<Action>,
+    /// `trigger NAME: ...` for each item that wasn't translated, or only in part
+    pub notes: Vec<String>,
+}
+
+/// An element with its attributes, child elements and text
+#[derive(Debug, Default)]
+struct Node {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+    text: String,
+}
+
+impl Node {
+    fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn child_text(&self, name: &str) -> &str {
+        self.child(name).map_or("", |c| c.text.as_str())
+    }
+
+    /// Elements named `name` anywhere below this one, in document order
+    fn descendants<'a>(&'a self, name: &'a str, out: &mut Vec<&'a Node>) {
+        for child in &self.children {
+            if child.name == name {
+                out.push(child);
+            }
+            child.descendants(name, out);
+        }
+    }
+}
+
+/// Whether `text` is one of the XML files this imports
+pub fn is_client_xml(text: &str) -> bool {
+    let text = text.trim_start_matches('\u{feff}');
+    text.trim_start().starts_with('<') && (text.contains("<MudletPackage") || text.contains("<muclient"))
+}
+
+fn parse_xml(text: &str) -> Result<Node, String> {
+    let mut reader = Reader::from_str(text);
+    let mut stack = vec![Node::default()];
+    let element = |e: &quick_xml::events::BytesStart| -> Result<Node, String> {
+        let mut attrs = Vec::new();
+        for attr in e.attributes() {
+            let attr = attr.map_err(|e| e.to_string())?;
+            let value = attr.unescape_value().map_err(|e| e.to_string())?;
+            attrs.push((String::from_utf8_lossy(attr.key.as_ref()).to_string(), value.to_string()));
+        }
+        Ok(Node { name: String::from_utf8_lossy(e.name().as_ref()).to_string(), attrs, ..Default::default() })
+    };
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error at byte {}: {}", reader.buffer_position(), e))? {
+            Event::Start(e) => stack.push(element(&e)?),
+            Event::Empty(e) => {
+                let node = element(&e)?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+            Event::End(_) => {
+                let node = stack.pop().filter(|_| !stack.is_empty()).ok_or("unbalanced XML")?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+            Event::Text(t) => {
+                let text = t.unescape().map_err(|e| e.to_string())?;
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&text);
+                }
+            }
+            Event::CData(c) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&String::from_utf8_lossy(&c.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    match stack.pop() {
+        Some(root) if stack.is_empty() => Ok(root),
+        _ => Err("unclosed XML element".to_string()),
+    }
+}
+
+pub fn parse(text: &str) -> Result<XmlImport, String> {
+    let root = parse_xml(text.trim_start_matches('\u{feff}'))?;
+    let mut import = XmlImport::default();
+    if let Some(package) = root.child("MudletPackage") {
+        mudlet(package, &mut import);
+    } else if let Some(muclient) = root.child("muclient") {
+        mushclient(muclient, &mut import);
+    } else {
+        return Err("not a Mudlet package or MUSHclient file".to_string());
+    }
+    Ok(import)
+}
+
+fn yes(value: Option<&str>) -> bool {
+    matches!(value.map(str::to_lowercase).as_deref(), Some("yes" | "y" | "1" | "true"))
+}
+
+/// A command line for an action (its `;` kept literal)
+fn literal(command: &str) -> String {
+    command.replace(';', "\\;")
+}
+
+fn push_command(action: &mut Action, command: &str) {
+    if command.is_empty() {
+        return;
+    }
+    if !action.command.is_empty() {
+        action.command.push(';');
+    }
+    action.command.push_str(command);
+}
+
+/// `#rrggbb` as the `r,g,b` form `/highlight` takes
+fn hex_color(hex: &str) -> Option<String> {
+    let hex = hex.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(format!("{},{},{}", channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// A regex alias's name: the literal word it starts with
+fn alias_name(regex: &str) -> Option<String> {
+    let name: String = regex.trim_start_matches('^').chars().take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-').collect();
+    let non_empty = !name.is_empty();
+    non_empty.then_some(name)
+}
+
+// ---------------------------------------------------------------------------
+// Mudlet
+// ---------------------------------------------------------------------------
+
+fn mudlet(package: &Node, import: &mut XmlImport) {
+    let mut triggers = Vec::new();
+    package.descendants("Trigger", &mut triggers);
+    let mut groups = Vec::new();
+    package.descendants("TriggerGroup", &mut groups);
+    // A TriggerGroup that isn't a folder is a trigger whose children fire after it
+    for node in triggers.into_iter().chain(groups.into_iter().filter(|g| !yes(g.attr("isFolder")))) {
+        if let Some(action) = mudlet_trigger(node, &mut import.notes) {
+            import.actions.push(action);
+        }
+    }
+    let mut aliases = Vec::new();
+    package.descendants("Alias", &mut aliases);
+    for node in aliases {
+        if let Some(action) = mudlet_alias(node, &mut import.notes) {
+            import.actions.push(action);
+        }
+    }
+    for kind in ["Timer", "Key", "Script", "Action"] {
+        let mut skipped = Vec::new();
+        package.descendants(kind, &mut skipped);
+        let count = skipped.iter().filter(|n| !yes(n.attr("isFolder"))).count();
+        if count > 0 {
+            import.notes.push(format!("{} {}(s) not translated (no Clay equivalent)", count, kind.to_lowercase()));
+        }
+    }
+}
+
+fn mudlet_trigger(node: &Node, notes: &mut Vec<String>) -> Option<Action> {
+    let name = node.child_text("name").trim().to_string();
+    let mut note = |what: &str| notes.push(format!("trigger {}: {}", name, what));
+    let codes: Vec<&str> = node.child("regexCodeList").map(|l| l.children.iter().map(|c| c.text.as_str()).collect()).unwrap_or_default();
+    let types: Vec<&str> = node.child("regexCodePropertyList").map(|l| l.children.iter().map(|c| c.text.trim()).collect()).unwrap_or_default();
+    let mut patterns = Vec::new();
+    for (i, code) in codes.iter().enumerate() {
+        let pattern = match types.get(i).copied().unwrap_or("0") {
+            "0" => regex::escape(code),
+            "1" => code.to_string(),
+            "2" => format!("^{}", regex::escape(code)),
+            "3" => format!("^{}$", regex::escape(code)),
+            _ => {
+                note("Lua, color, prompt and spacer patterns not translated");
+                continue;
+            }
+        };
+        patterns.push(MatchPattern { pattern, compiled_regex: None });
+    }
+    if patterns.is_empty() {
+        note("not translated (no text patterns)");
+        return None;
+    }
+    if yes(node.attr("isMultiline")) && patterns.len() > 1 {
+        note("multi-line (AND) trigger: any one pattern fires it in Clay");
+    }
+
+    let mut action = Action::new();
+    action.name = name.clone();
+    action.enabled = yes(node.attr("isActive"));
+    action.match_type = MatchType::Regexp;
+    action.patterns = patterns;
+    if yes(node.attr("isColorizerTrigger")) {
+        match hex_color(node.child_text("mBgColor")) {
+            Some(color) => push_command(&mut action, &format!("/highlight {}", color)),
+            None => push_command(&mut action, "/highlight"),
+        }
+    }
+    push_command(&mut action, &literal(node.child_text("mCommand").trim()));
+    let script = lua_commands(node.child_text("script"), false);
+    if script.gag {
+        action.command = if action.command.is_empty() { "/gag".to_string() } else { format!("/gag;{}", action.command) };
+    }
+    for command in &script.commands {
+        push_command(&mut action, command);
+    }
+    if script.untranslated {
+        note("Lua script only partly translated (send, expandAlias and deleteLine)");
+    }
+    Some(action)
+}
+
+fn mudlet_alias(node: &Node, notes: &mut Vec<String>) -> Option<Action> {
+    let label = node.child_text("name").trim().to_string();
+    let regex = node.child_text("regex").trim();
+    let Some(name) = alias_name(regex) else {
+        notes.push(format!("alias {}: not translated (its pattern doesn't start with a command word)", label));
+        return None;
+    };
+    let mut action = Action::new();
+    action.name = name;
+    action.enabled = yes(node.attr("isActive"));
+    // One capture is the rest of the line; several are taken to be words
+    let captures = regex.matches('(').count().saturating_sub(regex.matches("\\(").count() + regex.matches("(?").count());
+    push_command(&mut action, &literal(node.child_text("command").trim()));
+    let script = lua_commands(node.child_text("script"), captures == 1);
+    for command in &script.commands {
+        push_command(&mut action, command);
+    }
+    if script.untranslated {
+        notes.push(format!("alias {}: Lua script only partly translated (send and expandAlias)", label));
+    }
+    if action.command.is_empty() {
+        notes.push(format!("alias {}: not translated (nothing to send)", label));
+        return None;
+    }
+    Some(action)
+}
+
+struct LuaCommands {
+    commands: Vec<String>,
+    gag: bool,
+    untranslated: bool,
+}
+
+/// The commands of a Lua script made of `send(...)`/`expandAlias(...)` calls with
+/// string and `matches[N]` arguments, and `deleteLine()`. `matches[2]` is a trigger's
+/// `$1`; in an alias with `rest_capture` it's `$*`, otherwise the Nth word.
+fn lua_commands(script: &str, rest_capture: bool) -> LuaCommands {
+    let mut out = LuaCommands { commands: Vec::new(), gag: false, untranslated: false };
+    for statement in script.lines().flat_map(|l| split_unquoted(l, ';')).map(str::trim) {
+        if statement.is_empty() || statement.starts_with("--") {
+            continue;
+        }
+        if statement.replace(' ', "") == "deleteLine()" {
+            out.gag = true;
+            continue;
+        }
+        let call = ["send", "expandAlias"].iter().find_map(|f| {
+            let args = statement.strip_prefix(f)?.trim_start().strip_prefix('(')?.strip_suffix(')')?;
+            lua_string_expr(split_unquoted(args, ',').first()?, rest_capture)
+        });
+        match call {
+            Some(command) => out.commands.push(command),
+            None => out.untranslated = true,
+        }
+    }
+    out
+}
+
+/// `text` split at each `sep` outside "..." and '...' strings
+fn split_unquoted(text: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quote, mut escaped) = (0, None, false);
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(_), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), _) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, _) if c == sep => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+        escaped = false;
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// `"kill " .. matches[2]` and the like, as action text
+fn lua_string_expr(expr: &str, rest_capture: bool) -> Option<String> {
+    let mut text = String::new();
+    for part in expr.split("..").map(str::trim) {
+        if let Some(n) = part.strip_prefix("matches[").and_then(|p| p.strip_suffix(']')).and_then(|n| n.trim().parse::<u32>().ok()) {
+            text.push_str(&match n {
+                0 => return None,
+                1 => "$0".to_string(),
+                2 if rest_capture => "$*".to_string(),
+                n => format!("${}", n - 1),
+            });
+            continue;
+        }
+        let quoted = [("\"", "\""), ("'", "'"), ("[[", "]]")].iter()
+            .find_map(|(open, close)| part.strip_prefix(open)?.strip_suffix(close))?;
+        text.push_str(&literal(&quoted.replace("\\\"", "\"").replace("\\'", "'")));
+    }
+    Some(text)
+}
+
+// ---------------------------------------------------------------------------
+// MUSHclient
+// ---------------------------------------------------------------------------
+
+/// `send_to` values that put the text through as commands: to the world, or executed
+const SEND_TO_COMMANDS: [&str; 2] = ["0", "10"];
+
+fn mushclient(muclient: &Node, import: &mut XmlImport) {
+    let mut triggers = Vec::new();
+    muclient.descendants("trigger", &mut triggers);
+    for (i, node) in triggers.into_iter().enumerate() {
+        let name = node.attr("name").filter(|n| !n.is_empty()).map_or_else(|| format!("mush_trigger_{}", i + 1), str::to_string);
+        let mut action = mush_item(node, &name, &mut import.notes, "trigger");
+        let Some(pattern) = node.attr("match") else { continue };
+        action.match_type = MatchType::Regexp;
+        let pattern = if yes(node.attr("regexp")) { pattern.to_string() } else { mush_wildcard_regex(pattern) };
+        action.patterns = vec![MatchPattern { pattern, compiled_regex: None }];
+        let mut leading = Vec::new();
+        if yes(node.attr("omit_from_output")) {
+            leading.push("/gag".to_string());
+        }
+        if node.attr("custom_colour").is_some_and(|c| c != "0") {
+            leading.push(match node.attr("other_back_colour").filter(|c| !c.is_empty()) {
+                Some(color) => format!("/highlight {}", hex_color(color).unwrap_or_else(|| color.to_string())),
+                None => "/highlight".to_string(),
+            });
+        }
+        if !leading.is_empty() {
+            let rest = std::mem::take(&mut action.command);
+            action.command = leading.join(";");
+            push_command(&mut action, &rest);
+        }
+        import.actions.push(action);
+    }
+
+    let mut aliases = Vec::new();
+    muclient.descendants("alias", &mut aliases);
+    for node in aliases {
+        let pattern = node.attr("match").unwrap_or("");
+        let label = node.attr("name").filter(|n| !n.is_empty()).unwrap_or(pattern).to_string();
+        let regexp = yes(node.attr("regexp"));
+        let name = if regexp { alias_name(pattern) } else { alias_name(pattern).filter(|n| pattern[n.len()..].trim() == "*" || pattern.len() == n.len() || pattern[n.len()..].starts_with(' ')) };
+        let Some(name) = name else {
+            import.notes.push(format!("alias {}: not translated (its pattern doesn't start with a command word)", label));
+            continue;
+        };
+        let mut action = mush_item(node, &name, &mut import.notes, "alias");
+        // `name *`: %1 is everything after the command
+        let stars = pattern.matches('*').count();
+        if !regexp && stars == 1 {
+            action.command = action.command.replace("$1", "$*");
+        }
+        if action.command.is_empty() {
+            import.notes.push(format!("alias {}: not translated (nothing to send)", label));
+            continue;
+        }
+        import.actions.push(action);
+    }
+
+    let mut timers = Vec::new();
+    muclient.descendants("timer", &mut timers);
+    if !timers.is_empty() {
+        import.notes.push(format!("{} timer(s) not translated (no Clay equivalent)", timers.len()));
+    }
+}
+
+/// A trigger's or alias's name, enabled state and send text, with `%0`..`%9` as `$0`..`$9`
+fn mush_item(node: &Node, name: &str, notes: &mut Vec<String>, kind: &str) -> Action {
+    let mut action = Action::new();
+    action.name = name.to_string();
+    action.enabled = node.attr("enabled").map_or(true, |e| yes(Some(e)));
+    if node.attr("script").is_some_and(|s| !s.is_empty()) {
+        notes.push(format!("{} {}: script function not translated", kind, name));
+    }
+    let send = node.child_text("send");
+    if send.trim().is_empty() {
+        return action;
+    }
+    if !SEND_TO_COMMANDS.contains(&node.attr("send_to").unwrap_or("0")) {
+        notes.push(format!("{} {}: send text not translated (sent to a script, notepad or variable)", kind, name));
+        return action;
+    }
+    let mut untranslated = false;
+    for line in send.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let mut command = String::new();
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek().copied()) {
+                ('%', Some(d)) if d.is_ascii_digit() => {
+                    chars.next();
+                    command.push('$');
+                    command.push(d);
+                }
+                ('%', Some('%')) => {
+                    chars.next();
+                    command.push('%');
+                }
+                ('%', Some(_)) => {
+                    untranslated = true;
+                    command.push('%');
+                }
+                (';', _) => command.push_str("\\;"),
+                _ => command.push(c),
+            }
+        }
+        push_command(&mut action, &command);
+    }
+    if untranslated {
+        notes.push(format!("{} {}: MUSHclient substitutions other than %0-%9 kept as written", kind, name));
+    }
+    action
+}
+
+/// A MUSHclient wildcard pattern — the whole line, `*` matching anything — as a regex
+fn mush_wildcard_regex(pattern: &str) -> String {
+    let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+    format!("^{}$", parts.join("(.*)"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(import: &XmlImport) -> Vec<(&str, bool, &str, &str)> {
+        import.actions.iter().map(|a| (a.name.as_str(), a.enabled, a.display_pattern(), a.command.as_str())).collect()
+    }
+
+    #[test]
+    fn test_mudlet_package() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE MudletPackage>
+<MudletPackage version="1.001">
+  <TriggerPackage>
+    <TriggerGroup isActive="yes" isFolder="yes">
+      <name>Comms</name>
+      <Trigger isActive="yes" isFolder="no" isColorizerTrigger="yes">
+        <name>tells</name>
+        <script>send("reply thanks, " .. matches[2])</script>
+        <mCommand></mCommand>
+        <mBgColor>#800000</mBgColor>
+        <regexCodeList><string>^(\w+) tells you: (.*)$</string></regexCodeList>
+        <regexCodePropertyList><integer>1</integer></regexCodePropertyList>
+      </Trigger>
+      <Trigger isActive="no" isFolder="no">
+        <name>spam</name>
+        <script>deleteLine()
+echo("gone")</script>
+        <regexCodeList><string>[OOC]</string><string>You feel a.b</string></regexCodeList>
+        <regexCodePropertyList><integer>2</integer><integer>3</integer></regexCodePropertyList>
+      </Trigger>
+    </TriggerGroup>
+  </TriggerPackage>
+  <AliasPackage>
+    <Alias isActive="yes" isFolder="no">
+      <name>kill alias</name>
+      <script>send("kill " .. matches[2]); send('loot')</script>
+      <regex>^k (.+)$</regex>
+    </Alias>
+  </AliasPackage>
+  <TimerPackage><Timer isActive="yes" isFolder="no"><name>t</name></Timer></TimerPackage>
+</MudletPackage>"#;
+        assert!(is_client_xml(xml));
+        let import = parse(xml).unwrap();
+        assert_eq!(summary(&import), vec![
+            ("tells", true, r"^(\w+) tells you: (.*)$", "/highlight 128,0,0;reply thanks, $1"),
+            ("spam", false, r"^\[OOC\]", "/gag"),
+            ("k", true, "", "kill $*;loot"),
+        ]);
+        assert_eq!(import.actions[1].patterns[1].pattern, r"^You feel a\.b$");
+        assert_eq!(import.notes, vec![
+            "trigger spam: Lua script only partly translated (send, expandAlias and deleteLine)".to_string(),
+            "1 timer(s) not translated (no Clay equivalent)".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_mushclient_triggers_and_aliases() {
+        let xml = r##"<?xml version="1.0" encoding="iso-8859-1"?>
+<!DOCTYPE muclient>
+<muclient>
+<triggers>
+  <trigger enabled="y" match="* tells you: *" name="tell" omit_from_output="y" sequence="100">
+  <send>say Thanks, %1; really
+emote nods</send>
+  </trigger>
+  <trigger enabled="n" match="^You are (hungry|thirsty)\.$" regexp="y" custom_colour="17" other_back_colour="#004000" sequence="100">
+  <send>eat %1</send>
+  </trigger>
+</triggers>
+<aliases>
+  <alias match="k *" enabled="y" sequence="100"><send>kill %1</send></alias>
+  <alias match="* waves" enabled="y" sequence="100"><send>wave</send></alias>
+</aliases>
+</muclient>"##;
+        let import = parse(xml).unwrap();
+        assert_eq!(summary(&import), vec![
+            ("tell", true, "^(.*) tells you: (.*)$", "/gag;say Thanks, $1\\; really;emote nods"),
+            ("mush_trigger_2", false, r"^You are (hungry|thirsty)\.$", "/highlight 0,64,0;eat $1"),
+            ("k", true, "", "kill $*"),
+        ]);
+        assert_eq!(import.notes.len(), 1);
+        assert!(import.notes[0].starts_with("alias * waves: not translated"));
+    }
+}